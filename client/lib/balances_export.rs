@@ -0,0 +1,115 @@
+use serde::{Deserialize, Serialize};
+
+use casper_node::{
+    crypto::hash::Digest,
+    rpcs::state::{AccountBalance, AccountBalanceError},
+};
+use casper_types::U512;
+
+const CSV_HEADER: &str = "account_hash,main_purse,balance,error";
+
+/// The balances of all accounts' main purses at a single state root hash, collected from the
+/// pages of "state_get_all_balances" RPC responses.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BalancesExport {
+    /// The state root hash the balances were read from.
+    pub state_root_hash: Digest,
+    /// The sum of all balances read.
+    pub total_balance: U512,
+    /// The balances of the accounts, in ascending order of account hash.
+    pub balances: Vec<AccountBalance>,
+    /// The accounts which are malformed or whose balance couldn't be read.
+    pub errors: Vec<AccountBalanceError>,
+}
+
+impl BalancesExport {
+    pub(crate) fn new(state_root_hash: Digest) -> Self {
+        BalancesExport {
+            state_root_hash,
+            total_balance: U512::zero(),
+            balances: vec![],
+            errors: vec![],
+        }
+    }
+
+    /// Adds a page of balances and errors to the export.
+    pub(crate) fn extend(
+        &mut self,
+        balances: Vec<AccountBalance>,
+        errors: Vec<AccountBalanceError>,
+    ) {
+        for balance in &balances {
+            self.total_balance += balance.balance_value;
+        }
+        self.balances.extend(balances);
+        self.errors.extend(errors);
+    }
+
+    /// Renders the export as CSV, with one row per account.
+    ///
+    /// Rows for accounts whose balance couldn't be read have empty `main_purse` and `balance`
+    /// fields, with the reason given in the `error` field.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from(CSV_HEADER);
+        for balance in &self.balances {
+            csv.push_str(&format!(
+                "\n{},{},{},",
+                balance.account_hash, balance.main_purse, balance.balance_value
+            ));
+        }
+        for error in &self.errors {
+            csv.push_str(&format!(
+                "\n{},,,\"{}\"",
+                error.account_hash,
+                error.error.replace('"', "\"\"")
+            ));
+        }
+        csv
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_render_csv() {
+        let mut export = BalancesExport::new(Digest::from([1u8; Digest::LENGTH]));
+        export.extend(
+            vec![
+                AccountBalance {
+                    account_hash: "account-hash-01".to_string(),
+                    main_purse: "uref-01-007".to_string(),
+                    balance_value: U512::from(10),
+                },
+                AccountBalance {
+                    account_hash: "account-hash-02".to_string(),
+                    main_purse: "uref-02-007".to_string(),
+                    balance_value: U512::zero(),
+                },
+            ],
+            vec![],
+        );
+        export.extend(
+            vec![AccountBalance {
+                account_hash: "account-hash-03".to_string(),
+                main_purse: "uref-03-007".to_string(),
+                balance_value: U512::from(5),
+            }],
+            vec![AccountBalanceError {
+                account_hash: "account-hash-04".to_string(),
+                error: "Type mismatch: \"Account\"".to_string(),
+            }],
+        );
+
+        assert_eq!(export.total_balance, U512::from(15));
+        assert_eq!(
+            export.to_csv(),
+            "account_hash,main_purse,balance,error\n\
+            account-hash-01,uref-01-007,10,\n\
+            account-hash-02,uref-02-007,0,\n\
+            account-hash-03,uref-03-007,5,\n\
+            account-hash-04,,,\"Type mismatch: \"\"Account\"\"\""
+        );
+    }
+}
@@ -349,7 +349,7 @@ pub fn parts_to_cl_value(cl_type: CLType, value: &str) -> Result<CLValue> {
             let parse = || {
                 URef::from_formatted_str(trimmed_value).map_err(|error| {
                     Error::InvalidCLValue(format!(
-                        "can't parse {} as URef: {:?}",
+                        "can't parse {} as URef: {}",
                         trimmed_value, error
                     ))
                 })
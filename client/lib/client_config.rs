@@ -0,0 +1,280 @@
+//! Settings for the client read from a config file, grouped into named profiles, e.g. one per
+//! network.
+
+use std::{
+    collections::BTreeMap,
+    env,
+    fmt::{self, Display, Formatter},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+
+use crate::error::{Error, Result};
+
+/// The name of the client config file in the user's home directory, read if no other path is
+/// given.
+pub const DEFAULT_CLIENT_CONFIG_FILE_NAME: &str = ".casper-client.toml";
+
+/// The env var naming the profile to use, unless one is given explicitly.
+pub const PROFILE_ENV_VAR: &str = "CASPER_PROFILE";
+
+/// The contents of a client config file.
+///
+/// For example:
+///
+/// ```toml
+/// default_profile = 'testnet'
+///
+/// [profiles.testnet]
+/// node_address = 'http://localhost:7777'
+/// chain_name = 'casper-test'
+/// ttl = '1hr'
+/// gas_price = 1
+///
+/// [profiles.mainnet]
+/// node_address = 'http://node.example.com:7777'
+/// chain_name = 'casper'
+/// output_format = 'json'
+/// ```
+#[derive(Clone, Default, PartialEq, Eq, Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct ClientConfig {
+    /// The profile used if none is given explicitly or via the `CASPER_PROFILE` env var.
+    pub default_profile: Option<String>,
+    /// The profiles, by name.
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+impl ClientConfig {
+    /// Returns the path of the default client config file, i.e. `~/.casper-client.toml`, or
+    /// `None` if the home directory is unknown.
+    pub fn default_path() -> Option<PathBuf> {
+        env::var_os("HOME").map(|home| PathBuf::from(home).join(DEFAULT_CLIENT_CONFIG_FILE_NAME))
+    }
+
+    /// Reads the client config file at `path`.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path).map_err(|error| Error::IoError {
+            context: format!("failed to read client config file {}", path.display()),
+            error,
+        })?;
+        toml::from_str(&contents).map_err(|error| Error::InvalidClientConfig {
+            path: path.to_path_buf(),
+            error: error.to_string(),
+        })
+    }
+
+    /// Reads the client config file at `maybe_path` if given, or else the default file if it
+    /// exists.  If neither is the case, returns a config without any profiles.
+    pub fn load_or_default(maybe_path: Option<&Path>) -> Result<Self> {
+        match maybe_path {
+            Some(path) => ClientConfig::load(path),
+            None => match ClientConfig::default_path() {
+                Some(path) if path.exists() => ClientConfig::load(path),
+                _ => Ok(ClientConfig::default()),
+            },
+        }
+    }
+
+    /// Returns the selected profile.
+    ///
+    /// The profile is chosen by the first given of `maybe_profile_arg` (i.e. from the
+    /// command line), `maybe_profile_env_var` (i.e. the value of the `CASPER_PROFILE` env var) and
+    /// the config's `default_profile`.  If none are given, returns an empty profile, leaving every
+    /// setting at its built-in default.  It is an error for the chosen profile not to exist.
+    pub fn select_profile(
+        &self,
+        maybe_profile_arg: Option<&str>,
+        maybe_profile_env_var: Option<&str>,
+    ) -> Result<Profile> {
+        let name = match maybe_profile_arg
+            .or(maybe_profile_env_var)
+            .or_else(|| self.default_profile.as_deref())
+        {
+            Some(name) => name,
+            None => return Ok(Profile::default()),
+        };
+        self.profiles
+            .get(name)
+            .cloned()
+            .ok_or_else(|| Error::UnknownProfile {
+                name: name.to_string(),
+                known: self.profiles.keys().cloned().collect(),
+            })
+    }
+}
+
+/// A named set of settings, used in place of the built-in defaults of the corresponding
+/// command-line args.  Args passed explicitly always take precedence.
+#[derive(Clone, Default, PartialEq, Eq, Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    /// The hostname or IP and port of the node on which the HTTP service is running.
+    pub node_address: Option<String>,
+    /// The name of the chain for which deploys are created.
+    pub chain_name: Option<String>,
+    /// The time to live of created deploys, e.g. `"1hr"`.
+    pub ttl: Option<String>,
+    /// The gas price of created deploys.
+    pub gas_price: Option<u64>,
+    /// The format of output which can be rendered in several formats.
+    pub output_format: Option<OutputFormat>,
+}
+
+/// The format of output which can be rendered in several formats, e.g. exported balances.
+#[derive(Copy, Clone, PartialEq, Eq, Deserialize, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// Comma-separated values.
+    Csv,
+    /// JSON.
+    Json,
+}
+
+impl Display for OutputFormat {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self {
+            OutputFormat::Csv => write!(formatter, "csv"),
+            OutputFormat::Json => write!(formatter, "json"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+        default_profile = 'testnet'
+
+        [profiles.testnet]
+        node_address = 'http://localhost:7777'
+        chain_name = 'casper-test'
+        ttl = '1hr'
+        gas_price = 2
+
+        [profiles.mainnet]
+        node_address = 'http://node.example.com:7777'
+        chain_name = 'casper'
+        output_format = 'json'
+    "#;
+
+    fn config() -> ClientConfig {
+        toml::from_str(CONFIG).unwrap()
+    }
+
+    fn selected_chain_name(
+        config: &ClientConfig,
+        maybe_profile_arg: Option<&str>,
+        maybe_profile_env_var: Option<&str>,
+    ) -> Option<String> {
+        config
+            .select_profile(maybe_profile_arg, maybe_profile_env_var)
+            .unwrap()
+            .chain_name
+    }
+
+    #[test]
+    fn should_parse_profiles() {
+        let config = config();
+        assert_eq!(config.default_profile.as_deref(), Some("testnet"));
+        assert_eq!(
+            config.profiles["testnet"],
+            Profile {
+                node_address: Some("http://localhost:7777".to_string()),
+                chain_name: Some("casper-test".to_string()),
+                ttl: Some("1hr".to_string()),
+                gas_price: Some(2),
+                output_format: None,
+            }
+        );
+        assert_eq!(
+            config.profiles["mainnet"].output_format,
+            Some(OutputFormat::Json)
+        );
+    }
+
+    #[test]
+    fn should_select_profile_by_precedence() {
+        let config = config();
+
+        // The arg takes precedence over the env var, which takes precedence over the file.
+        let chain_name = selected_chain_name(&config, Some("mainnet"), Some("testnet"));
+        assert_eq!(chain_name.as_deref(), Some("casper"));
+        let chain_name = selected_chain_name(&config, None, Some("mainnet"));
+        assert_eq!(chain_name.as_deref(), Some("casper"));
+        let chain_name = selected_chain_name(&config, None, None);
+        assert_eq!(chain_name.as_deref(), Some("casper-test"));
+
+        // Without any profile selected, every setting is left at its built-in default.
+        let config = ClientConfig {
+            default_profile: None,
+            ..config
+        };
+        assert_eq!(
+            config.select_profile(None, None).unwrap(),
+            Profile::default()
+        );
+        assert_eq!(
+            ClientConfig::default().select_profile(None, None).unwrap(),
+            Profile::default()
+        );
+    }
+
+    #[test]
+    fn should_fail_to_select_unknown_profile() {
+        let config = config();
+        for (maybe_arg, maybe_env_var) in
+            &[(Some("devnet"), Some("testnet")), (None, Some("devnet"))]
+        {
+            match config.select_profile(*maybe_arg, *maybe_env_var) {
+                Err(Error::UnknownProfile { name, known }) => {
+                    assert_eq!(name, "devnet");
+                    assert_eq!(known, vec!["mainnet".to_string(), "testnet".to_string()]);
+                }
+                result => panic!("expected unknown profile error, got {:?}", result),
+            }
+        }
+
+        let config = ClientConfig {
+            default_profile: Some("devnet".to_string()),
+            ..config
+        };
+        assert!(matches!(
+            config.select_profile(None, None),
+            Err(Error::UnknownProfile { .. })
+        ));
+    }
+
+    #[test]
+    fn should_load_file() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join(DEFAULT_CLIENT_CONFIG_FILE_NAME);
+        fs::write(&path, CONFIG).unwrap();
+        assert_eq!(
+            ClientConfig::load_or_default(Some(&path)).unwrap(),
+            config()
+        );
+
+        fs::write(
+            &path,
+            "[profiles.testnet]\nnode-address = 'http://localhost:7777'",
+        )
+        .unwrap();
+        assert!(matches!(
+            ClientConfig::load(&path),
+            Err(Error::InvalidClientConfig { .. })
+        ));
+
+        // An explicitly given file must exist.
+        let missing_path = temp_dir.path().join("missing.toml");
+        assert!(matches!(
+            ClientConfig::load_or_default(Some(&missing_path)),
+            Err(Error::IoError { .. })
+        ));
+    }
+}
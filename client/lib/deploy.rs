@@ -2,18 +2,27 @@ use rand::{self, distributions::Alphanumeric, Rng};
 use serde::{Deserialize, Serialize};
 use std::{
     fs::{self, File},
-    io::{self, BufReader, Read, Write},
+    io::{self, Read, Write},
     path::{Path, PathBuf},
 };
 
 use casper_execution_engine::core::engine_state::ExecutableDeployItem;
 use casper_node::{
-    rpcs::{account::PutDeploy, chain::GetBlockResult, info::GetDeploy, RpcWithParams},
+    crypto::hash::Digest,
+    rpcs::{
+        account::PutDeploy,
+        chain::GetBlockResult,
+        info::{GetDeploy, GetDeployStatus},
+        RpcWithParams,
+    },
     types::{Deploy, DeployHash, TimeDiff, Timestamp},
 };
-use casper_types::{ProtocolVersion, RuntimeArgs, SecretKey, URef, U512};
+use casper_types::{
+    account::AccountHash, ProtocolVersion, PublicKey, RuntimeArgs, SecretKey, Signature, URef, U512,
+};
 
 use crate::{
+    deploy_file::DeployFile,
     error::{Error, Result},
     rpc::{RpcClient, TransferTarget},
 };
@@ -22,7 +31,7 @@ use crate::{
 ///
 /// Note: this should be kept in sync with the value of `[deploys.max_deploy_size]` in the
 /// production chainspec.
-const MAX_SERIALIZED_SIZE: u32 = 1_024 * 1_024;
+pub(crate) const MAX_SERIALIZED_SIZE: u32 = 1_024 * 1_024;
 
 /// SendDeploy allows sending a deploy to the node.
 pub(crate) struct SendDeploy;
@@ -38,6 +47,10 @@ impl RpcClient for GetDeploy {
     const RPC_METHOD: &'static str = Self::METHOD;
 }
 
+impl RpcClient for GetDeployStatus {
+    const RPC_METHOD: &'static str = Self::METHOD;
+}
+
 impl RpcClient for SendDeploy {
     const RPC_METHOD: &'static str = PutDeploy::METHOD;
 }
@@ -192,21 +205,53 @@ pub(super) trait DeployExt {
         payment: ExecutableDeployItem,
     ) -> Result<Deploy>;
 
+    /// Constructs a batched transfer `Deploy`, in which all the given transfers succeed or fail
+    /// together.
+    fn new_batched_transfer(
+        transfers: Vec<(TransferTarget, U512, Option<u64>)>,
+        params: DeployParams,
+        payment: ExecutableDeployItem,
+    ) -> Result<Deploy>;
+
     /// Writes the `Deploy` to `output`.
     fn write_deploy<W>(&self, output: W) -> Result<()>
     where
         W: Write;
 
     /// Reads a `Deploy` from the `input`.
+    ///
+    /// Fields unknown to this version of the client are dropped, unless they are in the deploy's
+    /// header or its payment or session code, in which case an error is returned.
     fn read_deploy<R>(input: R) -> Result<Deploy>
     where
         R: Read;
 
     /// Reads a `Deploy` from the reader at `input`, signs it, then writes it back to `output`.
+    ///
+    /// Fields unknown to this version of the client are written back untouched, unless they are in
+    /// the deploy's header or its payment or session code, in which case the deploy isn't signed
+    /// and an error is returned.
     fn sign_and_write_deploy<R, W>(input: R, secret_key: SecretKey, output: W) -> Result<()>
     where
         R: Read,
         W: Write;
+
+    /// Reads a `Deploy` from the reader at `input`, adds `signer`'s signature of it as returned by
+    /// `sign_digest`, then writes it back to `output`.
+    ///
+    /// `sign_digest` is passed the digest of the deploy hash, and should return a prehashed
+    /// signature of it, e.g. as created by an external signer.  Unknown fields are handled as for
+    /// `sign_and_write_deploy`.
+    fn sign_prehashed_and_write_deploy<R, W, F>(
+        input: R,
+        signer: PublicKey,
+        sign_digest: F,
+        output: W,
+    ) -> Result<()>
+    where
+        R: Read,
+        W: Write,
+        F: FnOnce(&Digest) -> Result<Signature>;
 }
 
 impl DeployExt for Deploy {
@@ -270,6 +315,29 @@ impl DeployExt for Deploy {
         Deploy::with_payment_and_session(params, payment, session)
     }
 
+    fn new_batched_transfer(
+        transfers: Vec<(TransferTarget, U512, Option<u64>)>,
+        params: DeployParams,
+        payment: ExecutableDeployItem,
+    ) -> Result<Deploy> {
+        const TRANSFER_ARG_TRANSFERS: &str = "transfers";
+
+        let transfers: Vec<(AccountHash, U512, Option<u64>)> = transfers
+            .into_iter()
+            .map(|(target, amount, maybe_transfer_id)| match target {
+                TransferTarget::Account(target_account) => {
+                    (target_account.to_account_hash(), amount, maybe_transfer_id)
+                }
+            })
+            .collect();
+        let mut transfer_args = RuntimeArgs::new();
+        transfer_args.insert(TRANSFER_ARG_TRANSFERS, transfers)?;
+        let session = ExecutableDeployItem::Transfer {
+            args: transfer_args,
+        };
+        Deploy::with_payment_and_session(params, payment, session)
+    }
+
     fn write_deploy<W>(&self, mut output: W) -> Result<()>
     where
         W: Write,
@@ -287,10 +355,7 @@ impl DeployExt for Deploy {
     where
         R: Read,
     {
-        let reader = BufReader::new(input);
-        let deploy: Deploy = serde_json::from_reader(reader)?;
-        deploy.is_valid_size(MAX_SERIALIZED_SIZE)?;
-        Ok(deploy)
+        DeployFile::read(input)?.into_deploy()
     }
 
     fn sign_and_write_deploy<R, W>(input: R, secret_key: SecretKey, output: W) -> Result<()>
@@ -298,19 +363,49 @@ impl DeployExt for Deploy {
         R: Read,
         W: Write,
     {
-        let mut deploy = Deploy::read_deploy(input)?;
+        let mut deploy_file = DeployFile::read(input)?;
+        let deploy = deploy_file.deploy_mut()?;
         deploy.sign(&secret_key);
         deploy.is_valid_size(MAX_SERIALIZED_SIZE)?;
-        deploy.write_deploy(output)?;
-        Ok(())
+        deploy_file.write(output)
+    }
+
+    fn sign_prehashed_and_write_deploy<R, W, F>(
+        input: R,
+        signer: PublicKey,
+        sign_digest: F,
+        output: W,
+    ) -> Result<()>
+    where
+        R: Read,
+        W: Write,
+        F: FnOnce(&Digest) -> Result<Signature>,
+    {
+        let mut deploy_file = DeployFile::read(input)?;
+        let deploy = deploy_file.deploy_mut()?;
+        let signature = sign_digest(deploy.id().inner())?;
+        deploy
+            .add_approval(signer, signature)
+            .map_err(|error| Error::CryptoError {
+                context: "prehashed signature",
+                error: error.into(),
+            })?;
+        deploy.is_valid_size(MAX_SERIALIZED_SIZE)?;
+        deploy_file.write(output)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::convert::TryInto;
+    use std::{collections::BTreeMap, convert::TryInto, io::Write};
 
-    use casper_node::{crypto::AsymmetricKeyExt, types::ExcessiveSizeDeployError};
+    use serde_json::{json, Value};
+
+    use casper_node::{
+        crypto::{self, AsymmetricKeyExt},
+        types::{DeployHashScheme, ExcessiveSizeDeployError},
+    };
+    use casper_types::{bytesrepr::ToBytes, AccessRights, CLValue, Key, U128, U256};
 
     use super::*;
     use crate::{DeployStrParams, PaymentStrParams, SessionStrParams};
@@ -457,6 +552,42 @@ mod tests {
         assert_eq!(expected.session(), actual.session());
     }
 
+    #[test]
+    fn should_create_identical_deploys_with_payment_amount_and_explicit_standard_payment() {
+        fn create_deploy(payment_params: PaymentStrParams) -> Deploy {
+            let deploy_params = DeployStrParams {
+                timestamp: "2021-01-19T01:18:19.120Z",
+                ..deploy_params()
+            };
+            let session_params = SessionStrParams::with_package_hash(
+                PKG_HASH,
+                VERSION,
+                ENTRYPOINT,
+                args_simple(),
+                "",
+            );
+            Deploy::with_payment_and_session(
+                deploy_params.try_into().unwrap(),
+                payment_params.try_into().unwrap(),
+                session_params.try_into().unwrap(),
+            )
+            .unwrap()
+        }
+
+        // The explicit equivalent of standard payment is empty module bytes with an "amount" arg.
+        let empty_module = tempfile::NamedTempFile::new().unwrap();
+        let empty_module_path = empty_module.path().to_str().unwrap();
+        let explicit_payment_params =
+            PaymentStrParams::with_path(empty_module_path, vec!["amount:u512='2500000000'"], "");
+        let expected = create_deploy(explicit_payment_params).to_bytes().unwrap();
+
+        for payment_amount in &["2500000000", "2.5cspr"] {
+            let payment_params = PaymentStrParams::with_amount(payment_amount);
+            let actual = create_deploy(payment_params).to_bytes().unwrap();
+            assert_eq!(actual, expected, "payment amount {}", payment_amount);
+        }
+    }
+
     #[test]
     fn should_fail_to_create_large_deploy() {
         let deploy_params = deploy_params();
@@ -491,6 +622,130 @@ mod tests {
         }
     }
 
+    #[test]
+    fn should_round_trip_json_args_of_every_cl_type() {
+        let key = Key::Hash([1; 32]);
+        let uref = URef::new([2; 32], AccessRights::READ_ADD_WRITE);
+        let public_key = PublicKey::from(&SecretKey::ed25519_from_bytes([3; 32]).unwrap());
+        let byte_array = [4_u8; 32];
+        let mut map = BTreeMap::new();
+        map.insert(1_u8, true);
+        map.insert(2_u8, false);
+
+        let json_args = json!([
+            { "name": "bool", "type": "Bool", "value": true },
+            { "name": "i32", "type": "I32", "value": -1 },
+            { "name": "i64", "type": "I64", "value": -2 },
+            { "name": "u8", "type": "U8", "value": 3 },
+            { "name": "u32", "type": "U32", "value": 4 },
+            { "name": "u64", "type": "U64", "value": 5 },
+            { "name": "u128", "type": "U128", "value": "6" },
+            { "name": "u256", "type": "U256", "value": "7" },
+            { "name": "u512", "type": "U512", "value": "8" },
+            { "name": "unit", "type": "Unit", "value": null },
+            { "name": "string", "type": "String", "value": "a value" },
+            { "name": "key", "type": "Key", "value": key },
+            { "name": "uref", "type": "URef", "value": uref },
+            { "name": "public_key", "type": "PublicKey", "value": public_key },
+            { "name": "option_some", "type": { "Option": "U512" }, "value": "9" },
+            { "name": "option_none", "type": { "Option": "U512" }, "value": null },
+            {
+                "name": "list",
+                "type": { "List": { "Tuple2": ["String", "I32"] } },
+                "value": [["a", -1], ["b", 2]]
+            },
+            { "name": "byte_array", "type": { "ByteArray": 32 }, "value": hex::encode(byte_array) },
+            {
+                "name": "result_ok",
+                "type": { "Result": { "ok": "U64", "err": "String" } },
+                "value": { "Ok": 10 }
+            },
+            {
+                "name": "result_err",
+                "type": { "Result": { "ok": "U64", "err": "String" } },
+                "value": { "Err": "failed" }
+            },
+            {
+                "name": "map",
+                "type": { "Map": { "key": "U8", "value": "Bool" } },
+                "value": [{ "key": 1, "value": true }, { "key": 2, "value": false }]
+            },
+            { "name": "tuple1", "type": { "Tuple1": ["Bool"] }, "value": [true] },
+            {
+                "name": "tuple3",
+                "type": { "Tuple3": ["U8", "String", { "Option": "Key" }] },
+                "value": [1, "two", null]
+            }
+        ]);
+        let expected = vec![
+            ("bool", CLValue::from_t(true)),
+            ("i32", CLValue::from_t(-1_i32)),
+            ("i64", CLValue::from_t(-2_i64)),
+            ("u8", CLValue::from_t(3_u8)),
+            ("u32", CLValue::from_t(4_u32)),
+            ("u64", CLValue::from_t(5_u64)),
+            ("u128", CLValue::from_t(U128::from(6))),
+            ("u256", CLValue::from_t(U256::from(7))),
+            ("u512", CLValue::from_t(U512::from(8))),
+            ("unit", CLValue::from_t(())),
+            ("string", CLValue::from_t(String::from("a value"))),
+            ("key", CLValue::from_t(key)),
+            ("uref", CLValue::from_t(uref)),
+            ("public_key", CLValue::from_t(public_key)),
+            ("option_some", CLValue::from_t(Some(U512::from(9)))),
+            ("option_none", CLValue::from_t(None::<U512>)),
+            (
+                "list",
+                CLValue::from_t(vec![(String::from("a"), -1_i32), (String::from("b"), 2)]),
+            ),
+            ("byte_array", CLValue::from_t(byte_array)),
+            ("result_ok", CLValue::from_t(Ok::<u64, String>(10))),
+            (
+                "result_err",
+                CLValue::from_t(Err::<u64, String>(String::from("failed"))),
+            ),
+            ("map", CLValue::from_t(map)),
+            ("tuple1", CLValue::from_t((true,))),
+            (
+                "tuple3",
+                CLValue::from_t((1_u8, String::from("two"), None::<Key>)),
+            ),
+        ];
+
+        let mut json_args_file = tempfile::NamedTempFile::new().unwrap();
+        json_args_file
+            .write_all(json_args.to_string().as_bytes())
+            .unwrap();
+        let json_args_path = json_args_file.path().to_str().unwrap();
+
+        let payment_params =
+            PaymentStrParams::with_package_hash(PKG_HASH, VERSION, ENTRYPOINT, args_simple(), "");
+        let session_params =
+            SessionStrParams::with_package_hash(PKG_HASH, VERSION, ENTRYPOINT, vec![], "")
+                .with_args_json(json_args_path);
+        let deploy = Deploy::with_payment_and_session(
+            deploy_params().try_into().unwrap(),
+            payment_params.try_into().unwrap(),
+            session_params.try_into().unwrap(),
+        )
+        .unwrap();
+
+        let mut output = Vec::new();
+        deploy.write_deploy(&mut output).unwrap();
+        let parsed_deploy = Deploy::read_deploy(&output[..]).unwrap();
+        let args = parsed_deploy.session().args();
+
+        assert_eq!(args.len(), expected.len());
+        for (name, expected_value) in expected {
+            assert_eq!(
+                args.get(name),
+                Some(&expected_value.unwrap()),
+                "wrong value for {}",
+                name
+            );
+        }
+    }
+
     #[test]
     fn should_read_deploy() {
         let bytes = SAMPLE_DEPLOY.as_bytes();
@@ -500,12 +755,47 @@ mod tests {
         );
     }
 
+    #[test]
+    fn should_read_deploy_with_legacy_module_bytes() {
+        let module_bytes: Vec<u8> = (0..=u8::MAX).collect();
+        let payment_params =
+            PaymentStrParams::with_package_hash(PKG_HASH, VERSION, ENTRYPOINT, args_simple(), "");
+        let session = ExecutableDeployItem::ModuleBytes {
+            module_bytes: module_bytes.clone().into(),
+            args: RuntimeArgs::new(),
+        };
+        let deploy = Deploy::with_payment_and_session(
+            deploy_params().try_into().unwrap(),
+            payment_params.try_into().unwrap(),
+            session,
+        )
+        .unwrap();
+
+        let mut output = Vec::new();
+        deploy.write_deploy(&mut output).unwrap();
+
+        // The module bytes should be written as a hex string and read back unchanged.
+        let mut json: Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(
+            json["session"]["ModuleBytes"]["module_bytes"],
+            serde_json::Value::String(hex::encode(&module_bytes))
+        );
+        assert_eq!(Deploy::read_deploy(&output[..]).unwrap(), deploy);
+
+        // Files written by older clients with the module bytes as an array of integers should
+        // still be readable.
+        json["session"]["ModuleBytes"]["module_bytes"] =
+            serde_json::to_value(&module_bytes).unwrap();
+        let legacy_output = serde_json::to_vec_pretty(&json).unwrap();
+        assert_eq!(Deploy::read_deploy(&legacy_output[..]).unwrap(), deploy);
+    }
+
     #[test]
     fn should_sign_deploy() {
         let bytes = SAMPLE_DEPLOY.as_bytes();
         let mut deploy = Deploy::read_deploy(bytes).unwrap();
         deploy
-            .is_valid()
+            .is_valid(DeployHashScheme::Legacy)
             .unwrap_or_else(|error| panic!("{} - {:#?}", error, deploy));
         assert_eq!(
             deploy.approvals().len(),
@@ -525,4 +815,138 @@ mod tests {
             signed_deploy
         );
     }
+
+    #[test]
+    fn should_sign_deploy_with_external_signer() {
+        let bytes = SAMPLE_DEPLOY.as_bytes();
+        let deploy = Deploy::read_deploy(bytes).unwrap();
+
+        let secret_key = SecretKey::generate_ed25519().unwrap();
+        let signer = PublicKey::from(&secret_key);
+        let sign_digest =
+            |digest: &Digest| Ok(crypto::sign_prehashed(digest, &secret_key, &signer));
+
+        let mut result = Vec::new();
+        Deploy::sign_prehashed_and_write_deploy(bytes, signer.clone(), sign_digest, &mut result)
+            .unwrap();
+        let mut signed_deploy = Deploy::read_deploy(&result[..]).unwrap();
+        assert_eq!(
+            signed_deploy.approvals().len(),
+            deploy.approvals().len() + 1
+        );
+        signed_deploy
+            .is_valid(DeployHashScheme::Legacy)
+            .unwrap_or_else(|error| panic!("{} - {:#?}", error, signed_deploy));
+
+        // A regular signature of some other message should be rejected.
+        let sign_digest = |_: &Digest| Ok(crypto::sign(b"other", &secret_key, &signer));
+        let error = Deploy::sign_prehashed_and_write_deploy(
+            bytes,
+            signer.clone(),
+            sign_digest,
+            std::io::sink(),
+        )
+        .unwrap_err();
+        assert!(matches!(error, Error::CryptoError { .. }));
+    }
+
+    #[test]
+    fn should_preserve_unknown_fields_when_signing() {
+        let mut json: Value = serde_json::from_str(SAMPLE_DEPLOY).unwrap();
+        json["future_field"] = json!({ "nested": [1, { "deeper": true }] });
+        json["approvals"][1]["future_weight"] = json!(7);
+        let input = serde_json::to_vec(&json).unwrap();
+
+        let secret_key = SecretKey::generate_ed25519().unwrap();
+        let mut output = Vec::new();
+        Deploy::sign_and_write_deploy(&input[..], secret_key, &mut output).unwrap();
+
+        let signed_json: Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(signed_json["future_field"], json["future_field"]);
+        let approvals = signed_json["approvals"].as_array().unwrap();
+        assert_eq!(approvals.len(), 3);
+        assert!(approvals.contains(&json["approvals"][1]));
+        assert_eq!(
+            approvals
+                .iter()
+                .filter(|approval| approval.get("future_weight").is_some())
+                .count(),
+            1
+        );
+
+        // The known fields should be read back as a valid deploy with the new approval.
+        let mut signed_deploy = Deploy::read_deploy(&output[..]).unwrap();
+        signed_deploy
+            .is_valid(DeployHashScheme::Legacy)
+            .unwrap_or_else(|error| panic!("{} - {:#?}", error, signed_deploy));
+
+        // Signing again via an external signer should keep the unknown fields too.
+        let secret_key = SecretKey::generate_ed25519().unwrap();
+        let signer = PublicKey::from(&secret_key);
+        let sign_digest =
+            |digest: &Digest| Ok(crypto::sign_prehashed(digest, &secret_key, &signer));
+        let mut resigned_output = Vec::new();
+        Deploy::sign_prehashed_and_write_deploy(
+            &output[..],
+            signer.clone(),
+            sign_digest,
+            &mut resigned_output,
+        )
+        .unwrap();
+        let resigned_json: Value = serde_json::from_slice(&resigned_output).unwrap();
+        assert_eq!(resigned_json["future_field"], json["future_field"]);
+        let approvals = resigned_json["approvals"].as_array().unwrap();
+        assert_eq!(approvals.len(), 4);
+        assert!(approvals.contains(&json["approvals"][1]));
+    }
+
+    #[test]
+    fn should_refuse_to_sign_deploy_with_unknown_signed_fields() {
+        let cases = [
+            ("/header", "header.future_field"),
+            (
+                "/payment/StoredVersionedContractByHash",
+                "payment.StoredVersionedContractByHash.future_field",
+            ),
+            (
+                "/session/StoredVersionedContractByHash/args/1/1",
+                "session.StoredVersionedContractByHash.args[1][1].future_field",
+            ),
+        ];
+
+        for (pointer, expected_field) in cases.iter() {
+            let mut json: Value = serde_json::from_str(SAMPLE_DEPLOY).unwrap();
+            json["future_field"] = json!("outside the signed portion");
+            json.pointer_mut(pointer)
+                .and_then(Value::as_object_mut)
+                .unwrap()
+                .insert("future_field".to_string(), json!(1));
+            let input = serde_json::to_vec(&json).unwrap();
+            let is_expected_error = |error: Error| match error {
+                Error::UnknownSignedDeployFields { fields } => fields == vec![*expected_field],
+                _ => false,
+            };
+
+            let secret_key = SecretKey::generate_ed25519().unwrap();
+            let signer = PublicKey::from(&secret_key);
+            let error =
+                Deploy::sign_and_write_deploy(&input[..], secret_key, std::io::sink()).unwrap_err();
+            assert!(is_expected_error(error), "{}", pointer);
+
+            let sign_digest = |_: &Digest| -> Result<Signature> {
+                panic!("should not sign deploy with unknown signed fields")
+            };
+            let error = Deploy::sign_prehashed_and_write_deploy(
+                &input[..],
+                signer,
+                sign_digest,
+                std::io::sink(),
+            )
+            .unwrap_err();
+            assert!(is_expected_error(error), "{}", pointer);
+
+            let error = Deploy::read_deploy(&input[..]).unwrap_err();
+            assert!(is_expected_error(error), "{}", pointer);
+        }
+    }
 }
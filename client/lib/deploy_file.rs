@@ -0,0 +1,342 @@
+//! Forward-compatible reading and writing of deploy files.
+//!
+//! A deploy file written by a newer version of the client may hold fields unknown to this version.
+//! These are set aside when the file is read, and written back untouched along with the deploy.
+//! Unknown fields in the deploy's header or its payment or session code are covered by the deploy
+//! hash though, so a deploy holding any of them is refused for signing or sending.
+
+use std::io::{BufReader, Read, Write};
+
+use serde_json::{Map, Value};
+
+use casper_node::types::{Approval, Deploy};
+
+use crate::{
+    deploy::MAX_SERIALIZED_SIZE,
+    error::{Error, Result},
+};
+
+const DEPLOY_FIELDS: &[&str] = &["hash", "header", "payment", "session", "approvals"];
+const HEADER_FIELDS: &[&str] = &[
+    "account",
+    "timestamp",
+    "ttl",
+    "gas_price",
+    "body_hash",
+    "dependencies",
+    "chain_name",
+];
+const APPROVAL_FIELDS: &[&str] = &["signer", "signature"];
+const CL_VALUE_FIELDS: &[&str] = &["cl_type", "bytes", "parsed"];
+
+/// Returns the known fields of the given variant of `ExecutableDeployItem`, or `None` if the
+/// variant is unknown.
+fn executable_deploy_item_fields(variant: &str) -> Option<&'static [&'static str]> {
+    let fields: &'static [&'static str] = match variant {
+        "ModuleBytes" => &["module_bytes", "args"],
+        "StoredContractByHash" => &["hash", "entry_point", "args"],
+        "StoredContractByName" => &["name", "entry_point", "args"],
+        "StoredVersionedContractByHash" => &["hash", "version", "entry_point", "args"],
+        "StoredVersionedContractByName" => &["name", "version", "entry_point", "args"],
+        "Transfer" => &["args"],
+        _ => return None,
+    };
+    Some(fields)
+}
+
+/// The location in a deploy file of a JSON object holding unknown fields.
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone, Debug, PartialEq)]
+enum Location {
+    /// The deploy itself.
+    Deploy,
+    /// The deploy's header.
+    Header,
+    /// The variant of the payment or session code.
+    Code { field: &'static str },
+    /// The value of an arg of the payment or session code.
+    Arg { field: &'static str, index: usize },
+    /// One of the deploy's approvals.
+    Approval(Approval),
+}
+
+impl Location {
+    /// Returns `true` if the object at this location is covered by the deploy hash.
+    fn is_signed(&self) -> bool {
+        !matches!(self, Location::Deploy | Location::Approval(_))
+    }
+
+    /// Returns the object at this location in the JSON form of a deploy.
+    fn find_mut<'a>(&self, deploy: &'a mut Value) -> Option<&'a mut Map<String, Value>> {
+        let object = match self {
+            Location::Deploy => deploy,
+            Location::Header => deploy.get_mut("header")?,
+            Location::Code { field } => code_variant_mut(deploy.get_mut(*field)?)?,
+            Location::Arg { field, index } => code_variant_mut(deploy.get_mut(*field)?)?
+                .get_mut("args")?
+                .get_mut(*index)?
+                .get_mut(1)?,
+            Location::Approval(approval) => {
+                let approval = serde_json::to_value(approval).ok()?;
+                deploy
+                    .get_mut("approvals")?
+                    .as_array_mut()?
+                    .iter_mut()
+                    .find(|candidate| **candidate == approval)?
+            }
+        };
+        object.as_object_mut()
+    }
+}
+
+/// Returns the variant object within the JSON form of an `ExecutableDeployItem`.
+fn code_variant_mut(code: &mut Value) -> Option<&mut Value> {
+    code.as_object_mut()?.values_mut().next()
+}
+
+/// Fields unknown to this version of the client, removed from a single JSON object.
+#[derive(Clone, Debug)]
+struct UnknownFields {
+    location: Location,
+    /// The path of the object within the deploy file, as a prefix for the names of its fields.
+    path: String,
+    fields: Map<String, Value>,
+}
+
+/// Removes the fields not in `known` from `object`, returning them.
+fn take(object: &mut Value, known: &[&str]) -> Map<String, Value> {
+    let object = match object.as_object_mut() {
+        Some(object) => object,
+        None => return Map::new(),
+    };
+    #[allow(clippy::needless_collect)] // The names are collected so that `object` can be mutated.
+    let unknown_names: Vec<String> = object
+        .keys()
+        .filter(|name| !known.contains(&name.as_str()))
+        .cloned()
+        .collect();
+    unknown_names
+        .into_iter()
+        .filter_map(|name| object.remove_entry(&name))
+        .collect()
+}
+
+/// Removes the fields unknown to this version of the client from the JSON form of a deploy.
+fn take_unknown_fields(deploy: &mut Value) -> Vec<UnknownFields> {
+    let mut unknown_fields = Vec::new();
+    let mut push = |location, path: String, fields: Map<String, Value>| {
+        if !fields.is_empty() {
+            unknown_fields.push(UnknownFields {
+                location,
+                path,
+                fields,
+            })
+        }
+    };
+
+    push(Location::Deploy, String::new(), take(deploy, DEPLOY_FIELDS));
+
+    if let Some(header) = deploy.get_mut("header") {
+        let fields = take(header, HEADER_FIELDS);
+        push(Location::Header, "header.".to_string(), fields);
+    }
+
+    for &field in &["payment", "session"] {
+        let (name, variant) = match deploy
+            .get_mut(field)
+            .and_then(Value::as_object_mut)
+            .and_then(|code| code.iter_mut().next())
+        {
+            Some(named_variant) => named_variant,
+            None => continue,
+        };
+        // An unknown variant can't be parsed at all, so is left for parsing to report.
+        let known = match executable_deploy_item_fields(name) {
+            Some(known) => known,
+            None => continue,
+        };
+        let path = format!("{}.{}", field, name);
+        let fields = take(variant, known);
+        push(Location::Code { field }, format!("{}.", path), fields);
+
+        let args = match variant.get_mut("args").and_then(Value::as_array_mut) {
+            Some(args) => args,
+            None => continue,
+        };
+        for (index, arg) in args.iter_mut().enumerate() {
+            if let Some(value) = arg.get_mut(1) {
+                let fields = take(value, CL_VALUE_FIELDS);
+                let path = format!("{}.args[{}][1].", path, index);
+                push(Location::Arg { field, index }, path, fields);
+            }
+        }
+    }
+
+    if let Some(approvals) = deploy.get_mut("approvals").and_then(Value::as_array_mut) {
+        for (index, approval) in approvals.iter_mut().enumerate() {
+            let fields = take(approval, APPROVAL_FIELDS);
+            // An approval which can't be parsed is left for parsing of the deploy to report.
+            if let Ok(parsed) = serde_json::from_value(approval.clone()) {
+                push(
+                    Location::Approval(parsed),
+                    format!("approvals[{}].", index),
+                    fields,
+                );
+            }
+        }
+    }
+
+    unknown_fields
+}
+
+/// A deploy read from a deploy file, along with the fields of the file unknown to this version of
+/// the client.
+#[derive(Debug)]
+pub(crate) struct DeployFile {
+    deploy: Deploy,
+    unknown_fields: Vec<UnknownFields>,
+}
+
+impl DeployFile {
+    /// Reads a deploy file from `input`, setting aside any unknown fields.
+    pub(crate) fn read<R: Read>(input: R) -> Result<Self> {
+        let mut json: Value = serde_json::from_reader(BufReader::new(input))?;
+        let unknown_fields = take_unknown_fields(&mut json);
+        let deploy: Deploy = serde_json::from_value(json)?;
+        deploy.is_valid_size(MAX_SERIALIZED_SIZE)?;
+        Ok(DeployFile {
+            deploy,
+            unknown_fields,
+        })
+    }
+
+    /// Returns the deploy for signing.
+    ///
+    /// Returns an error if the deploy's header or its payment or session code hold unknown fields.
+    pub(crate) fn deploy_mut(&mut self) -> Result<&mut Deploy> {
+        self.check_signed_fields()?;
+        Ok(&mut self.deploy)
+    }
+
+    /// Returns the deploy, dropping any unknown fields.
+    ///
+    /// Returns an error if the deploy's header or its payment or session code hold unknown fields.
+    pub(crate) fn into_deploy(self) -> Result<Deploy> {
+        self.check_signed_fields()?;
+        Ok(self.deploy)
+    }
+
+    /// Writes the deploy to `output`, along with its unknown fields.
+    pub(crate) fn write<W: Write>(&self, mut output: W) -> Result<()> {
+        let mut json = serde_json::to_value(&self.deploy)?;
+        for unknown in &self.unknown_fields {
+            if let Some(object) = unknown.location.find_mut(&mut json) {
+                object.extend(unknown.fields.clone());
+            }
+        }
+        let content = serde_json::to_string_pretty(&json)?;
+        output
+            .write_all(content.as_bytes())
+            .map_err(|error| Error::IoError {
+                context: "unable to write deploy".to_owned(),
+                error,
+            })
+    }
+
+    fn check_signed_fields(&self) -> Result<()> {
+        let fields: Vec<String> = self
+            .unknown_fields
+            .iter()
+            .filter(|unknown| unknown.location.is_signed())
+            .flat_map(|unknown| {
+                unknown
+                    .fields
+                    .keys()
+                    .map(move |name| format!("{}{}", unknown.path, name))
+            })
+            .collect();
+        if fields.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::UnknownSignedDeployFields { fields })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_execution_engine::core::engine_state::ExecutableDeployItem;
+    use casper_node::{crypto::AsymmetricKeyExt, types::Timestamp};
+    use casper_types::{
+        bytesrepr::Bytes, ContractHash, ContractPackageHash, RuntimeArgs, SecretKey, U512,
+    };
+
+    use super::*;
+
+    fn deploy_with_session(session: ExecutableDeployItem) -> Deploy {
+        let mut args = RuntimeArgs::new();
+        args.insert("amount", U512::from(1)).unwrap();
+        let payment = ExecutableDeployItem::ModuleBytes {
+            module_bytes: Bytes::new(),
+            args,
+        };
+        Deploy::new(
+            Timestamp::now(),
+            "1h".parse().unwrap(),
+            1,
+            vec![],
+            "casper-test-chain-name-1".to_string(),
+            payment,
+            session,
+            &SecretKey::generate_ed25519().unwrap(),
+        )
+    }
+
+    #[test]
+    fn should_know_every_field_of_a_deploy() {
+        let mut args = RuntimeArgs::new();
+        args.insert("target", [7u8; 32]).unwrap();
+        let sessions = vec![
+            ExecutableDeployItem::ModuleBytes {
+                module_bytes: vec![1, 2, 3].into(),
+                args: args.clone(),
+            },
+            ExecutableDeployItem::StoredContractByHash {
+                hash: ContractHash::new([1; 32]),
+                entry_point: "call".to_string(),
+                args: args.clone(),
+            },
+            ExecutableDeployItem::StoredContractByName {
+                name: "contract".to_string(),
+                entry_point: "call".to_string(),
+                args: args.clone(),
+            },
+            ExecutableDeployItem::StoredVersionedContractByHash {
+                hash: ContractPackageHash::new([2; 32]),
+                version: Some(1),
+                entry_point: "call".to_string(),
+                args: args.clone(),
+            },
+            ExecutableDeployItem::StoredVersionedContractByName {
+                name: "package".to_string(),
+                version: None,
+                entry_point: "call".to_string(),
+                args: args.clone(),
+            },
+            ExecutableDeployItem::Transfer { args },
+        ];
+
+        for session in sessions {
+            let deploy = deploy_with_session(session);
+            let expected = serde_json::to_value(&deploy).unwrap();
+            let mut json = expected.clone();
+            let unknown_fields = take_unknown_fields(&mut json);
+            assert!(
+                unknown_fields.is_empty(),
+                "known fields were taken: {:?}",
+                unknown_fields
+            );
+            assert_eq!(json, expected);
+        }
+    }
+}
@@ -6,10 +6,11 @@ use thiserror::Error;
 
 use casper_node::{crypto::Error as CryptoError, types::ExcessiveSizeDeployError};
 use casper_types::{
-    bytesrepr::Error as ToBytesError, CLValueError, UIntParseError, URefFromStrError,
+    bytesrepr::Error as ToBytesError, CLValueError, KeyFromStrError, UIntParseError,
+    URefFromStrError,
 };
 
-use crate::validation::ValidateResponseError;
+use crate::{runtime_args_builder::ArgMismatch, validation::ValidateResponseError};
 
 /// Crate-wide Result type wrapper.
 pub(crate) type Result<T> = std::result::Result<T, Error>;
@@ -23,8 +24,12 @@ pub enum Error {
     #[error("Failed to parse as a key")]
     FailedToParseKey,
 
+    /// Failed to parse a `Key` from a formatted string.
+    #[error("Failed to parse '{0}' as a key: {1}")]
+    FailedToParseFormattedKey(&'static str, KeyFromStrError),
+
     /// Failed to parse a `URef` from a formatted string.
-    #[error("Failed to parse '{0}' as a uref: {1:?}")]
+    #[error("Failed to parse '{0}' as a uref: {1}")]
     FailedToParseURef(&'static str, URefFromStrError),
 
     /// Failed to parse an integer from a string.
@@ -110,6 +115,27 @@ pub enum Error {
     #[error("Invalid argument '{0}': {1}")]
     InvalidArgument(&'static str, String),
 
+    /// Invalid arg in a JSON args file.
+    #[error("Invalid JSON arg '{name}': {details}")]
+    InvalidJsonArg {
+        /// The name of the offending arg.
+        name: String,
+        /// Description of the problem, such as the expected and actual types.
+        details: String,
+    },
+
+    /// Runtime args don't match the signature of the entry point being called.
+    #[error(
+        "Invalid args for entry point '{entry_point}': {}",
+        .mismatches.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
+    )]
+    InvalidRuntimeArgs {
+        /// The name of the entry point.
+        entry_point: String,
+        /// The differences between the provided args and those expected by the entry point.
+        mismatches: Vec<ArgMismatch>,
+    },
+
     /// Conflicting arguments.
     #[error("Conflicting arguments passed '{context}' {args:?}")]
     ConflictingArguments {
@@ -123,6 +149,46 @@ pub enum Error {
     #[error("Invalid response: {0}")]
     InvalidResponse(#[from] ValidateResponseError),
 
+    /// Failed to parse the client config file.
+    #[error("Invalid client config file {}: {error}", .path.display())]
+    InvalidClientConfig {
+        /// The path of the client config file.
+        path: PathBuf,
+        /// Description of the problem.
+        error: String,
+    },
+
+    /// The selected profile isn't defined in the client config file.
+    #[error("Unknown profile '{name}', expected one of {known:?}")]
+    UnknownProfile {
+        /// The name of the selected profile.
+        name: String,
+        /// The names of the profiles defined in the client config file.
+        known: Vec<String>,
+    },
+
+    /// The chain name doesn't match the name of the node's chainspec.
+    #[error("Chain name '{expected}' doesn't match the node's chainspec name '{actual}'")]
+    ChainNameMismatch {
+        /// The chain name for which deploys are created.
+        expected: String,
+        /// The name of the node's chainspec.
+        actual: String,
+    },
+
+    /// The deploy's header or its payment or session code hold fields unknown to this version of
+    /// the client.
+    #[error(
+        "Deploy has fields unknown to this version of the client in its signed header or body \
+        ({}), so is refused for signing or sending; it was probably created by a newer version of \
+        the client, which should be used instead",
+        .fields.join(", ")
+    )]
+    UnknownSignedDeployFields {
+        /// The paths of the unknown fields.
+        fields: Vec<String>,
+    },
+
     /// Must call FFI's setup function prior to making FFI calls.
     #[cfg(feature = "ffi")]
     #[error("Failed to call casper_setup_client()")]
@@ -53,6 +53,12 @@ pub enum casper_error_t {
     CASPER_FFI_PTR_NULL_BUT_REQUIRED = -22,
     CASPER_CONFLICTING_ARGUMENTS = -23,
     CASPER_DEPLOY_SIZE_TOO_LARGE = -24,
+    CASPER_INVALID_JSON_ARG = -25,
+    CASPER_INVALID_RUNTIME_ARGS = -26,
+    CASPER_INVALID_CLIENT_CONFIG = -27,
+    CASPER_UNKNOWN_PROFILE = -28,
+    CASPER_CHAIN_NAME_MISMATCH = -29,
+    CASPER_UNKNOWN_SIGNED_DEPLOY_FIELDS = -30,
 }
 
 trait AsFFIError {
@@ -63,6 +69,7 @@ impl AsFFIError for Error {
     fn as_ffi_error(&self) -> casper_error_t {
         match self {
             Error::FailedToParseKey => casper_error_t::CASPER_FAILED_TO_PARSE_KEY,
+            Error::FailedToParseFormattedKey(_, _) => casper_error_t::CASPER_FAILED_TO_PARSE_KEY,
             Error::FailedToParseURef(_, _) => casper_error_t::CASPER_FAILED_TO_PARSE_UREF,
             Error::FailedToParseInt(_, _) => casper_error_t::CASPER_FAILED_TO_PARSE_INT,
             Error::FailedToParseTimeDiff(_, _) => casper_error_t::CASPER_FAILED_TO_PARSE_TIME_DIFF,
@@ -86,6 +93,14 @@ impl AsFFIError for Error {
             Error::FFIPtrNullButRequired(_) => casper_error_t::CASPER_FFI_PTR_NULL_BUT_REQUIRED,
             Error::ConflictingArguments { .. } => casper_error_t::CASPER_CONFLICTING_ARGUMENTS,
             Error::DeploySizeTooLarge(_) => casper_error_t::CASPER_DEPLOY_SIZE_TOO_LARGE,
+            Error::InvalidJsonArg { .. } => casper_error_t::CASPER_INVALID_JSON_ARG,
+            Error::InvalidRuntimeArgs { .. } => casper_error_t::CASPER_INVALID_RUNTIME_ARGS,
+            Error::InvalidClientConfig { .. } => casper_error_t::CASPER_INVALID_CLIENT_CONFIG,
+            Error::UnknownProfile { .. } => casper_error_t::CASPER_UNKNOWN_PROFILE,
+            Error::ChainNameMismatch { .. } => casper_error_t::CASPER_CHAIN_NAME_MISMATCH,
+            Error::UnknownSignedDeployFields { .. } => {
+                casper_error_t::CASPER_UNKNOWN_SIGNED_DEPLOY_FIELDS
+            }
         }
     }
 }
@@ -440,6 +455,7 @@ pub extern "C" fn casper_get_deploy(
     node_address: *const c_char,
     verbosity_level: u64,
     deploy_hash: *const c_char,
+    verify: bool,
     response_buf: *mut c_uchar,
     response_buf_len: usize,
 ) -> casper_error_t {
@@ -449,7 +465,13 @@ pub extern "C" fn casper_get_deploy(
     let node_address = try_unsafe_arg!(node_address);
     let deploy_hash = try_unsafe_arg!(deploy_hash);
     runtime.block_on(async move {
-        let result = super::get_deploy(maybe_rpc_id, node_address, verbosity_level, deploy_hash);
+        let result = super::get_deploy(
+            maybe_rpc_id,
+            node_address,
+            verbosity_level,
+            deploy_hash,
+            verify,
+        );
         let response = try_unwrap_rpc!(result);
         copy_str_to_buf(&response, response_buf, response_buf_len);
         casper_error_t::CASPER_SUCCESS
@@ -465,6 +487,7 @@ pub extern "C" fn casper_get_block(
     node_address: *const c_char,
     verbosity_level: u64,
     maybe_block_id: *const c_char,
+    verify: bool,
     response_buf: *mut c_uchar,
     response_buf_len: usize,
 ) -> casper_error_t {
@@ -474,7 +497,13 @@ pub extern "C" fn casper_get_block(
     let node_address = try_unsafe_arg!(node_address);
     let maybe_block_id = try_unsafe_arg!(maybe_block_id);
     runtime.block_on(async move {
-        let result = super::get_block(maybe_rpc_id, node_address, verbosity_level, maybe_block_id);
+        let result = super::get_block(
+            maybe_rpc_id,
+            node_address,
+            verbosity_level,
+            maybe_block_id,
+            verify,
+        );
         let response = try_unwrap_rpc!(result);
         copy_str_to_buf(&response, response_buf, response_buf_len);
         casper_error_t::CASPER_SUCCESS
@@ -490,6 +519,7 @@ pub extern "C" fn casper_get_block_transfers(
     node_address: *const c_char,
     verbosity_level: u64,
     maybe_block_id: *const c_char,
+    verify: bool,
     response_buf: *mut c_uchar,
     response_buf_len: usize,
 ) -> casper_error_t {
@@ -499,8 +529,13 @@ pub extern "C" fn casper_get_block_transfers(
     let node_address = try_unsafe_arg!(node_address);
     let maybe_block_id = try_unsafe_arg!(maybe_block_id);
     runtime.block_on(async move {
-        let result =
-            super::get_block_transfers(maybe_rpc_id, node_address, verbosity_level, maybe_block_id);
+        let result = super::get_block_transfers(
+            maybe_rpc_id,
+            node_address,
+            verbosity_level,
+            maybe_block_id,
+            verify,
+        );
         let response = try_unwrap_rpc!(result);
         copy_str_to_buf(&response, response_buf, response_buf_len);
         casper_error_t::CASPER_SUCCESS
@@ -544,6 +579,7 @@ pub extern "C" fn casper_get_item(
     state_root_hash: *const c_char,
     key: *const c_char,
     path: *const c_char,
+    return_raw_bytes: bool,
     response_buf: *mut c_uchar,
     response_buf_len: usize,
 ) -> casper_error_t {
@@ -562,6 +598,7 @@ pub extern "C" fn casper_get_item(
             state_root_hash,
             key,
             path,
+            return_raw_bytes,
         );
         let response = try_unwrap_rpc!(result);
         copy_str_to_buf(&response, response_buf, response_buf_len);
@@ -767,6 +804,8 @@ impl TryInto<super::PaymentStrParams<'static>> for casper_payment_params_t {
             payment_path,
             payment_args_simple,
             payment_args_complex,
+            // Args given as a JSON document are not yet supported via FFI.
+            payment_args_json: "",
             payment_version,
             payment_entry_point,
         })
@@ -836,9 +875,14 @@ impl TryInto<super::SessionStrParams<'static>> for casper_session_params_t {
             session_path,
             session_args_simple,
             session_args_complex,
+            // Args given as a JSON document are not yet supported via FFI.
+            session_args_json: "",
             session_version,
             session_entry_point,
             is_session_transfer: self.is_session_transfer,
+            // Skipping validation of the args against the called entry point is not yet supported
+            // via FFI.
+            skip_arg_validation: false,
         })
     }
 }
@@ -12,31 +12,51 @@
     unused_qualifications
 )]
 
+mod balances_export;
 mod cl_type;
+mod client_config;
 mod deploy;
+mod deploy_file;
 mod error;
 #[cfg(feature = "ffi")]
 pub mod ffi;
 pub mod keygen;
+mod node_client;
+mod node_version;
 mod parsing;
+mod rendering;
 mod rpc;
+mod runtime_args_builder;
 mod validation;
 
 use std::{convert::TryInto, fs, io::Cursor};
 
+use futures::executor;
 use jsonrpc_lite::JsonRpc;
 use serde::Serialize;
 
 use casper_execution_engine::core::engine_state::ExecutableDeployItem;
-use casper_node::types::Deploy;
-use casper_types::{UIntParseError, U512};
-
+use casper_node::{
+    crypto::hash::Digest,
+    rpcs::{chain::GetBlockResult, state::GetAllBalancesResult},
+    types::{Deploy, GetStatusResult},
+};
+use casper_types::{PublicKey, Signature, UIntParseError, U512};
+
+pub use balances_export::BalancesExport;
 pub use cl_type::help;
+pub use client_config::{
+    ClientConfig, OutputFormat, Profile, DEFAULT_CLIENT_CONFIG_FILE_NAME, PROFILE_ENV_VAR,
+};
 pub use deploy::ListDeploysResult;
 use deploy::{DeployExt, DeployParams, OutputKind};
 pub use error::Error;
 use error::Result;
+pub use node_client::NodeClient;
+pub use node_version::NodeVersionResult;
+pub use rendering::render_cl_values;
 use rpc::{RpcCall, TransferTarget};
+pub use runtime_args_builder::{ArgMismatch, RuntimeArgsBuilder};
 pub use validation::ValidateResponseError;
 
 /// Creates a `Deploy` and sends it to the network for execution.
@@ -57,6 +77,12 @@ pub use validation::ValidateResponseError;
 ///   [`SessionStrParams`](struct.SessionStrParams.html) for more details.
 /// * `payment_params` contains payment-related options for this `Deploy`. See
 ///   [`PaymentStrParams`](struct.PaymentStrParams.html) for more details.
+///
+/// If the session calls a stored contract by hash, its args are first checked against the
+/// signature of the called entry point, read from the latest global state.  If they don't match,
+/// [`Error::InvalidRuntimeArgs`](enum.Error.html#variant.InvalidRuntimeArgs) is returned and the
+/// `Deploy` is not sent.  The check can be skipped by constructing the session params with
+/// [`with_skip_arg_validation()`](struct.SessionStrParams.html#method.with_skip_arg_validation).
 pub fn put_deploy(
     maybe_rpc_id: &str,
     node_address: &str,
@@ -65,12 +91,20 @@ pub fn put_deploy(
     session_params: SessionStrParams<'_>,
     payment_params: PaymentStrParams<'_>,
 ) -> Result<JsonRpc> {
+    let skip_arg_validation = session_params.skip_arg_validation;
+    let session = session_params.try_into()?;
+    if !skip_arg_validation {
+        executor::block_on(
+            RpcCall::new(maybe_rpc_id, node_address, verbosity_level)
+                .validate_session_args(&session),
+        )?;
+    }
     let deploy = Deploy::with_payment_and_session(
         deploy_params.try_into()?,
         payment_params.try_into()?,
-        session_params.try_into()?,
+        session,
     )?;
-    RpcCall::new(maybe_rpc_id, node_address, verbosity_level).put_deploy(deploy)
+    executor::block_on(RpcCall::new(maybe_rpc_id, node_address, verbosity_level).put_deploy(deploy))
 }
 
 /// Creates a `Deploy` and outputs it to a file or stdout.
@@ -147,6 +181,51 @@ pub fn sign_deploy_file(
     output.commit()
 }
 
+/// Reads a previously-saved `Deploy` from a file, adds a signature of it created by an external
+/// signer, and outputs it to a file or stdout.
+///
+/// * `input_path` specifies the path to the previously-saved `Deploy` file.
+/// * `signer` is the public key of the external signer.
+/// * `sign_digest` is passed the digest of the `Deploy`'s hash, and should return the external
+///   signer's prehashed signature of it, as created by `casper_node::crypto::sign_prehashed`.
+///   Ed25519 signers sign using Ed25519ph, so the `Deploy` itself need never be passed to the
+///   signer.  The signature is verified before being added to the `Deploy`.
+/// * `maybe_output_path` specifies the output file, or if empty, will print it to `stdout`.
+/// * If `force` is true, and a file exists at `maybe_output_path`, it will be overwritten. If
+///   `force` is false and a file exists at `maybe_output_path`,
+///   [`Error::FileAlreadyExists`](enum.Error.html#variant.FileAlreadyExists) is returned and a file
+///   will not be written.
+pub fn sign_deploy_file_with_external_signer<F>(
+    input_path: &str,
+    signer: PublicKey,
+    sign_digest: F,
+    maybe_output_path: &str,
+    force: bool,
+) -> Result<()>
+where
+    F: FnOnce(&Digest) -> Result<Signature>,
+{
+    let input = fs::read(input_path).map_err(|error| Error::IoError {
+        context: format!("unable to read deploy file at '{}'", input_path),
+        error,
+    })?;
+
+    let output = if maybe_output_path.is_empty() {
+        OutputKind::Stdout
+    } else {
+        OutputKind::file(maybe_output_path, force)
+    };
+
+    Deploy::sign_prehashed_and_write_deploy(
+        Cursor::new(input),
+        signer,
+        sign_digest,
+        output.get()?,
+    )?;
+
+    output.commit()
+}
+
 /// Reads a previously-saved `Deploy` from a file and sends it to the network for execution.
 ///
 /// * `maybe_rpc_id` is the JSON-RPC identifier, applied to the request and returned in the
@@ -166,7 +245,9 @@ pub fn send_deploy_file(
     verbosity_level: u64,
     input_path: &str,
 ) -> Result<JsonRpc> {
-    RpcCall::new(maybe_rpc_id, node_address, verbosity_level).send_deploy_file(input_path)
+    executor::block_on(
+        RpcCall::new(maybe_rpc_id, node_address, verbosity_level).send_deploy_file(input_path),
+    )
 }
 
 /// Transfers funds between purses.
@@ -207,13 +288,57 @@ pub fn transfer(
     let target = parsing::get_transfer_target(target_account)?;
     let transfer_id = parsing::transfer_id(transfer_id)?;
 
-    RpcCall::new(maybe_rpc_id, node_address, verbosity_level).transfer(
-        amount,
-        source_purse,
-        target,
-        transfer_id,
-        deploy_params.try_into()?,
-        payment_params.try_into()?,
+    executor::block_on(
+        RpcCall::new(maybe_rpc_id, node_address, verbosity_level).transfer(
+            amount,
+            source_purse,
+            target,
+            transfer_id,
+            deploy_params.try_into()?,
+            payment_params.try_into()?,
+        ),
+    )
+}
+
+/// Makes a batch of transfers between purses in a single `Deploy`.
+///
+/// The transfers are made atomically: if any of them fails, none of them are made.  The `Deploy` is
+/// charged the cost of a single transfer for each transfer in the batch.
+///
+/// * `maybe_rpc_id` is the JSON-RPC identifier, applied to the request and returned in the
+///   response. If it can be parsed as an `i64` it will be used as a JSON integer. If empty, a
+///   random `i64` will be assigned. Otherwise the provided string will be used verbatim.
+/// * `node_address` is the hostname or IP and port of the node on which the HTTP service is
+///   running, e.g. `"http://127.0.0.1:7777"`.
+/// * When `verbosity_level` is `1`, the JSON-RPC request will be printed to `stdout` with long
+///   string fields (e.g. hex-formatted raw Wasm bytes) shortened to a string indicating the char
+///   count of the field.  When `verbosity_level` is greater than `1`, the request will be printed
+///   to `stdout` with no abbreviation of long fields.  When `verbosity_level` is `0`, the request
+///   will not be printed to `stdout`.
+/// * `batch_file` is the path to a file holding the target account, amount and optional transfer id
+///   of each transfer, either as a JSON array of objects with `target`, `amount` and `id` fields,
+///   or as CSV lines of `target,amount,id`.  Targets are hex-encoded public keys and amounts are
+///   decimal strings.
+/// * `deploy_params` contains deploy-related options for this `Deploy`. See
+///   [`DeployStrParams`](struct.DeployStrParams.html) for more details.
+/// * `payment_params` contains payment-related options for this `Deploy`. See
+///   [`PaymentStrParams`](struct.PaymentStrParams.html) for more details.
+pub fn transfer_batch(
+    maybe_rpc_id: &str,
+    node_address: &str,
+    verbosity_level: u64,
+    batch_file: &str,
+    deploy_params: DeployStrParams<'_>,
+    payment_params: PaymentStrParams<'_>,
+) -> Result<JsonRpc> {
+    let transfers = parsing::transfer_batch(batch_file)?;
+
+    executor::block_on(
+        RpcCall::new(maybe_rpc_id, node_address, verbosity_level).transfer_batch(
+            transfers,
+            deploy_params.try_into()?,
+            payment_params.try_into()?,
+        ),
     )
 }
 
@@ -284,13 +409,47 @@ pub fn make_transfer(
 ///   to `stdout` with no abbreviation of long fields.  When `verbosity_level` is `0`, the request
 ///   will not be printed to `stdout`.
 /// * `deploy_hash` must be a hex-encoded, 32-byte hash digest.
+/// * If `verify` is `true`, the returned `Deploy`'s hash must be `deploy_hash`, and its hash and
+///   body hash are recomputed from its header and body.  Any mismatch is returned as an
+///   [`Error::InvalidResponse`].
 pub fn get_deploy(
     maybe_rpc_id: &str,
     node_address: &str,
     verbosity_level: u64,
     deploy_hash: &str,
+    verify: bool,
+) -> Result<JsonRpc> {
+    executor::block_on(
+        RpcCall::new(maybe_rpc_id, node_address, verbosity_level).get_deploy(deploy_hash, verify),
+    )
+}
+
+/// Retrieves the lifecycle stage a `Deploy` has reached on the node, e.g. pending, proposed,
+/// executed or expired.
+///
+/// This is cheaper than [`get_deploy`] as neither the `Deploy` itself nor its execution results
+/// are returned.  Consequently, there is nothing to verify in the response.
+///
+/// * `maybe_rpc_id` is the JSON-RPC identifier, applied to the request and returned in the
+///   response. If it can be parsed as an `i64` it will be used as a JSON integer. If empty, a
+///   random `i64` will be assigned. Otherwise the provided string will be used verbatim.
+/// * `node_address` is the hostname or IP and port of the node on which the HTTP service is
+///   running, e.g. `"http://127.0.0.1:7777"`.
+/// * When `verbosity_level` is `1`, the JSON-RPC request will be printed to `stdout` with long
+///   string fields (e.g. hex-formatted raw Wasm bytes) shortened to a string indicating the char
+///   count of the field.  When `verbosity_level` is greater than `1`, the request will be printed
+///   to `stdout` with no abbreviation of long fields.  When `verbosity_level` is `0`, the request
+///   will not be printed to `stdout`.
+/// * `deploy_hash` must be a hex-encoded, 32-byte hash digest.
+pub fn get_deploy_status(
+    maybe_rpc_id: &str,
+    node_address: &str,
+    verbosity_level: u64,
+    deploy_hash: &str,
 ) -> Result<JsonRpc> {
-    RpcCall::new(maybe_rpc_id, node_address, verbosity_level).get_deploy(deploy_hash)
+    executor::block_on(
+        RpcCall::new(maybe_rpc_id, node_address, verbosity_level).get_deploy_status(deploy_hash),
+    )
 }
 
 /// Retrieves a `Block` from the network.
@@ -307,13 +466,46 @@ pub fn get_deploy(
 ///   will not be printed to `stdout`.
 /// * `maybe_block_id` must be a hex-encoded, 32-byte hash digest or a `u64` representing the
 ///   `Block` height or empty. If empty, the latest `Block` will be retrieved.
+/// * If `verify` is `true`, the returned `Block`'s hash and body hash are recomputed from its
+///   header and body, and it must be the `Block` identified by `maybe_block_id`.  Any mismatch is
+///   returned as an [`Error::InvalidResponse`].
 pub fn get_block(
     maybe_rpc_id: &str,
     node_address: &str,
     verbosity_level: u64,
     maybe_block_id: &str,
+    verify: bool,
 ) -> Result<JsonRpc> {
-    RpcCall::new(maybe_rpc_id, node_address, verbosity_level).get_block(maybe_block_id)
+    executor::block_on(
+        RpcCall::new(maybe_rpc_id, node_address, verbosity_level).get_block(maybe_block_id, verify),
+    )
+}
+
+/// Retrieves the hashes of all `Deploy`s in a `Block` from the network.
+///
+/// * `maybe_rpc_id` is the JSON-RPC identifier, applied to the request and returned in the
+///   response. If it can be parsed as an `i64` it will be used as a JSON integer. If empty, a
+///   random `i64` will be assigned. Otherwise the provided string will be used verbatim.
+/// * `node_address` is the hostname or IP and port of the node on which the HTTP service is
+///   running, e.g. `"http://127.0.0.1:7777"`.
+/// * When `verbosity_level` is `1`, the JSON-RPC request will be printed to `stdout` with long
+///   string fields (e.g. hex-formatted raw Wasm bytes) shortened to a string indicating the char
+///   count of the field.  When `verbosity_level` is greater than `1`, the request will be printed
+///   to `stdout` with no abbreviation of long fields.  When `verbosity_level` is `0`, the request
+///   will not be printed to `stdout`.
+/// * `maybe_block_id` must be a hex-encoded, 32-byte hash digest or a `u64` representing the
+///   `Block` height or empty. If empty, the latest `Block` will be used.
+pub fn list_deploys(
+    maybe_rpc_id: &str,
+    node_address: &str,
+    verbosity_level: u64,
+    maybe_block_id: &str,
+) -> Result<ListDeploysResult> {
+    let response = executor::block_on(
+        RpcCall::new(maybe_rpc_id, node_address, verbosity_level).get_block(maybe_block_id, true),
+    )?;
+    let get_block_result: GetBlockResult = rpc::parse_result(response)?;
+    Ok(ListDeploysResult::from(get_block_result))
 }
 
 /// Retrieves all `Transfer` items for a `Block` from the network.
@@ -330,13 +522,19 @@ pub fn get_block(
 ///   will not be printed to `stdout`.
 /// * `maybe_block_id` must be a hex-encoded, 32-byte hash digest or a `u64` representing the
 ///   `Block` height or empty. If empty, the latest `Block` transfers will be retrieved.
+/// * If `verify` is `true` and `maybe_block_id` is a hash, the returned `Block` hash must match it,
+///   otherwise an [`Error::InvalidResponse`] is returned.
 pub fn get_block_transfers(
     maybe_rpc_id: &str,
     node_address: &str,
     verbosity_level: u64,
     maybe_block_id: &str,
+    verify: bool,
 ) -> Result<JsonRpc> {
-    RpcCall::new(maybe_rpc_id, node_address, verbosity_level).get_block_transfers(maybe_block_id)
+    executor::block_on(
+        RpcCall::new(maybe_rpc_id, node_address, verbosity_level)
+            .get_block_transfers(maybe_block_id, verify),
+    )
 }
 
 /// Retrieves a state root hash at a given `Block`.
@@ -359,7 +557,10 @@ pub fn get_state_root_hash(
     verbosity_level: u64,
     maybe_block_id: &str,
 ) -> Result<JsonRpc> {
-    RpcCall::new(maybe_rpc_id, node_address, verbosity_level).get_state_root_hash(maybe_block_id)
+    executor::block_on(
+        RpcCall::new(maybe_rpc_id, node_address, verbosity_level)
+            .get_state_root_hash(maybe_block_id),
+    )
 }
 
 /// Retrieves a stored value from the network.
@@ -387,6 +588,9 @@ pub fn get_state_root_hash(
 /// deploy-0102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f20      # Key::DeployInfo
 /// ```
 /// * `path` is comprised of components starting from the `key`, separated by `/`s.
+/// * If `return_raw_bytes` is `true`, the response will include the bytesrepr-serialized stored
+///   value as hex, allowing the merkle proof to be verified without relying on the node's JSON
+///   rendering of the value.  The raw bytes are checked against the merkle proof before returning.
 pub fn get_item(
     maybe_rpc_id: &str,
     node_address: &str,
@@ -394,8 +598,52 @@ pub fn get_item(
     state_root_hash: &str,
     key: &str,
     path: &str,
+    return_raw_bytes: bool,
 ) -> Result<JsonRpc> {
-    RpcCall::new(maybe_rpc_id, node_address, verbosity_level).get_item(state_root_hash, key, path)
+    executor::block_on(
+        RpcCall::new(maybe_rpc_id, node_address, verbosity_level).get_item(
+            state_root_hash,
+            key,
+            path,
+            return_raw_bytes,
+        ),
+    )
+}
+
+/// Retrieves the signature of a stored contract's entry point from the network.
+///
+/// * `maybe_rpc_id` is the JSON-RPC identifier, applied to the request and returned in the
+///   response. If it can be parsed as an `i64` it will be used as a JSON integer. If empty, a
+///   random `i64` will be assigned. Otherwise the provided string will be used verbatim.
+/// * `node_address` is the hostname or IP and port of the node on which the HTTP service is
+///   running, e.g. `"http://127.0.0.1:7777"`.
+/// * When `verbosity_level` is `1`, the JSON-RPC request will be printed to `stdout` with long
+///   string fields (e.g. hex-formatted raw Wasm bytes) shortened to a string indicating the char
+///   count of the field.  When `verbosity_level` is greater than `1`, the request will be printed
+///   to `stdout` with no abbreviation of long fields.  When `verbosity_level` is `0`, the request
+///   will not be printed to `stdout`.
+/// * `maybe_state_root_hash` must be a hex-encoded, 32-byte hash digest or empty. If empty, the
+///   contract will be read from the global state of the latest block.
+/// * `contract_hash` must be the hex-encoded hash of the stored contract, or a formatted
+///   `Key::Hash` of the form `hash-<HEX STRING>`.
+/// * `entry_point` is the name of the entry point.
+///
+/// The entry point is checked against the merkle proof of the contract before returning.
+pub fn get_entry_point(
+    maybe_rpc_id: &str,
+    node_address: &str,
+    verbosity_level: u64,
+    maybe_state_root_hash: &str,
+    contract_hash: &str,
+    entry_point: &str,
+) -> Result<JsonRpc> {
+    executor::block_on(
+        RpcCall::new(maybe_rpc_id, node_address, verbosity_level).get_entry_point(
+            maybe_state_root_hash,
+            contract_hash,
+            entry_point,
+        ),
+    )
 }
 
 /// Retrieves a purse's balance from the network.
@@ -422,7 +670,107 @@ pub fn get_balance(
     state_root_hash: &str,
     purse: &str,
 ) -> Result<JsonRpc> {
-    RpcCall::new(maybe_rpc_id, node_address, verbosity_level).get_balance(state_root_hash, purse)
+    executor::block_on(
+        RpcCall::new(maybe_rpc_id, node_address, verbosity_level)
+            .get_balance(state_root_hash, purse),
+    )
+}
+
+/// Retrieves the balances of several purses from the network, all read from the same global state.
+///
+/// * `maybe_rpc_id` is the JSON-RPC identifier, applied to the request and returned in the
+///   response. If it can be parsed as an `i64` it will be used as a JSON integer. If empty, a
+///   random `i64` will be assigned. Otherwise the provided string will be used verbatim.
+/// * `node_address` is the hostname or IP and port of the node on which the HTTP service is
+///   running, e.g. `"http://127.0.0.1:7777"`.
+/// * When `verbosity_level` is `1`, the JSON-RPC request will be printed to `stdout` with long
+///   string fields (e.g. hex-formatted raw Wasm bytes) shortened to a string indicating the char
+///   count of the field.  When `verbosity_level` is greater than `1`, the request will be printed
+///   to `stdout` with no abbreviation of long fields.  When `verbosity_level` is `0`, the request
+///   will not be printed to `stdout`.
+/// * `state_root_hash` must be a hex-encoded, 32-byte hash digest.
+/// * `purses` are each either a URef or an account hash, formatted as e.g.
+/// ```text
+/// uref-0102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f20-007
+/// account-hash-0102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f20
+/// ```
+///   For an account hash, the balance of the account's main purse is returned.
+///
+/// The node limits how many purses may be requested at once.  A purse whose balance can't be read
+/// doesn't fail the request; instead its entry in the response holds the error.
+pub fn get_balances(
+    maybe_rpc_id: &str,
+    node_address: &str,
+    verbosity_level: u64,
+    state_root_hash: &str,
+    purses: &[&str],
+) -> Result<JsonRpc> {
+    executor::block_on(
+        RpcCall::new(maybe_rpc_id, node_address, verbosity_level)
+            .get_balances(state_root_hash, purses),
+    )
+}
+
+/// Retrieves a page of the balances of all accounts' main purses from the network, all read from
+/// the same global state.
+///
+/// * `maybe_rpc_id` is the JSON-RPC identifier, applied to the request and returned in the
+///   response. If it can be parsed as an `i64` it will be used as a JSON integer. If empty, a
+///   random `i64` will be assigned. Otherwise the provided string will be used verbatim.
+/// * `node_address` is the hostname or IP and port of the node on which the HTTP service is
+///   running, e.g. `"http://127.0.0.1:7777"`.
+/// * When `verbosity_level` is `1`, the JSON-RPC request will be printed to `stdout` with long
+///   string fields (e.g. hex-formatted raw Wasm bytes) shortened to a string indicating the char
+///   count of the field.  When `verbosity_level` is greater than `1`, the request will be printed
+///   to `stdout` with no abbreviation of long fields.  When `verbosity_level` is `0`, the request
+///   will not be printed to `stdout`.
+/// * `state_root_hash` must be a hex-encoded, 32-byte hash digest.
+/// * `page_token` is the `next_page_token` of the previous page's response, or empty for the first
+///   page.
+///
+/// The node only serves this request if balance exports are enabled in its `[rpc_server]` config.
+pub fn get_all_balances(
+    maybe_rpc_id: &str,
+    node_address: &str,
+    verbosity_level: u64,
+    state_root_hash: &str,
+    page_token: &str,
+) -> Result<JsonRpc> {
+    executor::block_on(
+        RpcCall::new(maybe_rpc_id, node_address, verbosity_level)
+            .get_all_balances(state_root_hash, page_token),
+    )
+}
+
+/// Retrieves the balances of all accounts' main purses from the network, all read from the same
+/// global state, by requesting every page in turn.
+///
+/// See [`get_all_balances`] for details of the arguments.
+pub fn export_balances(
+    maybe_rpc_id: &str,
+    node_address: &str,
+    verbosity_level: u64,
+    state_root_hash: &str,
+) -> Result<BalancesExport> {
+    let mut export = BalancesExport::new(Digest::from_hex(state_root_hash).map_err(|error| {
+        Error::CryptoError {
+            context: "state_root_hash",
+            error: error.into(),
+        }
+    })?);
+    let mut page_token = String::new();
+    loop {
+        let response = executor::block_on(
+            RpcCall::new(maybe_rpc_id, node_address, verbosity_level)
+                .get_all_balances(state_root_hash, &page_token),
+        )?;
+        let page: GetAllBalancesResult = rpc::parse_result(response)?;
+        export.extend(page.balances, page.errors);
+        match page.next_page_token {
+            Some(next_page_token) => page_token = next_page_token,
+            None => return Ok(export),
+        }
+    }
 }
 
 /// Retrieves era information from the network.
@@ -446,8 +794,10 @@ pub fn get_era_info_by_switch_block(
     verbosity_level: u64,
     maybe_block_id: &str,
 ) -> Result<JsonRpc> {
-    RpcCall::new(maybe_rpc_id, node_address, verbosity_level)
-        .get_era_info_by_switch_block(maybe_block_id)
+    executor::block_on(
+        RpcCall::new(maybe_rpc_id, node_address, verbosity_level)
+            .get_era_info_by_switch_block(maybe_block_id),
+    )
 }
 
 /// Retrieves the bids and validators as of the most recently added `Block`.
@@ -471,7 +821,9 @@ pub fn get_auction_info(
     verbosity_level: u64,
     maybe_block_id: &str,
 ) -> Result<JsonRpc> {
-    RpcCall::new(maybe_rpc_id, node_address, verbosity_level).get_auction_info(maybe_block_id)
+    executor::block_on(
+        RpcCall::new(maybe_rpc_id, node_address, verbosity_level).get_auction_info(maybe_block_id),
+    )
 }
 
 /// Retrieves an Account from the network.
@@ -496,8 +848,151 @@ pub fn get_account_info(
     public_key: &str,
     maybe_block_id: &str,
 ) -> Result<JsonRpc> {
-    RpcCall::new(maybe_rpc_id, node_address, verbosity_level)
-        .get_account_info(public_key, maybe_block_id)
+    executor::block_on(
+        RpcCall::new(maybe_rpc_id, node_address, verbosity_level)
+            .get_account_info(public_key, maybe_block_id),
+    )
+}
+
+/// Retrieves the performance of validators in one or more recent eras.
+///
+/// * `maybe_rpc_id` is the JSON-RPC identifier, applied to the request and returned in the
+///   response. If it can be parsed as an `i64` it will be used as a JSON integer. If empty, a
+///   random `i64` will be assigned. Otherwise the provided string will be used verbatim.
+/// * `node_address` is the hostname or IP and port of the node on which the HTTP service is
+///   running, e.g. `"http://127.0.0.1:7777"`.
+/// * When `verbosity_level` is `1`, the JSON-RPC request will be printed to `stdout` with long
+///   string fields (e.g. hex-formatted raw Wasm bytes) shortened to a string indicating the char
+///   count of the field.  When `verbosity_level` is greater than `1`, the request will be printed
+///   to `stdout` with no abbreviation of long fields.  When `verbosity_level` is `0`, the request
+///   will not be printed to `stdout`.
+/// * `era_id` must be a `u64` representing the requested era, or the first era of the requested
+///   range if `maybe_to_era_id` is not empty.
+/// * `maybe_to_era_id` must be a `u64` representing the last era of the requested range, or empty
+///   to request only the era given by `era_id`.
+/// * `maybe_public_key` must be a hex-encoded public key of the validator whose performance is
+///   requested, or empty to request the performance of all validators.
+pub fn get_validator_performance(
+    maybe_rpc_id: &str,
+    node_address: &str,
+    verbosity_level: u64,
+    era_id: &str,
+    maybe_to_era_id: &str,
+    maybe_public_key: &str,
+) -> Result<JsonRpc> {
+    executor::block_on(
+        RpcCall::new(maybe_rpc_id, node_address, verbosity_level).get_validator_performance(
+            era_id,
+            maybe_to_era_id,
+            maybe_public_key,
+        ),
+    )
+}
+
+/// Retrieves the changes to the validator set taking effect in one or more eras, i.e. the
+/// validators added, removed, banned or evicted, and those whose weight changed.
+///
+/// * `maybe_rpc_id` is the JSON-RPC identifier, applied to the request and returned in the
+///   response. If it can be parsed as an `i64` it will be used as a JSON integer. If empty, a
+///   random `i64` will be assigned. Otherwise the provided string will be used verbatim.
+/// * `node_address` is the hostname or IP and port of the node on which the HTTP service is
+///   running, e.g. `"http://127.0.0.1:7777"`.
+/// * When `verbosity_level` is `1`, the JSON-RPC request will be printed to `stdout` with long
+///   string fields (e.g. hex-formatted raw Wasm bytes) shortened to a string indicating the char
+///   count of the field.  When `verbosity_level` is greater than `1`, the request will be printed
+///   to `stdout` with no abbreviation of long fields.  When `verbosity_level` is `0`, the request
+///   will not be printed to `stdout`.
+/// * `era_id` must be a `u64` representing the requested era, or the first era of the requested
+///   range if `maybe_to_era_id` is not empty.
+/// * `maybe_to_era_id` must be a `u64` representing the last era of the requested range, or empty
+///   to request only the era given by `era_id`.
+pub fn get_validator_changes(
+    maybe_rpc_id: &str,
+    node_address: &str,
+    verbosity_level: u64,
+    era_id: &str,
+    maybe_to_era_id: &str,
+) -> Result<JsonRpc> {
+    executor::block_on(
+        RpcCall::new(maybe_rpc_id, node_address, verbosity_level)
+            .get_validator_changes(era_id, maybe_to_era_id),
+    )
+}
+
+/// Retrieves the status of the node, including the progress of the current era.
+///
+/// * `maybe_rpc_id` is the JSON-RPC identifier, applied to the request and returned in the
+///   response. If it can be parsed as an `i64` it will be used as a JSON integer. If empty, a
+///   random `i64` will be assigned. Otherwise the provided string will be used verbatim.
+/// * `node_address` is the hostname or IP and port of the node on which the HTTP service is
+///   running, e.g. `"http://127.0.0.1:7777"`.
+/// * When `verbosity_level` is `1`, the JSON-RPC request will be printed to `stdout` with long
+///   string fields (e.g. hex-formatted raw Wasm bytes) shortened to a string indicating the char
+///   count of the field.  When `verbosity_level` is greater than `1`, the request will be printed
+///   to `stdout` with no abbreviation of long fields.  When `verbosity_level` is `0`, the request
+///   will not be printed to `stdout`.
+///
+/// The era progress in the `consensus` field of the response is `null` while the node is not yet
+/// participating in consensus, e.g. while it is still syncing.
+pub fn get_status(maybe_rpc_id: &str, node_address: &str, verbosity_level: u64) -> Result<JsonRpc> {
+    executor::block_on(RpcCall::new(maybe_rpc_id, node_address, verbosity_level).get_status())
+}
+
+/// Retrieves the build information, protocol version and chainspec hash of the node.
+///
+/// * `maybe_rpc_id` is the JSON-RPC identifier, applied to the request and returned in the
+///   response. If it can be parsed as an `i64` it will be used as a JSON integer. If empty, a
+///   random `i64` will be assigned. Otherwise the provided string will be used verbatim.
+/// * `node_address` is the hostname or IP and port of the node on which the HTTP service is
+///   running, e.g. `"http://127.0.0.1:7777"`.
+/// * When `verbosity_level` is `1`, the JSON-RPC request will be printed to `stdout` with long
+///   string fields (e.g. hex-formatted raw Wasm bytes) shortened to a string indicating the char
+///   count of the field.  When `verbosity_level` is greater than `1`, the request will be printed
+///   to `stdout` with no abbreviation of long fields.  When `verbosity_level` is `0`, the request
+///   will not be printed to `stdout`.
+pub fn get_node_version(
+    maybe_rpc_id: &str,
+    node_address: &str,
+    verbosity_level: u64,
+) -> Result<NodeVersionResult> {
+    let response =
+        executor::block_on(RpcCall::new(maybe_rpc_id, node_address, verbosity_level).get_status())?;
+    let get_status_result: GetStatusResult = rpc::parse_result(response)?;
+    Ok(NodeVersionResult::from(get_status_result))
+}
+
+/// Checks that the node's chainspec has the name `chain_name`, so that deploys created for that
+/// chain aren't sent to a node of a different network.
+///
+/// * `maybe_rpc_id` is the JSON-RPC identifier, applied to the request and returned in the
+///   response. If it can be parsed as an `i64` it will be used as a JSON integer. If empty, a
+///   random `i64` will be assigned. Otherwise the provided string will be used verbatim.
+/// * `node_address` is the hostname or IP and port of the node on which the HTTP service is
+///   running, e.g. `"http://127.0.0.1:7777"`.
+/// * When `verbosity_level` is `1`, the JSON-RPC request will be printed to `stdout` with long
+///   string fields (e.g. hex-formatted raw Wasm bytes) shortened to a string indicating the char
+///   count of the field.  When `verbosity_level` is greater than `1`, the request will be printed
+///   to `stdout` with no abbreviation of long fields.  When `verbosity_level` is `0`, the request
+///   will not be printed to `stdout`.
+///
+/// Returns [`Error::ChainNameMismatch`](enum.Error.html#variant.ChainNameMismatch) if the names
+/// differ.
+pub fn check_chain_name(
+    maybe_rpc_id: &str,
+    node_address: &str,
+    verbosity_level: u64,
+    chain_name: &str,
+) -> Result<()> {
+    let response =
+        executor::block_on(RpcCall::new(maybe_rpc_id, node_address, verbosity_level).get_status())?;
+    let get_status_result: GetStatusResult = rpc::parse_result(response)?;
+    if get_status_result.chainspec_name != chain_name {
+        return Err(Error::ChainNameMismatch {
+            expected: chain_name.to_string(),
+            actual: get_status_result.chainspec_name,
+        });
+    }
+    Ok(())
 }
 
 /// Retrieves information and examples for all currently supported RPCs.
@@ -513,7 +1008,7 @@ pub fn get_account_info(
 ///   to `stdout` with no abbreviation of long fields.  When `verbosity_level` is `0`, the request
 ///   will not be printed to `stdout`.
 pub fn list_rpcs(maybe_rpc_id: &str, node_address: &str, verbosity_level: u64) -> Result<JsonRpc> {
-    RpcCall::new(maybe_rpc_id, node_address, verbosity_level).list_rpcs()
+    executor::block_on(RpcCall::new(maybe_rpc_id, node_address, verbosity_level).list_rpcs())
 }
 
 /// Container for `Deploy` construction options.
@@ -629,10 +1124,33 @@ impl<'a> TryInto<DeployParams> for DeployStrParams<'a> {
 /// For methods taking `payment_args_complex`, this parameter is the payment contract arguments, in
 /// the form of a `ToBytes`-encoded file.
 ///
+/// ## `payment_args_json`
+///
+/// Set via [`with_args_json()`](#method.with_args_json), this is the path to a JSON file holding
+/// the payment contract arguments as an array of objects, each with a `name`, a `type` and a
+/// `value`.  The `type` is a `CLType` in the same form as used by the JSON schema for `CLType`, and
+/// the `value` is a JSON value of that type, allowing args of any `CLType` to be specified:
+///
+/// ```json
+/// [
+///   {"name": "amount", "type": "U512", "value": "2500000000"},
+///   {"name": "target", "type": {"ByteArray": 32}, "value": "0102...1f20"},
+///   {"name": "maybe", "type": {"Option": "U64"}, "value": null},
+///   {"name": "pairs", "type": {"List": {"Tuple2": ["String", "I32"]}}, "value": [["a", -1]]},
+///   {"name": "map", "type": {"Map": {"key": "U8", "value": "Bool"}},
+///    "value": [{"key": 1, "value": true}]},
+///   {"name": "result", "type": {"Result": {"ok": "Unit", "err": "String"}},
+///    "value": {"Err": "failed"}}
+/// ]
+/// ```
+///
+/// The big numeric types `U128`, `U256` and `U512` are given as decimal strings, byte arrays as
+/// hex strings, and `Key`s and `URef`s in their formatted string forms.
+///
 /// ---
 ///
 /// **Note** while multiple payment args can be specified for a single payment code instance, only
-/// one of `payment_args_simple` and `payment_args_complex` may be used.
+/// one of `payment_args_simple`, `payment_args_complex` and `payment_args_json` may be used.
 #[derive(Default)]
 pub struct PaymentStrParams<'a> {
     payment_amount: &'a str,
@@ -643,6 +1161,7 @@ pub struct PaymentStrParams<'a> {
     payment_path: &'a str,
     payment_args_simple: Vec<&'a str>,
     payment_args_complex: &'a str,
+    payment_args_json: &'a str,
     payment_version: &'a str,
     payment_entry_point: &'a str,
 }
@@ -660,6 +1179,7 @@ impl<'a> TryInto<ExecutableDeployItem> for PaymentStrParams<'a> {
             payment_path,
             payment_args_simple,
             payment_args_complex,
+            payment_args_json,
             payment_version,
             payment_entry_point,
         } = self;
@@ -673,6 +1193,7 @@ impl<'a> TryInto<ExecutableDeployItem> for PaymentStrParams<'a> {
             payment_path,
             &payment_args_simple,
             payment_args_complex,
+            payment_args_json,
             payment_version,
             payment_entry_point,
         )
@@ -701,7 +1222,8 @@ impl<'a> PaymentStrParams<'a> {
     /// Constructs a `PaymentStrParams` using a payment amount.
     ///
     /// `payment_amount` uses the standard-payment system contract rather than custom payment Wasm.
-    /// The value is the 'amount' arg of the standard-payment contract.
+    /// The value is the 'amount' arg of the standard-payment contract, in motes, or in CSPR if
+    /// suffixed with "cspr", e.g. "2.5cspr".
     pub fn with_amount(payment_amount: &'a str) -> Self {
         Self {
             payment_amount,
@@ -806,6 +1328,15 @@ impl<'a> PaymentStrParams<'a> {
             ..Default::default()
         }
     }
+
+    /// Sets the path to a JSON file containing the payment contract arguments.
+    ///
+    /// * See the struct docs for a description of [`payment_args_json`](#payment_args_json).  This
+    ///   must not be used along with `payment_args_simple` or `payment_args_complex`.
+    pub fn with_args_json(mut self, payment_args_json: &'a str) -> Self {
+        self.payment_args_json = payment_args_json;
+        self
+    }
 }
 
 impl<'a> TryInto<ExecutableDeployItem> for SessionStrParams<'a> {
@@ -820,9 +1351,11 @@ impl<'a> TryInto<ExecutableDeployItem> for SessionStrParams<'a> {
             session_path,
             session_args_simple,
             session_args_complex,
+            session_args_json,
             session_version,
             session_entry_point,
             is_session_transfer,
+            skip_arg_validation: _,
         } = self;
 
         parsing::parse_session_info(
@@ -833,6 +1366,7 @@ impl<'a> TryInto<ExecutableDeployItem> for SessionStrParams<'a> {
             session_path,
             &session_args_simple,
             session_args_complex,
+            session_args_json,
             session_version,
             session_entry_point,
             is_session_transfer,
@@ -856,10 +1390,19 @@ impl<'a> TryInto<ExecutableDeployItem> for SessionStrParams<'a> {
 /// For methods taking `session_args_complex`, this parameter is the session contract arguments, in
 /// the form of a `ToBytes`-encoded file.
 ///
+/// ## `session_args_json`
+///
+/// Set via [`with_args_json()`](#method.with_args_json), this is the path to a JSON file holding
+/// the session contract arguments.
+///
+/// There are further details in
+/// [the docs for the equivalent
+/// `payment_args_json`](struct.PaymentStrParams.html#payment_args_json).
+///
 /// ---
 ///
 /// **Note** while multiple payment args can be specified for a single session code instance, only
-/// one of `session_args_simple` and `session_args_complex` may be used.
+/// one of `session_args_simple`, `session_args_complex` and `session_args_json` may be used.
 #[derive(Default)]
 pub struct SessionStrParams<'a> {
     session_hash: &'a str,
@@ -869,9 +1412,11 @@ pub struct SessionStrParams<'a> {
     session_path: &'a str,
     session_args_simple: Vec<&'a str>,
     session_args_complex: &'a str,
+    session_args_json: &'a str,
     session_version: &'a str,
     session_entry_point: &'a str,
     is_session_transfer: bool,
+    skip_arg_validation: bool,
 }
 
 impl<'a> SessionStrParams<'a> {
@@ -1003,6 +1548,24 @@ impl<'a> SessionStrParams<'a> {
             ..Default::default()
         }
     }
+
+    /// Sets the path to a JSON file containing the session contract arguments.
+    ///
+    /// * See the struct docs for a description of [`session_args_json`](#session_args_json).  This
+    ///   must not be used along with `session_args_simple` or `session_args_complex`.
+    pub fn with_args_json(mut self, session_args_json: &'a str) -> Self {
+        self.session_args_json = session_args_json;
+        self
+    }
+
+    /// Disables checking the session args against the signature of the called entry point before
+    /// sending the `Deploy`.
+    ///
+    /// See [`put_deploy()`](fn.put_deploy.html) for details of the check.
+    pub fn with_skip_arg_validation(mut self) -> Self {
+        self.skip_arg_validation = true;
+        self
+    }
 }
 
 /// When `verbosity_level` is `1`, the value will be printed to `stdout` with long string fields
@@ -1295,6 +1858,19 @@ mod param_tests {
             }
         }
 
+        #[test]
+        fn should_fail_to_convert_with_zero_gas_price() {
+            let mut params = test_value();
+            params.gas_price = "0";
+            let result: StdResult<DeployParams, Error> = params.try_into();
+            let result = result.map(|_| ());
+            if let Err(Error::InvalidArgument(context, _)) = result {
+                assert_eq!(context, "gas_price");
+            } else {
+                panic!("should be an error");
+            }
+        }
+
         #[test]
         fn should_fail_to_convert_with_bad_chain_name() {
             let mut params = test_value();
@@ -0,0 +1,345 @@
+//! An asynchronous client for a node's JSON-RPC server, for embedding in async services.
+
+use std::convert::TryInto;
+
+use reqwest::Client;
+
+use casper_node::{
+    rpcs::{
+        account::PutDeployResult,
+        chain::{GetBlockResult, GetStateRootHashResult},
+        info::{
+            GetDeployResult, GetDeployStatusResult, GetValidatorChangesResult,
+            GetValidatorPerformanceResult,
+        },
+        state::{
+            GetAllBalancesResult, GetBalanceResult, GetBalancesResult, GetEntryPointResult,
+            GetItemResult,
+        },
+    },
+    types::{Deploy, GetStatusResult},
+};
+use casper_types::{UIntParseError, U512};
+
+use crate::{
+    deploy::DeployExt,
+    error::{Error, Result},
+    parsing,
+    rpc::{self, RpcCall},
+    DeployStrParams, ListDeploysResult, NodeVersionResult, PaymentStrParams, SessionStrParams,
+};
+
+/// A handle for making JSON-RPC requests to a single node without blocking.
+///
+/// Unlike the free functions of this crate, each request returns the node's typed response.  The
+/// handle is cheap to clone, and clones share the underlying HTTP client's connection pool.
+///
+/// Retrieved deploys and blocks are always verified against their hashes and the request, as
+/// described for [`get_deploy()`](fn.get_deploy.html) and [`get_block()`](fn.get_block.html).
+#[derive(Clone, Debug)]
+pub struct NodeClient {
+    node_address: String,
+    verbosity_level: u64,
+    http_client: Client,
+}
+
+impl NodeClient {
+    /// Constructs a new `NodeClient` for the node whose HTTP server is at `node_address`, e.g.
+    /// `"http://127.0.0.1:7777"`, using a default HTTP client.
+    pub fn new(node_address: &str) -> Self {
+        NodeClient::with_http_client(node_address, Client::new())
+    }
+
+    /// Constructs a new `NodeClient` which sends its requests via the given HTTP client.
+    ///
+    /// This allows the caller to control e.g. the request timeouts and connection pooling.
+    pub fn with_http_client(node_address: &str, http_client: Client) -> Self {
+        NodeClient {
+            node_address: node_address.to_string(),
+            verbosity_level: 0,
+            http_client,
+        }
+    }
+
+    /// Sets the level at which requests are printed to `stdout`.
+    ///
+    /// See [`put_deploy()`](fn.put_deploy.html) for details.  Defaults to `0`, i.e. requests
+    /// are not printed.
+    pub fn with_verbosity_level(mut self, verbosity_level: u64) -> Self {
+        self.verbosity_level = verbosity_level;
+        self
+    }
+
+    /// Creates a `Deploy` and sends it to the network for execution.
+    ///
+    /// See [`put_deploy()`](fn.put_deploy.html) for details of the arguments.
+    pub async fn put_deploy(
+        &self,
+        maybe_rpc_id: &str,
+        deploy_params: DeployStrParams<'_>,
+        session_params: SessionStrParams<'_>,
+        payment_params: PaymentStrParams<'_>,
+    ) -> Result<PutDeployResult> {
+        let skip_arg_validation = session_params.skip_arg_validation;
+        let session = session_params.try_into()?;
+        if !skip_arg_validation {
+            self.rpc_call(maybe_rpc_id)
+                .validate_session_args(&session)
+                .await?;
+        }
+        let deploy = Deploy::with_payment_and_session(
+            deploy_params.try_into()?,
+            payment_params.try_into()?,
+            session,
+        )?;
+        self.send_deploy(maybe_rpc_id, deploy).await
+    }
+
+    /// Sends an already-constructed `Deploy` to the network for execution.
+    pub async fn send_deploy(&self, maybe_rpc_id: &str, deploy: Deploy) -> Result<PutDeployResult> {
+        let response = self.rpc_call(maybe_rpc_id).put_deploy(deploy).await?;
+        rpc::parse_result(response)
+    }
+
+    /// Transfers funds between purses.
+    ///
+    /// See [`transfer()`](fn.transfer.html) for details of the arguments.
+    pub async fn transfer(
+        &self,
+        maybe_rpc_id: &str,
+        amount: &str,
+        target_account: &str,
+        transfer_id: &str,
+        deploy_params: DeployStrParams<'_>,
+        payment_params: PaymentStrParams<'_>,
+    ) -> Result<PutDeployResult> {
+        let amount = U512::from_dec_str(amount)
+            .map_err(|err| Error::FailedToParseUint("amount", UIntParseError::FromDecStr(err)))?;
+        let target = parsing::get_transfer_target(target_account)?;
+        let transfer_id = parsing::transfer_id(transfer_id)?;
+
+        let response = self
+            .rpc_call(maybe_rpc_id)
+            .transfer(
+                amount,
+                None,
+                target,
+                transfer_id,
+                deploy_params.try_into()?,
+                payment_params.try_into()?,
+            )
+            .await?;
+        rpc::parse_result(response)
+    }
+
+    /// Retrieves a `Deploy` from the network.
+    ///
+    /// See [`get_deploy()`](fn.get_deploy.html) for details of the arguments.
+    pub async fn get_deploy(
+        &self,
+        maybe_rpc_id: &str,
+        deploy_hash: &str,
+    ) -> Result<GetDeployResult> {
+        let response = self
+            .rpc_call(maybe_rpc_id)
+            .get_deploy(deploy_hash, true)
+            .await?;
+        rpc::parse_result(response)
+    }
+
+    /// Retrieves the lifecycle stage a `Deploy` has reached on the node.
+    ///
+    /// See [`get_deploy_status()`](fn.get_deploy_status.html) for details of the arguments.
+    pub async fn get_deploy_status(
+        &self,
+        maybe_rpc_id: &str,
+        deploy_hash: &str,
+    ) -> Result<GetDeployStatusResult> {
+        let response = self
+            .rpc_call(maybe_rpc_id)
+            .get_deploy_status(deploy_hash)
+            .await?;
+        rpc::parse_result(response)
+    }
+
+    /// Retrieves a `Block` from the network.
+    ///
+    /// See [`get_block()`](fn.get_block.html) for details of the arguments.
+    pub async fn get_block(
+        &self,
+        maybe_rpc_id: &str,
+        maybe_block_id: &str,
+    ) -> Result<GetBlockResult> {
+        let response = self
+            .rpc_call(maybe_rpc_id)
+            .get_block(maybe_block_id, true)
+            .await?;
+        rpc::parse_result(response)
+    }
+
+    /// Retrieves the hashes of all `Deploy`s in a `Block` from the network.
+    ///
+    /// See [`list_deploys()`](fn.list_deploys.html) for details of the arguments.
+    pub async fn list_deploys(
+        &self,
+        maybe_rpc_id: &str,
+        maybe_block_id: &str,
+    ) -> Result<ListDeploysResult> {
+        let get_block_result = self.get_block(maybe_rpc_id, maybe_block_id).await?;
+        Ok(ListDeploysResult::from(get_block_result))
+    }
+
+    /// Retrieves a state root hash at a given `Block`.
+    ///
+    /// See [`get_state_root_hash()`](fn.get_state_root_hash.html) for details of the arguments.
+    pub async fn get_state_root_hash(
+        &self,
+        maybe_rpc_id: &str,
+        maybe_block_id: &str,
+    ) -> Result<GetStateRootHashResult> {
+        let response = self
+            .rpc_call(maybe_rpc_id)
+            .get_state_root_hash(maybe_block_id)
+            .await?;
+        rpc::parse_result(response)
+    }
+
+    /// Retrieves a stored value from the network.
+    ///
+    /// See [`get_item()`](fn.get_item.html) for details of the arguments.
+    pub async fn get_item(
+        &self,
+        maybe_rpc_id: &str,
+        state_root_hash: &str,
+        key: &str,
+        path: &str,
+        return_raw_bytes: bool,
+    ) -> Result<GetItemResult> {
+        let response = self
+            .rpc_call(maybe_rpc_id)
+            .get_item(state_root_hash, key, path, return_raw_bytes)
+            .await?;
+        rpc::parse_result(response)
+    }
+
+    /// Retrieves the signature of a stored contract's entry point from the network.
+    ///
+    /// See [`get_entry_point()`](fn.get_entry_point.html) for details of the arguments.
+    pub async fn get_entry_point(
+        &self,
+        maybe_rpc_id: &str,
+        maybe_state_root_hash: &str,
+        contract_hash: &str,
+        entry_point: &str,
+    ) -> Result<GetEntryPointResult> {
+        let response = self
+            .rpc_call(maybe_rpc_id)
+            .get_entry_point(maybe_state_root_hash, contract_hash, entry_point)
+            .await?;
+        rpc::parse_result(response)
+    }
+
+    /// Retrieves a purse's balance from the network.
+    ///
+    /// See [`get_balance()`](fn.get_balance.html) for details of the arguments.
+    pub async fn get_balance(
+        &self,
+        maybe_rpc_id: &str,
+        state_root_hash: &str,
+        purse: &str,
+    ) -> Result<GetBalanceResult> {
+        let response = self
+            .rpc_call(maybe_rpc_id)
+            .get_balance(state_root_hash, purse)
+            .await?;
+        rpc::parse_result(response)
+    }
+
+    /// Retrieves the balances of several purses from a single global state.
+    ///
+    /// See [`get_balances()`](fn.get_balances.html) for details of the arguments.
+    pub async fn get_balances(
+        &self,
+        maybe_rpc_id: &str,
+        state_root_hash: &str,
+        purses: &[&str],
+    ) -> Result<GetBalancesResult> {
+        let response = self
+            .rpc_call(maybe_rpc_id)
+            .get_balances(state_root_hash, purses)
+            .await?;
+        rpc::parse_result(response)
+    }
+
+    /// Retrieves a page of the balances of all accounts' main purses from a single global state.
+    ///
+    /// See [`get_all_balances()`](fn.get_all_balances.html) for details of the arguments.
+    pub async fn get_all_balances(
+        &self,
+        maybe_rpc_id: &str,
+        state_root_hash: &str,
+        page_token: &str,
+    ) -> Result<GetAllBalancesResult> {
+        let response = self
+            .rpc_call(maybe_rpc_id)
+            .get_all_balances(state_root_hash, page_token)
+            .await?;
+        rpc::parse_result(response)
+    }
+
+    /// Retrieves the performance of validators in one or more recent eras.
+    ///
+    /// See [`get_validator_performance()`](fn.get_validator_performance.html) for details of the
+    /// arguments.
+    pub async fn get_validator_performance(
+        &self,
+        maybe_rpc_id: &str,
+        era_id: &str,
+        maybe_to_era_id: &str,
+        maybe_public_key: &str,
+    ) -> Result<GetValidatorPerformanceResult> {
+        let response = self
+            .rpc_call(maybe_rpc_id)
+            .get_validator_performance(era_id, maybe_to_era_id, maybe_public_key)
+            .await?;
+        rpc::parse_result(response)
+    }
+
+    /// Retrieves the changes to the validator set taking effect in one or more eras.
+    ///
+    /// See [`get_validator_changes()`](fn.get_validator_changes.html) for details of the
+    /// arguments.
+    pub async fn get_validator_changes(
+        &self,
+        maybe_rpc_id: &str,
+        era_id: &str,
+        maybe_to_era_id: &str,
+    ) -> Result<GetValidatorChangesResult> {
+        let response = self
+            .rpc_call(maybe_rpc_id)
+            .get_validator_changes(era_id, maybe_to_era_id)
+            .await?;
+        rpc::parse_result(response)
+    }
+
+    /// Retrieves the status of the node, including the progress of the current era.
+    ///
+    /// See [`get_status()`](fn.get_status.html) for details of the arguments.
+    pub async fn get_status(&self, maybe_rpc_id: &str) -> Result<GetStatusResult> {
+        let response = self.rpc_call(maybe_rpc_id).get_status().await?;
+        rpc::parse_result(response)
+    }
+
+    /// Retrieves the build information, protocol version and chainspec hash of the node.
+    ///
+    /// See [`get_node_version()`](fn.get_node_version.html) for details of the arguments.
+    pub async fn get_node_version(&self, maybe_rpc_id: &str) -> Result<NodeVersionResult> {
+        let get_status_result = self.get_status(maybe_rpc_id).await?;
+        Ok(NodeVersionResult::from(get_status_result))
+    }
+
+    fn rpc_call(&self, maybe_rpc_id: &str) -> RpcCall {
+        RpcCall::new(maybe_rpc_id, &self.node_address, self.verbosity_level)
+            .with_http_client(self.http_client.clone())
+    }
+}
@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+
+use casper_node::{build_info::BuildInfo, crypto::hash::Digest, types::GetStatusResult};
+use casper_types::ProtocolVersion;
+
+/// The version information of a node, taken from its "info_get_status" RPC response.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NodeVersionResult {
+    /// The RPC API version.
+    pub api_version: ProtocolVersion,
+    /// The compiled node version.
+    pub build_version: String,
+    /// Information about how the node was built.
+    pub build: BuildInfo,
+    /// The chainspec name.
+    pub chainspec_name: String,
+    /// The protocol version of the chainspec.
+    pub protocol_version: ProtocolVersion,
+    /// The hash of the chainspec.
+    pub chainspec_hash: Digest,
+}
+
+impl From<GetStatusResult> for NodeVersionResult {
+    fn from(get_status_result: GetStatusResult) -> Self {
+        NodeVersionResult {
+            api_version: get_status_result.api_version,
+            build_version: get_status_result.build_version,
+            build: get_status_result.build,
+            chainspec_name: get_status_result.chainspec_name,
+            protocol_version: get_status_result.protocol_version,
+            chainspec_hash: get_status_result.chainspec_hash,
+        }
+    }
+}
@@ -41,9 +41,16 @@ fn ttl(value: &str) -> Result<TimeDiff> {
 }
 
 fn gas_price(value: &str) -> Result<u64> {
-    value
+    let gas_price = value
         .parse::<u64>()
-        .map_err(|error| Error::FailedToParseInt("gas_price", error))
+        .map_err(|error| Error::FailedToParseInt("gas_price", error))?;
+    if gas_price == 0 {
+        return Err(Error::InvalidArgument(
+            "gas_price",
+            "must be greater than zero".to_string(),
+        ));
+    }
+    Ok(gas_price)
 }
 
 fn dependencies(values: &[&str]) -> Result<Vec<DeployHash>> {
@@ -51,7 +58,7 @@ fn dependencies(values: &[&str]) -> Result<Vec<DeployHash>> {
     for value in values {
         let digest = Digest::from_hex(value).map_err(|error| Error::CryptoError {
             context: "dependencies",
-            error,
+            error: error.into(),
         })?;
         hashes.push(DeployHash::new(digest))
     }
@@ -209,18 +216,134 @@ mod args_complex {
     }
 }
 
+/// Handles retrieval of session and payment args given as a JSON document. These are read in from
+/// a file.
+mod args_json {
+    use serde_json::Value;
+
+    use super::*;
+
+    /// A single named arg, with its `CLType` given in the same form as used by the JSON schema
+    /// for `CLType`.
+    #[derive(Debug, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct JsonArg {
+        name: String,
+        #[serde(rename = "type")]
+        cl_type: CLType,
+        value: Value,
+    }
+
+    pub mod session {
+        use super::*;
+
+        pub fn parse(path: &str) -> Result<Option<RuntimeArgs>> {
+            if path.is_empty() {
+                return Ok(None);
+            }
+            get("session_args_json", path).map(Some)
+        }
+    }
+
+    pub mod payment {
+        use super::*;
+
+        pub fn parse(path: &str) -> Result<Option<RuntimeArgs>> {
+            if path.is_empty() {
+                return Ok(None);
+            }
+            get("payment_args_json", path).map(Some)
+        }
+    }
+
+    fn get(context: &'static str, path: &str) -> Result<RuntimeArgs> {
+        let bytes = fs::read(path).map_err(|error| Error::IoError {
+            context: format!("error reading JSON args file at '{}'", path),
+            error,
+        })?;
+        let args: Vec<JsonArg> = serde_json::from_slice(&bytes).map_err(|error| {
+            Error::InvalidArgument(
+                context,
+                format!("invalid JSON args in '{}': {}", path, error),
+            )
+        })?;
+        let mut runtime_args = RuntimeArgs::new();
+        for JsonArg {
+            name,
+            cl_type,
+            value,
+        } in args
+        {
+            if runtime_args.get(&name).is_some() {
+                return Err(Error::InvalidJsonArg {
+                    name,
+                    details: "arg is specified more than once".to_string(),
+                });
+            }
+            let cl_value =
+                CLValue::from_json_lossless(cl_type.clone(), &value).ok_or_else(|| {
+                    Error::InvalidJsonArg {
+                        name: name.clone(),
+                        details: format!("{} is not a valid value of type {:?}", value, cl_type),
+                    }
+                })?;
+            runtime_args.insert_cl_value(name, cl_value);
+        }
+        Ok(runtime_args)
+    }
+}
+
 const STANDARD_PAYMENT_ARG_NAME: &str = "amount";
 fn standard_payment(value: &str) -> Result<RuntimeArgs> {
     if value.is_empty() {
         return Err(Error::InvalidCLValue(value.to_string()));
     }
-    let arg = U512::from_dec_str(value)
-        .map_err(|err| Error::FailedToParseUint("amount", UIntParseError::FromDecStr(err)))?;
+    let arg = motes("payment_amount", value)?;
     let mut runtime_args = RuntimeArgs::new();
     runtime_args.insert(STANDARD_PAYMENT_ARG_NAME, arg)?;
     Ok(runtime_args)
 }
 
+const CSPR_SUFFIX: &str = "cspr";
+const MOTES_SUFFIX: &str = "motes";
+/// The number of decimal places of CSPR which can be represented exactly in motes.
+const CSPR_DECIMAL_PLACES: usize = 9;
+
+/// Parses an amount of motes, given either as a number of motes optionally suffixed with "motes",
+/// or as a number of CSPR suffixed with "cspr", e.g. "2.5cspr".
+///
+/// Amounts which don't convert to a whole number of motes are rejected.
+fn motes(arg_name: &'static str, value: &str) -> Result<U512> {
+    let lowercase_value = value.trim().to_lowercase();
+    let (amount, decimal_places) = match lowercase_value.strip_suffix(CSPR_SUFFIX) {
+        Some(amount) => (amount, CSPR_DECIMAL_PLACES),
+        None => (
+            lowercase_value
+                .strip_suffix(MOTES_SUFFIX)
+                .unwrap_or(&lowercase_value),
+            0,
+        ),
+    };
+    let amount = amount.trim_end();
+    let (whole, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+    if whole.is_empty() || !fraction.chars().all(|c| c.is_ascii_digit()) {
+        return Err(Error::InvalidArgument(
+            arg_name,
+            format!("'{}' is not a valid amount", value),
+        ));
+    }
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.len() > decimal_places {
+        return Err(Error::InvalidArgument(
+            arg_name,
+            format!("'{}' is not a whole number of motes", value),
+        ));
+    }
+    let motes = format!("{}{:0<width$}", whole, fraction, width = decimal_places);
+    U512::from_dec_str(&motes)
+        .map_err(|error| Error::FailedToParseUint(arg_name, UIntParseError::FromDecStr(error)))
+}
+
 pub(crate) fn secret_key(value: &str) -> Result<SecretKey> {
     let path = PathBuf::from(value);
     SecretKey::from_file(path).map_err(|error| Error::CryptoError {
@@ -229,18 +352,38 @@ pub(crate) fn secret_key(value: &str) -> Result<SecretKey> {
     })
 }
 
-fn args_from_simple_or_complex(
+fn args_from_simple_complex_or_json(
     simple: Option<RuntimeArgs>,
     complex: Option<RuntimeArgs>,
+    json: Option<RuntimeArgs>,
 ) -> RuntimeArgs {
-    // We can have exactly zero or one of the two as `Some`.
-    match (simple, complex) {
-        (Some(args), None) | (None, Some(args)) => args,
-        (None, None) => RuntimeArgs::new(),
-        (Some(_), Some(_)) => unreachable!("should not have both simple and complex args"),
+    // We can have exactly zero or one of the three as `Some`.
+    match (simple, complex, json) {
+        (Some(args), None, None) | (None, Some(args), None) | (None, None, Some(args)) => args,
+        (None, None, None) => RuntimeArgs::new(),
+        _ => unreachable!("should not have more than one of simple, complex and JSON args"),
     }
 }
 
+/// Returns an error if more than one of the given styles of args is provided.
+fn check_at_most_one_args_style(
+    context: &'static str,
+    styles: &[(&'static str, bool)],
+) -> Result<()> {
+    let provided = styles
+        .iter()
+        .filter(|(_, is_empty)| !*is_empty)
+        .map(|(name, _)| name.to_string())
+        .collect::<Vec<_>>();
+    if provided.len() > 1 {
+        return Err(Error::ConflictingArguments {
+            context,
+            args: provided,
+        });
+    }
+    Ok(())
+}
+
 /// Private macro for enforcing parameter validity.
 /// e.g. check_exactly_one_not_empty!(
 ///   (field1) requires[another_field],
@@ -366,6 +509,7 @@ pub(super) fn parse_session_info(
     session_path: &str,
     session_args: &[&str],
     session_args_complex: &str,
+    session_args_json: &str,
     session_version: &str,
     session_entry_point: &str,
     session_transfer: bool,
@@ -388,16 +532,19 @@ pub(super) fn parse_session_info(
         (is_session_transfer)
             requires[] requires_empty[session_entry_point, session_version]
     );
-    if !session_args.is_empty() && !session_args_complex.is_empty() {
-        return Err(Error::ConflictingArguments {
-            context: "parse_session_info",
-            args: vec!["session_args".to_owned(), "session_args_complex".to_owned()],
-        });
-    }
-
-    let session_args = args_from_simple_or_complex(
+    check_at_most_one_args_style(
+        "parse_session_info",
+        &[
+            ("session_args", session_args.is_empty()),
+            ("session_args_complex", session_args_complex.is_empty()),
+            ("session_args_json", session_args_json.is_empty()),
+        ],
+    )?;
+
+    let session_args = args_from_simple_complex_or_json(
         arg_simple::session::parse(session_args)?,
         args_complex::session::parse(session_args_complex).ok(),
+        args_json::session::parse(session_args_json)?,
     );
     if session_transfer {
         if session_args.is_empty() {
@@ -465,6 +612,7 @@ pub(super) fn parse_payment_info(
     payment_path: &str,
     payment_args: &[&str],
     payment_args_complex: &str,
+    payment_args_json: &str,
     payment_version: &str,
     payment_entry_point: &str,
 ) -> Result<ExecutableDeployItem> {
@@ -482,26 +630,36 @@ pub(super) fn parse_payment_info(
             requires[payment_entry_point] requires_empty[],
         (payment_path) requires[] requires_empty[payment_entry_point, payment_version],
     );
-    if !payment_args.is_empty() && !payment_args_complex.is_empty() {
-        return Err(Error::ConflictingArguments {
-            context: "parse_payment_info",
-            args: vec!["payment_args".to_owned(), "payment_args_complex".to_owned()],
-        });
-    }
-
-    if let Ok(payment_args) = standard_payment(payment_amount) {
+    let payment_args_styles = [
+        ("payment_args", payment_args.is_empty()),
+        ("payment_args_complex", payment_args_complex.is_empty()),
+        ("payment_args_json", payment_args_json.is_empty()),
+    ];
+    check_at_most_one_args_style("parse_payment_info", &payment_args_styles)?;
+
+    if !payment_amount.is_empty() {
+        // The standard-payment args are generated from the amount, so can't also be given.
+        if let Some((payment_args_name, _)) =
+            payment_args_styles.iter().find(|(_, is_empty)| !*is_empty)
+        {
+            return Err(Error::ConflictingArguments {
+                context: "parse_payment_info",
+                args: vec!["payment_amount".to_owned(), payment_args_name.to_string()],
+            });
+        }
         return Ok(ExecutableDeployItem::ModuleBytes {
             module_bytes: vec![].into(),
-            args: payment_args,
+            args: standard_payment(payment_amount)?,
         });
     }
 
     let invalid_entry_point =
         || Error::InvalidArgument("payment_entry_point", payment_entry_point.to_string());
 
-    let payment_args = args_from_simple_or_complex(
+    let payment_args = args_from_simple_complex_or_json(
         arg_simple::payment::parse(payment_args)?,
         args_complex::payment::parse(payment_args_complex).ok(),
+        args_json::payment::parse(payment_args_json)?,
     );
 
     if let Some(payment_name) = name(payment_name) {
@@ -560,7 +718,7 @@ pub(crate) fn get_transfer_target(target_account: &str) -> Result<TransferTarget
     Ok(TransferTarget::Account(account))
 }
 
-fn parse_contract_hash(value: &str) -> Result<Option<HashAddr>> {
+pub(crate) fn parse_contract_hash(value: &str) -> Result<Option<HashAddr>> {
     if value.is_empty() {
         return Ok(None);
     }
@@ -593,9 +751,83 @@ pub(crate) fn transfer_id(value: &str) -> Result<u64> {
         .map_err(|error| Error::FailedToParseInt("transfer-id", error))
 }
 
+/// A single transfer in a JSON transfer batch file.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BatchedTransfer {
+    target: String,
+    amount: String,
+    #[serde(default)]
+    id: Option<u64>,
+}
+
+/// Parses the transfers in the batch file at `path`.
+///
+/// The file holds either a JSON array of objects with a hex-encoded public key `target`, a decimal
+/// string `amount` and an optional integer `id`, or CSV lines of `target,amount,id` where the id
+/// may be left empty.  A CSV header line starting with `target` is skipped.
+pub(crate) fn transfer_batch(path: &str) -> Result<Vec<(TransferTarget, U512, Option<u64>)>> {
+    let contents = fs::read_to_string(path).map_err(|error| Error::IoError {
+        context: format!("error reading transfer batch file at '{}'", path),
+        error,
+    })?;
+
+    let entries: Vec<(String, String, Option<u64>)> = if contents.trim_start().starts_with('[') {
+        let transfers: Vec<BatchedTransfer> = serde_json::from_str(&contents).map_err(|error| {
+            Error::InvalidArgument(
+                "batch_file",
+                format!("invalid JSON transfers in '{}': {}", path, error),
+            )
+        })?;
+        transfers
+            .into_iter()
+            .map(|transfer| (transfer.target, transfer.amount, transfer.id))
+            .collect()
+    } else {
+        let mut entries = vec![];
+        for line in contents.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with("target") {
+                continue;
+            }
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            let (target, amount, id) = match fields.as_slice() {
+                [target, amount] => (target, amount, ""),
+                [target, amount, id] => (target, amount, *id),
+                _ => {
+                    return Err(Error::InvalidArgument(
+                        "batch_file",
+                        format!("expected 'target,amount,id' but got '{}'", line),
+                    ))
+                }
+            };
+            let id = none_if_empty(id).map(transfer_id).transpose()?;
+            entries.push((target.to_string(), amount.to_string(), id));
+        }
+        entries
+    };
+
+    if entries.is_empty() {
+        return Err(Error::InvalidArgument(
+            "batch_file",
+            format!("no transfers in '{}'", path),
+        ));
+    }
+
+    entries
+        .into_iter()
+        .map(|(target, amount, id)| {
+            let target = get_transfer_target(&target)?;
+            let amount = U512::from_dec_str(&amount).map_err(|err| {
+                Error::FailedToParseUint("amount", UIntParseError::FromDecStr(err))
+            })?;
+            Ok((target, amount, id))
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
-    use std::{convert::TryFrom, result::Result as StdResult};
+    use std::{convert::TryFrom, io::Write, result::Result as StdResult};
 
     use casper_types::{
         account::AccountHash, bytesrepr::ToBytes, AccessRights, CLTyped, CLValue, NamedArg,
@@ -879,6 +1111,7 @@ mod tests {
                 &["something:u32='0'"],
                 "path_to/file",
                 "",
+                "",
                 "entrypoint",
                 false
             )
@@ -901,6 +1134,7 @@ mod tests {
                 &["something:u32='0'"],
                 "path_to/file",
                 "",
+                "",
                 "entrypoint",
             )
             .map(|_| ())
@@ -926,6 +1160,7 @@ mod tests {
                 "",
                 "",
                 "",
+                "",
                 false
             )
             .map(|_| ())
@@ -947,7 +1182,7 @@ mod tests {
     #[test]
     fn should_fail_to_parse_conflicting_payment_parameters() {
         assert_eq!(
-            parse_payment_info("12345", happy::HASH, happy::NAME, happy::PACKAGE_HASH, happy::PACKAGE_NAME, happy::PATH, &[], "", "", "",)
+            parse_payment_info("12345", happy::HASH, happy::NAME, happy::PACKAGE_HASH, happy::PACKAGE_NAME, happy::PATH, &[], "", "", "", "",)
                 .map(|_| ())
                 .map_err(ErrWrapper),
             Err(Error::ConflictingArguments {
@@ -965,6 +1200,182 @@ mod tests {
         );
     }
 
+    #[test]
+    fn should_parse_payment_amount_in_motes_or_cspr() {
+        let parse = |value: &str| motes("payment_amount", value).map_err(ErrWrapper);
+        let two_and_a_half_cspr = U512::from(2_500_000_000u64);
+        assert_eq!(parse("2500000000"), Ok(two_and_a_half_cspr));
+        assert_eq!(parse("2500000000motes"), Ok(two_and_a_half_cspr));
+        assert_eq!(parse("2.5cspr"), Ok(two_and_a_half_cspr));
+        assert_eq!(parse("2.5 CSPR"), Ok(two_and_a_half_cspr));
+        assert_eq!(parse("2.500000000000cspr"), Ok(two_and_a_half_cspr));
+        assert_eq!(parse("3cspr"), Ok(U512::from(3_000_000_000u64)));
+        assert_eq!(parse("0.000000001cspr"), Ok(U512::one()));
+        assert_eq!(parse("2.0"), Ok(U512::from(2)));
+    }
+
+    #[test]
+    fn should_fail_to_parse_fractional_motes() {
+        for value in &["0.0000000001cspr", "2.5", "2.5motes"] {
+            assert_eq!(
+                motes("payment_amount", value).map_err(ErrWrapper),
+                Err(Error::InvalidArgument(
+                    "payment_amount",
+                    format!("'{}' is not a whole number of motes", value)
+                )
+                .into())
+            );
+        }
+        for value in &["", "cspr", ".5cspr", "1.-5cspr", "1.5e3cspr", "-1", "one"] {
+            assert!(
+                motes("payment_amount", value).is_err(),
+                "{} should be an error",
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn should_fail_to_parse_payment_amount_with_payment_args() {
+        let payment_params = PaymentStrParams {
+            payment_amount: "2.5cspr",
+            payment_args_simple: vec!["amount:u512='1'"],
+            ..Default::default()
+        };
+        let result: StdResult<ExecutableDeployItem, Error> = payment_params.try_into();
+        assert_eq!(
+            result.map_err(ErrWrapper),
+            Err(Error::ConflictingArguments {
+                context: "parse_payment_info",
+                args: vec!["payment_amount".into(), "payment_args".into()],
+            }
+            .into())
+        );
+    }
+
+    #[test]
+    fn should_parse_transfer_batch_file() {
+        let target = "01522ef6c89038019cb7af05c340623804392dd2bb1f4dab5e4a9c3ab752fc0179";
+        let account_hash = PublicKey::from_hex(target).unwrap().to_account_hash();
+        let parse = |contents: &str| {
+            let file = json_args_file(contents);
+            transfer_batch(file.path().to_str().unwrap()).map(|transfers| {
+                transfers
+                    .into_iter()
+                    .map(|(TransferTarget::Account(public_key), amount, id)| {
+                        (public_key.to_account_hash(), amount, id)
+                    })
+                    .collect::<Vec<_>>()
+            })
+        };
+        let expected = vec![
+            (account_hash, U512::from(10), Some(1)),
+            (account_hash, U512::from(20), None),
+        ];
+
+        let csv = format!("target,amount,id\n{},10,1\n\n{}, 20,\n", target, target);
+        assert_eq!(parse(&csv).unwrap(), expected);
+
+        let json = format!(
+            r#"[{{"target": "{}", "amount": "10", "id": 1}}, {{"target": "{}", "amount": "20"}}]"#,
+            target, target
+        );
+        assert_eq!(parse(&json).unwrap(), expected);
+
+        assert!(parse("").is_err());
+        assert!(parse(&format!("{},10,1,2", target)).is_err());
+        assert!(parse(&format!("{},ten,1", target)).is_err());
+    }
+
+    fn json_args_file(contents: &str) -> tempfile::NamedTempFile {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(contents.as_bytes()).unwrap();
+        file
+    }
+
+    #[test]
+    fn should_fail_to_parse_json_args_with_other_args_styles() {
+        let file = json_args_file(r#"[{"name": "a", "type": "U8", "value": 1}]"#);
+        let path = file.path().to_str().unwrap();
+
+        let session_params = SessionStrParams {
+            session_name: happy::NAME,
+            session_entry_point: happy::ENTRY_POINT,
+            session_args_simple: vec!["something:u32='0'"],
+            session_args_json: path,
+            ..Default::default()
+        };
+        let result: StdResult<ExecutableDeployItem, Error> = session_params.try_into();
+        assert_eq!(
+            result.map_err(ErrWrapper),
+            Err(Error::ConflictingArguments {
+                context: "parse_session_info",
+                args: vec!["session_args".into(), "session_args_json".into()],
+            }
+            .into())
+        );
+
+        let payment_params = PaymentStrParams {
+            payment_amount: "2.5cspr",
+            payment_args_json: path,
+            ..Default::default()
+        };
+        let result: StdResult<ExecutableDeployItem, Error> = payment_params.try_into();
+        assert_eq!(
+            result.map_err(ErrWrapper),
+            Err(Error::ConflictingArguments {
+                context: "parse_payment_info",
+                args: vec!["payment_amount".into(), "payment_args_json".into()],
+            }
+            .into())
+        );
+    }
+
+    #[test]
+    fn should_name_offending_json_arg() {
+        let parse = |contents: &str| {
+            let file = json_args_file(contents);
+            let session_params = SessionStrParams {
+                session_name: happy::NAME,
+                session_entry_point: happy::ENTRY_POINT,
+                session_args_json: file.path().to_str().unwrap(),
+                ..Default::default()
+            };
+            let result: StdResult<ExecutableDeployItem, Error> = session_params.try_into();
+            result.map(|_| ())
+        };
+
+        assert!(parse(r#"[{"name": "a", "type": {"List": "U8"}, "value": [1, 2]}]"#).is_ok());
+
+        match parse(
+            r#"[
+                {"name": "a", "type": "U8", "value": 1},
+                {"name": "b", "type": {"List": "U8"}, "value": [1, 256]}
+            ]"#,
+        ) {
+            Err(Error::InvalidJsonArg { name, details }) => {
+                assert_eq!(name, "b");
+                assert!(details.contains("List(U8)"), "{}", details);
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        match parse(
+            r#"[
+                {"name": "a", "type": "U8", "value": 1},
+                {"name": "a", "type": "U8", "value": 2}
+            ]"#,
+        ) {
+            Err(Error::InvalidJsonArg { name, .. }) => assert_eq!(name, "a"),
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        match parse(r#"[{"name": "a", "type": "NotAType", "value": 1}]"#) {
+            Err(Error::InvalidArgument("session_args_json", _)) => (),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
     mod missing_args {
 
         use super::*;
@@ -0,0 +1,171 @@
+use serde_json::{Map, Value};
+
+use casper_types::CLValue;
+
+const CL_TYPE_FIELD: &str = "cl_type";
+const BYTES_FIELD: &str = "bytes";
+const PARSED_FIELD: &str = "parsed";
+
+/// Returns a copy of `value` in which the `parsed` field of every JSON-encoded `CLValue` has been
+/// replaced by the output of [`CLValue::to_json_lossless`].
+///
+/// This means values which the node couldn't represent in parsed form (given as `null`) are instead
+/// shown as a tagged hex blob, while all other parts of `value` are left unchanged.
+pub fn render_cl_values(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            if let Some(cl_value) = as_cl_value(map) {
+                let mut rendered = map.clone();
+                let _ = rendered.insert(PARSED_FIELD.to_string(), cl_value.to_json_lossless());
+                return Value::Object(rendered);
+            }
+            Value::Object(
+                map.iter()
+                    .map(|(key, value)| (key.clone(), render_cl_values(value)))
+                    .collect(),
+            )
+        }
+        Value::Array(values) => Value::Array(values.iter().map(render_cl_values).collect()),
+        _ => value.clone(),
+    }
+}
+
+fn as_cl_value(map: &Map<String, Value>) -> Option<CLValue> {
+    if !map.contains_key(CL_TYPE_FIELD) || !map.contains_key(BYTES_FIELD) {
+        return None;
+    }
+    serde_json::from_value(Value::Object(map.clone())).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde_json::json;
+
+    use super::*;
+
+    fn check_rendering(response: Value, expected: &str) {
+        let rendered = render_cl_values(&response);
+        let expected: Value = serde_json::from_str(expected).unwrap();
+        assert_eq!(rendered, expected);
+    }
+
+    #[test]
+    fn should_render_stored_account_unchanged() {
+        let response = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": {
+                "api_version": "1.0.0",
+                "stored_value": {
+                    "Account": {
+                        "account_hash": "account-hash-2c4a11c062a8a337bfc97e27fd66291caeb2c65865dcb5d3ef3759c4c97efecb",
+                        "named_keys": [
+                            {
+                                "name": "counter",
+                                "key": "uref-0101010101010101010101010101010101010101010101010101010101010101-007"
+                            }
+                        ],
+                        "main_purse": "uref-0909090909090909090909090909090909090909090909090909090909090909-007",
+                        "associated_keys": [
+                            {
+                                "account_hash": "account-hash-2c4a11c062a8a337bfc97e27fd66291caeb2c65865dcb5d3ef3759c4c97efecb",
+                                "weight": 1
+                            }
+                        ],
+                        "action_thresholds": {
+                            "deployment": 1,
+                            "key_management": 1
+                        }
+                    }
+                },
+                "merkle_proof": "01000000"
+            }
+        });
+        let expected = r#"{
+  "id": 1,
+  "jsonrpc": "2.0",
+  "result": {
+    "api_version": "1.0.0",
+    "merkle_proof": "01000000",
+    "stored_value": {
+      "Account": {
+        "account_hash": "account-hash-2c4a11c062a8a337bfc97e27fd66291caeb2c65865dcb5d3ef3759c4c97efecb",
+        "action_thresholds": {
+          "deployment": 1,
+          "key_management": 1
+        },
+        "associated_keys": [
+          {
+            "account_hash": "account-hash-2c4a11c062a8a337bfc97e27fd66291caeb2c65865dcb5d3ef3759c4c97efecb",
+            "weight": 1
+          }
+        ],
+        "main_purse": "uref-0909090909090909090909090909090909090909090909090909090909090909-007",
+        "named_keys": [
+          {
+            "key": "uref-0101010101010101010101010101010101010101010101010101010101010101-007",
+            "name": "counter"
+          }
+        ]
+      }
+    }
+  }
+}"#;
+        check_rendering(response, expected);
+    }
+
+    #[test]
+    fn should_render_stored_cl_values() {
+        let map_value = serde_json::to_value(
+            CLValue::from_t(
+                vec![(String::from("a"), 1_u64)]
+                    .into_iter()
+                    .collect::<BTreeMap<_, _>>(),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let ambiguous_value = serde_json::to_value(CLValue::from_t(Some(())).unwrap()).unwrap();
+        let response = json!({
+            "stored_values": [
+                { "CLValue": map_value },
+                { "CLValue": ambiguous_value }
+            ]
+        });
+        let expected = r#"{
+  "stored_values": [
+    {
+      "CLValue": {
+        "bytes": "0100000001000000610100000000000000",
+        "cl_type": {
+          "Map": {
+            "key": "String",
+            "value": "U64"
+          }
+        },
+        "parsed": [
+          {
+            "key": "a",
+            "value": 1
+          }
+        ]
+      }
+    },
+    {
+      "CLValue": {
+        "bytes": "01",
+        "cl_type": {
+          "Option": "Unit"
+        },
+        "parsed": {
+          "raw_bytes": "01"
+        }
+      }
+    }
+  ]
+}"#;
+        check_rendering(response, expected);
+    }
+}
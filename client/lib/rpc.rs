@@ -1,10 +1,9 @@
 use std::fs::File;
 
-use futures::executor;
 use jsonrpc_lite::{Id, JsonRpc, Params};
 use rand::Rng;
 use reqwest::Client;
-use serde::Serialize;
+use serde::{de::DeserializeOwned, Serialize};
 use serde_json::{json, Map, Value};
 
 use casper_execution_engine::core::engine_state::ExecutableDeployItem;
@@ -17,20 +16,28 @@ use casper_node::{
             GetEraInfoBySwitchBlock, GetEraInfoParams, GetStateRootHash, GetStateRootHashParams,
         },
         docs::ListRpcs,
-        info::{GetDeploy, GetDeployParams},
+        info::{
+            EraIdentifier, GetDeploy, GetDeployParams, GetDeployStatus, GetDeployStatusParams,
+            GetStatus, GetValidatorChanges, GetValidatorChangesParams, GetValidatorPerformance,
+            GetValidatorPerformanceParams,
+        },
         state::{
-            GetAccountInfo, GetAccountInfoParams, GetAuctionInfo, GetAuctionInfoParams, GetBalance,
-            GetBalanceParams, GetItem, GetItemParams,
+            GetAccountInfo, GetAccountInfoParams, GetAllBalances, GetAllBalancesParams,
+            GetAuctionInfo, GetAuctionInfoParams, GetBalance, GetBalanceParams, GetBalances,
+            GetBalancesParams, GetEntryPoint, GetEntryPointParams, GetEntryPointResult, GetItem,
+            GetItemParams, GlobalStateIdentifier,
         },
         RpcWithOptionalParams, RpcWithParams, RpcWithoutParams, RPC_API_PATH,
     },
     types::{BlockHash, Deploy, DeployHash},
 };
-use casper_types::{AsymmetricType, Key, PublicKey, URef, U512};
+use casper_types::{AsymmetricType, ContractHash, EraId, Key, PublicKey, URef, U512};
 
 use crate::{
     deploy::{DeployExt, DeployParams, SendDeploy, Transfer},
     error::{Error, Result},
+    parsing,
+    runtime_args_builder::RuntimeArgsBuilder,
     validation,
 };
 
@@ -46,6 +53,7 @@ pub(crate) struct RpcCall {
     rpc_id: Id,
     node_address: String,
     verbosity_level: u64,
+    http_client: Option<Client>,
 }
 
 /// `RpcCall` encapsulates calls made to the casper node service via JSON-RPC.
@@ -76,37 +84,65 @@ impl RpcCall {
             rpc_id,
             node_address: node_address.trim_end_matches('/').to_string(),
             verbosity_level,
+            http_client: None,
         }
     }
 
-    pub(crate) fn get_deploy(self, deploy_hash: &str) -> Result<JsonRpc> {
+    /// Sets the HTTP client used to send the request, allowing e.g. its connection pool to be
+    /// shared between calls.  If not set, a default client is constructed for the request.
+    pub(crate) fn with_http_client(mut self, http_client: Client) -> Self {
+        self.http_client = Some(http_client);
+        self
+    }
+
+    pub(crate) async fn get_deploy(self, deploy_hash: &str, verify: bool) -> Result<JsonRpc> {
         let hash = Digest::from_hex(deploy_hash).map_err(|error| Error::CryptoError {
             context: "deploy_hash",
-            error,
+            error: error.into(),
+        })?;
+        let deploy_hash = DeployHash::new(hash);
+        let params = GetDeployParams { deploy_hash };
+        let response = self.request_with_map_params::<GetDeploy, _>(params).await?;
+        if verify {
+            validation::validate_get_deploy_response(&response, &deploy_hash)?;
+        }
+        Ok(response)
+    }
+
+    pub(crate) async fn get_deploy_status(self, deploy_hash: &str) -> Result<JsonRpc> {
+        let hash = Digest::from_hex(deploy_hash).map_err(|error| Error::CryptoError {
+            context: "deploy_hash",
+            error: error.into(),
         })?;
-        let params = GetDeployParams {
+        let params = GetDeployStatusParams {
             deploy_hash: DeployHash::new(hash),
         };
-        GetDeploy::request_with_map_params(self, params)
+        self.request_with_map_params::<GetDeployStatus, _>(params)
+            .await
     }
 
-    pub(crate) fn get_item(self, state_root_hash: &str, key: &str, path: &str) -> Result<JsonRpc> {
+    pub(crate) async fn get_item(
+        self,
+        state_root_hash: &str,
+        key: &str,
+        path: &str,
+        return_raw_bytes: bool,
+    ) -> Result<JsonRpc> {
         let state_root_hash =
             Digest::from_hex(state_root_hash).map_err(|error| Error::CryptoError {
                 context: "state_root_hash",
-                error,
+                error: error.into(),
             })?;
 
-        let key = {
-            if let Ok(key) = Key::from_formatted_str(key) {
-                key
-            } else if let Ok(public_key) = PublicKey::from_hex(key) {
-                Key::Account(public_key.to_account_hash())
-            } else {
-                return Err(Error::FailedToParseKey);
-            }
+        let key = match Key::from_formatted_str(key) {
+            Ok(key) => key,
+            Err(error) => match PublicKey::from_hex(key) {
+                Ok(public_key) => Key::Account(public_key.to_account_hash()),
+                Err(_) => return Err(Error::FailedToParseFormattedKey("key", error)),
+            },
         };
 
+        let path = path.trim_matches('/');
         let path = if path.is_empty() {
             vec![]
         } else {
@@ -117,27 +153,33 @@ impl RpcCall {
             state_root_hash,
             key: key.to_formatted_string(),
             path: path.clone(),
+            return_raw_bytes,
         };
-        let response = GetItem::request_with_map_params(self, params)?;
+        let response = self.request_with_map_params::<GetItem, _>(params).await?;
         validation::validate_query_response(&response, &state_root_hash, &key, &path)?;
         Ok(response)
     }
 
-    pub(crate) fn get_state_root_hash(self, maybe_block_identifier: &str) -> Result<JsonRpc> {
+    pub(crate) async fn get_state_root_hash(self, maybe_block_identifier: &str) -> Result<JsonRpc> {
         match Self::block_identifier(maybe_block_identifier)? {
             Some(block_identifier) => {
                 let params = GetStateRootHashParams { block_identifier };
-                GetStateRootHash::request_with_map_params(self, params)
+                self.request_with_map_params::<GetStateRootHash, _>(params)
+                    .await
             }
-            None => GetStateRootHash::request(self),
+            None => self.request_without_params::<GetStateRootHash>().await,
         }
     }
 
-    pub(crate) fn get_balance(self, state_root_hash: &str, purse_uref: &str) -> Result<JsonRpc> {
+    pub(crate) async fn get_balance(
+        self,
+        state_root_hash: &str,
+        purse_uref: &str,
+    ) -> Result<JsonRpc> {
         let state_root_hash =
             Digest::from_hex(state_root_hash).map_err(|error| Error::CryptoError {
                 context: "state_root_hash",
-                error,
+                error: error.into(),
             })?;
         let uref = URef::from_formatted_str(purse_uref)
             .map_err(|error| Error::FailedToParseURef("purse_uref", error))?;
@@ -147,42 +189,164 @@ impl RpcCall {
             state_root_hash,
             purse_uref: purse_uref.to_string(),
         };
-        let response = GetBalance::request_with_map_params(self, params)?;
+        let response = self
+            .request_with_map_params::<GetBalance, _>(params)
+            .await?;
         validation::validate_get_balance_response(&response, &state_root_hash, &key)?;
         Ok(response)
     }
 
-    pub(crate) fn get_era_info_by_switch_block(
+    pub(crate) async fn get_balances(
+        self,
+        state_root_hash: &str,
+        purses: &[&str],
+    ) -> Result<JsonRpc> {
+        let state_root_hash =
+            Digest::from_hex(state_root_hash).map_err(|error| Error::CryptoError {
+                context: "state_root_hash",
+                error: error.into(),
+            })?;
+
+        let params = GetBalancesParams {
+            state_identifier: GlobalStateIdentifier::StateRootHash(state_root_hash),
+            purse_identifiers: purses.iter().map(|purse| purse.to_string()).collect(),
+        };
+        let response = self
+            .request_with_map_params::<GetBalances, _>(params)
+            .await?;
+        validation::validate_get_balances_response(&response, &state_root_hash, purses)?;
+        Ok(response)
+    }
+
+    pub(crate) async fn get_all_balances(
+        self,
+        state_root_hash: &str,
+        page_token: &str,
+    ) -> Result<JsonRpc> {
+        let state_root_hash =
+            Digest::from_hex(state_root_hash).map_err(|error| Error::CryptoError {
+                context: "state_root_hash",
+                error: error.into(),
+            })?;
+        let page_token = if page_token.is_empty() {
+            None
+        } else {
+            Some(page_token.to_string())
+        };
+
+        let params = GetAllBalancesParams {
+            state_identifier: GlobalStateIdentifier::StateRootHash(state_root_hash),
+            page_token: page_token.clone(),
+        };
+        let response = self
+            .request_with_map_params::<GetAllBalances, _>(params)
+            .await?;
+        validation::validate_get_all_balances_response(
+            &response,
+            &state_root_hash,
+            page_token.as_deref(),
+        )?;
+        Ok(response)
+    }
+
+    pub(crate) async fn get_entry_point(
+        self,
+        maybe_state_root_hash: &str,
+        contract_hash: &str,
+        entry_point: &str,
+    ) -> Result<JsonRpc> {
+        let maybe_state_root_hash = if maybe_state_root_hash.is_empty() {
+            None
+        } else {
+            let state_root_hash =
+                Digest::from_hex(maybe_state_root_hash).map_err(|error| Error::CryptoError {
+                    context: "state_root_hash",
+                    error: error.into(),
+                })?;
+            Some(state_root_hash)
+        };
+        let contract_hash = parsing::parse_contract_hash(contract_hash)?
+            .map(ContractHash::new)
+            .ok_or_else(|| {
+                Error::InvalidArgument("contract_hash", "must not be empty".to_string())
+            })?;
+
+        let params = GetEntryPointParams {
+            state_identifier: maybe_state_root_hash.map(GlobalStateIdentifier::StateRootHash),
+            contract_hash,
+            entry_point: entry_point.to_string(),
+        };
+        let response = self
+            .request_with_map_params::<GetEntryPoint, _>(params)
+            .await?;
+        validation::validate_get_entry_point_response(
+            &response,
+            maybe_state_root_hash.as_ref(),
+            &contract_hash,
+            entry_point,
+        )?;
+        Ok(response)
+    }
+
+    /// Checks the args of `session` against the signature of the entry point it calls, as read
+    /// from the global state of the latest block.  Only a session calling a stored contract by
+    /// hash is checked.
+    pub(crate) async fn validate_session_args(self, session: &ExecutableDeployItem) -> Result<()> {
+        if let ExecutableDeployItem::StoredContractByHash {
+            hash,
+            entry_point,
+            args,
+        } = session
+        {
+            let response = self
+                .get_entry_point("", &hex::encode(hash.value()), entry_point)
+                .await?;
+            let result: GetEntryPointResult = parse_result(response)?;
+            RuntimeArgsBuilder::from(args.clone()).build_for(&result.entry_point)?;
+        }
+        Ok(())
+    }
+
+    pub(crate) async fn get_era_info_by_switch_block(
         self,
         maybe_block_identifier: &str,
     ) -> Result<JsonRpc> {
         let response = match Self::block_identifier(maybe_block_identifier)? {
-            None => GetEraInfoBySwitchBlock::request(self),
+            None => {
+                self.request_without_params::<GetEraInfoBySwitchBlock>()
+                    .await
+            }
             Some(block_identifier) => {
                 let params = GetEraInfoParams { block_identifier };
-                GetEraInfoBySwitchBlock::request_with_map_params(self, params)
+                self.request_with_map_params::<GetEraInfoBySwitchBlock, _>(params)
+                    .await
             }
         }?;
         validation::validate_get_era_info_response(&response)?;
         Ok(response)
     }
 
-    pub(crate) fn get_auction_info(self, maybe_block_identifier: &str) -> Result<JsonRpc> {
+    pub(crate) async fn get_auction_info(self, maybe_block_identifier: &str) -> Result<JsonRpc> {
         let response = match Self::block_identifier(maybe_block_identifier)? {
-            None => GetAuctionInfo::request(self),
+            None => self.request_without_params::<GetAuctionInfo>().await,
             Some(block_identifier) => {
                 let params = GetAuctionInfoParams { block_identifier };
-                GetAuctionInfo::request_with_map_params(self, params)
+                self.request_with_map_params::<GetAuctionInfo, _>(params)
+                    .await
             }
         }?;
         Ok(response)
     }
 
-    pub(crate) fn list_rpcs(self) -> Result<JsonRpc> {
-        ListRpcs::request(self)
+    pub(crate) async fn get_status(self) -> Result<JsonRpc> {
+        self.request_without_params::<GetStatus>().await
+    }
+
+    pub(crate) async fn list_rpcs(self) -> Result<JsonRpc> {
+        self.request_without_params::<ListRpcs>().await
     }
 
-    pub(crate) fn transfer(
+    pub(crate) async fn transfer(
         self,
         amount: U512,
         source_purse: Option<URef>,
@@ -200,50 +364,75 @@ impl RpcCall {
             payment,
         )?;
         let params = PutDeployParams { deploy };
-        Transfer::request_with_map_params(self, params)
+        self.request_with_map_params::<Transfer, _>(params).await
+    }
+
+    pub(crate) async fn transfer_batch(
+        self,
+        transfers: Vec<(TransferTarget, U512, Option<u64>)>,
+        deploy_params: DeployParams,
+        payment: ExecutableDeployItem,
+    ) -> Result<JsonRpc> {
+        let deploy = Deploy::new_batched_transfer(transfers, deploy_params, payment)?;
+        let params = PutDeployParams { deploy };
+        self.request_with_map_params::<Transfer, _>(params).await
     }
 
-    pub(crate) fn send_deploy_file(self, input_path: &str) -> Result<JsonRpc> {
+    pub(crate) async fn send_deploy_file(self, input_path: &str) -> Result<JsonRpc> {
         let input = File::open(input_path).map_err(|error| Error::IoError {
             context: format!("unable to read input file '{}'", input_path),
             error,
         })?;
         let deploy = Deploy::read_deploy(input)?;
         let params = PutDeployParams { deploy };
-        SendDeploy::request_with_map_params(self, params)
+        self.request_with_map_params::<SendDeploy, _>(params).await
     }
 
-    pub(crate) fn put_deploy(self, deploy: Deploy) -> Result<JsonRpc> {
+    pub(crate) async fn put_deploy(self, deploy: Deploy) -> Result<JsonRpc> {
         let params = PutDeployParams { deploy };
-        PutDeploy::request_with_map_params(self, params)
+        self.request_with_map_params::<PutDeploy, _>(params).await
     }
 
-    pub(crate) fn get_block(self, maybe_block_identifier: &str) -> Result<JsonRpc> {
+    pub(crate) async fn get_block(
+        self,
+        maybe_block_identifier: &str,
+        verify: bool,
+    ) -> Result<JsonRpc> {
         let maybe_block_identifier = Self::block_identifier(maybe_block_identifier)?;
         let response = match maybe_block_identifier {
             Some(block_identifier) => {
                 let params = GetBlockParams { block_identifier };
-                GetBlock::request_with_map_params(self, params)
+                self.request_with_map_params::<GetBlock, _>(params).await
             }
-            None => GetBlock::request(self),
+            None => self.request_without_params::<GetBlock>().await,
         }?;
-        validation::validate_get_block_response(&response, &maybe_block_identifier)?;
+        if verify {
+            validation::validate_get_block_response(&response, &maybe_block_identifier)?;
+        }
         Ok(response)
     }
 
-    pub(crate) fn get_block_transfers(self, maybe_block_identifier: &str) -> Result<JsonRpc> {
+    pub(crate) async fn get_block_transfers(
+        self,
+        maybe_block_identifier: &str,
+        verify: bool,
+    ) -> Result<JsonRpc> {
         let maybe_block_identifier = Self::block_identifier(maybe_block_identifier)?;
         let response = match maybe_block_identifier {
             Some(block_identifier) => {
                 let params = GetBlockTransfersParams { block_identifier };
-                GetBlockTransfers::request_with_map_params(self, params)
+                self.request_with_map_params::<GetBlockTransfers, _>(params)
+                    .await
             }
-            None => GetBlockTransfers::request(self),
+            None => self.request_without_params::<GetBlockTransfers>().await,
         }?;
+        if verify {
+            validation::validate_get_block_transfers_response(&response, &maybe_block_identifier)?;
+        }
         Ok(response)
     }
 
-    pub(crate) fn get_account_info(
+    pub(crate) async fn get_account_info(
         self,
         public_key: &str,
         maybe_block_identifier: &str,
@@ -258,7 +447,55 @@ impl RpcCall {
             public_key: key,
             block_identifier,
         };
-        GetAccountInfo::request_with_map_params(self, params)
+        self.request_with_map_params::<GetAccountInfo, _>(params)
+            .await
+    }
+
+    pub(crate) async fn get_validator_performance(
+        self,
+        era_id: &str,
+        maybe_to_era_id: &str,
+        maybe_public_key: &str,
+    ) -> Result<JsonRpc> {
+        let era_identifier = Self::era_identifier(era_id, maybe_to_era_id)?;
+        let public_key = if maybe_public_key.is_empty() {
+            None
+        } else {
+            Some(PublicKey::from_hex(maybe_public_key).map_err(|_| Error::FailedToParseKey)?)
+        };
+        let params = GetValidatorPerformanceParams {
+            era_identifier,
+            public_key,
+        };
+        self.request_with_map_params::<GetValidatorPerformance, _>(params)
+            .await
+    }
+
+    pub(crate) async fn get_validator_changes(
+        self,
+        era_id: &str,
+        maybe_to_era_id: &str,
+    ) -> Result<JsonRpc> {
+        let era_identifier = Self::era_identifier(era_id, maybe_to_era_id)?;
+        let params = GetValidatorChangesParams { era_identifier };
+        self.request_with_map_params::<GetValidatorChanges, _>(params)
+            .await
+    }
+
+    fn era_identifier(era_id: &str, maybe_to_era_id: &str) -> Result<EraIdentifier> {
+        let from = era_id
+            .parse::<u64>()
+            .map_err(|error| Error::FailedToParseInt("era_id", error))?;
+        if maybe_to_era_id.is_empty() {
+            return Ok(EraIdentifier::EraId(EraId::from(from)));
+        }
+        let to = maybe_to_era_id
+            .parse::<u64>()
+            .map_err(|error| Error::FailedToParseInt("to_era_id", error))?;
+        Ok(EraIdentifier::Range {
+            from: EraId::from(from),
+            to: EraId::from(to),
+        })
     }
 
     fn block_identifier(maybe_block_identifier: &str) -> Result<Option<BlockIdentifier>> {
@@ -270,7 +507,7 @@ impl RpcCall {
             let hash =
                 Digest::from_hex(maybe_block_identifier).map_err(|error| Error::CryptoError {
                     context: "block_identifier",
-                    error,
+                    error: error.into(),
                 })?;
             Ok(Some(BlockIdentifier::Hash(BlockHash::new(hash))))
         } else {
@@ -281,14 +518,29 @@ impl RpcCall {
         }
     }
 
+    /// Calls a casper node's JSON-RPC endpoint.
+    async fn request_without_params<C: RpcClient>(self) -> Result<JsonRpc> {
+        self.request(C::RPC_METHOD, Params::None(())).await
+    }
+
+    /// Calls a casper node's JSON-RPC endpoint with parameters.
+    async fn request_with_map_params<C: RpcClient, T: IntoJsonMap>(
+        self,
+        params: T,
+    ) -> Result<JsonRpc> {
+        self.request(C::RPC_METHOD, Params::from(params.into_json_map()))
+            .await
+    }
+
     async fn request(self, method: &str, params: Params) -> Result<JsonRpc> {
         let url = format!("{}/{}", self.node_address, RPC_API_PATH);
         let rpc_req = JsonRpc::request_with_params(self.rpc_id, method, params);
 
         crate::pretty_print_at_level(&rpc_req, self.verbosity_level);
 
-        let client = Client::new();
-        let response = client
+        let response = self
+            .http_client
+            .unwrap_or_default()
             .post(&url)
             .json(&rpc_req)
             .send()
@@ -334,26 +586,28 @@ impl RpcCall {
     }
 }
 
+/// Parses the `result` field of a successful JSON-RPC response as `T`.
+pub(crate) fn parse_result<T: DeserializeOwned>(response: JsonRpc) -> Result<T> {
+    match response.get_result().cloned() {
+        Some(result) => Ok(serde_json::from_value(result)?),
+        None => Err(Error::InvalidRpcResponse(response)),
+    }
+}
+
 /// General purpose client trait for making requests to casper node's HTTP endpoints.
 pub(crate) trait RpcClient {
     const RPC_METHOD: &'static str;
+}
 
-    /// Calls a casper node's JSON-RPC endpoint.
-    fn request(rpc_call: RpcCall) -> Result<JsonRpc> {
-        executor::block_on(async { rpc_call.request(Self::RPC_METHOD, Params::None(())).await })
-    }
+impl RpcClient for GetBalance {
+    const RPC_METHOD: &'static str = Self::METHOD;
+}
 
-    /// Calls a casper node's JSON-RPC endpoint with parameters.
-    fn request_with_map_params<T: IntoJsonMap>(rpc_call: RpcCall, params: T) -> Result<JsonRpc> {
-        executor::block_on(async {
-            rpc_call
-                .request(Self::RPC_METHOD, Params::from(params.into_json_map()))
-                .await
-        })
-    }
+impl RpcClient for GetBalances {
+    const RPC_METHOD: &'static str = Self::METHOD;
 }
 
-impl RpcClient for GetBalance {
+impl RpcClient for GetAllBalances {
     const RPC_METHOD: &'static str = Self::METHOD;
 }
 
@@ -373,6 +627,10 @@ impl RpcClient for GetItem {
     const RPC_METHOD: &'static str = <Self as RpcWithParams>::METHOD;
 }
 
+impl RpcClient for GetEntryPoint {
+    const RPC_METHOD: &'static str = Self::METHOD;
+}
+
 impl RpcClient for GetEraInfoBySwitchBlock {
     const RPC_METHOD: &'static str = Self::METHOD;
 }
@@ -381,6 +639,10 @@ impl RpcClient for GetAuctionInfo {
     const RPC_METHOD: &'static str = Self::METHOD;
 }
 
+impl RpcClient for GetStatus {
+    const RPC_METHOD: &'static str = Self::METHOD;
+}
+
 impl RpcClient for ListRpcs {
     const RPC_METHOD: &'static str = Self::METHOD;
 }
@@ -389,6 +651,14 @@ impl RpcClient for GetAccountInfo {
     const RPC_METHOD: &'static str = Self::METHOD;
 }
 
+impl RpcClient for GetValidatorPerformance {
+    const RPC_METHOD: &'static str = Self::METHOD;
+}
+
+impl RpcClient for GetValidatorChanges {
+    const RPC_METHOD: &'static str = Self::METHOD;
+}
+
 pub(crate) trait IntoJsonMap: Serialize {
     fn into_json_map(self) -> Map<String, Value>
     where
@@ -406,9 +676,15 @@ impl IntoJsonMap for GetBlockParams {}
 impl IntoJsonMap for GetBlockTransfersParams {}
 impl IntoJsonMap for GetStateRootHashParams {}
 impl IntoJsonMap for GetDeployParams {}
+impl IntoJsonMap for GetDeployStatusParams {}
 impl IntoJsonMap for GetBalanceParams {}
+impl IntoJsonMap for GetBalancesParams {}
+impl IntoJsonMap for GetAllBalancesParams {}
 impl IntoJsonMap for GetItemParams {}
+impl IntoJsonMap for GetEntryPointParams {}
 impl IntoJsonMap for GetEraInfoParams {}
 impl IntoJsonMap for ListRpcs {}
 impl IntoJsonMap for GetAuctionInfoParams {}
 impl IntoJsonMap for GetAccountInfoParams {}
+impl IntoJsonMap for GetValidatorPerformanceParams {}
+impl IntoJsonMap for GetValidatorChangesParams {}
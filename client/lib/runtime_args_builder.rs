@@ -0,0 +1,305 @@
+use std::fmt::{self, Display, Formatter};
+
+use casper_types::{bytesrepr::ToBytes, CLType, CLTyped, CLValue, EntryPoint, RuntimeArgs};
+
+use crate::error::{Error, Result};
+
+/// A difference between the runtime args provided for a call and the args expected by the entry
+/// point being called.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ArgMismatch {
+    /// An arg required by the entry point was not provided.
+    Missing {
+        /// The name of the arg.
+        name: String,
+        /// The type expected by the entry point.
+        expected: CLType,
+    },
+    /// An arg was provided which the entry point doesn't take.
+    Unexpected {
+        /// The name of the arg.
+        name: String,
+    },
+    /// An arg was provided with a different type to the one expected by the entry point.
+    TypeMismatch {
+        /// The name of the arg.
+        name: String,
+        /// The type expected by the entry point.
+        expected: CLType,
+        /// The type of the provided value.
+        actual: CLType,
+    },
+}
+
+impl Display for ArgMismatch {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self {
+            ArgMismatch::Missing { name, expected } => {
+                write!(formatter, "missing arg '{}' of type {:?}", name, expected)
+            }
+            ArgMismatch::Unexpected { name } => write!(formatter, "unexpected arg '{}'", name),
+            ArgMismatch::TypeMismatch {
+                name,
+                expected,
+                actual,
+            } => write!(
+                formatter,
+                "arg '{}' has type {:?} but {:?} is expected",
+                name, actual, expected
+            ),
+        }
+    }
+}
+
+/// Builds `RuntimeArgs` for a call to a stored contract, optionally validating them against the
+/// signature of the entry point being called.
+///
+/// An entry point parameter of type `Option` may be omitted, and a parameter of type `Any` accepts
+/// a value of any type.
+#[derive(Clone, Debug, Default)]
+pub struct RuntimeArgsBuilder {
+    args: RuntimeArgs,
+}
+
+impl RuntimeArgsBuilder {
+    /// Constructs a new builder with no args.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an arg with the given name and value.
+    pub fn arg<T: CLTyped + ToBytes>(mut self, name: &str, value: T) -> Result<Self> {
+        self.args.insert(name, value)?;
+        Ok(self)
+    }
+
+    /// Adds an arg with the given name and already-constructed `CLValue`.
+    pub fn cl_value(mut self, name: &str, value: CLValue) -> Self {
+        self.args.insert_cl_value(name, value);
+        self
+    }
+
+    /// Returns all the differences between the args added so far and those expected by
+    /// `entry_point`.
+    ///
+    /// Missing and mistyped args are listed first in the order the entry point declares them,
+    /// followed by unexpected args in the order they were added.
+    pub fn validate(&self, entry_point: &EntryPoint) -> Vec<ArgMismatch> {
+        let mut mismatches = vec![];
+        for parameter in entry_point.args() {
+            let expected = parameter.cl_type();
+            match self.args.get(parameter.name()) {
+                None => {
+                    if !matches!(expected, CLType::Option(_)) {
+                        mismatches.push(ArgMismatch::Missing {
+                            name: parameter.name().to_string(),
+                            expected: expected.clone(),
+                        });
+                    }
+                }
+                Some(value) => {
+                    if *expected != CLType::Any && value.cl_type() != expected {
+                        mismatches.push(ArgMismatch::TypeMismatch {
+                            name: parameter.name().to_string(),
+                            expected: expected.clone(),
+                            actual: value.cl_type().clone(),
+                        });
+                    }
+                }
+            }
+        }
+
+        for named_arg in self.args.named_args() {
+            if !entry_point
+                .args()
+                .iter()
+                .any(|parameter| parameter.name() == named_arg.name())
+            {
+                mismatches.push(ArgMismatch::Unexpected {
+                    name: named_arg.name().to_string(),
+                });
+            }
+        }
+
+        mismatches
+    }
+
+    /// Returns the args without validating them.
+    pub fn build(self) -> RuntimeArgs {
+        self.args
+    }
+
+    /// Returns the args if they match the signature of `entry_point`, or
+    /// [`Error::InvalidRuntimeArgs`](enum.Error.html#variant.InvalidRuntimeArgs) listing all the
+    /// mismatches otherwise.
+    pub fn build_for(self, entry_point: &EntryPoint) -> Result<RuntimeArgs> {
+        let mismatches = self.validate(entry_point);
+        if mismatches.is_empty() {
+            Ok(self.args)
+        } else {
+            Err(Error::InvalidRuntimeArgs {
+                entry_point: entry_point.name().to_string(),
+                mismatches,
+            })
+        }
+    }
+}
+
+impl From<RuntimeArgs> for RuntimeArgsBuilder {
+    fn from(args: RuntimeArgs) -> Self {
+        RuntimeArgsBuilder { args }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use casper_execution_engine::{
+        shared::{newtypes::CorrelationId, stored_value::StoredValue},
+        storage::global_state::{in_memory::InMemoryGlobalState, StateProvider, StateReader},
+    };
+    use casper_node::types::json_compatibility::StoredValue as JsonStoredValue;
+    use casper_types::{
+        account::AccountHash, contracts::NamedKeys, Contract, ContractHash, ContractPackageHash,
+        ContractWasmHash, EntryPointAccess, EntryPointType, EntryPoints, Key, Parameter,
+        ProtocolVersion, U512,
+    };
+
+    use super::*;
+
+    const CONTRACT_HASH: [u8; 32] = [7; 32];
+    const ENTRY_POINT: &str = "transfer";
+
+    /// Stores a contract with a single entry point taking a required `recipient`, a required
+    /// `amount` and an optional `memo` in a test global state, then reads that entry point back
+    /// via its JSON representation, as returned by the "state_get_entry_point" RPC.
+    fn entry_point_from_global_state() -> EntryPoint {
+        let mut entry_points = EntryPoints::new();
+        entry_points.add_entry_point(EntryPoint::new(
+            ENTRY_POINT,
+            vec![
+                Parameter::new("recipient", CLType::Key),
+                Parameter::new("amount", CLType::U512),
+                Parameter::new("memo", CLType::Option(Box::new(CLType::String))),
+            ],
+            CLType::Unit,
+            EntryPointAccess::Public,
+            EntryPointType::Contract,
+        ));
+        let contract = Contract::new(
+            ContractPackageHash::new([8; 32]),
+            ContractWasmHash::new([9; 32]),
+            NamedKeys::new(),
+            entry_points,
+            ProtocolVersion::V1_0_0,
+        );
+        let key = Key::from(ContractHash::new(CONTRACT_HASH));
+
+        let correlation_id = CorrelationId::new();
+        let (global_state, root_hash) = InMemoryGlobalState::from_pairs(
+            correlation_id,
+            &[(key, StoredValue::Contract(contract))],
+        )
+        .unwrap();
+        let stored_value = global_state
+            .checkout(root_hash)
+            .unwrap()
+            .expect("should have root")
+            .read(correlation_id, &key)
+            .unwrap()
+            .expect("should have contract");
+
+        match JsonStoredValue::try_from(&stored_value).unwrap() {
+            JsonStoredValue::Contract(contract) => contract
+                .entry_point(ENTRY_POINT)
+                .expect("should have entry point")
+                .clone(),
+            _ => panic!("should be a contract"),
+        }
+    }
+
+    fn recipient() -> Key {
+        Key::Account(AccountHash::new([1; 32]))
+    }
+
+    #[test]
+    fn should_accept_matching_args() {
+        let entry_point = entry_point_from_global_state();
+
+        let builder = RuntimeArgsBuilder::new()
+            .arg("recipient", recipient())
+            .unwrap()
+            .arg("amount", U512::from(100))
+            .unwrap();
+        assert!(builder.validate(&entry_point).is_empty());
+
+        let args = builder.build_for(&entry_point).unwrap();
+        assert_eq!(
+            args.get("amount"),
+            Some(&CLValue::from_t(U512::from(100)).unwrap())
+        );
+
+        let with_optional_arg = RuntimeArgsBuilder::from(args)
+            .arg("memo", Some("rent".to_string()))
+            .unwrap();
+        assert!(with_optional_arg.validate(&entry_point).is_empty());
+    }
+
+    #[test]
+    fn should_report_all_mismatches() {
+        let entry_point = entry_point_from_global_state();
+
+        let builder = RuntimeArgsBuilder::new()
+            .arg("amount", 100_u64)
+            .unwrap()
+            .arg("memo", "rent".to_string())
+            .unwrap()
+            .arg("target", recipient())
+            .unwrap();
+        let expected_mismatches = vec![
+            ArgMismatch::Missing {
+                name: "recipient".to_string(),
+                expected: CLType::Key,
+            },
+            ArgMismatch::TypeMismatch {
+                name: "amount".to_string(),
+                expected: CLType::U512,
+                actual: CLType::U64,
+            },
+            ArgMismatch::TypeMismatch {
+                name: "memo".to_string(),
+                expected: CLType::Option(Box::new(CLType::String)),
+                actual: CLType::String,
+            },
+            ArgMismatch::Unexpected {
+                name: "target".to_string(),
+            },
+        ];
+        assert_eq!(builder.validate(&entry_point), expected_mismatches);
+
+        match builder.build_for(&entry_point) {
+            Err(Error::InvalidRuntimeArgs {
+                entry_point,
+                mismatches,
+            }) => {
+                assert_eq!(entry_point, ENTRY_POINT);
+                assert_eq!(mismatches, expected_mismatches);
+            }
+            result => panic!("expected InvalidRuntimeArgs, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn should_display_mismatches() {
+        let mismatch = ArgMismatch::TypeMismatch {
+            name: "amount".to_string(),
+            expected: CLType::U512,
+            actual: CLType::U64,
+        };
+        assert_eq!(
+            mismatch.to_string(),
+            "arg 'amount' has type U64 but U512 is expected"
+        );
+    }
+}
@@ -9,14 +9,27 @@ use casper_execution_engine::{
 };
 use casper_node::{
     crypto::hash::Digest,
-    rpcs::chain::{BlockIdentifier, EraSummary, GetEraInfoResult},
-    types::{json_compatibility, Block, BlockValidationError, JsonBlock},
+    rpcs::{
+        chain::{BlockIdentifier, EraSummary, GetEraInfoResult},
+        info::GetDeployResult,
+        state::{
+            GetAllBalancesResult, GetBalancesResult, GetEntryPointResult, PurseBalanceOutcome,
+        },
+    },
+    types::{
+        json_compatibility, Block, BlockHash, BlockValidationError, Deploy, DeployHash,
+        DeployHashScheme, DeployValidationFailure, JsonBlock,
+    },
+};
+use casper_types::{
+    bytesrepr::{self, ToBytes},
+    ContractHash, Key, ProtocolVersion, URef, U512,
 };
-use casper_types::{bytesrepr, Key, U512};
 
 const GET_ITEM_RESULT_BALANCE_VALUE: &str = "balance_value";
 const GET_ITEM_RESULT_STORED_VALUE: &str = "stored_value";
 const GET_ITEM_RESULT_MERKLE_PROOF: &str = "merkle_proof";
+const GET_ITEM_RESULT_RAW_STORED_VALUE: &str = "raw_stored_value";
 
 /// Error that can be returned when validating a block returned from a JSON-RPC method.
 #[derive(Error, Debug)]
@@ -56,6 +69,34 @@ pub enum ValidateResponseError {
     /// Block height was not as requested.
     #[error("block height was not as requested")]
     UnexpectedBlockHeight,
+
+    /// State root hash requested does not correspond to response.
+    #[error("state root hash requested does not correspond to response")]
+    UnexpectedStateRootHash,
+
+    /// Balances returned do not correspond to the purses requested.
+    #[error("balances returned do not correspond to the purses requested")]
+    UnexpectedBalances,
+
+    /// The page token for the next page of balances does not follow the one requested.
+    #[error("next page token does not follow the page token requested")]
+    UnexpectedPageToken,
+
+    /// Entry point returned does not correspond to the one requested.
+    #[error("entry point returned does not correspond to the one requested")]
+    UnexpectedEntryPoint,
+
+    /// No deploy in response.
+    #[error("no deploy in response")]
+    NoDeployInResponse,
+
+    /// Deploy hash requested does not correspond to response.
+    #[error("deploy hash requested does not correspond to response")]
+    UnexpectedDeployHash,
+
+    /// Failed to validate a deploy.
+    #[error("Deploy validation error: {0}")]
+    DeployValidationError(DeployValidationFailure),
 }
 
 impl From<bytesrepr::Error> for ValidateResponseError {
@@ -70,6 +111,12 @@ impl From<BlockValidationError> for ValidateResponseError {
     }
 }
 
+impl From<DeployValidationFailure> for ValidateResponseError {
+    fn from(e: DeployValidationFailure) -> Self {
+        ValidateResponseError::DeployValidationError(e)
+    }
+}
+
 pub(crate) fn validate_get_era_info_response(
     response: &JsonRpc,
 ) -> Result<(), ValidateResponseError> {
@@ -164,6 +211,17 @@ pub(crate) fn validate_query_response(
         }
     }
 
+    // If the raw stored value was requested, it must be exactly the serialized value in the proof.
+    if let Some(raw_value) = object.get(GET_ITEM_RESULT_RAW_STORED_VALUE) {
+        let raw_value_bytes = raw_value
+            .as_str()
+            .and_then(|raw_value_str| hex::decode(raw_value_str).ok())
+            .ok_or(ValidateResponseError::ValidateResponseFailedToParse)?;
+        if raw_value_bytes != proof_value.to_bytes()? {
+            return Err(ValidateResponseError::SerializedValueNotContainedInProof);
+        }
+    }
+
     core::validate_query_proof(
         &state_root_hash.to_owned().into(),
         &proofs,
@@ -219,6 +277,135 @@ pub(crate) fn validate_get_balance_response(
     .map_err(Into::into)
 }
 
+pub(crate) fn validate_get_entry_point_response(
+    response: &JsonRpc,
+    maybe_state_root_hash: Option<&Digest>,
+    contract_hash: &ContractHash,
+    entry_point_name: &str,
+) -> Result<(), ValidateResponseError> {
+    let value = response
+        .get_result()
+        .ok_or(ValidateResponseError::ValidateResponseFailedToParse)?;
+    let result: GetEntryPointResult = serde_json::from_value(value.to_owned())?;
+
+    if let Some(state_root_hash) = maybe_state_root_hash {
+        if result.state_root_hash != *state_root_hash {
+            return Err(ValidateResponseError::UnexpectedStateRootHash);
+        }
+    }
+    if result.entry_point.name() != entry_point_name {
+        return Err(ValidateResponseError::UnexpectedEntryPoint);
+    }
+
+    let proofs: Vec<TrieMerkleProof<Key, StoredValue>> = {
+        let proof_bytes = hex::decode(&result.merkle_proof)
+            .map_err(|_| ValidateResponseError::ValidateResponseFailedToParse)?;
+        bytesrepr::deserialize(proof_bytes)?
+    };
+    let proof_value = proofs
+        .last()
+        .ok_or(ValidateResponseError::ValidateResponseFailedToParse)?
+        .value();
+
+    // The returned entry point must be exactly the one held by the contract in the proof.
+    match proof_value {
+        StoredValue::Contract(contract)
+            if contract.entry_point(entry_point_name) == Some(&result.entry_point) => {}
+        _ => return Err(ValidateResponseError::SerializedValueNotContainedInProof),
+    }
+
+    core::validate_query_proof(
+        &result.state_root_hash.into(),
+        &proofs,
+        &Key::from(*contract_hash),
+        &[],
+        proof_value,
+    )
+    .map_err(Into::into)
+}
+
+pub(crate) fn validate_get_balances_response(
+    response: &JsonRpc,
+    state_root_hash: &Digest,
+    purse_identifiers: &[&str],
+) -> Result<(), ValidateResponseError> {
+    let value = response
+        .get_result()
+        .ok_or(ValidateResponseError::ValidateResponseFailedToParse)?;
+    let result: GetBalancesResult = serde_json::from_value(value.to_owned())?;
+
+    if result.state_root_hash != *state_root_hash {
+        return Err(ValidateResponseError::UnexpectedStateRootHash);
+    }
+    if result.balances.len() != purse_identifiers.len() {
+        return Err(ValidateResponseError::UnexpectedBalances);
+    }
+
+    for (entry, purse_identifier) in result.balances.iter().zip(purse_identifiers) {
+        if entry.purse_identifier != *purse_identifier {
+            return Err(ValidateResponseError::UnexpectedBalances);
+        }
+
+        let (purse_uref, balance_value, merkle_proof) = match &entry.outcome {
+            PurseBalanceOutcome::Found {
+                purse_uref,
+                balance_value,
+                merkle_proof,
+            } => (purse_uref, balance_value, merkle_proof),
+            PurseBalanceOutcome::Failed { .. } => continue,
+        };
+
+        let purse_uref = URef::from_formatted_str(purse_uref)
+            .map_err(|_| ValidateResponseError::ValidateResponseFailedToParse)?;
+        // A purse requested by URef must be the one the balance was read from.  For an account
+        // hash, the account's main purse is only known to the node.
+        if let Ok(requested_uref) = URef::from_formatted_str(purse_identifier) {
+            if requested_uref.addr() != purse_uref.addr() {
+                return Err(ValidateResponseError::UnexpectedBalances);
+            }
+        }
+
+        let proof_bytes = hex::decode(merkle_proof)
+            .map_err(|_| ValidateResponseError::ValidateResponseFailedToParse)?;
+        let balance_proof: TrieMerkleProof<Key, StoredValue> = bytesrepr::deserialize(proof_bytes)?;
+
+        core::validate_balance_proof(
+            &state_root_hash.to_owned().into(),
+            &balance_proof,
+            Key::from(purse_uref),
+            balance_value,
+        )?;
+    }
+
+    Ok(())
+}
+
+pub(crate) fn validate_get_all_balances_response(
+    response: &JsonRpc,
+    state_root_hash: &Digest,
+    maybe_page_token: Option<&str>,
+) -> Result<(), ValidateResponseError> {
+    let value = response
+        .get_result()
+        .ok_or(ValidateResponseError::ValidateResponseFailedToParse)?;
+    let result: GetAllBalancesResult = serde_json::from_value(value.to_owned())?;
+
+    if result.state_root_hash != *state_root_hash {
+        return Err(ValidateResponseError::UnexpectedStateRootHash);
+    }
+    // Accounts are returned in ascending order of account hash, with the page token being the last
+    // one scanned, so a token which doesn't advance would never reach the last page.
+    if let (Some(next_page_token), Some(page_token)) =
+        (result.next_page_token.as_deref(), maybe_page_token)
+    {
+        if next_page_token <= page_token {
+            return Err(ValidateResponseError::UnexpectedPageToken);
+        }
+    }
+
+    Ok(())
+}
+
 pub(crate) fn validate_get_block_response(
     response: &JsonRpc,
     maybe_block_identifier: &Option<BlockIdentifier>,
@@ -254,3 +441,61 @@ pub(crate) fn validate_get_block_response(
     }
     Ok(())
 }
+
+pub(crate) fn validate_get_deploy_response(
+    response: &JsonRpc,
+    deploy_hash: &DeployHash,
+) -> Result<(), ValidateResponseError> {
+    let value = response
+        .get_result()
+        .filter(|value| value.get("deploy").is_some())
+        .ok_or(ValidateResponseError::NoDeployInResponse)?;
+    let result: GetDeployResult = serde_json::from_value(value.to_owned())?;
+    let mut deploy = result.deploy;
+    if deploy.id() != deploy_hash {
+        return Err(ValidateResponseError::UnexpectedDeployHash);
+    }
+    verify_deploy_hashes(&mut deploy, result.api_version)
+}
+
+/// Recomputes the deploy's hash and body hash, along with verifying its approvals.
+///
+/// The hashes are checked using the scheme of the protocol version reported by the node, falling
+/// back to the legacy scheme for deploys accepted before the network adopted a newer one.
+fn verify_deploy_hashes(
+    deploy: &mut Deploy,
+    api_version: ProtocolVersion,
+) -> Result<(), ValidateResponseError> {
+    let hash_scheme = DeployHashScheme::for_protocol_version(api_version);
+    let validity = deploy.is_valid(hash_scheme);
+    if validity.is_err()
+        && hash_scheme != DeployHashScheme::Legacy
+        && deploy.is_valid(DeployHashScheme::Legacy).is_ok()
+    {
+        return Ok(());
+    }
+    validity.map_err(Into::into)
+}
+
+pub(crate) fn validate_get_block_transfers_response(
+    response: &JsonRpc,
+    maybe_block_identifier: &Option<BlockIdentifier>,
+) -> Result<(), ValidateResponseError> {
+    // The transfers themselves don't reference the block, so unless the block was requested by
+    // hash, there is nothing to check the response against.
+    let requested_block_hash = match maybe_block_identifier {
+        Some(BlockIdentifier::Hash(block_hash)) => block_hash,
+        Some(BlockIdentifier::Height(_)) | None => return Ok(()),
+    };
+    let block_hash_value = response
+        .get_result()
+        .and_then(|value| value.get("block_hash"))
+        .ok_or(ValidateResponseError::NoBlockInResponse)?;
+    let maybe_block_hash: Option<BlockHash> = serde_json::from_value(block_hash_value.to_owned())?;
+    match maybe_block_hash {
+        Some(block_hash) if block_hash != *requested_block_hash => {
+            Err(ValidateResponseError::UnexpectedBlockHash)
+        }
+        _ => Ok(()),
+    }
+}
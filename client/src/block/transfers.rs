@@ -13,6 +13,9 @@ enum DisplayOrder {
     NodeAddress,
     RpcId,
     BlockIdentifier,
+    BlockHash,
+    BlockHeight,
+    NoVerify,
 }
 
 impl<'a, 'b> ClientCommand<'a, 'b> for GetBlockTransfers {
@@ -31,19 +34,26 @@ impl<'a, 'b> ClientCommand<'a, 'b> for GetBlockTransfers {
             .arg(common::block_identifier::arg(
                 DisplayOrder::BlockIdentifier as usize,
             ))
+            .arg(common::block_hash::arg(DisplayOrder::BlockHash as usize))
+            .arg(common::block_height::arg(
+                DisplayOrder::BlockHeight as usize,
+            ))
+            .arg(common::no_verify::arg(DisplayOrder::NoVerify as usize))
     }
 
     fn run(matches: &ArgMatches<'_>) -> Result<Success, Error> {
         let maybe_rpc_id = common::rpc_id::get(matches);
         let node_address = common::node_address::get(matches);
         let verbosity_level = common::verbose::get(matches);
-        let maybe_block_id = common::block_identifier::get(matches);
+        let maybe_block_id = common::get_block_id(matches);
+        let verify = common::no_verify::get(matches);
 
         casper_client::get_block_transfers(
             maybe_rpc_id,
             node_address,
             verbosity_level,
             maybe_block_id,
+            verify,
         )
         .map(Success::from)
     }
@@ -34,6 +34,55 @@ pub mod verbose {
     }
 }
 
+/// Handles providing the arg for and retrieval of the path of the client config file.
+pub mod config {
+    use super::*;
+
+    const ARG_NAME: &str = "config";
+    const ARG_VALUE_NAME: &str = super::ARG_PATH;
+    const ARG_HELP: &str =
+        "Path to the client config file, which defines named profiles of settings used in place of \
+        the defaults of the corresponding args. If not provided, ~/.casper-client.toml is used if \
+        it exists. Must be given before the subcommand";
+
+    pub fn arg() -> Arg<'static, 'static> {
+        Arg::with_name(ARG_NAME)
+            .long(ARG_NAME)
+            .required(false)
+            .value_name(ARG_VALUE_NAME)
+            .help(ARG_HELP)
+    }
+
+    pub fn get<'a>(matches: &'a ArgMatches) -> Option<&'a str> {
+        matches.value_of(ARG_NAME)
+    }
+}
+
+/// Handles providing the arg for and retrieval of the name of the profile to use.
+pub mod profile {
+    use super::*;
+
+    const ARG_NAME: &str = "profile";
+    const ARG_VALUE_NAME: &str = "NAME";
+    const ARG_HELP: &str =
+        "Name of the profile in the client config file to use. If not provided, the profile named \
+        by the CASPER_PROFILE env var is used, or else the config file's default profile, if any. \
+        Args passed explicitly always override the profile's settings. Must be given before the \
+        subcommand";
+
+    pub fn arg() -> Arg<'static, 'static> {
+        Arg::with_name(ARG_NAME)
+            .long(ARG_NAME)
+            .required(false)
+            .value_name(ARG_VALUE_NAME)
+            .help(ARG_HELP)
+    }
+
+    pub fn get<'a>(matches: &'a ArgMatches) -> Option<&'a str> {
+        matches.value_of(ARG_NAME)
+    }
+}
+
 /// Handles providing the arg for and retrieval of the node hostname/IP and port.
 pub mod node_address {
     use super::*;
@@ -49,7 +98,12 @@ pub mod node_address {
             .long(ARG_NAME)
             .short(ARG_SHORT)
             .required(true)
-            .default_value(ARG_DEFAULT)
+            .default_value(
+                crate::profile::defaults()
+                    .node_address
+                    .as_deref()
+                    .unwrap_or(ARG_DEFAULT),
+            )
             .value_name(ARG_VALUE_NAME)
             .help(ARG_HELP)
             .display_order(order)
@@ -143,6 +197,33 @@ pub mod force {
     }
 }
 
+/// Handles the arg for whether to skip verifying the hashes in the response.
+pub mod no_verify {
+    use super::*;
+
+    /// The exit code of the client if the response fails verification.
+    pub const VERIFICATION_FAILED_EXIT_CODE: i32 = 2;
+
+    const ARG_NAME: &str = "no-verify";
+    const ARG_HELP: &str =
+        "If this flag is passed, the hashes in the response are not checked against its contents \
+        and the request. Without this flag, a response which fails verification is rejected with \
+        exit code 2";
+
+    pub fn arg(order: usize) -> Arg<'static, 'static> {
+        Arg::with_name(ARG_NAME)
+            .long(ARG_NAME)
+            .required(false)
+            .help(ARG_HELP)
+            .display_order(order)
+    }
+
+    /// Returns whether the response should be verified, i.e. `true` unless the flag was passed.
+    pub fn get(matches: &ArgMatches) -> bool {
+        !matches.is_present(ARG_NAME)
+    }
+}
+
 /// Handles providing the arg for and retrieval of the state root hash.
 pub mod state_root_hash {
     use super::*;
@@ -173,7 +254,7 @@ pub mod state_root_hash {
 pub mod block_identifier {
     use super::*;
 
-    const ARG_NAME: &str = "block-identifier";
+    pub const ARG_NAME: &str = "block-identifier";
     const ARG_SHORT: &str = "b";
     const ARG_VALUE_NAME: &str = "HEX STRING OR INTEGER";
     const ARG_HELP: &str =
@@ -195,6 +276,85 @@ pub mod block_identifier {
     }
 }
 
+/// Handles providing the arg for and retrieval of the block hash, as an alternative to the block
+/// identifier.
+pub mod block_hash {
+    use casper_node::crypto::hash::Digest;
+
+    use super::*;
+
+    pub const ARG_NAME: &str = "block-hash";
+    const ARG_VALUE_NAME: &str = super::ARG_HEX_STRING;
+    const ARG_HELP: &str = "Hex-encoded hash of the block";
+
+    pub(crate) fn arg(order: usize) -> Arg<'static, 'static> {
+        Arg::with_name(ARG_NAME)
+            .long(ARG_NAME)
+            .required(false)
+            .value_name(ARG_VALUE_NAME)
+            .help(ARG_HELP)
+            .validator(validate)
+            .conflicts_with_all(&[block_identifier::ARG_NAME, block_height::ARG_NAME])
+            .display_order(order)
+    }
+
+    fn validate(value: String) -> Result<(), String> {
+        if value.len() != Digest::LENGTH * 2 {
+            return Err(format!(
+                "block hash must be {} hex characters",
+                Digest::LENGTH * 2
+            ));
+        }
+        Digest::from_hex(&value)
+            .map(|_| ())
+            .map_err(|error| format!("invalid block hash: {}", error))
+    }
+
+    pub(crate) fn get<'a>(matches: &'a ArgMatches) -> Option<&'a str> {
+        matches.value_of(ARG_NAME)
+    }
+}
+
+/// Handles providing the arg for and retrieval of the block height, as an alternative to the block
+/// identifier.
+pub mod block_height {
+    use super::*;
+
+    pub const ARG_NAME: &str = "block-height";
+    const ARG_VALUE_NAME: &str = super::ARG_INTEGER;
+    const ARG_HELP: &str = "Height of the block";
+
+    pub(crate) fn arg(order: usize) -> Arg<'static, 'static> {
+        Arg::with_name(ARG_NAME)
+            .long(ARG_NAME)
+            .required(false)
+            .value_name(ARG_VALUE_NAME)
+            .help(ARG_HELP)
+            .validator(validate)
+            .conflicts_with(block_identifier::ARG_NAME)
+            .display_order(order)
+    }
+
+    fn validate(value: String) -> Result<(), String> {
+        value
+            .parse::<u64>()
+            .map(|_| ())
+            .map_err(|error| format!("invalid block height: {}", error))
+    }
+
+    pub(crate) fn get<'a>(matches: &'a ArgMatches) -> Option<&'a str> {
+        matches.value_of(ARG_NAME)
+    }
+}
+
+/// Returns the block hash or height provided via any of the `block-identifier`, `block-hash` or
+/// `block-height` args, or an empty string if none were provided.
+pub(crate) fn get_block_id<'a>(matches: &'a ArgMatches) -> &'a str {
+    block_hash::get(matches)
+        .or_else(|| block_height::get(matches))
+        .unwrap_or_else(|| block_identifier::get(matches))
+}
+
 /// Handles providing the arg for and retrieval of the public key.
 pub mod public_key {
     use casper_node::crypto::AsymmetricKeyExt;
@@ -5,7 +5,7 @@ use std::process;
 
 use clap::{App, AppSettings, Arg, ArgGroup, ArgMatches};
 
-use casper_client::{help, PaymentStrParams, SessionStrParams};
+use casper_client::{help, Error, PaymentStrParams, SessionStrParams};
 
 use crate::common;
 
@@ -21,6 +21,7 @@ pub(super) enum DisplayOrder {
     TransferAmount,
     TransferTargetAccount,
     TransferId,
+    TransferBatchFile,
     Timestamp,
     Ttl,
     GasPrice,
@@ -29,6 +30,7 @@ pub(super) enum DisplayOrder {
     SessionCode,
     SessionArgSimple,
     SessionArgsComplex,
+    SessionArgsJson,
     SessionHash,
     SessionName,
     SessionPackageHash,
@@ -36,10 +38,12 @@ pub(super) enum DisplayOrder {
     SessionEntryPoint,
     SessionVersion,
     SessionTransfer,
+    SkipArgValidation,
     StandardPayment,
     PaymentCode,
     PaymentArgSimple,
     PaymentArgsComplex,
+    PaymentArgsJson,
     PaymentHash,
     PaymentName,
     PaymentPackageHash,
@@ -81,6 +85,10 @@ pub(super) mod show_arg_examples {
 }
 
 pub(super) fn session_str_params<'a>(matches: &'a ArgMatches) -> SessionStrParams<'a> {
+    session_str_params_without_args_json(matches).with_args_json(args_json::session::get(matches))
+}
+
+fn session_str_params_without_args_json<'a>(matches: &'a ArgMatches) -> SessionStrParams<'a> {
     let session_args_simple = arg_simple::session::get(matches);
     let session_args_complex = args_complex::session::get(matches);
     if is_session_transfer::get(matches) {
@@ -133,6 +141,10 @@ pub(super) fn session_str_params<'a>(matches: &'a ArgMatches) -> SessionStrParam
 }
 
 pub(super) fn payment_str_params<'a>(matches: &'a ArgMatches) -> PaymentStrParams<'a> {
+    payment_str_params_without_args_json(matches).with_args_json(args_json::payment::get(matches))
+}
+
+fn payment_str_params_without_args_json<'a>(matches: &'a ArgMatches) -> PaymentStrParams<'a> {
     if let Some(payment_amount) = standard_payment_amount::get(matches) {
         return PaymentStrParams::with_amount(payment_amount);
     }
@@ -228,7 +240,12 @@ pub(super) mod ttl {
             .long(ARG_NAME)
             .required(false)
             .value_name(ARG_VALUE_NAME)
-            .default_value(ARG_DEFAULT)
+            .default_value(
+                crate::profile::defaults()
+                    .ttl
+                    .as_deref()
+                    .unwrap_or(ARG_DEFAULT),
+            )
             .help(ARG_HELP)
             .display_order(DisplayOrder::Ttl as usize)
     }
@@ -246,14 +263,20 @@ pub(super) mod gas_price {
     const ARG_VALUE_NAME: &str = common::ARG_INTEGER;
     const ARG_DEFAULT: &str = "1";
     const ARG_HELP: &str =
-        "Conversion rate between the cost of Wasm opcodes and the motes sent by the payment code";
+        "Conversion rate between the cost of Wasm opcodes and the motes sent by the payment code. \
+        Must be greater than zero and no greater than the network's maximum gas price";
 
     pub(in crate::deploy) fn arg() -> Arg<'static, 'static> {
         Arg::with_name(ARG_NAME)
             .long(ARG_NAME)
             .required(false)
             .value_name(ARG_VALUE_NAME)
-            .default_value(ARG_DEFAULT)
+            .default_value(
+                crate::profile::defaults()
+                    .gas_price
+                    .as_deref()
+                    .unwrap_or(ARG_DEFAULT),
+            )
             .help(ARG_HELP)
             .display_order(DisplayOrder::GasPrice as usize)
     }
@@ -304,12 +327,16 @@ pub(super) mod chain_name {
         a different chain";
 
     pub(in crate::deploy) fn arg() -> Arg<'static, 'static> {
-        Arg::with_name(ARG_NAME)
+        let arg = Arg::with_name(ARG_NAME)
             .long(ARG_NAME)
             .required_unless(show_arg_examples::ARG_NAME)
             .value_name(ARG_VALUE_NAME)
             .help(ARG_HELP)
-            .display_order(DisplayOrder::ChainName as usize)
+            .display_order(DisplayOrder::ChainName as usize);
+        match crate::profile::defaults().chain_name.as_deref() {
+            Some(chain_name) => arg.default_value(chain_name),
+            None => arg,
+        }
     }
 
     pub(in crate::deploy) fn get<'a>(matches: &'a ArgMatches) -> &'a str {
@@ -317,6 +344,29 @@ pub(super) mod chain_name {
             .value_of(ARG_NAME)
             .unwrap_or_else(|| panic!("should have {} arg", ARG_NAME))
     }
+
+    /// Checks the chain name against the name of the node's chainspec if it was taken from the
+    /// profile rather than passed explicitly, so that a deploy created for the profile's network
+    /// isn't sent to a node of another.
+    pub(in crate::deploy) fn check_if_from_profile(
+        matches: &ArgMatches,
+        maybe_rpc_id: &str,
+        node_address: &str,
+        verbosity_level: u64,
+    ) -> Result<(), Error> {
+        if matches.occurrences_of(ARG_NAME) > 0 {
+            return Ok(());
+        }
+        match crate::profile::defaults().chain_name.as_deref() {
+            Some(chain_name) => casper_client::check_chain_name(
+                maybe_rpc_id,
+                node_address,
+                verbosity_level,
+                chain_name,
+            ),
+            None => Ok(()),
+        }
+    }
 }
 
 /// Handles providing the arg for and retrieval of the session code bytes.
@@ -463,6 +513,59 @@ pub(super) mod args_complex {
     }
 }
 
+/// Handles providing the arg for and retrieval of session and payment args given as a JSON
+/// document, allowing args of any `CLType` to be specified.  These are read in from a file.
+pub(super) mod args_json {
+    use super::*;
+
+    const ARG_VALUE_NAME: &str = common::ARG_PATH;
+    const ARG_HELP: &str =
+        "Path to JSON file containing an array of named and typed args for passing to the Wasm \
+        code, each of the form {\"name\": NAME, \"type\": CLTYPE, \"value\": VALUE}. CLTYPE uses \
+        the same form as the JSON schema for CLType, e.g. \"U512\" or {\"List\": {\"Option\": \
+        \"String\"}}. U128, U256 and U512 values are given as decimal strings, and byte arrays as \
+        hex strings";
+
+    pub(in crate::deploy) mod session {
+        use super::*;
+
+        pub const ARG_NAME: &str = "session-args-json";
+
+        pub fn arg() -> Arg<'static, 'static> {
+            super::arg(ARG_NAME, DisplayOrder::SessionArgsJson as usize)
+                .requires(super::session::ARG_NAME)
+        }
+
+        pub fn get<'a>(matches: &'a ArgMatches) -> &'a str {
+            matches.value_of(ARG_NAME).unwrap_or_default()
+        }
+    }
+
+    pub(in crate::deploy) mod payment {
+        use super::*;
+
+        pub const ARG_NAME: &str = "payment-args-json";
+
+        pub fn arg() -> Arg<'static, 'static> {
+            super::arg(ARG_NAME, DisplayOrder::PaymentArgsJson as usize)
+                .requires(super::payment::ARG_NAME)
+        }
+
+        pub fn get<'a>(matches: &'a ArgMatches) -> &'a str {
+            matches.value_of(ARG_NAME).unwrap_or_default()
+        }
+    }
+
+    fn arg(name: &'static str, order: usize) -> Arg<'static, 'static> {
+        Arg::with_name(name)
+            .long(name)
+            .required(false)
+            .value_name(ARG_VALUE_NAME)
+            .help(ARG_HELP)
+            .display_order(order)
+    }
+}
+
 /// Handles providing the arg for and retrieval of the payment code bytes.
 pub(super) mod payment_path {
     use super::*;
@@ -494,8 +597,9 @@ pub(super) mod standard_payment_amount {
     const ARG_SHORT: &str = "p";
     const ARG_HELP: &str =
         "If provided, uses the standard-payment system contract rather than custom payment Wasm. \
-        The value is the 'amount' arg of the standard-payment contract. This arg is incompatible \
-        with all other --payment-xxx args";
+        The value is the 'amount' arg of the standard-payment contract, in motes by default, or in \
+        CSPR if suffixed with 'cspr' (e.g. 2.5cspr). This arg is incompatible with all other \
+        --payment-xxx args";
 
     pub(in crate::deploy) fn arg() -> Arg<'static, 'static> {
         Arg::with_name(ARG_NAME)
@@ -505,6 +609,14 @@ pub(super) mod standard_payment_amount {
             .value_name(ARG_VALUE_NAME)
             .help(ARG_HELP)
             .display_order(DisplayOrder::StandardPayment as usize)
+            // Conflicts with the other args in the "payment" `ArgGroup` are handled by the group.
+            .conflicts_with_all(&[
+                arg_simple::payment::ARG_NAME,
+                args_complex::payment::ARG_NAME,
+                args_json::payment::ARG_NAME,
+                payment_entry_point::ARG_NAME,
+                payment_version::ARG_NAME,
+            ])
     }
 
     pub fn get<'a>(matches: &'a ArgMatches) -> Option<&'a str> {
@@ -550,11 +662,13 @@ pub(super) fn apply_common_session_options<'a, 'b>(subcommand: App<'a, 'b>) -> A
         .arg(session_name::arg())
         .arg(arg_simple::session::arg())
         .arg(args_complex::session::arg())
+        .arg(args_json::session::arg())
         // Group the session-arg args so only one style is used to ensure consistent ordering.
         .group(
             ArgGroup::with_name("session-args")
                 .arg(arg_simple::session::ARG_NAME)
                 .arg(args_complex::session::ARG_NAME)
+                .arg(args_json::session::ARG_NAME)
                 .required(false),
         )
         .arg(session_entry_point::arg())
@@ -584,11 +698,13 @@ pub(crate) fn apply_common_payment_options(
         .arg(payment_name::arg())
         .arg(arg_simple::payment::arg())
         .arg(args_complex::payment::arg())
+        .arg(args_json::payment::arg())
         // Group the payment-arg args so only one style is used to ensure consistent ordering.
         .group(
             ArgGroup::with_name("payment-args")
                 .arg(arg_simple::payment::ARG_NAME)
                 .arg(args_complex::payment::ARG_NAME)
+                .arg(args_json::payment::ARG_NAME)
                 .required(false),
         )
         .arg(payment_entry_point::arg())
@@ -724,6 +840,29 @@ pub(super) mod is_session_transfer {
     }
 }
 
+/// Handles providing the arg for and retrieval of the skip-arg-validation flag.
+pub(super) mod skip_arg_validation {
+    use super::*;
+
+    const ARG_NAME: &str = "skip-arg-validation";
+    const ARG_HELP: &str =
+        "If passed, the session args are not checked against the signature of the called entry \
+        point before sending the deploy. The check is only made when the session is a stored \
+        contract called by hash";
+
+    pub fn arg() -> Arg<'static, 'static> {
+        Arg::with_name(ARG_NAME)
+            .long(ARG_NAME)
+            .help(ARG_HELP)
+            .required(false)
+            .display_order(DisplayOrder::SkipArgValidation as usize)
+    }
+
+    pub fn get(matches: &ArgMatches) -> bool {
+        matches.is_present(ARG_NAME)
+    }
+}
+
 pub(super) mod session_package_hash {
     use super::*;
 
@@ -13,6 +13,8 @@ enum DisplayOrder {
     NodeAddress,
     RpcId,
     DeployHash,
+    NoVerify,
+    StatusOnly,
 }
 
 /// Handles providing the arg for and retrieval of the deploy hash.
@@ -38,6 +40,29 @@ mod deploy_hash {
     }
 }
 
+/// Handles providing the arg for and retrieval of the status-only flag.
+mod status_only {
+    use super::*;
+
+    const ARG_NAME: &str = "status-only";
+    const ARG_HELP: &str =
+        "If this flag is passed, only the lifecycle stage the deploy has reached on the node is \
+        retrieved, e.g. pending, proposed, executed or expired, rather than the full deploy and \
+        its execution results. There is nothing to verify in such a response";
+
+    pub(super) fn arg() -> Arg<'static, 'static> {
+        Arg::with_name(ARG_NAME)
+            .long(ARG_NAME)
+            .required(false)
+            .help(ARG_HELP)
+            .display_order(DisplayOrder::StatusOnly as usize)
+    }
+
+    pub(super) fn get(matches: &ArgMatches) -> bool {
+        matches.is_present(ARG_NAME)
+    }
+}
+
 impl<'a, 'b> ClientCommand<'a, 'b> for GetDeploy {
     const NAME: &'static str = "get-deploy";
     const ABOUT: &'static str = "Retrieves a deploy from the network";
@@ -52,6 +77,8 @@ impl<'a, 'b> ClientCommand<'a, 'b> for GetDeploy {
             ))
             .arg(common::rpc_id::arg(DisplayOrder::RpcId as usize))
             .arg(deploy_hash::arg())
+            .arg(common::no_verify::arg(DisplayOrder::NoVerify as usize))
+            .arg(status_only::arg())
     }
 
     fn run(matches: &ArgMatches<'_>) -> Result<Success, Error> {
@@ -59,8 +86,25 @@ impl<'a, 'b> ClientCommand<'a, 'b> for GetDeploy {
         let node_address = common::node_address::get(matches);
         let verbosity_level = common::verbose::get(matches);
         let deploy_hash = deploy_hash::get(matches);
+        let verify = common::no_verify::get(matches);
+
+        if status_only::get(matches) {
+            return casper_client::get_deploy_status(
+                maybe_rpc_id,
+                node_address,
+                verbosity_level,
+                deploy_hash,
+            )
+            .map(Success::from);
+        }
 
-        casper_client::get_deploy(maybe_rpc_id, node_address, verbosity_level, deploy_hash)
-            .map(Success::from)
+        casper_client::get_deploy(
+            maybe_rpc_id,
+            node_address,
+            verbosity_level,
+            deploy_hash,
+            verify,
+        )
+        .map(Success::from)
     }
 }
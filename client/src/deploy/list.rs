@@ -2,8 +2,7 @@ use std::str;
 
 use clap::{App, ArgMatches, SubCommand};
 
-use casper_client::{Error, ListDeploysResult};
-use casper_node::rpcs::chain::GetBlockResult;
+use casper_client::Error;
 
 use crate::{command::ClientCommand, common, Success};
 
@@ -41,15 +40,9 @@ impl<'a, 'b> ClientCommand<'a, 'b> for ListDeploys {
         let verbosity_level = common::verbose::get(matches);
         let maybe_block_id = common::block_identifier::get(matches);
 
-        let result =
-            casper_client::get_block(maybe_rpc_id, node_address, verbosity_level, maybe_block_id);
-
-        result.map(|response| {
-            let response_value = response.get_result().cloned().unwrap();
-            let get_block_result =
-                serde_json::from_value::<GetBlockResult>(response_value).expect("should parse");
-            let list = ListDeploysResult::from(get_block_result);
-            Success::Output(serde_json::to_string_pretty(&list).expect("should encode"))
-        })
+        casper_client::list_deploys(maybe_rpc_id, node_address, verbosity_level, maybe_block_id)
+            .map(|list| {
+                Success::Output(serde_json::to_string_pretty(&list).expect("should encode"))
+            })
     }
 }
@@ -17,7 +17,8 @@ impl<'a, 'b> ClientCommand<'a, 'b> for PutDeploy {
             .arg(common::verbose::arg(DisplayOrder::Verbose as usize))
             .arg(common::rpc_id::arg(DisplayOrder::RpcId as usize));
         let subcommand = creation_common::apply_common_session_options(subcommand);
-        let subcommand = creation_common::apply_common_payment_options(subcommand);
+        let subcommand = creation_common::apply_common_payment_options(subcommand)
+            .arg(creation_common::skip_arg_validation::arg());
         creation_common::apply_common_creation_options(subcommand, true)
     }
 
@@ -34,8 +35,17 @@ impl<'a, 'b> ClientCommand<'a, 'b> for PutDeploy {
         let gas_price = creation_common::gas_price::get(matches);
         let dependencies = creation_common::dependencies::get(matches);
         let chain_name = creation_common::chain_name::get(matches);
+        creation_common::chain_name::check_if_from_profile(
+            matches,
+            maybe_rpc_id,
+            node_address,
+            verbosity_level,
+        )?;
 
-        let session_str_params = creation_common::session_str_params(matches);
+        let mut session_str_params = creation_common::session_str_params(matches);
+        if creation_common::skip_arg_validation::get(matches) {
+            session_str_params = session_str_params.with_skip_arg_validation();
+        }
         let payment_str_params = creation_common::payment_str_params(matches);
 
         casper_client::put_deploy(
@@ -9,7 +9,7 @@ use crate::{command::ClientCommand, common, Success};
 pub(super) mod amount {
     use super::*;
 
-    const ARG_NAME: &str = "amount";
+    pub(in crate::deploy) const ARG_NAME: &str = "amount";
     const ARG_SHORT: &str = "a";
     const ARG_VALUE_NAME: &str = "512-BIT INTEGER";
     const ARG_HELP: &str = "The number of motes to transfer";
@@ -83,6 +83,38 @@ pub(super) mod transfer_id {
     }
 }
 
+/// Handles providing the arg for and retrieval of the transfer batch file.
+mod batch_file {
+    use super::*;
+
+    pub(super) const ARG_NAME: &str = "batch-file";
+    const ARG_VALUE_NAME: &str = common::ARG_PATH;
+    const ARG_HELP: &str =
+        "Path to a file of transfers to be made atomically in a single deploy, each charged as a \
+        separate transfer. The file holds either CSV lines of 'target,amount,id' or a JSON array \
+        of objects with 'target', 'amount' and optional 'id' fields, where targets are hex-encoded \
+        public keys, amounts are decimal numbers of motes and ids are user-defined 64-bit \
+        identifiers. Conflicts with --amount, --target-account and --transfer-id";
+
+    pub(super) fn arg() -> Arg<'static, 'static> {
+        Arg::with_name(ARG_NAME)
+            .long(ARG_NAME)
+            .required(false)
+            .value_name(ARG_VALUE_NAME)
+            .help(ARG_HELP)
+            .conflicts_with_all(&[
+                amount::ARG_NAME,
+                target_account::ARG_NAME,
+                transfer_id::ARG_NAME,
+            ])
+            .display_order(DisplayOrder::TransferBatchFile as usize)
+    }
+
+    pub(super) fn get<'a>(matches: &'a ArgMatches) -> Option<&'a str> {
+        matches.value_of(ARG_NAME)
+    }
+}
+
 pub struct Transfer {}
 
 impl<'a, 'b> ClientCommand<'a, 'b> for Transfer {
@@ -95,9 +127,10 @@ impl<'a, 'b> ClientCommand<'a, 'b> for Transfer {
             .display_order(display_order)
             .arg(common::verbose::arg(DisplayOrder::Verbose as usize))
             .arg(common::rpc_id::arg(DisplayOrder::RpcId as usize))
-            .arg(amount::arg())
-            .arg(target_account::arg())
-            .arg(transfer_id::arg());
+            .arg(amount::arg().required_unless(batch_file::ARG_NAME))
+            .arg(target_account::arg().required_unless(batch_file::ARG_NAME))
+            .arg(transfer_id::arg().required_unless(batch_file::ARG_NAME))
+            .arg(batch_file::arg());
         let subcommand = creation_common::apply_common_payment_options(subcommand);
         creation_common::apply_common_creation_options(subcommand, true)
     }
@@ -105,10 +138,6 @@ impl<'a, 'b> ClientCommand<'a, 'b> for Transfer {
     fn run(matches: &ArgMatches<'_>) -> Result<Success, Error> {
         creation_common::show_arg_examples_and_exit_if_required(matches);
 
-        let amount = amount::get(matches);
-        let target_account = target_account::get(matches);
-        let transfer_id = transfer_id::get(matches);
-
         let maybe_rpc_id = common::rpc_id::get(matches);
         let node_address = common::node_address::get(matches);
         let verbosity_level = common::verbose::get(matches);
@@ -119,24 +148,43 @@ impl<'a, 'b> ClientCommand<'a, 'b> for Transfer {
         let gas_price = creation_common::gas_price::get(matches);
         let dependencies = creation_common::dependencies::get(matches);
         let chain_name = creation_common::chain_name::get(matches);
+        creation_common::chain_name::check_if_from_profile(
+            matches,
+            maybe_rpc_id,
+            node_address,
+            verbosity_level,
+        )?;
 
         let payment_str_params = creation_common::payment_str_params(matches);
+        let deploy_str_params = DeployStrParams {
+            secret_key,
+            timestamp,
+            ttl,
+            dependencies,
+            gas_price,
+            chain_name,
+        };
+
+        if let Some(batch_file) = batch_file::get(matches) {
+            return casper_client::transfer_batch(
+                maybe_rpc_id,
+                node_address,
+                verbosity_level,
+                batch_file,
+                deploy_str_params,
+                payment_str_params,
+            )
+            .map(Success::from);
+        }
 
         casper_client::transfer(
             maybe_rpc_id,
             node_address,
             verbosity_level,
-            amount,
-            target_account,
-            transfer_id,
-            DeployStrParams {
-                secret_key,
-                timestamp,
-                ttl,
-                dependencies,
-                gas_price,
-                chain_name,
-            },
+            amount::get(matches),
+            target_account::get(matches),
+            transfer_id::get(matches),
+            deploy_str_params,
             payment_str_params,
         )
         .map(Success::from)
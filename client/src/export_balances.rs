@@ -0,0 +1,104 @@
+use std::str;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+
+use casper_client::Error;
+
+use crate::{command::ClientCommand, common, Success};
+
+/// This struct defines the order in which the args are shown for this subcommand's help message.
+enum DisplayOrder {
+    Verbose,
+    NodeAddress,
+    RpcId,
+    StateRootHash,
+    Output,
+}
+
+/// Handles providing the arg for and retrieval of the output format.
+mod output {
+    use super::*;
+
+    const ARG_NAME: &str = "output";
+    const ARG_SHORT: &str = "o";
+    const ARG_VALUE_NAME: &str = "FORMAT";
+    const ARG_DEFAULT: &str = CSV;
+    const ARG_HELP: &str = "The format in which to output the balances";
+
+    const CSV: &str = "csv";
+    const JSON: &str = "json";
+
+    /// The format in which to output the balances.
+    pub(super) enum Format {
+        Csv,
+        Json,
+    }
+
+    pub(super) fn arg() -> Arg<'static, 'static> {
+        Arg::with_name(ARG_NAME)
+            .long(ARG_NAME)
+            .short(ARG_SHORT)
+            .required(false)
+            .default_value(
+                crate::profile::defaults()
+                    .output_format
+                    .as_deref()
+                    .unwrap_or(ARG_DEFAULT),
+            )
+            .possible_values(&[CSV, JSON])
+            .value_name(ARG_VALUE_NAME)
+            .help(ARG_HELP)
+            .display_order(DisplayOrder::Output as usize)
+    }
+
+    pub(super) fn get(matches: &ArgMatches) -> Format {
+        match matches
+            .value_of(ARG_NAME)
+            .unwrap_or_else(|| panic!("should have {} arg", ARG_NAME))
+        {
+            CSV => Format::Csv,
+            JSON => Format::Json,
+            other => panic!("invalid value for --{}: {}", ARG_NAME, other),
+        }
+    }
+}
+
+pub struct ExportBalances;
+
+impl<'a, 'b> ClientCommand<'a, 'b> for ExportBalances {
+    const NAME: &'static str = "export-balances";
+    const ABOUT: &'static str =
+        "Retrieves the balances of all accounts' main purses from the network at a single state \
+        root hash";
+
+    fn build(display_order: usize) -> App<'a, 'b> {
+        SubCommand::with_name(Self::NAME)
+            .about(Self::ABOUT)
+            .display_order(display_order)
+            .arg(common::verbose::arg(DisplayOrder::Verbose as usize))
+            .arg(common::node_address::arg(
+                DisplayOrder::NodeAddress as usize,
+            ))
+            .arg(common::rpc_id::arg(DisplayOrder::RpcId as usize))
+            .arg(common::state_root_hash::arg(
+                DisplayOrder::StateRootHash as usize,
+            ))
+            .arg(output::arg())
+    }
+
+    fn run(matches: &ArgMatches<'_>) -> Result<Success, Error> {
+        let maybe_rpc_id = common::rpc_id::get(matches);
+        let node_address = common::node_address::get(matches);
+        let verbosity_level = common::verbose::get(matches);
+        let state_root_hash = common::state_root_hash::get(matches);
+        let format = output::get(matches);
+
+        casper_client::export_balances(maybe_rpc_id, node_address, verbosity_level, state_root_hash)
+            .map(|export| match format {
+                output::Format::Csv => Success::Output(export.to_csv()),
+                output::Format::Json => {
+                    Success::Output(serde_json::to_string_pretty(&export).expect("should encode"))
+                }
+            })
+    }
+}
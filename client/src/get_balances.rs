@@ -0,0 +1,100 @@
+use std::{fs, str};
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+
+use casper_client::Error;
+use casper_node::rpcs::state::GetBalances;
+
+use crate::{command::ClientCommand, common, Success};
+
+/// This struct defines the order in which the args are shown for this subcommand's help message.
+enum DisplayOrder {
+    Verbose,
+    NodeAddress,
+    RpcId,
+    StateRootHash,
+    PursesFile,
+}
+
+/// Handles providing the arg for and retrieval of the purses file.
+mod purses_file {
+    use super::*;
+
+    const ARG_NAME: &str = "purses-file";
+    const ARG_SHORT: &str = "p";
+    const ARG_VALUE_NAME: &str = "PATH";
+    const ARG_HELP: &str =
+        "Path to a file listing the purses whose balances are requested, one per line. Each must \
+        be a properly formatted URef \"uref-<HEX STRING>-<THREE DIGIT INTEGER>\" or account hash \
+        \"account-hash-<HEX STRING>\". For an account hash, the balance of the account's main \
+        purse is retrieved. Blank lines are ignored";
+
+    pub(super) fn arg() -> Arg<'static, 'static> {
+        Arg::with_name(ARG_NAME)
+            .long(ARG_NAME)
+            .short(ARG_SHORT)
+            .required(true)
+            .value_name(ARG_VALUE_NAME)
+            .help(ARG_HELP)
+            .display_order(DisplayOrder::PursesFile as usize)
+    }
+
+    pub(super) fn get<'a>(matches: &'a ArgMatches) -> &'a str {
+        matches
+            .value_of(ARG_NAME)
+            .unwrap_or_else(|| panic!("should have {} arg", ARG_NAME))
+    }
+
+    /// Reads the file at `path`, returning its non-blank lines with surrounding whitespace trimmed.
+    pub(super) fn read(path: &str) -> Result<Vec<String>, Error> {
+        let contents = fs::read_to_string(path).map_err(|error| Error::IoError {
+            context: format!("unable to read purses file at '{}'", path),
+            error,
+        })?;
+        Ok(contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect())
+    }
+}
+
+impl<'a, 'b> ClientCommand<'a, 'b> for GetBalances {
+    const NAME: &'static str = "get-balances";
+    const ABOUT: &'static str =
+        "Retrieves the balances of several purses from the network at a single state root hash";
+
+    fn build(display_order: usize) -> App<'a, 'b> {
+        SubCommand::with_name(Self::NAME)
+            .about(Self::ABOUT)
+            .display_order(display_order)
+            .arg(common::verbose::arg(DisplayOrder::Verbose as usize))
+            .arg(common::node_address::arg(
+                DisplayOrder::NodeAddress as usize,
+            ))
+            .arg(common::rpc_id::arg(DisplayOrder::RpcId as usize))
+            .arg(common::state_root_hash::arg(
+                DisplayOrder::StateRootHash as usize,
+            ))
+            .arg(purses_file::arg())
+    }
+
+    fn run(matches: &ArgMatches<'_>) -> Result<Success, Error> {
+        let maybe_rpc_id = common::rpc_id::get(matches);
+        let node_address = common::node_address::get(matches);
+        let verbosity_level = common::verbose::get(matches);
+        let state_root_hash = common::state_root_hash::get(matches);
+        let purses = purses_file::read(purses_file::get(matches))?;
+        let purses: Vec<&str> = purses.iter().map(String::as_str).collect();
+
+        casper_client::get_balances(
+            maybe_rpc_id,
+            node_address,
+            verbosity_level,
+            state_root_hash,
+            &purses,
+        )
+        .map(Success::from)
+    }
+}
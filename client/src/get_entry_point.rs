@@ -0,0 +1,134 @@
+use clap::{App, Arg, ArgMatches, SubCommand};
+
+use casper_client::Error;
+use casper_node::rpcs::state::GetEntryPoint;
+
+use crate::{command::ClientCommand, common, Success};
+
+/// This struct defines the order in which the args are shown for this subcommand's help message.
+enum DisplayOrder {
+    Verbose,
+    NodeAddress,
+    RpcId,
+    StateRootHash,
+    ContractHash,
+    EntryPoint,
+}
+
+/// Handles providing the arg for and retrieval of the optional state root hash.
+mod state_root_hash {
+    use super::*;
+
+    const ARG_NAME: &str = "state-root-hash";
+    const ARG_SHORT: &str = "s";
+    const ARG_VALUE_NAME: &str = "HEX STRING";
+    const ARG_HELP: &str =
+        "Hex-encoded hash of the state root. If not given, the state root of the last block added \
+        to the chain as known at the given node will be used";
+
+    pub(super) fn arg() -> Arg<'static, 'static> {
+        Arg::with_name(ARG_NAME)
+            .long(ARG_NAME)
+            .short(ARG_SHORT)
+            .required(false)
+            .value_name(ARG_VALUE_NAME)
+            .help(ARG_HELP)
+            .display_order(DisplayOrder::StateRootHash as usize)
+    }
+
+    pub(super) fn get<'a>(matches: &'a ArgMatches) -> &'a str {
+        matches.value_of(ARG_NAME).unwrap_or_default()
+    }
+}
+
+/// Handles providing the arg for and retrieval of the contract hash.
+mod contract_hash {
+    use super::*;
+
+    const ARG_NAME: &str = "contract-hash";
+    const ARG_SHORT: &str = "c";
+    const ARG_VALUE_NAME: &str = "HEX STRING OR FORMATTED STRING";
+    const ARG_HELP: &str = "Hash of the stored contract, either hex-encoded or as a formatted key \
+        \"hash-<HEX STRING>\"";
+
+    pub(super) fn arg() -> Arg<'static, 'static> {
+        Arg::with_name(ARG_NAME)
+            .long(ARG_NAME)
+            .short(ARG_SHORT)
+            .required(true)
+            .value_name(ARG_VALUE_NAME)
+            .help(ARG_HELP)
+            .display_order(DisplayOrder::ContractHash as usize)
+    }
+
+    pub(super) fn get<'a>(matches: &'a ArgMatches) -> &'a str {
+        matches
+            .value_of(ARG_NAME)
+            .unwrap_or_else(|| panic!("should have {} arg", ARG_NAME))
+    }
+}
+
+/// Handles providing the arg for and retrieval of the entry point name.
+mod entry_point {
+    use super::*;
+
+    const ARG_NAME: &str = "entry-point";
+    const ARG_SHORT: &str = "e";
+    const ARG_VALUE_NAME: &str = "NAME";
+    const ARG_HELP: &str = "Name of the entry point";
+
+    pub(super) fn arg() -> Arg<'static, 'static> {
+        Arg::with_name(ARG_NAME)
+            .long(ARG_NAME)
+            .short(ARG_SHORT)
+            .required(true)
+            .value_name(ARG_VALUE_NAME)
+            .help(ARG_HELP)
+            .display_order(DisplayOrder::EntryPoint as usize)
+    }
+
+    pub(super) fn get<'a>(matches: &'a ArgMatches) -> &'a str {
+        matches
+            .value_of(ARG_NAME)
+            .unwrap_or_else(|| panic!("should have {} arg", ARG_NAME))
+    }
+}
+
+impl<'a, 'b> ClientCommand<'a, 'b> for GetEntryPoint {
+    const NAME: &'static str = "get-entry-point";
+    const ABOUT: &'static str =
+        "Retrieves the signature of a stored contract's entry point from the network";
+
+    fn build(display_order: usize) -> App<'a, 'b> {
+        SubCommand::with_name(Self::NAME)
+            .about(Self::ABOUT)
+            .display_order(display_order)
+            .arg(common::verbose::arg(DisplayOrder::Verbose as usize))
+            .arg(common::node_address::arg(
+                DisplayOrder::NodeAddress as usize,
+            ))
+            .arg(common::rpc_id::arg(DisplayOrder::RpcId as usize))
+            .arg(state_root_hash::arg())
+            .arg(contract_hash::arg())
+            .arg(entry_point::arg())
+    }
+
+    fn run(matches: &ArgMatches<'_>) -> Result<Success, Error> {
+        let maybe_rpc_id = common::rpc_id::get(matches);
+        let node_address = common::node_address::get(matches);
+        let verbosity_level = common::verbose::get(matches);
+        let maybe_state_root_hash = state_root_hash::get(matches);
+        let contract_hash = contract_hash::get(matches);
+        let entry_point = entry_point::get(matches);
+
+        casper_client::get_entry_point(
+            maybe_rpc_id,
+            node_address,
+            verbosity_level,
+            maybe_state_root_hash,
+            contract_hash,
+            entry_point,
+        )
+        .map(Success::from)
+    }
+}
@@ -0,0 +1,45 @@
+use std::str;
+
+use clap::{App, ArgMatches, SubCommand};
+
+use casper_client::Error;
+
+use crate::{command::ClientCommand, common, Success};
+
+/// This struct defines the order in which the args are shown for this subcommand's help message.
+enum DisplayOrder {
+    Verbose,
+    NodeAddress,
+    RpcId,
+}
+
+pub struct GetNodeVersion;
+
+impl<'a, 'b> ClientCommand<'a, 'b> for GetNodeVersion {
+    const NAME: &'static str = "get-node-version";
+    const ABOUT: &'static str =
+        "Retrieves the build information, protocol version and chainspec hash of the node";
+
+    fn build(display_order: usize) -> App<'a, 'b> {
+        SubCommand::with_name(Self::NAME)
+            .about(Self::ABOUT)
+            .display_order(display_order)
+            .arg(common::verbose::arg(DisplayOrder::Verbose as usize))
+            .arg(common::node_address::arg(
+                DisplayOrder::NodeAddress as usize,
+            ))
+            .arg(common::rpc_id::arg(DisplayOrder::RpcId as usize))
+    }
+
+    fn run(matches: &ArgMatches<'_>) -> Result<Success, Error> {
+        let maybe_rpc_id = common::rpc_id::get(matches);
+        let node_address = common::node_address::get(matches);
+        let verbosity_level = common::verbose::get(matches);
+
+        casper_client::get_node_version(maybe_rpc_id, node_address, verbosity_level).map(
+            |node_version| {
+                Success::Output(serde_json::to_string_pretty(&node_version).expect("should encode"))
+            },
+        )
+    }
+}
@@ -0,0 +1,40 @@
+use std::str;
+
+use clap::{App, ArgMatches, SubCommand};
+
+use casper_client::Error;
+use casper_node::rpcs::info::GetStatus;
+
+use crate::{command::ClientCommand, common, Success};
+
+/// This struct defines the order in which the args are shown for this subcommand's help message.
+enum DisplayOrder {
+    Verbose,
+    NodeAddress,
+    RpcId,
+}
+
+impl<'a, 'b> ClientCommand<'a, 'b> for GetStatus {
+    const NAME: &'static str = "get-status";
+    const ABOUT: &'static str =
+        "Retrieves the status of the node, including the progress of the current era";
+
+    fn build(display_order: usize) -> App<'a, 'b> {
+        SubCommand::with_name(Self::NAME)
+            .about(Self::ABOUT)
+            .display_order(display_order)
+            .arg(common::verbose::arg(DisplayOrder::Verbose as usize))
+            .arg(common::node_address::arg(
+                DisplayOrder::NodeAddress as usize,
+            ))
+            .arg(common::rpc_id::arg(DisplayOrder::RpcId as usize))
+    }
+
+    fn run(matches: &ArgMatches<'_>) -> Result<Success, Error> {
+        let maybe_rpc_id = common::rpc_id::get(matches);
+        let node_address = common::node_address::get(matches);
+        let verbosity_level = common::verbose::get(matches);
+
+        casper_client::get_status(maybe_rpc_id, node_address, verbosity_level).map(Success::from)
+    }
+}
@@ -0,0 +1,108 @@
+use std::str;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+
+use casper_client::Error;
+use casper_node::rpcs::info::GetValidatorChanges;
+
+use crate::{command::ClientCommand, common, Success};
+
+/// This struct defines the order in which the args are shown for this subcommand's help message.
+enum DisplayOrder {
+    Verbose,
+    NodeAddress,
+    RpcId,
+    EraId,
+    ToEraId,
+}
+
+/// Handles providing the arg for and retrieval of the era ID.
+mod era_id {
+    use super::*;
+
+    const ARG_NAME: &str = "era-id";
+    const ARG_SHORT: &str = "e";
+    const ARG_VALUE_NAME: &str = "INTEGER";
+    const ARG_HELP: &str =
+        "The era in which the requested validator changes take effect, or the first of a range of \
+        eras if --to-era-id is also provided";
+
+    pub(super) fn arg() -> Arg<'static, 'static> {
+        Arg::with_name(ARG_NAME)
+            .long(ARG_NAME)
+            .short(ARG_SHORT)
+            .required(true)
+            .value_name(ARG_VALUE_NAME)
+            .help(ARG_HELP)
+            .display_order(DisplayOrder::EraId as usize)
+    }
+
+    pub(super) fn get<'a>(matches: &'a ArgMatches) -> &'a str {
+        matches
+            .value_of(ARG_NAME)
+            .unwrap_or_else(|| panic!("should have {} arg", ARG_NAME))
+    }
+}
+
+/// Handles providing the arg for and retrieval of the last era of the range.
+mod to_era_id {
+    use super::*;
+
+    const ARG_NAME: &str = "to-era-id";
+    const ARG_SHORT: &str = "t";
+    const ARG_VALUE_NAME: &str = "INTEGER";
+    const ARG_HELP: &str =
+        "The last era of the range whose validator changes are requested. If not provided, only \
+        the era given by --era-id is requested";
+
+    pub(super) fn arg() -> Arg<'static, 'static> {
+        Arg::with_name(ARG_NAME)
+            .long(ARG_NAME)
+            .short(ARG_SHORT)
+            .required(false)
+            .value_name(ARG_VALUE_NAME)
+            .help(ARG_HELP)
+            .display_order(DisplayOrder::ToEraId as usize)
+    }
+
+    pub(super) fn get<'a>(matches: &'a ArgMatches) -> &'a str {
+        matches.value_of(ARG_NAME).unwrap_or_default()
+    }
+}
+
+impl<'a, 'b> ClientCommand<'a, 'b> for GetValidatorChanges {
+    const NAME: &'static str = "get-validator-changes";
+    const ABOUT: &'static str =
+        "Retrieves the validators added, removed, banned or evicted, and those whose weight \
+        changed, in one or more eras";
+
+    fn build(display_order: usize) -> App<'a, 'b> {
+        SubCommand::with_name(Self::NAME)
+            .about(Self::ABOUT)
+            .display_order(display_order)
+            .arg(common::verbose::arg(DisplayOrder::Verbose as usize))
+            .arg(common::node_address::arg(
+                DisplayOrder::NodeAddress as usize,
+            ))
+            .arg(common::rpc_id::arg(DisplayOrder::RpcId as usize))
+            .arg(era_id::arg())
+            .arg(to_era_id::arg())
+    }
+
+    fn run(matches: &ArgMatches<'_>) -> Result<Success, Error> {
+        let maybe_rpc_id = common::rpc_id::get(matches);
+        let node_address = common::node_address::get(matches);
+        let verbosity_level = common::verbose::get(matches);
+        let era_id = era_id::get(matches);
+        let maybe_to_era_id = to_era_id::get(matches);
+
+        casper_client::get_validator_changes(
+            maybe_rpc_id,
+            node_address,
+            verbosity_level,
+            era_id,
+            maybe_to_era_id,
+        )
+        .map(Success::from)
+    }
+}
@@ -4,13 +4,21 @@ mod command;
 mod common;
 mod deploy;
 mod docs;
+mod export_balances;
 mod generate_completion;
 mod get_account_info;
 mod get_auction_info;
 mod get_balance;
+mod get_balances;
+mod get_entry_point;
 mod get_era_info_by_switch_block;
+mod get_node_version;
 mod get_state_hash;
+mod get_status;
+mod get_validator_changes;
+mod get_validator_performance;
 mod keygen;
+mod profile;
 mod query_state;
 
 use std::process;
@@ -22,14 +30,19 @@ use casper_node::rpcs::{
     account::PutDeploy,
     chain::{GetBlock, GetBlockTransfers, GetEraInfoBySwitchBlock, GetStateRootHash},
     docs::ListRpcs,
-    info::GetDeploy,
-    state::{GetAccountInfo, GetAuctionInfo, GetBalance, GetItem as QueryState},
+    info::{GetDeploy, GetStatus, GetValidatorChanges, GetValidatorPerformance},
+    state::{
+        GetAccountInfo, GetAuctionInfo, GetBalance, GetBalances, GetEntryPoint,
+        GetItem as QueryState,
+    },
 };
 
 use account_address::GenerateAccountHash as AccountAddress;
 use command::{ClientCommand, Success};
 use deploy::{ListDeploys, MakeDeploy, MakeTransfer, SendDeploy, SignDeploy, Transfer};
+use export_balances::ExportBalances;
 use generate_completion::GenerateCompletion;
+use get_node_version::GetNodeVersion;
 use keygen::Keygen;
 
 const APP_NAME: &str = "Casper client";
@@ -49,9 +62,16 @@ enum DisplayOrder {
     GetStateRootHash,
     QueryState,
     GetBalance,
+    GetBalances,
+    ExportBalances,
     GetAccountInfo,
+    GetEntryPoint,
     GetEraInfo,
     GetAuctionInfo,
+    GetValidatorPerformance,
+    GetValidatorChanges,
+    GetStatus,
+    GetNodeVersion,
     Keygen,
     GenerateCompletion,
     GetRpcs,
@@ -62,6 +82,8 @@ fn cli<'a, 'b>() -> App<'a, 'b> {
     App::new(APP_NAME)
         .version(crate_version!())
         .about(crate_description!())
+        .arg(common::config::arg())
+        .arg(common::profile::arg())
         .subcommand(PutDeploy::build(DisplayOrder::PutDeploy as usize))
         .subcommand(MakeDeploy::build(DisplayOrder::MakeDeploy as usize))
         .subcommand(SignDeploy::build(DisplayOrder::SignDeploy as usize))
@@ -75,7 +97,10 @@ fn cli<'a, 'b>() -> App<'a, 'b> {
         ))
         .subcommand(ListDeploys::build(DisplayOrder::ListDeploys as usize))
         .subcommand(GetBalance::build(DisplayOrder::GetBalance as usize))
+        .subcommand(GetBalances::build(DisplayOrder::GetBalances as usize))
+        .subcommand(ExportBalances::build(DisplayOrder::ExportBalances as usize))
         .subcommand(GetAccountInfo::build(DisplayOrder::GetAccountInfo as usize))
+        .subcommand(GetEntryPoint::build(DisplayOrder::GetEntryPoint as usize))
         .subcommand(GetStateRootHash::build(
             DisplayOrder::GetStateRootHash as usize,
         ))
@@ -84,6 +109,14 @@ fn cli<'a, 'b>() -> App<'a, 'b> {
             DisplayOrder::GetEraInfo as usize,
         ))
         .subcommand(GetAuctionInfo::build(DisplayOrder::GetAuctionInfo as usize))
+        .subcommand(GetValidatorPerformance::build(
+            DisplayOrder::GetValidatorPerformance as usize,
+        ))
+        .subcommand(GetValidatorChanges::build(
+            DisplayOrder::GetValidatorChanges as usize,
+        ))
+        .subcommand(GetStatus::build(DisplayOrder::GetStatus as usize))
+        .subcommand(GetNodeVersion::build(DisplayOrder::GetNodeVersion as usize))
         .subcommand(Keygen::build(DisplayOrder::Keygen as usize))
         .subcommand(GenerateCompletion::build(
             DisplayOrder::GenerateCompletion as usize,
@@ -94,6 +127,12 @@ fn cli<'a, 'b>() -> App<'a, 'b> {
 
 #[tokio::main]
 async fn main() {
+    // The profile's settings are the default values of the args, so must be loaded first.
+    if let Err(error) = profile::init() {
+        println!("{}", error);
+        process::exit(1);
+    }
+
     let arg_matches = cli().get_matches();
     let (result, matches) = match arg_matches.subcommand() {
         (PutDeploy::NAME, Some(matches)) => (PutDeploy::run(matches), matches),
@@ -107,13 +146,22 @@ async fn main() {
         (GetBlockTransfers::NAME, Some(matches)) => (GetBlockTransfers::run(matches), matches),
         (ListDeploys::NAME, Some(matches)) => (ListDeploys::run(matches), matches),
         (GetBalance::NAME, Some(matches)) => (GetBalance::run(matches), matches),
+        (GetBalances::NAME, Some(matches)) => (GetBalances::run(matches), matches),
+        (ExportBalances::NAME, Some(matches)) => (ExportBalances::run(matches), matches),
         (GetAccountInfo::NAME, Some(matches)) => (GetAccountInfo::run(matches), matches),
+        (GetEntryPoint::NAME, Some(matches)) => (GetEntryPoint::run(matches), matches),
         (GetStateRootHash::NAME, Some(matches)) => (GetStateRootHash::run(matches), matches),
         (QueryState::NAME, Some(matches)) => (QueryState::run(matches), matches),
         (GetEraInfoBySwitchBlock::NAME, Some(matches)) => {
             (GetEraInfoBySwitchBlock::run(matches), matches)
         }
         (GetAuctionInfo::NAME, Some(matches)) => (GetAuctionInfo::run(matches), matches),
+        (GetValidatorPerformance::NAME, Some(matches)) => {
+            (GetValidatorPerformance::run(matches), matches)
+        }
+        (GetValidatorChanges::NAME, Some(matches)) => (GetValidatorChanges::run(matches), matches),
+        (GetStatus::NAME, Some(matches)) => (GetStatus::run(matches), matches),
+        (GetNodeVersion::NAME, Some(matches)) => (GetNodeVersion::run(matches), matches),
         (Keygen::NAME, Some(matches)) => (Keygen::run(matches), matches),
         (GenerateCompletion::NAME, Some(matches)) => (GenerateCompletion::run(matches), matches),
         (ListRpcs::NAME, Some(matches)) => (ListRpcs::run(matches), matches),
@@ -139,6 +187,10 @@ async fn main() {
             casper_client::pretty_print_at_level(&error, verbosity_level);
             process::exit(1);
         }
+        Err(error @ Error::InvalidResponse(_)) => {
+            println!("{}", error);
+            process::exit(common::no_verify::VERIFICATION_FAILED_EXIT_CODE);
+        }
         Err(error) => {
             println!("{}", error);
             process::exit(1);
@@ -0,0 +1,67 @@
+//! Loading of the profile selected from the client config file, whose settings replace the
+//! built-in defaults of the corresponding args.
+
+use std::{env, path::Path};
+
+use clap::{App, AppSettings, ArgMatches};
+use once_cell::sync::OnceCell;
+
+use casper_client::{ClientConfig, Error, Profile, PROFILE_ENV_VAR};
+
+use crate::common;
+
+static DEFAULTS: OnceCell<Defaults> = OnceCell::new();
+
+/// The settings of the selected profile, as default values of the corresponding args.
+#[derive(Default)]
+pub(crate) struct Defaults {
+    pub(crate) node_address: Option<String>,
+    pub(crate) chain_name: Option<String>,
+    pub(crate) ttl: Option<String>,
+    pub(crate) gas_price: Option<String>,
+    pub(crate) output_format: Option<String>,
+}
+
+impl From<Profile> for Defaults {
+    fn from(profile: Profile) -> Self {
+        Defaults {
+            node_address: profile.node_address,
+            chain_name: profile.chain_name,
+            ttl: profile.ttl,
+            gas_price: profile.gas_price.map(|gas_price| gas_price.to_string()),
+            output_format: profile
+                .output_format
+                .map(|output_format| output_format.to_string()),
+        }
+    }
+}
+
+/// Loads the profile selected via the `--config` and `--profile` args and the `CASPER_PROFILE`
+/// env var.
+///
+/// Must be called before the CLI is built, as the profile's settings become default values of its
+/// args.  Only the args preceding the subcommand are parsed here; if they can't be parsed, the
+/// default config file and profile are used, leaving the error to be reported by the CLI.
+pub(crate) fn init() -> Result<(), Error> {
+    let matches = App::new(crate::APP_NAME)
+        .setting(AppSettings::AllowExternalSubcommands)
+        .setting(AppSettings::DisableHelpFlags)
+        .setting(AppSettings::DisableVersion)
+        .arg(common::config::arg())
+        .arg(common::profile::arg())
+        .get_matches_safe()
+        .unwrap_or_else(|_| ArgMatches::default());
+    let config = ClientConfig::load_or_default(common::config::get(&matches).map(Path::new))?;
+    let maybe_profile_env_var = env::var(PROFILE_ENV_VAR).ok();
+    let profile = config.select_profile(
+        common::profile::get(&matches),
+        maybe_profile_env_var.as_deref(),
+    )?;
+    let _ = DEFAULTS.set(Defaults::from(profile));
+    Ok(())
+}
+
+/// Returns the settings of the selected profile, or no settings if none has been loaded.
+pub(crate) fn defaults() -> &'static Defaults {
+    DEFAULTS.get_or_init(Defaults::default)
+}
@@ -16,6 +16,7 @@ enum DisplayOrder {
     StateRootHash,
     Key,
     Path,
+    Raw,
 }
 
 /// Handles providing the arg for and retrieval of the key.
@@ -74,18 +75,23 @@ mod key {
     }
 }
 
-/// Handles providing the arg for and retrieval of the key.
+/// Handles providing the arg for and retrieval of the path.
 mod path {
     use super::*;
 
     const ARG_NAME: &str = "query-path";
+    const ARG_ALIAS: &str = "path";
     const ARG_SHORT: &str = "q";
     const ARG_VALUE_NAME: &str = "PATH/FROM/KEY";
-    const ARG_HELP: &str = "The path from the key of the query";
+    const ARG_HELP: &str =
+        "The path from the key of the query, with components separated by '/'. Each component is \
+        resolved by the node as a named key of the account or contract reached so far, with keys \
+        stored under URefs being followed to the value they refer to";
 
     pub(super) fn arg() -> Arg<'static, 'static> {
         Arg::with_name(ARG_NAME)
             .long(ARG_NAME)
+            .visible_alias(ARG_ALIAS)
             .short(ARG_SHORT)
             .required(false)
             .value_name(ARG_VALUE_NAME)
@@ -98,6 +104,30 @@ mod path {
     }
 }
 
+/// Handles providing the arg for and retrieval of the raw flag.
+mod raw {
+    use super::*;
+
+    const ARG_NAME: &str = "raw";
+    const ARG_HELP: &str =
+        "Requests the hex-encoded, bytesrepr-serialized stored value from the node along with the \
+        merkle proof, and prints the response as received. If not set, any CLValue in the \
+        response will have its \"parsed\" field rendered from the hex-encoded bytes, with values \
+        which can't be represented as JSON shown as a tagged hex blob";
+
+    pub(super) fn arg() -> Arg<'static, 'static> {
+        Arg::with_name(ARG_NAME)
+            .long(ARG_NAME)
+            .required(false)
+            .help(ARG_HELP)
+            .display_order(DisplayOrder::Raw as usize)
+    }
+
+    pub(super) fn get(matches: &ArgMatches) -> bool {
+        matches.is_present(ARG_NAME)
+    }
+}
+
 impl<'a, 'b> ClientCommand<'a, 'b> for GetItem {
     const NAME: &'static str = "query-state";
     const ABOUT: &'static str = "Retrieves a stored value from the network";
@@ -116,6 +146,7 @@ impl<'a, 'b> ClientCommand<'a, 'b> for GetItem {
             ))
             .arg(key::arg())
             .arg(path::arg())
+            .arg(raw::arg())
     }
 
     fn run(matches: &ArgMatches<'_>) -> Result<Success, Error> {
@@ -125,15 +156,26 @@ impl<'a, 'b> ClientCommand<'a, 'b> for GetItem {
         let state_root_hash = common::state_root_hash::get(matches);
         let key = key::get(matches)?;
         let path = path::get(matches);
+        let raw = raw::get(matches);
 
-        casper_client::get_item(
+        let response = casper_client::get_item(
             maybe_rpc_id,
             node_address,
             verbosity_level,
             state_root_hash,
             &key,
             path,
-        )
-        .map(Success::from)
+            raw,
+        )?;
+
+        if raw {
+            return Ok(Success::from(response));
+        }
+
+        let response_value = serde_json::to_value(&response).expect("should encode to JSON");
+        let rendered = casper_client::render_cl_values(&response_value);
+        Ok(Success::Output(
+            serde_json::to_string_pretty(&rendered).expect("should encode"),
+        ))
     }
 }
@@ -2,7 +2,8 @@ use std::{convert::Infallible, fs, net::SocketAddr, sync::Arc, time::Duration};
 
 use futures::{channel::oneshot, future};
 use hyper::{Body, Response, Server};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use tempfile::TempDir;
 use tokio::{sync::Mutex, task, task::JoinHandle};
 use tower::builder::ServiceBuilder;
@@ -26,6 +27,15 @@ const VALID_PURSE_UREF: &str =
 const VALID_STATE_ROOT_HASH: &str =
     "55db08058acb54c295b115cbd9b282eb2862e76d5bb8493bb80c0598a50a12a5";
 
+/// The JSON-RPC error code returned by the node when a requested block is not known.
+const NO_SUCH_BLOCK: i64 = -32001;
+/// The JSON-RPC error code returned by the node when too many balances are requested at once.
+const TOO_MANY_BALANCES_REQUESTED: i64 = -32010;
+/// The JSON-RPC error code returned by the node when an invalid range of eras is requested.
+const INVALID_ERA_RANGE: i64 = -32011;
+/// The JSON-RPC error code returned by the node when balance exports are disabled.
+const BALANCES_EXPORT_DISABLED: i64 = -32012;
+
 const DEFAULT_RATE_LIMIT: u64 = 1;
 const DEFAULT_RATE_PER: Duration = Duration::from_secs(1);
 
@@ -49,6 +59,34 @@ fn test_filter_without_params(
         .map(|builder: Builder| builder.success(()).unwrap())
 }
 
+/// Responds to JSON-RPC requests for `method` with an error having the given code and message.
+fn test_filter_with_error(
+    method: &'static str,
+    code: i64,
+    message: &'static str,
+) -> impl Filter<Extract = (Response<Body>,), Error = Rejection> + Copy {
+    warp_json_rpc::filters::json_rpc()
+        .and(warp_json_rpc::filters::method(method))
+        .map(move |builder: Builder| {
+            builder
+                .error(warp_json_rpc::Error::custom(code, message))
+                .unwrap()
+        })
+}
+
+/// Responds to JSON-RPC requests for `method` with the given JSON-encoded result.
+fn test_filter_with_result(
+    method: &'static str,
+    result: &'static str,
+) -> impl Filter<Extract = (Response<Body>,), Error = Rejection> + Copy {
+    warp_json_rpc::filters::json_rpc()
+        .and(warp_json_rpc::filters::method(method))
+        .map(move |builder: Builder| {
+            let result: Value = serde_json::from_str(result).unwrap();
+            builder.success(result).unwrap()
+        })
+}
+
 type ServerJoiner = Option<Arc<Mutex<JoinHandle<Result<(), hyper::Error>>>>>;
 
 struct MockServerHandle {
@@ -89,6 +127,26 @@ impl MockServerHandle {
         )
     }
 
+    /// Will spawn a server on localhost and respond to JSON-RPC requests with an error.
+    fn spawn_with_error(method: &'static str, code: i64, message: &'static str) -> Self {
+        Self::spawn_with_filter(
+            test_filter_with_error(method, code, message),
+            DEFAULT_RATE_LIMIT,
+            DEFAULT_RATE_PER,
+        )
+    }
+
+    /// Will spawn a server on localhost and respond to JSON-RPC requests for `method` with
+    /// `result`.
+    fn spawn_with_result<T: Serialize>(method: &'static str, result: &T) -> Self {
+        let result = Box::leak(serde_json::to_string(result).unwrap().into_boxed_str());
+        Self::spawn_with_filter(
+            test_filter_with_result(method, result),
+            DEFAULT_RATE_LIMIT,
+            DEFAULT_RATE_PER,
+        )
+    }
+
     fn spawn_with_filter<F>(filter: F, rate: u64, per: Duration) -> Self
     where
         F: Filter<Extract = (Response<Body>,), Error = Rejection> + Send + Sync + 'static + Copy,
@@ -125,8 +183,38 @@ impl MockServerHandle {
             .map_err(ErrWrapper)
     }
 
+    fn get_balances(&self, state_root_hash: &str, purses: &[&str]) -> Result<(), ErrWrapper> {
+        casper_client::get_balances("1", &self.url(), 0, state_root_hash, purses)
+            .map(|_| ())
+            .map_err(ErrWrapper)
+    }
+
+    fn get_validator_performance(
+        &self,
+        era_id: &str,
+        maybe_to_era_id: &str,
+        maybe_public_key: &str,
+    ) -> Result<(), ErrWrapper> {
+        casper_client::get_validator_performance(
+            "1",
+            &self.url(),
+            0,
+            era_id,
+            maybe_to_era_id,
+            maybe_public_key,
+        )
+        .map(|_| ())
+        .map_err(ErrWrapper)
+    }
+
+    fn get_validator_changes(&self, era_id: &str, maybe_to_era_id: &str) -> Result<(), ErrWrapper> {
+        casper_client::get_validator_changes("1", &self.url(), 0, era_id, maybe_to_era_id)
+            .map(|_| ())
+            .map_err(ErrWrapper)
+    }
+
     fn get_deploy(&self, deploy_hash: &str) -> Result<(), ErrWrapper> {
-        casper_client::get_deploy("1", &self.url(), 0, deploy_hash)
+        casper_client::get_deploy("1", &self.url(), 0, deploy_hash, true)
             .map(|_| ())
             .map_err(ErrWrapper)
     }
@@ -138,13 +226,19 @@ impl MockServerHandle {
     }
 
     fn get_block(&self, maybe_block_id: &str) -> Result<(), ErrWrapper> {
-        casper_client::get_block("1", &self.url(), 0, maybe_block_id)
+        casper_client::get_block("1", &self.url(), 0, maybe_block_id, true)
+            .map(|_| ())
+            .map_err(ErrWrapper)
+    }
+
+    fn get_block_transfers(&self, maybe_block_id: &str) -> Result<(), ErrWrapper> {
+        casper_client::get_block_transfers("1", &self.url(), 0, maybe_block_id, true)
             .map(|_| ())
             .map_err(ErrWrapper)
     }
 
     fn get_item(&self, state_root_hash: &str, key: &str, path: &str) -> Result<(), ErrWrapper> {
-        casper_client::get_item("1", &self.url(), 0, state_root_hash, key, path)
+        casper_client::get_item("1", &self.url(), 0, state_root_hash, key, path, false)
             .map(|_| ())
             .map_err(ErrWrapper)
     }
@@ -212,6 +306,25 @@ impl Drop for MockServerHandle {
     }
 }
 
+/// Returns the JSON encoding of `value` with the field at `pointer` replaced by `replacement`.
+fn corrupted<T: Serialize>(value: &T, pointer: &str, replacement: Value) -> Value {
+    let mut json = serde_json::to_value(value).unwrap();
+    *json
+        .pointer_mut(pointer)
+        .unwrap_or_else(|| panic!("should have {}", pointer)) = replacement;
+    json
+}
+
+/// Returns the string at `pointer` in the JSON encoding of `value`.
+fn json_str<T: Serialize>(value: &T, pointer: &str) -> String {
+    serde_json::to_value(value)
+        .unwrap()
+        .pointer(pointer)
+        .and_then(Value::as_str)
+        .unwrap_or_else(|| panic!("should have string at {}", pointer))
+        .to_string()
+}
+
 #[derive(Debug)]
 struct ErrWrapper(pub Error);
 
@@ -326,7 +439,13 @@ mod get_balance {
         let server_handle = MockServerHandle::spawn::<GetBalanceParams>(GetBalance::METHOD);
         assert_eq!(
             server_handle.get_balance(VALID_STATE_ROOT_HASH, ""),
-            Err(Error::FailedToParseURef("purse_uref", URefFromStrError::InvalidPrefix).into())
+            Err(Error::FailedToParseURef(
+                "purse_uref",
+                URefFromStrError::InvalidPrefix {
+                    expected_one_of: &["uref-"]
+                }
+            )
+            .into())
         );
     }
 
@@ -344,6 +463,242 @@ mod get_balance {
     }
 }
 
+mod get_balances {
+    use casper_node::rpcs::state::{GetBalances, GetBalancesParams};
+
+    use super::*;
+
+    const VALID_ACCOUNT_HASH: &str =
+        "account-hash-0102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f20";
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_fail_with_empty_state_root_hash() {
+        let server_handle = MockServerHandle::spawn::<GetBalancesParams>(GetBalances::METHOD);
+        assert_eq!(
+            server_handle.get_balances("", &[VALID_PURSE_UREF, VALID_ACCOUNT_HASH]),
+            Err(Error::CryptoError {
+                context: "state_root_hash",
+                error: CryptoError::FromHex(FromHexError::InvalidStringLength)
+            }
+            .into())
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_fail_with_too_many_purses() {
+        let server_handle = MockServerHandle::spawn_with_error(
+            GetBalances::METHOD,
+            TOO_MANY_BALANCES_REQUESTED,
+            "too many balances requested",
+        );
+        match server_handle.get_balances(
+            VALID_STATE_ROOT_HASH,
+            &[VALID_PURSE_UREF, VALID_ACCOUNT_HASH],
+        ) {
+            Err(ErrWrapper(Error::ResponseIsError(error))) => {
+                assert_eq!(error.code, TOO_MANY_BALANCES_REQUESTED);
+                assert_eq!(error.message, "too many balances requested");
+            }
+            other => panic!("incorrect error returned from client {:?}", other),
+        }
+    }
+}
+
+mod get_validator_performance {
+    use casper_node::rpcs::info::{GetValidatorPerformance, GetValidatorPerformanceParams};
+
+    use super::*;
+
+    const VALID_PUBLIC_KEY: &str =
+        "01522ef6c89038019cb7af05c340623804392dd2bb1f4dab5e4a9c3ab752fc0179";
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_succeed_with_single_era() {
+        let server_handle = MockServerHandle::spawn::<GetValidatorPerformanceParams>(
+            GetValidatorPerformance::METHOD,
+        );
+        assert_eq!(server_handle.get_validator_performance("3", "", ""), Ok(()));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_succeed_with_era_range_and_public_key() {
+        let server_handle = MockServerHandle::spawn::<GetValidatorPerformanceParams>(
+            GetValidatorPerformance::METHOD,
+        );
+        assert_eq!(
+            server_handle.get_validator_performance("3", "5", VALID_PUBLIC_KEY),
+            Ok(())
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_fail_with_invalid_era_id() {
+        let server_handle = MockServerHandle::spawn::<GetValidatorPerformanceParams>(
+            GetValidatorPerformance::METHOD,
+        );
+        match server_handle.get_validator_performance("<not an era>", "", "") {
+            Err(ErrWrapper(Error::FailedToParseInt("era_id", _))) => {}
+            other => panic!("incorrect error returned from client {:?}", other),
+        }
+        match server_handle.get_validator_performance("3", "<not an era>", "") {
+            Err(ErrWrapper(Error::FailedToParseInt("to_era_id", _))) => {}
+            other => panic!("incorrect error returned from client {:?}", other),
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_fail_with_invalid_public_key() {
+        let server_handle = MockServerHandle::spawn::<GetValidatorPerformanceParams>(
+            GetValidatorPerformance::METHOD,
+        );
+        assert_eq!(
+            server_handle.get_validator_performance("3", "", "<not a public key>"),
+            Err(Error::FailedToParseKey.into())
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_fail_with_invalid_era_range() {
+        let server_handle = MockServerHandle::spawn_with_error(
+            GetValidatorPerformance::METHOD,
+            INVALID_ERA_RANGE,
+            "invalid era range: 5 is after 3",
+        );
+        match server_handle.get_validator_performance("5", "3", "") {
+            Err(ErrWrapper(Error::ResponseIsError(error))) => {
+                assert_eq!(error.code, INVALID_ERA_RANGE);
+                assert_eq!(error.message, "invalid era range: 5 is after 3");
+            }
+            other => panic!("incorrect error returned from client {:?}", other),
+        }
+    }
+}
+
+mod get_validator_changes {
+    use casper_node::rpcs::info::{GetValidatorChanges, GetValidatorChangesParams};
+
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_succeed_with_single_era() {
+        let server_handle =
+            MockServerHandle::spawn::<GetValidatorChangesParams>(GetValidatorChanges::METHOD);
+        assert_eq!(server_handle.get_validator_changes("3", ""), Ok(()));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_succeed_with_era_range() {
+        let server_handle =
+            MockServerHandle::spawn::<GetValidatorChangesParams>(GetValidatorChanges::METHOD);
+        assert_eq!(server_handle.get_validator_changes("3", "5"), Ok(()));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_fail_with_invalid_era_id() {
+        let server_handle =
+            MockServerHandle::spawn::<GetValidatorChangesParams>(GetValidatorChanges::METHOD);
+        match server_handle.get_validator_changes("<not an era>", "") {
+            Err(ErrWrapper(Error::FailedToParseInt("era_id", _))) => {}
+            other => panic!("incorrect error returned from client {:?}", other),
+        }
+        match server_handle.get_validator_changes("3", "<not an era>") {
+            Err(ErrWrapper(Error::FailedToParseInt("to_era_id", _))) => {}
+            other => panic!("incorrect error returned from client {:?}", other),
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_fail_with_invalid_era_range() {
+        let server_handle = MockServerHandle::spawn_with_error(
+            GetValidatorChanges::METHOD,
+            INVALID_ERA_RANGE,
+            "invalid era range: 5 is after 3",
+        );
+        match server_handle.get_validator_changes("5", "3") {
+            Err(ErrWrapper(Error::ResponseIsError(error))) => {
+                assert_eq!(error.code, INVALID_ERA_RANGE);
+                assert_eq!(error.message, "invalid era range: 5 is after 3");
+            }
+            other => panic!("incorrect error returned from client {:?}", other),
+        }
+    }
+}
+
+mod export_balances {
+    use casper_node::{
+        crypto::hash::Digest,
+        rpcs::{
+            docs::DocExample,
+            state::{AccountBalance, AccountBalanceError, GetAllBalances, GetAllBalancesResult},
+        },
+    };
+    use casper_types::U512;
+
+    use super::*;
+
+    fn account_balance(seed: u8, balance_value: u64) -> AccountBalance {
+        AccountBalance {
+            account_hash: format!("account-hash-{}", hex::encode([seed; 32])),
+            main_purse: format!("uref-{}-007", hex::encode([seed; 32])),
+            balance_value: U512::from(balance_value),
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_export_balances() {
+        let result = GetAllBalancesResult {
+            api_version: GetAllBalancesResult::doc_example().api_version,
+            state_root_hash: Digest::from_hex(VALID_STATE_ROOT_HASH).unwrap(),
+            balances: vec![
+                account_balance(1, 0),
+                account_balance(2, 1_000),
+                account_balance(3, 2_500),
+            ],
+            errors: vec![AccountBalanceError {
+                account_hash: format!("account-hash-{}", hex::encode([4; 32])),
+                error: "malformed account".to_string(),
+            }],
+            next_page_token: None,
+        };
+        let server_handle = MockServerHandle::spawn_with_result(GetAllBalances::METHOD, &result);
+
+        let export =
+            casper_client::export_balances("1", &server_handle.url(), 0, VALID_STATE_ROOT_HASH)
+                .unwrap();
+        assert_eq!(export.total_balance, U512::from(3_500));
+        assert_eq!(export.balances.len(), 3);
+        assert_eq!(export.errors.len(), 1);
+        assert_eq!(export.to_csv().lines().count(), 5);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_fail_when_export_disabled() {
+        let server_handle = MockServerHandle::spawn_with_error(
+            GetAllBalances::METHOD,
+            BALANCES_EXPORT_DISABLED,
+            "balance exports are disabled on this node",
+        );
+        match casper_client::export_balances("1", &server_handle.url(), 0, VALID_STATE_ROOT_HASH) {
+            Err(Error::ResponseIsError(error)) => {
+                assert_eq!(error.code, BALANCES_EXPORT_DISABLED);
+            }
+            other => panic!("incorrect error returned from client {:?}", other),
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_fail_with_empty_state_root_hash() {
+        let server_handle = MockServerHandle::spawn_with_result(
+            GetAllBalances::METHOD,
+            GetAllBalancesResult::doc_example(),
+        );
+        match casper_client::export_balances("1", &server_handle.url(), 0, "") {
+            Err(Error::CryptoError { context, .. }) => assert_eq!(context, "state_root_hash"),
+            other => panic!("incorrect error returned from client {:?}", other),
+        }
+    }
+}
+
 mod get_state_root_hash {
     use super::*;
 
@@ -384,8 +739,16 @@ mod get_state_root_hash {
 }
 
 mod get_block {
+    use serde_json::json;
+
     use casper_client::ValidateResponseError;
-    use casper_node::rpcs::chain::{GetBlock, GetBlockParams};
+    use casper_node::{
+        rpcs::{
+            chain::{GetBlock, GetBlockParams, GetBlockResult},
+            docs::DocExample,
+        },
+        types::BlockValidationError,
+    };
 
     use super::*;
 
@@ -433,14 +796,275 @@ mod get_block {
             other => panic!("incorrect error returned from client {:?}", other),
         }
     }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_fail_with_unknown_block() {
+        let server_handle =
+            MockServerHandle::spawn_with_error(GetBlock::METHOD, NO_SUCH_BLOCK, "block not known");
+        match server_handle.get_block("1") {
+            Err(ErrWrapper(Error::ResponseIsError(error))) => {
+                assert_eq!(error.code, NO_SUCH_BLOCK);
+                assert_eq!(error.message, "block not known");
+            }
+            other => panic!("incorrect error returned from client {:?}", other),
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_verify_valid_block() {
+        let expected = GetBlockResult::doc_example();
+        let server_handle = MockServerHandle::spawn_with_result(GetBlock::METHOD, expected);
+        let block_hash = json_str(expected, "/block/hash");
+        assert_eq!(server_handle.get_block(&block_hash), Ok(()));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_fail_to_verify_block_with_corrupted_header() {
+        let expected = GetBlockResult::doc_example();
+        let block_hash = json_str(expected, "/block/hash");
+        let height = serde_json::to_value(expected).unwrap()["block"]["header"]["height"]
+            .as_u64()
+            .unwrap();
+        let result = corrupted(expected, "/block/header/height", json!(height + 1));
+        let server_handle = MockServerHandle::spawn_with_result(GetBlock::METHOD, &result);
+        match server_handle.get_block(&block_hash) {
+            Err(ErrWrapper(Error::InvalidResponse(
+                ValidateResponseError::BlockValidationError(
+                    BlockValidationError::UnexpectedBlockHash { .. },
+                ),
+            ))) => {}
+            other => panic!("incorrect error returned from client {:?}", other),
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_fail_to_verify_block_with_corrupted_body() {
+        let expected = GetBlockResult::doc_example();
+        let block_hash = json_str(expected, "/block/hash");
+        let result = corrupted(
+            expected,
+            "/block/body/deploy_hashes",
+            json!([hex::encode([7; 32])]),
+        );
+        let server_handle = MockServerHandle::spawn_with_result(GetBlock::METHOD, &result);
+        match server_handle.get_block(&block_hash) {
+            Err(ErrWrapper(Error::InvalidResponse(
+                ValidateResponseError::BlockValidationError(
+                    BlockValidationError::UnexpectedBodyHash { .. },
+                ),
+            ))) => {}
+            other => panic!("incorrect error returned from client {:?}", other),
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_fail_to_verify_block_other_than_requested() {
+        let server_handle =
+            MockServerHandle::spawn_with_result(GetBlock::METHOD, GetBlockResult::doc_example());
+        assert_eq!(
+            server_handle.get_block(VALID_STATE_ROOT_HASH),
+            Err(ErrWrapper(Error::InvalidResponse(
+                ValidateResponseError::UnexpectedBlockHash
+            )))
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_not_verify_block_if_disabled() {
+        let expected = GetBlockResult::doc_example();
+        let result = corrupted(
+            expected,
+            "/block/body/deploy_hashes",
+            json!([hex::encode([7; 32])]),
+        );
+        let server_handle = MockServerHandle::spawn_with_result(GetBlock::METHOD, &result);
+        let response =
+            casper_client::get_block("1", &server_handle.url(), 0, VALID_STATE_ROOT_HASH, false);
+        assert!(response.is_ok());
+    }
+}
+
+mod get_block_transfers {
+    use casper_client::ValidateResponseError;
+    use casper_node::rpcs::{
+        chain::{GetBlockTransfers, GetBlockTransfersParams, GetBlockTransfersResult},
+        docs::DocExample,
+    };
+
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_succeed_with_valid_block_hash() {
+        let server_handle =
+            MockServerHandle::spawn::<GetBlockTransfersParams>(GetBlockTransfers::METHOD);
+        // The mock responds without a block hash, so verification is disabled.
+        let response = casper_client::get_block_transfers(
+            "1",
+            &server_handle.url(),
+            0,
+            VALID_STATE_ROOT_HASH,
+            false,
+        );
+        assert_eq!(response.map(|_| ()).map_err(ErrWrapper), Ok(()));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_succeed_with_valid_block_height() {
+        let server_handle =
+            MockServerHandle::spawn::<GetBlockTransfersParams>(GetBlockTransfers::METHOD);
+        assert_eq!(server_handle.get_block_transfers("1"), Ok(()));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_succeed_with_empty_block_id() {
+        let server_handle = MockServerHandle::spawn_without_params(GetBlockTransfers::METHOD);
+        assert_eq!(server_handle.get_block_transfers(""), Ok(()));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_fail_to_verify_response_without_requested_block_hash() {
+        let server_handle =
+            MockServerHandle::spawn::<GetBlockTransfersParams>(GetBlockTransfers::METHOD);
+        assert_eq!(
+            server_handle.get_block_transfers(VALID_STATE_ROOT_HASH),
+            Err(ErrWrapper(Error::InvalidResponse(
+                ValidateResponseError::NoBlockInResponse
+            )))
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_verify_transfers_of_requested_block() {
+        let expected = GetBlockTransfersResult::doc_example();
+        let server_handle =
+            MockServerHandle::spawn_with_result(GetBlockTransfers::METHOD, expected);
+        let block_hash = json_str(expected, "/block_hash");
+        assert_eq!(server_handle.get_block_transfers(&block_hash), Ok(()));
+        assert_eq!(server_handle.get_block_transfers("1"), Ok(()));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_fail_to_verify_transfers_of_other_block() {
+        let server_handle = MockServerHandle::spawn_with_result(
+            GetBlockTransfers::METHOD,
+            GetBlockTransfersResult::doc_example(),
+        );
+        assert_eq!(
+            server_handle.get_block_transfers(VALID_STATE_ROOT_HASH),
+            Err(ErrWrapper(Error::InvalidResponse(
+                ValidateResponseError::UnexpectedBlockHash
+            )))
+        );
+        let response = casper_client::get_block_transfers(
+            "1",
+            &server_handle.url(),
+            0,
+            VALID_STATE_ROOT_HASH,
+            false,
+        );
+        assert!(response.is_ok());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_fail_with_invalid_block_id() {
+        let server_handle =
+            MockServerHandle::spawn::<GetBlockTransfersParams>(GetBlockTransfers::METHOD);
+        match server_handle.get_block_transfers("<not a valid hash>") {
+            Err(ErrWrapper(Error::FailedToParseInt("block_identifier", _))) => {}
+            other => panic!("incorrect error returned from client {:?}", other),
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_fail_with_unknown_block() {
+        let server_handle = MockServerHandle::spawn_with_error(
+            GetBlockTransfers::METHOD,
+            NO_SUCH_BLOCK,
+            "block not known",
+        );
+        match server_handle.get_block_transfers("1") {
+            Err(ErrWrapper(Error::ResponseIsError(error))) => {
+                assert_eq!(error.code, NO_SUCH_BLOCK);
+                assert_eq!(error.message, "block not known");
+            }
+            other => panic!("incorrect error returned from client {:?}", other),
+        }
+    }
 }
 
 mod get_item {
+    use std::convert::TryFrom;
+
     use casper_client::ValidateResponseError;
-    use casper_node::rpcs::state::{GetItem, GetItemParams};
+    use casper_execution_engine::{
+        shared::{newtypes::Blake2bHash, stored_value::StoredValue},
+        storage::trie::{
+            merkle_proof::{TrieMerkleProof, TrieMerkleProofStep},
+            Pointer,
+        },
+    };
+    use casper_node::{
+        rpcs::{
+            docs::DocExample,
+            state::{GetItem, GetItemParams, GetItemResult},
+        },
+        types::json_compatibility,
+    };
+    use casper_types::{
+        bytesrepr::{self, ToBytes},
+        CLValue, Key, KeyFromStrError,
+    };
 
     use super::*;
 
+    /// The bytes of a stored value, serialized verbatim so that a trie leaf holding the value can
+    /// be rehashed without decoding it.
+    #[derive(Clone)]
+    struct RawValue(Vec<u8>);
+
+    impl ToBytes for RawValue {
+        fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+            Ok(self.0.clone())
+        }
+
+        fn serialized_length(&self) -> usize {
+            self.0.len()
+        }
+    }
+
+    /// Returns a query result for `VALID_PURSE_UREF` including the raw stored value, along with the
+    /// state root hash which its merkle proof is for.
+    fn result_with_raw_stored_value() -> (GetItemResult, Blake2bHash) {
+        let key = Key::from_formatted_str(VALID_PURSE_UREF).unwrap();
+        let stored_value = StoredValue::CLValue(CLValue::from_t(42u64).unwrap());
+        let proof_steps = vec![
+            TrieMerkleProofStep::node(0, vec![(1, Pointer::LeafPointer(Blake2bHash::new(&[1])))]),
+            TrieMerkleProofStep::extension(vec![2, 3]),
+        ];
+        let proof = TrieMerkleProof::new(key, stored_value.clone(), proof_steps.into());
+        let state_root_hash = proof.compute_state_hash().unwrap();
+
+        let result = GetItemResult {
+            api_version: GetItemResult::doc_example().api_version,
+            stored_value: json_compatibility::StoredValue::try_from(&stored_value).unwrap(),
+            merkle_proof: hex::encode(vec![proof].to_bytes().unwrap()),
+            raw_stored_value: Some(hex::encode(stored_value.to_bytes().unwrap())),
+        };
+        (result, state_root_hash)
+    }
+
+    /// Recomputes the state root hash from the raw stored value and the steps of the last merkle
+    /// proof, i.e. independently of the JSON-encoded stored value.
+    fn rehash_raw_stored_value(result: &GetItemResult) -> Blake2bHash {
+        let proofs: Vec<TrieMerkleProof<Key, StoredValue>> =
+            bytesrepr::deserialize(hex::decode(&result.merkle_proof).unwrap()).unwrap();
+        let proof = proofs.last().unwrap();
+        let raw_value = RawValue(hex::decode(result.raw_stored_value.as_ref().unwrap()).unwrap());
+        TrieMerkleProof::new(*proof.key(), raw_value, proof.proof_steps().clone())
+            .compute_state_hash()
+            .unwrap()
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn should_succeed_with_valid_state_root_hash() {
         let server_handle = MockServerHandle::spawn::<GetItemParams>(GetItem::METHOD);
@@ -472,9 +1096,30 @@ mod get_item {
     #[tokio::test(flavor = "multi_thread")]
     async fn should_fail_with_invalid_key() {
         let server_handle = MockServerHandle::spawn::<GetItemParams>(GetItem::METHOD);
+        match server_handle.get_item(VALID_STATE_ROOT_HASH, "invalid key", "") {
+            Err(ErrWrapper(Error::FailedToParseFormattedKey(
+                "key",
+                KeyFromStrError::InvalidPrefix { .. },
+            ))) => {}
+            other => panic!("incorrect error returned from client {:?}", other),
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_fail_with_malformed_formatted_key() {
+        let server_handle = MockServerHandle::spawn::<GetItemParams>(GetItem::METHOD);
+        let short_hash = format!("hash-{}", &VALID_STATE_ROOT_HASH[2..]);
         assert_eq!(
-            server_handle.get_item(VALID_STATE_ROOT_HASH, "invalid key", ""),
-            Err(Error::FailedToParseKey.into())
+            server_handle.get_item(VALID_STATE_ROOT_HASH, &short_hash, ""),
+            Err(Error::FailedToParseFormattedKey(
+                "key",
+                KeyFromStrError::InvalidHexLength {
+                    prefix: "hash-",
+                    expected: 32,
+                    got: 31
+                }
+            )
+            .into())
         );
     }
 
@@ -490,21 +1135,155 @@ mod get_item {
             .into())
         );
     }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_verify_raw_stored_value_against_state_root_hash() {
+        let (result, state_root_hash) = result_with_raw_stored_value();
+        assert_eq!(rehash_raw_stored_value(&result), state_root_hash);
+
+        let server_handle =
+            MockServerHandle::spawn_with_result(<GetItem as RpcWithParams>::METHOD, &result);
+        let response = casper_client::get_item(
+            "1",
+            &server_handle.url(),
+            0,
+            &hex::encode(state_root_hash),
+            VALID_PURSE_UREF,
+            "",
+            true,
+        );
+        assert!(response.is_ok());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_fail_with_tampered_raw_stored_value() {
+        let (mut result, state_root_hash) = result_with_raw_stored_value();
+        let mut raw_value = hex::decode(result.raw_stored_value.as_ref().unwrap()).unwrap();
+        *raw_value.last_mut().unwrap() ^= 1;
+        result.raw_stored_value = Some(hex::encode(raw_value));
+        assert_ne!(rehash_raw_stored_value(&result), state_root_hash);
+
+        let server_handle =
+            MockServerHandle::spawn_with_result(<GetItem as RpcWithParams>::METHOD, &result);
+        let response = casper_client::get_item(
+            "1",
+            &server_handle.url(),
+            0,
+            &hex::encode(state_root_hash),
+            VALID_PURSE_UREF,
+            "",
+            true,
+        );
+        assert!(matches!(
+            response,
+            Err(Error::InvalidResponse(
+                ValidateResponseError::SerializedValueNotContainedInProof
+            ))
+        ));
+    }
 }
 
 mod get_deploy {
+    use serde_json::json;
+
+    use casper_client::ValidateResponseError;
+    use casper_node::{
+        rpcs::{docs::DocExample, info::GetDeployResult},
+        types::DeployValidationFailure,
+    };
+
     use super::*;
 
     #[tokio::test(flavor = "multi_thread")]
     async fn should_succeed_with_valid_hash() {
         let server_handle = MockServerHandle::spawn::<GetDeployParams>(GetDeploy::METHOD);
+        // The mock responds without a deploy, so verification is disabled.
+        let response = casper_client::get_deploy(
+            "1",
+            &server_handle.url(),
+            0,
+            "09dcee4b212cfd53642ab323fbef07dafafc6f945a80a00147f62910a915c4e6",
+            false,
+        );
+        assert_eq!(response.map(|_| ()).map_err(ErrWrapper), Ok(()));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_verify_valid_deploy() {
+        let expected = GetDeployResult::doc_example();
+        let server_handle = MockServerHandle::spawn_with_result(GetDeploy::METHOD, expected);
         assert_eq!(
-            server_handle
-                .get_deploy("09dcee4b212cfd53642ab323fbef07dafafc6f945a80a00147f62910a915c4e6"),
+            server_handle.get_deploy(&hex::encode(expected.deploy.id())),
             Ok(())
         );
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_fail_to_verify_deploy_with_corrupted_header() {
+        let expected = GetDeployResult::doc_example();
+        let gas_price = expected.deploy.header().gas_price();
+        let result = corrupted(expected, "/deploy/header/gas_price", json!(gas_price + 1));
+        let server_handle = MockServerHandle::spawn_with_result(GetDeploy::METHOD, &result);
+        assert_eq!(
+            server_handle.get_deploy(&hex::encode(expected.deploy.id())),
+            Err(ErrWrapper(Error::InvalidResponse(
+                ValidateResponseError::DeployValidationError(
+                    DeployValidationFailure::InvalidDeployHash
+                )
+            )))
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_fail_to_verify_deploy_with_corrupted_body() {
+        let expected = GetDeployResult::doc_example();
+        let result = corrupted(
+            expected,
+            "/deploy/session",
+            json!({"Transfer": {"args": []}}),
+        );
+        let server_handle = MockServerHandle::spawn_with_result(GetDeploy::METHOD, &result);
+        assert_eq!(
+            server_handle.get_deploy(&hex::encode(expected.deploy.id())),
+            Err(ErrWrapper(Error::InvalidResponse(
+                ValidateResponseError::DeployValidationError(
+                    DeployValidationFailure::InvalidBodyHash
+                )
+            )))
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_fail_to_verify_deploy_other_than_requested() {
+        let server_handle =
+            MockServerHandle::spawn_with_result(GetDeploy::METHOD, GetDeployResult::doc_example());
+        assert_eq!(
+            server_handle.get_deploy(VALID_STATE_ROOT_HASH),
+            Err(ErrWrapper(Error::InvalidResponse(
+                ValidateResponseError::UnexpectedDeployHash
+            )))
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_not_verify_deploy_if_disabled() {
+        let expected = GetDeployResult::doc_example();
+        let result = corrupted(
+            expected,
+            "/deploy/session",
+            json!({"Transfer": {"args": []}}),
+        );
+        let server_handle = MockServerHandle::spawn_with_result(GetDeploy::METHOD, &result);
+        let response = casper_client::get_deploy(
+            "1",
+            &server_handle.url(),
+            0,
+            &hex::encode(expected.deploy.id()),
+            false,
+        );
+        assert!(response.is_ok());
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn should_fail_with_invalid_hash() {
         let server_handle = MockServerHandle::spawn::<GetDeployParams>(GetDeploy::METHOD);
@@ -531,6 +1310,40 @@ mod get_auction_info {
     }
 }
 
+mod check_chain_name {
+    use casper_node::{
+        rpcs::{docs::DocExample, info::GetStatus, RpcWithoutParams},
+        types::GetStatusResult,
+    };
+
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_succeed_with_matching_chain_name() {
+        let status = GetStatusResult::doc_example();
+        let server_handle = MockServerHandle::spawn_with_result(GetStatus::METHOD, status);
+        assert_eq!(
+            casper_client::check_chain_name("1", &server_handle.url(), 0, &status.chainspec_name)
+                .map_err(ErrWrapper),
+            Ok(())
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_fail_with_mismatched_chain_name() {
+        let status = GetStatusResult::doc_example();
+        let server_handle = MockServerHandle::spawn_with_result(GetStatus::METHOD, status);
+        let chain_name = format!("{}-other", status.chainspec_name);
+        match casper_client::check_chain_name("1", &server_handle.url(), 0, &chain_name) {
+            Err(Error::ChainNameMismatch { expected, actual }) => {
+                assert_eq!(expected, chain_name);
+                assert_eq!(actual, status.chainspec_name);
+            }
+            result => panic!("expected chain name mismatch, got {:?}", result),
+        }
+    }
+}
+
 mod make_deploy {
     use super::*;
 
@@ -1024,6 +1837,93 @@ mod keygen_generate_files {
 mod put_deploy {
     use super::*;
 
+    use casper_client::ArgMismatch;
+    use casper_execution_engine::{
+        shared::{newtypes::CorrelationId, stored_value::StoredValue},
+        storage::global_state::{in_memory::InMemoryGlobalState, StateProvider, StateReader},
+    };
+    use casper_node::{
+        crypto::hash::Digest,
+        rpcs::state::{GetEntryPoint, GetEntryPointResult},
+    };
+    use casper_types::{
+        bytesrepr::ToBytes, contracts::NamedKeys, CLType, Contract, ContractHash,
+        ContractPackageHash, ContractWasmHash, EntryPoint, EntryPointAccess, EntryPointType,
+        EntryPoints, Key, Parameter, ProtocolVersion,
+    };
+
+    const CONTRACT_HASH: [u8; 32] = [7; 32];
+    const ENTRY_POINT: &str = "transfer";
+    const RECIPIENT: &str = concat!(
+        "recipient:key='account-hash-",
+        "0101010101010101010101010101010101010101010101010101010101010101'"
+    );
+
+    /// Returns the "state_get_entry_point" result for a contract whose "transfer" entry point takes
+    /// a `recipient` key and an `amount`, with a valid merkle proof from a test global state.
+    fn get_entry_point_result() -> GetEntryPointResult {
+        let entry_point = EntryPoint::new(
+            ENTRY_POINT,
+            vec![
+                Parameter::new("recipient", CLType::Key),
+                Parameter::new("amount", CLType::U512),
+            ],
+            CLType::Unit,
+            EntryPointAccess::Public,
+            EntryPointType::Contract,
+        );
+        let mut entry_points = EntryPoints::new();
+        entry_points.add_entry_point(entry_point.clone());
+        let contract = Contract::new(
+            ContractPackageHash::new([8; 32]),
+            ContractWasmHash::new([9; 32]),
+            NamedKeys::new(),
+            entry_points,
+            ProtocolVersion::V1_0_0,
+        );
+        let key = Key::from(ContractHash::new(CONTRACT_HASH));
+
+        let correlation_id = CorrelationId::new();
+        let (global_state, root_hash) = InMemoryGlobalState::from_pairs(
+            correlation_id,
+            &[(key, StoredValue::Contract(contract))],
+        )
+        .unwrap();
+        let proof = global_state
+            .checkout(root_hash)
+            .unwrap()
+            .expect("should have root")
+            .read_with_proof(correlation_id, &key)
+            .unwrap()
+            .expect("should have contract");
+
+        GetEntryPointResult {
+            api_version: ProtocolVersion::V1_0_0,
+            state_root_hash: Digest::from(root_hash),
+            entry_point,
+            merkle_proof: hex::encode(vec![proof].to_bytes().unwrap()),
+        }
+    }
+
+    /// Spawns a server which responds to "state_get_entry_point" with the contract's entry point
+    /// and accepts any deploy.
+    fn spawn_with_entry_point() -> MockServerHandle {
+        let result = serde_json::to_string(&get_entry_point_result()).unwrap();
+        let result = Box::leak(result.into_boxed_str());
+        MockServerHandle::spawn_with_filter(
+            test_filter_with_result(GetEntryPoint::METHOD, result)
+                .or(test_filter::<PutDeployParams>(PutDeploy::METHOD))
+                .unify(),
+            DEFAULT_RATE_LIMIT,
+            DEFAULT_RATE_PER,
+        )
+    }
+
+    fn session_params_with_args(args: Vec<&'static str>) -> SessionStrParams<'static> {
+        let contract_hash = Box::leak(hex::encode(CONTRACT_HASH).into_boxed_str());
+        SessionStrParams::with_hash(contract_hash, ENTRY_POINT, args, "")
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn should_send_put_deploy() {
         let server_handle = MockServerHandle::spawn::<PutDeployParams>(PutDeploy::METHOD);
@@ -1036,6 +1936,64 @@ mod put_deploy {
             Ok(())
         );
     }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_send_put_deploy_with_args_matching_entry_point() {
+        let server_handle = spawn_with_entry_point();
+        assert_eq!(
+            server_handle.put_deploy(
+                deploy_params::test_data_valid(),
+                session_params_with_args(vec![RECIPIENT, "amount:u512='100'"]),
+                payment_params::test_data_with_name()
+            ),
+            Ok(())
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_fail_with_args_not_matching_entry_point() {
+        let server_handle = spawn_with_entry_point();
+        assert_eq!(
+            server_handle.put_deploy(
+                deploy_params::test_data_valid(),
+                session_params_with_args(vec!["recipient:u64='1'", "memo:string='rent'"]),
+                payment_params::test_data_with_name()
+            ),
+            Err(Error::InvalidRuntimeArgs {
+                entry_point: ENTRY_POINT.to_string(),
+                mismatches: vec![
+                    ArgMismatch::TypeMismatch {
+                        name: "recipient".to_string(),
+                        expected: CLType::Key,
+                        actual: CLType::U64,
+                    },
+                    ArgMismatch::Missing {
+                        name: "amount".to_string(),
+                        expected: CLType::U512,
+                    },
+                    ArgMismatch::Unexpected {
+                        name: "memo".to_string(),
+                    },
+                ],
+            }
+            .into())
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_send_put_deploy_with_mismatched_args_if_validation_skipped() {
+        // The server doesn't serve "state_get_entry_point", so the deploy is only sent if the
+        // entry point isn't requested.
+        let server_handle = MockServerHandle::spawn::<PutDeployParams>(PutDeploy::METHOD);
+        assert_eq!(
+            server_handle.put_deploy(
+                deploy_params::test_data_valid(),
+                session_params_with_args(vec!["recipient:u64='1'"]).with_skip_arg_validation(),
+                payment_params::test_data_with_name()
+            ),
+            Ok(())
+        );
+    }
 }
 
 mod rate_limit {
@@ -1107,3 +2065,149 @@ mod transfer {
         );
     }
 }
+
+mod node_client {
+    use casper_client::NodeClient;
+    use casper_node::{
+        rpcs::{
+            account::PutDeployResult,
+            chain::{GetBlock, GetBlockResult, GetStateRootHash, GetStateRootHashResult},
+            docs::DocExample,
+            info::{GetDeployResult, GetStatus},
+            state::{GetBalanceResult, GetItem, GetItemResult},
+            RpcWithoutParams,
+        },
+        types::GetStatusResult,
+    };
+
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_put_deploy() {
+        let expected = PutDeployResult::doc_example();
+        let server_handle = MockServerHandle::spawn_with_result(PutDeploy::METHOD, expected);
+        let result = NodeClient::new(&server_handle.url())
+            .put_deploy(
+                "1",
+                deploy_params::test_data_valid(),
+                session_params::test_data_with_package_hash(),
+                payment_params::test_data_with_name(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(result.deploy_hash, expected.deploy_hash);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_transfer() {
+        let expected = PutDeployResult::doc_example();
+        let server_handle = MockServerHandle::spawn_with_result(PutDeploy::METHOD, expected);
+        let result = NodeClient::new(&server_handle.url())
+            .transfer(
+                "1",
+                "100",
+                "01522ef6c89038019cb7af05c340623804392dd2bb1f4dab5e4a9c3ab752fc0179",
+                "2",
+                deploy_params::test_data_valid(),
+                payment_params::test_data_with_name(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(result.deploy_hash, expected.deploy_hash);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_get_deploy_via_provided_http_client() {
+        let expected = GetDeployResult::doc_example();
+        let server_handle = MockServerHandle::spawn_with_result(GetDeploy::METHOD, expected);
+        let http_client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .unwrap();
+        let deploy_hash = hex::encode(expected.deploy.id());
+        let result = NodeClient::with_http_client(&server_handle.url(), http_client)
+            .get_deploy("1", &deploy_hash)
+            .await
+            .unwrap();
+        assert_eq!(result.deploy.id(), expected.deploy.id());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_list_deploys() {
+        let expected = GetBlockResult::doc_example();
+        let server_handle = MockServerHandle::spawn_with_result(GetBlock::METHOD, expected);
+        let result = NodeClient::new(&server_handle.url())
+            .list_deploys("1", "")
+            .await
+            .unwrap();
+        let expected_block = expected.block.as_ref().unwrap();
+        assert_eq!(
+            result.deploy_hashes.as_ref(),
+            Some(expected_block.deploy_hashes())
+        );
+        assert_eq!(
+            result.transfer_hashes.as_ref(),
+            Some(expected_block.transfer_hashes())
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_get_state_root_hash() {
+        let expected = GetStateRootHashResult::doc_example();
+        let server_handle = MockServerHandle::spawn_with_result(GetStateRootHash::METHOD, expected);
+        let result = NodeClient::new(&server_handle.url())
+            .get_state_root_hash("1", "")
+            .await
+            .unwrap();
+        assert_eq!(result.state_root_hash, expected.state_root_hash);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_get_status() {
+        let expected = GetStatusResult::doc_example();
+        let server_handle = MockServerHandle::spawn_with_result(GetStatus::METHOD, expected);
+        let result = NodeClient::new(&server_handle.url())
+            .get_status("1")
+            .await
+            .unwrap();
+        assert_eq!(result.consensus, expected.consensus);
+        assert!(result.consensus.is_some());
+    }
+
+    // The example responses don't include valid proofs for the given arguments, so validation of
+    // the typed responses is expected to fail.
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_validate_get_balance_response() {
+        let server_handle = MockServerHandle::spawn_with_result(
+            GetBalance::METHOD,
+            GetBalanceResult::doc_example(),
+        );
+        let result = NodeClient::new(&server_handle.url())
+            .get_balance("1", VALID_STATE_ROOT_HASH, VALID_PURSE_UREF)
+            .await;
+        assert!(matches!(result, Err(Error::InvalidResponse(_))));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_validate_get_item_response() {
+        let server_handle = MockServerHandle::spawn_with_result(
+            <GetItem as RpcWithParams>::METHOD,
+            GetItemResult::doc_example(),
+        );
+        let result = NodeClient::new(&server_handle.url())
+            .get_item("1", VALID_STATE_ROOT_HASH, VALID_PURSE_UREF, "", false)
+            .await;
+        assert!(matches!(result, Err(Error::InvalidResponse(_))));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_fail_to_parse_unexpected_result() {
+        // The mock server responds with a null result.
+        let server_handle = MockServerHandle::spawn::<GetDeployParams>(GetDeploy::METHOD);
+        let result = NodeClient::new(&server_handle.url())
+            .get_deploy("1", VALID_STATE_ROOT_HASH)
+            .await;
+        assert!(matches!(result, Err(Error::InvalidJson(_))));
+    }
+}
@@ -0,0 +1,312 @@
+//! Signing and verification of messages using asymmetric keys.
+
+use ed25519_dalek::ExpandedSecretKey;
+use k256::ecdsa::{
+    signature::{DigestSigner, DigestVerifier, Signer, Verifier},
+    Signature as Secp256k1Signature, VerifyingKey as Secp256k1PublicKey,
+};
+use sha2::{Digest as _, Sha256, Sha512};
+
+use casper_types::{crypto::Error, PublicKey, SecretKey, Signature};
+
+use crate::hash::Digest;
+
+/// The context hashed along with the digest signed by a prehashed secp256k1 signature.
+///
+/// A regular secp256k1 signature is over the SHA-256 hash of the message alone, so the context
+/// keeps prehashed and regular signatures apart.
+pub const SECP256K1_PREHASH_CONTEXT: &[u8] = b"casper-secp256k1-prehashed-v1";
+
+/// Signs the given message using the given key pair.
+pub fn sign<T: AsRef<[u8]>>(
+    message: T,
+    secret_key: &SecretKey,
+    public_key: &PublicKey,
+) -> Signature {
+    match (secret_key, public_key) {
+        (SecretKey::System, PublicKey::System) => {
+            panic!("cannot create signature with system keys",)
+        }
+        (SecretKey::Ed25519(secret_key), PublicKey::Ed25519(public_key)) => {
+            let expanded_secret_key = ExpandedSecretKey::from(secret_key);
+            let signature = expanded_secret_key.sign(message.as_ref(), public_key);
+            Signature::Ed25519(signature)
+        }
+        (SecretKey::Secp256k1(secret_key), PublicKey::Secp256k1(_public_key)) => {
+            let signer = secret_key;
+            let signature: Secp256k1Signature = signer
+                .try_sign(message.as_ref())
+                .expect("should create signature");
+            Signature::Secp256k1(signature)
+        }
+        _ => panic!("secret and public key types must match"),
+    }
+}
+
+/// Signs the given digest using the given key pair, e.g. the digest of a payload too large to be
+/// held in memory, or for a signer which only accepts digests.
+///
+/// Ed25519 keys sign the digest using Ed25519ph with an empty context.  Secp256k1 keys sign the
+/// SHA-256 hash of [`SECP256K1_PREHASH_CONTEXT`] followed by the digest.  The signature can only be
+/// verified via [`verify_prehashed`], never via [`verify`].
+pub fn sign_prehashed(
+    digest: &Digest,
+    secret_key: &SecretKey,
+    public_key: &PublicKey,
+) -> Signature {
+    match (secret_key, public_key) {
+        (SecretKey::System, PublicKey::System) => {
+            panic!("cannot create signature with system keys",)
+        }
+        (SecretKey::Ed25519(secret_key), PublicKey::Ed25519(public_key)) => {
+            let expanded_secret_key = ExpandedSecretKey::from(secret_key);
+            let signature = expanded_secret_key
+                .sign_prehashed(ed25519_prehash(digest), public_key, None)
+                .expect("should create signature");
+            Signature::Ed25519(signature)
+        }
+        (SecretKey::Secp256k1(secret_key), PublicKey::Secp256k1(_public_key)) => {
+            let signer = secret_key;
+            let signature: Secp256k1Signature = signer
+                .try_sign_digest(secp256k1_prehash(digest))
+                .expect("should create signature");
+            Signature::Secp256k1(signature)
+        }
+        _ => panic!("secret and public key types must match"),
+    }
+}
+
+/// Verifies the signature of the given message against the given public key.
+pub fn verify<T: AsRef<[u8]>>(
+    message: T,
+    signature: &Signature,
+    public_key: &PublicKey,
+) -> Result<(), Error> {
+    match (signature, public_key) {
+        (Signature::System, _) => Err(Error::AsymmetricKey(String::from(
+            "signatures based on the system key cannot be verified",
+        ))),
+        (Signature::Ed25519(signature), PublicKey::Ed25519(public_key)) => public_key
+            .verify_strict(message.as_ref(), signature)
+            .map_err(|_| Error::AsymmetricKey(String::from("failed to verify Ed25519 signature"))),
+        (Signature::Secp256k1(signature), PublicKey::Secp256k1(public_key)) => {
+            let verifier: &Secp256k1PublicKey = public_key;
+            verifier
+                .verify(message.as_ref(), signature)
+                .map_err(|error| {
+                    Error::AsymmetricKey(format!("failed to verify secp256k1 signature: {}", error))
+                })
+        }
+        _ => Err(Error::AsymmetricKey(format!(
+            "type mismatch between {} and {}",
+            signature, public_key
+        ))),
+    }
+}
+
+/// Verifies the signature of the given digest, as created by [`sign_prehashed`], against the given
+/// public key.
+pub fn verify_prehashed(
+    digest: &Digest,
+    signature: &Signature,
+    public_key: &PublicKey,
+) -> Result<(), Error> {
+    match (signature, public_key) {
+        (Signature::System, _) => Err(Error::AsymmetricKey(String::from(
+            "signatures based on the system key cannot be verified",
+        ))),
+        (Signature::Ed25519(signature), PublicKey::Ed25519(public_key)) => public_key
+            .verify_prehashed(ed25519_prehash(digest), None, signature)
+            .map_err(|_| {
+                Error::AsymmetricKey(String::from("failed to verify Ed25519ph signature"))
+            }),
+        (Signature::Secp256k1(signature), PublicKey::Secp256k1(public_key)) => {
+            let verifier: &Secp256k1PublicKey = public_key;
+            verifier
+                .verify_digest(secp256k1_prehash(digest), signature)
+                .map_err(|error| {
+                    Error::AsymmetricKey(format!(
+                        "failed to verify prehashed secp256k1 signature: {}",
+                        error
+                    ))
+                })
+        }
+        _ => Err(Error::AsymmetricKey(format!(
+            "type mismatch between {} and {}",
+            signature, public_key
+        ))),
+    }
+}
+
+/// Returns the Ed25519ph prehash of the digest, i.e. its SHA-512 hash.
+fn ed25519_prehash(digest: &Digest) -> Sha512 {
+    Sha512::new().chain(digest)
+}
+
+/// Returns the hash signed by a prehashed secp256k1 signature of the digest.
+fn secp256k1_prehash(digest: &Digest) -> Sha256 {
+    Sha256::new().chain(SECP256K1_PREHASH_CONTEXT).chain(digest)
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::AsymmetricType;
+
+    use super::*;
+    use crate::hash::hash;
+
+    const MESSAGE: &[u8] = b"message";
+
+    fn sign_and_verify(secret_key: SecretKey) {
+        let public_key = PublicKey::from(&secret_key);
+        let signature = sign(MESSAGE, &secret_key, &public_key);
+        assert!(verify(MESSAGE, &signature, &public_key).is_ok());
+        assert!(verify(b"other message", &signature, &public_key).is_err());
+    }
+
+    #[test]
+    fn should_sign_and_verify_ed25519() {
+        sign_and_verify(SecretKey::ed25519_from_bytes([1; SecretKey::ED25519_LENGTH]).unwrap());
+    }
+
+    #[test]
+    fn should_sign_and_verify_secp256k1() {
+        sign_and_verify(SecretKey::secp256k1_from_bytes([1; SecretKey::SECP256K1_LENGTH]).unwrap());
+    }
+
+    #[test]
+    fn should_fail_to_verify_with_mismatched_key_type() {
+        let ed25519_secret_key =
+            SecretKey::ed25519_from_bytes([1; SecretKey::ED25519_LENGTH]).unwrap();
+        let secp256k1_secret_key =
+            SecretKey::secp256k1_from_bytes([1; SecretKey::SECP256K1_LENGTH]).unwrap();
+        let signature = sign(
+            MESSAGE,
+            &ed25519_secret_key,
+            &PublicKey::from(&ed25519_secret_key),
+        );
+        assert!(verify(MESSAGE, &signature, &PublicKey::from(&secp256k1_secret_key)).is_err());
+    }
+
+    fn sign_and_verify_prehashed(secret_key: SecretKey) {
+        let public_key = PublicKey::from(&secret_key);
+        let digest = hash(b"large payload");
+        let signature = sign_prehashed(&digest, &secret_key, &public_key);
+        assert!(verify_prehashed(&digest, &signature, &public_key).is_ok());
+        assert!(verify_prehashed(&hash(b"other payload"), &signature, &public_key).is_err());
+
+        // A prehashed signature of the digest isn't a regular signature of the digest's bytes, and
+        // vice versa.
+        assert!(verify(digest, &signature, &public_key).is_err());
+        let regular_signature = sign(digest, &secret_key, &public_key);
+        assert_ne!(regular_signature, signature);
+        assert!(verify_prehashed(&digest, &regular_signature, &public_key).is_err());
+    }
+
+    #[test]
+    fn should_sign_and_verify_prehashed_ed25519() {
+        sign_and_verify_prehashed(
+            SecretKey::ed25519_from_bytes([1; SecretKey::ED25519_LENGTH]).unwrap(),
+        );
+    }
+
+    #[test]
+    fn should_sign_and_verify_prehashed_secp256k1() {
+        sign_and_verify_prehashed(
+            SecretKey::secp256k1_from_bytes([1; SecretKey::SECP256K1_LENGTH]).unwrap(),
+        );
+    }
+
+    #[test]
+    fn should_fail_to_verify_prehashed_with_mismatched_key_type() {
+        let ed25519_secret_key =
+            SecretKey::ed25519_from_bytes([1; SecretKey::ED25519_LENGTH]).unwrap();
+        let secp256k1_secret_key =
+            SecretKey::secp256k1_from_bytes([1; SecretKey::SECP256K1_LENGTH]).unwrap();
+        let digest = hash(MESSAGE);
+        let signature = sign_prehashed(
+            &digest,
+            &ed25519_secret_key,
+            &PublicKey::from(&ed25519_secret_key),
+        );
+        assert!(
+            verify_prehashed(&digest, &signature, &PublicKey::from(&secp256k1_secret_key)).is_err()
+        );
+    }
+
+    /// The Ed25519ph known-answer test from RFC 8032, section 7.3.
+    #[test]
+    fn should_match_rfc_8032_ed25519ph_vector() {
+        let secret_key = ed25519_dalek::SecretKey::from_bytes(
+            &hex::decode("833fe62409237b9d62ec77587520911e9a759cec1d19755b7da901b96dca3d42")
+                .unwrap(),
+        )
+        .unwrap();
+        let public_key = ed25519_dalek::PublicKey::from(&secret_key);
+        assert_eq!(
+            hex::encode(public_key.as_bytes()),
+            "ec172b93ad5e563bf4932c70e1245034c35467ef2efd4d64ebf819683467e2bf"
+        );
+
+        let signature = ExpandedSecretKey::from(&secret_key)
+            .sign_prehashed(Sha512::new().chain(b"abc"), &public_key, None)
+            .unwrap();
+        assert_eq!(
+            hex::encode(&signature.to_bytes()[..]),
+            "98a70222f0b8121aa9d30f813d683f809e462b469c7ff87639499bb94e6dae41\
+             31f85042463c2a355a2003d062adf5aaa10b8c61e636062aaad11c2a26083406"
+        );
+    }
+
+    #[test]
+    fn should_match_known_answer_ed25519() {
+        let secret_key = SecretKey::ed25519_from_bytes(
+            hex::decode("833fe62409237b9d62ec77587520911e9a759cec1d19755b7da901b96dca3d42")
+                .unwrap(),
+        )
+        .unwrap();
+        let public_key = PublicKey::from(&secret_key);
+        let digest = hash(b"large payload");
+        assert_eq!(
+            hex::encode(digest),
+            "0533a8f31a850735efac2d5133dc6c3e9244d4c74ad5e7818320639b1fedec18"
+        );
+
+        let expected = Signature::ed25519_from_bytes(
+            hex::decode(
+                "a72842966c3ba6c0493085b1cdbc1c956d8151557871c00e4aadbb9ab9a31b99\
+                 49df58c560bc0c40052c9e723700345c914c23d1a5630ac2ea2ed03e6903d001",
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(sign_prehashed(&digest, &secret_key, &public_key), expected);
+        assert!(verify_prehashed(&digest, &expected, &public_key).is_ok());
+    }
+
+    #[test]
+    fn should_match_known_answer_secp256k1() {
+        let secret_key = SecretKey::secp256k1_from_bytes([1; SecretKey::SECP256K1_LENGTH]).unwrap();
+        let public_key = PublicKey::from(&secret_key);
+        assert_eq!(
+            public_key,
+            PublicKey::secp256k1_from_bytes(
+                hex::decode("031b84c5567b126440995d3ed5aaba0565d71e1834604819ff9c17f5e9d5dd078f")
+                    .unwrap()
+            )
+            .unwrap()
+        );
+        let digest = hash(b"large payload");
+
+        let expected = Signature::secp256k1_from_bytes(
+            hex::decode(
+                "98b436580c958a34c01c9278eca6b40ca11f1053962ce17f4e66f8e9a9b048d1\
+                 1b763cab6f849a0b4c85298f61cf35eefab74d674bc9bb71f49cbad194371993",
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(sign_prehashed(&digest, &secret_key, &public_key), expected);
+        assert!(verify_prehashed(&digest, &expected, &public_key).is_ok());
+    }
+}
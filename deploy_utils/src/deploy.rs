@@ -0,0 +1,2031 @@
+//! Deploy types, along with their construction, hashing, signing and validation.
+
+// TODO - remove once schemars stops causing warning.
+#![allow(clippy::field_reassign_with_default)]
+
+use std::fmt::{self, Debug, Display, Formatter};
+
+use datasize::DataSize;
+use derive_more::Display;
+use itertools::Itertools;
+use rand::Rng;
+use schemars::JsonSchema;
+use serde::{Deserialize, Deserializer, Serialize};
+use thiserror::Error;
+use tracing::{info, warn};
+
+use casper_types::{
+    account::AccountHash,
+    bytesrepr::{self, FromBytes, NotU8, ToBytes},
+    system::{mint::ARG_TRANSFERS, standard_payment::ARG_AMOUNT},
+    CLTyped, CLValue, Key, ProtocolVersion, PublicKey, RuntimeArgs, SecretKey, Signature, U512,
+};
+
+use crate::{
+    crypto,
+    hash::{self, Digest, HashDomain},
+    ExecutableDeployItem, TimeDiff, Timestamp,
+};
+
+/// A representation of the way in which a deploy failed validation checks.
+#[derive(Clone, DataSize, Ord, PartialOrd, Eq, PartialEq, Hash, Debug, Error)]
+pub enum DeployValidationFailure {
+    /// Invalid chain name.
+    #[error("invalid chain name: expected {expected}, got {got}")]
+    InvalidChainName {
+        /// The expected chain name.
+        expected: String,
+        /// The received chain name.
+        got: String,
+    },
+
+    /// Excessive length of chain name.
+    #[error("chain name of {got} bytes exceeds limit of {max_length}")]
+    ExcessiveChainNameLength {
+        /// The byte size limit of the chain name.
+        max_length: usize,
+        /// The received length of the chain name.
+        got: usize,
+    },
+
+    /// Excessive length of a named key referenced by the payment or session code.
+    #[error("named key of {got} bytes exceeds limit of {max_length}")]
+    ExcessiveNamedKeyLength {
+        /// The byte size limit of named keys.
+        max_length: usize,
+        /// The received length of the named key.
+        got: usize,
+    },
+
+    /// Too many dependencies.
+    #[error("{got} dependencies exceeds limit of {max_dependencies}")]
+    ExcessiveDependencies {
+        /// The dependencies limit.
+        max_dependencies: u8,
+        /// The actual number of dependencies provided.
+        got: usize,
+    },
+
+    /// The gas price is zero.
+    #[error("gas price must not be zero")]
+    ZeroGasPrice,
+
+    /// Excessive gas price.
+    #[error("gas price of {got} exceeds limit of {max_gas_price}")]
+    ExcessiveGasPrice {
+        /// The gas price limit.
+        max_gas_price: u64,
+        /// The received gas price.
+        got: u64,
+    },
+
+    /// Deploy is too large.
+    #[error("deploy size too large: {0}")]
+    ExcessiveSize(#[from] ExcessiveSizeError),
+
+    /// Excessive time-to-live.
+    #[error("time-to-live of {got} exceeds limit of {max_ttl}")]
+    ExcessiveTimeToLive {
+        /// The time-to-live limit.
+        max_ttl: TimeDiff,
+        /// The received time-to-live.
+        got: TimeDiff,
+    },
+
+    /// The provided body hash does not match the actual hash of the body.
+    #[error("the provided body hash does not match the actual hash of the body")]
+    InvalidBodyHash,
+
+    /// The provided deploy hash does not match the actual hash of the deploy.
+    #[error("the provided hash does not match the actual hash of the deploy")]
+    InvalidDeployHash,
+
+    /// Invalid approval.
+    #[error("the approval at index {index} is invalid: {error_msg}")]
+    InvalidApproval {
+        /// The index of the approval at fault.
+        index: usize,
+        /// The approval validation error.
+        error_msg: String,
+    },
+
+    /// An approval is signed with an algorithm not allowed by the chainspec.
+    #[error("the approval at index {index} is signed with disallowed algorithm {algorithm}")]
+    DisallowedApprovalAlgorithm {
+        /// The index of the approval at fault.
+        index: usize,
+        /// The name of the approval's signature algorithm.
+        algorithm: String,
+    },
+
+    /// Excessive length of deploy's session args.
+    #[error("serialized session code runtime args of {got} exceeds limit of {max_length}")]
+    ExcessiveSessionArgsLength {
+        /// The byte size limit of session arguments.
+        max_length: usize,
+        /// The received length of session arguments.
+        got: usize,
+    },
+
+    /// Excessive length of deploy's payment args.
+    #[error("serialized payment code runtime args of {got} exceeds limit of {max_length}")]
+    ExcessivePaymentArgsLength {
+        /// The byte size limit of payment arguments.
+        max_length: usize,
+        /// The received length of payment arguments.
+        got: usize,
+    },
+
+    /// Missing transfer amount.
+    #[error("missing transfer amount")]
+    MissingTransferAmount,
+
+    /// Invalid transfer amount.
+    #[error("invalid transfer amount")]
+    InvalidTransferAmount,
+
+    /// Insufficient transfer amount.
+    #[error("insufficient transfer amount; minimum: {minimum} attempted: {attempted}")]
+    InsufficientTransferAmount {
+        /// The minimum transfer amount.
+        minimum: U512,
+        /// The attempted transfer amount.
+        attempted: U512,
+    },
+
+    /// Invalid batch of native transfers, e.g. an empty one or one of the wrong type.
+    #[error("invalid transfer batch")]
+    InvalidTransferBatch,
+
+    /// Too many native transfers in a batch.
+    #[error("batch of {got} transfers exceeds limit of {max_transfers}")]
+    ExcessiveTransferBatch {
+        /// The limit on the number of transfers in a batch.
+        max_transfers: u32,
+        /// The number of transfers in the batch.
+        got: usize,
+    },
+}
+
+/// Error returned when a Deploy is too large.
+#[derive(Clone, DataSize, Ord, PartialOrd, Eq, PartialEq, Hash, Debug, Error)]
+#[error("deploy size of {actual_deploy_size} bytes exceeds limit of {max_deploy_size}")]
+pub struct ExcessiveSizeError {
+    /// The maximum permitted serialized deploy size, in bytes.
+    pub max_deploy_size: u32,
+    /// The serialized size of the deploy provided, in bytes.
+    pub actual_deploy_size: usize,
+}
+
+/// The limits with which a deploy must comply to be accepted onto a chain.
+#[derive(Copy, Clone, DataSize, PartialEq, Eq, Debug)]
+pub struct DeployLimits {
+    /// The maximum time-to-live.
+    pub max_ttl: TimeDiff,
+    /// The maximum number of dependencies.
+    pub max_dependencies: u8,
+    /// The maximum serialized deploy size, in bytes.
+    pub max_deploy_size: u32,
+    /// The maximum serialized length of the payment code's runtime args, in bytes.
+    pub payment_args_max_length: u32,
+    /// The maximum serialized length of the session code's runtime args, in bytes.
+    pub session_args_max_length: u32,
+    /// The minimum amount of motes for a native transfer.
+    pub native_transfer_minimum_motes: u64,
+    /// The maximum number of transfers in a batched native transfer.  Zero disables batching.
+    pub max_transfers_per_batch: u32,
+    /// The maximum length of the chain name, in bytes.
+    pub max_chain_name_length: u32,
+    /// The maximum length of a named key referenced by the payment or session code, in bytes.
+    pub max_named_key_length: u32,
+    /// The maximum gas price.
+    pub max_gas_price: u64,
+}
+
+/// The scheme used to compute the hash and body hash of a [`Deploy`](struct.Deploy.html).
+///
+/// The scheme is determined by the deploy's timestamp relative to the time from which the network
+/// on which it is to be executed hashes deploys in their respective domains, so that deploys
+/// created before then remain verifiable after the network upgrades.
+#[derive(Copy, Clone, DataSize, Ord, PartialOrd, Eq, PartialEq, Hash, Debug)]
+pub enum DeployHashScheme {
+    /// Plain hashes of the serialized header and body.
+    Legacy,
+    /// Hashes of the serialized header and body in their respective
+    /// [`HashDomain`](../hash/enum.HashDomain.html)s.
+    DomainSeparated,
+}
+
+impl DeployHashScheme {
+    /// All schemes.
+    pub const ALL: [DeployHashScheme; 2] =
+        [DeployHashScheme::Legacy, DeployHashScheme::DomainSeparated];
+
+    /// The protocol version from which deploys are hashed using
+    /// [`DeployHashScheme::DomainSeparated`].
+    pub const DOMAIN_SEPARATED_PROTOCOL_VERSION: ProtocolVersion =
+        ProtocolVersion::from_parts(2, 0, 0);
+
+    /// Returns the scheme with which to create deploys for a network running the given protocol
+    /// version.
+    pub fn for_protocol_version(protocol_version: ProtocolVersion) -> Self {
+        if protocol_version >= Self::DOMAIN_SEPARATED_PROTOCOL_VERSION {
+            DeployHashScheme::DomainSeparated
+        } else {
+            DeployHashScheme::Legacy
+        }
+    }
+
+    /// Returns the scheme with which a deploy with the given timestamp is hashed, where
+    /// `activation` is the time from which the network hashes deploys using
+    /// [`DeployHashScheme::DomainSeparated`], if it does.
+    ///
+    /// This is the rule by which every node verifies a deploy's hashes, so that all agree on the
+    /// scheme regardless of the protocol version each is running.
+    pub fn for_timestamp(timestamp: Timestamp, activation: Option<Timestamp>) -> Self {
+        match activation {
+            Some(activation) if timestamp >= activation => DeployHashScheme::DomainSeparated,
+            _ => DeployHashScheme::Legacy,
+        }
+    }
+
+    fn hash(self, domain: HashDomain, data: &[u8]) -> Digest {
+        match self {
+            DeployHashScheme::Legacy => hash::hash(data),
+            DeployHashScheme::DomainSeparated => hash::hash_with_domain(domain, data),
+        }
+    }
+
+    fn body_hash(self, payment: &ExecutableDeployItem, session: &ExecutableDeployItem) -> Digest {
+        self.hash(HashDomain::DeployBody, &serialize_body(payment, session))
+    }
+
+    /// Returns the hash of a deploy with the given header under this scheme.
+    pub fn deploy_hash(self, header: &DeployHeader) -> DeployHash {
+        DeployHash::new(self.hash(HashDomain::DeployHeader, &serialize_header(header)))
+    }
+}
+
+/// The cryptographic hash of a [`Deploy`](struct.Deploy.html).
+#[derive(
+    Copy,
+    Clone,
+    DataSize,
+    Ord,
+    PartialOrd,
+    Eq,
+    PartialEq,
+    Hash,
+    Serialize,
+    Deserialize,
+    Debug,
+    Default,
+    JsonSchema,
+)]
+#[serde(deny_unknown_fields)]
+#[schemars(with = "String", description = "Hex-encoded deploy hash.")]
+pub struct DeployHash(#[schemars(skip)] Digest);
+
+impl DeployHash {
+    /// Constructs a new `DeployHash`.
+    pub fn new(hash: Digest) -> Self {
+        DeployHash(hash)
+    }
+
+    /// Returns the wrapped inner hash.
+    pub fn inner(&self) -> &Digest {
+        &self.0
+    }
+
+    /// Creates a random deploy hash.
+    pub fn random<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        let hash = Digest::random(rng);
+        DeployHash(hash)
+    }
+}
+
+impl Display for DeployHash {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "deploy-hash({})", self.0,)
+    }
+}
+
+impl From<Digest> for DeployHash {
+    fn from(digest: Digest) -> Self {
+        Self(digest)
+    }
+}
+
+impl AsRef<[u8]> for DeployHash {
+    fn as_ref(&self) -> &[u8] {
+        self.0.as_ref()
+    }
+}
+
+impl ToBytes for DeployHash {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        self.0.to_bytes()
+    }
+
+    fn serialized_length(&self) -> usize {
+        self.0.serialized_length()
+    }
+}
+
+impl FromBytes for DeployHash {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        Digest::from_bytes(bytes).map(|(inner, remainder)| (DeployHash(inner), remainder))
+    }
+}
+
+impl NotU8 for DeployHash {}
+
+/// The [`DeployHash`](struct.DeployHash.html) stored in a way distinguishing between WASM deploys
+/// and transfers.
+#[derive(
+    Copy,
+    Clone,
+    DataSize,
+    Ord,
+    PartialOrd,
+    Eq,
+    PartialEq,
+    Hash,
+    Serialize,
+    Deserialize,
+    Debug,
+    Display,
+    JsonSchema,
+)]
+#[serde(deny_unknown_fields)]
+pub enum DeployOrTransferHash {
+    /// Hash of a deploy.
+    #[display(fmt = "deploy {}", _0)]
+    Deploy(DeployHash),
+    /// Hash of a transfer.
+    #[display(fmt = "transfer {}", _0)]
+    Transfer(DeployHash),
+}
+
+impl DeployOrTransferHash {
+    /// Gets the inner `DeployHash`.
+    pub fn deploy_hash(&self) -> &DeployHash {
+        match self {
+            DeployOrTransferHash::Deploy(hash) | DeployOrTransferHash::Transfer(hash) => hash,
+        }
+    }
+
+    /// Returns `true` if this is a transfer hash.
+    pub fn is_transfer(&self) -> bool {
+        matches!(self, DeployOrTransferHash::Transfer(_))
+    }
+}
+
+impl From<DeployOrTransferHash> for DeployHash {
+    fn from(dt_hash: DeployOrTransferHash) -> DeployHash {
+        match dt_hash {
+            DeployOrTransferHash::Deploy(hash) => hash,
+            DeployOrTransferHash::Transfer(hash) => hash,
+        }
+    }
+}
+
+/// The header portion of a [`Deploy`](struct.Deploy.html).
+#[derive(
+    Clone, DataSize, Ord, PartialOrd, Eq, PartialEq, Hash, Serialize, Deserialize, Debug, JsonSchema,
+)]
+#[serde(deny_unknown_fields)]
+pub struct DeployHeader {
+    account: PublicKey,
+    timestamp: Timestamp,
+    ttl: TimeDiff,
+    gas_price: u64,
+    body_hash: Digest,
+    dependencies: Vec<DeployHash>,
+    chain_name: String,
+}
+
+impl DeployHeader {
+    /// The account within which the deploy will be run.
+    pub fn account(&self) -> &PublicKey {
+        &self.account
+    }
+
+    /// When the deploy was created.
+    pub fn timestamp(&self) -> Timestamp {
+        self.timestamp
+    }
+
+    /// How long the deploy will stay valid.
+    pub fn ttl(&self) -> TimeDiff {
+        self.ttl
+    }
+
+    /// Has this deploy expired?
+    pub fn expired(&self, current_instant: Timestamp) -> bool {
+        let lifespan = self.timestamp + self.ttl;
+        lifespan < current_instant
+    }
+
+    /// Price per gas unit for this deploy.
+    pub fn gas_price(&self) -> u64 {
+        self.gas_price
+    }
+
+    /// Hash of the Wasm code.
+    pub fn body_hash(&self) -> &Digest {
+        &self.body_hash
+    }
+
+    /// Other deploys that have to be run before this one.
+    pub fn dependencies(&self) -> &Vec<DeployHash> {
+        &self.dependencies
+    }
+
+    /// Which chain the deploy is supposed to be run on.
+    pub fn chain_name(&self) -> &str {
+        &self.chain_name
+    }
+
+    /// Determine if this deploy header has valid values based on a `DeployLimits` and timestamp.
+    pub fn is_valid(&self, limits: &DeployLimits, current_timestamp: Timestamp) -> bool {
+        let ttl_valid = self.ttl() <= limits.max_ttl;
+        let timestamp_valid = self.timestamp() <= current_timestamp;
+        let not_expired = !self.expired(current_timestamp);
+        let num_deps_valid = self.dependencies().len() <= limits.max_dependencies as usize;
+        let gas_price_valid = self.gas_price() != 0 && self.gas_price() <= limits.max_gas_price;
+        ttl_valid && timestamp_valid && not_expired && num_deps_valid && gas_price_valid
+    }
+}
+
+impl DeployHeader {
+    /// Returns the timestamp of when the deploy expires, i.e. `self.timestamp + self.ttl`.
+    pub fn expires(&self) -> Timestamp {
+        self.timestamp + self.ttl
+    }
+}
+
+impl ToBytes for DeployHeader {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        let mut buffer = bytesrepr::allocate_buffer(self)?;
+        buffer.extend(self.account.to_bytes()?);
+        buffer.extend(self.timestamp.to_bytes()?);
+        buffer.extend(self.ttl.to_bytes()?);
+        buffer.extend(self.gas_price.to_bytes()?);
+        buffer.extend(self.body_hash.to_bytes()?);
+        buffer.extend(self.dependencies.to_bytes()?);
+        buffer.extend(self.chain_name.to_bytes()?);
+        Ok(buffer)
+    }
+
+    fn serialized_length(&self) -> usize {
+        self.account.serialized_length()
+            + self.timestamp.serialized_length()
+            + self.ttl.serialized_length()
+            + self.gas_price.serialized_length()
+            + self.body_hash.serialized_length()
+            + self.dependencies.serialized_length()
+            + self.chain_name.serialized_length()
+    }
+}
+
+impl FromBytes for DeployHeader {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (account, remainder) = PublicKey::from_bytes(bytes)?;
+        let (timestamp, remainder) = Timestamp::from_bytes(remainder)?;
+        let (ttl, remainder) = TimeDiff::from_bytes(remainder)?;
+        let (gas_price, remainder) = u64::from_bytes(remainder)?;
+        let (body_hash, remainder) = Digest::from_bytes(remainder)?;
+        let (dependencies, remainder) = Vec::<DeployHash>::from_bytes(remainder)?;
+        let (chain_name, remainder) = String::from_bytes(remainder)?;
+        let deploy_header = DeployHeader {
+            account,
+            timestamp,
+            ttl,
+            gas_price,
+            body_hash,
+            dependencies,
+            chain_name,
+        };
+        Ok((deploy_header, remainder))
+    }
+}
+
+impl Display for DeployHeader {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(
+            formatter,
+            "deploy-header[account: {}, timestamp: {}, ttl: {}, gas_price: {}, body_hash: {}, dependencies: [{}], chain_name: {}]",
+            self.account,
+            self.timestamp,
+            self.ttl,
+            self.gas_price,
+            self.body_hash,
+            self.dependencies.iter().join(", "),
+            self.chain_name,
+        )
+    }
+}
+
+/// A struct containing a signature and the public key of the signer.
+#[derive(
+    Clone, DataSize, Ord, PartialOrd, Eq, PartialEq, Hash, Serialize, Deserialize, Debug, JsonSchema,
+)]
+#[serde(deny_unknown_fields)]
+pub struct Approval {
+    signer: PublicKey,
+    signature: Signature,
+}
+
+impl Approval {
+    /// Returns the public key of the approval's signer.
+    pub fn signer(&self) -> &PublicKey {
+        &self.signer
+    }
+
+    /// Returns the approval signature.
+    pub fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    /// Verifies the approval's signature over the given deploy hash.
+    ///
+    /// The signature is either a regular signature of the deploy hash, or a prehashed signature of
+    /// its digest as created by [`crypto::sign_prehashed`], e.g. by an external signer.
+    pub fn verify(&self, deploy_hash: &DeployHash) -> Result<(), casper_types::crypto::Error> {
+        crypto::verify(deploy_hash, &self.signature, &self.signer).or_else(|error| {
+            crypto::verify_prehashed(deploy_hash.inner(), &self.signature, &self.signer)
+                .map_err(|_| error)
+        })
+    }
+}
+
+impl Display for Approval {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "approval({})", self.signer)
+    }
+}
+
+impl ToBytes for Approval {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        let mut buffer = bytesrepr::allocate_buffer(self)?;
+        buffer.extend(self.signer.to_bytes()?);
+        buffer.extend(self.signature.to_bytes()?);
+        Ok(buffer)
+    }
+
+    fn serialized_length(&self) -> usize {
+        self.signer.serialized_length() + self.signature.serialized_length()
+    }
+}
+
+impl FromBytes for Approval {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (signer, remainder) = PublicKey::from_bytes(bytes)?;
+        let (signature, remainder) = Signature::from_bytes(remainder)?;
+        let approval = Approval { signer, signature };
+        Ok((approval, remainder))
+    }
+}
+
+impl NotU8 for Approval {}
+
+/// A deploy; an item containing a smart contract along with the requester's signature(s).
+#[derive(
+    Clone, DataSize, Ord, PartialOrd, Eq, PartialEq, Hash, Serialize, Deserialize, Debug, JsonSchema,
+)]
+#[serde(deny_unknown_fields)]
+pub struct Deploy {
+    hash: DeployHash,
+    header: DeployHeader,
+    payment: ExecutableDeployItem,
+    session: ExecutableDeployItem,
+    /// The approvals, held sorted and without duplicates so that the serialized form of a deploy
+    /// doesn't depend on the order in which it was signed.
+    #[serde(deserialize_with = "deserialize_approvals")]
+    approvals: Vec<Approval>,
+    #[serde(skip)]
+    is_valid: Option<(DeployHashScheme, Result<(), DeployValidationFailure>)>,
+}
+
+impl Deploy {
+    /// Constructs a new signed `Deploy`, hashed using [`DeployHashScheme::Legacy`].
+    ///
+    /// For networks running protocol version
+    /// [`DeployHashScheme::DOMAIN_SEPARATED_PROTOCOL_VERSION`] or later, use
+    /// [`Deploy::new_with_hash_scheme`] instead.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        timestamp: Timestamp,
+        ttl: TimeDiff,
+        gas_price: u64,
+        dependencies: Vec<DeployHash>,
+        chain_name: String,
+        payment: ExecutableDeployItem,
+        session: ExecutableDeployItem,
+        secret_key: &SecretKey,
+    ) -> Deploy {
+        Deploy::new_with_hash_scheme(
+            timestamp,
+            ttl,
+            gas_price,
+            dependencies,
+            chain_name,
+            payment,
+            session,
+            secret_key,
+            DeployHashScheme::Legacy,
+        )
+    }
+
+    /// Constructs a new signed `Deploy`, hashed using the given scheme.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_with_hash_scheme(
+        timestamp: Timestamp,
+        ttl: TimeDiff,
+        gas_price: u64,
+        dependencies: Vec<DeployHash>,
+        chain_name: String,
+        payment: ExecutableDeployItem,
+        session: ExecutableDeployItem,
+        secret_key: &SecretKey,
+        hash_scheme: DeployHashScheme,
+    ) -> Deploy {
+        let body_hash = hash_scheme.body_hash(&payment, &session);
+
+        let account = PublicKey::from(secret_key);
+        // Remove duplicates.
+        let dependencies = dependencies.into_iter().unique().collect();
+        let header = DeployHeader {
+            account,
+            timestamp,
+            ttl,
+            gas_price,
+            body_hash,
+            dependencies,
+            chain_name,
+        };
+        let hash = hash_scheme.deploy_hash(&header);
+
+        let mut deploy = Deploy {
+            hash,
+            header,
+            payment,
+            session,
+            approvals: vec![],
+            is_valid: None,
+        };
+
+        deploy.sign(secret_key);
+        deploy
+    }
+
+    /// Adds a signature of this deploy's hash to its approvals.
+    ///
+    /// The approvals are kept in sorted order, and an identical approval is only held once.
+    pub fn sign(&mut self, secret_key: &SecretKey) {
+        let signer = PublicKey::from(secret_key);
+        let signature = crypto::sign(&self.hash, secret_key, &signer);
+        self.insert_approval(Approval { signer, signature });
+    }
+
+    /// Adds the given signer's signature of this deploy's hash to its approvals, e.g. a prehashed
+    /// signature created by an external signer.
+    ///
+    /// The signature is verified as per [`Approval::verify`] before being added.  The approvals are
+    /// kept in sorted order, and an identical approval is only held once.
+    pub fn add_approval(
+        &mut self,
+        signer: PublicKey,
+        signature: Signature,
+    ) -> Result<(), casper_types::crypto::Error> {
+        let approval = Approval { signer, signature };
+        approval.verify(&self.hash)?;
+        self.insert_approval(approval);
+        Ok(())
+    }
+
+    fn insert_approval(&mut self, approval: Approval) {
+        if let Err(index) = self.approvals.binary_search(&approval) {
+            self.approvals.insert(index, approval);
+        }
+    }
+
+    /// Returns the `DeployHash` identifying this `Deploy`.
+    pub fn id(&self) -> &DeployHash {
+        &self.hash
+    }
+
+    /// Returns a reference to the `DeployHeader` of this `Deploy`.
+    pub fn header(&self) -> &DeployHeader {
+        &self.header
+    }
+
+    /// Returns the `DeployHeader` of this `Deploy`.
+    pub fn take_header(self) -> DeployHeader {
+        self.header
+    }
+
+    /// Returns the `ExecutableDeployItem` for payment code.
+    pub fn payment(&self) -> &ExecutableDeployItem {
+        &self.payment
+    }
+
+    /// Returns the `ExecutableDeployItem` for session code.
+    pub fn session(&self) -> &ExecutableDeployItem {
+        &self.session
+    }
+
+    /// Returns the `Approval`s for this deploy.
+    pub fn approvals(&self) -> &[Approval] {
+        &self.approvals
+    }
+
+    /// Returns the hash of this deploy wrapped in `DeployOrTransferHash`.
+    pub fn deploy_or_transfer_hash(&self) -> DeployOrTransferHash {
+        if self.session.is_transfer() {
+            DeployOrTransferHash::Transfer(self.hash)
+        } else {
+            DeployOrTransferHash::Deploy(self.hash)
+        }
+    }
+
+    /// Returns true if the serialized size of the deploy is not greater than `max_deploy_size`.
+    pub fn is_valid_size(&self, max_deploy_size: u32) -> Result<(), ExcessiveSizeError> {
+        let deploy_size = self.serialized_length();
+        if deploy_size > max_deploy_size as usize {
+            return Err(ExcessiveSizeError {
+                max_deploy_size,
+                actual_deploy_size: deploy_size,
+            });
+        }
+        Ok(())
+    }
+
+    /// Returns true if and only if:
+    ///   * the deploy hash is correct (should be the hash of the header), and
+    ///   * the body hash is correct (should be the hash of the body), and
+    ///   * all approvals are valid signatures of the deploy hash
+    ///
+    /// The hashes are checked using the given scheme, which should be that given by
+    /// [`DeployHashScheme::for_timestamp`] for the deploy.
+    pub fn is_valid(
+        &mut self,
+        hash_scheme: DeployHashScheme,
+    ) -> Result<(), DeployValidationFailure> {
+        self.is_valid_with(hash_scheme, |deploy_hash, approval| {
+            approval.verify(deploy_hash)
+        })
+    }
+
+    /// As [`is_valid`](Self::is_valid), but verifying each approval via `verify_approval`.
+    ///
+    /// `verify_approval` is only called once both hashes have been found to be correct, allowing
+    /// it to e.g. consult a cache of previously-verified approvals.
+    pub fn is_valid_with<F>(
+        &mut self,
+        hash_scheme: DeployHashScheme,
+        verify_approval: F,
+    ) -> Result<(), DeployValidationFailure>
+    where
+        F: FnMut(&DeployHash, &Approval) -> Result<(), casper_types::crypto::Error>,
+    {
+        match self.is_valid.as_ref() {
+            Some((cached_hash_scheme, validity)) if *cached_hash_scheme == hash_scheme => {
+                validity.clone()
+            }
+            _ => {
+                let validity = validate_deploy(self, hash_scheme, verify_approval);
+                self.is_valid = Some((hash_scheme, validity.clone()));
+                validity
+            }
+        }
+    }
+
+    /// As [`is_valid`](Self::is_valid), but checking that the deploy has the given hash under
+    /// whichever scheme it was hashed with, rather than a given one.
+    ///
+    /// This suits checking that a deploy received from a peer is the one requested where the time
+    /// from which the network hashes deploys using [`DeployHashScheme::DomainSeparated`] isn't
+    /// known.  The scheme given by [`DeployHashScheme::for_timestamp`] must still be checked
+    /// before the deploy is accepted.  A cached successful result of `is_valid` is relied upon if
+    /// available.
+    pub fn is_valid_for_hash(
+        &self,
+        deploy_hash: &DeployHash,
+    ) -> Result<(), DeployValidationFailure> {
+        if self.hash != *deploy_hash {
+            return Err(DeployValidationFailure::InvalidDeployHash);
+        }
+        if let Some((_, Ok(()))) = self.is_valid.as_ref() {
+            return Ok(());
+        }
+        let hash_scheme = DeployHashScheme::ALL
+            .iter()
+            .copied()
+            .find(|hash_scheme| hash_scheme.deploy_hash(&self.header) == self.hash)
+            .ok_or(DeployValidationFailure::InvalidDeployHash)?;
+        validate_deploy(self, hash_scheme, |deploy_hash, approval| {
+            approval.verify(deploy_hash)
+        })
+    }
+
+    /// Returns true if and only if:
+    ///   * the chain_name is correct,
+    ///   * the given limits are complied with,
+    ///   * the deploy is valid
+    ///
+    /// Note: if everything else checks out, calls the computationally expensive `is_valid` method.
+    pub fn is_acceptable(
+        &mut self,
+        chain_name: &str,
+        limits: &DeployLimits,
+        hash_scheme: DeployHashScheme,
+    ) -> Result<(), DeployValidationFailure> {
+        self.is_acceptable_with(chain_name, limits, hash_scheme, |deploy_hash, approval| {
+            approval.verify(deploy_hash)
+        })
+    }
+
+    /// As [`is_acceptable`](Self::is_acceptable), but verifying each approval via
+    /// `verify_approval`.  See [`is_valid_with`](Self::is_valid_with).
+    pub fn is_acceptable_with<F>(
+        &mut self,
+        chain_name: &str,
+        limits: &DeployLimits,
+        hash_scheme: DeployHashScheme,
+        verify_approval: F,
+    ) -> Result<(), DeployValidationFailure>
+    where
+        F: FnMut(&DeployHash, &Approval) -> Result<(), casper_types::crypto::Error>,
+    {
+        self.is_valid_size(limits.max_deploy_size)?;
+
+        let header = self.header();
+        // Check the length first, so that an oversized chain name is never copied or logged.
+        let chain_name_length = header.chain_name().len();
+        if chain_name_length > limits.max_chain_name_length as usize {
+            info!(
+                deploy_hash = %self.id(),
+                chain_name_length,
+                max_chain_name_length = limits.max_chain_name_length,
+                "chain name excessive"
+            );
+            return Err(DeployValidationFailure::ExcessiveChainNameLength {
+                max_length: limits.max_chain_name_length as usize,
+                got: chain_name_length,
+            });
+        }
+
+        if header.chain_name() != chain_name {
+            info!(
+                deploy_hash = %self.id(),
+                deploy_header = %header,
+                chain_name = %header.chain_name(),
+                "invalid chain identifier"
+            );
+            return Err(DeployValidationFailure::InvalidChainName {
+                expected: chain_name.to_string(),
+                got: header.chain_name().to_string(),
+            });
+        }
+
+        if header.dependencies().len() > limits.max_dependencies as usize {
+            info!(
+                deploy_hash = %self.id(),
+                deploy_header = %header,
+                max_dependencies = %limits.max_dependencies,
+                "deploy dependency ceiling exceeded"
+            );
+            return Err(DeployValidationFailure::ExcessiveDependencies {
+                max_dependencies: limits.max_dependencies,
+                got: header.dependencies().len(),
+            });
+        }
+
+        if header.ttl() > limits.max_ttl {
+            info!(
+                deploy_hash = %self.id(),
+                deploy_header = %header,
+                max_ttl = %limits.max_ttl,
+                "deploy ttl excessive"
+            );
+            return Err(DeployValidationFailure::ExcessiveTimeToLive {
+                max_ttl: limits.max_ttl,
+                got: header.ttl(),
+            });
+        }
+
+        if header.gas_price() == 0 {
+            info!(
+                deploy_hash = %self.id(),
+                deploy_header = %header,
+                "deploy gas price zero"
+            );
+            return Err(DeployValidationFailure::ZeroGasPrice);
+        }
+
+        if header.gas_price() > limits.max_gas_price {
+            info!(
+                deploy_hash = %self.id(),
+                deploy_header = %header,
+                max_gas_price = limits.max_gas_price,
+                "deploy gas price excessive"
+            );
+            return Err(DeployValidationFailure::ExcessiveGasPrice {
+                max_gas_price: limits.max_gas_price,
+                got: header.gas_price(),
+            });
+        }
+
+        let payment_args_length = self.payment().args().serialized_length();
+        if payment_args_length > limits.payment_args_max_length as usize {
+            info!(
+                payment_args_length,
+                payment_args_max_length = limits.payment_args_max_length,
+                "payment args excessive"
+            );
+            return Err(DeployValidationFailure::ExcessivePaymentArgsLength {
+                max_length: limits.payment_args_max_length as usize,
+                got: payment_args_length,
+            });
+        }
+
+        let session_args_length = self.session().args().serialized_length();
+        if session_args_length > limits.session_args_max_length as usize {
+            info!(
+                session_args_length,
+                session_args_max_length = limits.session_args_max_length,
+                "session args excessive"
+            );
+            return Err(DeployValidationFailure::ExcessiveSessionArgsLength {
+                max_length: limits.session_args_max_length as usize,
+                got: session_args_length,
+            });
+        }
+
+        for named_key in self
+            .payment()
+            .named_key()
+            .into_iter()
+            .chain(self.session().named_key())
+        {
+            if named_key.len() > limits.max_named_key_length as usize {
+                info!(
+                    named_key_length = named_key.len(),
+                    max_named_key_length = limits.max_named_key_length,
+                    "named key excessive"
+                );
+                return Err(DeployValidationFailure::ExcessiveNamedKeyLength {
+                    max_length: limits.max_named_key_length as usize,
+                    got: named_key.len(),
+                });
+            }
+        }
+
+        if self.session().is_transfer() {
+            let args = self.session().args();
+            let attempted_amounts = match batched_transfer_amounts(args)? {
+                Some(amounts) => {
+                    if amounts.len() > limits.max_transfers_per_batch as usize {
+                        info!(
+                            deploy_hash = %self.id(),
+                            transfer_count = amounts.len(),
+                            max_transfers_per_batch = limits.max_transfers_per_batch,
+                            "transfer batch excessive"
+                        );
+                        return Err(DeployValidationFailure::ExcessiveTransferBatch {
+                            max_transfers: limits.max_transfers_per_batch,
+                            got: amounts.len(),
+                        });
+                    }
+                    amounts
+                }
+                None => {
+                    let attempted = args
+                        .get(ARG_AMOUNT)
+                        .ok_or(DeployValidationFailure::MissingTransferAmount)?
+                        .clone()
+                        .into_t::<U512>()
+                        .map_err(|_| DeployValidationFailure::InvalidTransferAmount)?;
+                    vec![attempted]
+                }
+            };
+            let minimum = U512::from(limits.native_transfer_minimum_motes);
+            if let Some(attempted) = attempted_amounts
+                .into_iter()
+                .find(|attempted| *attempted < minimum)
+            {
+                return Err(DeployValidationFailure::InsufficientTransferAmount {
+                    minimum,
+                    attempted,
+                });
+            }
+        }
+
+        self.is_valid_with(hash_scheme, verify_approval)
+    }
+
+    /// Generates a random instance.
+    pub fn random<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        let timestamp = Timestamp::random(rng);
+        let ttl = TimeDiff::from(rng.gen_range(60_000..3_600_000));
+        let gas_price = rng.gen_range(1..100);
+
+        let dependencies = vec![
+            DeployHash::new(hash::hash(rng.next_u64().to_le_bytes())),
+            DeployHash::new(hash::hash(rng.next_u64().to_le_bytes())),
+            DeployHash::new(hash::hash(rng.next_u64().to_le_bytes())),
+        ];
+        let chain_name = String::from("casper-example");
+
+        let payment = rng.gen();
+        let session = rng.gen();
+
+        let secret_key = if rng.gen() {
+            SecretKey::ed25519_from_bytes(rng.gen::<[u8; SecretKey::ED25519_LENGTH]>())
+        } else {
+            SecretKey::secp256k1_from_bytes(rng.gen::<[u8; SecretKey::SECP256K1_LENGTH]>())
+        }
+        .expect("should create secret key");
+
+        Deploy::new(
+            timestamp,
+            ttl,
+            gas_price,
+            dependencies,
+            chain_name,
+            payment,
+            session,
+            &secret_key,
+        )
+    }
+}
+
+fn serialize_header(header: &DeployHeader) -> Vec<u8> {
+    header
+        .to_bytes()
+        .unwrap_or_else(|error| panic!("should serialize deploy header: {}", error))
+}
+
+fn serialize_body(payment: &ExecutableDeployItem, session: &ExecutableDeployItem) -> Vec<u8> {
+    let mut buffer = payment
+        .to_bytes()
+        .unwrap_or_else(|error| panic!("should serialize payment code: {}", error));
+    buffer.extend(
+        session
+            .to_bytes()
+            .unwrap_or_else(|error| panic!("should serialize session code: {}", error)),
+    );
+    buffer
+}
+
+// Computationally expensive validity check for a given deploy instance, including
+// asymmetric_key signing verification.
+fn validate_deploy<F>(
+    deploy: &Deploy,
+    hash_scheme: DeployHashScheme,
+    mut verify_approval: F,
+) -> Result<(), DeployValidationFailure>
+where
+    F: FnMut(&DeployHash, &Approval) -> Result<(), casper_types::crypto::Error>,
+{
+    let body_hash = hash_scheme.body_hash(&deploy.payment, &deploy.session);
+    if !body_hash.ct_eq(&deploy.header.body_hash) {
+        warn!(?deploy, ?body_hash, "invalid deploy body hash");
+        return Err(DeployValidationFailure::InvalidBodyHash);
+    }
+
+    let hash = hash_scheme.deploy_hash(&deploy.header);
+    if !hash.inner().ct_eq(deploy.hash.inner()) {
+        warn!(?deploy, ?hash, "invalid deploy hash");
+        return Err(DeployValidationFailure::InvalidDeployHash);
+    }
+
+    // We don't need to check for an empty set here. EE checks that the correct number and weight of
+    // signatures are provided when executing the deploy, so all we need to do here is check that
+    // any provided signatures are valid.
+    for (index, approval) in deploy.approvals.iter().enumerate() {
+        if let Err(error) = verify_approval(&deploy.hash, approval) {
+            warn!(?deploy, "failed to verify approval {}: {}", index, error);
+            return Err(DeployValidationFailure::InvalidApproval {
+                index,
+                error_msg: error.to_string(),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Sorts and deduplicates the given approvals.
+///
+/// Deploys serialized before approvals were held in canonical form may list them in any order, so
+/// this is applied whenever a deploy is decoded.
+fn canonicalize_approvals(mut approvals: Vec<Approval>) -> Vec<Approval> {
+    approvals.sort();
+    approvals.dedup();
+    approvals
+}
+
+fn deserialize_approvals<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<Approval>, D::Error> {
+    Vec::<Approval>::deserialize(deserializer).map(canonicalize_approvals)
+}
+
+impl Display for Deploy {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(
+            formatter,
+            "deploy[{}, {}, payment_code: {}, session_code: {}, approvals: {}]",
+            self.hash,
+            self.header,
+            self.payment,
+            self.session,
+            self.approvals.iter().join(", ")
+        )
+    }
+}
+
+impl ToBytes for Deploy {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        let mut buffer = bytesrepr::allocate_buffer(self)?;
+        buffer.extend(self.header.to_bytes()?);
+        buffer.extend(self.hash.to_bytes()?);
+        buffer.extend(self.payment.to_bytes()?);
+        buffer.extend(self.session.to_bytes()?);
+        buffer.extend(self.approvals.to_bytes()?);
+        Ok(buffer)
+    }
+
+    fn serialized_length(&self) -> usize {
+        self.header.serialized_length()
+            + self.hash.serialized_length()
+            + self.payment.serialized_length()
+            + self.session.serialized_length()
+            + self.approvals.serialized_length()
+    }
+}
+
+impl FromBytes for Deploy {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (header, remainder) = DeployHeader::from_bytes(bytes)?;
+        let (hash, remainder) = DeployHash::from_bytes(remainder)?;
+        let (payment, remainder) = ExecutableDeployItem::from_bytes(remainder)?;
+        let (session, remainder) = ExecutableDeployItem::from_bytes(remainder)?;
+        let (approvals, remainder) = Vec::<Approval>::from_bytes(remainder)?;
+        let maybe_valid_deploy = Deploy {
+            header,
+            hash,
+            payment,
+            session,
+            approvals: canonicalize_approvals(approvals),
+            is_valid: None,
+        };
+        Ok((maybe_valid_deploy, remainder))
+    }
+}
+
+/// Returns the amount of each transfer in a batched native transfer, or `None` if `args` are those
+/// of a single native transfer.
+///
+/// The batch's `transfers` arg must be a non-empty `List<Tuple3<T, U512, Option<u64>>>` of targets,
+/// amounts and ids, where the target `T` is an `AccountHash`, a `PublicKey` or a `Key`.
+fn batched_transfer_amounts(
+    args: &RuntimeArgs,
+) -> Result<Option<Vec<U512>>, DeployValidationFailure> {
+    let cl_value = match args.get(ARG_TRANSFERS) {
+        Some(cl_value) => cl_value,
+        None => return Ok(None),
+    };
+    let cl_type = cl_value.cl_type();
+    let maybe_amounts = if *cl_type == Vec::<(AccountHash, U512, Option<u64>)>::cl_type() {
+        batch_amounts::<AccountHash>(cl_value)
+    } else if *cl_type == Vec::<(PublicKey, U512, Option<u64>)>::cl_type() {
+        batch_amounts::<PublicKey>(cl_value)
+    } else if *cl_type == Vec::<(Key, U512, Option<u64>)>::cl_type() {
+        batch_amounts::<Key>(cl_value)
+    } else {
+        None
+    };
+    match maybe_amounts {
+        Some(amounts) if !amounts.is_empty() => Ok(Some(amounts)),
+        _ => Err(DeployValidationFailure::InvalidTransferBatch),
+    }
+}
+
+fn batch_amounts<T: CLTyped + FromBytes>(cl_value: &CLValue) -> Option<Vec<U512>> {
+    let batch: Vec<(T, U512, Option<u64>)> = cl_value.clone().into_t().ok()?;
+    Some(batch.into_iter().map(|(_, amount, _)| amount).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{iter, str::FromStr, time::Duration};
+
+    use casper_types::{bytesrepr::Bytes, runtime_args};
+
+    use super::*;
+
+    const MIN_TRANSFER_AMOUNT: u64 = 2_500_000_000;
+
+    fn deploy_limits() -> DeployLimits {
+        DeployLimits {
+            max_ttl: TimeDiff::from_str("1day").unwrap(),
+            max_dependencies: 10,
+            max_deploy_size: 1_048_576,
+            payment_args_max_length: 1024,
+            session_args_max_length: 1024,
+            native_transfer_minimum_motes: MIN_TRANSFER_AMOUNT,
+            max_transfers_per_batch: 3,
+            max_chain_name_length: 16,
+            max_named_key_length: 16,
+            max_gas_price: 10,
+        }
+    }
+
+    #[test]
+    fn json_roundtrip() {
+        let mut rng = rand::thread_rng();
+        let deploy = Deploy::random(&mut rng);
+        let json_string = serde_json::to_string_pretty(&deploy).unwrap();
+        let decoded = serde_json::from_str(&json_string).unwrap();
+        assert_eq!(deploy, decoded);
+    }
+
+    #[test]
+    fn bincode_roundtrip() {
+        let mut rng = rand::thread_rng();
+        let deploy = Deploy::random(&mut rng);
+        let serialized = bincode::serialize(&deploy).unwrap();
+        let deserialized = bincode::deserialize(&serialized).unwrap();
+        assert_eq!(deploy, deserialized);
+    }
+
+    #[test]
+    fn bytesrepr_roundtrip() {
+        let mut rng = rand::thread_rng();
+        let hash = DeployHash(Digest::random(&mut rng));
+        bytesrepr::test_serialization_roundtrip(&hash);
+
+        let deploy = Deploy::random(&mut rng);
+        bytesrepr::test_serialization_roundtrip(deploy.header());
+        bytesrepr::test_serialization_roundtrip(&deploy);
+    }
+
+    fn create_deploy<R: Rng>(
+        rng: &mut R,
+        ttl: TimeDiff,
+        dependency_count: usize,
+        chain_name: &str,
+    ) -> Deploy {
+        create_deploy_with_gas_price(rng, ttl, dependency_count, chain_name, 1)
+    }
+
+    fn create_deploy_with_gas_price<R: Rng>(
+        rng: &mut R,
+        ttl: TimeDiff,
+        dependency_count: usize,
+        chain_name: &str,
+        gas_price: u64,
+    ) -> Deploy {
+        let secret_key =
+            SecretKey::ed25519_from_bytes(rng.gen::<[u8; SecretKey::ED25519_LENGTH]>()).unwrap();
+        let dependencies = iter::repeat_with(|| DeployHash::random(rng))
+            .take(dependency_count)
+            .collect();
+        let transfer_args = {
+            let mut transfer_args = RuntimeArgs::new();
+            let value =
+                CLValue::from_t(U512::from(MIN_TRANSFER_AMOUNT)).expect("should create CLValue");
+            transfer_args.insert_cl_value(ARG_AMOUNT, value);
+            transfer_args
+        };
+        Deploy::new(
+            Timestamp::now(),
+            ttl,
+            gas_price,
+            dependencies,
+            chain_name.to_string(),
+            ExecutableDeployItem::ModuleBytes {
+                module_bytes: Bytes::new(),
+                args: RuntimeArgs::new(),
+            },
+            ExecutableDeployItem::Transfer {
+                args: transfer_args,
+            },
+            &secret_key,
+        )
+    }
+
+    #[test]
+    fn is_valid() {
+        let mut rng = rand::thread_rng();
+        let mut deploy = create_deploy(&mut rng, deploy_limits().max_ttl, 0, "net-1");
+        assert_eq!(deploy.is_valid, None, "is valid should initially be None");
+        deploy
+            .is_valid(DeployHashScheme::Legacy)
+            .expect("should be valid");
+        assert_eq!(
+            deploy.is_valid,
+            Some((DeployHashScheme::Legacy, Ok(()))),
+            "is valid should be true"
+        );
+    }
+
+    fn check_is_not_valid(mut invalid_deploy: Deploy, expected_error: DeployValidationFailure) {
+        assert!(
+            invalid_deploy.is_valid.is_none(),
+            "is valid should initially be None"
+        );
+        let actual_error = invalid_deploy
+            .is_valid(DeployHashScheme::Legacy)
+            .unwrap_err();
+
+        // Ignore the `error_msg` field of `InvalidApproval` when comparing to expected error, as
+        // this makes the test too fragile.  Otherwise expect the actual error should exactly match
+        // the expected error.
+        match expected_error {
+            DeployValidationFailure::InvalidApproval {
+                index: expected_index,
+                ..
+            } => match actual_error {
+                DeployValidationFailure::InvalidApproval {
+                    index: actual_index,
+                    ..
+                } => {
+                    assert_eq!(actual_index, expected_index);
+                }
+                _ => panic!("expected {}, got: {}", expected_error, actual_error),
+            },
+            _ => {
+                assert_eq!(actual_error, expected_error,);
+            }
+        }
+
+        // The actual error should have been lazily initialized correctly.
+        assert_eq!(
+            invalid_deploy.is_valid,
+            Some((DeployHashScheme::Legacy, Err(actual_error))),
+            "is valid should now be Some"
+        );
+    }
+
+    #[test]
+    fn not_valid_due_to_invalid_body_hash() {
+        let mut rng = rand::thread_rng();
+        let mut deploy = create_deploy(&mut rng, deploy_limits().max_ttl, 0, "net-1");
+
+        deploy.session = ExecutableDeployItem::Transfer {
+            args: runtime_args! {
+                "amount" => 1
+            },
+        };
+        check_is_not_valid(deploy, DeployValidationFailure::InvalidBodyHash);
+    }
+
+    #[test]
+    fn not_valid_due_to_invalid_deploy_hash() {
+        let mut rng = rand::thread_rng();
+        let mut deploy = create_deploy(&mut rng, deploy_limits().max_ttl, 0, "net-1");
+
+        deploy.header.gas_price = 2;
+        check_is_not_valid(deploy, DeployValidationFailure::InvalidDeployHash);
+    }
+
+    #[test]
+    fn not_valid_due_to_invalid_approval() {
+        let mut rng = rand::thread_rng();
+        let mut deploy = create_deploy(&mut rng, deploy_limits().max_ttl, 0, "net-1");
+
+        let deploy2 = Deploy::random(&mut rng);
+
+        deploy.approvals.extend(deploy2.approvals);
+        check_is_not_valid(
+            deploy,
+            DeployValidationFailure::InvalidApproval {
+                index: 1,
+                error_msg: String::new(), // This field is ignored in the check.
+            },
+        );
+    }
+
+    #[test]
+    fn should_not_verify_approvals_of_deploy_with_invalid_hash() {
+        let mut rng = rand::thread_rng();
+        let mut deploy = create_deploy(&mut rng, deploy_limits().max_ttl, 0, "net-1");
+        let mut verified = 0;
+        deploy
+            .is_valid_with(DeployHashScheme::Legacy, |deploy_hash, approval| {
+                verified += 1;
+                approval.verify(deploy_hash)
+            })
+            .expect("should be valid");
+        assert_eq!(verified, deploy.approvals.len());
+
+        let mut deploy = create_deploy(&mut rng, deploy_limits().max_ttl, 0, "net-1");
+        deploy.header.gas_price = 2;
+        let error = deploy
+            .is_valid_with(DeployHashScheme::Legacy, |_, _| {
+                panic!("should not verify approvals")
+            })
+            .unwrap_err();
+        assert_eq!(error, DeployValidationFailure::InvalidDeployHash);
+    }
+
+    #[test]
+    fn approvals_should_not_depend_on_signing_order() {
+        let mut rng = rand::thread_rng();
+        let deploy = create_deploy(&mut rng, deploy_limits().max_ttl, 0, "net-1");
+        let secret_key_1 =
+            SecretKey::ed25519_from_bytes(rng.gen::<[u8; SecretKey::ED25519_LENGTH]>()).unwrap();
+        let secret_key_2 =
+            SecretKey::ed25519_from_bytes(rng.gen::<[u8; SecretKey::ED25519_LENGTH]>()).unwrap();
+
+        let mut deploy_1 = deploy.clone();
+        deploy_1.sign(&secret_key_1);
+        deploy_1.sign(&secret_key_2);
+
+        let mut deploy_2 = deploy;
+        deploy_2.sign(&secret_key_2);
+        deploy_2.sign(&secret_key_1);
+
+        assert_eq!(deploy_1, deploy_2);
+        assert_eq!(deploy_1.to_bytes().unwrap(), deploy_2.to_bytes().unwrap());
+        assert_eq!(
+            serde_json::to_string(&deploy_1).unwrap(),
+            serde_json::to_string(&deploy_2).unwrap()
+        );
+
+        // Signing again with the same key shouldn't add a duplicate approval.
+        deploy_1.sign(&secret_key_1);
+        assert_eq!(deploy_1.approvals().len(), 3);
+        assert_eq!(deploy_1, deploy_2);
+    }
+
+    #[test]
+    fn should_add_prehashed_approval() {
+        let mut rng = rand::thread_rng();
+        let mut deploy = create_deploy(&mut rng, deploy_limits().max_ttl, 0, "net-1");
+        let secret_keys = vec![
+            SecretKey::ed25519_from_bytes(rng.gen::<[u8; SecretKey::ED25519_LENGTH]>()).unwrap(),
+            SecretKey::secp256k1_from_bytes([1; SecretKey::SECP256K1_LENGTH]).unwrap(),
+        ];
+
+        for secret_key in &secret_keys {
+            let signer = PublicKey::from(secret_key);
+            let signature = crypto::sign_prehashed(deploy.id().inner(), secret_key, &signer);
+            deploy
+                .add_approval(signer, signature)
+                .expect("should add approval");
+        }
+        assert_eq!(deploy.approvals().len(), 3);
+        deploy
+            .is_valid(DeployHashScheme::Legacy)
+            .expect("should be valid");
+
+        // A prehashed signature of some other digest should be rejected.
+        let secret_key = &secret_keys[0];
+        let signer = PublicKey::from(secret_key);
+        let signature = crypto::sign_prehashed(&hash::hash(b"other"), secret_key, &signer);
+        assert!(deploy.add_approval(signer, signature).is_err());
+        assert_eq!(deploy.approvals().len(), 3);
+    }
+
+    #[test]
+    fn should_canonicalize_approvals_when_decoding() {
+        let mut rng = rand::thread_rng();
+        let mut deploy = create_deploy(&mut rng, deploy_limits().max_ttl, 0, "net-1");
+        for _ in 0..3 {
+            let secret_key =
+                SecretKey::ed25519_from_bytes(rng.gen::<[u8; SecretKey::ED25519_LENGTH]>())
+                    .unwrap();
+            deploy.sign(&secret_key);
+        }
+
+        // Simulate a deploy serialized with unsorted and duplicated approvals.
+        let mut unsorted = deploy.clone();
+        unsorted.approvals.reverse();
+        unsorted.approvals.push(deploy.approvals[0].clone());
+        assert_ne!(unsorted, deploy);
+
+        let decoded: Deploy = bytesrepr::deserialize(unsorted.to_bytes().unwrap()).unwrap();
+        assert_eq!(decoded, deploy);
+
+        let json_string = serde_json::to_string(&unsorted).unwrap();
+        let decoded: Deploy = serde_json::from_str(&json_string).unwrap();
+        assert_eq!(decoded, deploy);
+
+        let serialized = bincode::serialize(&unsorted).unwrap();
+        let decoded: Deploy = bincode::deserialize(&serialized).unwrap();
+        assert_eq!(decoded, deploy);
+    }
+
+    #[test]
+    fn is_acceptable() {
+        let mut rng = rand::thread_rng();
+        let chain_name = "net-1";
+        let limits = deploy_limits();
+
+        let mut deploy = create_deploy(
+            &mut rng,
+            limits.max_ttl,
+            limits.max_dependencies.into(),
+            chain_name,
+        );
+        deploy
+            .is_acceptable(chain_name, &limits, DeployHashScheme::Legacy)
+            .expect("should be acceptable");
+    }
+
+    #[test]
+    fn not_acceptable_due_to_invalid_chain_name() {
+        let mut rng = rand::thread_rng();
+        let expected_chain_name = "net-1";
+        let wrong_chain_name = "net-2".to_string();
+        let limits = deploy_limits();
+
+        let mut deploy = create_deploy(
+            &mut rng,
+            limits.max_ttl,
+            limits.max_dependencies.into(),
+            &wrong_chain_name,
+        );
+
+        let expected_error = DeployValidationFailure::InvalidChainName {
+            expected: expected_chain_name.to_string(),
+            got: wrong_chain_name,
+        };
+
+        assert_eq!(
+            deploy.is_acceptable(expected_chain_name, &limits, DeployHashScheme::Legacy),
+            Err(expected_error)
+        );
+        assert!(
+            deploy.is_valid.is_none(),
+            "deploy should not have run expensive `is_valid` call"
+        );
+    }
+
+    #[test]
+    fn not_acceptable_due_to_excessive_dependencies() {
+        let mut rng = rand::thread_rng();
+        let chain_name = "net-1";
+        let limits = deploy_limits();
+
+        let dependency_count = usize::from(limits.max_dependencies + 1);
+
+        let mut deploy = create_deploy(&mut rng, limits.max_ttl, dependency_count, chain_name);
+
+        let expected_error = DeployValidationFailure::ExcessiveDependencies {
+            max_dependencies: limits.max_dependencies,
+            got: dependency_count,
+        };
+
+        assert_eq!(
+            deploy.is_acceptable(chain_name, &limits, DeployHashScheme::Legacy),
+            Err(expected_error)
+        );
+        assert!(
+            deploy.is_valid.is_none(),
+            "deploy should not have run expensive `is_valid` call"
+        );
+    }
+
+    #[test]
+    fn not_acceptable_due_to_excessive_ttl() {
+        let mut rng = rand::thread_rng();
+        let chain_name = "net-1";
+        let limits = deploy_limits();
+
+        let ttl = limits.max_ttl + TimeDiff::from(Duration::from_secs(1));
+
+        let mut deploy = create_deploy(&mut rng, ttl, limits.max_dependencies.into(), chain_name);
+
+        let expected_error = DeployValidationFailure::ExcessiveTimeToLive {
+            max_ttl: limits.max_ttl,
+            got: ttl,
+        };
+
+        assert_eq!(
+            deploy.is_acceptable(chain_name, &limits, DeployHashScheme::Legacy),
+            Err(expected_error)
+        );
+        assert!(
+            deploy.is_valid.is_none(),
+            "deploy should not have run expensive `is_valid` call"
+        );
+    }
+
+    #[test]
+    fn not_acceptable_due_to_excessive_chain_name_length() {
+        let mut rng = rand::thread_rng();
+        let limits = deploy_limits();
+
+        let chain_name = "n".repeat(limits.max_chain_name_length as usize);
+        let mut deploy = create_deploy(
+            &mut rng,
+            limits.max_ttl,
+            limits.max_dependencies.into(),
+            &chain_name,
+        );
+        deploy
+            .is_acceptable(&chain_name, &limits, DeployHashScheme::Legacy)
+            .expect("chain name at the limit should be acceptable");
+
+        let long_chain_name = "n".repeat(limits.max_chain_name_length as usize + 1);
+        let mut deploy = create_deploy(
+            &mut rng,
+            limits.max_ttl,
+            limits.max_dependencies.into(),
+            &long_chain_name,
+        );
+
+        let expected_error = DeployValidationFailure::ExcessiveChainNameLength {
+            max_length: limits.max_chain_name_length as usize,
+            got: long_chain_name.len(),
+        };
+
+        assert_eq!(
+            deploy.is_acceptable(&long_chain_name, &limits, DeployHashScheme::Legacy),
+            Err(expected_error)
+        );
+        assert!(
+            deploy.is_valid.is_none(),
+            "deploy should not have run expensive `is_valid` call"
+        );
+    }
+
+    #[test]
+    fn not_acceptable_due_to_zero_gas_price() {
+        let mut rng = rand::thread_rng();
+        let chain_name = "net-1";
+        let limits = deploy_limits();
+
+        let mut deploy = create_deploy_with_gas_price(
+            &mut rng,
+            limits.max_ttl,
+            limits.max_dependencies.into(),
+            chain_name,
+            0,
+        );
+
+        assert_eq!(
+            deploy.is_acceptable(chain_name, &limits, DeployHashScheme::Legacy),
+            Err(DeployValidationFailure::ZeroGasPrice)
+        );
+        assert!(
+            deploy.is_valid.is_none(),
+            "deploy should not have run expensive `is_valid` call"
+        );
+        assert!(!deploy.header().is_valid(&limits, Timestamp::now()));
+    }
+
+    #[test]
+    fn not_acceptable_due_to_excessive_gas_price() {
+        let mut rng = rand::thread_rng();
+        let chain_name = "net-1";
+        let limits = deploy_limits();
+
+        for gas_price in &[1, limits.max_gas_price] {
+            let mut deploy = create_deploy_with_gas_price(
+                &mut rng,
+                limits.max_ttl,
+                limits.max_dependencies.into(),
+                chain_name,
+                *gas_price,
+            );
+            deploy
+                .is_acceptable(chain_name, &limits, DeployHashScheme::Legacy)
+                .expect("gas price within the limit should be acceptable");
+            assert!(deploy.header().is_valid(&limits, Timestamp::now()));
+        }
+
+        for gas_price in &[limits.max_gas_price + 1, u64::MAX] {
+            let mut deploy = create_deploy_with_gas_price(
+                &mut rng,
+                limits.max_ttl,
+                limits.max_dependencies.into(),
+                chain_name,
+                *gas_price,
+            );
+
+            let expected_error = DeployValidationFailure::ExcessiveGasPrice {
+                max_gas_price: limits.max_gas_price,
+                got: *gas_price,
+            };
+
+            assert_eq!(
+                deploy.is_acceptable(chain_name, &limits, DeployHashScheme::Legacy),
+                Err(expected_error)
+            );
+            assert!(
+                deploy.is_valid.is_none(),
+                "deploy should not have run expensive `is_valid` call"
+            );
+            assert!(!deploy.header().is_valid(&limits, Timestamp::now()));
+        }
+    }
+
+    #[test]
+    fn not_acceptable_due_to_excessive_named_key_length() {
+        let mut rng = rand::thread_rng();
+        let chain_name = "net-1";
+        let limits = deploy_limits();
+        let secret_key =
+            SecretKey::ed25519_from_bytes(rng.gen::<[u8; SecretKey::ED25519_LENGTH]>()).unwrap();
+        let payment = ExecutableDeployItem::ModuleBytes {
+            module_bytes: Bytes::new(),
+            args: RuntimeArgs::new(),
+        };
+        let session_by_name = |name: String| ExecutableDeployItem::StoredContractByName {
+            name,
+            entry_point: String::from("call"),
+            args: RuntimeArgs::new(),
+        };
+
+        let name = "k".repeat(limits.max_named_key_length as usize);
+        let mut deploy = Deploy::new(
+            Timestamp::now(),
+            limits.max_ttl,
+            1,
+            vec![],
+            chain_name.to_string(),
+            payment.clone(),
+            session_by_name(name),
+            &secret_key,
+        );
+        deploy
+            .is_acceptable(chain_name, &limits, DeployHashScheme::Legacy)
+            .expect("named key at the limit should be acceptable");
+
+        let long_name = "k".repeat(limits.max_named_key_length as usize + 1);
+        let mut deploy = Deploy::new(
+            Timestamp::now(),
+            limits.max_ttl,
+            1,
+            vec![],
+            chain_name.to_string(),
+            payment,
+            session_by_name(long_name.clone()),
+            &secret_key,
+        );
+
+        let expected_error = DeployValidationFailure::ExcessiveNamedKeyLength {
+            max_length: limits.max_named_key_length as usize,
+            got: long_name.len(),
+        };
+
+        assert_eq!(
+            deploy.is_acceptable(chain_name, &limits, DeployHashScheme::Legacy),
+            Err(expected_error)
+        );
+        assert!(
+            deploy.is_valid.is_none(),
+            "deploy should not have run expensive `is_valid` call"
+        );
+    }
+
+    fn create_batched_transfer<R: Rng, T: CLTyped + ToBytes>(
+        rng: &mut R,
+        chain_name: &str,
+        batch: Vec<(T, U512, Option<u64>)>,
+    ) -> Deploy {
+        let secret_key =
+            SecretKey::ed25519_from_bytes(rng.gen::<[u8; SecretKey::ED25519_LENGTH]>()).unwrap();
+        Deploy::new(
+            Timestamp::now(),
+            deploy_limits().max_ttl,
+            1,
+            vec![],
+            chain_name.to_string(),
+            ExecutableDeployItem::ModuleBytes {
+                module_bytes: Bytes::new(),
+                args: RuntimeArgs::new(),
+            },
+            ExecutableDeployItem::Transfer {
+                args: runtime_args! { ARG_TRANSFERS => batch },
+            },
+            &secret_key,
+        )
+    }
+
+    #[test]
+    fn batched_transfer_is_acceptable() {
+        let mut rng = rand::thread_rng();
+        let chain_name = "net-1";
+        let limits = deploy_limits();
+        let amount = U512::from(MIN_TRANSFER_AMOUNT);
+
+        let batch = (0..limits.max_transfers_per_batch)
+            .map(|index| {
+                (
+                    AccountHash::new([index as u8; 32]),
+                    amount,
+                    Some(index.into()),
+                )
+            })
+            .collect();
+        let mut deploy = create_batched_transfer::<_, AccountHash>(&mut rng, chain_name, batch);
+        deploy
+            .is_acceptable(chain_name, &limits, DeployHashScheme::Legacy)
+            .expect("batch at the limit should be acceptable");
+
+        let public_key = PublicKey::from(&SecretKey::ed25519_from_bytes([1; 32]).unwrap());
+        let batch = vec![(public_key, amount, None)];
+        let mut deploy = create_batched_transfer::<_, PublicKey>(&mut rng, chain_name, batch);
+        deploy
+            .is_acceptable(chain_name, &limits, DeployHashScheme::Legacy)
+            .expect("batch targeting a public key should be acceptable");
+    }
+
+    #[test]
+    fn not_acceptable_due_to_excessive_transfer_batch() {
+        let mut rng = rand::thread_rng();
+        let chain_name = "net-1";
+        let limits = deploy_limits();
+        let amount = U512::from(MIN_TRANSFER_AMOUNT);
+
+        let got = limits.max_transfers_per_batch as usize + 1;
+        let batch = (0..got)
+            .map(|index| {
+                (
+                    Key::Account(AccountHash::new([index as u8; 32])),
+                    amount,
+                    None,
+                )
+            })
+            .collect();
+        let mut deploy = create_batched_transfer::<_, Key>(&mut rng, chain_name, batch);
+
+        let expected_error = DeployValidationFailure::ExcessiveTransferBatch {
+            max_transfers: limits.max_transfers_per_batch,
+            got,
+        };
+        assert_eq!(
+            deploy.is_acceptable(chain_name, &limits, DeployHashScheme::Legacy),
+            Err(expected_error)
+        );
+        assert!(
+            deploy.is_valid.is_none(),
+            "deploy should not have run expensive `is_valid` call"
+        );
+    }
+
+    #[test]
+    fn not_acceptable_due_to_invalid_transfer_batch() {
+        let mut rng = rand::thread_rng();
+        let chain_name = "net-1";
+        let limits = deploy_limits();
+
+        let empty_batch = create_batched_transfer::<_, AccountHash>(&mut rng, chain_name, vec![]);
+        let untyped_batch = create_batched_transfer(
+            &mut rng,
+            chain_name,
+            vec![(String::from("target"), U512::one(), None)],
+        );
+        for mut deploy in vec![empty_batch, untyped_batch] {
+            assert_eq!(
+                deploy.is_acceptable(chain_name, &limits, DeployHashScheme::Legacy),
+                Err(DeployValidationFailure::InvalidTransferBatch)
+            );
+        }
+
+        let too_small = U512::from(MIN_TRANSFER_AMOUNT - 1);
+        let batch = vec![
+            (
+                AccountHash::new([1; 32]),
+                U512::from(MIN_TRANSFER_AMOUNT),
+                None,
+            ),
+            (AccountHash::new([2; 32]), too_small, None),
+        ];
+        let mut deploy = create_batched_transfer(&mut rng, chain_name, batch);
+        let expected_error = DeployValidationFailure::InsufficientTransferAmount {
+            minimum: U512::from(MIN_TRANSFER_AMOUNT),
+            attempted: too_small,
+        };
+        assert_eq!(
+            deploy.is_acceptable(chain_name, &limits, DeployHashScheme::Legacy),
+            Err(expected_error)
+        );
+    }
+
+    /// Returns a deploy with fixed contents, hashed using the given scheme.
+    fn fixture_deploy(hash_scheme: DeployHashScheme) -> Deploy {
+        let secret_key = SecretKey::ed25519_from_bytes([7; SecretKey::ED25519_LENGTH]).unwrap();
+        let empty_module_bytes = ExecutableDeployItem::ModuleBytes {
+            module_bytes: Bytes::new(),
+            args: RuntimeArgs::new(),
+        };
+        Deploy::new_with_hash_scheme(
+            Timestamp::from(1_600_000_000_000),
+            TimeDiff::from_seconds(1_800),
+            1,
+            vec![],
+            String::from("casper-example"),
+            empty_module_bytes.clone(),
+            empty_module_bytes,
+            &secret_key,
+            hash_scheme,
+        )
+    }
+
+    #[test]
+    fn should_hash_fixture_deploy_using_legacy_scheme() {
+        let mut deploy = fixture_deploy(DeployHashScheme::Legacy);
+        assert_eq!(
+            format!("{:?}", deploy.header().body_hash()),
+            "1c12e51a42fefd2260348ebed12c8e82e4abafba92e55146350c0dc334c4fb4c"
+        );
+        assert_eq!(
+            format!("{:?}", deploy.id().inner()),
+            "fff036fd6c84f8c5b7f3de4a39d1722295c638eb0d37e252cfc8a6de67723368"
+        );
+        deploy
+            .is_valid(DeployHashScheme::Legacy)
+            .expect("should be valid");
+    }
+
+    #[test]
+    fn should_hash_fixture_deploy_using_domain_separated_scheme() {
+        let mut deploy = fixture_deploy(DeployHashScheme::DomainSeparated);
+        assert_eq!(
+            format!("{:?}", deploy.header().body_hash()),
+            "8f404856225ff18c292a5dc7c9caebe5da40dd8e36a7a582daa687750beb59da"
+        );
+        assert_eq!(
+            format!("{:?}", deploy.id().inner()),
+            "24a9e2b2d920794ae33541b0fd36d3870288052dfe665fc2db90bc5d003c0be1"
+        );
+        deploy
+            .is_valid(DeployHashScheme::DomainSeparated)
+            .expect("should be valid");
+    }
+
+    #[test]
+    fn should_reject_deploy_hashed_using_other_scheme() {
+        let mut legacy_deploy = fixture_deploy(DeployHashScheme::Legacy);
+        assert_eq!(
+            legacy_deploy.is_valid(DeployHashScheme::DomainSeparated),
+            Err(DeployValidationFailure::InvalidBodyHash)
+        );
+        // The cached result is only reused for the same scheme.
+        legacy_deploy
+            .is_valid(DeployHashScheme::Legacy)
+            .expect("should be valid");
+
+        let mut domain_separated_deploy = fixture_deploy(DeployHashScheme::DomainSeparated);
+        assert_eq!(
+            domain_separated_deploy.is_valid(DeployHashScheme::Legacy),
+            Err(DeployValidationFailure::InvalidBodyHash)
+        );
+    }
+
+    #[test]
+    fn should_select_hash_scheme_by_protocol_version() {
+        let activation_version = DeployHashScheme::DOMAIN_SEPARATED_PROTOCOL_VERSION;
+        assert_eq!(
+            DeployHashScheme::for_protocol_version(ProtocolVersion::V1_0_0),
+            DeployHashScheme::Legacy
+        );
+        assert_eq!(
+            DeployHashScheme::for_protocol_version(ProtocolVersion::from_parts(1, 99, 99)),
+            DeployHashScheme::Legacy
+        );
+        assert_eq!(
+            DeployHashScheme::for_protocol_version(activation_version),
+            DeployHashScheme::DomainSeparated
+        );
+        assert_eq!(
+            DeployHashScheme::for_protocol_version(ProtocolVersion::from_parts(
+                activation_version.value().major,
+                activation_version.value().minor + 1,
+                0
+            )),
+            DeployHashScheme::DomainSeparated
+        );
+    }
+
+    #[test]
+    fn should_select_hash_scheme_by_deploy_timestamp() {
+        let activation = Timestamp::from(1_000);
+        assert_eq!(
+            DeployHashScheme::for_timestamp(Timestamp::from(2_000), None),
+            DeployHashScheme::Legacy
+        );
+        assert_eq!(
+            DeployHashScheme::for_timestamp(Timestamp::from(999), Some(activation)),
+            DeployHashScheme::Legacy
+        );
+        assert_eq!(
+            DeployHashScheme::for_timestamp(activation, Some(activation)),
+            DeployHashScheme::DomainSeparated
+        );
+        assert_eq!(
+            DeployHashScheme::for_timestamp(Timestamp::from(1_001), Some(activation)),
+            DeployHashScheme::DomainSeparated
+        );
+    }
+}
@@ -0,0 +1,600 @@
+//! The executable code of a deploy's payment and session.
+
+// TODO - remove once schemars stops causing warning.
+#![allow(clippy::field_reassign_with_default)]
+
+use std::fmt::{self, Debug, Display, Formatter};
+
+use datasize::DataSize;
+use hex_buffer_serde::{Hex, HexForm};
+use hex_fmt::HexFmt;
+use rand::{
+    distributions::{Alphanumeric, Distribution, Standard},
+    Rng,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use casper_types::{
+    bytesrepr::{self, Bytes, FromBytes, ToBytes, U8_SERIALIZED_LENGTH},
+    contracts::{ContractVersion, DEFAULT_ENTRY_POINT_NAME},
+    system::mint::ARG_AMOUNT,
+    CLValue, ContractHash, ContractPackageHash, RuntimeArgs, U512,
+};
+
+const TAG_LENGTH: usize = U8_SERIALIZED_LENGTH;
+const MODULE_BYTES_TAG: u8 = 0;
+const STORED_CONTRACT_BY_HASH_TAG: u8 = 1;
+const STORED_CONTRACT_BY_NAME_TAG: u8 = 2;
+const STORED_VERSIONED_CONTRACT_BY_HASH_TAG: u8 = 3;
+const STORED_VERSIONED_CONTRACT_BY_NAME_TAG: u8 = 4;
+const TRANSFER_TAG: u8 = 5;
+
+/// The executable component of a deploy; either its payment or its session code.
+#[derive(
+    Clone, DataSize, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(deny_unknown_fields)]
+pub enum ExecutableDeployItem {
+    /// Raw Wasm module bytes, called via their implicit `call` entry point.
+    ModuleBytes {
+        /// The Wasm module.
+        ///
+        /// Encoded as a hex string in human-readable formats, although a JSON array of integers is
+        /// also accepted when deserializing.
+        #[schemars(with = "String", description = "Hex-encoded raw Wasm bytes.")]
+        module_bytes: Bytes,
+        /// The runtime arguments.
+        args: RuntimeArgs,
+    },
+    /// A stored contract referenced by its hash.
+    StoredContractByHash {
+        /// The contract hash.
+        #[serde(with = "HexForm")]
+        #[schemars(with = "String", description = "Hex-encoded hash.")]
+        hash: ContractHash,
+        /// The name of the entry point to call.
+        entry_point: String,
+        /// The runtime arguments.
+        args: RuntimeArgs,
+    },
+    /// A stored contract referenced by a named key in the caller's account.
+    StoredContractByName {
+        /// The named key.
+        name: String,
+        /// The name of the entry point to call.
+        entry_point: String,
+        /// The runtime arguments.
+        args: RuntimeArgs,
+    },
+    /// A version of a stored contract package referenced by its hash.
+    StoredVersionedContractByHash {
+        /// The contract package hash.
+        #[serde(with = "HexForm")]
+        #[schemars(with = "String", description = "Hex-encoded hash.")]
+        hash: ContractPackageHash,
+        /// The contract version, or `None` for the highest enabled version.
+        version: Option<ContractVersion>,
+        /// The name of the entry point to call.
+        entry_point: String,
+        /// The runtime arguments.
+        args: RuntimeArgs,
+    },
+    /// A version of a stored contract package referenced by a named key in the caller's account.
+    StoredVersionedContractByName {
+        /// The named key.
+        name: String,
+        /// The contract version, or `None` for the highest enabled version.
+        version: Option<ContractVersion>,
+        /// The name of the entry point to call.
+        entry_point: String,
+        /// The runtime arguments.
+        args: RuntimeArgs,
+    },
+    /// A native transfer, executed without Wasm.
+    Transfer {
+        /// The runtime arguments.
+        args: RuntimeArgs,
+    },
+}
+
+impl ExecutableDeployItem {
+    /// Returns the name of the entry point to be called.
+    pub fn entry_point_name(&self) -> &str {
+        match self {
+            ExecutableDeployItem::ModuleBytes { .. } | ExecutableDeployItem::Transfer { .. } => {
+                DEFAULT_ENTRY_POINT_NAME
+            }
+            ExecutableDeployItem::StoredVersionedContractByName { entry_point, .. }
+            | ExecutableDeployItem::StoredVersionedContractByHash { entry_point, .. }
+            | ExecutableDeployItem::StoredContractByHash { entry_point, .. }
+            | ExecutableDeployItem::StoredContractByName { entry_point, .. } => entry_point,
+        }
+    }
+
+    /// Returns the runtime arguments.
+    pub fn args(&self) -> &RuntimeArgs {
+        match self {
+            ExecutableDeployItem::ModuleBytes { args, .. }
+            | ExecutableDeployItem::StoredContractByHash { args, .. }
+            | ExecutableDeployItem::StoredContractByName { args, .. }
+            | ExecutableDeployItem::StoredVersionedContractByHash { args, .. }
+            | ExecutableDeployItem::StoredVersionedContractByName { args, .. }
+            | ExecutableDeployItem::Transfer { args } => args,
+        }
+    }
+
+    /// Returns the named key in the caller's account under which the stored contract or contract
+    /// package to be called is found, if it is referenced by name.
+    pub fn named_key(&self) -> Option<&str> {
+        match self {
+            ExecutableDeployItem::StoredContractByName { name, .. }
+            | ExecutableDeployItem::StoredVersionedContractByName { name, .. } => Some(name),
+            ExecutableDeployItem::ModuleBytes { .. }
+            | ExecutableDeployItem::StoredContractByHash { .. }
+            | ExecutableDeployItem::StoredVersionedContractByHash { .. }
+            | ExecutableDeployItem::Transfer { .. } => None,
+        }
+    }
+
+    /// Returns `true` if this is a native transfer.
+    pub fn is_transfer(&self) -> bool {
+        matches!(self, ExecutableDeployItem::Transfer { .. })
+    }
+}
+
+impl ToBytes for ExecutableDeployItem {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        let mut buffer = bytesrepr::allocate_buffer(self)?;
+        match self {
+            ExecutableDeployItem::ModuleBytes { module_bytes, args } => {
+                buffer.insert(0, MODULE_BYTES_TAG);
+                buffer.extend(module_bytes.to_bytes()?);
+                buffer.extend(args.to_bytes()?);
+            }
+            ExecutableDeployItem::StoredContractByHash {
+                hash,
+                entry_point,
+                args,
+            } => {
+                buffer.insert(0, STORED_CONTRACT_BY_HASH_TAG);
+                buffer.extend(hash.to_bytes()?);
+                buffer.extend(entry_point.to_bytes()?);
+                buffer.extend(args.to_bytes()?)
+            }
+            ExecutableDeployItem::StoredContractByName {
+                name,
+                entry_point,
+                args,
+            } => {
+                buffer.insert(0, STORED_CONTRACT_BY_NAME_TAG);
+                buffer.extend(name.to_bytes()?);
+                buffer.extend(entry_point.to_bytes()?);
+                buffer.extend(args.to_bytes()?)
+            }
+            ExecutableDeployItem::StoredVersionedContractByHash {
+                hash,
+                version,
+                entry_point,
+                args,
+            } => {
+                buffer.insert(0, STORED_VERSIONED_CONTRACT_BY_HASH_TAG);
+                buffer.extend(hash.to_bytes()?);
+                buffer.extend(version.to_bytes()?);
+                buffer.extend(entry_point.to_bytes()?);
+                buffer.extend(args.to_bytes()?)
+            }
+            ExecutableDeployItem::StoredVersionedContractByName {
+                name,
+                version,
+                entry_point,
+                args,
+            } => {
+                buffer.insert(0, STORED_VERSIONED_CONTRACT_BY_NAME_TAG);
+                buffer.extend(name.to_bytes()?);
+                buffer.extend(version.to_bytes()?);
+                buffer.extend(entry_point.to_bytes()?);
+                buffer.extend(args.to_bytes()?)
+            }
+            ExecutableDeployItem::Transfer { args } => {
+                buffer.insert(0, TRANSFER_TAG);
+                buffer.extend(args.to_bytes()?)
+            }
+        }
+        Ok(buffer)
+    }
+
+    fn serialized_length(&self) -> usize {
+        TAG_LENGTH
+            + match self {
+                ExecutableDeployItem::ModuleBytes { module_bytes, args } => {
+                    module_bytes.serialized_length() + args.serialized_length()
+                }
+                ExecutableDeployItem::StoredContractByHash {
+                    hash,
+                    entry_point,
+                    args,
+                } => {
+                    hash.serialized_length()
+                        + entry_point.serialized_length()
+                        + args.serialized_length()
+                }
+                ExecutableDeployItem::StoredContractByName {
+                    name,
+                    entry_point,
+                    args,
+                } => {
+                    name.serialized_length()
+                        + entry_point.serialized_length()
+                        + args.serialized_length()
+                }
+                ExecutableDeployItem::StoredVersionedContractByHash {
+                    hash,
+                    version,
+                    entry_point,
+                    args,
+                } => {
+                    hash.serialized_length()
+                        + version.serialized_length()
+                        + entry_point.serialized_length()
+                        + args.serialized_length()
+                }
+                ExecutableDeployItem::StoredVersionedContractByName {
+                    name,
+                    version,
+                    entry_point,
+                    args,
+                } => {
+                    name.serialized_length()
+                        + version.serialized_length()
+                        + entry_point.serialized_length()
+                        + args.serialized_length()
+                }
+                ExecutableDeployItem::Transfer { args } => args.serialized_length(),
+            }
+    }
+}
+
+impl FromBytes for ExecutableDeployItem {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (tag, remainder) = u8::from_bytes(bytes)?;
+        match tag {
+            MODULE_BYTES_TAG => {
+                let (module_bytes, remainder) = FromBytes::from_bytes(remainder)?;
+                let (args, remainder) = FromBytes::from_bytes(remainder)?;
+                Ok((
+                    ExecutableDeployItem::ModuleBytes { module_bytes, args },
+                    remainder,
+                ))
+            }
+            STORED_CONTRACT_BY_HASH_TAG => {
+                let (hash, remainder) = FromBytes::from_bytes(remainder)?;
+                let (entry_point, remainder) = String::from_bytes(remainder)?;
+                let (args, remainder) = FromBytes::from_bytes(remainder)?;
+                Ok((
+                    ExecutableDeployItem::StoredContractByHash {
+                        hash,
+                        entry_point,
+                        args,
+                    },
+                    remainder,
+                ))
+            }
+            STORED_CONTRACT_BY_NAME_TAG => {
+                let (name, remainder) = String::from_bytes(remainder)?;
+                let (entry_point, remainder) = String::from_bytes(remainder)?;
+                let (args, remainder) = FromBytes::from_bytes(remainder)?;
+                Ok((
+                    ExecutableDeployItem::StoredContractByName {
+                        name,
+                        entry_point,
+                        args,
+                    },
+                    remainder,
+                ))
+            }
+            STORED_VERSIONED_CONTRACT_BY_HASH_TAG => {
+                let (hash, remainder) = FromBytes::from_bytes(remainder)?;
+                let (version, remainder) = Option::<ContractVersion>::from_bytes(remainder)?;
+                let (entry_point, remainder) = String::from_bytes(remainder)?;
+                let (args, remainder) = FromBytes::from_bytes(remainder)?;
+                Ok((
+                    ExecutableDeployItem::StoredVersionedContractByHash {
+                        hash,
+                        version,
+                        entry_point,
+                        args,
+                    },
+                    remainder,
+                ))
+            }
+            STORED_VERSIONED_CONTRACT_BY_NAME_TAG => {
+                let (name, remainder) = String::from_bytes(remainder)?;
+                let (version, remainder) = Option::<ContractVersion>::from_bytes(remainder)?;
+                let (entry_point, remainder) = String::from_bytes(remainder)?;
+                let (args, remainder) = FromBytes::from_bytes(remainder)?;
+                Ok((
+                    ExecutableDeployItem::StoredVersionedContractByName {
+                        name,
+                        version,
+                        entry_point,
+                        args,
+                    },
+                    remainder,
+                ))
+            }
+            TRANSFER_TAG => {
+                let (args, remainder) = FromBytes::from_bytes(remainder)?;
+                Ok((ExecutableDeployItem::Transfer { args }, remainder))
+            }
+            tag => Err(bytesrepr::Error::invalid_tag::<Self>(tag)),
+        }
+    }
+}
+
+impl Display for ExecutableDeployItem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ExecutableDeployItem::ModuleBytes { module_bytes, .. } => {
+                write!(f, "module-bytes [{} bytes]", module_bytes.len())
+            }
+            ExecutableDeployItem::StoredContractByHash {
+                hash, entry_point, ..
+            } => write!(
+                f,
+                "stored-contract-by-hash: {:10}, entry-point: {}",
+                HexFmt(hash),
+                entry_point,
+            ),
+            ExecutableDeployItem::StoredContractByName {
+                name, entry_point, ..
+            } => write!(
+                f,
+                "stored-contract-by-name: {}, entry-point: {}",
+                name, entry_point,
+            ),
+            ExecutableDeployItem::StoredVersionedContractByHash {
+                hash,
+                version: Some(ver),
+                entry_point,
+                ..
+            } => write!(
+                f,
+                "stored-versioned-contract-by-hash: {:10}, version: {}, entry-point: {}",
+                HexFmt(hash),
+                ver,
+                entry_point,
+            ),
+            ExecutableDeployItem::StoredVersionedContractByHash {
+                hash, entry_point, ..
+            } => write!(
+                f,
+                "stored-versioned-contract-by-hash: {:10}, version: latest, entry-point: {}",
+                HexFmt(hash),
+                entry_point,
+            ),
+            ExecutableDeployItem::StoredVersionedContractByName {
+                name,
+                version: Some(ver),
+                entry_point,
+                ..
+            } => write!(
+                f,
+                "stored-versioned-contract: {}, version: {}, entry-point: {}",
+                name, ver, entry_point,
+            ),
+            ExecutableDeployItem::StoredVersionedContractByName {
+                name, entry_point, ..
+            } => write!(
+                f,
+                "stored-versioned-contract: {}, version: latest, entry-point: {}",
+                name, entry_point,
+            ),
+            ExecutableDeployItem::Transfer { .. } => write!(f, "transfer"),
+        }
+    }
+}
+
+impl Debug for ExecutableDeployItem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ExecutableDeployItem::ModuleBytes { module_bytes, args } => f
+                .debug_struct("ModuleBytes")
+                .field("module_bytes", &format!("[{} bytes]", module_bytes.len()))
+                .field("args", args)
+                .finish(),
+            ExecutableDeployItem::StoredContractByHash {
+                hash,
+                entry_point,
+                args,
+            } => f
+                .debug_struct("StoredContractByHash")
+                .field("hash", &HexFmt(hash))
+                .field("entry_point", &entry_point)
+                .field("args", args)
+                .finish(),
+            ExecutableDeployItem::StoredContractByName {
+                name,
+                entry_point,
+                args,
+            } => f
+                .debug_struct("StoredContractByName")
+                .field("name", &name)
+                .field("entry_point", &entry_point)
+                .field("args", args)
+                .finish(),
+            ExecutableDeployItem::StoredVersionedContractByHash {
+                hash,
+                version,
+                entry_point,
+                args,
+            } => f
+                .debug_struct("StoredVersionedContractByHash")
+                .field("hash", &HexFmt(hash))
+                .field("version", version)
+                .field("entry_point", &entry_point)
+                .field("args", args)
+                .finish(),
+            ExecutableDeployItem::StoredVersionedContractByName {
+                name,
+                version,
+                entry_point,
+                args,
+            } => f
+                .debug_struct("StoredVersionedContractByName")
+                .field("name", &name)
+                .field("version", version)
+                .field("entry_point", &entry_point)
+                .field("args", args)
+                .finish(),
+            ExecutableDeployItem::Transfer { args } => {
+                f.debug_struct("Transfer").field("args", args).finish()
+            }
+        }
+    }
+}
+
+impl Distribution<ExecutableDeployItem> for Standard {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> ExecutableDeployItem {
+        fn random_bytes<R: Rng + ?Sized>(rng: &mut R) -> Vec<u8> {
+            let mut bytes = vec![0u8; rng.gen_range(0..100)];
+            rng.fill_bytes(bytes.as_mut());
+            bytes
+        }
+
+        fn random_string<R: Rng + ?Sized>(rng: &mut R) -> String {
+            rng.sample_iter(&Alphanumeric)
+                .take(20)
+                .map(char::from)
+                .collect()
+        }
+
+        let mut args = RuntimeArgs::new();
+        let _ = args.insert(random_string(rng), Bytes::from(random_bytes(rng)));
+
+        match rng.gen_range(0..5) {
+            0 => ExecutableDeployItem::ModuleBytes {
+                module_bytes: random_bytes(rng).into(),
+                args,
+            },
+            1 => ExecutableDeployItem::StoredContractByHash {
+                hash: ContractHash::new(rng.gen()),
+                entry_point: random_string(rng),
+                args,
+            },
+            2 => ExecutableDeployItem::StoredContractByName {
+                name: random_string(rng),
+                entry_point: random_string(rng),
+                args,
+            },
+            3 => ExecutableDeployItem::StoredVersionedContractByHash {
+                hash: ContractPackageHash::new(rng.gen()),
+                version: rng.gen(),
+                entry_point: random_string(rng),
+                args,
+            },
+            4 => ExecutableDeployItem::StoredVersionedContractByName {
+                name: random_string(rng),
+                version: rng.gen(),
+                entry_point: random_string(rng),
+                args,
+            },
+            5 => {
+                let amount = rng.gen_range(2_500_000_000_u64..1_000_000_000_000_000);
+                let mut transfer_args = RuntimeArgs::new();
+                transfer_args.insert_cl_value(
+                    ARG_AMOUNT,
+                    CLValue::from_t(U512::from(amount)).expect("should get CLValue from U512"),
+                );
+                ExecutableDeployItem::Transfer {
+                    args: transfer_args,
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    const MODULE_BYTES_LENGTH: usize = 10_000;
+
+    fn module_bytes_item() -> (Vec<u8>, ExecutableDeployItem) {
+        let mut rng = rand::thread_rng();
+        let mut module_bytes = vec![0u8; MODULE_BYTES_LENGTH];
+        rng.fill(module_bytes.as_mut_slice());
+        let mut args = RuntimeArgs::new();
+        args.insert("bytes", Bytes::from(vec![1, 2, 3])).unwrap();
+        let item = ExecutableDeployItem::ModuleBytes {
+            module_bytes: module_bytes.clone().into(),
+            args,
+        };
+        (module_bytes, item)
+    }
+
+    /// Returns the JSON encoding of `item` with its module bytes replaced by the legacy encoding,
+    /// i.e. a JSON array of integers.
+    fn to_legacy_json(item: &ExecutableDeployItem, module_bytes: &[u8]) -> Value {
+        let mut json = serde_json::to_value(item).unwrap();
+        json["ModuleBytes"]["module_bytes"] = json!(module_bytes);
+        json
+    }
+
+    #[test]
+    fn serialization_roundtrip() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..10 {
+            let executable_deploy_item: ExecutableDeployItem = rng.gen();
+            bytesrepr::test_serialization_roundtrip(&executable_deploy_item);
+        }
+    }
+
+    #[test]
+    fn json_roundtrip() {
+        let (module_bytes, item) = module_bytes_item();
+        let json = serde_json::to_value(&item).unwrap();
+        assert_eq!(
+            json["ModuleBytes"]["module_bytes"],
+            json!(hex::encode(&module_bytes))
+        );
+        let decoded: ExecutableDeployItem = serde_json::from_value(json).unwrap();
+        assert_eq!(decoded, item);
+    }
+
+    #[test]
+    fn bincode_roundtrip() {
+        let (_, item) = module_bytes_item();
+        let serialized = bincode::serialize(&item).unwrap();
+        let decoded: ExecutableDeployItem = bincode::deserialize(&serialized).unwrap();
+        assert_eq!(decoded, item);
+    }
+
+    #[test]
+    fn should_deserialize_legacy_json() {
+        let (module_bytes, item) = module_bytes_item();
+        let legacy_json = to_legacy_json(&item, &module_bytes);
+        let decoded: ExecutableDeployItem = serde_json::from_value(legacy_json).unwrap();
+        assert_eq!(decoded, item);
+    }
+
+    #[test]
+    fn hex_encoding_should_be_smaller_than_legacy_encoding() {
+        let (module_bytes, item) = module_bytes_item();
+        let json_length = serde_json::to_string(&item).unwrap().len();
+        let legacy_json_length = serde_json::to_string(&to_legacy_json(&item, &module_bytes))
+            .unwrap()
+            .len();
+        // Each byte takes two characters as hex, but on average roughly 3.6 as a JSON integer with
+        // its separating comma.
+        assert!(
+            json_length * 10 < legacy_json_length * 6,
+            "hex: {}, legacy: {}",
+            json_length,
+            legacy_json_length
+        );
+    }
+}
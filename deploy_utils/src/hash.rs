@@ -0,0 +1,540 @@
+//! Cryptographic hash type and function.
+
+// TODO - remove once schemars stops causing warning.
+#![allow(clippy::field_reassign_with_default)]
+
+use std::{
+    array::TryFromSliceError,
+    convert::TryFrom,
+    fmt::{self, Debug, Display, Formatter, LowerHex, UpperHex},
+};
+
+use blake2::{
+    digest::{Update, VariableOutput},
+    VarBlake2b,
+};
+use datasize::DataSize;
+use hex_fmt::HexFmt;
+use rand::Rng;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use subtle::ConstantTimeEq;
+
+use casper_types::{
+    bytesrepr::{self, FromBytes, ToBytes},
+    crypto::Error,
+};
+
+/// The hash digest; a wrapped `u8` array.
+///
+/// The derived `PartialEq` may return as soon as it finds a differing byte.  When checking a digest
+/// computed from untrusted input against an expected value, use [`Digest::ct_eq`] instead.
+#[derive(
+    Copy,
+    Clone,
+    DataSize,
+    Ord,
+    PartialOrd,
+    Eq,
+    PartialEq,
+    Hash,
+    Serialize,
+    Deserialize,
+    Default,
+    JsonSchema,
+)]
+#[serde(deny_unknown_fields)]
+#[schemars(with = "String", description = "Hex-encoded hash digest.")]
+pub struct Digest(
+    #[serde(with = "hex_array")]
+    #[schemars(skip, with = "String")]
+    [u8; Digest::LENGTH],
+);
+
+impl Digest {
+    /// Length of `Digest` in bytes.
+    pub const LENGTH: usize = 32;
+
+    /// Returns a copy of the wrapped `u8` array.
+    pub fn to_array(&self) -> [u8; Digest::LENGTH] {
+        self.0
+    }
+
+    /// Returns a copy of the wrapped `u8` array as a `Vec`
+    pub fn to_vec(&self) -> Vec<u8> {
+        self.0.to_vec()
+    }
+
+    /// Returns `true` if `self` and `other` are equal, taking the same time regardless of where or
+    /// whether they differ.
+    pub fn ct_eq(&self, other: &Digest) -> bool {
+        self.0[..].ct_eq(&other.0[..]).into()
+    }
+
+    /// Returns a `Digest` parsed from a hex-encoded `Digest`.
+    ///
+    /// The input is decoded directly into the digest without any intermediate allocation.  Input of
+    /// odd length, of the wrong length or containing non-hex characters is rejected.
+    pub fn from_hex<T: AsRef<[u8]>>(hex_input: T) -> Result<Self, Error> {
+        let mut inner = [0; Digest::LENGTH];
+        hex::decode_to_slice(hex_input, &mut inner)?;
+        Ok(Digest(inner))
+    }
+
+    /// Generates a random instance.
+    pub fn random<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        Digest(rng.gen::<[u8; Digest::LENGTH]>())
+    }
+}
+
+impl AsRef<[u8]> for Digest {
+    fn as_ref(&self) -> &[u8] {
+        self.0.as_ref()
+    }
+}
+
+impl From<[u8; Digest::LENGTH]> for Digest {
+    fn from(inner: [u8; Digest::LENGTH]) -> Self {
+        Digest(inner)
+    }
+}
+
+impl TryFrom<&[u8]> for Digest {
+    type Error = TryFromSliceError;
+
+    fn try_from(slice: &[u8]) -> Result<Digest, Self::Error> {
+        <[u8; Digest::LENGTH]>::try_from(slice).map(Digest)
+    }
+}
+
+impl Debug for Digest {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        write!(formatter, "{}", HexFmt(&self.0))
+    }
+}
+
+impl Display for Digest {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        write!(formatter, "{:10}", HexFmt(&self.0))
+    }
+}
+impl LowerHex for Digest {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        if formatter.alternate() {
+            write!(formatter, "0x{}", HexFmt(&self.0))
+        } else {
+            write!(formatter, "{}", HexFmt(&self.0))
+        }
+    }
+}
+
+impl UpperHex for Digest {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        if formatter.alternate() {
+            write!(formatter, "0x{:X}", HexFmt(&self.0))
+        } else {
+            write!(formatter, "{:X}", HexFmt(&self.0))
+        }
+    }
+}
+
+impl ToBytes for Digest {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        self.0.to_bytes()
+    }
+
+    fn serialized_length(&self) -> usize {
+        self.0.serialized_length()
+    }
+}
+
+impl FromBytes for Digest {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        FromBytes::from_bytes(bytes).map(|(inner, remainder)| (Digest(inner), remainder))
+    }
+}
+
+/// Serde support for the wrapped array: hex-encoded in human-readable formats, raw bytes otherwise.
+///
+/// Unlike `hex_buffer_serde::HexForm`, deserialization decodes directly into the array rather than
+/// via an intermediate `Vec`.  The serialized forms are unchanged.
+mod hex_array {
+    use std::{
+        convert::TryFrom,
+        fmt::{self, Formatter},
+    };
+
+    use hex_fmt::HexFmt;
+    use serde::{
+        de::{Error as SerdeError, Visitor},
+        Deserializer, Serializer,
+    };
+
+    use super::Digest;
+
+    pub(super) fn serialize<S: Serializer>(
+        array: &[u8; Digest::LENGTH],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.collect_str(&HexFmt(array))
+        } else {
+            serializer.serialize_bytes(array)
+        }
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<[u8; Digest::LENGTH], D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(ArrayVisitor)
+        } else {
+            deserializer.deserialize_bytes(ArrayVisitor)
+        }
+    }
+
+    struct ArrayVisitor;
+
+    impl<'de> Visitor<'de> for ArrayVisitor {
+        type Value = [u8; Digest::LENGTH];
+
+        fn expecting(&self, formatter: &mut Formatter) -> fmt::Result {
+            write!(formatter, "a {}-byte digest", Digest::LENGTH)
+        }
+
+        fn visit_str<E: SerdeError>(self, value: &str) -> Result<Self::Value, E> {
+            let mut array = [0; Digest::LENGTH];
+            hex::decode_to_slice(value, &mut array).map_err(SerdeError::custom)?;
+            Ok(array)
+        }
+
+        fn visit_bytes<E: SerdeError>(self, value: &[u8]) -> Result<Self::Value, E> {
+            <[u8; Digest::LENGTH]>::try_from(value)
+                .map_err(|_| SerdeError::invalid_length(value.len(), &self))
+        }
+    }
+}
+
+/// Returns the hash of `data`.
+pub fn hash<T: AsRef<[u8]>>(data: T) -> Digest {
+    let mut result = [0; Digest::LENGTH];
+
+    let mut hasher = VarBlake2b::new(Digest::LENGTH).expect("should create hasher");
+    hasher.update(data);
+    hasher.finalize_variable(|slice| {
+        result.copy_from_slice(slice);
+    });
+    Digest(result)
+}
+
+/// The contexts in which data is hashed via [`hash_with_domain`].
+///
+/// Mixing the domain into the hash ensures that a preimage valid in one context can't produce the
+/// same digest as a preimage in another.
+#[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug)]
+pub enum HashDomain {
+    /// The serialized header of a deploy, yielding the deploy hash.
+    DeployHeader,
+    /// The serialized payment and session code of a deploy, yielding the deploy's body hash.
+    DeployBody,
+    /// The serialized header of a block, yielding the block hash.
+    BlockHeader,
+    /// The serialized body of a block, yielding the block's body hash.
+    BlockBody,
+}
+
+impl HashDomain {
+    /// Returns the tag which is mixed into hashes computed in this domain.
+    pub fn tag(&self) -> &'static str {
+        match self {
+            HashDomain::DeployHeader => "casper/deploy-header",
+            HashDomain::DeployBody => "casper/deploy-body",
+            HashDomain::BlockHeader => "casper/block-header",
+            HashDomain::BlockBody => "casper/block-body",
+        }
+    }
+}
+
+/// Returns the hash of `data` in the given domain.
+///
+/// The hashed preimage is the length of the domain's tag as a single byte, followed by the tag and
+/// then `data`.
+pub fn hash_with_domain<T: AsRef<[u8]>>(domain: HashDomain, data: T) -> Digest {
+    let tag = domain.tag().as_bytes();
+    let mut result = [0; Digest::LENGTH];
+
+    let mut hasher = VarBlake2b::new(Digest::LENGTH).expect("should create hasher");
+    hasher.update([tag.len() as u8]);
+    hasher.update(tag);
+    hasher.update(data);
+    hasher.finalize_variable(|slice| {
+        result.copy_from_slice(slice);
+    });
+    Digest(result)
+}
+
+#[cfg(test)]
+mod test {
+    use std::{collections::BTreeSet, iter};
+
+    use hex::FromHexError;
+
+    use super::*;
+
+    #[test]
+    fn blake2b_hash_known() {
+        let inputs_and_digests = [
+            (
+                "",
+                "0e5751c026e543b2e8ab2eb06099daa1d1e5df47778f7787faab45cdf12fe3a8",
+            ),
+            (
+                "abc",
+                "bddd813c634239723171ef3fee98579b94964e3bb1cb3e427262c8c068d52319",
+            ),
+            (
+                "The quick brown fox jumps over the lazy dog",
+                "01718cec35cd3d796dd00020e0bfecb473ad23457d063b75eff29c0ffa2e58a9",
+            ),
+        ];
+        for (known_input, expected_digest) in &inputs_and_digests {
+            let known_input: &[u8] = known_input.as_ref();
+            assert_eq!(*expected_digest, format!("{:?}", hash(known_input)));
+        }
+    }
+
+    #[test]
+    fn blake2b_hash_with_domain_known() {
+        let inputs_and_digests = [
+            (
+                HashDomain::DeployHeader,
+                "",
+                "f52f974ab8a84dc796a6fd0ac5be11ed7cf96bbdc57a975481f956e29e195706",
+            ),
+            (
+                HashDomain::DeployHeader,
+                "abc",
+                "ed21c75318920d8ff9b0d72c518778ceafff7c5bdee481e90300f3c4d6bbb3ec",
+            ),
+            (
+                HashDomain::DeployBody,
+                "abc",
+                "ece30b159ce92084f93483d880263f449ae2c04a7a71a9cc7fd826e379265023",
+            ),
+            (
+                HashDomain::BlockHeader,
+                "abc",
+                "bed40065b97c7bfddc6ea9e4dffd56560ed7c7ace587a93c5ba3ec69fe9ca36c",
+            ),
+            (
+                HashDomain::BlockBody,
+                "abc",
+                "2e6e0fea0a2248543dd8a70f8e743dac6400f757311f8f9254173090f1f09b17",
+            ),
+        ];
+        for (domain, known_input, expected_digest) in &inputs_and_digests {
+            let known_input: &[u8] = known_input.as_ref();
+            assert_eq!(
+                *expected_digest,
+                format!("{:?}", hash_with_domain(*domain, known_input))
+            );
+        }
+    }
+
+    #[test]
+    fn hash_with_domain_should_not_collide_across_domains() {
+        let domains = [
+            HashDomain::DeployHeader,
+            HashDomain::DeployBody,
+            HashDomain::BlockHeader,
+            HashDomain::BlockBody,
+        ];
+        let mut rng = rand::thread_rng();
+        for _ in 0..10 {
+            let data: Vec<u8> = (0..rng.gen_range(0..100)).map(|_| rng.gen()).collect();
+            let mut digests: Vec<Digest> = domains
+                .iter()
+                .map(|domain| hash_with_domain(*domain, &data))
+                .collect();
+            digests.push(hash(&data));
+            let unique_count = digests.iter().collect::<BTreeSet<_>>().len();
+            assert_eq!(unique_count, digests.len());
+
+            // Prepending one domain's tag to the data mustn't reproduce that domain's digest when
+            // hashed in another domain.
+            for domain in &domains {
+                let tag = domain.tag().as_bytes();
+                let mut prefixed = vec![tag.len() as u8];
+                prefixed.extend_from_slice(tag);
+                prefixed.extend_from_slice(&data);
+                for other_domain in domains.iter().filter(|other| *other != domain) {
+                    assert_ne!(
+                        hash_with_domain(*other_domain, &prefixed),
+                        hash_with_domain(*domain, &data)
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn from_valid_hex_should_succeed() {
+        for char in "abcdefABCDEF0123456789".chars() {
+            let input: String = iter::repeat(char).take(64).collect();
+            assert!(Digest::from_hex(input).is_ok());
+        }
+    }
+
+    #[test]
+    fn from_hex_invalid_length_should_fail() {
+        for len in &[2_usize, 62, 63, 65, 66] {
+            let input: String = "f".repeat(*len);
+            assert!(Digest::from_hex(input).is_err());
+        }
+    }
+
+    #[test]
+    fn from_hex_invalid_char_should_fail() {
+        for char in "g %-".chars() {
+            let input: String = iter::repeat('f').take(63).chain(iter::once(char)).collect();
+            assert!(Digest::from_hex(input).is_err());
+        }
+    }
+
+    #[test]
+    fn from_hex_should_report_precise_errors() {
+        let odd_length = "f".repeat(63);
+        assert!(matches!(
+            Digest::from_hex(odd_length),
+            Err(Error::FromHex(FromHexError::OddLength))
+        ));
+
+        let wrong_length = "f".repeat(66);
+        assert!(matches!(
+            Digest::from_hex(wrong_length),
+            Err(Error::FromHex(FromHexError::InvalidStringLength))
+        ));
+
+        let mut invalid_char = "f".repeat(64);
+        invalid_char.replace_range(10..11, "x");
+        assert!(matches!(
+            Digest::from_hex(invalid_char),
+            Err(Error::FromHex(FromHexError::InvalidHexCharacter {
+                c: 'x',
+                index: 10
+            }))
+        ));
+
+        let prefixed = format!("0x{}", "f".repeat(62));
+        assert!(matches!(
+            Digest::from_hex(prefixed),
+            Err(Error::FromHex(FromHexError::InvalidHexCharacter {
+                c: 'x',
+                index: 1
+            }))
+        ));
+
+        assert!(matches!(
+            Digest::from_hex(""),
+            Err(Error::FromHex(FromHexError::InvalidStringLength))
+        ));
+    }
+
+    #[test]
+    fn from_hex_should_accept_mixed_case() {
+        let input = format!("{}{}", "aB".repeat(16), "Cd".repeat(16));
+        let mut expected = [0xcd; Digest::LENGTH];
+        expected[..Digest::LENGTH / 2].fill(0xab);
+        assert_eq!(Digest::from_hex(input).unwrap(), Digest::from(expected));
+    }
+
+    #[test]
+    fn ct_eq_should_agree_with_eq() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..100 {
+            let digest = Digest::random(&mut rng);
+            assert!(digest.ct_eq(&digest));
+
+            let other = Digest::random(&mut rng);
+            assert_eq!(digest.ct_eq(&other), digest == other);
+
+            // Differ in a single byte at each possible position.
+            for index in 0..Digest::LENGTH {
+                let mut bytes = digest.to_array();
+                bytes[index] ^= 1 << rng.gen_range(0..8);
+                let nearly_equal = Digest::from(bytes);
+                assert!(!digest.ct_eq(&nearly_equal));
+                assert!(!nearly_equal.ct_eq(&digest));
+            }
+        }
+    }
+
+    #[test]
+    fn json_roundtrip() {
+        let mut rng = rand::thread_rng();
+        let digest = Digest::random(&mut rng);
+        let json = serde_json::to_string(&digest).unwrap();
+        assert_eq!(json, format!("\"{:?}\"", digest));
+        assert_eq!(serde_json::from_str::<Digest>(&json).unwrap(), digest);
+
+        let odd_length = format!("\"{}\"", "f".repeat(63));
+        assert!(serde_json::from_str::<Digest>(&odd_length).is_err());
+    }
+
+    #[test]
+    fn bincode_roundtrip() {
+        let mut rng = rand::thread_rng();
+        let digest = Digest::random(&mut rng);
+        let serialized = bincode::serialize(&digest).unwrap();
+        // Serialized as a length-prefixed byte string, as previously done by `HexForm`.
+        assert_eq!(serialized.len(), 8 + Digest::LENGTH);
+        assert_eq!(bincode::deserialize::<Digest>(&serialized).unwrap(), digest);
+    }
+
+    #[test]
+    fn should_display_digest_in_hex() {
+        let hash = Digest([0u8; 32]);
+        let hash_hex = format!("{:?}", hash);
+        assert_eq!(
+            hash_hex,
+            "0000000000000000000000000000000000000000000000000000000000000000"
+        );
+    }
+
+    #[test]
+    fn should_print_digest_lower_hex() {
+        let hash = Digest([10u8; 32]);
+        let hash_lower_hex = format!("{:x}", hash);
+        assert_eq!(
+            hash_lower_hex,
+            "0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a"
+        )
+    }
+
+    #[test]
+    fn should_print_digest_upper_hex() {
+        let hash = Digest([10u8; 32]);
+        let hash_lower_hex = format!("{:X}", hash);
+        assert_eq!(
+            hash_lower_hex,
+            "0A0A0A0A0A0A0A0A0A0A0A0A0A0A0A0A0A0A0A0A0A0A0A0A0A0A0A0A0A0A0A0A"
+        )
+    }
+
+    #[test]
+    fn alternate_should_prepend_0x() {
+        let hash = Digest([0u8; 32]);
+        let hash_hex_alt = format!("{:#x}", hash);
+        assert_eq!(
+            hash_hex_alt,
+            "0x0000000000000000000000000000000000000000000000000000000000000000"
+        )
+    }
+
+    #[test]
+    fn bytesrepr_roundtrip() {
+        let mut rng = rand::thread_rng();
+        let hash = Digest::random(&mut rng);
+        bytesrepr::test_serialization_roundtrip(&hash);
+    }
+}
@@ -0,0 +1,67 @@
+//! Construction, hashing and signing of deploys for use on the Casper network.
+//!
+//! This crate has no networking or storage dependencies, allowing deploys to be built and signed
+//! without linking the `casper-node` crate.
+//!
+//! # Example
+//!
+//! Constructing and signing a native transfer deploy:
+//!
+//! ```
+//! use casper_deploy_utils::{Deploy, DeployHashScheme, ExecutableDeployItem, TimeDiff, Timestamp};
+//! use casper_types::{bytesrepr::Bytes, runtime_args, PublicKey, RuntimeArgs, SecretKey, U512};
+//!
+//! let secret_key = SecretKey::ed25519_from_bytes([7; SecretKey::ED25519_LENGTH]).unwrap();
+//! let target_secret_key = SecretKey::ed25519_from_bytes([8; SecretKey::ED25519_LENGTH]).unwrap();
+//! let target = PublicKey::from(&target_secret_key);
+//!
+//! let payment = ExecutableDeployItem::ModuleBytes {
+//!     module_bytes: Bytes::new(),
+//!     args: runtime_args! { "amount" => U512::from(10_000_u64) },
+//! };
+//! let session = ExecutableDeployItem::Transfer {
+//!     args: runtime_args! {
+//!         "amount" => U512::from(2_500_000_000_u64),
+//!         "target" => target,
+//!         "id" => Some(1_u64),
+//!     },
+//! };
+//!
+//! let mut deploy = Deploy::new(
+//!     Timestamp::now(),
+//!     TimeDiff::from_seconds(1_800),
+//!     1,
+//!     vec![],
+//!     String::from("casper-example"),
+//!     payment,
+//!     session,
+//!     &secret_key,
+//! );
+//! assert!(deploy.is_valid(DeployHashScheme::Legacy).is_ok());
+//! ```
+
+#![doc(html_root_url = "https://docs.rs/casper-deploy-utils/1.0.0")]
+#![doc(
+    html_favicon_url = "https://raw.githubusercontent.com/CasperLabs/casper-node/master/images/CasperLabs_Logo_Favicon_RGB_50px.png",
+    html_logo_url = "https://raw.githubusercontent.com/CasperLabs/casper-node/master/images/CasperLabs_Logo_Symbol_RGB.png",
+    test(attr(forbid(warnings)))
+)]
+#![warn(
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unused_qualifications
+)]
+
+pub mod crypto;
+mod deploy;
+mod executable_deploy_item;
+pub mod hash;
+mod timestamp;
+
+pub use deploy::{
+    Approval, Deploy, DeployHash, DeployHashScheme, DeployHeader, DeployLimits,
+    DeployOrTransferHash, DeployValidationFailure, ExcessiveSizeError,
+};
+pub use executable_deploy_item::ExecutableDeployItem;
+pub use timestamp::{Clock, TimeDiff, Timestamp};
@@ -0,0 +1,586 @@
+// TODO - remove once schemars stops causing warning.
+#![allow(clippy::field_reassign_with_default)]
+
+use std::{
+    cell::RefCell,
+    convert::TryFrom,
+    fmt::{self, Display, Formatter},
+    ops::{Add, AddAssign, Div, Mul, Range, Rem, Sub},
+    str::FromStr,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+use datasize::DataSize;
+use derive_more::{Add, AddAssign, From, Shl, Shr, Sub, SubAssign};
+use humantime::{DurationError, TimestampError};
+use rand::Rng;
+use schemars::JsonSchema;
+use serde::{de::Error as SerdeError, Deserialize, Deserializer, Serialize, Serializer};
+
+use casper_types::bytesrepr::{self, FromBytes, ToBytes};
+
+thread_local! {
+    /// The clock consulted by `Timestamp::now` on this thread, if not the system clock.
+    static THREAD_CLOCK: RefCell<Option<Arc<dyn Clock>>> = RefCell::new(None);
+}
+
+/// A source of the current time.
+///
+/// [`Timestamp::now`] reads the system clock unless a different clock has been installed for the
+/// current thread via [`Timestamp::set_thread_clock`], e.g. to run tests in virtual time.
+pub trait Clock: Send + Sync {
+    /// Returns the current time according to this clock.
+    fn now(&self) -> Timestamp;
+}
+
+/// A timestamp type, representing a concrete moment in time.
+#[derive(
+    DataSize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Shr, Shl, JsonSchema,
+)]
+#[serde(deny_unknown_fields)]
+#[schemars(with = "String", description = "Timestamp formatted as per RFC 3339")]
+pub struct Timestamp(u64);
+
+impl Timestamp {
+    /// Returns the timestamp of the current moment.
+    ///
+    /// This is read from the clock installed for the current thread if there is one, or from the
+    /// system clock otherwise.
+    pub fn now() -> Self {
+        let maybe_now = THREAD_CLOCK.with(|clock| clock.borrow().as_ref().map(|clock| clock.now()));
+        if let Some(now) = maybe_now {
+            return now;
+        }
+        let millis = SystemTime::UNIX_EPOCH.elapsed().unwrap().as_millis() as u64;
+        Timestamp(millis)
+    }
+
+    /// Installs `clock` as the source of [`Timestamp::now`] on the current thread, or reverts to
+    /// the system clock if `None`.
+    ///
+    /// Returns the previously installed clock, if any.
+    pub fn set_thread_clock(clock: Option<Arc<dyn Clock>>) -> Option<Arc<dyn Clock>> {
+        THREAD_CLOCK.with(|thread_clock| thread_clock.replace(clock))
+    }
+
+    /// Returns the time that has elapsed since this timestamp.
+    pub fn elapsed(&self) -> TimeDiff {
+        TimeDiff(Timestamp::now().0.saturating_sub(self.0))
+    }
+
+    /// Returns a zero timestamp.
+    pub fn zero() -> Self {
+        Timestamp(0)
+    }
+
+    /// Returns the timestamp as the number of milliseconds since the Unix epoch
+    pub fn millis(&self) -> u64 {
+        self.0
+    }
+
+    /// Returns the difference between `self` and `other`, or `0` if `self` is earlier than `other`.
+    pub fn saturating_diff(self, other: Timestamp) -> TimeDiff {
+        TimeDiff(self.0.saturating_sub(other.0))
+    }
+
+    /// Returns the difference between `self` and `other`, or `0` if that would be before the epoch.
+    pub fn saturating_sub(self, other: TimeDiff) -> Timestamp {
+        Timestamp(self.0.saturating_sub(other.0))
+    }
+
+    /// Returns the number of trailing zeros in the number of milliseconds since the epoch.
+    pub fn trailing_zeros(&self) -> u8 {
+        self.0.trailing_zeros() as u8
+    }
+
+    /// Generates a random instance.
+    pub fn random<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        Timestamp(1_596_763_000_000 + rng.gen_range(200_000..1_000_000))
+    }
+
+    /// Formats the timestamp as per RFC 3339 in UTC with millisecond precision, e.g.
+    /// "2020-11-17T00:39:24.072Z".
+    pub fn to_rfc3339(&self) -> String {
+        self.to_string()
+    }
+
+    /// Parses a timestamp formatted as per RFC 3339.
+    ///
+    /// The UTC offset may be given as `Z` or numerically, e.g. `+02:00`, and is assumed to be UTC
+    /// if omitted.  Fractional seconds are optional and may have up to nanosecond precision, but
+    /// are truncated to milliseconds.  Times before the Unix epoch are rejected.
+    pub fn from_rfc3339(value: &str) -> Result<Self, TimestampError> {
+        let (date_time, utc_offset) = split_utc_offset(value)?;
+        let local_time = humantime::parse_rfc3339_weak(date_time)?;
+        let system_time = match utc_offset {
+            UtcOffset::East(offset) => local_time.checked_sub(offset),
+            UtcOffset::West(offset) => local_time.checked_add(offset),
+        }
+        .ok_or(TimestampError::OutOfRange)?;
+        Timestamp::try_from(system_time)
+    }
+}
+
+/// The offset from UTC of a time in RFC 3339 format.
+enum UtcOffset {
+    /// Ahead of UTC, as in `+02:00`.
+    East(Duration),
+    /// Behind UTC, as in `-02:00`.
+    West(Duration),
+}
+
+/// Splits a time in RFC 3339 format into the date-time and the UTC offset.
+///
+/// A missing offset is treated as UTC.
+fn split_utc_offset(value: &str) -> Result<(&str, UtcOffset), TimestampError> {
+    const OFFSET_LENGTH: usize = "+hh:mm".len();
+
+    if let Some(date_time) = value.strip_suffix(&['Z', 'z'][..]) {
+        return Ok((date_time, UtcOffset::East(Duration::default())));
+    }
+    if value.len() < OFFSET_LENGTH || !value.is_char_boundary(value.len() - OFFSET_LENGTH) {
+        return Ok((value, UtcOffset::East(Duration::default())));
+    }
+
+    let (date_time, offset) = value.split_at(value.len() - OFFSET_LENGTH);
+    let offset_bytes = offset.as_bytes();
+    if !matches!(offset_bytes[0], b'+' | b'-') || offset_bytes[3] != b':' {
+        return Ok((value, UtcOffset::East(Duration::default())));
+    }
+
+    let parse_digits = |range: Range<usize>| {
+        offset_bytes[range].iter().try_fold(0, |number, byte| {
+            if byte.is_ascii_digit() {
+                Ok(number * 10 + u64::from(byte - b'0'))
+            } else {
+                Err(TimestampError::InvalidDigit)
+            }
+        })
+    };
+    let hours = parse_digits(1..3)?;
+    let minutes = parse_digits(4..6)?;
+    if hours > 23 || minutes > 59 {
+        return Err(TimestampError::InvalidFormat);
+    }
+
+    let offset_duration = Duration::from_secs((hours * 60 + minutes) * 60);
+    let utc_offset = if offset_bytes[0] == b'+' {
+        UtcOffset::East(offset_duration)
+    } else {
+        UtcOffset::West(offset_duration)
+    };
+    Ok((date_time, utc_offset))
+}
+
+impl Display for Timestamp {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            humantime::format_rfc3339_millis(SystemTime::from(*self))
+        )
+    }
+}
+
+impl FromStr for Timestamp {
+    type Err = TimestampError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Timestamp::from_rfc3339(value)
+    }
+}
+
+impl From<Timestamp> for SystemTime {
+    fn from(timestamp: Timestamp) -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_millis(timestamp.0)
+    }
+}
+
+impl TryFrom<SystemTime> for Timestamp {
+    type Error = TimestampError;
+
+    /// Converts the given time, truncated to milliseconds.  Times before the Unix epoch are
+    /// rejected.
+    fn try_from(system_time: SystemTime) -> Result<Self, Self::Error> {
+        let millis = system_time
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_err(|_| TimestampError::OutOfRange)?
+            .as_millis();
+        u64::try_from(millis)
+            .map(Timestamp)
+            .map_err(|_| TimestampError::OutOfRange)
+    }
+}
+
+impl Add<TimeDiff> for Timestamp {
+    type Output = Timestamp;
+
+    fn add(self, diff: TimeDiff) -> Timestamp {
+        Timestamp(self.0 + diff.0)
+    }
+}
+
+impl AddAssign<TimeDiff> for Timestamp {
+    fn add_assign(&mut self, rhs: TimeDiff) {
+        self.0 += rhs.0;
+    }
+}
+
+impl Sub<TimeDiff> for Timestamp {
+    type Output = Timestamp;
+
+    fn sub(self, diff: TimeDiff) -> Timestamp {
+        Timestamp(self.0 - diff.0)
+    }
+}
+
+impl Rem<TimeDiff> for Timestamp {
+    type Output = TimeDiff;
+
+    fn rem(self, diff: TimeDiff) -> TimeDiff {
+        TimeDiff(self.0 % diff.0)
+    }
+}
+
+impl Serialize for Timestamp {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            self.to_rfc3339().serialize(serializer)
+        } else {
+            self.0.serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Timestamp {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            let value_as_string = String::deserialize(deserializer)?;
+            Timestamp::from_rfc3339(&value_as_string).map_err(SerdeError::custom)
+        } else {
+            let inner = u64::deserialize(deserializer)?;
+            Ok(Timestamp(inner))
+        }
+    }
+}
+
+impl ToBytes for Timestamp {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        self.0.to_bytes()
+    }
+
+    fn serialized_length(&self) -> usize {
+        self.0.serialized_length()
+    }
+}
+
+impl FromBytes for Timestamp {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        u64::from_bytes(bytes).map(|(inner, remainder)| (Timestamp(inner), remainder))
+    }
+}
+
+impl From<u64> for Timestamp {
+    fn from(milliseconds_since_epoch: u64) -> Timestamp {
+        Timestamp(milliseconds_since_epoch)
+    }
+}
+
+/// A time difference between two timestamps.
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    DataSize,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Add,
+    AddAssign,
+    Sub,
+    SubAssign,
+    From,
+    JsonSchema,
+)]
+#[serde(deny_unknown_fields)]
+#[schemars(with = "String", description = "Human-readable duration.")]
+pub struct TimeDiff(u64);
+
+impl Display for TimeDiff {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", humantime::format_duration(Duration::from(*self)))
+    }
+}
+
+impl FromStr for TimeDiff {
+    type Err = DurationError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let inner = humantime::parse_duration(value)?.as_millis() as u64;
+        Ok(TimeDiff(inner))
+    }
+}
+
+impl TimeDiff {
+    /// Returns the time difference as the number of milliseconds since the Unix epoch
+    pub fn millis(&self) -> u64 {
+        self.0
+    }
+
+    /// Creates a new time difference from seconds.
+    pub const fn from_seconds(seconds: u32) -> Self {
+        TimeDiff(seconds as u64 * 1_000)
+    }
+
+    /// Returns the product, or `TimeDiff(u64::MAX)` if it would overflow.
+    pub fn saturating_mul(self, rhs: u64) -> Self {
+        TimeDiff(self.0.saturating_mul(rhs))
+    }
+}
+
+impl Mul<u64> for TimeDiff {
+    type Output = TimeDiff;
+
+    fn mul(self, rhs: u64) -> TimeDiff {
+        TimeDiff(self.0 * rhs)
+    }
+}
+
+impl Div<u64> for TimeDiff {
+    type Output = TimeDiff;
+
+    fn div(self, rhs: u64) -> TimeDiff {
+        TimeDiff(self.0 / rhs)
+    }
+}
+
+impl Div<TimeDiff> for TimeDiff {
+    type Output = u64;
+
+    fn div(self, rhs: TimeDiff) -> u64 {
+        self.0 / rhs.0
+    }
+}
+
+impl From<TimeDiff> for Duration {
+    fn from(diff: TimeDiff) -> Duration {
+        Duration::from_millis(diff.0)
+    }
+}
+
+impl Serialize for TimeDiff {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            self.to_string().serialize(serializer)
+        } else {
+            self.0.serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for TimeDiff {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            let value_as_string = String::deserialize(deserializer)?;
+            TimeDiff::from_str(&value_as_string).map_err(SerdeError::custom)
+        } else {
+            let inner = u64::deserialize(deserializer)?;
+            Ok(TimeDiff(inner))
+        }
+    }
+}
+
+impl ToBytes for TimeDiff {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        self.0.to_bytes()
+    }
+
+    fn serialized_length(&self) -> usize {
+        self.0.serialized_length()
+    }
+}
+
+impl FromBytes for TimeDiff {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        u64::from_bytes(bytes).map(|(inner, remainder)| (TimeDiff(inner), remainder))
+    }
+}
+
+impl From<Duration> for TimeDiff {
+    fn from(duration: Duration) -> TimeDiff {
+        TimeDiff(duration.as_millis() as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timestamp_serialization_roundtrip() {
+        let timestamp = Timestamp::now();
+
+        let timestamp_as_string = timestamp.to_string();
+        assert_eq!(
+            timestamp,
+            Timestamp::from_str(&timestamp_as_string).unwrap()
+        );
+
+        let serialized_json = serde_json::to_string(&timestamp).unwrap();
+        assert_eq!(timestamp, serde_json::from_str(&serialized_json).unwrap());
+
+        let serialized_bincode = bincode::serialize(&timestamp).unwrap();
+        assert_eq!(
+            timestamp,
+            bincode::deserialize(&serialized_bincode).unwrap()
+        );
+
+        bytesrepr::test_serialization_roundtrip(&timestamp);
+    }
+
+    #[test]
+    fn should_read_now_from_thread_clock() {
+        struct FixedClock(Timestamp);
+
+        impl Clock for FixedClock {
+            fn now(&self) -> Timestamp {
+                self.0
+            }
+        }
+
+        let fixed = Timestamp::from(1_605_573_564_072);
+        assert!(Timestamp::set_thread_clock(Some(Arc::new(FixedClock(fixed)))).is_none());
+        assert_eq!(Timestamp::now(), fixed);
+
+        // Other threads should still use the system clock.
+        let other_thread_now = std::thread::spawn(Timestamp::now).join().unwrap();
+        assert!(other_thread_now > fixed);
+
+        assert!(Timestamp::set_thread_clock(None).is_some());
+        assert!(Timestamp::now() > fixed);
+    }
+
+    #[test]
+    fn timestamp_bytesrepr_should_be_raw_millis() {
+        let timestamp = Timestamp::random(&mut rand::thread_rng());
+        assert_eq!(
+            timestamp.to_bytes().unwrap(),
+            timestamp.millis().to_bytes().unwrap()
+        );
+    }
+
+    #[test]
+    fn should_normalize_utc_offsets() {
+        let expected = Timestamp::from_rfc3339("2020-11-17T00:39:24.072Z").unwrap();
+        assert_eq!(expected.millis(), 1_605_573_564_072);
+
+        for value in &[
+            "2020-11-17T00:39:24.072z",
+            "2020-11-17T00:39:24.072",
+            "2020-11-17T00:39:24.072+00:00",
+            "2020-11-17T00:39:24.072-00:00",
+            "2020-11-17T02:39:24.072+02:00",
+            "2020-11-16T19:09:24.072-05:30",
+            "2020-11-17 00:39:24.072Z",
+        ] {
+            assert_eq!(
+                Timestamp::from_rfc3339(value).unwrap(),
+                expected,
+                "{}",
+                value
+            );
+        }
+
+        assert!(Timestamp::from_rfc3339("2020-11-17T00:39:24.072+24:00").is_err());
+        assert!(Timestamp::from_rfc3339("2020-11-17T00:39:24.072+0a:00").is_err());
+        assert!(Timestamp::from_rfc3339("2020-11-17T00:39:24.072+02").is_err());
+    }
+
+    #[test]
+    fn should_truncate_fractional_seconds_to_millis() {
+        let whole_seconds = Timestamp::from_rfc3339("2020-11-17T00:39:24Z").unwrap();
+        assert_eq!(whole_seconds.millis(), 1_605_573_564_000);
+
+        for (value, expected_millis) in &[
+            ("2020-11-17T00:39:24.1Z", 1_605_573_564_100),
+            ("2020-11-17T00:39:24.0729Z", 1_605_573_564_072),
+            ("2020-11-17T00:39:24.072999Z", 1_605_573_564_072),
+            ("2020-11-17T00:39:24.072999999Z", 1_605_573_564_072),
+            ("2020-11-17T02:39:24.072999999+02:00", 1_605_573_564_072),
+        ] {
+            let timestamp = Timestamp::from_rfc3339(value).unwrap();
+            assert_eq!(timestamp.millis(), *expected_millis, "{}", value);
+        }
+
+        assert_eq!(
+            Timestamp::from(1_605_573_564_072).to_rfc3339(),
+            "2020-11-17T00:39:24.072Z"
+        );
+    }
+
+    #[test]
+    fn should_reject_pre_epoch_times() {
+        let before_epoch = SystemTime::UNIX_EPOCH - Duration::from_millis(1);
+        assert!(matches!(
+            Timestamp::try_from(before_epoch),
+            Err(TimestampError::OutOfRange)
+        ));
+
+        assert!(matches!(
+            Timestamp::from_rfc3339("1970-01-01T00:30:00+01:00"),
+            Err(TimestampError::OutOfRange)
+        ));
+        assert_eq!(
+            Timestamp::from_rfc3339("1970-01-01T00:30:00-01:00").unwrap(),
+            Timestamp::from(90 * 60 * 1_000)
+        );
+    }
+
+    #[test]
+    fn should_convert_to_and_from_system_time() {
+        let timestamp = Timestamp::random(&mut rand::thread_rng());
+        let system_time = SystemTime::from(timestamp);
+        assert_eq!(
+            system_time.duration_since(SystemTime::UNIX_EPOCH).unwrap(),
+            Duration::from_millis(timestamp.millis())
+        );
+        assert_eq!(Timestamp::try_from(system_time).unwrap(), timestamp);
+
+        // Sub-millisecond precision should be truncated.
+        let system_time = system_time + Duration::from_micros(999);
+        assert_eq!(Timestamp::try_from(system_time).unwrap(), timestamp);
+    }
+
+    #[test]
+    fn should_roundtrip_through_json_with_any_utc_offset() {
+        let timestamp = Timestamp::from(1_605_573_564_072);
+        let json = serde_json::to_string(&timestamp).unwrap();
+        assert_eq!(json, "\"2020-11-17T00:39:24.072Z\"");
+        assert_eq!(serde_json::from_str::<Timestamp>(&json).unwrap(), timestamp);
+
+        let with_offset: Timestamp =
+            serde_json::from_str("\"2020-11-17T01:39:24.072999+01:00\"").unwrap();
+        assert_eq!(with_offset, timestamp);
+    }
+
+    #[test]
+    fn timediff_serialization_roundtrip() {
+        let mut rng = rand::thread_rng();
+        let timediff = TimeDiff(rng.gen());
+
+        let timediff_as_string = timediff.to_string();
+        assert_eq!(timediff, TimeDiff::from_str(&timediff_as_string).unwrap());
+
+        let serialized_json = serde_json::to_string(&timediff).unwrap();
+        assert_eq!(timediff, serde_json::from_str(&serialized_json).unwrap());
+
+        let serialized_bincode = bincode::serialize(&timediff).unwrap();
+        assert_eq!(timediff, bincode::deserialize(&serialized_bincode).unwrap());
+
+        bytesrepr::test_serialization_roundtrip(&timediff);
+    }
+}
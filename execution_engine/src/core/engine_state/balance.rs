@@ -1,6 +1,7 @@
-use casper_types::{Key, URef, U512};
+use casper_types::{account::AccountHash, Key, URef, U512};
 
 use crate::{
+    core::engine_state::Error,
     shared::{newtypes::Blake2bHash, stored_value::StoredValue},
     storage::trie::merkle_proof::TrieMerkleProof,
 };
@@ -52,3 +53,131 @@ impl BalanceRequest {
         self.purse_uref
     }
 }
+
+/// Identifies a purse whose balance is requested as part of a [`BalancesRequest`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PurseIdentifier {
+    /// The purse with the given `URef`.
+    Purse(URef),
+    /// The main purse of the account with the given hash.
+    Account(AccountHash),
+}
+
+/// A request for the balances of several purses, all read from the same global state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BalancesRequest {
+    state_hash: Blake2bHash,
+    purse_identifiers: Vec<PurseIdentifier>,
+}
+
+impl BalancesRequest {
+    pub fn new(state_hash: Blake2bHash, purse_identifiers: Vec<PurseIdentifier>) -> Self {
+        BalancesRequest {
+            state_hash,
+            purse_identifiers,
+        }
+    }
+
+    pub fn state_hash(&self) -> Blake2bHash {
+        self.state_hash
+    }
+
+    pub fn purse_identifiers(&self) -> &[PurseIdentifier] {
+        &self.purse_identifiers
+    }
+}
+
+/// The balance of a single purse, as returned in a [`BalancesResult`].
+#[derive(Debug)]
+pub struct PurseBalance {
+    purse_uref: URef,
+    motes: U512,
+    proof: Box<TrieMerkleProof<Key, StoredValue>>,
+}
+
+impl PurseBalance {
+    pub fn new(
+        purse_uref: URef,
+        motes: U512,
+        proof: Box<TrieMerkleProof<Key, StoredValue>>,
+    ) -> Self {
+        PurseBalance {
+            purse_uref,
+            motes,
+            proof,
+        }
+    }
+
+    /// The purse the balance was read from.
+    pub fn purse_uref(&self) -> URef {
+        self.purse_uref
+    }
+
+    pub fn motes(&self) -> &U512 {
+        &self.motes
+    }
+
+    pub fn proof(self) -> TrieMerkleProof<Key, StoredValue> {
+        *self.proof
+    }
+}
+
+/// The result of a [`BalancesRequest`].
+///
+/// On success, holds one entry per requested purse, in request order.  A purse which can't be read
+/// yields an error in its own entry without affecting the others.
+#[derive(Debug)]
+pub enum BalancesResult {
+    RootNotFound,
+    Success(Vec<Result<PurseBalance, Error>>),
+}
+
+/// A request for the balances of the main purses of all accounts under a state root.
+///
+/// Accounts are scanned in ascending order of account hash, at most `limit` per request, so the
+/// whole global state is covered by repeating the request with `start_after` set to the `next`
+/// account hash of the previous [`GetAllBalancesResult`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GetAllBalancesRequest {
+    state_hash: Blake2bHash,
+    start_after: Option<AccountHash>,
+    limit: usize,
+}
+
+impl GetAllBalancesRequest {
+    pub fn new(state_hash: Blake2bHash, start_after: Option<AccountHash>, limit: usize) -> Self {
+        GetAllBalancesRequest {
+            state_hash,
+            start_after,
+            limit,
+        }
+    }
+
+    pub fn state_hash(&self) -> Blake2bHash {
+        self.state_hash
+    }
+
+    /// The account hash after which to start scanning, or `None` to start from the first account.
+    pub fn start_after(&self) -> Option<AccountHash> {
+        self.start_after
+    }
+
+    /// The maximum number of accounts to scan.
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+}
+
+/// The result of a [`GetAllBalancesRequest`].
+#[derive(Debug)]
+pub enum GetAllBalancesResult {
+    RootNotFound,
+    Success {
+        /// The account hash, main purse and balance of each account whose balance was read.
+        balances: Vec<(AccountHash, URef, U512)>,
+        /// The accounts which are malformed or whose balance couldn't be read.
+        errors: Vec<(AccountHash, Error)>,
+        /// The last account hash scanned if there may be further accounts, else `None`.
+        next: Option<AccountHash>,
+    },
+}
@@ -1,5 +1,6 @@
 use std::collections::BTreeSet;
 
+use casper_deploy_utils::Deploy;
 use casper_types::{account::AccountHash, DeployHash};
 
 use crate::core::engine_state::executable_deploy_item::ExecutableDeployItem;
@@ -37,3 +38,23 @@ impl DeployItem {
         }
     }
 }
+
+impl From<Deploy> for DeployItem {
+    fn from(deploy: Deploy) -> Self {
+        let address = deploy.header().account().to_account_hash();
+        let authorization_keys = deploy
+            .approvals()
+            .iter()
+            .map(|approval| approval.signer().to_account_hash())
+            .collect();
+
+        DeployItem::new(
+            address,
+            deploy.session().clone(),
+            deploy.payment().clone(),
+            deploy.header().gas_price(),
+            authorization_keys,
+            DeployHash::new(deploy.id().inner().to_array()),
+        )
+    }
+}
@@ -17,8 +17,8 @@ impl ExecutionEffect {
 
 impl From<&ExecutionEffect> for casper_types::ExecutionEffect {
     fn from(effect: &ExecutionEffect) -> Self {
-        casper_types::ExecutionEffect {
-            operations: effect
+        casper_types::ExecutionEffect::new(
+            effect
                 .ops
                 .iter()
                 .map(|(key, op)| casper_types::Operation {
@@ -26,7 +26,7 @@ impl From<&ExecutionEffect> for casper_types::ExecutionEffect {
                     kind: op.into(),
                 })
                 .collect(),
-            transforms: effect
+            effect
                 .transforms
                 .iter()
                 .map(|(key, transform)| casper_types::TransformEntry {
@@ -34,6 +34,6 @@ impl From<&ExecutionEffect> for casper_types::ExecutionEffect {
                     transform: transform.into(),
                 })
                 .collect(),
-        }
+        )
     }
 }
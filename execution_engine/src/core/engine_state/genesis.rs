@@ -449,7 +449,7 @@ impl FromBytes for GenesisAccount {
                 );
                 Ok((genesis_account, remainder))
             }
-            _ => Err(bytesrepr::Error::Formatting),
+            tag => Err(bytesrepr::Error::invalid_tag::<Self>(tag)),
         }
     }
 }
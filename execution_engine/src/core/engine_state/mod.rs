@@ -47,7 +47,10 @@ use casper_types::{
 };
 
 pub use self::{
-    balance::{BalanceRequest, BalanceResult},
+    balance::{
+        BalanceRequest, BalanceResult, BalancesRequest, BalancesResult, GetAllBalancesRequest,
+        GetAllBalancesResult, PurseBalance, PurseIdentifier,
+    },
     deploy_item::DeployItem,
     engine_config::EngineConfig,
     era_validators::{GetEraValidatorsError, GetEraValidatorsRequest},
@@ -57,17 +60,24 @@ pub use self::{
     execution::Error as ExecError,
     execution_result::{ExecutionResult, ExecutionResults, ForcedTransferResult},
     genesis::{ExecConfig, GenesisAccount, GenesisResult},
-    query::{GetBidsRequest, GetBidsResult, QueryRequest, QueryResult},
+    query::{
+        GetBidsRequest, GetBidsResult, GetKeyValuesRequest, GetKeyValuesResult, QueryRequest,
+        QueryResult,
+    },
     step::{RewardItem, SlashItem, StepRequest, StepResult},
     system_contract_cache::SystemContractCache,
-    transfer::{TransferArgs, TransferRuntimeArgsBuilder, TransferTargetMode},
+    transfer::{
+        batched_transfer_runtime_args, TransferArgs, TransferRuntimeArgsBuilder, TransferTargetMode,
+    },
     upgrade::{UpgradeConfig, UpgradeResult},
 };
 use crate::{
     core::{
         engine_state::{
-            executable_deploy_item::DeployKind, execution_result::ExecutionResultBuilder,
-            genesis::GenesisInstaller, upgrade::SystemUpgrader,
+            executable_deploy_item::{DeployKind, ExecutableDeployItemExt},
+            execution_result::ExecutionResultBuilder,
+            genesis::GenesisInstaller,
+            upgrade::SystemUpgrader,
         },
         execution::{self, DirectSystemContractCall, Executor},
         tracking_copy::{TrackingCopy, TrackingCopyExt},
@@ -81,6 +91,7 @@ use crate::{
         stored_value::StoredValue,
         transform::Transform,
         wasm_prep::Preprocessor,
+        TypeMismatch,
     },
     storage::{
         global_state::{CommitResult, StateProvider},
@@ -515,6 +526,128 @@ where
         Ok(BalanceResult::Success { motes, proof })
     }
 
+    /// Reads the balances of all purses in `balances_request` from a single view of global state.
+    pub fn get_purse_balances(
+        &self,
+        correlation_id: CorrelationId,
+        balances_request: BalancesRequest,
+    ) -> Result<BalancesResult, Error> {
+        let mut tracking_copy = match self.tracking_copy(balances_request.state_hash())? {
+            Some(tracking_copy) => tracking_copy,
+            None => return Ok(BalancesResult::RootNotFound),
+        };
+        let balances = balances_request
+            .purse_identifiers()
+            .iter()
+            .map(|purse_identifier| {
+                Self::read_purse_balance(correlation_id, &mut tracking_copy, *purse_identifier)
+            })
+            .collect();
+        Ok(BalancesResult::Success(balances))
+    }
+
+    fn read_purse_balance(
+        correlation_id: CorrelationId,
+        tracking_copy: &mut TrackingCopy<S::Reader>,
+        purse_identifier: PurseIdentifier,
+    ) -> Result<PurseBalance, Error> {
+        let purse_uref = match purse_identifier {
+            PurseIdentifier::Purse(purse_uref) => purse_uref,
+            PurseIdentifier::Account(account_hash) => tracking_copy
+                .read_account(correlation_id, account_hash)?
+                .main_purse(),
+        };
+        let purse_balance_key =
+            tracking_copy.get_purse_balance_key(correlation_id, purse_uref.into())?;
+        let (balance, proof) =
+            tracking_copy.get_purse_balance_with_proof(correlation_id, purse_balance_key)?;
+        Ok(PurseBalance::new(
+            purse_uref,
+            balance.value(),
+            Box::new(proof),
+        ))
+    }
+
+    /// Reads the balances of the main purses of a chunk of the accounts under the requested state
+    /// root.
+    ///
+    /// An account which is malformed or whose balance can't be read is reported in the result's
+    /// errors rather than failing the request.
+    pub fn get_all_balances(
+        &self,
+        correlation_id: CorrelationId,
+        get_all_balances_request: GetAllBalancesRequest,
+    ) -> Result<GetAllBalancesResult, Error> {
+        let state_hash = get_all_balances_request.state_hash();
+        let mut tracking_copy = match self.tracking_copy(state_hash)? {
+            Some(tracking_copy) => tracking_copy,
+            None => return Ok(GetAllBalancesResult::RootNotFound),
+        };
+        let limit = get_all_balances_request.limit();
+        let start_after = get_all_balances_request.start_after().map(Key::Account);
+        let key_values = match self
+            .state
+            .key_values_with_prefix(
+                correlation_id,
+                state_hash,
+                &[KeyTag::Account as u8],
+                start_after.as_ref(),
+                limit,
+            )
+            .map_err(Into::into)?
+        {
+            Some(key_values) => key_values,
+            None => return Ok(GetAllBalancesResult::RootNotFound),
+        };
+
+        let next = if key_values.len() == limit {
+            key_values.last().and_then(|(key, _)| key.into_account())
+        } else {
+            None
+        };
+
+        let mut balances = Vec::with_capacity(key_values.len());
+        let mut errors = vec![];
+        for (key, stored_value) in key_values {
+            let account_hash = match key.into_account() {
+                Some(account_hash) => account_hash,
+                None => continue,
+            };
+            let main_purse = match stored_value {
+                StoredValue::Account(account) => account.main_purse(),
+                other => {
+                    let error = execution::Error::TypeMismatch(TypeMismatch::new(
+                        "Account".to_string(),
+                        other.type_name(),
+                    ));
+                    errors.push((account_hash, error.into()));
+                    continue;
+                }
+            };
+            match Self::read_main_purse_balance(correlation_id, &mut tracking_copy, main_purse) {
+                Ok(motes) => balances.push((account_hash, main_purse, motes)),
+                Err(error) => errors.push((account_hash, error)),
+            }
+        }
+
+        Ok(GetAllBalancesResult::Success {
+            balances,
+            errors,
+            next,
+        })
+    }
+
+    fn read_main_purse_balance(
+        correlation_id: CorrelationId,
+        tracking_copy: &mut TrackingCopy<S::Reader>,
+        main_purse: URef,
+    ) -> Result<U512, Error> {
+        let purse_balance_key =
+            tracking_copy.get_purse_balance_key(correlation_id, main_purse.into())?;
+        let balance = tracking_copy.get_purse_balance(correlation_id, purse_balance_key)?;
+        Ok(balance.value())
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn transfer(
         &self,
@@ -625,9 +758,18 @@ where
 
         let gas_limit = Gas::new(U512::from(std::u64::MAX));
 
-        let wasmless_transfer_gas_cost = Gas::new(U512::from(
-            protocol_data.system_config().wasmless_transfer_cost(),
-        ));
+        // The cost of a batch scales with the number of transfers in it.  A malformed batch is
+        // charged as a single transfer.
+        let maybe_batch = batched_transfer_runtime_args(deploy_item.session.args());
+        let transfer_count = match &maybe_batch {
+            Ok(Some(batch)) => batch.len() as u64,
+            _ => 1,
+        };
+
+        let wasmless_transfer_gas_cost = Gas::new(
+            U512::from(protocol_data.system_config().wasmless_transfer_cost())
+                * U512::from(transfer_count),
+        );
 
         let wasmless_transfer_motes = match Motes::from_gas(
             wasmless_transfer_gas_cost,
@@ -700,71 +842,93 @@ where
         // All wasmless transfer preconditions are met.
         // Any error that occurs in logic below this point would result in a charge for user error.
 
-        let mut runtime_args_builder =
-            TransferRuntimeArgsBuilder::new(deploy_item.session.args().clone());
-
-        match runtime_args_builder.transfer_target_mode(correlation_id, Rc::clone(&tracking_copy)) {
-            Ok(mode) => match mode {
-                TransferTargetMode::Unknown | TransferTargetMode::PurseExists(_) => { /* noop */ }
-                TransferTargetMode::CreateAccount(public_key) => {
-                    let create_purse_call_stack = {
-                        let system = CallStackElement::session(PublicKey::System.to_account_hash());
-                        let mint = CallStackElement::stored_contract(
-                            mint_contract.contract_package_hash(),
-                            mint_contract_hash,
-                        );
-                        vec![system, mint]
-                    };
-                    let (maybe_uref, execution_result): (Option<URef>, ExecutionResult) = executor
-                        .exec_system_contract(
-                            DirectSystemContractCall::CreatePurse,
-                            system_module.clone(),
-                            RuntimeArgs::new(), // mint create takes no arguments
-                            &mut mint_named_keys,
-                            Default::default(),
-                            mint_base_key,
-                            &account,
-                            authorization_keys.clone(),
-                            blocktime,
-                            deploy_item.deploy_hash,
-                            gas_limit,
-                            protocol_version,
-                            correlation_id,
-                            Rc::clone(&tracking_copy),
-                            Phase::Session,
-                            protocol_data,
-                            SystemContractCache::clone(&self.system_contract_cache),
-                            create_purse_call_stack,
-                        );
-                    match maybe_uref {
-                        Some(main_purse) => {
-                            let new_account =
-                                Account::create(public_key, Default::default(), main_purse);
-                            mint_extra_keys.push(Key::from(main_purse));
-                            // write new account
-                            tracking_copy
-                                .borrow_mut()
-                                .write(Key::Account(public_key), StoredValue::Account(new_account))
-                        }
-                        None => {
-                            // This case implies that the execution_result is a failure variant as
-                            // implemented inside host_exec().
-                            let error = execution_result
-                                .take_error()
-                                .unwrap_or(Error::InsufficientPayment);
-                            return Ok(make_charged_execution_failure(error));
+        // Every transfer in a batch is resolved and checked before any of them is made.  Should any
+        // of them fail, the deploy fails as a whole, with only the cost of the transfers charged.
+        let (is_batch, all_runtime_args) = match maybe_batch {
+            Ok(Some(batch)) => (true, batch),
+            Ok(None) => (false, vec![deploy_item.session.args().clone()]),
+            Err(error) => return Ok(make_charged_execution_failure(error)),
+        };
+
+        let mut all_transfer_args = Vec::with_capacity(all_runtime_args.len());
+        for runtime_args in all_runtime_args {
+            let mut runtime_args_builder = TransferRuntimeArgsBuilder::new(runtime_args);
+
+            match runtime_args_builder
+                .transfer_target_mode(correlation_id, Rc::clone(&tracking_copy))
+            {
+                Ok(mode) => match mode {
+                    TransferTargetMode::Unknown | TransferTargetMode::PurseExists(_) => {}
+                    TransferTargetMode::CreateAccount(public_key) => {
+                        let create_purse_call_stack = {
+                            let system =
+                                CallStackElement::session(PublicKey::System.to_account_hash());
+                            let mint = CallStackElement::stored_contract(
+                                mint_contract.contract_package_hash(),
+                                mint_contract_hash,
+                            );
+                            vec![system, mint]
+                        };
+                        let (maybe_uref, execution_result): (Option<URef>, ExecutionResult) =
+                            executor.exec_system_contract(
+                                DirectSystemContractCall::CreatePurse,
+                                system_module.clone(),
+                                RuntimeArgs::new(), // mint create takes no arguments
+                                &mut mint_named_keys,
+                                Default::default(),
+                                mint_base_key,
+                                &account,
+                                authorization_keys.clone(),
+                                blocktime,
+                                deploy_item.deploy_hash,
+                                gas_limit,
+                                protocol_version,
+                                correlation_id,
+                                Rc::clone(&tracking_copy),
+                                Phase::Session,
+                                protocol_data,
+                                SystemContractCache::clone(&self.system_contract_cache),
+                                create_purse_call_stack,
+                            );
+                        match maybe_uref {
+                            Some(main_purse) => {
+                                let new_account =
+                                    Account::create(public_key, Default::default(), main_purse);
+                                mint_extra_keys.push(Key::from(main_purse));
+                                // write new account
+                                tracking_copy.borrow_mut().write(
+                                    Key::Account(public_key),
+                                    StoredValue::Account(new_account),
+                                )
+                            }
+                            None => {
+                                // This case implies that the execution_result is a failure variant
+                                // as implemented inside
+                                // host_exec().
+                                let error = execution_result
+                                    .take_error()
+                                    .unwrap_or(Error::InsufficientPayment);
+                                return Ok(make_charged_execution_failure(error));
+                            }
                         }
                     }
-                }
-            },
-            Err(error) => return Ok(make_charged_execution_failure(error)),
-        }
+                },
+                Err(error) => return Ok(make_charged_execution_failure(error)),
+            }
 
-        let transfer_args =
-            match runtime_args_builder.build(&account, correlation_id, Rc::clone(&tracking_copy)) {
+            let transfer_args = match runtime_args_builder.build(
+                &account,
+                correlation_id,
+                Rc::clone(&tracking_copy),
+            ) {
                 Ok(transfer_args) => transfer_args,
                 Err(error) => return Ok(make_charged_execution_failure(error)),
             };
+            all_transfer_args.push(transfer_args);
+        }
+
+        // All transfers in a batch share the same source purse.
+        let transfer_args = all_transfer_args[0];
 
         // Construct a payment code that will put cost of wasmless payment into payment purse
         let payment_result = {
@@ -791,7 +955,13 @@ where
                 account_main_purse_balance
             };
 
-            let transfer_amount_motes = Motes::new(transfer_args.amount());
+            let maybe_total_transfer_amount = all_transfer_args
+                .iter()
+                .try_fold(U512::zero(), |total, args| total.checked_add(args.amount()));
+            let transfer_amount_motes = match maybe_total_transfer_amount {
+                Some(total_amount) => Motes::new(total_amount),
+                None => return Ok(make_charged_execution_failure(Error::InsufficientPayment)),
+            };
 
             match wasmless_transfer_motes.checked_add(transfer_amount_motes) {
                 Some(total_amount) if source_purse_balance < total_amount => {
@@ -947,48 +1117,62 @@ where
             payment_result.with_cost(payment_gas)
         };
 
-        let runtime_args = match RuntimeArgs::try_from(transfer_args) {
-            Ok(runtime_args) => runtime_args,
-            Err(error) => {
-                return Ok(make_charged_execution_failure(
-                    ExecError::from(error).into(),
-                ))
-            }
-        };
+        let mut transfers = Vec::with_capacity(all_transfer_args.len());
+        let mut session_result = ExecutionResult::default();
+        for transfer_args in all_transfer_args {
+            let runtime_args = match RuntimeArgs::try_from(transfer_args) {
+                Ok(runtime_args) => runtime_args,
+                Err(error) => {
+                    return Ok(make_charged_execution_failure(
+                        ExecError::from(error).into(),
+                    ))
+                }
+            };
 
-        let transfer_call_stack = {
-            let deploy_account = CallStackElement::session(deploy_item.address);
-            let mint = CallStackElement::stored_contract(
-                mint_contract.contract_package_hash(),
-                mint_contract_hash,
-            );
-            vec![deploy_account, mint]
-        };
-        let (_, mut session_result): (Option<Result<(), u8>>, ExecutionResult) = executor
-            .exec_system_contract(
-                DirectSystemContractCall::Transfer,
-                system_module.clone(),
-                runtime_args,
-                &mut mint_named_keys,
-                mint_extra_keys.as_slice(),
-                mint_base_key,
-                &account,
-                authorization_keys.clone(),
-                blocktime,
-                deploy_item.deploy_hash,
-                gas_limit,
-                protocol_version,
-                correlation_id,
-                Rc::clone(&tracking_copy),
-                Phase::Session,
-                protocol_data,
-                SystemContractCache::clone(&self.system_contract_cache),
-                transfer_call_stack,
-            );
+            let transfer_call_stack = {
+                let deploy_account = CallStackElement::session(deploy_item.address);
+                let mint = CallStackElement::stored_contract(
+                    mint_contract.contract_package_hash(),
+                    mint_contract_hash,
+                );
+                vec![deploy_account, mint]
+            };
+            let (_, transfer_result): (Option<Result<(), u8>>, ExecutionResult) = executor
+                .exec_system_contract(
+                    DirectSystemContractCall::Transfer,
+                    system_module.clone(),
+                    runtime_args,
+                    &mut mint_named_keys,
+                    mint_extra_keys.as_slice(),
+                    mint_base_key,
+                    &account,
+                    authorization_keys.clone(),
+                    blocktime,
+                    deploy_item.deploy_hash,
+                    gas_limit,
+                    protocol_version,
+                    correlation_id,
+                    Rc::clone(&tracking_copy),
+                    Phase::Session,
+                    protocol_data,
+                    SystemContractCache::clone(&self.system_contract_cache),
+                    transfer_call_stack,
+                );
+
+            if is_batch {
+                if let Some(error) = transfer_result.as_error().cloned() {
+                    return Ok(make_charged_execution_failure(error));
+                }
+            }
+            transfers.extend_from_slice(transfer_result.transfers());
+            session_result = transfer_result;
+        }
 
         // User is already charged fee for wasmless contract, and we need to make sure we will not
         // charge for anything that happens while calling transfer entrypoint.
-        session_result = session_result.with_cost(Gas::default());
+        session_result = session_result
+            .with_cost(Gas::default())
+            .with_transfers(transfers);
 
         let finalize_result = {
             let handle_payment_args = {
@@ -1810,6 +1994,29 @@ where
         Ok(GetBidsResult::Success { bids })
     }
 
+    /// Returns a page of the key-value pairs under the requested state root whose serialized keys
+    /// start with the requested prefix.
+    pub fn get_key_values(
+        &self,
+        correlation_id: CorrelationId,
+        get_key_values_request: GetKeyValuesRequest,
+    ) -> Result<GetKeyValuesResult, Error>
+    where
+        Error: From<S::Error>,
+    {
+        let maybe_key_values = self.state.key_values_with_prefix(
+            correlation_id,
+            get_key_values_request.state_hash(),
+            get_key_values_request.prefix(),
+            get_key_values_request.start_after(),
+            get_key_values_request.limit(),
+        )?;
+        match maybe_key_values {
+            Some(key_values) => Ok(GetKeyValuesResult::Success { key_values }),
+            None => Ok(GetKeyValuesResult::RootNotFound),
+        }
+    }
+
     pub fn commit_step(
         &self,
         correlation_id: CorrelationId,
@@ -2145,3 +2352,275 @@ where
         Ok(BalanceResult::Success { motes, proof })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+    use crate::storage::global_state::in_memory::InMemoryGlobalState;
+
+    /// Wraps an `InMemoryGlobalState`, counting how many times state is checked out.
+    struct CountingGlobalState {
+        inner: InMemoryGlobalState,
+        checkouts: Cell<usize>,
+    }
+
+    impl StateProvider for CountingGlobalState {
+        type Error = <InMemoryGlobalState as StateProvider>::Error;
+        type Reader = <InMemoryGlobalState as StateProvider>::Reader;
+
+        fn checkout(&self, state_hash: Blake2bHash) -> Result<Option<Self::Reader>, Self::Error> {
+            self.checkouts.set(self.checkouts.get() + 1);
+            self.inner.checkout(state_hash)
+        }
+
+        fn commit(
+            &self,
+            correlation_id: CorrelationId,
+            state_hash: Blake2bHash,
+            effects: AdditiveMap<Key, Transform>,
+        ) -> Result<CommitResult, Self::Error> {
+            self.inner.commit(correlation_id, state_hash, effects)
+        }
+
+        fn put_protocol_data(
+            &self,
+            protocol_version: ProtocolVersion,
+            protocol_data: &ProtocolData,
+        ) -> Result<(), Self::Error> {
+            self.inner
+                .put_protocol_data(protocol_version, protocol_data)
+        }
+
+        fn get_protocol_data(
+            &self,
+            protocol_version: ProtocolVersion,
+        ) -> Result<Option<ProtocolData>, Self::Error> {
+            self.inner.get_protocol_data(protocol_version)
+        }
+
+        fn empty_root(&self) -> Blake2bHash {
+            self.inner.empty_root()
+        }
+
+        fn read_trie(
+            &self,
+            correlation_id: CorrelationId,
+            trie_key: &Blake2bHash,
+        ) -> Result<Option<Trie<Key, StoredValue>>, Self::Error> {
+            self.inner.read_trie(correlation_id, trie_key)
+        }
+
+        fn put_trie(
+            &self,
+            correlation_id: CorrelationId,
+            trie: &Trie<Key, StoredValue>,
+        ) -> Result<Blake2bHash, Self::Error> {
+            self.inner.put_trie(correlation_id, trie)
+        }
+
+        fn missing_trie_keys(
+            &self,
+            correlation_id: CorrelationId,
+            trie_keys: Vec<Blake2bHash>,
+        ) -> Result<Vec<Blake2bHash>, Self::Error> {
+            self.inner.missing_trie_keys(correlation_id, trie_keys)
+        }
+
+        fn key_values_with_prefix(
+            &self,
+            correlation_id: CorrelationId,
+            state_hash: Blake2bHash,
+            prefix: &[u8],
+            start_after: Option<&Key>,
+            limit: usize,
+        ) -> Result<Option<Vec<(Key, StoredValue)>>, Self::Error> {
+            self.inner.key_values_with_prefix(
+                correlation_id,
+                state_hash,
+                prefix,
+                start_after,
+                limit,
+            )
+        }
+    }
+
+    fn purse(seed: u8) -> URef {
+        URef::new([seed; 32], AccessRights::READ_ADD_WRITE)
+    }
+
+    fn balance_entry(purse: URef, motes: u64) -> (Key, StoredValue) {
+        let cl_value = CLValue::from_t(U512::from(motes)).unwrap();
+        (Key::Balance(purse.addr()), StoredValue::CLValue(cl_value))
+    }
+
+    fn account_entry(account_hash: AccountHash, main_purse: URef) -> (Key, StoredValue) {
+        let account = Account::create(account_hash, Default::default(), main_purse);
+        (Key::Account(account_hash), StoredValue::Account(account))
+    }
+
+    #[test]
+    fn should_get_partial_balances_from_single_checkout() {
+        let correlation_id = CorrelationId::new();
+        let account_1 = AccountHash::new([1; 32]);
+        let account_2 = AccountHash::new([2; 32]);
+        let missing_account = AccountHash::new([3; 32]);
+        let extra_purse = purse(13);
+        let missing_purse = purse(14);
+
+        let pairs = [
+            account_entry(account_1, purse(11)),
+            balance_entry(purse(11), 100),
+            account_entry(account_2, purse(12)),
+            balance_entry(purse(12), 200),
+            balance_entry(extra_purse, 300),
+        ];
+        let (inner, state_hash) = InMemoryGlobalState::from_pairs(correlation_id, &pairs).unwrap();
+        let state = CountingGlobalState {
+            inner,
+            checkouts: Cell::new(0),
+        };
+        let engine_state = EngineState::new(state, EngineConfig::default());
+
+        let request = BalancesRequest::new(
+            state_hash,
+            vec![
+                PurseIdentifier::Account(account_1),
+                PurseIdentifier::Purse(missing_purse),
+                PurseIdentifier::Purse(extra_purse),
+                PurseIdentifier::Account(missing_account),
+                PurseIdentifier::Account(account_2),
+            ],
+        );
+        let balances = match engine_state.get_purse_balances(correlation_id, request) {
+            Ok(BalancesResult::Success(balances)) => balances,
+            other => panic!("unexpected result: {:?}", other),
+        };
+        assert_eq!(engine_state.state.checkouts.get(), 1);
+
+        assert_eq!(balances.len(), 5);
+        let found: Vec<Option<(URef, U512)>> = balances
+            .iter()
+            .map(|result| {
+                result
+                    .as_ref()
+                    .ok()
+                    .map(|balance| (balance.purse_uref(), *balance.motes()))
+            })
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                Some((purse(11), U512::from(100))),
+                None,
+                Some((extra_purse, U512::from(300))),
+                None,
+                Some((purse(12), U512::from(200))),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_report_missing_root_for_balances_request() {
+        let correlation_id = CorrelationId::new();
+        let engine_state = EngineState::new(
+            InMemoryGlobalState::empty().unwrap(),
+            EngineConfig::default(),
+        );
+
+        let request = BalancesRequest::new(
+            Blake2bHash::new(&[1]),
+            vec![PurseIdentifier::Purse(purse(1))],
+        );
+        match engine_state.get_purse_balances(correlation_id, request) {
+            Ok(BalancesResult::RootNotFound) => (),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn should_get_all_balances_in_chunks() {
+        const ACCOUNT_COUNT: u8 = 40;
+        const CHUNK_SIZE: usize = 7;
+
+        let correlation_id = CorrelationId::new();
+        let mut pairs = vec![];
+        let mut expected_total = U512::zero();
+        for seed in 1..=ACCOUNT_COUNT {
+            let main_purse = purse(seed);
+            // The first account's balance is zero.
+            let motes = u64::from(seed - 1) * 1_000;
+            expected_total += U512::from(motes);
+            pairs.push(account_entry(AccountHash::new([seed; 32]), main_purse));
+            pairs.push(balance_entry(main_purse, motes));
+        }
+        // An account whose main purse has no balance, and a value of the wrong type stored under
+        // an account key.
+        let purseless_account = AccountHash::new([101; 32]);
+        let malformed_account = AccountHash::new([102; 32]);
+        pairs.push(account_entry(purseless_account, purse(101)));
+        pairs.push((
+            Key::Account(malformed_account),
+            StoredValue::CLValue(CLValue::from_t(1_u8).unwrap()),
+        ));
+        let (state, state_hash) = InMemoryGlobalState::from_pairs(correlation_id, &pairs).unwrap();
+        let engine_state = EngineState::new(state, EngineConfig::default());
+
+        let mut balances = vec![];
+        let mut errors = vec![];
+        let mut start_after = None;
+        let mut chunk_count = 0;
+        loop {
+            let request = GetAllBalancesRequest::new(state_hash, start_after, CHUNK_SIZE);
+            match engine_state.get_all_balances(correlation_id, request) {
+                Ok(GetAllBalancesResult::Success {
+                    balances: chunk_balances,
+                    errors: chunk_errors,
+                    next,
+                }) => {
+                    assert!(chunk_balances.len() + chunk_errors.len() <= CHUNK_SIZE);
+                    chunk_count += 1;
+                    balances.extend(chunk_balances);
+                    errors.extend(chunk_errors);
+                    match next {
+                        Some(account_hash) => start_after = Some(account_hash),
+                        None => break,
+                    }
+                }
+                other => panic!("unexpected result: {:?}", other),
+            }
+        }
+
+        let scanned_count = usize::from(ACCOUNT_COUNT) + 2;
+        assert_eq!(chunk_count, (scanned_count + CHUNK_SIZE) / CHUNK_SIZE);
+        assert_eq!(balances.len(), usize::from(ACCOUNT_COUNT));
+        let total = balances
+            .iter()
+            .fold(U512::zero(), |total, (_, _, motes)| total + *motes);
+        assert_eq!(total, expected_total);
+        assert!(balances.contains(&(AccountHash::new([1; 32]), purse(1), U512::zero())));
+
+        let mut error_accounts: Vec<AccountHash> = errors
+            .iter()
+            .map(|(account_hash, _)| *account_hash)
+            .collect();
+        error_accounts.sort();
+        assert_eq!(error_accounts, vec![purseless_account, malformed_account]);
+    }
+
+    #[test]
+    fn should_report_missing_root_for_all_balances_request() {
+        let correlation_id = CorrelationId::new();
+        let engine_state = EngineState::new(
+            InMemoryGlobalState::empty().unwrap(),
+            EngineConfig::default(),
+        );
+
+        let request = GetAllBalancesRequest::new(Blake2bHash::new(&[1]), None, 10);
+        match engine_state.get_all_balances(correlation_id, request) {
+            Ok(GetAllBalancesResult::RootNotFound) => (),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
@@ -98,3 +98,63 @@ impl GetBidsResult {
         }
     }
 }
+
+/// A request for a page of the key-value pairs under a state root whose serialized keys start with
+/// a given prefix.
+///
+/// Pairs are returned in ascending order of serialized key.  To read the following page, repeat
+/// the request with `start_after` set to the last key returned.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GetKeyValuesRequest {
+    state_hash: Blake2bHash,
+    prefix: Vec<u8>,
+    start_after: Option<Key>,
+    limit: usize,
+}
+
+impl GetKeyValuesRequest {
+    pub fn new(
+        state_hash: Blake2bHash,
+        prefix: Vec<u8>,
+        start_after: Option<Key>,
+        limit: usize,
+    ) -> Self {
+        GetKeyValuesRequest {
+            state_hash,
+            prefix,
+            start_after,
+            limit,
+        }
+    }
+
+    pub fn state_hash(&self) -> Blake2bHash {
+        self.state_hash
+    }
+
+    pub fn prefix(&self) -> &[u8] {
+        &self.prefix
+    }
+
+    pub fn start_after(&self) -> Option<&Key> {
+        self.start_after.as_ref()
+    }
+
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+}
+
+#[derive(Debug)]
+pub enum GetKeyValuesResult {
+    RootNotFound,
+    Success { key_values: Vec<(Key, StoredValue)> },
+}
+
+impl GetKeyValuesResult {
+    pub fn key_values(&self) -> Option<&[(Key, StoredValue)]> {
+        match self {
+            GetKeyValuesResult::RootNotFound => None,
+            GetKeyValuesResult::Success { key_values } => Some(key_values),
+        }
+    }
+}
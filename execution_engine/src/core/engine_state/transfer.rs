@@ -1,8 +1,11 @@
 use std::{cell::RefCell, convert::TryFrom, rc::Rc};
 
 use casper_types::{
-    account::AccountHash, system::mint, AccessRights, ApiError, CLType, CLValueError, Key,
-    RuntimeArgs, URef, U512,
+    account::AccountHash,
+    bytesrepr::{FromBytes, ToBytes},
+    system::mint,
+    AccessRights, ApiError, CLType, CLTyped, CLValue, CLValueError, Key, PublicKey, RuntimeArgs,
+    URef, U512,
 };
 
 use crate::{
@@ -81,6 +84,76 @@ impl TryFrom<TransferArgs> for RuntimeArgs {
     }
 }
 
+/// The target, amount and id of a single transfer in a batched native transfer.
+type BatchItem<T> = (T, U512, Option<u64>);
+
+/// Returns the runtime args of each transfer in a batched native transfer, or `None` if
+/// `runtime_args` are those of a single native transfer.
+///
+/// A batch is given as a `transfers` arg of type `List<Tuple3<T, U512, Option<u64>>>` holding the
+/// target, amount and id of each transfer, where the target `T` is an `AccountHash`, a
+/// `PublicKey` or a `Key::Account`.  A `source` arg, if given, applies to every transfer in the
+/// batch.  An empty batch is invalid.
+pub fn batched_transfer_runtime_args(
+    runtime_args: &RuntimeArgs,
+) -> Result<Option<Vec<RuntimeArgs>>, Error> {
+    let cl_value = match runtime_args.get(mint::ARG_TRANSFERS) {
+        Some(cl_value) => cl_value,
+        None => return Ok(None),
+    };
+    let maybe_source = runtime_args.get(mint::ARG_SOURCE);
+    let cl_type = cl_value.cl_type();
+
+    let batch = if *cl_type == Vec::<BatchItem<AccountHash>>::cl_type() {
+        decode_batch::<AccountHash>(cl_value)?
+            .into_iter()
+            .map(|item| batch_item_runtime_args(maybe_source, item))
+            .collect::<Result<Vec<_>, _>>()?
+    } else if *cl_type == Vec::<BatchItem<PublicKey>>::cl_type() {
+        decode_batch::<PublicKey>(cl_value)?
+            .into_iter()
+            .map(|(public_key, amount, id)| {
+                batch_item_runtime_args(maybe_source, (public_key.to_account_hash(), amount, id))
+            })
+            .collect::<Result<Vec<_>, _>>()?
+    } else if *cl_type == Vec::<BatchItem<Key>>::cl_type() {
+        decode_batch::<Key>(cl_value)?
+            .into_iter()
+            .map(|item| batch_item_runtime_args(maybe_source, item))
+            .collect::<Result<Vec<_>, _>>()?
+    } else {
+        return Err(Error::Exec(ExecError::Revert(ApiError::InvalidArgument)));
+    };
+
+    if batch.is_empty() {
+        return Err(Error::Exec(ExecError::Revert(ApiError::InvalidArgument)));
+    }
+    Ok(Some(batch))
+}
+
+fn decode_batch<T: CLTyped + FromBytes>(cl_value: &CLValue) -> Result<Vec<BatchItem<T>>, Error> {
+    cl_value
+        .clone()
+        .into_t()
+        .map_err(|error| Error::Exec(ExecError::Revert(error.into())))
+}
+
+fn batch_item_runtime_args<T: CLTyped + ToBytes>(
+    maybe_source: Option<&CLValue>,
+    (target, amount, id): BatchItem<T>,
+) -> Result<RuntimeArgs, Error> {
+    RuntimeArgs::try_new(|args| {
+        if let Some(source) = maybe_source {
+            args.insert_cl_value(mint::ARG_SOURCE, source.clone());
+        }
+        args.insert(mint::ARG_TARGET, target)?;
+        args.insert(mint::ARG_AMOUNT, amount)?;
+        args.insert(mint::ARG_ID, id)?;
+        Ok(())
+    })
+    .map_err(|error| Error::Exec(error.into()))
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct TransferRuntimeArgsBuilder {
     inner: RuntimeArgs,
@@ -13,7 +13,9 @@ use std::{
 use linked_hash_map::LinkedHashMap;
 use thiserror::Error;
 
-use casper_types::{bytesrepr, CLType, CLValue, CLValueError, Key, KeyTag, Tagged, U512};
+use casper_types::{
+    bytesrepr, contracts::NamedKeys, CLType, CLValue, CLValueError, Key, KeyTag, Tagged, U512,
+};
 
 pub use self::ext::TrackingCopyExt;
 use self::meter::{heap_meter::HeapSize, Meter};
@@ -94,6 +96,17 @@ impl Query {
         TrackingCopyQueryResult::ValueNotFound(msg)
     }
 
+    /// Panics if `unvisited_names` is empty.
+    fn into_not_traversable_result(self, stored_value: &StoredValue) -> TrackingCopyQueryResult {
+        let msg = format!(
+            "Failed to resolve path segment '{}' as {} value cannot be traversed, at path: {}",
+            self.unvisited_names.front().unwrap(),
+            stored_value.type_name(),
+            self.current_path()
+        );
+        TrackingCopyQueryResult::ValueNotFound(msg)
+    }
+
     fn into_circular_ref_result(self) -> TrackingCopyQueryResult {
         let msg = format!(
             "{:?} has formed a circular reference at path: {}",
@@ -480,7 +493,8 @@ impl<R: StateReader<Key, StoredValue>> TrackingCopy<R> {
                     if let Some(key) = account.named_keys().get(name) {
                         query.navigate(*key);
                     } else {
-                        let msg_prefix = format!("Name {} not found in Account", name);
+                        let msg_prefix =
+                            format!("Failed to resolve path segment '{}' in Account", name);
                         return Ok(query.into_not_found_result(&msg_prefix));
                     }
                 }
@@ -491,43 +505,25 @@ impl<R: StateReader<Key, StoredValue>> TrackingCopy<R> {
                         return Ok(query.into_not_found_result("Failed to parse CLValue as Key"));
                     }
                 }
-                StoredValue::CLValue(cl_value) => {
-                    let msg_prefix = format!(
-                        "Query cannot continue as {:?} is not an account, contract nor key to \
-                        such.  Value found",
-                        cl_value
-                    );
-                    return Ok(query.into_not_found_result(&msg_prefix));
-                }
                 StoredValue::Contract(contract) => {
                     let name = query.next_name();
                     if let Some(key) = contract.named_keys().get(name) {
                         query.navigate(*key);
                     } else {
-                        let msg_prefix = format!("Name {} not found in Contract", name);
+                        let msg_prefix =
+                            format!("Failed to resolve path segment '{}' in Contract", name);
                         return Ok(query.into_not_found_result(&msg_prefix));
                     }
                 }
-                StoredValue::ContractPackage(_) => {
-                    return Ok(query.into_not_found_result("ContractPackage value found."));
-                }
-                StoredValue::ContractWasm(_) => {
-                    return Ok(query.into_not_found_result("ContractWasm value found."));
-                }
-                StoredValue::Transfer(_) => {
-                    return Ok(query.into_not_found_result("Transfer value found."));
-                }
-                StoredValue::DeployInfo(_) => {
-                    return Ok(query.into_not_found_result("DeployInfo value found."));
-                }
-                StoredValue::EraInfo(_) => {
-                    return Ok(query.into_not_found_result("EraInfo value found."));
-                }
-                StoredValue::Bid(_) => {
-                    return Ok(query.into_not_found_result("Bid value found."));
-                }
-                StoredValue::Withdraw(_) => {
-                    return Ok(query.into_not_found_result("UnbondingPurses value found."));
+                StoredValue::CLValue(_)
+                | StoredValue::ContractPackage(_)
+                | StoredValue::ContractWasm(_)
+                | StoredValue::Transfer(_)
+                | StoredValue::DeployInfo(_)
+                | StoredValue::EraInfo(_)
+                | StoredValue::Bid(_)
+                | StoredValue::Withdraw(_) => {
+                    return Ok(query.into_not_traversable_result(stored_value));
                 }
             }
         }
@@ -616,6 +612,11 @@ impl From<bytesrepr::Error> for ValidationError {
     }
 }
 
+/// Validates the proofs returned by a successful query of global state.
+///
+/// As with the query itself, a value which is a `CLValue` holding a `Key` is followed to that key
+/// without consuming a component of `path`, while accounts and contracts are traversed via the
+/// named key given by the next component of `path`.
 pub fn validate_query_proof(
     hash: &Blake2bHash,
     proofs: &[TrieMerkleProof<Key, StoredValue>],
@@ -623,7 +624,8 @@ pub fn validate_query_proof(
     path: &[String],
     expected_value: &StoredValue,
 ) -> Result<(), ValidationError> {
-    if proofs.len() != path.len() + 1 {
+    // Each path component requires one proof, as does the base key.
+    if proofs.len() < path.len() + 1 {
         return Err(ValidationError::PathLengthDifferentThanProofLessOne);
     }
 
@@ -641,17 +643,21 @@ pub fn validate_query_proof(
     }
 
     let mut proof_value = first_proof.value();
+    let mut path_iter = path.iter().peekable();
 
-    for (proof, path_component) in proofs_iter.zip(path.iter()) {
-        let named_keys = match proof_value {
-            StoredValue::Account(account) => account.named_keys(),
-            StoredValue::Contract(contract) => contract.named_keys(),
-            _ => return Err(ValidationError::PathCold),
-        };
+    for proof in proofs_iter {
+        // A query stops as soon as the path is exhausted, so there should be no further proofs.
+        if path_iter.peek().is_none() {
+            return Err(ValidationError::PathLengthDifferentThanProofLessOne);
+        }
 
-        let key = match named_keys.get(path_component) {
-            Some(key) => key,
-            None => return Err(ValidationError::PathCold),
+        let key = match proof_value {
+            StoredValue::CLValue(cl_value) if cl_value.cl_type() == &CLType::Key => {
+                cl_value.to_owned().into_t::<Key>()?
+            }
+            StoredValue::Account(account) => named_key(account.named_keys(), path_iter.next())?,
+            StoredValue::Contract(contract) => named_key(contract.named_keys(), path_iter.next())?,
+            _ => return Err(ValidationError::PathCold),
         };
 
         if proof.key() != &key.normalize() {
@@ -665,6 +671,10 @@ pub fn validate_query_proof(
         proof_value = proof.value();
     }
 
+    if path_iter.next().is_some() {
+        return Err(ValidationError::PathLengthDifferentThanProofLessOne);
+    }
+
     if proof_value != expected_value {
         return Err(ValidationError::UnexpectedValue);
     }
@@ -672,6 +682,14 @@ pub fn validate_query_proof(
     Ok(())
 }
 
+/// Returns the key under `maybe_name` in `named_keys`, or `PathCold` if there is none.
+fn named_key(named_keys: &NamedKeys, maybe_name: Option<&String>) -> Result<Key, ValidationError> {
+    maybe_name
+        .and_then(|name| named_keys.get(name))
+        .copied()
+        .ok_or(ValidationError::PathCold)
+}
+
 pub fn validate_balance_proof(
     hash: &Blake2bHash,
     balance_proof: &TrieMerkleProof<Key, StoredValue>,
@@ -1150,3 +1150,128 @@ fn query_with_large_depth_with_urefs_should_fail() {
         result
     );
 }
+
+#[test]
+fn query_nested_path_via_uref_should_validate() {
+    // create a leaf value stored under a URef
+    let leaf_value = StoredValue::CLValue(CLValue::from_t(42_u64).unwrap());
+    let leaf_key = Key::URef(URef::new([21; 32], AccessRights::READ));
+
+    // create a contract holding the leaf under the name "c"
+    let inner_contract_key = Key::Hash([22; 32]);
+    let inner_contract = StoredValue::Contract(Contract::new(
+        [2; 32].into(),
+        [3; 32].into(),
+        iter::once(("c".to_string(), leaf_key)).collect(),
+        EntryPoints::default(),
+        ProtocolVersion::V1_0_0,
+    ));
+
+    // create a URef-addressed value referring to the inner contract
+    let pointer_key = Key::URef(URef::new([23; 32], AccessRights::READ));
+    let pointer_value = StoredValue::CLValue(CLValue::from_t(inner_contract_key).unwrap());
+
+    // create a contract holding the pointer under the name "b"
+    let outer_contract_key = Key::Hash([24; 32]);
+    let outer_contract = StoredValue::Contract(Contract::new(
+        [4; 32].into(),
+        [5; 32].into(),
+        iter::once(("b".to_string(), pointer_key)).collect(),
+        EntryPoints::default(),
+        ProtocolVersion::V1_0_0,
+    ));
+
+    // create an account holding the outer contract under the name "a"
+    let account_hash = AccountHash::new([25; 32]);
+    let account_key = Key::Account(account_hash);
+    let account = StoredValue::Account(Account::create(
+        account_hash,
+        iter::once(("a".to_string(), outer_contract_key)).collect(),
+        URef::new([26; 32], AccessRights::READ_ADD_WRITE),
+    ));
+
+    let correlation_id = CorrelationId::new();
+    let (global_state, root_hash) = InMemoryGlobalState::from_pairs(
+        correlation_id,
+        &[
+            (leaf_key, leaf_value.clone()),
+            (inner_contract_key, inner_contract),
+            (pointer_key, pointer_value),
+            (outer_contract_key, outer_contract),
+            (account_key, account),
+        ],
+    )
+    .unwrap();
+    let view = global_state.checkout(root_hash).unwrap().unwrap();
+    let tracking_copy = TrackingCopy::new(view);
+    let query = |path: &[&str]| {
+        let path: Vec<String> = path.iter().map(ToString::to_string).collect();
+        tracking_copy
+            .query(correlation_id, &EngineConfig::default(), account_key, &path)
+            .expect("should query")
+    };
+    let path = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+
+    // Successful traversal, following the named keys and the URef-addressed key.
+    let proofs = match query(&["a", "b", "c"]) {
+        TrackingCopyQueryResult::Success { value, proofs } => {
+            assert_eq!(value, leaf_value);
+            proofs
+        }
+        result => panic!("query was not successful: {:?}", result),
+    };
+    assert_eq!(proofs.len(), 5);
+    crate::core::validate_query_proof(&root_hash, &proofs, &account_key, &path, &leaf_value)
+        .expect("should validate");
+
+    // Broken path: the name doesn't exist in the outer contract.
+    match query(&["a", "x", "c"]) {
+        TrackingCopyQueryResult::ValueNotFound(msg) => {
+            assert!(msg.contains("'x'"), "{}", msg);
+            assert!(msg.contains("Contract"), "{}", msg);
+        }
+        result => panic!("query should have failed: {:?}", result),
+    }
+
+    // Broken path: the leaf value can't be traversed.
+    match query(&["a", "b", "c", "d"]) {
+        TrackingCopyQueryResult::ValueNotFound(msg) => {
+            assert!(msg.contains("'d'"), "{}", msg);
+            assert!(msg.contains("U64"), "{}", msg);
+        }
+        result => panic!("query should have failed: {:?}", result),
+    }
+
+    // Proof has been tampered with by replacing the leaf value.
+    let mut tampered_proofs = proofs.clone();
+    let tampered_value = StoredValue::CLValue(CLValue::from_t(43_u64).unwrap());
+    tampered_proofs[4] = TrieMerkleProof::new(
+        *proofs[4].key(),
+        tampered_value.clone(),
+        proofs[4].proof_steps().clone(),
+    );
+    assert_eq!(
+        crate::core::validate_query_proof(
+            &root_hash,
+            &tampered_proofs,
+            &account_key,
+            &path,
+            &tampered_value
+        ),
+        Err(ValidationError::InvalidProofHash)
+    );
+
+    // Proof has been tampered with by skipping the URef-addressed key.
+    let mut tampered_proofs = proofs;
+    tampered_proofs.remove(2);
+    assert_eq!(
+        crate::core::validate_query_proof(
+            &root_hash,
+            &tampered_proofs,
+            &account_key,
+            &path,
+            &leaf_value
+        ),
+        Err(ValidationError::UnexpectedKey)
+    );
+}
@@ -4,9 +4,14 @@ mod settings;
 mod structured_message;
 mod terminal_logger;
 
-use std::collections::BTreeMap;
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, PoisonError, RwLock},
+};
 
 use log::{self, Level, LevelFilter, Log, SetLoggerError};
+use once_cell::sync::OnceCell;
+use thiserror::Error;
 
 pub use self::terminal_logger::TerminalLogger;
 pub use settings::{Settings, Style};
@@ -19,15 +24,58 @@ pub(crate) const MESSAGE_TEMPLATE_KEY: &str = "message_template";
 pub(crate) const DEFAULT_MESSAGE_TEMPLATE: &str = "{message}";
 pub(crate) const DEFAULT_MESSAGE_KEY: &str = "message";
 
+/// The settings shared with the `TerminalLogger` installed via `initialize`, through which
+/// `reconfigure` swaps them.
+static INSTALLED_SETTINGS: OnceCell<Arc<RwLock<Settings>>> = OnceCell::new();
+
+/// Error returned when reconfiguring a logger which has not been initialized.
+#[derive(Debug, Error)]
+#[error("the global logger has not been initialized via `logging::initialize`")]
+pub struct NotInitializedError;
+
 /// Initializes the global logger using the given settings.
 ///
 /// The logger will write all log messages from crates prefixed with "casper_" to stdout, and
 /// can also log internal metrics generated by the Execution Engine.
 ///
+/// The logger is installed even if `settings` disable all logging, so that logging can be enabled
+/// later via [`reconfigure()`].
+///
 /// Returns an error if the global logger has already been set in this process.
 pub fn initialize(settings: Settings) -> Result<(), SetLoggerError> {
-    let logger = Box::new(TerminalLogger::new(&settings));
-    initialize_with_logger(logger, settings)
+    initialize_with_terminal_logger(settings, |logger| Box::new(logger))
+}
+
+/// Swaps the settings used by the logger installed via [`initialize()`], e.g. to raise the log
+/// level of a running process while diagnosing an issue and to lower it again afterwards.
+///
+/// Returns an error if the global logger was not installed via `initialize`.
+pub fn reconfigure(settings: Settings) -> Result<(), NotInitializedError> {
+    let installed_settings = INSTALLED_SETTINGS.get().ok_or(NotInitializedError)?;
+    *installed_settings
+        .write()
+        .unwrap_or_else(PoisonError::into_inner) = settings;
+    log::set_max_level(settings.max_level());
+    Ok(())
+}
+
+/// Installs the logger returned by `wrap` as the global logger, keeping the settings of the
+/// `TerminalLogger` passed to it reconfigurable via [`reconfigure()`].
+///
+/// This is public but undocumented to allow functional testing of reconfiguration, e.g. by
+/// wrapping the `TerminalLogger` in a logger which captures its output.
+#[doc(hidden)]
+pub fn initialize_with_terminal_logger<F>(settings: Settings, wrap: F) -> Result<(), SetLoggerError>
+where
+    F: FnOnce(TerminalLogger) -> Box<dyn Log>,
+{
+    let terminal_logger = TerminalLogger::new(&settings);
+    let shared_settings = terminal_logger.shared_settings();
+    log::set_boxed_logger(wrap(terminal_logger))?;
+    log::set_max_level(settings.max_level());
+    // Can't already be set, as setting the global logger can only succeed once per process.
+    let _ = INSTALLED_SETTINGS.set(shared_settings);
+    Ok(())
 }
 
 /// This and the `TerminalLogger` are public but undocumented to allow functional testing of this
@@ -67,3 +115,71 @@ pub fn log_details(
 pub fn log_host_function_metrics(_host_function: &str, _properties: BTreeMap<&str, String>) {
     // TODO: Metrics story https://casperlabs.atlassian.net/browse/NDRS-120
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use log::{Metadata, Record};
+
+    use super::*;
+
+    const MARKER: &str = "reconfigure-test-record";
+
+    /// Captures the lines prepared by the wrapped `TerminalLogger` rather than printing them.
+    struct CapturingLogger {
+        terminal_logger: TerminalLogger,
+        log_lines: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl Log for CapturingLogger {
+        fn enabled(&self, metadata: &Metadata) -> bool {
+            self.terminal_logger.enabled(metadata)
+        }
+
+        fn log(&self, record: &Record) {
+            if let Some(log_line) = self.terminal_logger.prepare_log_line(record) {
+                self.log_lines.lock().unwrap().push(log_line);
+            }
+        }
+
+        fn flush(&self) {}
+    }
+
+    /// Returns the number of captured lines emitted by this test, ignoring any logged concurrently
+    /// by other tests.
+    fn captured_count(log_lines: &Mutex<Vec<String>>) -> usize {
+        log_lines
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|log_line| log_line.contains(MARKER))
+            .count()
+    }
+
+    #[test]
+    fn should_reconfigure_max_level() {
+        let log_lines = Arc::new(Mutex::new(vec![]));
+        let captured = Arc::clone(&log_lines);
+        let info_settings = Settings::new(LevelFilter::Info).with_style(Style::HumanReadable);
+        initialize_with_terminal_logger(info_settings, move |terminal_logger| {
+            Box::new(CapturingLogger {
+                terminal_logger,
+                log_lines: captured,
+            })
+        })
+        .expect("should initialize logger");
+
+        log::debug!("{} while at info", MARKER);
+        assert_eq!(captured_count(&log_lines), 0);
+
+        let debug_settings = Settings::new(LevelFilter::Debug).with_style(Style::HumanReadable);
+        reconfigure(debug_settings).expect("should reconfigure logger");
+        log::debug!("{} while at debug", MARKER);
+        assert_eq!(captured_count(&log_lines), 1);
+
+        reconfigure(info_settings).expect("should reconfigure logger");
+        log::debug!("{} while back at info", MARKER);
+        assert_eq!(captured_count(&log_lines), 1);
+    }
+}
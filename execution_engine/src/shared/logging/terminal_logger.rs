@@ -1,6 +1,9 @@
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc, PoisonError, RwLock,
+};
 
-use log::{Level, LevelFilter, Log, Metadata, Record};
+use log::{Level, Log, Metadata, Record};
 
 use crate::shared::logging::{
     structured_message::{MessageId, MessageProperties, StructuredMessage, TimestampRfc3999},
@@ -10,31 +13,40 @@ use crate::shared::logging::{
 #[doc(hidden)]
 /// Logs messages from targets with prefix "casper_" or "METRIC" to stdout.
 pub struct TerminalLogger {
-    max_level: LevelFilter,
-    metrics_enabled: bool,
-    style: Style,
+    /// The settings in use, shared so that they can be swapped by `logging::reconfigure`.
+    settings: Arc<RwLock<Settings>>,
     next_message_id: AtomicUsize,
 }
 
 impl TerminalLogger {
     pub fn new(settings: &Settings) -> Self {
         TerminalLogger {
-            max_level: settings.max_level(),
-            metrics_enabled: settings.enable_metrics(),
-            style: settings.style(),
+            settings: Arc::new(RwLock::new(*settings)),
             next_message_id: AtomicUsize::new(0),
         }
     }
 
+    /// Returns the handle through which this logger's settings can be swapped.
+    pub(crate) fn shared_settings(&self) -> Arc<RwLock<Settings>> {
+        Arc::clone(&self.settings)
+    }
+
+    /// Returns a copy of the current settings, so that a single log line is never prepared using
+    /// a mix of old and new settings.
+    fn current_settings(&self) -> Settings {
+        *self.settings.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
     pub fn prepare_log_line(&self, record: &Record) -> Option<String> {
-        if !self.enabled(record.metadata()) {
+        let settings = self.current_settings();
+        if !is_enabled(&settings, record.metadata()) {
             return None;
         }
 
         let mut properties = MessageProperties::default();
         let _ = record.key_values().visit(&mut properties);
 
-        let log_line = match self.style {
+        let log_line = match settings.style() {
             Style::Structured => {
                 if record.key_values().count() == 0 {
                     properties.insert(
@@ -78,12 +90,7 @@ impl TerminalLogger {
 
 impl Log for TerminalLogger {
     fn enabled(&self, metadata: &Metadata) -> bool {
-        // If the target starts "casper_" it's either come from a log macro in one of our
-        // crates, or via `logging::log_details`.  In this case, check the level.
-        (metadata.target().starts_with(CASPER_METADATA_TARGET)
-            && metadata.level() <= self.max_level)
-            // Otherwise, check if the target is "METRIC" and if we have metric logging enabled.
-            || (self.metrics_enabled && metadata.target() == METRIC_METADATA_TARGET)
+        is_enabled(&self.current_settings(), metadata)
     }
 
     fn log(&self, record: &Record) {
@@ -95,6 +102,15 @@ impl Log for TerminalLogger {
     fn flush(&self) {}
 }
 
+fn is_enabled(settings: &Settings, metadata: &Metadata) -> bool {
+    // If the target starts "casper_" it's either come from a log macro in one of our
+    // crates, or via `logging::log_details`.  In this case, check the level.
+    (metadata.target().starts_with(CASPER_METADATA_TARGET)
+        && metadata.level() <= settings.max_level())
+        // Otherwise, check if the target is "METRIC" and if we have metric logging enabled.
+        || (settings.enable_metrics() && metadata.target() == METRIC_METADATA_TARGET)
+}
+
 fn level_to_str<'a>(record: &'a Record) -> &'a str {
     if record.target() == METRIC_METADATA_TARGET {
         return "Metric";
@@ -29,6 +29,11 @@ impl Motes {
         self.0
     }
 
+    /// Converts the given amount of gas to motes at the given gas price, i.e. returns
+    /// `gas * conv_rate`, or `None` if the multiplication overflows.
+    ///
+    /// This is the conversion used when charging for execution, so should also be used wherever
+    /// the cost of a deploy in motes is reported.
     pub fn from_gas(gas: Gas, conv_rate: u64) -> Option<Self> {
         gas.value()
             .checked_mul(U512::from(conv_rate))
@@ -228,4 +233,29 @@ mod tests {
         let maybe = Motes::from_gas(gas, conv_rate);
         assert!(maybe.is_none(), "should be none due to overflow");
     }
+
+    #[test]
+    fn should_convert_from_gas_at_boundary_gas_prices() {
+        let gas = Gas::new(U512::from(u64::MAX));
+
+        let motes = Motes::from_gas(gas, 0).expect("should have value");
+        assert_eq!(motes, Motes::default(), "should be zero at zero gas price");
+
+        let motes = Motes::from_gas(gas, u64::MAX).expect("should have value");
+        let expected_motes = Motes::new(U512::from(u64::MAX) * U512::from(u64::MAX));
+        assert_eq!(motes, expected_motes, "should be exact at max gas price");
+
+        let motes = Motes::from_gas(Gas::new(U512::MAX), 1).expect("should have value");
+        assert_eq!(motes.value(), U512::MAX, "should be exact at max gas");
+    }
+
+    #[test]
+    fn should_not_convert_from_gas_just_above_overflow_boundary() {
+        let max_gas = U512::MAX / U512::from(u64::MAX);
+        let motes = Motes::from_gas(Gas::new(max_gas), u64::MAX).expect("should have value");
+        assert_eq!(motes.value(), max_gas * U512::from(u64::MAX));
+
+        let maybe = Motes::from_gas(Gas::new(max_gas + 1), u64::MAX);
+        assert!(maybe.is_none(), "should be none due to overflow");
+    }
 }
@@ -7,6 +7,7 @@ use blake2::{
 };
 use serde::{Deserialize, Serialize};
 
+use casper_deploy_utils::hash::Digest;
 use casper_types::bytesrepr::{self, FromBytes, ToBytes};
 
 /// Represents a 32-byte BLAKE2b hash digest
@@ -97,6 +98,18 @@ impl From<Blake2bHash> for [u8; Blake2bHash::LENGTH] {
     }
 }
 
+impl From<Digest> for Blake2bHash {
+    fn from(digest: Digest) -> Self {
+        Blake2bHash(digest.to_array())
+    }
+}
+
+impl From<Blake2bHash> for Digest {
+    fn from(hash: Blake2bHash) -> Self {
+        Digest::from(hash.0)
+    }
+}
+
 impl ToBytes for Blake2bHash {
     #[inline(always)]
     fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
@@ -108,7 +108,7 @@ impl StoredValue {
         match self {
             StoredValue::CLValue(cl_value) => format!("{:?}", cl_value.cl_type()),
             StoredValue::Account(_) => "Account".to_string(),
-            StoredValue::ContractWasm(_) => "Contract".to_string(),
+            StoredValue::ContractWasm(_) => "ContractWasm".to_string(),
             StoredValue::Contract(_) => "Contract".to_string(),
             StoredValue::ContractPackage(_) => "ContractPackage".to_string(),
             StoredValue::Transfer(_) => "Transfer".to_string(),
@@ -335,7 +335,7 @@ impl FromBytes for StoredValue {
                     (StoredValue::Withdraw(unbonding_purses), remainder)
                 })
             }
-            _ => Err(bytesrepr::Error::Formatting),
+            tag => Err(bytesrepr::Error::invalid_tag::<Self>(tag)),
         }
     }
 }
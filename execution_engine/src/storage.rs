@@ -3,6 +3,7 @@
 // modules
 pub mod error;
 pub mod global_state;
+pub mod lmdb_stats;
 pub mod protocol_data;
 pub mod protocol_data_store;
 pub mod store;
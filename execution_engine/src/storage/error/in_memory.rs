@@ -4,6 +4,8 @@ use thiserror::Error;
 
 use casper_types::bytesrepr;
 
+use crate::storage::error::CorruptTrieError;
+
 #[derive(Debug, Error, PartialEq, Eq)]
 pub enum Error {
     #[error("{0}")]
@@ -11,6 +13,9 @@ pub enum Error {
 
     #[error("Another thread panicked while holding a lock")]
     Poison,
+
+    #[error(transparent)]
+    CorruptTrie(#[from] CorruptTrieError),
 }
 
 impl From<bytesrepr::Error> for Error {
@@ -5,7 +5,7 @@ use thiserror::Error;
 
 use casper_types::bytesrepr;
 
-use crate::storage::error::in_memory;
+use crate::storage::error::{in_memory, CorruptTrieError};
 
 #[derive(Debug, Clone, Error, PartialEq, Eq)]
 pub enum Error {
@@ -17,6 +17,9 @@ pub enum Error {
 
     #[error("Another thread panicked while holding a lock")]
     Poison,
+
+    #[error(transparent)]
+    CorruptTrie(#[from] CorruptTrieError),
 }
 
 impl wasmi::HostError for Error {}
@@ -38,6 +41,7 @@ impl From<in_memory::Error> for Error {
         match error {
             in_memory::Error::BytesRepr(error) => Error::BytesRepr(error),
             in_memory::Error::Poison => Error::Poison,
+            in_memory::Error::CorruptTrie(error) => Error::CorruptTrie(error),
         }
     }
 }
@@ -1,4 +1,20 @@
 pub mod in_memory;
 pub mod lmdb;
 
+use crate::shared::newtypes::Blake2bHash;
+
 pub use self::lmdb::Error;
+
+/// An error encountered while iterating over a trie which is not well-formed.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum CorruptTrieError {
+    /// A trie referenced by its parent is missing from the store.
+    #[error("trie {0} is missing from the store")]
+    MissingTrie(Blake2bHash),
+    /// A leaf's key doesn't match the path leading to it.
+    #[error("trie leaf key doesn't match its path")]
+    MisplacedLeaf,
+    /// An extension points to a trie which is not a node.
+    #[error("trie extension points to {0} which is not a node")]
+    InvalidExtension(Blake2bHash),
+}
@@ -10,7 +10,7 @@ use casper_types::{Key, ProtocolVersion};
 
 use crate::storage::{
     error::{self, in_memory},
-    global_state::{commit, CommitResult, StateProvider, StateReader},
+    global_state::{commit, key_values_page, CommitResult, StateProvider, StateReader},
     protocol_data::ProtocolData,
     protocol_data_store::in_memory::InMemoryProtocolDataStore,
     store::Store,
@@ -298,6 +298,28 @@ impl StateProvider for InMemoryGlobalState {
         txn.commit()?;
         Ok(missing_descendants)
     }
+
+    fn key_values_with_prefix(
+        &self,
+        correlation_id: CorrelationId,
+        state_hash: Blake2bHash,
+        prefix: &[u8],
+        start_after: Option<&Key>,
+        limit: usize,
+    ) -> Result<Option<Vec<(Key, StoredValue)>>, Self::Error> {
+        let txn = self.environment.create_read_txn()?;
+        let ret = key_values_page::<InMemoryReadTransaction, InMemoryTrieStore, Self::Error>(
+            correlation_id,
+            &txn,
+            self.trie_store.deref(),
+            &state_hash,
+            prefix,
+            start_after,
+            limit,
+        )?;
+        txn.commit()?;
+        Ok(ret)
+    }
 }
 
 #[cfg(test)]
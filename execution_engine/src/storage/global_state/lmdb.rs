@@ -10,7 +10,7 @@ use casper_types::{Key, ProtocolVersion};
 
 use crate::storage::{
     error,
-    global_state::{commit, CommitResult, StateProvider, StateReader},
+    global_state::{commit, key_values_page, CommitResult, StateProvider, StateReader},
     protocol_data::ProtocolData,
     protocol_data_store::lmdb::LmdbProtocolDataStore,
     store::Store,
@@ -256,6 +256,28 @@ impl StateProvider for LmdbGlobalState {
         txn.commit()?;
         Ok(missing_descendants)
     }
+
+    fn key_values_with_prefix(
+        &self,
+        correlation_id: CorrelationId,
+        state_hash: Blake2bHash,
+        prefix: &[u8],
+        start_after: Option<&Key>,
+        limit: usize,
+    ) -> Result<Option<Vec<(Key, StoredValue)>>, Self::Error> {
+        let txn = self.environment.create_read_txn()?;
+        let ret = key_values_page::<lmdb::RoTransaction, LmdbTrieStore, Self::Error>(
+            correlation_id,
+            &txn,
+            self.trie_store.deref(),
+            &state_hash,
+            prefix,
+            start_after,
+            limit,
+        )?;
+        txn.commit()?;
+        Ok(ret)
+    }
 }
 
 #[cfg(test)]
@@ -264,7 +286,7 @@ mod tests {
     use tempfile::tempdir;
 
     use crate::shared::newtypes::Blake2bHash;
-    use casper_types::{account::AccountHash, CLValue};
+    use casper_types::{account::AccountHash, CLValue, KeyTag};
 
     use super::*;
     use crate::storage::{
@@ -363,6 +385,57 @@ mod tests {
         }
     }
 
+    #[test]
+    fn key_values_with_prefix_returns_pages_in_key_order() {
+        let correlation_id = CorrelationId::new();
+        let (state, root_hash) = create_test_state();
+        let prefix = [KeyTag::Account as u8];
+        let expected = create_test_pairs()
+            .iter()
+            .cloned()
+            .map(|TestPair { key, value }| (key, value))
+            .collect::<Vec<_>>();
+
+        let first_page = state
+            .key_values_with_prefix(correlation_id, root_hash, &prefix, None, 1)
+            .unwrap()
+            .unwrap();
+        assert_eq!(first_page, expected[..1]);
+
+        let start_after = Some(&first_page[0].0);
+        let second_page = state
+            .key_values_with_prefix(correlation_id, root_hash, &prefix, start_after, 1)
+            .unwrap()
+            .unwrap();
+        assert_eq!(second_page, expected[1..]);
+
+        let start_after = Some(&second_page[0].0);
+        let last_page = state
+            .key_values_with_prefix(correlation_id, root_hash, &prefix, start_after, 1)
+            .unwrap()
+            .unwrap();
+        assert!(last_page.is_empty());
+
+        let all = state
+            .key_values_with_prefix(correlation_id, root_hash, &[], None, usize::MAX)
+            .unwrap()
+            .unwrap();
+        assert_eq!(all, expected);
+
+        let other_prefix = [KeyTag::Hash as u8];
+        let none_matching = state
+            .key_values_with_prefix(correlation_id, root_hash, &other_prefix, None, 10)
+            .unwrap()
+            .unwrap();
+        assert!(none_matching.is_empty());
+
+        let fake_hash = Blake2bHash::new(&[1u8; 32]);
+        let result = state
+            .key_values_with_prefix(correlation_id, fake_hash, &prefix, None, 10)
+            .unwrap();
+        assert!(result.is_none());
+    }
+
     #[test]
     fn checkout_fails_if_unknown_hash_is_given() {
         let (state, _) = create_test_state();
@@ -10,14 +10,18 @@ use crate::shared::{
     transform::{self, Transform},
     TypeMismatch,
 };
-use casper_types::{bytesrepr, Key, ProtocolVersion};
+use casper_types::{
+    bytesrepr::{self, ToBytes},
+    Key, ProtocolVersion,
+};
 
 use crate::storage::{
+    error::CorruptTrieError,
     protocol_data::ProtocolData,
-    transaction_source::{Transaction, TransactionSource},
+    transaction_source::{Readable, Transaction, TransactionSource},
     trie::{merkle_proof::TrieMerkleProof, Trie},
     trie_store::{
-        operations::{read, write, ReadResult, WriteResult},
+        operations::{self, read, write, ReadResult, WriteResult},
         TrieStore,
     },
 };
@@ -130,6 +134,20 @@ pub trait StateProvider {
         correlation_id: CorrelationId,
         trie_keys: Vec<Blake2bHash>,
     ) -> Result<Vec<Blake2bHash>, Self::Error>;
+
+    /// Returns at most `limit` key-value pairs under `state_hash` whose serialized keys start with
+    /// `prefix`, in ascending order of serialized key, or `None` if `state_hash` is not found.
+    ///
+    /// If `start_after` is given, only pairs whose keys sort after it are returned, allowing a
+    /// large range to be read in pages.
+    fn key_values_with_prefix(
+        &self,
+        correlation_id: CorrelationId,
+        state_hash: Blake2bHash,
+        prefix: &[u8],
+        start_after: Option<&Key>,
+        limit: usize,
+    ) -> Result<Option<Vec<(Key, StoredValue)>>, Self::Error>;
 }
 
 pub fn commit<'a, R, S, H, E>(
@@ -186,3 +204,51 @@ where
 
     Ok(CommitResult::Success { state_root })
 }
+
+/// Reads a page of key-value pairs from the trie under `state_hash`.
+///
+/// See [`StateProvider::key_values_with_prefix`] for details.  The trie is walked in order from
+/// its start, so pages further into a range take longer to find.
+pub fn key_values_page<T, S, E>(
+    correlation_id: CorrelationId,
+    txn: &T,
+    store: &S,
+    state_hash: &Blake2bHash,
+    prefix: &[u8],
+    start_after: Option<&Key>,
+    limit: usize,
+) -> Result<Option<Vec<(Key, StoredValue)>>, E>
+where
+    T: Readable<Handle = S::Handle>,
+    S: TrieStore<Key, StoredValue>,
+    S::Error: From<T::Error> + From<CorruptTrieError>,
+    E: From<S::Error> + From<bytesrepr::Error>,
+{
+    let maybe_root: Option<Trie<Key, StoredValue>> = store.get(txn, state_hash)?;
+    if maybe_root.is_none() {
+        return Ok(None);
+    }
+
+    let start_after = start_after.map(ToBytes::to_bytes).transpose()?;
+    let mut key_values = operations::key_values_with_prefix::<Key, StoredValue, _, _>(
+        correlation_id,
+        txn,
+        store,
+        state_hash,
+        prefix,
+    );
+    let mut ret = Vec::new();
+    while ret.len() < limit {
+        let (key, value) = match key_values.next() {
+            Some(result) => result?,
+            None => break,
+        };
+        if let Some(start_after) = &start_after {
+            if key.to_bytes()? <= *start_after {
+                continue;
+            }
+        }
+        ret.push((key, value));
+    }
+    Ok(Some(ret))
+}
@@ -0,0 +1,227 @@
+//! Statistics describing how much of an LMDB environment's memory map is in use.
+//!
+//! The safe `lmdb` bindings only expose environment-wide statistics, so the map size, per-database
+//! entry counts and free list are read via the raw `lmdb-sys` API.
+
+use std::{collections::BTreeMap, mem, ptr, slice};
+
+use libc::size_t;
+use lmdb::{Database, Environment, Transaction};
+use lmdb_sys as ffi;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// The handle of LMDB's internal database tracking pages which are free for reuse.
+const FREE_DBI: ffi::MDB_dbi = 0;
+
+/// Statistics of an LMDB environment and of the named databases within it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct LmdbStats {
+    /// The size of the memory map in bytes, i.e. the maximum size the environment can grow to.
+    pub map_size: u64,
+    /// The size of a database page in bytes.
+    pub page_size: u32,
+    /// The number of the highest page used so far.
+    pub last_used_page: u64,
+    /// The number of pages below `last_used_page` which are free to be reused.
+    pub free_pages: u64,
+    /// The number of entries in each of the named databases.
+    pub entries: BTreeMap<String, u64>,
+}
+
+impl LmdbStats {
+    /// Collects the statistics of `env` and of the given named `databases` within it.
+    ///
+    /// All values are read in a single read-only transaction, so they are consistent with each
+    /// other.
+    pub fn collect(env: &Environment, databases: &[(&str, Database)]) -> Result<Self, lmdb::Error> {
+        let page_size = env.stat()?.page_size();
+        let txn = env.begin_ro_txn()?;
+
+        let mut info: ffi::MDB_envinfo = unsafe { mem::zeroed() };
+        lmdb_result(unsafe { ffi::mdb_env_info(env.env(), &mut info) })?;
+
+        let mut entries = BTreeMap::new();
+        for (name, db) in databases {
+            let mut stat: ffi::MDB_stat = unsafe { mem::zeroed() };
+            lmdb_result(unsafe { ffi::mdb_stat(txn.txn(), db.dbi(), &mut stat) })?;
+            let _ = entries.insert(name.to_string(), stat.ms_entries as u64);
+        }
+
+        let free_pages = count_free_pages(&txn)?;
+        txn.commit()?;
+
+        Ok(LmdbStats {
+            map_size: info.me_mapsize as u64,
+            page_size,
+            last_used_page: info.me_last_pgno as u64,
+            free_pages,
+            entries,
+        })
+    }
+
+    /// Returns the number of bytes of the memory map holding live data.
+    ///
+    /// Pages on the free list are excluded, as LMDB reuses them before growing the file.
+    pub fn used_bytes(&self) -> u64 {
+        // Page numbers start at zero, the first two pages holding the environment's metadata.
+        let used_pages = self
+            .last_used_page
+            .saturating_add(1)
+            .saturating_sub(self.free_pages);
+        pages_to_bytes(used_pages, self.page_size)
+    }
+
+    /// Returns the percentage of the memory map holding live data.
+    pub fn utilization_percent(&self) -> f64 {
+        if self.map_size == 0 {
+            return 0.0;
+        }
+        self.used_bytes() as f64 * 100.0 / self.map_size as f64
+    }
+}
+
+/// Converts a number of LMDB pages of `page_size` bytes each into a number of bytes.
+pub fn pages_to_bytes(pages: u64, page_size: u32) -> u64 {
+    pages.saturating_mul(u64::from(page_size))
+}
+
+/// Sums the lengths of all page lists recorded in the free list database.
+///
+/// Each value in the free list database is an array of page numbers, prefixed by its length.
+fn count_free_pages<T: Transaction>(txn: &T) -> Result<u64, lmdb::Error> {
+    let mut cursor: *mut ffi::MDB_cursor = ptr::null_mut();
+    lmdb_result(unsafe { ffi::mdb_cursor_open(txn.txn(), FREE_DBI, &mut cursor) })?;
+
+    let mut free_pages = 0u64;
+    let result = loop {
+        let mut key = ffi::MDB_val {
+            mv_size: 0,
+            mv_data: ptr::null_mut(),
+        };
+        let mut data = ffi::MDB_val {
+            mv_size: 0,
+            mv_data: ptr::null_mut(),
+        };
+        match unsafe { ffi::mdb_cursor_get(cursor, &mut key, &mut data, ffi::MDB_NEXT) } {
+            ffi::MDB_SUCCESS => {
+                if data.mv_size < mem::size_of::<size_t>() {
+                    continue;
+                }
+                // The value isn't guaranteed to be aligned, so the length is copied out bytewise.
+                let length_bytes = unsafe {
+                    slice::from_raw_parts(data.mv_data as *const u8, mem::size_of::<size_t>())
+                };
+                let mut length = [0u8; mem::size_of::<size_t>()];
+                length.copy_from_slice(length_bytes);
+                free_pages = free_pages.saturating_add(size_t::from_ne_bytes(length) as u64);
+            }
+            ffi::MDB_NOTFOUND => break Ok(free_pages),
+            err_code => break Err(lmdb::Error::from_err_code(err_code)),
+        }
+    };
+
+    unsafe { ffi::mdb_cursor_close(cursor) };
+    result
+}
+
+fn lmdb_result(err_code: libc::c_int) -> Result<(), lmdb::Error> {
+    if err_code == ffi::MDB_SUCCESS {
+        Ok(())
+    } else {
+        Err(lmdb::Error::from_err_code(err_code))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lmdb::{DatabaseFlags, WriteFlags};
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::storage::{
+        transaction_source::lmdb::LmdbEnvironment, DEFAULT_TEST_MAX_DB_SIZE,
+        DEFAULT_TEST_MAX_READERS,
+    };
+
+    const VALUE_SIZE: usize = 4096;
+    const VALUE_COUNT: u32 = 1024;
+
+    #[test]
+    fn pages_to_bytes_should_saturate() {
+        assert_eq!(pages_to_bytes(3, 4096), 12_288);
+        assert_eq!(pages_to_bytes(u64::MAX, 4096), u64::MAX);
+    }
+
+    #[test]
+    fn utilization_should_rise_with_written_data() {
+        let tmp_dir = tempdir().unwrap();
+        let environment = LmdbEnvironment::new(
+            &tmp_dir.path().to_path_buf(),
+            DEFAULT_TEST_MAX_DB_SIZE,
+            DEFAULT_TEST_MAX_READERS,
+        )
+        .unwrap();
+        let env = environment.env();
+        let db = env.create_db(Some("test"), DatabaseFlags::empty()).unwrap();
+
+        let before = LmdbStats::collect(env, &[("test", db)]).unwrap();
+        assert_eq!(before.map_size, DEFAULT_TEST_MAX_DB_SIZE as u64);
+        assert_eq!(before.entries["test"], 0);
+
+        let mut txn = env.begin_rw_txn().unwrap();
+        for index in 0..VALUE_COUNT {
+            txn.put(
+                db,
+                &index.to_be_bytes(),
+                &[1u8; VALUE_SIZE],
+                WriteFlags::empty(),
+            )
+            .unwrap();
+        }
+        txn.commit().unwrap();
+
+        let after = LmdbStats::collect(env, &[("test", db)]).unwrap();
+        assert_eq!(after.entries["test"], u64::from(VALUE_COUNT));
+        let written_bytes = VALUE_SIZE as u64 * u64::from(VALUE_COUNT);
+        assert!(after.used_bytes() >= before.used_bytes() + written_bytes);
+        assert!(after.used_bytes() <= after.map_size);
+        assert!(after.utilization_percent() > before.utilization_percent());
+    }
+
+    #[test]
+    fn freed_pages_should_not_count_as_used() {
+        let tmp_dir = tempdir().unwrap();
+        let environment = LmdbEnvironment::new(
+            &tmp_dir.path().to_path_buf(),
+            DEFAULT_TEST_MAX_DB_SIZE,
+            DEFAULT_TEST_MAX_READERS,
+        )
+        .unwrap();
+        let env = environment.env();
+        let db = env.create_db(Some("test"), DatabaseFlags::empty()).unwrap();
+
+        let mut txn = env.begin_rw_txn().unwrap();
+        for index in 0..VALUE_COUNT {
+            txn.put(
+                db,
+                &index.to_be_bytes(),
+                &[1u8; VALUE_SIZE],
+                WriteFlags::empty(),
+            )
+            .unwrap();
+        }
+        txn.commit().unwrap();
+        let filled = LmdbStats::collect(env, &[("test", db)]).unwrap();
+
+        let mut txn = env.begin_rw_txn().unwrap();
+        txn.clear_db(db).unwrap();
+        txn.commit().unwrap();
+        let cleared = LmdbStats::collect(env, &[("test", db)]).unwrap();
+
+        assert_eq!(cleared.entries["test"], 0);
+        assert!(cleared.free_pages > 0);
+        assert!(cleared.used_bytes() < filled.used_bytes());
+    }
+}
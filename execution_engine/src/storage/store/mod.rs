@@ -2,7 +2,7 @@ mod store_ext;
 #[cfg(test)]
 pub(crate) mod tests;
 
-use casper_types::bytesrepr::{self, FromBytes, ToBytes};
+use casper_types::bytesrepr::{self, FromBytes, FromBytesRef, ToBytes};
 
 pub use self::store_ext::StoreExt;
 use crate::storage::transaction_source::{Readable, Writable};
@@ -20,12 +20,24 @@ pub trait Store<K, V> {
         K: ToBytes,
         V: FromBytes,
         Self::Error: From<T::Error>,
+    {
+        self.get_ref(txn, key)
+    }
+
+    /// Returns the value under `key` deserialized as `R`, which may borrow from the underlying
+    /// storage for as long as the transaction is alive, rather than copying from it.
+    fn get_ref<'a, T, R>(&self, txn: &'a T, key: &K) -> Result<Option<R>, Self::Error>
+    where
+        T: Readable<Handle = Self::Handle>,
+        K: ToBytes,
+        R: FromBytesRef<'a>,
+        Self::Error: From<T::Error>,
     {
         let handle = self.handle();
-        match txn.read(handle, &key.to_bytes()?)? {
+        match txn.read_ref(handle, &key.to_bytes()?)? {
             None => Ok(None),
             Some(value_bytes) => {
-                let value = bytesrepr::deserialize(value_bytes.into())?;
+                let value = bytesrepr::deserialize_ref(value_bytes)?;
                 Ok(Some(value))
             }
         }
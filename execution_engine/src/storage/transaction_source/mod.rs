@@ -26,7 +26,13 @@ pub trait Transaction: Sized {
 /// A transaction with the capability to read from a given [`Handle`](Transaction::Handle).
 pub trait Readable: Transaction {
     /// Returns the value from the corresponding key from a given [`Transaction::Handle`].
-    fn read(&self, handle: Self::Handle, key: &[u8]) -> Result<Option<Bytes>, Self::Error>;
+    fn read(&self, handle: Self::Handle, key: &[u8]) -> Result<Option<Bytes>, Self::Error> {
+        Ok(self.read_ref(handle, key)?.map(Bytes::from))
+    }
+
+    /// Returns the value from the corresponding key from a given [`Transaction::Handle`] without
+    /// copying it, borrowed for as long as the transaction is alive.
+    fn read_ref(&self, handle: Self::Handle, key: &[u8]) -> Result<Option<&[u8]>, Self::Error>;
 }
 
 /// A transaction with the capability to write to a given [`Handle`](Transaction::Handle).
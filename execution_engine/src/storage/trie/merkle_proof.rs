@@ -1,6 +1,6 @@
 use std::collections::VecDeque;
 
-use casper_types::bytesrepr::{self, Bytes, FromBytes, ToBytes};
+use casper_types::bytesrepr::{self, Bytes, FromBytes, NotU8, ToBytes};
 
 use crate::{
     shared::newtypes::Blake2bHash,
@@ -94,11 +94,13 @@ impl FromBytes for TrieMerkleProofStep {
                 let (affix, rem): (_, &[u8]) = FromBytes::from_bytes(rem)?;
                 Ok((TrieMerkleProofStep::Extension { affix }, rem))
             }
-            _ => Err(bytesrepr::Error::Formatting),
+            tag => Err(bytesrepr::Error::invalid_tag::<Self>(tag)),
         }
     }
 }
 
+impl NotU8 for TrieMerkleProofStep {}
+
 /// A proof that a node with a specified `key` and `value` is present in the Merkle trie.
 /// Given a state hash `x`, one can validate a proof `p` by checking `x == p.compute_state_hash()`.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -230,6 +232,8 @@ where
     }
 }
 
+impl<K, V> NotU8 for TrieMerkleProof<K, V> {}
+
 #[cfg(test)]
 mod gens {
     use proptest::{collection::vec, prelude::*};
@@ -13,9 +13,11 @@ use serde::{
 };
 
 use crate::shared::newtypes::Blake2bHash;
-use casper_types::bytesrepr::{self, Bytes, FromBytes, ToBytes, U8_SERIALIZED_LENGTH};
+use casper_types::bytesrepr::{
+    self, Bytes, FromBytes, FromBytesRef, ToBytes, U8_SERIALIZED_LENGTH,
+};
 
-#[cfg(test)]
+#[cfg(any(feature = "gens", test))]
 pub mod gens;
 
 pub mod merkle_proof;
@@ -91,7 +93,7 @@ impl FromBytes for Pointer {
                 let (hash, rem) = Blake2bHash::from_bytes(rem)?;
                 Ok((Pointer::NodePointer(hash), rem))
             }
-            _ => Err(bytesrepr::Error::Formatting),
+            tag => Err(bytesrepr::Error::invalid_tag::<Self>(tag)),
         }
     }
 }
@@ -434,7 +436,46 @@ impl<K: FromBytes, V: FromBytes> FromBytes for Trie<K, V> {
                 let (pointer, rem) = Pointer::from_bytes(rem)?;
                 Ok((Trie::Extension { affix, pointer }, rem))
             }
-            _ => Err(bytesrepr::Error::Formatting),
+            tag => Err(bytesrepr::Error::invalid_tag::<Self>(tag)),
+        }
+    }
+}
+
+/// A view of a serialized [`Trie`] which borrows a leaf's value rather than deserializing it.
+///
+/// The leaf value is left in its serialized form, allowing callers which only need to walk the
+/// structure of the trie to avoid copying potentially large values out of the store.
+#[derive(Debug)]
+pub(crate) enum TrieRef<'a, K> {
+    Leaf { key: K, value: &'a [u8] },
+    Node { pointer_block: Box<PointerBlock> },
+    Extension { affix: Bytes, pointer: Pointer },
+}
+
+impl<'a, K: FromBytes> FromBytesRef<'a> for TrieRef<'a, K> {
+    fn from_bytes_ref(bytes: &'a [u8]) -> Result<(Self, &'a [u8]), bytesrepr::Error> {
+        let (tag, rem) = u8::from_bytes(bytes)?;
+        match tag {
+            0 => {
+                // The value is the final field, so it occupies all remaining bytes.
+                let (key, value) = K::from_bytes(rem)?;
+                Ok((TrieRef::Leaf { key, value }, &[]))
+            }
+            1 => {
+                let (pointer_block, rem) = PointerBlock::from_bytes(rem)?;
+                Ok((
+                    TrieRef::Node {
+                        pointer_block: Box::new(pointer_block),
+                    },
+                    rem,
+                ))
+            }
+            2 => {
+                let (affix, rem) = FromBytes::from_bytes(rem)?;
+                let (pointer, rem) = Pointer::from_bytes(rem)?;
+                Ok((TrieRef::Extension { affix, pointer }, rem))
+            }
+            tag => Err(bytesrepr::Error::invalid_tag::<Self>(tag)),
         }
     }
 }
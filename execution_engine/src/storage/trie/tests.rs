@@ -49,11 +49,15 @@ mod pointer_block {
 mod proptests {
     use proptest::prelude::*;
 
-    use casper_types::{bytesrepr, gens::key_arb, Key};
+    use casper_types::{
+        bytesrepr::{self, ToBytes},
+        gens::key_arb,
+        Key,
+    };
 
     use crate::{
         shared::stored_value::StoredValue,
-        storage::trie::{gens::*, PointerBlock, Trie},
+        storage::trie::{gens::*, PointerBlock, Trie, TrieRef},
     };
 
     proptest! {
@@ -77,6 +81,34 @@ mod proptests {
             bytesrepr::test_serialization_roundtrip(&trie);
         }
 
+        #[test]
+        fn borrowed_and_owned_trie_decoding_should_agree(trie in trie_arb()) {
+            let serialized = trie.to_bytes()?;
+            let borrowed: TrieRef<Key> = bytesrepr::deserialize_ref(&serialized)?;
+            match (borrowed, trie) {
+                (
+                    TrieRef::Leaf { key: borrowed_key, value },
+                    Trie::Leaf { key, value: owned_value },
+                ) => {
+                    prop_assert_eq!(borrowed_key, key);
+                    let value: StoredValue = bytesrepr::deserialize_ref(value)?;
+                    prop_assert_eq!(value, owned_value);
+                }
+                (
+                    TrieRef::Node { pointer_block: borrowed_pointer_block },
+                    Trie::Node { pointer_block },
+                ) => prop_assert_eq!(borrowed_pointer_block, pointer_block),
+                (
+                    TrieRef::Extension { affix: borrowed_affix, pointer: borrowed_pointer },
+                    Trie::Extension { affix, pointer },
+                ) => {
+                    prop_assert_eq!(borrowed_affix, affix);
+                    prop_assert_eq!(borrowed_pointer, pointer);
+                }
+                (borrowed, _) => prop_assert!(false, "mismatched variant {:?}", borrowed),
+            }
+        }
+
         #[test]
         fn roundtrip_key(key in key_arb()) {
             bytesrepr::test_serialization_roundtrip(&key);
@@ -15,10 +15,11 @@ use casper_types::bytesrepr::{self, FromBytes, ToBytes};
 use crate::{
     shared::newtypes::{Blake2bHash, CorrelationId},
     storage::{
+        error::CorruptTrieError,
         transaction_source::{Readable, Writable},
         trie::{
             merkle_proof::{TrieMerkleProof, TrieMerkleProofStep},
-            Parents, Pointer, PointerBlock, Trie, RADIX, USIZE_EXCEEDS_U8,
+            Parents, Pointer, PointerBlock, Trie, TrieRef, RADIX, USIZE_EXCEEDS_U8,
         },
         trie_store::TrieStore,
     },
@@ -252,31 +253,34 @@ where
         if !visited.insert(trie_key) {
             continue;
         }
-        let maybe_retrieved_trie: Option<Trie<K, V>> = store.get(txn, &trie_key)?;
-        if let Some(trie_value) = &maybe_retrieved_trie {
-            let hash_of_trie_value = {
-                let node_bytes = trie_value.to_bytes()?;
-                Blake2bHash::new(&node_bytes)
-            };
-            if trie_key != hash_of_trie_value {
-                warn!(
-                    "Trie key {:?} has corrupted value {:?} (hash of value is {:?}); \
-                     adding to list of missing nodes",
-                    trie_key, trie_value, hash_of_trie_value
-                );
-                missing_descendants.push(trie_key);
-                continue;
-            }
-        }
-        match maybe_retrieved_trie {
+        // Hash and walk the trie in place rather than copying it out of the store, as leaves can
+        // hold large values such as Wasm.
+        let maybe_trie_bytes = txn
+            .read_ref(store.handle(), &trie_key.to_bytes()?)
+            .map_err(S::Error::from)?;
+        let trie_bytes = match maybe_trie_bytes {
+            Some(trie_bytes) => trie_bytes,
             // If we can't find the trie_key; it is missing and we'll return it
             None => {
                 missing_descendants.push(trie_key);
+                continue;
             }
+        };
+        let hash_of_trie_value = Blake2bHash::new(trie_bytes);
+        if trie_key != hash_of_trie_value {
+            warn!(
+                "Trie key {:?} has corrupted value {:?} (hash of value is {:?}); \
+                 adding to list of missing nodes",
+                trie_key, trie_bytes, hash_of_trie_value
+            );
+            missing_descendants.push(trie_key);
+            continue;
+        }
+        match bytesrepr::deserialize_ref::<TrieRef<K>>(trie_bytes)? {
             // If we could retrieve the node and it is a leaf, the search can move on
-            Some(Trie::Leaf { .. }) => (),
+            TrieRef::Leaf { .. } => (),
             // If we hit a pointer block, queue up all of the nodes it points to
-            Some(Trie::Node { pointer_block }) => {
+            TrieRef::Node { pointer_block } => {
                 for (_, pointer) in pointer_block.to_indexed_pointers() {
                     match pointer {
                         Pointer::LeafPointer(descendant_leaf_trie_key) => {
@@ -289,7 +293,7 @@ where
                 }
             }
             // If we hit an extension block, add its pointer to the queue
-            Some(Trie::Extension { pointer, .. }) => trie_keys_to_visit.push(pointer.into_hash()),
+            TrieRef::Extension { pointer, .. } => trie_keys_to_visit.push(pointer.into_hash()),
         }
     }
     Ok(missing_descendants)
@@ -1071,7 +1075,11 @@ struct VisitedTrieNode<K, V> {
     path: Vec<u8>,
 }
 
-pub struct KeysIterator<'a, 'b, K, V, T, S: TrieStore<K, V>> {
+/// An iterator over the key-value pairs of a trie, in ascending order of serialized key.
+///
+/// The trie is walked depth-first, holding only the nodes along the current path in memory.  A
+/// missing or malformed trie is returned as an error, after which the iterator is exhausted.
+pub struct KeyValuesIterator<'a, 'b, K, V, T, S: TrieStore<K, V>> {
     initial_descend: VecDeque<u8>,
     visited: Vec<VisitedTrieNode<K, V>>,
     store: &'a S,
@@ -1079,24 +1087,42 @@ pub struct KeysIterator<'a, 'b, K, V, T, S: TrieStore<K, V>> {
     state: KeysIteratorState<K, V, S>,
 }
 
-impl<'a, 'b, K, V, T, S> Iterator for KeysIterator<'a, 'b, K, V, T, S>
+impl<'a, 'b, K, V, T, S> KeyValuesIterator<'a, 'b, K, V, T, S>
 where
     K: ToBytes + FromBytes + Clone + Eq + std::fmt::Debug,
     V: ToBytes + FromBytes + Clone + Eq + std::fmt::Debug,
     T: Readable<Handle = S::Handle>,
     S: TrieStore<K, V>,
-    S::Error: From<T::Error> + From<bytesrepr::Error>,
+    S::Error: From<T::Error> + From<CorruptTrieError>,
 {
-    type Item = Result<K, S::Error>;
+    fn get_trie(&self, trie_key: &Blake2bHash) -> Result<Trie<K, V>, S::Error> {
+        self.store
+            .get(self.txn, trie_key)?
+            .ok_or_else(|| CorruptTrieError::MissingTrie(*trie_key).into())
+    }
+
+    fn fail(&mut self, error: S::Error) -> Option<Result<(K, V), S::Error>> {
+        self.state = KeysIteratorState::Failed;
+        Some(Err(error))
+    }
+}
+
+impl<'a, 'b, K, V, T, S> Iterator for KeyValuesIterator<'a, 'b, K, V, T, S>
+where
+    K: ToBytes + FromBytes + Clone + Eq + std::fmt::Debug,
+    V: ToBytes + FromBytes + Clone + Eq + std::fmt::Debug,
+    T: Readable<Handle = S::Handle>,
+    S: TrieStore<K, V>,
+    S::Error: From<T::Error> + From<CorruptTrieError>,
+{
+    type Item = Result<(K, V), S::Error>;
 
     fn next(&mut self) -> Option<Self::Item> {
         match mem::replace(&mut self.state, KeysIteratorState::Ok) {
             KeysIteratorState::Ok => (),
-            KeysIteratorState::ReturnError(e) => {
-                self.state = KeysIteratorState::Failed;
-                return Some(Err(e));
-            }
+            KeysIteratorState::ReturnError(e) => return self.fail(e),
             KeysIteratorState::Failed => {
+                self.state = KeysIteratorState::Failed;
                 return None;
             }
         }
@@ -1109,19 +1135,18 @@ where
             let mut maybe_next_trie: Option<Trie<K, V>> = None;
 
             match trie {
-                Trie::Leaf { key, .. } => {
+                Trie::Leaf { key, value } => {
                     let key_bytes = match key.to_bytes() {
                         Ok(bytes) => bytes,
-                        Err(e) => {
-                            self.state = KeysIteratorState::Failed;
-                            return Some(Err(e.into()));
-                        }
+                        Err(e) => return self.fail(e.into()),
                     };
-                    debug_assert!(key_bytes.starts_with(&path));
+                    if !key_bytes.starts_with(&path) {
+                        return self.fail(CorruptTrieError::MisplacedLeaf.into());
+                    }
                     // only return the leaf if it matches the initial descend path
                     path.extend(&self.initial_descend);
                     if key_bytes.starts_with(&path) {
-                        return Some(Ok(key));
+                        return Some(Ok((key, value)));
                     }
                 }
                 Trie::Node { ref pointer_block } => {
@@ -1136,14 +1161,10 @@ where
                         .unwrap_or_default();
                     while index < RADIX {
                         if let Some(ref pointer) = pointer_block[index] {
-                            maybe_next_trie = match self.store.get(self.txn, pointer.hash()) {
-                                Ok(trie) => trie,
-                                Err(e) => {
-                                    self.state = KeysIteratorState::Failed;
-                                    return Some(Err(e));
-                                }
+                            maybe_next_trie = match self.get_trie(pointer.hash()) {
+                                Ok(trie) => Some(trie),
+                                Err(e) => return self.fail(e),
                             };
-                            debug_assert!(maybe_next_trie.is_some());
                             if self.initial_descend.pop_front().is_none() {
                                 self.visited.push(VisitedTrieNode {
                                     trie,
@@ -1174,14 +1195,14 @@ where
                     // if we are not, the check_prefix will be empty, so we will enter the if
                     // anyway
                     if affix.starts_with(&check_prefix) {
-                        maybe_next_trie = match self.store.get(self.txn, pointer.hash()) {
-                            Ok(trie) => trie,
-                            Err(e) => {
-                                self.state = KeysIteratorState::Failed;
-                                return Some(Err(e));
+                        maybe_next_trie = match self.get_trie(pointer.hash()) {
+                            Ok(trie @ Trie::Node { .. }) => Some(trie),
+                            Ok(_) => {
+                                let error = CorruptTrieError::InvalidExtension(*pointer.hash());
+                                return self.fail(error.into());
                             }
+                            Err(e) => return self.fail(e),
                         };
-                        debug_assert!({ matches!(&maybe_next_trie, Some(Trie::Node { .. })) });
                         path.extend(affix);
                     }
                 }
@@ -1199,6 +1220,26 @@ where
     }
 }
 
+/// An iterator over the keys of a trie, in ascending order of serialized key.
+///
+/// See [`KeyValuesIterator`] for details.
+pub struct KeysIterator<'a, 'b, K, V, T, S: TrieStore<K, V>>(KeyValuesIterator<'a, 'b, K, V, T, S>);
+
+impl<'a, 'b, K, V, T, S> Iterator for KeysIterator<'a, 'b, K, V, T, S>
+where
+    K: ToBytes + FromBytes + Clone + Eq + std::fmt::Debug,
+    V: ToBytes + FromBytes + Clone + Eq + std::fmt::Debug,
+    T: Readable<Handle = S::Handle>,
+    S: TrieStore<K, V>,
+    S::Error: From<T::Error> + From<CorruptTrieError>,
+{
+    type Item = Result<K, S::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|result| result.map(|(key, _)| key))
+    }
+}
+
 /// Returns the iterator over the keys at a given root hash.
 ///
 /// The root should be the apex of the trie.
@@ -1223,12 +1264,38 @@ where
 ///
 /// The root should be the apex of the trie.
 pub fn keys_with_prefix<'a, 'b, K, V, T, S>(
-    _correlation_id: CorrelationId,
+    correlation_id: CorrelationId,
     txn: &'b T,
     store: &'a S,
     root: &Blake2bHash,
     prefix: &[u8],
 ) -> KeysIterator<'a, 'b, K, V, T, S>
+where
+    K: ToBytes + FromBytes + Clone + Eq + std::fmt::Debug,
+    V: ToBytes + FromBytes + Clone + Eq + std::fmt::Debug,
+    T: Readable<Handle = S::Handle>,
+    S: TrieStore<K, V>,
+    S::Error: From<T::Error>,
+{
+    KeysIterator(key_values_with_prefix(
+        correlation_id,
+        txn,
+        store,
+        root,
+        prefix,
+    ))
+}
+
+/// Returns the iterator over the key-value pairs in the subtrie matching `prefix`.
+///
+/// The root should be the apex of the trie.
+pub fn key_values_with_prefix<'a, 'b, K, V, T, S>(
+    _correlation_id: CorrelationId,
+    txn: &'b T,
+    store: &'a S,
+    root: &Blake2bHash,
+    prefix: &[u8],
+) -> KeyValuesIterator<'a, 'b, K, V, T, S>
 where
     K: ToBytes + FromBytes + Clone + Eq + std::fmt::Debug,
     V: ToBytes + FromBytes + Clone + Eq + std::fmt::Debug,
@@ -1249,7 +1316,7 @@ where
         ),
     };
 
-    KeysIterator {
+    KeyValuesIterator {
         initial_descend: prefix.iter().cloned().collect(),
         visited,
         store,
@@ -145,12 +145,12 @@ mod full_tries {
     }
 }
 
-#[cfg(debug_assertions)]
 mod keys_iterator {
     use crate::shared::newtypes::{Blake2bHash, CorrelationId};
     use casper_types::bytesrepr;
 
     use crate::storage::{
+        error::{in_memory, CorruptTrieError},
         transaction_source::TransactionSource,
         trie::{Pointer, Trie},
         trie_store::operations::{
@@ -200,95 +200,144 @@ mod keys_iterator {
         Ok((root_hash, tries))
     }
 
-    macro_rules! return_on_err {
-        ($x:expr) => {
-            match $x {
-                Ok(result) => result,
-                Err(_) => {
-                    return; // we expect the test to panic, so this will cause a test failure
-                }
-            }
-        };
-    }
-
-    fn test_trie(root_hash: Blake2bHash, tries: Vec<HashedTestTrie>) {
+    fn test_trie(
+        root_hash: Blake2bHash,
+        tries: Vec<HashedTestTrie>,
+    ) -> Vec<Result<TestKey, in_memory::Error>> {
         let correlation_id = CorrelationId::new();
-        let context = return_on_err!(InMemoryTestContext::new(&tries));
-        let txn = return_on_err!(context.environment.create_read_txn());
-        let _tmp = operations::keys::<TestKey, TestValue, _, _>(
+        let context = InMemoryTestContext::new(&tries).unwrap();
+        let txn = context.environment.create_read_txn().unwrap();
+        operations::keys::<TestKey, TestValue, _, _>(
             correlation_id,
             &txn,
             &context.store,
             &root_hash,
         )
-        .collect::<Vec<_>>();
+        .collect()
     }
 
     #[test]
-    #[should_panic]
-    fn should_panic_on_leaf_after_extension() {
-        let (root_hash, tries) = return_on_err!(create_invalid_extension_trie());
-        test_trie(root_hash, tries);
+    fn should_fail_on_leaf_after_extension() {
+        let (root_hash, tries) = create_invalid_extension_trie().unwrap();
+        let leaf_hash = tries[2].hash;
+        let expected_error = CorruptTrieError::InvalidExtension(leaf_hash);
+        let expected = vec![Err(in_memory::Error::CorruptTrie(expected_error))];
+        assert_eq!(test_trie(root_hash, tries), expected);
     }
 
     #[test]
-    #[should_panic]
-    fn should_panic_when_key_not_matching_path() {
-        let (root_hash, tries) = return_on_err!(create_invalid_path_trie());
-        test_trie(root_hash, tries);
+    fn should_fail_when_key_not_matching_path() {
+        let (root_hash, tries) = create_invalid_path_trie().unwrap();
+        let expected = vec![Err(in_memory::Error::CorruptTrie(
+            CorruptTrieError::MisplacedLeaf,
+        ))];
+        assert_eq!(test_trie(root_hash, tries), expected);
     }
 
     #[test]
-    #[should_panic]
-    fn should_panic_on_pointer_to_nonexisting_hash() {
-        let (root_hash, tries) = return_on_err!(create_invalid_hash_trie());
-        test_trie(root_hash, tries);
+    fn should_fail_on_pointer_to_nonexisting_hash() {
+        let (root_hash, tries) = create_invalid_hash_trie().unwrap();
+        let missing_hash = hash_test_tries(&TEST_LEAVES[1..2]).unwrap()[0].hash;
+        let expected_error = CorruptTrieError::MissingTrie(missing_hash);
+        let expected = vec![Err(in_memory::Error::CorruptTrie(expected_error))];
+        assert_eq!(test_trie(root_hash, tries), expected);
     }
 }
 
 mod keys_with_prefix_iterator {
-    use crate::shared::newtypes::CorrelationId;
+    use std::{sync::Arc, thread};
+
+    use lmdb::DatabaseFlags;
+    use tempfile::tempdir;
+
+    use casper_types::bytesrepr::ToBytes;
+
+    use crate::shared::newtypes::{Blake2bHash, CorrelationId};
 
     use crate::storage::{
-        transaction_source::TransactionSource,
+        error::{self, CorruptTrieError},
+        store::Store,
+        transaction_source::{lmdb::LmdbEnvironment, Transaction, TransactionSource},
         trie::Trie,
-        trie_store::operations::{
-            self,
-            tests::{create_6_leaf_trie, InMemoryTestContext, TestKey, TestValue, TEST_LEAVES},
+        trie_store::{
+            lmdb::LmdbTrieStore,
+            operations::{
+                self,
+                tests::{
+                    create_6_leaf_trie, put_tries, InMemoryTestContext, LmdbTestContext, TestKey,
+                    TestTrie, TestValue, TEST_LEAVES,
+                },
+            },
         },
+        DEFAULT_TEST_MAX_DB_SIZE, DEFAULT_TEST_MAX_READERS,
     };
 
-    fn expected_keys(prefix: &[u8]) -> Vec<TestKey> {
+    fn expected_key_values(prefix: &[u8]) -> Vec<(TestKey, TestValue)> {
         let mut tmp = TEST_LEAVES
             .iter()
-            .filter_map(Trie::key)
-            .filter(|key| key.0.starts_with(prefix))
-            .cloned()
-            .collect::<Vec<TestKey>>();
-        tmp.sort();
+            .filter_map(|leaf| match leaf {
+                Trie::Leaf { key, value } if key.0.starts_with(prefix) => Some((*key, *value)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        tmp.sort_by_key(|(key, _)| *key);
         tmp
     }
 
+    fn expected_keys(prefix: &[u8]) -> Vec<TestKey> {
+        expected_key_values(prefix)
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect()
+    }
+
     fn test_prefix(prefix: &[u8]) {
         let correlation_id = CorrelationId::new();
         let (root_hash, tries) = create_6_leaf_trie().expect("should create a trie");
+
+        // Results are expected in ascending key order, so aren't sorted before comparing.
         let context = InMemoryTestContext::new(&tries).expect("should create a new context");
         let txn = context
             .environment
             .create_read_txn()
             .expect("should create a read txn");
-        let expected = expected_keys(prefix);
-        let mut actual = operations::keys_with_prefix::<TestKey, TestValue, _, _>(
+        let actual_keys = operations::keys_with_prefix::<TestKey, TestValue, _, _>(
+            correlation_id,
+            &txn,
+            &context.store,
+            &root_hash,
+            prefix,
+        )
+        .collect::<Result<Vec<_>, _>>()
+        .expect("should iterate keys");
+        assert_eq!(expected_keys(prefix), actual_keys);
+        let actual_key_values = operations::key_values_with_prefix::<TestKey, TestValue, _, _>(
+            correlation_id,
+            &txn,
+            &context.store,
+            &root_hash,
+            prefix,
+        )
+        .collect::<Result<Vec<_>, _>>()
+        .expect("should iterate key values");
+        assert_eq!(expected_key_values(prefix), actual_key_values);
+
+        let context = LmdbTestContext::new(&tries).expect("should create a new context");
+        let txn = context
+            .environment
+            .create_read_txn()
+            .expect("should create a read txn");
+        let actual_key_values = operations::key_values_with_prefix::<TestKey, TestValue, _, _>(
             correlation_id,
             &txn,
             &context.store,
             &root_hash,
             prefix,
         )
-        .filter_map(Result::ok)
-        .collect::<Vec<_>>();
-        actual.sort();
-        assert_eq!(expected, actual);
+        .collect::<Result<Vec<_>, _>>()
+        .expect("should iterate key values");
+        txn.commit().expect("should commit");
+        assert_eq!(expected_key_values(prefix), actual_key_values);
     }
 
     #[test]
@@ -306,4 +355,77 @@ mod keys_with_prefix_iterator {
         test_prefix(&[0, 0, 0, 0, 0, 0]); // 2 leaves
         test_prefix(&[0, 0, 0, 0, 0, 0, 1]); // 1 leaf
     }
+
+    #[test]
+    fn should_iterate_snapshot_while_concurrent_writer_deletes_trie() {
+        let correlation_id = CorrelationId::new();
+        let (root_hash, tries) = create_6_leaf_trie().expect("should create a trie");
+        let temp_dir = tempdir().expect("should create a temp dir");
+        let environment = Arc::new(
+            LmdbEnvironment::new(
+                &temp_dir.path().to_path_buf(),
+                DEFAULT_TEST_MAX_DB_SIZE,
+                DEFAULT_TEST_MAX_READERS,
+            )
+            .expect("should create an environment"),
+        );
+        let store = LmdbTrieStore::new(&environment, None, DatabaseFlags::empty())
+            .expect("should create a store");
+        put_tries::<_, _, _, _, error::Error>(&*environment, &store, &tries)
+            .expect("should put tries");
+
+        // Delete the leaf which is visited last.
+        let expected = expected_keys(&[]);
+        let deleted_key = *expected.last().expect("should have keys");
+        let deleted_hash = tries
+            .iter()
+            .find(|hashed_trie| hashed_trie.trie.key() == Some(&deleted_key))
+            .expect("should have leaf")
+            .hash;
+
+        let txn = environment
+            .create_read_txn()
+            .expect("should create a read txn");
+        let mut keys =
+            operations::keys::<TestKey, TestValue, _, _>(correlation_id, &txn, &store, &root_hash);
+        assert_eq!(keys.next(), Some(Ok(expected[0])));
+
+        let writer = {
+            let environment = Arc::clone(&environment);
+            let handle = Store::<Blake2bHash, TestTrie>::handle(&store);
+            thread::spawn(move || {
+                let mut txn = environment
+                    .create_read_write_txn()
+                    .expect("should create a read-write txn");
+                let trie_key = deleted_hash.to_bytes().expect("should serialize");
+                txn.del(handle, &trie_key, None).expect("should delete");
+                txn.commit().expect("should commit");
+            })
+        };
+        writer.join().expect("writer should succeed");
+
+        // The ongoing iteration reads from its transaction's snapshot, so is unaffected.
+        let remaining = keys
+            .collect::<Result<Vec<_>, _>>()
+            .expect("should iterate keys");
+        assert_eq!(remaining, expected[1..]);
+        txn.commit().expect("should commit");
+
+        // A new iteration surfaces the missing trie as an error, then stops.
+        let txn = environment
+            .create_read_txn()
+            .expect("should create a read txn");
+        let results =
+            operations::keys::<TestKey, TestValue, _, _>(correlation_id, &txn, &store, &root_hash)
+                .collect::<Vec<_>>();
+        txn.commit().expect("should commit");
+        let expected_results = expected[..expected.len() - 1]
+            .iter()
+            .map(|key| Ok(*key))
+            .chain(Some(Err(error::Error::CorruptTrie(
+                CorruptTrieError::MissingTrie(deleted_hash),
+            ))))
+            .collect::<Vec<_>>();
+        assert_eq!(results, expected_results);
+    }
 }
@@ -15,7 +15,7 @@ use crate::shared::newtypes::{Blake2bHash, CorrelationId};
 use casper_types::bytesrepr::{self, FromBytes, ToBytes};
 
 use crate::storage::{
-    error::{self, in_memory},
+    error::{self, in_memory, CorruptTrieError},
     transaction_source::{
         in_memory::InMemoryEnvironment, lmdb::LmdbEnvironment, Readable, Transaction,
         TransactionSource,
@@ -708,7 +708,7 @@ where
     V: ToBytes + FromBytes + Eq + std::fmt::Debug + Copy,
     T: Readable<Handle = S::Handle>,
     S: TrieStore<K, V>,
-    S::Error: From<T::Error>,
+    S::Error: From<T::Error> + From<CorruptTrieError>,
     E: From<S::Error> + From<bytesrepr::Error>,
 {
     let expected = {
@@ -743,7 +743,7 @@ where
     V: ToBytes + FromBytes + Eq + std::fmt::Debug + Copy,
     R: TransactionSource<'a, Handle = S::Handle>,
     S: TrieStore<K, V>,
-    S::Error: From<R::Error>,
+    S::Error: From<R::Error> + From<CorruptTrieError>,
     E: From<R::Error> + From<S::Error> + From<bytesrepr::Error>,
 {
     let txn: R::ReadTransaction = environment.create_read_txn()?;
@@ -873,7 +873,7 @@ where
     V: ToBytes + FromBytes + Eq + std::fmt::Debug + Copy,
     R: TransactionSource<'a, Handle = S::Handle>,
     S: TrieStore<K, V>,
-    S::Error: From<R::Error>,
+    S::Error: From<R::Error> + From<CorruptTrieError>,
     E: From<R::Error> + From<S::Error> + From<bytesrepr::Error>,
 {
     let txn = environment.create_read_txn()?;
@@ -955,7 +955,7 @@ where
     V: ToBytes + FromBytes + Clone + Eq + std::fmt::Debug + Copy,
     R: TransactionSource<'a, Handle = S::Handle>,
     S: TrieStore<K, V>,
-    S::Error: From<R::Error>,
+    S::Error: From<R::Error> + From<CorruptTrieError>,
     E: From<R::Error> + From<S::Error> + From<bytesrepr::Error>,
 {
     let mut states = states.to_vec();
@@ -5,8 +5,7 @@ use casper_types::bytesrepr::{self, FromBytes, ToBytes};
 use crate::{
     shared::newtypes::{Blake2bHash, CorrelationId},
     storage::{
-        error,
-        error::in_memory,
+        error::{self, in_memory, CorruptTrieError},
         transaction_source::{Transaction, TransactionSource},
         trie_store::{
             operations::{
@@ -32,7 +31,7 @@ where
     V: ToBytes + FromBytes + Eq + std::fmt::Debug + Copy,
     R: TransactionSource<'a, Handle = S::Handle>,
     S: TrieStore<K, V>,
-    S::Error: From<R::Error>,
+    S::Error: From<R::Error> + From<CorruptTrieError>,
     E: From<R::Error> + From<S::Error> + From<bytesrepr::Error>,
 {
     // Make sure no missing nodes in source
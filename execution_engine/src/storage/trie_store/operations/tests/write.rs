@@ -128,7 +128,7 @@ mod partial_tries {
     where
         R: TransactionSource<'a, Handle = S::Handle>,
         S: TrieStore<TestKey, TestValue>,
-        S::Error: From<R::Error>,
+        S::Error: From<R::Error> + From<CorruptTrieError>,
         E: From<R::Error> + From<S::Error> + From<bytesrepr::Error>,
     {
         // Check that the expected set of leaves is in the trie
@@ -213,7 +213,7 @@ mod partial_tries {
     where
         R: TransactionSource<'a, Handle = S::Handle>,
         S: TrieStore<TestKey, TestValue>,
-        S::Error: From<R::Error>,
+        S::Error: From<R::Error> + From<CorruptTrieError>,
         E: From<R::Error> + From<S::Error> + From<bytesrepr::Error>,
     {
         let mut states = states.to_owned();
@@ -323,7 +323,7 @@ mod full_tries {
     where
         R: TransactionSource<'a, Handle = S::Handle>,
         S: TrieStore<TestKey, TestValue>,
-        S::Error: From<R::Error>,
+        S::Error: From<R::Error> + From<CorruptTrieError>,
         E: From<R::Error> + From<S::Error> + From<bytesrepr::Error>,
     {
         // Check that the expected set of leaves is in the trie at every state reference
@@ -420,7 +420,7 @@ mod full_tries {
     where
         R: TransactionSource<'a, Handle = S::Handle>,
         S: TrieStore<TestKey, TestValue>,
-        S::Error: From<R::Error>,
+        S::Error: From<R::Error> + From<CorruptTrieError>,
         E: From<R::Error> + From<S::Error> + From<bytesrepr::Error>,
     {
         let mut states = states.to_vec();
@@ -543,7 +543,7 @@ mod full_tries {
     where
         R: TransactionSource<'a, Handle = S::Handle>,
         S: TrieStore<TestKey, TestValue>,
-        S::Error: From<R::Error>,
+        S::Error: From<R::Error> + From<CorruptTrieError>,
         E: From<R::Error> + From<S::Error> + From<bytesrepr::Error>,
     {
         let mut states = states.to_vec();
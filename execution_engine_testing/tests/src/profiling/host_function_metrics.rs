@@ -26,7 +26,7 @@ use casper_execution_engine::{
     core::engine_state::EngineConfig,
     shared::logging::{self, Settings},
 };
-use casper_types::{runtime_args, ApiError, RuntimeArgs};
+use casper_types::{bytesrepr::Bytes, runtime_args, ApiError, RuntimeArgs};
 
 use casper_engine_tests::profiling;
 use casper_execution_engine::shared::newtypes::Blake2bHash;
@@ -165,7 +165,11 @@ fn run_test(root_hash: Vec<u8>, repetitions: usize, data_dir: &Path) {
                 HOST_FUNCTION_METRICS_CONTRACT,
                 runtime_args! {
                     ARG_SEED => seed,
-                    ARG_OTHERS => (random_bytes, account_1_account_hash, account_2_account_hash),
+                    ARG_OTHERS => (
+                        Bytes::from(random_bytes),
+                        account_1_account_hash,
+                        account_2_account_hash
+                    ),
                 },
             )
             .with_empty_payment_bytes(runtime_args! { ARG_AMOUNT => PAYMENT_AMOUNT })
@@ -7,7 +7,7 @@ use casper_engine_test_support::{
 };
 use casper_execution_engine::{
     core::{
-        engine_state::{Error as CoreError, WASMLESS_TRANSFER_FIXED_GAS_PRICE},
+        engine_state::{Error as CoreError, ExecuteRequest, WASMLESS_TRANSFER_FIXED_GAS_PRICE},
         execution::Error as ExecError,
     },
     shared::{
@@ -24,7 +24,7 @@ use casper_types::{
     account::AccountHash,
     runtime_args,
     system::{handle_payment, mint},
-    AccessRights, ApiError, EraId, Key, ProtocolVersion, RuntimeArgs, URef, U512,
+    AccessRights, ApiError, DeployHash, EraId, Key, ProtocolVersion, RuntimeArgs, URef, U512,
 };
 
 const CONTRACT_TRANSFER_PURSE_TO_ACCOUNT: &str = "transfer_purse_to_account.wasm";
@@ -35,6 +35,7 @@ const ARG_PURSE_NAME: &str = "purse_name";
 
 const ACCOUNT_1_ADDR: AccountHash = AccountHash::new([1u8; 32]);
 const ACCOUNT_2_ADDR: AccountHash = AccountHash::new([2u8; 32]);
+const ACCOUNT_3_ADDR: AccountHash = AccountHash::new([3u8; 32]);
 
 #[ignore]
 #[test]
@@ -988,3 +989,151 @@ fn transfer_wasmless_should_observe_upgraded_cost() {
         default_account_balance_before - default_account_balance_after - transfer_amount
     );
 }
+
+fn batched_transfer_request(
+    deploy_hash: [u8; 32],
+    batch: Vec<(AccountHash, U512, Option<u64>)>,
+) -> ExecuteRequest {
+    let deploy_item = DeployItemBuilder::new()
+        .with_address(ACCOUNT_1_ADDR)
+        .with_deploy_hash(deploy_hash)
+        .with_empty_payment_bytes(runtime_args! {})
+        .with_transfer_args(runtime_args! { mint::ARG_TRANSFERS => batch })
+        .with_authorization_keys(&[ACCOUNT_1_ADDR])
+        .build();
+    ExecuteRequestBuilder::from_deploy_item(deploy_item).build()
+}
+
+#[ignore]
+#[test]
+fn transfer_wasmless_batch_should_make_each_transfer() {
+    let wasmless_transfer_cost = Motes::from_gas(
+        Gas::from(DEFAULT_WASMLESS_TRANSFER_COST),
+        WASMLESS_TRANSFER_FIXED_GAS_PRICE,
+    )
+    .expect("gas overflow");
+
+    let create_account_2: bool = true;
+    let mut builder = init_wasmless_transform_builder(create_account_2);
+
+    let account_1 = builder
+        .get_account(ACCOUNT_1_ADDR)
+        .expect("should get account 1");
+    let account_2_purse = builder
+        .get_account(ACCOUNT_2_ADDR)
+        .expect("should get account 2")
+        .main_purse();
+    assert_eq!(
+        builder.get_account(ACCOUNT_3_ADDR),
+        None,
+        "account 3 should not exist"
+    );
+
+    let account_1_starting_balance = builder.get_purse_balance(account_1.main_purse());
+    let account_2_starting_balance = builder.get_purse_balance(account_2_purse);
+
+    // The second transfer to account 3 is made to the account created by the first one.
+    let batch = vec![
+        (ACCOUNT_2_ADDR, U512::from(1_000), Some(1)),
+        (ACCOUNT_3_ADDR, U512::from(2_000), Some(2)),
+        (ACCOUNT_3_ADDR, U512::from(3_000), None),
+    ];
+    let deploy_hash = [42; 32];
+    builder
+        .exec(batched_transfer_request(deploy_hash, batch.clone()))
+        .expect_success()
+        .commit();
+
+    let account_3_purse = builder
+        .get_account(ACCOUNT_3_ADDR)
+        .expect("account 3 should exist")
+        .main_purse();
+    let batch_cost = wasmless_transfer_cost.value() * U512::from(batch.len() as u64);
+    assert_eq!(
+        builder.get_purse_balance(account_1.main_purse()),
+        account_1_starting_balance - U512::from(6_000) - batch_cost,
+        "account 1 should pay every amount and the cost of each transfer"
+    );
+    assert_eq!(
+        builder.get_purse_balance(account_2_purse),
+        account_2_starting_balance + U512::from(1_000)
+    );
+    assert_eq!(
+        builder.get_purse_balance(account_3_purse),
+        U512::from(5_000)
+    );
+
+    let deploy_hash = DeployHash::new(deploy_hash);
+    let deploy_info = builder
+        .get_deploy_info(deploy_hash)
+        .expect("should have deploy info");
+    assert_eq!(deploy_info.gas, batch_cost);
+    assert_eq!(deploy_info.transfers.len(), batch.len());
+
+    for (transfer_addr, (target, amount, id)) in deploy_info.transfers.iter().zip(batch) {
+        let transfer = builder
+            .get_transfer(*transfer_addr)
+            .expect("should have transfer");
+        assert_eq!(transfer.deploy_hash, deploy_hash);
+        assert_eq!(transfer.from, ACCOUNT_1_ADDR);
+        assert_eq!(transfer.to, Some(target));
+        assert_eq!(transfer.source, account_1.main_purse());
+        assert_eq!(transfer.amount, amount);
+        assert_eq!(transfer.id, id);
+    }
+}
+
+#[ignore]
+#[test]
+fn transfer_wasmless_batch_should_make_no_transfers_if_any_is_invalid() {
+    let wasmless_transfer_cost = Motes::from_gas(
+        Gas::from(DEFAULT_WASMLESS_TRANSFER_COST),
+        WASMLESS_TRANSFER_FIXED_GAS_PRICE,
+    )
+    .expect("gas overflow");
+
+    let create_account_2: bool = true;
+    let mut builder = init_wasmless_transform_builder(create_account_2);
+
+    let account_1_purse = builder
+        .get_account(ACCOUNT_1_ADDR)
+        .expect("should get account 1")
+        .main_purse();
+    let account_2_purse = builder
+        .get_account(ACCOUNT_2_ADDR)
+        .expect("should get account 2")
+        .main_purse();
+
+    let account_1_starting_balance = builder.get_purse_balance(account_1_purse);
+    let account_2_starting_balance = builder.get_purse_balance(account_2_purse);
+
+    // The last transfer is to account 1 itself, which is invalid.
+    let batch = vec![
+        (ACCOUNT_2_ADDR, U512::from(1_000), None),
+        (ACCOUNT_3_ADDR, U512::from(2_000), None),
+        (ACCOUNT_1_ADDR, U512::from(3_000), None),
+    ];
+    let batch_cost = wasmless_transfer_cost.value() * U512::from(batch.len() as u64);
+    builder
+        .exec(batched_transfer_request([42; 32], batch))
+        .commit();
+
+    let error = builder.get_error().expect("should have error");
+    let expected_error = CoreError::Exec(ExecError::Revert(ApiError::InvalidPurse));
+    assert_eq!(format!("{}", expected_error), format!("{}", error));
+
+    // Only the cost of the batch is charged, and none of the transfers are made.
+    assert_eq!(
+        builder.get_purse_balance(account_1_purse),
+        account_1_starting_balance - batch_cost
+    );
+    assert_eq!(
+        builder.get_purse_balance(account_2_purse),
+        account_2_starting_balance
+    );
+    assert_eq!(
+        builder.get_account(ACCOUNT_3_ADDR),
+        None,
+        "account 3 should not have been created"
+    );
+}
@@ -0,0 +1,36 @@
+//! Seeds the cargo-fuzz corpus of each target with values generated by the proptest generators.
+//!
+//! Usage: `cargo run --example generate_corpus [SEEDS_PER_TARGET]`
+//!
+//! The seeds are written to `corpus/<TARGET>/seed-<INDEX>` alongside this crate's manifest, where
+//! `cargo fuzz run <TARGET>` picks them up.  Existing seed files are overwritten, while any inputs
+//! added to the corpus by previous fuzzing runs are left untouched.
+
+use std::{env, fs, path::PathBuf};
+
+use proptest::test_runner::TestRunner;
+
+use casper_fuzz::TARGETS;
+
+const DEFAULT_SEEDS_PER_TARGET: usize = 256;
+
+fn main() {
+    let seeds_per_target = env::args()
+        .nth(1)
+        .map(|arg| arg.parse().expect("seeds per target should be a number"))
+        .unwrap_or(DEFAULT_SEEDS_PER_TARGET);
+    let corpus_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("corpus");
+
+    let mut runner = TestRunner::deterministic();
+    for target in TARGETS {
+        let target_dir = corpus_dir.join(target.name);
+        fs::create_dir_all(&target_dir).expect("should create corpus dir");
+        let seeds = (target.seeds)(&mut runner, seeds_per_target);
+        for (index, seed) in seeds.iter().enumerate() {
+            let path = target_dir.join(format!("seed-{}", index));
+            fs::write(&path, seed)
+                .unwrap_or_else(|error| panic!("should write {}: {}", path.display(), error));
+        }
+        println!("wrote {} seeds to {}", seeds.len(), target_dir.display());
+    }
+}
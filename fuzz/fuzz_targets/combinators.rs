@@ -0,0 +1,7 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use casper_fuzz::{check_round_trip, Combinators};
+
+fuzz_target!(|data: &[u8]| check_round_trip::<Combinators>(data));
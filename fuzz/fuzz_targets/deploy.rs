@@ -0,0 +1,8 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use casper_deploy_utils::Deploy;
+use casper_fuzz::check_round_trip;
+
+fuzz_target!(|data: &[u8]| check_round_trip::<Deploy>(data));
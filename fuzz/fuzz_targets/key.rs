@@ -0,0 +1,8 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use casper_fuzz::check_round_trip;
+use casper_types::Key;
+
+fuzz_target!(|data: &[u8]| check_round_trip::<Key>(data));
@@ -0,0 +1,9 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use casper_execution_engine::{shared::stored_value::StoredValue, storage::trie::Trie};
+use casper_fuzz::check_round_trip;
+use casper_types::Key;
+
+fuzz_target!(|data: &[u8]| check_round_trip::<Trie<Key, StoredValue>>(data));
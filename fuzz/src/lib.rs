@@ -0,0 +1,210 @@
+//! Fuzz targets for the `bytesrepr` deserialization of network-facing types.
+//!
+//! Each target feeds arbitrary bytes to a type's [`FromBytes`] implementation.  Failing to
+//! deserialize is fine, but no input may cause a panic or an abort, and any value which does
+//! deserialize must re-serialize to bytes which deserialize to an equal value.
+//!
+//! The targets under `fuzz_targets` drive these checks via `cargo fuzz`, while the `bounded-fuzz`
+//! feature runs a fixed number of mutated inputs through them as part of `cargo test`.  Both are
+//! seeded from the proptest generators via [`Target::seeds`].
+
+#![warn(missing_docs)]
+
+use std::{
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    fmt::Debug,
+};
+
+use num_rational::Ratio;
+use proptest::{
+    collection::{btree_map, btree_set, vec},
+    option,
+    prelude::*,
+    result::maybe_ok,
+    strategy::ValueTree,
+    test_runner::TestRunner,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use casper_deploy_utils::{
+    Deploy, DeployHash, DeployHeader, ExecutableDeployItem, TimeDiff, Timestamp,
+};
+use casper_execution_engine::{
+    shared::stored_value::StoredValue,
+    storage::trie::{gens::trie_arb, Trie},
+};
+use casper_types::{
+    bytesrepr::{Bytes, FromBytes, ToBytes},
+    gens::{cl_value_arb, key_arb, named_args_arb, u512_arb},
+    CLValue, Key, RuntimeArgs, SecretKey, U512,
+};
+
+/// A composite of the generic collection, option, result and tuple implementations.
+pub type Combinators = (
+    Vec<(String, Option<U512>)>,
+    BTreeMap<Key, Result<bool, i32>>,
+    (BTreeSet<u64>, VecDeque<Bytes>, Ratio<u64>),
+);
+
+/// A fuzz target: a round-trip check over one type, along with seed inputs for it.
+pub struct Target {
+    /// The name of the target, matching its binary under `fuzz_targets`.
+    pub name: &'static str,
+    /// Runs the round-trip check on the given input.
+    pub check: fn(&[u8]),
+    /// Generates the given number of serialized values to seed the corpus with.
+    pub seeds: fn(&mut TestRunner, usize) -> Vec<Vec<u8>>,
+}
+
+/// All the fuzz targets.
+pub const TARGETS: &[Target] = &[
+    Target {
+        name: "deploy",
+        check: check_round_trip::<Deploy>,
+        seeds: |runner, count| serialized_seeds(deploy_arb(), runner, count),
+    },
+    Target {
+        name: "deploy_header",
+        check: check_round_trip::<DeployHeader>,
+        seeds: |runner, count| serialized_seeds(deploy_header_arb(), runner, count),
+    },
+    Target {
+        name: "trie",
+        check: check_round_trip::<Trie<Key, StoredValue>>,
+        seeds: |runner, count| serialized_seeds(trie_arb(), runner, count),
+    },
+    Target {
+        name: "cl_value",
+        check: check_round_trip::<CLValue>,
+        seeds: |runner, count| serialized_seeds(cl_value_arb(), runner, count),
+    },
+    Target {
+        name: "key",
+        check: check_round_trip::<Key>,
+        seeds: |runner, count| serialized_seeds(key_arb(), runner, count),
+    },
+    Target {
+        name: "combinators",
+        check: check_round_trip::<Combinators>,
+        seeds: |runner, count| serialized_seeds(combinators_arb(), runner, count),
+    },
+];
+
+/// Checks that if `data` deserializes to a `T`, re-serializing that value yields bytes which
+/// deserialize to an equal value.
+///
+/// Panics if the check fails.
+pub fn check_round_trip<T>(data: &[u8])
+where
+    T: FromBytes + ToBytes + PartialEq + Debug,
+{
+    let value = match T::from_bytes(data) {
+        Ok((value, _remainder)) => value,
+        Err(_) => return,
+    };
+
+    let serialized = value
+        .to_bytes()
+        .unwrap_or_else(|error| panic!("failed to re-serialize {:?}: {}", value, error));
+    assert_eq!(
+        serialized.len(),
+        value.serialized_length(),
+        "serialized_length() doesn't match the length of the serialized {:?}",
+        value
+    );
+
+    let (round_tripped, remainder) = T::from_bytes(&serialized).unwrap_or_else(|error| {
+        panic!("failed to deserialize re-serialized {:?}: {}", value, error)
+    });
+    assert!(
+        remainder.is_empty(),
+        "{} bytes left over after deserializing re-serialized {:?}",
+        remainder.len(),
+        value
+    );
+    assert_eq!(round_tripped, value);
+}
+
+/// Generates `count` values from `strategy` and returns them serialized.
+fn serialized_seeds<S>(strategy: S, runner: &mut TestRunner, count: usize) -> Vec<Vec<u8>>
+where
+    S: Strategy,
+    S::Value: ToBytes,
+{
+    (0..count)
+        .map(|_| {
+            let value = strategy
+                .new_tree(runner)
+                .expect("should generate a seed value")
+                .current();
+            value.to_bytes().expect("should serialize a seed value")
+        })
+        .collect()
+}
+
+/// Returns a strategy for signed deploys whose payment carries arbitrary runtime args.
+pub fn deploy_arb() -> impl Strategy<Value = Deploy> {
+    (
+        any::<u64>(),
+        "[a-z-]{0,20}",
+        vec(named_args_arb(), 0..4),
+        1..4usize,
+    )
+        .prop_map(|(seed, chain_name, named_args, signer_count)| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let timestamp = Timestamp::random(&mut rng);
+            let ttl = TimeDiff::from(rng.gen_range(60_000..3_600_000));
+            let gas_price = rng.gen_range(1..100);
+            let dependency_count = rng.gen_range(0..4);
+            let dependencies = (0..dependency_count)
+                .map(|_| DeployHash::random(&mut rng))
+                .collect();
+            let payment = ExecutableDeployItem::ModuleBytes {
+                module_bytes: Bytes::new(),
+                args: RuntimeArgs::from(named_args),
+            };
+            let session = rng.gen();
+
+            let mut deploy = Deploy::new(
+                timestamp,
+                ttl,
+                gas_price,
+                dependencies,
+                chain_name,
+                payment,
+                session,
+                &random_secret_key(&mut rng),
+            );
+            for _ in 1..signer_count {
+                deploy.sign(&random_secret_key(&mut rng));
+            }
+            deploy
+        })
+}
+
+/// Returns a strategy for the headers of the deploys generated by [`deploy_arb`].
+pub fn deploy_header_arb() -> impl Strategy<Value = DeployHeader> {
+    deploy_arb().prop_map(Deploy::take_header)
+}
+
+/// Returns a strategy for [`Combinators`].
+pub fn combinators_arb() -> impl Strategy<Value = Combinators> {
+    (
+        vec(("\\PC{0,16}", option::of(u512_arb())), 0..8),
+        btree_map(key_arb(), maybe_ok(any::<bool>(), any::<i32>()), 0..8),
+        (
+            btree_set(any::<u64>(), 0..8),
+            vec(vec(any::<u8>(), 0..32).prop_map(Bytes::from), 0..4).prop_map(VecDeque::from),
+            (any::<u64>(), 1..u64::max_value()).prop_map(|(numer, denom)| Ratio::new(numer, denom)),
+        ),
+    )
+}
+
+fn random_secret_key(rng: &mut StdRng) -> SecretKey {
+    if rng.gen() {
+        SecretKey::ed25519_from_bytes(rng.gen::<[u8; SecretKey::ED25519_LENGTH]>())
+    } else {
+        SecretKey::secp256k1_from_bytes(rng.gen::<[u8; SecretKey::SECP256K1_LENGTH]>())
+    }
+    .expect("should create secret key")
+}
@@ -0,0 +1,161 @@
+//! A short, deterministic fuzz run over every target, for running as part of the test suite.
+//!
+//! Rather than relying on libFuzzer's coverage guidance, this mutates seeds generated from the
+//! proptest generators, with the mutations biased towards the kinds of corruption which have
+//! historically tripped up `FromBytes` implementations: huge length prefixes, unexpected tags and
+//! long runs of a repeated byte.
+//!
+//! The number of iterations per target defaults to `DEFAULT_ITERATIONS` and can be overridden via
+//! the `CASPER_FUZZ_ITERATIONS` environment variable.
+
+#![cfg(feature = "bounded-fuzz")]
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    env, ptr,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use proptest::test_runner::TestRunner;
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+use casper_fuzz::TARGETS;
+
+const DEFAULT_ITERATIONS: usize = 5_000;
+const ITERATIONS_ENV_VAR: &str = "CASPER_FUZZ_ITERATIONS";
+const SEED_COUNT: usize = 32;
+const RNG_SEED: u64 = 0x5eed_f022;
+
+/// A single allocation may be at most this multiple of the input's size, plus
+/// `ALLOCATION_ALLOWANCE`.  Anything larger means a declared length was trusted ahead of the data
+/// backing it.
+const ALLOCATION_FACTOR: usize = 16;
+/// Covers fixed-size allocations such as a trie node's pointer block.
+const ALLOCATION_ALLOWANCE: usize = 16 * 1024;
+/// Allocations above this size are refused outright rather than attempted.
+const HARD_ALLOCATION_LIMIT: usize = 256 * 1024 * 1024;
+
+/// Records the size of the largest allocation requested since it was last reset.
+struct GuardedAllocator;
+
+static LARGEST_ALLOCATION: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for GuardedAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        LARGEST_ALLOCATION.fetch_max(layout.size(), Ordering::SeqCst);
+        if layout.size() > HARD_ALLOCATION_LIMIT {
+            return ptr::null_mut();
+        }
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        LARGEST_ALLOCATION.fetch_max(new_size, Ordering::SeqCst);
+        if new_size > HARD_ALLOCATION_LIMIT {
+            return ptr::null_mut();
+        }
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: GuardedAllocator = GuardedAllocator;
+
+/// Length prefixes and other `u32`s likely to find edge cases.
+const INTERESTING_U32S: [u32; 8] = [
+    0,
+    1,
+    2,
+    0x7f,
+    0xffff,
+    u32::max_value() / 2,
+    u32::max_value() - 1,
+    u32::max_value(),
+];
+
+/// Tags and other bytes likely to find edge cases, including the `CLType` tags for `Option` (13)
+/// and `Tuple2` (19), runs of which nest types arbitrarily deeply.
+const INTERESTING_BYTES: [u8; 8] = [0, 1, 2, 13, 19, 0x7f, 0x80, 0xff];
+
+fn mutate(rng: &mut StdRng, input: &mut Vec<u8>) {
+    for _ in 0..rng.gen_range(1..=4) {
+        let index = if input.is_empty() {
+            0
+        } else {
+            rng.gen_range(0..input.len())
+        };
+        match rng.gen_range(0..7) {
+            0 if !input.is_empty() => input[index] ^= 1 << rng.gen_range(0..8u8),
+            1 if !input.is_empty() => {
+                input[index] = *INTERESTING_BYTES.choose(rng).unwrap();
+            }
+            2 => {
+                let value = INTERESTING_U32S.choose(rng).unwrap().to_le_bytes();
+                let end = (index + value.len()).min(input.len());
+                input.splice(index..end, value.iter().copied());
+            }
+            3 => {
+                let run_length = rng.gen_range(1..65_536);
+                let byte = *INTERESTING_BYTES.choose(rng).unwrap();
+                input.splice(index..index, std::iter::repeat(byte).take(run_length));
+            }
+            4 => {
+                let inserted: Vec<u8> = (0..rng.gen_range(1..32)).map(|_| rng.gen()).collect();
+                input.splice(index..index, inserted);
+            }
+            5 if !input.is_empty() => {
+                let end = rng.gen_range(index..input.len());
+                input.drain(index..=end);
+            }
+            _ => input.truncate(index),
+        }
+    }
+}
+
+fn iterations() -> usize {
+    env::var(ITERATIONS_ENV_VAR)
+        .map(|value| {
+            value
+                .parse()
+                .unwrap_or_else(|_| panic!("{} should be a number", ITERATIONS_ENV_VAR))
+        })
+        .unwrap_or(DEFAULT_ITERATIONS)
+}
+
+fn check_with_allocation_guard(target_name: &str, check: fn(&[u8]), input: &[u8]) {
+    LARGEST_ALLOCATION.store(0, Ordering::SeqCst);
+    check(input);
+    let largest_allocation = LARGEST_ALLOCATION.load(Ordering::SeqCst);
+    assert!(
+        largest_allocation <= input.len() * ALLOCATION_FACTOR + ALLOCATION_ALLOWANCE,
+        "{}: allocated {} bytes for an input of {} bytes: {}",
+        target_name,
+        largest_allocation,
+        input.len(),
+        hex::encode(input)
+    );
+}
+
+#[test]
+fn should_survive_bounded_fuzz_run() {
+    let iterations = iterations();
+    let mut runner = TestRunner::deterministic();
+    let mut rng = StdRng::seed_from_u64(RNG_SEED);
+
+    for target in TARGETS {
+        let seeds = (target.seeds)(&mut runner, SEED_COUNT);
+        for seed in &seeds {
+            check_with_allocation_guard(target.name, target.check, seed);
+        }
+
+        for _ in 0..iterations {
+            let mut input = seeds.choose(&mut rng).unwrap().clone();
+            mutate(&mut rng, &mut input);
+            check_with_allocation_guard(target.name, target.check, &input);
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use criterion::{black_box, criterion_group, criterion_main, Bencher, Criterion};
+
+use casper_execution_engine::core::engine_state::executable_deploy_item::ExecutableDeployItem;
+use casper_node::types::{decode_canonical_deploy, Deploy, DeployHash, TimeDiff, Timestamp};
+use casper_types::{bytesrepr::Bytes, RuntimeArgs, SecretKey};
+
+static KB: usize = 1024;
+
+/// Returns the serialized form of a signed deploy whose session code is `module_size` bytes long.
+fn prepare_serialized_deploy(module_size: usize) -> Vec<u8> {
+    let secret_key = SecretKey::ed25519_from_bytes([1; SecretKey::ED25519_LENGTH]).unwrap();
+    let payment = ExecutableDeployItem::ModuleBytes {
+        module_bytes: Bytes::new(),
+        args: RuntimeArgs::new(),
+    };
+    let session = ExecutableDeployItem::ModuleBytes {
+        module_bytes: (0..module_size).map(|value| value as u8).collect(),
+        args: RuntimeArgs::new(),
+    };
+    let mut deploy = Deploy::new(
+        Timestamp::zero(),
+        TimeDiff::from(3_600_000),
+        1,
+        vec![DeployHash::default()],
+        String::from("casper-example"),
+        payment,
+        session,
+        &secret_key,
+    );
+    deploy.sign(&SecretKey::ed25519_from_bytes([2; SecretKey::ED25519_LENGTH]).unwrap());
+    bincode::serialize(&deploy).unwrap()
+}
+
+fn decode_deploy(b: &mut Bencher, module_size: usize) {
+    let serialized = prepare_serialized_deploy(module_size);
+    b.iter(|| bincode::deserialize::<Deploy>(black_box(&serialized)).unwrap());
+}
+
+fn decode_canonical(b: &mut Bencher, module_size: usize) {
+    let serialized = prepare_serialized_deploy(module_size);
+    b.iter(|| decode_canonical_deploy(black_box(&serialized)).unwrap());
+}
+
+fn deploy_decoding_bench(c: &mut Criterion) {
+    c.bench_function("decode_small_deploy", |b| decode_deploy(b, KB));
+    c.bench_function("decode_canonical_small_deploy", |b| decode_canonical(b, KB));
+    c.bench_function("decode_large_deploy", |b| decode_deploy(b, 512 * KB));
+    c.bench_function("decode_canonical_large_deploy", |b| {
+        decode_canonical(b, 512 * KB)
+    });
+}
+
+criterion_group!(benches, deploy_decoding_bench);
+criterion_main!(benches);
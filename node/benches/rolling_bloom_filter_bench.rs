@@ -0,0 +1,54 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use casper_node::{
+    crypto::hash::{self, Digest},
+    types::{TimeDiff, Timestamp},
+    utils::rolling_bloom_filter::RollingBloomFilter,
+};
+
+/// The numbers of entries in the filter to check against, spanning three orders of magnitude.
+const ENTRY_COUNTS: [u64; 3] = [1_000, 100_000, 1_000_000];
+
+/// The number of candidates checked per iteration, half of which are in the filter.
+const CANDIDATE_COUNT: u64 = 1_000;
+
+fn digest(index: u64) -> Digest {
+    hash::hash(index.to_le_bytes())
+}
+
+/// Returns a filter holding `entry_count` entries which all expire within one TTL.
+fn prepare_filter(entry_count: u64) -> RollingBloomFilter {
+    let ttl = TimeDiff::from_seconds(24 * 60 * 60);
+    let mut filter = RollingBloomFilter::new(ttl, entry_count);
+    for index in 0..entry_count {
+        let expires = Timestamp::from(ttl.millis() + index % ttl.millis());
+        filter.insert(&digest(index), expires);
+    }
+    filter
+}
+
+fn rolling_bloom_filter_bench(c: &mut Criterion) {
+    let mut group = c.benchmark_group("rolling_bloom_filter_may_contain");
+    for entry_count in ENTRY_COUNTS.iter().copied() {
+        let filter = prepare_filter(entry_count);
+        let candidates: Vec<Digest> = (0..CANDIDATE_COUNT)
+            .map(|index| digest(entry_count - CANDIDATE_COUNT / 2 + index))
+            .collect();
+        group.bench_with_input(
+            BenchmarkId::from_parameter(entry_count),
+            &candidates,
+            |b, candidates| {
+                b.iter(|| {
+                    candidates
+                        .iter()
+                        .filter(|candidate| filter.may_contain(black_box(candidate)))
+                        .count()
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, rolling_bloom_filter_bench);
+criterion_main!(benches);
@@ -1,10 +1,11 @@
-use std::env;
+use std::{env, process::Command};
 
 use vergen::ConstantsFlags;
 
 fn main() {
     let mut flags = ConstantsFlags::empty();
     flags.toggle(ConstantsFlags::SHA_SHORT);
+    flags.toggle(ConstantsFlags::BUILD_TIMESTAMP);
     flags.toggle(ConstantsFlags::REBUILD_ON_HEAD_CHANGE);
     vergen::generate_cargo_keys(flags).expect("should generate the cargo keys");
 
@@ -13,4 +14,15 @@ fn main() {
         "cargo:rustc-env=NODE_BUILD_PROFILE={}",
         env::var("PROFILE").unwrap()
     );
+
+    // Make the version of the compiler building the node available to rustc at compile time.
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let rustc_version = Command::new(rustc)
+        .arg("--version")
+        .output()
+        .ok()
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|version| version.trim().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=NODE_RUSTC_VERSION={}", rustc_version);
 }
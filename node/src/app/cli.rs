@@ -5,14 +5,19 @@
 pub mod arglang;
 
 use std::{
+    convert::TryFrom,
     env, fs,
     path::{Path, PathBuf},
     str::FromStr,
+    sync::atomic::Ordering,
+    time::Duration,
 };
 
 use anyhow::{self, Context};
+use itertools::Itertools;
 use regex::Regex;
 use structopt::StructOpt;
+use tokio::time;
 use toml::{value::Table, Value};
 use tracing::{error, info, warn};
 
@@ -20,11 +25,12 @@ use casper_node::{
     logging,
     reactor::{initializer, joiner, participating, ReactorExit, Runner},
     setup_signal_hooks,
-    types::ExitCode,
+    types::{Chainspec, ExitCode},
     utils::{
         pid_file::{PidFile, PidFileOutcome},
         WithDir,
     },
+    DiagnosticsOptions, LOG_FILTER_RELOAD_REQUESTED,
 };
 use prometheus::Registry;
 
@@ -33,6 +39,9 @@ use prometheus::Registry;
 #[global_allocator]
 static ALLOC: jemallocator::Jemalloc = jemallocator::Jemalloc;
 
+/// How often to check whether `SIGHUP` has been received, so the log filter should be reloaded.
+const LOG_FILTER_RELOAD_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
 // Note: The docstring on `Cli` is the help shown when calling the binary with `--help`.
 #[derive(Debug, StructOpt)]
 #[structopt(version = casper_node::VERSION_STRING_COLOR.as_str())]
@@ -41,11 +50,16 @@ pub enum Cli {
     /// Run the validator node.
     ///
     /// Loads the configuration values from the given configuration file or uses defaults if not
-    /// given, then runs the reactor.
+    /// given, validates them, then runs the reactor.
     Validator {
         /// Path to configuration file.
         config: PathBuf,
 
+        /// Validate the configuration, report every problem found, then exit without running the
+        /// node.
+        #[structopt(long)]
+        check_config: bool,
+
         #[structopt(
             short = "C",
             long,
@@ -75,9 +89,95 @@ pub enum Cli {
         #[structopt(long)]
         new_config: PathBuf,
     },
+    /// Clear the saved blocklist of misbehaving peers.
+    ///
+    /// Must only be run while the node is stopped, as a running node saves its blocklist on
+    /// shutdown.
+    ClearPeerBlocklist {
+        /// Path to configuration file.
+        config: PathBuf,
+    },
+    /// Check a chainspec for errors, then exit.
+    ///
+    /// Every problem found in the chainspec and its accounts file is reported, and the exit code
+    /// is non-zero if there are any.
+    CheckChainspec {
+        /// Path to chainspec file.  The accounts file must be in the same directory.
+        path: PathBuf,
+    },
+    /// Create a diagnostics bundle to attach to support requests, then exit.
+    ///
+    /// Gathers the effective config with secrets redacted, along with the status, metrics, recent
+    /// events and optionally log tail of the node running with that config, into a gzipped
+    /// tarball.
+    Diagnostics {
+        /// Path to configuration file of the running node.
+        config: PathBuf,
+
+        /// Path of the bundle to create, e.g. 'diagnostics.tar.gz'.
+        output: PathBuf,
+
+        #[structopt(
+            short = "C",
+            long,
+            env = "NODE_CONFIG",
+            use_delimiter(true),
+            value_delimiter(";")
+        )]
+        /// Overrides and extensions for configuration file entries in the form
+        /// <SECTION>.<KEY>=<VALUE>, as passed to the running node.
+        config_ext: Vec<ConfigExt>,
+
+        /// Path of the node's log file, the most recent lines of which are included.
+        #[structopt(long)]
+        log_file: Option<PathBuf>,
+
+        /// Maximum number of lines to include from the log file.
+        #[structopt(long, default_value = "1000")]
+        log_lines: usize,
+
+        /// Maximum number of the most recent events to include from each event stream.
+        #[structopt(long, default_value = "100")]
+        event_count: usize,
+    },
+    /// Export or import the node's storage as a portable chain archive.
+    Storage {
+        #[structopt(subcommand)]
+        command: StorageCommand,
+    },
 }
 
-#[derive(Debug)]
+/// Subcommands operating on the node's storage.
+#[derive(Debug, StructOpt)]
+pub enum StorageCommand {
+    /// Export the blocks up to the given height, along with their deploys, execution results and
+    /// the global state at that height, to a chain archive.
+    ///
+    /// Must only be run while the node is stopped.
+    Export {
+        /// Path to configuration file.
+        config: PathBuf,
+
+        /// Path of the directory to write the archive to, which must be empty if it exists.
+        #[structopt(long)]
+        output: PathBuf,
+
+        /// Height of the highest block to export.
+        #[structopt(long)]
+        up_to_height: u64,
+    },
+    /// Verify a chain archive, then import it into the empty storage directory of the node.
+    Import {
+        /// Path to configuration file.
+        config: PathBuf,
+
+        /// Path of the directory holding the archive.
+        #[structopt(long)]
+        input: PathBuf,
+    },
+}
+
+#[derive(Clone, Debug)]
 /// Command line extension to be applied to TOML-based config file values.
 pub struct ConfigExt {
     section: String,
@@ -142,12 +242,62 @@ impl Cli {
     /// Executes selected CLI command.
     pub async fn run(self) -> anyhow::Result<i32> {
         match self {
-            Cli::Validator { config, config_ext } => {
+            Cli::Validator {
+                config,
+                check_config,
+                config_ext,
+            } => {
+                let config_table = Self::load_config_table(&config, config_ext.clone())?;
+                if check_config {
+                    let result = casper_node::deserialize_config::<participating::Config>(
+                        config_table.value().clone(),
+                    )
+                    .and_then(|participating_config| {
+                        participating_config.validate(config_table.dir())
+                    });
+                    return match result {
+                        Ok(()) => {
+                            println!("{} is valid", config.display());
+                            Ok(ExitCode::Success as i32)
+                        }
+                        Err(error) => {
+                            eprintln!("{}: {}", config.display(), error);
+                            Ok(ExitCode::Abort as i32)
+                        }
+                    };
+                }
+
                 // Setup UNIX signal hooks.
                 setup_signal_hooks();
 
-                let validator_config = Self::init(&config, config_ext)?;
-                info!(version = %casper_node::VERSION_STRING.as_str(), "node starting up");
+                let validator_config = Self::init_with_config_table(&config_table)?;
+                tokio::spawn(Self::reload_log_filter_on_request(
+                    config.clone(),
+                    config_ext,
+                ));
+                // Fail before any component is constructed if the config doesn't make sense.
+                validator_config
+                    .value()
+                    .validate(validator_config.dir())
+                    .with_context(|| config.display().to_string())?;
+                let build_info = &*casper_node::build_info::BUILD_INFO;
+                info!(
+                    version = %casper_node::VERSION_STRING.as_str(),
+                    git_sha = %build_info.git_sha,
+                    build_timestamp = %build_info.build_timestamp,
+                    profile = %build_info.profile,
+                    rustc_version = %build_info.rustc_version,
+                    "node starting up"
+                );
+                let config_overrides = validator_config.value().overrides();
+                if config_overrides.is_empty() {
+                    info!("no config settings overridden");
+                } else {
+                    info!(
+                        "overridden config settings: {}",
+                        config_overrides.iter().join("; ")
+                    );
+                }
 
                 let pidfile_outcome = {
                     // Determine storage directory to store pidfile in.
@@ -208,7 +358,10 @@ impl Cli {
                 // initializer2_runner.run(&mut rng).await;
 
                 match initializer_runner.run(&mut rng).await {
-                    ReactorExit::ProcessShouldExit(exit_code) => return Ok(exit_code as i32),
+                    ReactorExit::ProcessShouldExit(exit_code) => {
+                        initializer_runner.shutdown(exit_code).await;
+                        return Ok(exit_code as i32);
+                    }
                     ReactorExit::ProcessShouldContinue => info!("finished initialization"),
                 }
 
@@ -224,7 +377,10 @@ impl Cli {
                 )
                 .await?;
                 match joiner_runner.run(&mut rng).await {
-                    ReactorExit::ProcessShouldExit(exit_code) => return Ok(exit_code as i32),
+                    ReactorExit::ProcessShouldExit(exit_code) => {
+                        joiner_runner.shutdown(exit_code).await;
+                        return Ok(exit_code as i32);
+                    }
                     ReactorExit::ProcessShouldContinue => info!("finished joining"),
                 }
 
@@ -236,9 +392,13 @@ impl Cli {
                         .await?;
 
                 match validator_runner.run(&mut rng).await {
-                    ReactorExit::ProcessShouldExit(exit_code) => Ok(exit_code as i32),
+                    ReactorExit::ProcessShouldExit(exit_code) => {
+                        validator_runner.shutdown(exit_code).await;
+                        Ok(exit_code as i32)
+                    }
                     reactor_exit => {
                         error!("validator should not exit with {:?}", reactor_exit);
+                        validator_runner.shutdown(ExitCode::Abort).await;
                         Ok(ExitCode::Abort as i32)
                     }
                 }
@@ -281,6 +441,114 @@ impl Cli {
                 casper_node::migrate_data(WithDir::new(old_root, old_config), new_config)?;
                 Ok(ExitCode::Success as i32)
             }
+            Cli::ClearPeerBlocklist { config } => {
+                let validator_config = Self::init(&config, vec![])?;
+
+                // The blocklist is saved in the storage directory.
+                let storage_config = validator_config.map_ref(|cfg| cfg.storage.clone());
+                let root = storage_config.with_dir(storage_config.value().path.clone());
+
+                if casper_node::clear_saved_peer_blocklist(&root)
+                    .context("could not clear saved peer blocklist")?
+                {
+                    info!("cleared saved peer blocklist");
+                } else {
+                    info!("no saved peer blocklist to clear");
+                }
+                Ok(ExitCode::Success as i32)
+            }
+            Cli::CheckChainspec { path } => {
+                let chainspec = Chainspec::try_from(path.as_path())
+                    .context("could not load chainspec")
+                    .with_context(|| path.display().to_string())?;
+
+                match chainspec.validate() {
+                    Ok(()) => {
+                        println!("{} is valid", path.display());
+                        Ok(ExitCode::Success as i32)
+                    }
+                    Err(errors) => {
+                        eprintln!("{} has {} problem(s):", path.display(), errors.len());
+                        for error in errors {
+                            eprintln!("  {}", error);
+                        }
+                        Ok(ExitCode::Abort as i32)
+                    }
+                }
+            }
+            Cli::Diagnostics {
+                config,
+                output,
+                config_ext,
+                log_file,
+                log_lines,
+                event_count,
+            } => {
+                let config_table = Self::load_config_table(&config, config_ext)?;
+                Self::init_with_config_table(&config_table)?;
+
+                let options = DiagnosticsOptions {
+                    event_count,
+                    log_file,
+                    log_lines,
+                };
+                let manifest = casper_node::create_diagnostics_bundle(
+                    WithDir::new(config_table.dir(), config_table.value()),
+                    &options,
+                    &output,
+                )
+                .await
+                .context("could not create diagnostics bundle")?;
+
+                println!(
+                    "wrote {} with {} file(s), {} setting(s) redacted",
+                    output.display(),
+                    manifest.files.len() + 1,
+                    manifest.redacted_settings.len()
+                );
+                if !manifest.failures.is_empty() {
+                    eprintln!("could not collect {} source(s):", manifest.failures.len());
+                    for (source, error) in &manifest.failures {
+                        eprintln!("  {}: {}", source, error);
+                    }
+                }
+                Ok(ExitCode::Success as i32)
+            }
+            Cli::Storage {
+                command:
+                    StorageCommand::Export {
+                        config,
+                        output,
+                        up_to_height,
+                    },
+            } => {
+                let validator_config = Self::init(&config, vec![])?;
+                let manifest =
+                    casper_node::export_chain_archive(&validator_config, &output, up_to_height)
+                        .context("could not export chain archive")?;
+                println!(
+                    "wrote {} with {} chunk(s) up to block {} at height {}",
+                    output.display(),
+                    manifest.chunks.len(),
+                    manifest.highest_block_hash,
+                    manifest.highest_block_height
+                );
+                Ok(ExitCode::Success as i32)
+            }
+            Cli::Storage {
+                command: StorageCommand::Import { config, input },
+            } => {
+                let validator_config = Self::init(&config, vec![])?;
+                let manifest = casper_node::import_chain_archive(&validator_config, &input)
+                    .context("could not import chain archive")?;
+                println!(
+                    "imported {} up to block {} at height {}",
+                    input.display(),
+                    manifest.highest_block_hash,
+                    manifest.highest_block_height
+                );
+                Ok(ExitCode::Success as i32)
+            }
         }
     }
 
@@ -289,6 +557,15 @@ impl Cli {
         config: &Path,
         config_ext: Vec<ConfigExt>,
     ) -> anyhow::Result<WithDir<participating::Config>> {
+        let config_table = Self::load_config_table(config, config_ext)?;
+        Self::init_with_config_table(&config_table)
+    }
+
+    /// Reads the config file as a TOML table, applying any command line overrides.
+    fn load_config_table(
+        config: &Path,
+        config_ext: Vec<ConfigExt>,
+    ) -> anyhow::Result<WithDir<Value>> {
         // Determine the parent directory of the configuration file, if any.
         // Otherwise, we default to `/`.
         let root = config
@@ -310,10 +587,38 @@ impl Cli {
             item.update_toml_table(&mut config_table)?;
         }
 
+        Ok(WithDir::new(root, config_table))
+    }
+
+    /// Reloads the log filter from the config file, applying any command line overrides, whenever
+    /// `SIGHUP` is received.
+    async fn reload_log_filter_on_request(config: PathBuf, config_ext: Vec<ConfigExt>) {
+        loop {
+            time::sleep(LOG_FILTER_RELOAD_CHECK_INTERVAL).await;
+            if !LOG_FILTER_RELOAD_REQUESTED.swap(false, Ordering::SeqCst) {
+                continue;
+            }
+            let result =
+                Self::load_config_table(&config, config_ext.clone()).and_then(|config_table| {
+                    let participating_config: participating::Config =
+                        casper_node::deserialize_config(config_table.value().clone())?;
+                    logging::reload_filter(&participating_config.logging)
+                });
+            if let Err(error) = result {
+                error!(%error, "failed to reload log filter; keeping previous filter");
+            }
+        }
+    }
+
+    /// Parses the config table for the current version of casper-node, and initializes logging.
+    fn init_with_config_table(
+        config_table: &WithDir<Value>,
+    ) -> anyhow::Result<WithDir<participating::Config>> {
         // Create participating config, including any overridden values.
-        let participating_config: participating::Config = config_table.try_into()?;
+        let participating_config: participating::Config =
+            casper_node::deserialize_config(config_table.value().clone())?;
         logging::init_with_config(&participating_config.logging)?;
 
-        Ok(WithDir::new(root, participating_config))
+        Ok(WithDir::new(config_table.dir(), participating_config))
     }
 }
@@ -9,6 +9,7 @@ pub mod config;
 use std::{
     panic::{self, PanicInfo},
     process,
+    time::Duration,
 };
 
 use backtrace::Backtrace;
@@ -16,13 +17,19 @@ use structopt::StructOpt;
 use tokio::runtime::Builder;
 use tracing::info;
 
-use casper_node::MAX_THREAD_COUNT;
+use casper_node::{reactor, MAX_THREAD_COUNT};
 
 use cli::Cli;
 
+/// The maximum time to wait for tasks still running on the tokio runtime to finish once the node
+/// has completed its shutdown sequence.
+const RUNTIME_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
 /// Aborting panic hook.
 ///
-/// Will exit the application using `abort` when an error occurs. Always shows a backtrace.
+/// Will exit the application using `abort` when an error occurs, unless the panic is going to be
+/// caught by the reactor's panic boundary around a component, which then handles it according to
+/// the component's failure policy. Always shows a backtrace.
 fn panic_hook(info: &PanicInfo) {
     let backtrace = Backtrace::new();
 
@@ -38,8 +45,10 @@ fn panic_hook(info: &PanicInfo) {
         eprintln!("{}", info);
     }
 
-    // Abort after a panic, even if only a worker thread panicked.
-    process::abort()
+    // Abort after a panic, even if only a worker thread panicked, unless the reactor handles it.
+    if !reactor::is_within_panic_boundary() {
+        process::abort()
+    }
 }
 
 /// Main function.
@@ -60,7 +69,11 @@ fn main() -> anyhow::Result<()> {
         // Parse CLI args and run selected subcommand.
         let opts = Cli::from_args();
 
-        runtime.block_on(async { opts.run().await })?
+        let result = runtime.block_on(async { opts.run().await });
+
+        // Don't let a wedged task prevent the process from exiting.
+        runtime.shutdown_timeout(RUNTIME_SHUTDOWN_TIMEOUT);
+        result?
     };
 
     info!(%exit_code, "exiting casper-node");
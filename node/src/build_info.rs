@@ -0,0 +1,36 @@
+//! Information about how the node was built.
+//!
+//! The values are gathered at compile time by the build script, and are reported in the node's
+//! first log line, its status and the client's `get-node-version` subcommand.
+
+// TODO - remove once schemars stops causing warning.
+#![allow(clippy::field_reassign_with_default)]
+
+use once_cell::sync::Lazy;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Information about how this node was built.
+pub static BUILD_INFO: Lazy<BuildInfo> = Lazy::new(|| BuildInfo {
+    crate_version: env!("CARGO_PKG_VERSION").to_string(),
+    git_sha: env!("VERGEN_SHA_SHORT").to_string(),
+    build_timestamp: env!("VERGEN_BUILD_TIMESTAMP").to_string(),
+    profile: env!("NODE_BUILD_PROFILE").to_string(),
+    rustc_version: env!("NODE_RUSTC_VERSION").to_string(),
+});
+
+/// Information about how a node was built.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct BuildInfo {
+    /// The version of the `casper-node` crate.
+    pub crate_version: String,
+    /// The short hash of the git commit from which the node was built.
+    pub git_sha: String,
+    /// The time at which the node was built.
+    pub build_timestamp: String,
+    /// The cargo profile with which the node was built, e.g. "release".
+    pub profile: String,
+    /// The version of the Rust compiler with which the node was built.
+    pub rustc_version: String,
+}
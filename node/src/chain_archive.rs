@@ -0,0 +1,1171 @@
+//! Export and import of a node's storage as a portable chain archive.
+//!
+//! A chain archive is a directory holding the blocks from genesis up to a given height, along with
+//! their deploys, execution results, transfers and finality signatures, and the global state tries
+//! reachable from the state root hash of the highest block.  It allows a node to be bootstrapped
+//! from an archive rather than by syncing from peers.
+//!
+//! The archive consists of:
+//!
+//! * `manifest.json`: the format version, the highest block and its state root hash, and the name,
+//!   kind, number of entries and checksum of every chunk, in order, and
+//! * `blocks-<index>.chunk` and `tries-<index>.chunk`: the chunks holding the blocks in ascending
+//!   order of height and the tries in breadth-first order from the state root respectively.
+//!
+//! Every chunk starts with the format version byte and a byte identifying its kind, followed by its
+//! entries, each prefixed by its length as a little-endian `u32`.  A block entry is the bincode
+//! encoding of an [`ArchivedBlock`], a trie entry the bytesrepr encoding of the trie, which is also
+//! what its key is the hash of.  A chunk's checksum is the hash of the whole file.
+//!
+//! The manifest is written last, so an interrupted export leaves no usable archive.
+//!
+//! # Import
+//!
+//! An archive is only imported into an empty storage directory.  Before anything is written, the
+//! checksum of every chunk is verified, along with the chain of blocks: their heights must run
+//! contiguously from genesis, each block's hash must match its header and body, each block's parent
+//! hash must be the hash of the block before it, and the last block must be the one named in the
+//! manifest.  The tries are then written, each of which must be a descendant of the state root
+//! still missing from global state, and the import fails unless global state is complete once all
+//! are written.
+//!
+//! The import is performed in a staging directory next to the storage directory, whose contents are
+//! only moved into the storage directory once the import has succeeded.
+
+use std::{
+    collections::{BTreeSet, HashSet, VecDeque},
+    convert::TryFrom,
+    ffi::OsString,
+    fmt::{self, Display, Formatter},
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use prometheus::Registry;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::info;
+
+use casper_execution_engine::{
+    core::engine_state,
+    shared::{newtypes::Blake2bHash, stored_value::StoredValue},
+    storage::trie::Trie,
+};
+use casper_types::{
+    bytesrepr::{self, ToBytes},
+    Key, ProtocolVersion, Transfer,
+};
+
+use crate::{
+    components::{
+        contract_runtime::{self, ContractRuntime},
+        storage::{self, Storage},
+    },
+    crypto::hash::{self, Digest},
+    reactor::participating::Config,
+    types::{Block, BlockHash, BlockHeader, BlockSignatures, Deploy, DeployHash, DeployMetadata},
+    utils::WithDir,
+};
+
+/// The version of the archive format, written to the manifest and the start of every chunk.
+const FORMAT_VERSION: u8 = 1;
+/// The name of the manifest file in the archive.
+const MANIFEST_FILE: &str = "manifest.json";
+/// The size of a chunk's header: the format version byte and the chunk kind byte.
+const CHUNK_HEADER_LENGTH: usize = 2;
+/// The size of the length prefix of each entry of a chunk.
+const ENTRY_LENGTH_PREFIX_LENGTH: usize = 4;
+/// The size beyond which no further entries are added to a chunk.
+const DEFAULT_MAX_CHUNK_SIZE: usize = 64 * 1024 * 1024;
+/// The suffix of the staging directory an archive is imported into.
+const STAGING_DIR_SUFFIX: &str = ".import";
+
+/// Error exporting or importing a chain archive.
+#[derive(Debug, Error)]
+pub enum Error {
+    /// Error opening or accessing storage.
+    #[error("storage error: {0}")]
+    Storage(#[from] storage::Error),
+
+    /// Error opening global state.
+    #[error("error opening global state: {0}")]
+    OpenGlobalState(#[from] contract_runtime::ConfigError),
+
+    /// Error accessing global state.
+    #[error("global state error: {0}")]
+    GlobalState(engine_state::Error),
+
+    /// Error reading a file or directory.
+    #[error("error reading {path}: {error}")]
+    Read {
+        /// The path of the file or directory.
+        path: String,
+        /// The IO error.
+        error: io::Error,
+    },
+
+    /// Error writing a file or directory.
+    #[error("error writing {path}: {error}")]
+    Write {
+        /// The path of the file or directory.
+        path: String,
+        /// The IO error.
+        error: io::Error,
+    },
+
+    /// The directory to export to is not empty.
+    #[error("{path} is not empty, so no archive was exported")]
+    OutputNotEmpty {
+        /// The directory to export to.
+        path: String,
+    },
+
+    /// The storage directory to import into is not empty.
+    #[error("storage directory {path} is not empty, so no archive was imported")]
+    StorageNotEmpty {
+        /// The storage directory.
+        path: String,
+    },
+
+    /// The staging directory of an import exists already.
+    #[error("staging directory {path} exists, remove it if no other import is running")]
+    StagingDirExists {
+        /// The staging directory.
+        path: String,
+    },
+
+    /// A block to export is not in storage.
+    #[error("block at height {height} not found in storage")]
+    MissingBlock {
+        /// The height of the block.
+        height: u64,
+    },
+
+    /// A deploy of a block to export is not in storage.
+    #[error("deploy {deploy_hash} of block at height {height} not found in storage")]
+    MissingDeploy {
+        /// The height of the block.
+        height: u64,
+        /// The hash of the deploy.
+        deploy_hash: DeployHash,
+    },
+
+    /// A trie to export is not in global state.
+    #[error("trie {trie_key} not found in global state")]
+    MissingTrie {
+        /// The key of the trie.
+        trie_key: Blake2bHash,
+    },
+
+    /// Error serializing an entry of a chunk.
+    #[error("error serializing {0}")]
+    Serialize(String),
+
+    /// Error encoding or decoding the manifest.
+    #[error("invalid manifest {path}: {error}")]
+    Manifest {
+        /// The path of the manifest.
+        path: String,
+        /// The JSON error.
+        error: serde_json::Error,
+    },
+
+    /// The archive is of a format version this node doesn't support.
+    #[error(
+        "unsupported archive format version {version}, expected {}",
+        FORMAT_VERSION
+    )]
+    UnsupportedVersion {
+        /// The format version of the archive.
+        version: u8,
+    },
+
+    /// The checksum of a chunk doesn't match the manifest.
+    #[error("checksum of chunk {file} doesn't match the manifest")]
+    ChecksumMismatch {
+        /// The name of the chunk.
+        file: String,
+    },
+
+    /// A chunk could not be decoded.
+    #[error("malformed chunk {file}: {reason}")]
+    MalformedChunk {
+        /// The name of the chunk.
+        file: String,
+        /// A description of the problem.
+        reason: String,
+    },
+
+    /// The archived blocks don't form a valid chain.
+    #[error("invalid chain at height {height}: {reason}")]
+    InvalidChain {
+        /// The height at which the problem was found.
+        height: u64,
+        /// A description of the problem.
+        reason: String,
+    },
+
+    /// A trie is not a missing descendant of the state root.
+    #[error("unexpected trie {trie_key}")]
+    UnexpectedTrie {
+        /// The key of the trie.
+        trie_key: Blake2bHash,
+    },
+
+    /// Global state is incomplete once all archived tries are written.
+    #[error("global state is missing {count} trie(s) after importing all archived tries")]
+    IncompleteGlobalState {
+        /// The number of tries missing.
+        count: usize,
+    },
+}
+
+/// The kind of the entries of a chunk.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChunkKind {
+    /// Blocks along with their deploys, execution results, transfers and finality signatures.
+    Blocks,
+    /// Global state tries.
+    Tries,
+}
+
+impl ChunkKind {
+    /// Returns the byte identifying the kind in a chunk's header.
+    fn tag(self) -> u8 {
+        match self {
+            ChunkKind::Blocks => 0,
+            ChunkKind::Tries => 1,
+        }
+    }
+}
+
+impl Display for ChunkKind {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ChunkKind::Blocks => formatter.write_str("blocks"),
+            ChunkKind::Tries => formatter.write_str("tries"),
+        }
+    }
+}
+
+/// A chunk of an archive, as listed in its manifest.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct ChunkInfo {
+    /// The name of the chunk's file in the archive.
+    pub file: String,
+    /// The kind of the chunk's entries.
+    pub kind: ChunkKind,
+    /// The number of entries in the chunk.
+    pub entry_count: u64,
+    /// The hash of the chunk's file.
+    pub checksum: Digest,
+}
+
+/// The contents of a chain archive, written to it as `manifest.json`.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct ChainArchiveManifest {
+    /// The version of the archive format.
+    pub version: u8,
+    /// The height of the highest block in the archive.
+    pub highest_block_height: u64,
+    /// The hash of the highest block in the archive.
+    pub highest_block_hash: BlockHash,
+    /// The state root hash of the highest block, from which all archived tries are reachable.
+    pub state_root_hash: Digest,
+    /// The chunks of the archive, in order.
+    pub chunks: Vec<ChunkInfo>,
+}
+
+/// A block along with everything stored for it, as held in a chain archive.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct ArchivedBlock {
+    /// The block.
+    pub(crate) block: Block,
+    /// The block's finality signatures, if any are stored.
+    pub(crate) signatures: Option<BlockSignatures>,
+    /// The block's deploys and transfers.
+    pub(crate) deploys: Vec<Deploy>,
+    /// The metadata, i.e. execution results, of the block's deploys and transfers.
+    pub(crate) deploy_metadata: Vec<(DeployHash, DeployMetadata)>,
+    /// The transfers executed in the block, if stored.
+    pub(crate) transfers: Option<Vec<Transfer>>,
+}
+
+/// Exports the blocks of the node's storage up to `up_to_height`, along with the global state at
+/// that height, to a chain archive in the directory `output`.
+///
+/// Must only be run while the node is stopped.  Returns the archive's manifest.
+pub fn export_chain_archive(
+    config: &WithDir<Config>,
+    output: &Path,
+    up_to_height: u64,
+) -> Result<ChainArchiveManifest, Error> {
+    export(
+        &config.map_ref(|config| config.storage.clone()),
+        &config.value().contract_runtime,
+        output,
+        up_to_height,
+        DEFAULT_MAX_CHUNK_SIZE,
+    )
+}
+
+/// Imports the chain archive in the directory `input` into the node's storage directory, which
+/// must be empty.
+///
+/// Returns the archive's manifest.
+pub fn import_chain_archive(
+    config: &WithDir<Config>,
+    input: &Path,
+) -> Result<ChainArchiveManifest, Error> {
+    import(
+        &config.map_ref(|config| config.storage.clone()),
+        &config.value().contract_runtime,
+        input,
+    )
+}
+
+fn export(
+    storage_config: &WithDir<storage::Config>,
+    contract_runtime_config: &contract_runtime::Config,
+    output: &Path,
+    up_to_height: u64,
+    max_chunk_size: usize,
+) -> Result<ChainArchiveManifest, Error> {
+    if !is_missing_or_empty(output)? {
+        return Err(Error::OutputNotEmpty {
+            path: output.display().to_string(),
+        });
+    }
+    fs::create_dir_all(output).map_err(|error| Error::Write {
+        path: output.display().to_string(),
+        error,
+    })?;
+
+    let storage = Storage::new(
+        storage_config,
+        None,
+        // Only used for a hard reset, which isn't requested.
+        ProtocolVersion::default(),
+        false,
+        &Registry::new(),
+    )?;
+
+    info!(%up_to_height, output = %output.display(), "exporting blocks");
+    let mut block_writer = ChunkWriter::new(output, ChunkKind::Blocks, max_chunk_size);
+    let mut highest_block_header = None;
+    for height in 0..=up_to_height {
+        let archived_block = storage
+            .read_archived_block(height)?
+            .ok_or(Error::MissingBlock { height })?;
+        let stored_deploy_hashes: HashSet<_> = archived_block
+            .deploys
+            .iter()
+            .map(|deploy| *deploy.id())
+            .collect();
+        let block = &archived_block.block;
+        if let Some(deploy_hash) = block
+            .deploy_hashes()
+            .iter()
+            .chain(block.transfer_hashes())
+            .find(|deploy_hash| !stored_deploy_hashes.contains(*deploy_hash))
+        {
+            return Err(Error::MissingDeploy {
+                height,
+                deploy_hash: *deploy_hash,
+            });
+        }
+        let entry = bincode::serialize(&archived_block)
+            .map_err(|error| Error::Serialize(format!("block at height {}: {}", height, error)))?;
+        block_writer.write_entry(&entry)?;
+        highest_block_header = Some(archived_block.block.take_header());
+    }
+    let highest_block_header = highest_block_header.ok_or(Error::MissingBlock { height: 0 })?;
+    let mut chunks = block_writer.finish()?;
+
+    let contract_runtime = ContractRuntime::new(
+        *highest_block_header.state_root_hash(),
+        Some(&highest_block_header),
+        highest_block_header.protocol_version(),
+        storage_config.clone(),
+        contract_runtime_config,
+        &Registry::new(),
+    )?;
+    let state_root = Blake2bHash::from(*highest_block_header.state_root_hash());
+    info!(%state_root, "exporting global state");
+    let mut trie_writer = ChunkWriter::new(output, ChunkKind::Tries, max_chunk_size);
+    let mut queued = HashSet::new();
+    let mut queue = VecDeque::new();
+    queued.insert(state_root);
+    queue.push_back(state_root);
+    while let Some(trie_key) = queue.pop_front() {
+        let trie = contract_runtime
+            .read_trie(trie_key)
+            .map_err(Error::GlobalState)?
+            .ok_or(Error::MissingTrie { trie_key })?;
+        for child in trie_children(&trie) {
+            if queued.insert(child) {
+                queue.push_back(child);
+            }
+        }
+        let entry = trie
+            .to_bytes()
+            .map_err(|error| Error::Serialize(format!("trie {}: {}", trie_key, error)))?;
+        trie_writer.write_entry(&entry)?;
+    }
+    chunks.extend(trie_writer.finish()?);
+
+    let manifest = ChainArchiveManifest {
+        version: FORMAT_VERSION,
+        highest_block_height: highest_block_header.height(),
+        highest_block_hash: highest_block_header.hash(),
+        state_root_hash: *highest_block_header.state_root_hash(),
+        chunks,
+    };
+    write_manifest(output, &manifest)?;
+    info!(
+        chunk_count = manifest.chunks.len(),
+        trie_count = queued.len(),
+        "exported chain archive"
+    );
+    Ok(manifest)
+}
+
+fn import(
+    storage_config: &WithDir<storage::Config>,
+    contract_runtime_config: &contract_runtime::Config,
+    input: &Path,
+) -> Result<ChainArchiveManifest, Error> {
+    let root = storage_config.with_dir(storage_config.value().path.clone());
+    if !is_missing_or_empty(&root)? {
+        return Err(Error::StorageNotEmpty {
+            path: root.display().to_string(),
+        });
+    }
+
+    let manifest = read_manifest(input)?;
+    info!(input = %input.display(), "verifying chain archive");
+    let highest_block_header = verify_archive(input, &manifest)?;
+
+    let staging_dir = staging_dir(&root);
+    if staging_dir.exists() {
+        return Err(Error::StagingDirExists {
+            path: staging_dir.display().to_string(),
+        });
+    }
+    let mut staging_config = storage_config.value().clone();
+    staging_config.path = staging_dir.clone();
+    let staging_config = WithDir::new(storage_config.dir(), staging_config);
+
+    info!(staging_dir = %staging_dir.display(), "importing chain archive");
+    if let Err(error) = populate(
+        &staging_config,
+        contract_runtime_config,
+        input,
+        &manifest,
+        &highest_block_header,
+    ) {
+        // Nothing but the staging directory has been written, so it's the only thing to remove.
+        let _ = fs::remove_dir_all(&staging_dir);
+        return Err(error);
+    }
+
+    fs::create_dir_all(&root).map_err(|error| Error::Write {
+        path: root.display().to_string(),
+        error,
+    })?;
+    for entry in read_dir(&staging_dir)? {
+        let destination = root.join(entry.file_name());
+        fs::rename(entry.path(), &destination).map_err(|error| Error::Write {
+            path: destination.display().to_string(),
+            error,
+        })?;
+    }
+    fs::remove_dir(&staging_dir).map_err(|error| Error::Write {
+        path: staging_dir.display().to_string(),
+        error,
+    })?;
+    info!(
+        highest_block_height = manifest.highest_block_height,
+        "imported chain archive"
+    );
+    Ok(manifest)
+}
+
+/// Verifies the checksums of all chunks and the chain of archived blocks, returning the header of
+/// the highest block.
+fn verify_archive(input: &Path, manifest: &ChainArchiveManifest) -> Result<BlockHeader, Error> {
+    let mut previous_header: Option<BlockHeader> = None;
+    for chunk in &manifest.chunks {
+        let bytes = read_chunk(input, chunk)?;
+        if chunk.kind != ChunkKind::Blocks {
+            continue;
+        }
+        for entry in chunk_entries(chunk, &bytes)? {
+            let archived_block = decode_archived_block(chunk, entry)?;
+            verify_block(previous_header.as_ref(), &archived_block)?;
+            previous_header = Some(archived_block.block.take_header());
+        }
+    }
+
+    let highest_block_header = previous_header.ok_or(Error::InvalidChain {
+        height: 0,
+        reason: "archive holds no blocks".to_string(),
+    })?;
+    let height = highest_block_header.height();
+    if height != manifest.highest_block_height
+        || highest_block_header.hash() != manifest.highest_block_hash
+    {
+        return Err(Error::InvalidChain {
+            height,
+            reason: format!(
+                "highest block is {}, but the manifest names {} at height {}",
+                highest_block_header.hash(),
+                manifest.highest_block_hash,
+                manifest.highest_block_height
+            ),
+        });
+    }
+    if *highest_block_header.state_root_hash() != manifest.state_root_hash {
+        return Err(Error::InvalidChain {
+            height,
+            reason: "state root hash of highest block doesn't match the manifest".to_string(),
+        });
+    }
+    Ok(highest_block_header)
+}
+
+/// Verifies that `archived_block` is consistent in itself and follows the block with header
+/// `previous_header`, or is the genesis block if there is none.
+fn verify_block(
+    previous_header: Option<&BlockHeader>,
+    archived_block: &ArchivedBlock,
+) -> Result<(), Error> {
+    let block = &archived_block.block;
+    let height = block.height();
+    let invalid = |reason: String| Error::InvalidChain { height, reason };
+
+    let expected_height = previous_header.map_or(0, |header| header.height() + 1);
+    if height != expected_height {
+        return Err(invalid(format!(
+            "expected block at height {}",
+            expected_height
+        )));
+    }
+    block.verify().map_err(|error| invalid(error.to_string()))?;
+    if let Some(previous_header) = previous_header {
+        if *block.header().parent_hash() != previous_header.hash() {
+            return Err(invalid(format!(
+                "parent hash {} is not the hash of the previous block {}",
+                block.header().parent_hash(),
+                previous_header.hash()
+            )));
+        }
+    }
+
+    if let Some(signatures) = &archived_block.signatures {
+        if signatures.block_hash != *block.hash() {
+            return Err(invalid(
+                "finality signatures are for another block".to_string(),
+            ));
+        }
+        signatures
+            .verify()
+            .map_err(|error| invalid(format!("invalid finality signature: {}", error)))?;
+    }
+
+    let expected_deploy_hashes: BTreeSet<_> = block
+        .deploy_hashes()
+        .iter()
+        .chain(block.transfer_hashes())
+        .collect();
+    let deploy_hashes: BTreeSet<_> = archived_block
+        .deploys
+        .iter()
+        .map(|deploy| deploy.id())
+        .collect();
+    if deploy_hashes != expected_deploy_hashes {
+        return Err(invalid(
+            "archived deploys don't match the block's deploys".to_string(),
+        ));
+    }
+    if let Some((deploy_hash, _)) = archived_block
+        .deploy_metadata
+        .iter()
+        .find(|(deploy_hash, _)| !expected_deploy_hashes.contains(&deploy_hash))
+    {
+        return Err(invalid(format!(
+            "execution results for deploy {} not in the block",
+            deploy_hash
+        )));
+    }
+    Ok(())
+}
+
+/// Writes the contents of a verified archive to a fresh storage directory.
+fn populate(
+    storage_config: &WithDir<storage::Config>,
+    contract_runtime_config: &contract_runtime::Config,
+    input: &Path,
+    manifest: &ChainArchiveManifest,
+    highest_block_header: &BlockHeader,
+) -> Result<(), Error> {
+    let mut storage = Storage::new(
+        storage_config,
+        None,
+        highest_block_header.protocol_version(),
+        false,
+        &Registry::new(),
+    )?;
+    let contract_runtime = ContractRuntime::new(
+        *highest_block_header.state_root_hash(),
+        Some(highest_block_header),
+        highest_block_header.protocol_version(),
+        storage_config.clone(),
+        contract_runtime_config,
+        &Registry::new(),
+    )?;
+
+    // The tries are written parent first, so each must be among the descendants found missing so
+    // far, and global state is complete once none remain.
+    let mut missing_trie_keys = HashSet::new();
+    missing_trie_keys.insert(Blake2bHash::from(manifest.state_root_hash));
+    for chunk in &manifest.chunks {
+        // The chunk is read again rather than held from verification, so its checksum is verified
+        // again too.
+        let bytes = read_chunk(input, chunk)?;
+        for entry in chunk_entries(chunk, &bytes)? {
+            match chunk.kind {
+                ChunkKind::Blocks => {
+                    storage.write_archived_block(&decode_archived_block(chunk, entry)?)?;
+                }
+                ChunkKind::Tries => {
+                    let trie_key = Blake2bHash::new(entry);
+                    if !missing_trie_keys.remove(&trie_key) {
+                        return Err(Error::UnexpectedTrie { trie_key });
+                    }
+                    let trie: Trie<Key, StoredValue> = bytesrepr::deserialize(entry.to_vec())
+                        .map_err(|error| {
+                            malformed(chunk, format!("invalid trie {}: {}", trie_key, error))
+                        })?;
+                    let missing_descendants = contract_runtime
+                        .put_trie(&trie)
+                        .map_err(Error::GlobalState)?;
+                    missing_trie_keys.extend(missing_descendants);
+                }
+            }
+        }
+    }
+    if !missing_trie_keys.is_empty() {
+        return Err(Error::IncompleteGlobalState {
+            count: missing_trie_keys.len(),
+        });
+    }
+    Ok(())
+}
+
+/// Writes entries of a single kind to a sequence of chunks, starting a new chunk once the current
+/// one reaches the maximum size.
+struct ChunkWriter<'a> {
+    dir: &'a Path,
+    kind: ChunkKind,
+    max_chunk_size: usize,
+    buffer: Vec<u8>,
+    entry_count: u64,
+    chunks: Vec<ChunkInfo>,
+}
+
+impl<'a> ChunkWriter<'a> {
+    fn new(dir: &'a Path, kind: ChunkKind, max_chunk_size: usize) -> Self {
+        ChunkWriter {
+            dir,
+            kind,
+            max_chunk_size,
+            buffer: chunk_header(kind).to_vec(),
+            entry_count: 0,
+            chunks: vec![],
+        }
+    }
+
+    /// Appends an entry to the current chunk, writing the chunk if it's full afterwards.
+    fn write_entry(&mut self, entry: &[u8]) -> Result<(), Error> {
+        let length = u32::try_from(entry.len()).map_err(|_| {
+            Error::Serialize(format!("{} entry of {} bytes", self.kind, entry.len()))
+        })?;
+        self.buffer.extend_from_slice(&length.to_le_bytes());
+        self.buffer.extend_from_slice(entry);
+        self.entry_count += 1;
+        if self.buffer.len() >= self.max_chunk_size {
+            self.write_chunk()?;
+        }
+        Ok(())
+    }
+
+    /// Writes the last chunk, if not empty, returning all chunks written.
+    fn finish(mut self) -> Result<Vec<ChunkInfo>, Error> {
+        if self.entry_count > 0 {
+            self.write_chunk()?;
+        }
+        Ok(self.chunks)
+    }
+
+    fn write_chunk(&mut self) -> Result<(), Error> {
+        let file = format!("{}-{:05}.chunk", self.kind, self.chunks.len());
+        let path = self.dir.join(&file);
+        fs::write(&path, &self.buffer).map_err(|error| Error::Write {
+            path: path.display().to_string(),
+            error,
+        })?;
+        self.chunks.push(ChunkInfo {
+            file,
+            kind: self.kind,
+            entry_count: self.entry_count,
+            checksum: hash::hash(&self.buffer),
+        });
+        self.buffer = chunk_header(self.kind).to_vec();
+        self.entry_count = 0;
+        Ok(())
+    }
+}
+
+fn chunk_header(kind: ChunkKind) -> [u8; CHUNK_HEADER_LENGTH] {
+    [FORMAT_VERSION, kind.tag()]
+}
+
+/// Reads a chunk, verifying its checksum and header.
+fn read_chunk(dir: &Path, chunk: &ChunkInfo) -> Result<Vec<u8>, Error> {
+    let path = dir.join(&chunk.file);
+    let bytes = fs::read(&path).map_err(|error| Error::Read {
+        path: path.display().to_string(),
+        error,
+    })?;
+    if hash::hash(&bytes) != chunk.checksum {
+        return Err(Error::ChecksumMismatch {
+            file: chunk.file.clone(),
+        });
+    }
+    match bytes.get(..CHUNK_HEADER_LENGTH) {
+        Some(&[version, _]) if version != FORMAT_VERSION => {
+            Err(Error::UnsupportedVersion { version })
+        }
+        Some(header) if header == chunk_header(chunk.kind) => Ok(bytes),
+        _ => Err(malformed(
+            chunk,
+            format!("invalid header for {} chunk", chunk.kind),
+        )),
+    }
+}
+
+/// Splits a chunk read via `read_chunk` into its entries.
+fn chunk_entries<'a>(chunk: &ChunkInfo, bytes: &'a [u8]) -> Result<Vec<&'a [u8]>, Error> {
+    let mut entries = vec![];
+    let mut remainder = &bytes[CHUNK_HEADER_LENGTH..];
+    while !remainder.is_empty() {
+        if remainder.len() < ENTRY_LENGTH_PREFIX_LENGTH {
+            return Err(malformed(chunk, "truncated entry length".to_string()));
+        }
+        let (length_bytes, rest) = remainder.split_at(ENTRY_LENGTH_PREFIX_LENGTH);
+        let mut length = [0; ENTRY_LENGTH_PREFIX_LENGTH];
+        length.copy_from_slice(length_bytes);
+        let length = u32::from_le_bytes(length) as usize;
+        if rest.len() < length {
+            return Err(malformed(chunk, "truncated entry".to_string()));
+        }
+        let (entry, rest) = rest.split_at(length);
+        entries.push(entry);
+        remainder = rest;
+    }
+    if entries.len() as u64 != chunk.entry_count {
+        return Err(malformed(
+            chunk,
+            format!(
+                "holds {} entries, but the manifest lists {}",
+                entries.len(),
+                chunk.entry_count
+            ),
+        ));
+    }
+    Ok(entries)
+}
+
+fn decode_archived_block(chunk: &ChunkInfo, entry: &[u8]) -> Result<ArchivedBlock, Error> {
+    bincode::deserialize(entry)
+        .map_err(|error| malformed(chunk, format!("invalid block: {}", error)))
+}
+
+fn malformed(chunk: &ChunkInfo, reason: String) -> Error {
+    Error::MalformedChunk {
+        file: chunk.file.clone(),
+        reason,
+    }
+}
+
+fn write_manifest(dir: &Path, manifest: &ChainArchiveManifest) -> Result<(), Error> {
+    let path = dir.join(MANIFEST_FILE);
+    let json = serde_json::to_vec_pretty(manifest).map_err(|error| Error::Manifest {
+        path: path.display().to_string(),
+        error,
+    })?;
+    fs::write(&path, json).map_err(|error| Error::Write {
+        path: path.display().to_string(),
+        error,
+    })
+}
+
+fn read_manifest(dir: &Path) -> Result<ChainArchiveManifest, Error> {
+    let path = dir.join(MANIFEST_FILE);
+    let json = fs::read(&path).map_err(|error| Error::Read {
+        path: path.display().to_string(),
+        error,
+    })?;
+    let manifest: ChainArchiveManifest =
+        serde_json::from_slice(&json).map_err(|error| Error::Manifest {
+            path: path.display().to_string(),
+            error,
+        })?;
+    if manifest.version != FORMAT_VERSION {
+        return Err(Error::UnsupportedVersion {
+            version: manifest.version,
+        });
+    }
+    Ok(manifest)
+}
+
+/// Returns the keys of the tries a trie points to.
+fn trie_children(trie: &Trie<Key, StoredValue>) -> Vec<Blake2bHash> {
+    match trie {
+        Trie::Leaf { .. } => vec![],
+        Trie::Node { pointer_block } => pointer_block
+            .to_indexed_pointers()
+            .map(|(_, pointer)| *pointer.hash())
+            .collect(),
+        Trie::Extension { pointer, .. } => vec![*pointer.hash()],
+    }
+}
+
+/// Returns the staging directory an archive is imported into before being moved to `root`.
+fn staging_dir(root: &Path) -> PathBuf {
+    let mut staging_dir = OsString::from(root.as_os_str());
+    staging_dir.push(STAGING_DIR_SUFFIX);
+    PathBuf::from(staging_dir)
+}
+
+fn is_missing_or_empty(dir: &Path) -> Result<bool, Error> {
+    match fs::read_dir(dir) {
+        Ok(mut entries) => Ok(entries.next().is_none()),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(true),
+        Err(error) => Err(Error::Read {
+            path: dir.display().to_string(),
+            error,
+        }),
+    }
+}
+
+fn read_dir(dir: &Path) -> Result<Vec<fs::DirEntry>, Error> {
+    let read_error = |error| Error::Read {
+        path: dir.display().to_string(),
+        error,
+    };
+    fs::read_dir(dir)
+        .map_err(read_error)?
+        .collect::<Result<_, _>>()
+        .map_err(read_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+    use tempfile::TempDir;
+
+    use casper_execution_engine::storage::trie::Pointer;
+    use casper_types::{CLValue, EraId, ExecutionResult, PublicKey, SecretKey};
+
+    use super::*;
+    use crate::{
+        crypto::AsymmetricKeyExt,
+        testing::TestRng,
+        types::{BlockPayload, FinalitySignature, FinalizedBlock, Timestamp},
+    };
+
+    const BLOCK_COUNT: u64 = 5;
+    const DEPLOYS_PER_BLOCK: usize = 2;
+    /// Small enough for every block to be written to a chunk of its own.
+    const MAX_CHUNK_SIZE: usize = 1024;
+
+    /// A storage directory, holding storage and global state, in a temporary directory.
+    struct Fixture {
+        storage_config: WithDir<storage::Config>,
+        contract_runtime_config: contract_runtime::Config,
+        _temp_dir: TempDir,
+    }
+
+    impl Fixture {
+        fn new() -> Self {
+            let (storage_config, temp_dir) = storage::Config::default_for_tests();
+            Fixture {
+                storage_config: WithDir::new(temp_dir.path(), storage_config),
+                contract_runtime_config: contract_runtime::Config::default(),
+                _temp_dir: temp_dir,
+            }
+        }
+
+        fn storage(&self) -> Storage {
+            Storage::new(
+                &self.storage_config,
+                None,
+                ProtocolVersion::V1_0_0,
+                false,
+                &Registry::new(),
+            )
+            .expect("should open storage")
+        }
+
+        fn contract_runtime(&self) -> ContractRuntime {
+            // Global state is kept within the storage directory, which only storage creates.
+            fs::create_dir_all(self.root()).expect("should create storage directory");
+            ContractRuntime::new(
+                Digest::default(),
+                None,
+                ProtocolVersion::V1_0_0,
+                self.storage_config.clone(),
+                &self.contract_runtime_config,
+                &Registry::new(),
+            )
+            .expect("should open global state")
+        }
+
+        fn root(&self) -> PathBuf {
+            self.storage_config
+                .with_dir(self.storage_config.value().path.clone())
+        }
+
+        fn export(&self, output: &Path, up_to_height: u64) -> Result<ChainArchiveManifest, Error> {
+            export(
+                &self.storage_config,
+                &self.contract_runtime_config,
+                output,
+                up_to_height,
+                MAX_CHUNK_SIZE,
+            )
+        }
+
+        fn import(&self, input: &Path) -> Result<ChainArchiveManifest, Error> {
+            import(&self.storage_config, &self.contract_runtime_config, input)
+        }
+
+        /// Populates the global state with a small trie, then storage with a chain of blocks on
+        /// its root, returning the keys of all tries.
+        fn populate(&self, rng: &mut TestRng) -> Vec<Blake2bHash> {
+            let contract_runtime = self.contract_runtime();
+            let leaf = |index: u8| {
+                Trie::leaf(
+                    Key::Hash([index; 32]),
+                    StoredValue::CLValue(CLValue::from_t(u64::from(index)).unwrap()),
+                )
+            };
+            let trie_key =
+                |trie: &Trie<Key, StoredValue>| Blake2bHash::new(&trie.to_bytes().unwrap());
+            let leaves = vec![leaf(1), leaf(2)];
+            let node = Trie::node(&[
+                (1, Pointer::LeafPointer(trie_key(&leaves[0]))),
+                (2, Pointer::LeafPointer(trie_key(&leaves[1]))),
+            ]);
+            let root = Trie::extension(vec![0], Pointer::NodePointer(trie_key(&node)));
+            let tries: Vec<_> = leaves.into_iter().chain(vec![node, root]).collect();
+            for trie in &tries {
+                contract_runtime.put_trie(trie).unwrap();
+            }
+            let trie_keys: Vec<_> = tries.iter().map(trie_key).collect();
+            let state_root_hash = Digest::from(*trie_keys.last().unwrap());
+
+            let mut storage = self.storage();
+            let mut parent_hash = BlockHash::default();
+            for height in 0..BLOCK_COUNT {
+                let deploys: Vec<_> = (0..DEPLOYS_PER_BLOCK)
+                    .map(|_| Deploy::random(rng))
+                    .collect();
+                let deploy_hashes = deploys.iter().map(|deploy| *deploy.id()).collect();
+                let finalized_block = FinalizedBlock::new(
+                    BlockPayload::new(deploy_hashes, vec![], vec![], rng.gen()),
+                    None,
+                    Timestamp::now(),
+                    EraId::from(0),
+                    height,
+                    PublicKey::from(&SecretKey::random(rng)),
+                );
+                let block = Block::new(
+                    parent_hash,
+                    Digest::random(rng),
+                    state_root_hash,
+                    finalized_block,
+                    None,
+                    ProtocolVersion::V1_0_0,
+                );
+
+                let mut signatures = BlockSignatures::new(*block.hash(), EraId::from(0));
+                let secret_key = SecretKey::random(rng);
+                let signature = FinalitySignature::new(
+                    *block.hash(),
+                    EraId::from(0),
+                    &secret_key,
+                    PublicKey::from(&secret_key),
+                );
+                signatures.insert_proof(signature.public_key, signature.signature);
+
+                let deploy_metadata = deploys
+                    .iter()
+                    .map(|deploy| {
+                        let mut metadata = DeployMetadata::default();
+                        let execution_result: ExecutionResult = rng.gen();
+                        metadata
+                            .execution_results
+                            .insert(*block.hash(), execution_result);
+                        (*deploy.id(), metadata)
+                    })
+                    .collect();
+
+                parent_hash = *block.hash();
+                storage
+                    .write_archived_block(&ArchivedBlock {
+                        block,
+                        signatures: Some(signatures),
+                        deploys,
+                        deploy_metadata,
+                        transfers: Some(vec![]),
+                    })
+                    .unwrap();
+            }
+            trie_keys
+        }
+
+        /// Returns the serialized responses to a set of queries on the blocks up to
+        /// `up_to_height`, their deploys and the given tries.
+        fn query(&self, up_to_height: u64, trie_keys: &[Blake2bHash]) -> Vec<Vec<u8>> {
+            let storage = self.storage();
+            let mut responses = vec![];
+            for height in 0..=up_to_height {
+                let (block, signatures) = storage
+                    .read_block_and_finality_signatures_by_height(height)
+                    .unwrap()
+                    .expect("should have block");
+                responses.push(serialize(&(&block, &signatures)));
+                responses.push(serialize(
+                    &storage.read_block_header_by_hash(block.hash()).unwrap(),
+                ));
+                responses.push(serialize(
+                    &storage.read_block_transfers(block.hash()).unwrap(),
+                ));
+                responses.push(serialize(&storage.read_archived_block(height).unwrap()));
+                for deploy_hash in block.deploy_hashes() {
+                    responses.push(serialize(&storage.get_deploy_by_hash(*deploy_hash)));
+                    responses.push(serialize(
+                        &storage.read_deploy_approvals(deploy_hash).unwrap(),
+                    ));
+                    responses.push(serialize(
+                        &storage.read_deploy_header_with_hash(deploy_hash).unwrap(),
+                    ));
+                }
+            }
+            drop(storage);
+
+            let contract_runtime = self.contract_runtime();
+            for trie_key in trie_keys {
+                let trie = contract_runtime.read_trie(*trie_key).unwrap();
+                responses.push(trie.to_bytes().unwrap());
+            }
+            responses
+        }
+    }
+
+    fn serialize<T: Serialize>(response: &T) -> Vec<u8> {
+        bincode::serialize(response).expect("should serialize response")
+    }
+
+    /// Flips the last byte of a chunk of the archive in `dir`.
+    fn tamper_with_chunk(dir: &Path, chunk: &ChunkInfo) {
+        let path = dir.join(&chunk.file);
+        let mut bytes = fs::read(&path).unwrap();
+        *bytes.last_mut().unwrap() ^= 1;
+        fs::write(&path, bytes).unwrap();
+    }
+
+    #[test]
+    fn should_export_and_import_chain_archive() {
+        let mut rng = TestRng::new();
+        let source = Fixture::new();
+        let trie_keys = source.populate(&mut rng);
+        let up_to_height = BLOCK_COUNT - 2;
+
+        let archive_dir = tempfile::tempdir().unwrap();
+        let manifest = source.export(archive_dir.path(), up_to_height).unwrap();
+        assert_eq!(manifest.highest_block_height, up_to_height);
+        let block_chunk_count = manifest
+            .chunks
+            .iter()
+            .filter(|chunk| chunk.kind == ChunkKind::Blocks)
+            .count();
+        assert_eq!(block_chunk_count as u64, up_to_height + 1);
+
+        let target = Fixture::new();
+        assert_eq!(target.import(archive_dir.path()).unwrap(), manifest);
+        assert!(!staging_dir(&target.root()).exists());
+
+        assert_eq!(
+            target.query(up_to_height, &trie_keys),
+            source.query(up_to_height, &trie_keys)
+        );
+        let storage = target.storage();
+        assert_eq!(
+            storage.read_highest_block_header().unwrap().unwrap().hash(),
+            manifest.highest_block_hash
+        );
+        assert!(storage
+            .read_archived_block(up_to_height + 1)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn should_reject_tampered_chunk() {
+        let mut rng = TestRng::new();
+        let source = Fixture::new();
+        source.populate(&mut rng);
+        let archive_dir = tempfile::tempdir().unwrap();
+        let manifest = source.export(archive_dir.path(), BLOCK_COUNT - 1).unwrap();
+
+        for chunk in [&manifest.chunks[1], manifest.chunks.last().unwrap()] {
+            let archive = tempfile::tempdir().unwrap();
+            for entry in fs::read_dir(archive_dir.path()).unwrap() {
+                let entry = entry.unwrap();
+                fs::copy(entry.path(), archive.path().join(entry.file_name())).unwrap();
+            }
+            tamper_with_chunk(archive.path(), chunk);
+
+            let target = Fixture::new();
+            match target.import(archive.path()) {
+                Err(Error::ChecksumMismatch { file }) => assert_eq!(file, chunk.file),
+                result => panic!("unexpected result {:?}", result),
+            }
+            assert!(is_missing_or_empty(&target.root()).unwrap());
+            assert!(!staging_dir(&target.root()).exists());
+        }
+    }
+
+    #[test]
+    fn should_refuse_to_import_into_non_empty_storage() {
+        let mut rng = TestRng::new();
+        let source = Fixture::new();
+        source.populate(&mut rng);
+        let archive_dir = tempfile::tempdir().unwrap();
+        source.export(archive_dir.path(), BLOCK_COUNT - 1).unwrap();
+
+        assert!(matches!(
+            source.import(archive_dir.path()),
+            Err(Error::StorageNotEmpty { .. })
+        ));
+        assert!(matches!(
+            source.export(archive_dir.path(), BLOCK_COUNT - 1),
+            Err(Error::OutputNotEmpty { .. })
+        ));
+    }
+}
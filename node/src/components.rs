@@ -10,11 +10,14 @@ pub(crate) mod collector;
 pub(crate) mod consensus;
 pub mod contract_runtime;
 pub(crate) mod deploy_acceptor;
+pub(crate) mod deploy_propagation;
 pub(crate) mod event_stream_server;
 pub(crate) mod fetcher;
 pub(crate) mod gossiper;
 pub(crate) mod linear_chain;
 pub(crate) mod linear_chain_sync;
+pub(crate) mod performance_tracker;
+pub(crate) mod request_limiter;
 pub(crate) mod rest_server;
 pub(crate) mod rpc_server;
 // The `in_memory_network` is public for use in doctests.
@@ -22,6 +25,7 @@ pub(crate) mod rpc_server;
 pub mod in_memory_network;
 pub(crate) mod metrics;
 pub(crate) mod network;
+pub(crate) mod network_provider;
 pub(crate) mod networking_metrics;
 pub(crate) mod small_network;
 pub(crate) mod storage;
@@ -3,6 +3,7 @@
 //! The block proposer stores deploy hashes in memory, tracking their suitability for inclusion into
 //! a new block. Upon request, it returns a list of candidates that can be included.
 
+mod config;
 mod deploy_sets;
 mod event;
 mod metrics;
@@ -11,7 +12,7 @@ mod tests;
 
 use std::{
     collections::{HashMap, HashSet},
-    convert::Infallible,
+    mem,
     sync::Arc,
     time::Duration,
 };
@@ -30,23 +31,28 @@ use crate::{
         Component,
     },
     effect::{
+        announcements::BlockProposerAnnouncement,
         requests::{BlockPayloadRequest, BlockProposerRequest, StateStoreRequest, StorageRequest},
         EffectBuilder, EffectExt, Effects,
     },
     types::{
         appendable_block::{AddError, AppendableBlock},
         chainspec::DeployConfig,
-        BlockPayload, Chainspec, Deploy, DeployHash, DeployHeader, DeployOrTransferHash, Timestamp,
+        deploy_span, BlockPayload, Chainspec, Deploy, DeployExt, DeployHash, DeployHeader,
+        DeployOrTransferHash, Summary, Timestamp,
     },
+    utils::rolling_bloom_filter::RollingBloomFilter,
     NodeRng,
 };
+pub use config::Config;
 use deploy_sets::BlockProposerDeploySets;
+pub(crate) use deploy_sets::DeployBufferStatus;
 pub(crate) use event::{DeployInfo, Event};
 use metrics::BlockProposerMetrics;
 
 /// Block proposer component.
 #[derive(DataSize, Debug)]
-pub(crate) struct BlockProposer {
+pub struct BlockProposer {
     /// The current state of the proposer component.
     state: BlockProposerState,
 
@@ -62,6 +68,10 @@ const PRUNE_INTERVAL: Duration = Duration::from_secs(10);
 /// within a threshold to break iteration of `pending` early.
 const DEPLOY_APPROX_MIN_SIZE: usize = 300;
 
+/// Once the buffer exceeds its maximum size, deploys are evicted until it is this percentage of
+/// the maximum below it, so that eviction isn't triggered again by every subsequent deploy.
+const EVICTION_HYSTERESIS_PERCENT: usize = 5;
+
 /// The type of values expressing the block height in the chain.
 type BlockHeight = u64;
 
@@ -85,6 +95,10 @@ enum BlockProposerState {
         pending: Vec<Event>,
         /// The deploy config from the current chainspec.
         deploy_config: DeployConfig,
+        /// The block proposer config.
+        config: Config,
+        /// The empty filter to populate with the loaded finalized deploys.
+        finalized_filter: RollingBloomFilter,
     },
     /// Normal operation.
     Ready(BlockProposerReady),
@@ -97,6 +111,7 @@ impl BlockProposer {
         effect_builder: EffectBuilder<REv>,
         next_finalized_block: BlockHeight,
         chainspec: &Chainspec,
+        config: Config,
     ) -> Result<(Self, Effects<Event>), prometheus::Error>
     where
         REv: From<Event> + From<StorageRequest> + From<StateStoreRequest> + Send + 'static,
@@ -113,6 +128,8 @@ impl BlockProposer {
             state: BlockProposerState::Initializing {
                 pending: Vec::new(),
                 deploy_config: chainspec.deploy_config,
+                config,
+                finalized_filter: finalized_filter(chainspec),
             },
             metrics: BlockProposerMetrics::new(registry)?,
         };
@@ -121,12 +138,32 @@ impl BlockProposer {
     }
 }
 
+/// Returns an empty filter for the finalized deploys, with one generation per TTL sized for the
+/// most deploys which can be finalized within one TTL.
+fn finalized_filter(chainspec: &Chainspec) -> RollingBloomFilter {
+    let deploy_config = &chainspec.deploy_config;
+    let min_round_length = chainspec.highway_config.min_round_length().millis().max(1);
+    let max_blocks_per_ttl = deploy_config.max_ttl.millis() / min_round_length + 1;
+    let max_deploys_per_block = u64::from(deploy_config.block_max_deploy_count)
+        .min(u64::from(deploy_config.max_block_size) / DEPLOY_APPROX_MIN_SIZE as u64)
+        + u64::from(deploy_config.block_max_transfer_count);
+    RollingBloomFilter::new(
+        deploy_config.max_ttl,
+        max_blocks_per_ttl.saturating_mul(max_deploys_per_block),
+    )
+}
+
 impl<REv> Component<REv> for BlockProposer
 where
-    REv: From<Event> + From<StorageRequest> + From<StateStoreRequest> + Send + 'static,
+    REv: From<Event>
+        + From<StorageRequest>
+        + From<StateStoreRequest>
+        + From<BlockProposerAnnouncement>
+        + Send
+        + 'static,
 {
     type Event = Event;
-    type ConstructionError = Infallible;
+    type ConstructionError = prometheus::Error;
 
     fn handle_event(
         &mut self,
@@ -144,6 +181,8 @@ where
                 BlockProposerState::Initializing {
                     ref mut pending,
                     deploy_config,
+                    config,
+                    ref mut finalized_filter,
                 },
                 Event::Loaded {
                     finalized_deploys,
@@ -154,9 +193,11 @@ where
                     sets: BlockProposerDeploySets::from_finalized(
                         finalized_deploys,
                         next_finalized_block,
+                        mem::take(finalized_filter),
                     ),
                     unhandled_finalized: Default::default(),
                     deploy_config: *deploy_config,
+                    config: *config,
                     request_queue: Default::default(),
                 };
 
@@ -192,6 +233,12 @@ where
                     ready_state.sets.pending_deploys.len() as i64
                         + ready_state.sets.pending_transfers.len() as i64,
                 );
+                self.metrics.finalized_filter_false_positive_rate.set(
+                    ready_state
+                        .sets
+                        .finalized_filter
+                        .estimated_false_positive_rate(),
+                );
             }
         };
 
@@ -209,8 +256,11 @@ struct BlockProposerReady {
     /// seen but were reported as reported to `finalized_deploys()`. They are used to
     /// filter deploys for proposal, similar to `self.sets.finalized_deploys`.
     unhandled_finalized: HashSet<DeployHash>,
-    /// We don't need the whole Chainspec here, just the deploy config.
+    /// We don't need the whole Chainspec here, just the deploy config of the active protocol
+    /// version.
     deploy_config: DeployConfig,
+    /// The block proposer config.
+    config: Config,
     /// The queue of requests awaiting being handled.
     request_queue: RequestQueue,
 }
@@ -222,7 +272,8 @@ impl BlockProposerReady {
         event: Event,
     ) -> Effects<Event>
     where
-        REv: Send + From<StorageRequest> + From<StateStoreRequest>,
+        REv:
+            Send + From<StorageRequest> + From<StateStoreRequest> + From<BlockProposerAnnouncement>,
     {
         match event {
             Event::Request(BlockProposerRequest::RequestBlockPayload(request)) => {
@@ -249,6 +300,17 @@ impl BlockProposerReady {
                         .ignore()
                 }
             }
+            Event::Request(BlockProposerRequest::GetDeployStatus {
+                deploy_hash,
+                responder,
+            }) => {
+                let status = if self.unhandled_finalized.contains(&deploy_hash) {
+                    DeployBufferStatus::Finalized
+                } else {
+                    self.sets.status(&deploy_hash)
+                };
+                responder.respond(status).ignore()
+            }
             Event::BufferDeploy(hash) => effect_builder
                 .get_deploys_from_storage(smallvec![hash])
                 .events(move |maybe_deploys| {
@@ -263,8 +325,17 @@ impl BlockProposerReady {
                         })
                 }),
             Event::GotFromStorage(deploy) => {
-                self.add_deploy(Timestamp::now(), deploy);
-                Effects::new()
+                let replacement = *deploy.id();
+                let mut effects = match self.add_deploy(Timestamp::now(), deploy) {
+                    Some(replaced) => effect_builder
+                        .announce_deploy_replaced(replaced, replacement)
+                        .ignore(),
+                    None => Effects::new(),
+                };
+                for evicted in self.evict_if_buffer_full() {
+                    effects.extend(effect_builder.announce_deploy_evicted(evicted).ignore());
+                }
+                effects
             }
             Event::Prune => {
                 let pruned = self.prune(Timestamp::now());
@@ -283,6 +354,9 @@ impl BlockProposerReady {
             Event::FinalizedBlock(block) => {
                 let deploys = block.deploys_and_transfers_iter().collect_vec();
                 let mut height = block.height();
+                // Blocks are finalized in timestamp order, so any other block proposed up to this
+                // one's timestamp has been either finalized or orphaned.
+                self.release_proposals_up_to(block.timestamp());
 
                 if height > self.sets.next_finalized {
                     warn!(
@@ -308,46 +382,193 @@ impl BlockProposerReady {
                     effects
                 }
             }
+            Event::DeployConfigActivated(deploy_config) => {
+                self.activate_deploy_config(deploy_config);
+                Effects::new()
+            }
         }
     }
 
+    /// Switches to the deploy config of a newly-committed upgrade, under which all subsequent
+    /// block payloads are proposed.
+    fn activate_deploy_config(&mut self, deploy_config: DeployConfig) {
+        info!(
+            max_deploy_size = deploy_config.max_deploy_size,
+            max_block_size = deploy_config.max_block_size,
+            "proposing block payloads under upgraded deploy config"
+        );
+        self.deploy_config = deploy_config;
+    }
+
     /// Adds a deploy or a transfer to the block proposer.
-    fn add_deploy(&mut self, current_instant: Timestamp, deploy: Box<Deploy>) {
+    ///
+    /// If a deploy from the same account with the same body is already pending, the new deploy
+    /// replaces it only if its gas price is strictly higher, and is rejected otherwise.  Returns
+    /// the hash of the replaced deploy, if any.
+    fn add_deploy(
+        &mut self,
+        current_instant: Timestamp,
+        deploy: Box<Deploy>,
+    ) -> Option<DeployHash> {
         let hash = deploy.deploy_or_transfer_hash();
         if deploy.header().expired(current_instant) {
             trace!(%hash, "expired deploy rejected from the buffer");
-            return;
+            return None;
         }
         if self.unhandled_finalized.remove(deploy.id()) {
-            info!(%hash, "deploy was previously marked as finalized, storing header");
-            self.sets
-                .finalized_deploys
-                .insert(*deploy.id(), deploy.take_header());
-            return;
+            info!(
+                deploy = %deploy.summary(),
+                "deploy was previously marked as finalized, storing header"
+            );
+            self.sets.add_finalized(*deploy.id(), deploy.take_header());
+            return None;
         }
         // only add the deploy if it isn't contained in a finalized block
-        if self.sets.finalized_deploys.contains_key(deploy.id()) {
-            info!(%hash, "deploy rejected from the buffer");
-            return;
+        if self.sets.is_finalized(deploy.id()) {
+            info!(deploy = %deploy.summary(), "deploy rejected from the buffer");
+            return None;
+        }
+        // a replaced deploy must never be proposed again
+        if self.sets.replaced_deploys.contains_key(deploy.id()) {
+            info!(deploy = %deploy.summary(), "replaced deploy rejected from the buffer");
+            return None;
+        }
+        // nor must an evicted one, lest it be evicted again as soon as it's re-gossiped
+        if self.sets.evicted_deploys.contains_key(deploy.id()) {
+            info!(deploy = %deploy.summary(), "evicted deploy rejected from the buffer");
+            return None;
+        }
+        // a re-gossiped deploy is announced to us again once gossiping it has finished
+        if self.sets.pending_deploys.contains_key(deploy.id())
+            || self.sets.pending_transfers.contains_key(deploy.id())
+        {
+            trace!(%hash, "deploy already in the buffer");
+            return None;
         }
 
         let deploy_info = match deploy.deploy_info() {
             Ok(deploy_info) => deploy_info,
             Err(error) => {
-                error!(%error, %deploy, "invalid deploy");
-                return;
+                error!(%error, deploy = %deploy.summary(), "invalid deploy");
+                return None;
             }
         };
 
-        if deploy.session().is_transfer() {
-            self.sets
-                .pending_transfers
-                .insert(*deploy.id(), deploy_info);
+        let pending = if deploy.session().is_transfer() {
+            &mut self.sets.pending_transfers
         } else {
-            self.sets.pending_deploys.insert(*deploy.id(), deploy_info);
+            &mut self.sets.pending_deploys
+        };
+
+        let header = deploy.header();
+        let maybe_existing = pending
+            .iter()
+            .find(|(_, existing)| {
+                existing.header.account() == header.account()
+                    && existing.header.body_hash() == header.body_hash()
+            })
+            .map(|(existing_hash, existing)| (*existing_hash, existing.header.gas_price()));
+
+        let maybe_replaced = match maybe_existing {
+            Some((existing_hash, existing_gas_price)) => {
+                if header.gas_price() <= existing_gas_price {
+                    info!(
+                        deploy = %deploy.summary(), %existing_hash, gas_price = header.gas_price(),
+                        existing_gas_price,
+                        "deploy rejected from the buffer: gas price not higher than that of \
+                        the pending deploy with the same body"
+                    );
+                    return None;
+                }
+                if let Some(existing) = pending.remove(&existing_hash) {
+                    self.sets
+                        .replaced_deploys
+                        .insert(existing_hash, existing.header);
+                }
+                let mut replaced_chain = self
+                    .sets
+                    .replacements
+                    .remove(&existing_hash)
+                    .unwrap_or_default();
+                replaced_chain.push(existing_hash);
+                self.sets.replacements.insert(*deploy.id(), replaced_chain);
+                info!(
+                    deploy = %deploy.summary(), replaced = %existing_hash,
+                    "deploy replaced a pending deploy"
+                );
+                Some(existing_hash)
+            }
+            None => None,
+        };
+
+        pending.insert(*deploy.id(), deploy_info);
+
+        info!(deploy = %deploy.summary(), "added deploy to the buffer");
+        maybe_replaced
+    }
+
+    /// Evicts pending deploys and transfers if their total size exceeds the configured maximum,
+    /// until it is `EVICTION_HYSTERESIS_PERCENT` below the maximum.
+    ///
+    /// Deploys with the lowest gas price are evicted first, and of those, the ones expiring
+    /// soonest.  Deploys included in an outstanding proposal are never evicted.  Returns the
+    /// hashes of the evicted deploys, in the order they were evicted.
+    fn evict_if_buffer_full(&mut self) -> Vec<DeployHash> {
+        let max_buffer_size = self.config.max_buffer_size();
+        let mut buffer_size = self.sets.pending_size();
+        if max_buffer_size == 0 || buffer_size <= max_buffer_size {
+            return Vec::new();
+        }
+        let target_size = max_buffer_size - max_buffer_size / 100 * EVICTION_HYSTERESIS_PERCENT;
+
+        let in_proposal = &self.sets.in_proposal;
+        let candidates = self
+            .sets
+            .pending_deploys
+            .iter()
+            .chain(self.sets.pending_transfers.iter())
+            .filter(|(hash, _)| !in_proposal.contains_key(hash))
+            .map(|(hash, deploy_info)| {
+                let header = &deploy_info.header;
+                (header.gas_price(), header.expires(), *hash)
+            })
+            .sorted()
+            .collect_vec();
+
+        let mut evicted = Vec::new();
+        for (gas_price, _, hash) in candidates {
+            if buffer_size <= target_size {
+                break;
+            }
+            let maybe_deploy_info = self
+                .sets
+                .pending_deploys
+                .remove(&hash)
+                .or_else(|| self.sets.pending_transfers.remove(&hash));
+            if let Some(deploy_info) = maybe_deploy_info {
+                info!(%hash, %gas_price, size = deploy_info.size, "evicted deploy from the buffer");
+                buffer_size -= deploy_info.size;
+                self.sets.replacements.remove(&hash);
+                self.sets.evicted_deploys.insert(hash, deploy_info.header);
+                evicted.push(hash);
+            }
         }
 
-        info!(%hash, "added deploy to the buffer");
+        if buffer_size > max_buffer_size {
+            warn!(
+                %buffer_size, %max_buffer_size,
+                "deploy buffer exceeds its maximum size with only proposed deploys remaining"
+            );
+        }
+        evicted
+    }
+
+    /// Releases the protection from eviction of deploys whose latest proposal was for a block no
+    /// later than the given timestamp.
+    fn release_proposals_up_to(&mut self, timestamp: Timestamp) {
+        self.sets
+            .in_proposal
+            .retain(|_, proposal_timestamp| *proposal_timestamp > timestamp);
     }
 
     /// Notifies the block proposer that a block has been finalized.
@@ -364,14 +585,55 @@ impl BlockProposerReady {
                     (hash, self.sets.pending_transfers.remove(&hash))
                 }
             };
+            self.sets.in_proposal.remove(&hash);
             match remove_result {
                 Some(deploy_info) => {
-                    self.sets.finalized_deploys.insert(hash, deploy_info.header);
-                }
-                // If we haven't seen this deploy before, we still need to take note of it.
-                None => {
-                    self.unhandled_finalized.insert(hash);
+                    self.sets.add_finalized(hash, deploy_info.header);
+                    self.sets.replacements.remove(&hash);
                 }
+                None => match self.sets.replaced_deploys.remove(&hash) {
+                    // A replaced deploy was included by another proposer: its replacement is
+                    // dropped.
+                    Some(header) => {
+                        self.sets.add_finalized(hash, header);
+                        self.drop_replacements_of(&hash);
+                    }
+                    None => match self.sets.evicted_deploys.remove(&hash) {
+                        // An evicted deploy was included by another proposer.
+                        Some(header) => self.sets.add_finalized(hash, header),
+                        // If we haven't seen this deploy before, we still need to take note of
+                        // it.
+                        None => {
+                            self.unhandled_finalized.insert(hash);
+                        }
+                    },
+                },
+            }
+        }
+    }
+
+    /// Removes all pending deploys which replaced the given finalized deploy, marking them as
+    /// replaced so that they are never proposed.
+    fn drop_replacements_of(&mut self, finalized_hash: &DeployHash) {
+        let dropped = self
+            .sets
+            .replacements
+            .iter()
+            .filter(|(_, replaced_chain)| replaced_chain.contains(finalized_hash))
+            .map(|(replacement, _)| *replacement)
+            .collect_vec();
+        for replacement in dropped {
+            self.sets.replacements.remove(&replacement);
+            let maybe_deploy_info = self
+                .sets
+                .pending_deploys
+                .remove(&replacement)
+                .or_else(|| self.sets.pending_transfers.remove(&replacement));
+            if let Some(deploy_info) = maybe_deploy_info {
+                info!(%replacement, replaced = %finalized_hash, "dropped replacement deploy");
+                self.sets
+                    .replaced_deploys
+                    .insert(replacement, deploy_info.header);
             }
         }
     }
@@ -418,6 +680,23 @@ impl BlockProposerReady {
             .all(|dep| past_deploys.contains(dep) || self.contains_finalized(dep))
     }
 
+    /// Checks if any deploy replaced by the given one has already been proposed or finalized, in
+    /// which case the replacement is not eligible for inclusion.
+    fn replaced_deploy_included(
+        &self,
+        hash: &DeployHash,
+        past_deploys: &HashSet<DeployHash>,
+    ) -> bool {
+        self.sets
+            .replacements
+            .get(hash)
+            .map_or(false, |replaced_chain| {
+                replaced_chain.iter().any(|replaced| {
+                    past_deploys.contains(replaced) || self.contains_finalized(replaced)
+                })
+            })
+    }
+
     /// Returns a list of candidates for inclusion into a block.
     fn propose_block_payload(
         &mut self,
@@ -436,11 +715,17 @@ impl BlockProposerReady {
         let block_timestamp = context.timestamp();
         let mut appendable_block = AppendableBlock::new(deploy_config, block_timestamp);
 
+        // Deploys buffered before an upgrade tightened the max deploy size are never proposed.
+        let exceeds_max_size =
+            |deploy_info: &DeployInfo| deploy_info.size > deploy_config.max_deploy_size as usize;
+
         // We prioritize transfers over deploys, so we try to include them first.
         for (hash, deploy_info) in &self.sets.pending_transfers {
-            if !self.deps_resolved(&deploy_info.header, &past_deploys)
+            if exceeds_max_size(deploy_info)
+                || !self.deps_resolved(&deploy_info.header, &past_deploys)
                 || past_deploys.contains(hash)
                 || self.contains_finalized(hash)
+                || self.replaced_deploy_included(hash, &past_deploys)
             {
                 continue;
             }
@@ -461,9 +746,11 @@ impl BlockProposerReady {
 
         // Now we try to add other deploys to the block.
         for (hash, deploy_info) in &self.sets.pending_deploys {
-            if !self.deps_resolved(&deploy_info.header, &past_deploys)
+            if exceeds_max_size(deploy_info)
+                || !self.deps_resolved(&deploy_info.header, &past_deploys)
                 || past_deploys.contains(hash)
                 || self.contains_finalized(hash)
+                || self.replaced_deploy_included(hash, &past_deploys)
             {
                 continue;
             }
@@ -493,7 +780,14 @@ impl BlockProposerReady {
             }
         }
 
-        Arc::new(appendable_block.into_block_payload(accusations, random_bit))
+        let block_payload = appendable_block.into_block_payload(accusations, random_bit);
+        for hash in block_payload.deploys_and_transfers_iter() {
+            deploy_span(hash.deploy_hash()).in_scope(|| debug!("proposing deploy"));
+            self.sets
+                .in_proposal
+                .insert(*hash.deploy_hash(), block_timestamp);
+        }
+        Arc::new(block_payload)
     }
 
     /// Prunes expired deploy information from the BlockProposer, returns the total deploys pruned.
@@ -502,6 +796,6 @@ impl BlockProposerReady {
     }
 
     fn contains_finalized(&self, dep: &DeployHash) -> bool {
-        self.sets.finalized_deploys.contains_key(dep) || self.unhandled_finalized.contains(dep)
+        self.sets.is_finalized(dep) || self.unhandled_finalized.contains(dep)
     }
 }
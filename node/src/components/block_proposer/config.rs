@@ -0,0 +1,35 @@
+use datasize::DataSize;
+use serde::{Deserialize, Serialize};
+
+/// The default maximum total size of the buffered deploys, i.e. 512 MiB.
+const DEFAULT_MAX_BUFFER_SIZE: usize = 512 * 1024 * 1024;
+
+/// Configuration options for the block proposer.
+#[derive(Clone, Copy, DataSize, Debug, Deserialize, Serialize)]
+pub struct Config {
+    /// The maximum total serialized size in bytes of the deploys and transfers buffered for
+    /// inclusion in a block.  Once exceeded, the lowest-priced deploys are evicted.  `0` means
+    /// unlimited.
+    max_buffer_size: usize,
+}
+
+impl Config {
+    /// Constructs a config with the given maximum buffer size.
+    #[cfg(test)]
+    pub(crate) fn new(max_buffer_size: usize) -> Self {
+        Config { max_buffer_size }
+    }
+
+    /// Get max_buffer_size setting.
+    pub(crate) fn max_buffer_size(&self) -> usize {
+        self.max_buffer_size
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            max_buffer_size: DEFAULT_MAX_BUFFER_SIZE,
+        }
+    }
+}
@@ -6,7 +6,30 @@ use std::{
 use datasize::DataSize;
 
 use super::{event::DeployInfo, BlockHeight, FinalizationQueue};
-use crate::types::{DeployHash, DeployHeader, Timestamp};
+use crate::{
+    types::{DeployHash, DeployHeader, Timestamp},
+    utils::rolling_bloom_filter::RollingBloomFilter,
+};
+
+/// The stage a deploy has reached as far as the block proposer's buffer is concerned.
+#[derive(Clone, Copy, DataSize, Debug, PartialEq, Eq)]
+pub enum DeployBufferStatus {
+    /// The block proposer doesn't know of the deploy, or has pruned it since it expired.
+    Unknown,
+    /// The deploy is buffered and awaiting inclusion in a proposed block.
+    Pending,
+    /// The deploy is buffered and has been included in at least one block proposed by this node.
+    Proposed {
+        /// The timestamp of the latest such block.
+        latest_proposal: Timestamp,
+    },
+    /// The deploy has been included in a finalized block.
+    Finalized,
+    /// The deploy has been replaced by one with a higher gas price and will never be proposed.
+    Replaced,
+    /// The deploy has been evicted from the full buffer and will never be proposed.
+    Evicted,
+}
 
 /// Stores the internal state of the BlockProposer.
 #[derive(Clone, DataSize, Debug, Default)]
@@ -19,6 +42,21 @@ pub(super) struct BlockProposerDeploySets {
     pub(super) pending_transfers: HashMap<DeployHash, DeployInfo>,
     /// The deploys that have already been included in a finalized block.
     pub(super) finalized_deploys: HashMap<DeployHash, DeployHeader>,
+    /// A filter over the hashes of `finalized_deploys`, checked before the map itself.
+    pub(super) finalized_filter: RollingBloomFilter,
+    /// The deploys which have been replaced by a deploy with a higher gas price, or which were
+    /// dropped as replacements since the deploy they replaced was finalized.  These are never
+    /// proposed.
+    pub(super) replaced_deploys: HashMap<DeployHash, DeployHeader>,
+    /// Maps each pending replacement deploy to the deploys it has (directly or transitively)
+    /// replaced.  A replacement is not proposed while any of these is in a proposed ancestor.
+    pub(super) replacements: HashMap<DeployHash, Vec<DeployHash>>,
+    /// The deploys which have been evicted since the buffer exceeded its maximum size.  These are
+    /// never proposed.
+    pub(super) evicted_deploys: HashMap<DeployHash, DeployHeader>,
+    /// Maps each pending deploy included in a proposed block to the latest such block's timestamp.
+    /// These are protected from eviction until a block at least as late has been finalized.
+    pub(super) in_proposal: HashMap<DeployHash, Timestamp>,
     /// The next block height we expect to be finalized.
     /// If we receive a notification of finalization of a later block, we will store it in
     /// finalization_queue.
@@ -31,25 +69,82 @@ pub(super) struct BlockProposerDeploySets {
 
 impl BlockProposerDeploySets {
     /// Constructs the instance of `BlockProposerDeploySets` from the list of finalized deploys.
+    ///
+    /// The given filter is expected to be empty, and is populated with the finalized deploys.
     pub(super) fn from_finalized(
         finalized_deploys: Vec<(DeployHash, DeployHeader)>,
         next_finalized_height: u64,
+        finalized_filter: RollingBloomFilter,
     ) -> BlockProposerDeploySets {
-        BlockProposerDeploySets {
-            finalized_deploys: finalized_deploys.into_iter().collect(),
+        let mut sets = BlockProposerDeploySets {
+            finalized_filter,
             next_finalized: next_finalized_height,
             ..Default::default()
+        };
+        for (hash, header) in finalized_deploys {
+            sets.add_finalized(hash, header);
+        }
+        sets
+    }
+
+    /// Records the deploy as included in a finalized block.
+    pub(super) fn add_finalized(&mut self, hash: DeployHash, header: DeployHeader) {
+        self.finalized_filter.insert(hash.inner(), header.expires());
+        self.finalized_deploys.insert(hash, header);
+    }
+
+    /// Returns whether the deploy has been included in a finalized block and not yet pruned.
+    ///
+    /// The filter rules out most deploys without a lookup in `finalized_deploys`, which is only
+    /// consulted to confirm a possible match.
+    pub(super) fn is_finalized(&self, hash: &DeployHash) -> bool {
+        self.finalized_filter.may_contain(hash.inner()) && self.finalized_deploys.contains_key(hash)
+    }
+
+    /// Returns the stage the given deploy has reached in the buffer.
+    ///
+    /// A finalized deploy is reported as such even if it is also known in another set, since
+    /// finalization is the most advanced stage.
+    pub(super) fn status(&self, hash: &DeployHash) -> DeployBufferStatus {
+        if self.is_finalized(hash) {
+            DeployBufferStatus::Finalized
+        } else if self.replaced_deploys.contains_key(hash) {
+            DeployBufferStatus::Replaced
+        } else if self.evicted_deploys.contains_key(hash) {
+            DeployBufferStatus::Evicted
+        } else if self.pending_deploys.contains_key(hash)
+            || self.pending_transfers.contains_key(hash)
+        {
+            match self.in_proposal.get(hash) {
+                Some(latest_proposal) => DeployBufferStatus::Proposed {
+                    latest_proposal: *latest_proposal,
+                },
+                None => DeployBufferStatus::Pending,
+            }
+        } else {
+            DeployBufferStatus::Unknown
         }
     }
+
+    /// Returns the total serialized size of the pending deploys and transfers.
+    pub(super) fn pending_size(&self) -> usize {
+        self.pending_deploys
+            .values()
+            .chain(self.pending_transfers.values())
+            .map(|deploy_info| deploy_info.size)
+            .sum()
+    }
 }
 
 impl Display for BlockProposerDeploySets {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         write!(
             f,
-            "(pending:{}, finalized:{})",
+            "(pending:{}, finalized:{}, replaced:{}, evicted:{})",
             self.pending_deploys.len() + self.pending_transfers.len(),
-            self.finalized_deploys.len()
+            self.finalized_deploys.len(),
+            self.replaced_deploys.len(),
+            self.evicted_deploys.len()
         )
     }
 }
@@ -61,7 +156,16 @@ impl BlockProposerDeploySets {
         let pending_deploys = prune_pending_deploys(&mut self.pending_deploys, current_instant);
         let pending_transfers = prune_pending_deploys(&mut self.pending_transfers, current_instant);
         let finalized = prune_deploys(&mut self.finalized_deploys, current_instant);
-        pending_deploys + pending_transfers + finalized
+        self.finalized_filter.prune(current_instant);
+        let replaced = prune_deploys(&mut self.replaced_deploys, current_instant);
+        let evicted = prune_deploys(&mut self.evicted_deploys, current_instant);
+        // Only keep the replacement chains and proposals of deploys which are still pending.
+        let (deploys, transfers) = (&self.pending_deploys, &self.pending_transfers);
+        self.replacements
+            .retain(|hash, _| deploys.contains_key(hash) || transfers.contains_key(hash));
+        self.in_proposal
+            .retain(|hash, _| deploys.contains_key(hash) || transfers.contains_key(hash));
+        pending_deploys + pending_transfers + finalized + replaced + evicted
     }
 }
 
@@ -8,7 +8,7 @@ use serde::{Deserialize, Serialize};
 use super::BlockHeight;
 use crate::{
     effect::requests::BlockProposerRequest,
-    types::{Deploy, DeployHash, DeployHeader, FinalizedBlock},
+    types::{chainspec::DeployConfig, Deploy, DeployHash, DeployHeader, FinalizedBlock},
 };
 use casper_execution_engine::shared::motes::Motes;
 
@@ -21,7 +21,7 @@ pub struct DeployInfo {
 }
 
 /// An event for when using the block proposer as a component.
-#[derive(DataSize, Debug, From)]
+#[derive(DataSize, Debug, From, Serialize)]
 pub enum Event {
     /// Incoming `BlockProposerRequest`.
     #[from]
@@ -42,6 +42,9 @@ pub enum Event {
     Prune,
     /// A block has been finalized. We should never propose its deploys again.
     FinalizedBlock(Box<FinalizedBlock>),
+    /// An upgrade has been committed: block payloads are henceforth proposed under its deploy
+    /// config.
+    DeployConfigActivated(DeployConfig),
 }
 
 impl Display for Event {
@@ -64,6 +67,7 @@ impl Display for Event {
             Event::FinalizedBlock(block) => {
                 write!(f, "block-proposer finalized block {}", block)
             }
+            Event::DeployConfigActivated(_) => write!(f, "block-proposer activate deploy config"),
         }
     }
 }
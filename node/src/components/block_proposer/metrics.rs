@@ -1,5 +1,5 @@
 use datasize::DataSize;
-use prometheus::{self, IntGauge, Registry};
+use prometheus::{self, Gauge, IntGauge, Registry};
 
 use crate::unregister_metric;
 
@@ -9,6 +9,9 @@ pub(super) struct BlockProposerMetrics {
     /// Amount of pending deploys
     #[data_size(skip)]
     pub(super) pending_deploys: IntGauge,
+    /// Estimated false positive rate of the filter over finalized deploys
+    #[data_size(skip)]
+    pub(super) finalized_filter_false_positive_rate: Gauge,
     /// Registry stored to allow deregistration later.
     #[data_size(skip)]
     registry: Registry,
@@ -19,8 +22,14 @@ impl BlockProposerMetrics {
     pub fn new(registry: Registry) -> Result<Self, prometheus::Error> {
         let pending_deploys = IntGauge::new("pending_deploy", "amount of pending deploys")?;
         registry.register(Box::new(pending_deploys.clone()))?;
+        let finalized_filter_false_positive_rate = Gauge::new(
+            "finalized_deploy_filter_false_positive_rate",
+            "estimated false positive rate of the filter over finalized deploys",
+        )?;
+        registry.register(Box::new(finalized_filter_false_positive_rate.clone()))?;
         Ok(BlockProposerMetrics {
             pending_deploys,
+            finalized_filter_false_positive_rate,
             registry,
         })
     }
@@ -29,5 +38,6 @@ impl BlockProposerMetrics {
 impl Drop for BlockProposerMetrics {
     fn drop(&mut self) {
         unregister_metric!(self.registry, self.pending_deploys);
+        unregister_metric!(self.registry, self.finalized_filter_false_positive_rate);
     }
 }
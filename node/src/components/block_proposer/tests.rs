@@ -1,8 +1,13 @@
+use std::iter;
+
 use casper_execution_engine::{
     core::engine_state::executable_deploy_item::ExecutableDeployItem, shared::gas::Gas,
 };
 use casper_types::{
-    bytesrepr::Bytes, runtime_args, system::standard_payment::ARG_AMOUNT, RuntimeArgs, SecretKey,
+    bytesrepr::{Bytes, ToBytes},
+    runtime_args,
+    system::standard_payment::ARG_AMOUNT,
+    RuntimeArgs, SecretKey,
 };
 use itertools::Itertools;
 
@@ -63,6 +68,24 @@ fn generate_deploy(
     gas_price: u64,
 ) -> Deploy {
     let secret_key = SecretKey::random(rng);
+    generate_deploy_with_key(
+        &secret_key,
+        timestamp,
+        ttl,
+        dependencies,
+        payment_amount,
+        gas_price,
+    )
+}
+
+fn generate_deploy_with_key(
+    secret_key: &SecretKey,
+    timestamp: Timestamp,
+    ttl: TimeDiff,
+    dependencies: Vec<DeployHash>,
+    payment_amount: Gas,
+    gas_price: u64,
+) -> Deploy {
     let chain_name = "chain".to_string();
     let args = runtime_args! {
         ARG_AMOUNT => payment_amount.value()
@@ -84,7 +107,7 @@ fn generate_deploy(
         chain_name,
         payment,
         session,
-        &secret_key,
+        secret_key,
     )
 }
 
@@ -357,7 +380,7 @@ fn should_keep_track_of_unhandled_deploys() {
     assert!(
         deploy2
             .header()
-            .is_valid(&proposer.deploy_config, test_time),
+            .is_valid(&proposer.deploy_config.deploy_limits(), test_time),
         "deploy2 should be valid"
     );
 
@@ -653,3 +676,531 @@ fn should_return_deploy_dependencies() {
     assert_eq!(deploys2.len(), 1);
     assert!(deploys2.contains(deploy2.id()));
 }
+
+#[test]
+fn should_replace_deploy_with_higher_gas_price() {
+    let creation_time = Timestamp::from(100);
+    let ttl = TimeDiff::from(Duration::from_millis(100));
+    let block_time = Timestamp::from(120);
+
+    let mut rng = crate::new_rng();
+    let secret_key = SecretKey::random(&mut rng);
+    let original = generate_deploy_with_key(
+        &secret_key,
+        creation_time,
+        ttl,
+        vec![],
+        default_gas_payment(),
+        DEFAULT_TEST_GAS_PRICE,
+    );
+    let replacement = generate_deploy_with_key(
+        &secret_key,
+        creation_time,
+        ttl,
+        vec![],
+        default_gas_payment(),
+        DEFAULT_TEST_GAS_PRICE + 1,
+    );
+    assert_eq!(
+        original.header().body_hash(),
+        replacement.header().body_hash()
+    );
+
+    let mut proposer = BlockProposerReady::default();
+    assert!(proposer
+        .add_deploy(block_time, Box::new(original.clone()))
+        .is_none());
+    assert_eq!(
+        proposer.add_deploy(block_time, Box::new(replacement.clone())),
+        Some(*original.id())
+    );
+
+    // the original deploy should no longer be pending
+    assert_eq!(proposer.sets.pending_deploys.len(), 1);
+    assert!(proposer.sets.pending_deploys.contains_key(replacement.id()));
+    assert!(proposer.sets.replaced_deploys.contains_key(original.id()));
+
+    // only the replacement should be proposed
+    let block = proposer.propose_block_payload(
+        DeployConfig::default(),
+        BlockContext::new(block_time, vec![]),
+        vec![],
+        true,
+    );
+    assert_eq!(block.deploy_hashes(), &[*replacement.id()]);
+
+    // the original deploy must not be buffered again, e.g. if gossiped to us by another peer
+    assert!(proposer
+        .add_deploy(block_time, Box::new(original.clone()))
+        .is_none());
+    assert!(!proposer.sets.pending_deploys.contains_key(original.id()));
+}
+
+#[test]
+fn should_reject_replacement_without_higher_gas_price() {
+    let creation_time = Timestamp::from(100);
+    let ttl = TimeDiff::from(Duration::from_millis(100));
+    let block_time = Timestamp::from(120);
+
+    let mut rng = crate::new_rng();
+    let secret_key = SecretKey::random(&mut rng);
+    let original = generate_deploy_with_key(
+        &secret_key,
+        creation_time,
+        ttl,
+        vec![],
+        default_gas_payment(),
+        DEFAULT_TEST_GAS_PRICE + 1,
+    );
+    // a different timestamp gives a different deploy hash, but the same body hash
+    let same_gas_price = generate_deploy_with_key(
+        &secret_key,
+        creation_time + TimeDiff::from(1),
+        ttl,
+        vec![],
+        default_gas_payment(),
+        DEFAULT_TEST_GAS_PRICE + 1,
+    );
+    let lower_gas_price = generate_deploy_with_key(
+        &secret_key,
+        creation_time,
+        ttl,
+        vec![],
+        default_gas_payment(),
+        DEFAULT_TEST_GAS_PRICE,
+    );
+    // a deploy with the same body from another account is not a replacement
+    let other_account = generate_deploy(
+        &mut rng,
+        creation_time,
+        ttl,
+        vec![],
+        default_gas_payment(),
+        DEFAULT_TEST_GAS_PRICE + 2,
+    );
+
+    let mut proposer = BlockProposerReady::default();
+    assert!(proposer
+        .add_deploy(block_time, Box::new(original.clone()))
+        .is_none());
+    assert!(proposer
+        .add_deploy(block_time, Box::new(same_gas_price))
+        .is_none());
+    assert!(proposer
+        .add_deploy(block_time, Box::new(lower_gas_price))
+        .is_none());
+    assert!(proposer
+        .add_deploy(block_time, Box::new(other_account.clone()))
+        .is_none());
+
+    assert_eq!(proposer.sets.pending_deploys.len(), 2);
+    assert!(proposer.sets.pending_deploys.contains_key(original.id()));
+    assert!(proposer
+        .sets
+        .pending_deploys
+        .contains_key(other_account.id()));
+    assert!(proposer.sets.replaced_deploys.is_empty());
+
+    let block = proposer.propose_block_payload(
+        DeployConfig::default(),
+        BlockContext::new(block_time, vec![]),
+        vec![],
+        true,
+    );
+    assert_eq!(block.deploy_hashes().len(), 2);
+    assert!(block.deploy_hashes().contains(original.id()));
+    assert!(block.deploy_hashes().contains(other_account.id()));
+}
+
+#[test]
+fn should_not_propose_replacement_if_original_already_proposed() {
+    let creation_time = Timestamp::from(100);
+    let ttl = TimeDiff::from(Duration::from_millis(100));
+    let block_time = Timestamp::from(120);
+
+    let mut rng = crate::new_rng();
+    let secret_key = SecretKey::random(&mut rng);
+    let original = generate_deploy_with_key(
+        &secret_key,
+        creation_time,
+        ttl,
+        vec![],
+        default_gas_payment(),
+        DEFAULT_TEST_GAS_PRICE,
+    );
+    let replacement = generate_deploy_with_key(
+        &secret_key,
+        creation_time,
+        ttl,
+        vec![],
+        default_gas_payment(),
+        DEFAULT_TEST_GAS_PRICE + 1,
+    );
+
+    let mut proposer = BlockProposerReady::default();
+    proposer.add_deploy(block_time, Box::new(original.clone()));
+    let block_with_original = proposer.propose_block_payload(
+        DeployConfig::default(),
+        BlockContext::new(block_time, vec![]),
+        vec![],
+        true,
+    );
+    assert_eq!(block_with_original.deploy_hashes(), &[*original.id()]);
+
+    assert_eq!(
+        proposer.add_deploy(block_time, Box::new(replacement.clone())),
+        Some(*original.id())
+    );
+
+    // the replacement must not be proposed while the original is in a proposed ancestor
+    let block = proposer.propose_block_payload(
+        DeployConfig::default(),
+        BlockContext::new(block_time, vec![block_with_original]),
+        vec![],
+        true,
+    );
+    assert!(block.deploy_hashes().is_empty());
+
+    // without that ancestor, the replacement is eligible
+    let block = proposer.propose_block_payload(
+        DeployConfig::default(),
+        BlockContext::new(block_time, vec![]),
+        vec![],
+        true,
+    );
+    assert_eq!(block.deploy_hashes(), &[*replacement.id()]);
+
+    // once the original is finalized, the replacement is dropped for good
+    proposer.finalized_deploys(iter::once(original.deploy_or_transfer_hash()));
+    assert!(proposer.sets.finalized_deploys.contains_key(original.id()));
+    assert!(proposer.sets.pending_deploys.is_empty());
+    assert!(proposer
+        .sets
+        .replaced_deploys
+        .contains_key(replacement.id()));
+    assert!(proposer.sets.replacements.is_empty());
+
+    let block = proposer.propose_block_payload(
+        DeployConfig::default(),
+        BlockContext::new(block_time, vec![]),
+        vec![],
+        true,
+    );
+    assert!(block.deploy_hashes().is_empty());
+}
+
+#[test]
+fn should_exclude_deploy_finalized_within_ttl() {
+    const LATER_BLOCK_COUNT: u64 = 10;
+    let creation_time = Timestamp::from(100);
+    let ttl = TimeDiff::from(Duration::from_millis(1_000));
+    let block_time = Timestamp::from(120);
+
+    let mut rng = crate::new_rng();
+    let mut proposer = BlockProposerReady {
+        sets: BlockProposerDeploySets::from_finalized(vec![], 0, RollingBloomFilter::new(ttl, 100)),
+        ..Default::default()
+    };
+    let generate = |rng: &mut TestRng| {
+        generate_deploy(
+            rng,
+            creation_time,
+            ttl,
+            vec![],
+            default_gas_payment(),
+            DEFAULT_TEST_GAS_PRICE,
+        )
+    };
+
+    // the deploy is finalized, followed by several blocks of other deploys
+    let deploy = generate(&mut rng);
+    proposer.add_deploy(creation_time, Box::new(deploy.clone()));
+    proposer.finalized_deploys(iter::once(deploy.deploy_or_transfer_hash()));
+    for _ in 0..LATER_BLOCK_COUNT {
+        let other = generate(&mut rng);
+        proposer.add_deploy(creation_time, Box::new(other.clone()));
+        proposer.finalized_deploys(iter::once(other.deploy_or_transfer_hash()));
+    }
+    assert!(proposer.sets.is_finalized(deploy.id()));
+
+    // gossiped again, it is rejected and never proposed
+    proposer.add_deploy(block_time, Box::new(deploy));
+    assert!(proposer.sets.pending_deploys.is_empty());
+    let block = proposer.propose_block_payload(
+        DeployConfig::default(),
+        BlockContext::new(block_time, vec![]),
+        vec![],
+        true,
+    );
+    assert!(block.deploy_hashes().is_empty());
+}
+
+#[test]
+fn should_not_exclude_deploy_finalized_outside_ttl() {
+    let creation_time = Timestamp::from(100);
+    let ttl = TimeDiff::from(Duration::from_millis(100));
+
+    let mut rng = crate::new_rng();
+    let mut proposer = BlockProposerReady {
+        sets: BlockProposerDeploySets::from_finalized(vec![], 0, RollingBloomFilter::new(ttl, 100)),
+        ..Default::default()
+    };
+    let deploy = generate_deploy(
+        &mut rng,
+        creation_time,
+        ttl,
+        vec![],
+        default_gas_payment(),
+        DEFAULT_TEST_GAS_PRICE,
+    );
+    proposer.add_deploy(creation_time, Box::new(deploy.clone()));
+    proposer.finalized_deploys(iter::once(deploy.deploy_or_transfer_hash()));
+    assert!(proposer.contains_finalized(deploy.id()));
+
+    // once the whole filter generation holding the deploy has expired, it is dropped along with
+    // the deploy itself
+    proposer.prune(creation_time + ttl + ttl);
+    assert!(!proposer
+        .sets
+        .finalized_filter
+        .may_contain(deploy.id().inner()));
+    assert!(!proposer.contains_finalized(deploy.id()));
+}
+
+/// Returns the total size the given deploys take up in the buffer.
+fn buffered_size<'a, I: IntoIterator<Item = &'a Deploy>>(deploys: I) -> usize {
+    deploys
+        .into_iter()
+        .map(|deploy| deploy.deploy_info().unwrap().size)
+        .sum()
+}
+
+/// Returns a proposer whose buffer is full once it holds more than `kept_size` bytes, but from
+/// which evicting any single deploy of a few hundred bytes or more takes it below the eviction
+/// target again.
+fn proposer_with_room_for(kept_size: usize) -> BlockProposerReady {
+    BlockProposerReady {
+        config: Config::new(kept_size + kept_size / 10),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn should_evict_lowest_gas_price_then_soonest_expiring_deploys() {
+    let creation_time = Timestamp::from(100);
+    let short_ttl = TimeDiff::from(Duration::from_millis(100));
+    let long_ttl = TimeDiff::from(Duration::from_millis(200));
+
+    let mut rng = crate::new_rng();
+    let mut generate = |ttl, gas_price| {
+        generate_deploy(
+            &mut rng,
+            creation_time,
+            ttl,
+            vec![],
+            default_gas_payment(),
+            gas_price,
+        )
+    };
+    let cheap_long = generate(long_ttl, 1);
+    let cheap_short = generate(short_ttl, 1);
+    let medium_short = generate(short_ttl, 2);
+    let expensive_short = generate(short_ttl, 3);
+    let expensive_long = generate(long_ttl, 3);
+    let medium_long = generate(long_ttl, 2);
+    let all = vec![
+        &cheap_long,
+        &cheap_short,
+        &medium_short,
+        &expensive_short,
+        &expensive_long,
+        &medium_long,
+    ];
+
+    let mut proposer = proposer_with_room_for(buffered_size(vec![
+        &medium_long,
+        &expensive_short,
+        &expensive_long,
+    ]));
+    for deploy in &all {
+        proposer.add_deploy(creation_time, Box::new((*deploy).clone()));
+    }
+
+    assert_eq!(
+        proposer.evict_if_buffer_full(),
+        vec![*cheap_short.id(), *cheap_long.id(), *medium_short.id()]
+    );
+    assert_eq!(proposer.sets.pending_deploys.len(), 3);
+    assert!(proposer.sets.pending_deploys.contains_key(medium_long.id()));
+    assert!(proposer
+        .sets
+        .pending_deploys
+        .contains_key(expensive_short.id()));
+    assert!(proposer
+        .sets
+        .pending_deploys
+        .contains_key(expensive_long.id()));
+    assert_eq!(proposer.sets.evicted_deploys.len(), 3);
+
+    // the buffer is no longer full, and the evicted deploys are never buffered again
+    assert!(proposer.evict_if_buffer_full().is_empty());
+    proposer.add_deploy(creation_time, Box::new(cheap_short.clone()));
+    assert!(!proposer.sets.pending_deploys.contains_key(cheap_short.id()));
+
+    // an evicted deploy included by another proposer is still recorded as finalized
+    proposer.finalized_deploys(iter::once(medium_short.deploy_or_transfer_hash()));
+    assert!(proposer.contains_finalized(medium_short.id()));
+    assert!(!proposer
+        .sets
+        .evicted_deploys
+        .contains_key(medium_short.id()));
+}
+
+#[test]
+fn should_not_evict_deploys_in_outstanding_proposal() {
+    let creation_time = Timestamp::from(100);
+    let ttl = TimeDiff::from(Duration::from_millis(100));
+    let block_time = Timestamp::from(120);
+
+    let mut rng = crate::new_rng();
+    let mut generate = |gas_price| {
+        generate_deploy(
+            &mut rng,
+            creation_time,
+            ttl,
+            vec![],
+            default_gas_payment(),
+            gas_price,
+        )
+    };
+    let proposed = generate(1);
+    let cheap = generate(2);
+    let expensive = generate(3);
+    let later = generate(3);
+
+    let mut proposer = proposer_with_room_for(buffered_size(vec![&proposed, &expensive]));
+    proposer.add_deploy(creation_time, Box::new(proposed.clone()));
+    let block = proposer.propose_block_payload(
+        DeployConfig::default(),
+        BlockContext::new(block_time, vec![]),
+        vec![],
+        true,
+    );
+    assert_eq!(block.deploy_hashes(), &[*proposed.id()]);
+
+    // the proposed deploy has the lowest gas price, but isn't evicted
+    proposer.add_deploy(creation_time, Box::new(cheap.clone()));
+    proposer.add_deploy(creation_time, Box::new(expensive));
+    assert_eq!(proposer.evict_if_buffer_full(), vec![*cheap.id()]);
+
+    // finalizing an earlier block doesn't resolve the proposal
+    proposer.release_proposals_up_to(block_time - TimeDiff::from(1));
+    assert!(proposer.sets.in_proposal.contains_key(proposed.id()));
+
+    // once a block at least as late is finalized without it, the deploy can be evicted
+    proposer.release_proposals_up_to(block_time);
+    assert!(proposer.sets.in_proposal.is_empty());
+    proposer.add_deploy(creation_time, Box::new(later));
+    assert_eq!(proposer.evict_if_buffer_full(), vec![*proposed.id()]);
+    assert_eq!(proposer.sets.pending_deploys.len(), 2);
+}
+
+#[test]
+fn should_evict_below_maximum_buffer_size() {
+    const DEPLOY_COUNT: u64 = 40;
+    let creation_time = Timestamp::from(100);
+    let ttl = TimeDiff::from(Duration::from_millis(100));
+
+    let mut rng = crate::new_rng();
+    let deploys = (1..=DEPLOY_COUNT)
+        .map(|gas_price| {
+            generate_deploy(
+                &mut rng,
+                creation_time,
+                ttl,
+                vec![],
+                default_gas_payment(),
+                gas_price,
+            )
+        })
+        .collect_vec();
+
+    let max_buffer_size = buffered_size(&deploys) - 1;
+    let mut proposer = BlockProposerReady {
+        config: Config::new(max_buffer_size),
+        ..Default::default()
+    };
+    for deploy in &deploys {
+        proposer.add_deploy(creation_time, Box::new(deploy.clone()));
+    }
+
+    // exceeding the maximum by a single byte evicts enough deploys to leave some headroom
+    let evicted = proposer.evict_if_buffer_full();
+    assert!(evicted.len() > 1);
+    let expected = deploys
+        .iter()
+        .map(|deploy| *deploy.id())
+        .take(evicted.len());
+    assert!(evicted.into_iter().eq(expected));
+    let target_size = max_buffer_size - max_buffer_size / 100 * EVICTION_HYSTERESIS_PERCENT;
+    assert!(proposer.sets.pending_size() <= target_size);
+
+    // so the next deploy doesn't trigger another eviction
+    let next = generate_deploy(
+        &mut rng,
+        creation_time,
+        ttl,
+        vec![],
+        default_gas_payment(),
+        DEPLOY_COUNT + 1,
+    );
+    proposer.add_deploy(creation_time, Box::new(next));
+    assert!(proposer.evict_if_buffer_full().is_empty());
+}
+
+/// Simulates an upgrade which tightens the max deploy size while deploys are buffered.
+#[test]
+fn should_not_propose_deploys_exceeding_upgraded_max_deploy_size() {
+    let creation_time = Timestamp::from(100);
+    let ttl = TimeDiff::from(Duration::from_millis(100));
+    let block_time = Timestamp::from(120);
+
+    let mut rng = crate::new_rng();
+    let mut proposer = BlockProposerReady::default();
+    let deploy = generate_deploy(
+        &mut rng,
+        creation_time,
+        ttl,
+        vec![],
+        default_gas_payment(),
+        DEFAULT_TEST_GAS_PRICE,
+    );
+    let transfer = generate_transfer(&mut rng, creation_time, ttl, vec![], default_gas_payment());
+    let deploy_size = deploy.serialized_length();
+    let transfer_size = transfer.serialized_length();
+    proposer.add_deploy(creation_time, Box::new(deploy.clone()));
+    proposer.add_deploy(creation_time, Box::new(transfer.clone()));
+
+    let propose = |proposer: &mut BlockProposerReady| {
+        let payload = proposer.propose_block_payload(
+            proposer.deploy_config,
+            BlockContext::new(block_time, vec![]),
+            vec![],
+            true,
+        );
+        payload
+            .deploys_and_transfers_iter()
+            .map(|hash| *hash.deploy_hash())
+            .collect::<HashSet<_>>()
+    };
+
+    // Both are proposed until the upgrade is committed...
+    let expected: HashSet<_> = vec![*deploy.id(), *transfer.id()].into_iter().collect();
+    assert_eq!(propose(&mut proposer), expected);
+
+    // ...but neither is afterwards.
+    let mut upgrade_deploy_config = proposer.deploy_config;
+    upgrade_deploy_config.max_deploy_size = deploy_size.min(transfer_size) as u32 - 1;
+    proposer.activate_deploy_config(upgrade_deploy_config);
+    assert!(propose(&mut proposer).is_empty());
+}
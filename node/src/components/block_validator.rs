@@ -36,7 +36,7 @@ use crate::{
         EffectBuilder, EffectExt, EffectOptionExt, Effects, Responder,
     },
     types::{
-        appendable_block::AppendableBlock, Block, Chainspec, Deploy, DeployHash,
+        appendable_block::AppendableBlock, Block, Chainspec, Deploy, DeployExt, DeployHash,
         DeployOrTransferHash, Timestamp,
     },
     NodeRng,
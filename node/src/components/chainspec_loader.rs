@@ -10,12 +10,14 @@
 // TODO - remove once schemars stops causing warning.
 #![allow(clippy::field_reassign_with_default)]
 
+mod upgrade_schedule;
+
 use std::{
     fmt::{self, Display, Formatter},
     fs,
     path::{Path, PathBuf},
     str::FromStr,
-    sync::Arc,
+    sync::{atomic::Ordering, Arc},
     time::Duration,
 };
 
@@ -46,18 +48,22 @@ use crate::{
         requests::{
             ChainspecLoaderRequest, ContractRuntimeRequest, StateStoreRequest, StorageRequest,
         },
-        EffectBuilder, EffectExt, Effects,
+        EffectBuilder, EffectExt, EffectOptionExt, Effects,
     },
-    reactor::ReactorExit,
+    reactor::{self, ReactorExit},
     types::{
-        chainspec::{Error, ProtocolConfig, CHAINSPEC_NAME},
+        chainspec::{DeployConfig, Error, ProtocolConfig, CHAINSPEC_NAME},
         ActivationPoint, Block, BlockHash, BlockHeader, Chainspec, ChainspecInfo, ExitCode,
     },
     utils::{self, Loadable},
-    NodeRng,
+    NodeRng, UPGRADE_SCHEDULE_RELOAD_REQUESTED,
 };
+pub use upgrade_schedule::{ScheduledUpgrade, UpgradeSchedule};
 
 const UPGRADE_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+/// The interval at which we check whether reloading the upgrade schedule has been requested via
+/// `SIGHUP`.
+const UPGRADE_SCHEDULE_RELOAD_CHECK_INTERVAL: Duration = Duration::from_secs(1);
 
 /// `ChainspecHandler` events.
 #[derive(Debug, From, Serialize)]
@@ -76,6 +82,10 @@ pub enum Event {
     CheckForNextUpgrade,
     /// If the result of checking for an upgrade is successful, it is passed here.
     GotNextUpgrade(NextUpgrade),
+    /// Check whether reloading the upgrade schedule has been requested, and if so reload it.
+    CheckForUpgradeScheduleReload,
+    /// If reloading the upgrade schedule is successful, the new schedule is passed here.
+    GotUpgradeSchedule(UpgradeSchedule),
     /// The result of the `ChainspecHandler` putting a `Chainspec` to the storage component.
     PutToStorage { version: ProtocolVersion },
 }
@@ -103,6 +113,16 @@ impl Display for Event {
             Event::GotNextUpgrade(next_upgrade) => {
                 write!(formatter, "got {}", next_upgrade)
             }
+            Event::CheckForUpgradeScheduleReload => {
+                write!(formatter, "check for upgrade schedule reload")
+            }
+            Event::GotUpgradeSchedule(upgrade_schedule) => {
+                write!(
+                    formatter,
+                    "got upgrade schedule with {} upgrades",
+                    upgrade_schedule.len()
+                )
+            }
             Event::PutToStorage { version } => {
                 write!(formatter, "put chainspec {} to storage", version)
             }
@@ -117,6 +137,11 @@ pub struct NextUpgrade {
     #[data_size(skip)]
     #[schemars(with = "String")]
     protocol_version: ProtocolVersion,
+    /// The deploy config of the upgrade's chainspec, which comes into force once the upgrade is
+    /// committed.  `None` if it couldn't be parsed by this version.
+    #[serde(skip)]
+    #[schemars(skip)]
+    deploy_config: Option<DeployConfig>,
 }
 
 impl NextUpgrade {
@@ -127,6 +152,7 @@ impl NextUpgrade {
         NextUpgrade {
             activation_point,
             protocol_version,
+            deploy_config: None,
         }
     }
 
@@ -140,6 +166,7 @@ impl From<ProtocolConfig> for NextUpgrade {
         NextUpgrade {
             activation_point: protocol_config.activation_point,
             protocol_version: protocol_config.version,
+            deploy_config: None,
         }
     }
 }
@@ -167,6 +194,8 @@ pub struct CurrentRunInfo {
 #[derive(Clone, DataSize, Debug)]
 pub struct ChainspecLoader {
     chainspec: Arc<Chainspec>,
+    /// The hash of `chainspec`, computed once on construction.
+    chainspec_hash: Digest,
     /// The path to the folder where all chainspec and upgrade_point files will be stored in
     /// subdirs corresponding to their versions.
     root_dir: PathBuf,
@@ -175,6 +204,8 @@ pub struct ChainspecLoader {
     /// The initial state root hash for this session.
     initial_state_root_hash: Digest,
     next_upgrade: Option<NextUpgrade>,
+    /// The upgrades staged in `root_dir` after the current version, reloaded on `SIGHUP`.
+    upgrade_schedule: UpgradeSchedule,
     initial_block: Option<Block>,
 }
 
@@ -223,18 +254,49 @@ impl ChainspecLoader {
                 PathBuf::new()
             });
 
+        let chainspec_hash = chainspec.hash();
+        info!(
+            %chainspec_hash,
+            protocol_version = %chainspec.protocol_version(),
+            "loaded chainspec"
+        );
+
         if !chainspec.is_valid() || root_dir.as_os_str().is_empty() {
             let chainspec_loader = ChainspecLoader {
                 chainspec,
+                chainspec_hash,
                 root_dir,
                 reactor_exit: Some(ReactorExit::ProcessShouldExit(ExitCode::Abort)),
                 initial_state_root_hash: Digest::default(),
                 next_upgrade: None,
+                upgrade_schedule: UpgradeSchedule::default(),
                 initial_block: None,
             };
             return (chainspec_loader, Effects::new());
         }
 
+        let upgrade_schedule = match UpgradeSchedule::load(
+            &root_dir,
+            chainspec.protocol_config.version,
+            chainspec.protocol_config.activation_point,
+        ) {
+            Ok(upgrade_schedule) => upgrade_schedule,
+            Err(error) => {
+                error!(%error, "invalid upgrade schedule");
+                let chainspec_loader = ChainspecLoader {
+                    chainspec,
+                    chainspec_hash,
+                    root_dir,
+                    reactor_exit: Some(ReactorExit::ProcessShouldExit(ExitCode::Abort)),
+                    initial_state_root_hash: Digest::default(),
+                    next_upgrade: None,
+                    upgrade_schedule: UpgradeSchedule::default(),
+                    initial_block: None,
+                };
+                return (chainspec_loader, Effects::new());
+            }
+        };
+
         let next_upgrade = next_upgrade(root_dir.clone(), chainspec.protocol_config.version);
 
         // If the next activation point is the same as the current chainspec one, we've installed
@@ -262,21 +324,28 @@ impl ChainspecLoader {
                 })
         };
 
-        // Start regularly checking for the next upgrade.
+        // Start regularly checking for the next upgrade and for requests to reload the schedule.
         effects.extend(
             effect_builder
                 .set_timeout(UPGRADE_CHECK_INTERVAL)
                 .event(|_| Event::CheckForNextUpgrade),
         );
+        effects.extend(
+            effect_builder
+                .set_timeout(UPGRADE_SCHEDULE_RELOAD_CHECK_INTERVAL)
+                .event(|_| Event::CheckForUpgradeScheduleReload),
+        );
 
         let reactor_exit = should_stop.then(|| ReactorExit::ProcessShouldExit(ExitCode::Success));
 
         let chainspec_loader = ChainspecLoader {
             chainspec,
+            chainspec_hash,
             root_dir,
             reactor_exit,
             initial_state_root_hash: Digest::default(),
             next_upgrade,
+            upgrade_schedule,
             initial_block: None,
         };
 
@@ -294,13 +363,25 @@ impl ChainspecLoader {
     where
         REv: From<ChainspecLoaderAnnouncement> + Send,
     {
-        self.check_for_next_upgrade(effect_builder)
+        let mut effects = self.check_for_next_upgrade(effect_builder);
+        effects.extend(self.check_for_upgrade_schedule_reload(effect_builder));
+        effects
     }
 
     pub(crate) fn reactor_exit(&self) -> Option<ReactorExit> {
         self.reactor_exit
     }
 
+    /// Returns the reason for the node shutting down with `exit_code`, as reported to clients.  If
+    /// the node is stopping for an upgrade, this includes the upgrade's protocol version.
+    pub(crate) fn shutdown_reason(&self, exit_code: ExitCode) -> String {
+        let maybe_next_version = self
+            .next_upgrade
+            .as_ref()
+            .map(|next_upgrade| next_upgrade.protocol_version);
+        reactor::shutdown_reason(exit_code, maybe_next_version)
+    }
+
     /// The state root hash with which this session is starting.  It will be the result of running
     /// `ContractRuntime::commit_genesis()` or `ContractRuntime::upgrade()` or else the state root
     /// hash specified in the highest block.
@@ -312,6 +393,29 @@ impl ChainspecLoader {
         &self.chainspec
     }
 
+    /// Returns the deploy config of the next upgrade if `block_header` is the switch block which
+    /// commits that upgrade, i.e. the last block before its activation point.
+    ///
+    /// Reactors call this when the block is added, so that the components checking deploys switch
+    /// to the new limits in the same step as the upgrade is committed.
+    pub(crate) fn upgrade_deploy_config(&self, block_header: &BlockHeader) -> Option<DeployConfig> {
+        let next_upgrade = self.next_upgrade.as_ref()?;
+        if !block_header.is_switch_block()
+            || !next_upgrade
+                .activation_point
+                .should_upgrade(&block_header.era_id())
+        {
+            return None;
+        }
+        if next_upgrade.deploy_config.is_none() {
+            warn!(
+                %next_upgrade,
+                "committing upgrade without its deploy config; keeping the current deploy config"
+            );
+        }
+        next_upgrade.deploy_config
+    }
+
     pub(crate) fn next_upgrade(&self) -> Option<NextUpgrade> {
         self.next_upgrade.clone()
     }
@@ -583,8 +687,11 @@ impl ChainspecLoader {
     fn new_chainspec_info(&self) -> ChainspecInfo {
         ChainspecInfo::new(
             self.chainspec.network_config.name.clone(),
+            self.chainspec.protocol_version(),
+            self.chainspec_hash,
             self.initial_state_root_hash,
             self.next_upgrade.clone(),
+            self.upgrade_schedule.clone(),
         )
     }
 
@@ -639,9 +746,69 @@ impl ChainspecLoader {
                 );
             }
         }
+        match self.upgrade_schedule.get(next_upgrade.activation_point) {
+            Some(scheduled) if scheduled.protocol_version() == next_upgrade.protocol_version => (),
+            _ => warn!(
+                %next_upgrade,
+                "next upgrade is not in the upgrade schedule; send SIGHUP to reload the schedule"
+            ),
+        }
         self.next_upgrade = Some(next_upgrade);
         Effects::new()
     }
+
+    fn check_for_upgrade_schedule_reload<REv>(
+        &self,
+        effect_builder: EffectBuilder<REv>,
+    ) -> Effects<Event>
+    where
+        REv: Send,
+    {
+        let mut effects = effect_builder
+            .set_timeout(UPGRADE_SCHEDULE_RELOAD_CHECK_INTERVAL)
+            .event(|_| Event::CheckForUpgradeScheduleReload);
+
+        if !UPGRADE_SCHEDULE_RELOAD_REQUESTED.swap(false, Ordering::SeqCst) {
+            return effects;
+        }
+
+        info!("reloading upgrade schedule");
+        let root_dir = self.root_dir.clone();
+        let current_version = self.chainspec.protocol_config.version;
+        let current_activation_point = self.chainspec.protocol_config.activation_point;
+        effects.extend(
+            async move {
+                let result = task::spawn_blocking(move || {
+                    UpgradeSchedule::load(&root_dir, current_version, current_activation_point)
+                })
+                .await;
+                match result {
+                    Ok(Ok(upgrade_schedule)) => Some(upgrade_schedule),
+                    Ok(Err(error)) => {
+                        error!(%error, "invalid upgrade schedule; keeping the previous schedule");
+                        None
+                    }
+                    Err(error) => {
+                        warn!(%error, "failed to join tokio task");
+                        None
+                    }
+                }
+            }
+            .map_some(Event::GotUpgradeSchedule),
+        );
+        effects
+    }
+
+    fn handle_got_upgrade_schedule(&mut self, upgrade_schedule: UpgradeSchedule) -> Effects<Event> {
+        if upgrade_schedule != self.upgrade_schedule {
+            info!(
+                upgrade_count = upgrade_schedule.len(),
+                "changing upgrade schedule"
+            );
+        }
+        self.upgrade_schedule = upgrade_schedule;
+        Effects::new()
+    }
 }
 
 impl<REv> Component<REv> for ChainspecLoader
@@ -677,6 +844,12 @@ where
             }
             Event::CheckForNextUpgrade => self.check_for_next_upgrade(effect_builder),
             Event::GotNextUpgrade(next_upgrade) => self.handle_got_next_upgrade(next_upgrade),
+            Event::CheckForUpgradeScheduleReload => {
+                self.check_for_upgrade_schedule_reload(effect_builder)
+            }
+            Event::GotUpgradeSchedule(upgrade_schedule) => {
+                self.handle_got_upgrade_schedule(upgrade_schedule)
+            }
             Event::PutToStorage { version } => {
                 debug!("stored chainspec {}", version);
                 effect_builder
@@ -694,6 +867,17 @@ where
 struct UpgradePoint {
     #[serde(rename = "protocol")]
     pub(crate) protocol_config: ProtocolConfig,
+    /// Parsed separately from the protocol config, so that a deploy config this version can't
+    /// parse doesn't prevent the upgrade point being read.
+    #[serde(skip)]
+    pub(crate) deploy_config: Option<DeployConfig>,
+}
+
+/// The deploy config section of a TOML-encoded chainspec file.
+#[derive(Deserialize)]
+struct UpgradeDeployConfig {
+    #[serde(rename = "deploys")]
+    deploy_config: DeployConfig,
 }
 
 impl UpgradePoint {
@@ -701,7 +885,16 @@ impl UpgradePoint {
     fn from_chainspec_path<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
         let bytes = utils::read_file(path.as_ref().join(&CHAINSPEC_NAME))
             .map_err(Error::LoadUpgradePoint)?;
-        Ok(toml::from_slice(&bytes)?)
+        let mut upgrade_point: UpgradePoint = toml::from_slice(&bytes)?;
+        upgrade_point.deploy_config = match toml::from_slice::<UpgradeDeployConfig>(&bytes) {
+            Ok(upgrade_deploy_config) => Some(upgrade_deploy_config.deploy_config),
+            Err(error) => {
+                let path = path.as_ref().display();
+                warn!(%path, %error, "failed to parse upgrade deploy config");
+                None
+            }
+        };
+        Ok(upgrade_point)
     }
 }
 
@@ -767,8 +960,8 @@ fn next_installed_version(
 }
 
 /// Uses `next_installed_version()` to find the next versioned subdir.  If it exists, reads the
-/// UpgradePoint file from there and returns its version and activation point.  Returns `None` if
-/// there is no greater version available, or if any step errors.
+/// UpgradePoint file from there and returns its version, activation point and deploy config.
+/// Returns `None` if there is no greater version available, or if any step errors.
 fn next_upgrade(dir: PathBuf, current_version: ProtocolVersion) -> Option<NextUpgrade> {
     let next_version = match next_installed_version(&dir, &current_version) {
         Ok(version) => version,
@@ -800,7 +993,10 @@ fn next_upgrade(dir: PathBuf, current_version: ProtocolVersion) -> Option<NextUp
         return None;
     }
 
-    Some(NextUpgrade::from(upgrade_point.protocol_config))
+    Some(NextUpgrade {
+        deploy_config: upgrade_point.deploy_config,
+        ..NextUpgrade::from(upgrade_point.protocol_config)
+    })
 }
 
 #[cfg(test)]
@@ -930,23 +1126,46 @@ mod tests {
             next_upgrade(tempdir.path().to_path_buf(), *current_version).unwrap()
         };
 
+        let expected_upgrade = |chainspec: Chainspec| NextUpgrade {
+            deploy_config: Some(chainspec.deploy_config),
+            ..NextUpgrade::from(chainspec.protocol_config)
+        };
+
         let mut rng = crate::new_rng();
 
         let mut current = ProtocolVersion::from_parts(0, 9, 9);
         let v1_0_0 = ProtocolVersion::from_parts(1, 0, 0);
         let chainspec_v1_0_0 = install_chainspec(&mut rng, tempdir.path(), &v1_0_0);
-        assert_eq!(
-            next_point(&current),
-            chainspec_v1_0_0.protocol_config.into()
-        );
+        assert_eq!(next_point(&current), expected_upgrade(chainspec_v1_0_0));
 
         current = v1_0_0;
         let v1_0_3 = ProtocolVersion::from_parts(1, 0, 3);
         let chainspec_v1_0_3 = install_chainspec(&mut rng, tempdir.path(), &v1_0_3);
-        assert_eq!(
-            next_point(&current),
-            chainspec_v1_0_3.protocol_config.into()
-        );
+        assert_eq!(next_point(&current), expected_upgrade(chainspec_v1_0_3));
+    }
+
+    #[test]
+    fn should_get_next_upgrade_without_unparseable_deploy_config() {
+        let tempdir = tempfile::tempdir().expect("should create temp dir");
+        let mut rng = crate::new_rng();
+
+        let current = ProtocolVersion::from_parts(1, 0, 0);
+        let v1_1_0 = ProtocolVersion::from_parts(1, 1, 0);
+        let chainspec_v1_1_0 = install_chainspec(&mut rng, tempdir.path(), &v1_1_0);
+
+        // Add a field to the deploy config which this version doesn't know about.
+        let path = tempdir
+            .path()
+            .join(dir_name_from_version(&v1_1_0))
+            .join(CHAINSPEC_NAME);
+        let contents = fs::read_to_string(&path)
+            .unwrap()
+            .replace("[deploys]", "[deploys]\nmax_future_field = 1");
+        fs::write(&path, contents).unwrap();
+
+        let next_upgrade = next_upgrade(tempdir.path().to_path_buf(), current).unwrap();
+        assert_eq!(next_upgrade, chainspec_v1_1_0.protocol_config.into());
+        assert!(next_upgrade.deploy_config.is_none());
     }
 
     #[test]
@@ -1015,12 +1234,15 @@ mod tests {
             chainspec.protocol_config.version = ProtocolVersion::from_parts(1, 5, 0);
             chainspec.protocol_config.activation_point = ActivationPoint::EraId(EraId::new(300));
 
+            let chainspec_hash = chainspec.hash();
             let chainspec_loader = ChainspecLoader {
                 chainspec: Arc::new(chainspec),
+                chainspec_hash,
                 root_dir: PathBuf::from("."),
                 reactor_exit: None,
                 initial_state_root_hash: Digest::default(),
                 next_upgrade: None,
+                upgrade_schedule: UpgradeSchedule::default(),
                 initial_block: None,
             };
 
@@ -1072,6 +1294,7 @@ mod tests {
                     self.current_activation_point() + era_diff,
                 ),
                 protocol_version: self.later_protocol_version(),
+                deploy_config: None,
             });
         }
 
@@ -1354,4 +1577,46 @@ mod tests {
         fixture.assert_handle_initialize(Some(highest_block), 0);
         fixture.assert_process_should_upgrade();
     }
+
+    /// Checks that the next upgrade's deploy config is only handed out for the switch block which
+    /// commits the upgrade.
+    #[test]
+    fn should_get_upgrade_deploy_config_at_upgrade_commit_point() {
+        let mut fixture = TestFixture::new();
+        let mut rng = TestRng::new();
+
+        // Set an upgrade for 10 eras after the current chainspec activation point, which halves the
+        // max deploy size.
+        let era_diff = 10;
+        fixture.set_next_upgrade(era_diff);
+        let mut upgrade_deploy_config = fixture.chainspec_loader.chainspec.deploy_config;
+        upgrade_deploy_config.max_deploy_size /= 2;
+        fixture
+            .chainspec_loader
+            .next_upgrade
+            .as_mut()
+            .unwrap()
+            .deploy_config = Some(upgrade_deploy_config);
+
+        let version = fixture.current_protocol_version();
+        let last_era = fixture.current_activation_point() + era_diff - 1;
+        let mut upgrade_deploy_config_for = |era_id: EraId, is_switch: bool| {
+            let height = rng.gen();
+            let block = Block::random_with_specifics(&mut rng, era_id, height, version, is_switch);
+            fixture
+                .chainspec_loader
+                .upgrade_deploy_config(block.header())
+        };
+
+        // Neither an earlier switch block nor a non-switch block in the last era commit the
+        // upgrade.
+        assert!(upgrade_deploy_config_for(last_era - 1, true).is_none());
+        assert!(upgrade_deploy_config_for(last_era, false).is_none());
+
+        // The switch block of the last era does.
+        assert_eq!(
+            upgrade_deploy_config_for(last_era, true),
+            Some(upgrade_deploy_config)
+        );
+    }
 }
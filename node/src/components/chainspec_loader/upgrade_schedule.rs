@@ -0,0 +1,563 @@
+//! The schedule of protocol upgrades staged on disk.
+//!
+//! Each upgrade is staged in a subdir of the chainspec loader's root dir, named after the new
+//! protocol version with dots replaced by underscores, and holding the new chainspec along with an
+//! optional global state update.
+
+// TODO - remove once schemars stops causing warning.
+#![allow(clippy::field_reassign_with_default)]
+
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use datasize::DataSize;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::{debug, trace};
+
+use casper_execution_engine::shared::stored_value::StoredValue;
+use casper_types::{bytesrepr, EraId, Key, ProtocolVersion};
+
+use super::UpgradePoint;
+use crate::{
+    types::{
+        chainspec::{Error as ChainspecError, GlobalStateUpdate},
+        ActivationPoint,
+    },
+    utils::Loadable,
+};
+
+/// Error loading the upgrade schedule.
+#[derive(Debug, Error)]
+pub enum UpgradeScheduleError {
+    /// Error reading the root dir.
+    #[error("failed to read dir {}: {error}", dir.display())]
+    ReadDir {
+        /// The dir being read.
+        dir: PathBuf,
+        /// The underlying error.
+        error: io::Error,
+    },
+
+    /// Two subdirs are named for the same protocol version.
+    #[error(
+        "{} and {} both stage an upgrade to {version}",
+        first_dir.display(),
+        second_dir.display()
+    )]
+    DuplicateVersion {
+        /// The duplicated version.
+        version: ProtocolVersion,
+        /// The first subdir staging the version.
+        first_dir: PathBuf,
+        /// The second subdir staging the version.
+        second_dir: PathBuf,
+    },
+
+    /// Error loading the chainspec or global state update from a subdir.
+    #[error("failed to load upgrade point from {}: {error}", dir.display())]
+    LoadUpgradePoint {
+        /// The subdir being loaded.
+        dir: PathBuf,
+        /// The underlying error.
+        error: ChainspecError,
+    },
+
+    /// The protocol version in a chainspec doesn't match the name of its subdir.
+    #[error(
+        "chainspec in {} is for version {found}, but should be for {expected}",
+        dir.display()
+    )]
+    VersionMismatch {
+        /// The subdir holding the chainspec.
+        dir: PathBuf,
+        /// The version given by the subdir's name.
+        expected: ProtocolVersion,
+        /// The version given in the chainspec.
+        found: ProtocolVersion,
+    },
+
+    /// An upgrade's activation point is a genesis timestamp rather than an era.
+    #[error("upgrade to {version} must be activated at an era, not at genesis")]
+    GenesisActivationPoint {
+        /// The version being upgraded to.
+        version: ProtocolVersion,
+    },
+
+    /// A value in an upgrade's global state update isn't a valid `StoredValue`.
+    #[error("global state update for {version} has an invalid value under {key}: {error}")]
+    InvalidGlobalStateValue {
+        /// The version being upgraded to.
+        version: ProtocolVersion,
+        /// The key of the invalid value.
+        key: Key,
+        /// The underlying error.
+        error: bytesrepr::Error,
+    },
+
+    /// An upgrade's activation point is not after that of the preceding version.
+    #[error(
+        "upgrade to {version} is activated at era {activation_era}, but {previous_version} is \
+        activated at era {previous_activation_era}"
+    )]
+    OutOfOrder {
+        /// The version being upgraded to.
+        version: ProtocolVersion,
+        /// The era at which the upgrade is activated.
+        activation_era: EraId,
+        /// The preceding version.
+        previous_version: ProtocolVersion,
+        /// The era at which the preceding version is activated.
+        previous_activation_era: EraId,
+    },
+}
+
+/// A protocol upgrade staged on disk.
+#[derive(Clone, PartialEq, Eq, DataSize, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ScheduledUpgrade {
+    activation_point: ActivationPoint,
+    #[data_size(skip)]
+    #[schemars(with = "String")]
+    protocol_version: ProtocolVersion,
+    /// Whether the upgrade applies a global state update.
+    has_global_state_update: bool,
+}
+
+impl ScheduledUpgrade {
+    pub(crate) fn new(
+        activation_point: ActivationPoint,
+        protocol_version: ProtocolVersion,
+        has_global_state_update: bool,
+    ) -> Self {
+        ScheduledUpgrade {
+            activation_point,
+            protocol_version,
+            has_global_state_update,
+        }
+    }
+
+    pub(crate) fn protocol_version(&self) -> ProtocolVersion {
+        self.protocol_version
+    }
+}
+
+/// The upgrades staged on disk after the current protocol version, ordered by activation point.
+#[derive(Clone, Default, PartialEq, Eq, DataSize, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct UpgradeSchedule(Vec<ScheduledUpgrade>);
+
+impl UpgradeSchedule {
+    /// Constructs a new `UpgradeSchedule` from upgrades already ordered by activation point.
+    pub(crate) fn new(upgrades: Vec<ScheduledUpgrade>) -> Self {
+        UpgradeSchedule(upgrades)
+    }
+
+    /// Scans `dir` for versioned subdirs, loading and validating an upgrade from each subdir whose
+    /// version is greater than `current_version`.
+    ///
+    /// Versions needn't be consecutive, but each must be activated strictly later than the one
+    /// before it.  The first may be activated at `current_activation_point`, in which case the
+    /// current version is replaced by it immediately.
+    pub(crate) fn load(
+        dir: &Path,
+        current_version: ProtocolVersion,
+        current_activation_point: ActivationPoint,
+    ) -> Result<Self, UpgradeScheduleError> {
+        let mut upgrades = vec![];
+        for (version, subdir) in installed_versions(dir)? {
+            if version <= current_version {
+                continue;
+            }
+            upgrades.push(load_upgrade(&subdir, version)?);
+        }
+
+        let mut previous_version = current_version;
+        let mut previous_activation_era = current_activation_point.era_id();
+        for (index, upgrade) in upgrades.iter().enumerate() {
+            let activation_era = upgrade.activation_point.era_id();
+            let in_order = if index == 0 {
+                activation_era >= previous_activation_era
+            } else {
+                activation_era > previous_activation_era
+            };
+            if !in_order {
+                return Err(UpgradeScheduleError::OutOfOrder {
+                    version: upgrade.protocol_version,
+                    activation_era,
+                    previous_version,
+                    previous_activation_era,
+                });
+            }
+            previous_version = upgrade.protocol_version;
+            previous_activation_era = activation_era;
+        }
+
+        Ok(UpgradeSchedule::new(upgrades))
+    }
+
+    /// Returns the upgrade activated at `activation_point`, if any.
+    pub(crate) fn get(&self, activation_point: ActivationPoint) -> Option<&ScheduledUpgrade> {
+        let era_id = activation_point.era_id();
+        self.0
+            .binary_search_by_key(&era_id, |upgrade| upgrade.activation_point.era_id())
+            .ok()
+            .map(|index| &self.0[index])
+    }
+
+    /// Returns the number of scheduled upgrades.
+    pub(crate) fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+/// Returns the versions named by the subdirs of `dir`, ordered by version.  Entries not named as a
+/// version are skipped.
+fn installed_versions(
+    dir: &Path,
+) -> Result<BTreeMap<ProtocolVersion, PathBuf>, UpgradeScheduleError> {
+    let mut versions = BTreeMap::new();
+    for entry in fs::read_dir(dir).map_err(|error| UpgradeScheduleError::ReadDir {
+        dir: dir.to_path_buf(),
+        error,
+    })? {
+        let path = match entry {
+            Ok(dir_entry) => dir_entry.path(),
+            Err(error) => {
+                debug!(dir=%dir.display(), %error, "bad entry while reading dir");
+                continue;
+            }
+        };
+
+        let subdir_name = match path.file_name() {
+            Some(name) => name.to_string_lossy().replace("_", "."),
+            None => continue,
+        };
+
+        let version = match ProtocolVersion::from_str(&subdir_name) {
+            Ok(version) => version,
+            Err(error) => {
+                trace!(%error, path=%path.display(), "failed to get a version");
+                continue;
+            }
+        };
+
+        if let Some(first_dir) = versions.insert(version, path.clone()) {
+            return Err(UpgradeScheduleError::DuplicateVersion {
+                version,
+                first_dir,
+                second_dir: path,
+            });
+        }
+    }
+    Ok(versions)
+}
+
+/// Loads and validates the upgrade staged in `subdir`.
+fn load_upgrade(
+    subdir: &Path,
+    version: ProtocolVersion,
+) -> Result<ScheduledUpgrade, UpgradeScheduleError> {
+    let load_error = |error| UpgradeScheduleError::LoadUpgradePoint {
+        dir: subdir.to_path_buf(),
+        error,
+    };
+
+    let protocol_config = UpgradePoint::from_chainspec_path(subdir)
+        .map_err(load_error)?
+        .protocol_config;
+    if protocol_config.version != version {
+        return Err(UpgradeScheduleError::VersionMismatch {
+            dir: subdir.to_path_buf(),
+            expected: version,
+            found: protocol_config.version,
+        });
+    }
+    if let ActivationPoint::Genesis(_) = protocol_config.activation_point {
+        return Err(UpgradeScheduleError::GenesisActivationPoint { version });
+    }
+
+    let global_state_update =
+        Option::<GlobalStateUpdate>::from_path(subdir).map_err(|error| load_error(error.into()))?;
+    if let Some(global_state_update) = global_state_update.as_ref() {
+        for (key, value) in global_state_update.0.iter() {
+            bytesrepr::deserialize::<StoredValue>(value.to_vec()).map_err(|error| {
+                UpgradeScheduleError::InvalidGlobalStateValue {
+                    version,
+                    key: *key,
+                    error,
+                }
+            })?;
+        }
+    }
+
+    Ok(ScheduledUpgrade::new(
+        protocol_config.activation_point,
+        version,
+        global_state_update.is_some(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::{bytesrepr::ToBytes, CLValue};
+
+    use super::{super::dir_name_from_version, *};
+    use crate::{
+        testing::TestRng,
+        types::{chainspec::CHAINSPEC_NAME, Chainspec, Timestamp},
+    };
+
+    const GLOBAL_STATE_UPDATE_FILENAME: &str = "global_state.toml";
+
+    fn current_activation_point() -> ActivationPoint {
+        ActivationPoint::EraId(EraId::from(10))
+    }
+
+    /// Creates the appropriate subdir in `root_dir`, and adds a random chainspec.toml with the
+    /// given version and activation era.
+    fn install_upgrade(rng: &mut TestRng, root_dir: &Path, version: ProtocolVersion, era: u64) {
+        let mut chainspec = Chainspec::random(rng);
+        chainspec.protocol_config.version = version;
+        chainspec.protocol_config.activation_point = ActivationPoint::EraId(EraId::from(era));
+        chainspec.protocol_config.global_state_update = None;
+
+        let subdir = root_dir.join(dir_name_from_version(&version));
+        fs::create_dir(&subdir).unwrap();
+        fs::write(
+            subdir.join(CHAINSPEC_NAME),
+            toml::to_string_pretty(&chainspec).expect("should encode to toml"),
+        )
+        .expect("should install chainspec");
+    }
+
+    /// Adds a global state update file for `version`, with a single entry having the given
+    /// base64-encoded value.
+    fn install_global_state_update(root_dir: &Path, version: ProtocolVersion, value: &str) {
+        let path = root_dir
+            .join(dir_name_from_version(&version))
+            .join(GLOBAL_STATE_UPDATE_FILENAME);
+        let contents = format!(
+            "[[entries]]\nkey = \"{}\"\nvalue = \"{}\"\n",
+            Key::EraInfo(EraId::from(1)).to_formatted_string(),
+            value
+        );
+        fs::write(path, contents).expect("should install global state update");
+    }
+
+    fn load(root_dir: &Path) -> Result<UpgradeSchedule, UpgradeScheduleError> {
+        UpgradeSchedule::load(
+            root_dir,
+            ProtocolVersion::from_parts(1, 0, 0),
+            current_activation_point(),
+        )
+    }
+
+    #[test]
+    fn should_load_multiple_upgrades() {
+        let tempdir = tempfile::tempdir().expect("should create temp dir");
+        let mut rng = crate::new_rng();
+
+        let v1_0_0 = ProtocolVersion::from_parts(1, 0, 0);
+        let v1_0_1 = ProtocolVersion::from_parts(1, 0, 1);
+        let v1_1_0 = ProtocolVersion::from_parts(1, 1, 0);
+        let v2_0_0 = ProtocolVersion::from_parts(2, 0, 0);
+        // The current version and unversioned entries should be ignored.
+        install_upgrade(&mut rng, tempdir.path(), v1_0_0, 10);
+        fs::create_dir(tempdir.path().join("not-a-version")).unwrap();
+        // Install out of version order to check the schedule is sorted.
+        install_upgrade(&mut rng, tempdir.path(), v2_0_0, 30);
+        install_upgrade(&mut rng, tempdir.path(), v1_0_1, 11);
+        install_upgrade(&mut rng, tempdir.path(), v1_1_0, 20);
+
+        let stored_value = StoredValue::CLValue(CLValue::from_t(1u64).unwrap());
+        let value = base64::encode(stored_value.to_bytes().unwrap());
+        install_global_state_update(tempdir.path(), v1_1_0, &value);
+
+        let schedule = load(tempdir.path()).unwrap();
+        assert_eq!(schedule.len(), 3);
+        let versions: Vec<_> = schedule
+            .0
+            .iter()
+            .map(|upgrade| upgrade.protocol_version)
+            .collect();
+        assert_eq!(versions, vec![v1_0_1, v1_1_0, v2_0_0]);
+
+        let upgrade = schedule
+            .get(ActivationPoint::EraId(EraId::from(20)))
+            .unwrap();
+        assert_eq!(upgrade.protocol_version(), v1_1_0);
+        assert!(upgrade.has_global_state_update);
+        assert!(!schedule.0[0].has_global_state_update);
+        assert!(schedule
+            .get(ActivationPoint::EraId(EraId::from(21)))
+            .is_none());
+    }
+
+    #[test]
+    fn should_load_empty_schedule() {
+        let tempdir = tempfile::tempdir().expect("should create temp dir");
+        assert!(load(tempdir.path()).unwrap().0.is_empty());
+    }
+
+    #[test]
+    fn should_allow_gap_in_versions() {
+        let tempdir = tempfile::tempdir().expect("should create temp dir");
+        let mut rng = crate::new_rng();
+
+        // Skipping from 1.0.0 to 1.3.0 and then to 3.0.0 is allowed, as long as activation points
+        // are increasing.
+        let v1_3_0 = ProtocolVersion::from_parts(1, 3, 0);
+        let v3_0_0 = ProtocolVersion::from_parts(3, 0, 0);
+        install_upgrade(&mut rng, tempdir.path(), v1_3_0, 15);
+        install_upgrade(&mut rng, tempdir.path(), v3_0_0, 16);
+
+        let schedule = load(tempdir.path()).unwrap();
+        assert_eq!(schedule.len(), 2);
+        assert_eq!(
+            schedule
+                .get(ActivationPoint::EraId(EraId::from(16)))
+                .unwrap()
+                .protocol_version(),
+            v3_0_0
+        );
+    }
+
+    #[test]
+    fn should_fail_if_versions_out_of_order() {
+        let tempdir = tempfile::tempdir().expect("should create temp dir");
+        let mut rng = crate::new_rng();
+
+        // The higher version activates before the lower one.
+        let v1_1_0 = ProtocolVersion::from_parts(1, 1, 0);
+        let v1_2_0 = ProtocolVersion::from_parts(1, 2, 0);
+        install_upgrade(&mut rng, tempdir.path(), v1_1_0, 20);
+        install_upgrade(&mut rng, tempdir.path(), v1_2_0, 19);
+
+        assert!(matches!(
+            load(tempdir.path()),
+            Err(UpgradeScheduleError::OutOfOrder {
+                version,
+                previous_version,
+                ..
+            }) if version == v1_2_0 && previous_version == v1_1_0
+        ));
+    }
+
+    #[test]
+    fn should_fail_if_activation_points_conflict() {
+        let tempdir = tempfile::tempdir().expect("should create temp dir");
+        let mut rng = crate::new_rng();
+
+        let v1_1_0 = ProtocolVersion::from_parts(1, 1, 0);
+        let v1_2_0 = ProtocolVersion::from_parts(1, 2, 0);
+        install_upgrade(&mut rng, tempdir.path(), v1_1_0, 20);
+        install_upgrade(&mut rng, tempdir.path(), v1_2_0, 20);
+        assert!(matches!(
+            load(tempdir.path()),
+            Err(UpgradeScheduleError::OutOfOrder { .. })
+        ));
+    }
+
+    #[test]
+    fn should_fail_if_activated_before_current_version() {
+        let tempdir = tempfile::tempdir().expect("should create temp dir");
+        let mut rng = crate::new_rng();
+
+        install_upgrade(
+            &mut rng,
+            tempdir.path(),
+            ProtocolVersion::from_parts(1, 1, 0),
+            9,
+        );
+        assert!(matches!(
+            load(tempdir.path()),
+            Err(UpgradeScheduleError::OutOfOrder { .. })
+        ));
+    }
+
+    #[test]
+    fn should_fail_with_malformed_upgrade_file() {
+        let tempdir = tempfile::tempdir().expect("should create temp dir");
+        let mut rng = crate::new_rng();
+
+        let v1_1_0 = ProtocolVersion::from_parts(1, 1, 0);
+        install_upgrade(&mut rng, tempdir.path(), v1_1_0, 20);
+        let chainspec_path = tempdir
+            .path()
+            .join(dir_name_from_version(&v1_1_0))
+            .join(CHAINSPEC_NAME);
+        fs::write(&chainspec_path, "bad data".as_bytes()).unwrap();
+        assert!(matches!(
+            load(tempdir.path()),
+            Err(UpgradeScheduleError::LoadUpgradePoint { .. })
+        ));
+
+        // A missing chainspec is also an error.
+        fs::remove_file(&chainspec_path).unwrap();
+        assert!(matches!(
+            load(tempdir.path()),
+            Err(UpgradeScheduleError::LoadUpgradePoint { .. })
+        ));
+    }
+
+    #[test]
+    fn should_fail_with_malformed_global_state_update() {
+        let tempdir = tempfile::tempdir().expect("should create temp dir");
+        let mut rng = crate::new_rng();
+
+        let v1_1_0 = ProtocolVersion::from_parts(1, 1, 0);
+        install_upgrade(&mut rng, tempdir.path(), v1_1_0, 20);
+
+        // Not base64.
+        install_global_state_update(tempdir.path(), v1_1_0, "not base64!");
+        assert!(matches!(
+            load(tempdir.path()),
+            Err(UpgradeScheduleError::LoadUpgradePoint { .. })
+        ));
+
+        // Valid base64, but not a `StoredValue`.
+        install_global_state_update(tempdir.path(), v1_1_0, &base64::encode(&[255, 1, 2]));
+        assert!(matches!(
+            load(tempdir.path()),
+            Err(UpgradeScheduleError::InvalidGlobalStateValue { version, .. }) if version == v1_1_0
+        ));
+    }
+
+    #[test]
+    fn should_fail_with_mismatched_version_or_genesis_activation_point() {
+        let tempdir = tempfile::tempdir().expect("should create temp dir");
+        let mut rng = crate::new_rng();
+
+        let v1_1_0 = ProtocolVersion::from_parts(1, 1, 0);
+        let mut chainspec = Chainspec::random(&mut rng);
+        chainspec.protocol_config.version = ProtocolVersion::from_parts(1, 2, 0);
+        chainspec.protocol_config.activation_point = ActivationPoint::EraId(EraId::from(20));
+        let subdir = tempdir.path().join(dir_name_from_version(&v1_1_0));
+        fs::create_dir(&subdir).unwrap();
+        let write_chainspec = |chainspec: &Chainspec| {
+            fs::write(
+                subdir.join(CHAINSPEC_NAME),
+                toml::to_string_pretty(chainspec).expect("should encode to toml"),
+            )
+            .unwrap()
+        };
+
+        write_chainspec(&chainspec);
+        assert!(matches!(
+            load(tempdir.path()),
+            Err(UpgradeScheduleError::VersionMismatch { .. })
+        ));
+
+        chainspec.protocol_config.version = v1_1_0;
+        chainspec.protocol_config.activation_point = ActivationPoint::Genesis(Timestamp::now());
+        write_chainspec(&chainspec);
+        assert!(matches!(
+            load(tempdir.path()),
+            Err(UpgradeScheduleError::GenesisActivationPoint { .. })
+        ));
+    }
+}
@@ -136,6 +136,10 @@ pub enum Event<I> {
     },
     /// Got the result of checking for an upgrade activation point.
     GotUpgradeActivationPoint(ActivationPoint),
+    /// A peer identifying as the given validator has connected.
+    ValidatorConnected { peer_id: I, validator: PublicKey },
+    /// A peer identifying as the given validator has disconnected.
+    ValidatorDisconnected { peer_id: I, validator: PublicKey },
 }
 
 impl Debug for ConsensusMessage {
@@ -235,6 +239,12 @@ impl<I: Debug> Display for Event<I> {
             Event::GotUpgradeActivationPoint(activation_point) => {
                 write!(f, "new upgrade activation point: {:?}", activation_point)
             }
+            Event::ValidatorConnected { peer_id, validator } => {
+                write!(f, "validator {} connected via {:?}", validator, peer_id)
+            }
+            Event::ValidatorDisconnected { peer_id, validator } => {
+                write!(f, "validator {} disconnected from {:?}", validator, peer_id)
+            }
         }
     }
 }
@@ -337,9 +347,18 @@ where
             Event::GotUpgradeActivationPoint(activation_point) => {
                 handling_es.got_upgrade_activation_point(activation_point)
             }
+            Event::ValidatorConnected { peer_id, validator } => {
+                handling_es.handle_validator_connectivity(peer_id, validator, true)
+            }
+            Event::ValidatorDisconnected { peer_id, validator } => {
+                handling_es.handle_validator_connectivity(peer_id, validator, false)
+            }
             Event::ConsensusRequest(ConsensusRequest::Status(responder)) => {
                 handling_es.status(responder)
             }
+            Event::ConsensusRequest(ConsensusRequest::EraProgress(responder)) => {
+                handling_es.era_progress(responder)
+            }
         }
     }
 }
@@ -1,24 +1,28 @@
 use std::{path::Path, sync::Arc};
 
 use datasize::DataSize;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use casper_types::{ProtocolVersion, PublicKey, SecretKey};
 
 use crate::{
     components::consensus::{protocols::highway::config::Config as HighwayConfig, EraId},
-    crypto::hash::Digest,
+    crypto::{self, hash::Digest, SecretKeyExt},
     types::{chainspec::HighwayConfig as HighwayProtocolConfig, Chainspec, TimeDiff, Timestamp},
     utils::{External, LoadError, Loadable},
 };
 
 /// Consensus configuration.
-#[derive(DataSize, Debug, Deserialize, Clone)]
+#[derive(DataSize, Debug, Deserialize, Serialize, Clone)]
 // Disallow unknown fields to ensure config files and command-line overrides contain valid keys.
 #[serde(deny_unknown_fields)]
 pub struct Config {
     /// Path to secret key file.
+    #[serde(skip_serializing_if = "External::is_missing")]
     pub secret_key_path: External<Arc<SecretKey>>,
+    /// Whether to load the secret key even if its file is readable by all users.
+    #[serde(default)]
+    pub allow_world_readable_secret_key: bool,
     /// Highway-specific node configuration.
     pub highway: HighwayConfig,
 }
@@ -27,6 +31,7 @@ impl Default for Config {
     fn default() -> Self {
         Config {
             secret_key_path: External::Missing,
+            allow_world_readable_secret_key: false,
             highway: HighwayConfig::default(),
         }
     }
@@ -34,12 +39,20 @@ impl Default for Config {
 
 impl Config {
     /// Loads the secret key from the configuration file and derives the public key.
+    ///
+    /// A secret key file readable by all users is refused unless `allow_world_readable_secret_key`
+    /// is set.
     #[allow(clippy::type_complexity)]
     pub(crate) fn load_keys<P: AsRef<Path>>(
         &self,
         root: P,
     ) -> Result<(Arc<SecretKey>, PublicKey), LoadError<<Arc<SecretKey> as Loadable>::Error>> {
-        let secret_signing_key = self.secret_key_path.clone().load(root)?;
+        let allow_world_readable = self.allow_world_readable_secret_key;
+        let secret_signing_key = self.secret_key_path.clone().load_with(root, |path| {
+            SecretKey::from_file_with_policy(path, allow_world_readable)
+                .map(Arc::new)
+                .map_err(crypto::Error::SecretKeyLoad)
+        })?;
         let public_key = PublicKey::from(secret_signing_key.as_ref());
         Ok((secret_signing_key, public_key))
     }
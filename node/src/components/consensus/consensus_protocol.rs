@@ -15,7 +15,7 @@ use crate::{
 };
 
 /// Information about the context in which a new block is created.
-#[derive(Clone, DataSize, Eq, PartialEq, Debug, Ord, PartialOrd, Hash)]
+#[derive(Clone, DataSize, Eq, PartialEq, Debug, Ord, PartialOrd, Hash, Serialize)]
 pub struct BlockContext<C>
 where
     C: Context,
@@ -241,6 +241,11 @@ pub(crate) trait ConsensusProtocol<I, C: Context>: Send {
     /// Sets the pause status: While paused we don't create consensus messages other than pings.
     fn set_paused(&mut self, paused: bool);
 
+    /// Sets whether the validator `vid` is currently connected to us.
+    ///
+    /// This is only a hint for timing heuristics, and must not affect which values get finalized.
+    fn set_validator_connected(&mut self, vid: &C::ValidatorId, connected: bool);
+
     /// Returns the list of all validators that were observed as faulty in this consensus instance.
     fn validators_with_evidence(&self) -> Vec<&C::ValidatorId>;
 
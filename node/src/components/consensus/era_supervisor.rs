@@ -51,7 +51,7 @@ use crate::{
     },
     fatal,
     types::{
-        ActivationPoint, BlockHash, BlockHeader, DeployHash, DeployOrTransferHash,
+        ActivationPoint, BlockHash, BlockHeader, DeployHash, DeployOrTransferHash, EraProgress,
         FinalitySignature, FinalizedBlock, TimeDiff, Timestamp,
     },
     utils::WithDir,
@@ -117,6 +117,11 @@ pub struct EraSupervisor<I> {
     stop_for_upgrade: bool,
     /// The era that was current when this node joined the network.
     era_where_we_joined: EraId,
+    /// The connected peers that identified as each validator we have seen so far.
+    ///
+    /// A validator may reconnect with a new node ID, so it is only considered disconnected once it
+    /// has no connected peers left.
+    validator_peers: HashMap<PublicKey, HashSet<I>>,
 }
 
 impl<I> Debug for EraSupervisor<I> {
@@ -175,6 +180,7 @@ where
             stop_for_upgrade: false,
             next_executed_height: next_height,
             era_where_we_joined: current_era,
+            validator_peers: HashMap::new(),
         };
 
         let bonded_eras = era_supervisor.bonded_eras();
@@ -347,6 +353,13 @@ where
             now,
         );
 
+        // Validator indices are specific to each era, so the new instance needs to be told again.
+        for (validator, peers) in &self.validator_peers {
+            if peers.is_empty() {
+                consensus.set_validator_connected(validator, false);
+            }
+        }
+
         if should_activate {
             let secret = Keypair::new(self.secret_signing_key.clone(), our_id.clone());
             outcomes.extend(consensus.activate_validator(
@@ -428,6 +441,20 @@ where
         self.stop_for_upgrade
     }
 
+    /// Returns the progress of the current era at the time `now`, or `None` if it isn't active.
+    pub(crate) fn era_progress(&self, now: Timestamp) -> Option<EraProgress> {
+        let era = self.active_eras.get(&self.current_era)?;
+        Some(EraProgress::new(
+            self.current_era,
+            era.start_time,
+            self.protocol_config.era_duration,
+            self.protocol_config.minimum_era_height,
+            self.next_block_height.saturating_sub(era.start_height),
+            era.consensus.next_round_length(),
+            now,
+        ))
+    }
+
     /// Updates `next_executed_height` based on the given block header, and unpauses consensus if
     /// block execution has caught up with finalization.
     #[allow(clippy::integer_arithmetic)] // Block height should never reach u64::MAX.
@@ -436,6 +463,27 @@ where
         self.update_consensus_pause();
     }
 
+    /// Records that the peer identifying as `validator` has connected or disconnected, and informs
+    /// the active eras if this changes whether the validator is connected at all.
+    fn update_validator_peers(&mut self, peer_id: I, validator: PublicKey, connected: bool) {
+        let peers = self.validator_peers.entry(validator.clone()).or_default();
+        let was_connected = !peers.is_empty();
+        if connected {
+            peers.insert(peer_id);
+        } else {
+            peers.remove(&peer_id);
+        }
+        let is_connected = !peers.is_empty();
+        if was_connected == is_connected {
+            return;
+        }
+        debug!(%validator, %is_connected, "validator connectivity changed");
+        for era in self.active_eras.values_mut() {
+            era.consensus
+                .set_validator_connected(&validator, is_connected);
+        }
+    }
+
     /// Pauses or unpauses consensus: Whenever the last executed block is too far behind the last
     /// finalized block, we suspend consensus.
     fn update_consensus_pause(&mut self) {
@@ -1102,11 +1150,25 @@ where
                 self.era_supervisor
                     .metrics
                     .finalized_block(&finalized_block);
-                // Announce the finalized block.
-                let mut effects = self
-                    .effect_builder
-                    .announce_finalized_block(finalized_block.clone())
-                    .ignore();
+                // Announce the finalized deploys ahead of the block, so that they stop being
+                // gossiped straight away, then announce the finalized block.
+                let mut effects = Effects::new();
+                let deploy_hashes: Vec<DeployHash> = finalized_block
+                    .deploys_and_transfers_iter()
+                    .map(DeployHash::from)
+                    .collect();
+                if !deploy_hashes.is_empty() {
+                    effects.extend(
+                        self.effect_builder
+                            .announce_deploys_finalized(deploy_hashes)
+                            .ignore(),
+                    );
+                }
+                effects.extend(
+                    self.effect_builder
+                        .announce_finalized_block(finalized_block.clone())
+                        .ignore(),
+                );
                 self.era_supervisor.next_block_height = self
                     .era_supervisor
                     .next_block_height
@@ -1243,6 +1305,17 @@ where
         Effects::new()
     }
 
+    pub(super) fn handle_validator_connectivity(
+        &mut self,
+        peer_id: I,
+        validator: PublicKey,
+        connected: bool,
+    ) -> Effects<Event<I>> {
+        self.era_supervisor
+            .update_validator_peers(peer_id, validator, connected);
+        Effects::new()
+    }
+
     pub(super) fn status(
         &self,
         responder: Responder<Option<(PublicKey, Option<TimeDiff>)>>,
@@ -1256,6 +1329,14 @@ where
         responder.respond(Some((public_key, round_length))).ignore()
     }
 
+    pub(super) fn era_progress(
+        &self,
+        responder: Responder<Option<EraProgress>>,
+    ) -> Effects<Event<I>> {
+        let era_progress = self.era_supervisor.era_progress(Timestamp::now());
+        responder.respond(era_progress).ignore()
+    }
+
     fn disconnect(&self, sender: I) -> Effects<Event<I>> {
         self.effect_builder
             .announce_disconnect_from_peer(sender)
@@ -915,6 +915,13 @@ where
         self.highway.set_paused(paused);
     }
 
+    fn set_validator_connected(&mut self, vid: &C::ValidatorId, connected: bool) {
+        if let Some(vidx) = self.highway.validators().get_index(vid) {
+            self.round_success_meter
+                .set_leader_connected(vidx, connected);
+        }
+    }
+
     fn validators_with_evidence(&self) -> Vec<&C::ValidatorId> {
         self.highway.validators_with_evidence().collect()
     }
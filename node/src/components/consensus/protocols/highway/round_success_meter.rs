@@ -1,11 +1,17 @@
-use std::{cmp::max, collections::VecDeque, mem};
+use std::{
+    cmp::max,
+    collections::{BTreeSet, VecDeque},
+    mem,
+};
 
 use datasize::DataSize;
 use tracing::trace;
 
 use crate::{
     components::consensus::{
-        highway_core::{finality_detector::FinalityDetector, state, State, Weight},
+        highway_core::{
+            finality_detector::FinalityDetector, state, validators::ValidatorIndex, State, Weight,
+        },
         traits::Context,
     },
     types::Timestamp,
@@ -28,6 +34,8 @@ where
     max_round_exp: u8,
     current_round_exp: u8,
     config: Config,
+    // validators that the network reported as disconnected; failed rounds they lead are ignored
+    disconnected_leaders: BTreeSet<ValidatorIndex>,
 }
 
 impl<C: Context> RoundSuccessMeter<C> {
@@ -47,6 +55,7 @@ impl<C: Context> RoundSuccessMeter<C> {
             max_round_exp,
             current_round_exp: round_exp,
             config,
+            disconnected_leaders: BTreeSet::new(),
         }
     }
 
@@ -73,6 +82,24 @@ impl<C: Context> RoundSuccessMeter<C> {
         finality_detector.find_summit(1, proposal_h, state) == 1
     }
 
+    /// Sets whether the validator is currently connected to us.
+    ///
+    /// A round without a successful proposal is not counted as failed if its leader is
+    /// disconnected: Slowing down would not help if the leader is simply unreachable.
+    pub fn set_leader_connected(&mut self, vidx: ValidatorIndex, connected: bool) {
+        if connected {
+            self.disconnected_leaders.remove(&vidx);
+        } else {
+            self.disconnected_leaders.insert(vidx);
+        }
+    }
+
+    /// Returns whether the leader of the round with the given ID is known to be disconnected.
+    fn is_leader_disconnected(&self, state: &State<C>, round_id: Timestamp) -> bool {
+        !self.disconnected_leaders.is_empty()
+            && self.disconnected_leaders.contains(&state.leader(round_id))
+    }
+
     /// Registers a proposal within this round - if it's finalized within the round, the round will
     /// be successful.
     pub fn new_proposal(&mut self, proposal_h: C::Hash, timestamp: Timestamp) {
@@ -103,7 +130,10 @@ impl<C: Context> RoundSuccessMeter<C> {
     /// If the exponent shouldn't grow, and the round ID is divisible by a certain number, a lower
     /// round exponent is returned.
     pub fn calculate_new_exponent(&mut self, state: &State<C>) -> u8 {
-        let now = Timestamp::now();
+        self.calculate_new_exponent_at(state, Timestamp::now())
+    }
+
+    fn calculate_new_exponent_at(&mut self, state: &State<C>, now: Timestamp) -> u8 {
         // if the round hasn't finished, just return whatever we have now
         if state::round_id(now, self.current_round_exp) <= self.current_round_id {
             return self.new_exponent();
@@ -113,6 +143,7 @@ impl<C: Context> RoundSuccessMeter<C> {
         let current_round_index = round_index(self.current_round_id, self.current_round_exp);
         let new_round_index = round_index(now, self.current_round_exp);
 
+        let round_len = state::round_len(self.current_round_exp);
         if mem::take(&mut self.proposals)
             .into_iter()
             .any(|proposal| self.check_proposals_success(state, &proposal))
@@ -120,21 +151,16 @@ impl<C: Context> RoundSuccessMeter<C> {
             trace!("round succeeded");
             self.rounds.push_front(true);
         } else {
-            trace!("round failed");
-            self.rounds.push_front(false);
+            self.push_failed_round(state, self.current_round_id);
         }
 
         // if we're just switching rounds and more than a single round has passed, all the
         // rounds since the last registered round have failed
-        let failed_round_count = new_round_index
-            .saturating_sub(current_round_index)
-            .saturating_sub(1);
-        for _ in 0..failed_round_count {
-            trace!("round failed");
-            self.rounds.push_front(false);
+        for round_index in current_round_index.saturating_add(1)..new_round_index {
+            let round_id = Timestamp::zero() + round_len.saturating_mul(round_index);
+            self.push_failed_round(state, round_id);
         }
 
-        let round_len = state::round_len(self.current_round_exp);
         self.current_round_id = Timestamp::zero() + round_len.saturating_mul(new_round_index);
 
         self.clean_old_rounds();
@@ -157,6 +183,16 @@ impl<C: Context> RoundSuccessMeter<C> {
         new_exp
     }
 
+    /// Records the round with the given ID as failed, unless its leader is disconnected.
+    fn push_failed_round(&mut self, state: &State<C>, round_id: Timestamp) {
+        if self.is_leader_disconnected(state, round_id) {
+            trace!(%round_id, "round failed, but its leader is disconnected");
+        } else {
+            trace!("round failed");
+            self.rounds.push_front(false);
+        }
+    }
+
     /// Returns an instance of `Self` for the new era: resetting the counters where appropriate.
     pub fn next_era(&self, timestamp: Timestamp) -> Self {
         Self {
@@ -167,6 +203,8 @@ impl<C: Context> RoundSuccessMeter<C> {
             max_round_exp: self.max_round_exp,
             current_round_exp: self.current_round_exp,
             config: self.config,
+            // validator indices are specific to each era
+            disconnected_leaders: BTreeSet::new(),
         }
     }
 
@@ -215,9 +253,14 @@ mod tests {
     use crate::{
         components::consensus::{
             cl_context::ClContext,
+            highway_core::{
+                state::{self, tests::TestContext},
+                validators::ValidatorIndex,
+                State, Weight,
+            },
             protocols::highway::round_success_meter::{config, round_index},
         },
-        types::TimeDiff,
+        types::{TimeDiff, Timestamp},
     };
 
     const TEST_ROUND_EXP: u8 = 13;
@@ -321,4 +364,44 @@ mod tests {
         }
         assert_eq!(round_success_meter.new_exponent(), TEST_MIN_ROUND_EXP);
     }
+
+    #[test]
+    fn failed_rounds_of_disconnected_leader_are_ignored() {
+        // With a single validator, it leads every round.
+        let state = State::<TestContext>::new_test(&[Weight(1)], 0);
+        let round_len = state::round_len(TEST_ROUND_EXP);
+        let failed_rounds = (MAX_FAILED_ROUNDS + 2) as u64;
+        let start = Timestamp::zero();
+        let mut round_success_meter: super::RoundSuccessMeter<TestContext> =
+            super::RoundSuccessMeter::new(
+                TEST_ROUND_EXP,
+                TEST_MIN_ROUND_EXP,
+                TEST_MAX_ROUND_EXP,
+                start,
+                Config::default(),
+            );
+        let mut connected_meter = round_success_meter.clone();
+
+        // Without any proposals, all rounds fail and we slow down.
+        let now = start + round_len.saturating_mul(failed_rounds);
+        assert_eq!(
+            connected_meter.calculate_new_exponent_at(&state, now),
+            TEST_ROUND_EXP + 1
+        );
+
+        // If the leader is disconnected, the failures are not held against the round length.
+        round_success_meter.set_leader_connected(ValidatorIndex(0), false);
+        assert_eq!(
+            round_success_meter.calculate_new_exponent_at(&state, now),
+            TEST_ROUND_EXP
+        );
+
+        // Once it reconnects, failed rounds count again.
+        round_success_meter.set_leader_connected(ValidatorIndex(0), true);
+        let now = now + round_len.saturating_mul(failed_rounds);
+        assert_eq!(
+            round_success_meter.calculate_new_exponent_at(&state, now),
+            TEST_ROUND_EXP + 1
+        );
+    }
 }
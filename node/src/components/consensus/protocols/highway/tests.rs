@@ -72,6 +72,7 @@ where
     let chainspec = new_test_chainspec(weights.clone());
     let config = Config {
         secret_key_path: Default::default(),
+        allow_world_readable_secret_key: false,
         highway: HighwayConfig {
             pending_vertex_timeout: "1min".parse().unwrap(),
             standstill_timeout: STANDSTILL_TIMEOUT.parse().unwrap(),
@@ -246,6 +247,52 @@ fn send_a_valid_wire_unit() {
     );
 }
 
+#[test]
+fn disconnection_hint_does_not_affect_finality() {
+    let creator: ValidatorIndex = ValidatorIndex(0);
+    let validators = vec![(ALICE_PUBLIC_KEY.clone(), 100)];
+    let state: State<ClContext> = new_test_state(validators.iter().map(|(_pk, w)| *w), 0);
+    let panorama: Panorama<ClContext> = Panorama::from(vec![N]);
+    let seq_number = panorama.next_seq_num(&state, creator);
+    let now = Timestamp::zero();
+    let wunit: WireUnit<ClContext> = WireUnit {
+        panorama,
+        creator,
+        instance_id: ClContext::hash(INSTANCE_ID_DATA),
+        value: Some(Arc::new(BlockPayload::new(vec![], vec![], vec![], false))),
+        seq_number,
+        timestamp: now,
+        round_exp: 14,
+        endorsed: BTreeSet::new(),
+    };
+    let alice_keypair: Keypair = Keypair::from(Arc::clone(&*ALICE_SECRET_KEY));
+    let highway_message: HighwayMessage<ClContext> = HighwayMessage::NewVertex(Vertex::Unit(
+        SignedWireUnit::new(wunit.into_hashed(), &alice_keypair),
+    ));
+    let msg = bincode::serialize(&highway_message).unwrap();
+
+    // Returns the blocks finalized after handling the unit, with Alice connected or not.
+    let finalized_blocks = |alice_connected: bool| {
+        let mut highway_protocol = new_test_highway_protocol(validators.clone(), vec![]);
+        highway_protocol.set_validator_connected(&*ALICE_PUBLIC_KEY, alice_connected);
+        let mut finalized = Vec::new();
+        let mut outcomes = highway_protocol.handle_message(NodeId(123), msg.clone(), now);
+        while let Some(outcome) = outcomes.pop() {
+            match outcome {
+                ProtocolOutcome::FinalizedBlock(finalized_block) => finalized.push(finalized_block),
+                ProtocolOutcome::CreatedGossipMessage(_) => (),
+                ProtocolOutcome::QueueAction(ACTION_ID_VERTEX) => {
+                    outcomes.extend(highway_protocol.handle_action(ACTION_ID_VERTEX, now))
+                }
+                outcome => panic!("Unexpected outcome: {:?}", outcome),
+            }
+        }
+        finalized
+    };
+
+    assert_eq!(finalized_blocks(true), finalized_blocks(false));
+}
+
 #[test]
 fn detect_doppelganger() {
     let creator: ValidatorIndex = ALICE;
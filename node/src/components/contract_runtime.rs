@@ -1,46 +1,54 @@
 //! Contract Runtime component.
 mod config;
 mod operations;
+mod parallel_execution;
 mod types;
 
 use std::{
     collections::{BTreeMap, HashMap, VecDeque},
     fmt::{self, Debug, Formatter},
+    mem,
     sync::Arc,
     time::Instant,
 };
 
 pub use config::Config;
+use operations::BlockExecutionContext;
+use parallel_execution::ParallelExecutor;
 use smallvec::SmallVec;
 
 pub use types::{EraValidatorsRequest, ValidatorWeightsByEraIdRequest};
 
 use datasize::DataSize;
 use derive_more::From;
-use lmdb::DatabaseFlags;
-use prometheus::{self, Histogram, HistogramOpts, IntGauge, Registry};
+use lmdb::{Database, DatabaseFlags};
+use prometheus::{self, Gauge, Histogram, HistogramOpts, IntCounter, IntGauge, Registry};
 use thiserror::Error;
 use tracing::{debug, error, trace};
 
 use casper_execution_engine::{
     core::engine_state::{
-        self, genesis::GenesisResult, step::EvictItem, DeployItem, EngineConfig, EngineState,
-        ExecuteRequest, GetEraValidatorsError, GetEraValidatorsRequest, RewardItem, SlashItem,
-        StepRequest, StepResult,
+        self, genesis::GenesisResult, step::EvictItem, EngineConfig, EngineState,
+        GetEraValidatorsError, GetEraValidatorsRequest, RewardItem, SlashItem, StepRequest,
+        StepResult,
+    },
+    shared::{
+        newtypes::{Blake2bHash, CorrelationId},
+        stored_value::StoredValue,
     },
-    shared::newtypes::{Blake2bHash, CorrelationId},
     storage::{
         error::lmdb::Error as StorageLmdbError, global_state::lmdb::LmdbGlobalState,
-        protocol_data_store::lmdb::LmdbProtocolDataStore,
-        transaction_source::lmdb::LmdbEnvironment, trie_store::lmdb::LmdbTrieStore,
+        protocol_data::ProtocolData, protocol_data_store::lmdb::LmdbProtocolDataStore,
+        store::Store, transaction_source::lmdb::LmdbEnvironment, trie::Trie,
+        trie_store::lmdb::LmdbTrieStore,
     },
 };
 use casper_types::{
-    system::auction::ValidatorWeights, ExecutionResult, ProtocolVersion, PublicKey, U512,
+    system::auction::ValidatorWeights, ExecutionResult, Key, ProtocolVersion, PublicKey, U512,
 };
 
 use crate::{
-    components::Component,
+    components::{storage::LmdbStatsCollector, Component},
     crypto::hash::Digest,
     effect::{
         announcements::ContractRuntimeAnnouncement,
@@ -140,6 +148,9 @@ pub struct ContractRuntime {
     initial_state: InitialState,
     engine_state: Arc<EngineState<LmdbGlobalState>>,
     metrics: Arc<ContractRuntimeMetrics>,
+    /// Executor of blocks' deploys in parallel, if enabled in the config.
+    #[data_size(skip)]
+    parallel_executor: Option<Arc<ParallelExecutor>>,
 
     protocol_version: ProtocolVersion,
 
@@ -152,6 +163,15 @@ pub struct ContractRuntime {
 
     /// Finalized blocks waiting for their pre-state hash to start executing.
     exec_queue: HashMap<BlockHeight, (FinalizedBlock, VecDeque<Deploy>)>,
+
+    /// The global state's LMDB environment, and its databases along with their names.
+    #[data_size(skip)]
+    lmdb_environment: Arc<LmdbEnvironment>,
+    #[data_size(skip)]
+    lmdb_databases: [(&'static str, Database); 2],
+    /// Collector of the global state environment's statistics.
+    #[data_size(skip)]
+    lmdb_stats: LmdbStatsCollector,
 }
 
 impl Debug for ContractRuntime {
@@ -169,6 +189,8 @@ pub struct ContractRuntimeMetrics {
     run_query: Histogram,
     commit_step: Histogram,
     get_balance: Histogram,
+    get_balances: Histogram,
+    get_all_balances: Histogram,
     get_validator_weights: Histogram,
     get_era_validators: Histogram,
     get_era_validator_weights_by_era_id: Histogram,
@@ -178,6 +200,12 @@ pub struct ContractRuntimeMetrics {
     read_trie: Histogram,
     /// The current chain height.
     pub chain_height: IntGauge,
+    /// The number of deploys executed optimistically in parallel.
+    parallel_deploys: IntCounter,
+    /// The number of deploys re-executed after conflicting with an earlier deploy in their block.
+    parallel_conflicts: IntCounter,
+    /// The estimated speedup of the last block executed in parallel over sequential execution.
+    parallel_speedup: Gauge,
 }
 
 /// Value of upper bound of histogram.
@@ -204,6 +232,10 @@ const COMMIT_UPGRADE_NAME: &str = "contract_runtime_commit_upgrade";
 const COMMIT_UPGRADE_HELP: &str = "tracking run of engine_state.commit_upgrade in seconds";
 const GET_BALANCE_NAME: &str = "contract_runtime_get_balance";
 const GET_BALANCE_HELP: &str = "tracking run of engine_state.get_balance in seconds.";
+const GET_BALANCES_NAME: &str = "contract_runtime_get_balances";
+const GET_BALANCES_HELP: &str = "tracking run of engine_state.get_purse_balances in seconds.";
+const GET_ALL_BALANCES_NAME: &str = "contract_runtime_get_all_balances";
+const GET_ALL_BALANCES_HELP: &str = "tracking run of engine_state.get_all_balances in seconds.";
 const GET_VALIDATOR_WEIGHTS_NAME: &str = "contract_runtime_get_validator_weights";
 const GET_VALIDATOR_WEIGHTS_HELP: &str =
     "tracking run of engine_state.get_validator_weights in seconds.";
@@ -221,6 +253,14 @@ const PUT_TRIE_NAME: &str = "contract_runtime_put_trie";
 const PUT_TRIE_HELP: &str = "tracking run of engine_state.put_trie in seconds.";
 const MISSING_TRIE_KEYS_NAME: &str = "contract_runtime_missing_trie_keys";
 const MISSING_TRIE_KEYS_HELP: &str = "tracking run of engine_state.missing_trie_keys in seconds.";
+const PARALLEL_DEPLOYS_NAME: &str = "contract_runtime_parallel_deploys";
+const PARALLEL_DEPLOYS_HELP: &str = "number of deploys executed optimistically in parallel.";
+const PARALLEL_CONFLICTS_NAME: &str = "contract_runtime_parallel_conflicts";
+const PARALLEL_CONFLICTS_HELP: &str =
+    "number of deploys re-executed after conflicting with an earlier deploy in their block.";
+const PARALLEL_SPEEDUP_NAME: &str = "contract_runtime_parallel_speedup";
+const PARALLEL_SPEEDUP_HELP: &str =
+    "estimated speedup of the last block executed in parallel over sequential execution.";
 
 /// Create prometheus Histogram and register.
 fn register_histogram_metric(
@@ -244,8 +284,17 @@ impl ContractRuntimeMetrics {
     fn new(registry: &Registry) -> Result<Self, prometheus::Error> {
         let chain_height = IntGauge::new("chain_height", "current chain height")?;
         registry.register(Box::new(chain_height.clone()))?;
+        let parallel_deploys = IntCounter::new(PARALLEL_DEPLOYS_NAME, PARALLEL_DEPLOYS_HELP)?;
+        registry.register(Box::new(parallel_deploys.clone()))?;
+        let parallel_conflicts = IntCounter::new(PARALLEL_CONFLICTS_NAME, PARALLEL_CONFLICTS_HELP)?;
+        registry.register(Box::new(parallel_conflicts.clone()))?;
+        let parallel_speedup = Gauge::new(PARALLEL_SPEEDUP_NAME, PARALLEL_SPEEDUP_HELP)?;
+        registry.register(Box::new(parallel_speedup.clone()))?;
         Ok(ContractRuntimeMetrics {
             chain_height,
+            parallel_deploys,
+            parallel_conflicts,
+            parallel_speedup,
             run_execute: register_histogram_metric(registry, RUN_EXECUTE_NAME, RUN_EXECUTE_HELP)?,
             apply_effect: register_histogram_metric(
                 registry,
@@ -260,6 +309,16 @@ impl ContractRuntimeMetrics {
                 COMMIT_UPGRADE_HELP,
             )?,
             get_balance: register_histogram_metric(registry, GET_BALANCE_NAME, GET_BALANCE_HELP)?,
+            get_balances: register_histogram_metric(
+                registry,
+                GET_BALANCES_NAME,
+                GET_BALANCES_HELP,
+            )?,
+            get_all_balances: register_histogram_metric(
+                registry,
+                GET_ALL_BALANCES_NAME,
+                GET_ALL_BALANCES_HELP,
+            )?,
             get_validator_weights: register_histogram_metric(
                 registry,
                 GET_VALIDATOR_WEIGHTS_NAME,
@@ -300,9 +359,17 @@ where
         _rng: &mut NodeRng,
         event: Self::Event,
     ) -> Effects<Self::Event> {
-        match event {
+        let effects = match event {
             Event::Request(request) => {
                 match *request {
+                    ContractRuntimeRequest::GetLmdbStats { responder } => {
+                        let maybe_stats = self
+                            .lmdb_stats
+                            .collect(self.lmdb_environment.env(), &self.lmdb_databases)
+                            .map_err(|error| error!(%error, "failed to collect LMDB statistics"))
+                            .ok();
+                        responder.respond(maybe_stats).ignore()
+                    }
                     ContractRuntimeRequest::GetProtocolData {
                         protocol_version,
                         responder,
@@ -379,6 +446,44 @@ where
                         }
                         .ignore()
                     }
+                    ContractRuntimeRequest::GetBalances {
+                        balances_request,
+                        responder,
+                    } => {
+                        trace!(?balances_request, "balances");
+                        let engine_state = Arc::clone(&self.engine_state);
+                        let metrics = Arc::clone(&self.metrics);
+                        async move {
+                            let correlation_id = CorrelationId::new();
+                            let start = Instant::now();
+                            let result =
+                                engine_state.get_purse_balances(correlation_id, balances_request);
+                            metrics.get_balances.observe(start.elapsed().as_secs_f64());
+                            trace!(?result, "balances result");
+                            responder.respond(result).await
+                        }
+                        .ignore()
+                    }
+                    ContractRuntimeRequest::GetAllBalances {
+                        get_all_balances_request,
+                        responder,
+                    } => {
+                        trace!(?get_all_balances_request, "all balances");
+                        let engine_state = Arc::clone(&self.engine_state);
+                        let metrics = Arc::clone(&self.metrics);
+                        async move {
+                            let correlation_id = CorrelationId::new();
+                            let start = Instant::now();
+                            let result = engine_state
+                                .get_all_balances(correlation_id, get_all_balances_request);
+                            metrics
+                                .get_all_balances
+                                .observe(start.elapsed().as_secs_f64());
+                            trace!(?result, "all balances result");
+                            responder.respond(result).await
+                        }
+                        .ignore()
+                    }
                     ContractRuntimeRequest::IsBonded {
                         state_root_hash,
                         era_id,
@@ -636,7 +741,10 @@ where
                     self.execute_all_deploys_or_finalize_block_or_step(effect_builder, state)
                 }
             },
-        }
+        };
+        self.lmdb_stats
+            .collect_if_due(self.lmdb_environment.env(), &self.lmdb_databases);
+        effects
     }
 }
 
@@ -680,9 +788,37 @@ impl ContractRuntime {
             DatabaseFlags::empty(),
         )?);
 
-        let global_state = LmdbGlobalState::empty(environment, trie_store, protocol_data_store)?;
+        let lmdb_databases = [
+            (
+                "trie_store",
+                <LmdbTrieStore as Store<Blake2bHash, Trie<Key, StoredValue>>>::handle(&trie_store),
+            ),
+            (
+                "protocol_data_store",
+                <LmdbProtocolDataStore as Store<ProtocolVersion, ProtocolData>>::handle(
+                    &protocol_data_store,
+                ),
+            ),
+        ];
+        let lmdb_stats = LmdbStatsCollector::new(
+            "global_state",
+            storage_config.value().lmdb_utilization_warning_percent(),
+            registry,
+        )?;
+
+        let global_state =
+            LmdbGlobalState::empty(Arc::clone(&environment), trie_store, protocol_data_store)?;
         let engine_config = EngineConfig::new(contract_runtime_config.max_query_depth());
 
+        let parallel_executor = if contract_runtime_config.parallel_execution() {
+            Some(Arc::new(ParallelExecutor::new(
+                &global_state,
+                contract_runtime_config.verify_parallel_execution(),
+            )))
+        } else {
+            None
+        };
+
         let engine_state = Arc::new(EngineState::new(global_state, engine_config));
 
         let metrics = Arc::new(ContractRuntimeMetrics::new(registry)?);
@@ -693,6 +829,10 @@ impl ContractRuntime {
             exec_queue: HashMap::new(),
             engine_state,
             metrics,
+            parallel_executor,
+            lmdb_environment: environment,
+            lmdb_databases,
+            lmdb_stats,
         })
     }
 
@@ -726,6 +866,23 @@ impl ContractRuntime {
         }
     }
 
+    /// Reads a trie from global state.
+    pub(crate) fn read_trie(
+        &self,
+        trie_key: Blake2bHash,
+    ) -> Result<Option<Trie<Key, StoredValue>>, engine_state::Error> {
+        self.engine_state.read_trie(CorrelationId::new(), trie_key)
+    }
+
+    /// Writes a trie to global state, returning the keys of its descendants which are missing.
+    pub(crate) fn put_trie(
+        &self,
+        trie: &Trie<Key, StoredValue>,
+    ) -> Result<Vec<Blake2bHash>, engine_state::Error> {
+        self.engine_state
+            .put_trie_and_find_missing_descendant_trie_keys(CorrelationId::new(), trie)
+    }
+
     pub(crate) fn set_initial_state(
         &mut self,
         initial_state_root_hash: Digest,
@@ -899,55 +1056,35 @@ impl ContractRuntime {
     fn execute_all_deploys_in_block(&mut self, mut state: Box<RequestState>) -> Effects<Event> {
         let engine_state = Arc::clone(&self.engine_state);
         let metrics = Arc::clone(&self.metrics);
-        let protocol_version = self.protocol_version;
-        let block_time = state.finalized_block.timestamp().millis();
-        let proposer = state.finalized_block.proposer();
+        let parallel_executor = self.parallel_executor.clone();
+        let context = BlockExecutionContext::new(&state.finalized_block, self.protocol_version);
         async move {
-            for deploy in state.remaining_deploys.drain(..) {
-                let deploy_hash = *deploy.id();
-                let deploy_header = deploy.header().clone();
-                let deploy_item = DeployItem::from(deploy);
-
-                let execute_request = ExecuteRequest::new(
-                    state.state_root_hash.into(),
-                    block_time,
-                    vec![deploy_item],
-                    protocol_version,
-                    proposer.clone(),
-                );
-
-                // TODO: this is currently working coincidentally because we are passing only one
-                // deploy_item per exec. The execution results coming back from the ee lacks the
-                // mapping between deploy_hash and execution result, and this outer logic is
-                // enriching it with the deploy hash. If we were passing multiple deploys per exec
-                // the relation between the deploy and the execution results would be lost.
-                let result =
-                    operations::execute(engine_state.clone(), metrics.clone(), execute_request)
-                        .await;
-
-                trace!(%deploy_hash, ?result, "deploy execution result");
-                // As for now a given state is expected to exist.
-                let execution_results = result.unwrap();
-                match operations::commit_execution_effects(
-                    engine_state.clone(),
-                    metrics.clone(),
-                    state.state_root_hash,
-                    deploy_hash,
-                    execution_results,
-                )
-                .await
-                {
-                    Ok((state_hash, execution_result)) => {
-                        state
-                            .execution_results
-                            .insert(deploy_hash, (deploy_header, execution_result));
-                        state.state_root_hash = state_hash;
-                    }
-                    // When commit fails we panic as we'll not be able to execute the next
-                    // block.
-                    Err(_err) => panic!("unable to commit"),
+            let deploys = mem::take(&mut state.remaining_deploys);
+            let (state_root_hash, execution_results) = match parallel_executor {
+                Some(parallel_executor) => {
+                    parallel_executor
+                        .execute_and_commit(
+                            engine_state,
+                            metrics,
+                            &context,
+                            state.state_root_hash,
+                            deploys,
+                        )
+                        .await
                 }
-            }
+                None => {
+                    operations::execute_and_commit_sequentially(
+                        engine_state,
+                        metrics,
+                        &context,
+                        state.state_root_hash,
+                        deploys,
+                    )
+                    .await
+                }
+            };
+            state.execution_results.extend(execution_results);
+            state.state_root_hash = state_root_hash;
             state
         }
         .event(|state| Event::Result(Box::new(ContractRuntimeResult::ExecutedAndCommitted(state))))
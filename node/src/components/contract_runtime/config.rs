@@ -6,6 +6,8 @@ use casper_execution_engine::shared::utils;
 const DEFAULT_MAX_GLOBAL_STATE_SIZE: usize = 805_306_368_000; // 750 GiB
 const DEFAULT_MAX_READERS: u32 = 512;
 const DEFAULT_MAX_QUERY_DEPTH: u64 = 5;
+const DEFAULT_PARALLEL_EXECUTION: bool = false;
+const DEFAULT_VERIFY_PARALLEL_EXECUTION: bool = false;
 
 /// Contract runtime configuration.
 #[derive(Clone, Copy, DataSize, Debug, Deserialize, Serialize)]
@@ -26,6 +28,16 @@ pub struct Config {
     ///
     /// Defaults to 5.
     max_query_depth: Option<u64>,
+    /// Whether to execute the deploys of a block optimistically in parallel, re-executing any
+    /// which conflict with an earlier deploy in the block.
+    ///
+    /// Defaults to false.
+    parallel_execution: Option<bool>,
+    /// Whether to also execute each block sequentially when parallel execution is enabled, and
+    /// panic if the results of the two differ.
+    ///
+    /// Defaults to false.
+    verify_parallel_execution: Option<bool>,
 }
 
 impl Config {
@@ -44,6 +56,16 @@ impl Config {
     pub(crate) fn max_query_depth(&self) -> u64 {
         self.max_query_depth.unwrap_or(DEFAULT_MAX_QUERY_DEPTH)
     }
+
+    pub(crate) fn parallel_execution(&self) -> bool {
+        self.parallel_execution
+            .unwrap_or(DEFAULT_PARALLEL_EXECUTION)
+    }
+
+    pub(crate) fn verify_parallel_execution(&self) -> bool {
+        self.verify_parallel_execution
+            .unwrap_or(DEFAULT_VERIFY_PARALLEL_EXECUTION)
+    }
 }
 
 impl Default for Config {
@@ -52,6 +74,8 @@ impl Default for Config {
             max_global_state_size: Some(DEFAULT_MAX_GLOBAL_STATE_SIZE),
             max_readers: Some(DEFAULT_MAX_READERS),
             max_query_depth: Some(DEFAULT_MAX_QUERY_DEPTH),
+            parallel_execution: Some(DEFAULT_PARALLEL_EXECUTION),
+            verify_parallel_execution: Some(DEFAULT_VERIFY_PARALLEL_EXECUTION),
         }
     }
 }
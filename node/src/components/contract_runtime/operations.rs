@@ -1,18 +1,111 @@
 use std::{collections::VecDeque, sync::Arc, time::Instant};
 
 use super::ContractRuntimeMetrics;
-use crate::{crypto::hash::Digest, types::DeployHash};
+use crate::{
+    crypto::hash::Digest,
+    types::{deploy_span, Deploy, DeployHash, DeployHeader, FinalizedBlock},
+};
 use casper_execution_engine::{
     core::engine_state::{
-        self, EngineState, ExecutionResult as EngineExecutionResult, ExecutionResults,
+        self, DeployItem, EngineState, ExecutionResult as EngineExecutionResult, ExecutionResults,
     },
     shared::{additive_map::AdditiveMap, newtypes::CorrelationId, transform::Transform},
     storage::global_state::{lmdb::LmdbGlobalState, CommitResult},
 };
-use casper_types::{ExecutionResult, Key};
+use casper_types::{ExecutionResult, Key, ProtocolVersion, PublicKey};
 use engine_state::ExecuteRequest;
 use itertools::Itertools;
 use tracing::{debug, error, trace};
+use tracing_futures::Instrument;
+
+/// The execution results of a block's deploys, in the order the deploys appear in the block.
+pub(super) type DeployExecutionResults = Vec<(DeployHash, (DeployHeader, ExecutionResult))>;
+
+/// The parts of a finalized block which are common to the execution of each of its deploys.
+#[derive(Clone, Debug)]
+pub(super) struct BlockExecutionContext {
+    block_time: u64,
+    proposer: PublicKey,
+    protocol_version: ProtocolVersion,
+}
+
+impl BlockExecutionContext {
+    pub(super) fn new(finalized_block: &FinalizedBlock, protocol_version: ProtocolVersion) -> Self {
+        BlockExecutionContext {
+            block_time: finalized_block.timestamp().millis(),
+            proposer: finalized_block.proposer(),
+            protocol_version,
+        }
+    }
+
+    /// Returns a request to execute the given deploy on top of `state_root_hash`.
+    pub(super) fn execute_request(
+        &self,
+        state_root_hash: Digest,
+        deploy_item: DeployItem,
+    ) -> ExecuteRequest {
+        ExecuteRequest::new(
+            state_root_hash.into(),
+            self.block_time,
+            vec![deploy_item],
+            self.protocol_version,
+            self.proposer.clone(),
+        )
+    }
+}
+
+/// Executes and commits the deploys one at a time in the given order, each on top of the state
+/// left by the previous one.
+///
+/// Returns the post-state hash of the last commit along with the execution results.
+pub(super) async fn execute_and_commit_sequentially(
+    engine_state: Arc<EngineState<LmdbGlobalState>>,
+    metrics: Arc<ContractRuntimeMetrics>,
+    context: &BlockExecutionContext,
+    mut state_root_hash: Digest,
+    deploys: VecDeque<Deploy>,
+) -> (Digest, DeployExecutionResults) {
+    let mut deploy_execution_results = Vec::with_capacity(deploys.len());
+    for deploy in deploys {
+        let deploy_hash = *deploy.id();
+        let deploy_header = deploy.header().clone();
+        let deploy_item = DeployItem::from(deploy);
+
+        async {
+            let execute_request = context.execute_request(state_root_hash, deploy_item);
+
+            // TODO: this is currently working coincidentally because we are passing only one
+            // deploy_item per exec. The execution results coming back from the ee lacks the
+            // mapping between deploy_hash and execution result, and this outer logic is enriching
+            // it with the deploy hash. If we were passing multiple deploys per exec the relation
+            // between the deploy and the execution results would be lost.
+            let result = execute(engine_state.clone(), metrics.clone(), execute_request).await;
+
+            trace!(%deploy_hash, ?result, "deploy execution result");
+            // As for now a given state is expected to exist.
+            let execution_results = result.unwrap();
+            match commit_execution_effects(
+                engine_state.clone(),
+                metrics.clone(),
+                state_root_hash,
+                deploy_hash,
+                execution_results,
+            )
+            .await
+            {
+                Ok((state_hash, execution_result)) => {
+                    deploy_execution_results.push((deploy_hash, (deploy_header, execution_result)));
+                    state_root_hash = state_hash;
+                }
+                // When commit fails we panic as we'll not be able to execute the next block.
+                Err(_err) => panic!("unable to commit"),
+            }
+        }
+        .instrument(deploy_span(&deploy_hash))
+        .await;
+    }
+    (state_root_hash, deploy_execution_results)
+}
 
 /// Commits the execution effects.
 pub(super) async fn commit_execution_effects(
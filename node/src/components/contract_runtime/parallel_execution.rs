@@ -0,0 +1,783 @@
+//! Optimistic parallel execution of the deploys in a block.
+//!
+//! All deploys of a block are first executed concurrently against the block's pre-state, with
+//! every read each makes from global state recorded along with its result.  The deploys are then
+//! committed one at a time in block order.  If all of a deploy's recorded reads return the same
+//! results on top of the state left by its predecessors, executing it there would have produced
+//! exactly the same results, so its optimistic results are committed as they are.  Otherwise the
+//! deploy conflicts with an earlier one and is re-executed on top of the current state before being
+//! committed.  Either way, the final state is identical to that of sequential execution.
+
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet, VecDeque},
+    rc::Rc,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use futures::{stream, StreamExt};
+use tokio::task;
+use tracing::{debug, error, trace};
+use tracing_futures::Instrument;
+
+use casper_execution_engine::{
+    core::engine_state::{
+        self, execution_effect::ExecutionEffect, DeployItem, EngineConfig, EngineState,
+        ExecuteRequest, ExecutionResults,
+    },
+    shared::{
+        additive_map::AdditiveMap,
+        newtypes::{Blake2bHash, CorrelationId},
+        stored_value::StoredValue,
+        transform::Transform,
+    },
+    storage::{
+        global_state::{
+            lmdb::{LmdbGlobalState, LmdbGlobalStateView},
+            CommitResult, StateProvider, StateReader,
+        },
+        protocol_data::ProtocolData,
+        trie::{merkle_proof::TrieMerkleProof, Trie},
+    },
+};
+use casper_types::{bytesrepr::ToBytes, Key, ProtocolVersion};
+
+use super::{
+    operations::{self, BlockExecutionContext, DeployExecutionResults},
+    ContractRuntimeMetrics,
+};
+use crate::{
+    crypto::hash::Digest,
+    types::{deploy_span, Deploy},
+};
+
+type GlobalStateError = <LmdbGlobalState as StateProvider>::Error;
+
+/// Executes the deploys of a block optimistically in parallel.
+pub(super) struct ParallelExecutor {
+    /// The global state, sharing its underlying stores with the contract runtime's engine state.
+    global_state: Arc<LmdbGlobalState>,
+    /// The maximum number of deploys to execute concurrently.
+    max_concurrency: usize,
+    /// Whether to also execute each block sequentially and panic if the results differ.
+    verify: bool,
+}
+
+impl ParallelExecutor {
+    pub(super) fn new(global_state: &LmdbGlobalState, verify: bool) -> Self {
+        let global_state = Arc::new(LmdbGlobalState {
+            environment: Arc::clone(&global_state.environment),
+            trie_store: Arc::clone(&global_state.trie_store),
+            protocol_data_store: Arc::clone(&global_state.protocol_data_store),
+            empty_root_hash: global_state.empty_root_hash,
+        });
+        ParallelExecutor {
+            global_state,
+            max_concurrency: num_cpus::get(),
+            verify,
+        }
+    }
+
+    /// Executes and commits the deploys on top of `pre_state_root_hash`, producing the same
+    /// results as `operations::execute_and_commit_sequentially`.
+    ///
+    /// Returns the post-state hash of the last commit along with the execution results.
+    pub(super) async fn execute_and_commit(
+        &self,
+        engine_state: Arc<EngineState<LmdbGlobalState>>,
+        metrics: Arc<ContractRuntimeMetrics>,
+        context: &BlockExecutionContext,
+        pre_state_root_hash: Digest,
+        deploys: VecDeque<Deploy>,
+    ) -> (Digest, DeployExecutionResults) {
+        let maybe_deploys_to_verify = if self.verify {
+            Some(deploys.clone())
+        } else {
+            None
+        };
+
+        let start = Instant::now();
+        let optimistic_results = self
+            .execute_optimistically(
+                *engine_state.config(),
+                Arc::clone(&metrics),
+                context,
+                pre_state_root_hash,
+                &deploys,
+            )
+            .await;
+        metrics.parallel_deploys.inc_by(deploys.len() as u64);
+        let mut execution_time: Duration = optimistic_results
+            .iter()
+            .map(|optimistic_result| optimistic_result.execution_time)
+            .sum();
+
+        let commit_start = Instant::now();
+        let mut state_root_hash = pre_state_root_hash;
+        let mut written_keys = HashSet::new();
+        let mut deploy_execution_results = Vec::with_capacity(deploys.len());
+        for (deploy, optimistic_result) in deploys.into_iter().zip(optimistic_results) {
+            let deploy_hash = *deploy.id();
+            let deploy_header = deploy.header().clone();
+
+            async {
+                let execution_results = match optimistic_result.execution_results {
+                    Ok(execution_results)
+                        if self.reads_are_unchanged(
+                            &optimistic_result.reads,
+                            &written_keys,
+                            state_root_hash,
+                        ) =>
+                    {
+                        execution_results
+                    }
+                    _ => {
+                        debug!("deploy conflicts with an earlier deploy in the block");
+                        metrics.parallel_conflicts.inc();
+                        // Only this re-execution would have happened under sequential execution.
+                        execution_time -= optimistic_result.execution_time;
+                        let execute_request =
+                            context.execute_request(state_root_hash, DeployItem::from(deploy));
+                        let result = operations::execute(
+                            engine_state.clone(),
+                            metrics.clone(),
+                            execute_request,
+                        )
+                        .await;
+                        trace!(%deploy_hash, ?result, "deploy re-execution result");
+                        // As for now a given state is expected to exist.
+                        result.unwrap()
+                    }
+                };
+                written_keys.extend(
+                    execution_results
+                        .iter()
+                        .flat_map(|execution_result| written_keys_of(execution_result.effect())),
+                );
+                match operations::commit_execution_effects(
+                    engine_state.clone(),
+                    metrics.clone(),
+                    state_root_hash,
+                    deploy_hash,
+                    execution_results,
+                )
+                .await
+                {
+                    Ok((state_hash, execution_result)) => {
+                        deploy_execution_results
+                            .push((deploy_hash, (deploy_header, execution_result)));
+                        state_root_hash = state_hash;
+                    }
+                    // When commit fails we panic as we'll not be able to execute the next block.
+                    Err(_err) => panic!("unable to commit"),
+                }
+            }
+            .instrument(deploy_span(&deploy_hash))
+            .await;
+        }
+
+        // Sequential execution would have spent the same time committing and re-executing, but
+        // would have executed the remaining deploys one at a time.
+        let elapsed = start.elapsed();
+        let sequential_estimate = commit_start.elapsed() + execution_time;
+        if elapsed > Duration::default() {
+            metrics
+                .parallel_speedup
+                .set(sequential_estimate.as_secs_f64() / elapsed.as_secs_f64());
+        }
+
+        let result = (state_root_hash, deploy_execution_results);
+        if let Some(deploys) = maybe_deploys_to_verify {
+            let expected = operations::execute_and_commit_sequentially(
+                engine_state,
+                metrics,
+                context,
+                pre_state_root_hash,
+                deploys,
+            )
+            .await;
+            if result != expected {
+                error!(
+                    parallel_state_root_hash = %result.0,
+                    sequential_state_root_hash = %expected.0,
+                    "parallel execution diverged from sequential execution"
+                );
+                panic!("parallel execution diverged from sequential execution");
+            }
+        }
+        result
+    }
+
+    /// Executes each of the deploys against `pre_state_root_hash`, up to `max_concurrency` at a
+    /// time, recording their reads from global state.
+    async fn execute_optimistically(
+        &self,
+        engine_config: EngineConfig,
+        metrics: Arc<ContractRuntimeMetrics>,
+        context: &BlockExecutionContext,
+        pre_state_root_hash: Digest,
+        deploys: &VecDeque<Deploy>,
+    ) -> Vec<OptimisticResult> {
+        // The requests are created up front so that the stream below only holds owned values, as
+        // a closure borrowing the deploys would stop the returned future from being `Send`.  The
+        // tasks are still only spawned as the stream is polled, limiting their concurrency.
+        #[allow(clippy::needless_collect)]
+        let execute_requests: Vec<_> = deploys
+            .iter()
+            .map(|deploy| {
+                context.execute_request(pre_state_root_hash, DeployItem::from(deploy.clone()))
+            })
+            .collect();
+        let global_state = Arc::clone(&self.global_state);
+        let executions = execute_requests.into_iter().map(move |execute_request| {
+            let global_state = Arc::clone(&global_state);
+            let metrics = Arc::clone(&metrics);
+            task::spawn_blocking(move || {
+                execute_recording_reads(global_state, engine_config, metrics, execute_request)
+            })
+        });
+        stream::iter(executions)
+            .buffered(self.max_concurrency)
+            .map(|join_result| join_result.expect("optimistic execution should not panic"))
+            .collect()
+            .await
+    }
+
+    /// Returns whether every read recorded during a deploy's optimistic execution returns the same
+    /// result on top of `state_root_hash` as it did on top of the pre-state.
+    ///
+    /// Only keys written by earlier deploys in the block can have changed since the pre-state, so
+    /// only reads involving those are repeated.
+    fn reads_are_unchanged(
+        &self,
+        reads: &RecordedReads,
+        written_keys: &HashSet<Key>,
+        state_root_hash: Digest,
+    ) -> bool {
+        let correlation_id = CorrelationId::new();
+        let view = match self.global_state.checkout(state_root_hash.into()) {
+            Ok(Some(view)) => view,
+            _ => return false,
+        };
+
+        let values_unchanged = reads
+            .values
+            .iter()
+            .filter(|(key, _)| written_keys.contains(&key.normalize()))
+            .all(|(key, value)| {
+                view.read(correlation_id, key)
+                    .map_or(false, |current_value| current_value == *value)
+            });
+        let prefixes_unchanged = reads
+            .prefixes
+            .iter()
+            .filter(|(prefix, _)| {
+                written_keys.iter().any(|key| {
+                    key.to_bytes()
+                        .map_or(true, |key_bytes| key_bytes.starts_with(prefix))
+                })
+            })
+            .all(|(prefix, keys)| {
+                view.keys_with_prefix(correlation_id, prefix)
+                    .map_or(false, |current_keys| current_keys == *keys)
+            });
+        values_unchanged && prefixes_unchanged
+    }
+}
+
+/// The outcome of executing a single deploy against the pre-state of its block.
+struct OptimisticResult {
+    execution_results: Result<ExecutionResults, engine_state::Error>,
+    reads: RecordedReads,
+    execution_time: Duration,
+}
+
+/// Executes the deploy in `execute_request`, recording every read made from global state.
+fn execute_recording_reads(
+    global_state: Arc<LmdbGlobalState>,
+    engine_config: EngineConfig,
+    metrics: Arc<ContractRuntimeMetrics>,
+    execute_request: ExecuteRequest,
+) -> OptimisticResult {
+    let reads = Rc::new(RefCell::new(RecordedReads::default()));
+    let state = ReadRecordingState {
+        inner: global_state,
+        reads: Rc::clone(&reads),
+    };
+    let engine_state = EngineState::new(state, engine_config);
+    let start = Instant::now();
+    let execution_results = engine_state.run_execute(CorrelationId::new(), execute_request);
+    let execution_time = start.elapsed();
+    metrics.run_execute.observe(execution_time.as_secs_f64());
+    OptimisticResult {
+        execution_results,
+        reads: reads.take(),
+        execution_time,
+    }
+}
+
+/// Returns the keys whose values are changed by the given effect.
+fn written_keys_of(effect: &ExecutionEffect) -> impl Iterator<Item = Key> + '_ {
+    effect
+        .transforms
+        .iter()
+        .filter(|(_, transform)| !matches!(transform, Transform::Identity))
+        .map(|(key, _)| *key)
+}
+
+/// The reads made from global state while executing a deploy, along with their results.
+#[derive(Debug, Default)]
+struct RecordedReads {
+    /// The values read, keyed by the key they were read from.
+    values: HashMap<Key, Option<StoredValue>>,
+    /// The keys found under each prefix read.
+    prefixes: HashMap<Vec<u8>, Vec<Key>>,
+}
+
+/// A global state recording every read made via the views it checks out.
+struct ReadRecordingState {
+    inner: Arc<LmdbGlobalState>,
+    reads: Rc<RefCell<RecordedReads>>,
+}
+
+impl StateProvider for ReadRecordingState {
+    type Error = GlobalStateError;
+    type Reader = ReadRecordingView;
+
+    fn checkout(&self, state_hash: Blake2bHash) -> Result<Option<Self::Reader>, Self::Error> {
+        let maybe_view = self.inner.checkout(state_hash)?;
+        Ok(maybe_view.map(|inner| ReadRecordingView {
+            inner,
+            reads: Rc::clone(&self.reads),
+        }))
+    }
+
+    fn commit(
+        &self,
+        correlation_id: CorrelationId,
+        state_hash: Blake2bHash,
+        effects: AdditiveMap<Key, Transform>,
+    ) -> Result<CommitResult, Self::Error> {
+        self.inner.commit(correlation_id, state_hash, effects)
+    }
+
+    fn put_protocol_data(
+        &self,
+        protocol_version: ProtocolVersion,
+        protocol_data: &ProtocolData,
+    ) -> Result<(), Self::Error> {
+        self.inner
+            .put_protocol_data(protocol_version, protocol_data)
+    }
+
+    fn get_protocol_data(
+        &self,
+        protocol_version: ProtocolVersion,
+    ) -> Result<Option<ProtocolData>, Self::Error> {
+        self.inner.get_protocol_data(protocol_version)
+    }
+
+    fn empty_root(&self) -> Blake2bHash {
+        self.inner.empty_root()
+    }
+
+    fn read_trie(
+        &self,
+        correlation_id: CorrelationId,
+        trie_key: &Blake2bHash,
+    ) -> Result<Option<Trie<Key, StoredValue>>, Self::Error> {
+        self.inner.read_trie(correlation_id, trie_key)
+    }
+
+    fn put_trie(
+        &self,
+        correlation_id: CorrelationId,
+        trie: &Trie<Key, StoredValue>,
+    ) -> Result<Blake2bHash, Self::Error> {
+        self.inner.put_trie(correlation_id, trie)
+    }
+
+    fn missing_trie_keys(
+        &self,
+        correlation_id: CorrelationId,
+        trie_keys: Vec<Blake2bHash>,
+    ) -> Result<Vec<Blake2bHash>, Self::Error> {
+        self.inner.missing_trie_keys(correlation_id, trie_keys)
+    }
+
+    fn key_values_with_prefix(
+        &self,
+        correlation_id: CorrelationId,
+        state_hash: Blake2bHash,
+        prefix: &[u8],
+        start_after: Option<&Key>,
+        limit: usize,
+    ) -> Result<Option<Vec<(Key, StoredValue)>>, Self::Error> {
+        self.inner
+            .key_values_with_prefix(correlation_id, state_hash, prefix, start_after, limit)
+    }
+}
+
+/// A view of global state recording every read made through it.
+struct ReadRecordingView {
+    inner: LmdbGlobalStateView,
+    reads: Rc<RefCell<RecordedReads>>,
+}
+
+impl StateReader<Key, StoredValue> for ReadRecordingView {
+    type Error = GlobalStateError;
+
+    fn read(
+        &self,
+        correlation_id: CorrelationId,
+        key: &Key,
+    ) -> Result<Option<StoredValue>, Self::Error> {
+        let maybe_value = self.inner.read(correlation_id, key)?;
+        self.reads
+            .borrow_mut()
+            .values
+            .entry(*key)
+            .or_insert_with(|| maybe_value.clone());
+        Ok(maybe_value)
+    }
+
+    fn read_with_proof(
+        &self,
+        correlation_id: CorrelationId,
+        key: &Key,
+    ) -> Result<Option<TrieMerkleProof<Key, StoredValue>>, Self::Error> {
+        let maybe_proof = self.inner.read_with_proof(correlation_id, key)?;
+        self.reads
+            .borrow_mut()
+            .values
+            .entry(*key)
+            .or_insert_with(|| maybe_proof.as_ref().map(|proof| proof.value().clone()));
+        Ok(maybe_proof)
+    }
+
+    fn keys_with_prefix(
+        &self,
+        correlation_id: CorrelationId,
+        prefix: &[u8],
+    ) -> Result<Vec<Key>, Self::Error> {
+        let keys = self.inner.keys_with_prefix(correlation_id, prefix)?;
+        self.reads
+            .borrow_mut()
+            .prefixes
+            .entry(prefix.to_vec())
+            .or_insert_with(|| keys.clone());
+        Ok(keys)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, iter};
+
+    use prometheus::Registry;
+
+    use casper_execution_engine::{
+        core::engine_state::{
+            executable_deploy_item::ExecutableDeployItem, genesis::GenesisResult,
+        },
+        shared::motes::Motes,
+    };
+    use casper_types::{
+        account::AccountHash,
+        bytesrepr::Bytes,
+        runtime_args,
+        system::{auction::DelegationRate, mint, standard_payment},
+        CLValue, EraId, ExecutionResult, PublicKey, RuntimeArgs, SecretKey, U512,
+    };
+
+    use super::*;
+    use crate::{
+        components::{
+            contract_runtime::{Config, ContractRuntime},
+            storage,
+        },
+        crypto::AsymmetricKeyExt,
+        testing::TestRng,
+        types::{
+            chainspec::{AccountConfig, AccountsConfig, ValidatorConfig},
+            BlockPayload, Chainspec, DeployHash, DeployHeader, FinalizedBlock, TimeDiff, Timestamp,
+        },
+        utils::{Loadable, WithDir},
+    };
+
+    const SENDER_COUNT: usize = 4;
+
+    /// A contract runtime with parallel execution enabled, initialized with a genesis state in
+    /// which each of the senders has a funded account, the first also being the only validator.
+    struct Fixture {
+        contract_runtime: ContractRuntime,
+        chainspec: Arc<Chainspec>,
+        senders: Vec<SecretKey>,
+        context: BlockExecutionContext,
+        pre_state_root_hash: Digest,
+        _temp_dir: tempfile::TempDir,
+    }
+
+    impl Fixture {
+        fn new(rng: &mut TestRng) -> Self {
+            let senders: Vec<SecretKey> =
+                (0..SENDER_COUNT).map(|_| SecretKey::random(rng)).collect();
+            let accounts = senders
+                .iter()
+                .enumerate()
+                .map(|(index, secret_key)| {
+                    let validator_config = if index == 0 {
+                        Some(ValidatorConfig::new(
+                            Motes::new(U512::from(1_000_000_000_000_u64)),
+                            DelegationRate::default(),
+                        ))
+                    } else {
+                        None
+                    };
+                    AccountConfig::new(
+                        PublicKey::from(secret_key),
+                        Motes::new(U512::from(1_000_000_000_000_000_u64)),
+                        validator_config,
+                    )
+                })
+                .collect();
+            let mut chainspec = Chainspec::from_resources("local");
+            chainspec.network_config.accounts_config = AccountsConfig::new(accounts, vec![]);
+            let chainspec = Arc::new(chainspec);
+
+            let (storage_config, temp_dir) = storage::Config::default_for_tests();
+            fs::create_dir_all(&storage_config.path).expect("should create global state dir");
+            let config: Config =
+                toml::from_str("parallel_execution = true").expect("should parse config");
+            let contract_runtime = ContractRuntime::new(
+                Digest::default(),
+                None,
+                chainspec.protocol_config.version,
+                WithDir::new(temp_dir.path(), storage_config),
+                &config,
+                &Registry::new(),
+            )
+            .expect("should create contract runtime");
+
+            let pre_state_root_hash = match contract_runtime
+                .commit_genesis(Arc::clone(&chainspec))
+                .expect("should commit genesis")
+            {
+                GenesisResult::Success {
+                    post_state_hash, ..
+                } => post_state_hash.into(),
+                other => panic!("genesis failed: {}", other),
+            };
+
+            let finalized_block = FinalizedBlock::new(
+                BlockPayload::new(vec![], vec![], vec![], false),
+                None,
+                Timestamp::now(),
+                EraId::from(0),
+                1,
+                PublicKey::from(&senders[0]),
+            );
+            let context =
+                BlockExecutionContext::new(&finalized_block, chainspec.protocol_config.version);
+
+            Fixture {
+                contract_runtime,
+                chainspec,
+                senders,
+                context,
+                pre_state_root_hash,
+                _temp_dir: temp_dir,
+            }
+        }
+
+        /// Creates a native transfer deploy from the `sender_index`th sender to `target`.
+        fn transfer(&self, sender_index: usize, target: AccountHash, id: u64) -> Deploy {
+            let payment = ExecutableDeployItem::ModuleBytes {
+                module_bytes: Bytes::new(),
+                args: runtime_args! { standard_payment::ARG_AMOUNT => U512::from(10_000) },
+            };
+            let session = ExecutableDeployItem::Transfer {
+                args: runtime_args! {
+                    mint::ARG_AMOUNT => U512::from(10_000_000_000_u64),
+                    mint::ARG_TARGET => target,
+                    mint::ARG_ID => Some(id),
+                },
+            };
+            Deploy::new(
+                Timestamp::now(),
+                TimeDiff::from_seconds(600),
+                1,
+                vec![],
+                self.chainspec.network_config.name.clone(),
+                payment,
+                session,
+                &self.senders[sender_index],
+            )
+        }
+
+        /// Executes the deploys on top of the pre-state both sequentially and in parallel,
+        /// returning the results of each along with the number of conflicts found.
+        async fn execute_both_ways(
+            &self,
+            deploys: VecDeque<Deploy>,
+        ) -> (
+            (Digest, DeployExecutionResults),
+            (Digest, DeployExecutionResults),
+            u64,
+        ) {
+            let engine_state = &self.contract_runtime.engine_state;
+            let metrics = &self.contract_runtime.metrics;
+            let sequential = operations::execute_and_commit_sequentially(
+                Arc::clone(engine_state),
+                Arc::clone(metrics),
+                &self.context,
+                self.pre_state_root_hash,
+                deploys.clone(),
+            )
+            .await;
+
+            let conflicts_before = metrics.parallel_conflicts.get();
+            let parallel = self
+                .contract_runtime
+                .parallel_executor
+                .as_ref()
+                .expect("parallel execution should be enabled")
+                .execute_and_commit(
+                    Arc::clone(engine_state),
+                    Arc::clone(metrics),
+                    &self.context,
+                    self.pre_state_root_hash,
+                    deploys,
+                )
+                .await;
+            let conflicts = metrics.parallel_conflicts.get() - conflicts_before;
+
+            (sequential, parallel, conflicts)
+        }
+    }
+
+    fn assert_all_succeeded(
+        deploy_execution_results: &[(DeployHash, (DeployHeader, ExecutionResult))],
+    ) {
+        for (deploy_hash, (_, execution_result)) in deploy_execution_results {
+            assert!(
+                matches!(execution_result, ExecutionResult::Success { .. }),
+                "deploy {} failed: {:?}",
+                deploy_hash,
+                execution_result
+            );
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn should_match_sequential_execution_for_independent_deploys() {
+        let mut rng = crate::new_rng();
+        let fixture = Fixture::new(&mut rng);
+
+        // Each sender transfers to a distinct new account.
+        let deploys = (0..SENDER_COUNT)
+            .map(|index| {
+                let target = PublicKey::from(&SecretKey::random(&mut rng)).to_account_hash();
+                fixture.transfer(index, target, index as u64)
+            })
+            .collect();
+
+        let (sequential, parallel, _) = fixture.execute_both_ways(deploys).await;
+        assert_all_succeeded(&sequential.1);
+        assert_eq!(sequential.0, parallel.0);
+        assert_eq!(sequential, parallel);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn should_match_sequential_execution_for_conflicting_deploys() {
+        let mut rng = crate::new_rng();
+        let fixture = Fixture::new(&mut rng);
+
+        // The first sender transfers twice, and two other senders transfer to the same new
+        // account, interleaved with an independent transfer.
+        let shared_target = PublicKey::from(&SecretKey::random(&mut rng)).to_account_hash();
+        let other_target = PublicKey::from(&SecretKey::random(&mut rng)).to_account_hash();
+        let deploys: VecDeque<Deploy> = vec![
+            fixture.transfer(0, shared_target, 0),
+            fixture.transfer(3, other_target, 1),
+            fixture.transfer(0, other_target, 2),
+            fixture.transfer(1, shared_target, 3),
+            fixture.transfer(2, shared_target, 4),
+        ]
+        .into_iter()
+        .collect();
+        let deploy_count = deploys.len() as u64;
+
+        let (sequential, parallel, conflicts) = fixture.execute_both_ways(deploys).await;
+        assert_all_succeeded(&sequential.1);
+        assert_eq!(sequential.0, parallel.0);
+        assert_eq!(sequential, parallel);
+        assert!(conflicts > 0);
+        assert!(conflicts < deploy_count);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn should_only_flag_changed_reads_as_conflicts() {
+        let mut rng = crate::new_rng();
+        let fixture = Fixture::new(&mut rng);
+        let parallel_executor = fixture
+            .contract_runtime
+            .parallel_executor
+            .as_ref()
+            .expect("parallel execution should be enabled");
+
+        // Overwrite one key, as an earlier deploy in the block would.
+        let written_key = Key::Hash([1; 32]);
+        let unwritten_key = Key::Hash([2; 32]);
+        let old_value = StoredValue::CLValue(CLValue::from_t(1_u64).unwrap());
+        let new_value = StoredValue::CLValue(CLValue::from_t(2_u64).unwrap());
+        let mut effects = AdditiveMap::new();
+        effects.insert(written_key, Transform::Write(new_value.clone()));
+        let state_root_hash = match fixture
+            .contract_runtime
+            .engine_state
+            .apply_effect(
+                CorrelationId::new(),
+                fixture.pre_state_root_hash.into(),
+                effects,
+            )
+            .expect("should apply effects")
+        {
+            CommitResult::Success { state_root } => state_root.into(),
+            other => panic!("commit failed: {}", other),
+        };
+        let written_keys: HashSet<Key> = iter::once(written_key).collect();
+
+        let check = |values: Vec<(Key, Option<StoredValue>)>,
+                     prefixes: Vec<(Vec<u8>, Vec<Key>)>| {
+            let reads = RecordedReads {
+                values: values.into_iter().collect(),
+                prefixes: prefixes.into_iter().collect(),
+            };
+            parallel_executor.reads_are_unchanged(&reads, &written_keys, state_root_hash)
+        };
+
+        // Reads of keys not written since the pre-state are unaffected.
+        assert!(check(vec![(unwritten_key, None)], vec![]));
+        // A read of a written key conflicts unless it saw the value now in place.
+        assert!(!check(vec![(written_key, None)], vec![]));
+        assert!(!check(vec![(written_key, Some(old_value))], vec![]));
+        assert!(check(vec![(written_key, Some(new_value))], vec![]));
+        // A prefix read conflicts if it covers a written key and its keys have changed.
+        let written_key_bytes = written_key.to_bytes().unwrap();
+        let hash_prefix = written_key_bytes[..1].to_vec();
+        let account_prefix =
+            Key::Account(AccountHash::new([0; 32])).to_bytes().unwrap()[..1].to_vec();
+        assert!(!check(vec![], vec![(written_key_bytes, vec![])]));
+        assert!(check(vec![], vec![(account_prefix, vec![])]));
+        let unchanged_hash_keys = parallel_executor
+            .global_state
+            .checkout(state_root_hash.into())
+            .unwrap()
+            .unwrap()
+            .keys_with_prefix(CorrelationId::new(), &hash_prefix)
+            .unwrap();
+        assert!(check(vec![], vec![(hash_prefix, unchanged_hash_keys)]));
+    }
+}
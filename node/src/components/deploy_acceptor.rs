@@ -1,29 +1,46 @@
+mod account_policy;
+mod approvals_cache;
 mod config;
 mod event;
+mod finalized_deploys;
+mod metrics;
+mod upgrade_boundary;
 
-use std::{convert::Infallible, fmt::Debug};
+use std::fmt::Debug;
 
+use prometheus::Registry;
 use thiserror::Error;
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 
 use crate::{
     components::Component,
     effect::{
-        announcements::DeployAcceptorAnnouncement,
+        announcements::{BlocklistAnnouncement, DeployAcceptorAnnouncement},
         requests::{ContractRuntimeRequest, StorageRequest},
         EffectBuilder, EffectExt, Effects,
     },
-    types::{chainspec::DeployConfig, Chainspec, Deploy, DeployValidationFailure, NodeId},
-    utils::Source,
+    reactor,
+    types::{
+        chainspec::{ActivationPoint, ApprovalAlgorithm, DeployConfig},
+        decode_canonical_deploy, deploy_span, BlockHeader, Chainspec, Deploy, DeployDecodingError,
+        DeployHash, DeployValidationFailure, NodeId, SharedObject, Summary, TimeDiff, Timestamp,
+    },
+    utils::{Source, WithDir},
     NodeRng,
 };
-use casper_types::Key;
+use casper_types::{Key, PublicKey};
 
 use crate::effect::Responder;
+use account_policy::AccountPolicy;
+pub use account_policy::AccountPolicyLoadError;
+use approvals_cache::{ApprovalsCache, SignatureVerifier};
 pub use config::Config;
 pub use event::Event;
+use finalized_deploys::FinalizedDeploys;
+use metrics::DeployAcceptorMetrics;
+use upgrade_boundary::UpgradeBoundary;
 
-#[derive(Debug, Error)]
+#[derive(Clone, Debug, Error)]
 pub enum Error {
     /// An invalid deploy was received from the client.
     #[error("invalid deploy: {0}")]
@@ -34,12 +51,53 @@ pub enum Error {
     /// A deploy was sent from account with insufficient balance.
     #[error("insufficient balance")]
     InsufficientBalance,
+    /// A deploy was sent via RPC from an account not on this node's allowlist.
+    #[error("account {0} is not in this node's deploy allowlist")]
+    AccountNotInAllowlist(PublicKey),
+    /// A deploy was sent via RPC from an account on this node's denylist.
+    #[error("account {0} is in this node's deploy denylist")]
+    AccountInDenylist(PublicKey),
+    /// A deploy was sent via RPC which expires after the next upgrade is expected to activate.
+    #[error(
+        "deploy expires at {expires}, after the next upgrade's {activation_point}, expected at \
+         {boundary}"
+    )]
+    ExpiresAfterUpgrade {
+        /// The time at which the deploy expires.
+        expires: Timestamp,
+        /// The activation point of the next upgrade.
+        activation_point: ActivationPoint,
+        /// The estimated time at which the next upgrade activates.
+        boundary: Timestamp,
+    },
+    /// A deploy was sent via RPC shortly before the next upgrade is expected to activate.
+    #[error(
+        "not accepting deploys until the next upgrade's {activation_point}, expected at {boundary}"
+    )]
+    UpgradeImminent {
+        /// The activation point of the next upgrade.
+        activation_point: ActivationPoint,
+        /// The estimated time at which the next upgrade activates.
+        boundary: Timestamp,
+    },
+}
+
+/// Error constructing a `DeployAcceptor`.
+#[derive(Debug, Error)]
+pub enum ConstructionError {
+    /// Error loading the account policy.
+    #[error(transparent)]
+    AccountPolicy(#[from] AccountPolicyLoadError),
+    /// Error registering metrics.
+    #[error("prometheus (metrics) error: {0}")]
+    Metrics(#[from] prometheus::Error),
 }
 
 /// A helper trait constraining `DeployAcceptor` compatible reactor events.
 pub trait ReactorEventT:
     From<Event>
     + From<DeployAcceptorAnnouncement<NodeId>>
+    + From<BlocklistAnnouncement<NodeId>>
     + From<StorageRequest>
     + From<ContractRuntimeRequest>
     + Send
@@ -49,6 +107,7 @@ pub trait ReactorEventT:
 impl<REv> ReactorEventT for REv where
     REv: From<Event>
         + From<DeployAcceptorAnnouncement<NodeId>>
+        + From<BlocklistAnnouncement<NodeId>>
         + From<StorageRequest>
         + From<ContractRuntimeRequest>
         + Send
@@ -63,47 +122,172 @@ impl<REv> ReactorEventT for REv where
 #[derive(Debug)]
 pub struct DeployAcceptor {
     chain_name: String,
+    /// The deploy config of the active protocol version, replaced as an upgrade is committed.
     deploy_config: DeployConfig,
     verify_accounts: bool,
+    /// The policy restricting which accounts may submit deploys via RPC, if configured.
+    account_policy: Option<AccountPolicy>,
+    /// Approvals already verified, so that deploys received repeatedly are cheap to re-validate.
+    approvals_cache: ApprovalsCache,
+    /// The next upgrade, beyond which deploys can't be expected to execute.
+    upgrade_boundary: UpgradeBoundary,
+    /// The period before the next upgrade during which no new deploys are accepted.
+    upgrade_drain_window: TimeDiff,
+    /// Deploys included in finalized blocks, copies of which received from peers are ignored.
+    finalized_deploys: FinalizedDeploys,
 }
 
 impl DeployAcceptor {
-    pub(crate) fn new(config: Config, chainspec: &Chainspec) -> Self {
-        DeployAcceptor {
+    pub(crate) fn new(
+        config: WithDir<&Config>,
+        chainspec: &Chainspec,
+        maybe_next_activation_point: Option<ActivationPoint>,
+        maybe_latest_block_header: Option<&BlockHeader>,
+        registry: &Registry,
+    ) -> Result<Self, ConstructionError> {
+        let account_policy = config
+            .value()
+            .account_policy_path()
+            .map(|path| AccountPolicy::load(config.with_dir(path.clone())))
+            .transpose()?;
+        let approvals_cache = ApprovalsCache::new(
+            config.value().verified_approvals_cache_size(),
+            Box::new(SignatureVerifier),
+            DeployAcceptorMetrics::new(registry)?,
+        );
+        Ok(DeployAcceptor {
             chain_name: chainspec.network_config.name.clone(),
             deploy_config: chainspec.deploy_config,
-            verify_accounts: config.verify_accounts(),
+            verify_accounts: config.value().verify_accounts(),
+            account_policy,
+            approvals_cache,
+            upgrade_boundary: UpgradeBoundary::new(
+                chainspec.core_config.era_duration,
+                maybe_next_activation_point,
+                maybe_latest_block_header,
+            ),
+            upgrade_drain_window: config.value().upgrade_drain_window(),
+            finalized_deploys: FinalizedDeploys::default(),
+        })
+    }
+
+    /// Starts the recurring task of checking the account policy file for changes, if an account
+    /// policy is configured.  It should be used in the participating reactor's constructor.
+    pub(crate) fn start_checking_account_policy<REv: ReactorEventT>(
+        &self,
+        effect_builder: EffectBuilder<REv>,
+    ) -> Effects<Event> {
+        if self.account_policy.is_none() {
+            return Effects::new();
         }
+        effect_builder
+            .set_timeout(account_policy::CHECK_INTERVAL)
+            .event(|_| Event::CheckAccountPolicy)
+    }
+
+    /// Reloads the account policy if its file has changed, then schedules the next check.
+    fn handle_check_account_policy<REv: ReactorEventT>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+    ) -> Effects<Event> {
+        if let Some(account_policy) = self.account_policy.as_mut() {
+            account_policy.reload_if_changed();
+        }
+        self.start_checking_account_policy(effect_builder)
     }
 
     /// Handles receiving a new `Deploy` from a peer or client.
     /// In the case of a peer, there should be no responder and the variant should be `None`
     /// In the case of a client, there should be a responder to communicate the validity of the
     /// deploy and the variant will be `Some`
+    ///
+    /// This opens the tracing span in which the rest of the deploy's lifecycle on this node is
+    /// traced, tagged with the short form of the deploy's hash.
     fn accept<REv: ReactorEventT>(
         &mut self,
         effect_builder: EffectBuilder<REv>,
         deploy: Box<Deploy>,
         source: Source<NodeId>,
         maybe_responder: Option<Responder<Result<(), Error>>>,
+    ) -> Effects<Event> {
+        let span = deploy_span(deploy.id());
+        let effects = span.in_scope(|| {
+            debug!(%source, deploy = %deploy.summary(), "accepting deploy");
+            self.check_acceptable(effect_builder, deploy, source, maybe_responder)
+        });
+        reactor::in_span(effects, span)
+    }
+
+    /// Checks whether a new `Deploy` is acceptable, verifying its account if required.
+    fn check_acceptable<REv: ReactorEventT>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        deploy: Box<Deploy>,
+        source: Source<NodeId>,
+        maybe_responder: Option<Responder<Result<(), Error>>>,
     ) -> Effects<Event> {
         let mut cloned_deploy = deploy.clone();
         let mut effects = Effects::new();
-        let is_acceptable = cloned_deploy.is_acceptable(&self.chain_name, &self.deploy_config);
-        if let Err(error) = is_acceptable {
+        let deploy_config = self.deploy_config;
+        if let Err(error) = self.validate(&mut cloned_deploy, &deploy_config) {
+            let error = Error::InvalidDeploy(error);
             // The client has submitted an invalid deploy. Return an error to the RPC component via
             // the responder.
             if let Some(responder) = maybe_responder {
-                effects.extend(responder.respond(Err(Error::InvalidDeploy(error))).ignore());
+                effects.extend(responder.respond(Err(error.clone())).ignore());
             }
             effects.extend(
                 effect_builder
-                    .announce_invalid_deploy(deploy, source)
+                    .announce_invalid_deploy(deploy, source, error)
                     .ignore(),
             );
             return effects;
         }
 
+        // Checked once the deploy is known to be valid, so that its expiry can be relied upon.
+        if let Err(error) = self.check_upgrade_boundary(&deploy, Timestamp::now()) {
+            if source.from_client() {
+                info!(%error, deploy = %deploy.summary(), "rejected deploy received from client");
+                if let Some(responder) = maybe_responder {
+                    effects.extend(responder.respond(Err(error.clone())).ignore());
+                }
+                effects.extend(
+                    effect_builder
+                        .announce_invalid_deploy(deploy, source, error)
+                        .ignore(),
+                );
+            } else {
+                // The peer is not at fault, as it may not yet know of the upgrade.
+                debug!(%error, deploy = %deploy.summary(), "dropping deploy received from peer");
+                self.approvals_cache
+                    .metrics()
+                    .deploys_dropped_near_upgrade
+                    .inc();
+            }
+            return effects;
+        }
+
+        // Deploys gossiped by peers are subject to consensus rules only, not this node's policy.
+        if source.from_client() {
+            if let Some(account_policy) = self.account_policy.as_ref() {
+                if let Err(error) = account_policy.check(deploy.header().account()) {
+                    info!(
+                        %error, deploy = %deploy.summary(),
+                        "rejected deploy received from client"
+                    );
+                    if let Some(responder) = maybe_responder {
+                        effects.extend(responder.respond(Err(error.clone())).ignore());
+                    }
+                    effects.extend(
+                        effect_builder
+                            .announce_invalid_deploy(deploy, source, error)
+                            .ignore(),
+                    );
+                    return effects;
+                }
+            }
+        }
+
         let account_key = deploy.header().account().to_account_hash().into();
 
         // Verify account if deploy received from client and node is configured to do so.
@@ -130,6 +314,152 @@ impl DeployAcceptor {
             })
     }
 
+    /// Checks `deploy` against this chain's name, and against the limits, allowed approval
+    /// algorithms and hash scheme for the deploy's timestamp of `deploy_config`, which should be
+    /// that of the active protocol version.
+    fn validate(
+        &mut self,
+        deploy: &mut Deploy,
+        deploy_config: &DeployConfig,
+    ) -> Result<(), DeployValidationFailure> {
+        // Checked before any signature is verified, as a disallowed approval is fatal regardless.
+        // Approvals by the system account have no algorithm, and are never restricted.
+        for (index, approval) in deploy.approvals().iter().enumerate() {
+            if let Some(algorithm) = ApprovalAlgorithm::of(approval.signer()) {
+                if !deploy_config
+                    .allowed_approval_algorithms
+                    .contains(algorithm)
+                {
+                    info!(index, %algorithm, "approval signed with disallowed algorithm");
+                    return Err(DeployValidationFailure::DisallowedApprovalAlgorithm {
+                        index,
+                        algorithm: algorithm.to_string(),
+                    });
+                }
+            }
+        }
+
+        let hash_scheme = deploy_config.hash_scheme(deploy.header().timestamp());
+        let approvals_cache = &mut self.approvals_cache;
+        deploy.is_acceptable_with(
+            &self.chain_name,
+            &deploy_config.deploy_limits(),
+            hash_scheme,
+            |deploy_hash, approval| approvals_cache.verify(deploy_hash, approval),
+        )
+    }
+
+    /// Checks that `deploy` expires before the next upgrade is expected to activate, and that the
+    /// upgrade isn't within the drain window, if configured.
+    fn check_upgrade_boundary(&self, deploy: &Deploy, now: Timestamp) -> Result<(), Error> {
+        let (activation_point, boundary) = match (
+            self.upgrade_boundary.activation_point(),
+            self.upgrade_boundary.estimate(now),
+        ) {
+            (Some(activation_point), Some(boundary)) => (activation_point, boundary),
+            _ => return Ok(()),
+        };
+
+        if self.upgrade_drain_window.millis() > 0
+            && now >= boundary.saturating_sub(self.upgrade_drain_window)
+        {
+            return Err(Error::UpgradeImminent {
+                activation_point,
+                boundary,
+            });
+        }
+
+        let expires = deploy.header().expires();
+        if expires > boundary {
+            return Err(Error::ExpiresAfterUpgrade {
+                expires,
+                activation_point,
+                boundary,
+            });
+        }
+        Ok(())
+    }
+
+    /// Handles the activation point of the next upgrade being read.
+    fn handle_got_upgrade_activation_point(
+        &mut self,
+        activation_point: ActivationPoint,
+    ) -> Effects<Event> {
+        // The activation point is read periodically, so is only logged when it changes.
+        if self.upgrade_boundary.activation_point() != Some(activation_point) {
+            info!(%activation_point, "rejecting deploys expiring after the next upgrade");
+        }
+        self.upgrade_boundary.set_activation_point(activation_point);
+        Effects::new()
+    }
+
+    /// Handles an upgrade being committed, after which deploys are checked against the upgrade's
+    /// deploy config.
+    fn handle_deploy_config_activated(&mut self, deploy_config: DeployConfig) -> Effects<Event> {
+        info!(
+            max_deploy_size = deploy_config.max_deploy_size,
+            "checking deploys against upgraded deploy config"
+        );
+        self.deploy_config = deploy_config;
+        Effects::new()
+    }
+
+    /// Handles deploys having been included in a finalized block.  Copies of them received from
+    /// peers are ignored until they must have expired.
+    fn handle_deploys_finalized(&mut self, deploy_hashes: Vec<DeployHash>) -> Effects<Event> {
+        let retain_until = Timestamp::now() + self.deploy_config.max_ttl;
+        for deploy_hash in deploy_hashes {
+            self.finalized_deploys.insert(deploy_hash, retain_until);
+        }
+        debug!(
+            count = self.finalized_deploys.len(),
+            "ignoring finalized deploys received from peers"
+        );
+        Effects::new()
+    }
+
+    /// Handles receiving a serialized `Deploy` from a peer.
+    ///
+    /// Only the canonical encoding of a deploy is accepted, so that the same deploy can't be
+    /// gossiped in several guises.  Since honest peers only ever send canonical encodings, a peer
+    /// sending any other is disconnected from.
+    fn handle_received_from_peer<REv: ReactorEventT>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        serialized_deploy: SharedObject<Vec<u8>>,
+        sender: NodeId,
+    ) -> Effects<Event> {
+        match decode_canonical_deploy(&serialized_deploy) {
+            Ok(deploy) => {
+                if self
+                    .finalized_deploys
+                    .contains(deploy.id(), Timestamp::now())
+                {
+                    debug!(
+                        %sender, deploy = %deploy.summary(),
+                        "ignoring finalized deploy received from peer"
+                    );
+                    self.approvals_cache
+                        .metrics()
+                        .finalized_deploys_ignored
+                        .inc();
+                    return Effects::new();
+                }
+                self.accept(effect_builder, Box::new(deploy), Source::Peer(sender), None)
+            }
+            Err(DeployDecodingError::Decode(error)) => {
+                error!("failed to decode deploy from {}: {}", sender, error);
+                Effects::new()
+            }
+            Err(error @ DeployDecodingError::NonCanonical(_)) => {
+                warn!(%sender, %error, "peer sent non-canonically encoded deploy");
+                effect_builder
+                    .announce_disconnect_from_peer(sender)
+                    .ignore()
+            }
+        }
+    }
+
     fn account_verification<REv: ReactorEventT>(
         &mut self,
         effect_builder: EffectBuilder<REv>,
@@ -141,7 +471,7 @@ impl DeployAcceptor {
     ) -> Effects<Event> {
         let mut effects = Effects::new();
 
-        match verified {
+        let error = match verified {
             Some(true) => {
                 effects.extend(effect_builder.put_deploy_to_storage(deploy.clone()).event(
                     move |is_new| Event::PutToStorageResult {
@@ -156,31 +486,32 @@ impl DeployAcceptor {
             }
 
             Some(false) => {
-                info! {
-                    "Received deploy from account {} that does not have minimum balance required", account_key
-                };
+                info!(
+                    %account_key, deploy = %deploy.summary(),
+                    "received deploy from account that does not have minimum balance required"
+                );
                 // The client has submitted a deploy from an account that does not have minimum
                 // balance required. Return an error message to the RPC component via the responder.
-                if let Some(responder) = maybe_responder {
-                    effects.extend(responder.respond(Err(Error::InsufficientBalance)).ignore());
-                }
+                Error::InsufficientBalance
             }
 
             None => {
                 // The client has submitted an invalid deploy. Return an error message to the RPC
                 // component via the responder.
-                info! {
-                    "Received deploy from invalid account using {}", account_key
-                };
-                if let Some(responder) = maybe_responder {
-                    effects.extend(responder.respond(Err(Error::InvalidAccount)).ignore());
-                }
+                info!(
+                    %account_key, deploy = %deploy.summary(),
+                    "received deploy from invalid account"
+                );
+                Error::InvalidAccount
             }
-        }
+        };
 
+        if let Some(responder) = maybe_responder {
+            effects.extend(responder.respond(Err(error.clone())).ignore());
+        }
         effects.extend(
             effect_builder
-                .announce_invalid_deploy(deploy, source)
+                .announce_invalid_deploy(deploy, source, error)
                 .ignore(),
         );
         effects
@@ -212,7 +543,7 @@ impl DeployAcceptor {
 
 impl<REv: ReactorEventT> Component<REv> for DeployAcceptor {
     type Event = Event;
-    type ConstructionError = Infallible;
+    type ConstructionError = ConstructionError;
 
     fn handle_event(
         &mut self,
@@ -220,13 +551,17 @@ impl<REv: ReactorEventT> Component<REv> for DeployAcceptor {
         _rng: &mut NodeRng,
         event: Self::Event,
     ) -> Effects<Self::Event> {
-        debug!(?event, "handling event");
+        debug!(%event, "handling event");
         match event {
             Event::Accept {
                 deploy,
                 source,
                 responder,
             } => self.accept(effect_builder, deploy, source, responder),
+            Event::ReceivedFromPeer {
+                serialized_deploy,
+                sender,
+            } => self.handle_received_from_peer(effect_builder, serialized_deploy, sender),
             Event::PutToStorageResult {
                 deploy,
                 source,
@@ -249,6 +584,319 @@ impl<REv: ReactorEventT> Component<REv> for DeployAcceptor {
                 verified,
                 maybe_responder,
             ),
+            Event::DeployConfigActivated(deploy_config) => {
+                self.handle_deploy_config_activated(deploy_config)
+            }
+            Event::GotUpgradeActivationPoint(activation_point) => {
+                self.handle_got_upgrade_activation_point(activation_point)
+            }
+            Event::BlockAdded(block_header) => {
+                self.upgrade_boundary.block_added(&block_header);
+                Effects::new()
+            }
+            Event::DeploysFinalized(deploy_hashes) => self.handle_deploys_finalized(deploy_hashes),
+            Event::CheckAccountPolicy => self.handle_check_account_policy(effect_builder),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_execution_engine::core::engine_state::executable_deploy_item::ExecutableDeployItem;
+    use casper_types::{
+        bytesrepr::{Bytes, ToBytes},
+        EraId, RuntimeArgs, SecretKey,
+    };
+
+    use super::*;
+    use crate::{
+        crypto::AsymmetricKeyExt,
+        testing::TestRng,
+        types::{chainspec::ApprovalAlgorithms, DeployHashScheme},
+        utils::Loadable,
+    };
+
+    const ERA_DURATION: u64 = 600_000;
+
+    /// Creates a deploy for the local chain whose session code is `session_size` bytes long, signed
+    /// by `secret_key`.
+    fn new_deploy(session_size: usize, secret_key: &SecretKey) -> Deploy {
+        let payment = ExecutableDeployItem::ModuleBytes {
+            module_bytes: Bytes::new(),
+            args: RuntimeArgs::new(),
+        };
+        let session = ExecutableDeployItem::ModuleBytes {
+            module_bytes: Bytes::from(vec![0; session_size]),
+            args: RuntimeArgs::new(),
+        };
+        Deploy::new(
+            Timestamp::now(),
+            TimeDiff::from(60_000),
+            1,
+            vec![],
+            "casper-example".to_string(),
+            payment,
+            session,
+            secret_key,
+        )
+    }
+
+    /// Creates a deploy for the local chain with the given timestamp, hashed using `hash_scheme`.
+    fn new_hashed_deploy(
+        timestamp: Timestamp,
+        hash_scheme: DeployHashScheme,
+        secret_key: &SecretKey,
+    ) -> Deploy {
+        let payment = ExecutableDeployItem::ModuleBytes {
+            module_bytes: Bytes::new(),
+            args: RuntimeArgs::new(),
+        };
+        let session = ExecutableDeployItem::ModuleBytes {
+            module_bytes: Bytes::from(vec![0; 1_000]),
+            args: RuntimeArgs::new(),
+        };
+        Deploy::new_with_hash_scheme(
+            timestamp,
+            TimeDiff::from(60_000),
+            1,
+            vec![],
+            "casper-example".to_string(),
+            payment,
+            session,
+            secret_key,
+            hash_scheme,
+        )
+    }
+
+    fn new_deploy_acceptor(chainspec: &Chainspec) -> DeployAcceptor {
+        let config = Config::default();
+        DeployAcceptor::new(
+            WithDir::new(".", &config),
+            chainspec,
+            None,
+            None,
+            &Registry::new(),
+        )
+        .unwrap()
+    }
+
+    /// Creates a deploy acceptor with an upgrade activating in era 3, where era 2 started at
+    /// `era_start`.
+    fn new_deploy_acceptor_before_upgrade(config: &Config, era_start: Timestamp) -> DeployAcceptor {
+        let mut chainspec = Chainspec::from_resources("local");
+        chainspec.core_config.era_duration = TimeDiff::from(ERA_DURATION);
+        let mut deploy_acceptor = DeployAcceptor::new(
+            WithDir::new(".", config),
+            &chainspec,
+            Some(ActivationPoint::EraId(EraId::from(3))),
+            None,
+            &Registry::new(),
+        )
+        .unwrap();
+        deploy_acceptor
+            .upgrade_boundary
+            .era_started(EraId::from(2), era_start);
+        deploy_acceptor
+    }
+
+    /// Simulates an upgrade which tightens the max deploy size.
+    #[test]
+    fn should_check_deploys_against_upgraded_deploy_config_once_activated() {
+        let mut rng = TestRng::new();
+        let chainspec = Chainspec::from_resources("local");
+        let mut deploy_acceptor = new_deploy_acceptor(&chainspec);
+
+        let deploy = new_deploy(1_000, &SecretKey::random(&mut rng));
+        let deploy_size = deploy.serialized_length();
+        assert!(deploy_size <= chainspec.deploy_config.max_deploy_size as usize);
+        let mut upgrade_deploy_config = chainspec.deploy_config;
+        upgrade_deploy_config.max_deploy_size = deploy_size as u32 - 1;
+
+        let check = |deploy_acceptor: &mut DeployAcceptor| {
+            let deploy_config = deploy_acceptor.deploy_config;
+            deploy_acceptor.validate(&mut deploy.clone(), &deploy_config)
+        };
+
+        // The deploy is acceptable until the upgrade is committed...
+        assert!(check(&mut deploy_acceptor).is_ok());
+
+        // ...but exceeds the max deploy size in force afterwards.
+        let _ = deploy_acceptor.handle_deploy_config_activated(upgrade_deploy_config);
+        match check(&mut deploy_acceptor) {
+            Err(DeployValidationFailure::ExcessiveSize(error)) => {
+                assert_eq!(error.max_deploy_size, upgrade_deploy_config.max_deploy_size);
+                assert_eq!(error.actual_deploy_size, deploy_size);
+            }
+            result => panic!("expected excessive size failure, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn should_check_deploy_hashes_using_scheme_for_deploy_timestamp() {
+        let mut rng = TestRng::new();
+        let secret_key = SecretKey::random(&mut rng);
+        let activation = Timestamp::now();
+        let mut chainspec = Chainspec::from_resources("local");
+        chainspec.deploy_config.domain_separated_hashing_activation = Some(activation);
+        let mut deploy_acceptor = new_deploy_acceptor(&chainspec);
+        let deploy_config = chainspec.deploy_config;
+
+        let mut check = |timestamp, hash_scheme| {
+            let mut deploy = new_hashed_deploy(timestamp, hash_scheme, &secret_key);
+            deploy_acceptor.validate(&mut deploy, &deploy_config)
+        };
+
+        // Deploys created before the activation must be hashed using the legacy scheme...
+        let before_activation = activation - TimeDiff::from(1);
+        assert!(check(before_activation, DeployHashScheme::Legacy).is_ok());
+        assert_eq!(
+            check(before_activation, DeployHashScheme::DomainSeparated),
+            Err(DeployValidationFailure::InvalidBodyHash)
+        );
+
+        // ...and those created from the activation using the domain-separated one.
+        assert!(check(activation, DeployHashScheme::DomainSeparated).is_ok());
+        assert_eq!(
+            check(activation, DeployHashScheme::Legacy),
+            Err(DeployValidationFailure::InvalidBodyHash)
+        );
+    }
+
+    #[test]
+    fn should_allow_all_approval_algorithms_by_default() {
+        let mut rng = TestRng::new();
+        let chainspec = Chainspec::from_resources("local");
+        let mut deploy_acceptor = new_deploy_acceptor(&chainspec);
+
+        for secret_key in &[
+            SecretKey::random_ed25519(&mut rng),
+            SecretKey::random_secp256k1(&mut rng),
+        ] {
+            let mut deploy = new_deploy(1_000, secret_key);
+            assert!(deploy_acceptor
+                .validate(&mut deploy, &chainspec.deploy_config)
+                .is_ok());
+        }
+    }
+
+    #[test]
+    fn should_reject_approval_signed_with_disallowed_algorithm() {
+        let mut rng = TestRng::new();
+        let mut chainspec = Chainspec::from_resources("local");
+        chainspec.deploy_config.allowed_approval_algorithms =
+            ApprovalAlgorithms::from(vec![ApprovalAlgorithm::Ed25519]);
+        let mut deploy_acceptor = new_deploy_acceptor(&chainspec);
+        let deploy_config = chainspec.deploy_config;
+
+        let mut deploy = new_deploy(1_000, &SecretKey::random_ed25519(&mut rng));
+        assert!(deploy_acceptor
+            .validate(&mut deploy, &deploy_config)
+            .is_ok());
+
+        // An otherwise valid deploy is rejected once it has any secp256k1 approval.
+        let mut deploy = new_deploy(1_000, &SecretKey::random_secp256k1(&mut rng));
+        assert_eq!(
+            deploy_acceptor.validate(&mut deploy.clone(), &deploy_config),
+            Err(DeployValidationFailure::DisallowedApprovalAlgorithm {
+                index: 0,
+                algorithm: "secp256k1".to_string(),
+            })
+        );
+        deploy.sign(&SecretKey::random_ed25519(&mut rng));
+        match deploy_acceptor.validate(&mut deploy, &deploy_config) {
+            Err(DeployValidationFailure::DisallowedApprovalAlgorithm { algorithm, .. }) => {
+                assert_eq!(algorithm, "secp256k1")
+            }
+            result => panic!("expected disallowed algorithm failure, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn should_accept_deploys_when_no_upgrade_scheduled() {
+        let mut rng = TestRng::new();
+        let deploy = new_deploy(1_000, &SecretKey::random(&mut rng));
+        let now = deploy.header().timestamp();
+        let mut config = Config::default();
+        config.set_upgrade_drain_window(TimeDiff::from(ERA_DURATION));
+        let chainspec = Chainspec::from_resources("local");
+        let mut deploy_acceptor = DeployAcceptor::new(
+            WithDir::new(".", &config),
+            &chainspec,
+            None,
+            None,
+            &Registry::new(),
+        )
+        .unwrap();
+        deploy_acceptor
+            .upgrade_boundary
+            .era_started(EraId::from(2), now);
+
+        assert!(deploy_acceptor.check_upgrade_boundary(&deploy, now).is_ok());
+    }
+
+    #[test]
+    fn should_reject_deploy_expiring_after_upgrade() {
+        let mut rng = TestRng::new();
+        let deploy = new_deploy(1_000, &SecretKey::random(&mut rng));
+        let now = deploy.header().timestamp();
+        let expected_boundary = deploy.header().expires().saturating_sub(TimeDiff::from(1));
+        let era_start = expected_boundary.saturating_sub(TimeDiff::from(ERA_DURATION));
+
+        // The upgrade is expected a millisecond before the deploy expires.
+        let deploy_acceptor = new_deploy_acceptor_before_upgrade(&Config::default(), era_start);
+        let error = deploy_acceptor
+            .check_upgrade_boundary(&deploy, now)
+            .unwrap_err();
+        assert!(error.to_string().contains("era 3"));
+        match error {
+            Error::ExpiresAfterUpgrade {
+                expires,
+                activation_point,
+                boundary,
+            } => {
+                assert_eq!(expires, deploy.header().expires());
+                assert_eq!(activation_point, ActivationPoint::EraId(EraId::from(3)));
+                assert_eq!(boundary, expected_boundary);
+            }
+            error => panic!("expected expires after upgrade error, got {:?}", error),
+        }
+
+        // A deploy expiring at the boundary can still execute before the upgrade.
+        let deploy_acceptor =
+            new_deploy_acceptor_before_upgrade(&Config::default(), era_start + TimeDiff::from(1));
+        assert!(deploy_acceptor.check_upgrade_boundary(&deploy, now).is_ok());
+    }
+
+    #[test]
+    fn should_refuse_deploys_in_upgrade_drain_window() {
+        let mut rng = TestRng::new();
+        let deploy = new_deploy(1_000, &SecretKey::random(&mut rng));
+        let era_start = deploy.header().timestamp();
+        let boundary = era_start + TimeDiff::from(ERA_DURATION);
+        let one_minute_before_upgrade = boundary.saturating_sub(TimeDiff::from(60_000));
+
+        // Without a drain window, the deploy is accepted right up to the upgrade.
+        let deploy_acceptor = new_deploy_acceptor_before_upgrade(&Config::default(), era_start);
+        assert!(deploy_acceptor
+            .check_upgrade_boundary(&deploy, one_minute_before_upgrade)
+            .is_ok());
+
+        // With a two minute drain window, it is accepted until two minutes before the upgrade.
+        let mut config = Config::default();
+        config.set_upgrade_drain_window(TimeDiff::from(120_000));
+        let deploy_acceptor = new_deploy_acceptor_before_upgrade(&config, era_start);
+        assert!(deploy_acceptor
+            .check_upgrade_boundary(&deploy, era_start)
+            .is_ok());
+        match deploy_acceptor.check_upgrade_boundary(&deploy, one_minute_before_upgrade) {
+            Err(Error::UpgradeImminent {
+                activation_point,
+                boundary: actual_boundary,
+            }) => {
+                assert_eq!(activation_point, ActivationPoint::EraId(EraId::from(3)));
+                assert_eq!(actual_boundary, boundary);
+            }
+            result => panic!("expected upgrade imminent error, got {:?}", result),
         }
     }
 }
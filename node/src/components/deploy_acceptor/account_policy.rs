@@ -0,0 +1,363 @@
+//! A node-level policy restricting which accounts may submit deploys via this node's RPC server.
+//!
+//! The policy is read from a TOML file of the form
+//!
+//! ```toml
+//! # If present, only these accounts may submit deploys.
+//! allowlist = ["01...", "02..."]
+//! # These accounts may never submit deploys, regardless of the allowlist.
+//! denylist = ["01..."]
+//! ```
+//!
+//! The file is checked for changes every `CHECK_INTERVAL`, and re-read whenever it has changed, so
+//! entries can be updated without restarting the node.
+
+use std::{
+    collections::BTreeSet,
+    fs, io,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use serde::Deserialize;
+use thiserror::Error;
+use tracing::{error, info};
+
+use casper_types::PublicKey;
+
+use super::Error as DeployAcceptorError;
+use crate::utils::{self, ReadFileError};
+
+/// The interval between checks of the policy file for changes.
+pub(super) const CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Error loading an account policy file.
+#[derive(Debug, Error)]
+pub enum AccountPolicyLoadError {
+    /// Error reading the file.
+    #[error(transparent)]
+    ReadFile(#[from] ReadFileError),
+    /// Error getting the file's metadata.
+    #[error("could not read metadata of '{}': {error}", .path.display())]
+    Metadata {
+        /// Path to the file.
+        path: PathBuf,
+        /// The underlying OS error.
+        #[source]
+        error: io::Error,
+    },
+    /// Error parsing the file's contents.
+    #[error("could not parse '{}': {error}", .path.display())]
+    DecodeFromToml {
+        /// Path to the file.
+        path: PathBuf,
+        /// The underlying parsing error.
+        #[source]
+        error: toml::de::Error,
+    },
+}
+
+/// The contents of an account policy file.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AccountPolicyFile {
+    allowlist: Option<BTreeSet<PublicKey>>,
+    #[serde(default)]
+    denylist: BTreeSet<PublicKey>,
+}
+
+/// Identifies a version of the policy file, used to detect when it needs reloading.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+struct FileVersion {
+    modified: SystemTime,
+    len: u64,
+}
+
+impl FileVersion {
+    fn read(path: &Path) -> Result<Self, AccountPolicyLoadError> {
+        let metadata_error = |error| AccountPolicyLoadError::Metadata {
+            path: path.to_path_buf(),
+            error,
+        };
+        let metadata = fs::metadata(path).map_err(metadata_error)?;
+        let modified = metadata.modified().map_err(metadata_error)?;
+        Ok(FileVersion {
+            modified,
+            len: metadata.len(),
+        })
+    }
+}
+
+/// The allowlist and denylist of accounts which may submit deploys via this node's RPC server.
+///
+/// An account on the denylist is always rejected, even if it also appears on the allowlist.  If no
+/// allowlist is given, every account not on the denylist is permitted.
+#[derive(Debug)]
+pub(super) struct AccountPolicy {
+    path: PathBuf,
+    version: FileVersion,
+    /// Whether the last check of the file for changes failed, so that failures are only logged as
+    /// they start and stop.
+    check_failed: bool,
+    allowlist: Option<BTreeSet<PublicKey>>,
+    denylist: BTreeSet<PublicKey>,
+}
+
+impl AccountPolicy {
+    /// Loads the policy from the file at `path`.
+    pub(super) fn load(path: PathBuf) -> Result<Self, AccountPolicyLoadError> {
+        let version = FileVersion::read(&path)?;
+        let AccountPolicyFile {
+            allowlist,
+            denylist,
+        } = Self::read(&path)?;
+        let account_policy = AccountPolicy {
+            path,
+            version,
+            check_failed: false,
+            allowlist,
+            denylist,
+        };
+        account_policy.log_loaded("loaded");
+        Ok(account_policy)
+    }
+
+    /// Checks whether `account` may submit deploys.
+    pub(super) fn check(&self, account: &PublicKey) -> Result<(), DeployAcceptorError> {
+        if self.denylist.contains(account) {
+            return Err(DeployAcceptorError::AccountInDenylist(account.clone()));
+        }
+        match &self.allowlist {
+            Some(allowlist) if !allowlist.contains(account) => {
+                Err(DeployAcceptorError::AccountNotInAllowlist(account.clone()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Reloads the policy if the underlying file has changed since it was last read.
+    ///
+    /// If the file can't be read or parsed, the error is logged and the previous policy remains
+    /// active.
+    pub(super) fn reload_if_changed(&mut self) {
+        let version = match FileVersion::read(&self.path) {
+            Ok(version) => version,
+            Err(error) => {
+                if !self.check_failed {
+                    error!(
+                        %error,
+                        "failed to check account policy file; keeping previous policy"
+                    );
+                    self.check_failed = true;
+                }
+                return;
+            }
+        };
+        if self.check_failed {
+            info!(path = %self.path.display(), "account policy file can be checked again");
+            self.check_failed = false;
+        }
+        if version == self.version {
+            return;
+        }
+        // Record the new version even on failure so that a malformed file is only reported once.
+        self.version = version;
+
+        match Self::read(&self.path) {
+            Ok(AccountPolicyFile {
+                allowlist,
+                denylist,
+            }) => {
+                self.allowlist = allowlist;
+                self.denylist = denylist;
+                self.log_loaded("reloaded");
+            }
+            Err(error) => {
+                error!(%error, "failed to reload account policy file; keeping previous policy")
+            }
+        }
+    }
+
+    fn read(path: &Path) -> Result<AccountPolicyFile, AccountPolicyLoadError> {
+        let bytes = utils::read_file(path)?;
+        toml::from_slice(&bytes).map_err(|error| AccountPolicyLoadError::DecodeFromToml {
+            path: path.to_path_buf(),
+            error,
+        })
+    }
+
+    fn log_loaded(&self, action: &str) {
+        match &self.allowlist {
+            Some(allowlist) => info!(
+                path = %self.path.display(),
+                allowlist_entries = allowlist.len(),
+                denylist_entries = self.denylist.len(),
+                "{} account policy",
+                action
+            ),
+            None => info!(
+                path = %self.path.display(),
+                denylist_entries = self.denylist.len(),
+                "{} account policy without allowlist",
+                action
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::Write as _;
+
+    use tempfile::TempDir;
+
+    use casper_types::AsymmetricType;
+
+    use super::*;
+    use crate::crypto::AsymmetricKeyExt;
+
+    fn policy_toml(allowlist: Option<&[&PublicKey]>, denylist: &[&PublicKey]) -> String {
+        let to_hex_list = |keys: &[&PublicKey]| {
+            let entries: Vec<String> = keys
+                .iter()
+                .map(|key| format!("\"{}\"", key.to_hex()))
+                .collect();
+            format!("[{}]", entries.join(", "))
+        };
+        let mut contents = String::new();
+        if let Some(allowlist) = allowlist {
+            writeln!(contents, "allowlist = {}", to_hex_list(allowlist)).unwrap();
+        }
+        writeln!(contents, "denylist = {}", to_hex_list(denylist)).unwrap();
+        contents
+    }
+
+    fn write_policy(path: &Path, allowlist: Option<&[&PublicKey]>, denylist: &[&PublicKey]) {
+        fs::write(path, policy_toml(allowlist, denylist)).unwrap();
+    }
+
+    #[test]
+    fn should_only_accept_allowlisted_accounts() {
+        let mut rng = crate::new_rng();
+        let allowed = PublicKey::random(&mut rng);
+        let other = PublicKey::random(&mut rng);
+
+        let tempdir = TempDir::new().unwrap();
+        let path = tempdir.path().join("account_policy.toml");
+        write_policy(&path, Some(&[&allowed]), &[]);
+
+        let account_policy = AccountPolicy::load(path).unwrap();
+        assert!(account_policy.check(&allowed).is_ok());
+        assert!(matches!(
+            account_policy.check(&other),
+            Err(DeployAcceptorError::AccountNotInAllowlist(account)) if account == other
+        ));
+    }
+
+    #[test]
+    fn should_reject_denylisted_accounts() {
+        let mut rng = crate::new_rng();
+        let denied = PublicKey::random(&mut rng);
+        let other = PublicKey::random(&mut rng);
+
+        let tempdir = TempDir::new().unwrap();
+        let path = tempdir.path().join("account_policy.toml");
+        write_policy(&path, None, &[&denied]);
+
+        let account_policy = AccountPolicy::load(path).unwrap();
+        assert!(account_policy.check(&other).is_ok());
+        assert!(matches!(
+            account_policy.check(&denied),
+            Err(DeployAcceptorError::AccountInDenylist(account)) if account == denied
+        ));
+    }
+
+    #[test]
+    fn denylist_should_take_precedence_over_allowlist() {
+        let mut rng = crate::new_rng();
+        let account = PublicKey::random(&mut rng);
+
+        let tempdir = TempDir::new().unwrap();
+        let path = tempdir.path().join("account_policy.toml");
+        write_policy(&path, Some(&[&account]), &[&account]);
+
+        let account_policy = AccountPolicy::load(path).unwrap();
+        assert!(matches!(
+            account_policy.check(&account),
+            Err(DeployAcceptorError::AccountInDenylist(_))
+        ));
+    }
+
+    #[test]
+    fn should_hot_reload_policy_and_keep_previous_on_malformed_file() {
+        let mut rng = crate::new_rng();
+        let first = PublicKey::random(&mut rng);
+        let second = PublicKey::random(&mut rng);
+
+        let tempdir = TempDir::new().unwrap();
+        let path = tempdir.path().join("account_policy.toml");
+        write_policy(&path, Some(&[&first]), &[]);
+
+        let mut account_policy = AccountPolicy::load(path.clone()).unwrap();
+        assert!(account_policy.check(&first).is_ok());
+        assert!(account_policy.check(&second).is_err());
+
+        // Allow both accounts.  The file's length changes, so the update is detected even if the
+        // filesystem's timestamps are coarse.
+        write_policy(&path, Some(&[&first, &second]), &[]);
+        account_policy.reload_if_changed();
+        assert!(account_policy.check(&first).is_ok());
+        assert!(account_policy.check(&second).is_ok());
+
+        // A malformed file should leave the previous policy in place.
+        fs::write(&path, "allowlist = [\"not a public key\"]").unwrap();
+        account_policy.reload_if_changed();
+        assert!(account_policy.check(&first).is_ok());
+        assert!(account_policy.check(&second).is_ok());
+
+        // Once fixed, the new policy should take effect.
+        write_policy(&path, None, &[&first]);
+        account_policy.reload_if_changed();
+        assert!(account_policy.check(&first).is_err());
+        assert!(account_policy.check(&second).is_ok());
+    }
+
+    #[test]
+    fn should_keep_previous_policy_while_file_is_missing() {
+        let mut rng = crate::new_rng();
+        let first = PublicKey::random(&mut rng);
+        let second = PublicKey::random(&mut rng);
+
+        let tempdir = TempDir::new().unwrap();
+        let path = tempdir.path().join("account_policy.toml");
+        write_policy(&path, Some(&[&first]), &[]);
+        let mut account_policy = AccountPolicy::load(path.clone()).unwrap();
+
+        // The failure to check the file is only recorded once, however often it is checked.
+        fs::remove_file(&path).unwrap();
+        account_policy.reload_if_changed();
+        assert!(account_policy.check_failed);
+        account_policy.reload_if_changed();
+        assert!(account_policy.check_failed);
+        assert!(account_policy.check(&first).is_ok());
+        assert!(account_policy.check(&second).is_err());
+
+        // Once the file is restored, it should be reloaded.
+        write_policy(&path, Some(&[&first, &second]), &[]);
+        account_policy.reload_if_changed();
+        assert!(!account_policy.check_failed);
+        assert!(account_policy.check(&second).is_ok());
+    }
+
+    #[test]
+    fn should_fail_to_load_malformed_file() {
+        let tempdir = TempDir::new().unwrap();
+        let path = tempdir.path().join("account_policy.toml");
+        fs::write(&path, "denylist = 3").unwrap();
+
+        assert!(matches!(
+            AccountPolicy::load(path),
+            Err(AccountPolicyLoadError::DecodeFromToml { .. })
+        ));
+    }
+}
@@ -0,0 +1,277 @@
+//! A bounded cache of deploy approvals whose signatures have already been verified.
+//!
+//! During gossip the same deploy arrives from many peers, and each copy would otherwise have all
+//! its approvals re-verified before it is found to be a duplicate.
+
+use std::fmt::Debug;
+
+use linked_hash_map::LinkedHashMap;
+
+use casper_types::crypto;
+
+use super::metrics::DeployAcceptorMetrics;
+use crate::types::{Approval, DeployHash};
+
+/// Verifies an approval's signature over a deploy hash.
+pub(super) trait ApprovalVerifier: Debug + Send {
+    fn verify(&self, deploy_hash: &DeployHash, approval: &Approval) -> Result<(), crypto::Error>;
+}
+
+/// An `ApprovalVerifier` which checks the approval's signature.
+#[derive(Debug)]
+pub(super) struct SignatureVerifier;
+
+impl ApprovalVerifier for SignatureVerifier {
+    fn verify(&self, deploy_hash: &DeployHash, approval: &Approval) -> Result<(), crypto::Error> {
+        approval.verify(deploy_hash)
+    }
+}
+
+/// A cache of approvals known to be valid signatures over given deploy hashes.
+///
+/// Entries are keyed on the deploy hash along with the whole approval, i.e. both the signer and
+/// the signature, so a cached entry only vouches for that exact signature.  Once full, the least
+/// recently used entry is evicted to make room for a new one.
+#[derive(Debug)]
+pub(super) struct ApprovalsCache {
+    capacity: usize,
+    verified: LinkedHashMap<(DeployHash, Approval), ()>,
+    verifier: Box<dyn ApprovalVerifier>,
+    metrics: DeployAcceptorMetrics,
+}
+
+impl ApprovalsCache {
+    /// Constructs a new cache holding at most `capacity` entries.  A capacity of `0` disables
+    /// caching.
+    pub(super) fn new(
+        capacity: usize,
+        verifier: Box<dyn ApprovalVerifier>,
+        metrics: DeployAcceptorMetrics,
+    ) -> Self {
+        ApprovalsCache {
+            capacity,
+            verified: LinkedHashMap::new(),
+            verifier,
+            metrics,
+        }
+    }
+
+    /// Returns the deploy acceptor's metrics, which are held by the cache.
+    pub(super) fn metrics(&self) -> &DeployAcceptorMetrics {
+        &self.metrics
+    }
+
+    /// Verifies the approval over the given deploy hash, unless it has been verified already.
+    ///
+    /// Only successful verifications are cached.  Every approval found to be valid, whether cached
+    /// or not, is counted in the metrics by its signature algorithm.
+    pub(super) fn verify(
+        &mut self,
+        deploy_hash: &DeployHash,
+        approval: &Approval,
+    ) -> Result<(), crypto::Error> {
+        let key = (*deploy_hash, approval.clone());
+        if self.verified.get_refresh(&key).is_some() {
+            self.metrics.approvals_cache_hits.inc();
+            self.metrics.record_approval_verified(approval.signer());
+            return Ok(());
+        }
+        self.metrics.approvals_cache_misses.inc();
+
+        self.verifier.verify(deploy_hash, approval)?;
+        self.metrics.record_approval_verified(approval.signer());
+
+        if self.capacity > 0 {
+            if self.verified.len() >= self.capacity {
+                let _ = self.verified.pop_front();
+            }
+            let _ = self.verified.insert(key, ());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use prometheus::Registry;
+
+    use casper_types::{
+        bytesrepr::{self, ToBytes},
+        PublicKey, SecretKey,
+    };
+
+    use super::*;
+    use crate::{crypto::AsymmetricKeyExt, testing::TestRng, types::Deploy};
+
+    /// An `ApprovalVerifier` which counts the verifications it performs.
+    #[derive(Clone, Debug, Default)]
+    struct CountingVerifier(Arc<AtomicUsize>);
+
+    impl CountingVerifier {
+        fn count(&self) -> usize {
+            self.0.load(Ordering::SeqCst)
+        }
+    }
+
+    impl ApprovalVerifier for CountingVerifier {
+        fn verify(
+            &self,
+            deploy_hash: &DeployHash,
+            approval: &Approval,
+        ) -> Result<(), crypto::Error> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            approval.verify(deploy_hash)
+        }
+    }
+
+    fn new_cache(capacity: usize, registry: &Registry) -> (ApprovalsCache, CountingVerifier) {
+        let verifier = CountingVerifier::default();
+        let metrics = DeployAcceptorMetrics::new(registry).unwrap();
+        let cache = ApprovalsCache::new(capacity, Box::new(verifier.clone()), metrics);
+        (cache, verifier)
+    }
+
+    #[test]
+    fn should_not_repeat_verification() {
+        let mut rng = TestRng::new();
+        let registry = Registry::new();
+        let (mut cache, verifier) = new_cache(10, &registry);
+        let deploy = Deploy::random(&mut rng);
+        let approval = &deploy.approvals()[0];
+
+        for _ in 0..3 {
+            assert!(cache.verify(deploy.id(), approval).is_ok());
+        }
+        assert_eq!(verifier.count(), 1);
+        assert_eq!(cache.metrics.approvals_cache_hits.get(), 2);
+        assert_eq!(cache.metrics.approvals_cache_misses.get(), 1);
+    }
+
+    #[test]
+    fn should_miss_for_different_signature_over_same_hash() {
+        let mut rng = TestRng::new();
+        let registry = Registry::new();
+        let (mut cache, verifier) = new_cache(10, &registry);
+        let deploy = Deploy::random(&mut rng);
+        assert!(cache.verify(deploy.id(), &deploy.approvals()[0]).is_ok());
+
+        // A valid approval by another signer.
+        let mut other_deploy = deploy.clone();
+        other_deploy.sign(&SecretKey::random(&mut rng));
+        let other_approval = other_deploy
+            .approvals()
+            .iter()
+            .find(|approval| **approval != deploy.approvals()[0])
+            .unwrap();
+        assert!(cache.verify(deploy.id(), other_approval).is_ok());
+        assert_eq!(verifier.count(), 2);
+
+        // The cached signer with a signature over a different hash.
+        let forged_approval: Approval = bytesrepr::deserialize(
+            [
+                deploy.approvals()[0].signer().to_bytes().unwrap(),
+                Deploy::random(&mut rng).approvals()[0]
+                    .signature()
+                    .to_bytes()
+                    .unwrap(),
+            ]
+            .concat(),
+        )
+        .unwrap();
+        assert!(cache.verify(deploy.id(), &forged_approval).is_err());
+        assert_eq!(verifier.count(), 3);
+
+        // The failed verification isn't cached.
+        assert!(cache.verify(deploy.id(), &forged_approval).is_err());
+        assert_eq!(verifier.count(), 4);
+        assert_eq!(cache.metrics.approvals_cache_hits.get(), 0);
+    }
+
+    #[test]
+    fn should_miss_for_same_approval_over_different_hash() {
+        let mut rng = TestRng::new();
+        let registry = Registry::new();
+        let (mut cache, verifier) = new_cache(10, &registry);
+        let deploy = Deploy::random(&mut rng);
+        let approval = &deploy.approvals()[0];
+        assert!(cache.verify(deploy.id(), approval).is_ok());
+
+        let other_deploy = Deploy::random(&mut rng);
+        assert!(cache.verify(other_deploy.id(), approval).is_err());
+        assert_eq!(verifier.count(), 2);
+    }
+
+    #[test]
+    fn should_count_verified_approvals_by_algorithm() {
+        let mut rng = TestRng::new();
+        let registry = Registry::new();
+        let (mut cache, _) = new_cache(10, &registry);
+        let ed25519_key = SecretKey::random_ed25519(&mut rng);
+        let secp256k1_key = SecretKey::random_secp256k1(&mut rng);
+        let mut deploy = Deploy::random(&mut rng);
+        deploy.sign(&ed25519_key);
+        deploy.sign(&secp256k1_key);
+        let approval_by = |secret_key: &SecretKey| {
+            let signer = PublicKey::from(secret_key);
+            deploy
+                .approvals()
+                .iter()
+                .find(|approval| *approval.signer() == signer)
+                .cloned()
+                .unwrap()
+        };
+        let ed25519_approval = approval_by(&ed25519_key);
+        let secp256k1_approval = approval_by(&secp256k1_key);
+
+        // Approvals found in the cache are counted as well as those verified afresh...
+        assert!(cache.verify(deploy.id(), &ed25519_approval).is_ok());
+        assert!(cache.verify(deploy.id(), &ed25519_approval).is_ok());
+        assert!(cache.verify(deploy.id(), &secp256k1_approval).is_ok());
+
+        // ...but invalid ones aren't.
+        let other_deploy = Deploy::random(&mut rng);
+        assert!(cache
+            .verify(other_deploy.id(), &secp256k1_approval)
+            .is_err());
+
+        let verified = |algorithm: &str| {
+            cache
+                .metrics
+                .approvals_verified
+                .with_label_values(&[algorithm])
+                .get()
+        };
+        assert_eq!(verified("ed25519"), 2);
+        assert_eq!(verified("secp256k1"), 1);
+        assert_eq!(verified("system"), 0);
+    }
+
+    #[test]
+    fn should_evict_least_recently_used() {
+        let mut rng = TestRng::new();
+        let registry = Registry::new();
+        let (mut cache, verifier) = new_cache(2, &registry);
+        let deploys: Vec<_> = (0..3).map(|_| Deploy::random(&mut rng)).collect();
+        let verify = |cache: &mut ApprovalsCache, index: usize| {
+            let deploy: &Deploy = &deploys[index];
+            assert!(cache.verify(deploy.id(), &deploy.approvals()[0]).is_ok());
+        };
+
+        verify(&mut cache, 0);
+        verify(&mut cache, 1);
+        // Refresh the first entry, so that adding a third evicts the second.
+        verify(&mut cache, 0);
+        verify(&mut cache, 2);
+        assert_eq!(verifier.count(), 3);
+
+        verify(&mut cache, 0);
+        assert_eq!(verifier.count(), 3);
+        verify(&mut cache, 1);
+        assert_eq!(verifier.count(), 4);
+    }
+}
@@ -1,28 +1,73 @@
+use std::{path::PathBuf, str::FromStr};
+
 use datasize::DataSize;
 use serde::{Deserialize, Serialize};
 
+use crate::types::TimeDiff;
+
+const DEFAULT_VERIFIED_APPROVALS_CACHE_SIZE: usize = 10_000;
+const DEFAULT_UPGRADE_DRAIN_WINDOW: &str = "0s";
+
 /// Configuration options for fetching.
-#[derive(Copy, Clone, DataSize, Debug, Deserialize, Serialize)]
+#[derive(Clone, DataSize, Debug, Deserialize, Serialize)]
 pub struct Config {
     verify_accounts: bool,
+    /// Path to a file listing the accounts allowed or denied to submit deploys via this node's
+    /// RPC server.  Relative paths are resolved against the config file's directory.
+    account_policy_path: Option<PathBuf>,
+    /// The maximum number of verified deploy approvals to cache, so that approvals of a deploy
+    /// received repeatedly aren't re-verified each time.  `0` disables the cache.
+    verified_approvals_cache_size: usize,
+    /// The period before the next upgrade's estimated activation during which no new deploys are
+    /// accepted, so that those already accepted can be proposed.  `0s` disables this.
+    upgrade_drain_window: TimeDiff,
 }
 
 impl Config {
     /// Constructor for deploy_acceptor config.
     pub fn new(verify_accounts: bool) -> Self {
-        Config { verify_accounts }
+        Config {
+            verify_accounts,
+            account_policy_path: None,
+            verified_approvals_cache_size: DEFAULT_VERIFIED_APPROVALS_CACHE_SIZE,
+            upgrade_drain_window: TimeDiff::from_str(DEFAULT_UPGRADE_DRAIN_WINDOW).unwrap(),
+        }
     }
 
     /// Get verify_accounts setting.
     pub(crate) fn verify_accounts(&self) -> bool {
         self.verify_accounts
     }
+
+    /// Get account_policy_path setting.
+    pub(crate) fn account_policy_path(&self) -> Option<&PathBuf> {
+        self.account_policy_path.as_ref()
+    }
+
+    /// Get verified_approvals_cache_size setting.
+    pub(crate) fn verified_approvals_cache_size(&self) -> usize {
+        self.verified_approvals_cache_size
+    }
+
+    /// Get upgrade_drain_window setting.
+    pub(crate) fn upgrade_drain_window(&self) -> TimeDiff {
+        self.upgrade_drain_window
+    }
+
+    /// Sets the upgrade_drain_window setting.
+    #[cfg(test)]
+    pub(crate) fn set_upgrade_drain_window(&mut self, upgrade_drain_window: TimeDiff) {
+        self.upgrade_drain_window = upgrade_drain_window;
+    }
 }
 
 impl Default for Config {
     fn default() -> Self {
         Config {
             verify_accounts: true,
+            account_policy_path: None,
+            verified_approvals_cache_size: DEFAULT_VERIFIED_APPROVALS_CACHE_SIZE,
+            upgrade_drain_window: TimeDiff::from_str(DEFAULT_UPGRADE_DRAIN_WINDOW).unwrap(),
         }
     }
 }
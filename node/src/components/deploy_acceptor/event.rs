@@ -6,7 +6,10 @@ use super::Source;
 use crate::{
     components::deploy_acceptor::Error,
     effect::{announcements::RpcServerAnnouncement, Responder},
-    types::{Deploy, NodeId},
+    types::{
+        chainspec::{ActivationPoint, DeployConfig},
+        BlockHeader, Deploy, DeployHash, NodeId, SharedObject, Summary,
+    },
 };
 use casper_types::Key;
 
@@ -19,6 +22,11 @@ pub enum Event {
         source: Source<NodeId>,
         responder: Option<Responder<Result<(), Error>>>,
     },
+    /// A serialized `Deploy` received from a peer, which is yet to be decoded.
+    ReceivedFromPeer {
+        serialized_deploy: SharedObject<Vec<u8>>,
+        sender: NodeId,
+    },
     /// The result of the `DeployAcceptor` putting a `Deploy` to the storage component.
     PutToStorageResult {
         deploy: Box<Deploy>,
@@ -34,6 +42,16 @@ pub enum Event {
         verified: Option<bool>,
         maybe_responder: Option<Responder<Result<(), Error>>>,
     },
+    /// An upgrade has been committed: deploys are henceforth checked against its deploy config.
+    DeployConfigActivated(DeployConfig),
+    /// The activation point of the next upgrade has been read.
+    GotUpgradeActivationPoint(ActivationPoint),
+    /// A block has been added to the linear chain.
+    BlockAdded(Box<BlockHeader>),
+    /// The given deploys have been included in a finalized block.
+    DeploysFinalized(Vec<DeployHash>),
+    /// The account policy file should be checked for changes.
+    CheckAccountPolicy,
 }
 
 impl From<RpcServerAnnouncement> for Event {
@@ -52,13 +70,22 @@ impl Display for Event {
     fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
         match self {
             Event::Accept { deploy, source, .. } => {
-                write!(formatter, "accept {} from {}", deploy.id(), source)
+                write!(formatter, "accept {} from {}", deploy.summary(), source)
             }
+            Event::ReceivedFromPeer {
+                serialized_deploy,
+                sender,
+            } => write!(
+                formatter,
+                "received serialized deploy of {} bytes from {}",
+                serialized_deploy.len(),
+                sender
+            ),
             Event::PutToStorageResult { deploy, is_new, .. } => {
                 if *is_new {
-                    write!(formatter, "put new {} to storage", deploy.id())
+                    write!(formatter, "put new {} to storage", deploy.summary())
                 } else {
-                    write!(formatter, "had already stored {}", deploy.id())
+                    write!(formatter, "had already stored {}", deploy.summary())
                 }
             }
             Event::AccountVerificationResult {
@@ -70,12 +97,30 @@ impl Display for Event {
                 let prefix = if verified.unwrap_or(false) { "" } else { "in" };
                 write!(
                     formatter,
-                    "{}valid deploy {} from account {}",
+                    "{}valid {} from account {}",
                     prefix,
-                    deploy.id(),
+                    deploy.summary(),
                     account_key
                 )
             }
+            Event::DeployConfigActivated(deploy_config) => write!(
+                formatter,
+                "activate deploy config with max deploy size {}",
+                deploy_config.max_deploy_size
+            ),
+            Event::GotUpgradeActivationPoint(activation_point) => {
+                write!(formatter, "new upgrade {}", activation_point)
+            }
+            Event::BlockAdded(block_header) => write!(
+                formatter,
+                "block added at height {} in {}",
+                block_header.height(),
+                block_header.era_id()
+            ),
+            Event::DeploysFinalized(deploy_hashes) => {
+                write!(formatter, "{} deploys finalized", deploy_hashes.len())
+            }
+            Event::CheckAccountPolicy => write!(formatter, "check account policy"),
         }
     }
 }
@@ -0,0 +1,82 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::types::{DeployHash, Timestamp};
+
+/// The deploys known to have been included in a finalized block, each retained until it must have
+/// expired.
+///
+/// Copies of these received from peers are late gossip, and needn't be accepted again.
+#[derive(Debug, Default)]
+pub(super) struct FinalizedDeploys {
+    /// The time until which each deploy is retained.
+    expiries: HashMap<DeployHash, Timestamp>,
+    /// The deploys in the order they were finalized, along with the time until which each was
+    /// retained at that point.
+    queue: VecDeque<(Timestamp, DeployHash)>,
+}
+
+impl FinalizedDeploys {
+    /// Records that `deploy_hash` was finalized, retaining it until `retain_until`.
+    pub(super) fn insert(&mut self, deploy_hash: DeployHash, retain_until: Timestamp) {
+        let expiry = self.expiries.entry(deploy_hash).or_insert(retain_until);
+        *expiry = (*expiry).max(retain_until);
+        self.queue.push_back((retain_until, deploy_hash));
+    }
+
+    /// Returns whether `deploy_hash` is known to have been finalized, purging any entries no
+    /// longer retained at `now`.
+    pub(super) fn contains(&mut self, deploy_hash: &DeployHash, now: Timestamp) -> bool {
+        self.purge(now);
+        self.expiries.contains_key(deploy_hash)
+    }
+
+    /// Returns the number of deploys currently retained.
+    pub(super) fn len(&self) -> usize {
+        self.expiries.len()
+    }
+
+    fn purge(&mut self, now: Timestamp) {
+        while let Some((retain_until, deploy_hash)) = self.queue.front().copied() {
+            if retain_until > now {
+                break;
+            }
+            let _ = self.queue.pop_front();
+            // The deploy may have been finalized again since, and be retained for longer.
+            if self.expiries.get(&deploy_hash) == Some(&retain_until) {
+                let _ = self.expiries.remove(&deploy_hash);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{testing::TestRng, types::TimeDiff};
+
+    #[test]
+    fn should_retain_finalized_deploys_until_expired() {
+        let mut rng = TestRng::new();
+        let deploy_hash_1 = DeployHash::random(&mut rng);
+        let deploy_hash_2 = DeployHash::random(&mut rng);
+        let now = Timestamp::from(1_000);
+        let ttl = TimeDiff::from(100);
+
+        let mut finalized_deploys = FinalizedDeploys::default();
+        finalized_deploys.insert(deploy_hash_1, now + ttl);
+        finalized_deploys.insert(deploy_hash_2, now + ttl + TimeDiff::from(10));
+        assert!(finalized_deploys.contains(&deploy_hash_1, now));
+        assert!(finalized_deploys.contains(&deploy_hash_2, now));
+
+        // The first deploy is no longer retained once its TTL has elapsed.
+        assert!(!finalized_deploys.contains(&deploy_hash_1, now + ttl));
+        assert!(finalized_deploys.contains(&deploy_hash_2, now + ttl));
+        assert_eq!(finalized_deploys.len(), 1);
+
+        // Finalizing the second deploy again retains it for longer.
+        finalized_deploys.insert(deploy_hash_2, now + ttl * 2);
+        assert!(finalized_deploys.contains(&deploy_hash_2, now + ttl + TimeDiff::from(10)));
+        assert!(!finalized_deploys.contains(&deploy_hash_2, now + ttl * 2));
+        assert_eq!(finalized_deploys.len(), 0);
+    }
+}
@@ -0,0 +1,83 @@
+use prometheus::{IntCounter, IntCounterVec, Opts, Registry};
+
+use casper_types::PublicKey;
+
+use crate::{types::chainspec::ApprovalAlgorithm, unregister_metric};
+
+#[derive(Debug)]
+pub(super) struct DeployAcceptorMetrics {
+    /// Number of approvals found in the cache of verified approvals.
+    pub(super) approvals_cache_hits: IntCounter,
+    /// Number of approvals not found in the cache of verified approvals, and hence verified.
+    pub(super) approvals_cache_misses: IntCounter,
+    /// Number of approvals successfully verified, by the signature algorithm of the signer.
+    pub(super) approvals_verified: IntCounterVec,
+    /// Number of deploys from peers dropped as they can't execute before the next upgrade.
+    pub(super) deploys_dropped_near_upgrade: IntCounter,
+    /// Number of deploys from peers ignored as they had already been finalized.
+    pub(super) finalized_deploys_ignored: IntCounter,
+    /// Reference to the registry for unregistering.
+    registry: Registry,
+}
+
+impl DeployAcceptorMetrics {
+    pub(super) fn new(registry: &Registry) -> Result<Self, prometheus::Error> {
+        let approvals_cache_hits = IntCounter::new(
+            "deploy_acceptor_approvals_cache_hits",
+            "number of deploy approvals found in the cache of verified approvals",
+        )?;
+        let approvals_cache_misses = IntCounter::new(
+            "deploy_acceptor_approvals_cache_misses",
+            "number of deploy approvals not found in the cache of verified approvals",
+        )?;
+        let approvals_verified = IntCounterVec::new(
+            Opts::new(
+                "deploy_acceptor_approvals_verified",
+                "number of deploy approvals successfully verified, by signature algorithm",
+            ),
+            &["algorithm"],
+        )?;
+        let deploys_dropped_near_upgrade = IntCounter::new(
+            "deploy_acceptor_deploys_dropped_near_upgrade",
+            "number of deploys received from peers dropped as they can't execute before the next \
+             upgrade",
+        )?;
+        let finalized_deploys_ignored = IntCounter::new(
+            "deploy_acceptor_finalized_deploys_ignored",
+            "number of deploys received from peers ignored as they had already been finalized",
+        )?;
+        registry.register(Box::new(approvals_cache_hits.clone()))?;
+        registry.register(Box::new(approvals_cache_misses.clone()))?;
+        registry.register(Box::new(approvals_verified.clone()))?;
+        registry.register(Box::new(deploys_dropped_near_upgrade.clone()))?;
+        registry.register(Box::new(finalized_deploys_ignored.clone()))?;
+
+        Ok(DeployAcceptorMetrics {
+            approvals_cache_hits,
+            approvals_cache_misses,
+            approvals_verified,
+            deploys_dropped_near_upgrade,
+            finalized_deploys_ignored,
+            registry: registry.clone(),
+        })
+    }
+
+    /// Records an approval by `signer` having been successfully verified.
+    pub(super) fn record_approval_verified(&self, signer: &PublicKey) {
+        let algorithm =
+            ApprovalAlgorithm::of(signer).map_or("system", |algorithm| algorithm.name());
+        self.approvals_verified
+            .with_label_values(&[algorithm])
+            .inc();
+    }
+}
+
+impl Drop for DeployAcceptorMetrics {
+    fn drop(&mut self) {
+        unregister_metric!(self.registry, self.approvals_cache_hits);
+        unregister_metric!(self.registry, self.approvals_cache_misses);
+        unregister_metric!(self.registry, self.approvals_verified);
+        unregister_metric!(self.registry, self.deploys_dropped_near_upgrade);
+        unregister_metric!(self.registry, self.finalized_deploys_ignored);
+    }
+}
@@ -0,0 +1,141 @@
+use casper_types::EraId;
+
+use crate::types::{chainspec::ActivationPoint, BlockHeader, TimeDiff, Timestamp};
+
+/// Tracks the next upgrade's activation point, estimating the time at which it will be reached.
+#[derive(Debug)]
+pub(super) struct UpgradeBoundary {
+    /// The chainspec's target duration of an era.
+    era_duration: TimeDiff,
+    /// The activation point of the next upgrade, if one is scheduled.
+    activation_point: Option<ActivationPoint>,
+    /// The latest era known to have started, and the earliest time at which it did so.
+    current_era: Option<(EraId, Timestamp)>,
+}
+
+impl UpgradeBoundary {
+    pub(super) fn new(
+        era_duration: TimeDiff,
+        activation_point: Option<ActivationPoint>,
+        maybe_latest_block_header: Option<&BlockHeader>,
+    ) -> Self {
+        let mut upgrade_boundary = UpgradeBoundary {
+            era_duration,
+            activation_point,
+            current_era: None,
+        };
+        if let Some(block_header) = maybe_latest_block_header {
+            upgrade_boundary.block_added(block_header);
+        }
+        upgrade_boundary
+    }
+
+    pub(super) fn activation_point(&self) -> Option<ActivationPoint> {
+        self.activation_point
+    }
+
+    pub(super) fn set_activation_point(&mut self, activation_point: ActivationPoint) {
+        self.activation_point = Some(activation_point);
+    }
+
+    /// Updates the current era from a block added to the linear chain.
+    ///
+    /// A switch block's timestamp is the start of the following era.  Any other block only shows
+    /// that its era had started by the block's timestamp, which is used until the era's actual
+    /// start is known, erring on the side of estimating the upgrade later than it will happen.
+    pub(super) fn block_added(&mut self, block_header: &BlockHeader) {
+        if block_header.is_switch_block() {
+            self.era_started(block_header.era_id().successor(), block_header.timestamp());
+        } else {
+            self.era_started(block_header.era_id(), block_header.timestamp());
+        }
+    }
+
+    /// Records that `era_id` started no later than `start`, unless a later era is already known.
+    pub(super) fn era_started(&mut self, era_id: EraId, start: Timestamp) {
+        match self.current_era {
+            Some((current_era_id, _)) if current_era_id >= era_id => (),
+            _ => self.current_era = Some((era_id, start)),
+        }
+    }
+
+    /// Returns the estimated time at which the next upgrade activates.
+    ///
+    /// Returns `None` if no upgrade is scheduled, if it has already activated, or if the time can't
+    /// be estimated.  An estimate which is already past is treated as unknown, since the upgrade is
+    /// then overdue rather than known to be about to happen.
+    pub(super) fn estimate(&self, now: Timestamp) -> Option<Timestamp> {
+        let boundary = match self.activation_point? {
+            ActivationPoint::Genesis(timestamp) => timestamp,
+            ActivationPoint::EraId(activation_era_id) => {
+                let (current_era_id, era_start) = self.current_era?;
+                if activation_era_id <= current_era_id {
+                    return None;
+                }
+                let remaining_eras = activation_era_id.value() - current_era_id.value();
+                era_start + self.era_duration.saturating_mul(remaining_eras)
+            }
+        };
+        if boundary <= now {
+            return None;
+        }
+        Some(boundary)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ERA_DURATION: u64 = 60_000;
+
+    #[test]
+    fn should_estimate_boundary_from_current_era_start() {
+        let mut upgrade_boundary = UpgradeBoundary::new(
+            TimeDiff::from(ERA_DURATION),
+            Some(ActivationPoint::EraId(EraId::from(5))),
+            None,
+        );
+        let now = Timestamp::from(1_000_000);
+
+        // The current era is needed to estimate the boundary.
+        assert_eq!(upgrade_boundary.estimate(now), None);
+
+        // Era 3 started at `now`, so era 5 is two eras' durations away.
+        upgrade_boundary.era_started(EraId::from(3), now);
+        assert_eq!(
+            upgrade_boundary.estimate(now),
+            Some(now + TimeDiff::from(2 * ERA_DURATION))
+        );
+
+        // A later block of an earlier era is ignored.
+        upgrade_boundary.era_started(EraId::from(2), now + TimeDiff::from(1_000));
+        assert_eq!(
+            upgrade_boundary.estimate(now),
+            Some(now + TimeDiff::from(2 * ERA_DURATION))
+        );
+
+        // Once the activation era has started, the upgrade no longer lies ahead.
+        upgrade_boundary.era_started(EraId::from(5), now);
+        assert_eq!(upgrade_boundary.estimate(now), None);
+    }
+
+    #[test]
+    fn should_not_estimate_boundary_already_past() {
+        let start = Timestamp::from(1_000_000);
+        let mut upgrade_boundary = UpgradeBoundary::new(
+            TimeDiff::from(ERA_DURATION),
+            Some(ActivationPoint::EraId(EraId::from(4))),
+            None,
+        );
+        upgrade_boundary.era_started(EraId::from(3), start);
+
+        let boundary = start + TimeDiff::from(ERA_DURATION);
+        assert_eq!(upgrade_boundary.estimate(start), Some(boundary));
+        assert_eq!(upgrade_boundary.estimate(boundary), None);
+
+        upgrade_boundary.set_activation_point(ActivationPoint::Genesis(boundary));
+        assert_eq!(upgrade_boundary.estimate(start), Some(boundary));
+        assert_eq!(upgrade_boundary.estimate(boundary), None);
+    }
+}
@@ -0,0 +1,314 @@
+//! Deploy propagation measurement.
+//!
+//! When enabled, every node records the time at which it first sees each deploy, whether received
+//! from a client or from a peer.  Periodically, a node asks a random sample of its peers when they
+//! first saw a random sample of the deploys it has recently seen, and aggregates their responses
+//! into the spread of each deploy through the network.  The spreads are exported as histogram
+//! metrics and served via the REST server.
+//!
+//! The times are read from each node's own clock, so the reported deltas include the clock skew
+//! between nodes and are left to operators to interpret.  Nothing else depends on these
+//! measurements.
+
+mod config;
+mod event;
+mod message;
+mod metrics;
+#[cfg(test)]
+mod tests;
+
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    time::Duration,
+};
+
+use datasize::DataSize;
+use prometheus::Registry;
+use rand::seq::{index, IteratorRandom};
+use tracing::debug;
+
+use crate::{
+    components::Component,
+    effect::{
+        requests::{DeployPropagationRequest, NetworkInfoRequest, NetworkRequest},
+        EffectBuilder, EffectExt, Effects,
+    },
+    types::{DeployHash, DeploySpread, NodeId, Timestamp},
+    NodeRng,
+};
+pub use config::Config;
+pub use event::Event;
+pub use message::Message;
+use metrics::DeployPropagationMetrics;
+
+/// The maximum number of deploys probed for in a single message, keeping probes and their
+/// responses small.
+pub(crate) const MAX_PROBED_DEPLOYS: usize = 100;
+
+/// The maximum number of spreads of the most recently probed deploys which are kept.
+const MAX_RETAINED_SPREADS: usize = 100;
+
+/// A trait constraining the reactor events the deploy propagation component can produce.
+pub(crate) trait ReactorEventT:
+    From<NetworkRequest<NodeId, Message>> + From<NetworkInfoRequest<NodeId>> + Send + 'static
+{
+}
+
+impl<REv> ReactorEventT for REv where
+    REv: From<NetworkRequest<NodeId, Message>> + From<NetworkInfoRequest<NodeId>> + Send + 'static
+{
+}
+
+/// A round of probes sent to peers.
+#[derive(DataSize, Debug)]
+struct ProbeRound {
+    /// The times at which we first saw the probed deploys.
+    first_seen: HashMap<DeployHash, Timestamp>,
+    /// The number of peers probed.
+    peers_probed: u32,
+    /// The probed peers which are yet to respond.
+    awaiting_response: HashSet<NodeId>,
+    /// The times reported by the responding peers for each probed deploy.
+    reports: HashMap<DeployHash, Vec<Option<Timestamp>>>,
+}
+
+/// The deploy propagation component.
+#[derive(DataSize, Debug)]
+pub(crate) struct DeployPropagation {
+    config: Config,
+    /// The times at which we first saw the most recently seen deploys.
+    first_seen: HashMap<DeployHash, Timestamp>,
+    /// The deploys in `first_seen`, in the order they were first seen.
+    seen_order: VecDeque<DeployHash>,
+    /// The deploys in `first_seen` which haven't been probed for, in the order they were first
+    /// seen.
+    unprobed: VecDeque<DeployHash>,
+    /// The current round of probes, if any.
+    current_round: Option<ProbeRound>,
+    /// The spreads of the most recently probed deploys.
+    spreads: VecDeque<DeploySpread>,
+    #[data_size(skip)]
+    metrics: DeployPropagationMetrics,
+}
+
+impl DeployPropagation {
+    /// Creates a new deploy propagation component, scheduling its first round of probes if
+    /// enabled.
+    pub(crate) fn new<REv: ReactorEventT>(
+        config: Config,
+        registry: &Registry,
+        effect_builder: EffectBuilder<REv>,
+    ) -> Result<(Self, Effects<Event>), prometheus::Error> {
+        let deploy_propagation = DeployPropagation {
+            config,
+            first_seen: HashMap::new(),
+            seen_order: VecDeque::new(),
+            unprobed: VecDeque::new(),
+            current_round: None,
+            spreads: VecDeque::new(),
+            metrics: DeployPropagationMetrics::new(registry)?,
+        };
+        let effects = if deploy_propagation.config.enabled() {
+            deploy_propagation.schedule_probe(effect_builder)
+        } else {
+            Effects::new()
+        };
+        Ok((deploy_propagation, effects))
+    }
+
+    fn schedule_probe<REv: ReactorEventT>(
+        &self,
+        effect_builder: EffectBuilder<REv>,
+    ) -> Effects<Event> {
+        effect_builder
+            .set_timeout(Duration::from(self.config.probe_interval()))
+            .event(|_| Event::Probe)
+    }
+
+    /// Records the time at which the deploy was first seen, if it hasn't been seen before.
+    fn record_first_seen(&mut self, deploy_hash: DeployHash) {
+        if self.first_seen.contains_key(&deploy_hash) {
+            return;
+        }
+        let _ = self.first_seen.insert(deploy_hash, Timestamp::now());
+        self.seen_order.push_back(deploy_hash);
+        self.unprobed.push_back(deploy_hash);
+
+        while self.seen_order.len() > self.config.max_tracked_deploys() {
+            if let Some(evicted) = self.seen_order.pop_front() {
+                let _ = self.first_seen.remove(&evicted);
+                // Both queues are in the order the deploys were first seen, so if the evicted
+                // deploy hasn't been probed for, it's at the front of `unprobed`.
+                if self.unprobed.front() == Some(&evicted) {
+                    let _ = self.unprobed.pop_front();
+                }
+            }
+        }
+    }
+
+    /// Concludes the current round of probes, and starts a new one probing a random sample of the
+    /// given peers for a random sample of the deploys not yet probed for.
+    fn start_round<REv: ReactorEventT>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        rng: &mut NodeRng,
+        peers: Vec<NodeId>,
+    ) -> Effects<Event> {
+        self.conclude_round();
+        if peers.is_empty() || self.unprobed.is_empty() {
+            return Effects::new();
+        }
+
+        let deploy_count = self
+            .config
+            .deploys_per_probe()
+            .min(MAX_PROBED_DEPLOYS)
+            .min(self.unprobed.len());
+        let chosen: HashSet<usize> = index::sample(rng, self.unprobed.len(), deploy_count)
+            .into_iter()
+            .collect();
+        let mut first_seen = HashMap::new();
+        let mut position = 0;
+        let tracked = &self.first_seen;
+        self.unprobed.retain(|deploy_hash| {
+            let is_chosen = chosen.contains(&position);
+            position += 1;
+            if is_chosen {
+                let _ = first_seen.insert(*deploy_hash, tracked[deploy_hash]);
+            }
+            !is_chosen
+        });
+        let deploy_hashes: Vec<DeployHash> = first_seen.keys().copied().collect();
+
+        let probed_peers = peers
+            .into_iter()
+            .choose_multiple(rng, self.config.peers_per_probe());
+        debug!(
+            deploy_count = deploy_hashes.len(),
+            peer_count = probed_peers.len(),
+            "probing peers for deploy propagation"
+        );
+        let mut effects = Effects::new();
+        for peer in &probed_peers {
+            let message = Message::Probe {
+                deploy_hashes: deploy_hashes.clone(),
+            };
+            effects.extend(effect_builder.send_message(*peer, message).ignore());
+            self.metrics.probes_sent.inc();
+        }
+        self.current_round = Some(ProbeRound {
+            first_seen,
+            peers_probed: probed_peers.len() as u32,
+            awaiting_response: probed_peers.into_iter().collect(),
+            reports: HashMap::new(),
+        });
+        effects
+    }
+
+    /// Records the spreads of the deploys probed for in the current round, if any.
+    ///
+    /// Deploys for which no probed peer responded are skipped.
+    fn conclude_round(&mut self) {
+        let mut round = match self.current_round.take() {
+            Some(round) => round,
+            None => return,
+        };
+        for (deploy_hash, first_seen) in round.first_seen {
+            let reports = round.reports.remove(&deploy_hash).unwrap_or_default();
+            if reports.is_empty() {
+                debug!(%deploy_hash, "no responses to deploy propagation probes");
+                continue;
+            }
+            let spread = DeploySpread::new(deploy_hash, first_seen, round.peers_probed, &reports);
+            debug!(?spread, "measured deploy propagation");
+            self.metrics.observe(&spread);
+            self.spreads.push_back(spread);
+            if self.spreads.len() > MAX_RETAINED_SPREADS {
+                let _ = self.spreads.pop_front();
+            }
+        }
+    }
+
+    fn handle_message<REv: ReactorEventT>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        sender: NodeId,
+        message: Message,
+    ) -> Effects<Event> {
+        match message {
+            Message::Probe { deploy_hashes } => {
+                let first_seen = deploy_hashes
+                    .into_iter()
+                    .take(MAX_PROBED_DEPLOYS)
+                    .map(|deploy_hash| (deploy_hash, self.first_seen.get(&deploy_hash).copied()))
+                    .collect();
+                effect_builder
+                    .send_message(sender, Message::ProbeResponse { first_seen })
+                    .ignore()
+            }
+            Message::ProbeResponse { first_seen } => {
+                let round = match self.current_round.as_mut() {
+                    Some(round) if round.awaiting_response.contains(&sender) => round,
+                    _ => {
+                        debug!(%sender, "ignoring unexpected deploy propagation probe response");
+                        return Effects::new();
+                    }
+                };
+                round.awaiting_response.remove(&sender);
+                for (deploy_hash, peer_first_seen) in first_seen {
+                    if round.first_seen.contains_key(&deploy_hash) {
+                        round
+                            .reports
+                            .entry(deploy_hash)
+                            .or_default()
+                            .push(peer_first_seen);
+                    }
+                }
+                if round.awaiting_response.is_empty() {
+                    self.conclude_round();
+                }
+                Effects::new()
+            }
+        }
+    }
+}
+
+impl<REv: ReactorEventT> Component<REv> for DeployPropagation {
+    type Event = Event;
+    type ConstructionError = prometheus::Error;
+
+    fn handle_event(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        rng: &mut NodeRng,
+        event: Self::Event,
+    ) -> Effects<Self::Event> {
+        match event {
+            Event::Request(DeployPropagationRequest::GetSpreads(responder)) => {
+                let maybe_spreads = if self.config.enabled() {
+                    Some(self.spreads.iter().cloned().collect())
+                } else {
+                    None
+                };
+                responder.respond(maybe_spreads).ignore()
+            }
+            // Nothing is recorded, nor are probes answered, while disabled.
+            _ if !self.config.enabled() => Effects::new(),
+            Event::DeployAccepted(deploy_hash) => {
+                self.record_first_seen(deploy_hash);
+                Effects::new()
+            }
+            Event::Probe => {
+                let mut effects = self.schedule_probe(effect_builder);
+                effects.extend(effect_builder.network_peers().event(|peers| {
+                    Event::GotPeers(peers.into_iter().map(|(peer, _)| peer).collect())
+                }));
+                effects
+            }
+            Event::GotPeers(peers) => self.start_round(effect_builder, rng, peers),
+            Event::MessageReceived { sender, message } => {
+                self.handle_message(effect_builder, sender, message)
+            }
+        }
+    }
+}
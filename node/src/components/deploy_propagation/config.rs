@@ -0,0 +1,97 @@
+use std::str::FromStr;
+
+use datasize::DataSize;
+use serde::{Deserialize, Serialize};
+
+use super::MAX_PROBED_DEPLOYS;
+use crate::{config_validation::ConfigValidationError, types::TimeDiff};
+
+const DEFAULT_PROBE_INTERVAL: &str = "30sec";
+const DEFAULT_DEPLOYS_PER_PROBE: u32 = 5;
+const DEFAULT_PEERS_PER_PROBE: u32 = 5;
+const DEFAULT_MAX_TRACKED_DEPLOYS: u32 = 10_000;
+
+/// Configuration options for measuring the propagation of deploys through the network.
+#[derive(Clone, DataSize, Debug, Deserialize, Serialize)]
+pub struct Config {
+    /// Whether to record when deploys are first seen, answer probes from peers and probe peers.
+    enabled: bool,
+    /// The interval between rounds of probes.
+    probe_interval: TimeDiff,
+    /// The maximum number of recently seen deploys to probe for in each round.
+    deploys_per_probe: u32,
+    /// The maximum number of peers to probe in each round.
+    peers_per_probe: u32,
+    /// The maximum number of most recently seen deploys whose first-seen times are kept.
+    max_tracked_deploys: u32,
+}
+
+impl Config {
+    /// Constructs an enabled config probing all peers for a single deploy every `probe_interval`.
+    #[cfg(test)]
+    pub(super) fn new_enabled(probe_interval: TimeDiff, peers_per_probe: u32) -> Self {
+        Config {
+            enabled: true,
+            probe_interval,
+            deploys_per_probe: 1,
+            peers_per_probe,
+            max_tracked_deploys: DEFAULT_MAX_TRACKED_DEPLOYS,
+        }
+    }
+
+    /// Get enabled setting.
+    pub(crate) fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Get probe_interval setting.
+    pub(crate) fn probe_interval(&self) -> TimeDiff {
+        self.probe_interval
+    }
+
+    /// Get deploys_per_probe setting.
+    pub(crate) fn deploys_per_probe(&self) -> usize {
+        self.deploys_per_probe as usize
+    }
+
+    /// Get peers_per_probe setting.
+    pub(crate) fn peers_per_probe(&self) -> usize {
+        self.peers_per_probe as usize
+    }
+
+    /// Get max_tracked_deploys setting.
+    pub(crate) fn max_tracked_deploys(&self) -> usize {
+        self.max_tracked_deploys as usize
+    }
+
+    /// Checks whether the values set in the config make sense, pushing every problem found to
+    /// `errors`.
+    pub(crate) fn validate(&self, errors: &mut Vec<ConfigValidationError>) {
+        if self.probe_interval.millis() == 0 {
+            errors.push(ConfigValidationError::out_of_range(
+                "deploy_propagation.probe_interval",
+                self.probe_interval,
+                "greater than 0s",
+            ));
+        }
+        if self.deploys_per_probe as usize > MAX_PROBED_DEPLOYS {
+            errors.push(ConfigValidationError::out_of_range(
+                "deploy_propagation.deploys_per_probe",
+                self.deploys_per_probe,
+                format!("at most {}", MAX_PROBED_DEPLOYS),
+            ));
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            enabled: false,
+            probe_interval: TimeDiff::from_str(DEFAULT_PROBE_INTERVAL).unwrap(),
+            deploys_per_probe: DEFAULT_DEPLOYS_PER_PROBE,
+            peers_per_probe: DEFAULT_PEERS_PER_PROBE,
+            max_tracked_deploys: DEFAULT_MAX_TRACKED_DEPLOYS,
+        }
+    }
+}
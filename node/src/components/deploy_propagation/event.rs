@@ -0,0 +1,41 @@
+use std::fmt::{self, Display, Formatter};
+
+use derive_more::From;
+
+use super::Message;
+use crate::{
+    effect::requests::DeployPropagationRequest,
+    types::{DeployHash, NodeId},
+};
+
+/// An event for the deploy propagation component.
+#[derive(Debug, From)]
+pub enum Event {
+    /// A deploy has been accepted, either from a client or from a peer.
+    DeployAccepted(DeployHash),
+    /// The next round of probes is due.
+    Probe,
+    /// The peers we're connected to, which the next round of probes is sent to a sample of.
+    GotPeers(Vec<NodeId>),
+    /// A message has been received from a peer.
+    MessageReceived { sender: NodeId, message: Message },
+    /// An incoming request.
+    #[from]
+    Request(DeployPropagationRequest),
+}
+
+impl Display for Event {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Event::DeployAccepted(deploy_hash) => {
+                write!(formatter, "deploy accepted: {}", deploy_hash)
+            }
+            Event::Probe => write!(formatter, "probe"),
+            Event::GotPeers(peers) => write!(formatter, "got {} peers", peers.len()),
+            Event::MessageReceived { sender, message } => {
+                write!(formatter, "{} received from {}", message, sender)
+            }
+            Event::Request(request) => write!(formatter, "{}", request),
+        }
+    }
+}
@@ -0,0 +1,30 @@
+use std::fmt::{self, Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::{DeployHash, Timestamp};
+
+/// A message measuring how far deploys have propagated through the network.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum Message {
+    /// Sent to random peers to ask when they first saw each of the given deploys.
+    Probe { deploy_hashes: Vec<DeployHash> },
+    /// Response to a `Probe`, giving the time at which the responder first saw each of the probed
+    /// deploys, or `None` if it hasn't seen it.
+    ProbeResponse {
+        first_seen: Vec<(DeployHash, Option<Timestamp>)>,
+    },
+}
+
+impl Display for Message {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Message::Probe { deploy_hashes } => {
+                write!(formatter, "probe({} deploys)", deploy_hashes.len())
+            }
+            Message::ProbeResponse { first_seen } => {
+                write!(formatter, "probe-response({} deploys)", first_seen.len())
+            }
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use prometheus::{Histogram, HistogramOpts, IntCounter, Registry};
+
+use crate::{types::DeploySpread, unregister_metric};
+
+/// Upper bounds in seconds of the buckets of the delta histograms.  Deltas at or below zero, e.g.
+/// due to clock skew, fall into the first bucket.
+const DELTA_BUCKETS: &[f64] = &[0.0, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0];
+
+#[derive(Debug)]
+pub(super) struct DeployPropagationMetrics {
+    /// Smallest first-seen delta of each probed deploy, in seconds.
+    min_delta: Histogram,
+    /// Median first-seen delta of each probed deploy, in seconds.
+    median_delta: Histogram,
+    /// Largest first-seen delta of each probed deploy, in seconds.
+    max_delta: Histogram,
+    /// Number of probes sent to peers.
+    pub(super) probes_sent: IntCounter,
+    /// Number of probed deploys which a responding peer hadn't seen.
+    pub(super) not_seen: IntCounter,
+    /// Reference to the registry for unregistering.
+    registry: Registry,
+}
+
+/// Creates a delta histogram and registers it.
+fn register_delta_histogram(
+    registry: &Registry,
+    name: &str,
+    help: &str,
+) -> Result<Histogram, prometheus::Error> {
+    let opts = HistogramOpts::new(name, help).buckets(DELTA_BUCKETS.to_vec());
+    let histogram = Histogram::with_opts(opts)?;
+    registry.register(Box::new(histogram.clone()))?;
+    Ok(histogram)
+}
+
+impl DeployPropagationMetrics {
+    pub(super) fn new(registry: &Registry) -> Result<Self, prometheus::Error> {
+        let min_delta = register_delta_histogram(
+            registry,
+            "deploy_propagation_min_delta_seconds",
+            "smallest delay from this node to a probed peer first seeing a deploy, in seconds",
+        )?;
+        let median_delta = register_delta_histogram(
+            registry,
+            "deploy_propagation_median_delta_seconds",
+            "median delay from this node to the probed peers first seeing a deploy, in seconds",
+        )?;
+        let max_delta = register_delta_histogram(
+            registry,
+            "deploy_propagation_max_delta_seconds",
+            "largest delay from this node to a probed peer first seeing a deploy, in seconds",
+        )?;
+        let probes_sent = IntCounter::new(
+            "deploy_propagation_probes_sent",
+            "number of deploy propagation probes sent to peers",
+        )?;
+        let not_seen = IntCounter::new(
+            "deploy_propagation_not_seen",
+            "number of probed deploys which a responding peer hadn't seen",
+        )?;
+        registry.register(Box::new(probes_sent.clone()))?;
+        registry.register(Box::new(not_seen.clone()))?;
+
+        Ok(DeployPropagationMetrics {
+            min_delta,
+            median_delta,
+            max_delta,
+            probes_sent,
+            not_seen,
+            registry: registry.clone(),
+        })
+    }
+
+    /// Records the spread of a probed deploy.
+    pub(super) fn observe(&self, spread: &DeploySpread) {
+        let observe = |histogram: &Histogram, maybe_delta_millis: Option<i64>| {
+            if let Some(delta_millis) = maybe_delta_millis {
+                histogram.observe(delta_millis as f64 / 1000.0);
+            }
+        };
+        observe(&self.min_delta, spread.min_delta_millis);
+        observe(&self.median_delta, spread.median_delta_millis);
+        observe(&self.max_delta, spread.max_delta_millis);
+        self.not_seen.inc_by(u64::from(spread.peers_not_seen));
+    }
+
+    /// Returns the number of deploys whose spread has been recorded with at least one delta.
+    #[cfg(test)]
+    pub(super) fn observed_deploys(&self) -> u64 {
+        self.median_delta.get_sample_count()
+    }
+}
+
+impl Drop for DeployPropagationMetrics {
+    fn drop(&mut self) {
+        unregister_metric!(self.registry, self.min_delta);
+        unregister_metric!(self.registry, self.median_delta);
+        unregister_metric!(self.registry, self.max_delta);
+        unregister_metric!(self.registry, self.probes_sent);
+        unregister_metric!(self.registry, self.not_seen);
+    }
+}
@@ -0,0 +1,371 @@
+#![cfg(test)]
+use std::{
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
+
+use derive_more::From;
+use futures::FutureExt;
+use prometheus::Registry;
+use serde::Serialize;
+use thiserror::Error;
+
+use super::*;
+use crate::{
+    components::in_memory_network::{self, InMemoryNetwork, NetworkController},
+    effect::announcements::{ControlAnnouncement, NetworkAnnouncement},
+    protocol::Message as NodeMessage,
+    reactor::{self, EventQueueHandle, ReactorEvent},
+    testing::{network::NetworkedReactor, simulation::Simulation, TestRng},
+    types::TimeDiff,
+};
+
+/// The interval between rounds of probes, much longer than it takes a deploy to reach every node.
+const PROBE_INTERVAL: &str = "10sec";
+
+/// The delay between consecutive nodes first seeing a deploy.
+const SPREAD_STEP: Duration = Duration::from_millis(100);
+
+/// Top-level event for the reactor.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, From, Serialize)]
+#[must_use]
+enum Event {
+    #[from]
+    Network(in_memory_network::Event<NodeMessage>),
+    #[from]
+    DeployPropagation(#[serde(skip_serializing)] super::Event),
+    #[from]
+    NetworkRequest(NetworkRequest<NodeId, NodeMessage>),
+    #[from]
+    NetworkInfoRequest(#[serde(skip_serializing)] NetworkInfoRequest<NodeId>),
+    #[from]
+    ControlAnnouncement(ControlAnnouncement),
+    #[from]
+    NetworkAnnouncement(#[serde(skip_serializing)] NetworkAnnouncement<NodeId, NodeMessage>),
+}
+
+impl ReactorEvent for Event {
+    fn as_control(&self) -> Option<&ControlAnnouncement> {
+        if let Self::ControlAnnouncement(ref ctrl_ann) = self {
+            Some(ctrl_ann)
+        } else {
+            None
+        }
+    }
+}
+
+impl From<NetworkRequest<NodeId, Message>> for Event {
+    fn from(request: NetworkRequest<NodeId, Message>) -> Self {
+        Event::NetworkRequest(request.map_payload(NodeMessage::from))
+    }
+}
+
+impl From<DeployPropagationRequest> for Event {
+    fn from(request: DeployPropagationRequest) -> Self {
+        Event::DeployPropagation(super::Event::Request(request))
+    }
+}
+
+impl Display for Event {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Event::Network(event) => write!(formatter, "event: {}", event),
+            Event::DeployPropagation(event) => write!(formatter, "deploy propagation: {}", event),
+            Event::NetworkRequest(req) => write!(formatter, "network request: {}", req),
+            Event::NetworkInfoRequest(req) => write!(formatter, "network info request: {}", req),
+            Event::ControlAnnouncement(ctrl_ann) => write!(formatter, "control: {}", ctrl_ann),
+            Event::NetworkAnnouncement(ann) => write!(formatter, "network announcement: {}", ann),
+        }
+    }
+}
+
+/// Error type returned by the test reactor.
+#[derive(Debug, Error)]
+enum Error {
+    #[error("prometheus (metrics) error: {0}")]
+    Metrics(#[from] prometheus::Error),
+}
+
+struct Reactor {
+    network: InMemoryNetwork<NodeMessage>,
+    deploy_propagation: DeployPropagation,
+}
+
+impl Drop for Reactor {
+    fn drop(&mut self) {
+        NetworkController::<NodeMessage>::remove_node(&self.network.node_id())
+    }
+}
+
+impl reactor::Reactor for Reactor {
+    type Event = Event;
+    type Config = Config;
+    type Error = Error;
+
+    fn new(
+        config: Self::Config,
+        registry: &Registry,
+        event_queue: EventQueueHandle<Self::Event>,
+        rng: &mut NodeRng,
+    ) -> Result<(Self, Effects<Self::Event>), Self::Error> {
+        let effect_builder = EffectBuilder::new(event_queue);
+        let network = NetworkController::create_node(event_queue, rng);
+        let (deploy_propagation, effects) =
+            DeployPropagation::new(config, registry, effect_builder)?;
+
+        let reactor = Reactor {
+            network,
+            deploy_propagation,
+        };
+
+        Ok((
+            reactor,
+            reactor::wrap_effects(Event::DeployPropagation, effects),
+        ))
+    }
+
+    fn dispatch_event(
+        &mut self,
+        effect_builder: EffectBuilder<Self::Event>,
+        rng: &mut NodeRng,
+        event: Event,
+    ) -> Effects<Self::Event> {
+        match event {
+            Event::Network(event) => reactor::wrap_effects(
+                Event::Network,
+                self.network.handle_event(effect_builder, rng, event),
+            ),
+            Event::DeployPropagation(event) => reactor::wrap_effects(
+                Event::DeployPropagation,
+                self.deploy_propagation
+                    .handle_event(effect_builder, rng, event),
+            ),
+            Event::NetworkRequest(request) => reactor::wrap_effects(
+                Event::Network,
+                self.network
+                    .handle_event(effect_builder, rng, request.into()),
+            ),
+            Event::NetworkInfoRequest(NetworkInfoRequest::GetPeers { responder }) => {
+                // Every node on the in-memory network is connected to every other.
+                let peers = self
+                    .network
+                    .peers()
+                    .into_iter()
+                    .map(|peer| (peer, peer.to_string()))
+                    .collect();
+                responder.respond(peers).ignore()
+            }
+            Event::NetworkInfoRequest(NetworkInfoRequest::GetDialBackoffs { .. }) => {
+                unreachable!("should not request dial backoffs")
+            }
+            Event::NetworkInfoRequest(NetworkInfoRequest::GetGossipState { .. }) => {
+                unreachable!("should not request gossip state")
+            }
+            Event::ControlAnnouncement(ctrl_ann) => {
+                unreachable!("unhandled control announcement: {}", ctrl_ann)
+            }
+            Event::NetworkAnnouncement(NetworkAnnouncement::MessageReceived {
+                sender,
+                payload,
+            }) => {
+                let reactor_event = match payload {
+                    NodeMessage::DeployPropagation(message) => {
+                        Event::DeployPropagation(super::Event::MessageReceived { sender, message })
+                    }
+                    msg => panic!("should not get {}", msg),
+                };
+                self.dispatch_event(effect_builder, rng, reactor_event)
+            }
+            Event::NetworkAnnouncement(_) => {
+                // We do not care about other network announcements in the deploy propagation test.
+                Effects::new()
+            }
+        }
+    }
+
+    fn maybe_exit(&self) -> Option<crate::reactor::ReactorExit> {
+        None
+    }
+}
+
+impl NetworkedReactor for Reactor {
+    type NodeId = NodeId;
+
+    fn node_id(&self) -> NodeId {
+        self.network.node_id()
+    }
+}
+
+fn accept_deploy(deploy_hash: DeployHash) -> impl FnOnce(EffectBuilder<Event>) -> Effects<Event> {
+    move |effect_builder: EffectBuilder<Event>| {
+        effect_builder
+            .immediately()
+            .event(move |_| Event::DeployPropagation(super::Event::DeployAccepted(deploy_hash)))
+    }
+}
+
+/// Adds a node with the given config to the simulation, returning its ID.
+async fn add_node(
+    simulation: &mut Simulation<Reactor>,
+    rng: &mut TestRng,
+    config: Config,
+) -> NodeId {
+    let (node_id, _runner) = simulation
+        .network_mut()
+        .add_node_with_config(config, rng)
+        .await
+        .unwrap();
+    node_id
+}
+
+/// Queries the node for its deploy spreads via the same request as the REST server.
+async fn get_deploy_spreads(
+    simulation: &mut Simulation<Reactor>,
+    rng: &mut TestRng,
+    node_id: &NodeId,
+) -> Option<Vec<DeploySpread>> {
+    let output = Arc::new(Mutex::new(None));
+    let output_clone = Arc::clone(&output);
+    simulation
+        .network_mut()
+        .process_injected_effect_on(node_id, move |effect_builder| {
+            effect_builder
+                .get_deploy_spreads()
+                .map(move |maybe_spreads| *output_clone.lock().unwrap() = Some(maybe_spreads))
+                .ignore()
+        })
+        .await;
+    simulation.settle(rng).await;
+    let maybe_spreads = output.lock().unwrap().take();
+    maybe_spreads.expect("should respond to request for deploy spreads")
+}
+
+#[tokio::test]
+async fn should_measure_spread_of_deploy_through_network() {
+    const NETWORK_SIZE: usize = 5;
+
+    NetworkController::<NodeMessage>::create_active();
+    let mut simulation = Simulation::<Reactor>::new();
+    let mut rng = crate::new_rng();
+
+    let probe_interval = TimeDiff::from_str(PROBE_INTERVAL).unwrap();
+    let mut node_ids = vec![];
+    for _ in 0..NETWORK_SIZE {
+        let config = Config::new_enabled(probe_interval, NETWORK_SIZE as u32 - 1);
+        node_ids.push(add_node(&mut simulation, &mut rng, config).await);
+    }
+
+    // The deploy reaches each node `SPREAD_STEP` after the previous one, starting with node 0.
+    let deploy_hash = DeployHash::random(&mut rng);
+    for node_id in &node_ids {
+        simulation
+            .network_mut()
+            .process_injected_effect_on(node_id, accept_deploy(deploy_hash))
+            .await;
+        simulation.advance_time(&mut rng, SPREAD_STEP).await;
+    }
+
+    // Every node runs a round of probes, all of which are answered.
+    simulation
+        .advance_time(&mut rng, Duration::from(probe_interval))
+        .await;
+
+    let spreads = get_deploy_spreads(&mut simulation, &mut rng, &node_ids[0])
+        .await
+        .expect("should be enabled");
+    assert_eq!(spreads.len(), 1);
+    let spread = &spreads[0];
+    assert_eq!(spread.deploy_hash, deploy_hash);
+    assert_eq!(spread.peers_probed, 4);
+    assert_eq!(spread.peers_responded, 4);
+    assert_eq!(spread.peers_not_seen, 0);
+    assert_eq!(spread.min_delta_millis, Some(100));
+    assert_eq!(spread.median_delta_millis, Some(250));
+    assert_eq!(spread.max_delta_millis, Some(400));
+
+    // The last node to see the deploy reports the raw deltas, all of which are negative.
+    let spreads = get_deploy_spreads(&mut simulation, &mut rng, &node_ids[4])
+        .await
+        .expect("should be enabled");
+    assert_eq!(spreads.len(), 1);
+    assert_eq!(spreads[0].min_delta_millis, Some(-400));
+    assert_eq!(spreads[0].max_delta_millis, Some(-100));
+
+    for node_id in &node_ids {
+        let metrics = &simulation.network().nodes()[node_id]
+            .reactor()
+            .inner()
+            .deploy_propagation
+            .metrics;
+        assert_eq!(metrics.probes_sent.get(), 4);
+        assert_eq!(metrics.not_seen.get(), 0);
+        assert_eq!(metrics.observed_deploys(), 1);
+    }
+
+    NetworkController::<NodeMessage>::remove_active();
+}
+
+#[tokio::test]
+async fn should_neither_record_nor_answer_probes_while_disabled() {
+    NetworkController::<NodeMessage>::create_active();
+    let mut simulation = Simulation::<Reactor>::new();
+    let mut rng = crate::new_rng();
+
+    let probe_interval = TimeDiff::from_str(PROBE_INTERVAL).unwrap();
+    let origin = add_node(
+        &mut simulation,
+        &mut rng,
+        Config::new_enabled(probe_interval, 2),
+    )
+    .await;
+    let enabled = add_node(
+        &mut simulation,
+        &mut rng,
+        Config::new_enabled(probe_interval, 2),
+    )
+    .await;
+    let disabled = add_node(&mut simulation, &mut rng, Config::default()).await;
+
+    let deploy_hash = DeployHash::random(&mut rng);
+    simulation
+        .network_mut()
+        .process_injected_effect_on(&origin, accept_deploy(deploy_hash))
+        .await;
+    simulation.advance_time(&mut rng, SPREAD_STEP).await;
+    for node_id in &[enabled, disabled] {
+        simulation
+            .network_mut()
+            .process_injected_effect_on(node_id, accept_deploy(deploy_hash))
+            .await;
+    }
+
+    // The disabled node never answers, so the origin's round is only concluded by its next round.
+    simulation
+        .advance_time(&mut rng, Duration::from(probe_interval) * 2)
+        .await;
+
+    let spreads = get_deploy_spreads(&mut simulation, &mut rng, &origin)
+        .await
+        .expect("should be enabled");
+    assert_eq!(spreads.len(), 1);
+    let spread = &spreads[0];
+    assert_eq!(spread.peers_probed, 2);
+    assert_eq!(spread.peers_responded, 1);
+    assert_eq!(spread.peers_not_seen, 0);
+    assert_eq!(spread.min_delta_millis, Some(100));
+    assert_eq!(spread.max_delta_millis, Some(100));
+
+    assert!(get_deploy_spreads(&mut simulation, &mut rng, &disabled)
+        .await
+        .is_none());
+    let deploy_propagation = &simulation.network().nodes()[&disabled]
+        .reactor()
+        .inner()
+        .deploy_propagation;
+    assert!(deploy_propagation.first_seen.is_empty());
+    assert_eq!(deploy_propagation.metrics.probes_sent.get(), 0);
+
+    NetworkController::<NodeMessage>::remove_active();
+}
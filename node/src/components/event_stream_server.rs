@@ -1,7 +1,7 @@
 //! Event stream server
 //!
 //! The event stream server provides clients with an event-stream returning Server-Sent Events
-//! (SSEs) holding JSON-encoded data.
+//! (SSEs) holding JSON-encoded data.  The same events can optionally be served over a websocket.
 //!
 //! The actual server is run in backgrounded tasks.
 //!
@@ -15,28 +15,50 @@
 //!
 //! This component uses a ring buffer for outbound events providing some robustness against
 //! unintended subscriber disconnects, if a disconnected subscriber re-subscribes before the buffer
-//! has advanced past their last received event.
+//! has advanced past their last received event.  The buffer is limited both in the number of events
+//! and in the estimated size in bytes of each event stream's events.
+//!
+//! Event IDs are persisted across restarts of the node, but the buffer isn't.  Each run of the
+//! server has a random stream instance ID sent to every new subscriber, and subscribers resuming
+//! from an event emitted before the node started are told that the earlier events were skipped.
+//! When the node shuts down, every subscriber is sent a final event giving the reason.
+//!
+//! If enabled in the config, the same HTTP server also accepts deploys submitted by clients,
+//! passing them to the deploy acceptor just as the RPC server does.
 //!
 //! For details about the SSE model and a list of supported SSEs, see:
 //! <https://github.com/CasperLabs/ceps/blob/master/text/0009-client-api.md#rpcs>
 
 mod config;
 mod deploy_getter;
+mod deploy_submission;
 mod event;
+mod event_buffer;
 mod event_indexer;
 mod http_server;
+mod metrics;
+mod peer_rejection_limiter;
 mod sse_server;
 #[cfg(test)]
 mod tests;
+mod ws_server;
 
-use std::{convert::Infallible, fmt::Debug, net::SocketAddr, path::PathBuf};
+use std::{fmt::Debug, net::SocketAddr, path::PathBuf, time::Instant};
 
 use datasize::DataSize;
-use tokio::sync::{
-    mpsc::{self, UnboundedSender},
-    oneshot,
+use futures::{future::BoxFuture, FutureExt};
+use prometheus::Registry;
+use thiserror::Error;
+use tokio::{
+    sync::{
+        mpsc::{self, UnboundedSender},
+        oneshot,
+    },
+    task::JoinHandle,
 };
-use tracing::{info, warn};
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+use warp::Filter;
 
 use casper_types::ProtocolVersion;
 
@@ -44,16 +66,21 @@ use super::Component;
 use crate::{
     effect::{EffectBuilder, Effects},
     reactor::participating::Event as ParticipatingReactorEvent,
-    types::JsonBlock,
-    utils::{self, ListeningError},
+    types::{DeployHash, JsonBlock, NodeId},
+    utils::{self, ListeningError, Source},
     NodeRng,
 };
 pub use config::Config;
 pub(crate) use deploy_getter::DeployGetter;
-pub(crate) use event::Event;
+pub(crate) use event::{DeployHeaderSummary, Event};
+use event_buffer::EventBuffer;
 use event_indexer::{EventIndex, EventIndexer};
+use peer_rejection_limiter::PeerRejectionLimiter;
 use sse_server::ChannelsAndFilter;
-pub(crate) use sse_server::SseData;
+pub(crate) use sse_server::{
+    DeploySource, SseData, QUERY_FIELD, SSE_API_DEPLOYS_PATH, SSE_API_MAIN_PATH, SSE_API_ROOT_PATH,
+    SSE_API_SIGNATURES_PATH,
+};
 
 /// This is used to define the number of events to buffer in the tokio broadcast channel to help
 /// slower clients to try to avoid missing events (See
@@ -65,6 +92,17 @@ pub(crate) use sse_server::SseData;
 /// that a new client can retrieve the entire set of buffered events if desired.
 const ADDITIONAL_PERCENT_FOR_BROADCAST_CHANNEL_SIZE: u32 = 20;
 
+/// Error constructing an `EventStreamServer`.
+#[derive(Debug, Error)]
+pub enum ConstructionError {
+    /// Error starting the HTTP server.
+    #[error(transparent)]
+    Listening(#[from] ListeningError),
+    /// Error registering metrics.
+    #[error("prometheus (metrics) error: {0}")]
+    Metrics(#[from] prometheus::Error),
+}
+
 /// A helper trait whose bounds represent the requirements for a reactor event that `run_server` can
 /// work with.
 pub trait ReactorEventT: From<Event> + Send {}
@@ -77,9 +115,20 @@ pub(crate) struct EventStreamServer {
     // TODO - this should not be skipped.  Awaiting support for `UnboundedSender` in datasize crate.
     #[data_size(skip)]
     sse_data_sender: UnboundedSender<(EventIndex, SseData)>,
+    /// Channel sender to pass the reason for the node shutting down to the event-stream server.
+    #[data_size(skip)]
+    shutdown_sender: Option<oneshot::Sender<String>>,
+    /// The task handle which will only join once the server has stopped.
+    #[data_size(skip)]
+    server_join_handle: Option<JoinHandle<()>>,
     event_indexer: EventIndexer,
+    /// The random ID of this run of the event stream, sent to every new subscriber.
+    #[allow(dead_code)] // Only read in tests.
+    #[data_size(skip)]
+    stream_instance_id: Uuid,
     listening_address: SocketAddr,
     deploy_getter: DeployGetter,
+    peer_rejection_limiter: PeerRejectionLimiter,
 }
 
 impl EventStreamServer {
@@ -87,8 +136,10 @@ impl EventStreamServer {
         config: Config,
         storage_path: PathBuf,
         api_version: ProtocolVersion,
+        max_deploy_size: u32,
         deploy_getter: DeployGetter,
-    ) -> Result<Self, ListeningError> {
+        registry: &Registry,
+    ) -> Result<Self, ConstructionError> {
         let required_address = utils::resolve_address(&config.address).map_err(|error| {
             warn!(
                 %error,
@@ -99,7 +150,12 @@ impl EventStreamServer {
         })?;
 
         let event_indexer = EventIndexer::new(storage_path);
+        let buffer = EventBuffer::new(&config, event_indexer.current_index(), registry)?;
+        let stream_instance_id = Uuid::new_v4();
+        let peer_rejection_limiter =
+            PeerRejectionLimiter::new(config.max_peer_rejections_per_second);
         let (sse_data_sender, sse_data_receiver) = mpsc::unbounded_channel();
+        let (shutdown_sender, shutdown_receiver) = oneshot::channel();
 
         // Event stream channels and filter.
         let broadcast_channel_size = config.event_stream_buffer_length
@@ -107,6 +163,7 @@ impl EventStreamServer {
             / 100;
         let ChannelsAndFilter {
             event_broadcaster,
+            new_subscriber_info_sender,
             new_subscriber_info_receiver,
             sse_filter,
         } = ChannelsAndFilter::new(
@@ -115,33 +172,74 @@ impl EventStreamServer {
             deploy_getter.clone(),
         );
 
-        let (shutdown_sender, shutdown_receiver) = oneshot::channel::<()>();
+        // The websocket filter must precede the SSE one, as the latter responds to all unmatched
+        // paths with a 404.
+        let filter = if config.enable_websocket {
+            ws_server::create_filter(
+                event_broadcaster.clone(),
+                new_subscriber_info_sender,
+                config.max_concurrent_websocket_subscribers,
+                deploy_getter.clone(),
+            )
+            .or(sse_filter)
+            .unify()
+            .boxed()
+        } else {
+            sse_filter
+        };
+        // The deploy submission filter must also precede the SSE one.
+        let filter = if config.enable_deploy_submission {
+            deploy_submission::create_filter(
+                api_version,
+                max_deploy_size,
+                config.deploy_submission_secret,
+                deploy_getter.clone(),
+            )
+            .or(filter)
+            .unify()
+            .boxed()
+        } else {
+            filter
+        };
 
-        let (listening_address, server_with_shutdown) = warp::serve(sse_filter)
+        let (server_shutdown_sender, server_shutdown_receiver) = oneshot::channel::<()>();
+
+        let (listening_address, server_with_shutdown) = warp::serve(filter)
             .try_bind_with_graceful_shutdown(required_address, async {
-                shutdown_receiver.await.ok();
+                server_shutdown_receiver.await.ok();
             })
             .map_err(|error| ListeningError::Listen {
                 address: required_address,
                 error: Box::new(error),
             })?;
-        info!(address=%listening_address, "started event stream server");
+        info!(
+            address=%listening_address,
+            %stream_instance_id,
+            first_event_id=%event_indexer.current_index(),
+            "started event stream server"
+        );
 
-        tokio::spawn(http_server::run(
-            config,
+        let server_join_handle = tokio::spawn(http_server::run(
+            buffer,
             api_version,
+            stream_instance_id,
             server_with_shutdown,
-            shutdown_sender,
+            server_shutdown_sender,
             sse_data_receiver,
+            shutdown_receiver,
             event_broadcaster,
             new_subscriber_info_receiver,
         ));
 
         Ok(EventStreamServer {
             sse_data_sender,
+            shutdown_sender: Some(shutdown_sender),
+            server_join_handle: Some(server_join_handle),
             event_indexer,
+            stream_instance_id,
             listening_address,
             deploy_getter,
+            peer_rejection_limiter,
         })
     }
 
@@ -153,12 +251,54 @@ impl EventStreamServer {
             .set_participating_effect_builder(effect_builder);
     }
 
+    /// Returns the address the server is listening on.
+    #[cfg(test)]
+    pub(crate) fn listening_address(&self) -> SocketAddr {
+        self.listening_address
+    }
+
+    /// Sends a final `Shutdown` event giving `reason` to all clients connected to the event stream,
+    /// after any events already broadcast, then stops the server.
+    ///
+    /// The returned future completes once the server has stopped, i.e. once every client's stream
+    /// has been closed.
+    pub(crate) fn shutdown(&mut self, reason: String) -> BoxFuture<'static, ()> {
+        if let Some(shutdown_sender) = self.shutdown_sender.take() {
+            let _ = shutdown_sender.send(reason);
+        }
+        match self.server_join_handle.take() {
+            Some(server_join_handle) => server_join_handle.map(|_| ()).boxed(),
+            None => async {}.boxed(),
+        }
+    }
+
     /// Broadcasts the SSE data to all clients connected to the event stream.
     fn broadcast(&mut self, sse_data: SseData) -> Effects<Event> {
         let event_index = self.event_indexer.next_index();
         let _ = self.sse_data_sender.send((event_index, sse_data));
         Effects::new()
     }
+
+    /// Broadcasts the rejection of a deploy, unless it was received from a peer and the rate at
+    /// which peer rejections are published has been exceeded.
+    fn broadcast_deploy_rejected(
+        &mut self,
+        deploy_hash: DeployHash,
+        reason: String,
+        source: Source<NodeId>,
+    ) -> Effects<Event> {
+        if let Source::Peer(_) = source {
+            if !self.peer_rejection_limiter.should_publish(Instant::now()) {
+                debug!(%deploy_hash, %source, "not publishing deploy rejection: rate limited");
+                return Effects::new();
+            }
+        }
+        self.broadcast(SseData::DeployRejected {
+            deploy_hash,
+            reason,
+            source: DeploySource::from(&source),
+        })
+    }
 }
 
 impl<REv> Component<REv> for EventStreamServer
@@ -166,7 +306,7 @@ where
     REv: ReactorEventT,
 {
     type Event = Event;
-    type ConstructionError = Infallible;
+    type ConstructionError = ConstructionError;
 
     fn handle_event(
         &mut self,
@@ -179,21 +319,35 @@ where
                 block_hash: *block.hash(),
                 block: Box::new(JsonBlock::new(*block, None)),
             }),
-            Event::DeployAccepted(deploy) => self.broadcast(SseData::DeployAccepted { deploy }),
+            Event::DeployAccepted(summary) => self.broadcast(SseData::DeployAccepted {
+                deploy: summary.deploy_hash,
+            }),
+            Event::DeployRejected {
+                deploy_hash,
+                reason,
+                source,
+            } => self.broadcast_deploy_rejected(deploy_hash, reason, source),
+            Event::DeployReplaced {
+                replaced,
+                replacement,
+            } => self.broadcast(SseData::DeployReplaced {
+                replaced,
+                replacement,
+            }),
+            Event::DeployEvicted { deploy_hash } => {
+                self.broadcast(SseData::DeployEvicted { deploy_hash })
+            }
             Event::DeployProcessed {
                 deploy_hash,
                 deploy_header,
                 block_hash,
                 execution_result,
-            } => self.broadcast(SseData::DeployProcessed {
-                deploy_hash: Box::new(deploy_hash),
-                account: Box::new(deploy_header.account().clone()),
-                timestamp: deploy_header.timestamp(),
-                ttl: deploy_header.ttl(),
-                dependencies: deploy_header.dependencies().clone(),
-                block_hash: Box::new(block_hash),
+            } => self.broadcast(SseData::deploy_processed(
+                deploy_hash,
+                &deploy_header,
+                block_hash,
                 execution_result,
-            }),
+            )),
             Event::Fault {
                 era_id,
                 public_key,
@@ -204,10 +358,7 @@ where
                 timestamp,
             }),
             Event::FinalitySignature(fs) => self.broadcast(SseData::FinalitySignature(fs)),
-            Event::Step { era_id, effect } => self.broadcast(SseData::Step {
-                era_id,
-                execution_effect: effect,
-            }),
+            Event::Step { era_id, effect } => self.broadcast(SseData::step(era_id, effect)),
         }
     }
 }
@@ -1,6 +1,8 @@
 use datasize::DataSize;
 use serde::{Deserialize, Serialize};
 
+use crate::config_validation::{self, ConfigValidationError};
+
 /// Default binding address for the SSE HTTP server.
 ///
 /// Uses a fixed port per node, but binds on any interface.
@@ -9,9 +11,24 @@ const DEFAULT_ADDRESS: &str = "0.0.0.0:0";
 /// Default number of SSEs to buffer.
 const DEFAULT_EVENT_STREAM_BUFFER_LENGTH: u32 = 5000;
 
+/// Default maximum estimated size in bytes of the buffered `/events/main` SSEs.
+const DEFAULT_MAX_MAIN_BUFFER_BYTES: u32 = 64 * 1024 * 1024;
+
+/// Default maximum estimated size in bytes of the buffered `/events/deploys` SSEs.
+const DEFAULT_MAX_DEPLOYS_BUFFER_BYTES: u32 = 8 * 1024 * 1024;
+
+/// Default maximum estimated size in bytes of the buffered `/events/sigs` SSEs.
+const DEFAULT_MAX_SIGS_BUFFER_BYTES: u32 = 8 * 1024 * 1024;
+
 /// Default maximum number of subscribers.
 const DEFAULT_MAX_CONCURRENT_SUBSCRIBERS: u32 = 100;
 
+/// Default maximum number of websocket subscribers.
+const DEFAULT_MAX_CONCURRENT_WEBSOCKET_SUBSCRIBERS: u32 = 100;
+
+/// Default maximum number of rejections of deploys received from peers to publish per second.
+const DEFAULT_MAX_PEER_REJECTIONS_PER_SECOND: u32 = 10;
+
 /// SSE HTTP server configuration.
 #[derive(Clone, DataSize, Debug, Deserialize, Serialize)]
 // Disallow unknown fields to ensure config files and command-line overrides contain valid keys.
@@ -20,11 +37,41 @@ pub struct Config {
     /// Address to bind event stream SSE HTTP server to.
     pub address: String,
 
-    /// Number of SSEs to buffer.
+    /// Number of SSEs to buffer across all event streams.
     pub event_stream_buffer_length: u32,
 
+    /// Maximum estimated size in bytes of the buffered `/events/main` SSEs.  The oldest such
+    /// events are evicted from the buffer once exceeded.
+    pub max_main_buffer_bytes: u32,
+
+    /// Maximum estimated size in bytes of the buffered `/events/deploys` SSEs.  The oldest such
+    /// events are evicted from the buffer once exceeded.
+    pub max_deploys_buffer_bytes: u32,
+
+    /// Maximum estimated size in bytes of the buffered `/events/sigs` SSEs.  The oldest such
+    /// events are evicted from the buffer once exceeded.
+    pub max_sigs_buffer_bytes: u32,
+
     /// Default maximum number of subscribers across all event streams permitted at any one time.
     pub max_concurrent_subscribers: u32,
+
+    /// Whether to also serve events over a websocket at `/ws/events`.
+    pub enable_websocket: bool,
+
+    /// Maximum number of websocket subscribers permitted at any one time.  These also count
+    /// towards `max_concurrent_subscribers`.
+    pub max_concurrent_websocket_subscribers: u32,
+
+    /// Maximum number of rejections of deploys received from peers to publish per second.  Any
+    /// further such rejections within the same second are not published.
+    pub max_peer_rejections_per_second: u32,
+
+    /// Whether to accept deploys via `PUT` requests to `/deploys`.
+    pub enable_deploy_submission: bool,
+
+    /// Secret which deploy submissions must provide in the `casper-deploy-secret` header.  If
+    /// `None`, deploy submissions aren't required to provide a secret.
+    pub deploy_submission_secret: Option<String>,
 }
 
 impl Config {
@@ -33,7 +80,36 @@ impl Config {
         Config {
             address: DEFAULT_ADDRESS.to_string(),
             event_stream_buffer_length: DEFAULT_EVENT_STREAM_BUFFER_LENGTH,
+            max_main_buffer_bytes: DEFAULT_MAX_MAIN_BUFFER_BYTES,
+            max_deploys_buffer_bytes: DEFAULT_MAX_DEPLOYS_BUFFER_BYTES,
+            max_sigs_buffer_bytes: DEFAULT_MAX_SIGS_BUFFER_BYTES,
             max_concurrent_subscribers: DEFAULT_MAX_CONCURRENT_SUBSCRIBERS,
+            enable_websocket: false,
+            max_concurrent_websocket_subscribers: DEFAULT_MAX_CONCURRENT_WEBSOCKET_SUBSCRIBERS,
+            max_peer_rejections_per_second: DEFAULT_MAX_PEER_REJECTIONS_PER_SECOND,
+            enable_deploy_submission: false,
+            deploy_submission_secret: None,
+        }
+    }
+
+    /// Checks whether the values set in the config make sense, pushing every problem found to
+    /// `errors`.
+    pub(crate) fn validate(&self, errors: &mut Vec<ConfigValidationError>) {
+        config_validation::check_address(
+            "event_stream_server.address",
+            &self.address,
+            true,
+            errors,
+        );
+        if self.max_concurrent_websocket_subscribers > self.max_concurrent_subscribers {
+            errors.push(ConfigValidationError::inconsistent(
+                "event_stream_server.max_concurrent_websocket_subscribers",
+                format!(
+                    "is {}, but must not be greater than \
+                    [event_stream_server.max_concurrent_subscribers] {}",
+                    self.max_concurrent_websocket_subscribers, self.max_concurrent_subscribers
+                ),
+            ));
         }
     }
 }
@@ -7,14 +7,22 @@ use once_cell::sync::OnceCell;
 use smallvec::smallvec;
 use tracing::error;
 
+#[cfg(test)]
+use crate::types::chainspec::DeployConfig;
 use crate::{
-    effect::EffectBuilder,
+    components::deploy_acceptor,
+    effect::{announcements::RpcServerAnnouncement, EffectBuilder},
     reactor::{
         joiner::Event as JoinerReactorEvent, participating::Event as ParticipatingReactorEvent,
+        QueueKind,
     },
     types::{Deploy, DeployHash},
 };
 
+/// The chain name for which the test-only stand-in for the deploy acceptor accepts deploys.
+#[cfg(test)]
+pub(super) const TEST_CHAIN_NAME: &str = "casper-example";
+
 /// A struct holding the two effect builders in use during the lifetime of the event stream
 /// server.
 ///
@@ -26,7 +34,8 @@ struct CommonEffectBuilder {
     participating: OnceCell<EffectBuilder<ParticipatingReactorEvent>>,
 }
 
-/// A struct to enable the event stream server tasks to fetch deploys from storage.
+/// A struct to enable the event stream server tasks to fetch deploys from storage, and to submit
+/// deploys received from clients to the deploy acceptor.
 #[derive(Clone, Debug, DataSize)]
 pub(crate) struct DeployGetter {
     #[data_size(skip)]
@@ -87,13 +96,38 @@ impl DeployGetter {
         }
         maybe_deploys.pop().unwrap()
     }
+
+    /// Submits the given `Deploy` to the `DeployAcceptor` exactly as the RPC server does, returning
+    /// the outcome, or `None` if the node isn't participating in the network yet.
+    ///
+    /// For tests, the deploy is checked by a stand-in for the `DeployAcceptor` instead.
+    #[cfg_attr(test, allow(unreachable_code))]
+    pub(super) async fn submit(
+        &self,
+        deploy: Deploy,
+    ) -> Option<Result<(), deploy_acceptor::Error>> {
+        #[cfg(test)]
+        return Some(self.accept_test_deploy(&deploy));
+
+        let participating_effect_builder = self.effect_builder.participating.get()?;
+        let result = participating_effect_builder
+            .make_request(
+                |responder| RpcServerAnnouncement::DeployReceived {
+                    deploy: Box::new(deploy),
+                    responder: Some(responder),
+                },
+                QueueKind::Api,
+            )
+            .await;
+        Some(result)
+    }
 }
 
 #[cfg(test)]
 impl DeployGetter {
     /// A test-only constructor taking the full set of `Deploy`s which will be available to the
     /// event stream server.
-    pub(super) fn with_deploys(deploys: HashMap<DeployHash, Deploy>) -> Self {
+    pub(crate) fn with_deploys(deploys: HashMap<DeployHash, Deploy>) -> Self {
         DeployGetter {
             effect_builder: Arc::new(CommonEffectBuilder::default()),
             deploys: Arc::new(deploys),
@@ -104,4 +138,18 @@ impl DeployGetter {
     pub(super) fn get_test_deploy(&self, deploy_hash: DeployHash) -> Option<Deploy> {
         self.deploys.get(&deploy_hash).cloned()
     }
+
+    /// A test-only stand-in for the `DeployAcceptor`, which only checks that the given `Deploy` is
+    /// valid and acceptable to a chain named `TEST_CHAIN_NAME` with the default deploy config.
+    pub(super) fn accept_test_deploy(&self, deploy: &Deploy) -> Result<(), deploy_acceptor::Error> {
+        let deploy_config = DeployConfig::default();
+        deploy
+            .clone()
+            .is_acceptable(
+                TEST_CHAIN_NAME,
+                &deploy_config.deploy_limits(),
+                deploy_config.hash_scheme(deploy.header().timestamp()),
+            )
+            .map_err(deploy_acceptor::Error::InvalidDeploy)
+    }
 }
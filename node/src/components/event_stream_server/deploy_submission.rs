@@ -0,0 +1,196 @@
+//! Types and functions used by the http server to accept deploys submitted by clients.
+//!
+//! If enabled in the config, a client can `PUT` a JSON-encoded [`Deploy`] to `/deploys`.  The
+//! deploy is handled exactly as if it had been sent via the JSON-RPC server's `account_put_deploy`,
+//! and the response holds either the deploy hash or the reason the deploy was rejected.  This
+//! allows nodes which only expose the event stream server's port to still accept deploys.
+//!
+//! If a secret is configured, requests must provide it in the [`SECRET_HEADER`] header.
+
+use std::{convert::Infallible, sync::Arc};
+
+use bytes::Bytes;
+use http::StatusCode;
+use hyper::Body;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+use warp::{
+    filters::BoxedFilter,
+    path,
+    reject::{self, LengthRequired, PayloadTooLarge, Reject},
+    reply::{self, Response},
+    Filter, Rejection, Reply,
+};
+
+use casper_types::ProtocolVersion;
+
+use super::DeployGetter;
+use crate::types::{Deploy, DeployHash};
+
+/// The URL path to which deploys are submitted.
+pub const PUT_DEPLOY_PATH: &str = "deploys";
+
+/// The header in which a client provides the deploy submission secret, if one is configured.
+pub const SECRET_HEADER: &str = "casper-deploy-secret";
+
+/// The factor by which the JSON-encoded size of a deploy may exceed its serialized size.
+///
+/// JSON hex-encodes all byte data and additionally includes a parsed form of each runtime arg, so
+/// the request body is allowed to be this many times larger than the chainspec's max deploy size.
+/// The deploy acceptor then enforces the exact limit on the deploy's serialized size.
+const JSON_SIZE_FACTOR: u64 = 4;
+
+/// The body of the response to a successfully-parsed deploy submission.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub(super) enum PutDeployResponse {
+    /// The deploy was accepted and stored.
+    Accepted {
+        api_version: ProtocolVersion,
+        deploy_hash: DeployHash,
+    },
+    /// The deploy was rejected.
+    Rejected {
+        deploy_hash: DeployHash,
+        reason: String,
+    },
+}
+
+/// The rejection used when a request doesn't provide the configured secret.
+#[derive(Debug)]
+struct Unauthorized;
+
+impl Reject for Unauthorized {}
+
+/// Creates the warp filter for the deploy submission endpoint.
+///
+/// Request bodies larger than `JSON_SIZE_FACTOR` times `max_deploy_size` are rejected without being
+/// read.
+pub(super) fn create_filter(
+    api_version: ProtocolVersion,
+    max_deploy_size: u32,
+    secret: Option<String>,
+    deploy_getter: DeployGetter,
+) -> BoxedFilter<(Response,)> {
+    let secret = Arc::new(secret);
+    let authorize = warp::header::optional::<String>(SECRET_HEADER)
+        .and_then(move |provided: Option<String>| {
+            let secret = Arc::clone(&secret);
+            async move {
+                if is_authorized(secret.as_deref(), provided.as_deref()) {
+                    Ok(())
+                } else {
+                    Err(reject::custom(Unauthorized))
+                }
+            }
+        })
+        .untuple_one();
+
+    let put_deploy = authorize
+        .and(warp::body::content_length_limit(
+            u64::from(max_deploy_size) * JSON_SIZE_FACTOR,
+        ))
+        .and(warp::body::bytes())
+        .and_then(move |body: Bytes| {
+            let deploy_getter = deploy_getter.clone();
+            async move { Ok::<_, Rejection>(put_deploy(body, api_version, deploy_getter).await) }
+        })
+        .recover(handle_rejection)
+        .unify();
+
+    warp::put()
+        .and(path(PUT_DEPLOY_PATH))
+        .and(path::end())
+        .and(put_deploy)
+        .boxed()
+}
+
+/// Returns whether the provided secret matches the configured one, or `true` if none is configured.
+///
+/// The comparison takes the same time regardless of where the secrets first differ.
+fn is_authorized(secret: Option<&str>, provided: Option<&str>) -> bool {
+    let secret = match secret {
+        Some(secret) => secret.as_bytes(),
+        None => return true,
+    };
+    let provided = match provided {
+        Some(provided) => provided.as_bytes(),
+        None => return false,
+    };
+    secret.len() == provided.len()
+        && secret
+            .iter()
+            .zip(provided)
+            .fold(0, |difference, (lhs, rhs)| difference | (lhs ^ rhs))
+            == 0
+}
+
+/// Parses the deploy and submits it to the deploy acceptor.
+async fn put_deploy(
+    body: Bytes,
+    api_version: ProtocolVersion,
+    deploy_getter: DeployGetter,
+) -> Response {
+    let deploy: Deploy = match serde_json::from_slice(&body) {
+        Ok(deploy) => deploy,
+        Err(error) => {
+            return create_response(
+                StatusCode::BAD_REQUEST,
+                format!("failed to parse deploy: {}\n", error),
+            )
+        }
+    };
+    let deploy_hash = *deploy.id();
+
+    let (status, response) = match deploy_getter.submit(deploy).await {
+        Some(Ok(())) => {
+            info!(%deploy_hash, "deploy submitted to event stream server was stored");
+            let response = PutDeployResponse::Accepted {
+                api_version,
+                deploy_hash,
+            };
+            (StatusCode::OK, response)
+        }
+        Some(Err(error)) => {
+            info!(
+                %deploy_hash,
+                %error,
+                "the deploy submitted to event stream server was invalid",
+            );
+            let response = PutDeployResponse::Rejected {
+                deploy_hash,
+                reason: error.to_string(),
+            };
+            (StatusCode::BAD_REQUEST, response)
+        }
+        None => {
+            return create_response(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "node is not yet accepting deploys\n",
+            )
+        }
+    };
+    reply::with_status(reply::json(&response), status).into_response()
+}
+
+/// Converts a rejection of a deploy submission into the appropriate error response.
+async fn handle_rejection(rejection: Rejection) -> Result<Response, Infallible> {
+    let response = if rejection.find::<Unauthorized>().is_some() {
+        create_response(
+            StatusCode::UNAUTHORIZED,
+            format!("missing or invalid '{}' header\n", SECRET_HEADER),
+        )
+    } else if rejection.find::<PayloadTooLarge>().is_some() {
+        create_response(StatusCode::PAYLOAD_TOO_LARGE, "deploy is too large\n")
+    } else if rejection.find::<LengthRequired>().is_some() {
+        create_response(StatusCode::LENGTH_REQUIRED, "content length is required\n")
+    } else {
+        create_response(StatusCode::BAD_REQUEST, "invalid deploy submission\n")
+    };
+    Ok(response)
+}
+
+fn create_response<T: Into<Body>>(status: StatusCode, body: T) -> Response {
+    let mut response = Response::new(body.into());
+    *response.status_mut() = status;
+    response
+}
@@ -2,18 +2,53 @@ use std::fmt::{self, Display, Formatter};
 
 use casper_types::{EraId, ExecutionEffect, ExecutionResult, PublicKey};
 
-use crate::types::{Block, BlockHash, DeployHash, DeployHeader, FinalitySignature, Timestamp};
+use crate::{
+    types::{
+        Block, BlockHash, Deploy, DeployHash, DeployHeader, FinalitySignature, NodeId, Timestamp,
+    },
+    utils::Source,
+};
+
+/// A summary of the header of a deploy newly accepted by this node.
+#[derive(Debug)]
+pub struct DeployHeaderSummary {
+    pub deploy_hash: DeployHash,
+    pub account: PublicKey,
+    pub timestamp: Timestamp,
+}
+
+impl From<&Deploy> for DeployHeaderSummary {
+    fn from(deploy: &Deploy) -> Self {
+        DeployHeaderSummary {
+            deploy_hash: *deploy.id(),
+            account: deploy.header().account().clone(),
+            timestamp: deploy.header().timestamp(),
+        }
+    }
+}
 
 #[derive(Debug)]
 pub enum Event {
     BlockAdded(Box<Block>),
-    DeployAccepted(DeployHash),
+    DeployAccepted(Box<DeployHeaderSummary>),
+    DeployRejected {
+        deploy_hash: DeployHash,
+        reason: String,
+        source: Source<NodeId>,
+    },
     DeployProcessed {
         deploy_hash: DeployHash,
         deploy_header: Box<DeployHeader>,
         block_hash: BlockHash,
         execution_result: Box<ExecutionResult>,
     },
+    DeployReplaced {
+        replaced: DeployHash,
+        replacement: DeployHash,
+    },
+    DeployEvicted {
+        deploy_hash: DeployHash,
+    },
     Fault {
         era_id: EraId,
         public_key: PublicKey,
@@ -30,12 +65,30 @@ impl Display for Event {
     fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
         match self {
             Event::BlockAdded(block) => write!(formatter, "block added {}", block.hash()),
-            Event::DeployAccepted(deploy_hash) => {
-                write!(formatter, "deploy accepted {}", deploy_hash)
-            }
+            Event::DeployAccepted(summary) => write!(
+                formatter,
+                "deploy accepted {} from account {} created at {}",
+                summary.deploy_hash, summary.account, summary.timestamp
+            ),
+            Event::DeployRejected {
+                deploy_hash,
+                reason,
+                source,
+            } => write!(
+                formatter,
+                "deploy rejected {} from {}: {}",
+                deploy_hash, source, reason
+            ),
             Event::DeployProcessed { deploy_hash, .. } => {
                 write!(formatter, "deploy processed {}", deploy_hash)
             }
+            Event::DeployReplaced {
+                replaced,
+                replacement,
+            } => write!(formatter, "deploy {} replaced by {}", replaced, replacement),
+            Event::DeployEvicted { deploy_hash } => {
+                write!(formatter, "deploy evicted {}", deploy_hash)
+            }
             Event::Fault {
                 era_id,
                 public_key,
@@ -0,0 +1,463 @@
+//! The buffer of recent events which are replayed to clients subscribing from a given event ID.
+//!
+//! The buffer holds at most `event_stream_buffer_length` events across all event streams.  Since
+//! events vary enormously in size (a `Step` event can hold the full execution effects of an era's
+//! auction, while a `FinalitySignature` is a few hundred bytes), each stream also has a budget for
+//! the estimated size of its buffered events, and its oldest events are evicted once that budget is
+//! exceeded.
+//!
+//! The buffer doesn't survive a restart of the node, so events from before the buffer was created
+//! are treated as having been skipped too.
+
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    iter,
+};
+
+use prometheus::Registry;
+use tracing::{debug, error};
+
+use super::{
+    metrics::ChannelMetrics,
+    sse_server::{
+        EventFilter, Id, ServerSentEvent, DEPLOYS_FILTER, SIGNATURES_FILTER, SSE_API_DEPLOYS_PATH,
+        SSE_API_MAIN_PATH, SSE_API_SIGNATURES_PATH,
+    },
+    Config,
+};
+
+/// The event stream on which a buffered event is served.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Channel {
+    Main,
+    Deploys,
+    Signatures,
+}
+
+impl From<EventFilter> for Channel {
+    fn from(event_kind: EventFilter) -> Self {
+        if DEPLOYS_FILTER.contains(&event_kind) {
+            Channel::Deploys
+        } else if SIGNATURES_FILTER.contains(&event_kind) {
+            Channel::Signatures
+        } else {
+            Channel::Main
+        }
+    }
+}
+
+/// The buffered events of a single event stream.
+#[derive(Debug)]
+struct ChannelBuffer {
+    /// The sequence numbers and estimated sizes of the buffered events, oldest first.
+    entries: VecDeque<(u64, usize)>,
+    /// The total estimated size of the buffered events.
+    size: usize,
+    /// The maximum total estimated size of the buffered events.
+    max_size: usize,
+    metrics: ChannelMetrics,
+}
+
+impl ChannelBuffer {
+    fn new(max_size: u32, channel: &str, registry: &Registry) -> Result<Self, prometheus::Error> {
+        Ok(ChannelBuffer {
+            entries: VecDeque::new(),
+            size: 0,
+            max_size: max_size as usize,
+            metrics: ChannelMetrics::new(channel, registry)?,
+        })
+    }
+
+    fn push(&mut self, sequence_number: u64, size: usize) {
+        self.entries.push_back((sequence_number, size));
+        self.size += size;
+        self.metrics.buffer_bytes.set(self.size as i64);
+    }
+
+    /// Removes the oldest entry, returning its sequence number.
+    fn pop_oldest(&mut self) -> Option<u64> {
+        let (sequence_number, size) = self.entries.pop_front()?;
+        self.size -= size;
+        self.metrics.buffer_bytes.set(self.size as i64);
+        Some(sequence_number)
+    }
+
+    fn is_over_budget(&self) -> bool {
+        self.size > self.max_size
+    }
+}
+
+/// The buffer of recent events.
+#[derive(Debug)]
+pub(super) struct EventBuffer {
+    /// The buffered events along with their kinds, keyed by the order in which they were pushed.
+    events: BTreeMap<u64, (EventFilter, ServerSentEvent)>,
+    /// The sequence number to assign to the next pushed event.
+    next_sequence_number: u64,
+    /// The maximum number of buffered events across all event streams.
+    max_length: usize,
+    main: ChannelBuffer,
+    deploys: ChannelBuffer,
+    signatures: ChannelBuffer,
+    /// The ID of the newest event of each kind which was evicted to keep within its event stream's
+    /// byte budget.
+    newest_evicted: HashMap<EventFilter, Id>,
+    /// The ID of the first event emitted since the node started.  Events with earlier IDs were
+    /// never buffered.
+    first_id: Id,
+}
+
+impl EventBuffer {
+    pub(super) fn new(
+        config: &Config,
+        first_id: Id,
+        registry: &Registry,
+    ) -> Result<Self, prometheus::Error> {
+        Ok(EventBuffer {
+            events: BTreeMap::new(),
+            next_sequence_number: 0,
+            max_length: config.event_stream_buffer_length as usize,
+            main: ChannelBuffer::new(config.max_main_buffer_bytes, SSE_API_MAIN_PATH, registry)?,
+            deploys: ChannelBuffer::new(
+                config.max_deploys_buffer_bytes,
+                SSE_API_DEPLOYS_PATH,
+                registry,
+            )?,
+            signatures: ChannelBuffer::new(
+                config.max_sigs_buffer_bytes,
+                SSE_API_SIGNATURES_PATH,
+                registry,
+            )?,
+            newest_evicted: HashMap::new(),
+            first_id,
+        })
+    }
+
+    /// Buffers the event, evicting the oldest event if the buffer is full, and then as many of the
+    /// oldest events of the same event stream as needed to keep within that stream's byte budget.
+    ///
+    /// An event which exceeds the byte budget on its own is evicted immediately.  Only evictions
+    /// due to the byte budget are recorded as skipped events, as clients have always been
+    /// expected to handle the buffer advancing past the requested starting event when full.
+    pub(super) fn push(&mut self, event: ServerSentEvent) {
+        let event_kind = match (event.id, event.data.event_kind()) {
+            (Some(_), Some(event_kind)) => event_kind,
+            _ => {
+                error!(
+                    ?event,
+                    "only events with an ID and a kind should be buffered"
+                );
+                return;
+            }
+        };
+        let channel = Channel::from(event_kind);
+        let size = event.data.estimated_size();
+        let sequence_number = self.next_sequence_number;
+        self.next_sequence_number += 1;
+        let _ = self.events.insert(sequence_number, (event_kind, event));
+        self.channel_mut(channel).push(sequence_number, size);
+
+        if self.events.len() > self.max_length {
+            let oldest_event_kind = self.events.values().next().map(|(kind, _)| *kind);
+            if let Some(sequence_number) = oldest_event_kind
+                .and_then(|kind| self.channel_mut(Channel::from(kind)).pop_oldest())
+            {
+                let _ = self.events.remove(&sequence_number);
+            }
+        }
+
+        while self.channel_mut(channel).is_over_budget() {
+            let sequence_number = match self.channel_mut(channel).pop_oldest() {
+                Some(sequence_number) => sequence_number,
+                None => break,
+            };
+            if let Some((kind, evicted)) = self.events.remove(&sequence_number) {
+                let id = evicted.id.unwrap_or_default();
+                debug!(event_id = %id, ?kind, "evicted event from event stream buffer");
+                let _ = self.newest_evicted.insert(kind, id);
+                self.channel_mut(channel).metrics.evicted_events.inc();
+            }
+        }
+    }
+
+    /// Returns the buffered events from `start_from` onwards, oldest first.
+    pub(super) fn events_from(&self, start_from: Id) -> impl Iterator<Item = &ServerSentEvent> {
+        // If the buffer's first event ID is in the range [0, buffer size) or
+        // (Id::MAX - buffer size, Id::MAX], then the events in the buffer are considered to have
+        // their IDs wrapping round, or that was recently the case.  In this case, we add
+        // `buffer_size` to `start_from` and the buffered events' IDs when considering which events
+        // to include, effectively shifting all the IDs past the wrapping transition.
+        let buffer_size = self.max_length as Id;
+        let in_wraparound_zone = self
+            .events
+            .values()
+            .next()
+            .and_then(|(_, event)| event.id)
+            .map(|id| id > Id::MAX - buffer_size || id < buffer_size)
+            .unwrap_or_default();
+        self.events
+            .values()
+            .map(|(_, event)| event)
+            .skip_while(move |event| {
+                let id = event.id.unwrap_or_default();
+                if in_wraparound_zone {
+                    id.wrapping_add(buffer_size) < start_from.wrapping_add(buffer_size)
+                } else {
+                    id < start_from
+                }
+            })
+    }
+
+    /// Returns the ID of the newest event of any of the kinds in `event_filter` which was evicted
+    /// to keep within its event stream's byte budget, or else the ID of the last event emitted
+    /// before the node started, if that ID is not before `start_from`.
+    ///
+    /// In other words, returns `Some` if a client subscribing from `start_from` would otherwise
+    /// silently miss some of the events it requested.
+    pub(super) fn last_skipped_id(
+        &self,
+        start_from: Id,
+        event_filter: &[EventFilter],
+    ) -> Option<Id> {
+        let last_id_before_start = self.first_id.wrapping_sub(1);
+        event_filter
+            .iter()
+            .filter_map(|event_kind| self.newest_evicted.get(event_kind))
+            .copied()
+            .chain(iter::once(last_id_before_start))
+            .filter(|id| id.wrapping_sub(start_from) <= Id::MAX / 2)
+            .max_by_key(|id| id.wrapping_sub(start_from))
+    }
+
+    fn channel_mut(&mut self, channel: Channel) -> &mut ChannelBuffer {
+        match channel {
+            Channel::Main => &mut self.main,
+            Channel::Deploys => &mut self.deploys,
+            Channel::Signatures => &mut self.signatures,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use casper_types::{EraId, ExecutionEffect, Transform, TransformEntry};
+
+    use super::*;
+    use crate::{
+        components::event_stream_server::{sse_server::MAIN_FILTER, SseData},
+        testing::TestRng,
+    };
+
+    const MAX_MAIN_BUFFER_BYTES: u32 = 64 * 1024;
+
+    fn new_buffer(config: Config) -> EventBuffer {
+        EventBuffer::new(&config, 0, &Registry::new()).unwrap()
+    }
+
+    /// Returns a `Step` event whose execution effect holds `transform_count` transforms of roughly
+    /// 100 bytes each.
+    fn step_event(rng: &mut TestRng, transform_count: usize) -> SseData {
+        let transforms = (0..transform_count)
+            .map(|index| TransformEntry {
+                key: format!("key-{}", index),
+                transform: Transform::Failure("x".repeat(100)),
+            })
+            .collect();
+        SseData::step(
+            EraId::new(rng.gen()),
+            ExecutionEffect::new(Vec::new(), transforms),
+        )
+    }
+
+    fn buffered_ids(buffer: &EventBuffer) -> Vec<Id> {
+        buffer
+            .events_from(0)
+            .map(|event| event.id.unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn should_respect_byte_budget_with_tiny_and_huge_events() {
+        let mut rng = crate::new_rng();
+        let mut buffer = new_buffer(Config {
+            max_main_buffer_bytes: MAX_MAIN_BUFFER_BYTES,
+            ..Default::default()
+        });
+
+        let huge_event_size = step_event(&mut rng, 200).estimated_size();
+        assert!(huge_event_size > MAX_MAIN_BUFFER_BYTES as usize / 4);
+
+        for id in 0..200 {
+            let data = if id % 10 == 0 {
+                step_event(&mut rng, 200)
+            } else {
+                SseData::random_fault(&mut rng)
+            };
+            buffer.push(ServerSentEvent { id: Some(id), data });
+
+            assert!(buffer.main.size <= MAX_MAIN_BUFFER_BYTES as usize);
+            assert_eq!(
+                buffer.main.metrics.buffer_bytes.get(),
+                buffer.main.size as i64
+            );
+            let ids = buffered_ids(&buffer);
+            assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+            assert_eq!(ids.last(), Some(&id));
+        }
+
+        // Far fewer than the maximum number of events should remain buffered, and the rest should
+        // have been evicted.
+        let buffered_count = buffered_ids(&buffer).len();
+        assert!(buffered_count < 200);
+        assert_eq!(
+            buffer.main.metrics.evicted_events.get(),
+            200 - buffered_count as u64
+        );
+
+        // A client starting from the first event should be told events were skipped, unless it
+        // only subscribed to signatures.
+        let oldest_buffered_id = buffered_ids(&buffer)[0];
+        assert_eq!(
+            buffer.last_skipped_id(0, &MAIN_FILTER),
+            Some(oldest_buffered_id - 1)
+        );
+        assert_eq!(
+            buffer.last_skipped_id(oldest_buffered_id, &MAIN_FILTER),
+            None
+        );
+        assert_eq!(buffer.last_skipped_id(0, &SIGNATURES_FILTER), None);
+    }
+
+    #[test]
+    fn should_evict_event_exceeding_byte_budget_on_its_own() {
+        let mut rng = crate::new_rng();
+        let mut buffer = new_buffer(Config {
+            max_main_buffer_bytes: MAX_MAIN_BUFFER_BYTES,
+            ..Default::default()
+        });
+
+        buffer.push(ServerSentEvent {
+            id: Some(0),
+            data: SseData::random_fault(&mut rng),
+        });
+        buffer.push(ServerSentEvent {
+            id: Some(1),
+            data: step_event(&mut rng, 2000),
+        });
+        buffer.push(ServerSentEvent {
+            id: Some(2),
+            data: SseData::random_fault(&mut rng),
+        });
+
+        // The huge event is evicted, along with the older event, which was evicted first.
+        assert_eq!(buffered_ids(&buffer), vec![2]);
+        assert_eq!(buffer.main.metrics.evicted_events.get(), 2);
+        assert_eq!(buffer.last_skipped_id(0, &MAIN_FILTER), Some(1));
+        assert_eq!(buffer.last_skipped_id(2, &MAIN_FILTER), None);
+    }
+
+    #[test]
+    fn should_only_evict_events_of_stream_over_byte_budget() {
+        let mut rng = crate::new_rng();
+        let mut buffer = new_buffer(Config {
+            max_main_buffer_bytes: MAX_MAIN_BUFFER_BYTES,
+            ..Default::default()
+        });
+
+        for id in 0..10 {
+            buffer.push(ServerSentEvent {
+                id: Some(id * 2),
+                data: SseData::random_finality_signature(&mut rng),
+            });
+            buffer.push(ServerSentEvent {
+                id: Some(id * 2 + 1),
+                data: step_event(&mut rng, 200),
+            });
+        }
+
+        // All the signatures are still buffered.
+        let signature_count = buffer
+            .events_from(0)
+            .filter(|event| event.data.should_include(&SIGNATURES_FILTER))
+            .count();
+        assert_eq!(signature_count, 10);
+        assert_eq!(buffer.signatures.metrics.evicted_events.get(), 0);
+        assert!(buffer.main.metrics.evicted_events.get() > 0);
+        assert_eq!(buffer.last_skipped_id(0, &SIGNATURES_FILTER), None);
+    }
+
+    #[test]
+    fn should_not_record_skipped_events_when_evicting_due_to_length() {
+        let mut rng = crate::new_rng();
+        let mut buffer = new_buffer(Config {
+            event_stream_buffer_length: 5,
+            ..Default::default()
+        });
+
+        for id in 0..10 {
+            buffer.push(ServerSentEvent {
+                id: Some(id),
+                data: SseData::random_fault(&mut rng),
+            });
+        }
+
+        assert_eq!(buffered_ids(&buffer), vec![5, 6, 7, 8, 9]);
+        assert_eq!(buffer.main.entries.len(), 5);
+        assert_eq!(buffer.main.metrics.evicted_events.get(), 0);
+        assert_eq!(buffer.last_skipped_id(0, &MAIN_FILTER), None);
+    }
+
+    #[test]
+    fn should_handle_skipped_ids_wrapping_past_max_event_id() {
+        let mut rng = crate::new_rng();
+        let mut buffer = new_buffer(Config {
+            max_main_buffer_bytes: MAX_MAIN_BUFFER_BYTES,
+            ..Default::default()
+        });
+
+        buffer.push(ServerSentEvent {
+            id: Some(Id::MAX),
+            data: SseData::random_fault(&mut rng),
+        });
+        buffer.push(ServerSentEvent {
+            id: Some(0),
+            data: step_event(&mut rng, 2000),
+        });
+        buffer.push(ServerSentEvent {
+            id: Some(1),
+            data: SseData::random_fault(&mut rng),
+        });
+
+        assert_eq!(buffered_ids(&buffer), vec![1]);
+        assert_eq!(buffer.last_skipped_id(Id::MAX, &MAIN_FILTER), Some(0));
+        assert_eq!(buffer.last_skipped_id(1, &MAIN_FILTER), None);
+    }
+    #[test]
+    fn should_skip_events_from_before_first_id() {
+        let mut rng = crate::new_rng();
+        let mut buffer = EventBuffer::new(&Config::default(), 100, &Registry::new()).unwrap();
+
+        for id in 100..105 {
+            buffer.push(ServerSentEvent {
+                id: Some(id),
+                data: SseData::random_fault(&mut rng),
+            });
+        }
+
+        // Clients starting from before the first ID are told all earlier events were skipped,
+        // whichever kinds they subscribed to.
+        assert_eq!(buffer.last_skipped_id(50, &MAIN_FILTER), Some(99));
+        assert_eq!(buffer.last_skipped_id(99, &SIGNATURES_FILTER), Some(99));
+        assert_eq!(buffer.last_skipped_id(100, &MAIN_FILTER), None);
+        assert_eq!(buffer.last_skipped_id(103, &MAIN_FILTER), None);
+        // Starting from a future event skips nothing.
+        assert_eq!(buffer.last_skipped_id(200, &MAIN_FILTER), None);
+
+        // The first ID may follow a wrap past the maximum event ID.
+        let buffer = EventBuffer::new(&Config::default(), 2, &Registry::new()).unwrap();
+        assert_eq!(buffer.last_skipped_id(Id::MAX - 1, &MAIN_FILTER), Some(1));
+        assert_eq!(buffer.last_skipped_id(2, &MAIN_FILTER), None);
+    }
+}
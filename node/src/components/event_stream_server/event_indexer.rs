@@ -1,15 +1,28 @@
+//! The source of the IDs assigned to events on the event stream.
+//!
+//! All event streams share a single sequence of IDs, which is persisted to a cache file so that a
+//! restarted node continues the sequence rather than starting again from zero.  As well as writing
+//! the next index when the indexer is dropped, the indexer reserves a block of indices in the cache
+//! file ahead of assigning them, so that even if the node stops without the indexer being dropped,
+//! the next session never reuses an ID already given to an event.
+
 use std::{fs, path::PathBuf};
 
 use datasize::DataSize;
 use tracing::{debug, warn};
 
 const CACHE_FILENAME: &str = "sse_index";
+/// The number of indices reserved in the cache file at a time.
+const RESERVATION_SIZE: EventIndex = 1_000;
 
 pub(super) type EventIndex = u32;
 
 #[derive(Debug, DataSize)]
 pub(super) struct EventIndexer {
     index: EventIndex,
+    /// The index at which the current reservation ends, and a new one must be written to the
+    /// cache.
+    reserved_until: EventIndex,
     persistent_cache: PathBuf,
 }
 
@@ -43,27 +56,38 @@ impl EventIndexer {
         let index = EventIndex::from_le_bytes(bytes);
         debug!(%index, "initialized sse index");
 
-        EventIndexer {
+        let mut event_indexer = EventIndexer {
             index,
+            reserved_until: index,
             persistent_cache,
-        }
+        };
+        event_indexer.reserve();
+        event_indexer
     }
 
     pub(super) fn next_index(&mut self) -> EventIndex {
         let index = self.index;
         self.index = index.wrapping_add(1);
+        if self.index == self.reserved_until {
+            self.reserve();
+        }
         index
     }
 
-    #[cfg(test)]
+    /// Returns the index which will be assigned to the next event.
     pub(super) fn current_index(&self) -> EventIndex {
         self.index
     }
-}
 
-impl Drop for EventIndexer {
-    fn drop(&mut self) {
-        match fs::write(&self.persistent_cache, self.index.to_le_bytes()) {
+    /// Reserves the next `RESERVATION_SIZE` indices by writing the end of the reservation to the
+    /// cache file.
+    fn reserve(&mut self) {
+        self.reserved_until = self.index.wrapping_add(RESERVATION_SIZE);
+        self.write_cache(self.reserved_until);
+    }
+
+    fn write_cache(&self, index: EventIndex) {
+        match fs::write(&self.persistent_cache, index.to_le_bytes()) {
             Err(error) => warn!(
                 file = %self.persistent_cache.display(),
                 %error,
@@ -71,16 +95,22 @@ impl Drop for EventIndexer {
             ),
             Ok(_) => debug!(
                 file = %self.persistent_cache.display(),
-                index = %self.index,
+                %index,
                 "cached sse index to file"
             ),
         }
     }
 }
 
+impl Drop for EventIndexer {
+    fn drop(&mut self) {
+        self.write_cache(self.index);
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use std::iter;
+    use std::{iter, mem};
 
     use super::*;
     use crate::logging;
@@ -109,6 +139,24 @@ mod tests {
         }
     }
 
+    #[test]
+    fn should_not_reuse_indices_after_unclean_shutdown() {
+        let _ = logging::init();
+        let tempdir = tempfile::tempdir().unwrap();
+
+        // Assign indices into the second reservation, then skip the indexer's `Drop` impl as if the
+        // node had crashed.
+        let mut event_indexer = EventIndexer::new(tempdir.path().to_path_buf());
+        for i in 0..(RESERVATION_SIZE + 5) {
+            assert_eq!(event_indexer.next_index(), i);
+        }
+        mem::forget(event_indexer);
+
+        // The next session should continue from the end of the second reservation.
+        let mut event_indexer = EventIndexer::new(tempdir.path().to_path_buf());
+        assert_eq!(event_indexer.next_index(), 2 * RESERVATION_SIZE);
+    }
+
     #[test]
     fn should_wrap() {
         let _ = logging::init();
@@ -1,86 +1,87 @@
-use futures::{future, Future, FutureExt};
+use futures::{
+    future::{self, Either},
+    Future, FutureExt,
+};
 use tokio::{
     select,
     sync::{broadcast, mpsc, oneshot},
     task,
 };
-use tracing::{info, trace};
-use wheelbuf::WheelBuf;
+use tracing::{debug, info, trace};
+use uuid::Uuid;
 
 use casper_types::ProtocolVersion;
 
 use super::{
-    sse_server::{BroadcastChannelMessage, Id, NewSubscriberInfo, ServerSentEvent},
-    Config, EventIndex, SseData,
+    event_buffer::EventBuffer,
+    sse_server::{BroadcastChannelMessage, NewSubscriberInfo, ServerSentEvent},
+    EventIndex, SseData,
 };
 
 /// Run the HTTP server.
 ///
+/// * `buffer` holds the recent events, to be provided to newly-subscribed clients on request.
+/// * `stream_instance_id` is the random ID of this run of the server, sent to each new client.
 /// * `server_with_shutdown` is the actual server as a future which can be gracefully shut down.
 /// * `server_shutdown_sender` is the channel by which the server will be notified to shut down.
 /// * `data_receiver` will provide the server with local events which should then be sent to all
 ///   subscribed clients.
+/// * `shutdown_receiver` will provide the reason for the node shutting down, to be sent to all
+///   subscribed clients as the final event before the server stops.
 /// * `broadcaster` is used by the server to send events to each subscribed client after receiving
 ///   them via the `data_receiver`.
 /// * `new_subscriber_info_receiver` is used to notify the server of the details of a new client
 ///   having subscribed to the event stream.  It allows the server to populate that client's stream
 ///   with the requested number of historical events.
+#[allow(clippy::too_many_arguments)]
 pub(super) async fn run(
-    config: Config,
+    mut buffer: EventBuffer,
     api_version: ProtocolVersion,
+    stream_instance_id: Uuid,
     server_with_shutdown: impl Future<Output = ()> + Send + 'static,
     server_shutdown_sender: oneshot::Sender<()>,
     mut data_receiver: mpsc::UnboundedReceiver<(EventIndex, SseData)>,
+    mut shutdown_receiver: oneshot::Receiver<String>,
     broadcaster: broadcast::Sender<BroadcastChannelMessage>,
     mut new_subscriber_info_receiver: mpsc::UnboundedReceiver<NewSubscriberInfo>,
 ) {
     let server_joiner = task::spawn(server_with_shutdown);
 
-    // Initialize the index and buffer for the SSEs.
-    let mut buffer = WheelBuf::new(vec![
-        ServerSentEvent::initial_event(api_version);
-        config.event_stream_buffer_length as usize
-    ]);
-
-    // Start handling received messages from the two channels; info on new client subscribers and
-    // incoming events announced by node components.
+    // Start handling received messages from the three channels; info on new client subscribers,
+    // incoming events announced by node components and the reason for shutting down.  The latter
+    // is only handled once all events received before it have been broadcast.
     let event_stream_fut = async {
         loop {
             select! {
+                biased;
+
                 maybe_new_subscriber = new_subscriber_info_receiver.recv() => {
                     if let Some(subscriber) = maybe_new_subscriber {
-                        // First send the client the `ApiVersion` event.  We don't care if this
-                        // errors - the client may have disconnected already.
+                        // First send the client the `ApiVersion` and `StreamInstance` events.  We
+                        // don't care if this errors - the client may have disconnected already.
                         let _ = subscriber
                             .initial_events_sender
                             .send(ServerSentEvent::initial_event(api_version));
+                        let _ = subscriber
+                            .initial_events_sender
+                            .send(ServerSentEvent::stream_instance_event(stream_instance_id));
                         // If the client supplied a "start_from" index, provide the buffered events.
                         // If they requested more than is buffered, just provide the whole buffer.
                         if let Some(start_index) = subscriber.start_from {
-                            // If the buffer's first event ID is in the range [0, buffer size) or
-                            // (Id::MAX - buffer size, Id::MAX], then the events in the buffer are
-                            // considered to have their IDs wrapping round, or that was recently the
-                            // case.  In this case, we add `buffer.capacity()` to `start_index` and
-                            // the buffered events' IDs when considering which events to include in
-                            // the requested initial events, effectively shifting all the IDs past
-                            // the wrapping transition.
-                            let buffer_size = buffer.capacity() as Id;
-                            let in_wraparound_zone = buffer
-                                .iter()
-                                .next()
-                                .map(|event| {
-                                    let id = event.id.unwrap();
-                                    id > Id::MAX - buffer_size || id < buffer_size
-                                })
-                                .unwrap_or_default();
-                            for event in buffer.iter().skip_while(|event| {
-                                if in_wraparound_zone {
-                                    event.id.unwrap().wrapping_add(buffer_size)
-                                        < start_index.wrapping_add(buffer_size)
-                                } else {
-                                    event.id.unwrap() < start_index
-                                }
-                            }) {
+                            // If any of the requested events were evicted to keep within the
+                            // buffer's byte budget or were emitted before the node started, tell
+                            // the client before providing the rest.
+                            if let Some(last_skipped_id) =
+                                buffer.last_skipped_id(start_index, &subscriber.event_filter)
+                            {
+                                debug!(%start_index, %last_skipped_id, "skipping evicted events");
+                                let skipped_event = ServerSentEvent {
+                                    id: None,
+                                    data: SseData::EventsSkipped { last_skipped_id },
+                                };
+                                let _ = subscriber.initial_events_sender.send(skipped_event);
+                            }
+                            for event in buffer.events_from(start_index) {
                                 // As per sending `SSE_INITIAL_EVENT`, we don't care if this errors.
                                 let _ = subscriber.initial_events_sender.send(event.clone());
                             }
@@ -107,17 +108,40 @@ pub(super) async fn run(
                         }
                     }
                 }
+
+                maybe_reason = &mut shutdown_receiver => {
+                    // If the shutdown sender was dropped rather than used, the server is being
+                    // dropped too, so just exit the loop.
+                    if let Ok(reason) = maybe_reason {
+                        debug!(%reason, "sending shutdown event to clients");
+                        let event = ServerSentEvent {
+                            id: None,
+                            data: SseData::Shutdown { reason },
+                        };
+                        let _ = broadcaster.send(BroadcastChannelMessage::ServerSentEvent(event));
+                    }
+                    info!("shutting down HTTP server");
+                    break;
+                }
             }
         }
     };
 
-    // Wait for the event stream future to exit, which will only happen if the last `data_sender`
-    // paired with `data_receiver` is dropped.  `server_joiner` will never return here.
-    let _ = future::select(server_joiner, event_stream_fut.boxed()).await;
+    // Wait for the event stream future to exit, which will only happen if the node is shutting
+    // down, or if the last `data_sender` paired with `data_receiver` is dropped.  `server_joiner`
+    // will never return here.
+    let maybe_server_joiner = match future::select(server_joiner, event_stream_fut.boxed()).await {
+        Either::Left(_) => None,
+        Either::Right((_, server_joiner)) => Some(server_joiner),
+    };
 
-    // Kill the event-stream handlers, and shut down the server.
+    // Kill the event-stream handlers, and shut down the server, waiting for it to finish serving
+    // the final events to the clients.
     let _ = broadcaster.send(BroadcastChannelMessage::Shutdown);
     let _ = server_shutdown_sender.send(());
+    if let Some(server_joiner) = maybe_server_joiner {
+        let _ = server_joiner.await;
+    }
 
     trace!("Event stream server stopped");
 }
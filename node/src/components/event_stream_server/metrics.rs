@@ -0,0 +1,49 @@
+use prometheus::{IntCounter, IntGauge, Opts, Registry};
+
+use crate::unregister_metric;
+
+/// Metrics for the buffered events of a single event stream.
+#[derive(Debug)]
+pub(super) struct ChannelMetrics {
+    /// Estimated size in bytes of the buffered events.
+    pub(super) buffer_bytes: IntGauge,
+    /// Number of events evicted from the buffer to keep within its byte budget.
+    pub(super) evicted_events: IntCounter,
+    /// Registry stored to allow deregistration later.
+    registry: Registry,
+}
+
+impl ChannelMetrics {
+    /// Creates a new instance of the metrics for the event stream at `/events/<channel>`.
+    pub(super) fn new(channel: &str, registry: &Registry) -> Result<Self, prometheus::Error> {
+        let buffer_bytes = IntGauge::with_opts(
+            Opts::new(
+                "event_stream_buffer_bytes",
+                "estimated size in bytes of the buffered events of an event stream",
+            )
+            .const_label("channel", channel),
+        )?;
+        registry.register(Box::new(buffer_bytes.clone()))?;
+        let evicted_events = IntCounter::with_opts(
+            Opts::new(
+                "event_stream_buffer_evicted_events",
+                "number of events of an event stream evicted from the buffer to keep within its \
+                 byte budget",
+            )
+            .const_label("channel", channel),
+        )?;
+        registry.register(Box::new(evicted_events.clone()))?;
+        Ok(ChannelMetrics {
+            buffer_bytes,
+            evicted_events,
+            registry: registry.clone(),
+        })
+    }
+}
+
+impl Drop for ChannelMetrics {
+    fn drop(&mut self) {
+        unregister_metric!(self.registry, self.buffer_bytes);
+        unregister_metric!(self.registry, self.evicted_events);
+    }
+}
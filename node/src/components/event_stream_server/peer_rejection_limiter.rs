@@ -0,0 +1,77 @@
+//! Limits the rate at which rejections of deploys received from peers are published, since a peer
+//! could otherwise cause the node to flood its event stream subscribers by gossiping invalid
+//! deploys.
+
+use std::time::{Duration, Instant};
+
+use datasize::DataSize;
+
+/// The period over which the number of published peer rejections is limited.
+const WINDOW: Duration = Duration::from_secs(1);
+
+#[derive(Debug, DataSize)]
+pub(super) struct PeerRejectionLimiter {
+    /// The maximum number of peer rejections to publish per `WINDOW`.
+    max_per_window: u32,
+    /// The start of the current window.
+    #[data_size(skip)]
+    window_start: Instant,
+    /// The number of peer rejections published in the current window.
+    count_in_window: u32,
+}
+
+impl PeerRejectionLimiter {
+    pub(super) fn new(max_per_second: u32) -> Self {
+        PeerRejectionLimiter {
+            max_per_window: max_per_second,
+            window_start: Instant::now(),
+            count_in_window: 0,
+        }
+    }
+
+    /// Returns whether a peer rejection occurring at `now` should be published, counting it
+    /// towards the limit if so.
+    pub(super) fn should_publish(&mut self, now: Instant) -> bool {
+        if now.saturating_duration_since(self.window_start) >= WINDOW {
+            self.window_start = now;
+            self.count_in_window = 0;
+        }
+        if self.count_in_window >= self.max_per_window {
+            return false;
+        }
+        self.count_in_window += 1;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_limit_rejections_per_window() {
+        let start = Instant::now();
+        let mut limiter = PeerRejectionLimiter::new(3);
+        limiter.window_start = start;
+
+        for _ in 0..3 {
+            assert!(limiter.should_publish(start));
+        }
+        assert!(!limiter.should_publish(start));
+        assert!(!limiter.should_publish(start + WINDOW / 2));
+
+        // A new window resets the count.
+        assert!(limiter.should_publish(start + WINDOW));
+        assert!(limiter.should_publish(start + WINDOW));
+        assert!(limiter.should_publish(start + WINDOW));
+        assert!(!limiter.should_publish(start + WINDOW));
+    }
+
+    #[test]
+    fn should_publish_nothing_if_limit_is_zero() {
+        let start = Instant::now();
+        let mut limiter = PeerRejectionLimiter::new(0);
+        assert!(!limiter.should_publish(start));
+        assert!(!limiter.should_publish(start + WINDOW));
+    }
+}
@@ -2,6 +2,7 @@
 
 use std::{
     collections::{HashMap, HashSet},
+    mem,
     sync::{Arc, RwLock},
     time::Duration,
 };
@@ -25,6 +26,7 @@ use tokio_stream::wrappers::{
     errors::BroadcastStreamRecvError, BroadcastStream, UnboundedReceiverStream,
 };
 use tracing::{debug, error, info, warn};
+use uuid::Uuid;
 use warp::{
     filters::BoxedFilter,
     path,
@@ -34,38 +36,53 @@ use warp::{
     Filter, Reply,
 };
 
-use casper_types::{EraId, ExecutionEffect, ExecutionResult, ProtocolVersion, PublicKey};
+use casper_types::{
+    bytesrepr::ToBytes, EraId, ExecutionEffect, ExecutionResult, ProtocolVersion, PublicKey,
+};
 
 use super::DeployGetter;
-use crate::types::{
-    BlockHash, Deploy, DeployHash, FinalitySignature, JsonBlock, TimeDiff, Timestamp,
+use crate::{
+    crypto::hash::{self, Digest},
+    types::{
+        BlockHash, Deploy, DeployHash, DeployHeader, FinalitySignature, JsonBlock, TimeDiff,
+        Timestamp,
+    },
+    utils::Source,
 };
 #[cfg(test)]
 use crate::{crypto::AsymmetricKeyExt, testing::TestRng, types::Block};
 
 /// The URL root path.
 pub const SSE_API_ROOT_PATH: &str = "events";
-/// The URL path part to subscribe to all events other than `DeployAccepted`s and
-/// `FinalitySignature`s.
+/// The URL path part to subscribe to all events other than `DeployAccepted`s, `DeployRejected`s,
+/// `DeployReplaced`s, `DeployEvicted`s and `FinalitySignature`s.
 pub const SSE_API_MAIN_PATH: &str = "main";
-/// The URL path part to subscribe to only `DeployAccepted` events.
+/// The URL path part to subscribe to only `DeployAccepted`, `DeployRejected`, `DeployReplaced` and
+/// `DeployEvicted` events.
 pub const SSE_API_DEPLOYS_PATH: &str = "deploys";
 /// The URL path part to subscribe to only `FinalitySignature` events.
 pub const SSE_API_SIGNATURES_PATH: &str = "sigs";
 /// The URL query string field name.
 pub const QUERY_FIELD: &str = "start_from";
+/// The header via which a reconnecting client provides the ID of the last event it received.
+pub const LAST_EVENT_ID_HEADER: &str = "last-event-id";
 
 /// The filter associated with `/events/main` path.
-const MAIN_FILTER: [EventFilter; 4] = [
+pub(super) const MAIN_FILTER: [EventFilter; 4] = [
     EventFilter::BlockAdded,
     EventFilter::DeployProcessed,
     EventFilter::Fault,
     EventFilter::Step,
 ];
 /// The filter associated with `/events/deploys` path.
-const DEPLOYS_FILTER: [EventFilter; 1] = [EventFilter::DeployAccepted];
+pub(super) const DEPLOYS_FILTER: [EventFilter; 4] = [
+    EventFilter::DeployAccepted,
+    EventFilter::DeployRejected,
+    EventFilter::DeployReplaced,
+    EventFilter::DeployEvicted,
+];
 /// The filter associated with `/events/sigs` path.
-const SIGNATURES_FILTER: [EventFilter; 1] = [EventFilter::FinalitySignature];
+pub(super) const SIGNATURES_FILTER: [EventFilter; 1] = [EventFilter::FinalitySignature];
 
 /// The max time to wait for getting a deploy before trying a second and final time.
 const GET_DEPLOY_TIMEOUT: Duration = Duration::from_secs(1);
@@ -73,6 +90,27 @@ const GET_DEPLOY_TIMEOUT: Duration = Duration::from_secs(1);
 /// The "id" field of the events sent on the event stream to clients.
 pub type Id = u32;
 
+/// Where a deploy reported on the event stream was received from.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Debug, DataSize, JsonSchema)]
+pub enum DeploySource {
+    /// A peer, having gossiped the deploy to this node.
+    Peer,
+    /// A client, having submitted the deploy to this node via RPC.
+    Client,
+    /// This node.
+    Ourself,
+}
+
+impl<I> From<&Source<I>> for DeploySource {
+    fn from(source: &Source<I>) -> Self {
+        match source {
+            Source::Peer(_) => DeploySource::Peer,
+            Source::Client => DeploySource::Client,
+            Source::Ourself => DeploySource::Ourself,
+        }
+    }
+}
+
 /// The "data" field of the events sent on the event stream to clients.
 #[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug, DataSize, JsonSchema)]
 pub enum SseData {
@@ -91,6 +129,10 @@ pub enum SseData {
         deploy: DeployHash,
     },
     /// The given deploy has been executed, committed and forms part of the given block.
+    ///
+    /// The effect of the execution result is in canonical order.  `transform_count` is the number
+    /// of its transforms and `effect_digest` the hash of its canonical binary serialization, so
+    /// that clients can verify the effect and detect truncation without parsing all of it.
     DeployProcessed {
         deploy_hash: Box<DeployHash>,
         account: Box<PublicKey>,
@@ -98,9 +140,26 @@ pub enum SseData {
         ttl: TimeDiff,
         dependencies: Vec<DeployHash>,
         block_hash: Box<BlockHash>,
+        transform_count: u64,
+        effect_digest: Digest,
         #[data_size(skip)]
         execution_result: Box<ExecutionResult>,
     },
+    /// The given pending deploy has been replaced by a deploy from the same account with the same
+    /// body but a higher gas price, and will no longer be proposed by this node.
+    DeployReplaced {
+        replaced: DeployHash,
+        replacement: DeployHash,
+    },
+    /// The given pending deploy has been evicted from this node's deploy buffer since the buffer
+    /// was full, and will no longer be proposed by this node.
+    DeployEvicted { deploy_hash: DeployHash },
+    /// The given deploy has been rejected by this node for the given reason.
+    DeployRejected {
+        deploy_hash: DeployHash,
+        reason: String,
+        source: DeploySource,
+    },
     /// Generic representation of validator's fault in an era.
     Fault {
         era_id: EraId,
@@ -109,25 +168,116 @@ pub enum SseData {
     },
     /// New finality signature received.
     FinalitySignature(Box<FinalitySignature>),
+    /// The auction's step has been committed at the end of the given era, with the given effect.
+    ///
+    /// The effect is in canonical order.  `transform_count` is the number of its transforms and
+    /// `effect_digest` the hash of its canonical binary serialization, so that clients can verify
+    /// the effect and detect truncation without parsing all of it.
     Step {
         era_id: EraId,
+        transform_count: u64,
+        effect_digest: Digest,
         #[data_size(skip)]
         execution_effect: ExecutionEffect,
     },
+    /// Some of the events which the client requested by subscribing from a given event ID have
+    /// been evicted from this node's buffer to limit its memory usage, or were emitted before this
+    /// node last restarted, and won't be sent.  The skipped events have IDs up to and including
+    /// `last_skipped_id`.  This event has no associated event ID, and is sent immediately after
+    /// the `StreamInstance` event.
+    EventsSkipped { last_skipped_id: Id },
+    /// The randomly-generated ID of this run of the node's event stream.  It changes every time
+    /// the node restarts, while event IDs continue from where the previous run stopped.  This
+    /// event has no associated event ID, and is sent immediately after the `ApiVersion` event.
+    #[data_size(skip)]
+    StreamInstance {
+        #[schemars(with = "String")]
+        stream_instance_id: Uuid,
+    },
+    /// The node is shutting down for the given reason.  This is the final event sent to every
+    /// client before the server closes the stream, and has no associated event ID.
+    Shutdown { reason: String },
 }
 
 impl SseData {
+    /// Returns a `DeployProcessed` event, with the effect of the execution result sorted into
+    /// canonical order.
+    pub(super) fn deploy_processed(
+        deploy_hash: DeployHash,
+        deploy_header: &DeployHeader,
+        block_hash: BlockHash,
+        mut execution_result: Box<ExecutionResult>,
+    ) -> Self {
+        let (transform_count, effect_digest) = match &mut *execution_result {
+            ExecutionResult::Success { effect, .. } | ExecutionResult::Failure { effect, .. } => {
+                canonicalize_effect(effect)
+            }
+        };
+        SseData::DeployProcessed {
+            deploy_hash: Box::new(deploy_hash),
+            account: Box::new(deploy_header.account().clone()),
+            timestamp: deploy_header.timestamp(),
+            ttl: deploy_header.ttl(),
+            dependencies: deploy_header.dependencies().clone(),
+            block_hash: Box::new(block_hash),
+            execution_result,
+            transform_count,
+            effect_digest,
+        }
+    }
+
+    /// Returns a `Step` event, with the effect sorted into canonical order.
+    pub(super) fn step(era_id: EraId, mut execution_effect: ExecutionEffect) -> Self {
+        let (transform_count, effect_digest) = canonicalize_effect(&mut execution_effect);
+        SseData::Step {
+            era_id,
+            execution_effect,
+            transform_count,
+            effect_digest,
+        }
+    }
+
     pub(super) fn should_include(&self, filter: &[EventFilter]) -> bool {
+        self.event_kind()
+            .map_or(true, |event_kind| filter.contains(&event_kind))
+    }
+
+    /// Returns the kind of this event, or `None` if it is sent to every client regardless of the
+    /// kinds they subscribed to.
+    pub(super) fn event_kind(&self) -> Option<EventFilter> {
         match self {
-            SseData::ApiVersion(_) => true,
-            SseData::BlockAdded { .. } => filter.contains(&EventFilter::BlockAdded),
-            SseData::DeployAccepted { .. } => filter.contains(&EventFilter::DeployAccepted),
-            SseData::DeployProcessed { .. } => filter.contains(&EventFilter::DeployProcessed),
-            SseData::Fault { .. } => filter.contains(&EventFilter::Fault),
-            SseData::FinalitySignature(_) => filter.contains(&EventFilter::FinalitySignature),
-            SseData::Step { .. } => filter.contains(&EventFilter::Step),
+            SseData::ApiVersion(_)
+            | SseData::EventsSkipped { .. }
+            | SseData::StreamInstance { .. }
+            | SseData::Shutdown { .. } => None,
+            SseData::BlockAdded { .. } => Some(EventFilter::BlockAdded),
+            SseData::DeployAccepted { .. } => Some(EventFilter::DeployAccepted),
+            SseData::DeployProcessed { .. } => Some(EventFilter::DeployProcessed),
+            SseData::DeployReplaced { .. } => Some(EventFilter::DeployReplaced),
+            SseData::DeployEvicted { .. } => Some(EventFilter::DeployEvicted),
+            SseData::DeployRejected { .. } => Some(EventFilter::DeployRejected),
+            SseData::Fault { .. } => Some(EventFilter::Fault),
+            SseData::FinalitySignature(_) => Some(EventFilter::FinalitySignature),
+            SseData::Step { .. } => Some(EventFilter::Step),
         }
     }
+
+    /// Returns a cheap estimate of the number of bytes this event occupies in memory.
+    ///
+    /// Execution results and effects are skipped by `DataSize`, so their serialized length is used
+    /// as an approximation of their size instead.
+    pub(super) fn estimated_size(&self) -> usize {
+        let skipped_size = match self {
+            SseData::DeployProcessed {
+                execution_result, ..
+            } => execution_result.serialized_length(),
+            SseData::Step {
+                execution_effect, ..
+            } => execution_effect.serialized_length(),
+            _ => 0,
+        };
+        mem::size_of::<Self>() + self.estimate_heap_size() + skipped_size
+    }
 }
 
 #[cfg(test)]
@@ -163,14 +313,40 @@ impl SseData {
     /// Returns a random `SseData::DeployProcessed`.
     pub(super) fn random_deploy_processed(rng: &mut TestRng) -> Self {
         let deploy = Deploy::random(rng);
-        SseData::DeployProcessed {
-            deploy_hash: Box::new(*deploy.id()),
-            account: Box::new(deploy.header().account().clone()),
-            timestamp: deploy.header().timestamp(),
-            ttl: deploy.header().ttl(),
-            dependencies: deploy.header().dependencies().clone(),
-            block_hash: Box::new(BlockHash::random(rng)),
-            execution_result: Box::new(rng.gen()),
+        SseData::deploy_processed(
+            *deploy.id(),
+            deploy.header(),
+            BlockHash::random(rng),
+            Box::new(rng.gen()),
+        )
+    }
+
+    /// Returns a random `SseData::DeployReplaced`.
+    pub(super) fn random_deploy_replaced(rng: &mut TestRng) -> Self {
+        SseData::DeployReplaced {
+            replaced: DeployHash::random(rng),
+            replacement: DeployHash::random(rng),
+        }
+    }
+
+    /// Returns a random `SseData::DeployEvicted`.
+    pub(super) fn random_deploy_evicted(rng: &mut TestRng) -> Self {
+        SseData::DeployEvicted {
+            deploy_hash: DeployHash::random(rng),
+        }
+    }
+
+    /// Returns a random `SseData::DeployRejected`.
+    pub(super) fn random_deploy_rejected(rng: &mut TestRng) -> Self {
+        let source = if rng.gen() {
+            DeploySource::Peer
+        } else {
+            DeploySource::Client
+        };
+        SseData::DeployRejected {
+            deploy_hash: DeployHash::random(rng),
+            reason: format!("invalid deploy: {}", rng.gen::<u64>()),
+            source,
         }
     }
 
@@ -191,6 +367,13 @@ impl SseData {
         )))
     }
 
+    /// Returns a random `SseData::Shutdown`.
+    pub(super) fn random_shutdown(rng: &mut TestRng) -> Self {
+        SseData::Shutdown {
+            reason: format!("upgrading to protocol version 1.{}.0", rng.gen::<u8>()),
+        }
+    }
+
     /// Returns a random `SseData::Step`.
     pub(super) fn random_step(rng: &mut TestRng) -> Self {
         let execution_effect = match rng.gen::<ExecutionResult>() {
@@ -198,17 +381,28 @@ impl SseData {
                 effect
             }
         };
-        SseData::Step {
-            era_id: EraId::new(rng.gen()),
-            execution_effect,
-        }
+        SseData::step(EraId::new(rng.gen()), execution_effect)
     }
 }
 
+/// Sorts the effect into canonical order, returning its number of transforms and the digest of its
+/// canonical binary serialization.
+fn canonicalize_effect(effect: &mut ExecutionEffect) -> (u64, Digest) {
+    effect.canonicalize();
+    let serialized_effect = effect
+        .to_bytes()
+        .expect("should serialize execution effect");
+    (
+        effect.transforms.len() as u64,
+        hash::hash(&serialized_effect),
+    )
+}
+
 /// The components of a single SSE.
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub(super) struct ServerSentEvent {
-    /// The ID should only be `None` where the `data` is `SseData::ApiVersion`.
+    /// The ID should only be `None` where the `data` is `SseData::ApiVersion`,
+    /// `SseData::StreamInstance`, `SseData::EventsSkipped` or `SseData::Shutdown`.
     pub(super) id: Option<Id>,
     pub(super) data: SseData,
 }
@@ -221,13 +415,22 @@ impl ServerSentEvent {
             data: SseData::ApiVersion(client_api_version),
         }
     }
+
+    /// The event sent to every subscribing client immediately after the initial event.
+    pub(super) fn stream_instance_event(stream_instance_id: Uuid) -> Self {
+        ServerSentEvent {
+            id: None,
+            data: SseData::StreamInstance { stream_instance_id },
+        }
+    }
 }
 
 /// The messages sent via the tokio broadcast channel to the handler of each client's SSE stream.
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub(super) enum BroadcastChannelMessage {
     /// The message should be sent to the client as an SSE with an optional ID.  The ID should only
-    /// be `None` where the `data` is `SseData::ApiVersion`.
+    /// be `None` where the `data` is `SseData::ApiVersion`, `SseData::StreamInstance`,
+    /// `SseData::EventsSkipped` or `SseData::Shutdown`.
     ServerSentEvent(ServerSentEvent),
     /// The stream should terminate as the server is shutting down.
     ///
@@ -240,17 +443,24 @@ pub(super) enum BroadcastChannelMessage {
 pub(super) struct NewSubscriberInfo {
     /// The event ID from which the stream should start for this client.
     pub(super) start_from: Option<Id>,
+    /// The kinds of event the client subscribed to, used to tell whether any of the events it
+    /// requested have been evicted from the buffer.
+    pub(super) event_filter: Vec<EventFilter>,
     /// A channel to send the initial events to the client's handler.  This will always send the
-    /// ApiVersion as the first event, and then any buffered events as indicated by `start_from`.
+    /// ApiVersion as the first event followed by the StreamInstance, and then any buffered events
+    /// as indicated by `start_from`.
     pub(super) initial_events_sender: mpsc::UnboundedSender<ServerSentEvent>,
 }
 
 /// A filter for event types a client has subscribed to receive.
-#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Deserialize, Debug)]
 pub(super) enum EventFilter {
     BlockAdded,
     DeployAccepted,
     DeployProcessed,
+    DeployRejected,
+    DeployReplaced,
+    DeployEvicted,
     Fault,
     FinalitySignature,
     Step,
@@ -268,15 +478,17 @@ async fn filter_map_server_sent_event(
 
     let id = match event.id {
         Some(id) => {
-            if matches!(&event.data, &SseData::ApiVersion { .. }) {
-                error!("ApiVersion should have no event ID");
+            if event.data.event_kind().is_none() {
+                error!("ApiVersion, StreamInstance, EventsSkipped and Shutdown should have no ID");
                 return None;
             }
             id.to_string()
         }
         None => {
-            if !matches!(&event.data, &SseData::ApiVersion { .. }) {
-                error!("only ApiVersion may have no event ID");
+            if event.data.event_kind().is_some() {
+                error!(
+                    "only ApiVersion, StreamInstance, EventsSkipped and Shutdown may have no ID"
+                );
                 return None;
             }
             String::new()
@@ -284,7 +496,10 @@ async fn filter_map_server_sent_event(
     };
 
     match &event.data {
-        &SseData::ApiVersion { .. } => Some(Ok(WarpServerSentEvent::default()
+        &SseData::ApiVersion { .. }
+        | &SseData::EventsSkipped { .. }
+        | &SseData::StreamInstance { .. }
+        | &SseData::Shutdown { .. } => Some(Ok(WarpServerSentEvent::default()
             .json_data(&event.data)
             .unwrap_or_else(|error| {
                 warn!(%error, ?event, "failed to jsonify sse event");
@@ -293,6 +508,9 @@ async fn filter_map_server_sent_event(
 
         &SseData::BlockAdded { .. }
         | &SseData::DeployProcessed { .. }
+        | &SseData::DeployRejected { .. }
+        | &SseData::DeployReplaced { .. }
+        | &SseData::DeployEvicted { .. }
         | &SseData::Fault { .. }
         | &SseData::Step { .. }
         | &SseData::FinalitySignature(_) => Some(Ok(WarpServerSentEvent::default()
@@ -306,19 +524,7 @@ async fn filter_map_server_sent_event(
         &SseData::DeployAccepted {
             deploy: deploy_hash,
         } => {
-            // We try twice to get the deploy since there's a chance that the first attempt could be
-            // lost when the joiner reactor's event queue is purged as we transition to the
-            // participating reactor.  This workaround should no longer be required once the reactor
-            // transitions are handled properly.
-            let deploy =
-                match time::timeout(GET_DEPLOY_TIMEOUT, deploy_getter.get(deploy_hash)).await {
-                    Ok(maybe_deploy) => maybe_deploy?,
-                    Err(_) => {
-                        info!("timed out getting deploy for event stream");
-                        deploy_getter.get(deploy_hash).await?
-                    }
-                };
-
+            let deploy = get_deploy(deploy_hash, deploy_getter).await?;
             Some(Ok(WarpServerSentEvent::default()
                 .json_data(&deploy)
                 .unwrap_or_else(|error| {
@@ -330,6 +536,24 @@ async fn filter_map_server_sent_event(
     }
 }
 
+/// Gets the full deploy for a `DeployAccepted` event, or `None` if it can't be retrieved.
+pub(super) async fn get_deploy(
+    deploy_hash: DeployHash,
+    deploy_getter: DeployGetter,
+) -> Option<Deploy> {
+    // We try twice to get the deploy since there's a chance that the first attempt could be lost
+    // when the joiner reactor's event queue is purged as we transition to the participating
+    // reactor.  This workaround should no longer be required once the reactor transitions are
+    // handled properly.
+    match time::timeout(GET_DEPLOY_TIMEOUT, deploy_getter.get(deploy_hash)).await {
+        Ok(maybe_deploy) => maybe_deploy,
+        Err(_) => {
+            info!("timed out getting deploy for event stream");
+            deploy_getter.get(deploy_hash).await
+        }
+    }
+}
+
 /// Converts the final URL path element to a slice of `EventFilter`s.
 pub(super) fn get_filter(path_param: &str) -> Option<&'static [EventFilter]> {
     match path_param {
@@ -362,6 +586,20 @@ fn parse_query(query: HashMap<String, String>) -> Result<Option<Id>, Response> {
     }
 }
 
+/// Extracts the starting event ID from the provided `Last-Event-ID` header value, i.e. the ID
+/// following the one in the header, or `None` if the header is absent.
+///
+/// Returns a 422 response if the header value doesn't represent an event ID.
+fn parse_last_event_id(maybe_last_event_id: Option<String>) -> Result<Option<Id>, Response> {
+    match maybe_last_event_id {
+        Some(id_str) => match id_str.trim().parse::<Id>() {
+            Ok(id) => Ok(Some(id.wrapping_add(1))),
+            Err(_) => Err(create_422_for_last_event_id()),
+        },
+        None => Ok(None),
+    }
+}
+
 /// Creates a 404 response with a useful error message in the body.
 fn create_404() -> Response {
     let mut response = Response::new(Body::from(format!(
@@ -386,9 +624,20 @@ fn create_422() -> Response {
     response
 }
 
+/// Creates a 422 response with a useful error message in the body for use in case of a bad
+/// `Last-Event-ID` header.
+fn create_422_for_last_event_id() -> Response {
+    let mut response = Response::new(Body::from(format!(
+        "invalid header: expected '{}: <EVENT ID>'\n",
+        LAST_EVENT_ID_HEADER
+    )));
+    *response.status_mut() = StatusCode::UNPROCESSABLE_ENTITY;
+    response
+}
+
 /// Creates a 503 response (Service Unavailable) to be returned if the server has too many
 /// subscribers.
-fn create_503() -> Response {
+pub(super) fn create_503() -> Response {
     let mut response = Response::new(Body::from("server has reached limit of subscribers"));
     *response.status_mut() = StatusCode::SERVICE_UNAVAILABLE;
     response
@@ -396,6 +645,7 @@ fn create_503() -> Response {
 
 pub(super) struct ChannelsAndFilter {
     pub(super) event_broadcaster: broadcast::Sender<BroadcastChannelMessage>,
+    pub(super) new_subscriber_info_sender: mpsc::UnboundedSender<NewSubscriberInfo>,
     pub(super) new_subscriber_info_receiver: mpsc::UnboundedReceiver<NewSubscriberInfo>,
     pub(super) sse_filter: BoxedFilter<(Response,)>,
 }
@@ -415,67 +665,82 @@ impl ChannelsAndFilter {
         // Create a channel for `NewSubscriberInfo`s to pass the information required to handle a
         // new client subscription.
         let (new_subscriber_info_sender, new_subscriber_info_receiver) = mpsc::unbounded_channel();
+        let cloned_subscriber_info_sender = new_subscriber_info_sender.clone();
 
         let sse_filter = warp::get()
             .and(path(SSE_API_ROOT_PATH))
             .and(path::param::<String>())
             .and(path::end())
             .and(warp::query())
-            .map(move |path_param: String, query: HashMap<String, String>| {
-                // If we already have the maximum number of subscribers, reject this new one.
-                if cloned_broadcaster.receiver_count() >= max_concurrent_subscribers as usize {
-                    info!(
-                        %max_concurrent_subscribers,
-                        "event stream server has max subscribers: rejecting new one"
-                    );
-                    return create_503();
-                }
-
-                // If `path_param` is not a valid string, return a 404.
-                let event_filter = match get_filter(path_param.as_str()) {
-                    Some(filter) => filter,
-                    None => return create_404(),
-                };
+            .and(warp::header::optional::<String>(LAST_EVENT_ID_HEADER))
+            .map(
+                move |path_param: String,
+                      query: HashMap<String, String>,
+                      last_id: Option<String>| {
+                    // If we already have the maximum number of subscribers, reject this new one.
+                    if cloned_broadcaster.receiver_count() >= max_concurrent_subscribers as usize {
+                        info!(
+                            %max_concurrent_subscribers,
+                            "event stream server has max subscribers: rejecting new one"
+                        );
+                        return create_503();
+                    }
 
-                let start_from = match parse_query(query) {
-                    Ok(maybe_id) => maybe_id,
-                    Err(error_response) => return error_response,
-                };
+                    // If `path_param` is not a valid string, return a 404.
+                    let event_filter = match get_filter(path_param.as_str()) {
+                        Some(filter) => filter,
+                        None => return create_404(),
+                    };
 
-                // Create a channel for the client's handler to receive the stream of initial
-                // events.
-                let (initial_events_sender, initial_events_receiver) = mpsc::unbounded_channel();
+                    // The query takes precedence over the `Last-Event-ID` header, which is only
+                    // sent automatically by clients reconnecting after a dropped connection.
+                    let start_from = match parse_query(query) {
+                        Ok(Some(id)) => Some(id),
+                        Ok(None) => match parse_last_event_id(last_id) {
+                            Ok(maybe_id) => maybe_id,
+                            Err(error_response) => return error_response,
+                        },
+                        Err(error_response) => return error_response,
+                    };
 
-                // Supply the server with the sender part of the channel along with the client's
-                // requested starting point.
-                let new_subscriber_info = NewSubscriberInfo {
-                    start_from,
-                    initial_events_sender,
-                };
-                if new_subscriber_info_sender
-                    .send(new_subscriber_info)
-                    .is_err()
-                {
-                    error!("failed to send new subscriber info");
-                }
+                    // Create a channel for the client's handler to receive the stream of initial
+                    // events.
+                    let (initial_events_sender, initial_events_receiver) =
+                        mpsc::unbounded_channel();
+
+                    // Supply the server with the sender part of the channel along with the client's
+                    // requested starting point.
+                    let new_subscriber_info = NewSubscriberInfo {
+                        start_from,
+                        event_filter: event_filter.to_vec(),
+                        initial_events_sender,
+                    };
+                    if cloned_subscriber_info_sender
+                        .send(new_subscriber_info)
+                        .is_err()
+                    {
+                        error!("failed to send new subscriber info");
+                    }
 
-                // Create a channel for the client's handler to receive the stream of ongoing
-                // events.
-                let ongoing_events_receiver = cloned_broadcaster.subscribe();
-
-                sse::reply(sse::keep_alive().stream(stream_to_client(
-                    initial_events_receiver,
-                    ongoing_events_receiver,
-                    event_filter,
-                    deploy_getter.clone(),
-                )))
-                .into_response()
-            })
+                    // Create a channel for the client's handler to receive the stream of ongoing
+                    // events.
+                    let ongoing_events_receiver = cloned_broadcaster.subscribe();
+
+                    sse::reply(sse::keep_alive().stream(stream_to_client(
+                        initial_events_receiver,
+                        ongoing_events_receiver,
+                        event_filter,
+                        deploy_getter.clone(),
+                    )))
+                    .into_response()
+                },
+            )
             .or_else(|_| async move { Ok::<_, Rejection>((create_404(),)) })
             .boxed();
 
         ChannelsAndFilter {
             event_broadcaster,
+            new_subscriber_info_sender,
             new_subscriber_info_receiver,
             sse_filter,
         }
@@ -485,15 +750,6 @@ impl ChannelsAndFilter {
 /// This takes the two channel receivers and turns them into a stream of SSEs to the subscribed
 /// client.
 ///
-/// The initial events receiver (an mpsc receiver) is exhausted first, and contains an initial
-/// `ApiVersion` message, followed by any historical events the client requested using the query
-/// string.
-///
-/// The ongoing events channel (a broadcast receiver) is then consumed, and will remain in use until
-/// either the client disconnects, or the server shuts down (indicated by sending a `Shutdown`
-/// variant via the channel).  This channel will receive all SSEs created from the moment the client
-/// subscribed to the server's event stream.
-///
 /// It also takes an `EventFilter` which causes events to which the client didn't subscribe to be
 /// skipped.
 fn stream_to_client(
@@ -502,6 +758,35 @@ fn stream_to_client(
     event_filter: &'static [EventFilter],
     deploy_getter: DeployGetter,
 ) -> impl Stream<Item = Result<WarpServerSentEvent, RecvError>> + 'static {
+    merged_event_stream(initial_events, ongoing_events).filter_map(move |result| {
+        let cloned_deploy_getter = deploy_getter.clone();
+        async move {
+            match result {
+                Ok(event) => {
+                    filter_map_server_sent_event(&event, event_filter, cloned_deploy_getter).await
+                }
+                Err(error) => Some(Err(error)),
+            }
+        }
+    })
+}
+
+/// This takes the two channel receivers and merges them into a single stream of events for a
+/// subscribed client.  It is shared by the SSE and websocket endpoints.
+///
+/// The initial events receiver (an mpsc receiver) is exhausted first, and contains an initial
+/// `ApiVersion` message and a `StreamInstance` message, followed by any historical events the
+/// client requested.
+///
+/// The ongoing events channel (a broadcast receiver) is then consumed, and will remain in use until
+/// either the client disconnects, or the server shuts down (indicated by sending a `Shutdown`
+/// variant via the channel, which ends the stream).  This channel will receive all events created
+/// from the moment the client subscribed to the server's event stream.  If the client lags too far
+/// behind, a `RecvError::Lagged` is yielded.
+pub(super) fn merged_event_stream(
+    initial_events: mpsc::UnboundedReceiver<ServerSentEvent>,
+    ongoing_events: broadcast::Receiver<BroadcastChannelMessage>,
+) -> impl Stream<Item = Result<ServerSentEvent, RecvError>> + 'static {
     // Keep a record of the IDs of the events delivered via the `initial_events` receiver.
     let initial_stream_ids = Arc::new(RwLock::new(HashSet::new()));
     let cloned_initial_ids = Arc::clone(&initial_stream_ids);
@@ -535,8 +820,7 @@ fn stream_to_client(
         })
         .take_while(|result| future::ready(!matches!(result, Err(RecvError::Closed))));
 
-    // Serve the initial events followed by the ongoing ones, filtering as dictated by the
-    // `event_filter`.
+    // Serve the initial events followed by the ongoing ones.
     UnboundedReceiverStream::new(initial_events)
         .map(move |event| {
             if let Some(id) = event.id {
@@ -545,18 +829,6 @@ fn stream_to_client(
             Ok(event)
         })
         .chain(ongoing_stream)
-        .filter_map(move |result| {
-            let cloned_deploy_getter = deploy_getter.clone();
-            async move {
-                match result {
-                    Ok(event) => {
-                        filter_map_server_sent_event(&event, event_filter, cloned_deploy_getter)
-                            .await
-                    }
-                    Err(error) => Some(Err(error)),
-                }
-            }
-        })
 }
 
 #[cfg(test)]
@@ -597,7 +869,7 @@ mod tests {
     }
 
     /// This test checks that events with correct IDs (i.e. all types have an ID except for
-    /// `ApiVersion`) are filtered properly.
+    /// `ApiVersion`, `StreamInstance`, `EventsSkipped` and `Shutdown`) are filtered properly.
     #[tokio::test]
     async fn should_filter_events_with_valid_ids() {
         let _ = logging::init();
@@ -623,6 +895,18 @@ mod tests {
             id: Some(rng.gen()),
             data: SseData::random_deploy_processed(&mut rng),
         };
+        let deploy_rejected = ServerSentEvent {
+            id: Some(rng.gen()),
+            data: SseData::random_deploy_rejected(&mut rng),
+        };
+        let deploy_replaced = ServerSentEvent {
+            id: Some(rng.gen()),
+            data: SseData::random_deploy_replaced(&mut rng),
+        };
+        let deploy_evicted = ServerSentEvent {
+            id: Some(rng.gen()),
+            data: SseData::random_deploy_evicted(&mut rng),
+        };
         let fault = ServerSentEvent {
             id: Some(rng.gen()),
             data: SseData::random_fault(&mut rng),
@@ -635,8 +919,20 @@ mod tests {
             id: Some(rng.gen()),
             data: SseData::random_step(&mut rng),
         };
+        let events_skipped = ServerSentEvent {
+            id: None,
+            data: SseData::EventsSkipped {
+                last_skipped_id: rng.gen(),
+            },
+        };
+        let stream_instance = ServerSentEvent::stream_instance_event(Uuid::new_v4());
+        let shutdown = ServerSentEvent {
+            id: None,
+            data: SseData::random_shutdown(&mut rng),
+        };
 
-        // `EventFilter::Main` should only filter out `DeployAccepted`s and `FinalitySignature`s.
+        // `EventFilter::Main` should only filter out `DeployAccepted`s, `DeployRejected`s,
+        // `DeployReplaced`s, `DeployEvicted`s and `FinalitySignature`s.
         should_not_filter_out(&api_version, &MAIN_FILTER[..], getter.clone()).await;
         should_not_filter_out(&block_added, &MAIN_FILTER[..], getter.clone()).await;
         should_not_filter_out(&deploy_processed, &MAIN_FILTER[..], getter.clone()).await;
@@ -644,12 +940,18 @@ mod tests {
         should_not_filter_out(&step, &MAIN_FILTER[..], getter.clone()).await;
 
         should_filter_out(&deploy_accepted, &MAIN_FILTER[..], getter.clone()).await;
+        should_filter_out(&deploy_rejected, &MAIN_FILTER[..], getter.clone()).await;
+        should_filter_out(&deploy_replaced, &MAIN_FILTER[..], getter.clone()).await;
+        should_filter_out(&deploy_evicted, &MAIN_FILTER[..], getter.clone()).await;
         should_filter_out(&finality_signature, &MAIN_FILTER[..], getter.clone()).await;
 
-        // `EventFilter::DeployAccepted` should filter out everything except `ApiVersion`s and
-        // `DeployAccepted`s.
+        // `EventFilter::DeployAccepted` should filter out everything except `ApiVersion`s,
+        // `DeployAccepted`s, `DeployRejected`s, `DeployReplaced`s and `DeployEvicted`s.
         should_not_filter_out(&api_version, &DEPLOYS_FILTER[..], getter.clone()).await;
         should_not_filter_out(&deploy_accepted, &DEPLOYS_FILTER[..], getter.clone()).await;
+        should_not_filter_out(&deploy_rejected, &DEPLOYS_FILTER[..], getter.clone()).await;
+        should_not_filter_out(&deploy_replaced, &DEPLOYS_FILTER[..], getter.clone()).await;
+        should_not_filter_out(&deploy_evicted, &DEPLOYS_FILTER[..], getter.clone()).await;
 
         should_filter_out(&block_added, &DEPLOYS_FILTER[..], getter.clone()).await;
         should_filter_out(&deploy_processed, &DEPLOYS_FILTER[..], getter.clone()).await;
@@ -665,12 +967,27 @@ mod tests {
         should_filter_out(&block_added, &SIGNATURES_FILTER[..], getter.clone()).await;
         should_filter_out(&deploy_accepted, &SIGNATURES_FILTER[..], getter.clone()).await;
         should_filter_out(&deploy_processed, &SIGNATURES_FILTER[..], getter.clone()).await;
+        should_filter_out(&deploy_rejected, &SIGNATURES_FILTER[..], getter.clone()).await;
+        should_filter_out(&deploy_replaced, &SIGNATURES_FILTER[..], getter.clone()).await;
+        should_filter_out(&deploy_evicted, &SIGNATURES_FILTER[..], getter.clone()).await;
         should_filter_out(&fault, &SIGNATURES_FILTER[..], getter.clone()).await;
-        should_filter_out(&step, &SIGNATURES_FILTER[..], getter).await;
+        should_filter_out(&step, &SIGNATURES_FILTER[..], getter.clone()).await;
+
+        // `EventsSkipped`s, `StreamInstance`s and `Shutdown`s, like `ApiVersion`s, should never be
+        // filtered out.
+        for filter in &[
+            &MAIN_FILTER[..],
+            &DEPLOYS_FILTER[..],
+            &SIGNATURES_FILTER[..],
+        ] {
+            should_not_filter_out(&events_skipped, filter, getter.clone()).await;
+            should_not_filter_out(&stream_instance, filter, getter.clone()).await;
+            should_not_filter_out(&shutdown, filter, getter.clone()).await;
+        }
     }
 
-    /// This test checks that events with incorrect IDs (i.e. no types have an ID except for
-    /// `ApiVersion`) are filtered out.
+    /// This test checks that events with incorrect IDs (i.e. `ApiVersion`, `StreamInstance`,
+    /// `EventsSkipped` or `Shutdown` with an ID, or any other type without one) are filtered out.
     #[tokio::test]
     async fn should_filter_events_with_invalid_ids() {
         let _ = logging::init();
@@ -696,6 +1013,18 @@ mod tests {
             id: None,
             data: SseData::random_deploy_processed(&mut rng),
         };
+        let malformed_deploy_rejected = ServerSentEvent {
+            id: None,
+            data: SseData::random_deploy_rejected(&mut rng),
+        };
+        let malformed_deploy_replaced = ServerSentEvent {
+            id: None,
+            data: SseData::random_deploy_replaced(&mut rng),
+        };
+        let malformed_deploy_evicted = ServerSentEvent {
+            id: None,
+            data: SseData::random_deploy_evicted(&mut rng),
+        };
         let malformed_fault = ServerSentEvent {
             id: None,
             data: SseData::random_fault(&mut rng),
@@ -708,6 +1037,22 @@ mod tests {
             id: None,
             data: SseData::random_step(&mut rng),
         };
+        let malformed_events_skipped = ServerSentEvent {
+            id: Some(rng.gen()),
+            data: SseData::EventsSkipped {
+                last_skipped_id: rng.gen(),
+            },
+        };
+        let malformed_stream_instance = ServerSentEvent {
+            id: Some(rng.gen()),
+            data: SseData::StreamInstance {
+                stream_instance_id: Uuid::new_v4(),
+            },
+        };
+        let malformed_shutdown = ServerSentEvent {
+            id: Some(rng.gen()),
+            data: SseData::random_shutdown(&mut rng),
+        };
 
         for filter in &[
             &MAIN_FILTER[..],
@@ -718,9 +1063,15 @@ mod tests {
             should_filter_out(&malformed_block_added, filter, getter.clone()).await;
             should_filter_out(&malformed_deploy_accepted, filter, getter.clone()).await;
             should_filter_out(&malformed_deploy_processed, filter, getter.clone()).await;
+            should_filter_out(&malformed_deploy_rejected, filter, getter.clone()).await;
+            should_filter_out(&malformed_deploy_replaced, filter, getter.clone()).await;
+            should_filter_out(&malformed_deploy_evicted, filter, getter.clone()).await;
             should_filter_out(&malformed_fault, filter, getter.clone()).await;
             should_filter_out(&malformed_finality_signature, filter, getter.clone()).await;
             should_filter_out(&malformed_step, filter, getter.clone()).await;
+            should_filter_out(&malformed_events_skipped, filter, getter.clone()).await;
+            should_filter_out(&malformed_stream_instance, filter, getter.clone()).await;
+            should_filter_out(&malformed_shutdown, filter, getter.clone()).await;
         }
     }
 
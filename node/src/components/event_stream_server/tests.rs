@@ -9,25 +9,46 @@ use std::{
     time::Duration,
 };
 
-use futures::{join, StreamExt};
+use futures::{join, SinkExt, StreamExt};
 use http::StatusCode;
 use pretty_assertions::assert_eq;
+use rand::Rng;
 use reqwest::Response;
 use schemars::schema_for;
+use serde::Deserialize;
+use serde_json::{json, Value};
 use tempfile::TempDir;
 use tokio::{
     sync::{Barrier, Notify},
     task::{self, JoinHandle},
     time,
 };
+use tokio_tungstenite::tungstenite::{self, Message as WsMessage};
 use tracing::debug;
+use uuid::Uuid;
+
+use casper_execution_engine::core::engine_state::executable_deploy_item::ExecutableDeployItem;
+use casper_types::{
+    bytesrepr::{Bytes, ToBytes},
+    EraId, ExecutionEffect, ExecutionResult, RuntimeArgs, SecretKey, TransformEntry,
+};
 
 use super::*;
-use crate::{logging, testing::TestRng};
+use crate::{
+    components::deploy_acceptor,
+    crypto::{hash, AsymmetricKeyExt},
+    logging,
+    testing::TestRng,
+    types::{chainspec::DeployConfig, BlockHash, Deploy, DeployHashScheme, TimeDiff, Timestamp},
+};
+use deploy_getter::TEST_CHAIN_NAME;
+use deploy_submission::{PutDeployResponse, PUT_DEPLOY_PATH, SECRET_HEADER};
 use sse_server::{
-    Id, QUERY_FIELD, SSE_API_DEPLOYS_PATH as DEPLOYS_PATH, SSE_API_MAIN_PATH as MAIN_PATH,
-    SSE_API_ROOT_PATH as ROOT_PATH, SSE_API_SIGNATURES_PATH as SIGS_PATH,
+    EventFilter, Id, LAST_EVENT_ID_HEADER, QUERY_FIELD, SSE_API_DEPLOYS_PATH as DEPLOYS_PATH,
+    SSE_API_MAIN_PATH as MAIN_PATH, SSE_API_ROOT_PATH as ROOT_PATH,
+    SSE_API_SIGNATURES_PATH as SIGS_PATH,
 };
+use ws_server::WS_API_PATH_PREFIX;
 
 /// The total number of random events each `EventStreamServer` will emit by default, excluding the
 /// initial `ApiVersion` event.
@@ -79,6 +100,9 @@ struct ServerBehavior {
     /// If `Some`, sets the `max_concurrent_subscribers` server config value, otherwise uses the
     /// config default.
     max_concurrent_subscribers: Option<u32>,
+    /// If `Some`, sets the `max_concurrent_websocket_subscribers` server config value, otherwise
+    /// uses the config default.
+    max_concurrent_websocket_subscribers: Option<u32>,
     clients: Vec<ClientSyncBehavior>,
 }
 
@@ -91,6 +115,7 @@ impl ServerBehavior {
             has_delay_between_events: true,
             repeat_events: false,
             max_concurrent_subscribers: None,
+            max_concurrent_websocket_subscribers: None,
             clients: Vec::new(),
         }
     }
@@ -104,6 +129,7 @@ impl ServerBehavior {
             has_delay_between_events: false,
             repeat_events: true,
             max_concurrent_subscribers: None,
+            max_concurrent_websocket_subscribers: None,
             clients: Vec::new(),
         }
     }
@@ -121,6 +147,11 @@ impl ServerBehavior {
         self.max_concurrent_subscribers = Some(count);
     }
 
+    /// Sets the `max_concurrent_websocket_subscribers` server config value.
+    fn set_max_concurrent_websocket_subscribers(&mut self, count: u32) {
+        self.max_concurrent_websocket_subscribers = Some(count);
+    }
+
     /// Waits for all clients which specified they wanted to join just before the given event ID.
     async fn wait_for_clients(&self, id: Id) {
         for client_behavior in &self.clients {
@@ -190,6 +221,7 @@ struct TestFixture {
     events: Vec<SseData>,
     deploy_getter: DeployGetter,
     first_event_id: Id,
+    stream_instance_id: Uuid,
     server_join_handle: Option<JoinHandle<()>>,
     server_stopper: ServerStopper,
 }
@@ -204,7 +236,7 @@ impl TestFixture {
 
         let mut deploys = HashMap::new();
         let events = (0..EVENT_COUNT)
-            .map(|i| match i % 6 {
+            .map(|i| match i % 9 {
                 0 => SseData::random_block_added(rng),
                 1 => {
                     let (event, deploy) = SseData::random_deploy_accepted(rng);
@@ -215,6 +247,9 @@ impl TestFixture {
                 3 => SseData::random_fault(rng),
                 4 => SseData::random_step(rng),
                 5 => SseData::random_finality_signature(rng),
+                6 => SseData::random_deploy_replaced(rng),
+                7 => SseData::random_deploy_rejected(rng),
+                8 => SseData::random_deploy_evicted(rng),
                 _ => unreachable!(),
             })
             .collect();
@@ -227,6 +262,7 @@ impl TestFixture {
             events,
             deploy_getter,
             first_event_id: 0,
+            stream_instance_id: Uuid::nil(),
             server_join_handle: None,
             server_stopper: ServerStopper::new(),
         }
@@ -260,17 +296,24 @@ impl TestFixture {
             max_concurrent_subscribers: server_behavior
                 .max_concurrent_subscribers
                 .unwrap_or(Config::default().max_concurrent_subscribers),
+            enable_websocket: true,
+            max_concurrent_websocket_subscribers: server_behavior
+                .max_concurrent_websocket_subscribers
+                .unwrap_or(Config::default().max_concurrent_websocket_subscribers),
             ..Default::default()
         };
         let mut server = EventStreamServer::new(
             config,
             self.storage_dir.path().to_path_buf(),
             self.protocol_version,
+            DeployConfig::default().max_deploy_size,
             self.deploy_getter.clone(),
+            &Registry::new(),
         )
         .unwrap();
 
         self.first_event_id = server.event_indexer.current_index();
+        self.stream_instance_id = server.stream_instance_id;
 
         let first_event_id = server.event_indexer.current_index();
         let server_address = server.listening_address;
@@ -328,10 +371,20 @@ impl TestFixture {
 
     /// Returns all the events which would have been received by a client via
     /// `/events/<final_path_element>`, where the client connected just before `from` was emitted
-    /// from the server.  This includes the initial `ApiVersion` event.
+    /// from the server.  This includes the initial `ApiVersion` and `StreamInstance` events.
     ///
     /// Also returns the last event's ID,
     fn filtered_events(&self, final_path_element: &str, from: Id) -> (Vec<ReceivedEvent>, Id) {
+        let filter = sse_server::get_filter(final_path_element).unwrap();
+        self.events_matching(filter, from)
+    }
+
+    /// Returns all the events matching `filter` which would have been received by a client which
+    /// connected just before `from` was emitted from the server.  This includes the initial
+    /// `ApiVersion` and `StreamInstance` events.
+    ///
+    /// Also returns the last event's ID,
+    fn events_matching(&self, filter: &[EventFilter], from: Id) -> (Vec<ReceivedEvent>, Id) {
         // Convert the IDs to `u128`s to cater for wrapping and add `Id::MAX + 1` to `from` if the
         // buffer wrapped and `from` represents an event from after the wrap.
         let threshold = Id::MAX - EVENT_COUNT;
@@ -367,8 +420,16 @@ impl TestFixture {
             data: serde_json::to_string(&SseData::ApiVersion(self.protocol_version)).unwrap(),
         };
 
-        let filter = sse_server::get_filter(final_path_element).unwrap();
+        let stream_instance_event = ReceivedEvent {
+            id: None,
+            data: serde_json::to_string(&SseData::StreamInstance {
+                stream_instance_id: self.stream_instance_id,
+            })
+            .unwrap(),
+        };
+
         let events: Vec<_> = iter::once(api_version_event)
+            .chain(iter::once(stream_instance_event))
             .chain(self.events.iter().enumerate().filter_map(|(id, event)| {
                 let id = id as u128 + self.first_event_id as u128;
                 if event.should_include(filter) {
@@ -389,7 +450,8 @@ impl TestFixture {
     }
 
     /// Returns all the events which would have been received by a client connected from server
-    /// startup via `/events/<final_path_element>`, including the initial `ApiVersion` event.
+    /// startup via `/events/<final_path_element>`, including the initial `ApiVersion` and
+    /// `StreamInstance` events.
     ///
     /// Also returns the last event's ID.
     fn all_filtered_events(&self, final_path_element: &str) -> (Vec<ReceivedEvent>, Id) {
@@ -521,6 +583,9 @@ async fn handle_response(
 ///
 /// The expected order is:
 ///   * data:<JSON-encoded ApiVersion> (note, no ID line follows this first event)
+///   * data:<JSON-encoded StreamInstance> (again, no ID line follows this event)
+/// then optionally, if some requested events were evicted from the server's buffer:
+///   * data:<JSON-encoded EventsSkipped> (again, no ID line follows this event)
 /// then the following three repeated for as many events as are applicable to that stream:
 ///   * data:<JSON-encoded event>
 ///   * id:<integer>
@@ -554,13 +619,18 @@ fn parse_response(response_text: String, client_id: &str) -> Vec<ReceivedEvent>
                 panic!("{}: failed to get ID line from:\n{}", client_id, id_line)
             })),
             None => {
-                if id_line.trim().is_empty() && received_events.is_empty() {
+                if id_line.trim().is_empty()
+                    && (received_events.is_empty()
+                        || data.starts_with("{\"StreamInstance\"")
+                        || data.starts_with("{\"EventsSkipped\""))
+                {
                     None
                 } else if id_line.trim() == ":" {
                     continue;
                 } else {
                     panic!(
-                        "{}: every event must have an ID except the first one",
+                        "{}: every event must have an ID except ApiVersion, StreamInstance and \
+                        EventsSkipped",
                         client_id
                     );
                 }
@@ -998,9 +1068,10 @@ async fn should_persist_event_ids(path: &str) {
         fixture.stop_server().await;
 
         assert_eq!(received_events, expected_events);
+        // The `ApiVersion` and `StreamInstance` events have no ID.
         assert!(received_events
             .iter()
-            .skip(1)
+            .skip(2)
             .all(|event| event.id.unwrap() >= first_run_final_id));
     }
 }
@@ -1020,6 +1091,73 @@ async fn should_persist_signature_event_ids() {
     should_persist_event_ids(SIGS_PATH).await;
 }
 
+/// Check that a client resuming from an event emitted before the server restarted is told that the
+/// earlier events were skipped, and that the restarted server has a new stream instance ID.
+async fn should_send_events_skipped_after_restart(path: &str) {
+    let mut rng = crate::new_rng();
+    let mut fixture = TestFixture::new(&mut rng);
+
+    // Run the first server to emit the 100 events, consume these and stop the server.  They're
+    // consumed via the main path, as the server stops emitting once stopped, and the last event
+    // matching other paths may precede the last event emitted.
+    let mut server_behavior = ServerBehavior::new();
+    let barrier = server_behavior.add_client_sync_before_event(0);
+    let server_address = fixture.run_server(server_behavior).await;
+    let first_stream_instance_id = fixture.stream_instance_id;
+
+    let url1 = url(server_address, MAIN_PATH, None);
+    let (expected_events, first_run_final_id) = fixture.all_filtered_events(MAIN_PATH);
+    let received_events = subscribe(&url1, barrier, first_run_final_id, "client 1")
+        .await
+        .unwrap();
+    fixture.stop_server().await;
+    assert_eq!(received_events, expected_events);
+
+    // Start a new server with a client barrier set for just before its first event, which should
+    // directly follow the last event emitted by the first server.
+    let mut server_behavior = ServerBehavior::new();
+    let barrier = server_behavior.add_client_sync_before_event(EVENT_COUNT);
+    let server_address = fixture.run_server(server_behavior).await;
+    assert_eq!(fixture.first_event_id, EVENT_COUNT);
+    assert_ne!(fixture.stream_instance_id, first_stream_instance_id);
+
+    // Resume from the final event received from the first server.  All the events emitted by the
+    // first server should be reported as skipped, followed by all those from the second server.
+    let url2 = url(server_address, path, Some(first_run_final_id));
+    let (mut expected_events, final_id) = fixture.filtered_events(path, first_run_final_id);
+    expected_events.insert(
+        2,
+        ReceivedEvent {
+            id: None,
+            data: serde_json::to_string(&SseData::EventsSkipped {
+                last_skipped_id: EVENT_COUNT - 1,
+            })
+            .unwrap(),
+        },
+    );
+    let received_events = subscribe(&url2, barrier, final_id, "client 2")
+        .await
+        .unwrap();
+    fixture.stop_server().await;
+
+    assert_eq!(received_events, expected_events);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn should_send_main_events_skipped_after_restart() {
+    should_send_events_skipped_after_restart(MAIN_PATH).await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn should_send_deploy_accepted_events_skipped_after_restart() {
+    should_send_events_skipped_after_restart(DEPLOYS_PATH).await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn should_send_signature_events_skipped_after_restart() {
+    should_send_events_skipped_after_restart(SIGS_PATH).await;
+}
+
 /// Check that a server handles wrapping round past the maximum value for event IDs.
 async fn should_handle_wrapping_past_max_event_id(path: &str) {
     let mut rng = crate::new_rng();
@@ -1160,6 +1298,489 @@ async fn should_limit_concurrent_subscribers() {
     fixture.stop_server().await;
 }
 
+/// Checks that a newly-accepted deploy and a rejected one are both served on the deploys channel,
+/// the latter along with the reason for its rejection and its source.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn should_serve_deploy_accepted_and_rejected_events() {
+    let _ = logging::init();
+    let mut rng = crate::new_rng();
+    let storage_dir = tempfile::tempdir().unwrap();
+
+    let good_deploy = Deploy::random(&mut rng);
+    let mut bad_deploy = Deploy::random(&mut rng);
+    let failure = bad_deploy
+        .is_acceptable(
+            "not-the-chain-name",
+            &DeployConfig::default().deploy_limits(),
+            DeployHashScheme::Legacy,
+        )
+        .unwrap_err();
+    let reason = deploy_acceptor::Error::InvalidDeploy(failure).to_string();
+
+    let mut deploys = HashMap::new();
+    let _ = deploys.insert(*good_deploy.id(), good_deploy.clone());
+    let mut server = EventStreamServer::new(
+        Config::default(),
+        storage_dir.path().to_path_buf(),
+        ProtocolVersion::V1_0_0,
+        DeployConfig::default().max_deploy_size,
+        DeployGetter::with_deploys(deploys),
+        &Registry::new(),
+    )
+    .unwrap();
+    let first_event_id = server.event_indexer.current_index();
+
+    let _ = server.broadcast(SseData::DeployAccepted {
+        deploy: *good_deploy.id(),
+    });
+    let _ = server.broadcast_deploy_rejected(*bad_deploy.id(), reason.clone(), Source::Client);
+
+    let url = url(server.listening_address, DEPLOYS_PATH, Some(first_event_id));
+    let received_events = subscribe_no_sync(&url, first_event_id + 1, "client")
+        .await
+        .unwrap();
+
+    let expected_events = vec![
+        ReceivedEvent {
+            id: None,
+            data: serde_json::to_string(&SseData::ApiVersion(ProtocolVersion::V1_0_0)).unwrap(),
+        },
+        ReceivedEvent {
+            id: None,
+            data: serde_json::to_string(&SseData::StreamInstance {
+                stream_instance_id: server.stream_instance_id,
+            })
+            .unwrap(),
+        },
+        ReceivedEvent {
+            id: Some(first_event_id),
+            data: serde_json::to_string(&good_deploy).unwrap(),
+        },
+        ReceivedEvent {
+            id: Some(first_event_id + 1),
+            data: serde_json::to_string(&SseData::DeployRejected {
+                deploy_hash: *bad_deploy.id(),
+                reason,
+                source: DeploySource::Client,
+            })
+            .unwrap(),
+        },
+    ];
+    assert_eq!(received_events, expected_events);
+}
+
+/// Checks that `Step` and `DeployProcessed` events are served with their effects in canonical
+/// order, along with the transform count and the digest of the canonical effect.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn should_serve_canonical_effects_with_transform_count_and_digest() {
+    let _ = logging::init();
+    let mut rng = crate::new_rng();
+    let storage_dir = tempfile::tempdir().unwrap();
+    let mut server = EventStreamServer::new(
+        Config::default(),
+        storage_dir.path().to_path_buf(),
+        ProtocolVersion::V1_0_0,
+        DeployConfig::default().max_deploy_size,
+        DeployGetter::with_deploys(HashMap::new()),
+        &Registry::new(),
+    )
+    .unwrap();
+    let first_event_id = server.event_indexer.current_index();
+
+    // Transforms of interleaved key types, out of canonical order.
+    let keys = [
+        "uref-0101010101010101010101010101010101010101010101010101010101010101-007",
+        "hash-0202020202020202020202020202020202020202020202020202020202020202",
+        "account-hash-0303030303030303030303030303030303030303030303030303030303030303",
+        "deploy-0404040404040404040404040404040404040404040404040404040404040404",
+        "balance-0505050505050505050505050505050505050505050505050505050505050505",
+    ];
+    let transforms = keys
+        .iter()
+        .map(|key| TransformEntry {
+            key: key.to_string(),
+            transform: rng.gen(),
+        })
+        .collect();
+    let effect = ExecutionEffect {
+        operations: Vec::new(),
+        transforms,
+    };
+    assert!(!effect.is_canonical());
+    let execution_result = ExecutionResult::Success {
+        effect: effect.clone(),
+        transfers: Vec::new(),
+        cost: 1.into(),
+    };
+    let deploy = Deploy::random(&mut rng);
+
+    let _ = server.broadcast(SseData::step(EraId::new(1), effect));
+    let _ = server.broadcast(SseData::deploy_processed(
+        *deploy.id(),
+        deploy.header(),
+        BlockHash::random(&mut rng),
+        Box::new(execution_result),
+    ));
+
+    let url = url(server.listening_address, MAIN_PATH, Some(first_event_id));
+    let received_events = subscribe_no_sync(&url, first_event_id + 1, "client")
+        .await
+        .unwrap();
+
+    // Skip the `ApiVersion` and `StreamInstance` events.
+    assert_eq!(received_events.len(), 4);
+    let mut expected_keys = keys.to_vec();
+    expected_keys.sort_unstable();
+    for received_event in &received_events[2..] {
+        let data: Value = serde_json::from_str(&received_event.data).unwrap();
+        let (event, effect) = match data.get("Step") {
+            Some(step) => (step, &step["execution_effect"]),
+            None => {
+                let deploy_processed = &data["DeployProcessed"];
+                (
+                    deploy_processed,
+                    &deploy_processed["execution_result"]["Success"]["effect"],
+                )
+            }
+        };
+        let effect: ExecutionEffect = serde_json::from_value(effect.clone()).unwrap();
+        let received_keys: Vec<_> = effect
+            .transforms
+            .iter()
+            .map(|entry| entry.key.as_str())
+            .collect();
+        assert_eq!(received_keys, expected_keys);
+        assert_eq!(event["transform_count"], json!(keys.len()));
+
+        // The digest survives the round trip via JSON, as it's of the canonical binary form.
+        let digest = hash::hash(&effect.to_bytes().unwrap());
+        assert_eq!(
+            event["effect_digest"],
+            serde_json::to_value(digest).unwrap()
+        );
+    }
+}
+
+/// Checks that a client reconnecting via the `Last-Event-ID` header is sent an `EventsSkipped`
+/// event if some of the events it missed were evicted from the buffer to keep within the byte
+/// budget, followed by the remaining buffered events.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn should_send_events_skipped_for_events_evicted_by_byte_budget() {
+    let _ = logging::init();
+    let mut rng = crate::new_rng();
+    let storage_dir = tempfile::tempdir().unwrap();
+
+    let config = Config {
+        max_deploys_buffer_bytes: 4096,
+        ..Default::default()
+    };
+    let mut server = EventStreamServer::new(
+        config,
+        storage_dir.path().to_path_buf(),
+        ProtocolVersion::V1_0_0,
+        DeployConfig::default().max_deploy_size,
+        DeployGetter::with_deploys(HashMap::new()),
+        &Registry::new(),
+    )
+    .unwrap();
+    let first_event_id = server.event_indexer.current_index();
+
+    // The huge rejection exceeds the byte budget on its own, so is evicted immediately.
+    let huge_rejection = SseData::DeployRejected {
+        deploy_hash: DeployHash::random(&mut rng),
+        reason: "x".repeat(10_000),
+        source: DeploySource::Client,
+    };
+    let tiny_evictions: Vec<_> = (0..3)
+        .map(|_| SseData::random_deploy_evicted(&mut rng))
+        .collect();
+    let _ = server.broadcast(huge_rejection);
+    for event in &tiny_evictions {
+        let _ = server.broadcast(event.clone());
+    }
+
+    // Reconnect as if the last event received was the one before the huge rejection.
+    let url = url(server.listening_address, DEPLOYS_PATH, None);
+    let response = reqwest::Client::new()
+        .get(&url)
+        .header(LAST_EVENT_ID_HEADER, first_event_id.wrapping_sub(1))
+        .send()
+        .await
+        .unwrap();
+    let received_events = handle_response(response, first_event_id + 3, "client")
+        .await
+        .unwrap();
+
+    let expected_events: Vec<_> = vec![
+        ReceivedEvent {
+            id: None,
+            data: serde_json::to_string(&SseData::ApiVersion(ProtocolVersion::V1_0_0)).unwrap(),
+        },
+        ReceivedEvent {
+            id: None,
+            data: serde_json::to_string(&SseData::StreamInstance {
+                stream_instance_id: server.stream_instance_id,
+            })
+            .unwrap(),
+        },
+        ReceivedEvent {
+            id: None,
+            data: serde_json::to_string(&SseData::EventsSkipped {
+                last_skipped_id: first_event_id,
+            })
+            .unwrap(),
+        },
+    ]
+    .into_iter()
+    .chain(
+        tiny_evictions
+            .iter()
+            .enumerate()
+            .map(|(index, event)| ReceivedEvent {
+                id: Some(first_event_id + 1 + index as Id),
+                data: serde_json::to_string(event).unwrap(),
+            }),
+    )
+    .collect();
+    assert_eq!(received_events, expected_events);
+}
+
+/// Checks that rejections of deploys received from peers are published no faster than the
+/// configured rate, while rejections of deploys submitted by clients are all published.
+#[tokio::test]
+async fn should_rate_limit_deploy_rejections_from_peers() {
+    let _ = logging::init();
+    let mut rng = crate::new_rng();
+    let storage_dir = tempfile::tempdir().unwrap();
+
+    let config = Config {
+        max_peer_rejections_per_second: 2,
+        ..Default::default()
+    };
+    let mut server = EventStreamServer::new(
+        config,
+        storage_dir.path().to_path_buf(),
+        ProtocolVersion::V1_0_0,
+        DeployConfig::default().max_deploy_size,
+        DeployGetter::with_deploys(HashMap::new()),
+        &Registry::new(),
+    )
+    .unwrap();
+    let first_event_id = server.event_indexer.current_index();
+
+    for _ in 0..5 {
+        let _ = server.broadcast_deploy_rejected(
+            DeployHash::random(&mut rng),
+            String::from("invalid deploy"),
+            Source::Peer(NodeId::random(&mut rng)),
+        );
+    }
+    assert_eq!(server.event_indexer.current_index(), first_event_id + 2);
+
+    for _ in 0..5 {
+        let _ = server.broadcast_deploy_rejected(
+            DeployHash::random(&mut rng),
+            String::from("invalid deploy"),
+            Source::Client,
+        );
+    }
+    assert_eq!(server.event_indexer.current_index(), first_event_id + 7);
+}
+
+/// Returns the URL for a websocket client to use to connect to the server at the given address.
+fn ws_url(server_address: SocketAddr) -> String {
+    format!(
+        "ws://{}/{}/{}",
+        server_address, WS_API_PATH_PREFIX, ROOT_PATH
+    )
+}
+
+/// The representation of a websocket frame as received by a subscribed client.
+#[derive(Deserialize)]
+enum ReceivedFrame {
+    Event { id: Option<Id>, data: Value },
+    Lagged { missed_events: u64 },
+    InvalidSubscription { error: String },
+}
+
+/// Converts the events as received via SSE into the form received via websocket.
+///
+/// The data is compared as JSON values, since the key order isn't significant.
+fn to_ws_events(events: Vec<ReceivedEvent>) -> Vec<(Option<Id>, Value)> {
+    events
+        .into_iter()
+        .map(|event| (event.id, serde_json::from_str(&event.data).unwrap()))
+        .collect()
+}
+
+/// Reads frames from the websocket until the next event, ignoring pings.
+///
+/// Panics if the server sends anything other than an event or closes the connection.
+async fn next_ws_event<S>(websocket: &mut S, client_id: &str) -> (Option<Id>, Value)
+where
+    S: futures::Stream<Item = Result<WsMessage, tungstenite::Error>> + Unpin,
+{
+    while let Some(message) = websocket.next().await {
+        match message.unwrap() {
+            WsMessage::Text(text) => match serde_json::from_str(&text).unwrap() {
+                ReceivedFrame::Event { id, data } => return (id, data),
+                ReceivedFrame::Lagged { missed_events } => {
+                    panic!("{} lagged by {} events", client_id, missed_events)
+                }
+                ReceivedFrame::InvalidSubscription { error } => {
+                    panic!("{} sent invalid subscription: {}", client_id, error)
+                }
+            },
+            WsMessage::Close(_) => break,
+            _ => continue,
+        }
+    }
+    panic!("{} disconnected by server", client_id);
+}
+
+/// Runs a websocket client, sending `subscription` and then consuming all events until the server
+/// has emitted the event with ID `final_event_id`.
+///
+/// The client waits at the barrier before connecting to the server, and then again once it has
+/// received the initial `ApiVersion` event to ensure the server doesn't start sending events before
+/// the client is subscribed.
+async fn subscribe_ws(
+    server_address: SocketAddr,
+    subscription: &str,
+    barrier: Arc<Barrier>,
+    final_event_id: Id,
+    client_id: &str,
+) -> Vec<(Option<Id>, Value)> {
+    debug!("{} waiting before connecting via websocket", client_id);
+    barrier.wait().await;
+    let (mut websocket, _) = tokio_tungstenite::connect_async(ws_url(server_address))
+        .await
+        .unwrap();
+    websocket
+        .send(WsMessage::Text(subscription.to_string()))
+        .await
+        .unwrap();
+    let mut received_events = vec![next_ws_event(&mut websocket, client_id).await];
+    debug!("{} waiting after subscribing", client_id);
+    barrier.wait().await;
+    debug!("{} finished waiting", client_id);
+
+    while received_events.last().unwrap().0 != Some(final_event_id) {
+        received_events.push(next_ws_event(&mut websocket, client_id).await);
+    }
+    let _ = websocket.close(None).await;
+    received_events
+}
+
+/// Client setup:
+///   * `<IP:port>/ws/events`
+///   * subscribed to `BlockAdded`, `DeployAccepted` and `FinalitySignature` events
+///   * no last-seen event ID
+///   * connected before first event
+///
+/// Expected to receive only the subscribed kinds of event, with the same IDs and data as via SSE.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn should_serve_filtered_events_via_websocket() {
+    let mut rng = crate::new_rng();
+    let mut fixture = TestFixture::new(&mut rng);
+
+    let mut server_behavior = ServerBehavior::new();
+    let barrier = server_behavior.add_client_sync_before_event(0);
+    let server_address = fixture.run_server(server_behavior).await;
+
+    let subscription = r#"{"event_kinds": ["BlockAdded", "DeployAccepted", "FinalitySignature"]}"#;
+    let filter = [
+        EventFilter::BlockAdded,
+        EventFilter::DeployAccepted,
+        EventFilter::FinalitySignature,
+    ];
+    let (expected_events, final_id) = fixture.events_matching(&filter, fixture.first_event_id);
+    let received_events =
+        subscribe_ws(server_address, subscription, barrier, final_id, "client").await;
+    fixture.stop_server().await;
+
+    assert_eq!(received_events, to_ws_events(expected_events));
+}
+
+/// Client setup:
+///   * `<IP:port>/ws/events`
+///   * subscribed to main events
+///   * last-seen event ID 24
+///   * connected just before event ID 50
+///
+/// Expected to receive main events from ID 25 onwards, as events 25 to 49 should still be in the
+/// server buffer.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn should_replay_events_after_last_seen_id_via_websocket() {
+    let mut rng = crate::new_rng();
+    let mut fixture = TestFixture::new(&mut rng);
+
+    let connect_at_event_id = BUFFER_LENGTH;
+    let start_from_event_id = BUFFER_LENGTH / 2;
+
+    let mut server_behavior = ServerBehavior::new();
+    let barrier = server_behavior.add_client_sync_before_event(connect_at_event_id);
+    let server_address = fixture.run_server(server_behavior).await;
+
+    let subscription = serde_json::json!({
+        "event_kinds": ["BlockAdded", "DeployProcessed", "Fault", "Step"],
+        "last_seen_event_id": start_from_event_id - 1
+    })
+    .to_string();
+    let (expected_events, final_id) = fixture.filtered_events(MAIN_PATH, start_from_event_id);
+    let received_events =
+        subscribe_ws(server_address, &subscription, barrier, final_id, "client").await;
+    fixture.stop_server().await;
+
+    assert_eq!(received_events, to_ws_events(expected_events));
+}
+
+/// Checks that a server rejects new websocket clients when it already has the specified limit of
+/// connected websocket clients, and accepts them again once a client disconnects.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn should_limit_concurrent_websocket_subscribers() {
+    let mut rng = crate::new_rng();
+    let mut fixture = TestFixture::new(&mut rng);
+
+    let mut server_behavior = ServerBehavior::new();
+    server_behavior.set_max_concurrent_websocket_subscribers(2);
+    let server_address = fixture.run_server(server_behavior).await;
+
+    let subscription = r#"{"event_kinds": ["BlockAdded"]}"#;
+    let connect = || async {
+        let (mut websocket, _) = tokio_tungstenite::connect_async(ws_url(server_address)).await?;
+        websocket
+            .send(WsMessage::Text(subscription.to_string()))
+            .await?;
+        Ok::<_, tungstenite::Error>(websocket)
+    };
+
+    // The first two clients should be accepted, and the third rejected.
+    let mut websocket1 = connect().await.unwrap();
+    let mut websocket2 = connect().await.unwrap();
+    let _ = next_ws_event(&mut websocket1, "client 1").await;
+    let _ = next_ws_event(&mut websocket2, "client 2").await;
+    assert!(connect().await.is_err());
+
+    // Once a client disconnects, a new one should be accepted.  The server handles the disconnect
+    // asynchronously, so retry until it has done so.
+    websocket1.close(None).await.unwrap();
+    drop(websocket1);
+    let mut websocket3 = time::timeout(MAX_TEST_TIME, async {
+        loop {
+            match connect().await {
+                Ok(websocket) => break websocket,
+                Err(_) => time::sleep(DELAY_BETWEEN_EVENTS).await,
+            }
+        }
+    })
+    .await
+    .expect("should accept new client after another disconnects");
+    let _ = next_ws_event(&mut websocket3, "client 3").await;
+
+    fixture.stop_server().await;
+}
+
 /// Rather than being a test proper, this is more a means to easily determine differences between
 /// versions of the events emitted by the SSE server by comparing the contents of
 /// `resources/test/sse_data_schema.json` across different versions of the codebase.
@@ -1176,3 +1797,167 @@ fn schema() {
         expected_schema.trim()
     );
 }
+
+/// Creates and runs a new `EventStreamServer` with deploy submission enabled.
+fn new_deploy_submission_server(
+    storage_dir: &TempDir,
+    max_deploy_size: u32,
+    secret: Option<&str>,
+) -> EventStreamServer {
+    let config = Config {
+        enable_deploy_submission: true,
+        deploy_submission_secret: secret.map(str::to_string),
+        ..Default::default()
+    };
+    EventStreamServer::new(
+        config,
+        storage_dir.path().to_path_buf(),
+        ProtocolVersion::V1_0_0,
+        max_deploy_size,
+        DeployGetter::with_deploys(HashMap::new()),
+        &Registry::new(),
+    )
+    .unwrap()
+}
+
+/// Returns a new signed deploy for the given chain.
+fn new_deploy(rng: &mut TestRng, chain_name: &str) -> Deploy {
+    let module_bytes = ExecutableDeployItem::ModuleBytes {
+        module_bytes: Bytes::new(),
+        args: RuntimeArgs::new(),
+    };
+    Deploy::new(
+        Timestamp::now(),
+        TimeDiff::from(60_000),
+        1,
+        vec![],
+        chain_name.to_string(),
+        module_bytes.clone(),
+        module_bytes,
+        &SecretKey::random(rng),
+    )
+}
+
+/// Submits the given body to the server's deploy submission endpoint, providing the secret if
+/// `Some`.
+async fn put_deploy(server_address: SocketAddr, body: Vec<u8>, secret: Option<&str>) -> Response {
+    let url = format!("http://{}/{}", server_address, PUT_DEPLOY_PATH);
+    let mut request = reqwest::Client::new().put(&url).body(body);
+    if let Some(secret) = secret {
+        request = request.header(SECRET_HEADER, secret);
+    }
+    request.send().await.unwrap()
+}
+
+async fn parse_put_deploy_response(response: Response) -> PutDeployResponse {
+    serde_json::from_str(&response.text().await.unwrap()).unwrap()
+}
+
+/// Checks that a deploy submitted to the event stream server is handled as if submitted via the RPC
+/// server: a valid deploy's hash is returned, while an invalid deploy is rejected for the same
+/// reason as the RPC server would give.
+#[tokio::test]
+async fn should_accept_valid_and_reject_invalid_deploy_submissions() {
+    let _ = logging::init();
+    let mut rng = crate::new_rng();
+    let storage_dir = tempfile::tempdir().unwrap();
+    let server =
+        new_deploy_submission_server(&storage_dir, DeployConfig::default().max_deploy_size, None);
+
+    let good_deploy = new_deploy(&mut rng, TEST_CHAIN_NAME);
+    let body = serde_json::to_vec(&good_deploy).unwrap();
+    let response = put_deploy(server.listening_address, body, None).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let expected_response = PutDeployResponse::Accepted {
+        api_version: ProtocolVersion::V1_0_0,
+        deploy_hash: *good_deploy.id(),
+    };
+    assert_eq!(parse_put_deploy_response(response).await, expected_response);
+
+    let mut bad_deploy = new_deploy(&mut rng, "not-the-chain-name");
+    let body = serde_json::to_vec(&bad_deploy).unwrap();
+    let failure = bad_deploy
+        .is_acceptable(
+            TEST_CHAIN_NAME,
+            &DeployConfig::default().deploy_limits(),
+            DeployHashScheme::Legacy,
+        )
+        .unwrap_err();
+    let response = put_deploy(server.listening_address, body, None).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let expected_response = PutDeployResponse::Rejected {
+        deploy_hash: *bad_deploy.id(),
+        reason: deploy_acceptor::Error::InvalidDeploy(failure).to_string(),
+    };
+    assert_eq!(parse_put_deploy_response(response).await, expected_response);
+
+    let body = b"not a deploy".to_vec();
+    let response = put_deploy(server.listening_address, body, None).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+/// Checks that deploy submissions whose body exceeds the limit derived from the max deploy size are
+/// rejected.
+#[tokio::test]
+async fn should_reject_oversized_deploy_submissions() {
+    let _ = logging::init();
+    let mut rng = crate::new_rng();
+    let storage_dir = tempfile::tempdir().unwrap();
+    let server = new_deploy_submission_server(&storage_dir, 100, None);
+
+    let deploy = new_deploy(&mut rng, TEST_CHAIN_NAME);
+    let body = serde_json::to_vec(&deploy).unwrap();
+    let response = put_deploy(server.listening_address, body, None).await;
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+}
+
+/// Checks that if a secret is configured, only deploy submissions providing it are handled.
+#[tokio::test]
+async fn should_require_secret_for_deploy_submissions_if_configured() {
+    let _ = logging::init();
+    let mut rng = crate::new_rng();
+    let storage_dir = tempfile::tempdir().unwrap();
+    let secret = "open sesame";
+    let server = new_deploy_submission_server(
+        &storage_dir,
+        DeployConfig::default().max_deploy_size,
+        Some(secret),
+    );
+
+    let deploy = new_deploy(&mut rng, TEST_CHAIN_NAME);
+    let body = serde_json::to_vec(&deploy).unwrap();
+    for wrong_secret in &[None, Some("open sesam"), Some("open sesame!")] {
+        let response = put_deploy(server.listening_address, body.clone(), *wrong_secret).await;
+        assert_eq!(
+            response.status(),
+            StatusCode::UNAUTHORIZED,
+            "secret: {:?}",
+            wrong_secret
+        );
+    }
+
+    let response = put_deploy(server.listening_address, body, Some(secret)).await;
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+/// Checks that deploy submissions are not handled unless enabled in the config.
+#[tokio::test]
+async fn should_not_accept_deploy_submissions_by_default() {
+    let _ = logging::init();
+    let mut rng = crate::new_rng();
+    let storage_dir = tempfile::tempdir().unwrap();
+    let server = EventStreamServer::new(
+        Config::default(),
+        storage_dir.path().to_path_buf(),
+        ProtocolVersion::V1_0_0,
+        DeployConfig::default().max_deploy_size,
+        DeployGetter::with_deploys(HashMap::new()),
+        &Registry::new(),
+    )
+    .unwrap();
+
+    let deploy = new_deploy(&mut rng, TEST_CHAIN_NAME);
+    let body = serde_json::to_vec(&deploy).unwrap();
+    let response = put_deploy(server.listening_address, body, None).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,297 @@
+//! Types and functions used by the http server to serve the event stream over a websocket.
+//!
+//! A client connects to `/ws/events` and must then send a single JSON-encoded [`Subscription`] as
+//! a text frame, specifying which kinds of event it wants to receive and optionally the ID of the
+//! last event it saw.  The server then streams the requested events as JSON-encoded text frames,
+//! with the same ID and data as would be sent via the SSE endpoints.  The server sends a ping
+//! frame every `PING_INTERVAL`, and if the client falls too far behind, it is sent a `Lagged` frame
+//! and the connection is closed.
+
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use futures::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use tokio::{
+    select,
+    sync::{
+        broadcast::{self, error::RecvError},
+        mpsc,
+    },
+    time,
+};
+use tracing::{debug, error, info, warn};
+use warp::{
+    filters::BoxedFilter,
+    path,
+    reply::Response,
+    ws::{Message, WebSocket, Ws},
+    Filter, Reply,
+};
+
+use super::{
+    sse_server::{
+        self, BroadcastChannelMessage, EventFilter, Id, NewSubscriberInfo, ServerSentEvent,
+        SSE_API_ROOT_PATH,
+    },
+    DeployGetter, SseData,
+};
+
+/// The URL path part preceding `SSE_API_ROOT_PATH` for the websocket endpoint.
+pub const WS_API_PATH_PREFIX: &str = "ws";
+
+/// The interval at which ping frames are sent to each client.
+const PING_INTERVAL: Duration = Duration::from_secs(10);
+
+/// The max time to wait for a newly-connected client to send its subscription.
+const SUBSCRIPTION_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The subscription which a client must send as the first frame after connecting.
+#[derive(Clone, PartialEq, Eq, Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub(super) struct Subscription {
+    /// The kinds of event the client wants to receive.
+    event_kinds: Vec<EventFilter>,
+    /// The ID of the last event the client received, if any.  Buffered events after this one will
+    /// be sent before any new events.
+    #[serde(default)]
+    last_seen_event_id: Option<Id>,
+}
+
+/// A JSON-encoded frame sent from the server to a client.
+#[derive(Serialize)]
+enum ServerFrame<'a, T: Serialize> {
+    /// An event as would be sent via the SSE endpoints.
+    Event { id: Option<Id>, data: &'a T },
+    /// The client lagged too far behind the server and missed the given number of events.  The
+    /// connection is closed after sending this frame.
+    Lagged { missed_events: u64 },
+    /// The client's subscription was invalid.  The connection is closed after sending this frame.
+    InvalidSubscription { error: String },
+}
+
+impl<'a, T: Serialize> ServerFrame<'a, T> {
+    fn to_message(&self) -> Option<Message> {
+        match serde_json::to_string(self) {
+            Ok(json) => Some(Message::text(json)),
+            Err(error) => {
+                warn!(%error, "failed to jsonify websocket frame");
+                None
+            }
+        }
+    }
+}
+
+/// A control frame, i.e. one which doesn't carry event data.
+type ControlFrame = ServerFrame<'static, ()>;
+
+/// Decrements the count of connected websocket clients when dropped.
+struct ConnectionGuard(Arc<AtomicUsize>);
+
+impl ConnectionGuard {
+    /// Returns a new guard if there are fewer than `max` connected clients, or `None` otherwise.
+    fn try_new(count: &Arc<AtomicUsize>, max: u32) -> Option<Self> {
+        let previous_count = count.fetch_add(1, Ordering::SeqCst);
+        let guard = ConnectionGuard(Arc::clone(count));
+        if previous_count >= max as usize {
+            return None;
+        }
+        Some(guard)
+    }
+}
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Creates the warp filter for the websocket endpoint.
+///
+/// This shares the broadcast channel and the `NewSubscriberInfo` channel with the SSE endpoints,
+/// so that event IDs and buffered events are the same regardless of endpoint.
+pub(super) fn create_filter(
+    event_broadcaster: broadcast::Sender<BroadcastChannelMessage>,
+    new_subscriber_info_sender: mpsc::UnboundedSender<NewSubscriberInfo>,
+    max_concurrent_subscribers: u32,
+    deploy_getter: DeployGetter,
+) -> BoxedFilter<(Response,)> {
+    let connection_count = Arc::new(AtomicUsize::new(0));
+    warp::get()
+        .and(path(WS_API_PATH_PREFIX))
+        .and(path(SSE_API_ROOT_PATH))
+        .and(path::end())
+        .and(warp::ws())
+        .map(move |ws: Ws| {
+            // If we already have the maximum number of subscribers, reject this new one.
+            let guard =
+                match ConnectionGuard::try_new(&connection_count, max_concurrent_subscribers) {
+                    Some(guard) => guard,
+                    None => {
+                        info!(
+                            %max_concurrent_subscribers,
+                            "event stream server has max websocket subscribers: rejecting new one"
+                        );
+                        return sse_server::create_503();
+                    }
+                };
+
+            let event_broadcaster = event_broadcaster.clone();
+            let new_subscriber_info_sender = new_subscriber_info_sender.clone();
+            let deploy_getter = deploy_getter.clone();
+            ws.on_upgrade(move |websocket| async move {
+                handle_client(
+                    websocket,
+                    event_broadcaster,
+                    new_subscriber_info_sender,
+                    deploy_getter,
+                )
+                .await;
+                drop(guard);
+            })
+            .into_response()
+        })
+        .boxed()
+}
+
+/// Waits for the client's subscription, then streams the requested events to the client until
+/// either the client disconnects, lags too far behind, or the server shuts down.
+async fn handle_client(
+    websocket: WebSocket,
+    event_broadcaster: broadcast::Sender<BroadcastChannelMessage>,
+    new_subscriber_info_sender: mpsc::UnboundedSender<NewSubscriberInfo>,
+    deploy_getter: DeployGetter,
+) {
+    let (mut sink, mut incoming) = websocket.split();
+
+    let subscription = match time::timeout(SUBSCRIPTION_TIMEOUT, incoming.next()).await {
+        Ok(Some(Ok(message))) => parse_subscription(&message),
+        Ok(Some(Err(error))) => {
+            debug!(%error, "websocket client errored before subscribing");
+            return;
+        }
+        Ok(None) => {
+            debug!("websocket client disconnected before subscribing");
+            return;
+        }
+        Err(_) => Err(String::from("timed out waiting for subscription")),
+    };
+    let Subscription {
+        event_kinds,
+        last_seen_event_id,
+    } = match subscription {
+        Ok(subscription) => subscription,
+        Err(error) => {
+            debug!(%error, "invalid websocket subscription");
+            let frame = ControlFrame::InvalidSubscription { error };
+            if let Some(message) = frame.to_message() {
+                let _ = sink.send(message).await;
+            }
+            let _ = sink.send(Message::close()).await;
+            return;
+        }
+    };
+
+    // Subscribe to the ongoing events before requesting the initial ones so that no events are
+    // missed in between.  Any duplicates are filtered out by `merged_event_stream`.
+    let ongoing_events_receiver = event_broadcaster.subscribe();
+    let (initial_events_sender, initial_events_receiver) = mpsc::unbounded_channel();
+    let new_subscriber_info = NewSubscriberInfo {
+        start_from: last_seen_event_id.map(|id| id.wrapping_add(1)),
+        event_filter: event_kinds.clone(),
+        initial_events_sender,
+    };
+    if new_subscriber_info_sender
+        .send(new_subscriber_info)
+        .is_err()
+    {
+        error!("failed to send new subscriber info");
+    }
+
+    let mut events = Box::pin(sse_server::merged_event_stream(
+        initial_events_receiver,
+        ongoing_events_receiver,
+    ));
+    let mut ping_interval = time::interval(PING_INTERVAL);
+
+    loop {
+        select! {
+            maybe_event = events.next() => {
+                let message = match maybe_event {
+                    Some(Ok(event)) => {
+                        if !event.data.should_include(&event_kinds) {
+                            continue;
+                        }
+                        match event_to_message(&event, deploy_getter.clone()).await {
+                            Some(message) => message,
+                            None => continue,
+                        }
+                    }
+                    Some(Err(RecvError::Lagged(missed_events))) => {
+                        let frame = ControlFrame::Lagged { missed_events };
+                        if let Some(message) = frame.to_message() {
+                            let _ = sink.send(message).await;
+                        }
+                        let _ = sink.send(Message::close()).await;
+                        return;
+                    }
+                    Some(Err(RecvError::Closed)) | None => {
+                        // The server is shutting down.
+                        let _ = sink.send(Message::close()).await;
+                        return;
+                    }
+                };
+                if sink.send(message).await.is_err() {
+                    debug!("websocket client disconnected");
+                    return;
+                }
+            }
+
+            _ = ping_interval.tick() => {
+                if sink.send(Message::ping(Vec::new())).await.is_err() {
+                    debug!("websocket client disconnected");
+                    return;
+                }
+            }
+
+            maybe_message = incoming.next() => {
+                match maybe_message {
+                    // Pongs and any further frames from the client are ignored.
+                    Some(Ok(message)) if !message.is_close() => {}
+                    _ => {
+                        debug!("websocket client disconnected");
+                        return;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Parses the client's subscription from its first frame.
+fn parse_subscription(message: &Message) -> Result<Subscription, String> {
+    let text = message
+        .to_str()
+        .map_err(|()| String::from("subscription must be sent as a text frame"))?;
+    serde_json::from_str(text).map_err(|error| error.to_string())
+}
+
+/// Converts the event to a websocket message, or `None` if it can't be converted.
+async fn event_to_message(event: &ServerSentEvent, deploy_getter: DeployGetter) -> Option<Message> {
+    match &event.data {
+        SseData::DeployAccepted { deploy } => {
+            let deploy = sse_server::get_deploy(*deploy, deploy_getter).await?;
+            ServerFrame::Event {
+                id: event.id,
+                data: &deploy,
+            }
+            .to_message()
+        }
+        data => ServerFrame::Event { id: event.id, data }.to_message(),
+    }
+}
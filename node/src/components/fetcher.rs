@@ -1,14 +1,19 @@
 mod config;
 mod event;
 mod metrics;
+mod scheduler;
 mod tests;
 
-use std::{collections::HashMap, fmt::Debug, time::Duration};
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    fmt::Debug,
+    time::{Duration, Instant},
+};
 
 use datasize::DataSize;
 use prometheus::Registry;
 use smallvec::smallvec;
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 
 use casper_execution_engine::{
     shared::{newtypes::Blake2bHash, stored_value::StoredValue},
@@ -17,13 +22,18 @@ use casper_execution_engine::{
 use casper_types::Key;
 
 use crate::{
-    components::{fetcher::event::FetchResponder, Component},
+    components::{fetcher::event::FetchResponder, network::ErrorKind, Component},
     effect::{
+        announcements::BlocklistAnnouncement,
         requests::{ContractRuntimeRequest, LinearChainRequest, NetworkRequest, StorageRequest},
         EffectBuilder, EffectExt, Effects,
     },
     protocol::Message,
-    types::{Block, BlockByHeight, BlockHash, Deploy, DeployHash, Item, NodeId},
+    reactor::{ReactorEvent, TimerHandle},
+    types::{
+        Block, BlockByHeight, BlockHash, BlockTransfers, Deploy, DeployApprovals, DeployHash,
+        DeployHeaderWithHash, Item, NodeId,
+    },
     utils::Source,
     NodeRng,
 };
@@ -31,6 +41,11 @@ use crate::{
 pub use config::Config;
 pub use event::{Event, FetchResult};
 use metrics::FetcherMetrics;
+pub(crate) use scheduler::{FetchPermit, FetchScheduler};
+
+/// The maximum number of times a request to a single peer is sent if the network keeps failing to
+/// deliver it with a retryable error.
+const MAX_SEND_ATTEMPTS: u32 = 3;
 
 /// A helper trait constraining `Fetcher` compatible reactor events.
 pub trait ReactorEventT<T>:
@@ -40,6 +55,8 @@ pub trait ReactorEventT<T>:
     + From<ContractRuntimeRequest>
     // Won't be needed when we implement "get block by height" feature in storage.
     + From<LinearChainRequest<NodeId>>
+    + From<BlocklistAnnouncement<NodeId>>
+    + ReactorEvent
     + Send
     + 'static
 where
@@ -57,6 +74,8 @@ where
         + From<StorageRequest>
         + From<ContractRuntimeRequest>
         + From<LinearChainRequest<NodeId>>
+        + From<BlocklistAnnouncement<NodeId>>
+        + ReactorEvent
         + Send
         + 'static,
 {
@@ -65,6 +84,13 @@ where
 pub trait ItemFetcher<T: Item + 'static> {
     fn responders(&mut self) -> &mut HashMap<T::Id, HashMap<NodeId, Vec<FetchResponder<T>>>>;
 
+    /// The pending timeouts of requests sent to peers, which are cancelled once the peer responds.
+    fn timeouts(&mut self) -> &mut HashMap<T::Id, HashMap<NodeId, TimerHandle>>;
+
+    /// The permits of the fetches from peers released by the fetch scheduler, which are returned
+    /// once the fetch from the peer completes.
+    fn permits(&mut self) -> &mut HashMap<T::Id, HashMap<NodeId, FetchPermit>>;
+
     fn peer_timeout(&self) -> Duration;
 
     /// We've been asked to fetch the item by another component of this node.  We'll try to get it
@@ -118,15 +144,9 @@ pub trait ItemFetcher<T: Item + 'static> {
     ) -> Effects<Event<T>> {
         match Message::new_get_request::<T>(&id) {
             Ok(message) => {
-                let mut effects = effect_builder.send_message(peer, message).ignore();
-
-                effects.extend(
-                    effect_builder
-                        .set_timeout(self.peer_timeout())
-                        .event(move |_| Event::TimeoutPeer { id, peer }),
-                );
-
-                effects
+                let timeout = self.peer_timeout();
+                self.set_peer_timeout(effect_builder, id, peer, timeout);
+                effect_builder.send_message(peer, message).ignore()
             }
             Err(error) => {
                 error!("failed to construct get request: {}", error);
@@ -135,7 +155,24 @@ pub trait ItemFetcher<T: Item + 'static> {
         }
     }
 
+    /// Sets a timeout for `peer` to respond to our request for the item, replacing any previous
+    /// one.
+    fn set_peer_timeout<REv: ReactorEventT<T>>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        id: T::Id,
+        peer: NodeId,
+        timeout: Duration,
+    ) {
+        let timer =
+            effect_builder.set_timeout_with_handle(timeout, Event::TimeoutPeer { id, peer });
+        self.timeouts().entry(id).or_default().insert(peer, timer);
+    }
+
     /// Handles signalling responders with the item or `None`.
+    ///
+    /// Cancels the timeouts of the requests which are answered by this, and returns their permits
+    /// to the fetch scheduler.
     fn signal(
         &mut self,
         id: T::Id,
@@ -146,6 +183,8 @@ pub trait ItemFetcher<T: Item + 'static> {
         let mut all_responders = self.responders().remove(&id).unwrap_or_default();
         match result {
             Some(ret) => {
+                self.timeouts().remove(&id);
+                self.permits().remove(&id);
                 // signal all responders waiting for this item
                 for (_, responders) in all_responders {
                     for responder in responders {
@@ -154,6 +193,18 @@ pub trait ItemFetcher<T: Item + 'static> {
                 }
             }
             None => {
+                if let Entry::Occupied(mut timeouts) = self.timeouts().entry(id) {
+                    timeouts.get_mut().remove(&peer);
+                    if timeouts.get().is_empty() {
+                        timeouts.remove();
+                    }
+                }
+                if let Entry::Occupied(mut permits) = self.permits().entry(id) {
+                    permits.get_mut().remove(&peer);
+                    if permits.get().is_empty() {
+                        permits.remove();
+                    }
+                }
                 // remove only the peer specific responders for this id
                 if let Some(responders) = all_responders.remove(&peer) {
                     for responder in responders {
@@ -176,7 +227,21 @@ where
     T: Item + 'static,
 {
     get_from_peer_timeout: Duration,
+    batch_window: Duration,
     responders: HashMap<T::Id, HashMap<NodeId, Vec<FetchResponder<T>>>>,
+    timeouts: HashMap<T::Id, HashMap<NodeId, TimerHandle>>,
+    /// The IDs waiting to be requested from each peer in a single batch.
+    batch_queues: HashMap<NodeId, Vec<T::Id>>,
+    /// The number of times the network has failed to deliver our request to each peer.
+    send_failures: HashMap<T::Id, HashMap<NodeId, u32>>,
+    /// The times until which no requests are sent to peers which asked us to try later.
+    #[data_size(skip)]
+    backoffs: HashMap<NodeId, Instant>,
+    /// The scheduler of fetches from peers, shared with the reactor's other fetchers.
+    #[data_size(skip)]
+    scheduler: FetchScheduler,
+    #[data_size(skip)]
+    permits: HashMap<T::Id, HashMap<NodeId, FetchPermit>>,
     #[data_size(skip)]
     metrics: FetcherMetrics,
 }
@@ -185,14 +250,262 @@ impl<T: Item> Fetcher<T> {
     pub(crate) fn new(
         name: &str,
         config: Config,
+        scheduler: &FetchScheduler,
         registry: &Registry,
     ) -> Result<Self, prometheus::Error> {
+        assert!(
+            !T::TAG.info().id_is_complete_item,
+            "{} items cannot be fetched as their IDs are the complete items",
+            T::TAG
+        );
         Ok(Fetcher {
             get_from_peer_timeout: Duration::from_secs(config.get_from_peer_timeout()),
+            batch_window: Duration::from_millis(config.batch_window()),
             responders: HashMap::new(),
-            metrics: FetcherMetrics::new(name, registry)?,
+            timeouts: HashMap::new(),
+            batch_queues: HashMap::new(),
+            send_failures: HashMap::new(),
+            backoffs: HashMap::new(),
+            scheduler: scheduler.clone(),
+            permits: HashMap::new(),
+            metrics: FetcherMetrics::new(name, T::TAG, registry)?,
         })
     }
+
+    /// Queues the fetch of the item from `peer` with the fetch scheduler, to be requested once
+    /// released.
+    fn schedule(&self, id: T::Id, peer: NodeId) -> Effects<Event<T>> {
+        self.scheduler
+            .acquire(T::TAG)
+            .event(move |permit| Event::FetchReleased { id, peer, permit })
+    }
+
+    /// Handles the fetch scheduler releasing the fetch of the item from `peer`.
+    ///
+    /// The item is requested from `peer`, unless it is no longer wanted from it or is already
+    /// being requested from it, in which case the permit is returned straight away.
+    fn fetch_released<REv: ReactorEventT<T>>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        id: T::Id,
+        peer: NodeId,
+        permit: FetchPermit,
+    ) -> Effects<Event<T>>
+    where
+        Self: ItemFetcher<T>,
+    {
+        let is_pending = self
+            .responders
+            .get(&id)
+            .map_or(false, |responders| responders.contains_key(&peer));
+        if !is_pending {
+            return Effects::new();
+        }
+        match self.permits.entry(id).or_default().entry(peer) {
+            Entry::Occupied(_) => return Effects::new(),
+            Entry::Vacant(entry) => {
+                entry.insert(permit);
+            }
+        }
+
+        if T::BATCHABLE && !self.batch_window.is_zero() {
+            self.enqueue_for_batch(effect_builder, id, peer)
+        } else {
+            self.metrics.requests_sent.inc();
+            self.failed_to_get_from_storage(effect_builder, id, peer)
+        }
+    }
+
+    /// Queues the item to be requested from `peer` as part of a batch, which is sent once the
+    /// batch window has elapsed.
+    fn enqueue_for_batch<REv: ReactorEventT<T>>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        id: T::Id,
+        peer: NodeId,
+    ) -> Effects<Event<T>>
+    where
+        Self: ItemFetcher<T>,
+    {
+        // The request can wait in the queue for up to the batch window before being sent.
+        let timeout = self.get_from_peer_timeout + self.batch_window;
+        self.set_peer_timeout(effect_builder, id, peer, timeout);
+
+        let mut effects = Effects::new();
+        let queue = self.batch_queues.entry(peer).or_default();
+        if queue.is_empty() {
+            effects.extend(
+                effect_builder
+                    .set_timeout(self.batch_window)
+                    .event(move |_| Event::SendBatch { peer }),
+            );
+        }
+        if !queue.contains(&id) {
+            queue.push(id);
+        }
+        effects
+    }
+
+    /// Sends the queued requests for `peer`, as a plain `GetRequest` if only a single item is
+    /// queued.
+    fn send_batch<REv: ReactorEventT<T>>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        peer: NodeId,
+    ) -> Effects<Event<T>>
+    where
+        Self: ItemFetcher<T>,
+    {
+        let ids = self.batch_queues.remove(&peer).unwrap_or_default();
+        let maybe_messages = match ids.as_slice() {
+            [id] => Message::new_get_request::<T>(id).map(|message| vec![message]),
+            _ => Message::new_get_batch_requests::<T>(&ids),
+        };
+
+        let mut effects = Effects::new();
+        match maybe_messages {
+            Ok(messages) => {
+                for message in messages {
+                    self.metrics.requests_sent.inc();
+                    effects.extend(effect_builder.send_message(peer, message).ignore());
+                }
+            }
+            Err(error) => {
+                error!("failed to construct get batch request: {}", error);
+                for id in ids {
+                    effects.extend(self.signal(id, None, peer));
+                }
+            }
+        }
+        effects
+    }
+
+    /// Handles the network failing to deliver our request for the item to `peer`.
+    ///
+    /// A retryable failure causes the request to be resent, up to `MAX_SEND_ATTEMPTS` times in
+    /// total.  Any other failure gives up on the peer immediately rather than waiting for the
+    /// request to time out, as resending could never succeed.
+    fn send_failed<REv: ReactorEventT<T>>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        id: T::Id,
+        peer: NodeId,
+        error_kind: ErrorKind,
+    ) -> Effects<Event<T>>
+    where
+        Self: ItemFetcher<T>,
+    {
+        let is_pending = self
+            .responders
+            .get(&id)
+            .map_or(false, |responders| responders.contains_key(&peer));
+        if !is_pending {
+            self.clear_send_failures(&id, &peer);
+            return Effects::new();
+        }
+
+        let failures = self
+            .send_failures
+            .entry(id)
+            .or_default()
+            .entry(peer)
+            .or_default();
+        *failures += 1;
+        if error_kind.is_retryable() && *failures < MAX_SEND_ATTEMPTS {
+            debug!(%id, %peer, %error_kind, attempt = *failures + 1, "resending request");
+            self.metrics.requests_sent.inc();
+            return self.failed_to_get_from_storage(effect_builder, id, peer);
+        }
+
+        info!(%id, %peer, %error_kind, "failed to send request");
+        self.clear_send_failures(&id, &peer);
+        self.signal(id, None, peer)
+    }
+
+    /// Handles `peer` rejecting our request for the item as it is too busy serving our other
+    /// requests.
+    ///
+    /// The fetch from `peer` is given up on immediately so that the item can be fetched from a
+    /// different peer, and further fetches from `peer` fail without a request being sent until
+    /// `retry_after` has elapsed, capped at the peer timeout.
+    fn try_later(&mut self, id: T::Id, peer: NodeId, retry_after: Duration) -> Effects<Event<T>>
+    where
+        Self: ItemFetcher<T>,
+    {
+        info!(%id, %peer, ?retry_after, "peer asked us to retry later");
+        self.metrics.try_later.inc();
+        let until = Instant::now() + retry_after.min(self.get_from_peer_timeout);
+        let backoff = self.backoffs.entry(peer).or_insert(until);
+        *backoff = (*backoff).max(until);
+        self.clear_send_failures(&id, &peer);
+        self.signal(id, None, peer)
+    }
+
+    /// Handles `item` being received from `peer`.
+    ///
+    /// The item is validated against its ID before being given to any responders awaiting it.
+    fn got_from_peer<REv: ReactorEventT<T>>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        item: Box<T>,
+        peer: NodeId,
+    ) -> Effects<Event<T>>
+    where
+        Self: ItemFetcher<T>,
+    {
+        let id = item.id();
+        if let Err(error) = item.validate(&id) {
+            return self.got_invalid_from_peer(effect_builder, id, peer, error);
+        }
+        self.metrics.found_on_peer.inc();
+        self.send_failures.remove(&id);
+        self.signal(id, Some(FetchResult::FromPeer(item, peer)), peer)
+    }
+
+    /// Handles an item which failed validation being received from `peer`.
+    ///
+    /// The item is dropped and the peer penalised, as an honest peer never sends an invalid item.
+    /// Fetches from the peer of the item's ID fail immediately rather than when the request times
+    /// out, so that they can be retried with a different peer.
+    fn got_invalid_from_peer<REv: ReactorEventT<T>>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        id: T::Id,
+        peer: NodeId,
+        error: T::ValidationError,
+    ) -> Effects<Event<T>>
+    where
+        Self: ItemFetcher<T>,
+    {
+        warn!(%id, %peer, %error, "peer sent invalid item");
+        self.metrics.invalid_items.inc();
+        self.clear_send_failures(&id, &peer);
+        let mut effects = self.signal(id, None, peer);
+        effects.extend(effect_builder.announce_disconnect_from_peer(peer).ignore());
+        effects
+    }
+
+    /// Returns whether we're backing off from sending requests to `peer`.
+    fn is_backing_off_from(&mut self, peer: &NodeId) -> bool {
+        match self.backoffs.get(peer) {
+            Some(until) if *until > Instant::now() => true,
+            Some(_) => {
+                self.backoffs.remove(peer);
+                false
+            }
+            None => false,
+        }
+    }
+
+    /// Forgets any failures to deliver our request for the item to `peer`.
+    fn clear_send_failures(&mut self, id: &T::Id, peer: &NodeId) {
+        if let Entry::Occupied(mut failures) = self.send_failures.entry(*id) {
+            failures.get_mut().remove(peer);
+            if failures.get().is_empty() {
+                failures.remove();
+            }
+        }
+    }
 }
 
 impl ItemFetcher<Deploy> for Fetcher<Deploy> {
@@ -202,6 +515,14 @@ impl ItemFetcher<Deploy> for Fetcher<Deploy> {
         &mut self.responders
     }
 
+    fn timeouts(&mut self) -> &mut HashMap<DeployHash, HashMap<NodeId, TimerHandle>> {
+        &mut self.timeouts
+    }
+
+    fn permits(&mut self) -> &mut HashMap<DeployHash, HashMap<NodeId, FetchPermit>> {
+        &mut self.permits
+    }
+
     fn peer_timeout(&self) -> Duration {
         self.get_from_peer_timeout
     }
@@ -223,6 +544,85 @@ impl ItemFetcher<Deploy> for Fetcher<Deploy> {
     }
 }
 
+impl ItemFetcher<DeployHeaderWithHash> for Fetcher<DeployHeaderWithHash> {
+    fn responders(
+        &mut self,
+    ) -> &mut HashMap<DeployHash, HashMap<NodeId, Vec<FetchResponder<DeployHeaderWithHash>>>> {
+        &mut self.responders
+    }
+
+    fn timeouts(&mut self) -> &mut HashMap<DeployHash, HashMap<NodeId, TimerHandle>> {
+        &mut self.timeouts
+    }
+
+    fn permits(&mut self) -> &mut HashMap<DeployHash, HashMap<NodeId, FetchPermit>> {
+        &mut self.permits
+    }
+
+    fn peer_timeout(&self) -> Duration {
+        self.get_from_peer_timeout
+    }
+
+    /// Gets a deploy's header from the storage component, without loading the deploy's body.
+    fn get_from_storage<REv: ReactorEventT<DeployHeaderWithHash>>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        id: DeployHash,
+        peer: NodeId,
+    ) -> Effects<Event<DeployHeaderWithHash>> {
+        effect_builder
+            .get_deploy_headers_from_storage(smallvec![id])
+            .event(move |mut results| Event::GetFromStorageResult {
+                id,
+                peer,
+                maybe_item: Box::new(
+                    results
+                        .pop()
+                        .expect("can only contain one result")
+                        .map(|header| DeployHeaderWithHash::new(id, header)),
+                ),
+            })
+    }
+}
+
+impl ItemFetcher<DeployApprovals> for Fetcher<DeployApprovals> {
+    fn responders(
+        &mut self,
+    ) -> &mut HashMap<DeployHash, HashMap<NodeId, Vec<FetchResponder<DeployApprovals>>>> {
+        &mut self.responders
+    }
+
+    fn timeouts(&mut self) -> &mut HashMap<DeployHash, HashMap<NodeId, TimerHandle>> {
+        &mut self.timeouts
+    }
+
+    fn permits(&mut self) -> &mut HashMap<DeployHash, HashMap<NodeId, FetchPermit>> {
+        &mut self.permits
+    }
+
+    fn peer_timeout(&self) -> Duration {
+        self.get_from_peer_timeout
+    }
+
+    /// Gets a deploy's approvals from the storage component, without loading the deploy's body.
+    fn get_from_storage<REv: ReactorEventT<DeployApprovals>>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        id: DeployHash,
+        peer: NodeId,
+    ) -> Effects<Event<DeployApprovals>> {
+        effect_builder
+            .get_deploy_approvals_from_storage(id)
+            .event(move |maybe_approvals| Event::GetFromStorageResult {
+                id,
+                peer,
+                maybe_item: Box::new(
+                    maybe_approvals.map(|approvals| DeployApprovals::new(id, approvals)),
+                ),
+            })
+    }
+}
+
 impl ItemFetcher<Block> for Fetcher<Block> {
     fn responders(
         &mut self,
@@ -230,6 +630,14 @@ impl ItemFetcher<Block> for Fetcher<Block> {
         &mut self.responders
     }
 
+    fn timeouts(&mut self) -> &mut HashMap<BlockHash, HashMap<NodeId, TimerHandle>> {
+        &mut self.timeouts
+    }
+
+    fn permits(&mut self) -> &mut HashMap<BlockHash, HashMap<NodeId, FetchPermit>> {
+        &mut self.permits
+    }
+
     fn peer_timeout(&self) -> Duration {
         self.get_from_peer_timeout
     }
@@ -250,6 +658,43 @@ impl ItemFetcher<Block> for Fetcher<Block> {
     }
 }
 
+impl ItemFetcher<BlockTransfers> for Fetcher<BlockTransfers> {
+    fn responders(
+        &mut self,
+    ) -> &mut HashMap<BlockHash, HashMap<NodeId, Vec<FetchResponder<BlockTransfers>>>> {
+        &mut self.responders
+    }
+
+    fn timeouts(&mut self) -> &mut HashMap<BlockHash, HashMap<NodeId, TimerHandle>> {
+        &mut self.timeouts
+    }
+
+    fn permits(&mut self) -> &mut HashMap<BlockHash, HashMap<NodeId, FetchPermit>> {
+        &mut self.permits
+    }
+
+    fn peer_timeout(&self) -> Duration {
+        self.get_from_peer_timeout
+    }
+
+    fn get_from_storage<REv: ReactorEventT<BlockTransfers>>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        id: BlockHash,
+        peer: NodeId,
+    ) -> Effects<Event<BlockTransfers>> {
+        effect_builder
+            .get_block_transfers_from_storage(id)
+            .event(move |maybe_transfers| Event::GetFromStorageResult {
+                id,
+                peer,
+                maybe_item: Box::new(
+                    maybe_transfers.map(|transfers| BlockTransfers::new(id, transfers)),
+                ),
+            })
+    }
+}
+
 impl ItemFetcher<BlockByHeight> for Fetcher<BlockByHeight> {
     fn responders(
         &mut self,
@@ -257,6 +702,14 @@ impl ItemFetcher<BlockByHeight> for Fetcher<BlockByHeight> {
         &mut self.responders
     }
 
+    fn timeouts(&mut self) -> &mut HashMap<u64, HashMap<NodeId, TimerHandle>> {
+        &mut self.timeouts
+    }
+
+    fn permits(&mut self) -> &mut HashMap<u64, HashMap<NodeId, FetchPermit>> {
+        &mut self.permits
+    }
+
     fn peer_timeout(&self) -> Duration {
         self.get_from_peer_timeout
     }
@@ -286,6 +739,14 @@ impl ItemFetcher<GlobalStorageTrie> for Fetcher<GlobalStorageTrie> {
         &mut self.responders
     }
 
+    fn timeouts(&mut self) -> &mut HashMap<Blake2bHash, HashMap<NodeId, TimerHandle>> {
+        &mut self.timeouts
+    }
+
+    fn permits(&mut self) -> &mut HashMap<Blake2bHash, HashMap<NodeId, FetchPermit>> {
+        &mut self.permits
+    }
+
     fn peer_timeout(&self) -> Duration {
         self.get_from_peer_timeout
     }
@@ -337,31 +798,59 @@ where
                     self.metrics.found_in_storage.inc();
                     self.got_from_storage(item, peer)
                 }
-                None => self.failed_to_get_from_storage(effect_builder, id, peer),
+                None if self.is_backing_off_from(&peer) => {
+                    debug!(%id, %peer, "backing off from peer, not sending request");
+                    self.signal(id, None, peer)
+                }
+                None => self.schedule(id, peer),
             },
             Event::GotRemotely { item, source } => {
                 match source {
-                    Source::Peer(peer) => {
-                        self.metrics.found_on_peer.inc();
-                        self.signal(item.id(), Some(FetchResult::FromPeer(item, peer)), peer)
-                    }
+                    Source::Peer(peer) => self.got_from_peer(effect_builder, item, peer),
                     Source::Client | Source::Ourself => {
                         // TODO - we could possibly also handle this case
                         Effects::new()
                     }
                 }
             }
-            // We do nothing in the case of having an incoming deploy rejected.
+            // An incoming deploy may be rejected for reasons other than being invalid, e.g. its
+            // account having insufficient balance, in which case we do nothing.
+            Event::RejectedRemotely {
+                item,
+                source: Source::Peer(peer),
+            } => {
+                let id = item.id();
+                match item.validate(&id) {
+                    Ok(()) => Effects::new(),
+                    Err(error) => self.got_invalid_from_peer(effect_builder, id, peer, error),
+                }
+            }
             Event::RejectedRemotely { .. } => Effects::new(),
             Event::AbsentRemotely { id, peer } => {
                 info!(%id, %peer, "element absent on the remote node");
+                self.clear_send_failures(&id, &peer);
                 self.signal(id, None, peer)
             }
+            Event::TryLater {
+                id,
+                peer,
+                retry_after,
+            } => self.try_later(id, peer, retry_after),
             Event::TimeoutPeer { id, peer } => {
                 info!(%id, %peer, "request timed out");
                 self.metrics.timeouts.inc();
+                self.clear_send_failures(&id, &peer);
                 self.signal(id, None, peer)
             }
+            Event::SendFailed {
+                id,
+                peer,
+                error_kind,
+            } => self.send_failed(effect_builder, id, peer, error_kind),
+            Event::FetchReleased { id, peer, permit } => {
+                self.fetch_released(effect_builder, id, peer, permit)
+            }
+            Event::SendBatch { peer } => self.send_batch(effect_builder, peer),
         }
     }
 }
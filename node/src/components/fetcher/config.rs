@@ -1,24 +1,90 @@
 use datasize::DataSize;
 use serde::{Deserialize, Serialize};
 
+use crate::config_validation::ConfigValidationError;
+
 const DEFAULT_GET_FROM_PEER_TIMEOUT_SECS: u64 = 3;
+const DEFAULT_BATCH_WINDOW_MILLIS: u64 = 10;
+const DEFAULT_MAX_IN_FLIGHT_FETCHES: u32 = 256;
+const DEFAULT_MAX_PRIORITY_STREAK: u32 = 9;
 
 /// Configuration options for fetching.
 #[derive(Copy, Clone, DataSize, Debug, Deserialize, Serialize)]
 pub struct Config {
     get_from_peer_timeout: u64,
+    /// The time in milliseconds for which requests to the same peer are collected before being
+    /// sent as a single batch.  `0` disables batching.
+    batch_window: u64,
+    /// The maximum number of items being fetched from peers at once, across all fetchers.
+    max_in_flight_fetches: u32,
+    /// The maximum number of fetches released in a row ahead of a waiting fetch of lower
+    /// priority, guaranteeing lower priority fetches a share of the released fetches.
+    max_priority_streak: u32,
 }
 
 impl Config {
+    /// Constructs a config with the given batch window.
+    #[cfg(test)]
+    pub(super) fn with_batch_window(batch_window: u64) -> Self {
+        Config {
+            batch_window,
+            ..Config::default()
+        }
+    }
+
+    /// Constructs a config with the given limits on scheduling fetches.
+    #[cfg(test)]
+    pub(super) fn with_scheduling(max_in_flight_fetches: u32, max_priority_streak: u32) -> Self {
+        Config {
+            max_in_flight_fetches,
+            max_priority_streak,
+            ..Config::default()
+        }
+    }
+
     pub(crate) fn get_from_peer_timeout(&self) -> u64 {
         self.get_from_peer_timeout
     }
+
+    pub(crate) fn batch_window(&self) -> u64 {
+        self.batch_window
+    }
+
+    pub(crate) fn max_in_flight_fetches(&self) -> u32 {
+        self.max_in_flight_fetches
+    }
+
+    pub(crate) fn max_priority_streak(&self) -> u32 {
+        self.max_priority_streak
+    }
+
+    /// Checks whether the values set in the config make sense, pushing every problem found to
+    /// `errors`.
+    pub(crate) fn validate(&self, errors: &mut Vec<ConfigValidationError>) {
+        if self.get_from_peer_timeout == 0 {
+            errors.push(ConfigValidationError::out_of_range(
+                "fetcher.get_from_peer_timeout",
+                self.get_from_peer_timeout,
+                "greater than 0",
+            ));
+        }
+        if self.max_in_flight_fetches == 0 {
+            errors.push(ConfigValidationError::out_of_range(
+                "fetcher.max_in_flight_fetches",
+                self.max_in_flight_fetches,
+                "greater than 0",
+            ));
+        }
+    }
 }
 
 impl Default for Config {
     fn default() -> Self {
         Config {
             get_from_peer_timeout: DEFAULT_GET_FROM_PEER_TIMEOUT_SECS,
+            batch_window: DEFAULT_BATCH_WINDOW_MILLIS,
+            max_in_flight_fetches: DEFAULT_MAX_IN_FLIGHT_FETCHES,
+            max_priority_streak: DEFAULT_MAX_PRIORITY_STREAK,
         }
     }
 }
@@ -1,10 +1,14 @@
-use std::fmt::{self, Display, Formatter};
+use std::{
+    fmt::{self, Display, Formatter},
+    time::Duration,
+};
 
 use datasize::DataSize;
 use serde::Serialize;
 
-use super::Item;
+use super::{FetchPermit, Item};
 use crate::{
+    components::network::ErrorKind,
     effect::{announcements::DeployAcceptorAnnouncement, requests::FetcherRequest, Responder},
     types::{Deploy, NodeId},
     utils::Source,
@@ -48,8 +52,30 @@ pub enum Event<T: Item> {
     },
     /// An item was not available on the remote peer.
     AbsentRemotely { id: T::Id, peer: NodeId },
+    /// The remote peer was too busy to serve our request for the item, and asked us not to send it
+    /// further requests until `retry_after` has elapsed.
+    TryLater {
+        id: T::Id,
+        peer: NodeId,
+        retry_after: Duration,
+    },
     /// The timeout has elapsed and we should clean up state.
     TimeoutPeer { id: T::Id, peer: NodeId },
+    /// The network component failed to send our request for the item to the peer.
+    SendFailed {
+        id: T::Id,
+        peer: NodeId,
+        error_kind: ErrorKind,
+    },
+    /// The fetch scheduler released the fetch of the item from the peer.
+    FetchReleased {
+        id: T::Id,
+        peer: NodeId,
+        #[serde(skip)]
+        permit: FetchPermit,
+    },
+    /// The batch window has elapsed and the queued requests to the peer should be sent.
+    SendBatch { peer: NodeId },
 }
 
 impl<T: Item> From<FetcherRequest<NodeId, T>> for Event<T> {
@@ -79,7 +105,7 @@ impl From<DeployAcceptorAnnouncement<NodeId>> for Event<Deploy> {
                     source,
                 }
             }
-            DeployAcceptorAnnouncement::InvalidDeploy { deploy, source } => {
+            DeployAcceptorAnnouncement::InvalidDeploy { deploy, source, .. } => {
                 Event::RejectedRemotely {
                     item: deploy,
                     source,
@@ -117,6 +143,30 @@ impl<T: Item> Display for Event<T> {
             Event::AbsentRemotely { id, peer } => {
                 write!(formatter, "Item {} was not available on {}", id, peer)
             }
+            Event::TryLater {
+                id,
+                peer,
+                retry_after,
+            } => write!(
+                formatter,
+                "{} asked to retry request for {} after {:?}",
+                peer, id, retry_after
+            ),
+            Event::SendFailed {
+                id,
+                peer,
+                error_kind,
+            } => write!(
+                formatter,
+                "failed to send request for {} to {} ({} error)",
+                id, peer, error_kind
+            ),
+            Event::FetchReleased { id, peer, .. } => {
+                write!(formatter, "fetch of {} from {} released", id, peer)
+            }
+            Event::SendBatch { peer } => {
+                write!(formatter, "send batched requests to {}", peer)
+            }
         }
     }
 }
@@ -1,6 +1,11 @@
-use prometheus::{IntCounter, Registry};
+use std::time::Duration;
 
-use crate::unregister_metric;
+use prometheus::{HistogramOpts, HistogramVec, IntCounter, IntGauge, IntGaugeVec, Opts, Registry};
+
+use crate::{types::Tag, unregister_metric};
+
+/// Upper bounds in seconds of the buckets of the fetch scheduler's wait time histograms.
+const WAIT_TIME_BUCKETS: &[f64] = &[0.001, 0.01, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0];
 
 #[derive(Debug)]
 pub(super) struct FetcherMetrics {
@@ -10,35 +15,73 @@ pub(super) struct FetcherMetrics {
     pub(super) found_on_peer: IntCounter,
     /// Number of fetch requests that timed out.
     pub(super) timeouts: IntCounter,
+    /// Number of request messages sent to peers, with a batched request counting as one.
+    pub(super) requests_sent: IntCounter,
+    /// Number of fetch requests which peers asked us to retry later.
+    pub(super) try_later: IntCounter,
+    /// Number of items received from peers which failed validation.
+    pub(super) invalid_items: IntCounter,
     /// Reference to the registry for unregistering.
     registry: Registry,
 }
 
 impl FetcherMetrics {
-    pub(super) fn new(name: &str, registry: &Registry) -> Result<Self, prometheus::Error> {
-        let found_in_storage = IntCounter::new(
+    /// Creates the fetcher's metrics, each labeled with the tag of the fetched item type.
+    pub(super) fn new(
+        name: &str,
+        tag: Tag,
+        registry: &Registry,
+    ) -> Result<Self, prometheus::Error> {
+        let new_counter = |metric_name: String, help: String| {
+            IntCounter::with_opts(Opts::new(metric_name, help).const_label("item", tag.name()))
+        };
+        let found_in_storage = new_counter(
             format!("{}_found_in_storage", name),
             format!(
                 "number of fetch requests that found {} in the storage.",
                 name
             ),
         )?;
-        let found_on_peer = IntCounter::new(
+        let found_on_peer = new_counter(
             format!("{}_found_on_peer", name),
             format!("number of fetch requests that fetched {} from peer.", name),
         )?;
-        let timeouts = IntCounter::new(
+        let timeouts = new_counter(
             format!("{}_timeouts", name),
             format!("number of {} fetch requests that timed out", name),
         )?;
+        let requests_sent = new_counter(
+            format!("{}_requests_sent", name),
+            format!("number of {} request messages sent to peers", name),
+        )?;
+        let try_later = new_counter(
+            format!("{}_try_later", name),
+            format!(
+                "number of {} fetch requests which peers asked us to retry later",
+                name
+            ),
+        )?;
+        let invalid_items = new_counter(
+            format!("{}_invalid_items", name),
+            format!(
+                "number of {} items received from peers which failed validation",
+                name
+            ),
+        )?;
         registry.register(Box::new(found_in_storage.clone()))?;
         registry.register(Box::new(found_on_peer.clone()))?;
         registry.register(Box::new(timeouts.clone()))?;
+        registry.register(Box::new(requests_sent.clone()))?;
+        registry.register(Box::new(try_later.clone()))?;
+        registry.register(Box::new(invalid_items.clone()))?;
 
         Ok(FetcherMetrics {
             found_in_storage,
             found_on_peer,
             timeouts,
+            requests_sent,
+            try_later,
+            invalid_items,
             registry: registry.clone(),
         })
     }
@@ -49,5 +92,81 @@ impl Drop for FetcherMetrics {
         unregister_metric!(self.registry, self.found_in_storage);
         unregister_metric!(self.registry, self.found_on_peer);
         unregister_metric!(self.registry, self.timeouts);
+        unregister_metric!(self.registry, self.requests_sent);
+        unregister_metric!(self.registry, self.try_later);
+        unregister_metric!(self.registry, self.invalid_items);
+    }
+}
+
+#[derive(Debug)]
+pub(super) struct FetchSchedulerMetrics {
+    /// Number of fetches from peers in flight.
+    in_flight: IntGauge,
+    /// Number of fetches waiting to be released, by the tag of the fetched item type.
+    queue_depths: IntGaugeVec,
+    /// Time in seconds for which fetches waited to be released, by the tag of the fetched item
+    /// type.
+    wait_times: HistogramVec,
+    /// Reference to the registry for unregistering.
+    registry: Registry,
+}
+
+impl FetchSchedulerMetrics {
+    pub(super) fn new(registry: &Registry) -> Result<Self, prometheus::Error> {
+        let in_flight = IntGauge::new(
+            "fetch_scheduler_in_flight",
+            "number of fetches from peers in flight",
+        )?;
+        let queue_depths = IntGaugeVec::new(
+            Opts::new(
+                "fetch_scheduler_queue_depth",
+                "number of fetches waiting to be released, by item type",
+            ),
+            &["item"],
+        )?;
+        let wait_times = HistogramVec::new(
+            HistogramOpts::new(
+                "fetch_scheduler_wait_seconds",
+                "time in seconds for which fetches waited to be released, by item type",
+            )
+            .buckets(WAIT_TIME_BUCKETS.to_vec()),
+            &["item"],
+        )?;
+        registry.register(Box::new(in_flight.clone()))?;
+        registry.register(Box::new(queue_depths.clone()))?;
+        registry.register(Box::new(wait_times.clone()))?;
+
+        Ok(FetchSchedulerMetrics {
+            in_flight,
+            queue_depths,
+            wait_times,
+            registry: registry.clone(),
+        })
+    }
+
+    /// Records the number of fetches in flight.
+    pub(super) fn set_in_flight(&self, in_flight: usize) {
+        self.in_flight.set(in_flight as i64);
+    }
+
+    /// Records a fetch of an item with the given tag being queued.
+    pub(super) fn queued(&self, tag: Tag) {
+        self.queue_depths.with_label_values(&[tag.name()]).inc();
+    }
+
+    /// Records a fetch of an item with the given tag being released after waiting for `wait_time`.
+    pub(super) fn released(&self, tag: Tag, wait_time: Duration) {
+        self.queue_depths.with_label_values(&[tag.name()]).dec();
+        self.wait_times
+            .with_label_values(&[tag.name()])
+            .observe(wait_time.as_secs_f64());
+    }
+}
+
+impl Drop for FetchSchedulerMetrics {
+    fn drop(&mut self) {
+        unregister_metric!(self.registry, self.in_flight);
+        unregister_metric!(self.registry, self.queue_depths);
+        unregister_metric!(self.registry, self.wait_times);
     }
 }
@@ -0,0 +1,323 @@
+//! Scheduling of fetches from peers.
+//!
+//! All the fetchers of a reactor share a single `FetchScheduler`, which bounds the number of items
+//! being fetched from peers at once.  A fetcher acquires a `FetchPermit` before requesting an item
+//! from a peer, and holds it until the fetch from that peer succeeds, fails or times out.
+//!
+//! Fetches waiting for a permit are queued in the priority class registered for the tag of the
+//! fetched item type, and released highest priority first as permits are returned.  So that lower
+//! priority fetches aren't starved, no more than `max_priority_streak` fetches are released in a
+//! row while one of lower priority is waiting, after which the lower priority fetch which has
+//! waited longest is released.
+
+use std::{
+    collections::{BTreeMap, VecDeque},
+    future::Future,
+    sync::{Arc, Mutex, MutexGuard},
+    time::Instant,
+};
+
+use futures::future;
+use prometheus::Registry;
+use tokio::sync::oneshot;
+
+use super::{metrics::FetchSchedulerMetrics, Config};
+use crate::types::{FetchPriority, Tag};
+
+/// A fetch waiting to be released.
+#[derive(Debug)]
+struct Waiter {
+    /// The tag of the fetched item type.
+    tag: Tag,
+    /// The position of the fetch in the order in which fetches of any priority were queued.
+    sequence: u64,
+    queued_at: Instant,
+    sender: oneshot::Sender<FetchPermit>,
+}
+
+/// The state of the scheduler.
+#[derive(Debug)]
+struct State {
+    max_in_flight: usize,
+    max_priority_streak: u32,
+    /// The number of permits currently held.
+    in_flight: usize,
+    /// The waiting fetches of each priority class.
+    queues: BTreeMap<FetchPriority, VecDeque<Waiter>>,
+    next_sequence: u64,
+    /// The number of fetches released in a row while one of lower priority was waiting.
+    priority_streak: u32,
+}
+
+impl State {
+    /// Removes the next fetch to be released from the queues.
+    fn pop_next(&mut self) -> Option<Waiter> {
+        let highest = *self.queues.iter().find(|(_, queue)| !queue.is_empty())?.0;
+        let oldest_lower = self
+            .queues
+            .range(highest..)
+            .skip(1)
+            .filter_map(|(priority, queue)| Some((queue.front()?.sequence, *priority)))
+            .min();
+        let priority = match oldest_lower {
+            Some((_, priority)) if self.priority_streak >= self.max_priority_streak => {
+                self.priority_streak = 0;
+                priority
+            }
+            Some(_) => {
+                self.priority_streak += 1;
+                highest
+            }
+            None => {
+                self.priority_streak = 0;
+                highest
+            }
+        };
+        self.queues.get_mut(&priority)?.pop_front()
+    }
+}
+
+/// The state and metrics shared by the scheduler and its permits.
+#[derive(Debug)]
+struct Shared {
+    state: Mutex<State>,
+    metrics: FetchSchedulerMetrics,
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().expect("fetch scheduler state poisoned")
+    }
+
+    /// Releases waiting fetches while permits are available.
+    fn dispatch(shared: &Arc<Shared>) {
+        let mut released = Vec::new();
+        {
+            let mut state = shared.lock();
+            while state.in_flight < state.max_in_flight {
+                match state.pop_next() {
+                    Some(waiter) => {
+                        state.in_flight += 1;
+                        released.push(waiter);
+                    }
+                    None => break,
+                }
+            }
+            shared.metrics.set_in_flight(state.in_flight);
+        }
+
+        // The permits are sent once the lock is released, as a permit whose fetch is no longer
+        // waiting for it is dropped, and hence returned, straight away.
+        for waiter in released {
+            shared
+                .metrics
+                .released(waiter.tag, waiter.queued_at.elapsed());
+            let permit = FetchPermit {
+                shared: Arc::clone(shared),
+            };
+            let _ = waiter.sender.send(permit);
+        }
+    }
+}
+
+/// The scheduler of fetches from peers, shared by all the fetchers of a reactor.
+#[derive(Clone, Debug)]
+pub struct FetchScheduler {
+    shared: Arc<Shared>,
+}
+
+impl FetchScheduler {
+    pub(crate) fn new(config: &Config, registry: &Registry) -> Result<Self, prometheus::Error> {
+        let state = State {
+            max_in_flight: config.max_in_flight_fetches() as usize,
+            max_priority_streak: config.max_priority_streak(),
+            in_flight: 0,
+            queues: BTreeMap::new(),
+            next_sequence: 0,
+            priority_streak: 0,
+        };
+        Ok(FetchScheduler {
+            shared: Arc::new(Shared {
+                state: Mutex::new(state),
+                metrics: FetchSchedulerMetrics::new(registry)?,
+            }),
+        })
+    }
+
+    /// Queues a fetch of an item with the given tag, returning a future which resolves to its
+    /// permit once the fetch is released.
+    ///
+    /// Dropping the future abandons the fetch.
+    pub(crate) fn acquire(&self, tag: Tag) -> impl Future<Output = FetchPermit> + Send + 'static {
+        let (sender, receiver) = oneshot::channel();
+        {
+            let mut state = self.shared.lock();
+            let sequence = state.next_sequence;
+            state.next_sequence += 1;
+            state
+                .queues
+                .entry(tag.info().fetch_priority)
+                .or_default()
+                .push_back(Waiter {
+                    tag,
+                    sequence,
+                    queued_at: Instant::now(),
+                    sender,
+                });
+        }
+        self.shared.metrics.queued(tag);
+        Shared::dispatch(&self.shared);
+
+        async move {
+            match receiver.await {
+                Ok(permit) => permit,
+                // The scheduler has been dropped, so the fetch will never be released.
+                Err(_) => future::pending().await,
+            }
+        }
+    }
+
+    /// Returns the number of permits currently held.
+    #[cfg(test)]
+    pub(crate) fn in_flight(&self) -> usize {
+        self.shared.lock().in_flight
+    }
+}
+
+/// Permission to fetch an item from a peer, returned to the scheduler when dropped.
+#[derive(Debug)]
+pub struct FetchPermit {
+    shared: Arc<Shared>,
+}
+
+impl Drop for FetchPermit {
+    fn drop(&mut self) {
+        self.shared.lock().in_flight -= 1;
+        Shared::dispatch(&self.shared);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::pin::Pin;
+
+    use futures::FutureExt;
+
+    use super::*;
+
+    type PendingFetch = (Tag, Pin<Box<dyn Future<Output = FetchPermit> + Send>>);
+
+    fn scheduler(max_in_flight_fetches: u32, max_priority_streak: u32) -> FetchScheduler {
+        let config = Config::with_scheduling(max_in_flight_fetches, max_priority_streak);
+        FetchScheduler::new(&config, &Registry::new()).unwrap()
+    }
+
+    fn acquire(scheduler: &FetchScheduler, tag: Tag, count: usize) -> Vec<PendingFetch> {
+        (0..count)
+            .map(|_| -> PendingFetch { (tag, Box::pin(scheduler.acquire(tag))) })
+            .collect()
+    }
+
+    /// Removes the released fetches from `pending`, returning their tags and permits in the order
+    /// in which they were queued.
+    fn released(pending: &mut Vec<PendingFetch>) -> Vec<(Tag, FetchPermit)> {
+        let mut released = Vec::new();
+        let mut index = 0;
+        while index < pending.len() {
+            match pending[index].1.as_mut().now_or_never() {
+                Some(permit) => released.push((pending.remove(index).0, permit)),
+                None => index += 1,
+            }
+        }
+        released
+    }
+
+    #[test]
+    fn should_dispatch_high_priority_fetch_within_bound() {
+        const MAX_IN_FLIGHT: u32 = 4;
+        const MAX_PRIORITY_STREAK: u32 = 2;
+        let low = Tag::DeployHeaderWithHash;
+        let high = Tag::Block;
+        assert!(low.info().fetch_priority > high.info().fetch_priority);
+
+        let scheduler = scheduler(MAX_IN_FLIGHT, MAX_PRIORITY_STREAK);
+        let mut pending = acquire(&scheduler, low, 20);
+        let mut held = released(&mut pending);
+        assert_eq!(held.len(), MAX_IN_FLIGHT as usize);
+        assert_eq!(scheduler.in_flight(), MAX_IN_FLIGHT as usize);
+
+        pending.extend(acquire(&scheduler, high, 1));
+        assert!(released(&mut pending).is_empty());
+
+        // Complete the fetches in flight one at a time, until every fetch has been released.
+        let mut releases_until_high = None;
+        let mut releases = 0;
+        while !held.is_empty() {
+            held.remove(0);
+            let newly_released = released(&mut pending);
+            assert!(newly_released.len() <= 1);
+            assert!(scheduler.in_flight() <= MAX_IN_FLIGHT as usize);
+            releases += newly_released.len();
+            if newly_released.iter().any(|(tag, _)| *tag == high) {
+                releases_until_high = Some(releases);
+            }
+            held.extend(newly_released);
+        }
+
+        assert!(pending.is_empty());
+        assert_eq!(scheduler.in_flight(), 0);
+        let releases_until_high = releases_until_high.expect("should release high priority fetch");
+        assert!(releases_until_high <= MAX_PRIORITY_STREAK as usize + 1);
+    }
+
+    #[test]
+    fn should_not_starve_lower_priority_fetches() {
+        let scheduler = scheduler(1, 2);
+        let mut pending = acquire(&scheduler, Tag::Block, 1);
+        let mut held = released(&mut pending);
+        assert_eq!(held.len(), 1);
+
+        pending.extend(acquire(&scheduler, Tag::DeployHeaderWithHash, 1));
+        pending.extend(acquire(&scheduler, Tag::DeployApprovals, 1));
+        pending.extend(acquire(&scheduler, Tag::Block, 6));
+
+        let mut order = Vec::new();
+        while !held.is_empty() {
+            held.clear();
+            held = released(&mut pending);
+            order.extend(held.iter().map(|(tag, _)| *tag));
+        }
+
+        // After two high priority fetches, the lower priority fetch which has waited longest is
+        // released, regardless of which lower priority class it is in.
+        assert_eq!(
+            order,
+            vec![
+                Tag::Block,
+                Tag::Block,
+                Tag::DeployHeaderWithHash,
+                Tag::Block,
+                Tag::Block,
+                Tag::DeployApprovals,
+                Tag::Block,
+                Tag::Block,
+            ]
+        );
+    }
+
+    #[test]
+    fn should_return_permit_of_abandoned_fetch() {
+        let scheduler = scheduler(1, 0);
+        let mut pending = acquire(&scheduler, Tag::Deploy, 3);
+        let held = released(&mut pending);
+        assert_eq!(held.len(), 1);
+
+        // Abandon the next fetch in the queue.
+        pending.remove(0);
+        drop(held);
+        let held = released(&mut pending);
+        assert_eq!(held.len(), 1);
+        assert!(pending.is_empty());
+        assert_eq!(scheduler.in_flight(), 1);
+    }
+}
@@ -13,23 +13,34 @@ use casper_types::ProtocolVersion;
 
 use super::*;
 use crate::{
-    components::{deploy_acceptor, in_memory_network::NetworkController, storage},
+    components::{
+        deploy_acceptor, in_memory_network::NetworkController, network::ErrorKind, request_limiter,
+        storage,
+    },
     effect::{
-        announcements::{DeployAcceptorAnnouncement, NetworkAnnouncement},
+        announcements::{
+            BlocklistAnnouncement, DeployAcceptorAnnouncement, NetworkAnnouncement,
+            RequestLimiterAnnouncement,
+        },
+        requests::FetcherRequest,
         Responder,
     },
     protocol::Message,
-    reactor::{Reactor as ReactorTrait, Runner},
+    reactor::{self, QueueKind, Reactor as ReactorTrait, Runner},
     testing::{
         network::{Network, NetworkedReactor},
         ConditionCheckReactor, TestRng,
     },
-    types::{Deploy, DeployHash, NodeId},
+    types::{Deploy, DeployApprovals, DeployHash, DeployHeaderWithHash, NodeId, SharedObject, Tag},
     utils::{WithDir, RESOURCES_PATH},
 };
 
 const TIMEOUT: Duration = Duration::from_secs(1);
 
+/// The batch window used by nodes testing batched fetching, long enough that all fetches injected
+/// in a single effect are collected into the same batch.
+const TEST_BATCH_WINDOW_MILLIS: u64 = 100;
+
 /// Error type returned by the test reactor.
 #[derive(Debug, Error)]
 enum Error {
@@ -48,6 +59,8 @@ pub struct FetcherTestConfig {
     fetcher_config: Config,
     storage_config: storage::Config,
     deploy_acceptor_config: deploy_acceptor::Config,
+    request_limiter_config: request_limiter::Config,
+    fetch_scheduler: FetchScheduler,
     temp_dir: TempDir,
 }
 
@@ -58,6 +71,8 @@ impl Default for FetcherTestConfig {
             fetcher_config: Default::default(),
             storage_config,
             deploy_acceptor_config: deploy_acceptor::Config::new(false),
+            request_limiter_config: Default::default(),
+            fetch_scheduler: FetchScheduler::new(&Config::default(), &Registry::new()).unwrap(),
             temp_dir,
         }
     }
@@ -77,23 +92,52 @@ reactor!(Reactor {
             chainspec_loader.hard_reset_to_start_of_era(),
             ProtocolVersion::from_parts(1, 0, 0),
             false,
+            registry
+        );
+        deploy_acceptor = DeployAcceptor(
+            WithDir::new(cfg.temp_dir.path(), &cfg.deploy_acceptor_config),
+            &*chainspec_loader.chainspec(),
+            None,
+            None,
+            registry
+        );
+        deploy_fetcher = Fetcher::<Deploy>(
+            "deploy",
+            cfg.fetcher_config,
+            &cfg.fetch_scheduler,
+            registry
         );
-        deploy_acceptor = infallible DeployAcceptor(cfg.deploy_acceptor_config, &*chainspec_loader.chainspec());
-        deploy_fetcher = Fetcher::<Deploy>("deploy", cfg.fetcher_config, registry);
+        deploy_header_fetcher = Fetcher::<DeployHeaderWithHash>(
+            "deploy_header",
+            cfg.fetcher_config,
+            &cfg.fetch_scheduler,
+            registry
+        );
+        deploy_approvals_fetcher = Fetcher::<DeployApprovals>(
+            "deploy_approvals",
+            cfg.fetcher_config,
+            &cfg.fetch_scheduler,
+            registry
+        );
+        request_limiter = RequestLimiter(cfg.request_limiter_config, registry);
     }
 
     events: {
         network = Event<Message>;
         deploy_fetcher = Event<Deploy>;
+        deploy_header_fetcher = Event<DeployHeaderWithHash>;
+        deploy_approvals_fetcher = Event<DeployApprovals>;
     }
 
     requests: {
         // This test contains no linear chain requests, so we panic if we receive any.
         LinearChainRequest<NodeId> -> !;
-        NetworkRequest<NodeId, Message> -> network;
+        NetworkRequest<NodeId, Message> -> fn handle_network_request;
         StorageRequest -> storage;
         StateStoreRequest -> storage;
         FetcherRequest<NodeId, Deploy> -> deploy_fetcher;
+        FetcherRequest<NodeId, DeployHeaderWithHash> -> deploy_header_fetcher;
+        FetcherRequest<NodeId, DeployApprovals> -> deploy_approvals_fetcher;
 
         // The only contract runtime request will be the commit of genesis, which we discard.
         ContractRuntimeRequest -> #;
@@ -103,10 +147,13 @@ reactor!(Reactor {
         // The deploy fetcher needs to be notified about new deploys.
         DeployAcceptorAnnouncement<NodeId> -> [deploy_fetcher];
         NetworkAnnouncement<NodeId, Message> -> [fn handle_message];
+        RequestLimiterAnnouncement -> [fn serve_request];
         // Currently the RpcServerAnnouncement is misnamed - it solely tells of new deploys arriving
         // from a client.
         RpcServerAnnouncement -> [deploy_acceptor];
         ChainspecLoaderAnnouncement -> [!];
+        // Tests of penalising peers check for the announcements as they are cranked.
+        BlocklistAnnouncement<NodeId> -> [#];
     }
 });
 
@@ -121,61 +168,300 @@ impl Reactor {
         // announcements.
         match network_announcement {
             NetworkAnnouncement::MessageReceived { sender, payload } => match payload {
-                Message::GetRequest { serialized_id, .. } => {
-                    let deploy_hash = match bincode::deserialize(&serialized_id) {
-                        Ok(hash) => hash,
-                        Err(error) => {
-                            error!(
-                                "failed to decode {:?} from {}: {}",
-                                serialized_id, sender, error
-                            );
-                            return Effects::new();
-                        }
-                    };
+                request @ Message::GetRequest { .. }
+                | request @ Message::GetBatchRequest { .. } => self.dispatch_event(
+                    effect_builder,
+                    rng,
+                    ReactorEvent::RequestLimiter(request_limiter::Event::RequestReceived {
+                        sender,
+                        payload: Box::new(request),
+                    }),
+                ),
 
-                    match self
-                        .storage
-                        .handle_deduplicated_legacy_direct_deploy_request(deploy_hash)
-                    {
-                        Some(serialized_item) => {
-                            let message =
-                                Message::new_get_response_raw_unchecked::<Deploy>(serialized_item);
-                            effect_builder.send_message(sender, message).ignore()
-                        }
-
-                        None => {
-                            debug!(%sender, %deploy_hash, "failed to get deploy (not found)");
-                            Effects::new()
-                        }
-                    }
-                }
+                Message::GetResponse {
+                    tag: Tag::Deploy,
+                    serialized_item,
+                } => self.dispatch_event(
+                    effect_builder,
+                    rng,
+                    ReactorEvent::DeployAcceptor(deploy_acceptor::Event::ReceivedFromPeer {
+                        serialized_deploy: serialized_item,
+                        sender,
+                    }),
+                ),
 
                 Message::GetResponse {
-                    serialized_item, ..
+                    tag: Tag::DeployHeaderWithHash,
+                    serialized_item,
                 } => {
-                    let deploy = match bincode::deserialize(&serialized_item) {
-                        Ok(deploy) => Box::new(deploy),
-                        Err(error) => {
-                            error!("failed to decode deploy from {}: {}", sender, error);
-                            return Effects::new();
-                        }
-                    };
+                    let deploy_header: DeployHeaderWithHash =
+                        bincode::deserialize(&serialized_item)
+                            .expect("should deserialize deploy header");
+                    self.dispatch_event(
+                        effect_builder,
+                        rng,
+                        ReactorEvent::DeployHeaderFetcher(Event::GotRemotely {
+                            item: Box::new(deploy_header),
+                            source: Source::Peer(sender),
+                        }),
+                    )
+                }
 
+                Message::GetResponse {
+                    tag: Tag::DeployApprovals,
+                    serialized_item,
+                } => {
+                    let deploy_approvals: DeployApprovals = bincode::deserialize(&serialized_item)
+                        .expect("should deserialize deploy approvals");
                     self.dispatch_event(
                         effect_builder,
                         rng,
-                        ReactorEvent::DeployAcceptor(deploy_acceptor::Event::Accept {
-                            deploy,
+                        ReactorEvent::DeployApprovalsFetcher(Event::GotRemotely {
+                            item: Box::new(deploy_approvals),
                             source: Source::Peer(sender),
-                            responder: None,
                         }),
                     )
                 }
+                Message::GetBatchResponse {
+                    tag,
+                    serialized_items,
+                    serialized_missing_ids,
+                } => {
+                    let mut effects = Effects::new();
+                    for serialized_item in serialized_items {
+                        let payload = Message::GetResponse {
+                            tag,
+                            serialized_item,
+                        };
+                        effects.extend(self.handle_message(
+                            effect_builder,
+                            rng,
+                            NetworkAnnouncement::MessageReceived { sender, payload },
+                        ));
+                    }
+                    for serialized_id in serialized_missing_ids {
+                        let id: DeployHash = bincode::deserialize(&serialized_id)
+                            .expect("should deserialize deploy hash");
+                        let peer = sender;
+                        let event = match tag {
+                            Tag::Deploy => {
+                                ReactorEvent::DeployFetcher(Event::AbsentRemotely { id, peer })
+                            }
+                            Tag::DeployHeaderWithHash => {
+                                ReactorEvent::DeployHeaderFetcher(Event::AbsentRemotely {
+                                    id,
+                                    peer,
+                                })
+                            }
+                            Tag::DeployApprovals => {
+                                ReactorEvent::DeployApprovalsFetcher(Event::AbsentRemotely {
+                                    id,
+                                    peer,
+                                })
+                            }
+                            _ => panic!("should not get batch response for {}", tag),
+                        };
+                        effects.extend(self.dispatch_event(effect_builder, rng, event));
+                    }
+                    effects
+                }
+                Message::TryLater {
+                    tag,
+                    serialized_ids,
+                    retry_after,
+                } => {
+                    let mut effects = Effects::new();
+                    for serialized_id in serialized_ids {
+                        let id: DeployHash = bincode::deserialize(&serialized_id)
+                            .expect("should deserialize deploy hash");
+                        let peer = sender;
+                        let retry_after = Duration::from(retry_after);
+                        let event = match tag {
+                            Tag::Deploy => ReactorEvent::DeployFetcher(Event::TryLater {
+                                id,
+                                peer,
+                                retry_after,
+                            }),
+                            Tag::DeployHeaderWithHash => {
+                                ReactorEvent::DeployHeaderFetcher(Event::TryLater {
+                                    id,
+                                    peer,
+                                    retry_after,
+                                })
+                            }
+                            Tag::DeployApprovals => {
+                                ReactorEvent::DeployApprovalsFetcher(Event::TryLater {
+                                    id,
+                                    peer,
+                                    retry_after,
+                                })
+                            }
+                            _ => panic!("should not get try-later for {}", tag),
+                        };
+                        effects.extend(self.dispatch_event(effect_builder, rng, event));
+                    }
+                    effects
+                }
                 msg => panic!("should not get {}", msg),
             },
             ann => panic!("should not received any network announcements: {:?}", ann),
         }
     }
+
+    fn serve_request(
+        &mut self,
+        effect_builder: EffectBuilder<ReactorEvent>,
+        _rng: &mut NodeRng,
+        announcement: RequestLimiterAnnouncement,
+    ) -> Effects<ReactorEvent> {
+        let RequestLimiterAnnouncement::RequestAdmitted { sender, payload } = announcement;
+        let effects = match *payload {
+            Message::GetRequest {
+                tag: Tag::Deploy,
+                serialized_id,
+            } => {
+                let deploy_hash = match bincode::deserialize(&serialized_id) {
+                    Ok(hash) => hash,
+                    Err(error) => {
+                        error!(
+                            "failed to decode {:?} from {}: {}",
+                            serialized_id, sender, error
+                        );
+                        return Effects::new();
+                    }
+                };
+
+                match self
+                    .storage
+                    .handle_deduplicated_legacy_direct_deploy_request(deploy_hash)
+                {
+                    Some(serialized_item) => {
+                        let message =
+                            Message::new_get_response_raw_unchecked::<Deploy>(serialized_item);
+                        effect_builder.send_message(sender, message).ignore()
+                    }
+
+                    None => {
+                        debug!(%sender, %deploy_hash, "failed to get deploy (not found)");
+                        Effects::new()
+                    }
+                }
+            }
+
+            Message::GetRequest {
+                tag: Tag::DeployHeaderWithHash,
+                serialized_id,
+            } => {
+                let deploy_hash =
+                    bincode::deserialize(&serialized_id).expect("should deserialize deploy hash");
+                match self
+                    .storage
+                    .read_deploy_header_with_hash(&deploy_hash)
+                    .expect("should read deploy header")
+                {
+                    Some(deploy_header) => {
+                        let message = Message::new_get_response(&deploy_header)
+                            .expect("should create get-response");
+                        effect_builder.send_message(sender, message).ignore()
+                    }
+                    None => Effects::new(),
+                }
+            }
+
+            Message::GetRequest {
+                tag: Tag::DeployApprovals,
+                serialized_id,
+            } => {
+                let deploy_hash =
+                    bincode::deserialize(&serialized_id).expect("should deserialize deploy hash");
+                match self
+                    .storage
+                    .read_deploy_approvals(&deploy_hash)
+                    .expect("should read deploy approvals")
+                {
+                    Some(deploy_approvals) => {
+                        let message = Message::new_get_response(&deploy_approvals)
+                            .expect("should create get-response");
+                        effect_builder.send_message(sender, message).ignore()
+                    }
+                    None => Effects::new(),
+                }
+            }
+            Message::GetBatchRequest {
+                tag,
+                serialized_ids,
+            } => {
+                let mut serialized_items = vec![];
+                let mut serialized_missing_ids = vec![];
+                for serialized_id in serialized_ids {
+                    let deploy_hash = bincode::deserialize(&serialized_id)
+                        .expect("should deserialize deploy hash");
+                    let maybe_serialized_item = match tag {
+                        Tag::Deploy => self
+                            .storage
+                            .handle_deduplicated_legacy_direct_deploy_request(deploy_hash),
+                        Tag::DeployHeaderWithHash => self
+                            .storage
+                            .read_deploy_header_with_hash(&deploy_hash)
+                            .expect("should read deploy header")
+                            .map(|deploy_header| {
+                                bincode::serialize(&deploy_header)
+                                    .expect("should serialize deploy header")
+                            })
+                            .map(SharedObject::owned),
+                        Tag::DeployApprovals => self
+                            .storage
+                            .read_deploy_approvals(&deploy_hash)
+                            .expect("should read deploy approvals")
+                            .map(|deploy_approvals| {
+                                bincode::serialize(&deploy_approvals)
+                                    .expect("should serialize deploy approvals")
+                            })
+                            .map(SharedObject::owned),
+                        _ => panic!("should not get batch request for {}", tag),
+                    };
+                    match maybe_serialized_item {
+                        Some(serialized_item) => serialized_items.push(serialized_item),
+                        None => serialized_missing_ids.push(serialized_id),
+                    }
+                }
+
+                let max_block_size = self
+                    .chainspec_loader
+                    .chainspec()
+                    .deploy_config
+                    .max_block_size;
+                let mut effects = Effects::new();
+                for message in Message::new_get_batch_responses(
+                    tag,
+                    serialized_items,
+                    serialized_missing_ids,
+                    max_block_size,
+                ) {
+                    effects.extend(effect_builder.send_message(sender, message).ignore());
+                }
+                effects
+            }
+            msg => panic!("should not serve {}", msg),
+        };
+        request_limiter::track_serving(sender, effects)
+    }
+
+    fn handle_network_request(
+        &mut self,
+        effect_builder: EffectBuilder<ReactorEvent>,
+        rng: &mut NodeRng,
+        request: NetworkRequest<NodeId, Message>,
+    ) -> Effects<ReactorEvent> {
+        if let NetworkRequest::SendMessage { dest, payload, .. } = &request {
+            self.request_limiter.record_outgoing(**dest, payload);
+        }
+        reactor::wrap_effects(
+            ReactorEvent::Network,
+            self.network
+                .handle_event(effect_builder, rng, request.into()),
+        )
+    }
 }
 
 impl NetworkedReactor for Reactor {
@@ -216,6 +502,193 @@ fn fetch_deploy(
     }
 }
 
+type FetchedItemResult<T> = Arc<Mutex<(bool, Option<FetchResult<T, NodeId>>)>>;
+
+fn fetch_item<T>(
+    id: T::Id,
+    node_id: NodeId,
+    fetched: FetchedItemResult<T>,
+) -> impl FnOnce(EffectBuilder<ReactorEvent>) -> Effects<ReactorEvent>
+where
+    T: Item + 'static,
+    ReactorEvent: From<FetcherRequest<NodeId, T>>,
+{
+    move |effect_builder: EffectBuilder<ReactorEvent>| {
+        effect_builder
+            .make_request(
+                move |responder| FetcherRequest::Fetch {
+                    id,
+                    peer: node_id,
+                    responder,
+                },
+                QueueKind::Regular,
+            )
+            .then(move |maybe_item| async move {
+                let mut result = fetched.lock().unwrap();
+                result.0 = true;
+                result.1 = maybe_item;
+            })
+            .ignore()
+    }
+}
+
+/// Fetches a partial deploy item on `requesting_node` from `holding_node`, returning the result
+/// once the fetch has settled.
+async fn fetch_partial_deploy_item<T>(
+    deploy_hash: DeployHash,
+    requesting_node: &NodeId,
+    holding_node: NodeId,
+    network: &mut Network<Reactor>,
+    rng: &mut TestRng,
+) -> Option<FetchResult<T, NodeId>>
+where
+    T: Item<Id = DeployHash> + 'static,
+    ReactorEvent: From<FetcherRequest<NodeId, T>>,
+{
+    let fetched = Arc::new(Mutex::new((false, None)));
+    network
+        .process_injected_effect_on(
+            requesting_node,
+            fetch_item::<T>(deploy_hash, holding_node, Arc::clone(&fetched)),
+        )
+        .await;
+
+    let has_responded = |_nodes: &HashMap<NodeId, Runner<ConditionCheckReactor<Reactor>>>| {
+        fetched.lock().unwrap().0
+    };
+    network.settle_on(rng, has_responded, TIMEOUT).await;
+
+    let mut result = fetched.lock().unwrap();
+    result.1.take()
+}
+
+/// Adds `count` nodes to the network whose fetchers batch requests using
+/// `TEST_BATCH_WINDOW_MILLIS`.
+async fn add_batching_nodes(
+    network: &mut Network<Reactor>,
+    rng: &mut TestRng,
+    count: usize,
+) -> Vec<NodeId> {
+    let mut node_ids = vec![];
+    for _ in 0..count {
+        let cfg = FetcherTestConfig {
+            fetcher_config: Config::with_batch_window(TEST_BATCH_WINDOW_MILLIS),
+            ..Default::default()
+        };
+        let (node_id, _) = network
+            .add_node_with_config(cfg, rng)
+            .await
+            .expect("should add node");
+        node_ids.push(node_id);
+    }
+    node_ids
+}
+
+/// Starts fetching all the given deploys on `requesting_node` from `holding_node` in a single
+/// injected effect, returning the shared results in the same order.
+async fn start_fetching_deploys(
+    deploy_hashes: &[DeployHash],
+    requesting_node: &NodeId,
+    holding_node: NodeId,
+    network: &mut Network<Reactor>,
+) -> Vec<FetchedDeployResult> {
+    let all_fetched: Vec<FetchedDeployResult> = deploy_hashes
+        .iter()
+        .map(|_| Arc::new(Mutex::new((false, None))))
+        .collect();
+    let fetches: Vec<_> = deploy_hashes
+        .iter()
+        .zip(&all_fetched)
+        .map(|(deploy_hash, fetched)| fetch_deploy(*deploy_hash, holding_node, Arc::clone(fetched)))
+        .collect();
+    network
+        .process_injected_effect_on(requesting_node, move |effect_builder| {
+            fetches
+                .into_iter()
+                .flat_map(|fetch| fetch(effect_builder))
+                .collect()
+        })
+        .await;
+    all_fetched
+}
+
+/// Fetches all the given deploys on `requesting_node` from `holding_node` in a single injected
+/// effect, returning the results in the same order once all fetches have settled.
+async fn fetch_deploys(
+    deploy_hashes: &[DeployHash],
+    requesting_node: &NodeId,
+    holding_node: NodeId,
+    network: &mut Network<Reactor>,
+    rng: &mut TestRng,
+    timeout: Duration,
+) -> Vec<Option<FetchResult<Deploy, NodeId>>> {
+    let all_fetched =
+        start_fetching_deploys(deploy_hashes, requesting_node, holding_node, network).await;
+
+    let all_responded = |_nodes: &HashMap<NodeId, Runner<ConditionCheckReactor<Reactor>>>| {
+        all_fetched.iter().all(|fetched| fetched.lock().unwrap().0)
+    };
+    network.settle_on(rng, all_responded, timeout).await;
+
+    all_fetched
+        .iter()
+        .map(|fetched| fetched.lock().unwrap().1.take())
+        .collect()
+}
+
+/// Returns the number of request messages sent by the deploy fetcher of the given node.
+fn deploy_requests_sent(
+    nodes: &HashMap<NodeId, Runner<ConditionCheckReactor<Reactor>>>,
+    node_id: &NodeId,
+) -> u64 {
+    nodes
+        .get(node_id)
+        .unwrap()
+        .reactor()
+        .inner()
+        .deploy_fetcher
+        .metrics
+        .requests_sent
+        .get()
+}
+
+/// Reports to the deploy fetcher of `node_id` that the network failed to send its request for the
+/// deploy to `peer`.
+async fn fail_deploy_request(
+    network: &mut Network<Reactor>,
+    node_id: &NodeId,
+    deploy_hash: DeployHash,
+    peer: NodeId,
+    error_kind: ErrorKind,
+) {
+    network
+        .process_injected_effect_on(node_id, move |effect_builder| {
+            let event = Event::<Deploy>::SendFailed {
+                id: deploy_hash,
+                peer,
+                error_kind,
+            };
+            effect_builder
+                .into_inner()
+                .schedule(event, QueueKind::Regular)
+                .ignore()
+        })
+        .await;
+}
+
+/// Returns whether the full deploy is held in the storage of the given node.
+fn has_deploy(network: &Network<Reactor>, node_id: &NodeId, deploy_hash: DeployHash) -> bool {
+    network
+        .nodes()
+        .get(node_id)
+        .unwrap()
+        .reactor()
+        .inner()
+        .storage
+        .get_deploy_by_hash(deploy_hash)
+        .is_some()
+}
+
 /// Store a deploy on a target node.
 async fn store_deploy(
     deploy: &Deploy,
@@ -455,3 +928,699 @@ async fn should_timeout_fetch_from_peer() {
 
     NetworkController::<Message>::remove_active();
 }
+
+#[tokio::test]
+async fn should_fetch_deploy_header_and_approvals_from_local() {
+    const NETWORK_SIZE: usize = 1;
+
+    NetworkController::<Message>::create_active();
+    let (mut network, mut rng, node_ids) = {
+        let mut network = Network::<Reactor>::new();
+        let mut rng = TestRng::new();
+        let node_ids = network.add_nodes(&mut rng, NETWORK_SIZE).await;
+        (network, rng, node_ids)
+    };
+
+    // Create a random deploy and store it on the only node.
+    let deploy = Deploy::random(&mut rng);
+    let deploy_hash = *deploy.id();
+    let node_id = node_ids[0];
+    store_deploy(&deploy, &node_id, &mut network, None, &mut rng).await;
+
+    let result = fetch_partial_deploy_item::<DeployHeaderWithHash>(
+        deploy_hash,
+        &node_id,
+        node_id,
+        &mut network,
+        &mut rng,
+    )
+    .await;
+    let expected_header = DeployHeaderWithHash::new(deploy_hash, deploy.header().clone());
+    assert_eq!(
+        result,
+        Some(FetchResult::FromStorage(Box::new(expected_header)))
+    );
+
+    let result = fetch_partial_deploy_item::<DeployApprovals>(
+        deploy_hash,
+        &node_id,
+        node_id,
+        &mut network,
+        &mut rng,
+    )
+    .await;
+    let expected_approvals = DeployApprovals::new(deploy_hash, deploy.approvals().to_vec());
+    assert_eq!(
+        result,
+        Some(FetchResult::FromStorage(Box::new(expected_approvals)))
+    );
+
+    NetworkController::<Message>::remove_active();
+}
+
+#[tokio::test]
+async fn should_fetch_deploy_header_and_approvals_from_peer_without_body() {
+    const NETWORK_SIZE: usize = 2;
+
+    NetworkController::<Message>::create_active();
+    let (mut network, mut rng, node_ids) = {
+        let mut network = Network::<Reactor>::new();
+        let mut rng = TestRng::new();
+        let node_ids = network.add_nodes(&mut rng, NETWORK_SIZE).await;
+        (network, rng, node_ids)
+    };
+
+    // Create a random deploy and store it on the holding node only.
+    let deploy = Deploy::random(&mut rng);
+    let deploy_hash = *deploy.id();
+    let holding_node = node_ids[0];
+    let requesting_node = node_ids[1];
+    store_deploy(&deploy, &holding_node, &mut network, None, &mut rng).await;
+
+    // Fetching the header should not transfer the deploy itself.
+    let result = fetch_partial_deploy_item::<DeployHeaderWithHash>(
+        deploy_hash,
+        &requesting_node,
+        holding_node,
+        &mut network,
+        &mut rng,
+    )
+    .await;
+    let expected_header = DeployHeaderWithHash::new(deploy_hash, deploy.header().clone());
+    assert_eq!(
+        result,
+        Some(FetchResult::FromPeer(
+            Box::new(expected_header),
+            holding_node
+        ))
+    );
+    assert!(!has_deploy(&network, &requesting_node, deploy_hash));
+
+    // Neither should fetching the approvals.
+    let result = fetch_partial_deploy_item::<DeployApprovals>(
+        deploy_hash,
+        &requesting_node,
+        holding_node,
+        &mut network,
+        &mut rng,
+    )
+    .await;
+    let expected_approvals = DeployApprovals::new(deploy_hash, deploy.approvals().to_vec());
+    assert_eq!(
+        result,
+        Some(FetchResult::FromPeer(
+            Box::new(expected_approvals),
+            holding_node
+        ))
+    );
+    assert!(!has_deploy(&network, &requesting_node, deploy_hash));
+
+    // The full deploy is only transferred once explicitly requested.
+    let fetched = Arc::new(Mutex::new((false, None)));
+    network
+        .process_injected_effect_on(
+            &requesting_node,
+            fetch_deploy(deploy_hash, holding_node, Arc::clone(&fetched)),
+        )
+        .await;
+    let expected_result = Some(FetchResult::FromPeer(Box::new(deploy), holding_node));
+    assert_settled(
+        &requesting_node,
+        deploy_hash,
+        expected_result,
+        fetched,
+        &mut network,
+        &mut rng,
+        TIMEOUT,
+    )
+    .await;
+
+    NetworkController::<Message>::remove_active();
+}
+
+#[tokio::test]
+async fn should_batch_fetches_from_peer() {
+    const NETWORK_SIZE: usize = 2;
+    // A block's worth of deploys under the local chainspec.
+    const DEPLOY_COUNT: usize = 100;
+    // Validating a block's worth of fetched deploys takes longer than a single fetch.
+    const BATCH_TIMEOUT: Duration = Duration::from_secs(5);
+
+    NetworkController::<Message>::create_active();
+    let mut network = Network::<Reactor>::new();
+    let mut rng = TestRng::new();
+    let node_ids = add_batching_nodes(&mut network, &mut rng, NETWORK_SIZE).await;
+    let holding_node = node_ids[0];
+    let requesting_node = node_ids[1];
+
+    let deploys: Vec<Deploy> = (0..DEPLOY_COUNT)
+        .map(|_| Deploy::random(&mut rng))
+        .collect();
+    for deploy in &deploys {
+        store_deploy(deploy, &holding_node, &mut network, None, &mut rng).await;
+    }
+
+    let deploy_hashes: Vec<DeployHash> = deploys.iter().map(|deploy| *deploy.id()).collect();
+    let results = fetch_deploys(
+        &deploy_hashes,
+        &requesting_node,
+        holding_node,
+        &mut network,
+        &mut rng,
+        BATCH_TIMEOUT,
+    )
+    .await;
+
+    for (deploy, result) in deploys.into_iter().zip(results) {
+        assert_eq!(
+            result,
+            Some(FetchResult::FromPeer(Box::new(deploy), holding_node))
+        );
+    }
+
+    // Unbatched, one request would have been sent per deploy.
+    let requests_sent = deploy_requests_sent(network.nodes(), &requesting_node);
+    assert!(
+        requests_sent <= (DEPLOY_COUNT / 10) as u64,
+        "sent {} requests for {} deploys",
+        requests_sent,
+        DEPLOY_COUNT
+    );
+
+    NetworkController::<Message>::remove_active();
+}
+
+#[tokio::test]
+async fn should_report_missing_items_of_batch() {
+    const NETWORK_SIZE: usize = 2;
+    const DEPLOY_COUNT: usize = 20;
+
+    NetworkController::<Message>::create_active();
+    let mut network = Network::<Reactor>::new();
+    let mut rng = TestRng::new();
+    let node_ids = add_batching_nodes(&mut network, &mut rng, NETWORK_SIZE).await;
+    let holding_node = node_ids[0];
+    let requesting_node = node_ids[1];
+
+    // Only store every other deploy on the holding node.
+    let deploys: Vec<Deploy> = (0..DEPLOY_COUNT)
+        .map(|_| Deploy::random(&mut rng))
+        .collect();
+    for deploy in deploys.iter().step_by(2) {
+        store_deploy(deploy, &holding_node, &mut network, None, &mut rng).await;
+    }
+
+    // The missing deploys should be reported in the batch response rather than timing out, so all
+    // fetches settle well within the peer timeout.
+    let deploy_hashes: Vec<DeployHash> = deploys.iter().map(|deploy| *deploy.id()).collect();
+    let results = fetch_deploys(
+        &deploy_hashes,
+        &requesting_node,
+        holding_node,
+        &mut network,
+        &mut rng,
+        TIMEOUT,
+    )
+    .await;
+
+    for (index, (deploy, result)) in deploys.into_iter().zip(results).enumerate() {
+        if index % 2 == 0 {
+            assert_eq!(
+                result,
+                Some(FetchResult::FromPeer(Box::new(deploy), holding_node))
+            );
+        } else {
+            assert_eq!(result, None);
+            assert!(!has_deploy(&network, &requesting_node, *deploy.id()));
+        }
+    }
+    assert_eq!(deploy_requests_sent(network.nodes(), &requesting_node), 1);
+
+    NetworkController::<Message>::remove_active();
+}
+
+/// Starts fetching a deploy on the single node of a new network from a peer which is not part of
+/// the network, so that the request is never answered, returning once the request has been sent.
+async fn fetch_from_absent_peer(
+    rng: &mut TestRng,
+) -> (
+    Network<Reactor>,
+    NodeId,
+    NodeId,
+    DeployHash,
+    FetchedDeployResult,
+) {
+    let mut network = Network::<Reactor>::new();
+    let node_id = network.add_nodes(rng, 1).await[0];
+    let absent_peer = NodeId::random(rng);
+    let deploy_hash = *Deploy::random(rng).id();
+
+    let fetched = Arc::new(Mutex::new((false, None)));
+    network
+        .process_injected_effect_on(
+            &node_id,
+            fetch_deploy(deploy_hash, absent_peer, Arc::clone(&fetched)),
+        )
+        .await;
+    let request_sent = |nodes: &HashMap<NodeId, Runner<ConditionCheckReactor<Reactor>>>| {
+        deploy_requests_sent(nodes, &node_id) == 1
+    };
+    network.settle_on(rng, request_sent, TIMEOUT).await;
+
+    (network, node_id, absent_peer, deploy_hash, fetched)
+}
+
+#[tokio::test]
+async fn should_not_resend_after_non_retryable_send_failure() {
+    NetworkController::<Message>::create_active();
+    let mut rng = TestRng::new();
+    let (mut network, node_id, absent_peer, deploy_hash, fetched) =
+        fetch_from_absent_peer(&mut rng).await;
+
+    // The fetch should be abandoned immediately rather than after the peer timeout, which is
+    // longer than `TIMEOUT`.
+    fail_deploy_request(
+        &mut network,
+        &node_id,
+        deploy_hash,
+        absent_peer,
+        ErrorKind::Protocol,
+    )
+    .await;
+    let has_responded = |_nodes: &HashMap<NodeId, Runner<ConditionCheckReactor<Reactor>>>| {
+        fetched.lock().unwrap().0
+    };
+    network.settle_on(&mut rng, has_responded, TIMEOUT).await;
+
+    assert_eq!(fetched.lock().unwrap().1, None);
+    assert_eq!(deploy_requests_sent(network.nodes(), &node_id), 1);
+
+    NetworkController::<Message>::remove_active();
+}
+
+#[tokio::test]
+async fn should_resend_after_retryable_send_failure_up_to_limit() {
+    NetworkController::<Message>::create_active();
+    let mut rng = TestRng::new();
+    let (mut network, node_id, absent_peer, deploy_hash, fetched) =
+        fetch_from_absent_peer(&mut rng).await;
+
+    for attempt in 1..MAX_SEND_ATTEMPTS {
+        fail_deploy_request(
+            &mut network,
+            &node_id,
+            deploy_hash,
+            absent_peer,
+            ErrorKind::Connection,
+        )
+        .await;
+        let request_resent = |nodes: &HashMap<NodeId, Runner<ConditionCheckReactor<Reactor>>>| {
+            deploy_requests_sent(nodes, &node_id) == u64::from(attempt) + 1
+        };
+        network.settle_on(&mut rng, request_resent, TIMEOUT).await;
+        assert!(!fetched.lock().unwrap().0);
+    }
+
+    // Once the limit is reached, the fetch should be abandoned.
+    fail_deploy_request(
+        &mut network,
+        &node_id,
+        deploy_hash,
+        absent_peer,
+        ErrorKind::Connection,
+    )
+    .await;
+    let has_responded = |_nodes: &HashMap<NodeId, Runner<ConditionCheckReactor<Reactor>>>| {
+        fetched.lock().unwrap().0
+    };
+    network.settle_on(&mut rng, has_responded, TIMEOUT).await;
+
+    assert_eq!(fetched.lock().unwrap().1, None);
+    assert_eq!(
+        deploy_requests_sent(network.nodes(), &node_id),
+        u64::from(MAX_SEND_ATTEMPTS)
+    );
+
+    NetworkController::<Message>::remove_active();
+}
+
+#[tokio::test]
+async fn should_serve_peers_fairly_when_one_is_greedy() {
+    const GREEDY_DEPLOY_COUNT: usize = 30;
+    const NORMAL_DEPLOY_COUNT: usize = 2;
+
+    NetworkController::<Message>::create_active();
+    let mut network = Network::<Reactor>::new();
+    let mut rng = TestRng::new();
+
+    let deploys: Vec<Deploy> = (0..GREEDY_DEPLOY_COUNT + NORMAL_DEPLOY_COUNT)
+        .map(|_| Deploy::random(&mut rng))
+        .collect();
+    let max_deploy_size = deploys
+        .iter()
+        .map(|deploy| bincode::serialize(deploy).unwrap().len() as u64)
+        .max()
+        .unwrap();
+
+    // The holding node serves one request per peer at a time, and gives each peer a budget of a
+    // few deploys which is practically never replenished.
+    let max_response_burst_bytes = 3 * max_deploy_size;
+    let holding_cfg = FetcherTestConfig {
+        request_limiter_config: request_limiter::Config::new(
+            1,
+            1,
+            max_response_burst_bytes,
+            64,
+            200,
+        ),
+        ..Default::default()
+    };
+    let (holding_node, _) = network
+        .add_node_with_config(holding_cfg, &mut rng)
+        .await
+        .expect("should add node");
+
+    // The requesting nodes send a separate request for every deploy.
+    let mut requesting_nodes = vec![];
+    for _ in 0..2 {
+        let cfg = FetcherTestConfig {
+            fetcher_config: Config::with_batch_window(0),
+            ..Default::default()
+        };
+        let (node_id, _) = network
+            .add_node_with_config(cfg, &mut rng)
+            .await
+            .expect("should add node");
+        requesting_nodes.push(node_id);
+    }
+    let greedy_node = requesting_nodes[0];
+    let normal_node = requesting_nodes[1];
+
+    for deploy in &deploys {
+        store_deploy(deploy, &holding_node, &mut network, None, &mut rng).await;
+    }
+
+    let deploy_hashes: Vec<DeployHash> = deploys.iter().map(|deploy| *deploy.id()).collect();
+    let (greedy_hashes, normal_hashes) = deploy_hashes.split_at(GREEDY_DEPLOY_COUNT);
+    let greedy_fetched =
+        start_fetching_deploys(greedy_hashes, &greedy_node, holding_node, &mut network).await;
+    let normal_fetched =
+        start_fetching_deploys(normal_hashes, &normal_node, holding_node, &mut network).await;
+
+    let all_responded = |_nodes: &HashMap<NodeId, Runner<ConditionCheckReactor<Reactor>>>| {
+        greedy_fetched
+            .iter()
+            .chain(&normal_fetched)
+            .all(|fetched| fetched.lock().unwrap().0)
+    };
+    network
+        .settle_on(&mut rng, all_responded, Duration::from_secs(5))
+        .await;
+
+    // The normal peer's fetches all succeed despite the greedy peer's load.
+    for (deploy, fetched) in deploys[GREEDY_DEPLOY_COUNT..].iter().zip(&normal_fetched) {
+        assert_eq!(
+            fetched.lock().unwrap().1.take(),
+            Some(FetchResult::FromPeer(
+                Box::new(deploy.clone()),
+                holding_node
+            ))
+        );
+    }
+
+    // The greedy peer is told to try later once its budget is exhausted.
+    let greedy_failures = greedy_fetched
+        .iter()
+        .filter(|fetched| fetched.lock().unwrap().1.is_none())
+        .count();
+    assert!(greedy_failures > 0);
+    let greedy_reactor = network.nodes().get(&greedy_node).unwrap().reactor().inner();
+    assert!(greedy_reactor.deploy_fetcher.metrics.try_later.get() > 0);
+
+    // The greedy peer is served no more than its budget plus the single response which exhausted
+    // it.
+    let holding_reactor = network
+        .nodes()
+        .get(&holding_node)
+        .unwrap()
+        .reactor()
+        .inner();
+    let served_to_greedy = holding_reactor.request_limiter.served_bytes(&greedy_node);
+    assert!(
+        served_to_greedy <= max_response_burst_bytes + max_deploy_size,
+        "served {} bytes to greedy peer",
+        served_to_greedy
+    );
+
+    NetworkController::<Message>::remove_active();
+}
+
+/// Fetches the item with `deploy_hash` on `requesting_node` from a peer which is not part of the
+/// network, and responds to the request on the peer's behalf with `invalid_item`.
+///
+/// Returns the peer once it has been penalised, along with the result of the fetch.
+async fn fetch_invalid_item<T>(
+    invalid_item: &T,
+    deploy_hash: DeployHash,
+    requesting_node: &NodeId,
+    network: &mut Network<Reactor>,
+    rng: &mut TestRng,
+) -> (NodeId, Option<FetchResult<T, NodeId>>)
+where
+    T: Item<Id = DeployHash> + 'static,
+    ReactorEvent: From<FetcherRequest<NodeId, T>>,
+{
+    let malicious_peer = NodeId::random(rng);
+    let fetched = Arc::new(Mutex::new((false, None)));
+    network
+        .process_injected_effect_on(
+            requesting_node,
+            fetch_item::<T>(deploy_hash, malicious_peer, Arc::clone(&fetched)),
+        )
+        .await;
+    network
+        .crank_until(
+            requesting_node,
+            rng,
+            move |event: &ReactorEvent| {
+                if let ReactorEvent::NetworkRequest(NetworkRequest::SendMessage {
+                    payload, ..
+                }) = event
+                {
+                    matches!(**payload, Message::GetRequest { .. })
+                } else {
+                    false
+                }
+            },
+            TIMEOUT,
+        )
+        .await;
+
+    let payload = Message::new_get_response(invalid_item).expect("should create get-response");
+    network
+        .process_injected_effect_on(requesting_node, move |effect_builder| {
+            effect_builder
+                .announce_message_received(malicious_peer, payload)
+                .ignore()
+        })
+        .await;
+    network
+        .crank_until(
+            requesting_node,
+            rng,
+            move |event: &ReactorEvent| {
+                matches!(
+                    event,
+                    ReactorEvent::BlocklistAnnouncement(BlocklistAnnouncement::OffenseCommitted(
+                        peer
+                    )) if **peer == malicious_peer
+                )
+            },
+            TIMEOUT,
+        )
+        .await;
+
+    // The fetch should fail immediately rather than after the peer timeout, which is longer than
+    // `TIMEOUT`.
+    let has_responded = |_nodes: &HashMap<NodeId, Runner<ConditionCheckReactor<Reactor>>>| {
+        fetched.lock().unwrap().0
+    };
+    network.settle_on(rng, has_responded, TIMEOUT).await;
+
+    let mut result = fetched.lock().unwrap();
+    (malicious_peer, result.1.take())
+}
+
+#[tokio::test]
+async fn should_reject_mismatched_deploy_header_and_fetch_from_honest_peer() {
+    const NETWORK_SIZE: usize = 2;
+
+    NetworkController::<Message>::create_active();
+    let mut network = Network::<Reactor>::new();
+    let mut rng = TestRng::new();
+    let node_ids = network.add_nodes(&mut rng, NETWORK_SIZE).await;
+    let holding_node = node_ids[0];
+    let requesting_node = node_ids[1];
+
+    let deploy = Deploy::random(&mut rng);
+    let deploy_hash = *deploy.id();
+    store_deploy(&deploy, &holding_node, &mut network, None, &mut rng).await;
+
+    // The header of a different deploy, claiming to be that of the requested one.
+    let other_deploy = Deploy::random(&mut rng);
+    let mismatched_header = DeployHeaderWithHash::new(deploy_hash, other_deploy.header().clone());
+    let (_, result) = fetch_invalid_item(
+        &mismatched_header,
+        deploy_hash,
+        &requesting_node,
+        &mut network,
+        &mut rng,
+    )
+    .await;
+    assert_eq!(result, None);
+    let metrics = &network
+        .nodes()
+        .get(&requesting_node)
+        .unwrap()
+        .reactor()
+        .inner()
+        .deploy_header_fetcher
+        .metrics;
+    assert_eq!(metrics.invalid_items.get(), 1);
+    assert_eq!(metrics.found_on_peer.get(), 0);
+
+    let result = fetch_partial_deploy_item::<DeployHeaderWithHash>(
+        deploy_hash,
+        &requesting_node,
+        holding_node,
+        &mut network,
+        &mut rng,
+    )
+    .await;
+    let expected_header = DeployHeaderWithHash::new(deploy_hash, deploy.header().clone());
+    assert_eq!(
+        result,
+        Some(FetchResult::FromPeer(
+            Box::new(expected_header),
+            holding_node
+        ))
+    );
+
+    NetworkController::<Message>::remove_active();
+}
+
+#[tokio::test]
+async fn should_reject_corrupted_deploy_approvals_and_fetch_from_honest_peer() {
+    const NETWORK_SIZE: usize = 2;
+
+    NetworkController::<Message>::create_active();
+    let mut network = Network::<Reactor>::new();
+    let mut rng = TestRng::new();
+    let node_ids = network.add_nodes(&mut rng, NETWORK_SIZE).await;
+    let holding_node = node_ids[0];
+    let requesting_node = node_ids[1];
+
+    let deploy = Deploy::random(&mut rng);
+    let deploy_hash = *deploy.id();
+    store_deploy(&deploy, &holding_node, &mut network, None, &mut rng).await;
+
+    // Approvals signing a different deploy's hash.
+    let other_deploy = Deploy::random(&mut rng);
+    let corrupted_approvals = DeployApprovals::new(deploy_hash, other_deploy.approvals().to_vec());
+    let (_, result) = fetch_invalid_item(
+        &corrupted_approvals,
+        deploy_hash,
+        &requesting_node,
+        &mut network,
+        &mut rng,
+    )
+    .await;
+    assert_eq!(result, None);
+    let metrics = &network
+        .nodes()
+        .get(&requesting_node)
+        .unwrap()
+        .reactor()
+        .inner()
+        .deploy_approvals_fetcher
+        .metrics;
+    assert_eq!(metrics.invalid_items.get(), 1);
+    assert_eq!(metrics.found_on_peer.get(), 0);
+
+    let result = fetch_partial_deploy_item::<DeployApprovals>(
+        deploy_hash,
+        &requesting_node,
+        holding_node,
+        &mut network,
+        &mut rng,
+    )
+    .await;
+    let expected_approvals = DeployApprovals::new(deploy_hash, deploy.approvals().to_vec());
+    assert_eq!(
+        result,
+        Some(FetchResult::FromPeer(
+            Box::new(expected_approvals),
+            holding_node
+        ))
+    );
+
+    NetworkController::<Message>::remove_active();
+}
+
+#[tokio::test]
+async fn should_reject_corrupted_deploy_and_fetch_from_honest_peer() {
+    const NETWORK_SIZE: usize = 2;
+
+    NetworkController::<Message>::create_active();
+    let mut network = Network::<Reactor>::new();
+    let mut rng = TestRng::new();
+    let node_ids = network.add_nodes(&mut rng, NETWORK_SIZE).await;
+    let holding_node = node_ids[0];
+    let requesting_node = node_ids[1];
+
+    let deploy = Deploy::random(&mut rng);
+    let deploy_hash = *deploy.id();
+    store_deploy(&deploy, &holding_node, &mut network, None, &mut rng).await;
+
+    // The requested deploy, but with a different deploy's approvals.
+    let other_deploy = Deploy::random(&mut rng);
+    let mut json_deploy = serde_json::to_value(&deploy).unwrap();
+    json_deploy["approvals"] = serde_json::to_value(other_deploy.approvals()).unwrap();
+    let corrupted_deploy: Deploy = serde_json::from_value(json_deploy).unwrap();
+    let (_, result) = fetch_invalid_item(
+        &corrupted_deploy,
+        deploy_hash,
+        &requesting_node,
+        &mut network,
+        &mut rng,
+    )
+    .await;
+    assert_eq!(result, None);
+    assert!(!has_deploy(&network, &requesting_node, deploy_hash));
+    let metrics = &network
+        .nodes()
+        .get(&requesting_node)
+        .unwrap()
+        .reactor()
+        .inner()
+        .deploy_fetcher
+        .metrics;
+    assert_eq!(metrics.invalid_items.get(), 1);
+    assert_eq!(metrics.found_on_peer.get(), 0);
+
+    let result = fetch_partial_deploy_item::<Deploy>(
+        deploy_hash,
+        &requesting_node,
+        holding_node,
+        &mut network,
+        &mut rng,
+    )
+    .await;
+    assert_eq!(
+        result,
+        Some(FetchResult::FromPeer(Box::new(deploy), holding_node))
+    );
+    assert!(has_deploy(&network, &requesting_node, deploy_hash));
+
+    NetworkController::<Message>::remove_active();
+}
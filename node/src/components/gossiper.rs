@@ -10,9 +10,10 @@ use datasize::DataSize;
 use prometheus::Registry;
 use smallvec::smallvec;
 use std::{
-    collections::HashSet,
+    collections::{hash_map::Entry, HashMap, HashSet},
     convert::Infallible,
     fmt::{self, Debug, Formatter},
+    hash::Hash,
     time::Duration,
 };
 use tracing::{debug, error, warn};
@@ -25,6 +26,7 @@ use crate::{
         EffectBuilder, EffectExt, Effects,
     },
     protocol::Message as NodeMessage,
+    reactor::{ReactorEvent, TimerHandle},
     types::{Deploy, DeployHash, Item, NodeId},
     utils::Source,
     NodeRng,
@@ -44,6 +46,7 @@ pub trait ReactorEventT<T>:
     + From<NetworkRequest<NodeId, NodeMessage>>
     + From<StorageRequest>
     + From<GossiperAnnouncement<T>>
+    + ReactorEvent
     + Send
     + 'static
 where
@@ -61,6 +64,7 @@ where
         + From<NetworkRequest<NodeId, NodeMessage>>
         + From<StorageRequest>
         + From<GossiperAnnouncement<T>>
+        + ReactorEvent
         + Send
         + 'static,
 {
@@ -92,6 +96,27 @@ pub(crate) fn get_deploy_from_storage<T: Item + 'static, REv: ReactorEventT<T>>(
         })
 }
 
+/// Returns the number of the `count` peers which should be validators, rounding up so that a
+/// non-zero percentage always asks for at least one validator.
+fn min_validator_count(count: usize, validator_target_percent: u8) -> usize {
+    let percent = validator_target_percent as usize;
+    (count * percent + 99) / 100
+}
+
+/// Cancels the pending timeout for `peer` regarding the given item, if any.
+fn cancel_timeout<Id: Eq + Hash>(
+    timeouts: &mut HashMap<Id, HashMap<NodeId, TimerHandle>>,
+    item_id: Id,
+    peer: NodeId,
+) {
+    if let Entry::Occupied(mut item_timeouts) = timeouts.entry(item_id) {
+        item_timeouts.get_mut().remove(&peer);
+        if item_timeouts.get().is_empty() {
+            item_timeouts.remove();
+        }
+    }
+}
+
 /// The component which gossips to peers and handles incoming gossip messages from peers.
 #[allow(clippy::type_complexity)]
 #[derive(DataSize)]
@@ -100,9 +125,16 @@ where
     T: Item + 'static,
     REv: ReactorEventT<T>,
 {
+    /// The gossip state of each item, keyed on the item's ID rather than its serialized form, so
+    /// that differently-encoded copies of an item are only gossiped once.
     table: GossipTable<T::Id>,
+    validator_target_percent: u8,
     gossip_timeout: Duration,
     get_from_peer_timeout: Duration,
+    /// The pending timeouts for peers to respond to our gossip requests.
+    gossip_timeouts: HashMap<T::Id, HashMap<NodeId, TimerHandle>>,
+    /// The pending timeouts for peers to provide the full items we requested from them.
+    get_from_peer_timeouts: HashMap<T::Id, HashMap<NodeId, TimerHandle>>,
     #[data_size(skip)] // Not well supported by datasize.
     get_from_holder:
         Box<dyn Fn(EffectBuilder<REv>, T::Id, NodeId) -> Effects<Event<T>> + Send + 'static>,
@@ -137,8 +169,11 @@ impl<T: Item + 'static, REv: ReactorEventT<T>> Gossiper<T, REv> {
         );
         Ok(Gossiper {
             table: GossipTable::new(config),
+            validator_target_percent: config.validator_target_percent(),
             gossip_timeout: Duration::from_secs(config.gossip_request_timeout_secs()),
             get_from_peer_timeout: Duration::from_secs(config.get_remainder_timeout_secs()),
+            gossip_timeouts: HashMap::new(),
+            get_from_peer_timeouts: HashMap::new(),
             get_from_holder: Box::new(get_from_holder),
             metrics: GossiperMetrics::new(name, registry)?,
         })
@@ -160,8 +195,11 @@ impl<T: Item + 'static, REv: ReactorEventT<T>> Gossiper<T, REv> {
         );
         Ok(Gossiper {
             table: GossipTable::new(config),
+            validator_target_percent: config.validator_target_percent(),
             gossip_timeout: Duration::from_secs(config.gossip_request_timeout_secs()),
             get_from_peer_timeout: Duration::from_secs(config.get_remainder_timeout_secs()),
+            gossip_timeouts: HashMap::new(),
+            get_from_peer_timeouts: HashMap::new(),
             get_from_holder: Box::new(|_, item, _| {
                 panic!("gossiper should never try to get {}", item)
             }),
@@ -179,6 +217,8 @@ impl<T: Item + 'static, REv: ReactorEventT<T>> Gossiper<T, REv> {
         source: Source<NodeId>,
     ) -> Effects<Event<T>> {
         debug!(item=%item_id, %source, "received new gossip item");
+        // We no longer need any peer to provide the item.
+        self.get_from_peer_timeouts.remove(&item_id);
         match self.table.new_complete_data(&item_id, source.node_id()) {
             GossipAction::ShouldGossip(should_gossip) => {
                 self.metrics.items_received.inc();
@@ -200,7 +240,51 @@ impl<T: Item + 'static, REv: ReactorEventT<T>> Gossiper<T, REv> {
         }
     }
 
+    /// Handles a request to restart gossiping an item we hold, e.g. as it has replaced another
+    /// item.
+    fn handle_regossip(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        item_id: T::Id,
+    ) -> Effects<Event<T>> {
+        debug!(item=%item_id, "regossiping item");
+        match self.table.regossip(&item_id) {
+            GossipAction::ShouldGossip(should_gossip) => self.gossip(
+                effect_builder,
+                item_id,
+                should_gossip.count,
+                should_gossip.exclude_peers,
+            ),
+            action => {
+                debug!(item=%item_id, %action, "not regossiping item");
+                Effects::new()
+            }
+        }
+    }
+
+    /// Handles items no longer needed by the network, e.g. deploys included in a finalized block.
+    ///
+    /// Gossiping them stops immediately: pending timeouts are dropped so no further gossip
+    /// requests are sent, and any gossip about them is ignored for `retain_for`.
+    fn handle_items_finished_externally(
+        &mut self,
+        item_ids: Vec<T::Id>,
+        retain_for: Duration,
+    ) -> Effects<Event<T>> {
+        for item_id in item_ids {
+            self.gossip_timeouts.remove(&item_id);
+            self.get_from_peer_timeouts.remove(&item_id);
+            let saved_count = self.table.finish_externally(&item_id, retain_for);
+            debug!(item=%item_id, saved_count, "finished gossiping item externally");
+            self.metrics.gossip_rounds_saved.inc_by(saved_count as u64);
+        }
+        Effects::new()
+    }
+
     /// Gossips the given item ID to `count` random peers excluding the indicated ones.
+    ///
+    /// If `T::PRIORITIZE_VALIDATORS` is true, at least `validator_target_percent` of the chosen
+    /// peers will be validators where enough are connected.
     fn gossip(
         &mut self,
         effect_builder: EffectBuilder<REv>,
@@ -208,9 +292,14 @@ impl<T: Item + 'static, REv: ReactorEventT<T>> Gossiper<T, REv> {
         count: usize,
         exclude_peers: HashSet<NodeId>,
     ) -> Effects<Event<T>> {
+        let min_validators = if T::PRIORITIZE_VALIDATORS {
+            min_validator_count(count, self.validator_target_percent)
+        } else {
+            0
+        };
         let message = Message::Gossip(item_id);
         effect_builder
-            .gossip_message(message, count, exclude_peers)
+            .gossip_message(message, count, min_validators, exclude_peers)
             .event(move |peers| Event::GossipedTo {
                 item_id,
                 requested_count: count,
@@ -246,11 +335,14 @@ impl<T: Item + 'static, REv: ReactorEventT<T>> Gossiper<T, REv> {
 
         // Set timeouts to check later that the specified peers all responded.
         for peer in peers {
-            effects.extend(
-                effect_builder
-                    .set_timeout(self.gossip_timeout)
-                    .event(move |_| Event::CheckGossipTimeout { item_id, peer }),
-            )
+            let timer = effect_builder.set_timeout_with_handle(
+                self.gossip_timeout,
+                Event::CheckGossipTimeout { item_id, peer },
+            );
+            self.gossip_timeouts
+                .entry(item_id)
+                .or_default()
+                .insert(peer, timer);
         }
 
         effects
@@ -263,6 +355,7 @@ impl<T: Item + 'static, REv: ReactorEventT<T>> Gossiper<T, REv> {
         item_id: T::Id,
         peer: NodeId,
     ) -> Effects<Event<T>> {
+        cancel_timeout(&mut self.gossip_timeouts, item_id, peer);
         match self.table.check_timeout(&item_id, peer) {
             GossipAction::ShouldGossip(should_gossip) => self.gossip(
                 effect_builder,
@@ -292,6 +385,7 @@ impl<T: Item + 'static, REv: ReactorEventT<T>> Gossiper<T, REv> {
         item_id: T::Id,
         peer: NodeId,
     ) -> Effects<Event<T>> {
+        cancel_timeout(&mut self.get_from_peer_timeouts, item_id, peer);
         match self.table.remove_holder_if_unresponsive(&item_id, peer) {
             GossipAction::ShouldGossip(should_gossip) => self.gossip(
                 effect_builder,
@@ -307,22 +401,17 @@ impl<T: Item + 'static, REv: ReactorEventT<T>> Gossiper<T, REv> {
                 let request = match NodeMessage::new_get_request::<T>(&item_id) {
                     Ok(request) => request,
                     Err(error) => {
+                        // Serializing the request would fail identically for every holder, so
+                        // rather than trying each of them in turn, give up on the item.
                         error!("failed to create get-request: {}", error);
-                        // Treat this as if the holder didn't respond - i.e. try to get from a
-                        // different holder.
-                        return self.check_get_from_peer_timeout(effect_builder, item_id, holder);
+                        if self.table.force_finish(&item_id) {
+                            return effect_builder.announce_finished_gossiping(item_id).ignore();
+                        }
+                        return Effects::new();
                     }
                 };
-                let mut effects = effect_builder.send_message(holder, request).ignore();
-                effects.extend(
-                    effect_builder
-                        .set_timeout(self.get_from_peer_timeout)
-                        .event(move |_| Event::CheckGetFromPeerTimeout {
-                            item_id,
-                            peer: holder,
-                        }),
-                );
-                effects
+                self.set_get_from_peer_timeout(effect_builder, item_id, holder);
+                effect_builder.send_message(holder, request).ignore()
             }
 
             GossipAction::AnnounceFinished => {
@@ -333,6 +422,23 @@ impl<T: Item + 'static, REv: ReactorEventT<T>> Gossiper<T, REv> {
         }
     }
 
+    /// Sets a timeout to check that `peer` provided the full item we requested from it.
+    fn set_get_from_peer_timeout(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        item_id: T::Id,
+        peer: NodeId,
+    ) {
+        let timer = effect_builder.set_timeout_with_handle(
+            self.get_from_peer_timeout,
+            Event::CheckGetFromPeerTimeout { item_id, peer },
+        );
+        self.get_from_peer_timeouts
+            .entry(item_id)
+            .or_default()
+            .insert(peer, timer);
+    }
+
     /// Handles an incoming gossip request from a peer on the network.
     fn handle_gossip(
         &mut self,
@@ -385,16 +491,8 @@ impl<T: Item + 'static, REv: ReactorEventT<T>> Gossiper<T, REv> {
                     item_id,
                     is_already_held: false,
                 };
-                let mut effects = effect_builder.send_message(sender, reply).ignore();
-                effects.extend(
-                    effect_builder
-                        .set_timeout(self.get_from_peer_timeout)
-                        .event(move |_| Event::CheckGetFromPeerTimeout {
-                            item_id,
-                            peer: sender,
-                        }),
-                );
-                effects
+                self.set_get_from_peer_timeout(effect_builder, item_id, sender);
+                effect_builder.send_message(sender, reply).ignore()
             }
             GossipAction::Noop
             | GossipAction::AwaitingRemainder
@@ -423,6 +521,7 @@ impl<T: Item + 'static, REv: ReactorEventT<T>> Gossiper<T, REv> {
         is_already_held: bool,
         sender: NodeId,
     ) -> Effects<Event<T>> {
+        cancel_timeout(&mut self.gossip_timeouts, item_id, sender);
         let mut effects: Effects<_> = Effects::new();
         let action = if is_already_held {
             self.table.already_infected(&item_id, sender)
@@ -503,13 +602,21 @@ impl<T: Item + 'static, REv: ReactorEventT<T>> Gossiper<T, REv> {
     }
 
     /// Updates the gossiper metrics from the state of the gossip table.
-    fn update_gossip_table_metrics(&self) {
+    fn update_gossip_table_metrics(&mut self) {
         self.metrics
             .table_items_current
             .set(self.table.items_current() as i64);
         self.metrics
             .table_items_finished
             .set(self.table.items_finished() as i64);
+
+        let transitions = self.table.take_phase_transitions();
+        self.metrics.items_saturated.inc_by(transitions.saturated);
+        self.metrics.items_finished.inc_by(transitions.finished);
+        self.metrics.items_purged.inc_by(transitions.purged);
+        self.metrics
+            .items_finished_externally
+            .inc_by(transitions.finished_externally);
     }
 }
 
@@ -531,6 +638,11 @@ where
             Event::ItemReceived { item_id, source } => {
                 self.handle_item_received(effect_builder, item_id, source)
             }
+            Event::RegossipItem { item_id } => self.handle_regossip(effect_builder, item_id),
+            Event::ItemsFinishedExternally {
+                item_ids,
+                retain_for,
+            } => self.handle_items_finished_externally(item_ids, retain_for),
             Event::GossipedTo {
                 item_id,
                 requested_count,
@@ -568,6 +680,7 @@ impl<T: Item + 'static, REv: ReactorEventT<T>> Debug for Gossiper<T, REv> {
         formatter
             .debug_struct("Gossiper")
             .field("table", &self.table)
+            .field("validator_target_percent", &self.validator_target_percent)
             .field("gossip_timeout", &self.gossip_timeout)
             .field("get_from_peer_timeout", &self.get_from_peer_timeout)
             .finish()
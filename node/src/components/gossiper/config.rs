@@ -5,12 +5,16 @@ use serde::{
 };
 use tracing::error;
 
+use crate::config_validation::ConfigValidationError;
+
 #[cfg(test)]
 use super::Error;
 
 const DEFAULT_INFECTION_TARGET: u8 = 3;
 const DEFAULT_SATURATION_LIMIT_PERCENT: u8 = 80;
 pub(super) const MAX_SATURATION_LIMIT_PERCENT: u8 = 99;
+const DEFAULT_VALIDATOR_TARGET_PERCENT: u8 = 50;
+pub(super) const MAX_VALIDATOR_TARGET_PERCENT: u8 = 100;
 pub(super) const DEFAULT_FINISHED_ENTRY_DURATION_SECS: u64 = 60;
 const DEFAULT_GOSSIP_REQUEST_TIMEOUT_SECS: u64 = 10;
 const DEFAULT_GET_REMAINDER_TIMEOUT_SECS: u64 = 60;
@@ -34,6 +38,12 @@ pub struct Config {
     /// holders excluding us since 80% saturation would imply 3 new infections in 15 peers.
     #[serde(deserialize_with = "deserialize_saturation_limit_percent")]
     saturation_limit_percent: u8,
+    /// The minimum percentage of peers gossiped to in each round which should be validators, with
+    /// a maximum value of 100.  Only applies to items which prioritize validators, i.e. deploys.
+    ///
+    /// If too few validators are connected, the remaining peers are chosen at random.
+    #[serde(deserialize_with = "deserialize_validator_target_percent")]
+    validator_target_percent: u8,
     /// The maximum duration in seconds for which to keep finished entries.
     ///
     /// The longer they are retained, the lower the likelihood of re-gossiping a piece of data.
@@ -53,6 +63,7 @@ impl Config {
     pub(crate) fn new(
         infection_target: u8,
         saturation_limit_percent: u8,
+        validator_target_percent: u8,
         finished_entry_duration_secs: u64,
         gossip_request_timeout_secs: u64,
         get_remainder_timeout_secs: u64,
@@ -60,9 +71,13 @@ impl Config {
         if saturation_limit_percent > MAX_SATURATION_LIMIT_PERCENT {
             return Err(Error::InvalidSaturationLimit);
         }
+        if validator_target_percent > MAX_VALIDATOR_TARGET_PERCENT {
+            return Err(Error::InvalidValidatorTarget);
+        }
         Ok(Config {
             infection_target,
             saturation_limit_percent,
+            validator_target_percent,
             finished_entry_duration_secs,
             gossip_request_timeout_secs,
             get_remainder_timeout_secs,
@@ -87,6 +102,10 @@ impl Config {
         self.saturation_limit_percent
     }
 
+    pub(crate) fn validator_target_percent(&self) -> u8 {
+        self.validator_target_percent
+    }
+
     pub(crate) fn finished_entry_duration_secs(&self) -> u64 {
         self.finished_entry_duration_secs
     }
@@ -98,6 +117,38 @@ impl Config {
     pub(crate) fn get_remainder_timeout_secs(&self) -> u64 {
         self.get_remainder_timeout_secs
     }
+
+    /// Checks whether the values set in the config make sense, pushing every problem found to
+    /// `errors`.
+    pub(crate) fn validate(&self, errors: &mut Vec<ConfigValidationError>) {
+        if self.infection_target == 0 {
+            errors.push(ConfigValidationError::out_of_range(
+                "gossip.infection_target",
+                self.infection_target,
+                "greater than 0",
+            ));
+        }
+
+        let timeouts = [
+            (
+                "gossip.gossip_request_timeout_secs",
+                self.gossip_request_timeout_secs,
+            ),
+            (
+                "gossip.get_remainder_timeout_secs",
+                self.get_remainder_timeout_secs,
+            ),
+        ];
+        for (field, timeout) in timeouts.iter() {
+            if *timeout == 0 {
+                errors.push(ConfigValidationError::out_of_range(
+                    field,
+                    timeout,
+                    "greater than 0",
+                ));
+            }
+        }
+    }
 }
 
 impl Default for Config {
@@ -105,6 +156,7 @@ impl Default for Config {
         Config {
             infection_target: DEFAULT_INFECTION_TARGET,
             saturation_limit_percent: DEFAULT_SATURATION_LIMIT_PERCENT,
+            validator_target_percent: DEFAULT_VALIDATOR_TARGET_PERCENT,
             finished_entry_duration_secs: DEFAULT_FINISHED_ENTRY_DURATION_SECS,
             gossip_request_timeout_secs: DEFAULT_GOSSIP_REQUEST_TIMEOUT_SECS,
             get_remainder_timeout_secs: DEFAULT_GET_REMAINDER_TIMEOUT_SECS,
@@ -132,6 +184,26 @@ where
     Ok(saturation_limit_percent)
 }
 
+/// Deserializes a `u8` but fails if it's above 100.
+fn deserialize_validator_target_percent<'de, D>(deserializer: D) -> Result<u8, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let validator_target_percent = u8::deserialize(deserializer)?;
+    if validator_target_percent > MAX_VALIDATOR_TARGET_PERCENT {
+        error!(
+            "validator_target_percent of {} is above {}",
+            validator_target_percent, MAX_VALIDATOR_TARGET_PERCENT
+        );
+        return Err(SerdeError::invalid_value(
+            Unexpected::Unsigned(validator_target_percent as u64),
+            &"a value between 0 and 100 inclusive",
+        ));
+    }
+
+    Ok(validator_target_percent)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -142,6 +214,7 @@ mod tests {
         let invalid_config = Config {
             infection_target: 3,
             saturation_limit_percent: MAX_SATURATION_LIMIT_PERCENT + 1,
+            validator_target_percent: DEFAULT_VALIDATOR_TARGET_PERCENT,
             finished_entry_duration_secs: DEFAULT_FINISHED_ENTRY_DURATION_SECS,
             gossip_request_timeout_secs: DEFAULT_GOSSIP_REQUEST_TIMEOUT_SECS,
             get_remainder_timeout_secs: DEFAULT_GET_REMAINDER_TIMEOUT_SECS,
@@ -155,6 +228,31 @@ mod tests {
         assert!(Config::new(
             3,
             MAX_SATURATION_LIMIT_PERCENT + 1,
+            DEFAULT_VALIDATOR_TARGET_PERCENT,
+            DEFAULT_FINISHED_ENTRY_DURATION_SECS,
+            DEFAULT_GOSSIP_REQUEST_TIMEOUT_SECS,
+            DEFAULT_GET_REMAINDER_TIMEOUT_SECS,
+        )
+        .is_err())
+    }
+
+    #[test]
+    fn invalid_validator_target_should_fail() {
+        // validator_target_percent > MAX_VALIDATOR_TARGET_PERCENT
+        let invalid_config = Config {
+            validator_target_percent: MAX_VALIDATOR_TARGET_PERCENT + 1,
+            ..Default::default()
+        };
+
+        // Parsing should fail.
+        let config_as_json = serde_json::to_string(&invalid_config).unwrap();
+        assert!(serde_json::from_str::<Config>(&config_as_json).is_err());
+
+        // Construction should fail.
+        assert!(Config::new(
+            3,
+            DEFAULT_SATURATION_LIMIT_PERCENT,
+            MAX_VALIDATOR_TARGET_PERCENT + 1,
             DEFAULT_FINISHED_ENTRY_DURATION_SECS,
             DEFAULT_GOSSIP_REQUEST_TIMEOUT_SECS,
             DEFAULT_GET_REMAINDER_TIMEOUT_SECS,
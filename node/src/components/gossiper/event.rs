@@ -1,6 +1,7 @@
 use std::{
     collections::HashSet,
     fmt::{self, Display, Formatter},
+    time::Duration,
 };
 
 use serde::Serialize;
@@ -19,6 +20,14 @@ pub enum Event<T: Item> {
         item_id: T::Id,
         source: Source<NodeId>,
     },
+    /// An item we hold should be gossiped afresh, even if gossiping it had already finished.
+    RegossipItem { item_id: T::Id },
+    /// The items are no longer needed by the network, e.g. deploys included in a finalized block,
+    /// and should not be gossiped further.  Any gossip about them is ignored for `retain_for`.
+    ItemsFinishedExternally {
+        item_ids: Vec<T::Id>,
+        retain_for: Duration,
+    },
     /// The network component gossiped to the included peers.
     GossipedTo {
         item_id: T::Id,
@@ -48,6 +57,12 @@ impl<T: Item> Display for Event<T> {
             Event::ItemReceived { item_id, source } => {
                 write!(formatter, "new item {} received from {}", item_id, source)
             }
+            Event::RegossipItem { item_id } => write!(formatter, "regossip item {}", item_id),
+            Event::ItemsFinishedExternally { item_ids, .. } => write!(
+                formatter,
+                "finished gossiping {} externally",
+                DisplayIter::new(item_ids)
+            ),
             Event::GossipedTo { item_id, peers, .. } => write!(
                 formatter,
                 "gossiped {} to {}",
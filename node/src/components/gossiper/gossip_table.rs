@@ -4,13 +4,14 @@ use std::{
     collections::{HashMap, HashSet},
     fmt::{self, Display, Formatter},
     hash::Hash,
+    mem,
     time::Duration,
 };
 
 use datasize::DataSize;
 #[cfg(test)]
 use fake_instant::FakeClock as Instant;
-use tracing::{debug, error, warn};
+use tracing::{debug, error, trace, warn};
 
 use super::Config;
 use crate::{types::NodeId, utils::DisplayIter};
@@ -82,6 +83,47 @@ impl Display for ShouldGossip {
     }
 }
 
+/// The phases an item passes through while held in the gossip table.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Phase {
+    /// We are still gossiping the item to further peers.
+    Infecting,
+    /// Responses indicate that enough of the peers we contacted already hold the item, as
+    /// determined by `Config::saturation_limit_percent`.
+    Saturated,
+    /// We have stopped gossiping the item.  It is retained for `Config::finished_entry_duration`
+    /// to avoid re-gossiping it.
+    Finished,
+    /// We have stopped gossiping the item as it is no longer needed by the network, e.g. a deploy
+    /// included in a finalized block.  It is retained for the duration given when it was finished,
+    /// to avoid re-gossiping it.
+    FinishedExternally,
+}
+
+impl Display for Phase {
+    fn fmt(&self, formatter: &mut Formatter) -> Result<(), fmt::Error> {
+        match self {
+            Phase::Infecting => write!(formatter, "infecting"),
+            Phase::Saturated => write!(formatter, "saturated"),
+            Phase::Finished => write!(formatter, "finished"),
+            Phase::FinishedExternally => write!(formatter, "finished externally"),
+        }
+    }
+}
+
+/// Counts of phase transitions in the gossip table which have not yet been reported.
+#[derive(DataSize, Debug, Default, PartialEq, Eq)]
+pub(crate) struct PhaseTransitions {
+    /// The number of items which became saturated.
+    pub(crate) saturated: u64,
+    /// The number of items which became finished.
+    pub(crate) finished: u64,
+    /// The number of items which became finished externally.
+    pub(crate) finished_externally: u64,
+    /// The number of finished items purged after being retained for the finished entry duration.
+    pub(crate) purged: u64,
+}
+
 #[derive(DataSize, Debug, Default)]
 pub(crate) struct State {
     /// The peers excluding us which hold the data.
@@ -98,7 +140,12 @@ pub(crate) struct State {
 impl State {
     /// Returns whether we should finish gossiping this data.
     fn is_finished(&self, infection_target: usize, holders_limit: usize) -> bool {
-        self.infected_by_us.len() >= infection_target || self.holders.len() >= holders_limit
+        self.infected_by_us.len() >= infection_target || self.is_saturated(holders_limit)
+    }
+
+    /// Returns whether enough peers already hold this data that gossiping it further is pointless.
+    fn is_saturated(&self, holders_limit: usize) -> bool {
+        self.holders.len() >= holders_limit
     }
 
     /// Returns a `GossipAction` derived from the given state.
@@ -179,6 +226,10 @@ pub(crate) struct GossipTable<T> {
     finished: HashSet<T>,
     /// Timeouts for removal of items from the `finished` cache.
     timeouts: Timeouts<T>,
+    /// Data IDs which were finished externally rather than by gossiping.
+    finished_externally: HashSet<T>,
+    /// Timeouts for removal of items from the `finished_externally` cache.
+    finished_externally_timeouts: Timeouts<T>,
     /// See `Config::infection_target`.
     infection_target: usize,
     /// Derived from `Config::saturation_limit_percent` - we gossip data while the number of
@@ -186,6 +237,8 @@ pub(crate) struct GossipTable<T> {
     holders_limit: usize,
     /// See `Config::finished_entry_duration`.
     finished_entry_duration: Duration,
+    /// Phase transitions not yet collected via `take_phase_transitions`.
+    phase_transitions: PhaseTransitions,
 }
 
 impl<T> GossipTable<T> {
@@ -196,7 +249,12 @@ impl<T> GossipTable<T> {
 
     /// Number of items that are kept but are finished gossiping.
     pub fn items_finished(&self) -> usize {
-        self.finished.len()
+        self.finished.len() + self.finished_externally.len()
+    }
+
+    /// Returns the phase transitions recorded since the last call, resetting the counts.
+    pub(crate) fn take_phase_transitions(&mut self) -> PhaseTransitions {
+        mem::take(&mut self.phase_transitions)
     }
 }
 
@@ -209,9 +267,12 @@ impl<T: Copy + Eq + Hash + Display> GossipTable<T> {
             current: HashMap::new(),
             finished: HashSet::new(),
             timeouts: Timeouts::new(),
+            finished_externally: HashSet::new(),
+            finished_externally_timeouts: Timeouts::new(),
             infection_target: usize::from(config.infection_target()),
             holders_limit,
             finished_entry_duration: Duration::from_secs(config.finished_entry_duration_secs()),
+            phase_transitions: PhaseTransitions::default(),
         }
     }
 
@@ -226,7 +287,7 @@ impl<T: Copy + Eq + Hash + Display> GossipTable<T> {
     pub(crate) fn new_partial_data(&mut self, data_id: &T, holder: NodeId) -> GossipAction {
         self.purge_finished();
 
-        if self.finished.contains(data_id) {
+        if self.is_finished(data_id) {
             debug!(item=%data_id, "no further action: item already finished");
             return GossipAction::Noop;
         }
@@ -265,7 +326,7 @@ impl<T: Copy + Eq + Hash + Display> GossipTable<T> {
     ) -> GossipAction {
         self.purge_finished();
 
-        if self.finished.contains(data_id) {
+        if self.is_finished(data_id) {
             debug!(item=%data_id, "no further action: item already finished");
             return GossipAction::Noop;
         }
@@ -290,6 +351,17 @@ impl<T: Copy + Eq + Hash + Display> GossipTable<T> {
         action
     }
 
+    /// We hold the complete data with given ID and want to gossip it afresh, even if gossiping it
+    /// had already finished.
+    ///
+    /// Returns whether we should gossip it, and a list of peers to exclude.
+    pub(crate) fn regossip(&mut self, data_id: &T) -> GossipAction {
+        if self.finished.remove(data_id) {
+            debug!(item=%data_id, "restarting gossip of finished item");
+        }
+        self.new_complete_data(data_id, None)
+    }
+
     /// We got a response from a peer we gossiped to indicating we infected it (it didn't previously
     /// know of this data).
     ///
@@ -426,12 +498,50 @@ impl<T: Copy + Eq + Hash + Display> GossipTable<T> {
     /// Returns `true` if there was a current entry for this data.
     pub(crate) fn force_finish(&mut self, data_id: &T) -> bool {
         if self.current.remove(data_id).is_some() {
-            self.insert_to_finished(data_id);
+            self.insert_to_finished(data_id, Phase::Infecting);
             return true;
         }
         false
     }
 
+    /// The data is no longer needed by the network, e.g. a deploy which has been included in a
+    /// finalized block, so gossiping it should stop immediately, whatever its phase.  The entry
+    /// is retained for `retain_for`, during which any gossip about it is ignored.
+    ///
+    /// Returns the number of gossip requests we no longer need to send for this data.
+    pub(crate) fn finish_externally(&mut self, data_id: &T, retain_for: Duration) -> usize {
+        self.purge_finished();
+
+        if self.finished_externally.contains(data_id) {
+            return 0;
+        }
+
+        let (from, saved_count) = match self.current.remove(data_id) {
+            Some(state) => {
+                let saved_count = self
+                    .infection_target
+                    .saturating_sub(state.in_flight_count + state.infected_by_us.len());
+                (Phase::Infecting, saved_count)
+            }
+            None if self.finished.remove(data_id) => (Phase::Finished, 0),
+            None => {
+                trace!(item=%data_id, to=%Phase::FinishedExternally, "new gossip entry");
+                self.insert_to_finished_externally(data_id, retain_for);
+                return 0;
+            }
+        };
+
+        trace!(
+            item=%data_id,
+            %from,
+            to=%Phase::FinishedExternally,
+            saved_count,
+            "gossip phase transition"
+        );
+        self.insert_to_finished_externally(data_id, retain_for);
+        saved_count
+    }
+
     /// Updates the entry under `data_id` in `self.current` and returns the action we should now
     /// take, or `None` if the entry does not exist.
     ///
@@ -444,7 +554,22 @@ impl<T: Copy + Eq + Hash + Display> GossipTable<T> {
         let mut state = self.current.remove(data_id)?;
         update(&mut state);
         if state.is_finished(self.infection_target, self.holders_limit) {
-            self.insert_to_finished(data_id);
+            let mut phase = Phase::Infecting;
+            if state.infected_by_us.len() < self.infection_target
+                && state.is_saturated(self.holders_limit)
+            {
+                trace!(
+                    item=%data_id,
+                    holders=%state.holders.len(),
+                    infected_by_us=%state.infected_by_us.len(),
+                    from=%Phase::Infecting,
+                    to=%Phase::Saturated,
+                    "gossip phase transition"
+                );
+                self.phase_transitions.saturated += 1;
+                phase = Phase::Saturated;
+            }
+            self.insert_to_finished(data_id, phase);
             return Some(GossipAction::AnnounceFinished);
         }
         let is_new = false;
@@ -453,18 +578,48 @@ impl<T: Copy + Eq + Hash + Display> GossipTable<T> {
         Some(action)
     }
 
-    fn insert_to_finished(&mut self, data_id: &T) {
+    fn insert_to_finished(&mut self, data_id: &T, from: Phase) {
+        trace!(item=%data_id, %from, to=%Phase::Finished, "gossip phase transition");
+        self.phase_transitions.finished += 1;
         let timeout = Instant::now() + self.finished_entry_duration;
         let _ = self.finished.insert(*data_id);
         let _ = self.timeouts.push(timeout, *data_id);
     }
 
+    fn insert_to_finished_externally(&mut self, data_id: &T, retain_for: Duration) {
+        self.phase_transitions.finished_externally += 1;
+        let timeout = Instant::now() + retain_for;
+        let _ = self.finished_externally.insert(*data_id);
+        let _ = self.finished_externally_timeouts.push(timeout, *data_id);
+    }
+
+    /// Returns whether gossiping the data has finished, whether by gossiping or externally.
+    fn is_finished(&self, data_id: &T) -> bool {
+        self.finished.contains(data_id) || self.finished_externally.contains(data_id)
+    }
+
     /// Retains only those finished entries which still haven't timed out.
     fn purge_finished(&mut self) {
         let now = Instant::now();
 
+        for expired_finished in self.finished_externally_timeouts.purge(&now) {
+            if self.finished_externally.remove(&expired_finished) {
+                trace!(
+                    item=%expired_finished,
+                    from=%Phase::FinishedExternally,
+                    "purged gossip entry"
+                );
+                self.phase_transitions.purged += 1;
+            }
+        }
+
         for expired_finished in self.timeouts.purge(&now) {
-            let _ = self.finished.remove(&expired_finished);
+            // Entries which have been regossiped or finished externally since finishing are no
+            // longer in `finished`.
+            if self.finished.remove(&expired_finished) {
+                trace!(item=%expired_finished, from=%Phase::Finished, "purged gossip entry");
+                self.phase_transitions.purged += 1;
+            }
         }
     }
 }
@@ -828,6 +983,177 @@ mod tests {
         assert_eq!(expected, action);
     }
 
+    /// Drives gossiping of new complete data until it finishes, answering each in-flight gossip
+    /// request in turn with a synthetic response from a previously-unseen peer.
+    ///
+    /// The `n`th response indicates the peer already held the data if `already_held(n)` is true.
+    /// Returns the number of gossip rounds, i.e. `ShouldGossip` actions, it took to finish.
+    fn gossip_until_finished<F: Fn(usize) -> bool>(
+        gossip_table: &mut GossipTable<u64>,
+        data_id: &u64,
+        rng: &mut TestRng,
+        already_held: F,
+    ) -> usize {
+        let mut action = gossip_table.new_complete_data(data_id, None);
+        let mut in_flight = 0;
+        let mut rounds = 0;
+        for response_index in 0.. {
+            match action {
+                GossipAction::ShouldGossip(ShouldGossip { count, .. }) => {
+                    rounds += 1;
+                    in_flight += count;
+                }
+                GossipAction::Noop => (),
+                GossipAction::AnnounceFinished => break,
+                action => panic!("unexpected action: {}", action),
+            }
+            assert!(gossip_table.current.contains_key(data_id));
+            assert!(
+                in_flight > 0,
+                "gossiping stalled with no requests in flight"
+            );
+            in_flight -= 1;
+
+            let peer = NodeId::random(rng);
+            action = if already_held(response_index) {
+                gossip_table.already_infected(data_id, peer)
+            } else {
+                gossip_table.we_infected(data_id, peer)
+            };
+        }
+        assert!(!gossip_table.current.contains_key(data_id));
+        assert!(gossip_table.finished.contains(data_id));
+        rounds
+    }
+
+    fn config(infection_target: u8, saturation_limit_percent: u8) -> Config {
+        Config::new(
+            infection_target,
+            saturation_limit_percent,
+            50,
+            DEFAULT_FINISHED_ENTRY_DURATION_SECS,
+            10,
+            60,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn should_saturate_after_rounds_determined_by_config() {
+        let _ = logging::init();
+        let mut rng = crate::new_rng();
+
+        // With every contacted peer already holding the data, each response triggers a further
+        // round of one until the holders limit is reached.
+        for (infection_target, saturation_limit_percent, expected_rounds) in
+            &[(3, 80, 15), (3, 50, 6), (2, 0, 2), (5, 90, 50)]
+        {
+            let mut gossip_table =
+                GossipTable::new(config(*infection_target, *saturation_limit_percent));
+            let data_id: u64 = rng.gen();
+
+            let rounds = gossip_until_finished(&mut gossip_table, &data_id, &mut rng, |_| true);
+            assert_eq!(*expected_rounds, rounds);
+
+            let expected = PhaseTransitions {
+                saturated: 1,
+                finished: 1,
+                finished_externally: 0,
+                purged: 0,
+            };
+            assert_eq!(expected, gossip_table.take_phase_transitions());
+            assert_eq!(
+                PhaseTransitions::default(),
+                gossip_table.take_phase_transitions()
+            );
+        }
+    }
+
+    #[test]
+    fn should_finish_via_infection_target_or_saturation_depending_on_config() {
+        let _ = logging::init();
+        let mut rng = crate::new_rng();
+
+        // Every fourth peer is newly infected by us.
+        let already_held = |response_index: usize| response_index % 4 != 3;
+
+        // With the default config, the infection target is reached after 12 responses, before
+        // the 15 holders required for saturation.
+        let mut gossip_table = GossipTable::new(config(3, 80));
+        let data_id: u64 = rng.gen();
+        let rounds = gossip_until_finished(&mut gossip_table, &data_id, &mut rng, already_held);
+        assert_eq!(10, rounds);
+        let expected = PhaseTransitions {
+            saturated: 0,
+            finished: 1,
+            finished_externally: 0,
+            purged: 0,
+        };
+        assert_eq!(expected, gossip_table.take_phase_transitions());
+
+        // With a lower saturation limit, only 6 holders are required and the data saturates after
+        // a single infection.
+        let mut gossip_table = GossipTable::new(config(3, 50));
+        let data_id: u64 = rng.gen();
+        let rounds = gossip_until_finished(&mut gossip_table, &data_id, &mut rng, already_held);
+        assert_eq!(5, rounds);
+        let expected = PhaseTransitions {
+            saturated: 1,
+            finished: 1,
+            finished_externally: 0,
+            purged: 0,
+        };
+        assert_eq!(expected, gossip_table.take_phase_transitions());
+    }
+
+    #[test]
+    fn should_purge_saturated_data_after_finished_entry_duration() {
+        const FINISHED_ENTRY_DURATION_SECS: u64 = 5;
+
+        let _ = logging::init();
+        let mut rng = crate::new_rng();
+        let data_id: u64 = rng.gen();
+
+        let config = Config::new(3, 80, 50, FINISHED_ENTRY_DURATION_SECS, 10, 60).unwrap();
+        let mut gossip_table = GossipTable::new(config);
+        let _ = gossip_until_finished(&mut gossip_table, &data_id, &mut rng, |_| true);
+        let _ = gossip_table.take_phase_transitions();
+
+        // Up to and including the deadline, the entry is retained and receiving the data again is
+        // a no-op.
+        Instant::advance_time(FINISHED_ENTRY_DURATION_SECS * 1_000);
+        assert_eq!(
+            GossipAction::Noop,
+            gossip_table.new_complete_data(&data_id, None)
+        );
+        assert!(gossip_table.finished.contains(&data_id));
+        assert_eq!(
+            PhaseTransitions::default(),
+            gossip_table.take_phase_transitions()
+        );
+
+        // Once the deadline has passed, the entry is purged and the data is gossiped afresh.
+        Instant::advance_time(1);
+        gossip_table.purge_finished();
+        assert!(!gossip_table.finished.contains(&data_id));
+        assert!(gossip_table.timeouts.values.is_empty());
+        let expected = PhaseTransitions {
+            saturated: 0,
+            finished: 0,
+            finished_externally: 0,
+            purged: 1,
+        };
+        assert_eq!(expected, gossip_table.take_phase_transitions());
+
+        let action = gossip_table.new_complete_data(&data_id, None);
+        let expected = GossipAction::ShouldGossip(ShouldGossip {
+            count: 3,
+            exclude_peers: HashSet::new(),
+            is_already_held: false,
+        });
+        assert_eq!(expected, action);
+    }
+
     #[test]
     fn check_timeout_should_detect_holder() {
         let _ = logging::init();
@@ -939,6 +1265,34 @@ mod tests {
         assert!(!gossip_table.force_finish(&data_id));
     }
 
+    #[test]
+    fn should_regossip_finished_data() {
+        let _ = logging::init();
+        let mut rng = crate::new_rng();
+        let data_id: u64 = rng.gen();
+
+        let mut gossip_table = GossipTable::new(Config::default());
+
+        // Add new complete data, then forcibly finish gossiping.  Receiving it again is a no-op.
+        let _ = gossip_table.new_complete_data(&data_id, None);
+        assert!(gossip_table.force_finish(&data_id));
+        assert_eq!(
+            GossipAction::Noop,
+            gossip_table.new_complete_data(&data_id, None)
+        );
+
+        // Regossiping it should cause `ShouldGossip` to be returned as per a completely new entry.
+        let action = gossip_table.regossip(&data_id);
+        let expected = GossipAction::ShouldGossip(ShouldGossip {
+            count: EXPECTED_DEFAULT_INFECTION_TARGET,
+            exclude_peers: HashSet::new(),
+            is_already_held: false,
+        });
+        assert_eq!(expected, action);
+        assert!(!gossip_table.finished.contains(&data_id));
+        assert!(gossip_table.current.contains_key(&data_id));
+    }
+
     #[test]
     fn should_purge() {
         let _ = logging::init();
@@ -971,6 +1325,91 @@ mod tests {
         assert!(!gossip_table.finished.contains(&data_id));
     }
 
+    #[test]
+    fn should_finish_externally() {
+        const RETAIN_FOR_MILLIS: u64 = DEFAULT_FINISHED_ENTRY_DURATION_SECS * 10_000;
+        let retain_for = Duration::from_millis(RETAIN_FOR_MILLIS);
+
+        let _ = logging::init();
+        let mut rng = crate::new_rng();
+        let node_ids = random_node_ids(&mut rng);
+        let data_id: u64 = rng.gen();
+        let unknown_data_id: u64 = rng.gen();
+
+        let mut gossip_table = GossipTable::new(Config::default());
+
+        // Add new complete data, infect one peer and fail to gossip to another, then finish it
+        // externally.  The gossip request yet to be sent should be reported as saved, but not the
+        // one still in flight.
+        let action = gossip_table.new_complete_data(&data_id, None);
+        assert!(matches!(action, GossipAction::ShouldGossip(_)));
+        let _ = gossip_table.we_infected(&data_id, node_ids[0]);
+        assert!(!gossip_table.reduce_in_flight_count(&data_id, 1));
+        assert_eq!(gossip_table.finish_externally(&data_id, retain_for), 1);
+        assert!(!gossip_table.current.contains_key(&data_id));
+        assert!(gossip_table.finished_externally.contains(&data_id));
+
+        // Finishing it again should be a no-op, as should finishing unknown data, although the
+        // latter should still be retained.
+        assert_eq!(gossip_table.finish_externally(&data_id, retain_for), 0);
+        assert_eq!(
+            gossip_table.finish_externally(&unknown_data_id, retain_for),
+            0
+        );
+        assert!(gossip_table.finished_externally.contains(&unknown_data_id));
+
+        let transitions = gossip_table.take_phase_transitions();
+        assert_eq!(transitions.finished_externally, 2);
+        assert_eq!(transitions.saturated, 0);
+        assert_eq!(transitions.finished, 0);
+
+        // Late gossip about either item should be ignored, even once the normal finished entry
+        // duration has elapsed.
+        Instant::advance_time(DEFAULT_FINISHED_ENTRY_DURATION_SECS * 1_000 + 1);
+        assert_eq!(
+            gossip_table.new_partial_data(&data_id, node_ids[1]),
+            GossipAction::Noop
+        );
+        assert_eq!(
+            gossip_table.new_complete_data(&unknown_data_id, Some(node_ids[1])),
+            GossipAction::Noop
+        );
+        assert_eq!(gossip_table.regossip(&data_id), GossipAction::Noop);
+        assert_eq!(gossip_table.items_current(), 0);
+        assert_eq!(gossip_table.items_finished(), 2);
+
+        // Once the retention period has elapsed, the entries should be purged.
+        Instant::advance_time(RETAIN_FOR_MILLIS);
+        gossip_table.purge_finished();
+        assert!(!gossip_table.finished_externally.contains(&data_id));
+        assert!(!gossip_table.finished_externally.contains(&unknown_data_id));
+        assert_eq!(gossip_table.take_phase_transitions().purged, 2);
+    }
+
+    #[test]
+    fn should_finish_externally_after_finishing() {
+        let retain_for = Duration::from_secs(DEFAULT_FINISHED_ENTRY_DURATION_SECS * 10);
+
+        let _ = logging::init();
+        let mut rng = crate::new_rng();
+        let data_id: u64 = rng.gen();
+
+        let mut gossip_table = GossipTable::new(Config::default());
+
+        // Add new complete data and forcibly finish, then finish it externally.  It should be
+        // retained beyond the normal finished entry duration.
+        let _ = gossip_table.new_complete_data(&data_id, None);
+        assert!(gossip_table.force_finish(&data_id));
+        assert_eq!(gossip_table.finish_externally(&data_id, retain_for), 0);
+        assert!(!gossip_table.finished.contains(&data_id));
+        assert!(gossip_table.finished_externally.contains(&data_id));
+
+        Instant::advance_time(DEFAULT_FINISHED_ENTRY_DURATION_SECS * 1_000 + 1);
+        gossip_table.purge_finished();
+        assert!(gossip_table.finished_externally.contains(&data_id));
+        assert_eq!(gossip_table.take_phase_transitions().purged, 0);
+    }
+
     #[test]
     fn timeouts_purge_in_order() {
         let mut timeouts = Timeouts::new();
@@ -15,6 +15,16 @@ pub struct GossiperMetrics {
     pub(super) table_items_current: IntGauge,
     /// Number of items in the gossip table that are finished.
     pub(super) table_items_finished: IntGauge,
+    /// Total number of items which stopped being gossiped due to saturation.
+    pub(super) items_saturated: IntCounter,
+    /// Total number of items which finished being gossiped.
+    pub(super) items_finished: IntCounter,
+    /// Total number of finished items purged from the gossip table.
+    pub(super) items_purged: IntCounter,
+    /// Total number of items which stopped being gossiped as they were no longer needed.
+    pub(super) items_finished_externally: IntCounter,
+    /// Total number of gossip requests not sent as the items were no longer needed.
+    pub(super) gossip_rounds_saved: IntCounter,
     /// Reference to the registry for unregistering.
     registry: Registry,
 }
@@ -51,12 +61,52 @@ impl GossiperMetrics {
                 name
             ),
         )?;
+        let items_saturated = IntCounter::new(
+            format!("{}_items_saturated", name),
+            format!(
+                "number of items the {} stopped gossiping due to saturation",
+                name
+            ),
+        )?;
+        let items_finished = IntCounter::new(
+            format!("{}_items_finished", name),
+            format!("number of items the {} finished gossiping", name),
+        )?;
+        let items_purged = IntCounter::new(
+            format!("{}_items_purged", name),
+            format!(
+                "number of finished items purged from the gossip table of {}",
+                name
+            ),
+        )?;
+
+        let items_finished_externally = IntCounter::new(
+            format!("{}_items_finished_externally", name),
+            format!(
+                "number of items the {} stopped gossiping as they were no longer needed, e.g. \
+                 deploys included in a finalized block",
+                name
+            ),
+        )?;
+        let gossip_rounds_saved = IntCounter::new(
+            format!("{}_gossip_rounds_saved", name),
+            format!(
+                "number of gossip requests the {} didn't need to send as the items were no \
+                 longer needed",
+                name
+            ),
+        )?;
 
         registry.register(Box::new(items_received.clone()))?;
         registry.register(Box::new(times_gossiped.clone()))?;
         registry.register(Box::new(times_ran_out_of_peers.clone()))?;
         registry.register(Box::new(table_items_current.clone()))?;
         registry.register(Box::new(table_items_finished.clone()))?;
+        registry.register(Box::new(items_saturated.clone()))?;
+        registry.register(Box::new(items_finished.clone()))?;
+        registry.register(Box::new(items_purged.clone()))?;
+        registry.register(Box::new(items_finished_externally.clone()))?;
+        registry.register(Box::new(gossip_rounds_saved.clone()))?;
 
         Ok(GossiperMetrics {
             items_received,
@@ -64,6 +114,11 @@ impl GossiperMetrics {
             times_ran_out_of_peers,
             table_items_current,
             table_items_finished,
+            items_saturated,
+            items_finished,
+            items_purged,
+            items_finished_externally,
+            gossip_rounds_saved,
             registry: registry.clone(),
         })
     }
@@ -76,5 +131,10 @@ impl Drop for GossiperMetrics {
         unregister_metric!(self.registry, self.times_ran_out_of_peers);
         unregister_metric!(self.registry, self.table_items_current);
         unregister_metric!(self.registry, self.table_items_finished);
+        unregister_metric!(self.registry, self.items_saturated);
+        unregister_metric!(self.registry, self.items_finished);
+        unregister_metric!(self.registry, self.items_purged);
+        unregister_metric!(self.registry, self.items_finished_externally);
+        unregister_metric!(self.registry, self.gossip_rounds_saved);
     }
 }
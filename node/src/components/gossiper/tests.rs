@@ -2,7 +2,8 @@
 use std::{
     collections::{BTreeSet, HashMap},
     fmt::{self, Debug, Display, Formatter},
-    iter,
+    io, iter,
+    sync::{Arc, Mutex},
 };
 
 use derive_more::From;
@@ -13,9 +14,9 @@ use serde::Serialize;
 use tempfile::TempDir;
 use thiserror::Error;
 use tokio::time;
-use tracing::debug;
+use tracing::{debug, Level};
 
-use casper_types::ProtocolVersion;
+use casper_types::{ProtocolVersion, SecretKey};
 
 use super::*;
 use crate::{
@@ -25,11 +26,12 @@ use crate::{
         in_memory_network::{self, InMemoryNetwork, NetworkController},
         storage::{self, Storage},
     },
-    crypto::hash::Digest,
+    crypto::{hash::Digest, AsymmetricKeyExt},
     effect::{
         announcements::{
-            ContractRuntimeAnnouncement, ControlAnnouncement, DeployAcceptorAnnouncement,
-            GossiperAnnouncement, NetworkAnnouncement, RpcServerAnnouncement,
+            BlocklistAnnouncement, ConsensusAnnouncement, ContractRuntimeAnnouncement,
+            ControlAnnouncement, DeployAcceptorAnnouncement, GossiperAnnouncement,
+            NetworkAnnouncement, RpcServerAnnouncement,
         },
         requests::{ConsensusRequest, ContractRuntimeRequest, LinearChainRequest},
         Responder,
@@ -38,9 +40,10 @@ use crate::{
     reactor::{self, EventQueueHandle, Runner},
     testing::{
         network::{Network, NetworkedReactor},
+        simulation::Simulation,
         ConditionCheckReactor, TestRng,
     },
-    types::{Chainspec, Deploy, NodeId, Tag},
+    types::{Chainspec, Deploy, NodeId, Tag, TimeDiff},
     utils::{Loadable, WithDir},
     NodeRng,
 };
@@ -71,6 +74,8 @@ enum Event {
     DeployGossiperAnnouncement(#[serde(skip_serializing)] GossiperAnnouncement<Deploy>),
     #[from]
     ContractRuntime(#[serde(skip_serializing)] contract_runtime::Event),
+    #[from]
+    ConsensusAnnouncement(#[serde(skip_serializing)] ConsensusAnnouncement),
 }
 
 impl ReactorEvent for Event {
@@ -119,6 +124,12 @@ impl From<ContractRuntimeAnnouncement> for Event {
     }
 }
 
+impl From<BlocklistAnnouncement<NodeId>> for Event {
+    fn from(_announcement: BlocklistAnnouncement<NodeId>) -> Self {
+        unimplemented!("not implemented for gossiper tests")
+    }
+}
+
 impl Display for Event {
     fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
         match self {
@@ -141,6 +152,9 @@ impl Display for Event {
             Event::ContractRuntime(event) => {
                 write!(formatter, "contract-runtime event: {:?}", event)
             }
+            Event::ConsensusAnnouncement(ann) => {
+                write!(formatter, "consensus announcement: {}", ann)
+            }
         }
     }
 }
@@ -158,6 +172,10 @@ struct Reactor {
     deploy_acceptor: DeployAcceptor,
     deploy_gossiper: Gossiper<Deploy, Event>,
     contract_runtime: ContractRuntime,
+    /// The max TTL of deploys, for which finalized deploys are retained.
+    max_ttl: TimeDiff,
+    /// The IDs of the deploys this node has sent gossip requests for, in order.
+    gossiped_deploys: Vec<DeployHash>,
     _storage_tempdir: TempDir,
 }
 
@@ -187,6 +205,7 @@ impl reactor::Reactor for Reactor {
             None,
             ProtocolVersion::from_parts(1, 0, 0),
             false,
+            registry,
         )
         .unwrap();
 
@@ -201,10 +220,15 @@ impl reactor::Reactor for Reactor {
         )
         .unwrap();
 
+        let chainspec = Chainspec::from_resources("local");
         let deploy_acceptor = DeployAcceptor::new(
-            deploy_acceptor::Config::new(false),
-            &Chainspec::from_resources("local"),
-        );
+            WithDir::new(storage_tempdir.path(), &deploy_acceptor::Config::new(false)),
+            &chainspec,
+            None,
+            None,
+            registry,
+        )
+        .unwrap();
         let deploy_gossiper = Gossiper::new_for_partial_items(
             "deploy_gossiper",
             config,
@@ -218,6 +242,8 @@ impl reactor::Reactor for Reactor {
             deploy_acceptor,
             deploy_gossiper,
             contract_runtime,
+            max_ttl: chainspec.deploy_config.max_ttl,
+            gossiped_deploys: vec![],
             _storage_tempdir: storage_tempdir,
         };
 
@@ -247,11 +273,18 @@ impl reactor::Reactor for Reactor {
                 self.deploy_gossiper
                     .handle_event(effect_builder, rng, event),
             ),
-            Event::NetworkRequest(request) => reactor::wrap_effects(
-                Event::Network,
-                self.network
-                    .handle_event(effect_builder, rng, request.into()),
-            ),
+            Event::NetworkRequest(request) => {
+                if let NetworkRequest::Gossip { payload, .. } = &request {
+                    if let NodeMessage::DeployGossiper(Message::Gossip(deploy_hash)) = **payload {
+                        self.gossiped_deploys.push(deploy_hash);
+                    }
+                }
+                reactor::wrap_effects(
+                    Event::Network,
+                    self.network
+                        .handle_event(effect_builder, rng, request.into()),
+                )
+            }
             Event::ControlAnnouncement(ctrl_ann) => {
                 unreachable!("unhandled control announcement: {}", ctrl_ann)
             }
@@ -298,20 +331,10 @@ impl reactor::Reactor for Reactor {
                     NodeMessage::GetResponse {
                         tag: Tag::Deploy,
                         serialized_item,
-                    } => {
-                        let deploy = match bincode::deserialize(&serialized_item) {
-                            Ok(deploy) => Box::new(deploy),
-                            Err(error) => {
-                                error!("failed to decode deploy from {}: {}", sender, error);
-                                return Effects::new();
-                            }
-                        };
-                        Event::DeployAcceptor(deploy_acceptor::Event::Accept {
-                            deploy,
-                            source: Source::Peer(sender),
-                            responder: None,
-                        })
-                    }
+                    } => Event::DeployAcceptor(deploy_acceptor::Event::ReceivedFromPeer {
+                        serialized_deploy: serialized_item,
+                        sender,
+                    }),
                     NodeMessage::DeployGossiper(message) => {
                         Event::DeployGossiper(super::Event::MessageReceived { sender, message })
                     }
@@ -326,6 +349,19 @@ impl reactor::Reactor for Reactor {
                 // We do not care about new peers in the gossiper test.
                 Effects::new()
             }
+            Event::NetworkAnnouncement(NetworkAnnouncement::PeerCountChanged(_)) => {
+                // We do not care about the peer count in the gossiper test.
+                Effects::new()
+            }
+            Event::NetworkAnnouncement(NetworkAnnouncement::PeerConnected { .. })
+            | Event::NetworkAnnouncement(NetworkAnnouncement::PeerDisconnected { .. }) => {
+                // We do not care about connection lifecycle in the gossiper test.
+                Effects::new()
+            }
+            Event::NetworkAnnouncement(NetworkAnnouncement::MessageSendFailed { .. }) => {
+                // We do not care about failed sends in the gossiper test.
+                Effects::new()
+            }
             Event::RpcServerAnnouncement(RpcServerAnnouncement::DeployReceived {
                 deploy,
                 responder,
@@ -348,8 +384,7 @@ impl reactor::Reactor for Reactor {
                 self.dispatch_event(effect_builder, rng, Event::DeployGossiper(event))
             }
             Event::DeployAcceptorAnnouncement(DeployAcceptorAnnouncement::InvalidDeploy {
-                deploy: _,
-                source: _,
+                ..
             }) => Effects::new(),
             Event::DeployGossiperAnnouncement(_ann) => {
                 // We do not care about deploy gossiper announcements in the gossiper test.
@@ -364,6 +399,26 @@ impl reactor::Reactor for Reactor {
                 self.contract_runtime
                     .handle_event(effect_builder, rng, event),
             ),
+            Event::ConsensusAnnouncement(ConsensusAnnouncement::DeploysFinalized(
+                deploy_hashes,
+            )) => {
+                let event = super::Event::ItemsFinishedExternally {
+                    item_ids: deploy_hashes.clone(),
+                    retain_for: self.max_ttl.into(),
+                };
+                let mut effects =
+                    self.dispatch_event(effect_builder, rng, Event::DeployGossiper(event));
+                let event = deploy_acceptor::Event::DeploysFinalized(deploy_hashes);
+                effects.extend(self.dispatch_event(
+                    effect_builder,
+                    rng,
+                    Event::DeployAcceptor(event),
+                ));
+                effects
+            }
+            Event::ConsensusAnnouncement(ann) => {
+                unreachable!("unhandled consensus announcement: {}", ann)
+            }
         }
     }
 
@@ -526,18 +581,18 @@ async fn should_get_from_alternate_source() {
 
 #[tokio::test]
 async fn should_timeout_gossip_response() {
-    const PAUSE_DURATION: Duration = Duration::from_millis(50);
     const TIMEOUT: Duration = Duration::from_secs(2);
 
     NetworkController::<NodeMessage>::create_active();
-    let mut network = Network::<Reactor>::new();
+    let mut simulation = Simulation::<Reactor>::new();
     let mut rng = crate::new_rng();
 
     // The target number of peers to infect with a given piece of data.
     let infection_target = Config::default().infection_target();
 
     // Add `infection_target + 1` nodes.
-    let mut node_ids = network
+    let mut node_ids = simulation
+        .network_mut()
         .add_nodes(&mut rng, infection_target as usize + 1)
         .await;
 
@@ -546,7 +601,8 @@ async fn should_timeout_gossip_response() {
     let deploy_id = *deploy.id();
 
     // Give the deploy to node 0 to be gossiped.
-    network
+    simulation
+        .network_mut()
         .process_injected_effect_on(&node_ids[0], announce_deploy_received(deploy.clone(), None))
         .await;
 
@@ -557,28 +613,28 @@ async fn should_timeout_gossip_response() {
             Event::DeployGossiper(super::Event::GossipedTo { .. })
         )
     };
-    network
-        .crank_until(&node_ids[0], &mut rng, made_gossip_request, TIMEOUT)
+    simulation
+        .crank_until(&node_ids[0], &mut rng, made_gossip_request)
         .await;
-    // Give node 0 time to set the timeouts before advancing the clock.
-    time::sleep(PAUSE_DURATION).await;
 
     // Replace all nodes except node 0 with new nodes.
     for node_id in node_ids.drain(1..) {
-        assert!(network.remove_node(&node_id).is_some());
+        assert!(simulation.network_mut().remove_node(&node_id).is_some());
         debug!("removed node {}", node_id);
     }
     for _ in 0..infection_target {
-        let (node_id, _runner) = network.add_node(&mut rng).await.unwrap();
+        let (node_id, _runner) = simulation.network_mut().add_node(&mut rng).await.unwrap();
         node_ids.push(node_id);
     }
 
-    // Advance time to trigger node 0's timeout causing it to gossip to the new nodes.
-    let secs_to_advance = Config::default().gossip_request_timeout_secs();
-    time::pause();
-    time::advance(Duration::from_secs(secs_to_advance)).await;
-    time::resume();
-    debug!("advanced time by {} secs", secs_to_advance);
+    // Advance virtual time to trigger node 0's timeout causing it to gossip to the new nodes.  The
+    // timeouts are only set once node 0's pending effects run, which happens before any timer
+    // fires.
+    let gossip_request_timeout =
+        Duration::from_secs(Config::default().gossip_request_timeout_secs());
+    simulation
+        .advance_time(&mut rng, gossip_request_timeout)
+        .await;
 
     // Check every node has every deploy stored locally.
     let deploy_held = |nodes: &HashMap<NodeId, Runner<ConditionCheckReactor<Reactor>>>| {
@@ -592,7 +648,266 @@ async fn should_timeout_gossip_response() {
                 .unwrap_or_default()
         })
     };
+    simulation.settle_on(&mut rng, deploy_held, TIMEOUT).await;
+
+    NetworkController::<NodeMessage>::remove_active();
+}
+
+#[tokio::test]
+async fn should_gossip_deploy_once_regardless_of_approval_order() {
+    const NETWORK_SIZE: usize = 2;
+    const QUIET_FOR: Duration = Duration::from_millis(50);
+    const TIMEOUT: Duration = Duration::from_secs(2);
+
+    NetworkController::<NodeMessage>::create_active();
+    let mut network = Network::<Reactor>::new();
+    let mut rng = crate::new_rng();
+
+    let node_ids = network.add_nodes(&mut rng, NETWORK_SIZE).await;
+
+    // Create two copies of a random deploy, each signed by the same two extra keys in a different
+    // order.
+    let deploy = Deploy::random(&mut rng);
+    let deploy_id = *deploy.id();
+    let secret_key_1 = SecretKey::random(&mut rng);
+    let secret_key_2 = SecretKey::random(&mut rng);
+    let mut deploy_1 = deploy.clone();
+    deploy_1.sign(&secret_key_1);
+    deploy_1.sign(&secret_key_2);
+    let mut deploy_2 = deploy;
+    deploy_2.sign(&secret_key_2);
+    deploy_2.sign(&secret_key_1);
+    assert_eq!(
+        bincode::serialize(&deploy_1).unwrap(),
+        bincode::serialize(&deploy_2).unwrap()
+    );
+
+    // Give both copies to node 0 to be gossiped.
+    for deploy in vec![deploy_1.clone(), deploy_2] {
+        network
+            .process_injected_effect_on(
+                &node_ids[0],
+                announce_deploy_received(Box::new(deploy), None),
+            )
+            .await;
+    }
+
+    // Check node 1 has the deploy stored locally.
+    let deploy_held = |nodes: &HashMap<NodeId, Runner<ConditionCheckReactor<Reactor>>>| {
+        let runner = nodes.get(&node_ids[1]).unwrap();
+        runner
+            .reactor()
+            .inner()
+            .storage
+            .get_deploy_by_hash(deploy_id)
+            .map(|retrieved_deploy| retrieved_deploy == deploy_1)
+            .unwrap_or_default()
+    };
+    network.settle_on(&mut rng, deploy_held, TIMEOUT).await;
+    network.settle(&mut rng, QUIET_FOR, TIMEOUT).await;
+
+    // Node 0 should only have started gossiping the deploy once, to its single peer.
+    let metrics = &network
+        .nodes()
+        .get(&node_ids[0])
+        .unwrap()
+        .reactor()
+        .inner()
+        .deploy_gossiper
+        .metrics;
+    assert_eq!(metrics.items_received.get(), 1);
+    assert_eq!(metrics.times_gossiped.get(), 1);
+
+    NetworkController::<NodeMessage>::remove_active();
+}
+
+#[tokio::test]
+async fn should_stop_gossiping_deploy_once_finalized() {
+    const NETWORK_SIZE: usize = 5;
+    const QUIET_FOR: Duration = Duration::from_millis(50);
+    const TIMEOUT: Duration = Duration::from_secs(2);
+
+    NetworkController::<NodeMessage>::create_active();
+    let mut network = Network::<Reactor>::new();
+    let mut rng = crate::new_rng();
+
+    let node_ids = network.add_nodes(&mut rng, NETWORK_SIZE).await;
+
+    // Give two random deploys to node 0 to be gossiped.
+    let finalized_deploy = Box::new(Deploy::random(&mut rng));
+    let finalized_deploy_id = *finalized_deploy.id();
+    let other_deploy = Box::new(Deploy::random(&mut rng));
+    let other_deploy_id = *other_deploy.id();
+    for deploy in vec![finalized_deploy, other_deploy] {
+        network
+            .process_injected_effect_on(&node_ids[0], announce_deploy_received(deploy, None))
+            .await;
+    }
+
+    // Run node 0 until it has started gossiping the deploy to be finalized.
+    let gossiped_finalized_deploy = move |event: &Event| -> bool {
+        matches!(
+            event,
+            Event::DeployGossiper(super::Event::GossipedTo { item_id, .. })
+                if *item_id == finalized_deploy_id
+        )
+    };
+    network
+        .crank_until(&node_ids[0], &mut rng, gossiped_finalized_deploy, TIMEOUT)
+        .await;
+
+    // Finalize the deploy on every node mid-gossip, forgetting the gossip requests each sent
+    // before handling the finalization.
+    let handled_finalization =
+        |event: &Event| -> bool { matches!(event, Event::ConsensusAnnouncement(_)) };
+    for node_id in &node_ids {
+        network
+            .process_injected_effect_on(node_id, |effect_builder| {
+                effect_builder
+                    .announce_deploys_finalized(vec![finalized_deploy_id])
+                    .ignore()
+            })
+            .await;
+        network
+            .crank_until(node_id, &mut rng, handled_finalization, TIMEOUT)
+            .await;
+        let runner = network.nodes_mut().get_mut(node_id).unwrap();
+        runner.reactor_mut().inner_mut().gossiped_deploys.clear();
+    }
+
+    // Check the other deploy continues to be gossiped to every node.
+    let other_deploy_held = |nodes: &HashMap<NodeId, Runner<ConditionCheckReactor<Reactor>>>| {
+        nodes.values().all(|runner| {
+            runner
+                .reactor()
+                .inner()
+                .storage
+                .get_deploy_by_hash(other_deploy_id)
+                .is_some()
+        })
+    };
+    network
+        .settle_on(&mut rng, other_deploy_held, TIMEOUT)
+        .await;
+    network.settle(&mut rng, QUIET_FOR, TIMEOUT).await;
+
+    // No node should have sent any further gossip requests for the finalized deploy.
+    for reactor in network.reactors() {
+        assert!(
+            !reactor.gossiped_deploys.contains(&finalized_deploy_id),
+            "{} gossiped finalized deploy",
+            reactor.node_id()
+        );
+    }
+    let metrics = &network
+        .nodes()
+        .get(&node_ids[0])
+        .unwrap()
+        .reactor()
+        .inner()
+        .deploy_gossiper
+        .metrics;
+    assert_eq!(metrics.items_finished_externally.get(), 1);
+
+    NetworkController::<NodeMessage>::remove_active();
+}
+
+/// A writer for capturing formatted log output in memory.
+#[derive(Clone, Default)]
+struct CapturedLogs(Arc<Mutex<Vec<u8>>>);
+
+impl io::Write for CapturedLogs {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn should_trace_deploy_lifecycle_within_deploy_spans() {
+    const NETWORK_SIZE: usize = 2;
+    const QUIET_FOR: Duration = Duration::from_millis(50);
+    const TIMEOUT: Duration = Duration::from_secs(2);
+
+    // The test runtime is single-threaded, so all reactor tasks log to this thread's subscriber.
+    let captured_logs = CapturedLogs::default();
+    let writer = captured_logs.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(Level::DEBUG)
+        .with_ansi(false)
+        .with_writer(move || writer.clone())
+        .finish();
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    NetworkController::<NodeMessage>::create_active();
+    let mut network = Network::<Reactor>::new();
+    let mut rng = crate::new_rng();
+
+    let node_ids = network.add_nodes(&mut rng, NETWORK_SIZE).await;
+
+    // Give a random deploy to node 0 to be gossiped.
+    let deploy = Box::new(Deploy::random(&mut rng));
+    let deploy_id = *deploy.id();
+    network
+        .process_injected_effect_on(&node_ids[0], announce_deploy_received(deploy, None))
+        .await;
+
+    // Check node 1 has the deploy stored locally.
+    let deploy_held = |nodes: &HashMap<NodeId, Runner<ConditionCheckReactor<Reactor>>>| {
+        let runner = nodes.get(&node_ids[1]).unwrap();
+        runner
+            .reactor()
+            .inner()
+            .storage
+            .get_deploy_by_hash(deploy_id)
+            .is_some()
+    };
     network.settle_on(&mut rng, deploy_held, TIMEOUT).await;
+    network.settle(&mut rng, QUIET_FOR, TIMEOUT).await;
 
     NetworkController::<NodeMessage>::remove_active();
+
+    let logs = String::from_utf8(captured_logs.0.lock().unwrap().clone()).unwrap();
+    let span_tag = format!("deploy{{hash={}}}", deploy_id.inner());
+    let traced_lines: Vec<&str> = logs
+        .lines()
+        .filter(|line| line.contains(&span_tag))
+        .collect();
+
+    // Every stage on node 0, from acceptance to gossiping, as well as the acceptance on node 1,
+    // should be traced within a span tagged with the deploy's hash.
+    let stages = vec![
+        "accepting deploy source=client".to_string(),
+        "event=storage: put ".to_string(),
+        "event=deploy acceptor: put new ".to_string(),
+        "event=deploy-acceptor announcement: accepted new deploy ".to_string(),
+        "gossiping new item should begin".to_string(),
+        "event=network request: gossip: ".to_string(),
+        format!("accepting deploy source={}", node_ids[0]),
+    ];
+    for stage in &stages {
+        assert!(
+            traced_lines
+                .iter()
+                .any(|line| line.contains(stage.as_str())),
+            "no log line containing {:?} within {}",
+            stage,
+            span_tag
+        );
+    }
+}
+
+#[test]
+fn should_round_up_min_validator_count() {
+    assert_eq!(min_validator_count(3, 0), 0);
+    assert_eq!(min_validator_count(3, 1), 1);
+    assert_eq!(min_validator_count(3, 33), 1);
+    assert_eq!(min_validator_count(3, 34), 2);
+    assert_eq!(min_validator_count(3, 50), 2);
+    assert_eq!(min_validator_count(3, 100), 3);
+    assert_eq!(min_validator_count(0, 50), 0);
 }
@@ -120,6 +120,7 @@
 //!                 if let Some(msg) = self.whispers.pop() {
 //!                     return effect_builder.gossip_message(msg,
 //!                                                          TEST_GOSSIP_COUNT,
+//!                                                          0,
 //!                                                          Default::default())
 //!                         .event(|_| ShouterEvent::ReadyToSend);
 //!                 }
@@ -494,6 +495,21 @@ where
     pub fn node_id(&self) -> NodeId {
         self.node_id
     }
+
+    /// Returns the IDs of all other nodes on the network.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock has been poisoned.
+    pub fn peers(&self) -> Vec<NodeId> {
+        self.nodes
+            .read()
+            .expect("network lock poisoned")
+            .keys()
+            .filter(|node_id| **node_id != self.node_id)
+            .copied()
+            .collect()
+    }
 }
 
 impl<P> InMemoryNetwork<P>
@@ -569,6 +585,8 @@ where
             NetworkRequest::Gossip {
                 payload,
                 count,
+                // The in-memory network has no notion of validators.
+                min_validators: _,
                 exclude,
                 responder,
             } => {
@@ -19,7 +19,7 @@ use self::{
 use super::Component;
 use crate::{
     effect::{
-        announcements::LinearChainAnnouncement,
+        announcements::{BlocklistAnnouncement, LinearChainAnnouncement},
         requests::{
             ChainspecLoaderRequest, ContractRuntimeRequest, LinearChainRequest, NetworkRequest,
             StorageRequest,
@@ -58,11 +58,25 @@ impl<I> LinearChainComponent<I> {
             _marker: PhantomData,
         })
     }
+
+    /// Updates the metrics tracking the size of the pending finality signatures collection.
+    fn update_pending_signature_metrics(&self) {
+        self.metrics
+            .pending_finality_signatures
+            .set(self.linear_chain_state.pending_signature_count() as i64);
+        self.metrics
+            .pending_finality_signature_blocks
+            .set(self.linear_chain_state.pending_block_count() as i64);
+    }
 }
 
+/// Converts `outcomes` into effects.  `maybe_sender` is the peer from which the finality signature
+/// being handled was received, if any.
 fn outcomes_to_effects<REv, I>(
     effect_builder: EffectBuilder<REv>,
+    metrics: &LinearChainMetrics,
     outcomes: Outcomes,
+    maybe_sender: Option<I>,
 ) -> Effects<Event<I>>
 where
     REv: From<StorageRequest>
@@ -70,8 +84,9 @@ where
         + From<LinearChainAnnouncement>
         + From<ContractRuntimeRequest>
         + From<ChainspecLoaderRequest>
+        + From<BlocklistAnnouncement<I>>
         + Send,
-    I: Display + Send + 'static,
+    I: Clone + Display + Send + 'static,
 {
     outcomes
         .into_iter()
@@ -93,30 +108,57 @@ where
                 effect_builder.announce_finality_signature(fs).ignore()
             }
             Outcome::AnnounceBlock(block) => effect_builder.announce_block_added(block).ignore(),
-            Outcome::LoadSignatures(fs) => effect_builder
-                .get_signatures_from_storage(fs.block_hash)
-                .event(move |maybe_signatures| {
-                    Event::GetStoredFinalitySignaturesResult(fs, maybe_signatures.map(Box::new))
-                }),
+            Outcome::LoadSignatures(fs) => {
+                let maybe_sender = maybe_sender.clone();
+                effect_builder
+                    .get_signatures_from_storage(fs.block_hash)
+                    .event(move |maybe_signatures| {
+                        Event::GetStoredFinalitySignaturesResult(
+                            fs,
+                            maybe_signatures.map(Box::new),
+                            maybe_sender,
+                        )
+                    })
+            }
             Outcome::VerifyIfBonded {
                 new_fs,
                 known_fs,
                 protocol_version,
                 latest_state_root_hash,
-            } => effect_builder
-                .is_bonded_validator(
-                    new_fs.public_key.clone(),
-                    new_fs.era_id,
-                    latest_state_root_hash,
-                    protocol_version,
-                )
-                .result(
-                    |is_bonded| Event::IsBonded(known_fs, new_fs, is_bonded),
-                    |error| {
-                        error!(%error, "checking in future eras returned an error.");
-                        panic!("couldn't check if validator is bonded")
-                    },
-                ),
+            } => {
+                let maybe_sender = maybe_sender.clone();
+                effect_builder
+                    .is_bonded_validator(
+                        new_fs.public_key.clone(),
+                        new_fs.era_id,
+                        latest_state_root_hash,
+                        protocol_version,
+                    )
+                    .result(
+                        |is_bonded| Event::IsBonded(known_fs, new_fs, is_bonded, maybe_sender),
+                        |error| {
+                            error!(%error, "checking in future eras returned an error.");
+                            panic!("couldn't check if validator is bonded")
+                        },
+                    )
+            }
+            Outcome::PendingBlockEvicted { dropped_signatures } => {
+                metrics.evicted_pending_signature_blocks.inc();
+                metrics
+                    .dropped_finality_signatures
+                    .inc_by(dropped_signatures as u64);
+                Effects::new()
+            }
+            Outcome::SignatureDropped => {
+                metrics.dropped_finality_signatures.inc();
+                Effects::new()
+            }
+            Outcome::DisconnectFromSender => match maybe_sender.clone() {
+                Some(sender) => effect_builder
+                    .announce_disconnect_from_peer(sender)
+                    .ignore(),
+                None => Effects::new(),
+            },
         })
         .concat()
 }
@@ -128,8 +170,9 @@ where
         + From<LinearChainAnnouncement>
         + From<ContractRuntimeRequest>
         + From<ChainspecLoaderRequest>
+        + From<BlocklistAnnouncement<I>>
         + Send,
-    I: Display + Send + 'static,
+    I: Clone + Display + Send + 'static,
 {
     type Event = Event<I>;
     type ConstructionError = Infallible;
@@ -186,7 +229,8 @@ where
                 let outcomes = self
                     .linear_chain_state
                     .handle_new_block(block, execution_results);
-                outcomes_to_effects(effect_builder, outcomes)
+                self.update_pending_signature_metrics();
+                outcomes_to_effects(effect_builder, &self.metrics, outcomes, None)
             }
             Event::PutBlockResult { block } => {
                 let completion_duration = block.header().timestamp().elapsed().millis();
@@ -194,27 +238,30 @@ where
                     .block_completion_duration
                     .set(completion_duration as i64);
                 let outcomes = self.linear_chain_state.handle_put_block(block);
-                outcomes_to_effects(effect_builder, outcomes)
+                outcomes_to_effects(effect_builder, &self.metrics, outcomes, None)
             }
-            Event::FinalitySignatureReceived(fs, gossiped) => {
+            Event::FinalitySignatureReceived(fs, maybe_sender) => {
                 let outcomes = self
                     .linear_chain_state
-                    .handle_finality_signature(fs, gossiped);
-                outcomes_to_effects(effect_builder, outcomes)
+                    .handle_finality_signature(fs, maybe_sender.is_some());
+                self.update_pending_signature_metrics();
+                outcomes_to_effects(effect_builder, &self.metrics, outcomes, maybe_sender)
             }
-            Event::GetStoredFinalitySignaturesResult(fs, maybe_signatures) => {
+            Event::GetStoredFinalitySignaturesResult(fs, maybe_signatures, maybe_sender) => {
                 let outcomes = self
                     .linear_chain_state
                     .handle_cached_signatures(maybe_signatures, fs);
-                outcomes_to_effects(effect_builder, outcomes)
+                self.update_pending_signature_metrics();
+                outcomes_to_effects(effect_builder, &self.metrics, outcomes, maybe_sender)
             }
-            Event::IsBonded(maybe_known_signatures, new_fs, is_bonded) => {
+            Event::IsBonded(maybe_known_signatures, new_fs, is_bonded, maybe_sender) => {
                 let outcomes = self.linear_chain_state.handle_is_bonded(
                     maybe_known_signatures,
                     new_fs,
                     is_bonded,
                 );
-                outcomes_to_effects(effect_builder, outcomes)
+                self.update_pending_signature_metrics();
+                outcomes_to_effects(effect_builder, &self.metrics, outcomes, maybe_sender)
             }
             Event::KnownLinearChainBlock(block) => {
                 self.linear_chain_state.set_latest_block(*block);
@@ -8,7 +8,7 @@ use derive_more::From;
 
 use crate::{
     effect::requests::LinearChainRequest,
-    types::{Block, BlockSignatures, DeployHash, FinalitySignature},
+    types::{Block, BlockSignatures, DeployHash, FinalitySignature, Summary},
 };
 
 #[derive(Debug, From)]
@@ -27,16 +27,28 @@ pub enum Event<I> {
     KnownLinearChainBlock(Box<Block>),
     /// Finality signature received.
     /// Not necessarily _new_ finality signature.
-    FinalitySignatureReceived(Box<FinalitySignature>, bool),
+    /// Carries the peer it was received from, or `None` if it was created by this node.
+    FinalitySignatureReceived(Box<FinalitySignature>, Option<I>),
     /// The result of putting a block to storage.
     PutBlockResult {
         /// The block.
         block: Box<Block>,
     },
     /// The result of requesting finality signatures from storage to add pending signatures.
-    GetStoredFinalitySignaturesResult(Box<FinalitySignature>, Option<Box<BlockSignatures>>),
+    /// Carries the peer the finality signature was received from, if any.
+    GetStoredFinalitySignaturesResult(
+        Box<FinalitySignature>,
+        Option<Box<BlockSignatures>>,
+        Option<I>,
+    ),
     /// Result of testing if creator of the finality signature is bonded validator.
-    IsBonded(Option<Box<BlockSignatures>>, Box<FinalitySignature>, bool),
+    /// Carries the peer the finality signature was received from, if any.
+    IsBonded(
+        Option<Box<BlockSignatures>>,
+        Box<FinalitySignature>,
+        bool,
+        Option<I>,
+    ),
 }
 
 impl<I: Display> Display for Event<I> {
@@ -44,30 +56,34 @@ impl<I: Display> Display for Event<I> {
         match self {
             Event::Request(req) => write!(f, "linear chain request: {}", req),
             Event::NewLinearChainBlock { block, .. } => {
-                write!(f, "linear chain new block: {}", block.hash())
+                write!(f, "linear chain new {}", block.summary())
             }
             Event::KnownLinearChainBlock(block) => {
-                write!(f, "linear chain known block: {}", block.hash())
+                write!(f, "linear chain known {}", block.summary())
             }
-            Event::FinalitySignatureReceived(fs, gossiped) => write!(
+            Event::FinalitySignatureReceived(fs, maybe_sender) => write!(
                 f,
-                "linear-chain new finality signature for block: {}, from: {}, external: {}",
-                fs.block_hash, fs.public_key, gossiped
+                "linear-chain new {}, external: {}",
+                fs.summary(),
+                maybe_sender.is_some()
             ),
-            Event::PutBlockResult { .. } => write!(f, "linear-chain put-block result"),
-            Event::GetStoredFinalitySignaturesResult(finality_signature, maybe_signatures) => {
+            Event::PutBlockResult { block } => {
+                write!(f, "linear-chain put-block result for {}", block.summary())
+            }
+            Event::GetStoredFinalitySignaturesResult(finality_signature, maybe_signatures, _) => {
                 write!(
                     f,
                     "linear chain get-stored-finality-signatures result for {} found: {}",
-                    finality_signature.block_hash,
+                    finality_signature.summary(),
                     maybe_signatures.is_some(),
                 )
             }
-            Event::IsBonded(_block, fs, is_bonded) => {
+            Event::IsBonded(_block, fs, is_bonded, _) => {
                 write!(
                     f,
-                    "linear chain is-bonded for era {} validator {}, is_bonded: {}",
-                    fs.era_id, fs.public_key, is_bonded
+                    "linear chain is-bonded for {}, is_bonded: {}",
+                    fs.summary(),
+                    is_bonded
                 )
             }
         }
@@ -1,10 +1,18 @@
-use prometheus::{IntGauge, Registry};
+use prometheus::{IntCounter, IntGauge, Registry};
 
 use crate::unregister_metric;
 
 #[derive(Debug)]
 pub(super) struct LinearChainMetrics {
     pub(super) block_completion_duration: IntGauge,
+    /// Number of finality signatures held while waiting for their blocks.
+    pub(super) pending_finality_signatures: IntGauge,
+    /// Number of distinct blocks for which finality signatures are pending.
+    pub(super) pending_finality_signature_blocks: IntGauge,
+    /// Number of received finality signatures dropped, including pending ones evicted.
+    pub(super) dropped_finality_signatures: IntCounter,
+    /// Number of blocks whose pending finality signatures were evicted.
+    pub(super) evicted_pending_signature_blocks: IntCounter,
     /// Prometheus registry used to publish metrics.
     registry: Registry,
 }
@@ -15,9 +23,33 @@ impl LinearChainMetrics {
             "block_completion_duration",
             "duration of time from consensus through execution for a block",
         )?;
+        let pending_finality_signatures = IntGauge::new(
+            "linear_chain_pending_finality_signatures",
+            "number of finality signatures held while waiting for their blocks",
+        )?;
+        let pending_finality_signature_blocks = IntGauge::new(
+            "linear_chain_pending_finality_signature_blocks",
+            "number of distinct blocks for which finality signatures are pending",
+        )?;
+        let dropped_finality_signatures = IntCounter::new(
+            "linear_chain_dropped_finality_signatures",
+            "number of received finality signatures dropped, including pending ones evicted",
+        )?;
+        let evicted_pending_signature_blocks = IntCounter::new(
+            "linear_chain_evicted_pending_signature_blocks",
+            "number of blocks whose pending finality signatures were evicted",
+        )?;
         registry.register(Box::new(block_completion_duration.clone()))?;
+        registry.register(Box::new(pending_finality_signatures.clone()))?;
+        registry.register(Box::new(pending_finality_signature_blocks.clone()))?;
+        registry.register(Box::new(dropped_finality_signatures.clone()))?;
+        registry.register(Box::new(evicted_pending_signature_blocks.clone()))?;
         Ok(Self {
             block_completion_duration,
+            pending_finality_signatures,
+            pending_finality_signature_blocks,
+            dropped_finality_signatures,
+            evicted_pending_signature_blocks,
             registry: registry.clone(),
         })
     }
@@ -26,5 +58,9 @@ impl LinearChainMetrics {
 impl Drop for LinearChainMetrics {
     fn drop(&mut self) {
         unregister_metric!(self.registry, self.block_completion_duration);
+        unregister_metric!(self.registry, self.pending_finality_signatures);
+        unregister_metric!(self.registry, self.pending_finality_signature_blocks);
+        unregister_metric!(self.registry, self.dropped_finality_signatures);
+        unregister_metric!(self.registry, self.evicted_pending_signature_blocks);
     }
 }
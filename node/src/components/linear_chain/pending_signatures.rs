@@ -1,16 +1,23 @@
 use datasize::DataSize;
 use itertools::Itertools;
 use std::collections::{hash_map::Entry, HashMap};
-use tracing::warn;
+use tracing::{debug, warn};
 
 use super::signature::Signature;
 use crate::types::BlockHash;
-use casper_types::PublicKey;
+use casper_types::{EraId, PublicKey};
 
 /// The maximum number of finality signatures from a single validator we keep in memory while
 /// waiting for their block.
 const MAX_PENDING_FINALITY_SIGNATURES_PER_VALIDATOR: usize = 1000;
 
+/// The maximum number of distinct blocks for which we keep pending finality signatures in memory.
+pub(super) const MAX_PENDING_BLOCKS: usize = 200;
+
+/// The maximum number of finality signatures for a single block we keep in memory while their
+/// creators are not yet known to be bonded.
+const MAX_UNVERIFIED_SIGNATURES_PER_BLOCK: usize = 200;
+
 #[derive(DataSize, Debug)]
 enum VerificationStatus {
     Unknown(Signature),
@@ -35,17 +42,68 @@ impl VerificationStatus {
     }
 }
 
+/// Bookkeeping for a block for which we hold pending finality signatures.
+#[derive(DataSize, Debug)]
+struct PendingBlock {
+    /// The era of the first signature received for the block.
+    era_id: EraId,
+    /// The number of pending signatures for the block.
+    signatures: usize,
+    /// The number of pending signatures for the block whose creator is not yet known to be bonded.
+    unverified: usize,
+}
+
+/// The result of adding a finality signature to the pending collection.
+#[derive(Debug, Eq, PartialEq)]
+pub(super) enum AddOutcome {
+    /// The signature was added.  If the signatures of another pending block had to be evicted to
+    /// make room for it, `evicted` holds the number of signatures dropped.
+    Added { evicted: Option<usize> },
+    /// The creator already has the maximum number of pending signatures.
+    CreatorLimitReached,
+    /// The block already has the maximum number of pending signatures from creators not yet known
+    /// to be bonded.
+    BlockLimitReached,
+    /// The maximum number of pending blocks has been reached, and none of them is further from the
+    /// current era than the signature's block.
+    TooFarFromCurrentEra,
+}
+
+impl AddOutcome {
+    #[cfg(test)]
+    pub(super) fn is_added(&self) -> bool {
+        matches!(self, AddOutcome::Added { .. })
+    }
+}
+
 /// Finality signatures to be inserted in a block once it is available.
 /// Keyed by public key of the creator to limit the maximum amount of pending signatures.
-#[derive(DataSize, Debug, Default)]
+#[derive(DataSize, Debug)]
 pub(super) struct PendingSignatures {
     pending_finality_signatures: HashMap<PublicKey, HashMap<BlockHash, VerificationStatus>>,
+    /// The blocks for which there are pending signatures.
+    pending_blocks: HashMap<BlockHash, PendingBlock>,
+    max_pending_blocks: usize,
+    max_unverified_signatures_per_block: usize,
 }
 
 impl PendingSignatures {
     pub(super) fn new() -> Self {
+        Self::with_limits(MAX_PENDING_BLOCKS, MAX_UNVERIFIED_SIGNATURES_PER_BLOCK)
+    }
+
+    /// Creates a new collection holding signatures for at most `max_pending_blocks` blocks, and
+    /// at most `max_unverified_signatures_per_block` signatures from creators not yet known to be
+    /// bonded for any one block.
+    pub(super) fn with_limits(
+        max_pending_blocks: usize,
+        max_unverified_signatures_per_block: usize,
+    ) -> Self {
         PendingSignatures {
             pending_finality_signatures: HashMap::new(),
+            pending_blocks: HashMap::new(),
+            max_pending_blocks,
+            max_unverified_signatures_per_block,
         }
     }
 
@@ -60,6 +118,19 @@ impl PendingSignatures {
             .map_or(false, |sigs| sigs.contains_key(block_hash))
     }
 
+    /// Returns the total number of pending signatures.
+    pub(super) fn signature_count(&self) -> usize {
+        self.pending_finality_signatures
+            .values()
+            .map(HashMap::len)
+            .sum()
+    }
+
+    /// Returns the number of distinct blocks for which there are pending signatures.
+    pub(super) fn block_count(&self) -> usize {
+        self.pending_blocks.len()
+    }
+
     /// Returns signatures for `block_hash` that are still pending.
     pub(super) fn collect_pending(&mut self, block_hash: &BlockHash) -> Vec<Signature> {
         let pending_sigs = self
@@ -72,34 +143,100 @@ impl PendingSignatures {
                     _ => None,
                 }
             })
-            .map(|status| status.into_inner())
             .collect_vec();
+        for status in &pending_sigs {
+            self.on_removed(block_hash, status);
+        }
         self.remove_empty_entries();
         pending_sigs
+            .into_iter()
+            .map(|status| status.into_inner())
+            .collect()
     }
 
     /// Adds finality signature to the pending collection.
-    /// Returns `true` if it was added.
-    pub(super) fn add(&mut self, signature: Signature) -> bool {
+    ///
+    /// If the maximum number of pending blocks has been reached, the signatures of the pending
+    /// block whose era is furthest from `current_era` are evicted to make room, provided it is
+    /// further away than the new signature's block.
+    pub(super) fn add(&mut self, signature: Signature, current_era: EraId) -> AddOutcome {
         let public_key = signature.public_key();
         let block_hash = signature.block_hash();
-        let sigs = self
-            .pending_finality_signatures
-            .entry(public_key.clone())
-            .or_default();
+        let era_id = signature.to_inner().era_id;
         // Limit the memory we use for storing unknown signatures from each validator.
-        if sigs.len() >= MAX_PENDING_FINALITY_SIGNATURES_PER_VALIDATOR {
+        let at_creator_limit = self
+            .pending_finality_signatures
+            .get(&public_key)
+            .map_or(false, |sigs| {
+                sigs.len() >= MAX_PENDING_FINALITY_SIGNATURES_PER_VALIDATOR
+            });
+        if at_creator_limit {
             warn!(
                 %block_hash, %public_key,
                 "received too many finality signatures for unknown blocks"
             );
-            return false;
+            return AddOutcome::CreatorLimitReached;
+        }
+
+        let mut evicted = None;
+        match self.pending_blocks.get(&block_hash) {
+            Some(pending_block) => {
+                // Limit the memory we use for storing signatures for a single block from creators
+                // which may not be validators at all.
+                if pending_block.unverified >= self.max_unverified_signatures_per_block {
+                    warn!(
+                        %block_hash, %public_key,
+                        "received too many unverified finality signatures for block"
+                    );
+                    return AddOutcome::BlockLimitReached;
+                }
+            }
+            None if self.pending_blocks.len() >= self.max_pending_blocks => {
+                let distance = era_distance(era_id, current_era);
+                let furthest = self
+                    .pending_blocks
+                    .iter()
+                    .map(|(hash, pending_block)| {
+                        (era_distance(pending_block.era_id, current_era), *hash)
+                    })
+                    .max_by_key(|(distance, _)| *distance);
+                match furthest {
+                    Some((furthest_distance, furthest_hash)) if furthest_distance > distance => {
+                        evicted = Some(self.evict(&furthest_hash));
+                    }
+                    _ => {
+                        warn!(
+                            %block_hash, %public_key, era_id = %era_id.value(),
+                            "received finality signature for too many unknown blocks"
+                        );
+                        return AddOutcome::TooFarFromCurrentEra;
+                    }
+                }
+            }
+            None => (),
         }
 
         // Add the pending signature.
+        let pending_block = self
+            .pending_blocks
+            .entry(block_hash)
+            .or_insert(PendingBlock {
+                era_id,
+                signatures: 0,
+                unverified: 0,
+            });
+        pending_block.signatures += 1;
+        pending_block.unverified += 1;
         let value = VerificationStatus::Unknown(signature);
-        sigs.insert(block_hash, value);
-        true
+        if let Some(replaced) = self
+            .pending_finality_signatures
+            .entry(public_key)
+            .or_default()
+            .insert(block_hash, value)
+        {
+            self.on_removed(&block_hash, &replaced);
+        }
+        AddOutcome::Added { evicted }
     }
 
     pub(super) fn remove(
@@ -109,6 +246,9 @@ impl PendingSignatures {
     ) -> Option<Signature> {
         let validator_sigs = self.pending_finality_signatures.get_mut(public_key)?;
         let sig = validator_sigs.remove(block_hash);
+        if let Some(status) = &sig {
+            self.on_removed(block_hash, status);
+        }
         self.remove_empty_entries();
         sig.map(|status| status.into_inner())
     }
@@ -124,6 +264,11 @@ impl PendingSignatures {
         match self.pending_finality_signatures.entry(public_key.clone()) {
             Entry::Occupied(mut validator_sigs) => {
                 let sig = validator_sigs.get_mut().get(&block_hash)?;
+                if !sig.is_bonded() {
+                    if let Some(pending_block) = self.pending_blocks.get_mut(&block_hash) {
+                        pending_block.unverified = pending_block.unverified.saturating_sub(1);
+                    }
+                }
                 let bonded_status = VerificationStatus::Bonded(sig.value().clone());
                 validator_sigs.get_mut().insert(block_hash, bonded_status);
                 Some(())
@@ -135,6 +280,33 @@ impl PendingSignatures {
         }
     }
 
+    /// Drops all pending signatures for `block_hash`, returning the number of signatures dropped.
+    fn evict(&mut self, block_hash: &BlockHash) -> usize {
+        self.pending_blocks.remove(block_hash);
+        let evicted = self
+            .pending_finality_signatures
+            .values_mut()
+            .filter_map(|sigs| sigs.remove(block_hash))
+            .count();
+        self.remove_empty_entries();
+        debug!(%block_hash, %evicted, "evicted pending finality signatures");
+        evicted
+    }
+
+    /// Updates the bookkeeping for `block_hash` after the signature with `status` was removed.
+    fn on_removed(&mut self, block_hash: &BlockHash, status: &VerificationStatus) {
+        if let Entry::Occupied(mut entry) = self.pending_blocks.entry(*block_hash) {
+            let pending_block = entry.get_mut();
+            pending_block.signatures = pending_block.signatures.saturating_sub(1);
+            if !status.is_bonded() {
+                pending_block.unverified = pending_block.unverified.saturating_sub(1);
+            }
+            if pending_block.signatures == 0 {
+                entry.remove();
+            }
+        }
+    }
+
     /// Removes all entries for which there are no finality signatures.
     fn remove_empty_entries(&mut self) {
         self.pending_finality_signatures
@@ -142,12 +314,21 @@ impl PendingSignatures {
     }
 }
 
+/// Returns the number of eras between `era_id` and `current_era`.
+fn era_distance(era_id: EraId, current_era: EraId) -> u64 {
+    if era_id > current_era {
+        era_id.value() - current_era.value()
+    } else {
+        current_era.value() - era_id.value()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::{crypto::generate_ed25519_keypair, testing::TestRng, types::FinalitySignature};
-    use casper_types::EraId;
 
+    use rand::Rng;
     use std::collections::BTreeMap;
 
     #[test]
@@ -160,7 +341,9 @@ mod tests {
         let sig_b = FinalitySignature::random_for_block(block_hash_other, 0);
         let public_key = sig_a.public_key.clone();
         let public_key_other = sig_b.public_key;
-        assert!(pending_sigs.add(Signature::External(Box::new(sig_a))));
+        assert!(pending_sigs
+            .add(Signature::External(Box::new(sig_a)), EraId::new(0))
+            .is_added());
         assert!(pending_sigs.has_finality_signature(&public_key, &block_hash));
         assert!(!pending_sigs.has_finality_signature(&public_key_other, &block_hash));
         assert!(!pending_sigs.has_finality_signature(&public_key, &block_hash_other));
@@ -175,11 +358,17 @@ mod tests {
         let sig_a1 = FinalitySignature::random_for_block(block_hash, 0);
         let sig_a2 = FinalitySignature::random_for_block(block_hash, 0);
         let sig_b = FinalitySignature::random_for_block(block_hash_other, 0);
-        assert!(pending_sigs.add(Signature::External(Box::new(sig_a1.clone()))));
+        assert!(pending_sigs
+            .add(Signature::External(Box::new(sig_a1.clone())), EraId::new(0))
+            .is_added());
         assert!(pending_sigs.mark_bonded(sig_a1.public_key.clone(), block_hash));
-        assert!(pending_sigs.add(Signature::External(Box::new(sig_a2.clone()))));
+        assert!(pending_sigs
+            .add(Signature::External(Box::new(sig_a2.clone())), EraId::new(0))
+            .is_added());
         assert!(pending_sigs.mark_bonded(sig_a2.public_key.clone(), block_hash));
-        assert!(pending_sigs.add(Signature::External(Box::new(sig_b))));
+        assert!(pending_sigs
+            .add(Signature::External(Box::new(sig_b)), EraId::new(0))
+            .is_added());
         let collected_sigs: BTreeMap<PublicKey, FinalitySignature> = pending_sigs
             .collect_pending(&block_hash)
             .into_iter()
@@ -206,7 +395,9 @@ mod tests {
         let mut pending_sigs = PendingSignatures::new();
         let block_hash = BlockHash::random(&mut rng);
         let sig = FinalitySignature::random_for_block(block_hash, 0);
-        assert!(pending_sigs.add(Signature::External(Box::new(sig.clone()))));
+        assert!(pending_sigs
+            .add(Signature::External(Box::new(sig.clone())), EraId::new(0))
+            .is_added());
         let removed_sig = pending_sigs.remove(&sig.public_key, &sig.block_hash);
         assert!(removed_sig.is_some());
         assert!(!pending_sigs.has_finality_signature(&sig.public_key, &sig.block_hash));
@@ -218,17 +409,140 @@ mod tests {
     #[test]
     fn max_limit_respected() {
         let mut rng = TestRng::new();
-        let mut pending_sigs = PendingSignatures::new();
+        let mut pending_sigs = PendingSignatures::with_limits(
+            MAX_PENDING_FINALITY_SIGNATURES_PER_VALIDATOR + 1,
+            MAX_UNVERIFIED_SIGNATURES_PER_BLOCK,
+        );
         let (sec_key, pub_key) = generate_ed25519_keypair();
         let era_id = EraId::new(0);
         for _ in 0..MAX_PENDING_FINALITY_SIGNATURES_PER_VALIDATOR {
             let block_hash = BlockHash::random(&mut rng);
             let sig = FinalitySignature::new(block_hash, era_id, &sec_key, pub_key.clone());
-            assert!(pending_sigs.add(Signature::External(Box::new(sig))));
+            assert!(pending_sigs
+                .add(Signature::External(Box::new(sig)), era_id)
+                .is_added());
         }
         let block_hash = BlockHash::random(&mut rng);
         let sig = FinalitySignature::new(block_hash, era_id, &sec_key, pub_key);
-        assert!(!pending_sigs.add(Signature::External(Box::new(sig))));
+        assert_eq!(
+            pending_sigs.add(Signature::External(Box::new(sig)), era_id),
+            AddOutcome::CreatorLimitReached
+        );
+    }
+
+    #[test]
+    fn max_unverified_per_block_respected() {
+        let mut rng = TestRng::new();
+        let max_unverified = 5;
+        let mut pending_sigs = PendingSignatures::with_limits(10, max_unverified);
+        let era_id = EraId::new(0);
+        let block_hash = BlockHash::random(&mut rng);
+        let mut bonded_sig = None;
+        for _ in 0..max_unverified {
+            let sig = FinalitySignature::random_for_block(block_hash, era_id.value());
+            bonded_sig = Some(sig.clone());
+            assert!(pending_sigs
+                .add(Signature::External(Box::new(sig)), era_id)
+                .is_added());
+        }
+        let sig = FinalitySignature::random_for_block(block_hash, era_id.value());
+        assert_eq!(
+            pending_sigs.add(Signature::External(Box::new(sig.clone())), era_id),
+            AddOutcome::BlockLimitReached
+        );
+        assert_eq!(pending_sigs.signature_count(), max_unverified);
+        // Signatures from creators known to be bonded don't count towards the limit.
+        let bonded_sig = bonded_sig.unwrap();
+        assert!(pending_sigs.mark_bonded(bonded_sig.public_key, block_hash));
+        assert!(pending_sigs
+            .add(Signature::External(Box::new(sig)), era_id)
+            .is_added());
+        assert_eq!(pending_sigs.signature_count(), max_unverified + 1);
+        assert_eq!(pending_sigs.block_count(), 1);
+    }
+
+    #[test]
+    fn evicts_block_furthest_from_current_era() {
+        let mut rng = TestRng::new();
+        let max_blocks = 3;
+        let mut pending_sigs = PendingSignatures::with_limits(max_blocks, 10);
+        let current_era = EraId::new(10);
+        let far_block_hash = BlockHash::random(&mut rng);
+        for era in &[12, 2, 9] {
+            let block_hash = if *era == 2 {
+                far_block_hash
+            } else {
+                BlockHash::random(&mut rng)
+            };
+            let sig = FinalitySignature::random_for_block(block_hash, *era);
+            assert_eq!(
+                pending_sigs.add(Signature::External(Box::new(sig)), current_era),
+                AddOutcome::Added { evicted: None }
+            );
+        }
+        // A block even further away than all pending ones is rejected.
+        let sig = FinalitySignature::random_for_block(BlockHash::random(&mut rng), 30);
+        assert_eq!(
+            pending_sigs.add(Signature::External(Box::new(sig)), current_era),
+            AddOutcome::TooFarFromCurrentEra
+        );
+        // A block closer to the current era replaces the furthest one.
+        let sig = FinalitySignature::random_for_block(BlockHash::random(&mut rng), 10);
+        let public_key = sig.public_key.clone();
+        let block_hash = sig.block_hash;
+        assert_eq!(
+            pending_sigs.add(Signature::External(Box::new(sig)), current_era),
+            AddOutcome::Added { evicted: Some(1) }
+        );
+        assert!(pending_sigs.has_finality_signature(&public_key, &block_hash));
+        assert_eq!(pending_sigs.block_count(), max_blocks);
+        assert_eq!(pending_sigs.signature_count(), max_blocks);
+        assert!(pending_sigs.collect_pending(&far_block_hash).is_empty());
+    }
+
+    #[test]
+    fn flooding_random_blocks_stays_within_bounds() {
+        let mut rng = TestRng::new();
+        let max_blocks = 20;
+        let max_unverified = 10;
+        let mut pending_sigs = PendingSignatures::with_limits(max_blocks, max_unverified);
+        let current_era = EraId::new(100);
+
+        // Legitimate signatures for a block at the tip, whose creators are bonded.
+        let tip_block_hash = BlockHash::random(&mut rng);
+        let tip_sigs: Vec<_> = (0..3)
+            .map(|_| FinalitySignature::random_for_block(tip_block_hash, current_era.value()))
+            .collect();
+        for sig in &tip_sigs {
+            assert!(pending_sigs
+                .add(Signature::External(Box::new(sig.clone())), current_era)
+                .is_added());
+            assert!(pending_sigs.mark_bonded(sig.public_key.clone(), tip_block_hash));
+        }
+
+        // A peer floods us with signatures for random blocks in random eras, and with many
+        // signatures for a single random block.
+        let flooded_block_hash = BlockHash::random(&mut rng);
+        for _ in 0..1_000 {
+            let era = current_era.value() + rng.gen_range(1..1_000);
+            let sig = FinalitySignature::random_for_block(BlockHash::random(&mut rng), era);
+            pending_sigs.add(Signature::External(Box::new(sig)), current_era);
+            let sig = FinalitySignature::random_for_block(flooded_block_hash, era);
+            pending_sigs.add(Signature::External(Box::new(sig)), current_era);
+            assert!(pending_sigs.block_count() <= max_blocks);
+            assert!(pending_sigs.signature_count() <= max_blocks * max_unverified + tip_sigs.len());
+        }
+
+        // The signatures for the tip block are unaffected.
+        let collected: Vec<_> = pending_sigs
+            .collect_pending(&tip_block_hash)
+            .into_iter()
+            .map(|sig| *sig.take())
+            .collect();
+        assert_eq!(collected.len(), tip_sigs.len());
+        for sig in &tip_sigs {
+            assert!(collected.contains(sig));
+        }
     }
 
     #[test]
@@ -241,7 +555,9 @@ mod tests {
         let block_hash = BlockHash::random(&mut rng);
         let sig_a = FinalitySignature::random_for_block(block_hash, 0);
         let public_key = sig_a.public_key.clone();
-        assert!(pending_sigs.add(Signature::External(Box::new(sig_a))));
+        assert!(pending_sigs
+            .add(Signature::External(Box::new(sig_a)), EraId::new(0))
+            .is_added());
         assert!(pending_sigs.has_finality_signature(&public_key, &block_hash));
         assert!(pending_sigs.mark_bonded(public_key.clone(), block_hash));
         assert!(pending_sigs.has_finality_signature(&public_key, &block_hash));
@@ -6,12 +6,14 @@ use tracing::{debug, warn};
 
 use crate::{
     crypto::hash::Digest,
-    types::{Block, BlockHash, BlockSignatures, DeployHash, FinalitySignature},
+    types::{Block, BlockHash, BlockSignatures, DeployHash, FinalitySignature, Summary},
 };
-use casper_types::{ExecutionResult, ProtocolVersion};
+use casper_types::{EraId, ExecutionResult, ProtocolVersion};
 
 use super::{
-    pending_signatures::PendingSignatures, signature::Signature, signature_cache::SignatureCache,
+    pending_signatures::{AddOutcome, PendingSignatures},
+    signature::Signature,
+    signature_cache::SignatureCache,
 };
 #[derive(DataSize, Debug)]
 pub(crate) struct LinearChain {
@@ -50,6 +52,14 @@ pub(super) enum Outcome {
         protocol_version: ProtocolVersion,
         latest_state_root_hash: Option<Digest>,
     },
+    // Record that the pending signatures for a block were evicted to make room for another block.
+    PendingBlockEvicted {
+        dropped_signatures: usize,
+    },
+    // Record that a received finality signature was dropped without being stored.
+    SignatureDropped,
+    // Penalize the peer from which the finality signature was received, if any.
+    DisconnectFromSender,
 }
 
 pub(super) type Outcomes = Vec<Outcome>;
@@ -105,59 +115,82 @@ impl LinearChain {
         signatures
     }
 
-    /// Tries to add the finality signature to the collection of pending finality signatures.
-    /// Returns true if added successfully, otherwise false.
-    fn add_pending_finality_signature(&mut self, fs: FinalitySignature, gossiped: bool) -> bool {
-        let FinalitySignature {
-            block_hash,
-            public_key,
-            era_id,
-            ..
-        } = fs.clone();
-        if let Some(latest_block) = self.latest_block.as_ref() {
+    /// Returns the era of the next block, or `None` if we don't know about any block yet.
+    fn current_era(&self) -> Option<EraId> {
+        self.latest_block.as_ref().map(|latest_block| {
             // If it's a switch block it has already forgotten its own era's validators,
             // unbonded some old validators, and determined new ones. In that case, we
             // should add 1 to last_block_era.
-            let current_era = latest_block.header().era_id()
+            latest_block.header().era_id()
                 + if latest_block.header().is_switch_block() {
                     1
                 } else {
                     0
-                };
+                }
+        })
+    }
+
+    /// Tries to add the finality signature to the collection of pending finality signatures.
+    /// Returns true if added successfully, otherwise false.  Outcomes recording dropped or evicted
+    /// signatures are pushed to `outcomes`.
+    fn add_pending_finality_signature(
+        &mut self,
+        fs: FinalitySignature,
+        gossiped: bool,
+        outcomes: &mut Outcomes,
+    ) -> bool {
+        let era_id = fs.era_id;
+        if let Some(current_era) = self.current_era() {
             let lowest_acceptable_era_id =
                 (current_era + self.auction_delay).saturating_sub(self.unbonding_delay);
             let highest_acceptable_era_id = current_era + self.auction_delay;
             if era_id < lowest_acceptable_era_id || era_id > highest_acceptable_era_id {
                 warn!(
-                    era_id=%era_id.value(),
-                    %public_key,
-                    %block_hash,
+                    finality_signature = %fs.summary(),
                     "received finality signature for not bonded era."
                 );
+                outcomes.push(Outcome::SignatureDropped);
                 return false;
             }
         }
         if self.is_pending(&fs) {
-            debug!(block_hash=%fs.block_hash, public_key=%fs.public_key,
-                "finality signature already pending");
+            debug!(finality_signature = %fs.summary(), "finality signature already pending");
             return false;
         }
         if !self.is_new(&fs) {
-            debug!(block_hash=%fs.block_hash, public_key=%fs.public_key,
-                "finality signature is already known");
+            debug!(finality_signature = %fs.summary(), "finality signature is already known");
             return false;
         }
         if let Err(err) = fs.verify() {
-            warn!(%block_hash, %public_key, %err, "received invalid finality signature");
+            warn!(
+                finality_signature = %fs.summary(), %err,
+                "received invalid finality signature"
+            );
+            outcomes.push(Outcome::SignatureDropped);
+            outcomes.push(Outcome::DisconnectFromSender);
             return false;
         }
-        debug!(%block_hash, %public_key, "received new finality signature");
+        debug!(finality_signature = %fs.summary(), "received new finality signature");
         let signature = if gossiped {
             Signature::External(Box::new(fs))
         } else {
             Signature::Local(Box::new(fs))
         };
-        self.pending_finality_signatures.add(signature)
+        let current_era = self.current_era().unwrap_or_default();
+        match self.pending_finality_signatures.add(signature, current_era) {
+            AddOutcome::Added { evicted } => {
+                if let Some(dropped_signatures) = evicted {
+                    outcomes.push(Outcome::PendingBlockEvicted { dropped_signatures });
+                }
+                true
+            }
+            AddOutcome::CreatorLimitReached
+            | AddOutcome::BlockLimitReached
+            | AddOutcome::TooFarFromCurrentEra => {
+                outcomes.push(Outcome::SignatureDropped);
+                false
+            }
+        }
     }
 
     /// Removes finality signature from the pending collection.
@@ -168,7 +201,10 @@ impl LinearChain {
             signature: _signature,
             public_key,
         } = fs;
-        debug!(%block_hash, %public_key, "removing finality signature from pending collection");
+        debug!(
+            finality_signature = %fs.summary(),
+            "removing finality signature from pending collection"
+        );
         self.pending_finality_signatures
             .remove(public_key, block_hash)
     }
@@ -203,6 +239,16 @@ impl LinearChain {
         &self.latest_block
     }
 
+    /// Returns the total number of pending finality signatures.
+    pub(super) fn pending_signature_count(&self) -> usize {
+        self.pending_finality_signatures.signature_count()
+    }
+
+    /// Returns the number of distinct blocks for which there are pending finality signatures.
+    pub(super) fn pending_block_count(&self) -> usize {
+        self.pending_finality_signatures.block_count()
+    }
+
     /// Returns finality signatures for `block_hash`.
     fn collect_pending_finality_signatures(&mut self, block_hash: &BlockHash) -> Vec<Signature> {
         self.pending_finality_signatures
@@ -259,18 +305,24 @@ impl LinearChain {
         gossiped: bool,
     ) -> Outcomes {
         let FinalitySignature { block_hash, .. } = *fs;
-        if !self.add_pending_finality_signature(*fs.clone(), gossiped) {
-            // If we did not add the signature it means it's either incorrect or we already
-            // know it.
-            return vec![];
+        let mut outcomes = vec![];
+        if !self.add_pending_finality_signature(*fs.clone(), gossiped, &mut outcomes) {
+            // If we did not add the signature it means it's either incorrect, we already
+            // know it or we have no room for it.
+            return outcomes;
         }
         match self.get_signatures(&block_hash) {
             // Not found in the cache, look in the storage.
-            None => vec![Outcome::LoadSignatures(fs)],
+            None => outcomes.push(Outcome::LoadSignatures(fs)),
             // We know about the block but we haven't seen any signatures for it yet.
-            Some(signatures) if signatures.proofs.is_empty() => vec![Outcome::LoadSignatures(fs)],
-            Some(signatures) => self.handle_cached_signatures(Some(Box::new(signatures)), fs),
+            Some(signatures) if signatures.proofs.is_empty() => {
+                outcomes.push(Outcome::LoadSignatures(fs))
+            }
+            Some(signatures) => {
+                outcomes.extend(self.handle_cached_signatures(Some(Box::new(signatures)), fs))
+            }
         }
+        outcomes
     }
 
     pub(super) fn handle_cached_signatures(
@@ -288,8 +340,7 @@ impl LinearChain {
                     got = %fs.era_id,
                     "finality signature with invalid era id.");
                 self.remove_from_pending_fs(&*fs);
-                // TODO: Disconnect from the sender.
-                return vec![];
+                return vec![Outcome::SignatureDropped, Outcome::DisconnectFromSender];
             }
             if known_signatures.has_proof(&fs.public_key) {
                 self.remove_from_pending_fs(&fs);
@@ -336,8 +387,7 @@ impl LinearChain {
                 %block_hash,
                 "Received a signature from a validator that is not bonded."
             );
-            // TODO: Disconnect from the sender.
-            return vec![];
+            return vec![Outcome::SignatureDropped, Outcome::DisconnectFromSender];
         }
 
         self.pending_finality_signatures
@@ -379,8 +429,11 @@ impl LinearChain {
 
 #[cfg(test)]
 mod tests {
-    use crate::{crypto::generate_ed25519_keypair, logging, testing::TestRng};
-    use casper_types::EraId;
+    use crate::{
+        components::linear_chain::pending_signatures::MAX_PENDING_BLOCKS,
+        crypto::generate_ed25519_keypair, logging, testing::TestRng,
+    };
+    use rand::Rng;
 
     use super::*;
 
@@ -632,19 +685,98 @@ mod tests {
         // signature's era either too low or too high
         let era_too_low_sig = FinalitySignature::random_for_block(block_hash, 0);
         let outcomes = lc.handle_finality_signature(Box::new(era_too_low_sig), false);
-        assert!(outcomes.is_empty());
+        assert_eq!(outcomes, vec![Outcome::SignatureDropped]);
         let era_too_high_sig =
             FinalitySignature::random_for_block(block_hash, block_era.value() + auction_delay + 1);
         let outcomes = lc.handle_finality_signature(Box::new(era_too_high_sig), false);
-        assert!(outcomes.is_empty());
+        assert_eq!(outcomes, vec![Outcome::SignatureDropped]);
         // signature is not valid
         let block_hash = BlockHash::random(&mut rng);
         let (_, pub_key) = generate_ed25519_keypair();
         let mut invalid_sig = FinalitySignature::random_for_block(block_hash, block_era.value());
         // replace the public key so that the verification fails.
         invalid_sig.public_key = pub_key;
-        let outcomes = lc.handle_finality_signature(Box::new(invalid_sig), false);
-        assert!(outcomes.is_empty())
+        let outcomes = lc.handle_finality_signature(Box::new(invalid_sig), true);
+        assert_eq!(
+            outcomes,
+            vec![Outcome::SignatureDropped, Outcome::DisconnectFromSender]
+        );
+    }
+
+    #[test]
+    fn unbonded_sig_dropped() {
+        let mut rng = TestRng::new();
+        let protocol_version = ProtocolVersion::V1_0_0;
+        let mut lc = LinearChain::new(protocol_version, 1u64, 1u64);
+        let block_hash = BlockHash::random(&mut rng);
+        let sig = add_pending(&mut lc, block_hash, EraId::new(0), false);
+        assert_eq!(lc.pending_signature_count(), 1);
+        let outcomes = lc.handle_is_bonded(None, Box::new(sig), false);
+        assert_eq!(
+            outcomes,
+            vec![Outcome::SignatureDropped, Outcome::DisconnectFromSender]
+        );
+        assert_eq!(lc.pending_signature_count(), 0);
+        assert_eq!(lc.pending_block_count(), 0);
+    }
+
+    #[test]
+    fn flooding_with_sigs_for_random_blocks_is_bounded() {
+        let _ = logging::init();
+        let mut rng = TestRng::new();
+        let protocol_version = ProtocolVersion::V1_0_0;
+        let mut lc = LinearChain::new(protocol_version, 1u64, 1u64);
+        let block =
+            Block::random_with_specifics(&mut rng, EraId::new(0), 0, protocol_version, false);
+        let block_hash = *block.hash();
+        let block_era = block.header().era_id();
+        // Signatures for the next block from bonded validators.
+        let sig_a = add_pending(&mut lc, block_hash, block_era, false);
+        let sig_b = add_pending(&mut lc, block_hash, block_era, false);
+        mark_bonded(&mut lc, sig_a.clone());
+        mark_bonded(&mut lc, sig_b.clone());
+
+        // A peer floods us with signatures for random blocks in future eras.
+        let flood_count = 2 * MAX_PENDING_BLOCKS;
+        let mut dropped = 0;
+        let mut evicted = 0;
+        for _ in 0..flood_count {
+            let era_id = rng.gen_range(1..1_000);
+            let sig = FinalitySignature::random_for_block(BlockHash::random(&mut rng), era_id);
+            for outcome in lc.handle_finality_signature(Box::new(sig), true) {
+                match outcome {
+                    Outcome::LoadSignatures(_) => (),
+                    Outcome::SignatureDropped => dropped += 1,
+                    Outcome::PendingBlockEvicted { dropped_signatures } => {
+                        evicted += dropped_signatures
+                    }
+                    other => panic!("unexpected outcome: {:?}", other),
+                }
+            }
+            assert!(lc.pending_block_count() <= MAX_PENDING_BLOCKS);
+        }
+        // Every flooded signature which didn't fit was either dropped or evicted later on.
+        assert_eq!(lc.pending_block_count(), MAX_PENDING_BLOCKS);
+        assert_eq!(
+            lc.pending_signature_count(),
+            MAX_PENDING_BLOCKS - 1 + 2,
+            "should keep one signature per flooded block and both signatures for the next block"
+        );
+        assert_eq!(dropped + evicted, flood_count - (MAX_PENDING_BLOCKS - 1));
+
+        // The signatures for the next block are unaffected.
+        let outcomes = lc.handle_new_block(Box::new(block.clone()), HashMap::new());
+        let mut block_signatures = BlockSignatures::new(block_hash, block_era);
+        block_signatures.insert_proof(sig_a.public_key.clone(), sig_a.signature);
+        block_signatures.insert_proof(sig_b.public_key.clone(), sig_b.signature);
+        let expected_outcomes = vec![
+            Outcome::StoreBlockSignatures(block_signatures),
+            Outcome::AnnounceSignature(Box::new(sig_a)),
+            Outcome::AnnounceSignature(Box::new(sig_b)),
+            Outcome::StoreBlock(Box::new(block)),
+            Outcome::StoreExecutionResults(block_hash, HashMap::new()),
+        ];
+        assert_equal(expected_outcomes, outcomes);
     }
 
     #[test]
@@ -4,7 +4,7 @@
 //!
 //! Steps are:
 //! 1. Fetch blocks up to initial, trusted hash (blocks are downloaded starting from trusted hash up
-//! until Genesis).
+//! until Genesis), along with the headers (but not the bodies) of their deploys.
 //! 2. Fetch deploys of the lowest height block.
 //! 3. Execute that block.
 //! 4. Repeat steps 2-3 until trusted hash is reached.
@@ -32,10 +32,11 @@ mod traits;
 use std::{collections::BTreeMap, convert::Infallible, fmt::Display, mem, str::FromStr};
 
 use datasize::DataSize;
+use futures::future;
 use prometheus::Registry;
 use tracing::{error, info, trace, warn};
 
-use self::event::{BlockByHashResult, DeploysResult};
+use self::event::{BlockByHashResult, DeployHeadersResult, DeploysResult};
 use casper_types::{EraId, ProtocolVersion, PublicKey, U512};
 
 use super::{
@@ -728,11 +729,42 @@ where
                                 ),
                             );
                         }
-                        self.peers.success(peer);
-                        self.block_downloaded(rng, effect_builder, &block)
+                        self.peers.success(peer.clone());
+                        // Blocks are only fetched by hash when syncing to the trusted hash, where
+                        // we check the block's deploy headers before moving on to its parent.
+                        fetch_block_deploy_headers(effect_builder, peer, *block)
                     }
                 }
             }
+            Event::GetDeployHeadersResult(fetch_result) => match fetch_result {
+                DeployHeadersResult::Found(block) => {
+                    let block_hash = block.hash();
+                    trace!(%block_hash, "deploy headers for linear chain block found");
+                    self.block_downloaded(rng, effect_builder, &block)
+                }
+                DeployHeadersResult::NotFound(block, peer) => {
+                    let block_hash = block.hash();
+                    trace!(
+                        %block_hash, %peer,
+                        "deploy headers for linear chain block not found. Trying next peer"
+                    );
+                    self.peers.failure(&peer);
+                    match self.peers.random() {
+                        None => {
+                            error!(
+                                %block_hash,
+                                "could not download deploy headers of linear chain block."
+                            );
+                            fatal!(
+                                effect_builder,
+                                "failed to download linear chain deploy headers"
+                            )
+                            .ignore()
+                        }
+                        Some(peer) => fetch_block_deploy_headers(effect_builder, peer, *block),
+                    }
+                }
+            },
             Event::GetDeploysResult(fetch_result) => {
                 self.metrics.observe_get_deploys();
                 match fetch_result {
@@ -843,6 +875,40 @@ where
         })
 }
 
+/// Fetches the headers of the block's deploys and transfers, checking that each of them could have
+/// been included in the block.  The full deploys are only fetched once the block is executed.
+fn fetch_block_deploy_headers<I: Clone + Send + 'static, REv>(
+    effect_builder: EffectBuilder<REv>,
+    peer: I,
+    block: Block,
+) -> Effects<Event<I>>
+where
+    REv: ReactorEventT<I>,
+{
+    let fetches: Vec<_> = block
+        .deploy_hashes()
+        .iter()
+        .chain(block.transfer_hashes())
+        .map(|deploy_hash| effect_builder.fetch_deploy_header(*deploy_hash, peer.clone()))
+        .collect();
+    future::join_all(fetches).event(move |results| {
+        let block_timestamp = block.timestamp();
+        let all_valid = results.into_iter().all(|maybe_result| match maybe_result {
+            Some(FetchResult::FromStorage(deploy_header))
+            | Some(FetchResult::FromPeer(deploy_header, _)) => {
+                let header = deploy_header.header();
+                header.timestamp() <= block_timestamp && !header.expired(block_timestamp)
+            }
+            None => false,
+        });
+        if all_valid {
+            Event::GetDeployHeadersResult(DeployHeadersResult::Found(Box::new(block)))
+        } else {
+            Event::GetDeployHeadersResult(DeployHeadersResult::NotFound(Box::new(block), peer))
+        }
+    })
+}
+
 fn fetch_block_by_hash<I: Clone + Send + 'static, REv>(
     effect_builder: EffectBuilder<REv>,
     peer: I,
@@ -7,6 +7,7 @@ pub enum Event<I> {
     Start(I),
     GetBlockHashResult(BlockHash, BlockByHashResult<I>),
     GetBlockHeightResult(u64, BlockByHeightResult<I>),
+    GetDeployHeadersResult(DeployHeadersResult<I>),
     GetDeploysResult(DeploysResult<I>),
     StartDownloadingDeploys,
     NewPeerConnected(I),
@@ -18,6 +19,12 @@ pub enum Event<I> {
     Shutdown(bool),
 }
 
+#[derive(Debug)]
+pub enum DeployHeadersResult<I> {
+    Found(Box<Block>),
+    NotFound(Box<Block>, I),
+}
+
 #[derive(Debug)]
 pub enum DeploysResult<I> {
     Found(Box<Block>),
@@ -48,6 +55,9 @@ where
             Event::GetBlockHashResult(block_hash, r) => {
                 write!(f, "Get block result for {}: {:?}", block_hash, r)
             }
+            Event::GetDeployHeadersResult(result) => {
+                write!(f, "Get deploy headers for block result {:?}", result)
+            }
             Event::GetDeploysResult(result) => {
                 write!(f, "Get deploys for block result {:?}", result)
             }
@@ -6,12 +6,13 @@ use crate::{
             StorageRequest,
         },
     },
-    types::{Block, BlockByHeight},
+    types::{Block, BlockByHeight, DeployHeaderWithHash},
 };
 pub trait ReactorEventT<I>:
     From<StorageRequest>
     + From<FetcherRequest<I, Block>>
     + From<FetcherRequest<I, BlockByHeight>>
+    + From<FetcherRequest<I, DeployHeaderWithHash>>
     + From<BlockValidationRequest<I>>
     + From<ContractRuntimeRequest>
     + From<StateStoreRequest>
@@ -24,6 +25,7 @@ impl<I, REv> ReactorEventT<I> for REv where
     REv: From<StorageRequest>
         + From<FetcherRequest<I, Block>>
         + From<FetcherRequest<I, BlockByHeight>>
+        + From<FetcherRequest<I, DeployHeaderWithHash>>
         + From<BlockValidationRequest<I>>
         + From<ContractRuntimeRequest>
         + From<StateStoreRequest>
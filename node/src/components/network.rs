@@ -1,19 +1,21 @@
+mod address_gossip;
 mod behavior;
 mod config;
 mod error;
 mod event;
 mod gossip;
+mod held_messages;
 mod one_way_messaging;
 mod peer_discovery;
 mod protocol_id;
+mod substream_limits;
 #[cfg(test)]
 mod tests;
 #[cfg(test)]
 mod tests_bulk_gossip;
 
 use std::{
-    collections::{HashMap, HashSet},
-    env,
+    collections::{BTreeMap, HashMap, HashSet},
     fmt::{self, Debug, Display, Formatter},
     marker::PhantomData,
     num::NonZeroU32,
@@ -24,14 +26,16 @@ use std::{
 use datasize::DataSize;
 use futures::{future::BoxFuture, FutureExt};
 use libp2p::{
-    core::{connection::ConnectedPoint, upgrade},
+    core::{connection::ConnectedPoint, network::ConnectionLimits, upgrade},
     gossipsub::GossipsubEvent,
     identify::IdentifyEvent,
     identity::Keypair,
     kad::KademliaEvent,
     mplex::{MaxBufferBehaviour, MplexConfig},
     noise::{self, NoiseConfig, X25519Spec},
-    request_response::{RequestResponseEvent, RequestResponseMessage},
+    request_response::{
+        InboundFailure, OutboundFailure, RequestResponseEvent, RequestResponseMessage,
+    },
     swarm::{SwarmBuilder, SwarmEvent},
     tcp::TokioTcpConfig,
     Multiaddr, PeerId, Swarm, Transport,
@@ -39,60 +43,90 @@ use libp2p::{
 use prometheus::{IntGauge, Registry};
 use rand::seq::IteratorRandom;
 use serde::{Deserialize, Serialize};
-use tokio::{select, sync::watch, task::JoinHandle, time};
+use tokio::{
+    select,
+    sync::{mpsc, watch},
+    task::JoinHandle,
+    time,
+};
 use tracing::{debug, error, info, trace, warn};
 
 pub(crate) use self::event::Event;
 use self::{
     behavior::{Behavior, SwarmBehaviorEvent},
     gossip::GossipMessage,
+    held_messages::{HeldMessages, HoldOutcome},
     one_way_messaging::{Codec as OneWayCodec, Outgoing as OneWayOutgoingMessage},
     protocol_id::ProtocolId,
 };
-pub use self::{config::Config, error::Error};
+pub use self::{
+    config::Config,
+    error::{Error, ErrorKind},
+};
 use crate::{
-    components::{networking_metrics::NetworkingMetrics, Component},
+    components::{
+        network_provider::{self, NetworkProvider, NetworkProviderKind},
+        networking_metrics::NetworkingMetrics,
+        Component,
+    },
     effect::{
-        announcements::NetworkAnnouncement,
-        requests::{NetworkInfoRequest, NetworkRequest},
-        EffectBuilder, EffectExt, Effects,
+        announcements::{BlocklistAnnouncement, NetworkAnnouncement},
+        EffectBuilder, EffectExt, Effects, Responder,
     },
     fatal,
     reactor::{EventQueueHandle, Finalize, QueueKind, ReactorEvent},
-    types::{Chainspec, NodeId},
-    utils::{self, ds, CountingReceiver, CountingSender, DisplayIter},
+    types::{Chainspec, DialBackoff, GossipState, NodeId, Timestamp},
+    utils::{self, display_error, ds, CountingReceiver, CountingSender, DisplayIter},
     NodeRng,
 };
 
-/// Env var which, if it's defined at runtime, enables the network (libp2p based) component.
-pub(crate) const ENABLE_LIBP2P_NET_ENV_VAR: &str = "CASPER_ENABLE_LIBP2P_NET";
-
 /// How long to sleep before reconnecting
 const RECONNECT_DELAY: Duration = Duration::from_millis(500);
 
+/// The number of oversized one-way messages a peer may send before it is disconnected and blocked.
+const MAX_OVERSIZED_MESSAGES_PER_PEER: u32 = 3;
+
+/// The number of refused inbound substreams a peer may open before it is disconnected and blocked.
+///
+/// This is generous, since substreams are also refused once the limit across all connections has
+/// been reached, through no fault of the peer opening them.
+const MAX_REFUSED_SUBSTREAMS_PER_PEER: u32 = 100;
+
 /// A helper trait whose bounds represent the requirements for a payload that `Network` can
 /// work with.
 pub trait PayloadT:
     Serialize + for<'de> Deserialize<'de> + Clone + Debug + Display + Send + 'static
 {
-}
-
-impl<P> PayloadT for P where
-    P: Serialize + for<'de> Deserialize<'de> + Clone + Debug + Display + Send + 'static
-{
+    /// Returns the maximum permitted serialized size in bytes of this payload.
+    ///
+    /// This is enforced in addition to the global `max_one_way_message_size` when sending and
+    /// receiving one-way messages, allowing each class of message to have an appropriate ceiling.
+    fn max_serialized_size(&self, _chainspec: &Chainspec) -> u32 {
+        u32::max_value()
+    }
 }
 
 /// A helper trait whose bounds represent the requirements for a reactor event that `Network` can
 /// work with.
 pub trait ReactorEventT<P: PayloadT>:
-    ReactorEvent + From<Event<P>> + From<NetworkAnnouncement<NodeId, P>> + Send + 'static
+    ReactorEvent
+    + From<Event<P>>
+    + From<NetworkAnnouncement<NodeId, P>>
+    + From<BlocklistAnnouncement<NodeId>>
+    + Send
+    + 'static
 {
 }
 
 impl<REv, P> ReactorEventT<P> for REv
 where
     P: PayloadT,
-    REv: ReactorEvent + From<Event<P>> + From<NetworkAnnouncement<NodeId, P>> + Send + 'static,
+    REv: ReactorEvent
+        + From<Event<P>>
+        + From<NetworkAnnouncement<NodeId, P>>
+        + From<BlocklistAnnouncement<NodeId>>
+        + Send
+        + 'static,
 {
 }
 
@@ -101,6 +135,8 @@ enum ConnectionState {
     Pending,
     Connected,
     Failed,
+    /// The peer at this address supports none of our protocols, so it is never redialed.
+    Incompatible,
 }
 
 // TODO: Get rid of the `Arc<Mutex<_>>` ASAP.
@@ -136,9 +172,37 @@ pub struct Network<REv, P> {
     /// The channel through which to send outgoing one-way requests.
     one_way_message_sender: CountingSender<OneWayOutgoingMessage>,
     max_one_way_message_size: u32,
+    /// The chainspec, used to determine the size limit for each class of one-way message.
+    #[data_size(skip)]
+    chainspec: Arc<Chainspec>,
+    /// Peers which have sent one-way messages exceeding the size limit for their class, along with
+    /// the number of such messages received since they connected.
+    #[data_size(with = ds::hash_map_fixed_size)]
+    oversized_message_senders: HashMap<NodeId, u32>,
+    /// Peers which have opened inbound substreams beyond the configured limits, along with the
+    /// number of such substreams refused since they connected.
+    #[data_size(with = ds::hash_map_fixed_size)]
+    refused_substream_peers: HashMap<NodeId, u32>,
+    /// Peers which support none of our protocols.  They are banned from connecting to us and no
+    /// further messages are sent to them.
+    #[data_size(with = ds::hash_set_fixed_size)]
+    incompatible_peers: HashSet<NodeId>,
+    /// Peers which committed an offense.  They are banned from connecting to us and no further
+    /// messages are sent to them.
+    #[data_size(with = ds::hash_set_fixed_size)]
+    blocked_peers: HashSet<NodeId>,
+    /// The channel through which to ban peers from connecting to us.
+    #[data_size(skip)]
+    ban_peer_sender: mpsc::UnboundedSender<PeerId>,
+    /// One-way messages held for recently disconnected peers, to be sent if they reconnect.
+    #[data_size(with = HeldMessages::estimate_heap_size)]
+    held_messages: HeldMessages<P>,
     /// The channel through which to send new messages for gossiping.
     gossip_message_sender: CountingSender<GossipMessage>,
     max_gossip_message_size: u32,
+    /// The channel through which to request snapshots of the gossipsub state.
+    #[data_size(skip)]
+    gossip_state_request_sender: mpsc::UnboundedSender<Responder<Option<GossipState>>>,
     /// Channel signaling a shutdown of the network component.
     #[data_size(skip)]
     shutdown_sender: Option<watch::Sender<()>>,
@@ -160,9 +224,8 @@ impl<REv: ReactorEventT<P>, P: PayloadT> Network<REv, P> {
         config: Config,
         registry: &Registry,
         network_identity: NetworkIdentity,
-        chainspec: &Chainspec,
+        chainspec: Arc<Chainspec>,
     ) -> Result<(Network<REv, P>, Effects<Event<P>>), Error> {
-        let our_peer_id = PeerId::from(&network_identity);
         let our_id = NodeId::from(&network_identity);
 
         // Convert the known addresses to multiaddr format and prepare the shutdown signal.
@@ -184,62 +247,15 @@ impl<REv: ReactorEventT<P>, P: PayloadT> Network<REv, P> {
         let (one_way_message_sender, one_way_message_receiver) =
             utils::counting_unbounded_channel();
         let (gossip_message_sender, gossip_message_receiver) = utils::counting_unbounded_channel();
+        let (gossip_state_request_sender, gossip_state_request_receiver) =
+            mpsc::unbounded_channel();
+        let (ban_peer_sender, ban_peer_receiver) = mpsc::unbounded_channel();
         let (server_shutdown_sender, server_shutdown_receiver) = watch::channel(());
 
-        // If the env var "CASPER_ENABLE_LIBP2P_NET" is defined, start the server and exit.
-        if env::var(ENABLE_LIBP2P_NET_ENV_VAR).is_err() {
-            let network = Network {
-                network_identity,
-                our_id,
-                peers: HashMap::new(),
-                seen_peers: HashSet::new(),
-                listening_addresses: vec![],
-                known_addresses_mut: Arc::new(Mutex::new(known_addresses)),
-                is_bootstrap_node: config.is_bootstrap_node,
-                one_way_message_sender,
-                max_one_way_message_size: 0,
-                gossip_message_sender,
-                max_gossip_message_size: 0,
-                shutdown_sender: Some(server_shutdown_sender),
-                server_join_handle: None,
-                net_metrics: NetworkingMetrics::new(&Registry::default())?,
-                _phantom: PhantomData,
-            };
-            return Ok((network, Effects::new()));
-        }
+        let net_metrics = NetworkingMetrics::new(NetworkProviderKind::Libp2p, registry)
+            .map_err(Error::Metrics)?;
 
-        let net_metrics = NetworkingMetrics::new(registry).map_err(Error::Metrics)?;
-
-        // Create a keypair for authenticated encryption of the transport.
-        let noise_keys = noise::Keypair::<X25519Spec>::new()
-            .into_authentic(&network_identity.keypair)
-            .map_err(Error::StaticKeypairSigning)?;
-
-        let mut mplex_config = MplexConfig::default();
-        mplex_config.set_max_buffer_behaviour(MaxBufferBehaviour::Block);
-
-        // Create a tokio-based TCP transport.  Use `noise` for authenticated encryption and `mplex`
-        // for multiplexing of substreams on a TCP stream.
-        let transport = TokioTcpConfig::new()
-            .nodelay(true)
-            .upgrade(upgrade::Version::V1)
-            .authenticate(NoiseConfig::xx(noise_keys).into_authenticated())
-            .multiplex(MplexConfig::default())
-            .timeout(config.connection_setup_timeout.into())
-            .boxed();
-
-        // Create a Swarm to manage peers and events.
-        let behavior = Behavior::new(
-            &config,
-            &net_metrics,
-            chainspec,
-            network_identity.keypair.public(),
-        );
-        let mut swarm = SwarmBuilder::new(transport, behavior, our_peer_id)
-            .executor(Box::new(|future| {
-                tokio::spawn(future);
-            }))
-            .build();
+        let mut swarm = new_swarm(&config, &network_identity, &net_metrics, &chainspec)?;
 
         // Specify listener.
         let listening_address = address_str_to_multiaddr(config.bind_address.as_str());
@@ -267,10 +283,13 @@ impl<REv: ReactorEventT<P>, P: PayloadT> Network<REv, P> {
             event_queue,
             one_way_message_receiver,
             gossip_message_receiver,
+            gossip_state_request_receiver,
+            ban_peer_receiver,
             server_shutdown_receiver,
             swarm,
             known_addresses_mut.clone(),
             is_bootstrap_node,
+            Arc::clone(&chainspec),
             net_metrics.queued_messages.clone(),
         )));
 
@@ -284,8 +303,20 @@ impl<REv: ReactorEventT<P>, P: PayloadT> Network<REv, P> {
             is_bootstrap_node,
             one_way_message_sender,
             max_one_way_message_size: config.max_one_way_message_size,
+            chainspec,
+            oversized_message_senders: HashMap::new(),
+            refused_substream_peers: HashMap::new(),
+            incompatible_peers: HashSet::new(),
+            blocked_peers: HashSet::new(),
+            ban_peer_sender,
+            held_messages: HeldMessages::new(
+                config.disconnected_peer_grace_period,
+                config.max_held_messages_per_peer,
+                config.max_held_bytes_per_peer,
+            ),
             gossip_message_sender,
             max_gossip_message_size: config.max_gossip_message_size,
+            gossip_state_request_sender,
             shutdown_sender: Some(server_shutdown_sender),
             server_join_handle,
             net_metrics,
@@ -322,26 +353,324 @@ impl<REv: ReactorEventT<P>, P: PayloadT> Network<REv, P> {
             }
         };
 
-        let _ = self.peers.insert(peer_id, endpoint);
+        let is_new_peer = self.peers.insert(peer_id, endpoint).is_none();
+
+        // Flush any messages held while the peer was disconnected.
+        for payload in self.held_messages.peer_connected(&peer_id) {
+            if let Ok(outgoing_message) = self.new_outgoing_message(peer_id, &payload) {
+                self.queue_outgoing_message(outgoing_message);
+            }
+        }
 
         self.net_metrics.peers.set(self.peers.len() as i64);
         // TODO - see if this can be removed.  The announcement is only used by the joiner reactor.
-        effect_builder.announce_new_peer(peer_id).ignore()
+        let mut effects = effect_builder.announce_new_peer(peer_id).ignore();
+        if is_new_peer {
+            effects.extend(
+                effect_builder
+                    .announce_peer_count_changed(self.peers.len())
+                    .ignore(),
+            );
+            // Validator keys are not exchanged by this component.
+            effects.extend(
+                effect_builder
+                    .announce_peer_connected(peer_id, None)
+                    .ignore(),
+            );
+        }
+        effects
+    }
+
+    fn handle_connection_closed(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        peer_id: NodeId,
+        num_established: u32,
+    ) -> Effects<Event<P>> {
+        if num_established != 0 {
+            return Effects::new();
+        }
+
+        // Offenses are only counted for the lifetime of the peer's connection.
+        self.oversized_message_senders.remove(&peer_id);
+        self.refused_substream_peers.remove(&peer_id);
+
+        let mut effects = if self.peers.remove(&peer_id).is_some() {
+            let mut effects = effect_builder
+                .announce_peer_count_changed(self.peers.len())
+                .ignore();
+            effects.extend(
+                effect_builder
+                    .announce_peer_disconnected(peer_id, None)
+                    .ignore(),
+            );
+            effects
+        } else {
+            Effects::new()
+        };
+
+        // Start holding one-way messages for the peer in case it reconnects shortly.
+        if let Some(grace_period) = self.held_messages.grace_period() {
+            self.held_messages
+                .peer_disconnected(peer_id, Timestamp::now());
+            effects.extend(
+                effect_builder
+                    .set_timeout(grace_period.into())
+                    .event(move |_| Event::HoldingPeriodExpired {
+                        peer_id: Box::new(peer_id),
+                    }),
+            );
+        }
+        effects
+    }
+
+    /// Drops any messages held for the peer if its grace period has lapsed, announcing each as
+    /// having failed to send.
+    fn handle_holding_period_expired(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        peer_id: NodeId,
+    ) -> Effects<Event<P>> {
+        let expired = self.held_messages.expire(&peer_id, Timestamp::now());
+        if !expired.is_empty() {
+            debug!(%peer_id, count = expired.len(), "{}: dropping held messages", self.our_id);
+        }
+        let error = Error::PeerDisconnected { peer_id };
+        expired
+            .into_iter()
+            .flat_map(|payload| {
+                effect_builder
+                    .announce_message_send_failed(peer_id, payload, error.kind())
+                    .ignore()
+            })
+            .collect()
+    }
+
+    /// Records that the peer supports none of our protocols, dropping any messages held for it.
+    ///
+    /// If we dialed the peer, its address is marked so that it is never redialed.
+    fn handle_incompatible_peer(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        peer_id: NodeId,
+    ) -> Effects<Event<P>> {
+        if !self.incompatible_peers.insert(peer_id) {
+            return Effects::new();
+        }
+        let error = Error::IncompatiblePeer { peer_id };
+        warn!(
+            err = display_error(&error),
+            "{}: excluding peer", self.our_id
+        );
+
+        if let Some(ConnectedPoint::Dialer { address }) = self.peers.get(&peer_id) {
+            match self.known_addresses_mut.lock() {
+                Ok(mut known_addresses) => {
+                    if let Some(state) = known_addresses.get_mut(address) {
+                        *state = ConnectionState::Incompatible;
+                    }
+                }
+                Err(err) => {
+                    return fatal!(
+                        effect_builder,
+                        "Could not acquire `known_addresses_mut` mutex: {:?}",
+                        err
+                    )
+                    .ignore()
+                }
+            }
+        }
+
+        self.held_messages
+            .discard(&peer_id)
+            .into_iter()
+            .flat_map(|payload| {
+                effect_builder
+                    .announce_message_send_failed(peer_id, payload, error.kind())
+                    .ignore()
+            })
+            .collect()
+    }
+
+    /// Records that the peer sent a message exceeding the size limit for its class, announcing it
+    /// as having committed an offense once it has done so `MAX_OVERSIZED_MESSAGES_PER_PEER` times.
+    fn handle_oversized_message_received(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        sender: NodeId,
+        max_size: u32,
+        actual_size: u64,
+    ) -> Effects<Event<P>> {
+        let count = self.oversized_message_senders.entry(sender).or_default();
+        *count += 1;
+        debug!(
+            %sender,
+            %max_size,
+            %actual_size,
+            count = *count,
+            "{}: received oversized message",
+            self.our_id
+        );
+        if *count < MAX_OVERSIZED_MESSAGES_PER_PEER {
+            return Effects::new();
+        }
+
+        self.oversized_message_senders.remove(&sender);
+        warn!(%sender, "{}: peer repeatedly sent oversized messages", self.our_id);
+        effect_builder
+            .announce_disconnect_from_peer(sender)
+            .ignore()
+    }
+
+    /// Records that an inbound substream opened by the peer was refused, announcing it as having
+    /// committed an offense once this has happened `MAX_REFUSED_SUBSTREAMS_PER_PEER` times.
+    fn handle_inbound_substream_refused(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        peer_id: NodeId,
+    ) -> Effects<Event<P>> {
+        let count = self.refused_substream_peers.entry(peer_id).or_default();
+        *count += 1;
+        if *count < MAX_REFUSED_SUBSTREAMS_PER_PEER {
+            return Effects::new();
+        }
+
+        self.refused_substream_peers.remove(&peer_id);
+        warn!(%peer_id, "{}: peer repeatedly exceeded inbound substream limits", self.our_id);
+        effect_builder
+            .announce_disconnect_from_peer(peer_id)
+            .ignore()
+    }
+
+    /// Bans the peer which committed an offense, dropping any messages held for it.
+    fn handle_offense_committed(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        peer_id: NodeId,
+    ) -> Effects<Event<P>> {
+        let libp2p_peer_id = match peer_id {
+            NodeId::P2p(libp2p_peer_id) => libp2p_peer_id,
+            NodeId::Tls(_) => {
+                debug!(%peer_id, "{}: ignoring offense by non-libp2p peer", self.our_id);
+                return Effects::new();
+            }
+        };
+        if !self.blocked_peers.insert(peer_id) {
+            return Effects::new();
+        }
+        let error = Error::PeerBlocked { peer_id };
+        warn!(
+            err = display_error(&error),
+            "{}: banning peer after offense", self.our_id
+        );
+
+        if self.ban_peer_sender.send(libp2p_peer_id).is_err() {
+            debug!("{}: dropped peer ban during shutdown", self.our_id);
+        }
+
+        self.held_messages
+            .discard(&peer_id)
+            .into_iter()
+            .flat_map(|payload| {
+                effect_builder
+                    .announce_message_send_failed(peer_id, payload, error.kind())
+                    .ignore()
+            })
+            .collect()
     }
 
     /// Queues a message to be sent to a specific node.
-    fn send_message(&self, destination: NodeId, payload: P) {
-        let outgoing_message = match OneWayOutgoingMessage::new(
-            destination,
-            &payload,
-            self.max_one_way_message_size,
-        ) {
-            Ok(msg) => msg,
+    ///
+    /// If the node has recently disconnected, the message is held until it either reconnects or
+    /// its grace period lapses.
+    fn send_message(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        destination: NodeId,
+        payload: P,
+    ) -> Effects<Event<P>> {
+        if self.incompatible_peers.contains(&destination) {
+            let error = Error::IncompatiblePeer {
+                peer_id: destination,
+            };
+            return effect_builder
+                .announce_message_send_failed(destination, payload, error.kind())
+                .ignore();
+        }
+        if self.blocked_peers.contains(&destination) {
+            let error = Error::PeerBlocked {
+                peer_id: destination,
+            };
+            return effect_builder
+                .announce_message_send_failed(destination, payload, error.kind())
+                .ignore();
+        }
+
+        let outgoing_message = match self.new_outgoing_message(destination, &payload) {
+            Ok(outgoing_message) => outgoing_message,
             Err(error) => {
-                warn!(%error, %payload, "{}: failed to construct outgoing message", self.our_id);
-                return;
+                return effect_builder
+                    .announce_message_send_failed(destination, payload, error.kind())
+                    .ignore()
             }
         };
+
+        if self.peers.contains_key(&destination) {
+            self.queue_outgoing_message(outgoing_message);
+            return Effects::new();
+        }
+
+        match self.held_messages.hold(
+            &destination,
+            payload,
+            outgoing_message.message.len(),
+            Timestamp::now(),
+        ) {
+            HoldOutcome::Held => {
+                trace!(%destination, "{}: holding message for disconnected peer", self.our_id);
+                Effects::new()
+            }
+            HoldOutcome::NotApplicable(_) => {
+                self.queue_outgoing_message(outgoing_message);
+                Effects::new()
+            }
+            HoldOutcome::Rejected(payload) => {
+                debug!(%destination, "{}: unable to hold message for disconnected peer", self.our_id);
+                let error = Error::PeerDisconnected {
+                    peer_id: destination,
+                };
+                effect_builder
+                    .announce_message_send_failed(destination, payload, error.kind())
+                    .ignore()
+            }
+        }
+    }
+
+    /// Serializes the payload into a one-way message for the given destination, logging on error.
+    fn new_outgoing_message(
+        &self,
+        destination: NodeId,
+        payload: &P,
+    ) -> Result<OneWayOutgoingMessage, Error> {
+        OneWayOutgoingMessage::new(
+            destination,
+            payload,
+            self.max_one_way_message_size,
+            &self.chainspec,
+        )
+        .map_err(|error| {
+            warn!(
+                err = display_error(&error),
+                %payload,
+                "{}: failed to construct outgoing message",
+                self.our_id
+            );
+            error
+        })
+    }
+
+    /// Passes a one-way message to the server task for sending.
+    fn queue_outgoing_message(&self, outgoing_message: OneWayOutgoingMessage) {
         if let Err(error) = self.one_way_message_sender.send_datasized(outgoing_message) {
             warn!(%error, "{}: dropped outgoing message, server has shut down", self.our_id);
         } else {
@@ -355,7 +684,12 @@ impl<REv: ReactorEventT<P>, P: PayloadT> Network<REv, P> {
         let gossip_message = match GossipMessage::new(&payload, self.max_gossip_message_size) {
             Ok(msg) => msg,
             Err(error) => {
-                warn!(%error, %payload, "{}: failed to construct new gossip message", self.our_id);
+                warn!(
+                    err = display_error(&error),
+                    %payload,
+                    "{}: failed to construct new gossip message",
+                    self.our_id
+                );
                 return;
             }
         };
@@ -375,7 +709,11 @@ impl<REv: ReactorEventT<P>, P: PayloadT> Network<REv, P> {
         let peer_ids = self
             .peers
             .keys()
-            .filter(|&peer_id| !exclude.contains(peer_id))
+            .filter(|&peer_id| {
+                !exclude.contains(peer_id)
+                    && !self.incompatible_peers.contains(peer_id)
+                    && !self.blocked_peers.contains(peer_id)
+            })
             .choose_multiple(rng, count);
 
         if peer_ids.len() != count {
@@ -390,19 +728,16 @@ impl<REv: ReactorEventT<P>, P: PayloadT> Network<REv, P> {
             );
         }
 
+        // Gossip has its own redundancy, so messages are never held for disconnected peers here.
         for &peer_id in &peer_ids {
-            self.send_message(*peer_id, payload.clone());
+            if let Ok(outgoing_message) = self.new_outgoing_message(*peer_id, &payload) {
+                self.queue_outgoing_message(outgoing_message);
+            }
         }
 
         peer_ids.into_iter().copied().collect()
     }
 
-    /// Returns the node id of this network node.
-    #[cfg(test)]
-    pub(crate) fn node_id(&self) -> NodeId {
-        self.our_id
-    }
-
     /// Returns the set of known addresses.
     #[cfg(test)]
     pub(crate) fn seen_peers(&self) -> &HashSet<PeerId> {
@@ -410,6 +745,49 @@ impl<REv: ReactorEventT<P>, P: PayloadT> Network<REv, P> {
     }
 }
 
+/// Constructs a new swarm to manage peers and events.
+fn new_swarm(
+    config: &Config,
+    network_identity: &NetworkIdentity,
+    net_metrics: &NetworkingMetrics,
+    chainspec: &Chainspec,
+) -> Result<Swarm<Behavior>, Error> {
+    // Create a keypair for authenticated encryption of the transport.
+    let noise_keys = noise::Keypair::<X25519Spec>::new()
+        .into_authentic(&network_identity.keypair)
+        .map_err(Error::StaticKeypairSigning)?;
+
+    let mut mplex_config = MplexConfig::default();
+    mplex_config.set_max_buffer_behaviour(MaxBufferBehaviour::Block);
+
+    // Create a tokio-based TCP transport.  Use `noise` for authenticated encryption and `mplex` for
+    // multiplexing of substreams on a TCP stream.
+    let transport = TokioTcpConfig::new()
+        .nodelay(true)
+        .upgrade(upgrade::Version::V1)
+        .authenticate(NoiseConfig::xx(noise_keys).into_authenticated())
+        .multiplex(MplexConfig::default())
+        .timeout(config.connection_setup_timeout.into())
+        .boxed();
+
+    let behavior = Behavior::new(
+        config,
+        net_metrics,
+        chainspec,
+        network_identity.keypair.public(),
+    );
+    // Inbound substreams are limited by the behavior itself, as libp2p has no such limit.
+    let connection_limits = ConnectionLimits::default()
+        .with_max_pending_incoming(Some(config.max_pending_incoming_connections));
+    let swarm = SwarmBuilder::new(transport, behavior, PeerId::from(network_identity))
+        .executor(Box::new(|future| {
+            tokio::spawn(future);
+        }))
+        .connection_limits(connection_limits)
+        .build();
+    Ok(swarm)
+}
+
 fn our_id(swarm: &Swarm<Behavior>) -> NodeId {
     NodeId::P2p(*Swarm::local_peer_id(swarm))
 }
@@ -422,11 +800,16 @@ async fn server_task<REv: ReactorEventT<P>, P: PayloadT>(
     mut one_way_outgoing_message_receiver: CountingReceiver<OneWayOutgoingMessage>,
     // Receives new gossip messages to be sent out via libp2p.
     mut gossip_message_receiver: CountingReceiver<GossipMessage>,
+    // Receives requests for a snapshot of the gossipsub state.
+    mut gossip_state_request_receiver: mpsc::UnboundedReceiver<Responder<Option<GossipState>>>,
+    // Receives peers to be banned from connecting to us.
+    mut ban_peer_receiver: mpsc::UnboundedReceiver<PeerId>,
     // Receives notification to shut down the server loop.
     mut shutdown_receiver: watch::Receiver<()>,
     mut swarm: Swarm<Behavior>,
     known_addresses_mut: Arc<Mutex<HashMap<Multiaddr, ConnectionState>>>,
     is_bootstrap_node: bool,
+    chainspec: Arc<Chainspec>,
     queued_messages: IntGauge,
 ) {
     //let our_id = our
@@ -444,7 +827,8 @@ async fn server_task<REv: ReactorEventT<P>, P: PayloadT>(
                         event_queue,
                         swarm_event,
                         &known_addresses_mut,
-                        is_bootstrap_node
+                        is_bootstrap_node,
+                        &chainspec,
                     )
                     .await;
                 }
@@ -483,6 +867,38 @@ async fn server_task<REv: ReactorEventT<P>, P: PayloadT>(
                     }
                 }
 
+                // `UnboundedReceiver::recv()` is cancellation safe - see
+                // https://tokio.rs/tokio/tutorial/select#cancellation
+                maybe_responder = gossip_state_request_receiver.recv() => {
+                    match maybe_responder {
+                        Some(responder) => {
+                            // Take the snapshot before awaiting, so the swarm isn't borrowed
+                            // across the await point.
+                            let gossip_state = swarm.gossip_state();
+                            responder.respond(Some(gossip_state)).await
+                        }
+                        None => {
+                            // The data sender has been dropped - exit the loop.
+                            info!("{}: exiting network server task", our_id(&swarm));
+                            break;
+                        }
+                    }
+                }
+
+                // `UnboundedReceiver::recv()` is cancellation safe - see
+                // https://tokio.rs/tokio/tutorial/select#cancellation
+                maybe_peer_id = ban_peer_receiver.recv() => {
+                    match maybe_peer_id {
+                        // Banning the peer also closes any existing connections to it.
+                        Some(peer_id) => Swarm::ban_peer_id(&mut swarm, peer_id),
+                        None => {
+                            // The data sender has been dropped - exit the loop.
+                            info!("{}: exiting network server task", our_id(&swarm));
+                            break;
+                        }
+                    }
+                }
+
                 maybe_shutdown = shutdown_receiver.changed() => {
                     // Since a `watch` channel is always constructed with an initial value enqueued,
                     // ignore this (and any others) from the `shutdown_receiver`.
@@ -506,6 +922,7 @@ async fn handle_swarm_event<REv: ReactorEventT<P>, P: PayloadT, E: Display>(
     swarm_event: SwarmEvent<SwarmBehaviorEvent, E>,
     known_addresses_mut: &Arc<Mutex<HashMap<Multiaddr, ConnectionState>>>,
     is_bootstrap_node: bool,
+    chainspec: &Chainspec,
 ) {
     let event = match swarm_event {
         SwarmEvent::ConnectionEstablished {
@@ -587,7 +1004,10 @@ async fn handle_swarm_event<REv: ReactorEventT<P>, P: PayloadT, E: Display>(
                             panic!("Could not acquire `known_addresses_mut` mutex: {:?}", err)
                         }
                         Ok(known_addresses) => {
-                            for address in known_addresses.keys() {
+                            for (address, state) in known_addresses.iter() {
+                                if *state == ConnectionState::Incompatible {
+                                    continue;
+                                }
                                 let our_id = our_id(swarm);
                                 debug!(%our_id, %address, "dialing known address");
                                 Swarm::dial_addr(swarm, address.clone()).unwrap_or_else(|err| {
@@ -609,7 +1029,7 @@ async fn handle_swarm_event<REv: ReactorEventT<P>, P: PayloadT, E: Display>(
         }
         SwarmEvent::ListenerError { error } => Event::ListenerError { error },
         SwarmEvent::Behaviour(SwarmBehaviorEvent::OneWayMessaging(event)) => {
-            return handle_one_way_messaging_event(swarm, event_queue, event).await;
+            return handle_one_way_messaging_event(swarm, event_queue, event, chainspec).await;
         }
         SwarmEvent::Behaviour(SwarmBehaviorEvent::Gossiper(event)) => {
             return handle_gossip_event(swarm, event_queue, event).await;
@@ -626,6 +1046,24 @@ async fn handle_swarm_event<REv: ReactorEventT<P>, P: PayloadT, E: Display>(
         SwarmEvent::Behaviour(SwarmBehaviorEvent::Identify(event)) => {
             return handle_identify_event(swarm, event);
         }
+        SwarmEvent::Behaviour(SwarmBehaviorEvent::PeerAddressesAnnounced {
+            peer_id,
+            addresses,
+        }) => {
+            debug!(
+                %peer_id,
+                "{}: peer announced its addresses {}",
+                our_id(swarm),
+                DisplayIter::new(addresses.iter())
+            );
+            return;
+        }
+        SwarmEvent::Behaviour(SwarmBehaviorEvent::InboundSubstreamRefused { peer_id }) => {
+            debug!(%peer_id, "{}: refused inbound substream", our_id(swarm));
+            Event::InboundSubstreamRefused {
+                peer_id: Box::new(NodeId::from(peer_id)),
+            }
+        }
         SwarmEvent::IncomingConnection { .. }
         | SwarmEvent::IncomingConnectionError { .. }
         | SwarmEvent::BannedPeer { .. }
@@ -641,13 +1079,14 @@ async fn handle_swarm_event<REv: ReactorEventT<P>, P: PayloadT, E: Display>(
 fn network_is_isolated(known_addresses: &HashMap<Multiaddr, ConnectionState>) -> bool {
     known_addresses
         .values()
-        .all(|state| *state == ConnectionState::Failed)
+        .all(|state| *state == ConnectionState::Failed || *state == ConnectionState::Incompatible)
 }
 
 async fn handle_one_way_messaging_event<REv: ReactorEventT<P>, P: PayloadT>(
     swarm: &mut Swarm<Behavior>,
     event_queue: EventQueueHandle<REv>,
     event: RequestResponseEvent<Vec<u8>, ()>,
+    chainspec: &Chainspec,
 ) {
     match event {
         RequestResponseEvent::Message {
@@ -657,7 +1096,7 @@ async fn handle_one_way_messaging_event<REv: ReactorEventT<P>, P: PayloadT>(
             // We've received a one-way request from a peer: announce it via the reactor on the
             // `NetworkIncoming` queue.
             let sender = NodeId::from(peer);
-            match bincode::deserialize::<P>(&request) {
+            match one_way_messaging::deserialize_incoming::<P>(&request, chainspec) {
                 Ok(payload) => {
                     debug!(%sender, %payload, "{}: incoming one-way message received", our_id(swarm));
                     event_queue
@@ -667,10 +1106,32 @@ async fn handle_one_way_messaging_event<REv: ReactorEventT<P>, P: PayloadT>(
                         )
                         .await;
                 }
+                Err(Error::MessageTooLarge {
+                    max_size,
+                    actual_size,
+                }) => {
+                    warn!(
+                        %sender,
+                        %max_size,
+                        %actual_size,
+                        "{}: dropping oversized incoming one-way message",
+                        our_id(swarm)
+                    );
+                    event_queue
+                        .schedule(
+                            Event::OversizedMessageReceived {
+                                sender: Box::new(sender),
+                                max_size,
+                                actual_size,
+                            },
+                            QueueKind::NetworkIncoming,
+                        )
+                        .await;
+                }
                 Err(error) => {
                     warn!(
                         %sender,
-                        %error,
+                        err = display_error(&error),
                         "{}: failed to deserialize incoming one-way message",
                         our_id(swarm)
                     );
@@ -685,6 +1146,28 @@ async fn handle_one_way_messaging_event<REv: ReactorEventT<P>, P: PayloadT>(
             // sent, since `RequestResponseCodec::read_response` for the one-way Codec does not
             // actually read anything from the given I/O stream.
         }
+        RequestResponseEvent::OutboundFailure {
+            peer,
+            request_id: _,
+            error: OutboundFailure::UnsupportedProtocols,
+        }
+        | RequestResponseEvent::InboundFailure {
+            peer,
+            request_id: _,
+            error: InboundFailure::UnsupportedProtocols,
+        } => {
+            // The peer can never exchange one-way messages with us: ban it so we neither accept
+            // nor make further connections to it.
+            Swarm::ban_peer_id(swarm, peer);
+            event_queue
+                .schedule(
+                    Event::IncompatiblePeer {
+                        peer_id: Box::new(NodeId::from(peer)),
+                    },
+                    QueueKind::Network,
+                )
+                .await;
+        }
         RequestResponseEvent::OutboundFailure {
             peer,
             request_id,
@@ -739,6 +1222,7 @@ async fn handle_gossip_event<REv: ReactorEventT<P>, P: PayloadT>(
                 Some(source) => NodeId::from(source),
                 None => {
                     warn!(sender=%propagation_source, ?message, "{}: libp2p gossiped message without source", our_id(swarm));
+                    swarm.reject_gossip_message(&message.topic);
                     return;
                 }
             };
@@ -759,6 +1243,7 @@ async fn handle_gossip_event<REv: ReactorEventT<P>, P: PayloadT>(
                         "{}: failed to deserialize gossiped message",
                         our_id(swarm)
                     );
+                    swarm.reject_gossip_message(&message.topic);
                 }
             }
         }
@@ -851,7 +1336,7 @@ impl<REv: Send + 'static, P: Send + 'static> Finalize for Network<REv, P> {
                     Ok(_) => debug!("{}: server exited cleanly", self.our_id),
                     Err(err) => error!(%err, "{}: could not join server task cleanly", self.our_id),
                 }
-            } else if env::var(ENABLE_LIBP2P_NET_ENV_VAR).is_ok() {
+            } else {
                 warn!("{}: server shutdown while already shut down", self.our_id)
             }
         }
@@ -898,15 +1383,14 @@ impl<REv: ReactorEventT<P>, P: PayloadT> Component<REv> for Network<REv, P> {
                 num_established,
                 cause,
             } => {
-                if num_established == 0 {
-                    let _ = self.peers.remove(&peer_id);
-                }
                 debug!(%peer_id, ?endpoint, %num_established, ?cause, "{}: connection closed", self.our_id);
+                let effects =
+                    self.handle_connection_closed(effect_builder, *peer_id, num_established);
 
                 // Note: We count multiple connections to the same peer as a single connection.
                 self.net_metrics.peers.set(self.peers.len() as i64);
 
-                Effects::new()
+                effects
             }
             Event::UnreachableAddress {
                 peer_id,
@@ -965,60 +1449,101 @@ impl<REv: ReactorEventT<P>, P: PayloadT> Component<REv> for Network<REv, P> {
                 Effects::new()
             }
 
-            Event::NetworkRequest {
-                request:
-                    NetworkRequest::SendMessage {
-                        dest,
-                        payload,
-                        responder,
-                    },
-            } => {
-                self.send_message(*dest, *payload);
-                responder.respond(()).ignore()
+            Event::HoldingPeriodExpired { peer_id } => {
+                self.handle_holding_period_expired(effect_builder, *peer_id)
             }
-            Event::NetworkRequest {
-                request: NetworkRequest::Broadcast { payload, responder },
-            } => {
-                self.net_metrics.broadcast_requests.inc();
-                self.gossip_message(*payload);
-                responder.respond(()).ignore()
+            Event::OversizedMessageReceived {
+                sender,
+                max_size,
+                actual_size,
+            } => self.handle_oversized_message_received(
+                effect_builder,
+                *sender,
+                max_size,
+                actual_size,
+            ),
+            Event::InboundSubstreamRefused { peer_id } => {
+                self.handle_inbound_substream_refused(effect_builder, *peer_id)
+            }
+            Event::IncompatiblePeer { peer_id } => {
+                self.handle_incompatible_peer(effect_builder, *peer_id)
+            }
+
+            Event::NetworkRequest { request } => {
+                network_provider::handle_network_request(self, effect_builder, rng, request)
+            }
+            Event::NetworkInfoRequest { info_request } => {
+                network_provider::handle_network_info_request(self, info_request)
+            }
+
+            Event::BlocklistAnnouncement(BlocklistAnnouncement::OffenseCommitted(peer_id)) => {
+                self.handle_offense_committed(effect_builder, *peer_id)
+            }
+        }
+    }
+}
+
+impl<REv: ReactorEventT<P>, P: PayloadT> NetworkProvider<REv, P> for Network<REv, P> {
+    fn node_id(&self) -> NodeId {
+        self.our_id
+    }
+
+    fn send_one_way_message(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        dest: NodeId,
+        payload: P,
+    ) -> Effects<Event<P>> {
+        self.net_metrics.direct_message_requests.inc();
+        self.send_message(effect_builder, dest, payload)
+    }
+
+    fn broadcast_payload(&mut self, payload: P) {
+        self.net_metrics.broadcast_requests.inc();
+        self.gossip_message(payload);
+    }
+
+    fn gossip_payload(
+        &mut self,
+        rng: &mut NodeRng,
+        payload: P,
+        count: usize,
+        // This network component doesn't know which peers are validators.
+        _min_validators: usize,
+        exclude: HashSet<NodeId>,
+    ) -> HashSet<NodeId> {
+        self.send_message_to_n_peers(rng, payload, count, exclude)
+    }
+
+    fn peers(&self) -> BTreeMap<NodeId, String> {
+        self.peers
+            .iter()
+            .map(|(node_id, endpoint)| (*node_id, endpoint.get_remote_address().to_string()))
+            .collect()
+    }
+
+    fn listening_addresses(&self) -> Vec<String> {
+        self.listening_addresses
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    fn dial_backoffs(&self) -> Vec<DialBackoff> {
+        // Dial backoff is tracked by the small network only.
+        Vec::new()
+    }
+
+    fn request_gossip_state(&self, responder: Responder<Option<GossipState>>) -> Effects<Event<P>> {
+        match self.gossip_state_request_sender.send(responder) {
+            Ok(()) => Effects::new(),
+            Err(mpsc::error::SendError(responder)) => {
+                warn!(
+                    "{}: cannot get gossip state, server has shut down",
+                    self.our_id
+                );
+                responder.respond(None).ignore()
             }
-            Event::NetworkRequest { request } => match request {
-                NetworkRequest::SendMessage {
-                    dest,
-                    payload,
-                    responder,
-                } => {
-                    self.net_metrics.direct_message_requests.inc();
-                    self.send_message(*dest, *payload);
-                    responder.respond(()).ignore()
-                }
-                NetworkRequest::Broadcast { payload, responder } => {
-                    self.gossip_message(*payload);
-                    responder.respond(()).ignore()
-                }
-                NetworkRequest::Gossip {
-                    payload,
-                    count,
-                    exclude,
-                    responder,
-                } => {
-                    let sent_to = self.send_message_to_n_peers(rng, *payload, count, exclude);
-                    responder.respond(sent_to).ignore()
-                }
-            },
-            Event::NetworkInfoRequest { info_request } => match info_request {
-                NetworkInfoRequest::GetPeers { responder } => {
-                    let peers = self
-                        .peers
-                        .iter()
-                        .map(|(node_id, endpoint)| {
-                            (*node_id, endpoint.get_remote_address().to_string())
-                        })
-                        .collect();
-                    responder.respond(peers).ignore()
-                }
-            },
         }
     }
 }
@@ -0,0 +1,145 @@
+//! This module is home to types/functions related to gossiping our own addresses, used to let peers
+//! learn promptly when the addresses on which we can be reached change.
+
+use std::{
+    collections::HashSet,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use libp2p::{
+    core::connection::ConnectionId,
+    gossipsub::IdentTopic,
+    swarm::{
+        protocols_handler::DummyProtocolsHandler, NetworkBehaviour, NetworkBehaviourAction,
+        PollParameters, ProtocolsHandler,
+    },
+    Multiaddr, PeerId,
+};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tokio::time::{self, Sleep};
+
+use super::Config;
+
+pub(super) static TOPIC: Lazy<IdentTopic> = Lazy::new(|| IdentTopic::new("addresses".to_string()));
+
+/// The full set of addresses on which the gossiping node can be reached.
+///
+/// Any address previously announced by the node but not included is considered withdrawn.
+#[derive(Serialize, Deserialize, Debug)]
+pub(super) struct AddressAnnouncement {
+    pub(super) addresses: Vec<Multiaddr>,
+}
+
+/// The event emitted by the `AddressTracker` when the set of our addresses has changed.
+#[derive(Debug)]
+pub(super) struct OurAddressesChanged(pub Vec<Multiaddr>);
+
+/// A behavior which tracks our listening addresses and confirmed external addresses.
+///
+/// It emits the full set of addresses whenever it changes, but no more than once per
+/// `min_address_gossip_interval`.
+pub(super) struct AddressTracker {
+    listening_addresses: HashSet<Multiaddr>,
+    external_addresses: HashSet<Multiaddr>,
+    /// The set of addresses most recently emitted.
+    announced_addresses: HashSet<Multiaddr>,
+    /// Whether the tracked addresses have changed since they were last emitted.
+    changed: bool,
+    min_interval: Duration,
+    /// Pending until `min_interval` has elapsed since the addresses were last emitted.
+    debounce_timer: Option<Pin<Box<Sleep>>>,
+}
+
+impl AddressTracker {
+    pub(super) fn new(config: &Config) -> Self {
+        AddressTracker {
+            listening_addresses: HashSet::new(),
+            external_addresses: HashSet::new(),
+            announced_addresses: HashSet::new(),
+            changed: false,
+            min_interval: config.min_address_gossip_interval.into(),
+            debounce_timer: None,
+        }
+    }
+}
+
+impl NetworkBehaviour for AddressTracker {
+    type ProtocolsHandler = DummyProtocolsHandler;
+    type OutEvent = OurAddressesChanged;
+
+    fn new_handler(&mut self) -> Self::ProtocolsHandler {
+        DummyProtocolsHandler::default()
+    }
+
+    fn addresses_of_peer(&mut self, _peer_id: &PeerId) -> Vec<Multiaddr> {
+        Vec::new()
+    }
+
+    fn inject_connected(&mut self, _peer_id: &PeerId) {}
+
+    fn inject_disconnected(&mut self, _peer_id: &PeerId) {}
+
+    fn inject_event(
+        &mut self,
+        _peer_id: PeerId,
+        _connection: ConnectionId,
+        event: <DummyProtocolsHandler as ProtocolsHandler>::OutEvent,
+    ) {
+        match event {}
+    }
+
+    fn inject_new_listen_addr(&mut self, address: &Multiaddr) {
+        self.changed |= self.listening_addresses.insert(address.clone());
+    }
+
+    fn inject_expired_listen_addr(&mut self, address: &Multiaddr) {
+        self.changed |= self.listening_addresses.remove(address);
+    }
+
+    fn inject_new_external_addr(&mut self, address: &Multiaddr) {
+        self.changed |= self.external_addresses.insert(address.clone());
+    }
+
+    fn poll(
+        &mut self,
+        context: &mut Context<'_>,
+        _parameters: &mut impl PollParameters,
+    ) -> Poll<
+        NetworkBehaviourAction<
+            <DummyProtocolsHandler as ProtocolsHandler>::InEvent,
+            Self::OutEvent,
+        >,
+    > {
+        if let Some(timer) = self.debounce_timer.as_mut() {
+            if timer.as_mut().poll(context).is_pending() {
+                return Poll::Pending;
+            }
+            self.debounce_timer = None;
+        }
+
+        if !self.changed {
+            return Poll::Pending;
+        }
+        self.changed = false;
+
+        // Only emit an event if the set differs materially from the one last emitted, e.g. not if
+        // an address expired and was subsequently re-added.
+        let addresses = self
+            .listening_addresses
+            .union(&self.external_addresses)
+            .cloned()
+            .collect::<HashSet<_>>();
+        if addresses == self.announced_addresses {
+            return Poll::Pending;
+        }
+        self.announced_addresses = addresses;
+        self.debounce_timer = Some(Box::pin(time::sleep(self.min_interval)));
+
+        let event = OurAddressesChanged(self.announced_addresses.iter().cloned().collect());
+        Poll::Ready(NetworkBehaviourAction::GenerateEvent(event))
+    }
+}
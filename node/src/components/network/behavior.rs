@@ -1,27 +1,33 @@
 use std::{
-    collections::VecDeque,
+    collections::{BTreeMap, HashMap, VecDeque},
     task::{Context, Poll},
 };
 
 use derive_more::From;
 use libp2p::{
     core::PublicKey,
-    gossipsub::{Gossipsub, GossipsubEvent},
+    gossipsub::{error::PublishError, Gossipsub, GossipsubEvent, GossipsubMessage, TopicHash},
     identify::{Identify, IdentifyEvent},
     kad::{record::store::MemoryStore, Kademlia, KademliaEvent},
     request_response::{RequestResponse, RequestResponseEvent},
-    swarm::{NetworkBehaviourAction, NetworkBehaviourEventProcess, PollParameters},
+    swarm::{
+        NetworkBehaviour as _, NetworkBehaviourAction, NetworkBehaviourEventProcess, PollParameters,
+    },
     Multiaddr, NetworkBehaviour, PeerId,
 };
 use tracing::{debug, trace, warn};
 
 use super::{
+    address_gossip::{self, AddressAnnouncement, AddressTracker, OurAddressesChanged},
     gossip::{self, TOPIC},
-    one_way_messaging, peer_discovery, Config, GossipMessage, OneWayCodec, OneWayOutgoingMessage,
+    one_way_messaging, peer_discovery,
+    substream_limits::{SubstreamLimiter, SubstreamLimiterEvent},
+    Config, GossipMessage, OneWayCodec, OneWayOutgoingMessage,
 };
 use crate::{
     components::networking_metrics::NetworkingMetrics,
-    types::{Chainspec, NodeId},
+    types::{Chainspec, GossipState, GossipTopicState, NodeId},
+    utils::DisplayIter,
 };
 
 /// An enum defining the top-level events passed to the swarm's handler.  This will be received in
@@ -32,6 +38,27 @@ pub(super) enum SwarmBehaviorEvent {
     Gossiper(GossipsubEvent),
     Kademlia(KademliaEvent),
     Identify(IdentifyEvent),
+    /// A peer has gossiped the full set of addresses on which it can be reached.
+    #[from(ignore)]
+    PeerAddressesAnnounced {
+        peer_id: PeerId,
+        addresses: Vec<Multiaddr>,
+    },
+    /// An inbound one-way message substream opened by a peer was refused as a limit on concurrent
+    /// inbound substreams had been reached.
+    #[from(ignore)]
+    InboundSubstreamRefused {
+        peer_id: PeerId,
+    },
+}
+
+/// Counters of the gossip messages published and received on a single topic since startup.
+#[derive(Clone, Copy, Debug, Default)]
+struct GossipCounters {
+    published: u64,
+    publish_failures: u64,
+    received: u64,
+    rejected: u64,
 }
 
 /// The top-level behavior used in the libp2p swarm.  It holds all subordinate behaviors required to
@@ -39,12 +66,16 @@ pub(super) enum SwarmBehaviorEvent {
 #[derive(NetworkBehaviour)]
 #[behaviour(out_event = "SwarmBehaviorEvent", poll_method = "custom_poll")]
 pub(super) struct Behavior {
-    one_way_message_behavior: RequestResponse<OneWayCodec>,
+    one_way_message_behavior: SubstreamLimiter<RequestResponse<OneWayCodec>>,
     gossip_behavior: Gossipsub,
     kademlia_behavior: Kademlia<MemoryStore>,
     identify_behavior: Identify,
+    address_tracker: AddressTracker,
     #[behaviour(ignore)]
     our_id: NodeId,
+    /// Counters of the gossip messages published and received on each topic.
+    #[behaviour(ignore)]
+    gossip_counters: HashMap<TopicHash, GossipCounters>,
     /// Events generated by the behavior that are pending a poll.
     #[behaviour(ignore)]
     events: VecDeque<SwarmBehaviorEvent>,
@@ -57,8 +88,10 @@ impl Behavior {
         chainspec: &Chainspec,
         our_public_key: PublicKey,
     ) -> Self {
-        let one_way_message_behavior =
-            one_way_messaging::new_behavior(config, net_metrics, chainspec);
+        let one_way_message_behavior = SubstreamLimiter::new(
+            config,
+            one_way_messaging::new_behavior(config, net_metrics, chainspec),
+        );
 
         let gossip_behavior = gossip::new_behavior(config, chainspec, our_public_key.clone());
 
@@ -70,7 +103,9 @@ impl Behavior {
             gossip_behavior,
             kademlia_behavior,
             identify_behavior,
+            address_tracker: AddressTracker::new(config),
             our_id: NodeId::P2p(PeerId::from(our_public_key)),
+            gossip_counters: HashMap::new(),
             events: VecDeque::new(),
         }
     }
@@ -79,6 +114,7 @@ impl Behavior {
     pub(super) fn send_one_way_message(&mut self, outgoing_message: OneWayOutgoingMessage) {
         let request_id = self
             .one_way_message_behavior
+            .inner_mut()
             .send_request(&outgoing_message.destination, outgoing_message.message);
         trace!("{}: sent one-way message {}", self.our_id, request_id);
     }
@@ -130,11 +166,123 @@ impl Behavior {
 
     /// Initiates gossiping the given message.
     pub(super) fn gossip(&mut self, message: GossipMessage) {
-        if let Err(error) = self.gossip_behavior.publish(TOPIC.clone(), message) {
-            warn!(?error, "{}: failed to gossip new message", self.our_id);
+        let result = self.gossip_behavior.publish(TOPIC.clone(), message);
+        let counters = self.gossip_counters.entry(TOPIC.hash()).or_default();
+        match result {
+            Ok(_) => counters.published += 1,
+            Err(error) => {
+                counters.publish_failures += 1;
+                warn!(?error, "{}: failed to gossip new message", self.our_id);
+            }
         }
     }
 
+    /// Records that a message received on the given topic was discarded as invalid.
+    pub(super) fn reject_gossip_message(&mut self, topic: &TopicHash) {
+        self.gossip_counters
+            .entry(topic.clone())
+            .or_default()
+            .rejected += 1;
+    }
+
+    /// Returns a snapshot of the gossipsub state of every topic we or our peers are subscribed to,
+    /// or on which we've published or received messages.
+    pub(super) fn gossip_state(&self) -> GossipState {
+        let mut topics: BTreeMap<TopicHash, GossipTopicState> = BTreeMap::new();
+        for topic in self.gossip_behavior.topics() {
+            let topic_state = topics.entry(topic.clone()).or_default();
+            topic_state.subscribed = true;
+            topic_state.mesh_peers = self
+                .gossip_behavior
+                .mesh_peers(topic)
+                .map(|peer_id| NodeId::from(*peer_id).to_string())
+                .collect();
+        }
+        for (peer_id, peer_topics) in self.gossip_behavior.all_peers() {
+            for topic in peer_topics {
+                topics
+                    .entry(topic.clone())
+                    .or_default()
+                    .subscribers
+                    .push(NodeId::from(*peer_id).to_string());
+            }
+        }
+        for (topic, counters) in &self.gossip_counters {
+            let topic_state = topics.entry(topic.clone()).or_default();
+            topic_state.published = counters.published;
+            topic_state.publish_failures = counters.publish_failures;
+            topic_state.received = counters.received;
+            topic_state.rejected = counters.rejected;
+        }
+
+        let topics = topics
+            .into_iter()
+            .map(|(topic, mut topic_state)| {
+                topic_state.topic = topic.into_string();
+                topic_state.mesh_peers.sort();
+                topic_state.subscribers.sort();
+                topic_state
+            })
+            .collect();
+        GossipState { topics }
+    }
+
+    /// Returns the addresses of the given peer held in the kademlia routing table.
+    #[cfg(test)]
+    pub(super) fn routing_table_addresses(&mut self, peer_id: &PeerId) -> Vec<Multiaddr> {
+        self.kademlia_behavior.addresses_of_peer(peer_id)
+    }
+
+    /// Replaces the addresses of the announcing peer held in the kademlia routing table with the
+    /// announced set, withdrawing any which are no longer announced.
+    fn handle_address_announcement(&mut self, message: &GossipsubMessage) {
+        let peer_id = match message.source {
+            Some(source) => source,
+            None => {
+                warn!("{}: address announcement without source", self.our_id);
+                self.reject_gossip_message(&message.topic);
+                return;
+            }
+        };
+        let addresses = match bincode::deserialize::<AddressAnnouncement>(&message.data) {
+            Ok(AddressAnnouncement { addresses }) if !addresses.is_empty() => addresses,
+            Ok(_) => {
+                warn!(%peer_id, "{}: empty address announcement", self.our_id);
+                self.reject_gossip_message(&message.topic);
+                return;
+            }
+            Err(error) => {
+                warn!(
+                    %peer_id,
+                    %error,
+                    "{}: failed to deserialize address announcement",
+                    self.our_id
+                );
+                self.reject_gossip_message(&message.topic);
+                return;
+            }
+        };
+
+        // Add the announced addresses before removing the stale ones, as kademlia drops a peer
+        // from the routing table once its final address is removed.
+        let stale_addresses = self
+            .kademlia_behavior
+            .addresses_of_peer(&peer_id)
+            .into_iter()
+            .filter(|address| !addresses.contains(address))
+            .collect::<Vec<_>>();
+        for address in &addresses {
+            self.kademlia_behavior
+                .add_address(&peer_id, address.clone());
+        }
+        for address in &stale_addresses {
+            self.kademlia_behavior.remove_address(&peer_id, address);
+        }
+
+        self.events
+            .push_front(SwarmBehaviorEvent::PeerAddressesAnnounced { peer_id, addresses });
+    }
+
     /// Polls the behavior for new events.
     fn custom_poll<T>(
         &mut self,
@@ -149,14 +297,32 @@ impl Behavior {
     }
 }
 
-impl NetworkBehaviourEventProcess<RequestResponseEvent<Vec<u8>, ()>> for Behavior {
-    fn inject_event(&mut self, event: RequestResponseEvent<Vec<u8>, ()>) {
-        self.events.push_front(SwarmBehaviorEvent::from(event));
+impl NetworkBehaviourEventProcess<SubstreamLimiterEvent<RequestResponseEvent<Vec<u8>, ()>>>
+    for Behavior
+{
+    fn inject_event(&mut self, event: SubstreamLimiterEvent<RequestResponseEvent<Vec<u8>, ()>>) {
+        let event = match event {
+            SubstreamLimiterEvent::Refused { peer_id } => {
+                SwarmBehaviorEvent::InboundSubstreamRefused { peer_id }
+            }
+            SubstreamLimiterEvent::Inner(event) => SwarmBehaviorEvent::from(event),
+        };
+        self.events.push_front(event);
     }
 }
 
 impl NetworkBehaviourEventProcess<GossipsubEvent> for Behavior {
     fn inject_event(&mut self, event: GossipsubEvent) {
+        if let GossipsubEvent::Message { message, .. } = &event {
+            self.gossip_counters
+                .entry(message.topic.clone())
+                .or_default()
+                .received += 1;
+            if message.topic == address_gossip::TOPIC.hash() {
+                self.handle_address_announcement(message);
+                return;
+            }
+        }
         self.events.push_front(SwarmBehaviorEvent::from(event));
     }
 }
@@ -172,3 +338,40 @@ impl NetworkBehaviourEventProcess<IdentifyEvent> for Behavior {
         self.events.push_front(SwarmBehaviorEvent::from(event));
     }
 }
+
+impl NetworkBehaviourEventProcess<OurAddressesChanged> for Behavior {
+    fn inject_event(&mut self, event: OurAddressesChanged) {
+        let OurAddressesChanged(addresses) = event;
+        debug!(
+            "{}: gossiping our addresses {}",
+            self.our_id,
+            DisplayIter::new(addresses.iter())
+        );
+        let announcement = match bincode::serialize(&AddressAnnouncement { addresses }) {
+            Ok(announcement) => announcement,
+            Err(error) => {
+                warn!(%error, "{}: failed to serialize address announcement", self.our_id);
+                return;
+            }
+        };
+        let result = self
+            .gossip_behavior
+            .publish(address_gossip::TOPIC.clone(), announcement);
+        let counters = self
+            .gossip_counters
+            .entry(address_gossip::TOPIC.hash())
+            .or_default();
+        match result {
+            Ok(_) => counters.published += 1,
+            // Peers will learn our addresses via the identify behavior once connected.
+            Err(PublishError::InsufficientPeers) => {
+                counters.publish_failures += 1;
+                debug!("{}: no peers to gossip our addresses to", self.our_id)
+            }
+            Err(error) => {
+                counters.publish_failures += 1;
+                warn!(?error, "{}: failed to gossip our addresses", self.our_id)
+            }
+        }
+    }
+}
@@ -8,7 +8,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::{components::small_network, types::TimeDiff};
 
-// TODO - remove these defaults once small_network's config has been replaced by this one.
+// TODO - move these defaults to the shared `small_network::Config` once it replaces this one.
 mod temp {
     pub(super) const CONNECTION_SETUP_TIMEOUT: &str = "10seconds";
     // TODO - set to reasonable limit, or remove.
@@ -19,6 +19,15 @@ mod temp {
     // TODO - set to reasonable limit, or remove.
     pub(super) const MAX_GOSSIP_MESSAGE_SIZE: u32 = u32::max_value();
     pub(super) const GOSSIP_DUPLICATE_CACHE_TIMEOUT: &str = "1minute";
+    pub(super) const DISCONNECTED_PEER_GRACE_PERIOD: &str = "5seconds";
+    pub(super) const MAX_HELD_MESSAGES_PER_PEER: u32 = 100;
+    pub(super) const MAX_HELD_BYTES_PER_PEER: u32 = 10 * 1024 * 1024;
+    pub(super) const COMPRESS_ONE_WAY_MESSAGES: bool = true;
+    pub(super) const ONE_WAY_COMPRESSION_THRESHOLD: u32 = 1024;
+    pub(super) const MIN_ADDRESS_GOSSIP_INTERVAL: &str = "2seconds";
+    pub(super) const MAX_INBOUND_SUBSTREAMS_PER_CONNECTION: u32 = 64;
+    pub(super) const MAX_INBOUND_SUBSTREAMS: u32 = 2048;
+    pub(super) const MAX_PENDING_INCOMING_CONNECTIONS: u32 = 32;
 }
 
 const DEFAULT_BIND_ADDRESS: &str = "0.0.0.0:22777";
@@ -54,6 +63,31 @@ pub struct Config {
     pub max_gossip_message_size: u32,
     /// Time for which to retain a cached gossip message ID to prevent duplicates being gossiped.
     pub gossip_duplicate_cache_timeout: TimeDiff,
+    /// Period after losing the final connection to a peer during which one-way messages to that
+    /// peer are held, to be sent if it reconnects.  A value of zero disables holding messages.
+    pub disconnected_peer_grace_period: TimeDiff,
+    /// Maximum number of one-way messages held for a single recently disconnected peer.
+    pub max_held_messages_per_peer: u32,
+    /// Maximum total size in bytes of the one-way messages held for a single recently
+    /// disconnected peer.
+    pub max_held_bytes_per_peer: u32,
+    /// Whether to offer zstd compression of one-way messages to peers.  Compression is only used
+    /// with peers which also offer it.
+    pub compress_one_way_messages: bool,
+    /// Minimum serialized size in bytes of a one-way message for it to be compressed.
+    pub one_way_compression_threshold: u32,
+    /// Minimum interval between gossiping our addresses to peers.  Our addresses are gossiped
+    /// whenever they change, but changes within this interval of the previous gossip are batched.
+    pub min_address_gossip_interval: TimeDiff,
+    /// Maximum number of concurrent inbound one-way message substreams on a single connection.
+    /// Further substreams are refused until one completes.
+    pub max_inbound_substreams_per_connection: u32,
+    /// Maximum number of concurrent inbound one-way message substreams across all connections.
+    /// Further substreams are refused until one completes.
+    pub max_inbound_substreams: u32,
+    /// Maximum number of incoming connections which may be in the process of being set up (i.e.
+    /// being upgraded) at any one time.  Further incoming connections are dropped.
+    pub max_pending_incoming_connections: u32,
 }
 
 impl Default for Config {
@@ -72,6 +106,19 @@ impl Default for Config {
                 temp::GOSSIP_DUPLICATE_CACHE_TIMEOUT,
             )
             .unwrap(),
+            disconnected_peer_grace_period: TimeDiff::from_str(
+                temp::DISCONNECTED_PEER_GRACE_PERIOD,
+            )
+            .unwrap(),
+            max_held_messages_per_peer: temp::MAX_HELD_MESSAGES_PER_PEER,
+            max_held_bytes_per_peer: temp::MAX_HELD_BYTES_PER_PEER,
+            compress_one_way_messages: temp::COMPRESS_ONE_WAY_MESSAGES,
+            one_way_compression_threshold: temp::ONE_WAY_COMPRESSION_THRESHOLD,
+            min_address_gossip_interval: TimeDiff::from_str(temp::MIN_ADDRESS_GOSSIP_INTERVAL)
+                .unwrap(),
+            max_inbound_substreams_per_connection: temp::MAX_INBOUND_SUBSTREAMS_PER_CONNECTION,
+            max_inbound_substreams: temp::MAX_INBOUND_SUBSTREAMS,
+            max_pending_incoming_connections: temp::MAX_PENDING_INCOMING_CONNECTIONS,
         }
     }
 }
@@ -124,7 +171,22 @@ impl From<&small_network::Config> for Config {
             bind_address: config.bind_address.clone(),
             known_addresses: config.known_addresses.clone(),
             is_bootstrap_node,
-            ..Default::default()
+            connection_setup_timeout: config.connection_setup_timeout,
+            max_one_way_message_size: config.max_one_way_message_size,
+            request_timeout: config.request_timeout,
+            connection_keep_alive: config.connection_keep_alive,
+            gossip_heartbeat_interval: config.gossip_heartbeat_interval,
+            max_gossip_message_size: config.max_gossip_message_size,
+            gossip_duplicate_cache_timeout: config.gossip_duplicate_cache_timeout,
+            disconnected_peer_grace_period: config.disconnected_peer_grace_period,
+            max_held_messages_per_peer: config.max_held_messages_per_peer,
+            max_held_bytes_per_peer: config.max_held_bytes_per_peer,
+            compress_one_way_messages: config.compress_one_way_messages,
+            one_way_compression_threshold: config.one_way_compression_threshold,
+            min_address_gossip_interval: config.min_address_gossip_interval,
+            max_inbound_substreams_per_connection: config.max_inbound_substreams_per_connection,
+            max_inbound_substreams: config.max_inbound_substreams,
+            max_pending_incoming_connections: config.max_pending_incoming_connections,
         }
     }
 }
@@ -1,9 +1,19 @@
-use std::io;
+use std::{
+    fmt::{self, Display, Formatter},
+    io,
+};
 
+use datasize::DataSize;
 use libp2p::{core::connection::ConnectionLimit, noise::NoiseError, Multiaddr, TransportError};
+use serde::Serialize;
 use thiserror::Error;
 
+use crate::types::NodeId;
+
 /// Error type returned by the `Network` component.
+///
+/// Each variant belongs to one of the categories given by [`ErrorKind`], which callers should use
+/// to decide how to react to the error rather than matching on the variants themselves.
 #[derive(Debug, Error)]
 pub enum Error {
     /// Invalid configuration: must have at least one known address.
@@ -11,30 +21,44 @@ pub enum Error {
     NoKnownAddress,
 
     /// Signing libp2p-noise static ID keypair failed.
-    #[error("signing libp2p-noise static ID keypair failed: {0}")]
-    StaticKeypairSigning(NoiseError),
+    #[error("signing libp2p-noise static ID keypair failed")]
+    StaticKeypairSigning(#[source] NoiseError),
 
     /// Failed to listen.
-    #[error("failed to listen on {address}: {error}")]
+    #[error("failed to listen on {address}")]
     Listen {
         address: Multiaddr,
+        #[source]
         error: TransportError<io::Error>,
     },
 
     /// Failed to dial the given peer.
-    #[error("failed to dial the peer on {address}: {error}")]
+    #[error("failed to dial the peer on {address}")]
     DialPeer {
         address: Multiaddr,
+        #[source]
         error: ConnectionLimit,
     },
 
+    /// The peer disconnected and did not reconnect in time for the message to be sent.
+    #[error("peer {peer_id} is not connected")]
+    PeerDisconnected { peer_id: NodeId },
+
+    /// The peer supports none of our protocols, so will never be able to receive messages from us.
+    #[error("peer {peer_id} is incompatible")]
+    IncompatiblePeer { peer_id: NodeId },
+
+    /// The peer committed an offense, so is banned and no further messages are sent to it.
+    #[error("peer {peer_id} is blocked")]
+    PeerBlocked { peer_id: NodeId },
+
     /// Failed to serialize a message.
-    #[error("failed to serialize: {0}")]
-    Serialization(bincode::ErrorKind),
+    #[error("failed to serialize")]
+    Serialization(#[source] bincode::ErrorKind),
 
     /// Failed to deserialize a message.
-    #[error("failed to deserialize: {0}")]
-    Deserialization(bincode::ErrorKind),
+    #[error("failed to deserialize")]
+    Deserialization(#[source] bincode::ErrorKind),
 
     /// Message too large.
     #[error("message of {actual_size} bytes exceeds limit of {max_size} bytes")]
@@ -44,3 +68,149 @@ pub enum Error {
     #[error(transparent)]
     Metrics(#[from] prometheus::Error),
 }
+
+impl Error {
+    /// Returns the category of the error.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Error::NoKnownAddress
+            | Error::StaticKeypairSigning(_)
+            | Error::Listen { .. }
+            | Error::Metrics(_) => ErrorKind::Configuration,
+            Error::DialPeer { .. } | Error::PeerDisconnected { .. } => ErrorKind::Connection,
+            Error::IncompatiblePeer { .. } | Error::PeerBlocked { .. } => ErrorKind::Protocol,
+            Error::Serialization(_) | Error::Deserialization(_) => ErrorKind::Serialization,
+            Error::MessageTooLarge { .. } => ErrorKind::MessageTooLarge,
+        }
+    }
+
+    /// Returns `true` if repeating the failed operation may succeed.
+    pub fn is_retryable(&self) -> bool {
+        self.kind().is_retryable()
+    }
+}
+
+/// The category of a network [`Error`], indicating how it should be handled.
+#[derive(Clone, Copy, DataSize, Debug, Eq, PartialEq, Serialize)]
+pub enum ErrorKind {
+    /// The component is misconfigured or failed to initialize.  Retrying will not help.
+    Configuration,
+    /// A connection to the peer could not be established or was lost.  Retrying, ideally after a
+    /// backoff, may succeed.
+    Connection,
+    /// A message could not be (de)serialized, indicating a bug in the sender.  Never retry.
+    Serialization,
+    /// A message exceeded the size limit for its class.  It will never be sent as-is, but may be
+    /// split into smaller messages.
+    MessageTooLarge,
+    /// The peer is incompatible with us or has misbehaved, so should be excluded rather than
+    /// contacted again.
+    Protocol,
+}
+
+impl ErrorKind {
+    /// Returns `true` if repeating an operation which failed with this kind of error may succeed.
+    pub fn is_retryable(self) -> bool {
+        match self {
+            ErrorKind::Connection => true,
+            ErrorKind::Configuration
+            | ErrorKind::Serialization
+            | ErrorKind::MessageTooLarge
+            | ErrorKind::Protocol => false,
+        }
+    }
+}
+
+impl Display for ErrorKind {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ErrorKind::Configuration => write!(formatter, "configuration"),
+            ErrorKind::Connection => write!(formatter, "connection"),
+            ErrorKind::Serialization => write!(formatter, "serialization"),
+            ErrorKind::MessageTooLarge => write!(formatter, "message too large"),
+            ErrorKind::Protocol => write!(formatter, "protocol"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error as StdError;
+
+    use super::*;
+    use crate::testing::TestRng;
+
+    #[test]
+    fn should_categorize_each_error() {
+        let mut rng = TestRng::new();
+        let address: Multiaddr = "/ip4/127.0.0.1/tcp/34553".parse().unwrap();
+        let peer_id = NodeId::random(&mut rng);
+
+        let cases = vec![
+            (Error::NoKnownAddress, ErrorKind::Configuration),
+            (
+                Error::StaticKeypairSigning(NoiseError::InvalidKey),
+                ErrorKind::Configuration,
+            ),
+            (
+                Error::Listen {
+                    address: address.clone(),
+                    error: TransportError::MultiaddrNotSupported(address.clone()),
+                },
+                ErrorKind::Configuration,
+            ),
+            (
+                Error::Metrics(prometheus::Error::Msg("test".to_string())),
+                ErrorKind::Configuration,
+            ),
+            (
+                Error::DialPeer {
+                    address,
+                    error: ConnectionLimit {
+                        limit: 1,
+                        current: 1,
+                    },
+                },
+                ErrorKind::Connection,
+            ),
+            (Error::PeerDisconnected { peer_id }, ErrorKind::Connection),
+            (Error::IncompatiblePeer { peer_id }, ErrorKind::Protocol),
+            (Error::PeerBlocked { peer_id }, ErrorKind::Protocol),
+            (
+                Error::Serialization(bincode::ErrorKind::SizeLimit),
+                ErrorKind::Serialization,
+            ),
+            (
+                Error::Deserialization(bincode::ErrorKind::SizeLimit),
+                ErrorKind::Serialization,
+            ),
+            (
+                Error::MessageTooLarge {
+                    max_size: 1,
+                    actual_size: 2,
+                },
+                ErrorKind::MessageTooLarge,
+            ),
+        ];
+
+        for (error, expected_kind) in cases {
+            assert_eq!(error.kind(), expected_kind, "wrong kind for {}", error);
+            assert_eq!(
+                error.is_retryable(),
+                expected_kind == ErrorKind::Connection,
+                "wrong retryability for {}",
+                error
+            );
+        }
+    }
+
+    #[test]
+    fn should_preserve_cause() {
+        let error = Error::Serialization(bincode::ErrorKind::SizeLimit);
+        let source = error.source().expect("should have a source");
+        assert_eq!(
+            source.to_string(),
+            bincode::ErrorKind::SizeLimit.to_string()
+        );
+    }
+}
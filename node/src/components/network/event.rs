@@ -13,7 +13,10 @@ use serde::Serialize;
 use static_assertions::const_assert;
 
 use crate::{
-    effect::requests::{NetworkInfoRequest, NetworkRequest},
+    effect::{
+        announcements::BlocklistAnnouncement,
+        requests::{NetworkInfoRequest, NetworkRequest},
+    },
     protocol::Message,
     types::NodeId,
 };
@@ -94,6 +97,35 @@ pub enum Event<P> {
     },
 
     // ========== Other events ==========
+    /// The grace period for holding messages destined for a disconnected peer may have lapsed.
+    HoldingPeriodExpired {
+        /// Identity of the disconnected peer.
+        peer_id: Box<NodeId>,
+    },
+
+    /// A one-way message exceeding the size limit for its class was received and dropped.
+    OversizedMessageReceived {
+        /// Identity of the peer which sent the message.
+        sender: Box<NodeId>,
+        /// The size limit for the message's class.
+        max_size: u32,
+        /// The serialized size of the message.
+        actual_size: u64,
+    },
+
+    /// An inbound one-way message substream opened by a peer was refused as a limit on concurrent
+    /// inbound substreams had been reached.
+    InboundSubstreamRefused {
+        /// Identity of the peer which opened the substream.
+        peer_id: Box<NodeId>,
+    },
+
+    /// A peer was found to support none of our protocols.
+    IncompatiblePeer {
+        /// Identity of the incompatible peer.
+        peer_id: Box<NodeId>,
+    },
+
     /// A network request made by a different component.
     #[from]
     NetworkRequest {
@@ -107,6 +139,10 @@ pub enum Event<P> {
         #[serde(skip_serializing)]
         info_request: NetworkInfoRequest<NodeId>,
     },
+
+    /// An announcement that a peer committed an offense.
+    #[from]
+    BlocklistAnnouncement(BlocklistAnnouncement<NodeId>),
 }
 
 impl<P: Display> Display for Event<P> {
@@ -172,10 +208,33 @@ impl<P: Display> Display for Event<P> {
                 Ok(())
             }
 
+            Event::HoldingPeriodExpired { peer_id } => {
+                write!(
+                    f,
+                    "holding period for messages to {} may have expired",
+                    peer_id
+                )
+            }
+            Event::OversizedMessageReceived {
+                sender,
+                max_size,
+                actual_size,
+            } => write!(
+                f,
+                "oversized message from {}: {} bytes exceeds limit of {} bytes",
+                sender, actual_size, max_size
+            ),
+            Event::InboundSubstreamRefused { peer_id } => {
+                write!(f, "refused inbound substream from {}", peer_id)
+            }
+            Event::IncompatiblePeer { peer_id } => write!(f, "incompatible peer {}", peer_id),
             Event::NetworkRequest { request } => write!(f, "request: {}", request),
             Event::NetworkInfoRequest { info_request } => {
                 write!(f, "info request: {}", info_request)
             }
+            Event::BlocklistAnnouncement(ann) => {
+                write!(f, "handling blocklist announcement: {}", ann)
+            }
         }
     }
 }
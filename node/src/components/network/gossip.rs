@@ -11,7 +11,7 @@ use libp2p::{
 };
 use once_cell::sync::Lazy;
 
-use super::{Config, Error, PayloadT};
+use super::{address_gossip, Config, Error, PayloadT};
 use crate::types::Chainspec;
 
 pub(super) static TOPIC: Lazy<IdentTopic> = Lazy::new(|| IdentTopic::new("all".to_string()));
@@ -64,5 +64,9 @@ pub(super) fn new_behavior(
     gossipsub
         .subscribe(&*TOPIC)
         .expect("should subscribe to topic");
+    // TODO - remove `expect`
+    gossipsub
+        .subscribe(&*address_gossip::TOPIC)
+        .expect("should subscribe to address topic");
     gossipsub
 }
@@ -0,0 +1,283 @@
+//! This module is home to a short-lived, per-peer buffer of one-way messages destined for peers
+//! which have recently disconnected.
+//!
+//! If a peer's final connection is closed, messages sent to it during the subsequent grace period
+//! are held rather than lost.  They are flushed if the peer reconnects within the grace period, or
+//! dropped once it lapses.
+
+use std::collections::HashMap;
+
+use crate::{
+    types::{NodeId, TimeDiff, Timestamp},
+    utils::ds,
+};
+
+/// The messages being held for a single recently disconnected peer.
+#[derive(Debug)]
+struct PeerBuffer<M> {
+    /// The time at which our final connection to the peer was closed.
+    disconnected_at: Timestamp,
+    messages: Vec<M>,
+    total_bytes: usize,
+}
+
+/// The outcome of trying to hold a message.
+#[derive(Debug, PartialEq)]
+pub(super) enum HoldOutcome<M> {
+    /// The message is being held for the peer.
+    Held,
+    /// The peer is not recently disconnected, so the message is returned unheld.
+    NotApplicable(M),
+    /// The peer is recently disconnected, but the message can't be held as the grace period has
+    /// lapsed or the peer's buffer is full.
+    Rejected(M),
+}
+
+/// Holds messages for recently disconnected peers until they reconnect or their grace period
+/// lapses.
+#[derive(Debug)]
+pub(super) struct HeldMessages<M> {
+    /// How long after disconnection messages for a peer will be held.  A value of zero disables
+    /// holding entirely.
+    grace_period: TimeDiff,
+    /// The maximum number of messages held per peer.
+    max_count: usize,
+    /// The maximum total size in bytes of the messages held per peer.
+    max_bytes: usize,
+    buffers: HashMap<NodeId, PeerBuffer<M>>,
+}
+
+impl<M> HeldMessages<M> {
+    pub(super) fn new(grace_period: TimeDiff, max_count: u32, max_bytes: u32) -> Self {
+        HeldMessages {
+            grace_period,
+            max_count: max_count as usize,
+            max_bytes: max_bytes as usize,
+            buffers: HashMap::new(),
+        }
+    }
+
+    /// Returns the grace period, or `None` if holding is disabled.
+    pub(super) fn grace_period(&self) -> Option<TimeDiff> {
+        if self.grace_period.millis() == 0 {
+            None
+        } else {
+            Some(self.grace_period)
+        }
+    }
+
+    /// Records that our final connection to the peer was closed at `now`, starting its grace
+    /// period.
+    ///
+    /// Any messages still held for the peer are retained and become subject to the new grace
+    /// period.
+    pub(super) fn peer_disconnected(&mut self, peer_id: NodeId, now: Timestamp) {
+        if self.grace_period().is_none() {
+            return;
+        }
+        self.buffers
+            .entry(peer_id)
+            .or_insert_with(|| PeerBuffer {
+                disconnected_at: now,
+                messages: vec![],
+                total_bytes: 0,
+            })
+            .disconnected_at = now;
+    }
+
+    /// Tries to hold `message` of `size` bytes for the given peer.
+    pub(super) fn hold(
+        &mut self,
+        peer_id: &NodeId,
+        message: M,
+        size: usize,
+        now: Timestamp,
+    ) -> HoldOutcome<M> {
+        let buffer = match self.buffers.get_mut(peer_id) {
+            Some(buffer) => buffer,
+            None => return HoldOutcome::NotApplicable(message),
+        };
+
+        if now >= buffer.disconnected_at + self.grace_period
+            || buffer.messages.len() >= self.max_count
+            || buffer.total_bytes + size > self.max_bytes
+        {
+            return HoldOutcome::Rejected(message);
+        }
+
+        buffer.messages.push(message);
+        buffer.total_bytes += size;
+        HoldOutcome::Held
+    }
+
+    /// Records that the peer has connected, returning all messages held for it in the order in
+    /// which they were held.
+    pub(super) fn peer_connected(&mut self, peer_id: &NodeId) -> Vec<M> {
+        self.buffers
+            .remove(peer_id)
+            .map(|buffer| buffer.messages)
+            .unwrap_or_default()
+    }
+
+    /// Removes and returns all messages held for the peer, regardless of its grace period.
+    pub(super) fn discard(&mut self, peer_id: &NodeId) -> Vec<M> {
+        self.buffers
+            .remove(peer_id)
+            .map(|buffer| buffer.messages)
+            .unwrap_or_default()
+    }
+
+    /// Estimates the heap size of the held messages, using the serialized size of each message as
+    /// an approximation of its in-memory size.
+    pub(super) fn estimate_heap_size(&self) -> usize {
+        ds::hash_map_fixed_size(&self.buffers)
+            + self
+                .buffers
+                .values()
+                .map(|buffer| buffer.total_bytes)
+                .sum::<usize>()
+    }
+
+    /// Removes and returns all messages held for the peer if its grace period has lapsed as of
+    /// `now`.
+    pub(super) fn expire(&mut self, peer_id: &NodeId, now: Timestamp) -> Vec<M> {
+        match self.buffers.get(peer_id) {
+            Some(buffer) if now >= buffer.disconnected_at + self.grace_period => self
+                .buffers
+                .remove(peer_id)
+                .map(|buffer| buffer.messages)
+                .unwrap_or_default(),
+            _ => vec![],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestRng;
+
+    const GRACE_PERIOD_MILLIS: u64 = 1_000;
+    const MAX_COUNT: u32 = 3;
+    const MAX_BYTES: u32 = 100;
+
+    fn new_held_messages() -> HeldMessages<u8> {
+        HeldMessages::new(TimeDiff::from(GRACE_PERIOD_MILLIS), MAX_COUNT, MAX_BYTES)
+    }
+
+    #[test]
+    fn should_deliver_on_reconnection_within_grace_period() {
+        let mut rng = TestRng::new();
+        let peer_id = NodeId::random(&mut rng);
+        let mut held_messages = new_held_messages();
+        let disconnected_at = Timestamp::from(10_000);
+
+        held_messages.peer_disconnected(peer_id, disconnected_at);
+        for message in 0..MAX_COUNT as u8 {
+            let now = disconnected_at + TimeDiff::from(GRACE_PERIOD_MILLIS - 1);
+            assert_eq!(
+                held_messages.hold(&peer_id, message, 1, now),
+                HoldOutcome::Held
+            );
+        }
+
+        // A timer firing before the grace period has lapsed should not expire the messages.
+        assert!(held_messages.expire(&peer_id, disconnected_at).is_empty());
+
+        assert_eq!(held_messages.peer_connected(&peer_id), vec![0, 1, 2]);
+        assert!(held_messages.peer_connected(&peer_id).is_empty());
+
+        // Now that the peer is connected, messages should no longer be held.
+        assert_eq!(
+            held_messages.hold(&peer_id, 3, 1, disconnected_at),
+            HoldOutcome::NotApplicable(3)
+        );
+    }
+
+    #[test]
+    fn should_fail_on_reconnection_beyond_grace_period() {
+        let mut rng = TestRng::new();
+        let peer_id = NodeId::random(&mut rng);
+        let mut held_messages = new_held_messages();
+        let disconnected_at = Timestamp::from(10_000);
+        let lapsed_at = disconnected_at + TimeDiff::from(GRACE_PERIOD_MILLIS);
+
+        held_messages.peer_disconnected(peer_id, disconnected_at);
+        assert_eq!(
+            held_messages.hold(&peer_id, 0, 1, disconnected_at),
+            HoldOutcome::Held
+        );
+        assert_eq!(
+            held_messages.hold(&peer_id, 1, 1, lapsed_at),
+            HoldOutcome::Rejected(1)
+        );
+
+        assert_eq!(held_messages.expire(&peer_id, lapsed_at), vec![0]);
+        assert!(held_messages.peer_connected(&peer_id).is_empty());
+    }
+
+    #[test]
+    fn should_bound_count_and_bytes() {
+        let mut rng = TestRng::new();
+        let peer_id = NodeId::random(&mut rng);
+        let mut held_messages = new_held_messages();
+        let now = Timestamp::from(10_000);
+
+        held_messages.peer_disconnected(peer_id, now);
+        assert_eq!(
+            held_messages.hold(&peer_id, 0, MAX_BYTES as usize + 1, now),
+            HoldOutcome::Rejected(0)
+        );
+        assert_eq!(
+            held_messages.hold(&peer_id, 1, MAX_BYTES as usize, now),
+            HoldOutcome::Held
+        );
+        assert_eq!(
+            held_messages.hold(&peer_id, 2, 1, now),
+            HoldOutcome::Rejected(2)
+        );
+
+        let other_peer_id = NodeId::random(&mut rng);
+        held_messages.peer_disconnected(other_peer_id, now);
+        for message in 0..MAX_COUNT as u8 {
+            assert_eq!(
+                held_messages.hold(&other_peer_id, message, 0, now),
+                HoldOutcome::Held
+            );
+        }
+        assert_eq!(
+            held_messages.hold(&other_peer_id, 9, 0, now),
+            HoldOutcome::Rejected(9)
+        );
+    }
+
+    #[test]
+    fn should_discard_within_grace_period() {
+        let mut rng = TestRng::new();
+        let peer_id = NodeId::random(&mut rng);
+        let mut held_messages = new_held_messages();
+        let now = Timestamp::from(10_000);
+
+        held_messages.peer_disconnected(peer_id, now);
+        assert_eq!(held_messages.hold(&peer_id, 0, 1, now), HoldOutcome::Held);
+
+        assert_eq!(held_messages.discard(&peer_id), vec![0]);
+        assert!(held_messages
+            .expire(&peer_id, now + TimeDiff::from(GRACE_PERIOD_MILLIS))
+            .is_empty());
+    }
+
+    #[test]
+    fn should_not_hold_if_disabled() {
+        let mut rng = TestRng::new();
+        let peer_id = NodeId::random(&mut rng);
+        let mut held_messages = HeldMessages::new(TimeDiff::from(0), MAX_COUNT, MAX_BYTES);
+        let now = Timestamp::from(10_000);
+
+        held_messages.peer_disconnected(peer_id, now);
+        assert_eq!(
+            held_messages.hold(&peer_id, 0, 1, now),
+            HoldOutcome::NotApplicable(0)
+        );
+    }
+}
@@ -3,18 +3,26 @@
 //!
 //! For now, as a side-effect of the original small_network component, all peer-to-peer messages
 //! defined outside of the network component are one-way.
+//!
+//! Two protocols are supported: the plain protocol, where each message is sent as a length prefix
+//! followed by the serialized message, and (if enabled in the config) a preferred variant with a
+//! `/zstd` suffix, where the length-prefixed frame starts with a one-byte flag indicating whether
+//! the remainder is zstd-compressed.  Only messages of at least the configured threshold size are
+//! compressed.
 
-use std::{fmt::Debug, future::Future, io, iter, pin::Pin};
+use std::{fmt::Debug, future::Future, io, pin::Pin};
 
 use datasize::DataSize;
 use futures::{AsyncReadExt, AsyncWriteExt, FutureExt};
 use futures_io::{AsyncRead, AsyncWrite};
 use libp2p::{
+    core::ProtocolName,
     request_response::{
         ProtocolSupport, RequestResponse, RequestResponseCodec, RequestResponseConfig,
     },
     PeerId,
 };
+use prometheus::Histogram;
 
 use super::{Config, Error, PayloadT, ProtocolId};
 use crate::{
@@ -26,6 +34,15 @@ use crate::{
 /// suffix will be applied to create the full protocol name.
 const PROTOCOL_NAME_INNER: &str = "validator/one-way";
 
+/// The suffix applied to the `ProtocolId` of the variant of the protocol supporting compression.
+const COMPRESSED_PROTOCOL_SUFFIX: &str = "/zstd";
+
+/// Flag byte preceding an uncompressed message when using the compressed protocol.
+const UNCOMPRESSED_FLAG: u8 = 0;
+
+/// Flag byte preceding a zstd-compressed message when using the compressed protocol.
+const ZSTD_FLAG: u8 = 1;
+
 /// Constructs a new libp2p behavior suitable for use by one-way messaging.
 pub(super) fn new_behavior(
     config: &Config,
@@ -33,15 +50,36 @@ pub(super) fn new_behavior(
     chainspec: &Chainspec,
 ) -> RequestResponse<Codec> {
     let codec = Codec::new(config, net_metrics);
-    let protocol_id = ProtocolId::new(chainspec, PROTOCOL_NAME_INNER);
     let request_response_config = RequestResponseConfig::from(config);
     RequestResponse::new(
         codec,
-        iter::once((protocol_id, ProtocolSupport::Full)),
+        supported_protocols(config, chainspec)
+            .into_iter()
+            .map(|protocol_id| (protocol_id, ProtocolSupport::Full)),
         request_response_config,
     )
 }
 
+/// Returns the protocols supported for one-way messaging, in order of preference.
+fn supported_protocols(config: &Config, chainspec: &Chainspec) -> Vec<ProtocolId> {
+    let protocol_id = ProtocolId::new(chainspec, PROTOCOL_NAME_INNER);
+    if config.compress_one_way_messages {
+        vec![
+            protocol_id.with_suffix(COMPRESSED_PROTOCOL_SUFFIX),
+            protocol_id,
+        ]
+    } else {
+        vec![protocol_id]
+    }
+}
+
+/// Returns whether messages sent via `protocol` are preceded by a compression flag.
+fn is_compressed_protocol(protocol: &ProtocolId) -> bool {
+    protocol
+        .protocol_name()
+        .ends_with(COMPRESSED_PROTOCOL_SUFFIX.as_bytes())
+}
+
 #[derive(DataSize, Debug)]
 pub(super) struct Outgoing {
     // Datasize note: `PeerId` can be skipped, as in our case it should be 100% stack allocated.
@@ -51,14 +89,18 @@ pub(super) struct Outgoing {
 }
 
 impl Outgoing {
+    /// Serializes the payload, returning an error if it exceeds either `max_size` or the limit for
+    /// the payload's class.
     pub(super) fn new<P: PayloadT>(
         destination: NodeId,
         payload: &P,
         max_size: u32,
+        chainspec: &Chainspec,
     ) -> Result<Self, Error> {
         let serialized_message =
             bincode::serialize(payload).map_err(|error| Error::Serialization(*error))?;
 
+        let max_size = max_size.min(payload.max_serialized_size(chainspec));
         if serialized_message.len() > max_size as usize {
             return Err(Error::MessageTooLarge {
                 max_size,
@@ -81,6 +123,28 @@ impl Outgoing {
     }
 }
 
+/// Deserializes an incoming one-way message, returning an error if it exceeds the limit for the
+/// payload's class.
+///
+/// The global `max_one_way_message_size` has already been enforced by the `Codec` at this stage.
+pub(super) fn deserialize_incoming<P: PayloadT>(
+    serialized_message: &[u8],
+    chainspec: &Chainspec,
+) -> Result<P, Error> {
+    let payload: P =
+        bincode::deserialize(serialized_message).map_err(|error| Error::Deserialization(*error))?;
+
+    let max_size = payload.max_serialized_size(chainspec);
+    if serialized_message.len() > max_size as usize {
+        return Err(Error::MessageTooLarge {
+            max_size,
+            actual_size: serialized_message.len() as u64,
+        });
+    }
+
+    Ok(payload)
+}
+
 impl From<Outgoing> for Vec<u8> {
     fn from(outgoing: Outgoing) -> Self {
         outgoing.message
@@ -92,20 +156,82 @@ impl From<Outgoing> for Vec<u8> {
 #[derive(Debug, Clone)]
 pub(super) struct Codec {
     max_message_size: u32,
+    compression_threshold: u32,
     read_futures_in_flight: prometheus::Gauge,
     read_futures_total: prometheus::Gauge,
     write_futures_in_flight: prometheus::Gauge,
     write_futures_total: prometheus::Gauge,
+    compression_ratio: Histogram,
 }
 
 impl Codec {
     pub(super) fn new(config: &Config, net_metrics: &NetworkingMetrics) -> Self {
         Self {
             max_message_size: config.max_one_way_message_size,
+            compression_threshold: config.one_way_compression_threshold,
             read_futures_in_flight: net_metrics.read_futures_in_flight.clone(),
             read_futures_total: net_metrics.read_futures_total.clone(),
             write_futures_in_flight: net_metrics.write_futures_in_flight.clone(),
             write_futures_total: net_metrics.write_futures_total.clone(),
+            compression_ratio: net_metrics.one_way_compression_ratio.clone(),
+        }
+    }
+
+    /// Returns the frame to be sent via the compressed protocol: a flag byte followed by the
+    /// message, compressed if it is at least the threshold size and compression shrinks it.
+    fn compress(&self, message: Vec<u8>) -> io::Result<Vec<u8>> {
+        if message.len() >= self.compression_threshold as usize {
+            let compressed = zstd::block::compress(&message, zstd::DEFAULT_COMPRESSION_LEVEL)?;
+            if compressed.len() < message.len() {
+                self.compression_ratio
+                    .observe(message.len() as f64 / compressed.len() as f64);
+                let mut frame = Vec::with_capacity(compressed.len() + 1);
+                frame.push(ZSTD_FLAG);
+                frame.extend_from_slice(&compressed);
+                return Ok(frame);
+            }
+        }
+
+        let mut frame = Vec::with_capacity(message.len() + 1);
+        frame.push(UNCOMPRESSED_FLAG);
+        frame.extend_from_slice(&message);
+        Ok(frame)
+    }
+
+    /// Returns the message contained in a frame received via the compressed protocol.
+    ///
+    /// Decompression stops as soon as the output exceeds the max message size, so a small frame
+    /// can't be used to exhaust our memory.
+    fn decompress(&self, frame: &[u8]) -> io::Result<Vec<u8>> {
+        use std::io::Read;
+
+        match frame.split_first() {
+            Some((&UNCOMPRESSED_FLAG, message)) => Ok(message.to_vec()),
+            Some((&ZSTD_FLAG, compressed)) => {
+                let max_message_size = u64::from(self.max_message_size);
+                let mut message = Vec::new();
+                zstd::stream::read::Decoder::new(compressed)?
+                    .take(max_message_size + 1)
+                    .read_to_end(&mut message)?;
+                if message.len() as u64 > max_message_size {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!(
+                            "decompressed message size exceeds limit: > {}",
+                            self.max_message_size
+                        ),
+                    ));
+                }
+                Ok(message)
+            }
+            Some((flag, _)) => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid compression flag: {}", flag),
+            )),
+            None => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "missing compression flag",
+            )),
         }
     }
 }
@@ -117,7 +243,7 @@ impl RequestResponseCodec for Codec {
 
     fn read_request<'life0, 'life1, 'life2, 'async_trait, T>(
         &'life0 mut self,
-        _protocol: &'life1 Self::Protocol,
+        protocol: &'life1 Self::Protocol,
         io: &'life2 mut T,
     ) -> Pin<Box<dyn Future<Output = io::Result<Self::Request>> + 'async_trait + Send>>
     where
@@ -127,27 +253,34 @@ impl RequestResponseCodec for Codec {
         Self: 'async_trait,
         T: AsyncRead + Unpin + Send + 'async_trait,
     {
+        let is_compressed = is_compressed_protocol(protocol);
         async move {
-            // Read the length.
+            // Read the length.  Frames sent via the compressed protocol have an extra flag byte.
             let mut buffer = [0; 4];
             io.read(&mut buffer[..])
                 .await
                 .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
             let length = u32::from_le_bytes(buffer);
-            if length > self.max_message_size {
+            let max_length = if is_compressed {
+                self.max_message_size.saturating_add(1)
+            } else {
+                self.max_message_size
+            };
+            if length > max_length {
                 return Err(io::Error::new(
                     io::ErrorKind::InvalidInput,
-                    format!(
-                        "message size exceeds limit: {} > {}",
-                        length, self.max_message_size
-                    ),
+                    format!("message size exceeds limit: {} > {}", length, max_length),
                 ));
             }
 
             // Read the payload.
             let mut buffer = vec![0; length as usize];
             io.read_exact(&mut buffer).await?;
-            Ok(buffer)
+            if is_compressed {
+                self.decompress(&buffer)
+            } else {
+                Ok(buffer)
+            }
         }
         .boxed()
     }
@@ -178,7 +311,7 @@ impl RequestResponseCodec for Codec {
 
     fn write_request<'life0, 'life1, 'life2, 'async_trait, T>(
         &'life0 mut self,
-        _protocol: &'life1 Self::Protocol,
+        protocol: &'life1 Self::Protocol,
         io: &'life2 mut T,
         request: Self::Request,
     ) -> Pin<Box<dyn Future<Output = io::Result<()>> + 'async_trait + Send>>
@@ -189,8 +322,8 @@ impl RequestResponseCodec for Codec {
         Self: 'async_trait,
         T: AsyncWrite + Unpin + Send + 'async_trait,
     {
+        let is_compressed = is_compressed_protocol(protocol);
         async move {
-            // Write the length.
             if request.len() > self.max_message_size as usize {
                 return Err(io::Error::new(
                     io::ErrorKind::InvalidInput,
@@ -201,6 +334,13 @@ impl RequestResponseCodec for Codec {
                     ),
                 ));
             }
+            let request = if is_compressed {
+                self.compress(request)?
+            } else {
+                request
+            };
+
+            // Write the length.
             let length = request.len() as u32;
             io.write_all(&length.to_le_bytes()).await?;
 
@@ -237,3 +377,361 @@ impl RequestResponseCodec for Codec {
         .boxed()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use futures::{executor, io::Cursor};
+    use prometheus::Registry;
+    use rand::Rng;
+
+    use casper_types::EraId;
+
+    use super::*;
+    use crate::{
+        components::{
+            consensus::ConsensusMessage, gossiper, network_provider::NetworkProviderKind,
+        },
+        protocol::Message,
+        testing::TestRng,
+        types::{BlockHash, Deploy, DeployHash, FinalitySignature, SharedObject, Tag},
+    };
+
+    const MAX_BLOCK_SIZE: u32 = 10_000;
+    const MAX_DEPLOY_SIZE: u32 = 2_000;
+
+    fn new_chainspec(rng: &mut TestRng) -> Chainspec {
+        let mut chainspec = Chainspec::random(rng);
+        chainspec.deploy_config.max_block_size = MAX_BLOCK_SIZE;
+        chainspec.deploy_config.max_deploy_size = MAX_DEPLOY_SIZE;
+        chainspec
+    }
+
+    /// Asserts that the message returned by `new_message` is accepted both outbound and inbound
+    /// when its serialized size equals its class limit, and rejected when one byte larger.
+    fn assert_limit_enforced_at_boundary<F: Fn(usize) -> Message>(
+        rng: &mut TestRng,
+        new_message: F,
+    ) {
+        let chainspec = new_chainspec(rng);
+        let destination = NodeId::random_p2p(rng);
+
+        // Find the variable portion length which makes the message exactly the limit.
+        let empty_message = new_message(0);
+        let max_size = empty_message.max_serialized_size(&chainspec);
+        let empty_size = bincode::serialized_size(&empty_message).unwrap() as usize;
+        let at_limit = new_message(max_size as usize - empty_size);
+        let over_limit = new_message(max_size as usize - empty_size + 1);
+
+        let outgoing = Outgoing::new(destination, &at_limit, u32::max_value(), &chainspec)
+            .expect("should accept message at limit");
+        assert_eq!(outgoing.message.len(), max_size as usize);
+        deserialize_incoming::<Message>(&outgoing.message, &chainspec)
+            .expect("should accept incoming message at limit");
+
+        match Outgoing::new(destination, &over_limit, u32::max_value(), &chainspec) {
+            Err(Error::MessageTooLarge {
+                max_size: reported_max_size,
+                actual_size,
+            }) => {
+                assert_eq!(reported_max_size, max_size);
+                assert_eq!(actual_size, max_size as u64 + 1);
+            }
+            result => panic!("should reject message over limit, got {:?}", result),
+        }
+        let serialized = bincode::serialize(&over_limit).unwrap();
+        assert!(matches!(
+            deserialize_incoming::<Message>(&serialized, &chainspec),
+            Err(Error::MessageTooLarge { .. })
+        ));
+    }
+
+    #[test]
+    fn should_enforce_consensus_limit() {
+        let mut rng = TestRng::new();
+        assert_limit_enforced_at_boundary(&mut rng, |length| {
+            Message::Consensus(ConsensusMessage::Protocol {
+                era_id: EraId::new(1),
+                payload: vec![0; length],
+            })
+        });
+    }
+
+    #[test]
+    fn should_enforce_get_request_limit() {
+        let mut rng = TestRng::new();
+        assert_limit_enforced_at_boundary(&mut rng, |length| Message::GetRequest {
+            tag: Tag::Deploy,
+            serialized_id: vec![0; length],
+        });
+    }
+
+    #[test]
+    fn should_enforce_get_response_limits() {
+        let mut rng = TestRng::new();
+        for tag in &[
+            Tag::Deploy,
+            Tag::Block,
+            Tag::GossipedAddress,
+            Tag::BlockByHeight,
+            Tag::BlockHeaderByHash,
+            Tag::BlockHeaderAndFinalitySignaturesByHeight,
+            Tag::DeployHeaderWithHash,
+            Tag::DeployApprovals,
+            Tag::BlockTransfers,
+        ] {
+            assert_limit_enforced_at_boundary(&mut rng, |length| Message::GetResponse {
+                tag: *tag,
+                serialized_item: SharedObject::owned(vec![0; length]),
+            });
+        }
+    }
+
+    #[test]
+    fn should_enforce_get_batch_limits() {
+        let mut rng = TestRng::new();
+        assert_limit_enforced_at_boundary(&mut rng, |length| Message::GetBatchRequest {
+            tag: Tag::Deploy,
+            serialized_ids: vec![vec![0; length]],
+        });
+        assert_limit_enforced_at_boundary(&mut rng, |length| Message::GetBatchResponse {
+            tag: Tag::Deploy,
+            serialized_items: vec![SharedObject::owned(vec![0; length])],
+            serialized_missing_ids: vec![],
+        });
+    }
+
+    #[test]
+    fn should_split_get_batch_responses_within_limit() {
+        const ITEM_COUNT: usize = (3 * MAX_BLOCK_SIZE / MAX_DEPLOY_SIZE) as usize;
+
+        let mut rng = TestRng::new();
+        let chainspec = new_chainspec(&mut rng);
+        let destination = NodeId::random_p2p(&mut rng);
+
+        // Enough maximum-sized deploys to fill several blocks, along with some missing IDs.
+        let serialized_items = (0..ITEM_COUNT)
+            .map(|_| SharedObject::owned(vec![0; MAX_DEPLOY_SIZE as usize]))
+            .collect();
+        let serialized_missing_ids = vec![vec![0; 32]; 10];
+        let responses = Message::new_get_batch_responses(
+            Tag::Deploy,
+            serialized_items,
+            serialized_missing_ids,
+            MAX_BLOCK_SIZE,
+        );
+        assert!(responses.len() > 1);
+
+        let mut item_count = 0;
+        for response in &responses {
+            Outgoing::new(destination, response, u32::max_value(), &chainspec)
+                .expect("should accept batch response");
+            if let Message::GetBatchResponse {
+                serialized_items, ..
+            } = response
+            {
+                item_count += serialized_items.len();
+            }
+        }
+        assert_eq!(item_count, ITEM_COUNT);
+    }
+
+    #[test]
+    fn should_accept_fixed_size_variants() {
+        let mut rng = TestRng::new();
+        let chainspec = new_chainspec(&mut rng);
+        let destination = NodeId::random_p2p(&mut rng);
+
+        let messages = vec![
+            Message::DeployGossiper(gossiper::Message::<Deploy>::Gossip(DeployHash::random(
+                &mut rng,
+            ))),
+            Message::FinalitySignature(Box::new(FinalitySignature::random_for_block(
+                BlockHash::random(&mut rng),
+                1,
+            ))),
+        ];
+        for message in &messages {
+            let outgoing = Outgoing::new(destination, message, u32::max_value(), &chainspec)
+                .expect("should accept message");
+            deserialize_incoming::<Message>(&outgoing.message, &chainspec)
+                .expect("should accept incoming message");
+        }
+    }
+
+    #[test]
+    fn should_reject_message_within_global_limit_but_over_class_limit() {
+        let mut rng = TestRng::new();
+        let chainspec = new_chainspec(&mut rng);
+        let destination = NodeId::random_p2p(&mut rng);
+        let global_max_size = MAX_BLOCK_SIZE * 10;
+
+        // A get request only carries an ID, so is limited to well below the size of a block.
+        let message = Message::GetRequest {
+            tag: Tag::Block,
+            serialized_id: vec![0; MAX_BLOCK_SIZE as usize],
+        };
+        let serialized = bincode::serialize(&message).unwrap();
+        assert!(serialized.len() < global_max_size as usize);
+
+        assert!(matches!(
+            Outgoing::new(destination, &message, global_max_size, &chainspec),
+            Err(Error::MessageTooLarge { .. })
+        ));
+        assert!(matches!(
+            deserialize_incoming::<Message>(&serialized, &chainspec),
+            Err(Error::MessageTooLarge { .. })
+        ));
+    }
+
+    fn new_codec(config: &Config) -> Codec {
+        let net_metrics =
+            NetworkingMetrics::new(NetworkProviderKind::Libp2p, &Registry::new()).unwrap();
+        Codec::new(config, &net_metrics)
+    }
+
+    /// Writes `message` via `writer` using `protocol`, then reads it via `reader`.
+    fn round_trip(
+        writer: &mut Codec,
+        reader: &mut Codec,
+        protocol: &ProtocolId,
+        message: Vec<u8>,
+    ) -> io::Result<Vec<u8>> {
+        let mut io = Cursor::new(Vec::new());
+        executor::block_on(writer.write_request(protocol, &mut io, message))?;
+        io.set_position(0);
+        executor::block_on(reader.read_request(protocol, &mut io))
+    }
+
+    /// Returns a compressible message of the given length.
+    fn compressible_message(length: usize) -> Vec<u8> {
+        (0..length).map(|index| (index % 7) as u8).collect()
+    }
+
+    #[test]
+    fn should_round_trip_messages_around_compression_threshold() {
+        let mut rng = TestRng::new();
+        let chainspec = Chainspec::random(&mut rng);
+        let config = Config::default();
+        let threshold = config.one_way_compression_threshold as usize;
+        let mut codec = new_codec(&config);
+        let mut peer_codec = new_codec(&config);
+        let protocol = supported_protocols(&config, &chainspec).remove(0);
+        assert!(is_compressed_protocol(&protocol));
+
+        for &length in &[0, threshold - 1, threshold, threshold + 1, threshold * 10] {
+            let message = compressible_message(length);
+            let frame = codec.compress(message.clone()).unwrap();
+            let expected_flag = if length >= threshold {
+                ZSTD_FLAG
+            } else {
+                UNCOMPRESSED_FLAG
+            };
+            assert_eq!(frame[0], expected_flag, "wrong flag for length {}", length);
+
+            let received =
+                round_trip(&mut codec, &mut peer_codec, &protocol, message.clone()).unwrap();
+            assert_eq!(received, message, "corrupted message of length {}", length);
+        }
+    }
+
+    #[test]
+    fn should_send_incompressible_message_uncompressed() {
+        let mut rng = TestRng::new();
+        let config = Config::default();
+        let codec = new_codec(&config);
+        let message: Vec<u8> = (0..config.one_way_compression_threshold * 2)
+            .map(|_| rng.gen())
+            .collect();
+
+        let frame = codec.compress(message.clone()).unwrap();
+        assert_eq!(frame[0], UNCOMPRESSED_FLAG);
+        assert_eq!(codec.decompress(&frame).unwrap(), message);
+    }
+
+    #[test]
+    fn should_reject_decompression_bomb() {
+        let max_one_way_message_size = 1_000;
+        let config = Config {
+            max_one_way_message_size,
+            ..Config::default()
+        };
+        let codec = new_codec(&config);
+
+        // A tiny compressed frame which would decompress to far more than the limit.
+        let bomb = vec![0; 100 * max_one_way_message_size as usize];
+        let mut frame = vec![ZSTD_FLAG];
+        frame.extend(zstd::block::compress(&bomb, zstd::DEFAULT_COMPRESSION_LEVEL).unwrap());
+        assert!(frame.len() < max_one_way_message_size as usize);
+
+        let error = codec.decompress(&frame).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        // A frame decompressing to exactly the limit is accepted.
+        let mut frame = vec![ZSTD_FLAG];
+        frame.extend(
+            zstd::block::compress(
+                &bomb[..max_one_way_message_size as usize],
+                zstd::DEFAULT_COMPRESSION_LEVEL,
+            )
+            .unwrap(),
+        );
+        assert_eq!(
+            codec.decompress(&frame).unwrap().len(),
+            max_one_way_message_size as usize
+        );
+    }
+
+    #[test]
+    fn should_reject_invalid_compression_flag() {
+        let codec = new_codec(&Config::default());
+        assert!(codec.decompress(&[]).is_err());
+        assert!(codec.decompress(&[2, 1, 2, 3]).is_err());
+    }
+
+    #[test]
+    fn should_communicate_with_peers_of_mixed_capabilities() {
+        let mut rng = TestRng::new();
+        let chainspec = Chainspec::random(&mut rng);
+        let new_config = |compress_one_way_messages| Config {
+            compress_one_way_messages,
+            ..Config::default()
+        };
+        let threshold = Config::default().one_way_compression_threshold as usize;
+
+        for &(dialer_compresses, listener_compresses) in
+            &[(true, true), (true, false), (false, true), (false, false)]
+        {
+            let dialer_config = new_config(dialer_compresses);
+            let listener_config = new_config(listener_compresses);
+            let listener_protocols = supported_protocols(&listener_config, &chainspec);
+
+            // Negotiation picks the dialer's most preferred protocol supported by the listener.
+            let protocol = supported_protocols(&dialer_config, &chainspec)
+                .into_iter()
+                .find(|protocol| {
+                    listener_protocols
+                        .iter()
+                        .any(|other| other.protocol_name() == protocol.protocol_name())
+                })
+                .expect("should negotiate a protocol");
+            assert_eq!(
+                is_compressed_protocol(&protocol),
+                dialer_compresses && listener_compresses
+            );
+
+            let mut dialer_codec = new_codec(&dialer_config);
+            let mut listener_codec = new_codec(&listener_config);
+            for &length in &[threshold - 1, threshold + 1] {
+                let message = compressible_message(length);
+                let received = round_trip(
+                    &mut dialer_codec,
+                    &mut listener_codec,
+                    &protocol,
+                    message.clone(),
+                )
+                .unwrap();
+                assert_eq!(received, message);
+            }
+        }
+    }
+}
@@ -46,7 +46,7 @@ pub(super) fn new_behaviors(
 
     // Protocol version and agent version are separate to the protocol ID for the Identify behavior.
     // See https://github.com/libp2p/specs/tree/master/identify for further details.
-    let protocol_version = format!("/casper/{}", chainspec.protocol_config.version);
+    let protocol_version = format!("/casper/{}", chainspec.protocol_version());
     let agent_version = format!("{}/{}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
     let identify = Identify::new(protocol_version, agent_version, our_public_key);
 
@@ -16,7 +16,9 @@ impl ProtocolId {
     pub(super) fn new(chainspec: &Chainspec, name: &str) -> Self {
         let id = format!(
             "/casper/{}/{}/{}",
-            chainspec.network_config.name, name, chainspec.protocol_config.version
+            chainspec.network_config.name,
+            name,
+            chainspec.protocol_version()
         );
 
         assert!(
@@ -27,6 +29,19 @@ impl ProtocolId {
 
         ProtocolId { id }
     }
+
+    /// Returns a variant of this protocol ID with `suffix` appended.
+    pub(super) fn with_suffix(&self, suffix: &str) -> Self {
+        let id = format!("{}{}", self.id, suffix);
+
+        assert!(
+            id.as_bytes().len() <= MAX_PROTOCOL_ID_LENGTH,
+            "Protocol IDs must not exceed {} bytes in length",
+            MAX_PROTOCOL_ID_LENGTH
+        );
+
+        ProtocolId { id }
+    }
 }
 
 impl ProtocolName for ProtocolId {
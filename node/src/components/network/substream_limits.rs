@@ -0,0 +1,554 @@
+//! This module is home to the enforcement of limits on the number of concurrent inbound
+//! substreams.
+//!
+//! libp2p can limit the number of connections, but not the number of substreams a peer may open
+//! on an established connection.  `SubstreamLimiter` wraps a behavior and refuses inbound
+//! substreams for it once either the per-connection or the global limit is reached.
+//!
+//! A refused substream has its protocol negotiated as normal, but its upgrade then fails
+//! immediately, dropping only that substream.  The failure is not passed on to the wrapped
+//! behavior, so the peer is neither treated as unable to speak the protocol nor disconnected.
+//! Instead, a `SubstreamLimiterEvent::Refused` is emitted so the peer can be penalized.
+
+use std::{
+    collections::VecDeque,
+    error, io,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+};
+
+use futures::{
+    future::{self, BoxFuture},
+    FutureExt,
+};
+use libp2p::{
+    core::{
+        connection::{ConnectionId, ListenerId},
+        upgrade::{InboundUpgrade, UpgradeError, UpgradeInfo},
+        ConnectedPoint,
+    },
+    swarm::{
+        protocols_handler::{InboundUpgradeSend, OutboundUpgradeSend, UpgradeInfoSend},
+        IntoProtocolsHandler, KeepAlive, NegotiatedSubstream, NetworkBehaviour,
+        NetworkBehaviourAction, PollParameters, ProtocolsHandler, ProtocolsHandlerEvent,
+        ProtocolsHandlerUpgrErr, SubstreamProtocol,
+    },
+    Multiaddr, PeerId,
+};
+
+use super::Config;
+
+/// The handler type of the behavior wrapped in a `SubstreamLimiter`.
+type InnerHandler<B> = <<B as NetworkBehaviour>::ProtocolsHandler as IntoProtocolsHandler>::Handler;
+
+/// A count of concurrent substreams, along with the limit on that count.
+#[derive(Clone, Debug)]
+struct Counter {
+    count: Arc<AtomicUsize>,
+    limit: usize,
+}
+
+impl Counter {
+    fn new(limit: u32) -> Self {
+        Counter {
+            count: Arc::new(AtomicUsize::new(0)),
+            limit: limit as usize,
+        }
+    }
+
+    /// Increments the count, returning a guard which decrements it again when dropped, or `None`
+    /// if the limit has already been reached.
+    fn try_increment(&self) -> Option<CounterGuard> {
+        if self.count.fetch_add(1, Ordering::SeqCst) >= self.limit {
+            self.count.fetch_sub(1, Ordering::SeqCst);
+            return None;
+        }
+        Some(CounterGuard(Arc::clone(&self.count)))
+    }
+}
+
+struct CounterGuard(Arc<AtomicUsize>);
+
+impl Drop for CounterGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Permission for an inbound substream to proceed, held until its upgrade has completed.
+struct Permit {
+    _connection: CounterGuard,
+    _global: CounterGuard,
+}
+
+/// The error returned by a `LimitedUpgrade`.
+#[derive(Debug)]
+pub(super) enum LimitedUpgradeError<E> {
+    /// The substream was refused as a limit had been reached.
+    Refused,
+    /// The wrapped upgrade failed.
+    Inner(E),
+}
+
+/// An inbound upgrade which either applies the wrapped upgrade while holding a `Permit`, or fails
+/// immediately if no permit could be acquired.
+pub(super) struct LimitedUpgrade<U> {
+    inner: U,
+    permit: Option<Permit>,
+}
+
+impl<U: UpgradeInfoSend> UpgradeInfo for LimitedUpgrade<U> {
+    type Info = U::Info;
+    type InfoIter = U::InfoIter;
+
+    fn protocol_info(&self) -> Self::InfoIter {
+        self.inner.protocol_info()
+    }
+}
+
+impl<U: InboundUpgradeSend> InboundUpgrade<NegotiatedSubstream> for LimitedUpgrade<U> {
+    type Output = U::Output;
+    type Error = LimitedUpgradeError<U::Error>;
+    type Future = BoxFuture<'static, Result<Self::Output, Self::Error>>;
+
+    fn upgrade_inbound(self, socket: NegotiatedSubstream, info: Self::Info) -> Self::Future {
+        let LimitedUpgrade { inner, permit } = self;
+        match permit {
+            Some(permit) => InboundUpgradeSend::upgrade_inbound(inner, socket, info)
+                .map(move |result| {
+                    drop(permit);
+                    result.map_err(LimitedUpgradeError::Inner)
+                })
+                .boxed(),
+            None => future::ready(Err(LimitedUpgradeError::Refused)).boxed(),
+        }
+    }
+}
+
+/// The event emitted by a `LimitedHandler`.
+#[derive(Debug)]
+pub(super) enum LimitedHandlerEvent<E> {
+    /// An inbound substream was refused.
+    Refused,
+    /// An event emitted by the wrapped handler.
+    Inner(E),
+}
+
+/// A prototype of a `LimitedHandler`.
+pub(super) struct IntoLimitedHandler<H> {
+    inner: H,
+    max_substreams_per_connection: u32,
+    all_substreams: Counter,
+}
+
+impl<H: IntoProtocolsHandler> IntoProtocolsHandler for IntoLimitedHandler<H> {
+    type Handler = LimitedHandler<H::Handler>;
+
+    fn into_handler(self, peer_id: &PeerId, connected_point: &ConnectedPoint) -> Self::Handler {
+        LimitedHandler {
+            inner: self.inner.into_handler(peer_id, connected_point),
+            connection_substreams: Counter::new(self.max_substreams_per_connection),
+            all_substreams: self.all_substreams,
+            refused_count: 0,
+        }
+    }
+
+    /// Returns the wrapped handler's inbound protocol, without a permit.  This is only used to
+    /// enumerate the supported protocols.
+    fn inbound_protocol(&self) -> <Self::Handler as ProtocolsHandler>::InboundProtocol {
+        LimitedUpgrade {
+            inner: self.inner.inbound_protocol(),
+            permit: None,
+        }
+    }
+}
+
+/// A handler which limits the number of concurrent inbound substreams of the wrapped handler on
+/// its connection, and across all connections.
+pub(super) struct LimitedHandler<H> {
+    inner: H,
+    /// The inbound substreams currently being upgraded on this connection.
+    connection_substreams: Counter,
+    /// The inbound substreams currently being upgraded on all connections.
+    all_substreams: Counter,
+    /// The number of refused substreams not yet reported to the behavior.
+    refused_count: usize,
+}
+
+impl<H> LimitedHandler<H> {
+    fn try_acquire_permit(&self) -> Option<Permit> {
+        let connection = self.connection_substreams.try_increment()?;
+        let global = self.all_substreams.try_increment()?;
+        Some(Permit {
+            _connection: connection,
+            _global: global,
+        })
+    }
+}
+
+impl<H: ProtocolsHandler> ProtocolsHandler for LimitedHandler<H> {
+    type InEvent = H::InEvent;
+    type OutEvent = LimitedHandlerEvent<H::OutEvent>;
+    type Error = H::Error;
+    type InboundProtocol = LimitedUpgrade<H::InboundProtocol>;
+    type OutboundProtocol = H::OutboundProtocol;
+    type InboundOpenInfo = H::InboundOpenInfo;
+    type OutboundOpenInfo = H::OutboundOpenInfo;
+
+    fn listen_protocol(&self) -> SubstreamProtocol<Self::InboundProtocol, Self::InboundOpenInfo> {
+        let permit = self.try_acquire_permit();
+        self.inner
+            .listen_protocol()
+            .map_upgrade(|inner| LimitedUpgrade { inner, permit })
+    }
+
+    fn inject_fully_negotiated_inbound(
+        &mut self,
+        protocol: <Self::InboundProtocol as InboundUpgradeSend>::Output,
+        info: Self::InboundOpenInfo,
+    ) {
+        self.inner.inject_fully_negotiated_inbound(protocol, info)
+    }
+
+    fn inject_fully_negotiated_outbound(
+        &mut self,
+        protocol: <Self::OutboundProtocol as OutboundUpgradeSend>::Output,
+        info: Self::OutboundOpenInfo,
+    ) {
+        self.inner.inject_fully_negotiated_outbound(protocol, info)
+    }
+
+    fn inject_event(&mut self, event: Self::InEvent) {
+        self.inner.inject_event(event)
+    }
+
+    fn inject_address_change(&mut self, new_address: &Multiaddr) {
+        self.inner.inject_address_change(new_address)
+    }
+
+    fn inject_dial_upgrade_error(
+        &mut self,
+        info: Self::OutboundOpenInfo,
+        error: ProtocolsHandlerUpgrErr<<Self::OutboundProtocol as OutboundUpgradeSend>::Error>,
+    ) {
+        self.inner.inject_dial_upgrade_error(info, error)
+    }
+
+    fn inject_listen_upgrade_error(
+        &mut self,
+        info: Self::InboundOpenInfo,
+        error: ProtocolsHandlerUpgrErr<<Self::InboundProtocol as InboundUpgradeSend>::Error>,
+    ) {
+        let inner_error = match error {
+            ProtocolsHandlerUpgrErr::Upgrade(UpgradeError::Apply(LimitedUpgradeError::Refused)) => {
+                self.refused_count += 1;
+                return;
+            }
+            ProtocolsHandlerUpgrErr::Upgrade(UpgradeError::Apply(LimitedUpgradeError::Inner(
+                error,
+            ))) => ProtocolsHandlerUpgrErr::Upgrade(UpgradeError::Apply(error)),
+            ProtocolsHandlerUpgrErr::Upgrade(UpgradeError::Select(error)) => {
+                ProtocolsHandlerUpgrErr::Upgrade(UpgradeError::Select(error))
+            }
+            ProtocolsHandlerUpgrErr::Timeout => ProtocolsHandlerUpgrErr::Timeout,
+            ProtocolsHandlerUpgrErr::Timer => ProtocolsHandlerUpgrErr::Timer,
+        };
+        self.inner.inject_listen_upgrade_error(info, inner_error)
+    }
+
+    fn connection_keep_alive(&self) -> KeepAlive {
+        self.inner.connection_keep_alive()
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn poll(
+        &mut self,
+        context: &mut Context<'_>,
+    ) -> Poll<
+        ProtocolsHandlerEvent<
+            Self::OutboundProtocol,
+            Self::OutboundOpenInfo,
+            Self::OutEvent,
+            Self::Error,
+        >,
+    > {
+        if self.refused_count > 0 {
+            self.refused_count -= 1;
+            return Poll::Ready(ProtocolsHandlerEvent::Custom(LimitedHandlerEvent::Refused));
+        }
+        self.inner
+            .poll(context)
+            .map(|event| event.map_custom(LimitedHandlerEvent::Inner))
+    }
+}
+
+/// The event emitted by a `SubstreamLimiter`.
+#[derive(Debug)]
+pub(super) enum SubstreamLimiterEvent<E> {
+    /// An inbound substream opened by the given peer was refused as a limit had been reached.
+    Refused { peer_id: PeerId },
+    /// An event emitted by the wrapped behavior.
+    Inner(E),
+}
+
+/// A behavior which limits the number of concurrent inbound substreams of the wrapped behavior,
+/// both per connection and across all connections.
+pub(super) struct SubstreamLimiter<B> {
+    inner: B,
+    max_substreams_per_connection: u32,
+    /// The inbound substreams currently being upgraded on all connections.
+    all_substreams: Counter,
+    /// Peers whose substreams have been refused, pending a poll.
+    refusals: VecDeque<PeerId>,
+}
+
+impl<B> SubstreamLimiter<B> {
+    pub(super) fn new(config: &Config, inner: B) -> Self {
+        SubstreamLimiter {
+            inner,
+            max_substreams_per_connection: config.max_inbound_substreams_per_connection,
+            all_substreams: Counter::new(config.max_inbound_substreams),
+            refusals: VecDeque::new(),
+        }
+    }
+
+    /// Returns a mutable reference to the wrapped behavior.
+    pub(super) fn inner_mut(&mut self) -> &mut B {
+        &mut self.inner
+    }
+}
+
+impl<B: NetworkBehaviour> NetworkBehaviour for SubstreamLimiter<B> {
+    type ProtocolsHandler = IntoLimitedHandler<B::ProtocolsHandler>;
+    type OutEvent = SubstreamLimiterEvent<B::OutEvent>;
+
+    fn new_handler(&mut self) -> Self::ProtocolsHandler {
+        IntoLimitedHandler {
+            inner: self.inner.new_handler(),
+            max_substreams_per_connection: self.max_substreams_per_connection,
+            all_substreams: self.all_substreams.clone(),
+        }
+    }
+
+    fn addresses_of_peer(&mut self, peer_id: &PeerId) -> Vec<Multiaddr> {
+        self.inner.addresses_of_peer(peer_id)
+    }
+
+    fn inject_connected(&mut self, peer_id: &PeerId) {
+        self.inner.inject_connected(peer_id)
+    }
+
+    fn inject_disconnected(&mut self, peer_id: &PeerId) {
+        self.inner.inject_disconnected(peer_id)
+    }
+
+    fn inject_connection_established(
+        &mut self,
+        peer_id: &PeerId,
+        connection: &ConnectionId,
+        endpoint: &ConnectedPoint,
+    ) {
+        self.inner
+            .inject_connection_established(peer_id, connection, endpoint)
+    }
+
+    fn inject_connection_closed(
+        &mut self,
+        peer_id: &PeerId,
+        connection: &ConnectionId,
+        endpoint: &ConnectedPoint,
+    ) {
+        self.inner
+            .inject_connection_closed(peer_id, connection, endpoint)
+    }
+
+    fn inject_address_change(
+        &mut self,
+        peer_id: &PeerId,
+        connection: &ConnectionId,
+        old: &ConnectedPoint,
+        new: &ConnectedPoint,
+    ) {
+        self.inner
+            .inject_address_change(peer_id, connection, old, new)
+    }
+
+    fn inject_event(
+        &mut self,
+        peer_id: PeerId,
+        connection: ConnectionId,
+        event: LimitedHandlerEvent<<InnerHandler<B> as ProtocolsHandler>::OutEvent>,
+    ) {
+        match event {
+            LimitedHandlerEvent::Refused => self.refusals.push_back(peer_id),
+            LimitedHandlerEvent::Inner(event) => {
+                self.inner.inject_event(peer_id, connection, event)
+            }
+        }
+    }
+
+    fn inject_addr_reach_failure(
+        &mut self,
+        peer_id: Option<&PeerId>,
+        address: &Multiaddr,
+        error: &dyn error::Error,
+    ) {
+        self.inner
+            .inject_addr_reach_failure(peer_id, address, error)
+    }
+
+    fn inject_dial_failure(&mut self, peer_id: &PeerId) {
+        self.inner.inject_dial_failure(peer_id)
+    }
+
+    fn inject_new_listen_addr(&mut self, address: &Multiaddr) {
+        self.inner.inject_new_listen_addr(address)
+    }
+
+    fn inject_expired_listen_addr(&mut self, address: &Multiaddr) {
+        self.inner.inject_expired_listen_addr(address)
+    }
+
+    fn inject_new_external_addr(&mut self, address: &Multiaddr) {
+        self.inner.inject_new_external_addr(address)
+    }
+
+    fn inject_listener_error(&mut self, id: ListenerId, error: &(dyn error::Error + 'static)) {
+        self.inner.inject_listener_error(id, error)
+    }
+
+    fn inject_listener_closed(&mut self, id: ListenerId, reason: Result<(), &io::Error>) {
+        self.inner.inject_listener_closed(id, reason)
+    }
+
+    fn poll(
+        &mut self,
+        context: &mut Context<'_>,
+        parameters: &mut impl PollParameters,
+    ) -> Poll<NetworkBehaviourAction<<InnerHandler<B> as ProtocolsHandler>::InEvent, Self::OutEvent>>
+    {
+        if let Some(peer_id) = self.refusals.pop_front() {
+            return Poll::Ready(NetworkBehaviourAction::GenerateEvent(
+                SubstreamLimiterEvent::Refused { peer_id },
+            ));
+        }
+        self.inner
+            .poll(context, parameters)
+            .map(|action| action.map_out(SubstreamLimiterEvent::Inner))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use libp2p::swarm::protocols_handler::DummyProtocolsHandler;
+
+    use super::*;
+
+    const MAX_PER_CONNECTION: u32 = 4;
+    const MAX_GLOBAL: u32 = 6;
+
+    fn new_limiter() -> SubstreamLimiter<()> {
+        let config = Config {
+            max_inbound_substreams_per_connection: MAX_PER_CONNECTION,
+            max_inbound_substreams: MAX_GLOBAL,
+            ..Default::default()
+        };
+        SubstreamLimiter::new(&config, ())
+    }
+
+    fn new_handler(limiter: &SubstreamLimiter<()>) -> LimitedHandler<DummyProtocolsHandler> {
+        IntoLimitedHandler {
+            inner: DummyProtocolsHandler::default(),
+            max_substreams_per_connection: limiter.max_substreams_per_connection,
+            all_substreams: limiter.all_substreams.clone(),
+        }
+        .into_handler(
+            &PeerId::random(),
+            &ConnectedPoint::Dialer {
+                address: Multiaddr::empty(),
+            },
+        )
+    }
+
+    /// Opens `count` inbound substreams on the handler, returning those which were accepted.
+    fn open_substreams(
+        handler: &LimitedHandler<DummyProtocolsHandler>,
+        count: u32,
+    ) -> Vec<LimitedUpgrade<<DummyProtocolsHandler as ProtocolsHandler>::InboundProtocol>> {
+        (0..count)
+            .map(|_| handler.listen_protocol().into_upgrade().1)
+            .filter(|upgrade| upgrade.permit.is_some())
+            .collect()
+    }
+
+    #[test]
+    fn should_refuse_substreams_beyond_per_connection_limit() {
+        let limiter = new_limiter();
+        let spammer = new_handler(&limiter);
+        let other_peer = new_handler(&limiter);
+
+        let mut accepted = Vec::new();
+        for attempt in 1..=MAX_PER_CONNECTION * 2 {
+            let upgrade = spammer.listen_protocol().into_upgrade().1;
+            assert_eq!(
+                upgrade.permit.is_some(),
+                attempt <= MAX_PER_CONNECTION,
+                "unexpected outcome for attempt {}",
+                attempt
+            );
+            accepted.push(upgrade);
+        }
+
+        // The other peer is unaffected.
+        assert_eq!(open_substreams(&other_peer, 1).len(), 1);
+
+        // Completing a substream frees a slot for a further one.
+        accepted.remove(0);
+        assert_eq!(open_substreams(&spammer, 2).len(), 1);
+    }
+
+    #[test]
+    fn should_refuse_substreams_beyond_global_limit() {
+        let limiter = new_limiter();
+        let first = new_handler(&limiter);
+        let second = new_handler(&limiter);
+
+        let first_accepted = open_substreams(&first, MAX_PER_CONNECTION);
+        assert_eq!(first_accepted.len(), MAX_PER_CONNECTION as usize);
+
+        let second_accepted = open_substreams(&second, MAX_PER_CONNECTION);
+        assert_eq!(
+            second_accepted.len(),
+            (MAX_GLOBAL - MAX_PER_CONNECTION) as usize
+        );
+
+        // A refused attempt on a connection below its own limit mustn't consume a slot.
+        drop(first_accepted);
+        assert_eq!(open_substreams(&second, MAX_PER_CONNECTION).len(), 2);
+    }
+
+    #[test]
+    fn should_report_refusals_without_forwarding_them() {
+        let limiter = new_limiter();
+        let mut handler = new_handler(&limiter);
+        let _accepted = open_substreams(&handler, MAX_PER_CONNECTION);
+
+        let (_, upgrade, info) = handler.listen_protocol().into_upgrade();
+        assert!(upgrade.permit.is_none());
+        handler.inject_listen_upgrade_error(
+            info,
+            ProtocolsHandlerUpgrErr::Upgrade(UpgradeError::Apply(LimitedUpgradeError::Refused)),
+        );
+
+        let waker = futures::task::noop_waker();
+        let mut context = Context::from_waker(&waker);
+        assert!(matches!(
+            handler.poll(&mut context),
+            Poll::Ready(ProtocolsHandlerEvent::Custom(LimitedHandlerEvent::Refused))
+        ));
+        assert!(handler.poll(&mut context).is_pending());
+    }
+}
@@ -1,36 +1,53 @@
 use std::{
     collections::{HashMap, HashSet},
-    env,
     fmt::{self, Debug, Display, Formatter},
+    iter,
+    num::NonZeroU32,
+    sync::Arc,
     time::{Duration, Instant},
 };
 
 use derive_more::From;
+use futures::future;
+use libp2p::{
+    core::connection::ConnectedPoint,
+    gossipsub::GossipsubEvent,
+    swarm::{NetworkBehaviour, SwarmEvent},
+    Multiaddr, PeerId, Swarm,
+};
 use pnet::datalink;
 use prometheus::Registry;
 use reactor::ReactorEvent;
 use serde::Serialize;
+use tokio::time;
 use tracing::{debug, info};
 
 use super::{
-    network_is_isolated, Config, Event as NetworkEvent, Network as NetworkComponent,
-    ENABLE_LIBP2P_NET_ENV_VAR,
+    address_gossip, address_str_to_multiaddr, gossip, network_is_isolated, new_swarm, Behavior,
+    Config, Event as NetworkEvent, GossipMessage, Network as NetworkComponent, PayloadT,
+    SwarmBehaviorEvent, MAX_OVERSIZED_MESSAGES_PER_PEER, MAX_REFUSED_SUBSTREAMS_PER_PEER,
 };
 use crate::{
-    components::{network::NetworkIdentity, Component},
+    components::{
+        network::NetworkIdentity,
+        network_provider::{NetworkProvider, NetworkProviderKind},
+        networking_metrics::NetworkingMetrics,
+        Component,
+    },
     effect::{
-        announcements::{ControlAnnouncement, NetworkAnnouncement},
+        announcements::{BlocklistAnnouncement, ControlAnnouncement, NetworkAnnouncement},
         requests::NetworkRequest,
-        EffectBuilder, Effects,
+        EffectBuilder, EffectExt, Effects,
     },
     protocol,
-    reactor::{self, EventQueueHandle, Finalize, Reactor, Runner},
+    reactor::{self, EventQueueHandle, Finalize, QueueKind, Reactor, Runner},
     testing::{
         self, init_logging,
         network::{Network, NetworkedReactor},
         ConditionCheckReactor,
     },
-    types::{Chainspec, NodeId},
+    types::{Chainspec, GossipState, NodeId, TimeDiff},
+    utils::Loadable,
     NodeRng,
 };
 
@@ -45,8 +62,12 @@ enum Event {
     ControlAnnouncement(ControlAnnouncement),
     #[from]
     NetworkAnnouncement(#[serde(skip_serializing)] NetworkAnnouncement<NodeId, String>),
+    #[from]
+    BlocklistAnnouncement(#[serde(skip_serializing)] BlocklistAnnouncement<NodeId>),
 }
 
+impl PayloadT for String {}
+
 impl ReactorEvent for Event {
     fn as_control(&self) -> Option<&ControlAnnouncement> {
         if let Self::ControlAnnouncement(ref ctrl_ann) = self {
@@ -75,6 +96,10 @@ impl Display for Event {
 #[derive(Debug)]
 struct TestReactor {
     network_component: NetworkComponent<Event, String>,
+    /// The one-way messages received, along with their senders.
+    received_messages: Vec<(NodeId, String)>,
+    /// The one-way messages which failed to send, along with their intended recipients.
+    failed_sends: Vec<(NodeId, String)>,
 }
 
 impl Reactor for TestReactor {
@@ -86,15 +111,20 @@ impl Reactor for TestReactor {
         config: Self::Config,
         registry: &Registry,
         event_queue: EventQueueHandle<Self::Event>,
-        rng: &mut NodeRng,
+        _rng: &mut NodeRng,
     ) -> anyhow::Result<(Self, Effects<Self::Event>)> {
-        let chainspec = Chainspec::random(rng);
+        // All nodes must share a chainspec, as it is part of the protocol IDs they negotiate.
+        let chainspec = Arc::new(Chainspec::from_resources("local"));
         let network_identity = NetworkIdentity::new();
         let (network_component, effects) =
-            NetworkComponent::new(event_queue, config, registry, network_identity, &chainspec)?;
+            NetworkComponent::new(event_queue, config, registry, network_identity, chainspec)?;
 
         Ok((
-            TestReactor { network_component },
+            TestReactor {
+                network_component,
+                received_messages: vec![],
+                failed_sends: vec![],
+            },
             reactor::wrap_effects(Event::Network, effects),
         ))
     }
@@ -123,7 +153,8 @@ impl Reactor for TestReactor {
                 sender,
                 payload,
             }) => {
-                todo!("{} -- {}", sender, payload);
+                self.received_messages.push((sender, payload));
+                Effects::new()
             }
             Event::NetworkAnnouncement(NetworkAnnouncement::GossipOurAddress(
                 _gossiped_address,
@@ -134,6 +165,26 @@ impl Reactor for TestReactor {
                 // We do not care about the announcement of new peers in this test.
                 Effects::new()
             }
+            Event::NetworkAnnouncement(NetworkAnnouncement::PeerCountChanged(_)) => {
+                // We do not care about the announcement of the peer count in this test.
+                Effects::new()
+            }
+            Event::NetworkAnnouncement(NetworkAnnouncement::PeerConnected { .. })
+            | Event::NetworkAnnouncement(NetworkAnnouncement::PeerDisconnected { .. }) => {
+                // We do not care about the announcement of connection lifecycle in this test.
+                Effects::new()
+            }
+            Event::NetworkAnnouncement(NetworkAnnouncement::MessageSendFailed {
+                destination,
+                payload,
+                ..
+            }) => {
+                self.failed_sends.push((destination, payload));
+                Effects::new()
+            }
+            Event::BlocklistAnnouncement(ann) => {
+                self.dispatch_event(effect_builder, rng, Event::Network(ann.into()))
+            }
         }
     }
 
@@ -204,11 +255,6 @@ fn network_started(net: &Network<TestReactor>) -> bool {
 /// Ensures that network cleanup and basic networking works.
 #[tokio::test]
 async fn run_two_node_network_five_times() {
-    // If the env var "CASPER_ENABLE_LIBP2P_NET" is not defined, exit without running the test.
-    if env::var(ENABLE_LIBP2P_NET_ENV_VAR).is_err() {
-        return;
-    }
-
     let mut rng = crate::new_rng();
 
     // The networking port used by the tests for the root node.
@@ -270,11 +316,6 @@ async fn run_two_node_network_five_times() {
 /// Very unlikely to ever fail on a real machine.
 #[tokio::test]
 async fn bind_to_real_network_interface() {
-    // If the env var "CASPER_ENABLE_LIBP2P_NET" is not defined, exit without running the test.
-    if env::var(ENABLE_LIBP2P_NET_ENV_VAR).is_err() {
-        return;
-    }
-
     init_logging();
 
     let mut rng = crate::new_rng();
@@ -315,11 +356,6 @@ async fn bind_to_real_network_interface() {
 /// Check that a network of varying sizes will connect all nodes properly.
 #[tokio::test]
 async fn check_varying_size_network_connects() {
-    // If the env var "CASPER_ENABLE_LIBP2P_NET" is not defined, exit without running the test.
-    if env::var(ENABLE_LIBP2P_NET_ENV_VAR).is_err() {
-        return;
-    }
-
     init_logging();
 
     let mut rng = crate::new_rng();
@@ -367,3 +403,510 @@ async fn check_varying_size_network_connects() {
         net.finalize().await;
     }
 }
+
+/// Checks that a peer which commits the offense created by `offense` `limit` times is blocked and
+/// disconnected, but not before reaching the limit.
+async fn check_peer_blocked_after_offenses<F>(offense: F, limit: u32)
+where
+    F: Fn(NodeId) -> NetworkEvent<String> + Copy,
+{
+    init_logging();
+
+    let mut rng = crate::new_rng();
+    let first_node_port = testing::unused_port_on_localhost() + 1;
+
+    let mut net = Network::new();
+    let (first_node_id, _) = net
+        .add_node_with_config(
+            Config::default_local_net_first_node(first_node_port),
+            &mut rng,
+        )
+        .await
+        .unwrap();
+    let (offender_id, _) = net
+        .add_node_with_config(Config::default_local_net(first_node_port), &mut rng)
+        .await
+        .unwrap();
+
+    let timeout = Duration::from_secs(20);
+    let blocklist = HashSet::new();
+    net.settle_on(
+        &mut rng,
+        |nodes| network_is_complete(&blocklist, nodes),
+        timeout,
+    )
+    .await;
+
+    let commit_offense = move |effect_builder: EffectBuilder<Event>| {
+        effect_builder
+            .into_inner()
+            .schedule(offense(offender_id), QueueKind::NetworkIncoming)
+            .ignore()
+    };
+
+    // Up to the limit, the offender should be tolerated.
+    for _ in 1..limit {
+        net.process_injected_effect_on(&first_node_id, commit_offense)
+            .await;
+    }
+    net.settle(&mut rng, Duration::from_millis(25), Duration::from_secs(2))
+        .await;
+    let first_node = &net.nodes()[&first_node_id]
+        .reactor()
+        .inner()
+        .network_component;
+    assert!(!first_node.blocked_peers.contains(&offender_id));
+    assert!(first_node.peers.contains_key(&offender_id));
+
+    // Once the limit is reached, it should be blocked and disconnected.
+    net.process_injected_effect_on(&first_node_id, commit_offense)
+        .await;
+    net.settle_on(
+        &mut rng,
+        |nodes| {
+            let first_node = &nodes[&first_node_id].reactor().inner().network_component;
+            first_node.blocked_peers.contains(&offender_id)
+                && !first_node.peers.contains_key(&offender_id)
+        },
+        timeout,
+    )
+    .await;
+
+    net.finalize().await;
+}
+
+/// Check that a peer which repeatedly sends messages exceeding the size limit for their class is
+/// blocked.
+#[tokio::test]
+async fn should_block_peer_sending_oversized_messages() {
+    let oversized_message = |sender: NodeId| NetworkEvent::OversizedMessageReceived {
+        sender: Box::new(sender),
+        max_size: 1,
+        actual_size: 2,
+    };
+    check_peer_blocked_after_offenses(oversized_message, MAX_OVERSIZED_MESSAGES_PER_PEER).await;
+}
+
+/// Check that a peer which repeatedly exceeds the inbound substream limits is blocked.
+#[tokio::test]
+async fn should_block_peer_exceeding_substream_limits() {
+    let refused_substream = |peer_id: NodeId| NetworkEvent::InboundSubstreamRefused {
+        peer_id: Box::new(peer_id),
+    };
+    check_peer_blocked_after_offenses(refused_substream, MAX_REFUSED_SUBSTREAMS_PER_PEER).await;
+}
+
+/// Sets up a fully connected two-node network with the given grace period for holding messages for
+/// disconnected peers, then has the first node observe the second disconnecting and send it a
+/// message.
+///
+/// Returns the network, the IDs of the first and second nodes, and the first node's endpoint of its
+/// connection to the second.
+async fn send_to_disconnected_peer(
+    rng: &mut NodeRng,
+    grace_period: TimeDiff,
+    message: &str,
+) -> (Network<TestReactor>, NodeId, NodeId, ConnectedPoint) {
+    init_logging();
+
+    let first_node_port = testing::unused_port_on_localhost() + 1;
+    let first_node_config = Config {
+        disconnected_peer_grace_period: grace_period,
+        ..Config::default_local_net_first_node(first_node_port)
+    };
+
+    let mut net = Network::new();
+    let (first_node_id, _) = net
+        .add_node_with_config(first_node_config, rng)
+        .await
+        .unwrap();
+    let (second_node_id, _) = net
+        .add_node_with_config(Config::default_local_net(first_node_port), rng)
+        .await
+        .unwrap();
+
+    let blocklist = HashSet::new();
+    net.settle_on(
+        rng,
+        |nodes| network_is_complete(&blocklist, nodes),
+        Duration::from_secs(20),
+    )
+    .await;
+
+    let endpoint = net.nodes()[&first_node_id]
+        .reactor()
+        .inner()
+        .network_component
+        .peers[&second_node_id]
+        .clone();
+    let closed_endpoint = endpoint.clone();
+    let message = message.to_string();
+    net.process_injected_effect_on(&first_node_id, move |effect_builder| {
+        let mut effects = effect_builder
+            .into_inner()
+            .schedule(
+                NetworkEvent::ConnectionClosed {
+                    peer_id: Box::new(second_node_id),
+                    endpoint: closed_endpoint,
+                    num_established: 0,
+                    cause: None,
+                },
+                QueueKind::Network,
+            )
+            .ignore();
+        effects.extend(
+            effect_builder
+                .send_message(second_node_id, message)
+                .ignore(),
+        );
+        effects
+    })
+    .await;
+    net.settle(rng, Duration::from_millis(25), Duration::from_secs(2))
+        .await;
+
+    (net, first_node_id, second_node_id, endpoint)
+}
+
+/// Has the first node observe the second reconnecting via the given endpoint.
+async fn reconnect(
+    net: &mut Network<TestReactor>,
+    first_node_id: NodeId,
+    second_node_id: NodeId,
+    endpoint: ConnectedPoint,
+) {
+    net.process_injected_effect_on(&first_node_id, move |effect_builder| {
+        effect_builder
+            .into_inner()
+            .schedule(
+                NetworkEvent::ConnectionEstablished {
+                    peer_id: Box::new(second_node_id),
+                    endpoint,
+                    num_established: NonZeroU32::new(1).unwrap(),
+                },
+                QueueKind::Network,
+            )
+            .ignore()
+    })
+    .await;
+}
+
+/// Check that a message sent to a peer which reconnects within the grace period is delivered once
+/// it reconnects.
+#[tokio::test]
+async fn should_deliver_held_message_on_reconnection_within_grace_period() {
+    let mut rng = crate::new_rng();
+    let message = "held message";
+    let (mut net, first_node_id, second_node_id, endpoint) =
+        send_to_disconnected_peer(&mut rng, TimeDiff::from(60_000), message).await;
+
+    // The message should be held rather than sent while the peer is disconnected.
+    assert!(net.nodes()[&second_node_id]
+        .reactor()
+        .inner()
+        .received_messages
+        .is_empty());
+
+    reconnect(&mut net, first_node_id, second_node_id, endpoint).await;
+    net.settle_on(
+        &mut rng,
+        |nodes| {
+            nodes[&second_node_id]
+                .reactor()
+                .inner()
+                .received_messages
+                .contains(&(first_node_id, message.to_string()))
+        },
+        Duration::from_secs(10),
+    )
+    .await;
+    assert!(net.nodes()[&first_node_id]
+        .reactor()
+        .inner()
+        .failed_sends
+        .is_empty());
+
+    net.finalize().await;
+}
+
+/// Check that a message sent to a peer which doesn't reconnect within the grace period is announced
+/// as having failed to send, and is not delivered when the peer does reconnect.
+#[tokio::test]
+async fn should_not_deliver_held_message_on_reconnection_after_grace_period() {
+    let mut rng = crate::new_rng();
+    let message = "held message";
+    let (mut net, first_node_id, second_node_id, endpoint) =
+        send_to_disconnected_peer(&mut rng, TimeDiff::from(1_000), message).await;
+
+    net.settle_on(
+        &mut rng,
+        |nodes| {
+            nodes[&first_node_id]
+                .reactor()
+                .inner()
+                .failed_sends
+                .contains(&(second_node_id, message.to_string()))
+        },
+        Duration::from_secs(10),
+    )
+    .await;
+
+    reconnect(&mut net, first_node_id, second_node_id, endpoint).await;
+    net.settle(&mut rng, Duration::from_millis(500), Duration::from_secs(5))
+        .await;
+    assert!(net.nodes()[&second_node_id]
+        .reactor()
+        .inner()
+        .received_messages
+        .is_empty());
+
+    net.finalize().await;
+}
+
+/// Drives the given swarms until `done` returns `true` for an event generated by the behavior of
+/// one of them, returning `false` if this doesn't happen before `timeout` elapses.
+async fn drive_swarms_until<F>(
+    swarms: &mut [Swarm<Behavior>],
+    timeout: Duration,
+    mut done: F,
+) -> bool
+where
+    F: FnMut(usize, &mut Swarm<Behavior>, SwarmBehaviorEvent) -> bool,
+{
+    let deadline = time::Instant::now() + timeout;
+    loop {
+        let next_events = swarms
+            .iter_mut()
+            .enumerate()
+            .map(|(index, swarm)| Box::pin(async move { (index, swarm.next_event().await) }));
+        let ((index, swarm_event), _, remaining) =
+            match time::timeout_at(deadline, future::select_all(next_events)).await {
+                Ok(selected) => selected,
+                Err(_) => return false,
+            };
+        drop(remaining);
+
+        if let SwarmEvent::Behaviour(event) = swarm_event {
+            if done(index, &mut swarms[index], event) {
+                return true;
+            }
+        }
+    }
+}
+
+/// Check that when a node's listening address changes, its peers learn the new address and drop
+/// the old one within one address gossip interval, rather than waiting for the periodic identify
+/// exchange.
+#[tokio::test]
+async fn should_gossip_changed_listening_address() {
+    init_logging();
+
+    let mut rng = crate::new_rng();
+    let chainspec = Chainspec::random(&mut rng);
+    let config = Config::default();
+    let timeout = Duration::from(config.min_address_gossip_interval) + Duration::from_secs(1);
+    let net_metrics =
+        NetworkingMetrics::new(NetworkProviderKind::Libp2p, &Registry::default()).unwrap();
+
+    let identities = iter::repeat_with(NetworkIdentity::new)
+        .take(3)
+        .collect::<Vec<_>>();
+    let changing_peer_id = PeerId::from(&identities[0]);
+    let mut swarms = identities
+        .iter()
+        .map(|identity| new_swarm(&config, identity, &net_metrics, &chainspec).unwrap())
+        .collect::<Vec<_>>();
+
+    // Start the first node listening on its old address, and have the other two dial it.
+    let old_address = address_str_to_multiaddr(&format!(
+        "127.0.0.1:{}",
+        testing::unused_port_on_localhost()
+    ));
+    let old_listener = Swarm::listen_on(&mut swarms[0], old_address.clone()).unwrap();
+    for swarm in &mut swarms[1..] {
+        Swarm::listen_on(swarm, address_str_to_multiaddr("127.0.0.1:0")).unwrap();
+        Swarm::dial_addr(swarm, old_address.clone()).unwrap();
+    }
+
+    // Wait until both peers have subscribed to the first node's address gossip.
+    let mut subscribers = HashSet::new();
+    let subscribed = drive_swarms_until(&mut swarms, Duration::from_secs(10), |index, _, event| {
+        if let SwarmBehaviorEvent::Gossiper(GossipsubEvent::Subscribed { peer_id, topic }) = event {
+            if index == 0 && topic == address_gossip::TOPIC.hash() {
+                subscribers.insert(peer_id);
+            }
+        }
+        subscribers.len() == 2
+    })
+    .await;
+    assert!(subscribed, "peers should subscribe to address gossip");
+
+    // Replace the first node's listener.
+    let new_address = address_str_to_multiaddr(&format!(
+        "127.0.0.1:{}",
+        testing::unused_port_on_localhost()
+    ));
+    Swarm::listen_on(&mut swarms[0], new_address.clone()).unwrap();
+    Swarm::remove_listener(&mut swarms[0], old_listener).unwrap();
+    // Removing a listener doesn't report its addresses as expired, so do so as the swarm would if
+    // the listener had closed.
+    swarms[0].inject_expired_listen_addr(&old_address);
+
+    // The new address should be learned within one interval.
+    let mut learned_by = HashSet::new();
+    let learned = drive_swarms_until(&mut swarms, timeout, |index, swarm, event| {
+        if let SwarmBehaviorEvent::PeerAddressesAnnounced { peer_id, .. } = event {
+            if peer_id == changing_peer_id
+                && swarm
+                    .routing_table_addresses(&peer_id)
+                    .contains(&new_address)
+            {
+                learned_by.insert(index);
+            }
+        }
+        learned_by.len() == 2
+    })
+    .await;
+    assert!(
+        learned,
+        "peers should learn the new address within one interval"
+    );
+
+    // The old address may have been announced alongside the new one, in which case its withdrawal
+    // follows within a further interval.
+    let mut withdrawn_by = (1..swarms.len())
+        .filter(|&index| {
+            !swarms[index]
+                .routing_table_addresses(&changing_peer_id)
+                .contains(&old_address)
+        })
+        .collect::<HashSet<_>>();
+    let withdrawn = withdrawn_by.len() == 2
+        || drive_swarms_until(&mut swarms, timeout, |index, swarm, event| {
+            if let SwarmBehaviorEvent::PeerAddressesAnnounced { peer_id, .. } = event {
+                if peer_id == changing_peer_id
+                    && !swarm
+                        .routing_table_addresses(&peer_id)
+                        .contains(&old_address)
+                {
+                    withdrawn_by.insert(index);
+                }
+            }
+            withdrawn_by.len() == 2
+        })
+        .await;
+    assert!(
+        withdrawn,
+        "peers should drop the old address within one interval"
+    );
+}
+
+/// Check that each node of a fully connected three-node swarm reports the other two as subscribers
+/// to the gossip topic and at least one of them as a mesh peer, and that gossiped messages are
+/// counted.
+#[tokio::test]
+async fn should_report_gossip_state() {
+    init_logging();
+
+    let mut rng = crate::new_rng();
+    let chainspec = Chainspec::random(&mut rng);
+    let config = Config::default();
+    let net_metrics =
+        NetworkingMetrics::new(NetworkProviderKind::Libp2p, &Registry::default()).unwrap();
+
+    let identities = iter::repeat_with(NetworkIdentity::new)
+        .take(3)
+        .collect::<Vec<_>>();
+    let node_ids = identities
+        .iter()
+        .map(|identity| NodeId::from(identity).to_string())
+        .collect::<Vec<_>>();
+    let mut swarms = identities
+        .iter()
+        .map(|identity| new_swarm(&config, identity, &net_metrics, &chainspec).unwrap())
+        .collect::<Vec<_>>();
+
+    // Have each node dial every node started before it.
+    let mut addresses: Vec<Multiaddr> = vec![];
+    for swarm in &mut swarms {
+        let address = address_str_to_multiaddr(&format!(
+            "127.0.0.1:{}",
+            testing::unused_port_on_localhost()
+        ));
+        Swarm::listen_on(swarm, address.clone()).unwrap();
+        for earlier_address in &addresses {
+            Swarm::dial_addr(swarm, earlier_address.clone()).unwrap();
+        }
+        addresses.push(address);
+    }
+
+    let topic = gossip::TOPIC.hash();
+    let topic_state = |swarm: &Swarm<Behavior>| swarm.gossip_state().topic(topic.as_str()).cloned();
+    let others = |index: usize| {
+        let mut others = node_ids.clone();
+        others.remove(index);
+        others.sort();
+        others
+    };
+    let meshed = |swarms: &[Swarm<Behavior>]| {
+        swarms
+            .iter()
+            .enumerate()
+            .all(|(index, swarm)| match topic_state(swarm) {
+                Some(state) => {
+                    state.subscribed
+                        && state.subscribers == others(index)
+                        && !state.mesh_peers.is_empty()
+                }
+                None => false,
+            })
+    };
+
+    // The mesh is formed on the gossipsub heartbeat at the latest.
+    let deadline = time::Instant::now() + Duration::from_secs(15);
+    while !meshed(&swarms) {
+        assert!(
+            time::Instant::now() < deadline,
+            "each node should report both peers as subscribers and at least one mesh peer"
+        );
+        drive_swarms_until(&mut swarms, Duration::from_millis(100), |_, _, _| false).await;
+    }
+
+    // Gossip a message from the first node.
+    let message =
+        GossipMessage::new(&"gossip state".to_string(), config.max_gossip_message_size).unwrap();
+    swarms[0].gossip(message);
+    let mut received_by = HashSet::new();
+    let received = drive_swarms_until(&mut swarms, Duration::from_secs(5), |index, _, event| {
+        if let SwarmBehaviorEvent::Gossiper(GossipsubEvent::Message { message, .. }) = event {
+            if message.topic == topic {
+                received_by.insert(index);
+            }
+        }
+        received_by.len() == 2
+    })
+    .await;
+    assert!(received, "peers should receive the gossiped message");
+
+    let counters = swarms
+        .iter()
+        .map(|swarm| {
+            let state = topic_state(swarm).unwrap();
+            (
+                state.published,
+                state.publish_failures,
+                state.received,
+                state.rejected,
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(counters, vec![(1, 0, 0, 0), (0, 0, 1, 0), (0, 0, 1, 0)]);
+
+    // The state should survive a JSON round trip unchanged.
+    let gossip_state = swarms[0].gossip_state();
+    let json = serde_json::to_string(&gossip_state).unwrap();
+    assert_eq!(
+        serde_json::from_str::<GossipState>(&json).unwrap(),
+        gossip_state
+    );
+}
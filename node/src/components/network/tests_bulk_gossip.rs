@@ -3,7 +3,7 @@
 use std::{
     collections::{HashMap, HashSet},
     convert::TryFrom,
-    env, fmt,
+    fmt,
     fmt::{Debug, Display, Formatter},
     sync::Arc,
     thread,
@@ -17,11 +17,12 @@ use tracing::info;
 
 use casper_node_macros::reactor;
 
-use super::ENABLE_LIBP2P_NET_ENV_VAR;
+use super::PayloadT;
 use crate::{
     components::{
         collector::Collectable,
         network::{Config as NetworkComponentConfig, NetworkIdentity},
+        network_provider::NetworkProvider,
     },
     effect::EffectExt,
     reactor::Runner,
@@ -40,7 +41,7 @@ reactor!(LoadTestingReactor {
 
   components: {
       net = has_effects Network::<LoadTestingReactorEvent, DummyPayload>(
-        event_queue, cfg.network_config, registry, NetworkIdentity::new(), &cfg.chainspec
+        event_queue, cfg.network_config, registry, NetworkIdentity::new(), Arc::clone(&cfg.chainspec)
       );
       collector = infallible Collector::<DummyPayload>();
   }
@@ -56,6 +57,7 @@ reactor!(LoadTestingReactor {
 
   announcements: {
       NetworkAnnouncement<NodeId, DummyPayload> -> [collector];
+      BlocklistAnnouncement<NodeId> -> [net];
   }
 });
 
@@ -107,6 +109,8 @@ impl DummyPayload {
     }
 }
 
+impl PayloadT for DummyPayload {}
+
 impl Collectable for DummyPayload {
     type CollectedType = DummyPayloadId;
 
@@ -139,11 +143,6 @@ impl Display for DummyPayload {
 async fn send_large_message_across_network() {
     testing::init_logging();
 
-    if env::var(ENABLE_LIBP2P_NET_ENV_VAR).is_err() {
-        eprintln!("{} set, skipping test", ENABLE_LIBP2P_NET_ENV_VAR);
-        return;
-    }
-
     // This can, on a decent machine, be set to 30, 50, maybe even 100 nodes. The default is set to
     // 5 to avoid overloading CI.
     let node_count: usize = read_env("TEST_NODE_COUNT").unwrap_or(5);
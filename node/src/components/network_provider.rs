@@ -0,0 +1,410 @@
+//! Runtime selection of the networking component.
+//!
+//! The node can run either the `small_network` component or the libp2p-based `network` component,
+//! chosen via `network_provider` in the `[network]` section of the config.  Both implement the
+//! [`NetworkProvider`] trait, and reactors hold whichever was chosen in a [`NetworkComponent`].
+
+#[cfg(test)]
+mod tests;
+
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt::{self, Display, Formatter},
+    path::Path,
+    sync::Arc,
+};
+
+use casper_types::EraId;
+use datasize::DataSize;
+use futures::future::BoxFuture;
+use prometheus::Registry;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::debug;
+
+use crate::{
+    components::{
+        consensus,
+        network::{self, Network, NetworkIdentity, PayloadT},
+        small_network::{self, Payload, SmallNetwork, SmallNetworkIdentity},
+        Component,
+    },
+    effect::{
+        announcements::BlocklistAnnouncement,
+        requests::{
+            ChainspecLoaderRequest, ContractRuntimeRequest, NetworkInfoRequest, NetworkRequest,
+            StorageRequest,
+        },
+        EffectBuilder, EffectExt, Effects, Responder,
+    },
+    reactor::{self, EventQueueHandle, Finalize},
+    types::{Chainspec, DialBackoff, GossipState, NodeId},
+    utils::WithDir,
+    NodeRng,
+};
+
+/// The networking component run by the node.
+///
+/// Defaults to "small".
+#[derive(Clone, Copy, DataSize, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NetworkProviderKind {
+    /// The TLS-based `small_network` component.
+    Small,
+    /// The libp2p-based `network` component.
+    Libp2p,
+}
+
+impl Default for NetworkProviderKind {
+    fn default() -> Self {
+        NetworkProviderKind::Small
+    }
+}
+
+impl Display for NetworkProviderKind {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            NetworkProviderKind::Small => write!(formatter, "small"),
+            NetworkProviderKind::Libp2p => write!(formatter, "libp2p"),
+        }
+    }
+}
+
+/// The effect surface shared by the networking components.
+pub(crate) trait NetworkProvider<REv, P>: Component<REv> {
+    /// Returns the ID of this node on the network.
+    fn node_id(&self) -> NodeId;
+
+    /// Queues a one-way message to be sent to the given peer.
+    fn send_one_way_message(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        dest: NodeId,
+        payload: P,
+    ) -> Effects<Self::Event>;
+
+    /// Queues a message to be sent to all peers.
+    fn broadcast_payload(&mut self, payload: P);
+
+    /// Queues a message to be sent to up to `count` peers not in `exclude`, returning the chosen
+    /// peers.
+    ///
+    /// Providers able to identify validator peers choose at least `min_validators` of them where
+    /// enough are connected.
+    fn gossip_payload(
+        &mut self,
+        rng: &mut NodeRng,
+        payload: P,
+        count: usize,
+        min_validators: usize,
+        exclude: HashSet<NodeId>,
+    ) -> HashSet<NodeId>;
+
+    /// Returns the connected peers and their addresses.
+    fn peers(&self) -> BTreeMap<NodeId, String>;
+
+    /// Returns the addresses on which this node can be reached.
+    fn listening_addresses(&self) -> Vec<String>;
+
+    /// Returns the addresses waiting to be redialed after failed connection attempts.
+    fn dial_backoffs(&self) -> Vec<DialBackoff>;
+
+    /// Responds with a snapshot of the gossipsub state, or `None` if this provider doesn't use
+    /// gossipsub.
+    fn request_gossip_state(
+        &self,
+        responder: Responder<Option<GossipState>>,
+    ) -> Effects<Self::Event>;
+}
+
+/// Handles a network request using the given provider.
+pub(crate) fn handle_network_request<REv, P, N>(
+    provider: &mut N,
+    effect_builder: EffectBuilder<REv>,
+    rng: &mut NodeRng,
+    request: NetworkRequest<NodeId, P>,
+) -> Effects<N::Event>
+where
+    N: NetworkProvider<REv, P>,
+{
+    match request {
+        NetworkRequest::SendMessage {
+            dest,
+            payload,
+            responder,
+        } => {
+            let mut effects = provider.send_one_way_message(effect_builder, *dest, *payload);
+            effects.extend(responder.respond(()).ignore());
+            effects
+        }
+        NetworkRequest::Broadcast { payload, responder } => {
+            provider.broadcast_payload(*payload);
+            responder.respond(()).ignore()
+        }
+        NetworkRequest::Gossip {
+            payload,
+            count,
+            min_validators,
+            exclude,
+            responder,
+        } => {
+            let sent_to = provider.gossip_payload(rng, *payload, count, min_validators, exclude);
+            responder.respond(sent_to).ignore()
+        }
+    }
+}
+
+/// Handles a network info request using the given provider.
+pub(crate) fn handle_network_info_request<REv, P, N>(
+    provider: &N,
+    request: NetworkInfoRequest<NodeId>,
+) -> Effects<N::Event>
+where
+    N: NetworkProvider<REv, P>,
+{
+    match request {
+        NetworkInfoRequest::GetPeers { responder } => responder.respond(provider.peers()).ignore(),
+        NetworkInfoRequest::GetDialBackoffs { responder } => {
+            responder.respond(provider.dial_backoffs()).ignore()
+        }
+        NetworkInfoRequest::GetGossipState { responder } => {
+            provider.request_gossip_state(responder)
+        }
+    }
+}
+
+/// A helper trait whose bounds represent the requirements for a reactor event that every
+/// networking component can work with.
+pub(crate) trait ReactorEventT<P: PayloadT>:
+    network::ReactorEventT<P>
+    + From<small_network::Event<P>>
+    + From<ContractRuntimeRequest>
+    + From<StorageRequest>
+    + From<ChainspecLoaderRequest>
+{
+}
+
+impl<REv, P> ReactorEventT<P> for REv
+where
+    P: PayloadT,
+    REv: network::ReactorEventT<P>
+        + From<small_network::Event<P>>
+        + From<ContractRuntimeRequest>
+        + From<StorageRequest>
+        + From<ChainspecLoaderRequest>,
+{
+}
+
+/// Error constructing the networking component.
+#[derive(Debug, Error)]
+pub enum ConstructionError {
+    /// `SmallNetwork` component error.
+    #[error("small network error: {0}")]
+    SmallNetwork(#[from] small_network::Error),
+
+    /// `Network` component error.
+    #[error("network error: {0}")]
+    Network(#[from] network::Error),
+}
+
+/// The networking component chosen by the config.
+#[derive(DataSize, Debug)]
+pub(crate) enum NetworkComponent<REv, P>
+where
+    REv: 'static,
+    P: Payload,
+{
+    /// The TLS-based `small_network` component.
+    Small(SmallNetwork<REv, P>),
+    /// The libp2p-based `network` component.
+    Libp2p(Network<REv, P>),
+}
+
+impl<REv, P> NetworkComponent<REv, P>
+where
+    REv: ReactorEventT<P>,
+    P: Payload + PayloadT,
+{
+    /// Creates the networking component selected by `config.network_provider`.
+    ///
+    /// Only the identity of the selected provider is used.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn new(
+        event_queue: EventQueueHandle<REv>,
+        config: small_network::Config,
+        consensus_config: Option<WithDir<&consensus::Config>>,
+        storage_path: &Path,
+        registry: &Registry,
+        small_network_identity: SmallNetworkIdentity,
+        network_identity: NetworkIdentity,
+        chainspec: Arc<Chainspec>,
+        initial_era: Option<EraId>,
+    ) -> Result<(Self, Effects<REv>), ConstructionError> {
+        match config.network_provider {
+            NetworkProviderKind::Small => {
+                let (small_network, effects) = SmallNetwork::new(
+                    event_queue,
+                    config,
+                    consensus_config,
+                    storage_path,
+                    registry,
+                    small_network_identity,
+                    chainspec.as_ref(),
+                    initial_era,
+                )?;
+                Ok((
+                    NetworkComponent::Small(small_network),
+                    reactor::wrap_effects(REv::from, effects),
+                ))
+            }
+            NetworkProviderKind::Libp2p => {
+                let (network, effects) = Network::new(
+                    event_queue,
+                    network::Config::from(&config),
+                    registry,
+                    network_identity,
+                    chainspec,
+                )?;
+                Ok((
+                    NetworkComponent::Libp2p(network),
+                    reactor::wrap_effects(REv::from, effects),
+                ))
+            }
+        }
+    }
+
+    /// Returns which provider is running.
+    #[cfg(test)]
+    pub(crate) fn kind(&self) -> NetworkProviderKind {
+        match self {
+            NetworkComponent::Small(_) => NetworkProviderKind::Small,
+            NetworkComponent::Libp2p(_) => NetworkProviderKind::Libp2p,
+        }
+    }
+
+    /// Returns the ID of this node on the network.
+    #[cfg(test)]
+    pub(crate) fn node_id(&self) -> NodeId {
+        match self {
+            NetworkComponent::Small(small_network) => small_network.node_id(),
+            NetworkComponent::Libp2p(network) => network.node_id(),
+        }
+    }
+
+    /// Returns the connected peers and their addresses.
+    #[cfg(test)]
+    pub(crate) fn peers(&self) -> BTreeMap<NodeId, String> {
+        match self {
+            NetworkComponent::Small(small_network) => small_network.peers(),
+            NetworkComponent::Libp2p(network) => network.peers(),
+        }
+    }
+
+    /// Handles a network request with the running provider.
+    pub(crate) fn handle_network_request(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        rng: &mut NodeRng,
+        request: NetworkRequest<NodeId, P>,
+    ) -> Effects<REv> {
+        match self {
+            NetworkComponent::Small(small_network) => reactor::wrap_effects(
+                REv::from,
+                handle_network_request(small_network, effect_builder, rng, request),
+            ),
+            NetworkComponent::Libp2p(network) => reactor::wrap_effects(
+                REv::from,
+                handle_network_request(network, effect_builder, rng, request),
+            ),
+        }
+    }
+
+    /// Handles a network info request with the running provider.
+    pub(crate) fn handle_network_info_request(
+        &self,
+        request: NetworkInfoRequest<NodeId>,
+    ) -> Effects<REv> {
+        match self {
+            NetworkComponent::Small(small_network) => reactor::wrap_effects(
+                REv::from,
+                handle_network_info_request(small_network, request),
+            ),
+            NetworkComponent::Libp2p(network) => {
+                reactor::wrap_effects(REv::from, handle_network_info_request(network, request))
+            }
+        }
+    }
+
+    /// Handles an event of the `small_network` component.
+    ///
+    /// The event is dropped if the libp2p provider is running.
+    pub(crate) fn handle_small_network_event(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        rng: &mut NodeRng,
+        event: small_network::Event<P>,
+    ) -> Effects<REv> {
+        match self {
+            NetworkComponent::Small(small_network) => reactor::wrap_effects(
+                REv::from,
+                small_network.handle_event(effect_builder, rng, event),
+            ),
+            NetworkComponent::Libp2p(_) => {
+                debug!(%event, "ignoring small network event, running libp2p network");
+                Effects::new()
+            }
+        }
+    }
+
+    /// Handles an event of the libp2p-based `network` component.
+    ///
+    /// The event is dropped if the small network provider is running.
+    pub(crate) fn handle_network_event(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        rng: &mut NodeRng,
+        event: network::Event<P>,
+    ) -> Effects<REv> {
+        match self {
+            NetworkComponent::Small(_) => {
+                debug!(%event, "ignoring libp2p network event, running small network");
+                Effects::new()
+            }
+            NetworkComponent::Libp2p(network) => {
+                reactor::wrap_effects(REv::from, network.handle_event(effect_builder, rng, event))
+            }
+        }
+    }
+
+    /// Handles a blocklist announcement with the running provider.
+    pub(crate) fn handle_blocklist_announcement(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        rng: &mut NodeRng,
+        announcement: BlocklistAnnouncement<NodeId>,
+    ) -> Effects<REv> {
+        match self {
+            NetworkComponent::Small(small_network) => reactor::wrap_effects(
+                REv::from,
+                small_network.handle_event(effect_builder, rng, announcement.into()),
+            ),
+            NetworkComponent::Libp2p(network) => reactor::wrap_effects(
+                REv::from,
+                network.handle_event(effect_builder, rng, announcement.into()),
+            ),
+        }
+    }
+}
+
+impl<REv, P> Finalize for NetworkComponent<REv, P>
+where
+    REv: Send + 'static,
+    P: Payload,
+{
+    fn finalize(self) -> BoxFuture<'static, ()> {
+        match self {
+            NetworkComponent::Small(small_network) => small_network.finalize(),
+            NetworkComponent::Libp2p(network) => network.finalize(),
+        }
+    }
+}
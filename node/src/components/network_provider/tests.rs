@@ -0,0 +1,431 @@
+//! Smoke tests running networks of each provider from the same config file.
+
+use std::{
+    env,
+    fmt::{self, Debug, Display, Formatter},
+    fs,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use derive_more::From;
+use prometheus::Registry;
+use reactor::ReactorEvent;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info};
+
+use super::{NetworkComponent, NetworkProviderKind};
+use crate::{
+    components::{
+        gossiper::{self, Gossiper},
+        network::{self, NetworkIdentity, PayloadT},
+        small_network::{self, GossipedAddress, MessageKind, Payload, SmallNetworkIdentity},
+        Component,
+    },
+    effect::{
+        announcements::{
+            BlocklistAnnouncement, ControlAnnouncement, GossiperAnnouncement, NetworkAnnouncement,
+        },
+        requests::{
+            ChainspecLoaderRequest, ContractRuntimeRequest, NetworkRequest, StorageRequest,
+        },
+        EffectBuilder, Effects,
+    },
+    protocol,
+    reactor::{self, participating, EventQueueHandle, Finalize, Reactor},
+    testing::{
+        self, init_logging,
+        network::{Network, NetworkedReactor, Nodes},
+    },
+    types::{Chainspec, NodeId},
+    utils::{Loadable, Source, RESOURCES_PATH},
+    NodeRng,
+};
+
+/// Test-reactor event.
+#[derive(Debug, From, Serialize)]
+enum Event {
+    #[from]
+    SmallNetwork(#[serde(skip_serializing)] small_network::Event<Message>),
+    #[from]
+    Network(#[serde(skip_serializing)] network::Event<Message>),
+    #[from]
+    AddressGossiper(#[serde(skip_serializing)] gossiper::Event<GossipedAddress>),
+    #[from]
+    NetworkRequest(#[serde(skip_serializing)] NetworkRequest<NodeId, Message>),
+    #[from]
+    ControlAnnouncement(ControlAnnouncement),
+    #[from]
+    NetworkAnnouncement(#[serde(skip_serializing)] NetworkAnnouncement<NodeId, Message>),
+    #[from]
+    AddressGossiperAnnouncement(#[serde(skip_serializing)] GossiperAnnouncement<GossipedAddress>),
+    #[from]
+    BlocklistAnnouncement(BlocklistAnnouncement<NodeId>),
+}
+
+impl ReactorEvent for Event {
+    fn as_control(&self) -> Option<&ControlAnnouncement> {
+        if let Self::ControlAnnouncement(ref ctrl_ann) = self {
+            Some(ctrl_ann)
+        } else {
+            None
+        }
+    }
+}
+
+impl From<NetworkRequest<NodeId, gossiper::Message<GossipedAddress>>> for Event {
+    fn from(request: NetworkRequest<NodeId, gossiper::Message<GossipedAddress>>) -> Self {
+        Event::NetworkRequest(request.map_payload(Message::from))
+    }
+}
+
+impl From<NetworkRequest<NodeId, protocol::Message>> for Event {
+    fn from(_request: NetworkRequest<NodeId, protocol::Message>) -> Self {
+        unreachable!()
+    }
+}
+
+impl From<StorageRequest> for Event {
+    fn from(_request: StorageRequest) -> Self {
+        unreachable!()
+    }
+}
+
+impl From<ChainspecLoaderRequest> for Event {
+    fn from(_request: ChainspecLoaderRequest) -> Self {
+        unreachable!()
+    }
+}
+
+impl From<ContractRuntimeRequest> for Event {
+    fn from(_request: ContractRuntimeRequest) -> Self {
+        unreachable!()
+    }
+}
+
+impl Display for Event {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Debug::fmt(self, f)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, From)]
+enum Message {
+    #[from]
+    AddressGossiper(gossiper::Message<GossipedAddress>),
+}
+
+impl Display for Message {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Debug::fmt(self, f)
+    }
+}
+
+impl Payload for Message {
+    #[inline]
+    fn classify(&self) -> MessageKind {
+        match self {
+            Message::AddressGossiper(_) => MessageKind::AddressGossip,
+        }
+    }
+}
+
+impl PayloadT for Message {}
+
+/// Test reactor configuration.
+#[derive(Debug)]
+struct TestReactorConfig {
+    network: small_network::Config,
+    chainspec: Arc<Chainspec>,
+}
+
+/// Test reactor.
+///
+/// Runs a single instance of whichever networking component is configured.
+#[derive(Debug)]
+struct TestReactor {
+    net: NetworkComponent<Event, Message>,
+    address_gossiper: Gossiper<GossipedAddress, Event>,
+}
+
+impl Reactor for TestReactor {
+    type Event = Event;
+    type Config = TestReactorConfig;
+    type Error = anyhow::Error;
+
+    fn new(
+        cfg: Self::Config,
+        registry: &Registry,
+        event_queue: EventQueueHandle<Self::Event>,
+        _rng: &mut NodeRng,
+    ) -> anyhow::Result<(Self, Effects<Self::Event>)> {
+        let (net, effects) = NetworkComponent::new(
+            event_queue,
+            cfg.network,
+            None,
+            &env::temp_dir(),
+            registry,
+            SmallNetworkIdentity::new()?,
+            NetworkIdentity::new(),
+            cfg.chainspec,
+            None,
+        )?;
+        let gossiper_config = gossiper::Config::new_with_small_timeouts();
+        let address_gossiper =
+            Gossiper::new_for_complete_items("address_gossiper", gossiper_config, registry)?;
+
+        Ok((
+            TestReactor {
+                net,
+                address_gossiper,
+            },
+            effects,
+        ))
+    }
+
+    fn dispatch_event(
+        &mut self,
+        effect_builder: EffectBuilder<Self::Event>,
+        rng: &mut NodeRng,
+        event: Self::Event,
+    ) -> Effects<Self::Event> {
+        match event {
+            Event::SmallNetwork(event) => {
+                self.net
+                    .handle_small_network_event(effect_builder, rng, event)
+            }
+            Event::Network(event) => self.net.handle_network_event(effect_builder, rng, event),
+            Event::AddressGossiper(event) => reactor::wrap_effects(
+                Event::AddressGossiper,
+                self.address_gossiper
+                    .handle_event(effect_builder, rng, event),
+            ),
+            Event::NetworkRequest(req) => self.net.handle_network_request(effect_builder, rng, req),
+            Event::ControlAnnouncement(ctrl_ann) => {
+                unreachable!("unhandled control announcement: {}", ctrl_ann)
+            }
+            Event::NetworkAnnouncement(NetworkAnnouncement::MessageReceived {
+                sender,
+                payload,
+            }) => {
+                let reactor_event = match payload {
+                    Message::AddressGossiper(message) => {
+                        Event::AddressGossiper(gossiper::Event::MessageReceived { sender, message })
+                    }
+                };
+                self.dispatch_event(effect_builder, rng, reactor_event)
+            }
+            Event::NetworkAnnouncement(NetworkAnnouncement::GossipOurAddress(gossiped_address)) => {
+                let event = gossiper::Event::ItemReceived {
+                    item_id: gossiped_address,
+                    source: Source::<NodeId>::Ourself,
+                };
+                self.dispatch_event(effect_builder, rng, Event::AddressGossiper(event))
+            }
+            Event::NetworkAnnouncement(NetworkAnnouncement::NewPeer(_))
+            | Event::NetworkAnnouncement(NetworkAnnouncement::PeerCountChanged(_))
+            | Event::NetworkAnnouncement(NetworkAnnouncement::PeerConnected { .. })
+            | Event::NetworkAnnouncement(NetworkAnnouncement::PeerDisconnected { .. })
+            | Event::NetworkAnnouncement(NetworkAnnouncement::MessageSendFailed { .. }) => {
+                // We do not care about these announcements in this test.
+                Effects::new()
+            }
+            Event::AddressGossiperAnnouncement(GossiperAnnouncement::NewCompleteItem(
+                gossiped_address,
+            )) => {
+                let reactor_event = Event::SmallNetwork(small_network::Event::PeerAddressReceived(
+                    Box::new(gossiped_address),
+                ));
+                self.dispatch_event(effect_builder, rng, reactor_event)
+            }
+            Event::AddressGossiperAnnouncement(GossiperAnnouncement::FinishedGossiping(_)) => {
+                // We do not care about the announcement of gossiping finished in this test.
+                Effects::new()
+            }
+            Event::BlocklistAnnouncement(ann) => {
+                self.net
+                    .handle_blocklist_announcement(effect_builder, rng, ann)
+            }
+        }
+    }
+
+    fn maybe_exit(&self) -> Option<crate::reactor::ReactorExit> {
+        unimplemented!()
+    }
+}
+
+impl NetworkedReactor for TestReactor {
+    type NodeId = NodeId;
+
+    fn node_id(&self) -> NodeId {
+        self.net.node_id()
+    }
+}
+
+impl Finalize for TestReactor {
+    fn finalize(self) -> futures::future::BoxFuture<'static, ()> {
+        self.net.finalize()
+    }
+}
+
+/// Loads the `[network]` section of the local config file, selecting the given provider.
+fn load_network_config(provider: NetworkProviderKind) -> small_network::Config {
+    let path = RESOURCES_PATH.join("local/config.toml");
+    let contents = fs::read_to_string(&path)
+        .expect("should read local config file")
+        .replace(
+            "network_provider = 'small'",
+            &format!("network_provider = '{}'", provider),
+        );
+    let config: participating::Config =
+        toml::from_str(&contents).expect("should parse local config file");
+    config.network
+}
+
+/// Constructs the config of the first node of a testnet on a single machine, differing from the
+/// loaded config file only in its addresses.
+fn first_node_config(provider: NetworkProviderKind, port: u16) -> TestReactorConfig {
+    let address = format!("127.0.0.1:{}", port);
+    TestReactorConfig {
+        network: small_network::Config {
+            bind_address: address.clone(),
+            public_address: address.clone(),
+            known_addresses: vec![address],
+            persist_blocklist: false,
+            ..load_network_config(provider)
+        },
+        chainspec: Arc::new(Chainspec::from_resources("local")),
+    }
+}
+
+/// Constructs the config of a node joining a testnet on a single machine, differing from the
+/// loaded config file only in its addresses.
+fn joining_node_config(provider: NetworkProviderKind, known_port: u16) -> TestReactorConfig {
+    TestReactorConfig {
+        network: small_network::Config {
+            bind_address: "127.0.0.1:0".to_string(),
+            public_address: "127.0.0.1:0".to_string(),
+            known_addresses: vec![format!("127.0.0.1:{}", known_port)],
+            persist_blocklist: false,
+            ..load_network_config(provider)
+        },
+        chainspec: Arc::new(Chainspec::from_resources("local")),
+    }
+}
+
+/// Checks whether or not every node in the network is connected to at least one other node.
+fn network_is_connected(nodes: &Nodes<TestReactor>) -> bool {
+    nodes
+        .values()
+        .all(|runner| !runner.reactor().inner().net.peers().is_empty())
+}
+
+/// Runs a two-node network five times using the given provider.
+///
+/// Ensures that network cleanup and basic networking works.
+async fn run_two_node_network_five_times(provider: NetworkProviderKind) {
+    let mut rng = crate::new_rng();
+
+    // The networking port used by the tests for the root node.
+    let first_node_port = testing::unused_port_on_localhost() + 1;
+
+    init_logging();
+
+    for i in 0..5 {
+        info!(%provider, "two-network test round {}", i);
+
+        let mut net = Network::<TestReactor>::new();
+
+        let start = Instant::now();
+        net.add_node_with_config(first_node_config(provider, first_node_port), &mut rng)
+            .await
+            .unwrap();
+        net.add_node_with_config(joining_node_config(provider, first_node_port), &mut rng)
+            .await
+            .unwrap();
+        let end = Instant::now();
+
+        debug!(
+            total_time_ms = (end - start).as_millis() as u64,
+            "finished setting up networking nodes"
+        );
+
+        for runner in net.nodes().values() {
+            let reactor = runner.reactor().inner();
+            assert_eq!(reactor.net.kind(), provider);
+        }
+
+        let timeout = Duration::from_secs(20);
+        net.settle_on(&mut rng, network_is_connected, timeout).await;
+
+        let quiet_for = Duration::from_millis(25);
+        let timeout = Duration::from_secs(2);
+        net.settle(&mut rng, quiet_for, timeout).await;
+
+        assert!(
+            network_is_connected(net.nodes()),
+            "network did not stay connected"
+        );
+
+        net.finalize().await;
+    }
+}
+
+/// Checks that networks of varying sizes using the given provider connect every node.
+async fn check_varying_size_network_connects(provider: NetworkProviderKind) {
+    init_logging();
+
+    let mut rng = crate::new_rng();
+
+    // Try with a few predefined sets of network sizes.
+    for &number_of_nodes in &[2u16, 3, 5] {
+        let timeout = Duration::from_secs(10 * number_of_nodes as u64);
+
+        let mut net = Network::<TestReactor>::new();
+
+        // Pick a random port in the higher ranges that is likely to be unused.
+        let first_node_port = testing::unused_port_on_localhost();
+
+        net.add_node_with_config(first_node_config(provider, first_node_port), &mut rng)
+            .await
+            .unwrap();
+
+        for _ in 1..number_of_nodes {
+            net.add_node_with_config(joining_node_config(provider, first_node_port), &mut rng)
+                .await
+                .unwrap();
+        }
+
+        net.settle_on(&mut rng, network_is_connected, timeout).await;
+
+        // This test will run multiple times, so ensure we cleanup all ports.
+        net.finalize().await;
+    }
+}
+
+#[tokio::test]
+async fn should_run_two_node_small_network_five_times() {
+    run_two_node_network_five_times(NetworkProviderKind::Small).await
+}
+
+#[tokio::test]
+async fn should_run_two_node_libp2p_network_five_times() {
+    run_two_node_network_five_times(NetworkProviderKind::Libp2p).await
+}
+
+#[tokio::test]
+async fn should_connect_varying_size_small_networks() {
+    check_varying_size_network_connects(NetworkProviderKind::Small).await
+}
+
+#[tokio::test]
+async fn should_connect_varying_size_libp2p_networks() {
+    check_varying_size_network_connects(NetworkProviderKind::Libp2p).await
+}
+
+#[test]
+fn should_select_network_provider_from_config() {
+    for &provider in &[NetworkProviderKind::Small, NetworkProviderKind::Libp2p] {
+        assert_eq!(load_network_config(provider).network_provider, provider);
+    }
+}
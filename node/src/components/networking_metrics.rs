@@ -1,11 +1,19 @@
 use std::sync::Weak;
 
-use prometheus::{IntCounter, IntGauge, Registry};
+use prometheus::{Histogram, HistogramOpts, IntCounter, IntGauge, Opts, Registry};
 use tracing::debug;
 
-use super::small_network::MessageKind;
+use super::{network_provider::NetworkProviderKind, small_network::MessageKind};
 use crate::unregister_metric;
 
+/// Name of the label identifying the network provider which recorded a metric.
+const PROVIDER_LABEL: &str = "network_provider";
+
+/// Creates the options for a networking metric recorded by the given provider.
+fn opts(name: &str, help: &str, provider: NetworkProviderKind) -> Opts {
+    Opts::new(name, help).const_label(PROVIDER_LABEL, provider.to_string())
+}
+
 /// Network-type agnostic networking metrics.
 #[derive(Debug)]
 pub(super) struct NetworkingMetrics {
@@ -19,6 +27,13 @@ pub(super) struct NetworkingMetrics {
     pub(super) queued_messages: IntGauge,
     /// Number of connected peers.
     pub(super) peers: IntGauge,
+    /// Count of incoming messages dropped as their payload used a variant unknown to this node.
+    pub(super) in_count_unknown_payload_variant: IntCounter,
+    /// Number of gossip rounds which should have included a validator, but for which no validator
+    /// peer was reachable.
+    pub(super) gossip_rounds_without_validator_peer: IntCounter,
+    /// Number of connections to or from peers on the blocklist which were dropped.
+    pub(super) rejected_blocked_peer_connections: IntCounter,
 
     /// Count of outgoing messages that are protocol overhead.
     pub(super) out_count_protocol: IntCounter,
@@ -50,6 +65,13 @@ pub(super) struct NetworkingMetrics {
     /// Volume in bytes of outgoing messages with other payload.
     pub(super) out_bytes_other: IntCounter,
 
+    /// Count of outgoing messages suppressed as duplicates of a message recently sent to the same
+    /// peer.
+    pub(super) out_count_suppressed_duplicate: IntCounter,
+    /// Volume in bytes of outgoing messages suppressed as duplicates of a message recently sent to
+    /// the same peer.
+    pub(super) out_bytes_suppressed_duplicate: IntCounter,
+
     // Potentially temporary metrics, not supported by all networking components:
     /// Number of do-nothing futures that have not finished executing for read requests.
     pub(super) read_futures_in_flight: prometheus::Gauge,
@@ -59,101 +81,168 @@ pub(super) struct NetworkingMetrics {
     pub(super) write_futures_in_flight: prometheus::Gauge,
     /// Number of do-nothing futures created total (write).
     pub(super) write_futures_total: prometheus::Gauge,
+    /// Ratio of uncompressed to compressed size of compressed outgoing one-way messages.
+    pub(super) one_way_compression_ratio: Histogram,
 
     /// Registry instance.
     registry: Registry,
 }
 
 impl NetworkingMetrics {
-    /// Creates a new instance of networking metrics.
-    pub(super) fn new(registry: &Registry) -> Result<Self, prometheus::Error> {
-        let broadcast_requests =
-            IntCounter::new("net_broadcast_requests", "number of broadcasting requests")?;
-        let direct_message_requests = IntCounter::new(
+    /// Creates a new instance of networking metrics, labelled with the provider they are recorded
+    /// by.
+    pub(super) fn new(
+        provider: NetworkProviderKind,
+        registry: &Registry,
+    ) -> Result<Self, prometheus::Error> {
+        let broadcast_requests = IntCounter::with_opts(opts(
+            "net_broadcast_requests",
+            "number of broadcasting requests",
+            provider,
+        ))?;
+        let direct_message_requests = IntCounter::with_opts(opts(
             "net_direct_message_requests",
             "number of requests to send a message directly to a peer",
-        )?;
-        let open_connections =
-            IntGauge::new("net_open_connections", "number of established connections")?;
-        let queued_messages = IntGauge::new(
+            provider,
+        ))?;
+        let open_connections = IntGauge::with_opts(opts(
+            "net_open_connections",
+            "number of established connections",
+            provider,
+        ))?;
+        let queued_messages = IntGauge::with_opts(opts(
             "net_queued_direct_messages",
             "number of messages waiting to be sent out",
-        )?;
-        let peers = IntGauge::new("peers", "number of connected peers")?;
+            provider,
+        ))?;
+        let peers = IntGauge::with_opts(opts("peers", "number of connected peers", provider))?;
+        let in_count_unknown_payload_variant = IntCounter::with_opts(opts(
+            "net_in_count_unknown_payload_variant",
+            "count of incoming messages dropped as their payload used an unknown variant",
+            provider,
+        ))?;
+        let gossip_rounds_without_validator_peer = IntCounter::with_opts(opts(
+            "net_gossip_rounds_without_validator_peer",
+            "number of gossip rounds prioritizing validators where no validator peer was reachable",
+            provider,
+        ))?;
+        let rejected_blocked_peer_connections = IntCounter::with_opts(opts(
+            "net_rejected_blocked_peer_connections",
+            "number of connections to or from peers on the blocklist which were dropped",
+            provider,
+        ))?;
 
-        let out_count_protocol = IntCounter::new(
+        let out_count_protocol = IntCounter::with_opts(opts(
             "net_out_count_protocol",
             "count of outgoing messages that are protocol overhead",
-        )?;
-        let out_count_consensus = IntCounter::new(
+            provider,
+        ))?;
+        let out_count_consensus = IntCounter::with_opts(opts(
             "net_out_count_consensus",
             "count of outgoing messages with consensus payload",
-        )?;
-        let out_count_deploy_gossip = IntCounter::new(
+            provider,
+        ))?;
+        let out_count_deploy_gossip = IntCounter::with_opts(opts(
             "net_out_count_deploy_gossip",
             "count of outgoing messages with deploy gossiper payload",
-        )?;
-        let out_count_address_gossip = IntCounter::new(
+            provider,
+        ))?;
+        let out_count_address_gossip = IntCounter::with_opts(opts(
             "net_out_count_address_gossip",
             "count of outgoing messages with address gossiper payload",
-        )?;
-        let out_count_deploy_transfer = IntCounter::new(
+            provider,
+        ))?;
+        let out_count_deploy_transfer = IntCounter::with_opts(opts(
             "net_out_count_deploy_transfer",
             "count of outgoing messages with deploy request/response payload",
-        )?;
-        let out_count_block_transfer = IntCounter::new(
+            provider,
+        ))?;
+        let out_count_block_transfer = IntCounter::with_opts(opts(
             "net_out_count_block_transfer",
             "count of outgoing messages with block request/response payload",
-        )?;
-        let out_count_other = IntCounter::new(
+            provider,
+        ))?;
+        let out_count_other = IntCounter::with_opts(opts(
             "net_out_count_other",
             "count of outgoing messages with other payload",
-        )?;
+            provider,
+        ))?;
 
-        let out_bytes_protocol = IntCounter::new(
+        let out_bytes_protocol = IntCounter::with_opts(opts(
             "net_out_bytes_protocol",
             "volume in bytes of outgoing messages that are protocol overhead",
-        )?;
-        let out_bytes_consensus = IntCounter::new(
+            provider,
+        ))?;
+        let out_bytes_consensus = IntCounter::with_opts(opts(
             "net_out_bytes_consensus",
             "volume in bytes of outgoing messages with consensus payload",
-        )?;
-        let out_bytes_deploy_gossip = IntCounter::new(
+            provider,
+        ))?;
+        let out_bytes_deploy_gossip = IntCounter::with_opts(opts(
             "net_out_bytes_deploy_gossip",
             "volume in bytes of outgoing messages with deploy gossiper payload",
-        )?;
-        let out_bytes_address_gossip = IntCounter::new(
+            provider,
+        ))?;
+        let out_bytes_address_gossip = IntCounter::with_opts(opts(
             "net_out_bytes_address_gossip",
             "volume in bytes of outgoing messages with address gossiper payload",
-        )?;
-        let out_bytes_deploy_transfer = IntCounter::new(
+            provider,
+        ))?;
+        let out_bytes_deploy_transfer = IntCounter::with_opts(opts(
             "net_out_bytes_deploy_transfer",
             "volume in bytes of outgoing messages with deploy request/response payload",
-        )?;
-        let out_bytes_block_transfer = IntCounter::new(
+            provider,
+        ))?;
+        let out_bytes_block_transfer = IntCounter::with_opts(opts(
             "net_out_bytes_block_transfer",
             "volume in bytes of outgoing messages with block request/response payload",
-        )?;
-        let out_bytes_other = IntCounter::new(
+            provider,
+        ))?;
+        let out_bytes_other = IntCounter::with_opts(opts(
             "net_out_bytes_other",
             "volume in bytes of outgoing messages with other payload",
-        )?;
+            provider,
+        ))?;
 
-        let read_futures_in_flight = prometheus::Gauge::new(
+        let out_count_suppressed_duplicate = IntCounter::with_opts(opts(
+            "net_out_count_suppressed_duplicate",
+            "count of outgoing messages suppressed as duplicates of a recent message",
+            provider,
+        ))?;
+        let out_bytes_suppressed_duplicate = IntCounter::with_opts(opts(
+            "net_out_bytes_suppressed_duplicate",
+            "volume in bytes of outgoing messages suppressed as duplicates of a recent message",
+            provider,
+        ))?;
+
+        let read_futures_in_flight = prometheus::Gauge::with_opts(opts(
             "owm_read_futures_in_flight",
             "number of do-nothing futures in flight created by `Codec::read_response`",
-        )?;
-        let read_futures_total = prometheus::Gauge::new(
+            provider,
+        ))?;
+        let read_futures_total = prometheus::Gauge::with_opts(opts(
             "owm_read_futures_total",
             "number of do-nothing futures total created by `Codec::read_response`",
-        )?;
-        let write_futures_in_flight = prometheus::Gauge::new(
+            provider,
+        ))?;
+        let write_futures_in_flight = prometheus::Gauge::with_opts(opts(
             "owm_write_futures_in_flight",
             "number of do-nothing futures in flight created by `Codec::write_response`",
-        )?;
-        let write_futures_total = prometheus::Gauge::new(
+            provider,
+        ))?;
+        let write_futures_total = prometheus::Gauge::with_opts(opts(
             "owm_write_futures_total",
             "number of do-nothing futures total created by `Codec::write_response`",
+            provider,
+        ))?;
+        let one_way_compression_ratio = Histogram::with_opts(
+            HistogramOpts::new(
+                "owm_compression_ratio",
+                "ratio of uncompressed to compressed size of compressed outgoing one-way messages",
+            )
+            .const_label(PROVIDER_LABEL, provider.to_string())
+            // Create buckets from a ratio of one to ten.
+            .buckets(prometheus::linear_buckets(1.0, 0.5, 19)?),
         )?;
 
         registry.register(Box::new(broadcast_requests.clone()))?;
@@ -161,6 +250,9 @@ impl NetworkingMetrics {
         registry.register(Box::new(open_connections.clone()))?;
         registry.register(Box::new(queued_messages.clone()))?;
         registry.register(Box::new(peers.clone()))?;
+        registry.register(Box::new(in_count_unknown_payload_variant.clone()))?;
+        registry.register(Box::new(gossip_rounds_without_validator_peer.clone()))?;
+        registry.register(Box::new(rejected_blocked_peer_connections.clone()))?;
 
         registry.register(Box::new(out_count_protocol.clone()))?;
         registry.register(Box::new(out_count_consensus.clone()))?;
@@ -178,10 +270,14 @@ impl NetworkingMetrics {
         registry.register(Box::new(out_bytes_block_transfer.clone()))?;
         registry.register(Box::new(out_bytes_other.clone()))?;
 
+        registry.register(Box::new(out_count_suppressed_duplicate.clone()))?;
+        registry.register(Box::new(out_bytes_suppressed_duplicate.clone()))?;
+
         registry.register(Box::new(read_futures_in_flight.clone()))?;
         registry.register(Box::new(read_futures_total.clone()))?;
         registry.register(Box::new(write_futures_in_flight.clone()))?;
         registry.register(Box::new(write_futures_total.clone()))?;
+        registry.register(Box::new(one_way_compression_ratio.clone()))?;
 
         Ok(NetworkingMetrics {
             broadcast_requests,
@@ -189,6 +285,9 @@ impl NetworkingMetrics {
             open_connections,
             queued_messages,
             peers,
+            in_count_unknown_payload_variant,
+            gossip_rounds_without_validator_peer,
+            rejected_blocked_peer_connections,
             out_count_protocol,
             out_count_consensus,
             out_count_deploy_gossip,
@@ -203,10 +302,13 @@ impl NetworkingMetrics {
             out_bytes_deploy_transfer,
             out_bytes_block_transfer,
             out_bytes_other,
+            out_count_suppressed_duplicate,
+            out_bytes_suppressed_duplicate,
             read_futures_in_flight,
             read_futures_total,
             write_futures_in_flight,
             write_futures_total,
+            one_way_compression_ratio,
             registry: registry.clone(),
         })
     }
@@ -248,6 +350,16 @@ impl NetworkingMetrics {
             debug!("not recording metrics, component already shut down");
         }
     }
+
+    /// Records an outgoing message suppressed as a duplicate.
+    pub(super) fn record_suppressed_duplicate(this: &Weak<Self>, size: u64) {
+        if let Some(metrics) = this.upgrade() {
+            metrics.out_bytes_suppressed_duplicate.inc_by(size);
+            metrics.out_count_suppressed_duplicate.inc();
+        } else {
+            debug!("not recording metrics, component already shut down");
+        }
+    }
 }
 
 impl Drop for NetworkingMetrics {
@@ -257,6 +369,9 @@ impl Drop for NetworkingMetrics {
         unregister_metric!(self.registry, self.open_connections);
         unregister_metric!(self.registry, self.queued_messages);
         unregister_metric!(self.registry, self.peers);
+        unregister_metric!(self.registry, self.in_count_unknown_payload_variant);
+        unregister_metric!(self.registry, self.gossip_rounds_without_validator_peer);
+        unregister_metric!(self.registry, self.rejected_blocked_peer_connections);
 
         unregister_metric!(self.registry, self.out_count_protocol);
         unregister_metric!(self.registry, self.out_count_consensus);
@@ -272,10 +387,13 @@ impl Drop for NetworkingMetrics {
         unregister_metric!(self.registry, self.out_bytes_deploy_transfer);
         unregister_metric!(self.registry, self.out_bytes_block_transfer);
         unregister_metric!(self.registry, self.out_bytes_other);
+        unregister_metric!(self.registry, self.out_count_suppressed_duplicate);
+        unregister_metric!(self.registry, self.out_bytes_suppressed_duplicate);
 
         unregister_metric!(self.registry, self.read_futures_in_flight);
         unregister_metric!(self.registry, self.read_futures_total);
         unregister_metric!(self.registry, self.write_futures_in_flight);
         unregister_metric!(self.registry, self.write_futures_total);
+        unregister_metric!(self.registry, self.one_way_compression_ratio);
     }
 }
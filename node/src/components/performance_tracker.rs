@@ -0,0 +1,335 @@
+//! Validator performance tracker.
+//!
+//! The performance tracker aggregates, per era, the number of blocks proposed and finality
+//! signatures provided by each validator, along with whether it equivocated.  The aggregates of
+//! the most recent eras are persisted to storage, from where they're served by the JSON-RPC
+//! server.
+//!
+//! On startup, the aggregates of the retained eras are rebuilt from the blocks and finality
+//! signatures held in storage, so that they aren't lost when the node restarts.
+
+mod config;
+mod event;
+#[cfg(test)]
+mod tests;
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    mem,
+};
+
+use datasize::DataSize;
+use tracing::{debug, info, trace};
+
+use casper_types::{EraId, PublicKey, U512};
+
+use crate::{
+    components::{
+        storage::{Error as StorageError, Storage},
+        Component,
+    },
+    effect::{requests::StorageRequest, EffectBuilder, EffectExt, Effects},
+    types::{Block, ValidatorPerformance},
+    NodeRng,
+};
+pub use config::Config;
+pub use event::Event;
+
+/// The performance of all validators during a single era, as far as it is known.
+#[derive(DataSize, Debug)]
+struct EraPerformance {
+    /// The weights of the era's validators.
+    validator_weights: BTreeMap<PublicKey, U512>,
+    /// The number of blocks of the era added to the linear chain.
+    block_count: u64,
+    /// The number of blocks proposed by each validator.
+    blocks_proposed: BTreeMap<PublicKey, u64>,
+    /// The number of finality signatures provided by each validator.
+    finality_signatures: BTreeMap<PublicKey, u64>,
+    /// The validators found to have equivocated.
+    equivocators: BTreeSet<PublicKey>,
+}
+
+impl EraPerformance {
+    fn new(validator_weights: BTreeMap<PublicKey, U512>) -> Self {
+        EraPerformance {
+            validator_weights,
+            block_count: 0,
+            blocks_proposed: BTreeMap::new(),
+            finality_signatures: BTreeMap::new(),
+            equivocators: BTreeSet::new(),
+        }
+    }
+
+    /// Returns the performance of every validator of the era, and of any other validator which
+    /// contributed to it.
+    fn validator_performance(&self) -> BTreeMap<PublicKey, ValidatorPerformance> {
+        let total_weight = self
+            .validator_weights
+            .values()
+            .fold(U512::zero(), |total, weight| total + *weight);
+        self.validator_weights
+            .keys()
+            .chain(self.blocks_proposed.keys())
+            .chain(self.finality_signatures.keys())
+            .chain(&self.equivocators)
+            .map(|public_key| {
+                let performance = self.performance_of(public_key, total_weight);
+                (public_key.clone(), performance)
+            })
+            .collect()
+    }
+
+    fn performance_of(&self, public_key: &PublicKey, total_weight: U512) -> ValidatorPerformance {
+        let (blocks_expected, finality_signatures_expected) =
+            match self.validator_weights.get(public_key) {
+                Some(weight) if !total_weight.is_zero() => {
+                    let blocks_expected = U512::from(self.block_count) * *weight / total_weight;
+                    (blocks_expected.as_u64(), self.block_count)
+                }
+                Some(_) => (0, self.block_count),
+                None => (0, 0),
+            };
+        ValidatorPerformance {
+            blocks_proposed: self
+                .blocks_proposed
+                .get(public_key)
+                .copied()
+                .unwrap_or_default(),
+            blocks_expected,
+            finality_signatures_provided: self
+                .finality_signatures
+                .get(public_key)
+                .copied()
+                .unwrap_or_default(),
+            finality_signatures_expected,
+            equivocated: self.equivocators.contains(public_key),
+        }
+    }
+}
+
+/// The validator performance tracker component.
+#[derive(DataSize, Debug)]
+pub(crate) struct PerformanceTracker {
+    /// The number of most recent eras whose performance is retained.
+    retained_eras: u64,
+    /// The era of the most recently added block.
+    latest_era_id: EraId,
+    /// The performance in each retained era whose validators are known.
+    eras: BTreeMap<EraId, EraPerformance>,
+    /// The eras whose performance has changed since it was last stored.
+    unstored_eras: BTreeSet<EraId>,
+}
+
+impl PerformanceTracker {
+    /// Creates a new performance tracker, rebuilding the performance in the retained eras from the
+    /// blocks and finality signatures held in storage.
+    ///
+    /// `genesis_validator_weights` are the weights of the validators in era 0.  The validators of
+    /// any later era are taken from the switch block of the preceding one.
+    pub(crate) fn new<REv>(
+        config: Config,
+        storage: &Storage,
+        genesis_validator_weights: BTreeMap<PublicKey, U512>,
+        effect_builder: EffectBuilder<REv>,
+    ) -> Result<(Self, Effects<Event>), StorageError>
+    where
+        REv: From<StorageRequest> + Send,
+    {
+        let mut performance_tracker = PerformanceTracker {
+            retained_eras: config.retained_eras(),
+            latest_era_id: EraId::from(0),
+            eras: BTreeMap::new(),
+            unstored_eras: BTreeSet::new(),
+        };
+
+        let highest_block_header = match storage.read_highest_block_header()? {
+            Some(block_header) => block_header,
+            None => {
+                let _ = performance_tracker.eras.insert(
+                    EraId::from(0),
+                    EraPerformance::new(genesis_validator_weights),
+                );
+                return Ok((performance_tracker, Effects::new()));
+            }
+        };
+
+        // Find the oldest retained era whose validators are known, and the height of its first
+        // block.  The switch blocks of earlier eras may be missing if the node joined the network
+        // recently.
+        let latest_era_id = highest_block_header.era_id();
+        performance_tracker.latest_era_id = latest_era_id;
+        let mut era_id = performance_tracker.oldest_retained_era();
+        let start_height = loop {
+            if era_id > latest_era_id {
+                return Ok((performance_tracker, Effects::new()));
+            }
+            if era_id.value() == 0 {
+                let _ = performance_tracker
+                    .eras
+                    .insert(era_id, EraPerformance::new(genesis_validator_weights));
+                break 0;
+            }
+            if let Some(switch_block_header) =
+                storage.read_switch_block_header_by_era_id(era_id - 1)?
+            {
+                if let Some(validator_weights) = switch_block_header.next_era_validator_weights() {
+                    let _ = performance_tracker
+                        .eras
+                        .insert(era_id, EraPerformance::new(validator_weights.clone()));
+                    break switch_block_header.height() + 1;
+                }
+            }
+            era_id = era_id.successor();
+        };
+
+        info!(
+            %era_id,
+            %latest_era_id,
+            "rebuilding validator performance from storage"
+        );
+        for height in start_height..=highest_block_header.height() {
+            let (block, signatures) =
+                match storage.read_block_and_finality_signatures_by_height(height)? {
+                    Some(block_and_signatures) => block_and_signatures,
+                    None => {
+                        debug!(
+                            height,
+                            "block missing while rebuilding validator performance"
+                        );
+                        continue;
+                    }
+                };
+            performance_tracker.add_block(&block);
+            for public_key in signatures.proofs.keys() {
+                performance_tracker.add_finality_signature(signatures.era_id, public_key);
+            }
+        }
+
+        let effects = performance_tracker.store_unstored_eras(effect_builder);
+        Ok((performance_tracker, effects))
+    }
+
+    /// Returns the oldest retained era.
+    fn oldest_retained_era(&self) -> EraId {
+        self.latest_era_id.saturating_sub(self.retained_eras - 1)
+    }
+
+    /// Adds the block to the performance of its era, and starts tracking the next era if it is a
+    /// switch block.
+    fn add_block(&mut self, block: &Block) {
+        let era_id = block.header().era_id();
+        match self.eras.get_mut(&era_id) {
+            Some(era_performance) => {
+                era_performance.block_count += 1;
+                *era_performance
+                    .blocks_proposed
+                    .entry(block.body().proposer().clone())
+                    .or_default() += 1;
+                if let Some(era_report) = block.header().era_end() {
+                    era_performance
+                        .equivocators
+                        .extend(era_report.equivocators.iter().cloned());
+                }
+                let _ = self.unstored_eras.insert(era_id);
+            }
+            None => trace!(%era_id, "ignoring block of untracked era"),
+        }
+
+        if let Some(validator_weights) = block.header().next_era_validator_weights() {
+            let _ = self
+                .eras
+                .entry(era_id.successor())
+                .or_insert_with(|| EraPerformance::new(validator_weights.clone()));
+        }
+
+        self.latest_era_id = self.latest_era_id.max(era_id);
+        let oldest_retained_era = self.oldest_retained_era();
+        self.eras = self.eras.split_off(&oldest_retained_era);
+        self.unstored_eras = self.unstored_eras.split_off(&oldest_retained_era);
+    }
+
+    /// Adds a finality signature by the given validator for a block of the given era.
+    fn add_finality_signature(&mut self, era_id: EraId, public_key: &PublicKey) {
+        match self.eras.get_mut(&era_id) {
+            Some(era_performance) => {
+                *era_performance
+                    .finality_signatures
+                    .entry(public_key.clone())
+                    .or_default() += 1;
+                let _ = self.unstored_eras.insert(era_id);
+            }
+            None => trace!(%era_id, "ignoring finality signature of untracked era"),
+        }
+    }
+
+    /// Records that the given validator equivocated in the given era.
+    fn add_fault(&mut self, era_id: EraId, public_key: PublicKey) {
+        match self.eras.get_mut(&era_id) {
+            Some(era_performance) => {
+                if era_performance.equivocators.insert(public_key) {
+                    let _ = self.unstored_eras.insert(era_id);
+                }
+            }
+            None => trace!(%era_id, "ignoring fault in untracked era"),
+        }
+    }
+
+    /// Stores the performance in all eras which changed since they were last stored.
+    ///
+    /// Eras without any blocks yet are skipped, as their performance is all zeros.
+    fn store_unstored_eras<REv>(&mut self, effect_builder: EffectBuilder<REv>) -> Effects<Event>
+    where
+        REv: From<StorageRequest> + Send,
+    {
+        let oldest_retained_era = self.oldest_retained_era();
+        let mut effects = Effects::new();
+        for era_id in mem::take(&mut self.unstored_eras) {
+            let era_performance = &self.eras[&era_id];
+            if era_performance.block_count == 0 {
+                continue;
+            }
+            effects.extend(
+                effect_builder
+                    .put_era_performance_to_storage(
+                        era_id,
+                        era_performance.validator_performance(),
+                        oldest_retained_era,
+                    )
+                    .ignore(),
+            );
+        }
+        effects
+    }
+}
+
+impl<REv> Component<REv> for PerformanceTracker
+where
+    REv: From<StorageRequest> + Send,
+{
+    type Event = Event;
+    type ConstructionError = StorageError;
+
+    fn handle_event(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        _rng: &mut NodeRng,
+        event: Self::Event,
+    ) -> Effects<Self::Event> {
+        match event {
+            // The performance is stored once per block, rather than on every signature.
+            Event::BlockAdded(block) => {
+                self.add_block(&block);
+                self.store_unstored_eras(effect_builder)
+            }
+            Event::FinalitySignature(fs) => {
+                self.add_finality_signature(fs.era_id, &fs.public_key);
+                Effects::new()
+            }
+            Event::Fault { era_id, public_key } => {
+                self.add_fault(era_id, *public_key);
+                Effects::new()
+            }
+        }
+    }
+}
@@ -0,0 +1,34 @@
+use datasize::DataSize;
+use serde::{Deserialize, Serialize};
+
+/// The default number of most recent eras whose validator performance is retained.
+const DEFAULT_RETAINED_ERAS: u64 = 10;
+
+/// Configuration options for the performance tracker.
+#[derive(Clone, Copy, DataSize, Debug, Deserialize, Serialize)]
+pub struct Config {
+    /// The number of most recent eras, including the current one, whose validator performance is
+    /// retained.  Values below 1 are treated as 1.
+    retained_eras: u64,
+}
+
+impl Config {
+    /// Constructs a config retaining the given number of eras.
+    #[cfg(test)]
+    pub(super) fn new(retained_eras: u64) -> Self {
+        Config { retained_eras }
+    }
+
+    /// Get retained_eras setting.
+    pub(crate) fn retained_eras(&self) -> u64 {
+        self.retained_eras.max(1)
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            retained_eras: DEFAULT_RETAINED_ERAS,
+        }
+    }
+}
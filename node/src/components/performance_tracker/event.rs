@@ -0,0 +1,33 @@
+use std::fmt::{self, Display, Formatter};
+
+use casper_types::{EraId, PublicKey};
+
+use crate::types::{Block, FinalitySignature};
+
+/// An event for the performance tracker.
+#[derive(Debug)]
+pub enum Event {
+    /// A block has been added to the linear chain.
+    BlockAdded(Box<Block>),
+    /// A new finality signature has been added to the linear chain.
+    FinalitySignature(Box<FinalitySignature>),
+    /// A validator has been found to equivocate.
+    Fault {
+        /// The era in which the validator equivocated.
+        era_id: EraId,
+        /// The public key of the equivocator.
+        public_key: Box<PublicKey>,
+    },
+}
+
+impl Display for Event {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Event::BlockAdded(block) => write!(formatter, "block added: {}", block.hash()),
+            Event::FinalitySignature(fs) => write!(formatter, "finality signature: {}", fs),
+            Event::Fault { era_id, public_key } => {
+                write!(formatter, "fault by {} in era {}", public_key, era_id)
+            }
+        }
+    }
+}
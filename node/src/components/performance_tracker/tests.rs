@@ -0,0 +1,168 @@
+use rand::Rng;
+
+use casper_types::ProtocolVersion;
+
+use super::*;
+use crate::{
+    components::consensus::EraReport,
+    crypto::{hash::Digest, AsymmetricKeyExt},
+    testing::TestRng,
+    types::{BlockHash, BlockPayload, FinalizedBlock, Timestamp},
+};
+
+/// Creates a block of the given era proposed by `proposer`.  If `switch` is `Some`, the block is a
+/// switch block reporting the given equivocators and the given validator weights for the next era.
+fn new_block(
+    rng: &mut TestRng,
+    era_id: u64,
+    height: u64,
+    proposer: &PublicKey,
+    switch: Option<(Vec<PublicKey>, BTreeMap<PublicKey, U512>)>,
+) -> Block {
+    let (era_report, next_era_validator_weights) = match switch {
+        Some((equivocators, validator_weights)) => {
+            let era_report = EraReport {
+                equivocators,
+                rewards: BTreeMap::new(),
+                inactive_validators: vec![],
+            };
+            (Some(era_report), Some(validator_weights))
+        }
+        None => (None, None),
+    };
+    let finalized_block = FinalizedBlock::new(
+        BlockPayload::new(vec![], vec![], vec![], rng.gen()),
+        era_report,
+        Timestamp::now(),
+        EraId::from(era_id),
+        height,
+        proposer.clone(),
+    );
+    Block::new(
+        BlockHash::new(Digest::random(rng)),
+        Digest::random(rng),
+        Digest::random(rng),
+        finalized_block,
+        next_era_validator_weights,
+        ProtocolVersion::V1_0_0,
+    )
+}
+
+fn new_performance_tracker(
+    retained_eras: u64,
+    genesis_validator_weights: BTreeMap<PublicKey, U512>,
+) -> PerformanceTracker {
+    let mut eras = BTreeMap::new();
+    let _ = eras.insert(
+        EraId::from(0),
+        EraPerformance::new(genesis_validator_weights),
+    );
+    PerformanceTracker {
+        retained_eras: Config::new(retained_eras).retained_eras(),
+        latest_era_id: EraId::from(0),
+        eras,
+        unstored_eras: BTreeSet::new(),
+    }
+}
+
+#[test]
+fn should_aggregate_validator_performance_per_era() {
+    let mut rng = crate::new_rng();
+    let alice = PublicKey::random(&mut rng);
+    let bob = PublicKey::random(&mut rng);
+    let carol = PublicKey::random(&mut rng);
+    let validator_weights: BTreeMap<_, _> = vec![
+        (alice.clone(), U512::from(1)),
+        (bob.clone(), U512::from(1)),
+        (carol.clone(), U512::from(2)),
+    ]
+    .into_iter()
+    .collect();
+    let mut performance_tracker = new_performance_tracker(10, validator_weights.clone());
+
+    // Bob is offline, so proposes and signs none of the era's four blocks, and is reported as an
+    // equivocator in the switch block.
+    let proposers = [&alice, &alice, &carol, &carol];
+    for (height, proposer) in proposers.iter().enumerate() {
+        let switch = if height == proposers.len() - 1 {
+            Some((vec![bob.clone()], validator_weights.clone()))
+        } else {
+            None
+        };
+        let block = new_block(&mut rng, 0, height as u64, proposer, switch);
+        performance_tracker.add_block(&block);
+        performance_tracker.add_finality_signature(EraId::from(0), &alice);
+        performance_tracker.add_finality_signature(EraId::from(0), &carol);
+    }
+
+    let performance = performance_tracker.eras[&EraId::from(0)].validator_performance();
+    assert_eq!(performance.len(), 3);
+    assert_eq!(
+        performance[&alice],
+        ValidatorPerformance {
+            blocks_proposed: 2,
+            blocks_expected: 1,
+            finality_signatures_provided: 4,
+            finality_signatures_expected: 4,
+            equivocated: false,
+        }
+    );
+    assert_eq!(
+        performance[&bob],
+        ValidatorPerformance {
+            blocks_proposed: 0,
+            blocks_expected: 1,
+            finality_signatures_provided: 0,
+            finality_signatures_expected: 4,
+            equivocated: true,
+        }
+    );
+    assert_eq!(performance[&bob].finality_signatures_missed(), 4);
+    assert_eq!(
+        performance[&carol],
+        ValidatorPerformance {
+            blocks_proposed: 2,
+            blocks_expected: 2,
+            finality_signatures_provided: 4,
+            finality_signatures_expected: 4,
+            equivocated: false,
+        }
+    );
+
+    // The switch block starts tracking the next era.
+    assert_eq!(
+        performance_tracker.eras[&EraId::from(1)].validator_weights,
+        validator_weights
+    );
+    assert_eq!(performance_tracker.eras[&EraId::from(1)].block_count, 0);
+}
+
+#[test]
+fn should_retain_only_configured_number_of_eras() {
+    let mut rng = crate::new_rng();
+    let alice = PublicKey::random(&mut rng);
+    let validator_weights: BTreeMap<_, _> =
+        vec![(alice.clone(), U512::from(1))].into_iter().collect();
+    let mut performance_tracker = new_performance_tracker(2, validator_weights.clone());
+
+    // Each era consists of a single switch block.
+    for era_id in 0..5 {
+        let switch = Some((vec![], validator_weights.clone()));
+        let block = new_block(&mut rng, era_id, era_id, &alice, switch);
+        performance_tracker.add_block(&block);
+    }
+
+    // Eras 3 and 4 are retained, along with era 5 which has no blocks yet.
+    let retained_eras: Vec<_> = performance_tracker.eras.keys().copied().collect();
+    assert_eq!(
+        retained_eras,
+        vec![EraId::from(3), EraId::from(4), EraId::from(5)]
+    );
+    assert_eq!(performance_tracker.oldest_retained_era(), EraId::from(3));
+
+    // Signatures and faults in eras no longer retained are ignored.
+    performance_tracker.add_finality_signature(EraId::from(2), &alice);
+    performance_tracker.add_fault(EraId::from(2), alice);
+    assert!(!performance_tracker.eras.contains_key(&EraId::from(2)));
+    assert!(!performance_tracker.unstored_eras.contains(&EraId::from(2)));
+}
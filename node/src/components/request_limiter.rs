@@ -0,0 +1,474 @@
+//! Per-peer fairness when serving requests.
+//!
+//! Every `GetRequest` and `GetBatchRequest` received from a peer passes through the request limiter
+//! before being served.  Each peer may only have a limited number of requests served at once, and
+//! has a budget of response bytes which is replenished at a constant rate up to a maximum burst.
+//! A request which can't be served immediately waits in its peer's queue, and is rejected with a
+//! `TryLater` message if the queue is full or the request is still waiting once the queue timeout
+//! has elapsed.  The requester's fetcher treats a `TryLater` as a signal to back off from the peer
+//! rather than waiting for its request to time out.
+//!
+//! The limits apply to each peer separately, so a peer requesting more than its share only delays
+//! its own requests, not those of other peers.
+
+mod config;
+mod event;
+mod metrics;
+
+use std::{
+    collections::{HashMap, VecDeque},
+    convert::TryFrom,
+    time::{Duration, Instant},
+};
+
+use datasize::DataSize;
+use futures::FutureExt;
+use prometheus::Registry;
+use smallvec::smallvec;
+use tracing::{debug, error};
+
+use crate::{
+    components::Component,
+    effect::{
+        announcements::RequestLimiterAnnouncement, requests::NetworkRequest, EffectBuilder,
+        EffectExt, Effects, Multiple,
+    },
+    protocol::Message,
+    types::NodeId,
+    NodeRng,
+};
+pub use config::Config;
+pub(crate) use event::Event;
+use metrics::RequestLimiterMetrics;
+
+const NANOS_PER_SECOND: u128 = 1_000_000_000;
+
+/// A request waiting to be served.
+#[derive(Debug)]
+struct QueuedRequest {
+    payload: Box<Message>,
+    queued_at: Instant,
+}
+
+/// The serving state of a single peer.
+#[derive(Debug)]
+struct PeerState {
+    /// The number of the peer's requests currently being served.
+    in_flight: u32,
+    /// The number of response bytes which may still be served to the peer.  Negative once a
+    /// response larger than the remaining budget has been served.
+    budget: i64,
+    /// The time at which the budget was last replenished.
+    last_refill: Instant,
+    /// The requests waiting to be served, oldest first.
+    queue: VecDeque<QueuedRequest>,
+    /// Whether a `ProcessQueue` event is pending for the peer.
+    process_queue_scheduled: bool,
+}
+
+impl PeerState {
+    fn new(config: &Config, now: Instant) -> Self {
+        PeerState {
+            in_flight: 0,
+            budget: max_budget(config),
+            last_refill: now,
+            queue: VecDeque::new(),
+            process_queue_scheduled: false,
+        }
+    }
+
+    /// Replenishes the budget for the time elapsed since it was last replenished.
+    fn refill(&mut self, config: &Config, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last_refill);
+        let refill =
+            elapsed.as_nanos() * u128::from(config.response_bytes_per_second()) / NANOS_PER_SECOND;
+        if refill == 0 {
+            // Leave `last_refill` unchanged so that fractions of a byte aren't lost.
+            return;
+        }
+        let budget = (i128::from(self.budget) + refill as i128).min(i128::from(max_budget(config)));
+        self.budget = i64::try_from(budget).unwrap_or(i64::MAX);
+        self.last_refill = now;
+    }
+
+    /// Returns whether another request from the peer can be served at `now`.
+    fn can_serve(&mut self, config: &Config, now: Instant) -> bool {
+        if config.max_in_flight_requests() != 0 && self.in_flight >= config.max_in_flight_requests()
+        {
+            return false;
+        }
+        if config.response_bytes_per_second() == 0 {
+            return true;
+        }
+        self.refill(config, now);
+        self.budget > 0
+    }
+
+    /// Deducts the size of a served response from the budget.
+    fn charge(&mut self, config: &Config, bytes: u64, now: Instant) {
+        if config.response_bytes_per_second() == 0 {
+            return;
+        }
+        self.refill(config, now);
+        self.budget = self
+            .budget
+            .saturating_sub(i64::try_from(bytes).unwrap_or(i64::MAX));
+    }
+
+    /// Returns the time until the budget will have been replenished to a positive value.
+    fn time_until_budget(&self, config: &Config, now: Instant) -> Duration {
+        let bytes_per_second = config.response_bytes_per_second();
+        if bytes_per_second == 0 || self.budget > 0 {
+            return Duration::from_secs(0);
+        }
+        let missing = (1 - i128::from(self.budget)) as u128;
+        let nanos = (missing * NANOS_PER_SECOND + u128::from(bytes_per_second) - 1)
+            / u128::from(bytes_per_second);
+        let elapsed = now.saturating_duration_since(self.last_refill);
+        Duration::from_nanos(u64::try_from(nanos).unwrap_or(u64::MAX)).saturating_sub(elapsed)
+    }
+
+    /// Returns the time after which the peer should retry a rejected request.
+    fn retry_after(&self, config: &Config, now: Instant) -> Duration {
+        let until_budget = self.time_until_budget(config, now);
+        if until_budget.as_nanos() == 0 {
+            Duration::from_millis(config.queue_timeout())
+        } else {
+            until_budget
+        }
+    }
+
+    /// Returns the delay until the queue should next be processed, if it holds any requests.
+    fn next_check(&self, config: &Config, now: Instant) -> Option<Duration> {
+        let oldest = self.queue.front()?;
+        let until_expiry = (oldest.queued_at + Duration::from_millis(config.queue_timeout()))
+            .saturating_duration_since(now);
+        let until_budget = self.time_until_budget(config, now);
+        if until_budget.as_nanos() == 0 {
+            Some(until_expiry)
+        } else {
+            Some(until_expiry.min(until_budget))
+        }
+    }
+
+    /// Returns whether the state holds nothing which couldn't be recreated for a new peer.
+    fn is_idle(&mut self, config: &Config, now: Instant) -> bool {
+        if self.in_flight != 0 || !self.queue.is_empty() || self.process_queue_scheduled {
+            return false;
+        }
+        self.refill(config, now);
+        config.response_bytes_per_second() == 0 || self.budget >= max_budget(config)
+    }
+}
+
+/// Returns the budget of a peer which has been idle for long enough, which is at least one byte so
+/// that requests can always be served eventually.
+fn max_budget(config: &Config) -> i64 {
+    i64::try_from(config.max_response_burst_bytes().max(1)).unwrap_or(i64::MAX)
+}
+
+/// The component which admits requests from peers to be served, limiting each peer's share.
+#[derive(DataSize, Debug)]
+pub struct RequestLimiter {
+    config: Config,
+    /// The serving state of each peer with requests being served, waiting to be served or a
+    /// depleted budget.
+    #[data_size(skip)]
+    peers: HashMap<NodeId, PeerState>,
+    #[data_size(skip)]
+    metrics: RequestLimiterMetrics,
+}
+
+impl RequestLimiter {
+    pub(crate) fn new(config: Config, registry: &Registry) -> Result<Self, prometheus::Error> {
+        Ok(RequestLimiter {
+            config,
+            peers: HashMap::new(),
+            metrics: RequestLimiterMetrics::new(registry)?,
+        })
+    }
+
+    /// Accounts for a message about to be sent to `peer`, deducting the size of any served items
+    /// from the peer's budget.
+    pub(crate) fn record_outgoing(&mut self, peer: NodeId, message: &Message) {
+        let bytes: usize = match message {
+            Message::GetResponse {
+                serialized_item, ..
+            } => serialized_item.len(),
+            Message::GetBatchResponse {
+                serialized_items, ..
+            } => serialized_items.iter().map(|item| item.len()).sum(),
+            _ => return,
+        };
+        let bytes = bytes as u64;
+        self.metrics.record_served_bytes(&peer, bytes);
+
+        let now = Instant::now();
+        let config = &self.config;
+        self.peers
+            .entry(peer)
+            .or_insert_with(|| PeerState::new(config, now))
+            .charge(config, bytes, now);
+    }
+
+    /// Returns the number of bytes of items served to `peer`.
+    #[cfg(test)]
+    pub(crate) fn served_bytes(&self, peer: &NodeId) -> u64 {
+        self.metrics.served_bytes(peer)
+    }
+
+    /// Admits, queues or rejects a request received from `sender`.
+    fn handle_request<REv>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        sender: NodeId,
+        payload: Box<Message>,
+        now: Instant,
+    ) -> Effects<Event>
+    where
+        REv: From<RequestLimiterAnnouncement> + From<NetworkRequest<NodeId, Message>> + Send,
+    {
+        let config = &self.config;
+        let state = self
+            .peers
+            .entry(sender)
+            .or_insert_with(|| PeerState::new(config, now));
+
+        if state.queue.is_empty() && state.can_serve(config, now) {
+            state.in_flight += 1;
+            return effect_builder
+                .announce_request_admitted(sender, payload)
+                .ignore();
+        }
+
+        if state.queue.len() >= config.max_queued_requests() as usize {
+            debug!(%sender, "request queue full, rejecting request");
+            self.metrics.rejected_requests.inc();
+            let retry_after = state.retry_after(config, now);
+            return try_later(effect_builder, sender, *payload, retry_after);
+        }
+
+        self.metrics.queued_requests.inc();
+        state.queue.push_back(QueuedRequest {
+            payload,
+            queued_at: now,
+        });
+        self.schedule_process_queue(effect_builder, sender, now)
+    }
+
+    /// Admits the requests queued for `peer` which can now be served, and rejects those which have
+    /// waited for longer than the queue timeout.
+    fn process_queue<REv>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        peer: NodeId,
+        now: Instant,
+    ) -> Effects<Event>
+    where
+        REv: From<RequestLimiterAnnouncement> + From<NetworkRequest<NodeId, Message>> + Send,
+    {
+        let config = &self.config;
+        let state = match self.peers.get_mut(&peer) {
+            Some(state) => state,
+            None => return Effects::new(),
+        };
+
+        let queue_timeout = Duration::from_millis(config.queue_timeout());
+        let mut effects = Effects::new();
+        while let Some(request) = state.queue.pop_front() {
+            if now.saturating_duration_since(request.queued_at) >= queue_timeout {
+                debug!(%peer, "request waited too long, rejecting request");
+                self.metrics.rejected_requests.inc();
+                let retry_after = state.retry_after(config, now);
+                effects.extend(try_later(
+                    effect_builder,
+                    peer,
+                    *request.payload,
+                    retry_after,
+                ));
+            } else if state.can_serve(config, now) {
+                state.in_flight += 1;
+                effects.extend(
+                    effect_builder
+                        .announce_request_admitted(peer, request.payload)
+                        .ignore(),
+                );
+            } else {
+                state.queue.push_front(request);
+                break;
+            }
+        }
+
+        if state.is_idle(config, now) {
+            self.peers.remove(&peer);
+        } else {
+            effects.extend(self.schedule_process_queue(effect_builder, peer, now));
+        }
+        effects
+    }
+
+    /// Schedules the queue of `peer` to be processed again once a queued request could be admitted
+    /// or has expired, unless already scheduled.
+    fn schedule_process_queue<REv>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        peer: NodeId,
+        now: Instant,
+    ) -> Effects<Event>
+    where
+        REv: Send,
+    {
+        let state = match self.peers.get_mut(&peer) {
+            Some(state) if !state.process_queue_scheduled => state,
+            _ => return Effects::new(),
+        };
+        match state.next_check(&self.config, now) {
+            Some(delay) => {
+                state.process_queue_scheduled = true;
+                effect_builder
+                    .set_timeout(delay)
+                    .event(move |_| Event::ProcessQueue { peer })
+            }
+            None => Effects::new(),
+        }
+    }
+}
+
+/// Rejects the given request from `peer` with a `TryLater` message.
+fn try_later<REv>(
+    effect_builder: EffectBuilder<REv>,
+    peer: NodeId,
+    request: Message,
+    retry_after: Duration,
+) -> Effects<Event>
+where
+    REv: From<NetworkRequest<NodeId, Message>> + Send,
+{
+    let (tag, serialized_ids) = match request {
+        Message::GetRequest { tag, serialized_id } => (tag, vec![serialized_id]),
+        Message::GetBatchRequest {
+            tag,
+            serialized_ids,
+        } => (tag, serialized_ids),
+        other => {
+            error!(%other, %peer, "cannot reject message which is not a get request");
+            return Effects::new();
+        }
+    };
+    let message = Message::TryLater {
+        tag,
+        serialized_ids,
+        retry_after: retry_after.into(),
+    };
+    effect_builder.send_message(peer, message).ignore()
+}
+
+/// Combines the effects of serving a request admitted from `peer` into a single effect, which
+/// informs the request limiter once they have all completed.
+pub(crate) fn track_serving<REv>(peer: NodeId, effects: Effects<REv>) -> Effects<REv>
+where
+    REv: From<Event> + Send + 'static,
+{
+    let served = futures::future::join_all(effects).map(move |results| {
+        let mut events: Multiple<REv> = results.into_iter().flatten().collect();
+        events.push(Event::RequestServed { peer }.into());
+        events
+    });
+    smallvec![served.boxed()]
+}
+
+impl<REv> Component<REv> for RequestLimiter
+where
+    REv: From<Event>
+        + From<RequestLimiterAnnouncement>
+        + From<NetworkRequest<NodeId, Message>>
+        + Send
+        + 'static,
+{
+    type Event = Event;
+    type ConstructionError = prometheus::Error;
+
+    fn handle_event(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        _rng: &mut NodeRng,
+        event: Self::Event,
+    ) -> Effects<Self::Event> {
+        let now = Instant::now();
+        match event {
+            Event::RequestReceived { sender, payload } => {
+                self.handle_request(effect_builder, sender, payload, now)
+            }
+            Event::RequestServed { peer } => {
+                if let Some(state) = self.peers.get_mut(&peer) {
+                    state.in_flight = state.in_flight.saturating_sub(1);
+                }
+                self.process_queue(effect_builder, peer, now)
+            }
+            Event::ProcessQueue { peer } => {
+                if let Some(state) = self.peers.get_mut(&peer) {
+                    state.process_queue_scheduled = false;
+                }
+                self.process_queue(effect_builder, peer, now)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BYTES_PER_SECOND: u64 = 1_000;
+
+    fn config() -> Config {
+        Config::new(2, BYTES_PER_SECOND, 2 * BYTES_PER_SECOND, 4, 500)
+    }
+
+    #[test]
+    fn should_limit_requests_in_flight() {
+        let config = config();
+        let now = Instant::now();
+        let mut state = PeerState::new(&config, now);
+
+        assert!(state.can_serve(&config, now));
+        state.in_flight = 2;
+        assert!(!state.can_serve(&config, now));
+        state.in_flight = 1;
+        assert!(state.can_serve(&config, now));
+    }
+
+    #[test]
+    fn should_replenish_budget_at_configured_rate() {
+        let config = config();
+        let start = Instant::now();
+        let mut state = PeerState::new(&config, start);
+
+        // Serving a response larger than the whole budget is allowed, but leaves it depleted.
+        state.charge(&config, 3 * BYTES_PER_SECOND, start);
+        assert!(!state.can_serve(&config, start));
+        let expected = Duration::from_millis(1_001);
+        assert_eq!(state.time_until_budget(&config, start), expected);
+        assert_eq!(state.retry_after(&config, start), expected);
+
+        assert!(!state.can_serve(&config, start + Duration::from_secs(1)));
+        assert!(state.can_serve(&config, start + expected));
+
+        // The budget never exceeds the maximum burst, however long the peer is idle.
+        state.refill(&config, start + Duration::from_secs(60));
+        assert_eq!(state.budget, 2 * BYTES_PER_SECOND as i64);
+        assert!(state.is_idle(&config, start + Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn should_not_limit_bytes_if_rate_is_zero() {
+        let config = Config::new(0, 0, 0, 4, 500);
+        let now = Instant::now();
+        let mut state = PeerState::new(&config, now);
+
+        state.charge(&config, u64::MAX, now);
+        state.in_flight = 1_000;
+        assert!(state.can_serve(&config, now));
+        assert_eq!(state.retry_after(&config, now), Duration::from_millis(500));
+    }
+}
@@ -0,0 +1,93 @@
+use datasize::DataSize;
+use serde::{Deserialize, Serialize};
+
+use crate::config_validation::ConfigValidationError;
+
+const DEFAULT_MAX_IN_FLIGHT_REQUESTS: u32 = 32;
+const DEFAULT_RESPONSE_BYTES_PER_SECOND: u64 = 16 * 1024 * 1024;
+const DEFAULT_MAX_RESPONSE_BURST_BYTES: u64 = 32 * 1024 * 1024;
+const DEFAULT_MAX_QUEUED_REQUESTS: u32 = 256;
+const DEFAULT_QUEUE_TIMEOUT_MILLIS: u64 = 500;
+
+/// Configuration options for limiting the requests served to each peer.
+#[derive(Copy, Clone, DataSize, Debug, Deserialize, Serialize)]
+pub struct Config {
+    /// The maximum number of requests from a single peer which are served at once.  `0` disables
+    /// the limit.
+    max_in_flight_requests: u32,
+    /// The rate in bytes per second at which each peer's budget for responses is replenished.
+    /// `0` disables the budget.
+    response_bytes_per_second: u64,
+    /// The maximum number of bytes a peer's budget can accumulate while it is idle, i.e. the
+    /// largest burst of responses served at once.
+    max_response_burst_bytes: u64,
+    /// The maximum number of requests from a single peer which wait to be served.  Requests beyond
+    /// this are rejected immediately.
+    max_queued_requests: u32,
+    /// The time in milliseconds for which a request waits to be served before being rejected.
+    queue_timeout: u64,
+}
+
+impl Config {
+    /// Constructs a config with the given per-peer limits.
+    #[cfg(test)]
+    pub(crate) fn new(
+        max_in_flight_requests: u32,
+        response_bytes_per_second: u64,
+        max_response_burst_bytes: u64,
+        max_queued_requests: u32,
+        queue_timeout: u64,
+    ) -> Self {
+        Config {
+            max_in_flight_requests,
+            response_bytes_per_second,
+            max_response_burst_bytes,
+            max_queued_requests,
+            queue_timeout,
+        }
+    }
+
+    pub(crate) fn max_in_flight_requests(&self) -> u32 {
+        self.max_in_flight_requests
+    }
+
+    pub(crate) fn response_bytes_per_second(&self) -> u64 {
+        self.response_bytes_per_second
+    }
+
+    pub(crate) fn max_response_burst_bytes(&self) -> u64 {
+        self.max_response_burst_bytes
+    }
+
+    pub(crate) fn max_queued_requests(&self) -> u32 {
+        self.max_queued_requests
+    }
+
+    pub(crate) fn queue_timeout(&self) -> u64 {
+        self.queue_timeout
+    }
+
+    /// Checks whether the values set in the config make sense, pushing every problem found to
+    /// `errors`.
+    pub(crate) fn validate(&self, errors: &mut Vec<ConfigValidationError>) {
+        if self.queue_timeout == 0 {
+            errors.push(ConfigValidationError::out_of_range(
+                "request_limiter.queue_timeout",
+                self.queue_timeout,
+                "greater than 0",
+            ));
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            max_in_flight_requests: DEFAULT_MAX_IN_FLIGHT_REQUESTS,
+            response_bytes_per_second: DEFAULT_RESPONSE_BYTES_PER_SECOND,
+            max_response_burst_bytes: DEFAULT_MAX_RESPONSE_BURST_BYTES,
+            max_queued_requests: DEFAULT_MAX_QUEUED_REQUESTS,
+            queue_timeout: DEFAULT_QUEUE_TIMEOUT_MILLIS,
+        }
+    }
+}
@@ -0,0 +1,33 @@
+use std::fmt::{self, Display, Formatter};
+
+use serde::Serialize;
+
+use crate::{protocol::Message, types::NodeId};
+
+/// `RequestLimiter` events.
+#[derive(Debug, Serialize)]
+pub enum Event {
+    /// A `GetRequest` or `GetBatchRequest` was received from a peer.
+    RequestReceived {
+        sender: NodeId,
+        payload: Box<Message>,
+    },
+    /// Serving a request admitted from the peer has finished.
+    RequestServed { peer: NodeId },
+    /// The requests queued for the peer should be admitted or rejected if possible.
+    ProcessQueue { peer: NodeId },
+}
+
+impl Display for Event {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Event::RequestReceived { sender, payload } => {
+                write!(formatter, "received {} from {}", payload, sender)
+            }
+            Event::RequestServed { peer } => write!(formatter, "served request from {}", peer),
+            Event::ProcessQueue { peer } => {
+                write!(formatter, "process queued requests from {}", peer)
+            }
+        }
+    }
+}
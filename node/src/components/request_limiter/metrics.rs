@@ -0,0 +1,68 @@
+use prometheus::{IntCounter, IntCounterVec, Opts, Registry};
+
+use crate::{types::NodeId, unregister_metric};
+
+#[derive(Debug)]
+pub(super) struct RequestLimiterMetrics {
+    /// Number of bytes of items served in responses, by requesting peer.
+    pub(super) served_bytes: IntCounterVec,
+    /// Number of requests which had to wait to be served.
+    pub(super) queued_requests: IntCounter,
+    /// Number of requests rejected with a try-later message.
+    pub(super) rejected_requests: IntCounter,
+    /// Reference to the registry for unregistering.
+    registry: Registry,
+}
+
+impl RequestLimiterMetrics {
+    pub(super) fn new(registry: &Registry) -> Result<Self, prometheus::Error> {
+        let served_bytes = IntCounterVec::new(
+            Opts::new(
+                "request_limiter_served_bytes",
+                "number of bytes of items served in responses to get requests, by peer",
+            ),
+            &["peer"],
+        )?;
+        let queued_requests = IntCounter::new(
+            "request_limiter_queued_requests",
+            "number of get requests from peers which had to wait to be served",
+        )?;
+        let rejected_requests = IntCounter::new(
+            "request_limiter_rejected_requests",
+            "number of get requests from peers rejected with a try-later message",
+        )?;
+        registry.register(Box::new(served_bytes.clone()))?;
+        registry.register(Box::new(queued_requests.clone()))?;
+        registry.register(Box::new(rejected_requests.clone()))?;
+
+        Ok(RequestLimiterMetrics {
+            served_bytes,
+            queued_requests,
+            rejected_requests,
+            registry: registry.clone(),
+        })
+    }
+
+    /// Records `bytes` having been served to `peer`.
+    pub(super) fn record_served_bytes(&self, peer: &NodeId, bytes: u64) {
+        self.served_bytes
+            .with_label_values(&[&peer.to_string()])
+            .inc_by(bytes);
+    }
+
+    /// Returns the number of bytes served to `peer`.
+    #[cfg(test)]
+    pub(super) fn served_bytes(&self, peer: &NodeId) -> u64 {
+        self.served_bytes
+            .with_label_values(&[&peer.to_string()])
+            .get()
+    }
+}
+
+impl Drop for RequestLimiterMetrics {
+    fn drop(&mut self) {
+        unregister_metric!(self.registry, self.served_bytes);
+        unregister_metric!(self.registry, self.queued_requests);
+        unregister_metric!(self.registry, self.rejected_requests);
+    }
+}
@@ -12,18 +12,29 @@
 //! reactor, and an external facing http server that exposes various uri routes and converts
 //! HTTP requests into the appropriate component events.
 //!
-//! Currently this component supports two endpoints, each of which takes no arguments:
+//! Currently this component supports the following endpoints, each of which takes no arguments:
 //! /status : a human readable JSON equivalent of the info-get-status rpc method.
 //!     example: curl -X GET 'http://<ip>:8888/status'
 //! /metrics : time series data collected from the internals of the node being queried.
 //!     example: curl -X GET 'http://<ip>:8888/metrics'
+//! /health : a cheap liveness check, returning 503 if the node's event loop is unresponsive.
+//!     example: curl -X GET 'http://<ip>:8888/health'
+//! /ready : a cheap readiness check, returning 503 with the failed checks if the node is not
+//!     sufficiently peered, synced or storing blocks.
+//!     example: curl -X GET 'http://<ip>:8888/ready'
 
 mod config;
 mod event;
 mod filters;
+mod health;
 mod http_server;
 
-use std::{convert::Infallible, fmt::Debug};
+use std::{
+    convert::Infallible,
+    fmt::Debug,
+    sync::{Arc, RwLock},
+    time::Instant,
+};
 
 use datasize::DataSize;
 use futures::{future::BoxFuture, join, FutureExt};
@@ -34,15 +45,16 @@ use casper_types::ProtocolVersion;
 
 use super::Component;
 use crate::{
+    config_overrides::ConfigOverride,
     effect::{
         requests::{
-            ChainspecLoaderRequest, ConsensusRequest, MetricsRequest, NetworkInfoRequest,
-            StorageRequest,
+            ChainspecLoaderRequest, ConsensusRequest, ContractRuntimeRequest,
+            DeployPropagationRequest, MetricsRequest, NetworkInfoRequest, StorageRequest,
         },
         EffectBuilder, EffectExt, Effects,
     },
     reactor::Finalize,
-    types::{NodeId, StatusFeed},
+    types::{NodeId, StatusFeed, StorageStatus},
     utils::{self, ListeningError},
     NodeRng,
 };
@@ -50,6 +62,8 @@ use crate::{
 use crate::{components::rpc_server::rpcs::docs::OPEN_RPC_SCHEMA, effect::requests::RestRequest};
 pub use config::Config;
 pub(crate) use event::Event;
+pub(crate) use filters::{METRICS_API_PATH, STATUS_API_PATH};
+use health::NodeHealth;
 
 /// A helper trait capturing all of this components Request type dependencies.
 pub trait ReactorEventT:
@@ -59,7 +73,9 @@ pub trait ReactorEventT:
     + From<StorageRequest>
     + From<ChainspecLoaderRequest>
     + From<ConsensusRequest>
+    + From<ContractRuntimeRequest>
     + From<MetricsRequest>
+    + From<DeployPropagationRequest>
     + Send
 {
 }
@@ -71,7 +87,9 @@ impl<REv> ReactorEventT for REv where
         + From<StorageRequest>
         + From<ChainspecLoaderRequest>
         + From<ConsensusRequest>
+        + From<ContractRuntimeRequest>
         + From<MetricsRequest>
+        + From<DeployPropagationRequest>
         + Send
         + 'static
 {
@@ -85,32 +103,66 @@ pub(crate) struct RestServer {
     /// The task handle which will only join once the server loop has exited.
     #[data_size(skip)]
     server_join_handle: Option<JoinHandle<()>>,
+    /// The state used to answer health and readiness probes, shared with the server.
+    #[data_size(skip)]
+    node_health: Arc<RwLock<NodeHealth>>,
+    /// The settings of the node's config which differ from their defaults, served in the status.
+    config_overrides: Vec<ConfigOverride>,
 }
 
 impl RestServer {
+    /// Creates a new REST server.
+    ///
+    /// `is_joining` and `maybe_highest_block_height` provide the initial inputs to the readiness
+    /// checks; subsequent changes are provided via events.
     pub(crate) fn new<REv>(
         config: Config,
         effect_builder: EffectBuilder<REv>,
         api_version: ProtocolVersion,
-    ) -> Result<Self, ListeningError>
+        is_joining: bool,
+        maybe_highest_block_height: Option<u64>,
+        config_overrides: Vec<ConfigOverride>,
+    ) -> Result<(Self, Effects<Event>), ListeningError>
     where
         REv: ReactorEventT,
     {
         let (shutdown_sender, shutdown_receiver) = oneshot::channel::<()>();
 
+        let node_health = Arc::new(RwLock::new(NodeHealth::new(
+            &config,
+            is_joining,
+            maybe_highest_block_height,
+        )));
+
         let builder = utils::start_listening(&config.address)?;
         let server_join_handle = tokio::spawn(http_server::run(
             builder,
             effect_builder,
             api_version,
+            Arc::clone(&node_health),
             shutdown_receiver,
             config.qps_limit,
         ));
 
-        Ok(RestServer {
+        let rest_server = RestServer {
             shutdown_sender,
             server_join_handle: Some(server_join_handle),
-        })
+            node_health,
+            config_overrides,
+        };
+        let effects = effect_builder
+            .set_timeout(health::HEARTBEAT_INTERVAL)
+            .event(|_| Event::Heartbeat);
+
+        Ok((rest_server, effects))
+    }
+
+    /// Applies `update` to the cached health and readiness state.
+    fn update_node_health<F: FnOnce(&mut NodeHealth)>(&self, update: F) {
+        match self.node_health.write() {
+            Ok(mut node_health) => update(&mut *node_health),
+            Err(error) => error!(%error, "failed to update node health"),
+        }
     }
 }
 
@@ -128,18 +180,46 @@ where
         event: Self::Event,
     ) -> Effects<Self::Event> {
         match event {
-            Event::RestRequest(RestRequest::GetStatus { responder }) => async move {
-                let (last_added_block, peers, chainspec_info, consensus_status) = join!(
-                    effect_builder.get_highest_block_from_storage(),
-                    effect_builder.network_peers(),
-                    effect_builder.get_chainspec_info(),
-                    effect_builder.consensus_status()
-                );
-                let status_feed =
-                    StatusFeed::new(last_added_block, peers, chainspec_info, consensus_status);
-                responder.respond(status_feed).await;
+            Event::RestRequest(RestRequest::GetStatus { responder }) => {
+                let config_overrides = self.config_overrides.clone();
+                async move {
+                    let (
+                        last_added_block,
+                        peers,
+                        dial_backoffs,
+                        chainspec_info,
+                        consensus_status,
+                        era_progress,
+                        node_storage,
+                        global_state,
+                    ) = join!(
+                        effect_builder.get_highest_block_from_storage(),
+                        effect_builder.network_peers(),
+                        effect_builder.network_dial_backoffs(),
+                        effect_builder.get_chainspec_info(),
+                        effect_builder.consensus_status(),
+                        effect_builder.consensus_era_progress(),
+                        effect_builder.get_storage_lmdb_stats(),
+                        effect_builder.get_global_state_lmdb_stats()
+                    );
+                    let storage = StorageStatus {
+                        node_storage,
+                        global_state,
+                    };
+                    let status_feed = StatusFeed::new(
+                        last_added_block,
+                        peers,
+                        dial_backoffs,
+                        chainspec_info,
+                        consensus_status,
+                        era_progress,
+                        storage,
+                        config_overrides,
+                    );
+                    responder.respond(status_feed).await;
+                }
+                .ignore()
             }
-            .ignore(),
             Event::RestRequest(RestRequest::GetMetrics { responder }) => effect_builder
                 .get_metrics()
                 .event(move |text| Event::GetMetricsResult {
@@ -150,10 +230,33 @@ where
                 let schema = OPEN_RPC_SCHEMA.clone();
                 responder.respond(schema).ignore()
             }
+            Event::RestRequest(RestRequest::GetDeployPropagation { responder }) => async move {
+                let maybe_spreads = effect_builder.get_deploy_spreads().await;
+                responder.respond(maybe_spreads).await;
+            }
+            .ignore(),
             Event::GetMetricsResult {
                 text,
                 main_responder,
             } => main_responder.respond(text).ignore(),
+            Event::Heartbeat => {
+                self.update_node_health(|node_health| node_health.record_heartbeat(Instant::now()));
+                effect_builder
+                    .set_timeout(health::HEARTBEAT_INTERVAL)
+                    .event(|_| Event::Heartbeat)
+            }
+            Event::BlockAdded { height } => {
+                self.update_node_health(|node_health| node_health.block_stored(height));
+                Effects::new()
+            }
+            Event::BlockFinalized { height } => {
+                self.update_node_health(|node_health| node_health.block_observed(height));
+                Effects::new()
+            }
+            Event::PeerCountChanged(peer_count) => {
+                self.update_node_health(|node_health| node_health.set_peer_count(peer_count));
+                Effects::new()
+            }
         }
     }
 }
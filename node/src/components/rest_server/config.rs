@@ -1,12 +1,18 @@
 use datasize::DataSize;
 use serde::{Deserialize, Serialize};
 
+use crate::config_validation::{self, ConfigValidationError};
+
 /// Default binding address for the REST HTTP server.
 ///
 /// Uses a fixed port per node, but binds on any interface.
 const DEFAULT_ADDRESS: &str = "0.0.0.0:0";
 /// Default rate limit in qps.
 const DEFAULT_QPS_LIMIT: u64 = 100;
+/// Default minimum number of connected peers for the node to be reported as ready.
+const DEFAULT_READY_MIN_PEERS: usize = 1;
+/// Default maximum number of blocks the node can lag the observed tip and be reported as ready.
+const DEFAULT_READY_MAX_BLOCKS_BEHIND: u64 = 5;
 
 /// REST HTTP server configuration.
 #[derive(Clone, DataSize, Debug, Deserialize, Serialize)]
@@ -18,6 +24,13 @@ pub struct Config {
 
     /// Max rate limit in qps.
     pub qps_limit: u64,
+
+    /// Minimum number of connected peers for the `/ready` endpoint to report the node as ready.
+    pub ready_min_peers: usize,
+
+    /// Maximum number of blocks the node's highest stored block can be behind the highest observed
+    /// block for the `/ready` endpoint to report the node as ready.
+    pub ready_max_blocks_behind: u64,
 }
 
 impl Config {
@@ -26,6 +39,21 @@ impl Config {
         Config {
             address: DEFAULT_ADDRESS.to_string(),
             qps_limit: DEFAULT_QPS_LIMIT,
+            ready_min_peers: DEFAULT_READY_MIN_PEERS,
+            ready_max_blocks_behind: DEFAULT_READY_MAX_BLOCKS_BEHIND,
+        }
+    }
+
+    /// Checks whether the values set in the config make sense, pushing every problem found to
+    /// `errors`.
+    pub(crate) fn validate(&self, errors: &mut Vec<ConfigValidationError>) {
+        config_validation::check_address("rest_server.address", &self.address, true, errors);
+        if self.qps_limit == 0 {
+            errors.push(ConfigValidationError::out_of_range(
+                "rest_server.qps_limit",
+                self.qps_limit,
+                "greater than 0",
+            ));
         }
     }
 }
@@ -22,6 +22,14 @@ pub enum Event {
         text: Option<String>,
         main_responder: Responder<Option<String>>,
     },
+    /// The periodic heartbeat showing the reactor's event loop is responsive.
+    Heartbeat,
+    /// A block at the given height was written to storage.
+    BlockAdded { height: u64 },
+    /// A block at the given height was finalized, but may not yet be stored.
+    BlockFinalized { height: u64 },
+    /// The number of connected peers changed.
+    PeerCountChanged(usize),
 }
 
 impl Display for Event {
@@ -32,6 +40,14 @@ impl Display for Event {
                 Some(txt) => write!(formatter, "get metrics ({} bytes)", txt.len()),
                 None => write!(formatter, "get metrics (failed)"),
             },
+            Event::Heartbeat => write!(formatter, "heartbeat"),
+            Event::BlockAdded { height } => write!(formatter, "block added at height {}", height),
+            Event::BlockFinalized { height } => {
+                write!(formatter, "block finalized at height {}", height)
+            }
+            Event::PeerCountChanged(peer_count) => {
+                write!(formatter, "peer count changed to {}", peer_count)
+            }
         }
     }
 }
@@ -1,7 +1,12 @@
+use std::{
+    sync::{Arc, RwLock},
+    time::Instant,
+};
+
 use futures::FutureExt;
 use http::Response;
 use hyper::Body;
-use tracing::warn;
+use tracing::{error, warn};
 use warp::{
     filters::BoxedFilter,
     http::StatusCode,
@@ -12,7 +17,7 @@ use warp::{
 
 use casper_types::ProtocolVersion;
 
-use super::ReactorEventT;
+use super::{health::NodeHealth, ReactorEventT};
 use crate::{
     effect::{requests::RestRequest, EffectBuilder},
     reactor::QueueKind,
@@ -28,6 +33,15 @@ pub const METRICS_API_PATH: &str = "metrics";
 /// The OpenRPC scehma URL path.
 pub const JSON_RPC_SCHEMA_API_PATH: &str = "rpc-schema";
 
+/// The health check URL path.
+pub const HEALTH_API_PATH: &str = "health";
+
+/// The readiness check URL path.
+pub const READY_API_PATH: &str = "ready";
+
+/// The deploy propagation URL path.
+pub const DEPLOY_PROPAGATION_API_PATH: &str = "deploy-propagation";
+
 pub(super) fn create_status_filter<REv: ReactorEventT>(
     effect_builder: EffectBuilder<REv>,
     api_version: ProtocolVersion,
@@ -93,3 +107,74 @@ pub(super) fn create_rpc_schema_filter<REv: ReactorEventT>(
         })
         .boxed()
 }
+
+pub(super) fn create_deploy_propagation_filter<REv: ReactorEventT>(
+    effect_builder: EffectBuilder<REv>,
+) -> BoxedFilter<(Response<Body>,)> {
+    warp::get()
+        .and(warp::path(DEPLOY_PROPAGATION_API_PATH))
+        .and_then(move || {
+            effect_builder
+                .make_request(
+                    |responder| RestRequest::GetDeployPropagation { responder },
+                    QueueKind::Api,
+                )
+                .map(|maybe_spreads| match maybe_spreads {
+                    Some(spreads) => Ok::<_, Rejection>(reply::json(&spreads).into_response()),
+                    None => Ok(reply::with_status(
+                        "deploy propagation measurement not enabled",
+                        StatusCode::NOT_FOUND,
+                    )
+                    .into_response()),
+                })
+        })
+        .boxed()
+}
+
+pub(super) fn create_health_filter(
+    node_health: Arc<RwLock<NodeHealth>>,
+) -> BoxedFilter<(Response<Body>,)> {
+    warp::get()
+        .and(warp::path(HEALTH_API_PATH))
+        .map(move || match node_health.read() {
+            Ok(node_health) => {
+                let report = node_health.health(Instant::now());
+                let status = if report.is_healthy() {
+                    StatusCode::OK
+                } else {
+                    StatusCode::SERVICE_UNAVAILABLE
+                };
+                reply::with_status(reply::json(&report), status).into_response()
+            }
+            Err(error) => {
+                error!(%error, "failed to read node health");
+                reply::with_status("node health not available", StatusCode::SERVICE_UNAVAILABLE)
+                    .into_response()
+            }
+        })
+        .boxed()
+}
+
+pub(super) fn create_ready_filter(
+    node_health: Arc<RwLock<NodeHealth>>,
+) -> BoxedFilter<(Response<Body>,)> {
+    warp::get()
+        .and(warp::path(READY_API_PATH))
+        .map(move || match node_health.read() {
+            Ok(node_health) => {
+                let report = node_health.readiness();
+                let status = if report.is_ready() {
+                    StatusCode::OK
+                } else {
+                    StatusCode::SERVICE_UNAVAILABLE
+                };
+                reply::with_status(reply::json(&report), status).into_response()
+            }
+            Err(error) => {
+                error!(%error, "failed to read node health");
+                reply::with_status("node health not available", StatusCode::SERVICE_UNAVAILABLE)
+                    .into_response()
+            }
+        })
+        .boxed()
+}
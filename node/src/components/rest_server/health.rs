@@ -0,0 +1,286 @@
+//! Cached inputs for the `/health` and `/ready` endpoints.
+//!
+//! These endpoints are intended for frequent probing, so they never involve other components.
+//! Instead, the REST server caches the relevant state as it is pushed to the component via
+//! announcements and timer events, and the endpoints only read from this cache.
+
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use super::Config;
+
+/// How often the reactor's event loop records a heartbeat.
+pub(super) const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The age beyond which the last heartbeat indicates the reactor's event loop is unresponsive.
+const MAX_HEARTBEAT_AGE: Duration = Duration::from_secs(10);
+
+/// The state used to answer health and readiness probes.
+#[derive(Debug)]
+pub(super) struct NodeHealth {
+    /// Minimum number of connected peers to be ready.
+    min_peers: usize,
+    /// Maximum number of blocks the highest stored block can be behind the observed tip.
+    max_blocks_behind: u64,
+    /// Whether the node is still joining the network.
+    is_joining: bool,
+    /// When the reactor's event loop last handled a heartbeat.
+    last_heartbeat: Instant,
+    /// The number of currently connected peers.
+    peer_count: usize,
+    /// The height of the highest block stored locally.
+    highest_stored_height: Option<u64>,
+    /// The height of the highest block observed, which may not yet be stored locally.
+    highest_observed_height: Option<u64>,
+    /// Whether a block has been written to storage since the REST server started.
+    block_stored: bool,
+}
+
+impl NodeHealth {
+    pub(super) fn new(
+        config: &Config,
+        is_joining: bool,
+        maybe_highest_block_height: Option<u64>,
+    ) -> Self {
+        NodeHealth {
+            min_peers: config.ready_min_peers,
+            max_blocks_behind: config.ready_max_blocks_behind,
+            is_joining,
+            last_heartbeat: Instant::now(),
+            peer_count: 0,
+            highest_stored_height: maybe_highest_block_height,
+            highest_observed_height: maybe_highest_block_height,
+            block_stored: false,
+        }
+    }
+
+    /// Records that the reactor's event loop handled a heartbeat at `now`.
+    pub(super) fn record_heartbeat(&mut self, now: Instant) {
+        self.last_heartbeat = now;
+    }
+
+    /// Records the current number of connected peers.
+    pub(super) fn set_peer_count(&mut self, peer_count: usize) {
+        self.peer_count = peer_count;
+    }
+
+    /// Records that a block at the given height has been written to storage.
+    pub(super) fn block_stored(&mut self, height: u64) {
+        self.block_stored = true;
+        self.highest_stored_height = self.highest_stored_height.max(Some(height));
+        self.block_observed(height);
+    }
+
+    /// Records that a block at the given height exists, whether or not it is stored locally.
+    pub(super) fn block_observed(&mut self, height: u64) {
+        self.highest_observed_height = self.highest_observed_height.max(Some(height));
+    }
+
+    /// Returns the result of the health check at `now`.
+    pub(super) fn health(&self, now: Instant) -> HealthReport {
+        let last_heartbeat_age = now.saturating_duration_since(self.last_heartbeat);
+        HealthReport {
+            healthy: last_heartbeat_age <= MAX_HEARTBEAT_AGE,
+            last_heartbeat_age_ms: last_heartbeat_age.as_millis() as u64,
+        }
+    }
+
+    /// Returns the result of the readiness checks.
+    pub(super) fn readiness(&self) -> ReadinessReport {
+        let mut failed_checks = Vec::new();
+
+        if self.peer_count < self.min_peers {
+            failed_checks.push(FailedCheck {
+                check: ReadinessCheck::Peers,
+                reason: format!(
+                    "{} peers connected, at least {} required",
+                    self.peer_count, self.min_peers
+                ),
+            });
+        }
+
+        if self.is_joining {
+            failed_checks.push(FailedCheck {
+                check: ReadinessCheck::Sync,
+                reason: "node is joining the network".to_string(),
+            });
+        } else if let Some(highest_observed_height) = self.highest_observed_height {
+            // If nothing is stored, we're behind by the full height of the chain.
+            let blocks_behind = match self.highest_stored_height {
+                Some(highest_stored_height) => {
+                    highest_observed_height.saturating_sub(highest_stored_height)
+                }
+                None => highest_observed_height.saturating_add(1),
+            };
+            if blocks_behind > self.max_blocks_behind {
+                failed_checks.push(FailedCheck {
+                    check: ReadinessCheck::Sync,
+                    reason: format!(
+                        "{} blocks behind the highest observed block at height {}, at most {} \
+                        allowed",
+                        blocks_behind, highest_observed_height, self.max_blocks_behind
+                    ),
+                });
+            }
+        }
+
+        if !self.block_stored {
+            failed_checks.push(FailedCheck {
+                check: ReadinessCheck::Storage,
+                reason: "no block has been written to storage yet".to_string(),
+            });
+        }
+
+        ReadinessReport {
+            ready: failed_checks.is_empty(),
+            failed_checks,
+        }
+    }
+}
+
+/// The JSON body returned by the `/health` endpoint.
+#[derive(Debug, Serialize)]
+pub(super) struct HealthReport {
+    healthy: bool,
+    last_heartbeat_age_ms: u64,
+}
+
+impl HealthReport {
+    pub(super) fn is_healthy(&self) -> bool {
+        self.healthy
+    }
+}
+
+/// The JSON body returned by the `/ready` endpoint.
+#[derive(Debug, Serialize)]
+pub(super) struct ReadinessReport {
+    ready: bool,
+    failed_checks: Vec<FailedCheck>,
+}
+
+impl ReadinessReport {
+    pub(super) fn is_ready(&self) -> bool {
+        self.ready
+    }
+}
+
+/// A readiness check which did not pass.
+#[derive(Debug, Serialize)]
+pub(super) struct FailedCheck {
+    check: ReadinessCheck,
+    reason: String,
+}
+
+/// The individual checks performed by the `/ready` endpoint.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(super) enum ReadinessCheck {
+    /// Enough peers are connected.
+    Peers,
+    /// The highest stored block is close enough to the highest observed block.
+    Sync,
+    /// Blocks are being written to storage.
+    Storage,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn failed_checks(node_health: &NodeHealth) -> Vec<ReadinessCheck> {
+        let report = node_health.readiness();
+        assert_eq!(report.is_ready(), report.failed_checks.is_empty());
+        report
+            .failed_checks
+            .iter()
+            .map(|failed_check| failed_check.check)
+            .collect()
+    }
+
+    fn config() -> Config {
+        Config {
+            ready_min_peers: 3,
+            ready_max_blocks_behind: 5,
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn should_become_ready_as_behind_and_under_peered_node_catches_up() {
+        let mut node_health = NodeHealth::new(&config(), false, Some(10));
+        node_health.set_peer_count(1);
+        node_health.block_observed(100);
+        assert_eq!(
+            failed_checks(&node_health),
+            vec![
+                ReadinessCheck::Peers,
+                ReadinessCheck::Sync,
+                ReadinessCheck::Storage
+            ]
+        );
+
+        // Storing blocks which are still too far behind the observed tip only fixes storage.
+        node_health.block_stored(94);
+        assert_eq!(
+            failed_checks(&node_health),
+            vec![ReadinessCheck::Peers, ReadinessCheck::Sync]
+        );
+
+        // Catching up to within the threshold fixes sync.
+        node_health.block_stored(95);
+        assert_eq!(failed_checks(&node_health), vec![ReadinessCheck::Peers]);
+
+        // More peers, but still too few.
+        node_health.set_peer_count(2);
+        assert_eq!(failed_checks(&node_health), vec![ReadinessCheck::Peers]);
+
+        node_health.set_peer_count(3);
+        assert!(failed_checks(&node_health).is_empty());
+        assert!(node_health.readiness().is_ready());
+
+        // Falling behind again, or losing peers, makes the node unready.
+        node_health.block_observed(101);
+        assert_eq!(failed_checks(&node_health), vec![ReadinessCheck::Sync]);
+        node_health.set_peer_count(0);
+        assert_eq!(
+            failed_checks(&node_health),
+            vec![ReadinessCheck::Peers, ReadinessCheck::Sync]
+        );
+    }
+
+    #[test]
+    fn should_not_be_synced_with_no_stored_blocks() {
+        let mut node_health = NodeHealth::new(&config(), false, None);
+        node_health.set_peer_count(3);
+        // With nothing stored, the node is behind by the full height of the chain.
+        node_health.block_observed(5);
+        assert_eq!(
+            failed_checks(&node_health),
+            vec![ReadinessCheck::Sync, ReadinessCheck::Storage]
+        );
+    }
+
+    #[test]
+    fn should_not_be_ready_while_joining() {
+        let mut node_health = NodeHealth::new(&config(), true, None);
+        node_health.set_peer_count(3);
+        node_health.block_stored(100);
+        assert_eq!(failed_checks(&node_health), vec![ReadinessCheck::Sync]);
+    }
+
+    #[test]
+    fn should_be_unhealthy_if_heartbeat_is_stale() {
+        let mut node_health = NodeHealth::new(&config(), false, None);
+        let start = Instant::now();
+        node_health.record_heartbeat(start);
+        assert!(node_health.health(start).is_healthy());
+        assert!(node_health.health(start + MAX_HEARTBEAT_AGE).is_healthy());
+
+        let stale = start + MAX_HEARTBEAT_AGE + Duration::from_millis(1);
+        assert!(!node_health.health(stale).is_healthy());
+
+        node_health.record_heartbeat(stale);
+        assert!(node_health.health(stale).is_healthy());
+    }
+}
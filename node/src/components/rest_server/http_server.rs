@@ -1,4 +1,8 @@
-use std::{convert::Infallible, time::Duration};
+use std::{
+    convert::Infallible,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
 
 use futures::{future, TryFutureExt};
 use hyper::server::{conn::AddrIncoming, Builder};
@@ -9,7 +13,7 @@ use warp::Filter;
 
 use casper_types::ProtocolVersion;
 
-use super::{filters, ReactorEventT};
+use super::{filters, health::NodeHealth, ReactorEventT};
 use crate::effect::EffectBuilder;
 
 /// Run the REST HTTP server.
@@ -19,6 +23,7 @@ pub(super) async fn run<REv: ReactorEventT>(
     builder: Builder<AddrIncoming>,
     effect_builder: EffectBuilder<REv>,
     api_version: ProtocolVersion,
+    node_health: Arc<RwLock<NodeHealth>>,
     shutdown_receiver: oneshot::Receiver<()>,
     qps_limit: u64,
 ) {
@@ -26,8 +31,18 @@ pub(super) async fn run<REv: ReactorEventT>(
     let rest_status = filters::create_status_filter(effect_builder, api_version);
     let rest_metrics = filters::create_metrics_filter(effect_builder);
     let rest_open_rpc = filters::create_rpc_schema_filter(effect_builder);
+    let rest_deploy_propagation = filters::create_deploy_propagation_filter(effect_builder);
+    let rest_health = filters::create_health_filter(Arc::clone(&node_health));
+    let rest_ready = filters::create_ready_filter(node_health);
 
-    let service = warp::service(rest_status.or(rest_metrics).or(rest_open_rpc));
+    let service = warp::service(
+        rest_status
+            .or(rest_metrics)
+            .or(rest_open_rpc)
+            .or(rest_deploy_propagation)
+            .or(rest_health)
+            .or(rest_ready),
+    );
 
     // Start the server, passing a oneshot receiver to allow the server to be shut down gracefully.
     let make_svc =
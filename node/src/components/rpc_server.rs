@@ -18,36 +18,48 @@ mod config;
 mod event;
 mod http_server;
 pub mod rpcs;
+#[cfg(test)]
+mod tests;
 
-use std::{convert::Infallible, fmt::Debug};
+use std::{
+    convert::Infallible,
+    fmt::Debug,
+    sync::{Arc, RwLock},
+};
 
 use datasize::DataSize;
 use futures::join;
+use thiserror::Error;
+use tracing::error;
 
 use casper_execution_engine::{
     core::engine_state::{
-        self, BalanceRequest, BalanceResult, GetBidsRequest, GetEraValidatorsError, QueryRequest,
-        QueryResult,
+        self, BalanceRequest, BalanceResult, BalancesRequest, BalancesResult,
+        GetAllBalancesRequest, GetAllBalancesResult, GetBidsRequest, GetEraValidatorsError,
+        PurseIdentifier, QueryRequest, QueryResult,
     },
     storage::protocol_data::ProtocolData,
 };
-use casper_types::{system::auction::EraValidators, Key, ProtocolVersion, URef};
+use casper_types::{
+    account::AccountHash, system::auction::EraValidators, Key, ProtocolVersion, URef,
+};
 
 use self::rpcs::chain::BlockIdentifier;
 
 use super::Component;
 use crate::{
     components::contract_runtime::EraValidatorsRequest,
+    config_overrides::ConfigOverride,
     crypto::hash::Digest,
     effect::{
         announcements::RpcServerAnnouncement,
         requests::{
-            ChainspecLoaderRequest, ConsensusRequest, ContractRuntimeRequest, LinearChainRequest,
-            MetricsRequest, NetworkInfoRequest, RpcRequest, StorageRequest,
+            BlockProposerRequest, ChainspecLoaderRequest, ConsensusRequest, ContractRuntimeRequest,
+            LinearChainRequest, MetricsRequest, NetworkInfoRequest, RpcRequest, StorageRequest,
         },
         EffectBuilder, EffectExt, Effects, Responder,
     },
-    types::{NodeId, StatusFeed},
+    types::{DeployHash, DeployStatus, GossipState, NodeId, StatusFeed, StorageStatus, Timestamp},
     utils::{self, ListeningError},
     NodeRng,
 };
@@ -60,6 +72,7 @@ pub trait ReactorEventT:
     From<Event>
     + From<RpcRequest<NodeId>>
     + From<RpcServerAnnouncement>
+    + From<BlockProposerRequest>
     + From<ChainspecLoaderRequest>
     + From<ContractRuntimeRequest>
     + From<ConsensusRequest>
@@ -75,6 +88,7 @@ impl<REv> ReactorEventT for REv where
     REv: From<Event>
         + From<RpcRequest<NodeId>>
         + From<RpcServerAnnouncement>
+        + From<BlockProposerRequest>
         + From<ChainspecLoaderRequest>
         + From<ContractRuntimeRequest>
         + From<ConsensusRequest>
@@ -87,27 +101,122 @@ impl<REv> ReactorEventT for REv where
 {
 }
 
+/// Error returned in response to a request for the balances of several purses.
+#[derive(Debug, Error)]
+pub enum GetBalancesError {
+    /// More purses were requested than the configured limit allows.
+    #[error("requested {requested} balances, but at most {max} may be requested at once")]
+    TooManyPurses {
+        /// The number of purses requested.
+        requested: usize,
+        /// The configured maximum.
+        max: usize,
+    },
+    /// The contract runtime failed to read the balances.
+    #[error(transparent)]
+    EngineState(#[from] engine_state::Error),
+}
+
+/// Error returned in response to a request for a page of the balances of all accounts.
+#[derive(Debug, Error)]
+pub enum GetAllBalancesError {
+    /// Balance exports are disabled in the config.
+    #[error("balance exports are disabled on this node")]
+    Disabled,
+    /// The contract runtime failed to read the balances.
+    #[error(transparent)]
+    EngineState(#[from] engine_state::Error),
+}
+
+/// Error returned in response to a request for the gossipsub state.
+#[derive(Debug, Error)]
+pub enum GetGossipStateError {
+    /// Gossip state requests are disabled in the config.
+    #[error("gossip state requests are disabled on this node")]
+    Disabled,
+    /// The networking component doesn't use gossipsub.
+    #[error("gossip state is only available with the libp2p network provider")]
+    Unavailable,
+}
+
+/// Returns the lifecycle stage the given deploy has reached.
+///
+/// The block proposer is queried before storage, since the stages recorded in storage are the
+/// later ones: a deploy advancing in between is then reported at its later stage.
+async fn get_deploy_status<REv>(
+    effect_builder: EffectBuilder<REv>,
+    deploy_hash: DeployHash,
+) -> DeployStatus
+where
+    REv: From<BlockProposerRequest> + From<StorageRequest>,
+{
+    let buffer_status = effect_builder.get_deploy_buffer_status(deploy_hash).await;
+    let (maybe_header_and_metadata, maybe_block_header) = join!(
+        effect_builder.get_deploy_header_and_metadata_from_storage(deploy_hash),
+        effect_builder.get_block_header_for_deploy_from_storage(deploy_hash)
+    );
+    DeployStatus::new(
+        buffer_status,
+        maybe_header_and_metadata,
+        maybe_block_header,
+        Timestamp::now(),
+    )
+}
+
 #[derive(DataSize, Debug)]
-pub(crate) struct RpcServer {}
+pub(crate) struct RpcServer {
+    /// The maximum number of purses allowed in a single batched balance request.
+    max_balances_per_request: usize,
+    /// Whether requests for the balances of all accounts are served.
+    enable_balances_export: bool,
+    /// The maximum number of accounts scanned for a single page of all accounts' balances.
+    max_accounts_per_balances_page: usize,
+    /// Whether requests for the gossipsub state are served.
+    enable_gossip_state: bool,
+    /// The reason for the node shutting down, shared with the server, which refuses all requests
+    /// once it's set.
+    #[data_size(skip)]
+    shutdown_reason: Arc<RwLock<Option<String>>>,
+    /// The settings of the node's config which differ from their defaults, served in the status.
+    config_overrides: Vec<ConfigOverride>,
+}
 
 impl RpcServer {
     pub(crate) fn new<REv>(
         config: Config,
         effect_builder: EffectBuilder<REv>,
         api_version: ProtocolVersion,
+        config_overrides: Vec<ConfigOverride>,
     ) -> Result<Self, ListeningError>
     where
         REv: ReactorEventT,
     {
+        let shutdown_reason = Arc::new(RwLock::new(None));
         let builder = utils::start_listening(&config.address)?;
         tokio::spawn(http_server::run(
             builder,
             effect_builder,
             api_version,
             config.qps_limit,
+            Arc::clone(&shutdown_reason),
         ));
 
-        Ok(RpcServer {})
+        Ok(RpcServer {
+            max_balances_per_request: config.max_balances_per_request,
+            enable_balances_export: config.enable_balances_export,
+            max_accounts_per_balances_page: config.max_accounts_per_balances_page,
+            enable_gossip_state: config.enable_gossip_state,
+            shutdown_reason,
+            config_overrides,
+        })
+    }
+
+    /// Stops serving requests, responding to any received from now on with a 503 giving `reason`.
+    pub(crate) fn shutdown(&self, reason: String) {
+        match self.shutdown_reason.write() {
+            Ok(mut shutdown_reason) => *shutdown_reason = Some(reason),
+            Err(error) => error!(%error, "failed to set shutdown reason"),
+        }
     }
 }
 
@@ -174,6 +283,74 @@ impl RpcServer {
                 main_responder: responder,
             })
     }
+
+    fn handle_get_balances<REv: ReactorEventT>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        state_root_hash: Digest,
+        purse_identifiers: Vec<PurseIdentifier>,
+        responder: Responder<Result<BalancesResult, GetBalancesError>>,
+    ) -> Effects<Event> {
+        if purse_identifiers.len() > self.max_balances_per_request {
+            let error = GetBalancesError::TooManyPurses {
+                requested: purse_identifiers.len(),
+                max: self.max_balances_per_request,
+            };
+            return responder.respond(Err(error)).ignore();
+        }
+        let request = BalancesRequest::new(state_root_hash.into(), purse_identifiers);
+        effect_builder
+            .get_balances(request)
+            .event(move |result| Event::GetBalancesResult {
+                result,
+                main_responder: responder,
+            })
+    }
+
+    fn handle_get_all_balances<REv: ReactorEventT>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        state_root_hash: Digest,
+        start_after: Option<AccountHash>,
+        responder: Responder<Result<GetAllBalancesResult, GetAllBalancesError>>,
+    ) -> Effects<Event> {
+        if !self.enable_balances_export {
+            return responder
+                .respond(Err(GetAllBalancesError::Disabled))
+                .ignore();
+        }
+        let request = GetAllBalancesRequest::new(
+            state_root_hash.into(),
+            start_after,
+            self.max_accounts_per_balances_page,
+        );
+        effect_builder
+            .get_all_balances(request)
+            .event(move |result| Event::GetAllBalancesResult {
+                result,
+                main_responder: responder,
+            })
+    }
+
+    fn handle_get_gossip_state<REv: ReactorEventT>(
+        &self,
+        effect_builder: EffectBuilder<REv>,
+        responder: Responder<Result<GossipState, GetGossipStateError>>,
+    ) -> Effects<Event> {
+        if !self.enable_gossip_state {
+            return responder
+                .respond(Err(GetGossipStateError::Disabled))
+                .ignore();
+        }
+        async move {
+            let result = effect_builder
+                .network_gossip_state::<NodeId>()
+                .await
+                .ok_or(GetGossipStateError::Unavailable);
+            responder.respond(result).await;
+        }
+        .ignore()
+    }
 }
 
 impl<REv> Component<REv> for RpcServer
@@ -270,6 +447,26 @@ where
                 purse_uref,
                 responder,
             }) => self.handle_get_balance(effect_builder, state_root_hash, purse_uref, responder),
+            Event::RpcRequest(RpcRequest::GetBalances {
+                state_root_hash,
+                purse_identifiers,
+                responder,
+            }) => self.handle_get_balances(
+                effect_builder,
+                state_root_hash,
+                purse_identifiers,
+                responder,
+            ),
+            Event::RpcRequest(RpcRequest::GetAllBalances {
+                state_root_hash,
+                start_after,
+                responder,
+            }) => self.handle_get_all_balances(
+                effect_builder,
+                state_root_hash,
+                start_after,
+                responder,
+            ),
             Event::RpcRequest(RpcRequest::GetDeploy { hash, responder }) => effect_builder
                 .get_deploy_and_metadata_from_storage(hash)
                 .event(move |result| Event::GetDeployResult {
@@ -277,24 +474,78 @@ where
                     result: Box::new(result),
                     main_responder: responder,
                 }),
+            Event::RpcRequest(RpcRequest::GetDeployStatus { hash, responder }) => async move {
+                let status = get_deploy_status(effect_builder, hash).await;
+                responder.respond(status).await;
+            }
+            .ignore(),
+            Event::RpcRequest(RpcRequest::GetValidatorPerformance {
+                era_ids,
+                maybe_public_key,
+                responder,
+            }) => effect_builder
+                .get_era_performance_from_storage(era_ids, maybe_public_key)
+                .event(move |result| Event::GetValidatorPerformanceResult {
+                    result,
+                    main_responder: responder,
+                }),
+            Event::RpcRequest(RpcRequest::GetValidatorChanges { era_ids, responder }) => {
+                effect_builder
+                    .get_validator_changes_from_storage(era_ids)
+                    .event(move |result| Event::GetValidatorChangesResult {
+                        result,
+                        main_responder: responder,
+                    })
+            }
             Event::RpcRequest(RpcRequest::GetPeers { responder }) => effect_builder
                 .network_peers()
                 .event(move |peers| Event::GetPeersResult {
                     peers,
                     main_responder: responder,
                 }),
-            Event::RpcRequest(RpcRequest::GetStatus { responder }) => async move {
-                let (last_added_block, peers, chainspec_info, consensus_status) = join!(
-                    effect_builder.get_highest_block_from_storage(),
-                    effect_builder.network_peers(),
-                    effect_builder.get_chainspec_info(),
-                    effect_builder.consensus_status()
-                );
-                let status_feed =
-                    StatusFeed::new(last_added_block, peers, chainspec_info, consensus_status);
-                responder.respond(status_feed).await;
+            Event::RpcRequest(RpcRequest::GetGossipState { responder }) => {
+                self.handle_get_gossip_state(effect_builder, responder)
+            }
+            Event::RpcRequest(RpcRequest::GetStatus { responder }) => {
+                let config_overrides = self.config_overrides.clone();
+                async move {
+                    let (
+                        last_added_block,
+                        peers,
+                        dial_backoffs,
+                        chainspec_info,
+                        consensus_status,
+                        era_progress,
+                        node_storage,
+                        global_state,
+                    ) = join!(
+                        effect_builder.get_highest_block_from_storage(),
+                        effect_builder.network_peers(),
+                        effect_builder.network_dial_backoffs(),
+                        effect_builder.get_chainspec_info(),
+                        effect_builder.consensus_status(),
+                        effect_builder.consensus_era_progress(),
+                        effect_builder.get_storage_lmdb_stats(),
+                        effect_builder.get_global_state_lmdb_stats()
+                    );
+                    let storage = StorageStatus {
+                        node_storage,
+                        global_state,
+                    };
+                    let status_feed = StatusFeed::new(
+                        last_added_block,
+                        peers,
+                        dial_backoffs,
+                        chainspec_info,
+                        consensus_status,
+                        era_progress,
+                        storage,
+                        config_overrides,
+                    );
+                    responder.respond(status_feed).await;
+                }
+                .ignore()
             }
-            .ignore(),
             Event::RpcRequest(RpcRequest::GetMetrics { responder }) => effect_builder
                 .get_metrics()
                 .event(move |text| Event::GetMetricsResult {
@@ -331,11 +582,31 @@ where
                 result,
                 main_responder,
             } => main_responder.respond(result).ignore(),
+            Event::GetBalancesResult {
+                result,
+                main_responder,
+            } => main_responder
+                .respond(result.map_err(GetBalancesError::from))
+                .ignore(),
+            Event::GetAllBalancesResult {
+                result,
+                main_responder,
+            } => main_responder
+                .respond(result.map_err(GetAllBalancesError::from))
+                .ignore(),
             Event::GetDeployResult {
                 hash: _,
                 result,
                 main_responder,
             } => main_responder.respond(*result).ignore(),
+            Event::GetValidatorPerformanceResult {
+                result,
+                main_responder,
+            } => main_responder.respond(result).ignore(),
+            Event::GetValidatorChangesResult {
+                result,
+                main_responder,
+            } => main_responder.respond(result).ignore(),
             Event::GetPeersResult {
                 peers,
                 main_responder,
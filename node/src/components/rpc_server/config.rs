@@ -1,12 +1,18 @@
 use datasize::DataSize;
 use serde::{Deserialize, Serialize};
 
+use crate::config_validation::{self, ConfigValidationError};
+
 /// Default binding address for the JSON-RPC HTTP server.
 ///
 /// Uses a fixed port per node, but binds on any interface.
 const DEFAULT_ADDRESS: &str = "0.0.0.0:0";
 /// Default rate limit in qps.
 const DEFAULT_QPS_LIMIT: u64 = 100;
+/// Default maximum number of purses in a single batched balance request.
+const DEFAULT_MAX_BALANCES_PER_REQUEST: usize = 200;
+/// Default maximum number of accounts scanned for a single page of a balance export.
+const DEFAULT_MAX_ACCOUNTS_PER_BALANCES_PAGE: usize = 1_000;
 
 /// JSON-RPC HTTP server configuration.
 #[derive(Clone, DataSize, Debug, Deserialize, Serialize)]
@@ -18,6 +24,21 @@ pub struct Config {
 
     /// Max rate limit in qps.
     pub qps_limit: u64,
+
+    /// Maximum number of purses whose balances can be requested in a single "state_get_balances"
+    /// request.
+    pub max_balances_per_request: usize,
+
+    /// Whether to serve "state_get_all_balances" requests, which scan every account in global
+    /// state.
+    pub enable_balances_export: bool,
+
+    /// Maximum number of accounts scanned for a single page of a "state_get_all_balances" request.
+    pub max_accounts_per_balances_page: usize,
+
+    /// Whether to serve "network_get_gossip_state" requests, which expose the node's gossipsub
+    /// mesh for network troubleshooting.
+    pub enable_gossip_state: bool,
 }
 
 impl Config {
@@ -26,6 +47,23 @@ impl Config {
         Config {
             address: DEFAULT_ADDRESS.to_string(),
             qps_limit: DEFAULT_QPS_LIMIT,
+            max_balances_per_request: DEFAULT_MAX_BALANCES_PER_REQUEST,
+            enable_balances_export: false,
+            max_accounts_per_balances_page: DEFAULT_MAX_ACCOUNTS_PER_BALANCES_PAGE,
+            enable_gossip_state: false,
+        }
+    }
+
+    /// Checks whether the values set in the config make sense, pushing every problem found to
+    /// `errors`.
+    pub(crate) fn validate(&self, errors: &mut Vec<ConfigValidationError>) {
+        config_validation::check_address("rpc_server.address", &self.address, true, errors);
+        if self.qps_limit == 0 {
+            errors.push(ConfigValidationError::out_of_range(
+                "rpc_server.qps_limit",
+                self.qps_limit,
+                "greater than 0",
+            ));
         }
     }
 }
@@ -6,15 +6,22 @@ use std::{
 use derive_more::From;
 
 use casper_execution_engine::{
-    core::engine_state::{self, BalanceResult, GetBidsResult, GetEraValidatorsError, QueryResult},
+    core::engine_state::{
+        self, BalanceResult, BalancesResult, GetAllBalancesResult, GetBidsResult,
+        GetEraValidatorsError, QueryResult,
+    },
     storage::protocol_data::ProtocolData,
 };
-use casper_types::{system::auction::EraValidators, Transfer};
+use casper_types::{system::auction::EraValidators, EraId, PublicKey, Transfer};
 
+use super::{GetAllBalancesError, GetBalancesError};
 use crate::{
     effect::{requests::RpcRequest, Responder},
     rpcs::chain::BlockIdentifier,
-    types::{Block, BlockHash, BlockSignatures, Deploy, DeployHash, DeployMetadata, NodeId},
+    types::{
+        Block, BlockHash, BlockSignatures, Deploy, DeployHash, DeployMetadata, NodeId,
+        ValidatorChange, ValidatorPerformance,
+    },
 };
 
 #[derive(Debug, From)]
@@ -52,6 +59,14 @@ pub enum Event {
         result: Box<Option<(Deploy, DeployMetadata)>>,
         main_responder: Responder<Option<(Deploy, DeployMetadata)>>,
     },
+    GetValidatorPerformanceResult {
+        result: BTreeMap<EraId, BTreeMap<PublicKey, ValidatorPerformance>>,
+        main_responder: Responder<BTreeMap<EraId, BTreeMap<PublicKey, ValidatorPerformance>>>,
+    },
+    GetValidatorChangesResult {
+        result: BTreeMap<EraId, BTreeMap<PublicKey, Vec<ValidatorChange>>>,
+        main_responder: Responder<BTreeMap<EraId, BTreeMap<PublicKey, Vec<ValidatorChange>>>>,
+    },
     GetPeersResult {
         peers: BTreeMap<NodeId, String>,
         main_responder: Responder<BTreeMap<NodeId, String>>,
@@ -64,6 +79,14 @@ pub enum Event {
         result: Result<BalanceResult, engine_state::Error>,
         main_responder: Responder<Result<BalanceResult, engine_state::Error>>,
     },
+    GetBalancesResult {
+        result: Result<BalancesResult, engine_state::Error>,
+        main_responder: Responder<Result<BalancesResult, GetBalancesError>>,
+    },
+    GetAllBalancesResult {
+        result: Result<GetAllBalancesResult, engine_state::Error>,
+        main_responder: Responder<Result<GetAllBalancesResult, GetAllBalancesError>>,
+    },
 }
 
 impl Display for Event {
@@ -107,9 +130,25 @@ impl Display for Event {
             Event::GetBalanceResult { result, .. } => {
                 write!(formatter, "balance result: {:?}", result)
             }
+            Event::GetBalancesResult { result, .. } => {
+                write!(formatter, "balances result: {:?}", result)
+            }
+            Event::GetAllBalancesResult { result, .. } => {
+                write!(formatter, "all balances result: {:?}", result)
+            }
             Event::GetDeployResult { hash, result, .. } => {
                 write!(formatter, "get deploy result for {}: {:?}", hash, result)
             }
+            Event::GetValidatorPerformanceResult { result, .. } => {
+                write!(
+                    formatter,
+                    "get validator performance: {} eras",
+                    result.len()
+                )
+            }
+            Event::GetValidatorChangesResult { result, .. } => {
+                write!(formatter, "get validator changes: {} eras", result.len())
+            }
             Event::GetPeersResult { peers, .. } => write!(formatter, "get peers: {}", peers.len()),
             Event::GetMetricsResult { text, .. } => match text {
                 Some(txt) => write!(formatter, "get metrics ({} bytes)", txt.len()),
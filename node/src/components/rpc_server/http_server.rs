@@ -1,4 +1,8 @@
-use std::{convert::Infallible, time::Duration};
+use std::{
+    convert::Infallible,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
 
 use futures::future;
 use http::{Response, StatusCode};
@@ -9,20 +13,23 @@ use hyper::{
 use serde::Serialize;
 use tokio::sync::oneshot;
 use tower::builder::ServiceBuilder;
-use tracing::{info, trace};
+use tracing::{error, info, trace};
 use warp::{Filter, Rejection};
 
 use casper_types::ProtocolVersion;
 
 use super::{
-    rpcs::{self, RpcWithOptionalParamsExt, RpcWithParamsExt, RpcWithoutParamsExt, RPC_API_PATH},
+    rpcs::{
+        self, ErrorCode, RpcWithOptionalParamsExt, RpcWithParamsExt, RpcWithoutParamsExt,
+        RPC_API_PATH,
+    },
     ReactorEventT,
 };
 use crate::effect::EffectBuilder;
 
 // This is a workaround for not being able to create a `warp_json_rpc::Response` without a
 // `warp_json_rpc::Builder`.
-fn new_error_response(error: warp_json_rpc::Error) -> Response<Body> {
+fn new_error_response(error: warp_json_rpc::Error, status: StatusCode) -> Response<Body> {
     #[derive(Serialize)]
     struct JsonRpcErrorResponse {
         jsonrpc: String,
@@ -38,19 +45,45 @@ fn new_error_response(error: warp_json_rpc::Error) -> Response<Body> {
 
     let body = Body::from(serde_json::to_vec(&json_response).unwrap());
     Response::builder()
-        .status(StatusCode::OK)
+        .status(status)
         .header("Content-Type", "application/json")
         .body(body)
         .unwrap()
 }
 
 /// Run the JSON-RPC server.
+///
+/// Once `shutdown_reason` is set, every request is refused with a 503 response giving the reason.
 pub(super) async fn run<REv: ReactorEventT>(
     builder: Builder<AddrIncoming>,
     effect_builder: EffectBuilder<REv>,
     api_version: ProtocolVersion,
     qps_limit: u64,
+    shutdown_reason: Arc<RwLock<Option<String>>>,
 ) {
+    // Catch all requests once the node has started shutting down.
+    let shutting_down = warp::any().and_then(move || {
+        let maybe_reason = match shutdown_reason.read() {
+            Ok(shutdown_reason) => shutdown_reason.clone(),
+            Err(error) => {
+                error!(%error, "failed to read shutdown reason");
+                None
+            }
+        };
+        async move {
+            match maybe_reason {
+                Some(reason) => {
+                    let error = warp_json_rpc::Error::custom(
+                        ErrorCode::NodeShuttingDown as i64,
+                        format!("node is shutting down: {}", reason),
+                    );
+                    Ok(new_error_response(error, StatusCode::SERVICE_UNAVAILABLE))
+                }
+                None => Err(warp::reject()),
+            }
+        }
+    });
+
     // RPC filters.
     let rpc_put_deploy = rpcs::account::PutDeploy::create_filter(effect_builder, api_version);
     let rpc_get_block = rpcs::chain::GetBlock::create_filter(effect_builder, api_version);
@@ -60,11 +93,24 @@ pub(super) async fn run<REv: ReactorEventT>(
         rpcs::chain::GetStateRootHash::create_filter(effect_builder, api_version);
     let rpc_get_item = rpcs::state::GetItem::create_filter(effect_builder, api_version);
     let rpc_get_balance = rpcs::state::GetBalance::create_filter(effect_builder, api_version);
+    let rpc_get_balances = rpcs::state::GetBalances::create_filter(effect_builder, api_version);
+    let rpc_get_all_balances =
+        rpcs::state::GetAllBalances::create_filter(effect_builder, api_version);
     let rpc_get_account_info =
         rpcs::state::GetAccountInfo::create_filter(effect_builder, api_version);
+    let rpc_get_entry_point =
+        rpcs::state::GetEntryPoint::create_filter(effect_builder, api_version);
     let rpc_get_deploy = rpcs::info::GetDeploy::create_filter(effect_builder, api_version);
+    let rpc_get_deploy_status =
+        rpcs::info::GetDeployStatus::create_filter(effect_builder, api_version);
+    let rpc_get_validator_performance =
+        rpcs::info::GetValidatorPerformance::create_filter(effect_builder, api_version);
+    let rpc_get_validator_changes =
+        rpcs::info::GetValidatorChanges::create_filter(effect_builder, api_version);
     let rpc_get_peers = rpcs::info::GetPeers::create_filter(effect_builder, api_version);
     let rpc_get_status = rpcs::info::GetStatus::create_filter(effect_builder, api_version);
+    let rpc_get_gossip_state =
+        rpcs::network::GetGossipState::create_filter(effect_builder, api_version);
     let rpc_get_era_info =
         rpcs::chain::GetEraInfoBySwitchBlock::create_filter(effect_builder, api_version);
     let rpc_get_auction_info =
@@ -82,7 +128,7 @@ pub(super) async fn run<REv: ReactorEventT>(
 
     // Catch requests which don't parse as JSON.
     let parse_failure = warp::path(RPC_API_PATH).and_then(move || async move {
-        let error_response = new_error_response(warp_json_rpc::Error::PARSE_ERROR);
+        let error_response = new_error_response(warp_json_rpc::Error::PARSE_ERROR, StatusCode::OK);
         Ok::<_, Rejection>(error_response)
     });
 
@@ -90,18 +136,26 @@ pub(super) async fn run<REv: ReactorEventT>(
     //        (i.e. where the request is JSON, but not valid JSON-RPC).  This will require an
     //        update to or move away from warp_json_rpc.
     let service = warp_json_rpc::service(
-        rpc_put_deploy
+        shutting_down
+            .or(rpc_put_deploy)
             .or(rpc_get_block)
             .or(rpc_get_block_transfers)
             .or(rpc_get_state_root_hash)
             .or(rpc_get_item)
             .or(rpc_get_balance)
+            .or(rpc_get_balances)
+            .or(rpc_get_all_balances)
             .or(rpc_get_deploy)
+            .or(rpc_get_deploy_status)
+            .or(rpc_get_validator_performance)
+            .or(rpc_get_validator_changes)
             .or(rpc_get_peers)
             .or(rpc_get_status)
+            .or(rpc_get_gossip_state)
             .or(rpc_get_era_info)
             .or(rpc_get_auction_info)
             .or(rpc_get_account_info)
+            .or(rpc_get_entry_point)
             .or(rpc_get_rpcs)
             .or(unknown_method)
             .or(parse_failure),
@@ -6,6 +6,7 @@ pub mod account;
 pub mod chain;
 pub mod docs;
 pub mod info;
+pub mod network;
 pub mod state;
 
 use std::str;
@@ -36,7 +37,7 @@ pub const RPC_API_PATH: &str = "rpc";
 ///
 /// See <https://www.jsonrpc.org/specification#error_object> for details.
 #[repr(i64)]
-enum ErrorCode {
+pub(super) enum ErrorCode {
     NoSuchDeploy = -32000,
     NoSuchBlock = -32001,
     ParseQueryKey = -32002,
@@ -47,6 +48,15 @@ enum ErrorCode {
     GetBalanceFailedToExecute = -32007,
     InvalidDeploy = -32008,
     NoSuchAccount = -32009,
+    TooManyBalancesRequested = -32010,
+    InvalidEraRange = -32011,
+    BalancesExportDisabled = -32012,
+    InvalidPageToken = -32013,
+    NoSuchContract = -32014,
+    NoSuchEntryPoint = -32015,
+    NodeShuttingDown = -32016,
+    GossipStateDisabled = -32017,
+    GossipStateUnavailable = -32018,
 }
 
 #[derive(Debug)]
@@ -295,6 +305,15 @@ mod common {
     pub(super) fn extract_query_result(
         query_result: Result<QueryResult, engine_state::Error>,
     ) -> Result<(StoredValue, Vec<u8>), (ErrorCode, String)> {
+        extract_query_result_with_raw_value(query_result)
+            .map(|(value_compat, proof_bytes, _)| (value_compat, proof_bytes))
+    }
+
+    // As `extract_query_result`, but also returning the bytesrepr-serialized EE `StoredValue`.
+    #[allow(clippy::type_complexity)]
+    pub(super) fn extract_query_result_with_raw_value(
+        query_result: Result<QueryResult, engine_state::Error>,
+    ) -> Result<(StoredValue, Vec<u8>, Vec<u8>), (ErrorCode, String)> {
         let (value, proof) = match query_result {
             Ok(QueryResult::Success { value, proofs }) => (value, proofs),
             Ok(query_result) => {
@@ -323,6 +342,14 @@ mod common {
             }
         };
 
-        Ok((value_compat, proof_bytes))
+        let value_bytes = match value.to_bytes() {
+            Ok(value_bytes) => value_bytes,
+            Err(error) => {
+                let error_msg = format!("failed to encode stored value: {:?}", error);
+                return Err((ErrorCode::QueryFailed, error_msg));
+            }
+        };
+
+        Ok((value_compat, proof_bytes, value_bytes))
     }
 }
@@ -21,8 +21,12 @@ use casper_types::ProtocolVersion;
 use super::{
     account::PutDeploy,
     chain::{GetBlock, GetBlockTransfers, GetStateRootHash},
-    info::{GetDeploy, GetPeers, GetStatus},
-    state::{GetAuctionInfo, GetBalance, GetItem},
+    info::{
+        GetDeploy, GetDeployStatus, GetPeers, GetStatus, GetValidatorChanges,
+        GetValidatorPerformance,
+    },
+    network::GetGossipState,
+    state::{GetAllBalances, GetAuctionInfo, GetBalance, GetBalances, GetEntryPoint, GetItem},
     Error, ReactorEventT, RpcWithOptionalParams, RpcWithParams, RpcWithoutParams,
     RpcWithoutParamsExt,
 };
@@ -72,9 +76,21 @@ pub(crate) static OPEN_RPC_SCHEMA: Lazy<OpenRpcSchema> = Lazy::new(|| {
 
     schema.push_with_params::<PutDeploy>("receives a Deploy to be executed by the network");
     schema.push_with_params::<GetDeploy>("returns a Deploy from the network");
+    schema.push_with_params::<GetDeployStatus>(
+        "returns the lifecycle stage a Deploy has reached on this node",
+    );
     schema.push_with_params::<GetAccountInfo>("returns an Account from the network");
     schema.push_without_params::<GetPeers>("returns a list of peers connected to the node");
     schema.push_without_params::<GetStatus>("returns the current status of the node");
+    schema.push_without_params::<GetGossipState>(
+        "returns the node's gossipsub mesh and the topics its peers are subscribed to, if enabled",
+    );
+    schema.push_with_params::<GetValidatorPerformance>(
+        "returns the performance of validators in recent eras",
+    );
+    schema.push_with_params::<GetValidatorChanges>(
+        "returns the changes to the validator set taking effect in the given eras",
+    );
     schema.push_with_optional_params::<GetBlock>("returns a Block from the network");
     schema.push_with_optional_params::<GetBlockTransfers>(
         "returns all transfers for a Block from the network",
@@ -84,6 +100,15 @@ pub(crate) static OPEN_RPC_SCHEMA: Lazy<OpenRpcSchema> = Lazy::new(|| {
     );
     schema.push_with_params::<GetItem>("returns a stored value from the network");
     schema.push_with_params::<GetBalance>("returns a purse's balance from the network");
+    schema.push_with_params::<GetBalances>(
+        "returns the balances of several purses from a single global state",
+    );
+    schema.push_with_params::<GetAllBalances>(
+        "returns a page of the balances of all accounts' main purses from a single global state",
+    );
+    schema.push_with_params::<GetEntryPoint>(
+        "returns the signature of a stored contract's entry point from the network",
+    );
     schema.push_with_optional_params::<GetEraInfoBySwitchBlock>(
         "returns an EraInfo from the network",
     );
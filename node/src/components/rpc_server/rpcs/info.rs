@@ -3,7 +3,7 @@
 // TODO - remove once schemars stops causing warning.
 #![allow(clippy::field_reassign_with_default)]
 
-use std::str;
+use std::{ops::RangeInclusive, str};
 
 use futures::{future::BoxFuture, FutureExt};
 use http::Response;
@@ -14,7 +14,8 @@ use serde::{Deserialize, Serialize};
 use tracing::info;
 use warp_json_rpc::Builder;
 
-use casper_types::{ExecutionResult, ProtocolVersion};
+use casper_execution_engine::shared::{gas::Gas, motes::Motes};
+use casper_types::{EraId, ExecutionResult, ProtocolVersion, PublicKey, U512};
 
 use super::{
     docs::{DocExample, DOCS_EXAMPLE_PROTOCOL_VERSION},
@@ -22,9 +23,13 @@ use super::{
     RpcWithoutParamsExt,
 };
 use crate::{
+    crypto::AsymmetricKeyExt,
     effect::EffectBuilder,
     reactor::QueueKind,
-    types::{Block, BlockHash, Deploy, DeployHash, GetStatusResult, Item, PeersMap},
+    types::{
+        Block, BlockHash, Deploy, DeployHash, DeployStatus, GetStatusResult, Item, PeersMap,
+        ValidatorChange, ValidatorPerformance,
+    },
 };
 
 static GET_DEPLOY_PARAMS: Lazy<GetDeployParams> = Lazy::new(|| GetDeployParams {
@@ -33,10 +38,99 @@ static GET_DEPLOY_PARAMS: Lazy<GetDeployParams> = Lazy::new(|| GetDeployParams {
 static GET_DEPLOY_RESULT: Lazy<GetDeployResult> = Lazy::new(|| GetDeployResult {
     api_version: DOCS_EXAMPLE_PROTOCOL_VERSION,
     deploy: Deploy::doc_example().clone(),
-    execution_results: vec![JsonExecutionResult {
-        block_hash: Block::doc_example().id(),
-        result: ExecutionResult::example().clone(),
-    }],
+    execution_results: vec![JsonExecutionResult::new(
+        Block::doc_example().id(),
+        ExecutionResult::example().clone(),
+        Deploy::doc_example().header().gas_price(),
+    )],
+});
+static GET_DEPLOY_STATUS_PARAMS: Lazy<GetDeployStatusParams> =
+    Lazy::new(|| GetDeployStatusParams {
+        deploy_hash: *Deploy::doc_example().id(),
+    });
+static GET_DEPLOY_STATUS_RESULT: Lazy<GetDeployStatusResult> = Lazy::new(|| {
+    let execution_result = JsonExecutionResult::new(
+        Block::doc_example().id(),
+        ExecutionResult::example().clone(),
+        Deploy::doc_example().header().gas_price(),
+    );
+    let (cost, error_message) = match execution_result.result {
+        ExecutionResult::Failure {
+            cost,
+            error_message,
+            ..
+        } => (cost, Some(error_message)),
+        ExecutionResult::Success { cost, .. } => (cost, None),
+    };
+    GetDeployStatusResult {
+        api_version: DOCS_EXAMPLE_PROTOCOL_VERSION,
+        deploy_hash: *Deploy::doc_example().id(),
+        status: DeployStatus::Executed {
+            block_hash: execution_result.block_hash,
+            block_height: Some(Block::doc_example().height()),
+            cost,
+            cost_in_motes: execution_result.cost_in_motes,
+            error_message,
+        },
+    }
+});
+static GET_VALIDATOR_PERFORMANCE_PARAMS: Lazy<GetValidatorPerformanceParams> =
+    Lazy::new(|| GetValidatorPerformanceParams {
+        era_identifier: EraIdentifier::Range {
+            from: EraId::from(41),
+            to: EraId::from(42),
+        },
+        public_key: Some(PublicKey::doc_example().clone()),
+    });
+static GET_VALIDATOR_PERFORMANCE_RESULT: Lazy<GetValidatorPerformanceResult> = Lazy::new(|| {
+    let performances = vec![41, 42]
+        .into_iter()
+        .map(|era_id| JsonValidatorPerformance {
+            era_id: EraId::from(era_id),
+            public_key: PublicKey::doc_example().clone(),
+            performance: ValidatorPerformance {
+                blocks_proposed: 3,
+                blocks_expected: 4,
+                finality_signatures_provided: 18,
+                finality_signatures_expected: 20,
+                equivocated: false,
+            },
+        })
+        .collect();
+    GetValidatorPerformanceResult {
+        api_version: DOCS_EXAMPLE_PROTOCOL_VERSION,
+        performances,
+    }
+});
+static GET_VALIDATOR_CHANGES_PARAMS: Lazy<GetValidatorChangesParams> =
+    Lazy::new(|| GetValidatorChangesParams {
+        era_identifier: EraIdentifier::Range {
+            from: EraId::from(41),
+            to: EraId::from(42),
+        },
+    });
+static GET_VALIDATOR_CHANGES_RESULT: Lazy<GetValidatorChangesResult> = Lazy::new(|| {
+    let changes = vec![
+        JsonValidatorChange {
+            era_id: EraId::from(41),
+            public_key: PublicKey::doc_example().clone(),
+            change: ValidatorChange::Added {
+                weight: U512::from(1_000_000_000_000u64),
+            },
+        },
+        JsonValidatorChange {
+            era_id: EraId::from(42),
+            public_key: PublicKey::doc_example().clone(),
+            change: ValidatorChange::WeightChanged {
+                previous_weight: U512::from(1_000_000_000_000u64),
+                weight: U512::from(1_000_000_123_456u64),
+            },
+        },
+    ];
+    GetValidatorChangesResult {
+        api_version: DOCS_EXAMPLE_PROTOCOL_VERSION,
+        changes,
+    }
 });
 static GET_PEERS_RESULT: Lazy<GetPeersResult> = Lazy::new(|| GetPeersResult {
     api_version: DOCS_EXAMPLE_PROTOCOL_VERSION,
@@ -65,6 +159,24 @@ pub struct JsonExecutionResult {
     pub block_hash: BlockHash,
     /// Execution result.
     pub result: ExecutionResult,
+    /// The cost of the execution in motes, i.e. the gas cost multiplied by the deploy's gas price,
+    /// or `None` if the multiplication overflows.
+    pub cost_in_motes: Option<U512>,
+}
+
+impl JsonExecutionResult {
+    fn new(block_hash: BlockHash, result: ExecutionResult, gas_price: u64) -> Self {
+        let gas_cost = match &result {
+            ExecutionResult::Failure { cost, .. } | ExecutionResult::Success { cost, .. } => *cost,
+        };
+        let cost_in_motes =
+            Motes::from_gas(Gas::new(gas_cost), gas_price).map(|motes| motes.value());
+        JsonExecutionResult {
+            block_hash,
+            result,
+            cost_in_motes,
+        }
+    }
 }
 
 /// Result for "info_get_deploy" RPC response.
@@ -129,10 +241,11 @@ impl RpcWithParamsExt for GetDeploy {
             };
 
             // Return the result.
+            let gas_price = deploy.header().gas_price();
             let execution_results = metadata
                 .execution_results
                 .into_iter()
-                .map(|(block_hash, result)| JsonExecutionResult { block_hash, result })
+                .map(|(block_hash, result)| JsonExecutionResult::new(block_hash, result, gas_price))
                 .collect();
 
             let result = Self::ResponseResult {
@@ -146,6 +259,312 @@ impl RpcWithParamsExt for GetDeploy {
     }
 }
 
+/// Params for "info_get_deploy_status" RPC request.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetDeployStatusParams {
+    /// The deploy hash.
+    pub deploy_hash: DeployHash,
+}
+
+impl DocExample for GetDeployStatusParams {
+    fn doc_example() -> &'static Self {
+        &*GET_DEPLOY_STATUS_PARAMS
+    }
+}
+
+/// Result for "info_get_deploy_status" RPC response.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+pub struct GetDeployStatusResult {
+    /// The RPC API version.
+    #[schemars(with = "String")]
+    pub api_version: ProtocolVersion,
+    /// The deploy hash.
+    pub deploy_hash: DeployHash,
+    /// The lifecycle stage the deploy has reached, along with the details relevant to that stage.
+    #[serde(flatten)]
+    pub status: DeployStatus,
+}
+
+impl DocExample for GetDeployStatusResult {
+    fn doc_example() -> &'static Self {
+        &*GET_DEPLOY_STATUS_RESULT
+    }
+}
+
+/// "info_get_deploy_status" RPC.
+pub struct GetDeployStatus {}
+
+impl RpcWithParams for GetDeployStatus {
+    const METHOD: &'static str = "info_get_deploy_status";
+    type RequestParams = GetDeployStatusParams;
+    type ResponseResult = GetDeployStatusResult;
+}
+
+impl RpcWithParamsExt for GetDeployStatus {
+    fn handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        response_builder: Builder,
+        params: Self::RequestParams,
+        api_version: ProtocolVersion,
+    ) -> BoxFuture<'static, Result<Response<Body>, Error>> {
+        async move {
+            // An unknown deploy is reported as such rather than as an error, since a deploy which
+            // is unknown now may become known later.
+            let status = effect_builder
+                .make_request(
+                    |responder| RpcRequest::GetDeployStatus {
+                        hash: params.deploy_hash,
+                        responder,
+                    },
+                    QueueKind::Api,
+                )
+                .await;
+
+            let result = Self::ResponseResult {
+                api_version,
+                deploy_hash: params.deploy_hash,
+                status,
+            };
+            Ok(response_builder.success(result)?)
+        }
+        .boxed()
+    }
+}
+
+/// Identifier of the eras whose validator performance or validator changes are requested.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub enum EraIdentifier {
+    /// A single era.
+    EraId(EraId),
+    /// An inclusive range of eras.
+    Range {
+        /// The first era of the range.
+        from: EraId,
+        /// The last era of the range.
+        to: EraId,
+    },
+}
+
+/// Params for "info_get_validator_performance" RPC request.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetValidatorPerformanceParams {
+    /// The era or range of eras.
+    pub era_identifier: EraIdentifier,
+    /// The public key of the validator, or `None` for all validators.
+    pub public_key: Option<PublicKey>,
+}
+
+impl DocExample for GetValidatorPerformanceParams {
+    fn doc_example() -> &'static Self {
+        &*GET_VALIDATOR_PERFORMANCE_PARAMS
+    }
+}
+
+/// The performance of a single validator during a single era.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct JsonValidatorPerformance {
+    /// The era ID.
+    pub era_id: EraId,
+    /// The public key of the validator.
+    pub public_key: PublicKey,
+    /// The performance of the validator during the era.
+    pub performance: ValidatorPerformance,
+}
+
+/// Result for "info_get_validator_performance" RPC response.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetValidatorPerformanceResult {
+    /// The RPC API version.
+    #[schemars(with = "String")]
+    pub api_version: ProtocolVersion,
+    /// The performance of each requested validator in each requested era, as far as it is
+    /// retained by the node.
+    pub performances: Vec<JsonValidatorPerformance>,
+}
+
+impl DocExample for GetValidatorPerformanceResult {
+    fn doc_example() -> &'static Self {
+        &*GET_VALIDATOR_PERFORMANCE_RESULT
+    }
+}
+
+/// "info_get_validator_performance" RPC.
+pub struct GetValidatorPerformance {}
+
+impl RpcWithParams for GetValidatorPerformance {
+    const METHOD: &'static str = "info_get_validator_performance";
+    type RequestParams = GetValidatorPerformanceParams;
+    type ResponseResult = GetValidatorPerformanceResult;
+}
+
+impl RpcWithParamsExt for GetValidatorPerformance {
+    fn handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        response_builder: Builder,
+        params: Self::RequestParams,
+        api_version: ProtocolVersion,
+    ) -> BoxFuture<'static, Result<Response<Body>, Error>> {
+        async move {
+            let era_ids = match era_range(params.era_identifier) {
+                Ok(era_ids) => era_ids,
+                Err(error) => return Ok(response_builder.error(error)?),
+            };
+
+            // Get the retained performance from storage.
+            let performance_by_era = effect_builder
+                .make_request(
+                    |responder| RpcRequest::GetValidatorPerformance {
+                        era_ids,
+                        maybe_public_key: params.public_key.map(Box::new),
+                        responder,
+                    },
+                    QueueKind::Api,
+                )
+                .await;
+
+            let performances = performance_by_era
+                .into_iter()
+                .flat_map(|(era_id, performance_by_validator)| {
+                    performance_by_validator
+                        .into_iter()
+                        .map(move |(public_key, performance)| JsonValidatorPerformance {
+                            era_id,
+                            public_key,
+                            performance,
+                        })
+                })
+                .collect();
+
+            let result = Self::ResponseResult {
+                api_version,
+                performances,
+            };
+            Ok(response_builder.success(result)?)
+        }
+        .boxed()
+    }
+}
+
+/// Params for "info_get_validator_changes" RPC request.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetValidatorChangesParams {
+    /// The era or range of eras in which the requested changes take effect.
+    pub era_identifier: EraIdentifier,
+}
+
+impl DocExample for GetValidatorChangesParams {
+    fn doc_example() -> &'static Self {
+        &*GET_VALIDATOR_CHANGES_PARAMS
+    }
+}
+
+/// A change to the status of a single validator, taking effect at the start of an era.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct JsonValidatorChange {
+    /// The era in which the change takes effect.
+    pub era_id: EraId,
+    /// The public key of the validator.
+    pub public_key: PublicKey,
+    /// The change to the validator's status.
+    pub change: ValidatorChange,
+}
+
+/// Result for "info_get_validator_changes" RPC response.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetValidatorChangesResult {
+    /// The RPC API version.
+    #[schemars(with = "String")]
+    pub api_version: ProtocolVersion,
+    /// The changes to the validator set taking effect in the requested eras, ordered by era and
+    /// public key.
+    pub changes: Vec<JsonValidatorChange>,
+}
+
+impl DocExample for GetValidatorChangesResult {
+    fn doc_example() -> &'static Self {
+        &*GET_VALIDATOR_CHANGES_RESULT
+    }
+}
+
+/// "info_get_validator_changes" RPC.
+pub struct GetValidatorChanges {}
+
+impl RpcWithParams for GetValidatorChanges {
+    const METHOD: &'static str = "info_get_validator_changes";
+    type RequestParams = GetValidatorChangesParams;
+    type ResponseResult = GetValidatorChangesResult;
+}
+
+impl RpcWithParamsExt for GetValidatorChanges {
+    fn handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        response_builder: Builder,
+        params: Self::RequestParams,
+        api_version: ProtocolVersion,
+    ) -> BoxFuture<'static, Result<Response<Body>, Error>> {
+        async move {
+            let era_ids = match era_range(params.era_identifier) {
+                Ok(era_ids) => era_ids,
+                Err(error) => return Ok(response_builder.error(error)?),
+            };
+
+            // Get the stored changes from storage.
+            let changes_by_era = effect_builder
+                .make_request(
+                    |responder| RpcRequest::GetValidatorChanges { era_ids, responder },
+                    QueueKind::Api,
+                )
+                .await;
+
+            let changes = changes_by_era
+                .into_iter()
+                .flat_map(|(era_id, changes_by_validator)| {
+                    changes_by_validator
+                        .into_iter()
+                        .flat_map(move |(public_key, changes)| {
+                            changes.into_iter().map(move |change| JsonValidatorChange {
+                                era_id,
+                                public_key: public_key.clone(),
+                                change,
+                            })
+                        })
+                })
+                .collect();
+
+            let result = Self::ResponseResult {
+                api_version,
+                changes,
+            };
+            Ok(response_builder.success(result)?)
+        }
+        .boxed()
+    }
+}
+
+/// Returns the range of eras identified by `era_identifier`, or an error if it is empty.
+fn era_range(era_identifier: EraIdentifier) -> Result<RangeInclusive<EraId>, warp_json_rpc::Error> {
+    match era_identifier {
+        EraIdentifier::EraId(era_id) => Ok(RangeInclusive::new(era_id, era_id)),
+        EraIdentifier::Range { from, to } if from <= to => Ok(RangeInclusive::new(from, to)),
+        EraIdentifier::Range { from, to } => {
+            let error_msg = format!("invalid era range: {} is after {}", from, to);
+            info!("{}", error_msg);
+            Err(warp_json_rpc::Error::custom(
+                ErrorCode::InvalidEraRange as i64,
+                error_msg,
+            ))
+        }
+    }
+}
+
 /// Result for "info_get_peers" RPC response.
 #[derive(Serialize, Deserialize, Debug, JsonSchema)]
 #[serde(deny_unknown_fields)]
@@ -225,3 +644,73 @@ impl RpcWithoutParamsExt for GetStatus {
         .boxed()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use casper_types::ExecutionEffect;
+
+    use super::*;
+    use crate::testing;
+
+    #[test]
+    fn should_pin_json_execution_result_shape() {
+        let execution_result = &GET_DEPLOY_RESULT.execution_results[0];
+        let expected = json!({
+            "block_hash": "string",
+            "result": {
+                "Success": {
+                    "effect": {
+                        "operations": [
+                            { "key": "string", "kind": "string" },
+                            { "key": "string", "kind": "string" }
+                        ],
+                        "transforms": [
+                            { "key": "string", "transform": "string" },
+                            { "key": "string", "transform": { "AddUInt64": "number" } }
+                        ]
+                    },
+                    "transfers": ["string", "string"],
+                    "cost": "string"
+                }
+            },
+            "cost_in_motes": "string"
+        });
+        assert_eq!(testing::json_shape(execution_result), expected);
+    }
+
+    #[test]
+    fn should_serialize_empty_collections_and_absent_options_in_json_execution_result() {
+        let execution_result = JsonExecutionResult {
+            block_hash: Block::doc_example().id(),
+            result: ExecutionResult::Failure {
+                effect: ExecutionEffect::default(),
+                transfers: vec![],
+                cost: U512::MAX,
+                error_message: String::from("example error"),
+            },
+            cost_in_motes: None,
+        };
+        let expected = json!({
+            "block_hash": "string",
+            "result": {
+                "Failure": {
+                    "effect": { "operations": [], "transforms": [] },
+                    "transfers": [],
+                    "cost": "string",
+                    "error_message": "string"
+                }
+            },
+            "cost_in_motes": null
+        });
+        assert_eq!(testing::json_shape(&execution_result), expected);
+
+        // An absent `Option` field is still accepted, and decodes the same as an explicit `null`.
+        let mut json_value = serde_json::to_value(&execution_result).unwrap();
+        json_value.as_object_mut().unwrap().remove("cost_in_motes");
+        let decoded: JsonExecutionResult = serde_json::from_value(json_value).unwrap();
+        assert!(decoded.cost_in_motes.is_none());
+        assert_eq!(decoded.result, execution_result.result);
+    }
+}
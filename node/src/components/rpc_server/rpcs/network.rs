@@ -0,0 +1,95 @@
+//! RPCs for troubleshooting the node's networking.
+
+// TODO - remove once schemars stops causing warning.
+#![allow(clippy::field_reassign_with_default)]
+
+use futures::{future::BoxFuture, FutureExt};
+use http::Response;
+use hyper::Body;
+use once_cell::sync::Lazy;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+use warp_json_rpc::Builder;
+
+use casper_types::ProtocolVersion;
+
+use super::{
+    docs::{DocExample, DOCS_EXAMPLE_PROTOCOL_VERSION},
+    Error, ErrorCode, ReactorEventT, RpcRequest, RpcWithoutParams, RpcWithoutParamsExt,
+};
+use crate::{
+    components::rpc_server::GetGossipStateError,
+    effect::EffectBuilder,
+    reactor::QueueKind,
+    types::{GossipState, GossipTopicState},
+};
+
+static GET_GOSSIP_STATE_RESULT: Lazy<GetGossipStateResult> = Lazy::new(|| GetGossipStateResult {
+    api_version: DOCS_EXAMPLE_PROTOCOL_VERSION,
+    topics: GossipState::doc_example().topics.clone(),
+});
+
+/// Result for "network_get_gossip_state" RPC response.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetGossipStateResult {
+    /// The RPC API version.
+    #[schemars(with = "String")]
+    pub api_version: ProtocolVersion,
+    /// The gossipsub state of each topic this node or any of its peers are subscribed to, ordered
+    /// by name.
+    pub topics: Vec<GossipTopicState>,
+}
+
+impl DocExample for GetGossipStateResult {
+    fn doc_example() -> &'static Self {
+        &*GET_GOSSIP_STATE_RESULT
+    }
+}
+
+/// "network_get_gossip_state" RPC.
+pub struct GetGossipState {}
+
+impl RpcWithoutParams for GetGossipState {
+    const METHOD: &'static str = "network_get_gossip_state";
+    type ResponseResult = GetGossipStateResult;
+}
+
+impl RpcWithoutParamsExt for GetGossipState {
+    fn handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        response_builder: Builder,
+        api_version: ProtocolVersion,
+    ) -> BoxFuture<'static, Result<Response<Body>, Error>> {
+        async move {
+            let gossip_state_result = effect_builder
+                .make_request(
+                    |responder| RpcRequest::GetGossipState { responder },
+                    QueueKind::Api,
+                )
+                .await;
+
+            let gossip_state = match gossip_state_result {
+                Ok(gossip_state) => gossip_state,
+                Err(error) => {
+                    let error_code = match error {
+                        GetGossipStateError::Disabled => ErrorCode::GossipStateDisabled,
+                        GetGossipStateError::Unavailable => ErrorCode::GossipStateUnavailable,
+                    };
+                    let error_msg = format!("get-gossip-state failed: {}", error);
+                    info!("{}", error_msg);
+                    return Ok(response_builder
+                        .error(warp_json_rpc::Error::custom(error_code as i64, error_msg))?);
+                }
+            };
+
+            let result = Self::ResponseResult {
+                api_version,
+                topics: gossip_state.topics,
+            };
+            Ok(response_builder.success(result)?)
+        }
+        .boxed()
+    }
+}
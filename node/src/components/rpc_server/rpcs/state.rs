@@ -14,9 +14,13 @@ use serde::{Deserialize, Serialize};
 use tracing::info;
 use warp_json_rpc::Builder;
 
-use casper_execution_engine::core::engine_state::{BalanceResult, GetBidsResult};
+use casper_execution_engine::core::engine_state::{
+    self, BalanceResult, BalancesResult, GetBidsResult, PurseIdentifier,
+};
 use casper_types::{
-    bytesrepr::ToBytes, CLValue, Key, ProtocolVersion, PublicKey, SecretKey, URef, U512,
+    account::AccountHash, bytesrepr::ToBytes, CLType, CLValue, ContractHash, EntryPoint,
+    EntryPointAccess, EntryPointType, Key, Parameter, ProtocolVersion, PublicKey, SecretKey, URef,
+    U512,
 };
 
 use super::{
@@ -24,7 +28,7 @@ use super::{
     Error, ErrorCode, ReactorEventT, RpcRequest, RpcWithParams, RpcWithParamsExt,
 };
 use crate::{
-    components::rpc_server::rpcs::RpcWithOptionalParams,
+    components::rpc_server::{rpcs::RpcWithOptionalParams, GetAllBalancesError, GetBalancesError},
     crypto::hash::Digest,
     effect::EffectBuilder,
     reactor::QueueKind,
@@ -35,7 +39,7 @@ use crate::{
     },
     types::{
         json_compatibility::{Account, AuctionState, StoredValue},
-        Block,
+        Block, BlockHash,
     },
 };
 
@@ -43,11 +47,13 @@ static GET_ITEM_PARAMS: Lazy<GetItemParams> = Lazy::new(|| GetItemParams {
     state_root_hash: *Block::doc_example().header().state_root_hash(),
     key: "deploy-af684263911154d26fa05be9963171802801a0b6aff8f199b7391eacb8edc9e1".to_string(),
     path: vec!["inner".to_string()],
+    return_raw_bytes: false,
 });
 static GET_ITEM_RESULT: Lazy<GetItemResult> = Lazy::new(|| GetItemResult {
     api_version: DOCS_EXAMPLE_PROTOCOL_VERSION,
     stored_value: StoredValue::CLValue(CLValue::from_t(1u64).unwrap()),
     merkle_proof: MERKLE_PROOF.clone(),
+    raw_stored_value: None,
 });
 static GET_BALANCE_PARAMS: Lazy<GetBalanceParams> = Lazy::new(|| GetBalanceParams {
     state_root_hash: *Block::doc_example().header().state_root_hash(),
@@ -59,6 +65,75 @@ static GET_BALANCE_RESULT: Lazy<GetBalanceResult> = Lazy::new(|| GetBalanceResul
     balance_value: U512::from(123_456),
     merkle_proof: MERKLE_PROOF.clone(),
 });
+static GET_BALANCES_PARAMS: Lazy<GetBalancesParams> = Lazy::new(|| GetBalancesParams {
+    state_identifier: GlobalStateIdentifier::StateRootHash(
+        *Block::doc_example().header().state_root_hash(),
+    ),
+    purse_identifiers: vec![
+        "uref-09480c3248ef76b603d386f3f4f8a5f87f597d4eaffd475433f861af187ab5db-007".to_string(),
+        "account-hash-0000000000000000000000000000000000000000000000000000000000000000".to_string(),
+    ],
+});
+static GET_BALANCES_RESULT: Lazy<GetBalancesResult> = Lazy::new(|| {
+    GetBalancesResult {
+    api_version: DOCS_EXAMPLE_PROTOCOL_VERSION,
+    state_root_hash: *Block::doc_example().header().state_root_hash(),
+    balances: vec![
+        PurseBalanceEntry {
+            purse_identifier:
+                "uref-09480c3248ef76b603d386f3f4f8a5f87f597d4eaffd475433f861af187ab5db-007"
+                    .to_string(),
+            outcome: PurseBalanceOutcome::Found {
+                purse_uref:
+                    "uref-09480c3248ef76b603d386f3f4f8a5f87f597d4eaffd475433f861af187ab5db-007"
+                        .to_string(),
+                balance_value: U512::from(123_456),
+                merkle_proof: MERKLE_PROOF.clone(),
+            },
+        },
+        PurseBalanceEntry {
+            purse_identifier:
+                "account-hash-0000000000000000000000000000000000000000000000000000000000000000"
+                    .to_string(),
+            outcome: PurseBalanceOutcome::Failed {
+                error: "Key Key::Account(0000000000000000000000000000000000000000000000000000000000000000) \
+                        not found"
+                    .to_string(),
+            },
+        },
+    ],
+}
+});
+static GET_ALL_BALANCES_PARAMS: Lazy<GetAllBalancesParams> = Lazy::new(|| GetAllBalancesParams {
+    state_identifier: GlobalStateIdentifier::StateRootHash(
+        *Block::doc_example().header().state_root_hash(),
+    ),
+    page_token: None,
+});
+static GET_ALL_BALANCES_RESULT: Lazy<GetAllBalancesResult> = Lazy::new(|| GetAllBalancesResult {
+    api_version: DOCS_EXAMPLE_PROTOCOL_VERSION,
+    state_root_hash: *Block::doc_example().header().state_root_hash(),
+    balances: vec![AccountBalance {
+        account_hash:
+            "account-hash-0909090909090909090909090909090909090909090909090909090909090909"
+                .to_string(),
+        main_purse: "uref-09480c3248ef76b603d386f3f4f8a5f87f597d4eaffd475433f861af187ab5db-007"
+            .to_string(),
+        balance_value: U512::from(123_456),
+    }],
+    errors: vec![AccountBalanceError {
+        account_hash:
+            "account-hash-0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a"
+                .to_string(),
+        error:
+            "Key Key::Balance(0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b) \
+                not found"
+                .to_string(),
+    }],
+    next_page_token: Some(
+        "account-hash-0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a".to_string(),
+    ),
+});
 static GET_AUCTION_INFO_PARAMS: Lazy<GetAuctionInfoParams> = Lazy::new(|| GetAuctionInfoParams {
     block_identifier: BlockIdentifier::Hash(*Block::doc_example().hash()),
 });
@@ -79,6 +154,28 @@ static GET_ACCOUNT_INFO_RESULT: Lazy<GetAccountInfoResult> = Lazy::new(|| GetAcc
     account: Account::doc_example().clone(),
     merkle_proof: MERKLE_PROOF.clone(),
 });
+static GET_ENTRY_POINT_PARAMS: Lazy<GetEntryPointParams> = Lazy::new(|| GetEntryPointParams {
+    state_identifier: Some(GlobalStateIdentifier::BlockHash(
+        *Block::doc_example().hash(),
+    )),
+    contract_hash: ContractHash::new([7; 32]),
+    entry_point: "transfer".to_string(),
+});
+static GET_ENTRY_POINT_RESULT: Lazy<GetEntryPointResult> = Lazy::new(|| GetEntryPointResult {
+    api_version: DOCS_EXAMPLE_PROTOCOL_VERSION,
+    state_root_hash: *Block::doc_example().header().state_root_hash(),
+    entry_point: EntryPoint::new(
+        "transfer",
+        vec![
+            Parameter::new("recipient", CLType::Key),
+            Parameter::new("amount", CLType::U512),
+        ],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ),
+    merkle_proof: MERKLE_PROOF.clone(),
+});
 
 /// Params for "state_get_item" RPC request.
 #[derive(Serialize, Deserialize, Debug, JsonSchema)]
@@ -91,6 +188,10 @@ pub struct GetItemParams {
     /// The path components starting from the key as base.
     #[serde(default)]
     pub path: Vec<String>,
+    /// Whether to include the bytesrepr-serialized stored value in the result, allowing the
+    /// merkle proof to be verified without relying on the JSON rendering of the stored value.
+    #[serde(default)]
+    pub return_raw_bytes: bool,
 }
 
 impl DocExample for GetItemParams {
@@ -109,7 +210,20 @@ pub struct GetItemResult {
     /// The stored value.
     pub stored_value: StoredValue,
     /// The merkle proof.
+    ///
+    /// This is the hex-encoded, bytesrepr-serialized list of trie merkle proofs, one for the base
+    /// key and one for each component of the path.  Each proof holds the key and value of a trie
+    /// leaf along with the trie nodes on the path from it to the state root.
     pub merkle_proof: String,
+    /// The hex-encoded, bytesrepr-serialized stored value, if requested via `return_raw_bytes`.
+    ///
+    /// This is identical to the value held by the last of the merkle proofs.
+    ///
+    /// Unlike the other optional fields of the RPC API, this is omitted entirely rather than set
+    /// to `null` when not requested, so that responses to clients which don't request it are
+    /// unchanged from before the field was added, and still parse under `deny_unknown_fields`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw_stored_value: Option<String>,
 }
 
 impl DocExample for GetItemResult {
@@ -149,6 +263,8 @@ impl RpcWithParamsExt for GetItem {
                 }
             };
 
+            let return_raw_bytes = params.return_raw_bytes;
+
             // Run the query.
             let query_result = effect_builder
                 .make_request(
@@ -162,19 +278,25 @@ impl RpcWithParamsExt for GetItem {
                 )
                 .await;
 
-            let (stored_value, proof_bytes) = match common::extract_query_result(query_result) {
-                Ok(tuple) => tuple,
-                Err((error_code, error_msg)) => {
-                    info!("{}", error_msg);
-                    return Ok(response_builder
-                        .error(warp_json_rpc::Error::custom(error_code as i64, error_msg))?);
-                }
-            };
+            let (stored_value, proof_bytes, value_bytes) =
+                match common::extract_query_result_with_raw_value(query_result) {
+                    Ok(tuple) => tuple,
+                    Err((error_code, error_msg)) => {
+                        info!("{}", error_msg);
+                        return Ok(response_builder
+                            .error(warp_json_rpc::Error::custom(error_code as i64, error_msg))?);
+                    }
+                };
 
             let result = Self::ResponseResult {
                 api_version,
                 stored_value,
                 merkle_proof: hex::encode(proof_bytes),
+                raw_stored_value: if return_raw_bytes {
+                    Some(hex::encode(value_bytes))
+                } else {
+                    None
+                },
             };
 
             Ok(response_builder.success(result)?)
@@ -237,7 +359,7 @@ impl RpcWithParamsExt for GetBalance {
         async move {
             // Try to parse the purse's URef from the params.
             let purse_uref = match URef::from_formatted_str(&params.purse_uref)
-                .map_err(|error| format!("failed to parse purse_uref: {:?}", error))
+                .map_err(|error| format!("failed to parse purse_uref: {}", error))
             {
                 Ok(uref) => uref,
                 Err(error_msg) => {
@@ -303,6 +425,432 @@ impl RpcWithParamsExt for GetBalance {
     }
 }
 
+/// Identifies the global state to read from.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub enum GlobalStateIdentifier {
+    /// The global state as of the block with the given hash.
+    BlockHash(BlockHash),
+    /// The global state with the given state root hash.
+    StateRootHash(Digest),
+}
+
+/// Params for "state_get_balances" RPC request.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetBalancesParams {
+    /// The global state to read the balances from.
+    pub state_identifier: GlobalStateIdentifier,
+    /// The purses whose balances are requested, each given as either a formatted purse URef or a
+    /// formatted account hash.  For an account hash, the balance of the account's main purse is
+    /// returned.
+    pub purse_identifiers: Vec<String>,
+}
+
+impl DocExample for GetBalancesParams {
+    fn doc_example() -> &'static Self {
+        &*GET_BALANCES_PARAMS
+    }
+}
+
+/// The outcome of reading the balance of a single purse.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub enum PurseBalanceOutcome {
+    /// The balance was read successfully.
+    Found {
+        /// The formatted URef of the purse the balance was read from.
+        purse_uref: String,
+        /// The balance value.
+        balance_value: U512,
+        /// The merkle proof.
+        merkle_proof: String,
+    },
+    /// The balance could not be read.
+    Failed {
+        /// The reason for the failure.
+        error: String,
+    },
+}
+
+/// The balance of a single purse in a "state_get_balances" RPC response.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct PurseBalanceEntry {
+    /// The purse identifier, as given in the request.
+    pub purse_identifier: String,
+    /// The outcome of reading the purse's balance.
+    pub outcome: PurseBalanceOutcome,
+}
+
+/// Result for "state_get_balances" RPC response.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetBalancesResult {
+    /// The RPC API version.
+    #[schemars(with = "String")]
+    pub api_version: ProtocolVersion,
+    /// The state root hash the balances were read from.
+    pub state_root_hash: Digest,
+    /// The balances, one per requested purse and in request order.
+    pub balances: Vec<PurseBalanceEntry>,
+}
+
+impl DocExample for GetBalancesResult {
+    fn doc_example() -> &'static Self {
+        &*GET_BALANCES_RESULT
+    }
+}
+
+/// "state_get_balances" RPC.
+pub struct GetBalances {}
+
+impl RpcWithParams for GetBalances {
+    const METHOD: &'static str = "state_get_balances";
+    type RequestParams = GetBalancesParams;
+    type ResponseResult = GetBalancesResult;
+}
+
+/// Parses a formatted purse URef or account hash.
+fn parse_purse_identifier(identifier: &str) -> Result<PurseIdentifier, String> {
+    if let Ok(purse_uref) = URef::from_formatted_str(identifier) {
+        return Ok(PurseIdentifier::Purse(purse_uref));
+    }
+    AccountHash::from_formatted_str(identifier)
+        .map(PurseIdentifier::Account)
+        .map_err(|_| {
+            format!(
+                "failed to parse purse identifier '{}' as a URef or account hash",
+                identifier
+            )
+        })
+}
+
+impl RpcWithParamsExt for GetBalances {
+    fn handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        response_builder: Builder,
+        params: Self::RequestParams,
+        api_version: ProtocolVersion,
+    ) -> BoxFuture<'static, Result<Response<Body>, Error>> {
+        async move {
+            // Try to parse the purse identifiers from the params.
+            let mut purse_identifiers = Vec::with_capacity(params.purse_identifiers.len());
+            for identifier in &params.purse_identifiers {
+                match parse_purse_identifier(identifier) {
+                    Ok(purse_identifier) => purse_identifiers.push(purse_identifier),
+                    Err(error_msg) => {
+                        info!("{}", error_msg);
+                        return Ok(response_builder.error(warp_json_rpc::Error::custom(
+                            ErrorCode::ParseGetBalanceURef as i64,
+                            error_msg,
+                        ))?);
+                    }
+                }
+            }
+
+            let state_root_hash = match params.state_identifier {
+                GlobalStateIdentifier::StateRootHash(state_root_hash) => state_root_hash,
+                GlobalStateIdentifier::BlockHash(block_hash) => {
+                    let maybe_block = effect_builder
+                        .make_request(
+                            |responder| RpcRequest::GetBlock {
+                                maybe_id: Some(BlockIdentifier::Hash(block_hash)),
+                                responder,
+                            },
+                            QueueKind::Api,
+                        )
+                        .await;
+                    match maybe_block {
+                        Some((block, _)) => *block.header().state_root_hash(),
+                        None => {
+                            let error_msg =
+                                "get-balances failed to get specified block".to_string();
+                            info!("{}", error_msg);
+                            return Ok(response_builder.error(warp_json_rpc::Error::custom(
+                                ErrorCode::NoSuchBlock as i64,
+                                error_msg,
+                            ))?);
+                        }
+                    }
+                }
+            };
+
+            // Get the balances.
+            let balances_result = effect_builder
+                .make_request(
+                    |responder| RpcRequest::GetBalances {
+                        state_root_hash,
+                        purse_identifiers,
+                        responder,
+                    },
+                    QueueKind::Api,
+                )
+                .await;
+
+            let balances = match balances_result {
+                Ok(BalancesResult::Success(balances)) => balances,
+                Ok(BalancesResult::RootNotFound) => {
+                    let error_msg = "get-balances failed: root not found".to_string();
+                    info!("{}", error_msg);
+                    return Ok(response_builder.error(warp_json_rpc::Error::custom(
+                        ErrorCode::GetBalanceFailed as i64,
+                        error_msg,
+                    ))?);
+                }
+                Err(error @ GetBalancesError::TooManyPurses { .. }) => {
+                    let error_msg = format!("get-balances failed: {}", error);
+                    info!("{}", error_msg);
+                    return Ok(response_builder.error(warp_json_rpc::Error::custom(
+                        ErrorCode::TooManyBalancesRequested as i64,
+                        error_msg,
+                    ))?);
+                }
+                Err(error) => {
+                    let error_msg = format!("get-balances failed to execute: {}", error);
+                    info!("{}", error_msg);
+                    return Ok(response_builder.error(warp_json_rpc::Error::custom(
+                        ErrorCode::GetBalanceFailedToExecute as i64,
+                        error_msg,
+                    ))?);
+                }
+            };
+
+            let mut entries = Vec::with_capacity(balances.len());
+            for (purse_identifier, balance) in params.purse_identifiers.into_iter().zip(balances) {
+                let outcome = match balance {
+                    Ok(purse_balance) => {
+                        let purse_uref = purse_balance.purse_uref().to_formatted_string();
+                        let balance_value = *purse_balance.motes();
+                        let proof_bytes = match purse_balance.proof().to_bytes() {
+                            Ok(proof_bytes) => proof_bytes,
+                            Err(error) => {
+                                info!("failed to encode stored value: {}", error);
+                                return Ok(
+                                    response_builder.error(warp_json_rpc::Error::INTERNAL_ERROR)?
+                                );
+                            }
+                        };
+                        PurseBalanceOutcome::Found {
+                            purse_uref,
+                            balance_value,
+                            merkle_proof: hex::encode(proof_bytes),
+                        }
+                    }
+                    Err(error) => PurseBalanceOutcome::Failed {
+                        error: error.to_string(),
+                    },
+                };
+                entries.push(PurseBalanceEntry {
+                    purse_identifier,
+                    outcome,
+                });
+            }
+
+            // Return the result.
+            let result = Self::ResponseResult {
+                api_version,
+                state_root_hash,
+                balances: entries,
+            };
+            Ok(response_builder.success(result)?)
+        }
+        .boxed()
+    }
+}
+
+/// Params for "state_get_all_balances" RPC request.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetAllBalancesParams {
+    /// The global state to read the balances from.
+    pub state_identifier: GlobalStateIdentifier,
+    /// The `next_page_token` of the previous page's response, or `None` for the first page.
+    pub page_token: Option<String>,
+}
+
+impl DocExample for GetAllBalancesParams {
+    fn doc_example() -> &'static Self {
+        &*GET_ALL_BALANCES_PARAMS
+    }
+}
+
+/// The balance of an account's main purse in a "state_get_all_balances" RPC response.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AccountBalance {
+    /// The formatted account hash.
+    pub account_hash: String,
+    /// The formatted URef of the account's main purse.
+    pub main_purse: String,
+    /// The balance value.
+    pub balance_value: U512,
+}
+
+/// An account whose balance couldn't be read in a "state_get_all_balances" RPC response.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AccountBalanceError {
+    /// The formatted account hash.
+    pub account_hash: String,
+    /// The reason the account is malformed or its balance couldn't be read.
+    pub error: String,
+}
+
+/// Result for "state_get_all_balances" RPC response.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetAllBalancesResult {
+    /// The RPC API version.
+    #[schemars(with = "String")]
+    pub api_version: ProtocolVersion,
+    /// The state root hash the balances were read from.
+    pub state_root_hash: Digest,
+    /// The balances of the accounts in this page, in ascending order of account hash.
+    pub balances: Vec<AccountBalance>,
+    /// The accounts in this page which are malformed or whose balance couldn't be read.
+    pub errors: Vec<AccountBalanceError>,
+    /// The token to pass in the request for the next page, or `None` if this is the last page.
+    pub next_page_token: Option<String>,
+}
+
+impl DocExample for GetAllBalancesResult {
+    fn doc_example() -> &'static Self {
+        &*GET_ALL_BALANCES_RESULT
+    }
+}
+
+/// "state_get_all_balances" RPC.
+pub struct GetAllBalances {}
+
+impl RpcWithParams for GetAllBalances {
+    const METHOD: &'static str = "state_get_all_balances";
+    type RequestParams = GetAllBalancesParams;
+    type ResponseResult = GetAllBalancesResult;
+}
+
+impl RpcWithParamsExt for GetAllBalances {
+    fn handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        response_builder: Builder,
+        params: Self::RequestParams,
+        api_version: ProtocolVersion,
+    ) -> BoxFuture<'static, Result<Response<Body>, Error>> {
+        async move {
+            // Try to parse the page token, which is the formatted hash of the last account scanned.
+            let start_after = match params.page_token {
+                Some(page_token) => match AccountHash::from_formatted_str(&page_token) {
+                    Ok(account_hash) => Some(account_hash),
+                    Err(_) => {
+                        let error_msg = format!("failed to parse page token '{}'", page_token);
+                        info!("{}", error_msg);
+                        return Ok(response_builder.error(warp_json_rpc::Error::custom(
+                            ErrorCode::InvalidPageToken as i64,
+                            error_msg,
+                        ))?);
+                    }
+                },
+                None => None,
+            };
+
+            let state_root_hash = match params.state_identifier {
+                GlobalStateIdentifier::StateRootHash(state_root_hash) => state_root_hash,
+                GlobalStateIdentifier::BlockHash(block_hash) => {
+                    let maybe_block = effect_builder
+                        .make_request(
+                            |responder| RpcRequest::GetBlock {
+                                maybe_id: Some(BlockIdentifier::Hash(block_hash)),
+                                responder,
+                            },
+                            QueueKind::Api,
+                        )
+                        .await;
+                    match maybe_block {
+                        Some((block, _)) => *block.header().state_root_hash(),
+                        None => {
+                            let error_msg =
+                                "get-all-balances failed to get specified block".to_string();
+                            info!("{}", error_msg);
+                            return Ok(response_builder.error(warp_json_rpc::Error::custom(
+                                ErrorCode::NoSuchBlock as i64,
+                                error_msg,
+                            ))?);
+                        }
+                    }
+                }
+            };
+
+            // Get the page of balances.
+            let all_balances_result = effect_builder
+                .make_request(
+                    |responder| RpcRequest::GetAllBalances {
+                        state_root_hash,
+                        start_after,
+                        responder,
+                    },
+                    QueueKind::Api,
+                )
+                .await;
+
+            let (balances, errors, next) = match all_balances_result {
+                Ok(engine_state::GetAllBalancesResult::Success {
+                    balances,
+                    errors,
+                    next,
+                }) => (balances, errors, next),
+                Ok(engine_state::GetAllBalancesResult::RootNotFound) => {
+                    let error_msg = "get-all-balances failed: root not found".to_string();
+                    info!("{}", error_msg);
+                    return Ok(response_builder.error(warp_json_rpc::Error::custom(
+                        ErrorCode::GetBalanceFailed as i64,
+                        error_msg,
+                    ))?);
+                }
+                Err(error @ GetAllBalancesError::Disabled) => {
+                    let error_msg = format!("get-all-balances failed: {}", error);
+                    info!("{}", error_msg);
+                    return Ok(response_builder.error(warp_json_rpc::Error::custom(
+                        ErrorCode::BalancesExportDisabled as i64,
+                        error_msg,
+                    ))?);
+                }
+                Err(error) => {
+                    let error_msg = format!("get-all-balances failed to execute: {}", error);
+                    info!("{}", error_msg);
+                    return Ok(response_builder.error(warp_json_rpc::Error::custom(
+                        ErrorCode::GetBalanceFailedToExecute as i64,
+                        error_msg,
+                    ))?);
+                }
+            };
+
+            // Return the result.
+            let result = Self::ResponseResult {
+                api_version,
+                state_root_hash,
+                balances: balances
+                    .into_iter()
+                    .map(|(account_hash, main_purse, balance_value)| AccountBalance {
+                        account_hash: account_hash.to_formatted_string(),
+                        main_purse: main_purse.to_formatted_string(),
+                        balance_value,
+                    })
+                    .collect(),
+                errors: errors
+                    .into_iter()
+                    .map(|(account_hash, error)| AccountBalanceError {
+                        account_hash: account_hash.to_formatted_string(),
+                        error: error.to_string(),
+                    })
+                    .collect(),
+                next_page_token: next.map(|account_hash| account_hash.to_formatted_string()),
+            };
+            Ok(response_builder.success(result)?)
+        }
+        .boxed()
+    }
+}
+
 /// Params for "state_get_auction_info" RPC request.
 #[derive(Serialize, Deserialize, Debug, JsonSchema)]
 #[serde(deny_unknown_fields)]
@@ -558,3 +1106,193 @@ impl RpcWithParamsExt for GetAccountInfo {
         .boxed()
     }
 }
+
+/// Params for "state_get_entry_point" RPC request.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetEntryPointParams {
+    /// The global state to read the contract from, or the one of the most recently added block if
+    /// not given.
+    pub state_identifier: Option<GlobalStateIdentifier>,
+    /// The hash of the stored contract.
+    pub contract_hash: ContractHash,
+    /// The name of the entry point.
+    pub entry_point: String,
+}
+
+impl DocExample for GetEntryPointParams {
+    fn doc_example() -> &'static Self {
+        &*GET_ENTRY_POINT_PARAMS
+    }
+}
+
+/// Result for "state_get_entry_point" RPC response.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetEntryPointResult {
+    /// The RPC API version.
+    #[schemars(with = "String")]
+    pub api_version: ProtocolVersion,
+    /// The state root hash the contract was read from.
+    pub state_root_hash: Digest,
+    /// The entry point, including the names and types of its args.
+    pub entry_point: EntryPoint,
+    /// The merkle proof of the contract.
+    pub merkle_proof: String,
+}
+
+impl DocExample for GetEntryPointResult {
+    fn doc_example() -> &'static Self {
+        &*GET_ENTRY_POINT_RESULT
+    }
+}
+
+/// "state_get_entry_point" RPC.
+pub struct GetEntryPoint {}
+
+/// Returns the state root hash of the given block, or of the most recently added block if
+/// `maybe_id` is `None`.
+async fn block_state_root_hash<REv: ReactorEventT>(
+    effect_builder: EffectBuilder<REv>,
+    maybe_id: Option<BlockIdentifier>,
+) -> Option<Digest> {
+    effect_builder
+        .make_request(
+            |responder| RpcRequest::GetBlock {
+                maybe_id,
+                responder,
+            },
+            QueueKind::Api,
+        )
+        .await
+        .map(|(block, _)| *block.header().state_root_hash())
+}
+
+impl RpcWithParams for GetEntryPoint {
+    const METHOD: &'static str = "state_get_entry_point";
+    type RequestParams = GetEntryPointParams;
+    type ResponseResult = GetEntryPointResult;
+}
+
+impl RpcWithParamsExt for GetEntryPoint {
+    fn handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        response_builder: Builder,
+        params: Self::RequestParams,
+        api_version: ProtocolVersion,
+    ) -> BoxFuture<'static, Result<Response<Body>, Error>> {
+        async move {
+            let maybe_state_root_hash = match params.state_identifier {
+                Some(GlobalStateIdentifier::StateRootHash(state_root_hash)) => {
+                    Some(state_root_hash)
+                }
+                Some(GlobalStateIdentifier::BlockHash(block_hash)) => {
+                    block_state_root_hash(effect_builder, Some(BlockIdentifier::Hash(block_hash)))
+                        .await
+                }
+                None => block_state_root_hash(effect_builder, None).await,
+            };
+            let state_root_hash = match maybe_state_root_hash {
+                Some(state_root_hash) => state_root_hash,
+                None => {
+                    let error_msg = "get-entry-point failed to get specified block".to_string();
+                    info!("{}", error_msg);
+                    return Ok(response_builder.error(warp_json_rpc::Error::custom(
+                        ErrorCode::NoSuchBlock as i64,
+                        error_msg,
+                    ))?);
+                }
+            };
+
+            let base_key = Key::from(params.contract_hash);
+            let query_result = effect_builder
+                .make_request(
+                    |responder| RpcRequest::QueryGlobalState {
+                        state_root_hash,
+                        base_key,
+                        path: vec![],
+                        responder,
+                    },
+                    QueueKind::Api,
+                )
+                .await;
+
+            let (stored_value, proof_bytes) = match common::extract_query_result(query_result) {
+                Ok(tuple) => tuple,
+                Err((error_code, error_msg)) => {
+                    info!("{}", error_msg);
+                    return Ok(response_builder
+                        .error(warp_json_rpc::Error::custom(error_code as i64, error_msg))?);
+                }
+            };
+
+            let contract = if let StoredValue::Contract(contract) = stored_value {
+                contract
+            } else {
+                let error_msg = format!(
+                    "get-entry-point failed: {} is not a contract",
+                    params.contract_hash.to_formatted_string()
+                );
+                info!("{}", error_msg);
+                return Ok(response_builder.error(warp_json_rpc::Error::custom(
+                    ErrorCode::NoSuchContract as i64,
+                    error_msg,
+                ))?);
+            };
+
+            let entry_point = match contract.entry_point(&params.entry_point) {
+                Some(entry_point) => entry_point.clone(),
+                None => {
+                    let error_msg = format!(
+                        "get-entry-point failed: {} has no entry point named '{}'",
+                        params.contract_hash.to_formatted_string(),
+                        params.entry_point
+                    );
+                    info!("{}", error_msg);
+                    return Ok(response_builder.error(warp_json_rpc::Error::custom(
+                        ErrorCode::NoSuchEntryPoint as i64,
+                        error_msg,
+                    ))?);
+                }
+            };
+
+            let result = Self::ResponseResult {
+                api_version,
+                state_root_hash,
+                entry_point,
+                merkle_proof: hex::encode(proof_bytes),
+            };
+            Ok(response_builder.success(result)?)
+        }
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_omit_raw_stored_value_unless_requested() {
+        let get_item_result = GetItemResult::doc_example();
+        assert!(get_item_result.raw_stored_value.is_none());
+        let json_value = serde_json::to_value(get_item_result).unwrap();
+        assert!(!json_value
+            .as_object()
+            .unwrap()
+            .contains_key("raw_stored_value"));
+        let decoded: GetItemResult = serde_json::from_value(json_value).unwrap();
+        assert!(decoded.raw_stored_value.is_none());
+
+        let get_item_result = GetItemResult {
+            api_version: get_item_result.api_version,
+            stored_value: get_item_result.stored_value.clone(),
+            merkle_proof: get_item_result.merkle_proof.clone(),
+            raw_stored_value: Some(String::from("0102")),
+        };
+        let json_value = serde_json::to_value(&get_item_result).unwrap();
+        assert_eq!(json_value["raw_stored_value"], "0102");
+        let decoded: GetItemResult = serde_json::from_value(json_value).unwrap();
+        assert_eq!(decoded.raw_stored_value, get_item_result.raw_stored_value);
+    }
+}
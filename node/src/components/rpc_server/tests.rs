@@ -0,0 +1,366 @@
+#![cfg(test)]
+#![allow(unreachable_code)]
+
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use casper_node_macros::reactor;
+use futures::FutureExt;
+use rand::Rng;
+use tempfile::TempDir;
+use tokio::time;
+
+use casper_execution_engine::core::engine_state::executable_deploy_item::ExecutableDeployItem;
+use casper_types::{
+    bytesrepr::{Bytes, ToBytes},
+    runtime_args,
+    system::standard_payment::ARG_AMOUNT,
+    EraId, ExecutionResult, ProtocolVersion, PublicKey, RuntimeArgs, SecretKey, U512,
+};
+
+use super::*;
+use crate::{
+    components::{block_proposer, consensus::BlockContext, storage},
+    crypto::AsymmetricKeyExt,
+    reactor::{QueueKind, Runner},
+    testing::TestRng,
+    types::{Block, BlockHash, BlockPayload, Deploy, FinalizedBlock, TimeDiff},
+    utils::{WithDir, RESOURCES_PATH},
+};
+
+/// The time within which each injected request is expected to complete.
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The interval at which an idle runner is polled while awaiting a result.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// The TTL of the deploys expected to stay live for the duration of a test.
+const LONG_TTL: Duration = Duration::from_secs(3_600);
+
+#[derive(Debug)]
+pub struct TestConfig {
+    storage_config: storage::Config,
+    block_proposer_config: block_proposer::Config,
+    temp_dir: TempDir,
+}
+
+impl TestConfig {
+    fn new(block_proposer_config: block_proposer::Config) -> Self {
+        let (storage_config, temp_dir) = storage::Config::default_for_tests();
+        TestConfig {
+            storage_config,
+            block_proposer_config,
+            temp_dir,
+        }
+    }
+}
+
+reactor!(Reactor {
+    type Config = TestConfig;
+
+    components: {
+        chainspec_loader = has_effects ChainspecLoader(
+            &RESOURCES_PATH.join("local"),
+            effect_builder
+        );
+        storage = Storage(
+            &WithDir::new(cfg.temp_dir.path(), cfg.storage_config),
+            chainspec_loader.hard_reset_to_start_of_era(),
+            ProtocolVersion::from_parts(1, 0, 0),
+            false,
+            registry
+        );
+        block_proposer = has_effects BlockProposer(
+            registry.clone(),
+            effect_builder,
+            0,
+            &*chainspec_loader.chainspec(),
+            cfg.block_proposer_config
+        );
+    }
+
+    events: {}
+
+    requests: {
+        StorageRequest -> storage;
+        StateStoreRequest -> storage;
+        BlockProposerRequest -> block_proposer;
+
+        // The only contract runtime request will be the commit of genesis, which we discard.
+        ContractRuntimeRequest -> #;
+    }
+
+    announcements: {
+        ChainspecLoaderAnnouncement -> [!];
+        // Replacements and evictions are observed via the deploy status instead.
+        BlockProposerAnnouncement -> [#];
+    }
+});
+
+/// Injects the future created by `create_future` and cranks the runner until it has completed,
+/// returning its output.
+async fn run_until_complete<T, F, Fut>(
+    runner: &mut Runner<Reactor>,
+    rng: &mut TestRng,
+    create_future: F,
+) -> T
+where
+    T: Send + 'static,
+    F: FnOnce(EffectBuilder<ReactorEvent>) -> Fut,
+    Fut: Future<Output = T> + Send + 'static,
+{
+    let output = Arc::new(Mutex::new(None));
+    let output_clone = Arc::clone(&output);
+    runner
+        .process_injected_effects(move |effect_builder| {
+            create_future(effect_builder)
+                .map(move |value| *output_clone.lock().unwrap() = Some(value))
+                .ignore()
+        })
+        .await;
+
+    time::timeout(TIMEOUT, async {
+        loop {
+            if let Some(value) = output.lock().unwrap().take() {
+                return value;
+            }
+            if runner.try_crank(rng).await.is_none() {
+                time::sleep(POLL_INTERVAL).await;
+            }
+        }
+    })
+    .await
+    .expect("injected future should complete in time")
+}
+
+/// Queries the deploy's status until it is `expected`, since some stages are only reached once the
+/// block proposer has handled the events triggered by the preceding step.
+async fn await_status(
+    runner: &mut Runner<Reactor>,
+    rng: &mut TestRng,
+    deploy_hash: DeployHash,
+    expected: DeployStatus,
+) {
+    let mut status = DeployStatus::Unknown;
+    for _ in 0..(TIMEOUT.as_millis() / POLL_INTERVAL.as_millis()) {
+        status = run_until_complete(runner, rng, move |effect_builder| {
+            get_deploy_status(effect_builder, deploy_hash)
+        })
+        .await;
+        if status == expected {
+            return;
+        }
+        time::sleep(POLL_INTERVAL).await;
+    }
+    panic!(
+        "deploy status of {} should be {:?}, but is {:?}",
+        deploy_hash, expected, status
+    );
+}
+
+/// Creates a deploy from the given account, whose body is determined by `payment_amount`.
+fn new_deploy(
+    secret_key: &SecretKey,
+    payment_amount: u64,
+    gas_price: u64,
+    ttl: Duration,
+) -> Deploy {
+    let payment = ExecutableDeployItem::ModuleBytes {
+        module_bytes: Bytes::new(),
+        args: runtime_args! { ARG_AMOUNT => U512::from(payment_amount) },
+    };
+    let session = ExecutableDeployItem::ModuleBytes {
+        module_bytes: Bytes::new(),
+        args: RuntimeArgs::new(),
+    };
+    Deploy::new(
+        Timestamp::now(),
+        TimeDiff::from(ttl),
+        gas_price,
+        vec![],
+        "casper-example".to_string(),
+        payment,
+        session,
+        secret_key,
+    )
+}
+
+async fn store_deploy(runner: &mut Runner<Reactor>, rng: &mut TestRng, deploy: &Deploy) {
+    let deploy = Box::new(deploy.clone());
+    let stored = run_until_complete(runner, rng, move |effect_builder| {
+        effect_builder.put_deploy_to_storage(deploy)
+    })
+    .await;
+    assert!(stored, "deploy should be newly stored");
+}
+
+/// Announces the deploy to the block proposer as having been gossiped, so it gets buffered.
+async fn buffer_deploy(runner: &mut Runner<Reactor>, rng: &mut TestRng, deploy_hash: DeployHash) {
+    run_until_complete(runner, rng, move |effect_builder| {
+        effect_builder.into_inner().schedule(
+            block_proposer::Event::BufferDeploy(deploy_hash),
+            QueueKind::Regular,
+        )
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn should_report_deploy_status_through_execution() {
+    let mut rng = crate::new_rng();
+    let mut runner: Runner<Reactor> = Runner::new(TestConfig::new(Default::default()), &mut rng)
+        .await
+        .unwrap();
+
+    let secret_key = SecretKey::random(&mut rng);
+    let deploy = new_deploy(&secret_key, 1, 1, LONG_TTL);
+    let deploy_hash = *deploy.id();
+
+    await_status(&mut runner, &mut rng, deploy_hash, DeployStatus::Unknown).await;
+
+    // Accepted, but not yet gossiped.
+    store_deploy(&mut runner, &mut rng, &deploy).await;
+    let expected = DeployStatus::Pending { buffered: false };
+    await_status(&mut runner, &mut rng, deploy_hash, expected).await;
+
+    buffer_deploy(&mut runner, &mut rng, deploy_hash).await;
+    let expected = DeployStatus::Pending { buffered: true };
+    await_status(&mut runner, &mut rng, deploy_hash, expected).await;
+
+    let block_timestamp = Timestamp::now();
+    let block_payload: Arc<BlockPayload> =
+        run_until_complete(&mut runner, &mut rng, |effect_builder| {
+            let context = BlockContext::new(block_timestamp, vec![]);
+            effect_builder.request_block_payload(context, 0, vec![], false)
+        })
+        .await;
+    assert_eq!(*block_payload.deploy_hashes(), vec![deploy_hash]);
+    let expected = DeployStatus::Proposed {
+        latest_proposal_timestamp: block_timestamp,
+    };
+    await_status(&mut runner, &mut rng, deploy_hash, expected).await;
+
+    // Finalized, but not yet executed, so the block isn't stored yet.
+    let finalized_block = FinalizedBlock::new(
+        (*block_payload).clone(),
+        None,
+        block_timestamp,
+        EraId::from(0),
+        0,
+        PublicKey::from(&secret_key),
+    );
+    let event = block_proposer::Event::FinalizedBlock(Box::new(finalized_block.clone()));
+    run_until_complete(&mut runner, &mut rng, move |effect_builder| {
+        effect_builder
+            .into_inner()
+            .schedule(event, QueueKind::Regular)
+    })
+    .await;
+    let expected = DeployStatus::Finalized { block_hash: None };
+    await_status(&mut runner, &mut rng, deploy_hash, expected).await;
+
+    let block = Block::new(
+        BlockHash::new(Digest::random(&mut rng)),
+        Digest::random(&mut rng),
+        Digest::random(&mut rng),
+        finalized_block,
+        None,
+        ProtocolVersion::V1_0_0,
+    );
+    let block_hash = *block.hash();
+    let stored = run_until_complete(&mut runner, &mut rng, move |effect_builder| {
+        effect_builder.put_block_to_storage(Box::new(block))
+    })
+    .await;
+    assert!(stored, "block should be stored");
+    let expected = DeployStatus::Finalized {
+        block_hash: Some(block_hash),
+    };
+    await_status(&mut runner, &mut rng, deploy_hash, expected).await;
+
+    let execution_result: ExecutionResult = rng.gen();
+    let (cost, error_message) = match &execution_result {
+        ExecutionResult::Failure {
+            cost,
+            error_message,
+            ..
+        } => (*cost, Some(error_message.clone())),
+        ExecutionResult::Success { cost, .. } => (*cost, None),
+    };
+    let mut execution_results = HashMap::new();
+    execution_results.insert(deploy_hash, execution_result);
+    run_until_complete(&mut runner, &mut rng, move |effect_builder| {
+        effect_builder.put_execution_results_to_storage(block_hash, execution_results)
+    })
+    .await;
+    let expected = DeployStatus::Executed {
+        block_hash,
+        block_height: Some(0),
+        cost,
+        // The gas price is 1, so the cost in motes equals the gas cost.
+        cost_in_motes: Some(cost),
+        error_message,
+    };
+    await_status(&mut runner, &mut rng, deploy_hash, expected).await;
+}
+
+#[tokio::test]
+async fn should_report_expired_deploy() {
+    let mut rng = crate::new_rng();
+    let mut runner: Runner<Reactor> = Runner::new(TestConfig::new(Default::default()), &mut rng)
+        .await
+        .unwrap();
+
+    let secret_key = SecretKey::random(&mut rng);
+    let deploy = new_deploy(&secret_key, 1, 1, Duration::from_millis(1));
+    let deploy_hash = *deploy.id();
+
+    store_deploy(&mut runner, &mut rng, &deploy).await;
+    time::sleep(Duration::from_millis(10)).await;
+
+    // The block proposer rejects the deploy as expired, so it's only known to storage.
+    buffer_deploy(&mut runner, &mut rng, deploy_hash).await;
+    let expected = DeployStatus::Expired {
+        expired_at: deploy.header().expires(),
+    };
+    await_status(&mut runner, &mut rng, deploy_hash, expected).await;
+}
+
+#[tokio::test]
+async fn should_report_replaced_and_evicted_deploys() {
+    let mut rng = crate::new_rng();
+    let secret_key = SecretKey::random(&mut rng);
+
+    // The first two deploys share a body, so the pricier one replaces the other.
+    let replaced = new_deploy(&secret_key, 1, 1, LONG_TTL);
+    let replacement = new_deploy(&secret_key, 1, 2, LONG_TTL);
+    // The buffer only has room for one more deploy, so the cheaper of the remaining two is
+    // evicted.
+    let evicted = new_deploy(&secret_key, 2, 1, LONG_TTL);
+    let max_buffer_size = replacement.serialized_length() * 3 / 2;
+
+    let config = TestConfig::new(block_proposer::Config::new(max_buffer_size));
+    let mut runner: Runner<Reactor> = Runner::new(config, &mut rng).await.unwrap();
+
+    let buffered = DeployStatus::Pending { buffered: true };
+
+    store_deploy(&mut runner, &mut rng, &replaced).await;
+    buffer_deploy(&mut runner, &mut rng, *replaced.id()).await;
+    await_status(&mut runner, &mut rng, *replaced.id(), buffered.clone()).await;
+
+    store_deploy(&mut runner, &mut rng, &replacement).await;
+    buffer_deploy(&mut runner, &mut rng, *replacement.id()).await;
+    await_status(&mut runner, &mut rng, *replacement.id(), buffered.clone()).await;
+    let expected = DeployStatus::Replaced;
+    await_status(&mut runner, &mut rng, *replaced.id(), expected).await;
+
+    store_deploy(&mut runner, &mut rng, &evicted).await;
+    buffer_deploy(&mut runner, &mut rng, *evicted.id()).await;
+    let expected = DeployStatus::Evicted;
+    await_status(&mut runner, &mut rng, *evicted.id(), expected).await;
+    await_status(&mut runner, &mut rng, *replacement.id(), buffered).await;
+}
@@ -23,6 +23,7 @@
 //! Nodes gossip their public listening addresses periodically, and will try to establish and
 //! maintain an outgoing connection to any new address learned.
 
+mod blocklist;
 mod chain_info;
 mod config;
 mod counting_format;
@@ -33,6 +34,7 @@ mod limiter;
 mod message;
 mod message_pack_format;
 mod outgoing;
+mod sent_payloads;
 mod symmetry;
 pub(crate) mod tasks;
 #[cfg(test)]
@@ -44,6 +46,7 @@ use std::{
     fmt::{self, Debug, Display, Formatter},
     io, mem,
     net::{SocketAddr, TcpListener},
+    path::Path,
     result,
     sync::{Arc, Weak},
     time::{Duration, Instant},
@@ -71,38 +74,43 @@ use tokio_util::codec::LengthDelimitedCodec;
 use tracing::{debug, error, info, trace, warn, Instrument, Span};
 
 use self::{
+    blocklist::Blocklist,
     counting_format::{ConnectionId, CountingFormat, Role},
     error::{ConnectionError, Result},
     event::{IncomingConnection, OutgoingConnection},
+    gossiped_address::{AddressRejection, PeerAddressRecords, SignedAddressRecord},
     limiter::Limiter,
     message::ConsensusKeyPair,
     message_pack_format::MessagePackFormat,
     outgoing::{DialOutcome, DialRequest, OutgoingConfig, OutgoingManager},
+    sent_payloads::SentPayloadsCache,
     symmetry::ConnectionSymmetry,
     tasks::NetworkContext,
 };
 pub(crate) use self::{
     event::Event,
-    gossiped_address::GossipedAddress,
+    gossiped_address::{legacy_address_message, GossipedAddress},
     message::{Message, MessageKind, Payload},
 };
 use super::consensus;
 use crate::{
-    components::{networking_metrics::NetworkingMetrics, Component},
+    components::{
+        network_provider::{self, NetworkProvider, NetworkProviderKind},
+        networking_metrics::NetworkingMetrics,
+        Component,
+    },
     effect::{
         announcements::{BlocklistAnnouncement, LinearChainAnnouncement, NetworkAnnouncement},
-        requests::{
-            ChainspecLoaderRequest, ContractRuntimeRequest, NetworkInfoRequest, NetworkRequest,
-            StorageRequest,
-        },
-        EffectBuilder, EffectExt, Effects,
+        requests::{ChainspecLoaderRequest, ContractRuntimeRequest, StorageRequest},
+        EffectBuilder, EffectExt, Effects, Responder,
     },
     reactor::{EventQueueHandle, Finalize, ReactorEvent},
     tls::{self, TlsCert, ValidationError},
-    types::NodeId,
+    types::{DialBackoff, GossipState, NodeId, Timestamp},
     utils::{self, display_error, WithDir},
     NodeRng,
 };
+pub use blocklist::clear_saved_blocklist;
 use chain_info::ChainInfo;
 pub use config::Config;
 pub use error::Error;
@@ -115,6 +123,10 @@ const DROP_RETRY_DELAY: Duration = Duration::from_millis(100);
 /// Duration peers are kept on the block list, before being redeemed.
 const BLOCKLIST_RETAIN_DURATION: Duration = Duration::from_secs(60 * 10);
 
+/// Delay after a change to the blocklist before it is saved, allowing multiple changes to be
+/// batched into a single write.
+const BLOCKLIST_SAVE_DELAY: Duration = Duration::from_secs(5);
+
 /// How often to keep attempting to reconnect to a node before giving up. Note that reconnection
 /// delays increase exponentially!
 const RECONNECTION_ATTEMPTS: u8 = 8;
@@ -124,6 +136,16 @@ const RECONNECTION_ATTEMPTS: u8 = 8;
 /// The first reconnection attempt will be made after 2x this timeout.
 const BASE_RECONNECTION_TIMEOUT: Duration = Duration::from_secs(1);
 
+/// Maximum reconnection timeout, before jitter is applied.
+const MAX_RECONNECTION_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Maximum fraction by which a reconnection timeout is randomly lengthened or shortened, so that
+/// nodes which lost a peer at the same time don't all redial it at the same time.
+const RECONNECTION_JITTER: f64 = 0.25;
+
+/// Maximum number of outgoing connections being dialed at the same time.
+const MAX_CONCURRENT_DIALS: usize = 64;
+
 /// Interval during which to perform outgoing manager housekeeping.
 const OUTGOING_MANAGER_SWEEP_INTERVAL: Duration = Duration::from_secs(1);
 
@@ -135,6 +157,8 @@ pub struct OutgoingHandle<P> {
     #[data_size(skip)] // Unfortunately, there is no way to inspect an `UnboundedSender`.
     sender: UnboundedSender<Arc<Message<P>>>,
     peer_addr: SocketAddr,
+    /// The peer's consensus public key, if it provided one during the handshake.
+    peer_consensus_public_key: Option<PublicKey>,
 }
 
 impl<P> Display for OutgoingHandle<P> {
@@ -158,6 +182,8 @@ where
     outgoing_manager: OutgoingManager<OutgoingHandle<P>, ConnectionError>,
     /// Tracks whether a connection is symmetric or not.
     connection_symmetries: HashMap<NodeId, ConnectionSymmetry>,
+    /// Peers blocked by node ID after misbehaving.
+    blocklist: Blocklist,
 
     /// Channel signaling a shutdown of the small network.
     // Note: This channel is closed when `SmallNetwork` is dropped, signalling the receivers that
@@ -182,6 +208,11 @@ where
     /// era, so we treat the highest era we have seen as the active era.
     highest_era_seen: EraId,
 
+    /// The validators of the current and next eras.
+    ///
+    /// Used to prioritize validator peers when gossiping.
+    active_validators: HashSet<PublicKey>,
+
     /// The outgoing bandwidth limiter.
     #[data_size(skip)]
     outgoing_limiter: Box<dyn Limiter>,
@@ -191,6 +222,14 @@ where
     /// This is not incoming bandwidth but an independent resource estimate.
     #[data_size(skip)]
     incoming_limiter: Box<dyn Limiter>,
+
+    /// The number of connected peers most recently announced.
+    announced_peer_count: usize,
+
+    /// Our own address record, signed with our network identity key.
+    our_address_record: SignedAddressRecord,
+    /// The latest signed address records accepted from peers.
+    peer_address_records: PeerAddressRecords,
 }
 
 impl<REv, P> SmallNetwork<REv, P>
@@ -204,11 +243,14 @@ where
         + From<ChainspecLoaderRequest>,
 {
     /// Creates a new small network component instance.
-    #[allow(clippy::type_complexity)]
+    ///
+    /// If enabled in `cfg`, the blocklist is persisted in `storage_path`.
+    #[allow(clippy::type_complexity, clippy::too_many_arguments)]
     pub(crate) fn new<C: Into<ChainInfo>>(
         event_queue: EventQueueHandle<REv>,
         cfg: Config,
         consensus_cfg: Option<WithDir<&consensus::Config>>,
+        storage_path: &Path,
         registry: &Registry,
         small_network_identity: SmallNetworkIdentity,
         chain_info_source: C,
@@ -256,12 +298,26 @@ where
             base_timeout: BASE_RECONNECTION_TIMEOUT,
             unblock_after: BLOCKLIST_RETAIN_DURATION,
             sweep_timeout: cfg.max_addr_pending_time.into(),
+            max_backoff: MAX_RECONNECTION_TIMEOUT,
+            backoff_jitter: RECONNECTION_JITTER,
+            max_concurrent_dials: MAX_CONCURRENT_DIALS,
         });
 
+        let blocklist = Blocklist::new(
+            storage_path,
+            cfg.persist_blocklist,
+            cfg.max_blocklist_entries,
+            BLOCKLIST_RETAIN_DURATION.into(),
+            Timestamp::now(),
+        );
+
         let mut public_addr =
             utils::resolve_address(&cfg.public_address).map_err(Error::ResolveAddr)?;
 
-        let net_metrics = Arc::new(NetworkingMetrics::new(registry)?);
+        let net_metrics = Arc::new(NetworkingMetrics::new(
+            NetworkProviderKind::Small,
+            registry,
+        )?);
 
         // We can now create a listener.
         let bind_address = utils::resolve_address(&cfg.bind_address).map_err(Error::ResolveAddr)?;
@@ -289,6 +345,13 @@ where
             .map_err(Error::LoadConsensusKeys)?
             .map(|(secret_key, public_key)| ConsensusKeyPair::new(secret_key, public_key));
 
+        let our_address_record = SignedAddressRecord::new(
+            &small_network_identity.secret_key,
+            public_addr,
+            Timestamp::now().millis(),
+        )
+        .map_err(Error::SignAddressRecord)?;
+
         let context = Arc::new(NetworkContext {
             event_queue,
             our_id: NodeId::from(&small_network_identity),
@@ -318,13 +381,18 @@ where
             context,
             outgoing_manager,
             connection_symmetries: HashMap::new(),
+            blocklist,
             shutdown_sender: Some(server_shutdown_sender),
             shutdown_receiver,
             server_join_handle: Some(server_join_handle),
             net_metrics,
             highest_era_seen: EraId::new(0),
+            active_validators: HashSet::new(),
             outgoing_limiter,
             incoming_limiter,
+            announced_peer_count: 0,
+            our_address_record,
+            peer_address_records: PeerAddressRecords::default(),
         };
 
         let effect_builder = EffectBuilder::new(event_queue);
@@ -368,18 +436,53 @@ where
     }
 
     /// Queues a message to `count` random nodes on the network.
+    ///
+    /// At least `min_validators` of the chosen peers are validators where enough validators are
+    /// connected, with the remainder chosen at random from all other peers.
     fn gossip_message(
         &self,
         rng: &mut NodeRng,
         msg: Arc<Message<P>>,
         count: usize,
+        min_validators: usize,
         exclude: HashSet<NodeId>,
     ) -> HashSet<NodeId> {
-        let peer_ids = self
+        let (validator_peers, other_peers): (Vec<NodeId>, Vec<NodeId>) = self
             .outgoing_manager
             .connected_peers()
             .filter(|peer_id| !exclude.contains(peer_id))
-            .choose_multiple(rng, count);
+            .partition(|peer_id| self.is_active_validator(*peer_id));
+
+        let validator_count = min_validators.min(count);
+        let mut peer_ids = validator_peers
+            .iter()
+            .copied()
+            .choose_multiple(rng, validator_count);
+        // Validators which were excluded have presumably already been reached, so only record the
+        // round if we aren't connected to any validators at all.  Validators we aren't directly
+        // connected to will still be reached via other peers, just after more rounds of gossip.
+        if validator_count > 0
+            && peer_ids.is_empty()
+            && !self
+                .outgoing_manager
+                .connected_peers()
+                .any(|peer_id| self.is_active_validator(peer_id))
+        {
+            self.net_metrics.gossip_rounds_without_validator_peer.inc();
+            debug!(
+                our_id=%self.context.our_id,
+                "no validator peer reachable for gossiping"
+            );
+        }
+
+        #[allow(clippy::needless_collect)] // The chosen peers are extended below.
+        let unchosen_peers: Vec<NodeId> = validator_peers
+            .into_iter()
+            .filter(|peer_id| !peer_ids.contains(peer_id))
+            .chain(other_peers)
+            .collect();
+        let remaining = count - peer_ids.len();
+        peer_ids.extend(unchosen_peers.into_iter().choose_multiple(rng, remaining));
 
         if peer_ids.len() != count {
             // TODO - set this to `warn!` once we are normally testing with networks large enough to
@@ -400,6 +503,25 @@ where
         peer_ids.into_iter().collect()
     }
 
+    /// Returns the consensus public key the given peer provided during the handshake of our
+    /// outgoing connection to it, if any.
+    fn peer_consensus_public_key(&self, peer_id: NodeId) -> Option<PublicKey> {
+        self.outgoing_manager
+            .get_route(peer_id)
+            .and_then(|handle| handle.peer_consensus_public_key.clone())
+    }
+
+    /// Returns whether the given peer identified itself as one of the current or next era's
+    /// validators.
+    fn is_active_validator(&self, peer_id: NodeId) -> bool {
+        self.outgoing_manager
+            .get_route(peer_id)
+            .and_then(|handle| handle.peer_consensus_public_key.as_ref())
+            .map_or(false, |public_key| {
+                self.active_validators.contains(public_key)
+            })
+    }
+
     /// Queues a message to be sent to a specific node.
     fn send_message(&self, dest: NodeId, msg: Arc<Message<P>>) {
         // Try to send the message.
@@ -457,8 +579,16 @@ where
                 public_addr,
                 peer_id,
                 peer_consensus_public_key,
+                peer_protocol_version,
                 stream,
             } => {
+                if self.blocklist.is_blocked(&peer_id, Timestamp::now()) {
+                    // Dropping the stream closes the connection.
+                    info!("dropping incoming connection from blocked peer");
+                    self.net_metrics.rejected_blocked_peer_connections.inc();
+                    return Effects::new();
+                }
+
                 info!("new incoming connection established");
 
                 // Learn the address the peer gave us.
@@ -487,6 +617,7 @@ where
                             .create_handle(peer_id, peer_consensus_public_key),
                         self.shutdown_receiver.clone(),
                         peer_id,
+                        peer_protocol_version,
                         span.clone(),
                     )
                     .instrument(span)
@@ -505,6 +636,7 @@ where
 
     fn handle_incoming_closed(
         &mut self,
+        effect_builder: EffectBuilder<REv>,
         result: io::Result<()>,
         peer_id: Box<NodeId>,
         peer_addr: SocketAddr,
@@ -522,12 +654,17 @@ where
             }
 
             // Update the connection symmetries.
-            self.connection_symmetries
-                .entry(*peer_id)
-                .or_default()
-                .remove_incoming(peer_addr, Instant::now());
-
-            Effects::new()
+            let symmetry = self.connection_symmetries.entry(*peer_id).or_default();
+            let was_symmetric = symmetry.is_symmetric();
+            symmetry.remove_incoming(peer_addr, Instant::now());
+
+            if was_symmetric && !symmetry.is_symmetric() {
+                // The outgoing connection is still in place, so we can still look up the key.
+                let validator = self.peer_consensus_public_key(*peer_id);
+                self.connection_lost(effect_builder, *peer_id, validator)
+            } else {
+                Effects::new()
+            }
         })
     }
 
@@ -605,10 +742,23 @@ where
                 peer_consensus_public_key,
                 sink,
             } => {
+                if self.blocklist.is_blocked(&peer_id, Timestamp::now()) {
+                    // Blocking the address prevents redialing it until the address block expires,
+                    // while dropping the sink closes the connection.
+                    info!("dropping outgoing connection to blocked peer");
+                    self.net_metrics.rejected_blocked_peer_connections.inc();
+                    let requests = self.outgoing_manager.block_addr(peer_addr, now);
+                    return self.process_dial_requests(requests);
+                }
+
                 info!("new outgoing connection established");
 
                 let (sender, receiver) = mpsc::unbounded_channel();
-                let handle = OutgoingHandle { peer_addr, sender };
+                let handle = OutgoingHandle {
+                    peer_addr,
+                    sender,
+                    peer_consensus_public_key: peer_consensus_public_key.clone(),
+                };
 
                 let request = self
                     .outgoing_manager
@@ -637,6 +787,11 @@ where
                         self.outgoing_limiter
                             .create_handle(peer_id, peer_consensus_public_key),
                         self.net_metrics.queued_messages.clone(),
+                        SentPayloadsCache::new(
+                            self.cfg.max_sent_payloads_per_peer as usize,
+                            self.cfg.sent_payload_duplicate_window.into(),
+                        ),
+                        Arc::downgrade(&self.net_metrics),
                     )
                     .instrument(span)
                     .event(move |_| Event::OutgoingDropped {
@@ -652,27 +807,52 @@ where
 
     fn handle_outgoing_dropped(
         &mut self,
+        effect_builder: EffectBuilder<REv>,
         peer_id: NodeId,
         peer_addr: SocketAddr,
     ) -> Effects<Event<P>> {
+        // Look up the key before the handle is dropped along with the connection.
+        let validator = self.peer_consensus_public_key(peer_id);
         let requests = self
             .outgoing_manager
             .handle_connection_drop(peer_addr, Instant::now());
 
-        self.connection_symmetries
-            .entry(peer_id)
-            .or_default()
-            .unmark_outgoing(Instant::now());
+        let symmetry = self.connection_symmetries.entry(peer_id).or_default();
+        let was_symmetric = symmetry.is_symmetric();
+        symmetry.unmark_outgoing(Instant::now());
+        let became_asymmetric = was_symmetric && !symmetry.is_symmetric();
 
-        self.process_dial_requests(requests)
+        let mut effects = self.process_dial_requests(requests);
+        if became_asymmetric {
+            effects.extend(self.connection_lost(effect_builder, peer_id, validator));
+        }
+        effects
     }
 
     /// Gossips our public listening address, and schedules the next such gossip round.
     fn gossip_our_address(&mut self, effect_builder: EffectBuilder<REv>) -> Effects<Event<P>> {
-        let our_address = GossipedAddress::new(self.context.public_addr);
-        effect_builder
-            .announce_gossip_our_address(our_address)
-            .ignore()
+        match self.our_address_record.refreshed(
+            &self.context.secret_key,
+            self.context.public_addr,
+            Timestamp::now().millis(),
+        ) {
+            Ok(record) => self.our_address_record = record,
+            Err(error) => warn!(%error, "failed to refresh our address record"),
+        }
+
+        let mut effects = effect_builder
+            .announce_gossip_our_address(GossipedAddress::Signed(self.our_address_record))
+            .ignore();
+        if self.cfg.unsigned_address_gossip {
+            effects.extend(
+                effect_builder
+                    .announce_gossip_our_address(GossipedAddress::Unsigned(
+                        self.context.public_addr,
+                    ))
+                    .ignore(),
+            );
+        }
+        effects
     }
 
     /// Sweeps across connection symmetry, enforcing symmetrical connections.
@@ -815,6 +995,22 @@ where
         )
     }
 
+    /// Adds a peer to the blocklist, scheduling the blocklist to be saved if required.
+    fn block_peer(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        peer_id: NodeId,
+        reason: String,
+    ) -> Effects<Event<P>> {
+        if self.blocklist.block(peer_id, reason, Timestamp::now()) {
+            effect_builder
+                .set_timeout(BLOCKLIST_SAVE_DELAY)
+                .event(|_| Event::SaveBlocklist)
+        } else {
+            Effects::new()
+        }
+    }
+
     /// Emits an announcement that a connection has been completed.
     fn connection_completed(
         &self,
@@ -823,7 +1019,42 @@ where
     ) -> Effects<Event<P>> {
         trace!(num_peers = self.peers().len(), new_peer=%peer_id, "connection complete");
         self.net_metrics.peers.set(self.peers().len() as i64);
-        effect_builder.announce_new_peer(peer_id).ignore()
+        let validator = self.peer_consensus_public_key(peer_id);
+        let mut effects = effect_builder.announce_new_peer(peer_id).ignore();
+        effects.extend(
+            effect_builder
+                .announce_peer_connected(peer_id, validator)
+                .ignore(),
+        );
+        effects
+    }
+
+    /// Emits an announcement that a previously completed connection has been lost.
+    fn connection_lost(
+        &self,
+        effect_builder: EffectBuilder<REv>,
+        peer_id: NodeId,
+        validator: Option<PublicKey>,
+    ) -> Effects<Event<P>> {
+        debug!(%peer_id, ?validator, "completed connection lost");
+        effect_builder
+            .announce_peer_disconnected(peer_id, validator)
+            .ignore()
+    }
+
+    /// Announces the number of connected peers if it has changed since it was last announced.
+    fn announce_peer_count_if_changed(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+    ) -> Effects<Event<P>> {
+        let peer_count = self.peers().len();
+        if peer_count == self.announced_peer_count {
+            return Effects::new();
+        }
+        self.announced_peer_count = peer_count;
+        effect_builder
+            .announce_peer_count_changed(peer_count)
+            .ignore()
     }
 
     /// Returns the set of connected nodes.
@@ -849,10 +1080,16 @@ where
         ret
     }
 
-    /// Returns the node id of this network node.
-    #[cfg(test)]
-    pub(crate) fn node_id(&self) -> NodeId {
-        self.context.our_id
+    /// Returns the addresses waiting to be redialed after failed connection attempts.
+    pub(crate) fn dial_backoffs(&self) -> Vec<DialBackoff> {
+        self.outgoing_manager
+            .waiting_addrs(Instant::now())
+            .map(|(addr, failed_attempts, next_attempt_in)| DialBackoff {
+                address: addr.to_string(),
+                failed_attempts,
+                next_attempt_in: next_attempt_in.into(),
+            })
+            .collect()
     }
 }
 
@@ -862,6 +1099,8 @@ where
     P: Payload,
 {
     fn finalize(mut self) -> BoxFuture<'static, ()> {
+        self.blocklist.save(Timestamp::now());
+
         async move {
             // Close the shutdown socket, causing the server to exit.
             drop(self.shutdown_sender.take());
@@ -914,76 +1153,60 @@ where
                 peer_id,
                 peer_addr,
                 span,
-            } => self.handle_incoming_closed(result, peer_id, peer_addr, *span),
+            } => self.handle_incoming_closed(effect_builder, result, peer_id, peer_addr, *span),
 
             Event::OutgoingConnection { outgoing, span } => {
                 self.handle_outgoing_connection(effect_builder, *outgoing, span)
             }
 
             Event::OutgoingDropped { peer_id, peer_addr } => {
-                self.handle_outgoing_dropped(*peer_id, peer_addr)
+                self.handle_outgoing_dropped(effect_builder, *peer_id, peer_addr)
             }
 
             Event::NetworkRequest { req } => {
-                match *req {
-                    NetworkRequest::SendMessage {
-                        dest,
-                        payload,
-                        responder,
-                    } => {
-                        // We're given a message to send out.
-                        self.net_metrics.direct_message_requests.inc();
-                        self.send_message(*dest, Arc::new(Message::Payload(*payload)));
-                        responder.respond(()).ignore()
+                network_provider::handle_network_request(self, effect_builder, rng, *req)
+            }
+            Event::NetworkInfoRequest { req } => {
+                network_provider::handle_network_info_request(self, *req)
+            }
+            Event::PeerAddressReceived(gossiped_address) => {
+                match self
+                    .peer_address_records
+                    .accept(*gossiped_address, self.cfg.unsigned_address_gossip)
+                {
+                    Ok(address) => {
+                        let now = Instant::now();
+                        let requests = self.outgoing_manager.learn_addr(address, false, now);
+                        self.process_dial_requests(requests)
                     }
-                    NetworkRequest::Broadcast { payload, responder } => {
-                        // We're given a message to broadcast.
-                        self.net_metrics.broadcast_requests.inc();
-                        self.broadcast_message(Arc::new(Message::Payload(*payload)));
-                        responder.respond(()).ignore()
+                    Err(
+                        error @ AddressRejection::Stale { .. } | error @ AddressRejection::Unsigned,
+                    ) => {
+                        debug!(%gossiped_address, %error, "ignoring gossiped address");
+                        Effects::new()
                     }
-                    NetworkRequest::Gossip {
-                        payload,
-                        count,
-                        exclude,
-                        responder,
-                    } => {
-                        // We're given a message to gossip.
-                        let sent_to = self.gossip_message(
-                            rng,
-                            Arc::new(Message::Payload(*payload)),
-                            count,
-                            exclude,
-                        );
-                        responder.respond(sent_to).ignore()
+                    Err(error) => {
+                        warn!(%gossiped_address, %error, "ignoring gossiped address");
+                        Effects::new()
                     }
                 }
             }
-            Event::NetworkInfoRequest { req } => match *req {
-                NetworkInfoRequest::GetPeers { responder } => {
-                    responder.respond(self.peers()).ignore()
-                }
-            },
-            Event::PeerAddressReceived(gossiped_address) => {
-                let requests = self.outgoing_manager.learn_addr(
-                    gossiped_address.into(),
-                    false,
-                    Instant::now(),
-                );
-                self.process_dial_requests(requests)
-            }
             Event::BlocklistAnnouncement(BlocklistAnnouncement::OffenseCommitted(peer_id)) => {
-                // TODO: We do not have a proper by-node-ID blocklist, but rather only block the
-                // current outgoing address of a peer.
                 warn!(%peer_id, "adding peer to blocklist after transgression");
+                let mut effects =
+                    self.block_peer(effect_builder, *peer_id, "committed offense".to_string());
 
+                // Also block the current outgoing address of the peer, which disconnects it.
                 if let Some(addr) = self.outgoing_manager.get_addr(*peer_id) {
                     let requests = self.outgoing_manager.block_addr(addr, Instant::now());
-                    self.process_dial_requests(requests)
-                } else {
-                    // Peer got away with it, no longer an outgoing connection.
-                    Effects::new()
+                    effects.extend(self.process_dial_requests(requests));
                 }
+
+                effects
+            }
+            Event::SaveBlocklist => {
+                self.blocklist.save(Timestamp::now());
+                Effects::new()
             }
 
             Event::GossipOurAddress => {
@@ -1013,6 +1236,7 @@ where
                 let now = Instant::now();
                 let requests = self.outgoing_manager.perform_housekeeping(now);
                 let mut effects = self.process_dial_requests(requests);
+                effects.extend(self.announce_peer_count_if_changed(effect_builder));
 
                 effects.extend(
                     effect_builder
@@ -1047,6 +1271,7 @@ where
                 active_validators,
                 upcoming_validators,
             } => {
+                self.active_validators = (*active_validators).clone();
                 self.outgoing_limiter.update_validators(
                     (*active_validators).clone(),
                     (*upcoming_validators).clone(),
@@ -1059,6 +1284,71 @@ where
     }
 }
 
+impl<REv, P> NetworkProvider<REv, P> for SmallNetwork<REv, P>
+where
+    REv: ReactorEvent
+        + From<Event<P>>
+        + From<NetworkAnnouncement<NodeId, P>>
+        + From<ContractRuntimeRequest>
+        + From<StorageRequest>
+        + From<ChainspecLoaderRequest>,
+    P: Payload,
+{
+    fn node_id(&self) -> NodeId {
+        self.context.our_id
+    }
+
+    fn send_one_way_message(
+        &mut self,
+        _effect_builder: EffectBuilder<REv>,
+        dest: NodeId,
+        payload: P,
+    ) -> Effects<Event<P>> {
+        self.net_metrics.direct_message_requests.inc();
+        self.send_message(dest, Arc::new(Message::Payload(payload)));
+        Effects::new()
+    }
+
+    fn broadcast_payload(&mut self, payload: P) {
+        self.net_metrics.broadcast_requests.inc();
+        self.broadcast_message(Arc::new(Message::Payload(payload)));
+    }
+
+    fn gossip_payload(
+        &mut self,
+        rng: &mut NodeRng,
+        payload: P,
+        count: usize,
+        min_validators: usize,
+        exclude: HashSet<NodeId>,
+    ) -> HashSet<NodeId> {
+        self.gossip_message(
+            rng,
+            Arc::new(Message::Payload(payload)),
+            count,
+            min_validators,
+            exclude,
+        )
+    }
+
+    fn peers(&self) -> BTreeMap<NodeId, String> {
+        SmallNetwork::peers(self)
+    }
+
+    fn listening_addresses(&self) -> Vec<String> {
+        vec![self.context.public_addr.to_string()]
+    }
+
+    fn dial_backoffs(&self) -> Vec<DialBackoff> {
+        SmallNetwork::dial_backoffs(self)
+    }
+
+    fn request_gossip_state(&self, responder: Responder<Option<GossipState>>) -> Effects<Event<P>> {
+        // The small network doesn't use gossipsub.
+        responder.respond(None).ignore()
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum SmallNetworkIdentityError {
     #[error("could not generate TLS certificate: {0}")]
@@ -1128,7 +1418,7 @@ fn framed<P>(
     maximum_net_message_size: u32,
 ) -> FramedTransport<P>
 where
-    for<'de> P: Serialize + Deserialize<'de>,
+    P: Payload,
     for<'de> Message<P>: Serialize + Deserialize<'de>,
 {
     let length_delimited = tokio_util::codec::Framed::new(
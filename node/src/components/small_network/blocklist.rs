@@ -0,0 +1,368 @@
+//! A blocklist of misbehaving peers, keyed by node ID.
+//!
+//! Each entry records why the peer was blocked, a score counting its offenses and when the block
+//! expires.  Repeat offenders are blocked for progressively longer, up to `MAX_BLOCK_DURATION`.
+//!
+//! If persistence is enabled, the blocklist is saved to a file in the storage directory shortly
+//! after it changes and when the component shuts down, and is reloaded on startup, so that
+//! restarting the node does not forgive known misbehaving peers.  Note that node IDs are derived
+//! from the peers' TLS certificates, so a peer restarting with a new certificate is not recognized.
+
+use std::{
+    cmp,
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use datasize::DataSize;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+
+use crate::{
+    types::{NodeId, TimeDiff, Timestamp},
+    utils,
+};
+
+/// The name of the file in the storage directory to which the blocklist is saved.
+const BLOCKLIST_FILENAME: &str = "peer_blocklist.json";
+
+/// The maximum duration for which a peer is blocked, regardless of its score.
+const MAX_BLOCK_DURATION: TimeDiff = TimeDiff::from_seconds(24 * 60 * 60);
+
+/// A blocked peer.
+#[derive(Clone, DataSize, Debug, PartialEq, Serialize, Deserialize)]
+struct BlocklistEntry {
+    /// The ID of the blocked peer.
+    peer_id: NodeId,
+    /// Why the peer was most recently blocked.
+    reason: String,
+    /// The number of offenses the peer has committed while blocked.
+    score: u32,
+    /// When the block expires.
+    expires: Timestamp,
+}
+
+/// The blocklist of misbehaving peers.
+#[derive(DataSize, Debug)]
+pub(super) struct Blocklist {
+    /// The blocked peers.
+    entries: BTreeMap<NodeId, BlocklistEntry>,
+    /// The maximum number of entries to retain.
+    max_entries: usize,
+    /// The duration for which a peer is blocked after its first offense.
+    base_duration: TimeDiff,
+    /// The file to which the blocklist is saved, if persistence is enabled.
+    path: Option<PathBuf>,
+    /// Whether the blocklist has changed since it was last saved.
+    has_unsaved_changes: bool,
+}
+
+impl Blocklist {
+    /// Creates a new blocklist.
+    ///
+    /// If `persist` is `true`, any unexpired entries previously saved in `storage_path` are loaded.
+    pub(super) fn new(
+        storage_path: &Path,
+        persist: bool,
+        max_entries: u32,
+        base_duration: TimeDiff,
+        now: Timestamp,
+    ) -> Self {
+        let mut blocklist = Blocklist {
+            entries: BTreeMap::new(),
+            max_entries: max_entries as usize,
+            base_duration,
+            path: None,
+            has_unsaved_changes: false,
+        };
+
+        if persist {
+            let path = storage_path.join(BLOCKLIST_FILENAME);
+            blocklist.entries = read_entries(&path)
+                .into_iter()
+                .map(|entry| (entry.peer_id, entry))
+                .collect();
+            blocklist.purge_expired(now);
+            blocklist.evict_until_below(blocklist.max_entries.saturating_add(1));
+            info!(
+                file = %path.display(),
+                entries = blocklist.entries.len(),
+                "loaded peer blocklist"
+            );
+            blocklist.path = Some(path);
+        }
+
+        blocklist
+    }
+
+    /// Returns `true` if the given peer is currently blocked.
+    pub(super) fn is_blocked(&self, peer_id: &NodeId, now: Timestamp) -> bool {
+        self.entries
+            .get(peer_id)
+            .map_or(false, |entry| entry.expires > now)
+    }
+
+    /// Blocks the given peer, or extends its block if it is already blocked.
+    ///
+    /// Returns `true` if a save of the blocklist should be scheduled, i.e. if persistence is
+    /// enabled and no save is already due.
+    pub(super) fn block(&mut self, peer_id: NodeId, reason: String, now: Timestamp) -> bool {
+        self.purge_expired(now);
+        if !self.entries.contains_key(&peer_id) {
+            self.evict_until_below(self.max_entries);
+        }
+
+        let entry = self
+            .entries
+            .entry(peer_id)
+            .or_insert_with(|| BlocklistEntry {
+                peer_id,
+                reason: String::new(),
+                score: 0,
+                expires: now,
+            });
+        entry.reason = reason;
+        entry.score = entry.score.saturating_add(1);
+        let duration = cmp::min(
+            self.base_duration.saturating_mul(entry.score.into()),
+            MAX_BLOCK_DURATION,
+        );
+        entry.expires = cmp::max(entry.expires, now + duration);
+        debug!(%peer_id, score = entry.score, expires = %entry.expires, "blocked peer");
+
+        let save_already_due = self.has_unsaved_changes;
+        self.has_unsaved_changes = true;
+        self.path.is_some() && !save_already_due
+    }
+
+    /// Saves the blocklist, omitting any expired entries, if persistence is enabled.
+    ///
+    /// Failure to save the blocklist is logged, but otherwise ignored.
+    pub(super) fn save(&mut self, now: Timestamp) {
+        let path = match self.path.clone() {
+            Some(path) => path,
+            None => return,
+        };
+        self.purge_expired(now);
+        self.has_unsaved_changes = false;
+
+        let entries: Vec<&BlocklistEntry> = self.entries.values().collect();
+        let serialized = match serde_json::to_vec(&entries) {
+            Ok(serialized) => serialized,
+            Err(error) => {
+                warn!(%error, "failed to serialize peer blocklist");
+                return;
+            }
+        };
+        match utils::write_file(&path, serialized) {
+            Ok(()) => debug!(
+                file = %path.display(),
+                entries = entries.len(),
+                "saved peer blocklist"
+            ),
+            Err(error) => warn!(%error, "failed to save peer blocklist"),
+        }
+    }
+
+    /// Removes all expired entries.
+    fn purge_expired(&mut self, now: Timestamp) {
+        self.entries.retain(|_, entry| entry.expires > now);
+    }
+
+    /// Evicts the entries expiring soonest until there are fewer than `limit` left.
+    fn evict_until_below(&mut self, limit: usize) {
+        while !self.entries.is_empty() && self.entries.len() >= limit {
+            let soonest = self
+                .entries
+                .values()
+                .min_by_key(|entry| entry.expires)
+                .map(|entry| entry.peer_id);
+            if let Some(peer_id) = soonest {
+                debug!(%peer_id, "evicting peer from full blocklist");
+                self.entries.remove(&peer_id);
+            }
+        }
+    }
+}
+
+/// Reads the entries saved in the file at `path`.
+///
+/// Returns no entries if the file doesn't exist or can't be parsed.
+fn read_entries(path: &Path) -> Vec<BlocklistEntry> {
+    let bytes = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(error) => {
+            if path.exists() {
+                warn!(file = %path.display(), %error, "failed to read peer blocklist file");
+            }
+            return Vec::new();
+        }
+    };
+    serde_json::from_slice(&bytes).unwrap_or_else(|error| {
+        warn!(file = %path.display(), %error, "failed to parse peer blocklist file");
+        Vec::new()
+    })
+}
+
+/// Deletes the blocklist saved in the given storage directory, if any.
+///
+/// Returns `true` if a saved blocklist was found and deleted.  This must not be called while a node
+/// using the same storage directory is running, as it would overwrite the file on shutdown.
+pub fn clear_saved_blocklist(storage_path: &Path) -> io::Result<bool> {
+    match fs::remove_file(storage_path.join(BLOCKLIST_FILENAME)) {
+        Ok(()) => Ok(true),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(false),
+        Err(error) => Err(error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    const BASE_DURATION: TimeDiff = TimeDiff::from_seconds(600);
+
+    fn new_blocklist(storage_path: &Path, max_entries: u32, now: Timestamp) -> Blocklist {
+        Blocklist::new(storage_path, true, max_entries, BASE_DURATION, now)
+    }
+
+    #[test]
+    fn should_block_until_expiry() {
+        let mut rng = crate::new_rng();
+        let peer_id = NodeId::random_tls(&mut rng);
+        let other_peer_id = NodeId::random_tls(&mut rng);
+        let now = Timestamp::from(1_000_000);
+
+        let mut blocklist = Blocklist::new(Path::new("."), false, 10, BASE_DURATION, now);
+        // Persistence is disabled, so no save should be scheduled.
+        assert!(!blocklist.block(peer_id, "test".to_string(), now));
+
+        assert!(blocklist.is_blocked(&peer_id, now));
+        assert!(!blocklist.is_blocked(&other_peer_id, now));
+        assert!(blocklist.is_blocked(&peer_id, now + BASE_DURATION / 2));
+        assert!(!blocklist.is_blocked(&peer_id, now + BASE_DURATION));
+    }
+
+    #[test]
+    fn should_block_repeat_offenders_for_longer() {
+        let mut rng = crate::new_rng();
+        let peer_id = NodeId::random_tls(&mut rng);
+        let now = Timestamp::from(1_000_000);
+
+        let mut blocklist = Blocklist::new(Path::new("."), false, 10, BASE_DURATION, now);
+        blocklist.block(peer_id, "first".to_string(), now);
+        blocklist.block(peer_id, "second".to_string(), now);
+        assert!(blocklist.is_blocked(&peer_id, now + BASE_DURATION));
+        assert!(!blocklist.is_blocked(&peer_id, now + BASE_DURATION * 2));
+
+        let entry = &blocklist.entries[&peer_id];
+        assert_eq!(entry.score, 2);
+        assert_eq!(entry.reason, "second");
+
+        // The block duration should be capped.
+        for _ in 0..1000 {
+            blocklist.block(peer_id, "repeat".to_string(), now);
+        }
+        assert_eq!(
+            blocklist.entries[&peer_id].expires,
+            now + MAX_BLOCK_DURATION
+        );
+    }
+
+    #[test]
+    fn should_evict_soonest_expiring_entry_when_full() {
+        let mut rng = crate::new_rng();
+        let peer_ids: Vec<NodeId> = (0..3).map(|_| NodeId::random_tls(&mut rng)).collect();
+        let now = Timestamp::from(1_000_000);
+
+        let mut blocklist = Blocklist::new(Path::new("."), false, 2, BASE_DURATION, now);
+        blocklist.block(peer_ids[0], "test".to_string(), now);
+        blocklist.block(
+            peer_ids[1],
+            "test".to_string(),
+            now + TimeDiff::from_seconds(1),
+        );
+        blocklist.block(
+            peer_ids[2],
+            "test".to_string(),
+            now + TimeDiff::from_seconds(2),
+        );
+
+        assert_eq!(blocklist.entries.len(), 2);
+        assert!(!blocklist.is_blocked(&peer_ids[0], now));
+        assert!(blocklist.is_blocked(&peer_ids[1], now));
+        assert!(blocklist.is_blocked(&peer_ids[2], now));
+    }
+
+    #[test]
+    fn should_persist_and_purge_expired_entries_on_load() {
+        let mut rng = crate::new_rng();
+        let short_lived = NodeId::random_tls(&mut rng);
+        let long_lived = NodeId::random_tls(&mut rng);
+        let now = Timestamp::from(1_000_000);
+        let tempdir = TempDir::new().unwrap();
+
+        let mut blocklist = new_blocklist(tempdir.path(), 10, now);
+        assert!(blocklist.block(short_lived, "test".to_string(), now));
+        // A save is already due, so no further one should be scheduled.
+        assert!(!blocklist.block(long_lived, "test".to_string(), now));
+        assert!(!blocklist.block(long_lived, "test".to_string(), now));
+        blocklist.save(now);
+
+        // Once saved, a change should schedule a new save.
+        assert!(blocklist.block(long_lived, "test".to_string(), now));
+        blocklist.save(now);
+
+        // Both should be blocked after a restart.
+        let reloaded = new_blocklist(tempdir.path(), 10, now);
+        assert_eq!(reloaded.entries, blocklist.entries);
+        assert!(reloaded.is_blocked(&short_lived, now));
+        assert!(reloaded.is_blocked(&long_lived, now));
+
+        // Once the first has expired, it should be purged on load.
+        let later = now + BASE_DURATION;
+        let reloaded = new_blocklist(tempdir.path(), 10, later);
+        assert_eq!(reloaded.entries.len(), 1);
+        assert!(!reloaded.is_blocked(&short_lived, later));
+        assert!(reloaded.is_blocked(&long_lived, later));
+
+        // Loading with a smaller maximum should retain the entries expiring last.
+        let reloaded = new_blocklist(tempdir.path(), 1, now);
+        assert_eq!(reloaded.entries.len(), 1);
+        assert!(reloaded.is_blocked(&long_lived, now));
+
+        // Without persistence, nothing should be loaded.
+        let unpersisted = Blocklist::new(tempdir.path(), false, 10, BASE_DURATION, now);
+        assert!(unpersisted.entries.is_empty());
+    }
+
+    #[test]
+    fn should_ignore_malformed_file() {
+        let tempdir = TempDir::new().unwrap();
+        fs::write(tempdir.path().join(BLOCKLIST_FILENAME), "not json").unwrap();
+
+        let blocklist = new_blocklist(tempdir.path(), 10, Timestamp::from(1_000_000));
+        assert!(blocklist.entries.is_empty());
+    }
+
+    #[test]
+    fn should_clear_saved_blocklist() {
+        let mut rng = crate::new_rng();
+        let peer_id = NodeId::random_tls(&mut rng);
+        let now = Timestamp::from(1_000_000);
+        let tempdir = TempDir::new().unwrap();
+
+        assert!(!clear_saved_blocklist(tempdir.path()).unwrap());
+
+        let mut blocklist = new_blocklist(tempdir.path(), 10, now);
+        blocklist.block(peer_id, "test".to_string(), now);
+        blocklist.save(now);
+
+        assert!(clear_saved_blocklist(tempdir.path()).unwrap());
+        let reloaded = new_blocklist(tempdir.path(), 10, now);
+        assert!(!reloaded.is_blocked(&peer_id, now));
+    }
+}
@@ -5,7 +5,11 @@ use std::time::Duration;
 use datasize::DataSize;
 use serde::{Deserialize, Serialize};
 
-use crate::types::TimeDiff;
+use crate::{
+    components::{network, network_provider::NetworkProviderKind},
+    config_validation::{self, ConfigValidationError},
+    types::TimeDiff,
+};
 
 /// Default binding address.
 ///
@@ -20,10 +24,18 @@ const DEFAULT_PUBLIC_ADDRESS: &str = "127.0.0.1:0";
 /// Default interval for gossiping network addresses.
 const DEFAULT_GOSSIP_INTERVAL: Duration = Duration::from_secs(30);
 
+/// Default maximum number of entries on the blocklist.
+const DEFAULT_MAX_BLOCKLIST_ENTRIES: u32 = 1000;
+
+/// Default maximum number of recently sent messages remembered per peer.
+const DEFAULT_MAX_SENT_PAYLOADS_PER_PEER: u32 = 1000;
+
 // Default values for networking configuration:
 impl Default for Config {
     fn default() -> Self {
+        let libp2p = network::Config::default();
         Config {
+            network_provider: NetworkProviderKind::default(),
             bind_address: DEFAULT_BIND_ADDRESS.to_string(),
             public_address: DEFAULT_PUBLIC_ADDRESS.to_string(),
             known_addresses: Vec::new(),
@@ -33,15 +45,41 @@ impl Default for Config {
             max_addr_pending_time: TimeDiff::from_seconds(60),
             max_outgoing_byte_rate_non_validators: 0,
             max_incoming_message_rate_non_validators: 0,
+            persist_blocklist: false,
+            max_blocklist_entries: DEFAULT_MAX_BLOCKLIST_ENTRIES,
+            max_sent_payloads_per_peer: DEFAULT_MAX_SENT_PAYLOADS_PER_PEER,
+            sent_payload_duplicate_window: TimeDiff::from_seconds(10),
+            unsigned_address_gossip: true,
+            connection_setup_timeout: libp2p.connection_setup_timeout,
+            max_one_way_message_size: libp2p.max_one_way_message_size,
+            request_timeout: libp2p.request_timeout,
+            connection_keep_alive: libp2p.connection_keep_alive,
+            gossip_heartbeat_interval: libp2p.gossip_heartbeat_interval,
+            max_gossip_message_size: libp2p.max_gossip_message_size,
+            gossip_duplicate_cache_timeout: libp2p.gossip_duplicate_cache_timeout,
+            disconnected_peer_grace_period: libp2p.disconnected_peer_grace_period,
+            max_held_messages_per_peer: libp2p.max_held_messages_per_peer,
+            max_held_bytes_per_peer: libp2p.max_held_bytes_per_peer,
+            compress_one_way_messages: libp2p.compress_one_way_messages,
+            one_way_compression_threshold: libp2p.one_way_compression_threshold,
+            min_address_gossip_interval: libp2p.min_address_gossip_interval,
+            max_inbound_substreams_per_connection: libp2p.max_inbound_substreams_per_connection,
+            max_inbound_substreams: libp2p.max_inbound_substreams,
+            max_pending_incoming_connections: libp2p.max_pending_incoming_connections,
         }
     }
 }
 
-/// Small network configuration.
+/// Networking configuration, shared by all network providers.
+///
+/// Settings which only apply to the libp2p provider are ignored by the small network, and vice
+/// versa.
 #[derive(DataSize, Debug, Clone, Deserialize, Serialize)]
 // Disallow unknown fields to ensure config files and command-line overrides contain valid keys.
 #[serde(deny_unknown_fields)]
 pub struct Config {
+    /// The networking component to run, either "small" or "libp2p".
+    pub network_provider: NetworkProviderKind,
     /// Address to bind to.
     pub bind_address: String,
     /// Publicly advertised address, in case the node has a different external IP.
@@ -63,6 +101,128 @@ pub struct Config {
     pub max_outgoing_byte_rate_non_validators: u32,
     /// Maximum of requests answered from non-validating peers. Unlimited if 0.
     pub max_incoming_message_rate_non_validators: u32,
+    /// Whether to save the blocklist of misbehaving peers in the storage directory, so that it is
+    /// retained across restarts.
+    pub persist_blocklist: bool,
+    /// Maximum number of peers kept on the blocklist.
+    ///
+    /// Once full, the entry expiring soonest is evicted to make room for a newly blocked peer.
+    pub max_blocklist_entries: u32,
+    /// Maximum number of recently sent messages remembered per peer in order to suppress sending
+    /// identical ones again.  Suppression is disabled if 0.
+    ///
+    /// Only gossip messages are suppressed.
+    pub max_sent_payloads_per_peer: u32,
+    /// Period after sending a message to a peer during which identical messages to that peer are
+    /// suppressed.
+    pub sent_payload_duplicate_window: TimeDiff,
+    /// Whether to also gossip our address unsigned, and to dial unsigned addresses gossiped by
+    /// peers, for interoperability with nodes which don't sign their address records.
+    ///
+    /// Only intended for the transition to signed address records; once disabled, only verified
+    /// signed records are dialed.
+    pub unsigned_address_gossip: bool,
+    /// The timeout for connection setup (including upgrades) for all inbound and outbound
+    /// connections.  Only used by the libp2p provider.
+    pub connection_setup_timeout: TimeDiff,
+    /// The maximum serialized one-way message size in bytes.  Only used by the libp2p provider.
+    pub max_one_way_message_size: u32,
+    /// The timeout for inbound and outbound requests.  Only used by the libp2p provider.
+    pub request_timeout: TimeDiff,
+    /// The keep-alive timeout of idle connections.  Only used by the libp2p provider.
+    pub connection_keep_alive: TimeDiff,
+    /// Interval used for gossip heartbeats.  Only used by the libp2p provider.
+    pub gossip_heartbeat_interval: TimeDiff,
+    /// Maximum serialized gossip message size in bytes.  Only used by the libp2p provider.
+    pub max_gossip_message_size: u32,
+    /// Time for which to retain a cached gossip message ID to prevent duplicates being gossiped.
+    /// Only used by the libp2p provider.
+    pub gossip_duplicate_cache_timeout: TimeDiff,
+    /// Period after losing the final connection to a peer during which one-way messages to that
+    /// peer are held, to be sent if it reconnects.  A value of zero disables holding messages.
+    /// Only used by the libp2p provider.
+    pub disconnected_peer_grace_period: TimeDiff,
+    /// Maximum number of one-way messages held for a single recently disconnected peer.  Only
+    /// used by the libp2p provider.
+    pub max_held_messages_per_peer: u32,
+    /// Maximum total size in bytes of the one-way messages held for a single recently
+    /// disconnected peer.  Only used by the libp2p provider.
+    pub max_held_bytes_per_peer: u32,
+    /// Whether to offer zstd compression of one-way messages to peers.  Only used by the libp2p
+    /// provider.
+    pub compress_one_way_messages: bool,
+    /// Minimum serialized size in bytes of a one-way message for it to be compressed.  Only used
+    /// by the libp2p provider.
+    pub one_way_compression_threshold: u32,
+    /// Minimum interval between gossiping our addresses to peers.  Only used by the libp2p
+    /// provider.
+    pub min_address_gossip_interval: TimeDiff,
+    /// Maximum number of concurrent inbound one-way message substreams on a single connection.
+    /// Only used by the libp2p provider.
+    pub max_inbound_substreams_per_connection: u32,
+    /// Maximum number of concurrent inbound one-way message substreams across all connections.
+    /// Only used by the libp2p provider.
+    pub max_inbound_substreams: u32,
+    /// Maximum number of incoming connections being set up at any one time.  Only used by the
+    /// libp2p provider.
+    pub max_pending_incoming_connections: u32,
+}
+
+impl Config {
+    /// Checks whether the values set in the config make sense, pushing every problem found to
+    /// `errors`.
+    pub(crate) fn validate(&self, errors: &mut Vec<ConfigValidationError>) {
+        config_validation::check_address("network.bind_address", &self.bind_address, true, errors);
+        config_validation::check_address(
+            "network.public_address",
+            &self.public_address,
+            true,
+            errors,
+        );
+        for (index, address) in self.known_addresses.iter().enumerate() {
+            let field = format!("network.known_addresses[{}]", index);
+            config_validation::check_address(&field, address, false, errors);
+        }
+
+        let timeouts = [
+            ("network.gossip_interval", self.gossip_interval.into()),
+            (
+                "network.connection_setup_timeout",
+                self.connection_setup_timeout,
+            ),
+            ("network.request_timeout", self.request_timeout),
+        ];
+        for (field, timeout) in timeouts.iter() {
+            if timeout.millis() == 0 {
+                errors.push(ConfigValidationError::out_of_range(
+                    field,
+                    timeout,
+                    "greater than 0",
+                ));
+            }
+        }
+
+        if self.max_one_way_message_size == 0 {
+            errors.push(ConfigValidationError::out_of_range(
+                "network.max_one_way_message_size",
+                self.max_one_way_message_size,
+                "greater than 0",
+            ));
+        }
+
+        // Gossip messages are only size-limited separately by the libp2p provider.
+        if self.network_provider == NetworkProviderKind::Libp2p
+            && self.max_gossip_message_size > self.max_one_way_message_size
+        {
+            errors.push(ConfigValidationError::inconsistent(
+                "network.max_gossip_message_size",
+                format!(
+                    "is {}, but must not be greater than [network.max_one_way_message_size] {}",
+                    self.max_gossip_message_size, self.max_one_way_message_size
+                ),
+            ));
+        }
+    }
 }
 
 #[cfg(test)]
@@ -8,7 +8,7 @@ use thiserror::Error;
 
 use crate::{
     crypto,
-    tls::ValidationError,
+    tls::{SignatureError, ValidationError},
     utils::{LoadError, Loadable, ResolveAddressError},
 };
 
@@ -30,6 +30,13 @@ pub enum Error {
     /// Our own certificate is not valid.
     #[error("own certificate invalid")]
     OwnCertificateInvalid(#[source] ValidationError),
+    /// Failed to sign our own address record.
+    #[error("failed to sign own address record")]
+    SignAddressRecord(
+        #[serde(skip_serializing)]
+        #[source]
+        SignatureError,
+    ),
     /// Failed to create a TCP listener.
     #[error("failed to create listener on {1}")]
     ListenerCreation(
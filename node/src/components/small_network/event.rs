@@ -6,7 +6,7 @@ use std::{
     sync::Arc,
 };
 
-use casper_types::PublicKey;
+use casper_types::{ProtocolVersion, PublicKey};
 use derive_more::From;
 use futures::stream::{SplitSink, SplitStream};
 use serde::Serialize;
@@ -80,7 +80,7 @@ pub enum Event<P> {
     /// The node should gossip its own public listening address.
     GossipOurAddress,
     /// We received a peer's public listening address via gossip.
-    PeerAddressReceived(GossipedAddress),
+    PeerAddressReceived(Box<GossipedAddress>),
 
     /// We are due for a sweep of the connection symmetries.
     SweepSymmetries,
@@ -90,6 +90,8 @@ pub enum Event<P> {
     /// Blocklist announcement
     #[from]
     BlocklistAnnouncement(BlocklistAnnouncement<NodeId>),
+    /// The blocklist has changed and is due to be saved.
+    SaveBlocklist,
 
     /// Announcement from the linear chain.
     ///
@@ -146,6 +148,7 @@ impl<P: Display> Display for Event<P> {
             Event::BlocklistAnnouncement(ann) => {
                 write!(f, "handling blocklist announcement: {}", ann)
             }
+            Event::SaveBlocklist => write!(f, "save blocklist"),
             Event::SweepOutgoing => {
                 write!(f, "sweep outgoing connections")
             }
@@ -201,6 +204,8 @@ pub enum IncomingConnection<P> {
         peer_id: NodeId,
         /// The public key the peer is validating with, if any.
         peer_consensus_public_key: Option<PublicKey>,
+        /// Protocol version advertised by the peer.
+        peer_protocol_version: ProtocolVersion,
         /// Stream of incoming messages. for incoming connections.
         #[serde(skip_serializing)]
         stream: SplitStream<FramedTransport<P>>,
@@ -224,12 +229,13 @@ impl<P> Display for IncomingConnection<P> {
                 public_addr,
                 peer_id,
                 peer_consensus_public_key,
+                peer_protocol_version,
                 stream: _,
             } => {
                 write!(
                     f,
-                    "connection established from {}/{}; public: {}",
-                    peer_addr, peer_id, public_addr,
+                    "connection established from {}/{}; public: {}; protocol version: {}",
+                    peer_addr, peer_id, public_addr, peer_protocol_version,
                 )?;
 
                 if let Some(public_key) = peer_consensus_public_key {
@@ -1,43 +1,547 @@
 use std::{
+    collections::HashMap,
+    convert::{Infallible, TryInto},
     fmt::{self, Display, Formatter},
     net::SocketAddr,
 };
 
 use datasize::DataSize;
+use hex_fmt::HexFmt;
+use openssl::pkey::{PKeyRef, Private};
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
-use crate::types::{Item, Tag};
+use casper_types::bytesrepr::{self, FromBytes, ToBytes};
+
+use crate::{
+    tls::{
+        self, KeyFingerprint, SignatureError, COMPRESSED_PUBLIC_KEY_LENGTH, RAW_SIGNATURE_LENGTH,
+    },
+    types::{Item, NodeId, Tag},
+};
+
+// This is inside a private module so that the generated `BigArray` does not form part of this
+// crate's public API, and hence also doesn't appear in the rustdocs.
+mod big_array {
+    use serde_big_array::big_array;
+
+    big_array! { BigArray; 67, 132, }
+}
+
+/// Prefixed to the serialized fields of a `SignedAddressRecord` before signing, so that the
+/// signature cannot be passed off as one over any other data signed with the same key.
+const SIGNATURE_DOMAIN: &[u8] = b"casper-node-signed-address-record";
+
+/// A listening address of a node, signed with the node's network identity key.
+///
+/// The public key is that of the node's TLS certificate, so the record can be verified to have
+/// been created by the node with the resulting ID.  The sequence number increases every time the
+/// node creates a new record, allowing receivers to discard stale records.
+#[derive(
+    Copy, Clone, DataSize, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug,
+)]
+pub struct SignedAddressRecord {
+    /// The compressed public key of the signing node.
+    #[serde(with = "big_array::BigArray")]
+    #[data_size(skip)]
+    public_key: [u8; COMPRESSED_PUBLIC_KEY_LENGTH],
+    /// The signing node's public listening address.
+    address: SocketAddr,
+    /// The sequence number of the record.
+    sequence: u64,
+    /// The signature over the other fields.
+    #[serde(with = "big_array::BigArray")]
+    #[data_size(skip)]
+    signature: [u8; RAW_SIGNATURE_LENGTH],
+}
+
+impl SignedAddressRecord {
+    /// Creates a new record of `address`, signed with `secret_key`.
+    pub(super) fn new(
+        secret_key: &PKeyRef<Private>,
+        address: SocketAddr,
+        sequence: u64,
+    ) -> Result<Self, SignatureError> {
+        let public_key = tls::compressed_public_key(secret_key)?;
+        let signed_fields = Self::signed_fields_to_bytes(&public_key, address, sequence)
+            .expect("should serialize address record");
+        let signature =
+            tls::sign_raw(secret_key, &[SIGNATURE_DOMAIN, &signed_fields[..]].concat())?;
+        Ok(SignedAddressRecord {
+            public_key,
+            address,
+            sequence,
+            signature,
+        })
+    }
+
+    /// Returns a record of `address`, signed with `secret_key`.
+    ///
+    /// If the address is unchanged, this record is returned as is.  Otherwise a new record is
+    /// created with a sequence number greater than this one's and no less than `min_sequence`.
+    pub(super) fn refreshed(
+        &self,
+        secret_key: &PKeyRef<Private>,
+        address: SocketAddr,
+        min_sequence: u64,
+    ) -> Result<Self, SignatureError> {
+        if address == self.address {
+            return Ok(*self);
+        }
+        let sequence = self.sequence.saturating_add(1).max(min_sequence);
+        SignedAddressRecord::new(secret_key, address, sequence)
+    }
+
+    /// Verifies the signature, returning the ID of the node which created the record.
+    pub(crate) fn verify(&self) -> Result<NodeId, SignatureError> {
+        let signed_fields =
+            Self::signed_fields_to_bytes(&self.public_key, self.address, self.sequence)
+                .expect("should serialize address record");
+        tls::verify_raw(
+            &self.public_key,
+            &[SIGNATURE_DOMAIN, &signed_fields[..]].concat(),
+            &self.signature,
+        )?;
+        Ok(NodeId::Tls(KeyFingerprint::from_compressed_public_key(
+            &self.public_key,
+        )))
+    }
+
+    /// Serializes the fields covered by the signature.
+    fn signed_fields_to_bytes(
+        public_key: &[u8; COMPRESSED_PUBLIC_KEY_LENGTH],
+        address: SocketAddr,
+        sequence: u64,
+    ) -> Result<Vec<u8>, bytesrepr::Error> {
+        let mut buffer = public_key.to_vec();
+        buffer.extend(address.to_string().to_bytes()?);
+        buffer.extend(sequence.to_bytes()?);
+        Ok(buffer)
+    }
+}
+
+impl Display for SignedAddressRecord {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(
+            formatter,
+            "{} (seq {}, key {:10})",
+            self.address,
+            self.sequence,
+            HexFmt(&self.public_key[..])
+        )
+    }
+}
+
+impl ToBytes for SignedAddressRecord {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        let mut buffer = bytesrepr::allocate_buffer(self)?;
+        buffer.extend(Self::signed_fields_to_bytes(
+            &self.public_key,
+            self.address,
+            self.sequence,
+        )?);
+        buffer.extend_from_slice(&self.signature);
+        Ok(buffer)
+    }
+
+    fn serialized_length(&self) -> usize {
+        COMPRESSED_PUBLIC_KEY_LENGTH
+            + self.address.to_string().serialized_length()
+            + self.sequence.serialized_length()
+            + RAW_SIGNATURE_LENGTH
+    }
+}
+
+impl FromBytes for SignedAddressRecord {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (public_key, remainder) = split_array(bytes)?;
+        let (address, remainder) = String::from_bytes(remainder)?;
+        let address = address.parse().map_err(|_| bytesrepr::Error::Formatting)?;
+        let (sequence, remainder) = u64::from_bytes(remainder)?;
+        let (signature, remainder) = split_array(remainder)?;
+        let record = SignedAddressRecord {
+            public_key,
+            address,
+            sequence,
+            signature,
+        };
+        Ok((record, remainder))
+    }
+}
+
+/// Splits a fixed-size array off the front of `bytes`.
+fn split_array<const N: usize>(bytes: &[u8]) -> Result<([u8; N], &[u8]), bytesrepr::Error> {
+    if bytes.len() < N {
+        return Err(bytesrepr::Error::EarlyEndOfStream);
+    }
+    let (array, remainder) = bytes.split_at(N);
+    Ok((
+        array.try_into().expect("should have exactly N bytes"),
+        remainder,
+    ))
+}
 
 /// Used to gossip our public listening address to peers.
+#[allow(clippy::large_enum_variant)]
 #[derive(
     Copy, Clone, DataSize, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug,
 )]
-pub struct GossipedAddress(SocketAddr);
+pub enum GossipedAddress {
+    /// An address as gossiped by nodes which don't sign their address records.
+    ///
+    /// Only gossiped and accepted for interoperability with such nodes while
+    /// `Config::unsigned_address_gossip` is enabled.
+    Unsigned(SocketAddr),
+    /// An address record signed by the node listening on the address.
+    Signed(SignedAddressRecord),
+}
 
 impl GossipedAddress {
-    pub(super) fn new(address: SocketAddr) -> Self {
-        GossipedAddress(address)
+    /// Returns whether the address is a signed record.
+    pub(crate) fn is_signed(&self) -> bool {
+        matches!(self, GossipedAddress::Signed(_))
     }
 }
 
 impl Display for GossipedAddress {
     fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
-        write!(formatter, "gossiped-address {}", self.0)
+        match self {
+            GossipedAddress::Unsigned(address) => {
+                write!(formatter, "gossiped-address {} (unsigned)", address)
+            }
+            GossipedAddress::Signed(record) => write!(formatter, "gossiped-address {}", record),
+        }
     }
 }
 
 impl Item for GossipedAddress {
     type Id = GossipedAddress;
+    type ValidationError = Infallible;
     const TAG: Tag = Tag::GossipedAddress;
-    const ID_IS_COMPLETE_ITEM: bool = true;
 
     fn id(&self) -> Self::Id {
         *self
     }
+
+    /// The address is its own ID, so is trivially valid.  The signature of a signed record is
+    /// checked when the record is accepted.
+    fn validate(&self, _expected_id: &Self::Id) -> Result<(), Self::ValidationError> {
+        Ok(())
+    }
 }
 
-impl From<GossipedAddress> for SocketAddr {
-    fn from(gossiped_address: GossipedAddress) -> Self {
-        gossiped_address.0
+/// Reason for not dialing a gossiped address.
+#[derive(Debug, Error)]
+pub(super) enum AddressRejection {
+    /// The address is unsigned, while only signed records are accepted.
+    #[error("unsigned addresses are not accepted")]
+    Unsigned,
+    /// The record's signature is invalid.
+    #[error("invalid signature: {0}")]
+    InvalidSignature(#[from] SignatureError),
+    /// A record of the same node with a higher sequence number has already been accepted.
+    #[error("stale sequence number {sequence}, already have {latest}")]
+    Stale { sequence: u64, latest: u64 },
+    /// A different record of the same node with the same sequence number has already been
+    /// accepted.
+    #[error("conflicting record with sequence number {sequence}")]
+    Conflicting { sequence: u64 },
+}
+
+/// The latest signed address record accepted for each peer.
+#[derive(DataSize, Debug, Default)]
+pub(super) struct PeerAddressRecords {
+    latest: HashMap<NodeId, SignedAddressRecord>,
+}
+
+impl PeerAddressRecords {
+    /// Checks a gossiped address, returning the address to dial if it is accepted.
+    ///
+    /// Signed records are accepted if their signature is valid and they are not older than the
+    /// latest record accepted from the same node.  Unsigned addresses are only accepted if
+    /// `accept_unsigned` is set.
+    pub(super) fn accept(
+        &mut self,
+        gossiped_address: GossipedAddress,
+        accept_unsigned: bool,
+    ) -> Result<SocketAddr, AddressRejection> {
+        let record = match gossiped_address {
+            GossipedAddress::Unsigned(address) if accept_unsigned => return Ok(address),
+            GossipedAddress::Unsigned(_) => return Err(AddressRejection::Unsigned),
+            GossipedAddress::Signed(record) => record,
+        };
+
+        let node_id = record.verify()?;
+        if let Some(latest) = self.latest.get(&node_id) {
+            if record.sequence < latest.sequence {
+                return Err(AddressRejection::Stale {
+                    sequence: record.sequence,
+                    latest: latest.sequence,
+                });
+            }
+            if record.sequence == latest.sequence && record != *latest {
+                return Err(AddressRejection::Conflicting {
+                    sequence: record.sequence,
+                });
+            }
+        }
+        self.latest.insert(node_id, record);
+        Ok(record.address)
+    }
+}
+
+/// Serde support for address gossip messages in the format used by nodes which don't sign their
+/// address records, i.e. carrying a bare socket address.
+///
+/// Only unsigned addresses can be sent in this format.
+pub(crate) mod legacy_address_message {
+    use std::net::SocketAddr;
+
+    use serde::{ser::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::GossipedAddress;
+    use crate::components::gossiper::Message;
+
+    /// The address type gossiped by nodes which don't sign their address records.
+    #[derive(Serialize, Deserialize)]
+    struct LegacyGossipedAddress(SocketAddr);
+
+    /// Mirrors `gossiper::Message<LegacyGossipedAddress>`.
+    #[derive(Serialize, Deserialize)]
+    enum LegacyMessage {
+        Gossip(LegacyGossipedAddress),
+        GossipResponse {
+            item_id: LegacyGossipedAddress,
+            is_already_held: bool,
+        },
+    }
+
+    fn to_legacy(address: GossipedAddress) -> Option<LegacyGossipedAddress> {
+        match address {
+            GossipedAddress::Unsigned(address) => Some(LegacyGossipedAddress(address)),
+            GossipedAddress::Signed(_) => None,
+        }
+    }
+
+    pub(crate) fn serialize<S: Serializer>(
+        message: &Message<GossipedAddress>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let unsupported = || S::Error::custom("cannot send a signed address in legacy format");
+        let legacy_message = match *message {
+            Message::Gossip(item_id) => {
+                LegacyMessage::Gossip(to_legacy(item_id).ok_or_else(unsupported)?)
+            }
+            Message::GossipResponse {
+                item_id,
+                is_already_held,
+            } => LegacyMessage::GossipResponse {
+                item_id: to_legacy(item_id).ok_or_else(unsupported)?,
+                is_already_held,
+            },
+        };
+        legacy_message.serialize(serializer)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Message<GossipedAddress>, D::Error> {
+        let message = match LegacyMessage::deserialize(deserializer)? {
+            LegacyMessage::Gossip(LegacyGossipedAddress(address)) => {
+                Message::Gossip(GossipedAddress::Unsigned(address))
+            }
+            LegacyMessage::GossipResponse {
+                item_id: LegacyGossipedAddress(address),
+                is_already_held,
+            } => Message::GossipResponse {
+                item_id: GossipedAddress::Unsigned(address),
+                is_already_held,
+            },
+        };
+        Ok(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use openssl::pkey::PKey;
+
+    use super::*;
+    use crate::components::gossiper;
+
+    fn new_secret_key() -> PKey<Private> {
+        let (_cert, secret_key) = tls::generate_node_cert().expect("failed to generate key");
+        secret_key
+    }
+
+    fn address(port: u16) -> SocketAddr {
+        SocketAddr::from(([127, 0, 0, 1], port))
+    }
+
+    #[test]
+    fn should_verify_signed_record() {
+        let secret_key = new_secret_key();
+        let record = SignedAddressRecord::new(&secret_key, address(34553), 1).unwrap();
+        let public_key = tls::compressed_public_key(&secret_key).unwrap();
+        assert_eq!(
+            record.verify().unwrap(),
+            NodeId::Tls(KeyFingerprint::from_compressed_public_key(&public_key))
+        );
+        bytesrepr::test_serialization_roundtrip(&record);
+    }
+
+    #[test]
+    fn should_fail_to_verify_tampered_record() {
+        let secret_key = new_secret_key();
+        let record = SignedAddressRecord::new(&secret_key, address(34553), 1).unwrap();
+
+        let spoofed_address = SignedAddressRecord {
+            address: address(1),
+            ..record
+        };
+        let bumped_sequence = SignedAddressRecord {
+            sequence: 2,
+            ..record
+        };
+        let other_key = SignedAddressRecord {
+            public_key: tls::compressed_public_key(&new_secret_key()).unwrap(),
+            ..record
+        };
+        for tampered in &[spoofed_address, bumped_sequence, other_key] {
+            assert!(matches!(
+                tampered.verify(),
+                Err(SignatureError::InvalidSignature)
+            ));
+        }
+
+        let mut records = PeerAddressRecords::default();
+        assert!(matches!(
+            records.accept(GossipedAddress::Signed(spoofed_address), true),
+            Err(AddressRejection::InvalidSignature(_))
+        ));
+    }
+
+    #[test]
+    fn should_reject_stale_and_replayed_records() {
+        let secret_key = new_secret_key();
+        let first = SignedAddressRecord::new(&secret_key, address(1), 10).unwrap();
+        let second = first.refreshed(&secret_key, address(2), 0).unwrap();
+        assert_eq!(second.sequence, 11);
+        assert_eq!(
+            second.refreshed(&secret_key, address(2), 100).unwrap(),
+            second
+        );
+        let conflicting = SignedAddressRecord::new(&secret_key, address(3), 11).unwrap();
+
+        let mut records = PeerAddressRecords::default();
+        assert_eq!(
+            records
+                .accept(GossipedAddress::Signed(second), false)
+                .unwrap(),
+            address(2)
+        );
+        // Receiving the latest record again is harmless.
+        assert_eq!(
+            records
+                .accept(GossipedAddress::Signed(second), false)
+                .unwrap(),
+            address(2)
+        );
+        assert!(matches!(
+            records.accept(GossipedAddress::Signed(first), false),
+            Err(AddressRejection::Stale {
+                sequence: 10,
+                latest: 11
+            })
+        ));
+        assert!(matches!(
+            records.accept(GossipedAddress::Signed(conflicting), false),
+            Err(AddressRejection::Conflicting { sequence: 11 })
+        ));
+
+        let third = second.refreshed(&secret_key, address(3), 100).unwrap();
+        assert_eq!(third.sequence, 100);
+        assert_eq!(
+            records
+                .accept(GossipedAddress::Signed(third), false)
+                .unwrap(),
+            address(3)
+        );
+    }
+
+    #[test]
+    fn should_accept_unsigned_address_only_if_enabled() {
+        let mut records = PeerAddressRecords::default();
+        let unsigned = GossipedAddress::Unsigned(address(34553));
+        assert_eq!(records.accept(unsigned, true).unwrap(), address(34553));
+        assert!(matches!(
+            records.accept(unsigned, false),
+            Err(AddressRejection::Unsigned)
+        ));
+    }
+
+    #[test]
+    fn legacy_message_should_match_legacy_format() {
+        // The address type as gossiped by nodes which don't sign their address records.
+        #[derive(Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize, Debug)]
+        struct LegacyGossipedAddress(SocketAddr);
+
+        impl Display for LegacyGossipedAddress {
+            fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+                write!(formatter, "gossiped-address {}", self.0)
+            }
+        }
+
+        impl Item for LegacyGossipedAddress {
+            type Id = LegacyGossipedAddress;
+            type ValidationError = Infallible;
+            const TAG: Tag = Tag::GossipedAddress;
+
+            fn id(&self) -> Self::Id {
+                *self
+            }
+
+            fn validate(&self, _expected_id: &Self::Id) -> Result<(), Self::ValidationError> {
+                Ok(())
+            }
+        }
+
+        fn serialize(message: &gossiper::Message<GossipedAddress>) -> Result<Vec<u8>, String> {
+            let mut serialized = vec![];
+            legacy_address_message::serialize(
+                message,
+                &mut rmp_serde::Serializer::new(&mut serialized),
+            )
+            .map_err(|error| error.to_string())?;
+            Ok(serialized)
+        }
+
+        let legacy_messages: Vec<gossiper::Message<LegacyGossipedAddress>> = vec![
+            gossiper::Message::Gossip(LegacyGossipedAddress(address(34553))),
+            gossiper::Message::GossipResponse {
+                item_id: LegacyGossipedAddress(address(34553)),
+                is_already_held: true,
+            },
+        ];
+        for legacy_message in legacy_messages {
+            let legacy_serialized = rmp_serde::to_vec(&legacy_message).unwrap();
+            let message = legacy_address_message::deserialize(&mut rmp_serde::Deserializer::new(
+                Cursor::new(&legacy_serialized),
+            ))
+            .unwrap();
+            let item_id = match message {
+                gossiper::Message::Gossip(item_id) => item_id,
+                gossiper::Message::GossipResponse { item_id, .. } => item_id,
+            };
+            assert_eq!(item_id, GossipedAddress::Unsigned(address(34553)));
+            assert_eq!(serialize(&message).unwrap(), legacy_serialized);
+        }
+
+        // Signed records can't be sent to nodes only understanding the legacy format.
+        let record = SignedAddressRecord::new(&new_secret_key(), address(34553), 1).unwrap();
+        let message = gossiper::Message::Gossip(GossipedAddress::Signed(record));
+        assert!(serialize(&message).is_err());
     }
 }
@@ -164,6 +164,26 @@ impl Display for MessageKind {
     }
 }
 
+impl MessageKind {
+    /// Whether an identical message of this kind may be suppressed if it was sent to the same peer
+    /// very recently.
+    ///
+    /// Only gossip is suppressed.  Consensus relies on messages being re-delivered when re-sent,
+    /// protocol messages are only sent once per connection anyway, and a fetcher re-requesting an
+    /// item from a peer, e.g. after a timeout or being told to try later, needs the request to
+    /// reach the peer.
+    pub(super) fn allows_duplicate_suppression(self) -> bool {
+        match self {
+            MessageKind::DeployGossip | MessageKind::AddressGossip => true,
+            MessageKind::Protocol
+            | MessageKind::Consensus
+            | MessageKind::DeployTransfer
+            | MessageKind::BlockTransfer
+            | MessageKind::Other => false,
+        }
+    }
+}
+
 /// Network message payload.
 ///
 /// Payloads are what is transferred across the network outside of control messages from the
@@ -197,6 +217,7 @@ mod tests {
     ///
     /// Note that the message itself may go out of sync over time as `protocol::Message` changes.
     /// The test further below ensures that the handshake is accurate in the meantime.
+    #[allow(clippy::large_enum_variant)]
     #[derive(Clone, Debug, Deserialize, Serialize)]
     pub enum V1_0_0_Message {
         Handshake {
@@ -5,8 +5,14 @@
 //! to the dependency specified in `tokio_serde`'s `Cargo.toml`.
 //!
 //! The encoder is also specialized to `Message<P>` instead of a generic payload for simplicity.
+//!
+//! Message pack encodes an enum as a single-entry map keyed by the variant index, so the payload's
+//! variant index can always be read, even if the variant itself is unknown to this node.  This
+//! allows messages from peers running a newer protocol version to be distinguished from corrupt
+//! ones.
 
 use std::{
+    fmt::{self, Formatter},
     io::{self, Cursor},
     pin::Pin,
     sync::Arc,
@@ -14,15 +20,119 @@ use std::{
 
 use bytes::{Bytes, BytesMut};
 use rmp_serde::{self};
-use serde::{Deserialize, Serialize};
+use serde::{
+    de::{self, DeserializeOwned, EnumAccess, IgnoredAny, VariantAccess, Visitor},
+    forward_to_deserialize_any, Deserialize, Serialize,
+};
+use thiserror::Error;
 use tokio_serde::{Deserializer, Serializer};
 
-use super::Message;
+use super::{Message, Payload};
 
 /// msgpack encoder/decoder for messages.
 #[derive(Debug)]
 pub struct MessagePackFormat;
 
+/// Error indicating that an incoming payload uses a variant unknown to this node, most likely as
+/// it was sent by a peer running a newer protocol version.
+#[derive(Debug, Error)]
+#[error("payload uses unknown variant {discriminant}")]
+pub(super) struct UnknownPayloadVariant {
+    /// The variant index of the payload.
+    pub(super) discriminant: u32,
+}
+
+impl UnknownPayloadVariant {
+    /// Returns the `UnknownPayloadVariant` wrapped by the given error, if any.
+    pub(super) fn from_io_error(error: &io::Error) -> Option<&Self> {
+        error.get_ref()?.downcast_ref()
+    }
+}
+
+/// Mirror of `Message` which only retains the payload's variant index.
+#[derive(Deserialize)]
+enum MessageProbe {
+    Handshake(IgnoredAny),
+    Payload(VariantIndex),
+}
+
+/// The variant index of an enum, with the variant's contents ignored.
+struct VariantIndex(u32);
+
+impl<'de> Deserialize<'de> for VariantIndex {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct VariantIndexVisitor;
+
+        impl<'de> Visitor<'de> for VariantIndexVisitor {
+            type Value = VariantIndex;
+
+            fn expecting(&self, formatter: &mut Formatter) -> fmt::Result {
+                formatter.write_str("an enum")
+            }
+
+            fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<Self::Value, A::Error> {
+                let (index, variant) = data.variant::<u32>()?;
+                variant.newtype_variant::<IgnoredAny>()?;
+                Ok(VariantIndex(index))
+            }
+        }
+
+        deserializer.deserialize_enum("", &[], VariantIndexVisitor)
+    }
+}
+
+/// Deserializer which records the number of variants of the enum it is asked to deserialize, and
+/// fails to deserialize anything.
+struct VariantCounter<'a>(&'a mut Option<u32>);
+
+impl<'de, 'a> serde::Deserializer<'de> for VariantCounter<'a> {
+    type Error = de::value::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
+        Err(de::Error::custom("not an enum"))
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        variants: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        *self.0 = Some(variants.len() as u32);
+        Err(de::Error::custom("only counting variants"))
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf option
+        unit unit_struct newtype_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+/// Returns the number of variants of `T` known to this node, or `None` if `T` is not deserialized
+/// as an enum.
+///
+/// The count is taken from the variant names passed by `T`'s `Deserialize` impl, so it can't fall
+/// out of step with the definition of `T`.
+fn known_variant_count<T: DeserializeOwned>() -> Option<u32> {
+    let mut variant_count = None;
+    let _ = T::deserialize(VariantCounter(&mut variant_count));
+    variant_count
+}
+
+/// Returns the payload's variant index if `src` holds a payload message whose variant is unknown to
+/// this node.
+fn unknown_payload_variant<P: Payload>(src: &BytesMut) -> Option<u32> {
+    let known_variant_count = known_variant_count::<P>()?;
+    match rmp_serde::from_read(Cursor::new(src)).ok()? {
+        MessageProbe::Payload(VariantIndex(discriminant))
+            if discriminant >= known_variant_count =>
+        {
+            Some(discriminant)
+        }
+        _ => None,
+    }
+}
+
 impl<P> Serializer<Arc<Message<P>>> for MessagePackFormat
 where
     Message<P>: Serialize,
@@ -41,13 +151,105 @@ where
 
 impl<P> Deserializer<Message<P>> for MessagePackFormat
 where
+    P: Payload,
     for<'de> Message<P>: Deserialize<'de>,
 {
     type Error = io::Error;
 
     #[inline]
     fn deserialize(self: Pin<&mut Self>, src: &BytesMut) -> Result<Message<P>, Self::Error> {
-        rmp_serde::from_read(Cursor::new(src))
-            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+        rmp_serde::from_read(Cursor::new(src)).map_err(|err| {
+            match unknown_payload_variant::<P>(src) {
+                Some(discriminant) => io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    UnknownPayloadVariant { discriminant },
+                ),
+                None => io::Error::new(io::ErrorKind::InvalidData, err),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::{self, Display, Formatter};
+
+    use super::*;
+    use crate::components::small_network::MessageKind;
+
+    /// A payload as known to an older node.
+    #[derive(Clone, Debug, Deserialize, Serialize)]
+    enum OldPayload {
+        Number(u8),
+        Text(String),
+    }
+
+    impl Display for OldPayload {
+        fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+            fmt::Debug::fmt(self, f)
+        }
+    }
+
+    impl Payload for OldPayload {
+        fn classify(&self) -> MessageKind {
+            MessageKind::Other
+        }
+    }
+
+    /// The same payload as known to a newer node, with an additional variant.
+    #[derive(Serialize)]
+    enum NewPayload {
+        Number(u8),
+        #[allow(dead_code)]
+        Text(String),
+        Bytes(Vec<u8>),
+    }
+
+    fn serialize<T: Serialize>(message: &Message<T>) -> BytesMut {
+        BytesMut::from(&rmp_serde::to_vec(message).unwrap()[..])
+    }
+
+    fn deserialize(src: &BytesMut) -> io::Result<Message<OldPayload>> {
+        Deserializer::deserialize(Pin::new(&mut MessagePackFormat), src)
+    }
+
+    #[test]
+    fn should_decode_known_variant_from_newer_node() {
+        let src = serialize(&Message::Payload(NewPayload::Number(7)));
+        match deserialize(&src).expect("should decode") {
+            Message::Payload(OldPayload::Number(7)) => {}
+            other => panic!("unexpected message {:?}", other),
+        }
+    }
+
+    #[test]
+    fn should_detect_unknown_variant_from_newer_node() {
+        let src = serialize(&Message::Payload(NewPayload::Bytes(vec![1, 2, 3])));
+        let error = deserialize(&src).expect_err("should fail to decode");
+        let unknown = UnknownPayloadVariant::from_io_error(&error)
+            .expect("should be detected as an unknown variant");
+        assert_eq!(unknown.discriminant, 2);
+    }
+
+    #[test]
+    fn should_not_treat_garbage_as_unknown_variant() {
+        // A known variant with an invalid body.
+        let src = serialize(&Message::Payload(NewPayload::Number(7)));
+        let mut corrupt = src.to_vec();
+        let last = corrupt.len() - 1;
+        corrupt[last] = 0xc1; // Never used in message pack.
+        let error = deserialize(&BytesMut::from(&corrupt[..])).expect_err("should fail to decode");
+        assert!(UnknownPayloadVariant::from_io_error(&error).is_none());
+
+        // Random bytes.
+        let error = deserialize(&BytesMut::from(&[0xff, 0x00, 0x13, 0x37][..]))
+            .expect_err("should fail to decode");
+        assert!(UnknownPayloadVariant::from_io_error(&error).is_none());
+    }
+
+    #[test]
+    fn should_count_known_variants_of_enums_only() {
+        assert_eq!(known_variant_count::<OldPayload>(), Some(2));
+        assert_eq!(known_variant_count::<String>(), None);
     }
 }
@@ -11,6 +11,8 @@
 //! * Handed a `SocketAddr`s via the `learn_addr` function, it will permanently maintain a
 //!   connection to the given address, only giving up if retry thresholds are exceeded, after which
 //!   it will be forgotten.
+//! * Failed dials are retried with an exponential, capped and randomly jittered backoff per
+//!   address, while the number of dials in progress at any time is limited.
 //! * `block_addr` and `redeem_addr` can be used to maintain a `SocketAddr`-keyed block list.
 //! * `OutgoingManager` maintains an internal routing table. The `get_route` function can be used to
 //!   retrieve a "route" (typically a `sync::channel` accepting network messages) to a remote peer
@@ -85,6 +87,22 @@
 //! If a conflict (multiple successful dial results) occurs, the more recent connection takes
 //! precedence over the previous one. This prevents problems when a notification of a terminated
 //! connection is overtaken by the new connection announcement.
+//!
+//! # Backoff
+//!
+//! Once a peer goes down, every node that knew it keeps redialing, and once it comes back up all of
+//! them reconnect at the same moment. To spread these retries out, the delay before redialing an
+//! address doubles with every consecutive failure up to [`OutgoingConfig::max_backoff`], and is
+//! then randomly varied by up to [`OutgoingConfig::backoff_jitter`] in either direction. A
+//! successful connection resets the failure count.
+//!
+//! Unforgettable addresses are never given up on. Once past the retry limit they keep the backoff
+//! reached at that limit rather than starting over, so they keep being retried without joining
+//! the storm of quick first retries.
+//!
+//! At most [`OutgoingConfig::max_concurrent_dials`] dials are in progress at any time. Dials
+//! beyond that are deferred: the address is put into the waiting state, due immediately, and
+//! dialed by a later housekeeping call once enough dials have finished.
 //! ```
 
 // Clippy has a lot of false positives due to `span.clone()`-closures.
@@ -101,6 +119,8 @@ use std::{
 
 use datasize::DataSize;
 
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
 use tracing::{debug, error_span, field::Empty, info, trace, warn, Span};
 
 use super::{display_error, NodeId};
@@ -132,16 +152,20 @@ where
         /// Time when the connection attempt was instantiated.
         since: Instant,
     },
-    /// The connection has failed at least one connection attempt and is waiting for a retry.
+    /// The connection has failed at least one connection attempt and is waiting for a retry, or
+    /// its dial was deferred because too many dials were in progress.
     Waiting {
         /// Number of attempts that failed, so far.
         failures_so_far: u8,
         /// The most recent connection error.
         ///
-        /// If not given, the connection was put into a `Waiting` state due to a sweep timeout.
+        /// If not given, the connection was put into a `Waiting` state due to a sweep timeout or a
+        /// deferred dial.
         error: Option<E>,
         /// The precise moment when the last connection attempt failed.
         last_failure: Instant,
+        /// The delay after `last_failure` before the address is dialed again.
+        backoff: Duration,
     },
     /// An established outgoing connection.
     Connected {
@@ -265,15 +289,29 @@ pub struct OutgoingConfig {
     pub(crate) unblock_after: Duration,
     /// Safety timeout, after which a connection is no longer expected to finish dialing.
     pub(crate) sweep_timeout: Duration,
+    /// The upper limit of the backoff, before jitter is applied.
+    pub(crate) max_backoff: Duration,
+    /// The maximum fraction of the backoff by which it is randomly lengthened or shortened.
+    #[data_size(skip)]
+    pub(crate) backoff_jitter: f64,
+    /// The maximum number of dials in progress at the same time.
+    pub(crate) max_concurrent_dials: usize,
 }
 
 impl OutgoingConfig {
     /// Calculates the backoff time.
     ///
-    /// `failed_attempts` (n) is the number of previous attempts *before* the current failure (thus
-    /// starting at 0). The backoff time will be double for each attempt.
-    fn calc_backoff(&self, failed_attempts: u8) -> Duration {
-        2u32.pow(failed_attempts as u32) * self.base_timeout
+    /// `failures_so_far` (n) is the number of consecutive failures including the current one. The
+    /// backoff time will be double for each failure, starting at twice the `base_timeout` and
+    /// capped at `max_backoff`. It is then multiplied by a random factor within
+    /// `1 ± backoff_jitter`.
+    fn calc_backoff<R: Rng>(&self, failures_so_far: u8, rng: &mut R) -> Duration {
+        let backoff = 2u32
+            .checked_pow(u32::from(failures_so_far))
+            .and_then(|factor| self.base_timeout.checked_mul(factor))
+            .map_or(self.max_backoff, |backoff| backoff.min(self.max_backoff));
+        let jitter = rng.gen_range(-self.backoff_jitter..=self.backoff_jitter);
+        backoff.mul_f64(1.0 + jitter)
     }
 }
 
@@ -295,6 +333,12 @@ where
     /// Contains a mapping from node IDs to connected socket addresses. A missing entry means that
     /// the destination is not connected.
     routes: HashMap<NodeId, SocketAddr>,
+    /// Source of randomness for the backoff jitter.
+    ///
+    /// Seeded from entropy rather than being a `NodeRng`, as tests create the manager alongside
+    /// their own `TestRng`.
+    #[data_size(skip)]
+    rng: ChaCha20Rng,
 }
 
 impl<H, E> OutgoingManager<H, E>
@@ -308,6 +352,7 @@ where
             config,
             outgoing: Default::default(),
             routes: Default::default(),
+            rng: ChaCha20Rng::from_entropy(),
         }
     }
 }
@@ -424,6 +469,72 @@ where
         self.routes.keys().cloned()
     }
 
+    /// Returns the addresses waiting to be redialed, along with their number of consecutive failed
+    /// dials and the time remaining until the next attempt.
+    #[allow(clippy::needless_lifetimes)]
+    pub(crate) fn waiting_addrs<'a>(
+        &'a self,
+        now: Instant,
+    ) -> impl Iterator<Item = (SocketAddr, u8, Duration)> + 'a {
+        self.outgoing
+            .iter()
+            .filter_map(move |(addr, outgoing)| match outgoing.state {
+                OutgoingState::Waiting {
+                    failures_so_far,
+                    last_failure,
+                    backoff,
+                    ..
+                } => Some((
+                    *addr,
+                    failures_so_far,
+                    (last_failure + backoff).saturating_duration_since(now),
+                )),
+                _ => None,
+            })
+    }
+
+    /// Returns the number of dials currently in progress.
+    fn dials_in_progress(&self) -> usize {
+        self.outgoing
+            .values()
+            .filter(|outgoing| matches!(outgoing.state, OutgoingState::Connecting { .. }))
+            .count()
+    }
+
+    /// Starts dialing an address with no failures so far, unless too many dials are in progress.
+    ///
+    /// A deferred dial puts the address into the waiting state, due immediately, for housekeeping
+    /// to dial it once enough dials have finished.
+    fn dial_or_defer(
+        &mut self,
+        addr: SocketAddr,
+        now: Instant,
+        span: Span,
+    ) -> (&mut Outgoing<H, E>, Option<DialRequest<H>>) {
+        if self.dials_in_progress() >= self.config.max_concurrent_dials {
+            debug!("deferring dial, too many dials in progress");
+            let outgoing = self.change_outgoing_state(
+                addr,
+                OutgoingState::Waiting {
+                    failures_so_far: 0,
+                    error: None,
+                    last_failure: now,
+                    backoff: Duration::from_secs(0),
+                },
+            );
+            return (outgoing, None);
+        }
+
+        let outgoing = self.change_outgoing_state(
+            addr,
+            OutgoingState::Connecting {
+                failures_so_far: 0,
+                since: now,
+            },
+        );
+        (outgoing, Some(DialRequest::Dial { addr, span }))
+    }
+
     /// Notify about a potentially new address that has been discovered.
     ///
     /// Immediately triggers the connection process to said address if it was not known before.
@@ -445,18 +556,12 @@ where
                 }
                 Entry::Vacant(_vacant) => {
                     info!("connecting to newly learned address");
-                    let outgoing = self.change_outgoing_state(
-                        addr,
-                        OutgoingState::Connecting {
-                            failures_so_far: 0,
-                            since: now,
-                        },
-                    );
+                    let (outgoing, maybe_request) = self.dial_or_defer(addr, now, span);
                     if outgoing.is_unforgettable != unforgettable {
                         outgoing.is_unforgettable = unforgettable;
                         debug!(unforgettable, "marked");
                     }
-                    Some(DialRequest::Dial { addr, span })
+                    maybe_request
                 }
             })
     }
@@ -548,29 +653,21 @@ where
                 OutgoingState::Waiting {
                     failures_so_far,
                     last_failure,
+                    backoff,
                     ..
                 } => {
-                    if failures_so_far > self.config.retry_attempts {
-                        if outgoing.is_unforgettable {
-                            // Unforgettable addresses simply have their timer reset.
-                            info!("unforgettable address reset");
-
-                            to_reconnect.push((addr, 0));
-                        } else {
-                            // Address had too many attempts at reconnection, we will forget
-                            // it after exiting this closure.
-                            to_forget.push(addr);
-
-                            info!("address forgotten");
-                        }
-                    } else {
-                        // The address has not exceeded the limit, so check if it is due.
-                        let due = last_failure + self.config.calc_backoff(failures_so_far);
-                        if now >= due {
-                            debug!(attempts = failures_so_far, "address reconnecting");
-
-                            to_reconnect.push((addr, failures_so_far));
-                        }
+                    if failures_so_far > self.config.retry_attempts && !outgoing.is_unforgettable {
+                        // Address had too many attempts at reconnection, we will forget it after
+                        // exiting this closure.
+                        to_forget.push(addr);
+
+                        info!("address forgotten");
+                    } else if now >= last_failure + backoff {
+                        debug!(attempts = failures_so_far, "address reconnecting");
+
+                        // Unforgettable addresses past the retry limit keep their backoff rather
+                        // than starting over.
+                        to_reconnect.push((addr, failures_so_far.min(self.config.retry_attempts)));
                     }
                 }
 
@@ -615,18 +712,32 @@ where
             let span = make_span(addr, self.outgoing.get(&addr));
 
             span.in_scope(|| {
+                let backoff = self.config.calc_backoff(failures_so_far, &mut self.rng);
                 self.change_outgoing_state(
                     addr,
                     OutgoingState::Waiting {
                         failures_so_far,
                         error: None,
                         last_failure: now,
+                        backoff,
                     },
                 )
             });
         });
 
-        // Reconnect all others.
+        // Reconnect all others, as far as the limit of dials in progress permits. The remaining
+        // ones stay due and are reconnected by a later call.
+        let available_dials = self
+            .config
+            .max_concurrent_dials
+            .saturating_sub(self.dials_in_progress());
+        if to_reconnect.len() > available_dials {
+            debug!(
+                due = to_reconnect.len(),
+                available_dials, "deferring reconnections, too many dials in progress"
+            );
+            to_reconnect.truncate(available_dials);
+        }
         to_reconnect
             .into_iter()
             .map(|(addr, failures_so_far)| {
@@ -713,12 +824,15 @@ where
                     1
                 };
 
+                let backoff = self.config.calc_backoff(failures_so_far, &mut self.rng);
+                debug!(?backoff, "backing off before redialing");
                 self.change_outgoing_state(
                     addr,
                     OutgoingState::Waiting {
                         failures_so_far,
                         error: Some(error),
                         last_failure: when,
+                        backoff,
                     },
                 );
                 None
@@ -754,14 +868,7 @@ where
                     }
                     OutgoingState::Connected { .. } => {
                         // Drop the handle, immediately initiate a reconnection.
-                        self.change_outgoing_state(
-                            addr,
-                            OutgoingState::Connecting {
-                                failures_so_far: 0,
-                                since: now,
-                            },
-                        );
-                        Some(DialRequest::Dial { addr, span })
+                        self.dial_or_defer(addr, now, span).1
                     }
                     OutgoingState::Blocked { .. } => {
                         // Blocked addresses ignore connection drops.
@@ -803,9 +910,39 @@ mod tests {
             base_timeout: Duration::from_secs(1),
             unblock_after: Duration::from_secs(60),
             sweep_timeout: Duration::from_secs(45),
+            max_backoff: Duration::from_secs(60),
+            backoff_jitter: 0.0,
+            max_concurrent_dials: 100,
         }
     }
 
+    /// Fails the dial to the given address.
+    fn fail_dial(
+        manager: &mut OutgoingManager<u32, TestDialerError>,
+        addr: SocketAddr,
+        clock: &TestClock,
+    ) {
+        assert!(manager
+            .handle_dial_outcome(DialOutcome::Failed {
+                addr,
+                error: TestDialerError { id: 1 },
+                when: clock.now(),
+            })
+            .is_none());
+    }
+
+    /// Returns the time remaining until the given address is dialed again, if it is waiting.
+    fn next_attempt_in(
+        manager: &OutgoingManager<u32, TestDialerError>,
+        addr: SocketAddr,
+        clock: &TestClock,
+    ) -> Option<Duration> {
+        manager
+            .waiting_addrs(clock.now())
+            .find(|(waiting_addr, _, _)| *waiting_addr == addr)
+            .map(|(_, _, next_attempt_in)| next_attempt_in)
+    }
+
     /// Helper function that checks if a given dial request actually dials the expected address.
     fn dials<'a, H, T>(expected: SocketAddr, requests: T) -> bool
     where
@@ -1038,14 +1175,29 @@ mod tests {
             },)
             .is_none());
 
-        // Only the unforgettable address should be reconnecting.
+        // Only the unforgettable address should be reconnecting, after the next longer backoff of
+        // 16 seconds.
+        assert!(manager.perform_housekeeping(clock.now()).is_empty());
+        clock.advance_time(15_999);
+        assert!(manager.perform_housekeeping(clock.now()).is_empty());
+        clock.advance_time(1);
         let requests = manager.perform_housekeeping(clock.now());
         assert!(!dials(addr_a, &requests));
         assert!(dials(addr_b, &requests));
 
-        // But not `addr_a`, even after a long wait.
-        clock.advance_time(1_000_000_000);
+        // Further failures of the unforgettable address keep it at that backoff.
+        fail_dial(&mut manager, addr_b, &clock);
+        clock.advance_time(15_999);
         assert!(manager.perform_housekeeping(clock.now()).is_empty());
+        clock.advance_time(1);
+        assert!(dials(addr_b, &manager.perform_housekeeping(clock.now())));
+        fail_dial(&mut manager, addr_b, &clock);
+
+        // But `addr_a` is not reconnected, even after a long wait.
+        clock.advance_time(1_000_000_000);
+        let requests = manager.perform_housekeeping(clock.now());
+        assert!(!dials(addr_a, &requests));
+        assert!(dials(addr_b, &requests));
     }
 
     #[test]
@@ -1282,4 +1434,162 @@ mod tests {
         // We now expect to be connected through the first connection (see documentation).
         assert_eq!(manager.get_route(id_a), Some(&1));
     }
+
+    #[test]
+    fn backoff_doubles_up_to_the_maximum() {
+        init_logging();
+
+        let mut clock = TestClock::new();
+
+        let addr_a: SocketAddr = "1.2.3.4:1234".parse().unwrap();
+
+        let mut manager = OutgoingManager::<u32, TestDialerError>::new(OutgoingConfig {
+            retry_attempts: 10,
+            max_backoff: Duration::from_secs(30),
+            ..test_config()
+        });
+
+        assert!(dials(
+            addr_a,
+            &manager.learn_addr(addr_a, true, clock.now())
+        ));
+
+        // Every failure doubles the backoff, until it is capped at 30 seconds.
+        for expected_secs in &[2, 4, 8, 16, 30, 30, 30] {
+            fail_dial(&mut manager, addr_a, &clock);
+
+            let expected = Duration::from_secs(*expected_secs);
+            assert_eq!(next_attempt_in(&manager, addr_a, &clock), Some(expected));
+
+            clock.advance_time(expected.as_millis() as u64 - 1);
+            assert!(manager.perform_housekeeping(clock.now()).is_empty());
+            clock.advance_time(1);
+            assert!(dials(addr_a, &manager.perform_housekeeping(clock.now())));
+        }
+    }
+
+    #[test]
+    fn backoff_jitter_stays_within_bounds() {
+        init_logging();
+
+        let clock = TestClock::new();
+
+        let mut manager = OutgoingManager::<u32, TestDialerError>::new(OutgoingConfig {
+            backoff_jitter: 0.25,
+            ..test_config()
+        });
+
+        // The first failure of each address has an unjittered backoff of 2 seconds.
+        let mut backoffs = Vec::new();
+        for port in 1..=200 {
+            let addr = SocketAddr::from(([1, 2, 3, 4], port));
+            assert!(dials(addr, &manager.learn_addr(addr, false, clock.now())));
+            fail_dial(&mut manager, addr, &clock);
+            backoffs.push(next_attempt_in(&manager, addr, &clock).unwrap());
+        }
+
+        for backoff in &backoffs {
+            assert!(*backoff >= Duration::from_millis(1_500), "{:?}", backoff);
+            assert!(*backoff <= Duration::from_millis(2_500), "{:?}", backoff);
+        }
+
+        // The backoffs are spread out rather than all equal.
+        assert!(backoffs.iter().any(|backoff| *backoff != backoffs[0]));
+    }
+
+    #[test]
+    fn backoff_resets_after_successful_connection() {
+        init_logging();
+
+        let mut rng = crate::new_rng();
+        let mut clock = TestClock::new();
+
+        let addr_a: SocketAddr = "1.2.3.4:1234".parse().unwrap();
+        let id_a = NodeId::random_tls(&mut rng);
+
+        let mut manager = OutgoingManager::<u32, TestDialerError>::new(test_config());
+
+        assert!(dials(
+            addr_a,
+            &manager.learn_addr(addr_a, false, clock.now())
+        ));
+
+        // Fail twice, raising the backoff to 4 seconds.
+        fail_dial(&mut manager, addr_a, &clock);
+        clock.advance_time(2_000);
+        assert!(dials(addr_a, &manager.perform_housekeeping(clock.now())));
+        fail_dial(&mut manager, addr_a, &clock);
+        assert_eq!(
+            next_attempt_in(&manager, addr_a, &clock),
+            Some(Duration::from_secs(4))
+        );
+        clock.advance_time(4_000);
+        assert!(dials(addr_a, &manager.perform_housekeeping(clock.now())));
+
+        // Connect successfully, then lose the connection again.
+        assert!(manager
+            .handle_dial_outcome(DialOutcome::Successful {
+                addr: addr_a,
+                handle: 1,
+                node_id: id_a,
+            })
+            .is_none());
+        assert_eq!(next_attempt_in(&manager, addr_a, &clock), None);
+        clock.advance_time(10_000);
+        assert!(dials(
+            addr_a,
+            &manager.handle_connection_drop(addr_a, clock.now())
+        ));
+
+        // The next failure is treated as the first one again.
+        fail_dial(&mut manager, addr_a, &clock);
+        assert_eq!(
+            next_attempt_in(&manager, addr_a, &clock),
+            Some(Duration::from_secs(2))
+        );
+    }
+
+    #[test]
+    fn concurrent_dials_are_limited() {
+        init_logging();
+
+        let mut clock = TestClock::new();
+
+        let addr_a: SocketAddr = "1.2.3.4:1234".parse().unwrap();
+        let addr_b: SocketAddr = "5.6.7.8:5678".parse().unwrap();
+        let addr_c: SocketAddr = "9.0.1.2:9012".parse().unwrap();
+
+        let mut manager = OutgoingManager::<u32, TestDialerError>::new(OutgoingConfig {
+            max_concurrent_dials: 2,
+            ..test_config()
+        });
+
+        // Only two of three addresses are dialed right away, the third one is deferred.
+        assert!(dials(
+            addr_a,
+            &manager.learn_addr(addr_a, false, clock.now())
+        ));
+        assert!(dials(
+            addr_b,
+            &manager.learn_addr(addr_b, false, clock.now())
+        ));
+        assert!(manager.learn_addr(addr_c, false, clock.now()).is_none());
+        assert_eq!(
+            next_attempt_in(&manager, addr_c, &clock),
+            Some(Duration::from_secs(0))
+        );
+        assert!(manager.perform_housekeeping(clock.now()).is_empty());
+
+        // Once a dial has finished, the deferred address is dialed.
+        fail_dial(&mut manager, addr_a, &clock);
+        let requests = manager.perform_housekeeping(clock.now());
+        assert_eq!(requests.len(), 1);
+        assert!(dials(addr_c, &requests));
+
+        // When `addr_a` is due again, no slot is free until another dial finishes.
+        clock.advance_time(2_000);
+        assert!(manager.perform_housekeeping(clock.now()).is_empty());
+        fail_dial(&mut manager, addr_b, &clock);
+        assert!(dials(addr_a, &manager.perform_housekeeping(clock.now())));
+    }
 }
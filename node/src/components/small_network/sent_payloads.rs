@@ -0,0 +1,121 @@
+//! A per-peer cache of recently sent message contents.
+//!
+//! The same payload can reach the send path for a peer several times in quick succession, e.g.
+//! when it is both gossiped and broadcast, or re-gossiped before the peer has acted on it.  Sending
+//! it again is wasted bandwidth, so each outgoing connection remembers a short hash of the
+//! serialized messages it recently sent, and skips identical ones within a configurable window.
+
+use std::time::{Duration, Instant};
+
+use blake2::{
+    digest::{Update, VariableOutput},
+    VarBlake2b,
+};
+use linked_hash_map::LinkedHashMap;
+
+/// Length in bytes of the content hash kept for each sent message.
+const CONTENT_HASH_LENGTH: usize = 8;
+
+/// A short hash of a serialized message.
+type ContentHash = [u8; CONTENT_HASH_LENGTH];
+
+/// A cache of the content hashes of messages recently sent to a single peer.
+///
+/// Once full, the least recently used entry is evicted to make room for a new one.
+#[derive(Debug)]
+pub(super) struct SentPayloadsCache {
+    capacity: usize,
+    window: Duration,
+    sent: LinkedHashMap<ContentHash, Instant>,
+}
+
+impl SentPayloadsCache {
+    /// Constructs a new cache holding at most `capacity` entries, each suppressing duplicates for
+    /// `window` after being sent.  A capacity of `0` disables suppression.
+    pub(super) fn new(capacity: usize, window: Duration) -> Self {
+        SentPayloadsCache {
+            capacity,
+            window,
+            sent: LinkedHashMap::new(),
+        }
+    }
+
+    /// Records the serialized message as being sent at `now`.
+    ///
+    /// Returns `false` if an identical message was already sent within the window, in which case
+    /// it should be suppressed rather than sent again.
+    pub(super) fn record_send(&mut self, serialized: &[u8], now: Instant) -> bool {
+        if self.capacity == 0 {
+            return true;
+        }
+
+        let hash = content_hash(serialized);
+        if let Some(sent_at) = self.sent.get_refresh(&hash) {
+            if now.saturating_duration_since(*sent_at) < self.window {
+                return false;
+            }
+        }
+
+        if self.sent.remove(&hash).is_none() && self.sent.len() >= self.capacity {
+            let _ = self.sent.pop_front();
+        }
+        let _ = self.sent.insert(hash, now);
+        true
+    }
+}
+
+/// Computes the content hash of a serialized message.
+fn content_hash(serialized: &[u8]) -> ContentHash {
+    let mut result = ContentHash::default();
+    let mut hasher = VarBlake2b::new(CONTENT_HASH_LENGTH).expect("should create hasher");
+    hasher.update(serialized);
+    hasher.finalize_variable(|slice| {
+        result.copy_from_slice(slice);
+    });
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WINDOW: Duration = Duration::from_secs(10);
+
+    #[test]
+    fn should_suppress_duplicate_within_window_only() {
+        let mut cache = SentPayloadsCache::new(10, WINDOW);
+        let start = Instant::now();
+
+        assert!(cache.record_send(b"payload", start));
+        assert!(!cache.record_send(b"payload", start + WINDOW / 2));
+        assert!(cache.record_send(b"other payload", start + WINDOW / 2));
+
+        // A suppressed duplicate doesn't extend the window.
+        assert!(cache.record_send(b"payload", start + WINDOW));
+        assert!(!cache.record_send(b"payload", start + WINDOW + WINDOW / 2));
+    }
+
+    #[test]
+    fn should_not_suppress_when_disabled() {
+        let mut cache = SentPayloadsCache::new(0, WINDOW);
+        let now = Instant::now();
+
+        assert!(cache.record_send(b"payload", now));
+        assert!(cache.record_send(b"payload", now));
+    }
+
+    #[test]
+    fn should_evict_least_recently_used() {
+        let mut cache = SentPayloadsCache::new(2, WINDOW);
+        let now = Instant::now();
+
+        assert!(cache.record_send(b"first", now));
+        assert!(cache.record_send(b"second", now));
+        // Refresh the first entry, so that adding a third evicts the second.
+        assert!(!cache.record_send(b"first", now));
+        assert!(cache.record_send(b"third", now));
+
+        assert!(!cache.record_send(b"first", now));
+        assert!(cache.record_send(b"second", now));
+    }
+}
@@ -7,14 +7,14 @@ use std::{
     net::SocketAddr,
     pin::Pin,
     sync::{Arc, Weak},
-    time::Duration,
+    time::{Duration, Instant},
 };
 
-use casper_types::PublicKey;
+use casper_types::{ProtocolVersion, PublicKey};
 use futures::{
     future::{self, Either},
-    stream::{SplitSink, SplitStream},
-    Future, SinkExt, StreamExt,
+    stream::SplitStream,
+    Future, Sink, SinkExt, StreamExt,
 };
 use openssl::{
     pkey::{PKey, Private},
@@ -41,6 +41,8 @@ use super::{
     framed,
     limiter::LimiterHandle,
     message::ConsensusKeyPair,
+    message_pack_format::UnknownPayloadVariant,
+    sent_payloads::SentPayloadsCache,
     Event, FramedTransport, Message, Payload, Transport,
 };
 use crate::{
@@ -133,7 +135,7 @@ where
 
     // Negotiate the handshake, concluding the incoming connection process.
     match negotiate_handshake(&context, &mut transport, connection_id).await {
-        Ok((public_addr, peer_consensus_public_key)) => {
+        Ok((public_addr, peer_consensus_public_key, _peer_protocol_version)) => {
             if let Some(ref public_key) = peer_consensus_public_key {
                 Span::current().record("validator_id", &field::display(public_key));
             }
@@ -228,7 +230,7 @@ where
 
     // Negotiate the handshake, concluding the incoming connection process.
     match negotiate_handshake(&context, &mut transport, connection_id).await {
-        Ok((public_addr, peer_consensus_public_key)) => {
+        Ok((public_addr, peer_consensus_public_key, peer_protocol_version)) => {
             if let Some(ref public_key) = peer_consensus_public_key {
                 Span::current().record("validator_id", &field::display(public_key));
             }
@@ -241,6 +243,7 @@ where
                 public_addr,
                 peer_id,
                 peer_consensus_public_key,
+                peer_protocol_version,
                 stream,
             }
         }
@@ -318,7 +321,7 @@ async fn negotiate_handshake<P, REv>(
     context: &NetworkContext<REv>,
     transport: &mut FramedTransport<P>,
     connection_id: ConnectionId,
-) -> Result<(SocketAddr, Option<PublicKey>), ConnectionError>
+) -> Result<(SocketAddr, Option<PublicKey>, ProtocolVersion), ConnectionError>
 where
     P: Payload,
 {
@@ -358,7 +361,7 @@ where
             })
             .transpose()?;
 
-        Ok((public_addr, peer_consensus_public_key))
+        Ok((public_addr, peer_consensus_public_key, protocol_version))
     } else {
         // Received a non-handshake, this is an error.
         Err(ConnectionError::DidNotSendHandshake)
@@ -440,13 +443,15 @@ pub(super) async fn server<P, REv>(
 
 /// Network message reader.
 ///
-/// Schedules all received messages until the stream is closed or an error occurs.
+/// Schedules all received messages until the stream is closed or an error occurs.  Messages using a
+/// payload variant unknown to this node are dropped without closing the connection.
 pub(super) async fn message_reader<REv, P>(
     context: Arc<NetworkContext<REv>>,
     mut stream: SplitStream<FramedTransport<P>>,
     limiter: Box<dyn LimiterHandle>,
     mut shutdown_receiver: watch::Receiver<()>,
     peer_id: NodeId,
+    peer_protocol_version: ProtocolVersion,
     span: Span,
 ) -> io::Result<()>
 where
@@ -477,6 +482,18 @@ where
                         .await;
                 }
                 Err(err) => {
+                    if let Some(unknown) = UnknownPayloadVariant::from_io_error(&err) {
+                        info!(
+                            discriminant = unknown.discriminant,
+                            %peer_protocol_version,
+                            "dropping message with unknown payload variant"
+                        );
+                        if let Some(net_metrics) = context.net_metrics.upgrade() {
+                            net_metrics.in_count_unknown_payload_variant.inc();
+                        }
+                        continue;
+                    }
+
                     warn!(
                         err = display_error(&err),
                         "receiving message failed, closing connection"
@@ -503,23 +520,42 @@ where
 /// Network message sender.
 ///
 /// Reads from a channel and sends all messages, until the stream is closed or an error occurs.
-pub(super) async fn message_sender<P>(
+///
+/// Messages identical to one recently sent over the same connection are suppressed, unless their
+/// kind requires re-delivery.
+pub(super) async fn message_sender<P, S>(
     mut queue: UnboundedReceiver<Arc<Message<P>>>,
-    mut sink: SplitSink<FramedTransport<P>, Arc<Message<P>>>,
+    mut sink: S,
     limiter: Box<dyn LimiterHandle>,
     counter: IntGauge,
+    mut sent_payloads: SentPayloadsCache,
+    net_metrics: Weak<NetworkingMetrics>,
 ) where
     P: Payload,
+    S: Sink<Arc<Message<P>>> + Unpin,
+    S::Error: StdError,
 {
     while let Some(message) = queue.recv().await {
         counter.dec();
 
         // TODO: Refactor message sending to not use `tokio_serde` anymore to avoid duplicate
         //       serialization.
-        let estimated_wire_size = rmp_serde::to_vec(&message)
-            .as_ref()
-            .map(Vec::len)
-            .unwrap_or(0) as u32;
+        let serialized = rmp_serde::to_vec(&message);
+        let estimated_wire_size = serialized.as_ref().map(Vec::len).unwrap_or(0) as u32;
+
+        if let Ok(ref serialized) = serialized {
+            if message.classify().allows_duplicate_suppression()
+                && !sent_payloads.record_send(serialized, Instant::now())
+            {
+                trace!(%message, "suppressing duplicate outgoing message");
+                NetworkingMetrics::record_suppressed_duplicate(
+                    &net_metrics,
+                    u64::from(estimated_wire_size),
+                );
+                continue;
+            }
+        }
+
         limiter.request_allowance(estimated_wire_size).await;
 
         // We simply error-out if the sink fails, it means that our connection broke.
@@ -5,33 +5,48 @@
 
 use std::{
     collections::{HashMap, HashSet},
+    env,
     fmt::{self, Debug, Display, Formatter},
+    path::PathBuf,
+    sync::Arc,
     time::{Duration, Instant},
 };
 
 use derive_more::From;
+use futures::StreamExt;
 use pnet::datalink;
 use prometheus::Registry;
 use reactor::ReactorEvent;
 use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
 use tracing::{debug, info};
 
+use casper_types::{PublicKey, SecretKey};
+
 use super::{
-    chain_info::ChainInfo, Config, Event as SmallNetworkEvent, GossipedAddress, MessageKind,
-    Payload, SmallNetwork,
+    chain_info::ChainInfo,
+    limiter::{self, Limiter},
+    sent_payloads::SentPayloadsCache,
+    tasks, Config, Event as SmallNetworkEvent, GossipedAddress, MessageKind, Payload, SmallNetwork,
 };
 use crate::{
     components::{
+        consensus,
         gossiper::{self, Gossiper},
+        network_provider::{NetworkProvider, NetworkProviderKind},
+        networking_metrics::NetworkingMetrics,
         small_network::SmallNetworkIdentity,
         Component,
     },
+    crypto::AsymmetricKeyExt,
     effect::{
-        announcements::{ControlAnnouncement, GossiperAnnouncement, NetworkAnnouncement},
+        announcements::{
+            BlocklistAnnouncement, ControlAnnouncement, GossiperAnnouncement, NetworkAnnouncement,
+        },
         requests::{
             ChainspecLoaderRequest, ContractRuntimeRequest, NetworkRequest, StorageRequest,
         },
-        EffectBuilder, Effects,
+        EffectBuilder, EffectExt, Effects,
     },
     protocol,
     reactor::{self, EventQueueHandle, Finalize, Reactor, Runner},
@@ -40,8 +55,8 @@ use crate::{
         network::{Network, NetworkedReactor, Nodes},
         ConditionCheckReactor,
     },
-    types::NodeId,
-    utils::Source,
+    types::{NodeId, Tag, Timestamp},
+    utils::{External, Source, WithDir},
     NodeRng,
 };
 
@@ -60,6 +75,8 @@ enum Event {
     NetworkAnnouncement(#[serde(skip_serializing)] NetworkAnnouncement<NodeId, Message>),
     #[from]
     AddressGossiperAnnouncement(#[serde(skip_serializing)] GossiperAnnouncement<GossipedAddress>),
+    #[from]
+    BlocklistAnnouncement(BlocklistAnnouncement<NodeId>),
 }
 
 impl ReactorEvent for Event {
@@ -137,6 +154,26 @@ impl Payload for Message {
     }
 }
 
+/// Test reactor configuration.
+#[derive(Debug)]
+struct TestReactorConfig {
+    net: Config,
+    /// The consensus secret key with which to identify as a validator, if any.
+    secret_key: Option<Arc<SecretKey>>,
+    /// The directory in which to persist the blocklist, if enabled in `net`.
+    storage_path: PathBuf,
+}
+
+impl From<Config> for TestReactorConfig {
+    fn from(net: Config) -> Self {
+        TestReactorConfig {
+            net,
+            secret_key: None,
+            storage_path: env::temp_dir(),
+        }
+    }
+}
+
 /// Test reactor.
 ///
 /// Runs a single small network.
@@ -144,11 +181,15 @@ impl Payload for Message {
 struct TestReactor {
     net: SmallNetwork<Event, Message>,
     address_gossiper: Gossiper<GossipedAddress, Event>,
+    /// Peers announced as connected, along with their validator keys.
+    connected_peers: Vec<(NodeId, Option<PublicKey>)>,
+    /// Peers announced as disconnected, along with their validator keys.
+    disconnected_peers: Vec<(NodeId, Option<PublicKey>)>,
 }
 
 impl Reactor for TestReactor {
     type Event = Event;
-    type Config = Config;
+    type Config = TestReactorConfig;
     type Error = anyhow::Error;
 
     fn new(
@@ -158,10 +199,17 @@ impl Reactor for TestReactor {
         _rng: &mut NodeRng,
     ) -> anyhow::Result<(Self, Effects<Self::Event>)> {
         let small_network_identity = SmallNetworkIdentity::new()?;
+        let consensus_cfg = cfg.secret_key.map(|secret_key| consensus::Config {
+            secret_key_path: External::from_value(secret_key),
+            ..Default::default()
+        });
         let (net, effects) = SmallNetwork::new(
             event_queue,
-            cfg,
-            None,
+            cfg.net,
+            consensus_cfg
+                .as_ref()
+                .map(|consensus_cfg| WithDir::new(".", consensus_cfg)),
+            &cfg.storage_path,
             registry,
             small_network_identity,
             ChainInfo::create_for_testing(),
@@ -175,6 +223,8 @@ impl Reactor for TestReactor {
             TestReactor {
                 net,
                 address_gossiper,
+                connected_peers: Vec::new(),
+                disconnected_peers: Vec::new(),
             },
             reactor::wrap_effects(Event::SmallNet, effects),
         ))
@@ -226,17 +276,43 @@ impl Reactor for TestReactor {
                 // We do not care about the announcement of new peers in this test.
                 Effects::new()
             }
+            Event::NetworkAnnouncement(NetworkAnnouncement::PeerCountChanged(_)) => {
+                // We do not care about the announcement of the peer count in this test.
+                Effects::new()
+            }
+            Event::NetworkAnnouncement(NetworkAnnouncement::MessageSendFailed { .. }) => {
+                // We do not care about the announcement of failed sends in this test.
+                Effects::new()
+            }
+            Event::NetworkAnnouncement(NetworkAnnouncement::PeerConnected {
+                peer_id,
+                validator,
+            }) => {
+                self.connected_peers.push((peer_id, validator));
+                Effects::new()
+            }
+            Event::NetworkAnnouncement(NetworkAnnouncement::PeerDisconnected {
+                peer_id,
+                validator,
+            }) => {
+                self.disconnected_peers.push((peer_id, validator));
+                Effects::new()
+            }
             Event::AddressGossiperAnnouncement(GossiperAnnouncement::NewCompleteItem(
                 gossiped_address,
             )) => {
-                let reactor_event =
-                    Event::SmallNet(SmallNetworkEvent::PeerAddressReceived(gossiped_address));
+                let reactor_event = Event::SmallNet(SmallNetworkEvent::PeerAddressReceived(
+                    Box::new(gossiped_address),
+                ));
                 self.dispatch_event(effect_builder, rng, reactor_event)
             }
             Event::AddressGossiperAnnouncement(GossiperAnnouncement::FinishedGossiping(_)) => {
                 // We do not care about the announcement of gossiping finished in this test.
                 Effects::new()
             }
+            Event::BlocklistAnnouncement(ann) => {
+                self.dispatch_event(effect_builder, rng, Event::SmallNet(ann.into()))
+            }
         }
     }
 
@@ -320,12 +396,12 @@ async fn run_two_node_network_five_times() {
 
         let start = Instant::now();
         net.add_node_with_config(
-            Config::default_local_net_first_node(first_node_port),
+            Config::default_local_net_first_node(first_node_port).into(),
             &mut rng,
         )
         .await
         .unwrap();
-        net.add_node_with_config(Config::default_local_net(first_node_port), &mut rng)
+        net.add_node_with_config(Config::default_local_net(first_node_port).into(), &mut rng)
             .await
             .unwrap();
         let end = Instant::now();
@@ -387,7 +463,7 @@ async fn bind_to_real_network_interface() {
     let local_net_config = Config::new((local_addr, port).into());
 
     let mut net = Network::<TestReactor>::new();
-    net.add_node_with_config(local_net_config, &mut rng)
+    net.add_node_with_config(local_net_config.into(), &mut rng)
         .await
         .unwrap();
 
@@ -422,14 +498,14 @@ async fn check_varying_size_network_connects() {
 
         let _ = net
             .add_node_with_config(
-                Config::default_local_net_first_node(first_node_port),
+                Config::default_local_net_first_node(first_node_port).into(),
                 &mut rng,
             )
             .await
             .unwrap();
 
         for _ in 1..number_of_nodes {
-            net.add_node_with_config(Config::default_local_net(first_node_port), &mut rng)
+            net.add_node_with_config(Config::default_local_net(first_node_port).into(), &mut rng)
                 .await
                 .unwrap();
         }
@@ -475,14 +551,14 @@ async fn ensure_peers_metric_is_correct() {
 
         let _ = net
             .add_node_with_config(
-                Config::default_local_net_first_node(first_node_port),
+                Config::default_local_net_first_node(first_node_port).into(),
                 &mut rng,
             )
             .await
             .unwrap();
 
         for _ in 1..number_of_nodes {
-            net.add_node_with_config(Config::default_local_net(first_node_port), &mut rng)
+            net.add_node_with_config(Config::default_local_net(first_node_port).into(), &mut rng)
                 .await
                 .unwrap();
         }
@@ -502,3 +578,419 @@ async fn ensure_peers_metric_is_correct() {
         net.finalize().await;
     }
 }
+
+/// Check that on a network of validators and observers, gossiping which prioritizes validators
+/// reaches a validator in every round.
+#[tokio::test]
+async fn should_prioritize_validators_when_gossiping() {
+    const VALIDATOR_COUNT: usize = 2;
+    const OBSERVER_COUNT: usize = 4;
+    const GOSSIP_COUNT: usize = 3;
+    const ROUNDS: usize = 20;
+
+    init_logging();
+
+    let mut rng = crate::new_rng();
+
+    let mut net = Network::new();
+
+    // Pick a random port in the higher ranges that is likely to be unused.
+    let first_node_port = testing::unused_port_on_localhost();
+
+    let secret_keys: Vec<Arc<SecretKey>> = (0..VALIDATOR_COUNT)
+        .map(|_| Arc::new(SecretKey::random(&mut rng)))
+        .collect();
+    let validators: HashSet<PublicKey> = secret_keys
+        .iter()
+        .map(|secret_key| PublicKey::from(secret_key.as_ref()))
+        .collect();
+
+    let mut validator_ids = HashSet::new();
+    for (index, secret_key) in secret_keys.into_iter().enumerate() {
+        let net_config = if index == 0 {
+            Config::default_local_net_first_node(first_node_port)
+        } else {
+            Config::default_local_net(first_node_port)
+        };
+        let config = TestReactorConfig {
+            net: net_config,
+            secret_key: Some(secret_key),
+            storage_path: env::temp_dir(),
+        };
+        let (node_id, _) = net.add_node_with_config(config, &mut rng).await.unwrap();
+        validator_ids.insert(node_id);
+    }
+
+    let mut observer_ids = Vec::new();
+    for _ in 0..OBSERVER_COUNT {
+        let (node_id, _) = net
+            .add_node_with_config(Config::default_local_net(first_node_port).into(), &mut rng)
+            .await
+            .unwrap();
+        observer_ids.push(node_id);
+    }
+
+    // Gossip is only sent via outgoing connections, so wait until every node has one to each peer.
+    let timeout = Duration::from_secs(20);
+    net.settle_on(
+        &mut rng,
+        |nodes: &Nodes<TestReactor>| {
+            nodes.values().all(|runner| {
+                runner
+                    .reactor()
+                    .inner()
+                    .net
+                    .outgoing_manager
+                    .connected_peers()
+                    .count()
+                    == VALIDATOR_COUNT + OBSERVER_COUNT - 1
+            })
+        },
+        timeout,
+    )
+    .await;
+
+    // Let every node know the validator set, as would happen on an era change.
+    for reactor in net.reactors_mut() {
+        reactor.net.active_validators = validators.clone();
+    }
+
+    for observer_id in &observer_ids {
+        let observer = &net.nodes()[observer_id].reactor().inner().net;
+        let message = Arc::new(super::Message::Payload(Message::AddressGossiper(
+            gossiper::Message::Gossip(GossipedAddress::Signed(observer.our_address_record)),
+        )));
+
+        for _ in 0..ROUNDS {
+            let one_validator =
+                observer.gossip_message(&mut rng, message.clone(), GOSSIP_COUNT, 1, HashSet::new());
+            assert_eq!(one_validator.len(), GOSSIP_COUNT);
+            assert!(one_validator
+                .iter()
+                .any(|node_id| validator_ids.contains(node_id)));
+
+            let all_validators = observer.gossip_message(
+                &mut rng,
+                message.clone(),
+                GOSSIP_COUNT,
+                VALIDATOR_COUNT,
+                HashSet::new(),
+            );
+            assert_eq!(all_validators.len(), GOSSIP_COUNT);
+            assert!(validator_ids.is_subset(&all_validators));
+        }
+
+        assert_eq!(
+            observer
+                .net_metrics
+                .gossip_rounds_without_validator_peer
+                .get(),
+            0
+        );
+    }
+
+    // With the validators excluded, gossiping should fall back to the remaining peers without
+    // recording a round without a validator peer.
+    let observer = &net.nodes()[&observer_ids[0]].reactor().inner().net;
+    let message = Arc::new(super::Message::Payload(Message::AddressGossiper(
+        gossiper::Message::Gossip(GossipedAddress::Signed(observer.our_address_record)),
+    )));
+    let sent_to = observer.gossip_message(
+        &mut rng,
+        message.clone(),
+        GOSSIP_COUNT,
+        1,
+        validator_ids.clone(),
+    );
+    assert_eq!(sent_to.len(), GOSSIP_COUNT);
+    assert!(sent_to.is_disjoint(&validator_ids));
+    assert_eq!(
+        observer
+            .net_metrics
+            .gossip_rounds_without_validator_peer
+            .get(),
+        0
+    );
+
+    // A node which doesn't know of any validators should fall back to random selection, and record
+    // the round as having no validator peer.
+    let observer_id = observer_ids[0];
+    let observer = &mut net
+        .nodes_mut()
+        .get_mut(&observer_id)
+        .unwrap()
+        .reactor_mut()
+        .inner_mut()
+        .net;
+    observer.active_validators.clear();
+    let sent_to = observer.gossip_message(&mut rng, message, GOSSIP_COUNT, 1, HashSet::new());
+    assert_eq!(sent_to.len(), GOSSIP_COUNT);
+    assert_eq!(
+        observer
+            .net_metrics
+            .gossip_rounds_without_validator_peer
+            .get(),
+        1
+    );
+
+    net.finalize().await;
+}
+
+/// Check that a peer blocked after committing an offense remains blocked once the blocking node is
+/// restarted with the same storage directory.
+#[tokio::test]
+async fn should_keep_peer_blocked_after_restart() {
+    init_logging();
+
+    let mut rng = crate::new_rng();
+
+    let storage_dir = tempfile::tempdir().unwrap();
+
+    // Pick a random port in the higher ranges that is likely to be unused.
+    let first_node_port = testing::unused_port_on_localhost();
+
+    let first_node_config = || {
+        let mut net = Config::default_local_net_first_node(first_node_port);
+        net.persist_blocklist = true;
+        TestReactorConfig {
+            net,
+            secret_key: None,
+            storage_path: storage_dir.path().to_path_buf(),
+        }
+    };
+
+    let mut net = Network::new();
+    let (first_node_id, _) = net
+        .add_node_with_config(first_node_config(), &mut rng)
+        .await
+        .unwrap();
+    let (offender_id, _) = net
+        .add_node_with_config(Config::default_local_net(first_node_port).into(), &mut rng)
+        .await
+        .unwrap();
+
+    let timeout = Duration::from_secs(20);
+    let blocklist = HashSet::new();
+    net.settle_on(
+        &mut rng,
+        |nodes| network_is_complete(&blocklist, nodes),
+        timeout,
+    )
+    .await;
+
+    // Penalize the second node on the first.
+    net.process_injected_effect_on(&first_node_id, |effect_builder| {
+        effect_builder
+            .announce_disconnect_from_peer(offender_id)
+            .ignore()
+    })
+    .await;
+    net.settle_on(
+        &mut rng,
+        |nodes: &Nodes<TestReactor>| {
+            nodes[&first_node_id]
+                .reactor()
+                .inner()
+                .net
+                .blocklist
+                .is_blocked(&offender_id, Timestamp::now())
+        },
+        timeout,
+    )
+    .await;
+
+    // Restart the first node, which the offender will try to reconnect to.
+    let first_node = net.remove_node(&first_node_id).unwrap();
+    first_node.drain_into_inner().await.finalize().await;
+    let (restarted_node_id, _) = net
+        .add_node_with_config(first_node_config(), &mut rng)
+        .await
+        .unwrap();
+
+    net.settle_on(
+        &mut rng,
+        |nodes: &Nodes<TestReactor>| {
+            nodes[&restarted_node_id]
+                .reactor()
+                .inner()
+                .net
+                .net_metrics
+                .rejected_blocked_peer_connections
+                .get()
+                > 0
+        },
+        timeout,
+    )
+    .await;
+
+    let restarted_node = &net.nodes()[&restarted_node_id].reactor().inner().net;
+    assert!(restarted_node
+        .blocklist
+        .is_blocked(&offender_id, Timestamp::now()));
+    assert!(!restarted_node.peers().contains_key(&offender_id));
+
+    net.finalize().await;
+}
+
+/// Check that dropping a validator's connection is announced along with its validator key.
+#[tokio::test]
+async fn should_announce_validator_disconnection() {
+    init_logging();
+
+    let mut rng = crate::new_rng();
+
+    // Pick a random port in the higher ranges that is likely to be unused.
+    let first_node_port = testing::unused_port_on_localhost();
+
+    let secret_key = Arc::new(SecretKey::random(&mut rng));
+    let validator = PublicKey::from(secret_key.as_ref());
+
+    let mut net = Network::new();
+    let (observer_id, _) = net
+        .add_node_with_config(
+            Config::default_local_net_first_node(first_node_port).into(),
+            &mut rng,
+        )
+        .await
+        .unwrap();
+    let validator_config = TestReactorConfig {
+        net: Config::default_local_net(first_node_port),
+        secret_key: Some(secret_key),
+        storage_path: env::temp_dir(),
+    };
+    let (validator_id, _) = net
+        .add_node_with_config(validator_config, &mut rng)
+        .await
+        .unwrap();
+
+    let expected = (validator_id, Some(validator));
+    let timeout = Duration::from_secs(20);
+    net.settle_on(
+        &mut rng,
+        |nodes: &Nodes<TestReactor>| {
+            nodes[&observer_id]
+                .reactor()
+                .inner()
+                .connected_peers
+                .contains(&expected)
+        },
+        timeout,
+    )
+    .await;
+    assert!(net.nodes()[&observer_id]
+        .reactor()
+        .inner()
+        .disconnected_peers
+        .is_empty());
+
+    // Shut down the validator, which closes its connections to the observer.
+    let validator_node = net.remove_node(&validator_id).unwrap();
+    validator_node.drain_into_inner().await.finalize().await;
+
+    net.settle_on(
+        &mut rng,
+        |nodes: &Nodes<TestReactor>| {
+            nodes[&observer_id]
+                .reactor()
+                .inner()
+                .disconnected_peers
+                .contains(&expected)
+        },
+        timeout,
+    )
+    .await;
+
+    net.finalize().await;
+}
+
+/// Returns a gossip message for the test reactor.
+fn gossip_message() -> Message {
+    let gossiped_address = GossipedAddress::Unsigned(([127, 0, 0, 1], 34553).into());
+    Message::from(gossiper::Message::Gossip(gossiped_address))
+}
+
+/// Passes the same message twice to a message sender, pausing for `pause` in between, and returns
+/// the number of messages it transmitted along with its metrics.
+async fn send_message_twice<P: Payload>(
+    payload: P,
+    duplicate_window: Duration,
+    pause: Duration,
+) -> (usize, Arc<NetworkingMetrics>) {
+    let mut rng = crate::new_rng();
+    let registry = Registry::new();
+    let metrics = Arc::new(NetworkingMetrics::new(NetworkProviderKind::Small, &registry).unwrap());
+
+    let (queue_sender, queue) = mpsc::unbounded_channel();
+    let (sink, transmitted) = futures::channel::mpsc::unbounded();
+    let sender = tokio::spawn(tasks::message_sender(
+        queue,
+        sink,
+        limiter::Unlimited.create_handle(NodeId::random(&mut rng), None),
+        metrics.queued_messages.clone(),
+        SentPayloadsCache::new(10, duplicate_window),
+        Arc::downgrade(&metrics),
+    ));
+
+    let message = Arc::new(super::Message::Payload(payload));
+    queue_sender.send(message.clone()).unwrap();
+    tokio::time::sleep(pause).await;
+    queue_sender.send(message).unwrap();
+
+    // Closing the queue stops the sender, which in turn closes the sink.
+    drop(queue_sender);
+    sender.await.unwrap();
+    let transmitted_count = transmitted.collect::<Vec<_>>().await.len();
+
+    (transmitted_count, metrics)
+}
+
+/// Check that a message identical to one sent to the same peer within the duplicate window is
+/// suppressed.
+#[tokio::test]
+async fn should_suppress_duplicate_message_within_window() {
+    let (transmitted_count, metrics) = send_message_twice(
+        gossip_message(),
+        Duration::from_secs(10),
+        Duration::from_millis(0),
+    )
+    .await;
+
+    assert_eq!(transmitted_count, 1);
+    assert_eq!(metrics.out_count_suppressed_duplicate.get(), 1);
+    assert!(metrics.out_bytes_suppressed_duplicate.get() > 0);
+}
+
+/// Check that a message identical to one sent to the same peer before the duplicate window is sent
+/// again.
+#[tokio::test]
+async fn should_resend_duplicate_message_outside_window() {
+    let (transmitted_count, metrics) = send_message_twice(
+        gossip_message(),
+        Duration::from_millis(10),
+        Duration::from_millis(100),
+    )
+    .await;
+
+    assert_eq!(transmitted_count, 2);
+    assert_eq!(metrics.out_count_suppressed_duplicate.get(), 0);
+    assert_eq!(metrics.out_bytes_suppressed_duplicate.get(), 0);
+}
+
+/// Check that a request identical to one sent to the same peer within the duplicate window is still
+/// sent, as a fetcher may need to re-request an item from the same peer.
+#[tokio::test]
+async fn should_not_suppress_duplicate_get_request_within_window() {
+    let get_request = protocol::Message::GetRequest {
+        tag: Tag::Deploy,
+        serialized_id: vec![1, 2, 3],
+    };
+    let (transmitted_count, metrics) = send_message_twice(
+        get_request,
+        Duration::from_secs(10),
+        Duration::from_millis(0),
+    )
+    .await;
+
+    assert_eq!(transmitted_count, 2);
+    assert_eq!(metrics.out_count_suppressed_duplicate.get(), 0);
+}
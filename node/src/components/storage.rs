@@ -7,7 +7,9 @@
 //! * storing and loading deploys,
 //! * [temporary until refactored] holding `DeployMetadata` for each deploy,
 //! * holding a read-only copy of the chainspec,
-//! * keeping an index of blocks by height and
+//! * keeping an index of blocks by height,
+//! * holding the performance of validators in recent eras,
+//! * holding the changes to the validator set in each era and
 //! * [unimplemented] managing disk usage by pruning blocks and deploys from storage.
 //!
 //! Any I/O performed by the component is done on the event handling thread, this is on purpose as
@@ -37,7 +39,11 @@
 //! Corruption, temporary resource exhaustion and potential bugs.
 
 mod blob_cache;
+mod deploy_metadata;
 mod lmdb_ext;
+mod lmdb_stats;
+mod metrics;
+mod write_batch;
 
 #[cfg(test)]
 mod tests;
@@ -48,15 +54,23 @@ use std::{
     collections::{btree_map::Entry, BTreeMap, HashSet},
     fmt::{self, Display, Formatter},
     fs, io, mem,
+    ops::RangeInclusive,
     path::{Path, PathBuf},
     sync::Arc,
+    time::Instant,
 };
 
 use datasize::DataSize;
 use derive_more::From;
+use futures::{
+    future::{self, BoxFuture},
+    FutureExt,
+};
 use lmdb::{
-    Cursor, Database, DatabaseFlags, Environment, EnvironmentFlags, Transaction, WriteFlags,
+    Cursor, Database, DatabaseFlags, Environment, EnvironmentFlags, RwTransaction, Transaction,
+    WriteFlags,
 };
+use prometheus::Registry;
 use serde::{Deserialize, Serialize};
 use static_assertions::const_assert;
 #[cfg(test)]
@@ -65,12 +79,14 @@ use thiserror::Error;
 use tracing::{debug, error, info};
 
 use casper_execution_engine::shared::newtypes::Blake2bHash;
-use casper_types::{EraId, ExecutionResult, ProtocolVersion, Transfer, Transform};
+use casper_types::{EraId, ExecutionResult, ProtocolVersion, PublicKey, Transfer, Transform};
 
 use super::Component;
 #[cfg(test)]
 use crate::crypto::hash::Digest;
 use crate::{
+    chain_archive::ArchivedBlock,
+    config_validation::{self, ConfigValidationError},
     effect::{
         requests::{StateStoreRequest, StorageRequest},
         EffectBuilder, EffectExt, Effects,
@@ -78,14 +94,19 @@ use crate::{
     fatal,
     reactor::ReactorEvent,
     types::{
-        Block, BlockBody, BlockHash, BlockHeader, BlockHeaderWithMetadata, BlockSignatures, Deploy,
-        DeployHash, DeployHeader, DeployMetadata, Item, SharedObject, TimeDiff,
+        Approval, Block, BlockBody, BlockHash, BlockHeader, BlockHeaderWithMetadata,
+        BlockSignatures, Deploy, DeployApprovals, DeployHash, DeployHeader, DeployHeaderWithHash,
+        DeployMetadata, Item, SharedObject, TimeDiff, ValidatorChange, ValidatorPerformance,
     },
     utils::{display_error, WithDir},
     NodeRng,
 };
 use blob_cache::BlobCache;
+use deploy_metadata::{DeployMetadataV1, StoredFormat};
 use lmdb_ext::{LmdbExtError, TransactionExt, WriteTransactionExt};
+pub(crate) use lmdb_stats::LmdbStatsCollector;
+use metrics::StorageMetrics;
+use write_batch::{WriteBatch, MAX_WRITE_BATCH_DELAY};
 
 /// Filename for the LMDB database created by the Storage component.
 const STORAGE_DB_FILENAME: &str = "storage.lmdb";
@@ -105,8 +126,11 @@ const DEFAULT_MAX_DEPLOY_STORE_SIZE: usize = 300 * GIB;
 const DEFAULT_MAX_DEPLOY_METADATA_STORE_SIZE: usize = 300 * GIB;
 /// Default max state store size.
 const DEFAULT_MAX_STATE_STORE_SIZE: usize = 10 * GIB;
+/// Default utilization of an LMDB environment's maximum size, in percent, from which on a warning
+/// is logged.
+const DEFAULT_LMDB_UTILIZATION_WARNING_PERCENT: u8 = 85;
 /// Maximum number of allowed dbs.
-const MAX_DB_COUNT: u32 = 7;
+const MAX_DB_COUNT: u32 = 10;
 
 /// OS-specific lmdb flags.
 #[cfg(not(target_os = "macos"))]
@@ -129,6 +153,8 @@ pub enum Event {
     /// Incoming state storage request.
     #[from]
     StateStoreRequest(StateStoreRequest),
+    /// The write batch should be committed.
+    FlushWriteBatch,
 }
 
 /// A storage component initialization error.
@@ -170,6 +196,9 @@ pub enum Error {
     /// LMDB error while operating.
     #[error("internal database error: {0}")]
     InternalStorage(#[from] LmdbExtError),
+    /// Failed to register the storage metrics.
+    #[error("failed to register metrics: {0}")]
+    Metrics(#[from] prometheus::Error),
 }
 
 // We wholesale wrap lmdb errors and treat them as internal errors here.
@@ -186,6 +215,30 @@ pub struct Storage {
     /// Environment holding LMDB databases.
     #[data_size(skip)]
     env: Environment,
+    /// The databases and indices, on which all reads are performed.
+    stores: Stores,
+    /// Whether or not memory deduplication is enabled.
+    enable_mem_deduplication: bool,
+    /// Pool of loaded items.
+    deploy_cache: BlobCache<<Deploy as Item>::Id>,
+    /// Whether a flush of the write batch has been scheduled.
+    flush_scheduled: bool,
+    /// Metrics for the storage component.
+    #[data_size(skip)]
+    metrics: StorageMetrics,
+    /// Collector of the LMDB environment's statistics.
+    #[data_size(skip)]
+    lmdb_stats: LmdbStatsCollector,
+}
+
+/// The LMDB databases and in-memory indices of the storage component.
+///
+/// All read helpers are implemented on this type rather than on `Storage`, and take the transaction
+/// to read from as a parameter.  Since this type has no access to the LMDB environment, the helpers
+/// can't open transactions of their own: each entry point on `Storage` creates exactly one and
+/// passes it down, so transactions are never nested.
+#[derive(DataSize, Debug)]
+struct Stores {
     /// The block header database.
     #[data_size(skip)]
     block_header_db: Database,
@@ -198,6 +251,9 @@ pub struct Storage {
     /// The deploy database.
     #[data_size(skip)]
     deploy_db: Database,
+    /// The deploy approvals database, allowing approvals to be read without their deploy.
+    #[data_size(skip)]
+    deploy_approvals_db: Database,
     /// The deploy metadata database.
     #[data_size(skip)]
     deploy_metadata_db: Database,
@@ -207,16 +263,20 @@ pub struct Storage {
     /// The state storage database.
     #[data_size(skip)]
     state_store_db: Database,
+    /// The validator performance database, keyed by era ID and validator public key.
+    #[data_size(skip)]
+    era_performance_db: Database,
+    /// The validator changes database, keyed by the era in which the changes take effect.
+    #[data_size(skip)]
+    validator_changes_db: Database,
     /// A map of block height to block ID.
     block_height_index: BTreeMap<u64, BlockHash>,
     /// A map of era ID to switch block ID.
     switch_block_era_id_index: BTreeMap<EraId, BlockHash>,
     /// A map of deploy hashes to hashes of blocks containing them.
     deploy_hash_index: BTreeMap<DeployHash, BlockHash>,
-    /// Whether or not memory deduplication is enabled.
-    enable_mem_deduplication: bool,
-    /// Pool of loaded items.
-    deploy_cache: BlobCache<<Deploy as Item>::Id>,
+    /// Deploys and block signatures which are yet to be committed, consulted by all reads.
+    write_batch: WriteBatch,
 }
 
 impl<REv> Component<REv> for Storage
@@ -233,11 +293,22 @@ where
         event: Self::Event,
     ) -> Effects<Self::Event> {
         let result = match event {
-            Event::StorageRequest(req) => self.handle_storage_request::<REv>(req),
+            Event::StorageRequest(req) => self.handle_storage_request::<REv>(effect_builder, req),
             Event::StateStoreRequest(req) => {
                 self.handle_state_store_request::<REv>(effect_builder, req)
             }
-        };
+            Event::FlushWriteBatch => self.flush_write_batch(),
+        }
+        .and_then(|mut effects| {
+            // The scheduled flush is lost if the reactor is replaced while it is pending, so a
+            // batch is also committed once it's overdue.
+            if self.stores.write_batch.is_overdue() {
+                effects.extend(self.flush_write_batch()?);
+            }
+            self.lmdb_stats
+                .collect_if_due(&self.env, &self.stores.named_databases());
+            Ok(effects)
+        });
 
         // Any error is turned into a fatal effect, the component itself does not panic. Note that
         // we are dropping a lot of responders this way, but since we are crashing with fatal
@@ -260,6 +331,7 @@ impl Storage {
         hard_reset_to_start_of_era: Option<EraId>,
         protocol_version: ProtocolVersion,
         should_check_integrity: bool,
+        registry: &Registry,
     ) -> Result<Self, Error> {
         let config = cfg.value();
 
@@ -293,10 +365,15 @@ impl Storage {
         let block_header_db = env.create_db(Some("block_header"), DatabaseFlags::empty())?;
         let block_metadata_db = env.create_db(Some("block_metadata"), DatabaseFlags::empty())?;
         let deploy_db = env.create_db(Some("deploys"), DatabaseFlags::empty())?;
+        let deploy_approvals_db =
+            env.create_db(Some("deploy_approvals"), DatabaseFlags::empty())?;
         let deploy_metadata_db = env.create_db(Some("deploy_metadata"), DatabaseFlags::empty())?;
         let transfer_db = env.create_db(Some("transfer"), DatabaseFlags::empty())?;
         let state_store_db = env.create_db(Some("state_store"), DatabaseFlags::empty())?;
         let block_body_db = env.create_db(Some("block_body"), DatabaseFlags::empty())?;
+        let era_performance_db = env.create_db(Some("era_performance"), DatabaseFlags::empty())?;
+        let validator_changes_db =
+            env.create_db(Some("validator_changes"), DatabaseFlags::empty())?;
 
         // We now need to restore the block-height index. Log messages allow timing here.
         info!("reindexing block store");
@@ -307,6 +384,7 @@ impl Storage {
         let mut cursor = block_txn.open_rw_cursor(block_header_db)?;
 
         let mut deleted_block_hashes = HashSet::new();
+        let mut body_hashes = Vec::new();
         // Note: `iter_start` has an undocumented panic if called on an empty database. We rely on
         //       the iterator being at the start when created.
         for (raw_key, raw_val) in cursor.iter() {
@@ -336,23 +414,28 @@ impl Storage {
                 &block,
             )?;
 
-            let mut body_txn = env.begin_ro_txn()?;
-            let block_body: BlockBody = body_txn
-                .get_value(block_body_db, block.body_hash())?
+            body_hashes.push((block.hash(), *block.body_hash()));
+        }
+        drop(cursor);
+
+        // The bodies are read via the same transaction once the cursor is released, rather than
+        // opening a nested one per block.
+        for (block_hash, body_hash) in body_hashes {
+            let block_body: BlockBody = block_txn
+                .get_value(block_body_db, &body_hash)?
                 .expect("non-existent block body referred to by header");
 
             if should_check_integrity {
                 assert_eq!(
-                    *block.body_hash(),
+                    body_hash,
                     block_body.hash(),
                     "found corrupt block body in database"
                 );
             }
 
-            insert_to_deploy_index(&mut deploy_hash_index, block.hash(), &block_body)?;
+            insert_to_deploy_index(&mut deploy_hash_index, block_hash, &block_body)?;
         }
         info!("block store reindexing complete");
-        drop(cursor);
         block_txn.commit()?;
 
         let deleted_block_hashes_raw = deleted_block_hashes.iter().map(BlockHash::as_ref).collect();
@@ -369,24 +452,58 @@ impl Storage {
             &deleted_block_hashes_raw,
             should_check_integrity,
         )?;
-        initialize_deploy_metadata_db(&env, &deploy_metadata_db, &deleted_block_hashes)?;
+        initialize_deploy_metadata_db(
+            &env,
+            &deploy_metadata_db,
+            &deleted_block_hashes,
+            config.migrate_deploy_metadata,
+        )?;
 
-        Ok(Storage {
+        let storage = Storage {
             root,
             env,
-            block_header_db,
-            block_body_db,
-            block_metadata_db,
-            deploy_db,
-            deploy_metadata_db,
-            transfer_db,
-            state_store_db,
-            block_height_index,
-            switch_block_era_id_index,
-            deploy_hash_index,
+            stores: Stores {
+                block_header_db,
+                block_body_db,
+                block_metadata_db,
+                deploy_db,
+                deploy_approvals_db,
+                deploy_metadata_db,
+                transfer_db,
+                state_store_db,
+                era_performance_db,
+                validator_changes_db,
+                block_height_index,
+                switch_block_era_id_index,
+                deploy_hash_index,
+                write_batch: WriteBatch::default(),
+            },
             enable_mem_deduplication: config.enable_mem_deduplication,
             deploy_cache: BlobCache::new(config.mem_pool_prune_interval),
-        })
+            flush_scheduled: false,
+            metrics: StorageMetrics::new(registry)?,
+            lmdb_stats: LmdbStatsCollector::new(
+                "storage",
+                config.lmdb_utilization_warning_percent,
+                registry,
+            )?,
+        };
+
+        // Changes to the validator set are computed as switch blocks are stored, so if none are
+        // stored yet, those of any switch blocks stored before they were tracked are computed now.
+        let no_validator_changes_stored = {
+            let txn = storage.env.begin_ro_txn()?;
+            let mut cursor = txn.open_ro_cursor(validator_changes_db)?;
+            let is_empty = cursor.iter().next().is_none();
+            is_empty
+        };
+        if no_validator_changes_stored {
+            for &era_id in storage.stores.switch_block_era_id_index.keys() {
+                storage.write_validator_changes(era_id)?;
+            }
+        }
+
+        Ok(storage)
     }
 
     /// Handles a state store request.
@@ -407,13 +524,18 @@ impl Storage {
                 responder,
             } => {
                 let mut txn = self.env.begin_rw_txn()?;
-                txn.put(self.state_store_db, &key, &data, WriteFlags::default())?;
+                txn.put(
+                    self.stores.state_store_db,
+                    &key,
+                    &data,
+                    WriteFlags::default(),
+                )?;
                 txn.commit()?;
                 Ok(responder.respond(()).ignore())
             }
             StateStoreRequest::Load { key, responder } => {
                 let txn = self.env.begin_ro_txn()?;
-                let bytes = match txn.get(self.state_store_db, &key) {
+                let bytes = match txn.get(self.stores.state_store_db, &key) {
                     Ok(slice) => Some(slice.to_owned()),
                     Err(lmdb::Error::NotFound) => None,
                     Err(err) => return Err(err.into()),
@@ -431,7 +553,7 @@ impl Storage {
         K: AsRef<[u8]>,
     {
         let txn = self.env.begin_ro_txn()?;
-        let bytes = match txn.get(self.state_store_db, &key) {
+        let bytes = match txn.get(self.stores.state_store_db, &key) {
             Ok(slice) => Some(slice.to_owned()),
             Err(lmdb::Error::NotFound) => None,
             Err(err) => return Err(err.into()),
@@ -445,7 +567,7 @@ impl Storage {
         K: AsRef<[u8]>,
     {
         let mut txn = self.env.begin_rw_txn()?;
-        let result = match txn.del(self.state_store_db, &key, None) {
+        let result = match txn.del(self.stores.state_store_db, &key, None) {
             Ok(_) => Ok(true),
             Err(lmdb::Error::NotFound) => Ok(false),
             Err(err) => Err(err),
@@ -460,9 +582,14 @@ impl Storage {
     }
 
     /// Handles a storage request.
-    fn handle_storage_request<REv>(&mut self, req: StorageRequest) -> Result<Effects<Event>, Error>
+    fn handle_storage_request<REv>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        req: StorageRequest,
+    ) -> Result<Effects<Event>, Error>
     where
         Self: Component<REv>,
+        REv: Send,
     {
         // Note: Database IO is handled in a blocking fashion on purpose throughout this function.
         // The rationale is that long IO operations are very rare and cache misses frequent, so on
@@ -471,7 +598,7 @@ impl Storage {
             StorageRequest::PutBlock { block, responder } => {
                 let mut txn = self.env.begin_rw_txn()?;
                 if !txn.put_value(
-                    self.block_body_db,
+                    self.stores.block_body_db,
                     block.header().body_hash(),
                     block.body(),
                     true,
@@ -480,56 +607,85 @@ impl Storage {
                     txn.abort();
                     return Ok(responder.respond(false).ignore());
                 }
-                if !txn.put_value(self.block_header_db, block.hash(), block.header(), true)? {
+                if !txn.put_value(
+                    self.stores.block_header_db,
+                    block.hash(),
+                    block.header(),
+                    true,
+                )? {
                     error!("Could not insert block header for block: {}", block);
                     txn.abort();
                     return Ok(responder.respond(false).ignore());
                 }
                 txn.commit()?;
                 insert_to_block_header_indices(
-                    &mut self.block_height_index,
-                    &mut self.switch_block_era_id_index,
+                    &mut self.stores.block_height_index,
+                    &mut self.stores.switch_block_era_id_index,
                     block.header(),
                 )?;
                 insert_to_deploy_index(
-                    &mut self.deploy_hash_index,
+                    &mut self.stores.deploy_hash_index,
                     block.header().hash(),
                     block.body(),
                 )?;
+                if block.header().is_switch_block() {
+                    self.write_validator_changes(block.header().era_id())?;
+                }
                 responder.respond(true).ignore()
             }
             StorageRequest::GetBlock {
                 block_hash,
                 responder,
             } => responder
-                .respond(self.get_single_block(&mut self.env.begin_ro_txn()?, &block_hash)?)
+                .respond(
+                    self.stores
+                        .get_single_block(&mut self.env.begin_ro_txn()?, &block_hash)?,
+                )
                 .ignore(),
             StorageRequest::GetBlockHeaderAtHeight { height, responder } => responder
-                .respond(self.get_block_header_by_height(&mut self.env.begin_ro_txn()?, height)?)
+                .respond(
+                    self.stores
+                        .get_block_header_by_height(&mut self.env.begin_ro_txn()?, height)?,
+                )
                 .ignore(),
             StorageRequest::GetBlockAtHeight { height, responder } => responder
-                .respond(self.get_block_by_height(&mut self.env.begin_ro_txn()?, height)?)
+                .respond(
+                    self.stores
+                        .get_block_by_height(&mut self.env.begin_ro_txn()?, height)?,
+                )
                 .ignore(),
+            StorageRequest::GetLmdbStats { responder } => {
+                let maybe_stats = self
+                    .lmdb_stats
+                    .collect(&self.env, &self.stores.named_databases())
+                    .map_err(|error| error!(%error, "failed to collect LMDB statistics"))
+                    .ok();
+                responder.respond(maybe_stats).ignore()
+            }
             StorageRequest::GetHighestBlock { responder } => {
                 let mut txn = self.env.begin_ro_txn()?;
                 responder
-                    .respond(self.get_highest_block(&mut txn)?)
+                    .respond(self.stores.get_highest_block(&mut txn)?)
                     .ignore()
             }
             StorageRequest::GetSwitchBlockHeaderAtEraId { era_id, responder } => responder
                 .respond(
-                    self.get_switch_block_header_by_era_id(&mut self.env.begin_ro_txn()?, era_id)?,
+                    self.stores
+                        .get_switch_block_header_by_era_id(&mut self.env.begin_ro_txn()?, era_id)?,
                 )
                 .ignore(),
             StorageRequest::GetSwitchBlockAtEraId { era_id, responder } => responder
-                .respond(self.get_switch_block_by_era_id(&mut self.env.begin_ro_txn()?, era_id)?)
+                .respond(
+                    self.stores
+                        .get_switch_block_by_era_id(&mut self.env.begin_ro_txn()?, era_id)?,
+                )
                 .ignore(),
             StorageRequest::GetBlockHeaderForDeploy {
                 deploy_hash,
                 responder,
             } => {
                 responder
-                    .respond(self.get_block_header_by_deploy_hash(
+                    .respond(self.stores.get_block_header_by_deploy_hash(
                         &mut self.env.begin_ro_txn()?,
                         deploy_hash,
                     )?)
@@ -539,11 +695,13 @@ impl Storage {
                 let mut txn = self.env.begin_ro_txn()?;
                 responder
                     .respond(
-                        self.switch_block_era_id_index
+                        self.stores
+                            .switch_block_era_id_index
                             .keys()
                             .last()
                             .and_then(|&era_id| {
-                                self.get_switch_block_by_era_id(&mut txn, era_id)
+                                self.stores
+                                    .get_switch_block_by_era_id(&mut txn, era_id)
                                     .transpose()
                             })
                             .transpose()?,
@@ -557,7 +715,8 @@ impl Storage {
                 // TODO: Find a solution for efficiently retrieving the blocker header without the
                 // block. Deserialization that allows trailing bytes could be a possible solution.
                 .respond(
-                    self.get_single_block(&mut self.env.begin_ro_txn()?, &block_hash)?
+                    self.stores
+                        .get_single_block(&mut self.env.begin_ro_txn()?, &block_hash)?
                         .map(|block| block.header().clone()),
                 )
                 .ignore(),
@@ -565,30 +724,54 @@ impl Storage {
                 block_hash,
                 responder,
             } => responder
-                .respond(self.get_transfers(&mut self.env.begin_ro_txn()?, &block_hash)?)
+                .respond(
+                    self.stores
+                        .get_transfers(&mut self.env.begin_ro_txn()?, &block_hash)?,
+                )
                 .ignore(),
             StorageRequest::PutDeploy { deploy, responder } => {
-                let mut txn = self.env.begin_rw_txn()?;
-                let outcome = txn.put_value(self.deploy_db, deploy.id(), &deploy, false)?;
-                txn.commit()?;
-                responder.respond(outcome).ignore()
+                // Whether the deploy is new is decided on arrival, so that a duplicate within the
+                // same batch is reported as previously stored.
+                let is_new = self.stores.write_batch.deploy(deploy.id()).is_none()
+                    && !self
+                        .env
+                        .begin_ro_txn()?
+                        .value_exists(self.stores.deploy_db, deploy.id())?;
+                if is_new {
+                    self.stores.write_batch.put_deploy(*deploy, responder);
+                } else {
+                    self.stores.write_batch.add_response(responder, false);
+                }
+                self.on_batched_write(effect_builder)?
             }
             StorageRequest::GetDeploys {
                 deploy_hashes,
                 responder,
             } => responder
-                .respond(self.get_deploys(&mut self.env.begin_ro_txn()?, deploy_hashes.as_slice())?)
+                .respond(
+                    self.stores
+                        .get_deploys(&mut self.env.begin_ro_txn()?, deploy_hashes.as_slice())?,
+                )
                 .ignore(),
             StorageRequest::GetDeployHeaders {
                 deploy_hashes,
                 responder,
+            } => responder
+                .respond({
+                    let mut txn = self.env.begin_ro_txn()?;
+                    deploy_hashes
+                        .iter()
+                        .map(|deploy_hash| self.stores.get_deploy_header(&mut txn, deploy_hash))
+                        .collect::<Result<Vec<_>, LmdbExtError>>()?
+                })
+                .ignore(),
+            StorageRequest::GetDeployApprovals {
+                deploy_hash,
+                responder,
             } => responder
                 .respond(
-                    // TODO: Similarly to getting block headers, requires optimized function.
-                    self.get_deploys(&mut self.env.begin_ro_txn()?, deploy_hashes.as_slice())?
-                        .into_iter()
-                        .map(|opt| opt.map(|deploy| deploy.header().clone()))
-                        .collect(),
+                    self.stores
+                        .get_deploy_approvals(&mut self.env.begin_ro_txn()?, &deploy_hash)?,
                 )
                 .ignore(),
             StorageRequest::PutExecutionResults {
@@ -602,11 +785,12 @@ impl Storage {
 
                 for (deploy_hash, execution_result) in execution_results {
                     let mut metadata = self
+                        .stores
                         .get_deploy_metadata(&mut txn, &deploy_hash)?
                         .unwrap_or_default();
 
                     // If we have a previous execution result, we can continue if it is the same.
-                    if let Some(prev) = metadata.execution_results.get(&block_hash) {
+                    if let Some(prev) = metadata.execution_result(&block_hash) {
                         if prev == &execution_result {
                             continue;
                         } else {
@@ -628,21 +812,14 @@ impl Storage {
                     // inverted; for a given block_hash 0n deploys and each deploy has exactly 1
                     // result (aka deploy_metadata in this context).
 
-                    // Update metadata and write back to db.
-                    metadata
-                        .execution_results
-                        .insert(*block_hash, execution_result);
-                    let was_written =
-                        txn.put_value(self.deploy_metadata_db, &deploy_hash, &metadata, true)?;
-                    assert!(
-                        was_written,
-                        "failed to write deploy metadata for block_hash {} deploy_hash {}",
-                        block_hash, deploy_hash
-                    );
+                    // Update metadata and write back to db, in the current format.
+                    metadata.insert_execution_result(*block_hash, execution_result);
+                    self.stores
+                        .put_deploy_metadata(&mut txn, &deploy_hash, &metadata)?;
                 }
 
                 let was_written =
-                    txn.put_value(self.transfer_db, &*block_hash, &transfers, true)?;
+                    txn.put_value(self.stores.transfer_db, &*block_hash, &transfers, true)?;
                 assert!(
                     was_written,
                     "failed to write transfers for block_hash {}",
@@ -660,7 +837,7 @@ impl Storage {
 
                 // A missing deploy causes an early `None` return.
                 let deploy: Deploy =
-                    if let Some(deploy) = txn.get_value(self.deploy_db, &deploy_hash)? {
+                    if let Some(deploy) = self.stores.get_deploy(&mut txn, &deploy_hash)? {
                         deploy
                     } else {
                         return Ok(responder.respond(None).ignore());
@@ -668,10 +845,34 @@ impl Storage {
 
                 // Missing metadata is filled using a default.
                 let metadata = self
+                    .stores
                     .get_deploy_metadata(&mut txn, &deploy_hash)?
+                    .map(DeployMetadata::from)
                     .unwrap_or_default();
                 responder.respond(Some((deploy, metadata))).ignore()
             }
+            StorageRequest::GetDeployHeaderAndMetadata {
+                deploy_hash,
+                responder,
+            } => {
+                let mut txn = self.env.begin_ro_txn()?;
+
+                // A missing deploy causes an early `None` return.
+                let header =
+                    if let Some(header) = self.stores.get_deploy_header(&mut txn, &deploy_hash)? {
+                        header
+                    } else {
+                        return Ok(responder.respond(None).ignore());
+                    };
+
+                // Missing metadata is filled using a default.
+                let metadata = self
+                    .stores
+                    .get_deploy_metadata(&mut txn, &deploy_hash)?
+                    .map(DeployMetadata::from)
+                    .unwrap_or_default();
+                responder.respond(Some((header, metadata))).ignore()
+            }
             StorageRequest::GetBlockAndMetadataByHash {
                 block_hash,
                 responder,
@@ -679,14 +880,14 @@ impl Storage {
                 let mut txn = self.env.begin_ro_txn()?;
 
                 let block: Block =
-                    if let Some(block) = self.get_single_block(&mut txn, &block_hash)? {
+                    if let Some(block) = self.stores.get_single_block(&mut txn, &block_hash)? {
                         block
                     } else {
                         return Ok(responder.respond(None).ignore());
                     };
                 // Check that the hash of the block retrieved is correct.
                 assert_eq!(&block_hash, block.hash());
-                let signatures = match self.get_finality_signatures(&mut txn, &block_hash)? {
+                let signatures = match self.stores.get_finality_signatures(&mut txn, &block_hash)? {
                     Some(signatures) => signatures,
                     None => BlockSignatures::new(block_hash, block.header().era_id()),
                 };
@@ -701,14 +902,14 @@ impl Storage {
                 let mut txn = self.env.begin_ro_txn()?;
 
                 let block: Block =
-                    if let Some(block) = self.get_block_by_height(&mut txn, block_height)? {
+                    if let Some(block) = self.stores.get_block_by_height(&mut txn, block_height)? {
                         block
                     } else {
                         return Ok(responder.respond(None).ignore());
                     };
 
                 let hash = block.hash();
-                let signatures = match self.get_finality_signatures(&mut txn, hash)? {
+                let signatures = match self.stores.get_finality_signatures(&mut txn, hash)? {
                     Some(signatures) => signatures,
                     None => BlockSignatures::new(*hash, block.header().era_id()),
                 };
@@ -717,10 +918,15 @@ impl Storage {
             StorageRequest::GetHighestBlockWithMetadata { responder } => {
                 let mut txn = self.env.begin_ro_txn()?;
                 let highest_block: Block = if let Some(block) = self
+                    .stores
                     .block_height_index
                     .keys()
                     .last()
-                    .and_then(|&height| self.get_block_by_height(&mut txn, height).transpose())
+                    .and_then(|&height| {
+                        self.stores
+                            .get_block_by_height(&mut txn, height)
+                            .transpose()
+                    })
                     .transpose()?
                 {
                     block
@@ -728,7 +934,7 @@ impl Storage {
                     return Ok(responder.respond(None).ignore());
                 };
                 let hash = highest_block.hash();
-                let signatures = match self.get_finality_signatures(&mut txn, hash)? {
+                let signatures = match self.stores.get_finality_signatures(&mut txn, hash)? {
                     Some(signatures) => signatures,
                     None => BlockSignatures::new(*hash, highest_block.header().era_id()),
                 };
@@ -740,9 +946,10 @@ impl Storage {
                 signatures,
                 responder,
             } => {
-                let mut txn = self.env.begin_rw_txn()?;
-                let old_data: Option<BlockSignatures> =
-                    txn.get_value(self.block_metadata_db, &signatures.block_hash)?;
+                let old_data = self.stores.get_finality_signatures(
+                    &mut self.env.begin_ro_txn()?,
+                    &signatures.block_hash,
+                )?;
                 let new_data = match old_data {
                     None => signatures,
                     Some(mut data) => {
@@ -752,29 +959,442 @@ impl Storage {
                         data
                     }
                 };
-                let outcome = txn.put_value(
-                    self.block_metadata_db,
-                    &new_data.block_hash,
-                    &new_data,
-                    true,
-                )?;
-                txn.commit()?;
-                responder.respond(outcome).ignore()
+                self.stores
+                    .write_batch
+                    .put_block_signatures(new_data, responder);
+                self.on_batched_write(effect_builder)?
             }
             StorageRequest::GetBlockSignatures {
                 block_hash,
                 responder,
             } => {
-                let result =
-                    self.get_finality_signatures(&mut self.env.begin_ro_txn()?, &block_hash)?;
+                let result = self
+                    .stores
+                    .get_finality_signatures(&mut self.env.begin_ro_txn()?, &block_hash)?;
                 responder.respond(result).ignore()
             }
             StorageRequest::GetFinalizedDeploys { ttl, responder } => {
-                responder.respond(self.get_finalized_deploys(ttl)?).ignore()
+                let mut txn = self.env.begin_ro_txn()?;
+                responder
+                    .respond(self.stores.get_finalized_deploys(&mut txn, ttl)?)
+                    .ignore()
             }
+            StorageRequest::PutEraPerformance {
+                era_id,
+                performance,
+                oldest_retained_era,
+                responder,
+            } => {
+                self.write_era_performance(era_id, &performance, oldest_retained_era)?;
+                responder.respond(()).ignore()
+            }
+            StorageRequest::GetEraPerformance {
+                era_ids,
+                maybe_public_key,
+                responder,
+            } => responder
+                .respond(self.read_era_performance(era_ids, maybe_public_key.as_deref())?)
+                .ignore(),
+            StorageRequest::GetValidatorChanges { era_ids, responder } => responder
+                .respond(self.read_validator_changes(era_ids)?)
+                .ignore(),
         })
     }
 
+    /// Commits the write batch if it's full, otherwise schedules a flush unless one is pending.
+    fn on_batched_write<REv: Send>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+    ) -> Result<Effects<Event>, Error> {
+        if self.stores.write_batch.is_full() {
+            return self.flush_write_batch();
+        }
+        if self.flush_scheduled {
+            return Ok(Effects::new());
+        }
+        self.flush_scheduled = true;
+        Ok(effect_builder
+            .set_timeout(MAX_WRITE_BATCH_DELAY)
+            .event(|_| Event::FlushWriteBatch))
+    }
+
+    /// Commits any pending batched writes immediately, as part of the node's shutdown sequence.
+    ///
+    /// The returned future responds to the requests which made the writes.
+    pub(crate) fn flush(&mut self) -> BoxFuture<'static, ()> {
+        match self.flush_write_batch() {
+            Ok(effects) => future::join_all(effects).map(|_| ()).boxed(),
+            Err(error) => {
+                error!(%error, "failed to flush pending writes");
+                async {}.boxed()
+            }
+        }
+    }
+
+    /// Commits the write batch in a single transaction, responding to its requests afterwards.
+    fn flush_write_batch(&mut self) -> Result<Effects<Event>, Error> {
+        // A still pending timer only causes a redundant flush, so a new one can be scheduled.
+        self.flush_scheduled = false;
+        let batch = self.stores.write_batch.take();
+        if batch.is_empty() {
+            return Ok(Effects::new());
+        }
+        let start = Instant::now();
+        let batch_size = batch.len();
+        let mut txn = self.env.begin_rw_txn()?;
+        let responders = batch.write(&mut txn, &self.stores)?;
+        txn.commit()?;
+        self.metrics
+            .observe_write_batch(batch_size, start.elapsed());
+
+        Ok(responders
+            .into_iter()
+            .flat_map(|(responder, response)| responder.respond(response).ignore())
+            .collect())
+    }
+
+    // Retrieves a block header to handle a network request.
+    pub fn read_block_header_and_finality_signatures_by_height(
+        &self,
+        height: u64,
+    ) -> Result<Option<BlockHeaderWithMetadata>, Error> {
+        let mut txn = self.env.begin_ro_txn()?;
+        let maybe_block_header_and_finality_signatures = self
+            .stores
+            .get_block_header_and_metadata_by_height(&mut txn, height)?;
+        drop(txn);
+        Ok(maybe_block_header_and_finality_signatures)
+    }
+
+    /// Retrieves the header of the highest stored block.
+    pub(crate) fn read_highest_block_header(&self) -> Result<Option<BlockHeader>, Error> {
+        let mut txn = self.env.begin_ro_txn()?;
+        let maybe_block_header = self
+            .stores
+            .block_height_index
+            .keys()
+            .last()
+            .and_then(|&height| {
+                self.stores
+                    .get_block_header_by_height(&mut txn, height)
+                    .transpose()
+            })
+            .transpose()?;
+        drop(txn);
+        Ok(maybe_block_header)
+    }
+
+    /// Retrieves the header of the switch block of the given era.
+    pub(crate) fn read_switch_block_header_by_era_id(
+        &self,
+        era_id: EraId,
+    ) -> Result<Option<BlockHeader>, Error> {
+        let mut txn = self.env.begin_ro_txn()?;
+        let maybe_block_header = self
+            .stores
+            .get_switch_block_header_by_era_id(&mut txn, era_id)?;
+        drop(txn);
+        Ok(maybe_block_header)
+    }
+
+    /// Retrieves the block at the given height along with its finality signatures.
+    pub(crate) fn read_block_and_finality_signatures_by_height(
+        &self,
+        height: u64,
+    ) -> Result<Option<(Block, BlockSignatures)>, Error> {
+        let mut txn = self.env.begin_ro_txn()?;
+        let block = match self.stores.get_block_by_height(&mut txn, height)? {
+            Some(block) => block,
+            None => return Ok(None),
+        };
+        let signatures = match self
+            .stores
+            .get_finality_signatures(&mut txn, block.hash())?
+        {
+            Some(signatures) => signatures,
+            None => BlockSignatures::new(*block.hash(), block.header().era_id()),
+        };
+        drop(txn);
+        Ok(Some((block, signatures)))
+    }
+
+    /// Retrieves the block at the given height along with everything stored for it, for export to
+    /// a chain archive.
+    pub(crate) fn read_archived_block(&self, height: u64) -> Result<Option<ArchivedBlock>, Error> {
+        let mut txn = self.env.begin_ro_txn()?;
+        let block = match self.stores.get_block_by_height(&mut txn, height)? {
+            Some(block) => block,
+            None => return Ok(None),
+        };
+        let signatures = self
+            .stores
+            .get_finality_signatures(&mut txn, block.hash())?;
+        let mut deploys = vec![];
+        let mut deploy_metadata = vec![];
+        for deploy_hash in block.deploy_hashes().iter().chain(block.transfer_hashes()) {
+            if let Some(deploy) = self.stores.get_deploy(&mut txn, deploy_hash)? {
+                deploys.push(deploy);
+            }
+            if let Some(metadata) = self.stores.get_deploy_metadata(&mut txn, deploy_hash)? {
+                deploy_metadata.push((*deploy_hash, DeployMetadata::from(metadata)));
+            }
+        }
+        let transfers = self.stores.get_transfers(&mut txn, block.hash())?;
+        drop(txn);
+        Ok(Some(ArchivedBlock {
+            block,
+            signatures,
+            deploys,
+            deploy_metadata,
+            transfers,
+        }))
+    }
+
+    /// Writes a block imported from a chain archive along with everything stored for it, in a
+    /// single transaction.
+    pub(crate) fn write_archived_block(
+        &mut self,
+        archived_block: &ArchivedBlock,
+    ) -> Result<(), Error> {
+        let block = &archived_block.block;
+        let mut txn = self.env.begin_rw_txn()?;
+        txn.put_value(
+            self.stores.block_body_db,
+            block.header().body_hash(),
+            block.body(),
+            true,
+        )?;
+        txn.put_value(
+            self.stores.block_header_db,
+            block.hash(),
+            block.header(),
+            true,
+        )?;
+        if let Some(signatures) = &archived_block.signatures {
+            txn.put_value(
+                self.stores.block_metadata_db,
+                block.hash(),
+                signatures,
+                true,
+            )?;
+        }
+        for deploy in &archived_block.deploys {
+            txn.put_value(self.stores.deploy_db, deploy.id(), deploy, true)?;
+            txn.put_value(
+                self.stores.deploy_approvals_db,
+                deploy.id(),
+                &deploy.approvals(),
+                true,
+            )?;
+        }
+        for (deploy_hash, metadata) in &archived_block.deploy_metadata {
+            let metadata = DeployMetadataV1::from(metadata.clone());
+            self.stores
+                .put_deploy_metadata(&mut txn, deploy_hash, &metadata)?;
+        }
+        if let Some(transfers) = &archived_block.transfers {
+            txn.put_value(self.stores.transfer_db, block.hash(), transfers, true)?;
+        }
+        txn.commit()?;
+        insert_to_block_header_indices(
+            &mut self.stores.block_height_index,
+            &mut self.stores.switch_block_era_id_index,
+            block.header(),
+        )?;
+        insert_to_deploy_index(
+            &mut self.stores.deploy_hash_index,
+            block.header().hash(),
+            block.body(),
+        )?;
+        if block.header().is_switch_block() {
+            self.write_validator_changes(block.header().era_id())?;
+        }
+        Ok(())
+    }
+
+    /// Retrieves the stored performance of validators in the given eras, restricted to the given
+    /// validator if `maybe_public_key` is `Some`.
+    ///
+    /// Eras without any stored performance are omitted from the result.
+    pub(crate) fn read_era_performance(
+        &self,
+        era_ids: RangeInclusive<EraId>,
+        maybe_public_key: Option<&PublicKey>,
+    ) -> Result<BTreeMap<EraId, BTreeMap<PublicKey, ValidatorPerformance>>, Error> {
+        let mut txn = self.env.begin_ro_txn()?;
+        let era_performance =
+            self.stores
+                .get_era_performance(&mut txn, &era_ids, maybe_public_key)?;
+        drop(txn);
+        Ok(era_performance)
+    }
+
+    /// Writes the performance of validators in the given era, overwriting any previously stored
+    /// for the same validators, and deletes the performance stored for eras older than
+    /// `oldest_retained_era`.
+    fn write_era_performance(
+        &self,
+        era_id: EraId,
+        performance: &BTreeMap<PublicKey, ValidatorPerformance>,
+        oldest_retained_era: EraId,
+    ) -> Result<(), Error> {
+        let mut txn = self.env.begin_rw_txn()?;
+        let mut cursor = txn.open_rw_cursor(self.stores.era_performance_db)?;
+        // Entries are ordered by era, so the pruned ones are all at the start.
+        for (_, raw_val) in cursor.iter() {
+            let (stored_era_id, _, _): (EraId, PublicKey, ValidatorPerformance) =
+                lmdb_ext::deserialize(raw_val)?;
+            if stored_era_id >= oldest_retained_era {
+                break;
+            }
+            cursor.del(WriteFlags::empty())?;
+        }
+        drop(cursor);
+
+        for (public_key, validator_performance) in performance {
+            let _ = txn.put_value(
+                self.stores.era_performance_db,
+                &era_performance_key(era_id, public_key)?,
+                &(era_id, public_key, validator_performance),
+                true,
+            )?;
+        }
+        txn.commit()?;
+        Ok(())
+    }
+
+    /// Retrieves the stored changes to the validator set taking effect in the given eras.
+    ///
+    /// Eras for which no changes were computed are omitted from the result.
+    pub(crate) fn read_validator_changes(
+        &self,
+        era_ids: RangeInclusive<EraId>,
+    ) -> Result<BTreeMap<EraId, BTreeMap<PublicKey, Vec<ValidatorChange>>>, Error> {
+        let mut txn = self.env.begin_ro_txn()?;
+        let validator_changes = self.stores.get_validator_changes(&mut txn, &era_ids)?;
+        drop(txn);
+        Ok(validator_changes)
+    }
+
+    /// Computes and writes the changes to the validator set recorded by the switch block of the
+    /// given era, as well as those recorded by the switch block of the following era in case that
+    /// was stored first.
+    ///
+    /// The changes recorded by a switch block take effect in the following era, and can only be
+    /// computed if the switch block of the preceding era is stored too.
+    fn write_validator_changes(&self, switch_block_era_id: EraId) -> Result<(), Error> {
+        let mut txn = self.env.begin_rw_txn()?;
+        for &era_id in &[switch_block_era_id, switch_block_era_id.successor()] {
+            let previous_era_id = match era_id.checked_sub(1) {
+                Some(previous_era_id) => previous_era_id,
+                None => continue,
+            };
+            let (previous_header, header) = match (
+                self.stores
+                    .get_switch_block_header_by_era_id(&mut txn, previous_era_id)?,
+                self.stores
+                    .get_switch_block_header_by_era_id(&mut txn, era_id)?,
+            ) {
+                (Some(previous_header), Some(header)) => (previous_header, header),
+                _ => continue,
+            };
+            let effective_era_id = era_id.successor();
+            let _ = txn.put_value(
+                self.stores.validator_changes_db,
+                &effective_era_id.value().to_be_bytes(),
+                &(
+                    effective_era_id,
+                    validator_changes(&previous_header, &header),
+                ),
+                true,
+            )?;
+        }
+        txn.commit()?;
+        Ok(())
+    }
+
+    /// Retrieves the state root hashes from storage to check the integrity of the trie store.
+    pub fn get_state_root_hashes_for_trie_check(&self) -> Result<Vec<Blake2bHash>, Error> {
+        let mut txn = self.env.begin_ro_txn()?;
+        let state_root_hashes = self.stores.get_state_root_hashes(&mut txn)?;
+        drop(txn);
+        Ok(state_root_hashes)
+    }
+
+    // Retrieves a block header to handle a network request.
+    pub fn read_block_header_by_hash(
+        &self,
+        block_hash: &BlockHash,
+    ) -> Result<Option<BlockHeader>, LmdbExtError> {
+        let mut txn = self.env.begin_ro_txn()?;
+        let maybe_block_header = self.stores.get_single_block_header(&mut txn, block_hash)?;
+        drop(txn);
+        Ok(maybe_block_header)
+    }
+
+    /// Retrieves a deploy's header to handle a network request.
+    pub fn read_deploy_header_with_hash(
+        &self,
+        deploy_hash: &DeployHash,
+    ) -> Result<Option<DeployHeaderWithHash>, LmdbExtError> {
+        let mut txn = self.env.begin_ro_txn()?;
+        let maybe_header = self.stores.get_deploy_header(&mut txn, deploy_hash)?;
+        drop(txn);
+        Ok(maybe_header.map(|header| DeployHeaderWithHash::new(*deploy_hash, header)))
+    }
+
+    /// Retrieves a deploy's approvals to handle a network request.
+    pub fn read_deploy_approvals(
+        &self,
+        deploy_hash: &DeployHash,
+    ) -> Result<Option<DeployApprovals>, LmdbExtError> {
+        let mut txn = self.env.begin_ro_txn()?;
+        let maybe_approvals = self.stores.get_deploy_approvals(&mut txn, deploy_hash)?;
+        drop(txn);
+        Ok(maybe_approvals.map(|approvals| DeployApprovals::new(*deploy_hash, approvals)))
+    }
+
+    /// Retrieves the transfers executed in a block to handle a network request.
+    pub fn read_block_transfers(
+        &self,
+        block_hash: &BlockHash,
+    ) -> Result<Option<Vec<Transfer>>, LmdbExtError> {
+        let mut txn = self.env.begin_ro_txn()?;
+        let maybe_transfers = txn.get_value(self.stores.transfer_db, block_hash)?;
+        drop(txn);
+        Ok(maybe_transfers)
+    }
+
+    /// Get the lmdb environment
+    #[cfg(test)]
+    pub(crate) fn env(&self) -> &Environment {
+        &self.env
+    }
+
+    /// Returns `true` if any writes are batched but not yet committed.
+    #[cfg(test)]
+    pub(crate) fn has_pending_writes(&self) -> bool {
+        !self.stores.write_batch.is_empty()
+    }
+}
+
+impl Stores {
+    /// Returns the named databases along with their names, for collecting their statistics.
+    fn named_databases(&self) -> [(&'static str, Database); MAX_DB_COUNT as usize] {
+        [
+            ("block_header", self.block_header_db),
+            ("block_body", self.block_body_db),
+            ("block_metadata", self.block_metadata_db),
+            ("deploys", self.deploy_db),
+            ("deploy_approvals", self.deploy_approvals_db),
+            ("deploy_metadata", self.deploy_metadata_db),
+            ("transfer", self.transfer_db),
+            ("state_store", self.state_store_db),
+            ("era_performance", self.era_performance_db),
+            ("validator_changes", self.validator_changes_db),
+        ]
+    }
+
     /// Retrieves single block header by height by looking it up in the index and returning it.
     fn get_block_header_and_metadata_by_height<Tx: Transaction>(
         &self,
@@ -799,18 +1419,6 @@ impl Storage {
         }))
     }
 
-    // Retrieves a block header to handle a network request.
-    pub fn read_block_header_and_finality_signatures_by_height(
-        &self,
-        height: u64,
-    ) -> Result<Option<BlockHeaderWithMetadata>, Error> {
-        let mut txn = self.env.begin_ro_txn()?;
-        let maybe_block_header_and_finality_signatures =
-            self.get_block_header_and_metadata_by_height(&mut txn, height)?;
-        drop(txn);
-        Ok(maybe_block_header_and_finality_signatures)
-    }
-
     /// Retrieves single block header by height by looking it up in the index and returning it.
     fn get_block_header_by_height<Tx: Transaction>(
         &self,
@@ -903,15 +1511,15 @@ impl Storage {
     }
 
     /// Returns the vector of deploys whose TTL hasn't expired yet.
-    fn get_finalized_deploys(
+    fn get_finalized_deploys<Tx: Transaction>(
         &self,
+        txn: &mut Tx,
         ttl: TimeDiff,
     ) -> Result<Vec<(DeployHash, DeployHeader)>, LmdbExtError> {
-        let mut txn = self.env.begin_ro_txn()?;
         // We're interested in deploys whose TTL hasn't expired yet.
         let ttl_expired = |block: &Block| block.timestamp().elapsed() < ttl;
         let mut deploys = Vec::new();
-        for block in self.get_blocks_while(&mut txn, ttl_expired)? {
+        for block in self.get_blocks_while(txn, ttl_expired)? {
             for deploy_hash in block
                 .body()
                 .deploy_hashes()
@@ -919,7 +1527,7 @@ impl Storage {
                 .chain(block.body().transfer_hashes())
             {
                 let deploy_header = self
-                    .get_deploy_header(&mut txn, deploy_hash)?
+                    .get_deploy_header(txn, deploy_hash)?
                     .expect("deploy to exist in storage");
                 // If block's deploy has already expired, ignore it.
                 // It may happen that deploy was not expired at the time of proposing a block but it
@@ -945,30 +1553,7 @@ impl Storage {
             .transpose()
     }
 
-    /// Retrieves the state root hashes from storage to check the integrity of the trie store.
-    pub fn get_state_root_hashes_for_trie_check(&self) -> Option<Vec<Blake2bHash>> {
-        let mut blake_hashes: Vec<Blake2bHash> = Vec::new();
-        let txn =
-            self.env.begin_ro_txn().ok().unwrap_or_else(|| {
-                panic!("could not open storage transaction for trie store check")
-            });
-        let mut cursor = txn
-            .open_ro_cursor(self.block_header_db)
-            .ok()
-            .unwrap_or_else(|| panic!("could not create cursor for trie store check"));
-        for (_, raw_val) in cursor.iter() {
-            let header: BlockHeader = lmdb_ext::deserialize(raw_val).ok()?;
-            let blake_hash = Blake2bHash::from(*header.state_root_hash());
-            blake_hashes.push(blake_hash);
-        }
-
-        blake_hashes.sort();
-        blake_hashes.dedup();
-
-        Some(blake_hashes)
-    }
-
-    /// Retrieves a single block header in a separate transaction from storage.
+    /// Retrieves a single block header from storage.
     fn get_single_block_header<Tx: Transaction>(
         &self,
         tx: &mut Tx,
@@ -988,18 +1573,7 @@ impl Storage {
         Ok(Some(block_header))
     }
 
-    // Retrieves a block header to handle a network request.
-    pub fn read_block_header_by_hash(
-        &self,
-        block_hash: &BlockHash,
-    ) -> Result<Option<BlockHeader>, LmdbExtError> {
-        let mut txn = self.env.begin_ro_txn()?;
-        let maybe_block_header = self.get_single_block_header(&mut txn, block_hash)?;
-        drop(txn);
-        Ok(maybe_block_header)
-    }
-
-    /// Retrieves a single block in a separate transaction from storage.
+    /// Retrieves a single block from storage.
     fn get_single_block<Tx: Transaction>(
         &self,
         tx: &mut Tx,
@@ -1033,30 +1607,87 @@ impl Storage {
     ) -> Result<Vec<Option<Deploy>>, LmdbExtError> {
         deploy_hashes
             .iter()
-            .map(|deploy_hash| tx.get_value(self.deploy_db, deploy_hash))
+            .map(|deploy_hash| self.get_deploy(tx, deploy_hash))
             .collect()
     }
 
+    /// Retrieves a single deploy, which may still be pending in the write batch.
+    fn get_deploy<Tx: Transaction>(
+        &self,
+        tx: &mut Tx,
+        deploy_hash: &DeployHash,
+    ) -> Result<Option<Deploy>, LmdbExtError> {
+        if let Some(deploy) = self.write_batch.deploy(deploy_hash) {
+            return Ok(Some(deploy.clone()));
+        }
+        tx.get_value(self.deploy_db, deploy_hash)
+    }
+
     /// Returns the deploy's header.
+    ///
+    /// A stored deploy is serialized starting with its hash and header, so only that prefix is
+    /// deserialized, leaving the body untouched.
     fn get_deploy_header<Tx: Transaction>(
         &self,
         txn: &mut Tx,
         deploy_hash: &DeployHash,
     ) -> Result<Option<DeployHeader>, LmdbExtError> {
-        let maybe_deploy: Option<Deploy> = txn.get_value(self.deploy_db, deploy_hash)?;
-        Ok(maybe_deploy.map(|deploy| deploy.header().clone()))
+        if let Some(deploy) = self.write_batch.deploy(deploy_hash) {
+            return Ok(Some(deploy.header().clone()));
+        }
+        let maybe_hash_and_header: Option<(DeployHash, DeployHeader)> =
+            txn.get_value(self.deploy_db, deploy_hash)?;
+        Ok(maybe_hash_and_header.map(|(_, header)| header))
     }
 
-    /// Retrieves deploy metadata associated with deploy.
+    /// Returns the deploy's approvals.
     ///
-    /// If no deploy metadata is stored for the specific deploy, an empty metadata instance will be
-    /// created, but not stored.
+    /// Deploys stored before the approvals database was introduced have no entry in it, in which
+    /// case the approvals are read from the full deploy.
+    fn get_deploy_approvals<Tx: Transaction>(
+        &self,
+        txn: &mut Tx,
+        deploy_hash: &DeployHash,
+    ) -> Result<Option<Vec<Approval>>, LmdbExtError> {
+        if let Some(deploy) = self.write_batch.deploy(deploy_hash) {
+            return Ok(Some(deploy.approvals().to_vec()));
+        }
+        if let Some(approvals) = txn.get_value(self.deploy_approvals_db, deploy_hash)? {
+            return Ok(Some(approvals));
+        }
+        let maybe_deploy: Option<Deploy> = txn.get_value(self.deploy_db, deploy_hash)?;
+        Ok(maybe_deploy.map(|deploy| deploy.approvals().to_vec()))
+    }
+
+    /// Retrieves deploy metadata associated with deploy, whichever format it is stored in.
     fn get_deploy_metadata<Tx: Transaction>(
         &self,
         tx: &mut Tx,
         deploy_hash: &DeployHash,
-    ) -> Result<Option<DeployMetadata>, Error> {
-        Ok(tx.get_value(self.deploy_metadata_db, deploy_hash)?)
+    ) -> Result<Option<DeployMetadataV1>, Error> {
+        match tx.get(self.deploy_metadata_db, deploy_hash) {
+            Ok(raw) => Ok(Some(deploy_metadata::decode(raw)?.0)),
+            Err(lmdb::Error::NotFound) => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Writes deploy metadata associated with deploy in the current format, replacing any already
+    /// stored.
+    fn put_deploy_metadata(
+        &self,
+        txn: &mut RwTransaction,
+        deploy_hash: &DeployHash,
+        metadata: &DeployMetadataV1,
+    ) -> Result<(), Error> {
+        let buffer = deploy_metadata::encode(metadata)?;
+        txn.put(
+            self.deploy_metadata_db,
+            deploy_hash,
+            &buffer,
+            WriteFlags::empty(),
+        )?;
+        Ok(())
     }
 
     /// Retrieves transfers associated with block.
@@ -1077,16 +1708,151 @@ impl Storage {
         tx: &mut Tx,
         block_hash: &BlockHash,
     ) -> Result<Option<BlockSignatures>, Error> {
+        if let Some(signatures) = self.write_batch.block_signatures(block_hash) {
+            return Ok(Some(signatures.clone()));
+        }
         Ok(tx.get_value(self.block_metadata_db, block_hash)?)
     }
 
-    /// Get the lmdb environment
-    #[cfg(test)]
-    pub(crate) fn env(&self) -> &Environment {
-        &self.env
+    /// Retrieves the performance of validators in the given eras, optionally restricted to a
+    /// single validator.
+    fn get_era_performance<Tx: Transaction>(
+        &self,
+        txn: &mut Tx,
+        era_ids: &RangeInclusive<EraId>,
+        maybe_public_key: Option<&PublicKey>,
+    ) -> Result<BTreeMap<EraId, BTreeMap<PublicKey, ValidatorPerformance>>, LmdbExtError> {
+        let mut result: BTreeMap<EraId, BTreeMap<PublicKey, ValidatorPerformance>> =
+            BTreeMap::new();
+        let mut cursor = txn.open_ro_cursor(self.era_performance_db)?;
+        // Entries are ordered by era, so iteration can stop once past the requested range.
+        for (_, raw_val) in cursor.iter() {
+            let (era_id, public_key, validator_performance): (
+                EraId,
+                PublicKey,
+                ValidatorPerformance,
+            ) = lmdb_ext::deserialize(raw_val)?;
+            if era_id > *era_ids.end() {
+                break;
+            }
+            if era_id < *era_ids.start()
+                || maybe_public_key.map_or(false, |requested| *requested != public_key)
+            {
+                continue;
+            }
+            let _ = result
+                .entry(era_id)
+                .or_default()
+                .insert(public_key, validator_performance);
+        }
+        Ok(result)
+    }
+
+    /// Retrieves the changes to the validator set taking effect in the given eras.
+    fn get_validator_changes<Tx: Transaction>(
+        &self,
+        txn: &mut Tx,
+        era_ids: &RangeInclusive<EraId>,
+    ) -> Result<BTreeMap<EraId, BTreeMap<PublicKey, Vec<ValidatorChange>>>, LmdbExtError> {
+        let mut result = BTreeMap::new();
+        let mut cursor = txn.open_ro_cursor(self.validator_changes_db)?;
+        // Entries are ordered by era, so iteration can stop once past the requested range.
+        for (_, raw_val) in cursor.iter() {
+            let (era_id, changes): (EraId, BTreeMap<PublicKey, Vec<ValidatorChange>>) =
+                lmdb_ext::deserialize(raw_val)?;
+            if era_id > *era_ids.end() {
+                break;
+            }
+            if era_id >= *era_ids.start() {
+                let _ = result.insert(era_id, changes);
+            }
+        }
+        Ok(result)
+    }
+
+    /// Retrieves the sorted and deduplicated state root hashes of all stored blocks.
+    fn get_state_root_hashes<Tx: Transaction>(
+        &self,
+        txn: &mut Tx,
+    ) -> Result<Vec<Blake2bHash>, LmdbExtError> {
+        let mut cursor = txn.open_ro_cursor(self.block_header_db)?;
+        let mut blake_hashes = cursor
+            .iter()
+            .map(|(_, raw_val)| {
+                let header: BlockHeader = lmdb_ext::deserialize(raw_val)?;
+                Ok(Blake2bHash::from(*header.state_root_hash()))
+            })
+            .collect::<Result<Vec<_>, LmdbExtError>>()?;
+
+        blake_hashes.sort();
+        blake_hashes.dedup();
+
+        Ok(blake_hashes)
     }
 }
 
+/// Returns the key under which the performance of a validator in an era is stored.
+///
+/// The key starts with the big-endian era ID, so that entries are ordered by era.
+fn era_performance_key(era_id: EraId, public_key: &PublicKey) -> Result<Vec<u8>, LmdbExtError> {
+    let mut key = era_id.value().to_be_bytes().to_vec();
+    key.extend(lmdb_ext::serialize(public_key)?);
+    Ok(key)
+}
+
+/// Returns the changes between the validator sets of the eras following the two given switch
+/// blocks, along with the validators banned or evicted by the latter.
+fn validator_changes(
+    previous_switch_block_header: &BlockHeader,
+    switch_block_header: &BlockHeader,
+) -> BTreeMap<PublicKey, Vec<ValidatorChange>> {
+    let no_validators = BTreeMap::new();
+    let previous_weights = previous_switch_block_header
+        .next_era_validator_weights()
+        .unwrap_or(&no_validators);
+    let weights = switch_block_header
+        .next_era_validator_weights()
+        .unwrap_or(&no_validators);
+
+    let mut changes: BTreeMap<PublicKey, Vec<ValidatorChange>> = BTreeMap::new();
+    for (public_key, weight) in weights {
+        let change = match previous_weights.get(public_key) {
+            None => ValidatorChange::Added { weight: *weight },
+            Some(previous_weight) if previous_weight != weight => ValidatorChange::WeightChanged {
+                previous_weight: *previous_weight,
+                weight: *weight,
+            },
+            Some(_) => continue,
+        };
+        changes.entry(public_key.clone()).or_default().push(change);
+    }
+    for (public_key, previous_weight) in previous_weights {
+        if !weights.contains_key(public_key) {
+            changes
+                .entry(public_key.clone())
+                .or_default()
+                .push(ValidatorChange::Removed {
+                    previous_weight: *previous_weight,
+                });
+        }
+    }
+    if let Some(era_report) = switch_block_header.era_end() {
+        for public_key in &era_report.equivocators {
+            changes
+                .entry(public_key.clone())
+                .or_default()
+                .push(ValidatorChange::Banned);
+        }
+        for public_key in &era_report.inactive_validators {
+            changes
+                .entry(public_key.clone())
+                .or_default()
+                .push(ValidatorChange::Evicted);
+        }
+    }
+    changes
+}
+
 /// Inserts the relevant entries to the two indices.
 ///
 /// If a duplicate entry is encountered, neither index is updated and an error is returned.
@@ -1191,6 +1957,16 @@ pub struct Config {
     enable_mem_deduplication: bool,
     /// How many loads before memory duplication checks for dead references.
     mem_pool_prune_interval: u16,
+    /// Utilization of an LMDB environment's maximum size, in percent, from which on a warning is
+    /// logged.
+    ///
+    /// Applies to both the storage component's and the global state's environment.
+    lmdb_utilization_warning_percent: u8,
+    /// Whether to rewrite all deploy metadata stored in a legacy format in the current format on
+    /// startup.
+    ///
+    /// Legacy entries are otherwise rewritten as they are next written to.
+    migrate_deploy_metadata: bool,
 }
 
 impl Default for Config {
@@ -1204,23 +1980,70 @@ impl Default for Config {
             max_state_store_size: DEFAULT_MAX_STATE_STORE_SIZE,
             enable_mem_deduplication: false,
             mem_pool_prune_interval: 1024,
+            lmdb_utilization_warning_percent: DEFAULT_LMDB_UTILIZATION_WARNING_PERCENT,
+            migrate_deploy_metadata: false,
         }
     }
 }
 
 impl Config {
+    /// Returns the utilization of an LMDB environment, in percent, from which on a warning is
+    /// logged.
+    pub(crate) fn lmdb_utilization_warning_percent(&self) -> u8 {
+        self.lmdb_utilization_warning_percent
+    }
+
+    /// Checks whether the values set in the config make sense, pushing every problem found to
+    /// `errors`.
+    ///
+    /// A relative `path` is resolved against `root`, the directory of the config file.
+    pub(crate) fn validate(&self, root: &Path, errors: &mut Vec<ConfigValidationError>) {
+        config_validation::check_writable_dir("storage.path", &root.join(&self.path), errors);
+
+        let sizes = [
+            ("storage.max_block_store_size", self.max_block_store_size),
+            ("storage.max_deploy_store_size", self.max_deploy_store_size),
+            (
+                "storage.max_deploy_metadata_store_size",
+                self.max_deploy_metadata_store_size,
+            ),
+            ("storage.max_state_store_size", self.max_state_store_size),
+        ];
+        for (field, size) in sizes.iter() {
+            if *size == 0 {
+                errors.push(ConfigValidationError::out_of_range(
+                    field,
+                    size,
+                    "greater than 0",
+                ));
+            }
+        }
+
+        if self.lmdb_utilization_warning_percent > 100 {
+            errors.push(ConfigValidationError::out_of_range(
+                "storage.lmdb_utilization_warning_percent",
+                self.lmdb_utilization_warning_percent,
+                "at most 100",
+            ));
+        }
+    }
+
     /// Returns a default `Config` suitable for tests, along with a `TempDir` which must be kept
     /// alive for the duration of the test since its destructor removes the dir from the filesystem.
     #[cfg(test)]
     pub(crate) fn default_for_tests() -> (Self, TempDir) {
         let tempdir = tempfile::tempdir().expect("should get tempdir");
-        let path = tempdir.path().join("lmdb");
+        let config = Config::default_for_tests_at(tempdir.path().join("lmdb"));
+        (config, tempdir)
+    }
 
-        let config = Config {
+    /// Returns a default `Config` suitable for tests, using the given path for the storage files.
+    #[cfg(test)]
+    pub(crate) fn default_for_tests_at(path: PathBuf) -> Self {
+        Config {
             path,
             ..Default::default()
-        };
-        (config, tempdir)
+        }
     }
 }
 
@@ -1229,6 +2052,7 @@ impl Display for Event {
         match self {
             Event::StorageRequest(req) => req.fmt(f),
             Event::StateStoreRequest(req) => req.fmt(f),
+            Event::FlushWriteBatch => write!(f, "flush write batch"),
         }
     }
 }
@@ -1250,7 +2074,7 @@ impl Storage {
         self.env
             .begin_ro_txn()
             .map_err(Into::into)
-            .and_then(|mut tx| tx.get_value(self.deploy_db, &deploy_hash))
+            .and_then(|mut tx| self.stores.get_deploy(&mut tx, &deploy_hash))
             .expect("legacy direct deploy request failed")
     }
 
@@ -1307,7 +2131,8 @@ impl Storage {
             .env
             .begin_ro_txn()
             .expect("could not create RO transaction");
-        txn.get_value(self.deploy_db, &deploy_hash)
+        self.stores
+            .get_deploy(&mut txn, &deploy_hash)
             .expect("could not retrieve value from storage")
     }
 
@@ -1323,7 +2148,7 @@ impl Storage {
             .expect("could not create RO transaction");
 
         let mut cursor = txn
-            .open_ro_cursor(self.deploy_db)
+            .open_ro_cursor(self.stores.deploy_db)
             .expect("could not create cursor");
 
         cursor
@@ -1331,6 +2156,7 @@ impl Storage {
             .map(|(raw_key, _)| {
                 DeployHash::new(Digest::try_from(raw_key).expect("malformed deploy hash in DB"))
             })
+            .chain(self.stores.write_batch.deploy_hashes().copied())
             .collect()
     }
 
@@ -1348,6 +2174,7 @@ impl Storage {
             .begin_ro_txn()
             .expect("Could not start read only transaction for lmdb");
         let switch_block = self
+            .stores
             .get_switch_block_by_era_id(
                 &mut read_only_lmdb_transaction,
                 EraId::from(switch_block_era_num),
@@ -1439,31 +2266,36 @@ fn initialize_block_metadata_db(
     Ok(())
 }
 
-/// Purges stale entries from the deploy metadata database.
+/// Purges stale entries from the deploy metadata database, and rewrites all entries stored in a
+/// legacy format in the current one if `should_migrate` is true.
+///
+/// Entries which are modified are always rewritten in the current format.
 fn initialize_deploy_metadata_db(
     env: &Environment,
     deploy_metadata_db: &Database,
     deleted_block_hashes: &HashSet<BlockHash>,
+    should_migrate: bool,
 ) -> Result<(), LmdbExtError> {
     info!("initializing deploy metadata database");
     let mut txn = env.begin_rw_txn()?;
     let mut cursor = txn.open_rw_cursor(*deploy_metadata_db)?;
+    let mut migrated_count = 0_usize;
 
     for (raw_key, raw_val) in cursor.iter() {
-        let mut deploy_metadata: DeployMetadata = lmdb_ext::deserialize(raw_val)?;
-        let len_before = deploy_metadata.execution_results.len();
-
-        deploy_metadata.execution_results = deploy_metadata
-            .execution_results
-            .drain()
-            .filter(|(block_hash, _)| !deleted_block_hashes.contains(block_hash))
-            .collect();
+        let (mut deploy_metadata, format) = deploy_metadata::decode(raw_val)?;
+        let was_modified = deploy_metadata.remove_execution_results(deleted_block_hashes);
 
         // If the deploy's execution results are now empty, we just remove them entirely.
-        if deploy_metadata.execution_results.is_empty() {
+        if deploy_metadata.is_empty() {
             cursor.del(WriteFlags::empty())?;
-        } else if len_before != deploy_metadata.execution_results.len() {
-            let buffer = lmdb_ext::serialize(&deploy_metadata)?;
+        } else if was_modified || (should_migrate && format == StoredFormat::Legacy) {
+            if format == StoredFormat::Legacy {
+                migrated_count += 1;
+            }
+            let buffer = deploy_metadata::encode(&deploy_metadata)?;
+            // The key must be copied out, as LMDB may overwrite the page it points into while
+            // putting the new value.
+            let raw_key = raw_key.to_vec();
             cursor.put(&raw_key, &buffer, WriteFlags::empty())?;
         }
     }
@@ -1471,6 +2303,9 @@ fn initialize_deploy_metadata_db(
     drop(cursor);
     txn.commit()?;
 
+    if should_migrate {
+        info!(%migrated_count, "migrated deploy metadata to the current format");
+    }
     info!("deploy metadata database initialized");
     Ok(())
 }
@@ -0,0 +1,187 @@
+//! Versioned encoding of deploy metadata.
+//!
+//! Deploy metadata is stored as `VERSIONED_MARKER`, followed by a version byte and the bincode
+//! encoding of that version's envelope.  Records written before versioning was introduced hold
+//! just the bincode encoding of a `DeployMetadata`.  These can never start with the marker, as they
+//! start with the number of execution results as a little-endian `u64`.
+//!
+//! Legacy records are read transparently, and are rewritten in the current format the next time
+//! they are written to, or on startup if `migrate_deploy_metadata` is enabled in the config.
+
+use std::collections::{BTreeMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use casper_types::ExecutionResult;
+
+use super::lmdb_ext::{self, LmdbExtError};
+use crate::types::{BlockHash, DeployMetadata};
+
+/// Prefix of every versioned deploy metadata record.
+const VERSIONED_MARKER: [u8; 8] = [u8::max_value(); 8];
+
+/// Version byte of records holding a `DeployMetadataV1`.
+const VERSION_1: u8 = 1;
+
+/// The format in which a deploy's metadata was stored.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum StoredFormat {
+    /// The unversioned encoding of a `DeployMetadata`.
+    Legacy,
+    /// A versioned `DeployMetadataV1`.
+    V1,
+}
+
+/// An error decoding a versioned deploy metadata record.
+#[derive(Debug, Error)]
+enum VersionError {
+    #[error("missing deploy metadata version")]
+    Missing,
+    #[error("unsupported deploy metadata version {0}")]
+    Unsupported(u8),
+}
+
+/// The metadata of a deploy, as stored.
+#[derive(Clone, Default, Serialize, Deserialize, Debug, PartialEq)]
+pub(super) struct DeployMetadataV1 {
+    /// The results of executing the deploy, keyed by the hash of each block in which it was
+    /// executed.
+    executions: BTreeMap<BlockHash, ExecutionResult>,
+}
+
+impl DeployMetadataV1 {
+    /// Returns the result of executing the deploy in the given block, if any.
+    pub(super) fn execution_result(&self, block_hash: &BlockHash) -> Option<&ExecutionResult> {
+        self.executions.get(block_hash)
+    }
+
+    /// Returns `true` if no execution results are held.
+    pub(super) fn is_empty(&self) -> bool {
+        self.executions.is_empty()
+    }
+
+    /// Sets the result of executing the deploy in the given block.
+    pub(super) fn insert_execution_result(
+        &mut self,
+        block_hash: BlockHash,
+        execution_result: ExecutionResult,
+    ) {
+        self.executions.insert(block_hash, execution_result);
+    }
+
+    /// Removes the results of executing the deploy in any of the given blocks, returning `true`
+    /// if any were removed.
+    pub(super) fn remove_execution_results(&mut self, block_hashes: &HashSet<BlockHash>) -> bool {
+        let len_before = self.executions.len();
+        self.executions
+            .retain(|block_hash, _| !block_hashes.contains(block_hash));
+        self.executions.len() != len_before
+    }
+}
+
+impl From<DeployMetadata> for DeployMetadataV1 {
+    fn from(metadata: DeployMetadata) -> Self {
+        DeployMetadataV1 {
+            executions: metadata.execution_results.into_iter().collect(),
+        }
+    }
+}
+
+impl From<DeployMetadataV1> for DeployMetadata {
+    fn from(metadata: DeployMetadataV1) -> Self {
+        DeployMetadata {
+            execution_results: metadata.executions.into_iter().collect(),
+        }
+    }
+}
+
+/// Decodes a stored deploy metadata record of any format, returning the format along with it.
+pub(super) fn decode(raw: &[u8]) -> Result<(DeployMetadataV1, StoredFormat), LmdbExtError> {
+    let versioned = match raw.strip_prefix(&VERSIONED_MARKER[..]) {
+        Some(versioned) => versioned,
+        None => {
+            let legacy: DeployMetadata = lmdb_ext::deserialize(raw)?;
+            return Ok((legacy.into(), StoredFormat::Legacy));
+        }
+    };
+    match versioned.split_first() {
+        Some((&VERSION_1, encoded)) => Ok((lmdb_ext::deserialize(encoded)?, StoredFormat::V1)),
+        Some((version, _)) => Err(LmdbExtError::DataCorrupted(Box::new(
+            VersionError::Unsupported(*version),
+        ))),
+        None => Err(LmdbExtError::DataCorrupted(Box::new(VersionError::Missing))),
+    }
+}
+
+/// Encodes deploy metadata in the current format.
+pub(super) fn encode(metadata: &DeployMetadataV1) -> Result<Vec<u8>, LmdbExtError> {
+    let encoded = lmdb_ext::serialize(metadata)?;
+    let mut buffer = Vec::with_capacity(VERSIONED_MARKER.len() + 1 + encoded.len());
+    buffer.extend_from_slice(&VERSIONED_MARKER);
+    buffer.push(VERSION_1);
+    buffer.extend(encoded);
+    Ok(buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::*;
+    use crate::testing::TestRng;
+
+    fn random_metadata(rng: &mut TestRng) -> DeployMetadataV1 {
+        let mut metadata = DeployMetadataV1::default();
+        for _ in 0..3 {
+            metadata.insert_execution_result(BlockHash::random(rng), rng.gen());
+        }
+        metadata
+    }
+
+    #[test]
+    fn should_round_trip_v1() {
+        let mut rng = TestRng::new();
+        let metadata = random_metadata(&mut rng);
+
+        let encoded = encode(&metadata).unwrap();
+        assert_eq!(&encoded[..VERSIONED_MARKER.len()], &VERSIONED_MARKER[..]);
+        assert_eq!(encoded[VERSIONED_MARKER.len()], VERSION_1);
+        assert_eq!(decode(&encoded).unwrap(), (metadata, StoredFormat::V1));
+    }
+
+    #[test]
+    fn should_decode_legacy() {
+        let mut rng = TestRng::new();
+        let metadata = random_metadata(&mut rng);
+
+        let legacy = bincode::serialize(&DeployMetadata::from(metadata.clone())).unwrap();
+        assert_eq!(decode(&legacy).unwrap(), (metadata, StoredFormat::Legacy));
+    }
+
+    #[test]
+    fn should_decode_legacy_fixture() {
+        // The legacy encoding of a `DeployMetadata` without execution results.
+        let fixture = [0u8; 8];
+        assert_eq!(
+            decode(&fixture).unwrap(),
+            (DeployMetadataV1::default(), StoredFormat::Legacy)
+        );
+    }
+
+    #[test]
+    fn should_reject_unknown_version() {
+        let mut encoded = encode(&DeployMetadataV1::default()).unwrap();
+        encoded[VERSIONED_MARKER.len()] = VERSION_1 + 1;
+        assert!(matches!(
+            decode(&encoded),
+            Err(LmdbExtError::DataCorrupted(_))
+        ));
+
+        encoded.truncate(VERSIONED_MARKER.len());
+        assert!(matches!(
+            decode(&encoded),
+            Err(LmdbExtError::DataCorrupted(_))
+        ));
+    }
+}
@@ -98,6 +98,10 @@ pub(super) trait TransactionExt {
         db: Database,
         key: &K,
     ) -> Result<Option<V>, LmdbExtError>;
+
+    /// Returns `true` if the given key has a value in the database.
+    fn value_exists<K: AsRef<[u8]>>(&mut self, db: Database, key: &K)
+        -> Result<bool, LmdbExtError>;
 }
 
 /// Additional methods on write transactions.
@@ -133,6 +137,19 @@ where
             Err(err) => Err(err.into()),
         }
     }
+
+    #[inline]
+    fn value_exists<K: AsRef<[u8]>>(
+        &mut self,
+        db: Database,
+        key: &K,
+    ) -> Result<bool, LmdbExtError> {
+        match self.get(db, key) {
+            Ok(_) => Ok(true),
+            Err(lmdb::Error::NotFound) => Ok(false),
+            Err(err) => Err(err.into()),
+        }
+    }
 }
 
 impl WriteTransactionExt for RwTransaction<'_> {
@@ -0,0 +1,183 @@
+//! Periodic collection of LMDB environment statistics.
+//!
+//! Both the storage component and the contract runtime hold an LMDB environment with a fixed
+//! maximum size, and writes fail once that size is reached.  The collector exports how much of
+//! each environment is in use as metrics, and warns well before it fills up.
+
+use std::{
+    convert::TryFrom,
+    time::{Duration, Instant},
+};
+
+use lmdb::{Database, Environment};
+use prometheus::{Gauge, IntCounter, IntGauge, IntGaugeVec, Opts, Registry};
+use tracing::{error, warn};
+
+use casper_execution_engine::storage::lmdb_stats::{pages_to_bytes, LmdbStats};
+
+use crate::unregister_metric;
+
+/// The minimum time between two collections triggered by `collect_if_due`.
+const COLLECTION_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Collects the statistics of an LMDB environment and exports them as metrics.
+#[derive(Debug)]
+pub(crate) struct LmdbStatsCollector {
+    /// Name of the environment, used as the prefix of the metric names and in log messages.
+    name: &'static str,
+    /// Utilization of the map size in percent at or above which a warning is logged.
+    warning_threshold_percent: u8,
+    /// When the statistics were last collected.
+    last_collected: Option<Instant>,
+    /// Size of the memory map, in bytes.
+    map_size: IntGauge,
+    /// Bytes of the memory map holding live data.
+    used_bytes: IntGauge,
+    /// Bytes of the memory map on the free list, available for reuse.
+    free_bytes: IntGauge,
+    /// Percentage of the memory map holding live data.
+    utilization: Gauge,
+    /// Number of entries in each named database.
+    entries: IntGaugeVec,
+    /// Number of times a high utilization warning was logged.
+    utilization_warnings: IntCounter,
+    /// Reference to the registry for unregistering.
+    registry: Registry,
+}
+
+impl LmdbStatsCollector {
+    /// Creates a new collector, registering its metrics with names prefixed by `name`.
+    pub(crate) fn new(
+        name: &'static str,
+        warning_threshold_percent: u8,
+        registry: &Registry,
+    ) -> Result<Self, prometheus::Error> {
+        let map_size = IntGauge::new(
+            format!("{}_lmdb_map_size_bytes", name),
+            format!("maximum size of the {} LMDB environment in bytes", name),
+        )?;
+        let used_bytes = IntGauge::new(
+            format!("{}_lmdb_used_bytes", name),
+            format!("bytes of the {} LMDB environment holding live data", name),
+        )?;
+        let free_bytes = IntGauge::new(
+            format!("{}_lmdb_free_bytes", name),
+            format!("bytes of the {} LMDB environment free for reuse", name),
+        )?;
+        let utilization = Gauge::new(
+            format!("{}_lmdb_utilization_percent", name),
+            format!(
+                "percentage of the {} LMDB environment's maximum size in use",
+                name
+            ),
+        )?;
+        let entries = IntGaugeVec::new(
+            Opts::new(
+                format!("{}_lmdb_entries", name),
+                format!(
+                    "number of entries in each database of the {} LMDB environment",
+                    name
+                ),
+            ),
+            &["database"],
+        )?;
+        let utilization_warnings = IntCounter::new(
+            format!("{}_lmdb_utilization_warnings", name),
+            format!(
+                "number of warnings about the {} LMDB environment filling up",
+                name
+            ),
+        )?;
+        registry.register(Box::new(map_size.clone()))?;
+        registry.register(Box::new(used_bytes.clone()))?;
+        registry.register(Box::new(free_bytes.clone()))?;
+        registry.register(Box::new(utilization.clone()))?;
+        registry.register(Box::new(entries.clone()))?;
+        registry.register(Box::new(utilization_warnings.clone()))?;
+
+        Ok(LmdbStatsCollector {
+            name,
+            warning_threshold_percent,
+            last_collected: None,
+            map_size,
+            used_bytes,
+            free_bytes,
+            utilization,
+            entries,
+            utilization_warnings,
+            registry: registry.clone(),
+        })
+    }
+
+    /// Collects the statistics unless they were already collected within `COLLECTION_INTERVAL`.
+    ///
+    /// A failure to collect is logged rather than returned, as it doesn't affect normal operation.
+    pub(crate) fn collect_if_due(&mut self, env: &Environment, databases: &[(&str, Database)]) {
+        let is_due = self
+            .last_collected
+            .map_or(true, |collected| collected.elapsed() >= COLLECTION_INTERVAL);
+        if !is_due {
+            return;
+        }
+        if let Err(error) = self.collect(env, databases) {
+            error!(%error, environment = self.name, "failed to collect LMDB statistics");
+        }
+    }
+
+    /// Collects the statistics and updates the metrics, warning if the utilization is at or above
+    /// the configured threshold.
+    pub(crate) fn collect(
+        &mut self,
+        env: &Environment,
+        databases: &[(&str, Database)],
+    ) -> Result<LmdbStats, lmdb::Error> {
+        self.last_collected = Some(Instant::now());
+        let stats = LmdbStats::collect(env, databases)?;
+
+        let free_bytes = pages_to_bytes(stats.free_pages, stats.page_size);
+        self.map_size.set(saturating_i64(stats.map_size));
+        self.used_bytes.set(saturating_i64(stats.used_bytes()));
+        self.free_bytes.set(saturating_i64(free_bytes));
+        self.utilization.set(stats.utilization_percent());
+        for (database, entries) in &stats.entries {
+            self.entries
+                .with_label_values(&[database.as_str()])
+                .set(saturating_i64(*entries));
+        }
+
+        if stats.utilization_percent() >= f64::from(self.warning_threshold_percent) {
+            self.utilization_warnings.inc();
+            warn!(
+                environment = self.name,
+                utilization = %format!("{:.1}%", stats.utilization_percent()),
+                used_bytes = stats.used_bytes(),
+                map_size = stats.map_size,
+                "LMDB environment is close to its maximum size, which should be increased"
+            );
+        }
+
+        Ok(stats)
+    }
+
+    /// Returns the number of high utilization warnings logged so far.
+    #[cfg(test)]
+    pub(crate) fn utilization_warnings(&self) -> u64 {
+        self.utilization_warnings.get()
+    }
+}
+
+impl Drop for LmdbStatsCollector {
+    fn drop(&mut self) {
+        unregister_metric!(self.registry, self.map_size);
+        unregister_metric!(self.registry, self.used_bytes);
+        unregister_metric!(self.registry, self.free_bytes);
+        unregister_metric!(self.registry, self.utilization);
+        unregister_metric!(self.registry, self.entries);
+        unregister_metric!(self.registry, self.utilization_warnings);
+    }
+}
+
+/// Converts a statistic to the value type of an integer gauge.
+fn saturating_i64(value: u64) -> i64 {
+    i64::try_from(value).unwrap_or(i64::MAX)
+}
@@ -0,0 +1,69 @@
+use std::time::Duration;
+
+use prometheus::{Histogram, HistogramOpts, Registry};
+
+use crate::unregister_metric;
+
+/// Upper bounds of the buckets of the write batch size histogram.
+const WRITE_BATCH_SIZE_BUCKETS: &[f64] = &[1.0, 2.0, 4.0, 8.0, 16.0, 32.0, 64.0, 128.0, 256.0];
+/// Value of upper bound of the first bucket of the commit latency histogram, in seconds.
+const COMMIT_LATENCY_BUCKET_START: f64 = 0.000_5;
+/// Multiplier of previous upper bound for next bound.
+const COMMIT_LATENCY_BUCKET_FACTOR: f64 = 2.0;
+/// Bucket count, with last going to +Inf.
+const COMMIT_LATENCY_BUCKET_COUNT: usize = 12;
+
+#[derive(Debug)]
+pub(super) struct StorageMetrics {
+    /// Number of requests committed together in each write batch.
+    pub(super) write_batch_size: Histogram,
+    /// Time taken to write and commit each write batch, in seconds.
+    pub(super) write_batch_commit_latency: Histogram,
+    /// Reference to the registry for unregistering.
+    registry: Registry,
+}
+
+impl StorageMetrics {
+    pub(super) fn new(registry: &Registry) -> Result<Self, prometheus::Error> {
+        let write_batch_size = Histogram::with_opts(
+            HistogramOpts::new(
+                "storage_write_batch_size",
+                "number of storage requests committed together in a single write transaction",
+            )
+            .buckets(WRITE_BATCH_SIZE_BUCKETS.to_vec()),
+        )?;
+        let write_batch_commit_latency = Histogram::with_opts(
+            HistogramOpts::new(
+                "storage_write_batch_commit_latency",
+                "time in seconds to write and commit a batch of storage requests",
+            )
+            .buckets(prometheus::exponential_buckets(
+                COMMIT_LATENCY_BUCKET_START,
+                COMMIT_LATENCY_BUCKET_FACTOR,
+                COMMIT_LATENCY_BUCKET_COUNT,
+            )?),
+        )?;
+        registry.register(Box::new(write_batch_size.clone()))?;
+        registry.register(Box::new(write_batch_commit_latency.clone()))?;
+
+        Ok(StorageMetrics {
+            write_batch_size,
+            write_batch_commit_latency,
+            registry: registry.clone(),
+        })
+    }
+
+    /// Records a committed write batch of `size` requests.
+    pub(super) fn observe_write_batch(&self, size: usize, commit_latency: Duration) {
+        self.write_batch_size.observe(size as f64);
+        self.write_batch_commit_latency
+            .observe(commit_latency.as_secs_f64());
+    }
+}
+
+impl Drop for StorageMetrics {
+    fn drop(&mut self) {
+        unregister_metric!(self.registry, self.write_batch_size);
+        unregister_metric!(self.registry, self.write_batch_commit_latency);
+    }
+}
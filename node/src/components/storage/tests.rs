@@ -1,26 +1,41 @@
 //! Unit tests for the storage component.
 
-use std::{borrow::Cow, collections::HashMap};
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap},
+    ops::RangeInclusive,
+};
 
-use lmdb::Transaction;
+use futures::channel::oneshot;
+use lmdb::{Transaction, WriteFlags};
+use prometheus::Registry;
 use rand::{prelude::SliceRandom, Rng};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use smallvec::smallvec;
 
-use casper_types::{EraId, ExecutionResult, ProtocolVersion, PublicKey, SecretKey};
+use casper_execution_engine::shared::newtypes::Blake2bHash;
+use casper_types::{EraId, ExecutionResult, ProtocolVersion, PublicKey, SecretKey, U512};
 
-use super::{Config, Storage};
+use super::{
+    deploy_metadata::{self, StoredFormat},
+    write_batch::MAX_WRITE_BATCH_COUNT,
+    Config, Error, Event, Storage,
+};
 use crate::{
-    components::storage::lmdb_ext::WriteTransactionExt,
-    crypto::AsymmetricKeyExt,
+    components::{
+        consensus::EraReport,
+        storage::lmdb_ext::{LmdbExtError, WriteTransactionExt},
+    },
+    crypto::{hash::Digest, AsymmetricKeyExt},
     effect::{
         requests::{StateStoreRequest, StorageRequest},
-        Multiple,
+        Effects, Multiple, Responder,
     },
     testing::{ComponentHarness, TestRng, UnitTestEvent},
     types::{
-        Block, BlockHash, BlockHeader, BlockSignatures, Deploy, DeployHash, DeployMetadata,
-        FinalitySignature,
+        Approval, Block, BlockHash, BlockHeader, BlockPayload, BlockSignatures, Deploy, DeployHash,
+        DeployMetadata, FinalitySignature, FinalizedBlock, Timestamp, ValidatorChange,
+        ValidatorPerformance,
     },
     utils::WithDir,
 };
@@ -37,6 +52,8 @@ fn new_config(harness: &ComponentHarness<UnitTestEvent>) -> Config {
         max_state_store_size: 50 * MIB,
         enable_mem_deduplication: false,
         mem_pool_prune_interval: 1024,
+        lmdb_utilization_warning_percent: 85,
+        migrate_deploy_metadata: false,
     }
 }
 
@@ -54,6 +71,7 @@ fn storage_fixture(harness: &ComponentHarness<UnitTestEvent>) -> Storage {
         None,
         ProtocolVersion::from_parts(1, 0, 0),
         false,
+        &Registry::new(),
     )
     .expect("could not create storage component fixture")
 }
@@ -75,6 +93,7 @@ fn storage_fixture_with_hard_reset(
         Some(reset_era_id),
         ProtocolVersion::from_parts(1, 1, 0),
         false,
+        &Registry::new(),
     )
     .expect("could not create storage component fixture")
 }
@@ -181,6 +200,23 @@ fn get_deploys(
     response
 }
 
+/// Loads a deploy's approvals from a storage component.
+fn get_deploy_approvals(
+    harness: &mut ComponentHarness<UnitTestEvent>,
+    storage: &mut Storage,
+    deploy_hash: DeployHash,
+) -> Option<Vec<Approval>> {
+    let response = harness.send_request(storage, move |responder| {
+        StorageRequest::GetDeployApprovals {
+            deploy_hash,
+            responder,
+        }
+        .into()
+    });
+    assert!(harness.is_idle());
+    response
+}
+
 /// Loads a deploy with associated metadata from the storage component.
 fn get_deploy_and_metadata(
     harness: &mut ComponentHarness<UnitTestEvent>,
@@ -247,30 +283,160 @@ fn put_block_signatures(
     storage: &mut Storage,
     signatures: BlockSignatures,
 ) -> bool {
-    let response = harness.send_request(storage, move |responder| {
+    let receiver = send_batched_request(harness, storage, move |responder| {
         StorageRequest::PutBlockSignatures {
             signatures,
             responder,
         }
         .into()
     });
+    flush_write_batch(harness, storage);
+    let response = harness
+        .runtime
+        .block_on(receiver)
+        .expect("should receive response");
+    assert!(harness.is_idle());
+    response
+}
+
+/// Stores the validator performance of an era in a storage component.
+fn put_era_performance(
+    harness: &mut ComponentHarness<UnitTestEvent>,
+    storage: &mut Storage,
+    era_id: u64,
+    performance: BTreeMap<PublicKey, ValidatorPerformance>,
+    oldest_retained_era: u64,
+) {
+    harness.send_request(storage, move |responder| {
+        StorageRequest::PutEraPerformance {
+            era_id: EraId::from(era_id),
+            performance,
+            oldest_retained_era: EraId::from(oldest_retained_era),
+            responder,
+        }
+        .into()
+    });
+    assert!(harness.is_idle());
+}
+
+/// Loads the validator performance of a range of eras from a storage component.
+fn get_era_performance(
+    harness: &mut ComponentHarness<UnitTestEvent>,
+    storage: &mut Storage,
+    era_ids: RangeInclusive<u64>,
+    maybe_public_key: Option<PublicKey>,
+) -> BTreeMap<EraId, BTreeMap<PublicKey, ValidatorPerformance>> {
+    let era_ids = EraId::from(*era_ids.start())..=EraId::from(*era_ids.end());
+    let response = harness.send_request(storage, move |responder| {
+        StorageRequest::GetEraPerformance {
+            era_ids,
+            maybe_public_key: maybe_public_key.map(Box::new),
+            responder,
+        }
+        .into()
+    });
+    assert!(harness.is_idle());
+    response
+}
+
+/// Loads the changes to the validator set taking effect in a range of eras from a storage
+/// component.
+fn get_validator_changes(
+    harness: &mut ComponentHarness<UnitTestEvent>,
+    storage: &mut Storage,
+    era_ids: RangeInclusive<u64>,
+) -> BTreeMap<EraId, BTreeMap<PublicKey, Vec<ValidatorChange>>> {
+    let era_ids = EraId::from(*era_ids.start())..=EraId::from(*era_ids.end());
+    let response = harness.send_request(storage, move |responder| {
+        StorageRequest::GetValidatorChanges { era_ids, responder }.into()
+    });
     assert!(harness.is_idle());
     response
 }
 
+/// Creates a switch block of the given era with the given validator weights for the next era,
+/// reporting the given equivocators and inactive validators.
+fn switch_block(
+    rng: &mut TestRng,
+    era_id: u64,
+    next_era_validator_weights: BTreeMap<PublicKey, U512>,
+    equivocators: Vec<PublicKey>,
+    inactive_validators: Vec<PublicKey>,
+) -> Box<Block> {
+    let era_report = EraReport {
+        equivocators,
+        rewards: BTreeMap::new(),
+        inactive_validators,
+    };
+    let finalized_block = FinalizedBlock::new(
+        BlockPayload::new(vec![], vec![], vec![], rng.gen()),
+        Some(era_report),
+        Timestamp::now(),
+        EraId::from(era_id),
+        era_id * 10 + 9,
+        PublicKey::random(rng),
+    );
+    Box::new(Block::new(
+        BlockHash::random(rng),
+        Digest::random(rng),
+        Digest::random(rng),
+        finalized_block,
+        Some(next_era_validator_weights),
+        ProtocolVersion::V1_0_0,
+    ))
+}
+
 /// Stores a deploy in a storage component.
 fn put_deploy(
     harness: &mut ComponentHarness<UnitTestEvent>,
     storage: &mut Storage,
     deploy: Box<Deploy>,
 ) -> bool {
-    let response = harness.send_request(storage, move |responder| {
+    let receiver = send_batched_request(harness, storage, move |responder| {
         StorageRequest::PutDeploy { deploy, responder }.into()
     });
+    flush_write_batch(harness, storage);
+    let response = harness
+        .runtime
+        .block_on(receiver)
+        .expect("should receive response");
     assert!(harness.is_idle());
     response
 }
 
+/// Sends a request which is added to the write batch, returning the receiver of its response.
+///
+/// The response is only sent once the batch has been committed, e.g. via `flush_write_batch`.
+fn send_batched_request<F>(
+    harness: &mut ComponentHarness<UnitTestEvent>,
+    storage: &mut Storage,
+    f: F,
+) -> oneshot::Receiver<bool>
+where
+    F: FnOnce(Responder<bool>) -> Event,
+{
+    let (sender, receiver) = oneshot::channel();
+    let effects = harness.send_event(storage, f(Responder::create(sender)));
+    run_effects(harness, effects);
+    receiver
+}
+
+/// Commits the write batch of a storage component.
+///
+/// The harness drops the events created by effects, so the flush scheduled by a batched request
+/// never reaches the component and has to be triggered explicitly.
+fn flush_write_batch(harness: &mut ComponentHarness<UnitTestEvent>, storage: &mut Storage) {
+    let effects = harness.send_event(storage, Event::FlushWriteBatch);
+    run_effects(harness, effects);
+}
+
+/// Spawns the given effects on the harness' runtime.
+fn run_effects(harness: &mut ComponentHarness<UnitTestEvent>, effects: Effects<Event>) {
+    for effect in effects {
+        let _ = harness.runtime.spawn(effect);
+    }
+}
+
 /// Stores execution results in a storage component.
 fn put_execution_results(
     harness: &mut ComponentHarness<UnitTestEvent>,
@@ -290,6 +456,52 @@ fn put_execution_results(
     response
 }
 
+/// Writes deploy metadata directly to a storage component in the legacy, unversioned format.
+fn put_legacy_deploy_metadata(
+    storage: &Storage,
+    deploy_hash: &DeployHash,
+    deploy_metadata: &DeployMetadata,
+) {
+    let mut txn = storage.env.begin_rw_txn().unwrap();
+    txn.put_value(
+        storage.stores.deploy_metadata_db,
+        deploy_hash,
+        deploy_metadata,
+        true,
+    )
+    .unwrap();
+    txn.commit().unwrap();
+}
+
+/// Returns the format in which the metadata of the given deploy is stored.
+fn stored_deploy_metadata_format(storage: &Storage, deploy_hash: &DeployHash) -> StoredFormat {
+    let txn = storage.env.begin_ro_txn().unwrap();
+    let raw = txn
+        .get(storage.stores.deploy_metadata_db, deploy_hash)
+        .expect("should have stored deploy metadata");
+    deploy_metadata::decode(raw).unwrap().1
+}
+
+/// Creates a storage component in the same directory as the given harness's earlier ones, with
+/// eager migration of deploy metadata set as given.
+fn storage_fixture_with_migration(
+    harness: &ComponentHarness<UnitTestEvent>,
+    migrate_deploy_metadata: bool,
+) -> Storage {
+    let cfg = Config {
+        migrate_deploy_metadata,
+        ..new_config(harness)
+    };
+    Storage::new(
+        &WithDir::new(harness.tmp.path(), cfg),
+        None,
+        ProtocolVersion::from_parts(1, 0, 0),
+        false,
+        &Registry::new(),
+    )
+    .expect("could not create storage component fixture")
+}
+
 /// Saves state from the storage component.
 fn save_state<T>(
     harness: &mut ComponentHarness<UnitTestEvent>,
@@ -406,7 +618,7 @@ fn test_get_block_header_and_finality_signatures_by_height() {
         .expect("Could not start transaction");
     let was_new = txn
         .put_value(
-            storage.block_metadata_db,
+            storage.stores.block_metadata_db,
             &block.hash(),
             &block_signatures,
             true,
@@ -652,6 +864,23 @@ fn can_retrieve_store_and_load_deploys() {
     });
     assert_eq!(response, vec![Some(deploy.header().clone())]);
 
+    // And just the approvals.
+    let response = get_deploy_approvals(&mut harness, &mut storage, *deploy.id());
+    assert_eq!(response, Some(deploy.approvals().to_vec()));
+
+    // The network-facing reads should return the same.
+    let deploy_header = storage
+        .read_deploy_header_with_hash(deploy.id())
+        .expect("should read deploy header")
+        .expect("deploy header should exist");
+    assert_eq!(deploy_header.deploy_hash(), deploy.id());
+    assert_eq!(deploy_header.header(), deploy.header());
+    let deploy_approvals = storage
+        .read_deploy_approvals(deploy.id())
+        .expect("should read deploy approvals")
+        .expect("deploy approvals should exist");
+    assert_eq!(deploy_approvals.approvals(), deploy.approvals());
+
     // Finally try to get the metadata as well. Since we did not store any, we expect empty default
     // metadata to present.
     let (deploy_response, metadata_response) = harness
@@ -668,6 +897,32 @@ fn can_retrieve_store_and_load_deploys() {
     assert_eq!(metadata_response, DeployMetadata::default());
 }
 
+#[test]
+fn should_get_approvals_of_deploy_stored_without_approvals_entry() {
+    let mut harness = ComponentHarness::default();
+    let mut storage = storage_fixture(&harness);
+
+    // Write a deploy directly to the deploy database, as done before the approvals database
+    // existed.
+    let deploy = Deploy::random(&mut harness.rng);
+    let mut txn = storage
+        .env
+        .begin_rw_txn()
+        .expect("Could not start transaction");
+    let was_new = txn
+        .put_value(storage.stores.deploy_db, deploy.id(), &deploy, false)
+        .expect("should put value into LMDB");
+    assert!(was_new, "putting deploy should have returned `true`");
+    txn.commit().expect("Could not commit transaction");
+
+    let response = get_deploy_approvals(&mut harness, &mut storage, *deploy.id());
+    assert_eq!(response, Some(deploy.approvals().to_vec()));
+
+    let unknown_deploy_hash = DeployHash::random(&mut harness.rng);
+    let response = get_deploy_approvals(&mut harness, &mut storage, unknown_deploy_hash);
+    assert!(response.is_none());
+}
+
 #[test]
 fn storing_and_loading_a_lot_of_deploys_does_not_exhaust_handles() {
     let mut harness = ComponentHarness::default();
@@ -694,6 +949,123 @@ fn storing_and_loading_a_lot_of_deploys_does_not_exhaust_handles() {
     }
 }
 
+#[test]
+fn should_batch_deploy_writes() {
+    let mut harness = ComponentHarness::default();
+    let mut storage = storage_fixture(&harness);
+
+    let count = 20;
+    let deploys: Vec<_> = (0..count)
+        .map(|_| Box::new(Deploy::random(&mut harness.rng)))
+        .collect();
+
+    // Pending deploys should be visible to reads interleaved with the writes.
+    let mut receivers = Vec::new();
+    for deploy in &deploys {
+        let deploy = deploy.clone();
+        let deploy_hash = *deploy.id();
+        receivers.push(send_batched_request(
+            &mut harness,
+            &mut storage,
+            move |responder| StorageRequest::PutDeploy { deploy, responder }.into(),
+        ));
+        let deploy_header = storage
+            .read_deploy_header_with_hash(&deploy_hash)
+            .expect("should read deploy header");
+        assert!(deploy_header.is_some());
+        let deploy_approvals = storage
+            .read_deploy_approvals(&deploy_hash)
+            .expect("should read deploy approvals");
+        assert!(deploy_approvals.is_some());
+    }
+
+    // A duplicate of a pending deploy should be reported as already stored.
+    let duplicate = deploys[0].clone();
+    receivers.push(send_batched_request(
+        &mut harness,
+        &mut storage,
+        move |responder| {
+            StorageRequest::PutDeploy {
+                deploy: duplicate,
+                responder,
+            }
+            .into()
+        },
+    ));
+
+    flush_write_batch(&mut harness, &mut storage);
+    let responses: Vec<bool> = receivers
+        .into_iter()
+        .map(|receiver| {
+            harness
+                .runtime
+                .block_on(receiver)
+                .expect("should receive response")
+        })
+        .collect();
+    assert!(responses[..count].iter().all(|was_new| *was_new));
+    assert!(!responses[count]);
+
+    // All requests should have been committed in fewer transactions than requests.  A batch is
+    // only committed before the explicit flush if it became overdue while requests were being sent.
+    let committed = storage.metrics.write_batch_size.get_sample_sum();
+    let commit_count = storage.metrics.write_batch_size.get_sample_count();
+    assert_eq!(committed as usize, count + 1);
+    assert!(commit_count < count as u64);
+
+    // Every deploy should have been stored.
+    let deploy_hashes = deploys.iter().map(|deploy| *deploy.id()).collect();
+    let response = get_deploys(&mut harness, &mut storage, deploy_hashes);
+    assert!(response.iter().all(Option::is_some));
+    assert_eq!(storage.get_all_deploy_hashes().len(), count);
+}
+
+#[test]
+fn should_commit_full_write_batch() {
+    let mut harness = ComponentHarness::default();
+    let mut storage = storage_fixture(&harness);
+
+    let mut deploys: Vec<_> = (0..MAX_WRITE_BATCH_COUNT)
+        .map(|_| Deploy::random(&mut harness.rng))
+        .collect();
+    let last_deploy = Box::new(deploys.pop().expect("should have deploys"));
+
+    // Add all but one deploy to the batch directly, as sending that many requests could take long
+    // enough for the batch to become overdue.
+    let mut receivers: Vec<_> = deploys
+        .into_iter()
+        .map(|deploy| {
+            let (sender, receiver) = oneshot::channel();
+            storage
+                .stores
+                .write_batch
+                .put_deploy(deploy, Responder::create(sender));
+            receiver
+        })
+        .collect();
+    assert!(!storage.stores.write_batch.is_full());
+
+    // The batch should be committed without an explicit flush once the last request fills it.
+    receivers.push(send_batched_request(
+        &mut harness,
+        &mut storage,
+        move |responder| {
+            StorageRequest::PutDeploy {
+                deploy: last_deploy,
+                responder,
+            }
+            .into()
+        },
+    ));
+    assert!(storage.stores.write_batch.is_empty());
+    for receiver in receivers {
+        assert!(harness
+            .runtime
+            .block_on(receiver)
+            .expect("should receive response"));
+    }
+}
+
 #[test]
 fn store_execution_results_for_two_blocks() {
     let mut harness = ComponentHarness::default();
@@ -1152,3 +1524,406 @@ fn should_hard_reset() {
     // Test with a hard reset to era 0, deleting all blocks and associated data.
     check(0);
 }
+
+#[test]
+fn should_reindex_blocks_and_deploys_on_restart_with_integrity_check() {
+    let mut harness = ComponentHarness::default();
+    let mut storage = storage_fixture(&harness);
+
+    // Reindexing reads every block's header and body.  The bodies used to be read in a transaction
+    // nested within the one used to iterate the headers.
+    let blocks: Vec<_> = (0..5)
+        .map(|height| random_block_at_height(&mut harness.rng, height))
+        .collect();
+    for block in &blocks {
+        put_block(&mut harness, &mut storage, block.clone());
+    }
+    drop(storage);
+
+    let storage = Storage::new(
+        &WithDir::new(harness.tmp.path(), new_config(&harness)),
+        None,
+        ProtocolVersion::from_parts(1, 0, 0),
+        true,
+        &Registry::new(),
+    )
+    .expect("should reindex storage");
+
+    for block in &blocks {
+        assert_eq!(
+            storage.stores.block_height_index.get(&block.height()),
+            Some(block.hash())
+        );
+        for deploy_hash in block.deploy_hashes() {
+            assert_eq!(
+                storage.stores.deploy_hash_index.get(deploy_hash),
+                Some(block.hash())
+            );
+        }
+    }
+}
+
+#[test]
+fn should_get_state_root_hashes_for_trie_check() {
+    let mut harness = ComponentHarness::default();
+    let mut storage = storage_fixture(&harness);
+
+    let mut expected_state_root_hashes = Vec::new();
+    for height in 0..3 {
+        let block = random_block_at_height(&mut harness.rng, height);
+        expected_state_root_hashes.push(Blake2bHash::from(*block.header().state_root_hash()));
+        put_block(&mut harness, &mut storage, block);
+    }
+    expected_state_root_hashes.sort();
+
+    let state_root_hashes = storage
+        .get_state_root_hashes_for_trie_check()
+        .expect("should get state root hashes");
+    assert_eq!(state_root_hashes, expected_state_root_hashes);
+}
+
+#[test]
+fn should_propagate_transaction_creation_failure() {
+    let mut harness = ComponentHarness::default();
+    let mut storage = storage_fixture(&harness);
+
+    let block = random_block_at_height(&mut harness.rng, 0);
+    put_block(&mut harness, &mut storage, block.clone());
+
+    // Hold open read transactions until LMDB refuses to create any more, so that the storage
+    // component's own attempts to create one fail.
+    let mut held_txns = Vec::new();
+    while let Ok(txn) = storage.env().begin_ro_txn() {
+        held_txns.push(txn);
+    }
+
+    let is_readers_full = |error: &LmdbExtError| {
+        matches!(
+            error,
+            LmdbExtError::ResourceExhausted(lmdb::Error::ReadersFull)
+        )
+    };
+    assert!(matches!(
+        storage.get_state_root_hashes_for_trie_check(),
+        Err(Error::InternalStorage(ref error)) if is_readers_full(error)
+    ));
+    assert!(matches!(
+        storage.read_block_header_by_hash(block.hash()),
+        Err(ref error) if is_readers_full(error)
+    ));
+    assert!(matches!(
+        storage.read_block_header_and_finality_signatures_by_height(0),
+        Err(Error::InternalStorage(ref error)) if is_readers_full(error)
+    ));
+
+    // Once the transactions are released, the same calls should succeed.
+    drop(held_txns);
+    assert!(storage.get_state_root_hashes_for_trie_check().is_ok());
+    assert_eq!(
+        storage
+            .read_block_header_by_hash(block.hash())
+            .expect("should read block header"),
+        Some(block.header().clone())
+    );
+}
+
+#[test]
+fn should_put_get_and_prune_era_performance() {
+    let mut harness = ComponentHarness::default();
+    let mut storage = storage_fixture(&harness);
+
+    let alice = PublicKey::random(&mut harness.rng);
+    let bob = PublicKey::random(&mut harness.rng);
+    let performance_in_era = |blocks_proposed: u64| -> BTreeMap<_, _> {
+        let performance = ValidatorPerformance {
+            blocks_proposed,
+            blocks_expected: 2,
+            finality_signatures_provided: 3,
+            finality_signatures_expected: 4,
+            equivocated: false,
+        };
+        vec![(alice.clone(), performance), (bob.clone(), performance)]
+            .into_iter()
+            .collect()
+    };
+
+    // Nothing is stored initially.
+    assert!(get_era_performance(&mut harness, &mut storage, 0..=10, None).is_empty());
+
+    for era_id in 1..=3 {
+        put_era_performance(
+            &mut harness,
+            &mut storage,
+            era_id,
+            performance_in_era(era_id),
+            1,
+        );
+    }
+    let stored = get_era_performance(&mut harness, &mut storage, 0..=10, None);
+    let expected: BTreeMap<_, _> = (1..=3)
+        .map(|era_id| (EraId::from(era_id), performance_in_era(era_id)))
+        .collect();
+    assert_eq!(stored, expected);
+
+    // Overwriting an era replaces its performance.
+    put_era_performance(&mut harness, &mut storage, 3, performance_in_era(5), 1);
+    let stored = get_era_performance(&mut harness, &mut storage, 3..=3, None);
+    assert_eq!(stored[&EraId::from(3)], performance_in_era(5));
+
+    // Only the requested eras and validator are returned.
+    let stored = get_era_performance(&mut harness, &mut storage, 2..=3, Some(bob.clone()));
+    assert_eq!(stored.len(), 2);
+    assert!(stored
+        .values()
+        .all(|performance| performance.keys().eq(Some(&bob))));
+
+    // Storing a later era prunes those older than the oldest retained one.
+    put_era_performance(&mut harness, &mut storage, 4, performance_in_era(4), 3);
+    let stored = get_era_performance(&mut harness, &mut storage, 0..=10, None);
+    let stored_eras: Vec<_> = stored.keys().copied().collect();
+    assert_eq!(stored_eras, vec![EraId::from(3), EraId::from(4)]);
+}
+
+#[test]
+fn should_report_lmdb_utilization_and_warn_past_threshold() {
+    const VALUE_SIZE: usize = 4096;
+    const VALUE_COUNT: u32 = 2560;
+
+    let mut harness = ComponentHarness::default();
+    // The environment's map size is 150 MiB, of which the written values take up over 10 MiB.
+    let cfg = Config {
+        lmdb_utilization_warning_percent: 10,
+        ..new_config(&harness)
+    };
+    let mut storage = Storage::new(
+        &WithDir::new(harness.tmp.path(), cfg),
+        None,
+        ProtocolVersion::from_parts(1, 0, 0),
+        false,
+        &Registry::new(),
+    )
+    .expect("could not create storage component fixture");
+
+    let before = harness
+        .send_request(&mut storage, |responder| {
+            StorageRequest::GetLmdbStats { responder }.into()
+        })
+        .expect("should collect statistics");
+    assert_eq!(before.entries["state_store"], 0);
+    assert_eq!(storage.lmdb_stats.utilization_warnings(), 0);
+
+    let mut txn = storage.env.begin_rw_txn().unwrap();
+    for index in 0..VALUE_COUNT {
+        txn.put(
+            storage.stores.state_store_db,
+            &index.to_be_bytes(),
+            &[1u8; VALUE_SIZE],
+            WriteFlags::empty(),
+        )
+        .unwrap();
+    }
+    txn.commit().unwrap();
+
+    let after = harness
+        .send_request(&mut storage, |responder| {
+            StorageRequest::GetLmdbStats { responder }.into()
+        })
+        .expect("should collect statistics");
+    assert_eq!(after.entries["state_store"], u64::from(VALUE_COUNT));
+    assert!(after.used_bytes() >= before.used_bytes() + VALUE_SIZE as u64 * u64::from(VALUE_COUNT));
+    assert!(after.utilization_percent() >= 10.0);
+    assert_eq!(storage.lmdb_stats.utilization_warnings(), 1);
+}
+
+#[test]
+fn should_read_legacy_deploy_metadata_and_rewrite_it_lazily() {
+    let mut harness = ComponentHarness::default();
+    let mut storage = storage_fixture(&harness);
+
+    let deploy = Deploy::random(&mut harness.rng);
+    put_deploy(&mut harness, &mut storage, Box::new(deploy.clone()));
+    let block_hash_a = BlockHash::random(&mut harness.rng);
+    let mut legacy_metadata = DeployMetadata::default();
+    legacy_metadata
+        .execution_results
+        .insert(block_hash_a, harness.rng.gen());
+    put_legacy_deploy_metadata(&storage, deploy.id(), &legacy_metadata);
+
+    // Reading the legacy entry doesn't rewrite it.
+    let (_, metadata) = get_deploy_and_metadata(&mut harness, &mut storage, *deploy.id())
+        .expect("should have deploy");
+    assert_eq!(metadata, legacy_metadata);
+    assert_eq!(
+        stored_deploy_metadata_format(&storage, deploy.id()),
+        StoredFormat::Legacy
+    );
+
+    // Writing to it does.
+    let block_hash_b = BlockHash::random(&mut harness.rng);
+    let execution_result: ExecutionResult = harness.rng.gen();
+    let mut execution_results = HashMap::new();
+    execution_results.insert(*deploy.id(), execution_result.clone());
+    put_execution_results(&mut harness, &mut storage, block_hash_b, execution_results);
+    assert_eq!(
+        stored_deploy_metadata_format(&storage, deploy.id()),
+        StoredFormat::V1
+    );
+
+    let (_, metadata) = get_deploy_and_metadata(&mut harness, &mut storage, *deploy.id())
+        .expect("should have deploy");
+    let mut expected_metadata = legacy_metadata;
+    expected_metadata
+        .execution_results
+        .insert(block_hash_b, execution_result);
+    assert_eq!(metadata, expected_metadata);
+}
+
+#[test]
+fn should_migrate_legacy_deploy_metadata_on_startup_only_if_enabled() {
+    let mut harness = ComponentHarness::default();
+    let mut storage = storage_fixture(&harness);
+
+    let mut expected = Vec::new();
+    for _ in 0..3 {
+        let deploy = Deploy::random(&mut harness.rng);
+        put_deploy(&mut harness, &mut storage, Box::new(deploy.clone()));
+        let mut legacy_metadata = DeployMetadata::default();
+        for _ in 0..2 {
+            legacy_metadata
+                .execution_results
+                .insert(BlockHash::random(&mut harness.rng), harness.rng.gen());
+        }
+        put_legacy_deploy_metadata(&storage, deploy.id(), &legacy_metadata);
+        expected.push((*deploy.id(), legacy_metadata));
+    }
+
+    drop(storage);
+    let storage = storage_fixture_with_migration(&harness, false);
+    for (deploy_hash, _) in &expected {
+        assert_eq!(
+            stored_deploy_metadata_format(&storage, deploy_hash),
+            StoredFormat::Legacy
+        );
+    }
+
+    drop(storage);
+    let mut storage = storage_fixture_with_migration(&harness, true);
+    for (deploy_hash, legacy_metadata) in expected {
+        assert_eq!(
+            stored_deploy_metadata_format(&storage, &deploy_hash),
+            StoredFormat::V1
+        );
+        let (_, metadata) = get_deploy_and_metadata(&mut harness, &mut storage, deploy_hash)
+            .expect("should have deploy");
+        assert_eq!(metadata, legacy_metadata);
+    }
+}
+
+#[test]
+fn should_compute_validator_changes_of_switch_blocks_stored_in_any_order() {
+    let mut harness = ComponentHarness::default();
+    let mut storage = storage_fixture(&harness);
+
+    let alice = PublicKey::random(&mut harness.rng);
+    let bob = PublicKey::random(&mut harness.rng);
+    let carol = PublicKey::random(&mut harness.rng);
+    let weights = |entries: &[(&PublicKey, u64)]| -> BTreeMap<PublicKey, U512> {
+        entries
+            .iter()
+            .map(|(public_key, weight)| ((*public_key).clone(), U512::from(*weight)))
+            .collect()
+    };
+
+    let switch_block_0 = switch_block(
+        &mut harness.rng,
+        0,
+        weights(&[(&alice, 10), (&bob, 20)]),
+        vec![],
+        vec![],
+    );
+    let switch_block_1 = switch_block(
+        &mut harness.rng,
+        1,
+        weights(&[(&alice, 10), (&bob, 25), (&carol, 5)]),
+        vec![],
+        vec![alice.clone()],
+    );
+    let switch_block_2 = switch_block(
+        &mut harness.rng,
+        2,
+        weights(&[(&bob, 25), (&carol, 5)]),
+        vec![carol.clone()],
+        vec![],
+    );
+
+    // Nothing can be computed without the switch block of the preceding era.
+    assert!(put_block(&mut harness, &mut storage, switch_block_0));
+    assert!(put_block(&mut harness, &mut storage, switch_block_2));
+    assert!(get_validator_changes(&mut harness, &mut storage, 0..=10).is_empty());
+
+    // Storing the missing switch block allows the changes of both following eras to be computed.
+    assert!(put_block(&mut harness, &mut storage, switch_block_1));
+    let stored = get_validator_changes(&mut harness, &mut storage, 0..=10);
+
+    let mut expected = BTreeMap::new();
+    let mut era_2_changes = BTreeMap::new();
+    era_2_changes.insert(alice.clone(), vec![ValidatorChange::Evicted]);
+    era_2_changes.insert(
+        bob,
+        vec![ValidatorChange::WeightChanged {
+            previous_weight: U512::from(20),
+            weight: U512::from(25),
+        }],
+    );
+    era_2_changes.insert(
+        carol.clone(),
+        vec![ValidatorChange::Added {
+            weight: U512::from(5),
+        }],
+    );
+    expected.insert(EraId::from(2), era_2_changes);
+    let mut era_3_changes = BTreeMap::new();
+    era_3_changes.insert(
+        alice,
+        vec![ValidatorChange::Removed {
+            previous_weight: U512::from(10),
+        }],
+    );
+    era_3_changes.insert(carol, vec![ValidatorChange::Banned]);
+    expected.insert(EraId::from(3), era_3_changes);
+    assert_eq!(stored, expected);
+
+    // Only the requested eras are returned.
+    let stored = get_validator_changes(&mut harness, &mut storage, 3..=3);
+    assert_eq!(stored.keys().collect::<Vec<_>>(), vec![&EraId::from(3)]);
+}
+
+#[test]
+fn should_compute_missing_validator_changes_on_startup() {
+    let mut harness = ComponentHarness::default();
+    let mut storage = storage_fixture(&harness);
+
+    let alice = PublicKey::random(&mut harness.rng);
+    let bob = PublicKey::random(&mut harness.rng);
+    for &(era_id, validator) in &[(0, &alice), (1, &bob)] {
+        let mut weights = BTreeMap::new();
+        weights.insert(validator.clone(), U512::from(100));
+        let block = switch_block(&mut harness.rng, era_id, weights, vec![], vec![]);
+        assert!(put_block(&mut harness, &mut storage, block));
+    }
+    let expected = get_validator_changes(&mut harness, &mut storage, 0..=10);
+    assert_eq!(expected[&EraId::from(2)].len(), 2);
+
+    // Simulate a database written before validator changes were tracked.
+    let mut txn = storage.env.begin_rw_txn().unwrap();
+    txn.clear_db(storage.stores.validator_changes_db).unwrap();
+    txn.commit().unwrap();
+    assert!(get_validator_changes(&mut harness, &mut storage, 0..=10).is_empty());
+
+    drop(storage);
+    let mut storage = storage_fixture(&harness);
+    assert_eq!(
+        get_validator_changes(&mut harness, &mut storage, 0..=10),
+        expected
+    );
+}
@@ -0,0 +1,153 @@
+//! Batching of deploy and block signature writes.
+//!
+//! LMDB only allows a single write transaction at a time, and syncs to disk whenever one is
+//! committed, so writing every deploy or set of block signatures in a transaction of its own makes
+//! storing a block's worth of them slow.  Instead, these writes are collected in a [`WriteBatch`]
+//! and committed together in a single transaction once the batch holds [`MAX_WRITE_BATCH_COUNT`]
+//! requests or [`MAX_WRITE_BATCH_SIZE`] bytes, or has been open for [`MAX_WRITE_BATCH_DELAY`].
+//!
+//! The responders of batched requests are only notified once the batch has been committed, so a
+//! response still means the write is durable.  Until then, reads consult the batch before the
+//! databases, so the node always observes its own writes.
+
+use std::{
+    collections::BTreeMap,
+    mem,
+    time::{Duration, Instant},
+};
+
+use datasize::DataSize;
+use lmdb::RwTransaction;
+use serde::Serialize;
+
+use super::{
+    lmdb_ext::{LmdbExtError, WriteTransactionExt},
+    Stores,
+};
+use crate::{
+    effect::Responder,
+    types::{BlockHash, BlockSignatures, Deploy, DeployHash},
+};
+
+/// The maximum number of requests in a write batch.
+pub(super) const MAX_WRITE_BATCH_COUNT: usize = 256;
+/// The maximum approximate size in bytes of the values in a write batch.
+pub(super) const MAX_WRITE_BATCH_SIZE: usize = 8 * 1024 * 1024;
+/// The maximum time a write batch is kept open before being committed.
+pub(super) const MAX_WRITE_BATCH_DELAY: Duration = Duration::from_millis(5);
+
+/// Writes which have been requested but not yet committed.
+#[derive(DataSize, Debug, Default)]
+pub(super) struct WriteBatch {
+    /// Deploys which are not yet stored.
+    deploys: BTreeMap<DeployHash, Deploy>,
+    /// Block signatures, each already merged with any signatures stored for the same block.
+    block_signatures: BTreeMap<BlockHash, BlockSignatures>,
+    /// The responders of the batched requests, along with the response to send to each.
+    responders: Vec<(Responder<bool>, bool)>,
+    /// The approximate size in bytes of the batched values.
+    size: usize,
+    /// The time at which the first request was added to the batch.
+    #[data_size(skip)]
+    opened: Option<Instant>,
+}
+
+impl WriteBatch {
+    /// Returns the number of requests in the batch.
+    pub(super) fn len(&self) -> usize {
+        self.responders.len()
+    }
+
+    /// Returns `true` if the batch holds no requests.
+    pub(super) fn is_empty(&self) -> bool {
+        self.responders.is_empty()
+    }
+
+    /// Returns `true` if the batch has reached its count or size bound.
+    pub(super) fn is_full(&self) -> bool {
+        self.len() >= MAX_WRITE_BATCH_COUNT || self.size >= MAX_WRITE_BATCH_SIZE
+    }
+
+    /// Returns `true` if the batch has been open for longer than `MAX_WRITE_BATCH_DELAY`.
+    pub(super) fn is_overdue(&self) -> bool {
+        self.opened
+            .map_or(false, |opened| opened.elapsed() >= MAX_WRITE_BATCH_DELAY)
+    }
+
+    /// Returns the pending deploy with the given hash, if any.
+    pub(super) fn deploy(&self, deploy_hash: &DeployHash) -> Option<&Deploy> {
+        self.deploys.get(deploy_hash)
+    }
+
+    /// Returns the hashes of all pending deploys.
+    #[cfg(test)]
+    pub(super) fn deploy_hashes(&self) -> impl Iterator<Item = &DeployHash> {
+        self.deploys.keys()
+    }
+
+    /// Returns the pending signatures of the given block, if any.
+    pub(super) fn block_signatures(&self, block_hash: &BlockHash) -> Option<&BlockSignatures> {
+        self.block_signatures.get(block_hash)
+    }
+
+    /// Adds a deploy which is not yet stored to the batch.
+    pub(super) fn put_deploy(&mut self, deploy: Deploy, responder: Responder<bool>) {
+        self.size += approximate_size(&deploy);
+        let _ = self.deploys.insert(*deploy.id(), deploy);
+        self.add_response(responder, true);
+    }
+
+    /// Adds a block's signatures to the batch, replacing any pending ones for the same block.
+    ///
+    /// The signatures must already include any previously stored or pending ones.
+    pub(super) fn put_block_signatures(
+        &mut self,
+        signatures: BlockSignatures,
+        responder: Responder<bool>,
+    ) {
+        self.size += approximate_size(&signatures);
+        let _ = self
+            .block_signatures
+            .insert(signatures.block_hash, signatures);
+        self.add_response(responder, true);
+    }
+
+    /// Adds a request which requires no write, but is only responded to along with the batch.
+    pub(super) fn add_response(&mut self, responder: Responder<bool>, response: bool) {
+        self.opened.get_or_insert_with(Instant::now);
+        self.responders.push((responder, response));
+    }
+
+    /// Removes and returns all requests, leaving the batch empty.
+    pub(super) fn take(&mut self) -> Self {
+        mem::take(self)
+    }
+
+    /// Writes the batch to the given transaction, returning the responders to notify once the
+    /// transaction has been committed.
+    pub(super) fn write(
+        self,
+        txn: &mut RwTransaction,
+        stores: &Stores,
+    ) -> Result<Vec<(Responder<bool>, bool)>, LmdbExtError> {
+        for (deploy_hash, deploy) in self.deploys {
+            if txn.put_value(stores.deploy_db, &deploy_hash, &deploy, false)? {
+                txn.put_value(
+                    stores.deploy_approvals_db,
+                    &deploy_hash,
+                    &deploy.approvals(),
+                    false,
+                )?;
+            }
+        }
+        for (block_hash, signatures) in self.block_signatures {
+            txn.put_value(stores.block_metadata_db, &block_hash, &signatures, true)?;
+        }
+        Ok(self.responders)
+    }
+}
+
+/// Returns the approximate size of a value once stored.
+fn approximate_size<T: Serialize>(value: &T) -> usize {
+    bincode::serialized_size(value).unwrap_or_default() as usize
+}
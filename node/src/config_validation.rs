@@ -0,0 +1,193 @@
+//! Validation of the node's config, run before any component is constructed.
+//!
+//! Each config section checks its own values via a `validate` method, pushing a
+//! [`ConfigValidationError`] identifying the offending field by its path, e.g.
+//! `network.bind_address`, for every problem found.  All problems are reported at once.
+
+use std::{
+    ffi::CString,
+    fmt::{self, Display, Formatter},
+    os::unix::ffi::OsStrExt,
+    path::Path,
+};
+
+use serde::de::DeserializeOwned;
+use thiserror::Error;
+use toml::Value;
+
+/// A problem found while validating the node's config, identifying the offending field by its
+/// path, e.g. `network.bind_address`.
+#[derive(Clone, PartialEq, Eq, Debug, Error)]
+pub enum ConfigValidationError {
+    /// A value could not be parsed as the type of the field.
+    #[error("[{field}] could not be parsed: {reason}")]
+    Malformed {
+        /// The path of the field.
+        field: String,
+        /// A description of the problem.
+        reason: String,
+    },
+
+    /// A value is outside its permitted range.
+    #[error("[{field}] is {value}, but must be {expected}")]
+    OutOfRange {
+        /// The path of the field.
+        field: String,
+        /// The value of the field.
+        value: String,
+        /// A description of the permitted range.
+        expected: String,
+    },
+
+    /// A value is inconsistent with the value of another field.
+    #[error("[{field}] {reason}")]
+    Inconsistent {
+        /// The path of the field.
+        field: String,
+        /// A description of the inconsistency.
+        reason: String,
+    },
+
+    /// An address is not of the form `<host>:<port>`.
+    #[error("[{field}] is '{address}', which is not a valid address: {reason}")]
+    InvalidAddress {
+        /// The path of the field.
+        field: String,
+        /// The value of the field.
+        address: String,
+        /// A description of the problem.
+        reason: String,
+    },
+
+    /// A directory can't be used as required.
+    #[error("[{field}] is '{path}', but {reason}")]
+    UnusableDirectory {
+        /// The path of the field.
+        field: String,
+        /// The directory, resolved relative to the config file's directory.
+        path: String,
+        /// A description of the problem.
+        reason: String,
+    },
+}
+
+impl ConfigValidationError {
+    /// Returns the path of the offending field.
+    pub fn field(&self) -> &str {
+        match self {
+            ConfigValidationError::Malformed { field, .. }
+            | ConfigValidationError::OutOfRange { field, .. }
+            | ConfigValidationError::Inconsistent { field, .. }
+            | ConfigValidationError::InvalidAddress { field, .. }
+            | ConfigValidationError::UnusableDirectory { field, .. } => field,
+        }
+    }
+
+    pub(crate) fn out_of_range<V: Display, E: Display>(field: &str, value: V, expected: E) -> Self {
+        ConfigValidationError::OutOfRange {
+            field: field.to_string(),
+            value: value.to_string(),
+            expected: expected.to_string(),
+        }
+    }
+
+    pub(crate) fn inconsistent<R: Display>(field: &str, reason: R) -> Self {
+        ConfigValidationError::Inconsistent {
+            field: field.to_string(),
+            reason: reason.to_string(),
+        }
+    }
+}
+
+/// The problems found in a config, all of which are listed when displayed.
+#[derive(Clone, PartialEq, Eq, Debug, Error)]
+pub struct InvalidConfigError(pub Vec<ConfigValidationError>);
+
+impl Display for InvalidConfigError {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        write!(formatter, "config has {} problem(s):", self.0.len())?;
+        for error in &self.0 {
+            write!(formatter, "\n  {}", error)?;
+        }
+        Ok(())
+    }
+}
+
+/// Deserializes a config from its TOML table, reporting the path of the field which failed to
+/// parse, if any.
+pub fn deserialize_config<C: DeserializeOwned>(table: Value) -> Result<C, InvalidConfigError> {
+    serde_path_to_error::deserialize(table).map_err(|error| {
+        InvalidConfigError(vec![ConfigValidationError::Malformed {
+            field: error.path().to_string(),
+            reason: error.inner().to_string(),
+        }])
+    })
+}
+
+/// Checks that `address` is of the form `<host>:<port>`, without resolving the host.
+///
+/// If `allow_port_zero` is false, the port must also be non-zero.
+pub(crate) fn check_address(
+    field: &str,
+    address: &str,
+    allow_port_zero: bool,
+    errors: &mut Vec<ConfigValidationError>,
+) {
+    let invalid = |reason: &str| ConfigValidationError::InvalidAddress {
+        field: field.to_string(),
+        address: address.to_string(),
+        reason: reason.to_string(),
+    };
+
+    let (host, port) = match address.rsplit_once(':') {
+        Some(host_and_port) => host_and_port,
+        None => return errors.push(invalid("missing ':<port>'")),
+    };
+    if host.is_empty() {
+        return errors.push(invalid("missing host"));
+    }
+    match port.parse::<u16>() {
+        Ok(0) if !allow_port_zero => errors.push(invalid("port must be in range 1..=65535")),
+        Ok(_) => (),
+        Err(_) => errors.push(invalid("port must be in range 0..=65535")),
+    }
+}
+
+/// Checks that `path` is a writable directory, or, if it doesn't exist yet, that it can be created
+/// in its nearest existing ancestor.
+pub(crate) fn check_writable_dir(
+    field: &str,
+    path: &Path,
+    errors: &mut Vec<ConfigValidationError>,
+) {
+    let unusable = |reason: String| ConfigValidationError::UnusableDirectory {
+        field: field.to_string(),
+        path: path.display().to_string(),
+        reason,
+    };
+
+    let existing = match path.ancestors().find(|ancestor| ancestor.exists()) {
+        Some(existing) => existing,
+        None => return errors.push(unusable("no part of it exists".to_string())),
+    };
+    let which = if existing == path {
+        "it".to_string()
+    } else {
+        format!("its ancestor '{}'", existing.display())
+    };
+    if !existing.is_dir() {
+        errors.push(unusable(format!("{} is not a directory", which)));
+    } else if !is_writable(existing) {
+        errors.push(unusable(format!("{} is not writable", which)));
+    }
+}
+
+/// Returns whether the current user may write to `path`.
+fn is_writable(path: &Path) -> bool {
+    let c_path = match CString::new(path.as_os_str().as_bytes()) {
+        Ok(c_path) => c_path,
+        Err(_) => return false,
+    };
+    // Safe as `c_path` is a valid nul-terminated string which outlives the call.
+    unsafe { libc::access(c_path.as_ptr(), libc::W_OK) == 0 }
+}
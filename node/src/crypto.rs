@@ -5,6 +5,8 @@ mod asymmetric_key_ext;
 mod error;
 pub mod hash;
 
-pub use asymmetric_key::{generate_ed25519_keypair, sign, verify};
-pub use asymmetric_key_ext::AsymmetricKeyExt;
-pub use error::{Error, Result};
+pub use asymmetric_key::{
+    generate_ed25519_keypair, sign, sign_prehashed, verify, verify_prehashed,
+};
+pub use asymmetric_key_ext::{AsymmetricKeyExt, SecretKeyExt};
+pub use error::{Error, Result, SecretKeyLoadError};
@@ -3,18 +3,14 @@
 // TODO - remove once schemars stops causing warning.
 #![allow(clippy::field_reassign_with_default)]
 
-use ed25519_dalek::ExpandedSecretKey;
-use k256::ecdsa::{
-    signature::{Signer, Verifier},
-    Signature as Secp256k1Signature, VerifyingKey as Secp256k1PublicKey,
-};
 #[cfg(test)]
 use k256::elliptic_curve::sec1::ToEncodedPoint;
 
+pub use casper_deploy_utils::crypto::{sign, sign_prehashed};
 use casper_types::{PublicKey, SecretKey, Signature};
 
 pub use super::{Error, Result};
-use crate::crypto::AsymmetricKeyExt;
+use crate::crypto::{hash::Digest, AsymmetricKeyExt};
 
 /// Generates an Ed25519 keypair using the operating system's cryptographically secure random number
 /// generator.
@@ -24,58 +20,24 @@ pub fn generate_ed25519_keypair() -> (SecretKey, PublicKey) {
     (secret_key, public_key)
 }
 
-/// Signs the given message using the given key pair.
-pub fn sign<T: AsRef<[u8]>>(
+/// Verifies the signature of the given message against the given public key.
+pub fn verify<T: AsRef<[u8]>>(
     message: T,
-    secret_key: &SecretKey,
+    signature: &Signature,
     public_key: &PublicKey,
-) -> Signature {
-    match (secret_key, public_key) {
-        (SecretKey::System, PublicKey::System) => {
-            panic!("cannot create signature with system keys",)
-        }
-        (SecretKey::Ed25519(secret_key), PublicKey::Ed25519(public_key)) => {
-            let expanded_secret_key = ExpandedSecretKey::from(secret_key);
-            let signature = expanded_secret_key.sign(message.as_ref(), public_key);
-            Signature::Ed25519(signature)
-        }
-        (SecretKey::Secp256k1(secret_key), PublicKey::Secp256k1(_public_key)) => {
-            let signer = secret_key;
-            let signature: Secp256k1Signature = signer
-                .try_sign(message.as_ref())
-                .expect("should create signature");
-            Signature::Secp256k1(signature)
-        }
-        _ => panic!("secret and public key types must match"),
-    }
+) -> Result<()> {
+    casper_deploy_utils::crypto::verify(message, signature, public_key).map_err(Error::from)
 }
 
-/// Verifies the signature of the given message against the given public key.
-pub fn verify<T: AsRef<[u8]>>(
-    message: T,
+/// Verifies the signature of the given digest, as created by [`sign_prehashed`], against the given
+/// public key.
+pub fn verify_prehashed(
+    digest: &Digest,
     signature: &Signature,
     public_key: &PublicKey,
 ) -> Result<()> {
-    match (signature, public_key) {
-        (Signature::System, _) => Err(Error::AsymmetricKey(String::from(
-            "signatures based on the system key cannot be verified",
-        ))),
-        (Signature::Ed25519(signature), PublicKey::Ed25519(public_key)) => public_key
-            .verify_strict(message.as_ref(), signature)
-            .map_err(|_| Error::AsymmetricKey(String::from("failed to verify Ed25519 signature"))),
-        (Signature::Secp256k1(signature), PublicKey::Secp256k1(public_key)) => {
-            let verifier: &Secp256k1PublicKey = public_key;
-            verifier
-                .verify(message.as_ref(), signature)
-                .map_err(|error| {
-                    Error::AsymmetricKey(format!("failed to verify secp256k1 signature: {}", error))
-                })
-        }
-        _ => Err(Error::AsymmetricKey(format!(
-            "type mismatch between {} and {}",
-            signature, public_key
-        ))),
-    }
+    casper_deploy_utils::crypto::verify_prehashed(digest, signature, public_key)
+        .map_err(Error::from)
 }
 
 #[cfg(test)]
@@ -833,4 +795,171 @@ kv+kBR5u4ISEAkuc2TFWQHX0Yj9oTB9fx9+vvQdxJOhMtu46kGo0Uw==
         let secret_key = SecretKey::generate_secp256k1().unwrap();
         assert!(matches!(secret_key, SecretKey::Secp256k1(_)))
     }
+
+    mod secret_key_file {
+        use std::{
+            fs,
+            os::unix::fs::PermissionsExt,
+            path::{Path, PathBuf},
+        };
+
+        use openssl::{
+            ec::{EcGroup, EcKey},
+            nid::Nid,
+            rsa::Rsa,
+        };
+        use pem::Pem;
+        use tempfile::TempDir;
+
+        use super::*;
+        use crate::crypto::{SecretKeyExt, SecretKeyLoadError};
+
+        /// Writes `contents` to a file with the given permission bits, returning its path.
+        fn write_key_file(tempdir: &TempDir, contents: &[u8], mode: u32) -> PathBuf {
+            let path = tempdir.path().join("secret_key.pem");
+            fs::write(&path, contents).unwrap();
+            fs::set_permissions(&path, fs::Permissions::from_mode(mode)).unwrap();
+            path
+        }
+
+        /// Loads the secret key via both the blocking and the async loader, checking they agree.
+        async fn load(
+            path: &Path,
+            allow_world_readable: bool,
+        ) -> std::result::Result<SecretKey, SecretKeyLoadError> {
+            let blocking_result = SecretKey::from_file_with_policy(path, allow_world_readable);
+            let async_result = SecretKey::from_file_async(path, allow_world_readable).await;
+            match (&blocking_result, &async_result) {
+                (Ok(blocking_key), Ok(async_key)) => {
+                    assert_secret_keys_equal(blocking_key, async_key)
+                }
+                (Err(blocking_error), Err(async_error)) => {
+                    assert_eq!(blocking_error.to_string(), async_error.to_string())
+                }
+                _ => panic!("blocking and async loads should have the same outcome"),
+            }
+            async_result
+        }
+
+        #[tokio::test]
+        async fn should_load_secret_key_from_private_file() {
+            let mut rng = crate::new_rng();
+            let secret_key = SecretKey::random(&mut rng);
+            let tempdir = tempfile::tempdir().unwrap();
+            let path = write_key_file(&tempdir, secret_key.to_pem().unwrap().as_bytes(), 0o600);
+
+            let loaded = load(&path, false).await.unwrap();
+            assert_secret_keys_equal(&secret_key, &loaded);
+        }
+
+        #[tokio::test]
+        async fn should_fail_to_load_missing_secret_key_file() {
+            let tempdir = tempfile::tempdir().unwrap();
+            let path = tempdir.path().join("missing.pem");
+
+            let error = load(&path, false).await.unwrap_err();
+            match error {
+                SecretKeyLoadError::NotFound {
+                    path: error_path, ..
+                } => assert_eq!(error_path, path),
+                error => panic!("unexpected error: {}", error),
+            }
+        }
+
+        #[tokio::test]
+        async fn should_fail_to_load_unreadable_secret_key_file() {
+            // Permissions aren't enforced for root, so there's nothing to test if running as root.
+            if unsafe { libc::geteuid() } == 0 {
+                return;
+            }
+            let mut rng = crate::new_rng();
+            let secret_key = SecretKey::random(&mut rng);
+            let tempdir = tempfile::tempdir().unwrap();
+            let path = write_key_file(&tempdir, secret_key.to_pem().unwrap().as_bytes(), 0o200);
+
+            let error = load(&path, false).await.unwrap_err();
+            match error {
+                SecretKeyLoadError::PermissionDenied {
+                    path: error_path, ..
+                } => assert_eq!(error_path, path),
+                error => panic!("unexpected error: {}", error),
+            }
+        }
+
+        #[tokio::test]
+        async fn should_refuse_world_readable_secret_key_file_unless_allowed() {
+            let mut rng = crate::new_rng();
+            let secret_key = SecretKey::random(&mut rng);
+            let tempdir = tempfile::tempdir().unwrap();
+            let path = write_key_file(&tempdir, secret_key.to_pem().unwrap().as_bytes(), 0o644);
+
+            let error = load(&path, false).await.unwrap_err();
+            assert!(error.to_string().contains("644"));
+            match error {
+                SecretKeyLoadError::WorldReadable {
+                    path: error_path,
+                    mode,
+                } => {
+                    assert_eq!(error_path, path);
+                    assert_eq!(mode, 0o644);
+                }
+                error => panic!("unexpected error: {}", error),
+            }
+
+            let loaded = load(&path, true).await.unwrap();
+            assert_secret_keys_equal(&secret_key, &loaded);
+        }
+
+        #[tokio::test]
+        async fn should_fail_to_load_malformed_secret_key_file() {
+            let invalid_der = pem::encode(&Pem {
+                tag: "PRIVATE KEY".to_string(),
+                contents: vec![1, 2, 3],
+            });
+            let mut rng = crate::new_rng();
+            let truncated_pem = SecretKey::random(&mut rng).to_pem().unwrap()[1..].to_string();
+
+            for contents in &["not a PEM file".to_string(), invalid_der, truncated_pem] {
+                let tempdir = tempfile::tempdir().unwrap();
+                let path = write_key_file(&tempdir, contents.as_bytes(), 0o600);
+
+                let error = load(&path, false).await.unwrap_err();
+                match error {
+                    SecretKeyLoadError::InvalidPem {
+                        path: error_path, ..
+                    } => assert_eq!(error_path, path),
+                    error => panic!("unexpected error {} for {}", error, contents),
+                }
+            }
+        }
+
+        #[tokio::test]
+        async fn should_fail_to_load_secret_key_of_unsupported_algorithm() {
+            let rsa_pem = Rsa::generate(2048).unwrap().private_key_to_pem().unwrap();
+            let p256_group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+            let p256_key = EcKey::generate(&p256_group).unwrap();
+            let p256_sec1_pem = p256_key.private_key_to_pem().unwrap();
+            let p256_pkcs8_pem = PKey::from_ec_key(p256_key)
+                .unwrap()
+                .private_key_to_pem_pkcs8()
+                .unwrap();
+
+            for contents in &[rsa_pem, p256_sec1_pem, p256_pkcs8_pem] {
+                let tempdir = tempfile::tempdir().unwrap();
+                let path = write_key_file(&tempdir, contents, 0o600);
+
+                let error = load(&path, false).await.unwrap_err();
+                match error {
+                    SecretKeyLoadError::UnsupportedAlgorithm {
+                        path: error_path, ..
+                    } => assert_eq!(error_path, path),
+                    error => panic!(
+                        "unexpected error {} for {}",
+                        error,
+                        String::from_utf8_lossy(contents)
+                    ),
+                }
+            }
+        }
+    }
 }
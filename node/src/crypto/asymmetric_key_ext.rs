@@ -1,19 +1,30 @@
 //! Additional operations an asymmetric key
 
-use std::path::Path;
-
+use std::{
+    fs::{self, Metadata},
+    io,
+    os::unix::fs::PermissionsExt,
+    panic,
+    path::Path,
+};
+
+use async_trait::async_trait;
 use derp::{Der, Tag};
 use once_cell::sync::Lazy;
 use pem::Pem;
 #[cfg(test)]
 use rand::{Rng, RngCore};
+use tracing::warn;
 use untrusted::Input;
 
 use casper_types::{AsymmetricType, PublicKey, SecretKey, ED25519_TAG, SECP256K1_TAG, SYSTEM_TAG};
 
 #[cfg(test)]
 use crate::testing::TestRng;
-use crate::{crypto::Error, utils};
+use crate::{
+    crypto::{Error, SecretKeyLoadError},
+    utils,
+};
 
 // See https://tools.ietf.org/html/rfc8410#section-10.3
 const ED25519_OBJECT_IDENTIFIER: [u8; 3] = [43, 101, 112];
@@ -28,6 +39,9 @@ const SECP256K1_PEM_PUBLIC_KEY_TAG: &str = "PUBLIC KEY";
 // See https://www.secg.org/sec1-v2.pdf#subsection.C.4
 const EC_PUBLIC_KEY_OBJECT_IDENTIFIER: [u8; 7] = [42, 134, 72, 206, 61, 2, 1];
 
+/// The permission bit allowing all users to read a file.
+const WORLD_READABLE_MODE: u32 = 0o004;
+
 static ED25519_SECRET_KEY: Lazy<SecretKey> = Lazy::new(|| {
     let bytes = [15u8; SecretKey::ED25519_LENGTH];
     SecretKey::ed25519_from_bytes(bytes).unwrap()
@@ -83,6 +97,84 @@ pub trait AsymmetricKeyExt: Sized {
     fn doc_example() -> &'static Self;
 }
 
+/// Loading of secret keys from files, distinguishing why a load failed.
+#[async_trait]
+pub trait SecretKeyExt: Sized {
+    /// Reads a PEM-encoded secret key from the given file.
+    ///
+    /// Refuses to read a file which is readable by all users unless `allow_world_readable` is set.
+    fn from_file_with_policy<P: AsRef<Path>>(
+        file: P,
+        allow_world_readable: bool,
+    ) -> Result<Self, SecretKeyLoadError>;
+
+    /// Like [`SecretKeyExt::from_file_with_policy`], but without blocking the async runtime's
+    /// threads: the file is read via `tokio::fs` and parsed on the blocking thread pool.
+    async fn from_file_async<P: AsRef<Path> + Send>(
+        file: P,
+        allow_world_readable: bool,
+    ) -> Result<Self, SecretKeyLoadError>;
+}
+
+/// The contents of a DER-encoded secret key.
+enum SecretKeyDer<'a> {
+    /// The raw bytes of a key of the type given by `tag`.
+    Key { tag: u8, raw_bytes: &'a [u8] },
+    /// A key of an algorithm identified by an object identifier we don't support.
+    Unsupported { object_identifier: &'a [u8] },
+}
+
+/// Classifies an error reading the secret key file at `path`.
+fn secret_key_read_error(path: &Path, error: io::Error) -> SecretKeyLoadError {
+    let path = path.to_owned();
+    match error.kind() {
+        io::ErrorKind::NotFound => SecretKeyLoadError::NotFound { path, error },
+        io::ErrorKind::PermissionDenied => SecretKeyLoadError::PermissionDenied { path, error },
+        _ => SecretKeyLoadError::Read { path, error },
+    }
+}
+
+/// Refuses the secret key file at `path` if it's readable by all users, unless
+/// `allow_world_readable` is set, in which case a warning is logged.
+fn check_secret_key_file_mode(
+    path: &Path,
+    metadata: &Metadata,
+    allow_world_readable: bool,
+) -> Result<(), SecretKeyLoadError> {
+    let mode = metadata.permissions().mode() & 0o777;
+    if mode & WORLD_READABLE_MODE == 0 {
+        return Ok(());
+    }
+    if !allow_world_readable {
+        return Err(SecretKeyLoadError::WorldReadable {
+            path: path.to_owned(),
+            mode,
+        });
+    }
+    warn!(
+        path = %path.display(),
+        mode = %format!("{:o}", mode),
+        "loading secret key from a file readable by all users"
+    );
+    Ok(())
+}
+
+/// Parses the contents of the secret key file at `path`.
+fn parse_secret_key_file(path: &Path, data: &[u8]) -> Result<SecretKey, SecretKeyLoadError> {
+    SecretKey::from_pem(data).map_err(|error| {
+        let path = path.to_owned();
+        match error {
+            Error::UnsupportedAlgorithm(detail) => {
+                SecretKeyLoadError::UnsupportedAlgorithm { path, detail }
+            }
+            error => SecretKeyLoadError::InvalidPem {
+                path,
+                detail: error.to_string(),
+            },
+        }
+    })
+}
+
 impl AsymmetricKeyExt for SecretKey {
     fn generate_ed25519() -> Result<Self, Error> {
         let mut bytes = [0u8; Self::ED25519_LENGTH];
@@ -101,8 +193,7 @@ impl AsymmetricKeyExt for SecretKey {
     }
 
     fn from_file<P: AsRef<Path>>(file: P) -> Result<Self, Error> {
-        let data = utils::read_file(file).map_err(Error::SecretKeyLoad)?;
-        Self::from_pem(data)
+        Self::from_file_with_policy(file, false).map_err(Error::SecretKeyLoad)
     }
 
     fn to_der(&self) -> Result<Vec<u8>, Error> {
@@ -144,7 +235,7 @@ impl AsymmetricKeyExt for SecretKey {
     fn from_der<T: AsRef<[u8]>>(input: T) -> Result<Self, Error> {
         let input = Input::from(input.as_ref());
 
-        let (key_type_tag, raw_bytes) = input.read_all(derp::Error::Read, |input| {
+        let secret_key_der = input.read_all(derp::Error::Read, |input| {
             derp::nested(input, Tag::Sequence, |input| {
                 // Safe to ignore the first value which should be an integer.
                 let version_slice =
@@ -163,11 +254,17 @@ impl AsymmetricKeyExt for SecretKey {
                     }
 
                     // The sequence should have one element: an object identifier defining Ed25519.
+                    // Other algorithms might have parameters following the object identifier.
                     let object_identifier = value.read_all(derp::Error::Read, |input| {
-                        derp::expect_tag_and_get_value(input, Tag::Oid)
+                        let object_identifier = derp::expect_tag_and_get_value(input, Tag::Oid)?;
+                        let _ = input.read_bytes_to_end();
+                        Ok(object_identifier)
                     })?;
                     if object_identifier.as_slice_less_safe() != ED25519_OBJECT_IDENTIFIER {
-                        return Err(derp::Error::WrongValue);
+                        let _ = input.read_bytes_to_end();
+                        return Ok(SecretKeyDer::Unsupported {
+                            object_identifier: object_identifier.as_slice_less_safe(),
+                        });
                     }
 
                     // The third and final value should be the raw bytes of the secret key as an
@@ -177,7 +274,10 @@ impl AsymmetricKeyExt for SecretKey {
                     })?
                     .as_slice_less_safe();
 
-                    return Ok((ED25519_TAG, raw_bytes));
+                    return Ok(SecretKeyDer::Key {
+                        tag: ED25519_TAG,
+                        raw_bytes,
+                    });
                 } else if tag == Tag::OctetString as u8 {
                     // Expecting a secp256k1 key.
                     if version != 1 {
@@ -194,7 +294,10 @@ impl AsymmetricKeyExt for SecretKey {
                         derp::expect_tag_and_get_value(input, Tag::Oid)
                     })?;
                     if object_identifier.as_slice_less_safe() != SECP256K1_OBJECT_IDENTIFIER {
-                        return Err(derp::Error::WrongValue);
+                        let _ = input.read_bytes_to_end();
+                        return Ok(SecretKeyDer::Unsupported {
+                            object_identifier: object_identifier.as_slice_less_safe(),
+                        });
                     }
 
                     // There might be an optional public key as the final value, but we're not
@@ -202,13 +305,26 @@ impl AsymmetricKeyExt for SecretKey {
                     // with unused bytes error.
                     let _ = derp::read_tag_and_get_value(input);
 
-                    return Ok((SECP256K1_TAG, raw_bytes));
+                    return Ok(SecretKeyDer::Key {
+                        tag: SECP256K1_TAG,
+                        raw_bytes,
+                    });
                 }
 
                 Err(derp::Error::WrongValue)
             })
         })?;
 
+        let (key_type_tag, raw_bytes) = match secret_key_der {
+            SecretKeyDer::Key { tag, raw_bytes } => (tag, raw_bytes),
+            SecretKeyDer::Unsupported { object_identifier } => {
+                return Err(Error::UnsupportedAlgorithm(format!(
+                    "object identifier {}",
+                    hex::encode(object_identifier)
+                )));
+            }
+        };
+
         match key_type_tag {
             SYSTEM_TAG => Err(Error::AsymmetricKey("cannot construct variant".to_string())),
             ED25519_TAG => SecretKey::ed25519_from_bytes(raw_bytes).map_err(Into::into),
@@ -231,6 +347,14 @@ impl AsymmetricKeyExt for SecretKey {
     fn from_pem<T: AsRef<[u8]>>(input: T) -> Result<Self, Error> {
         let pem = pem::parse(input)?;
 
+        // Secret keys of other algorithms, e.g. "RSA PRIVATE KEY", have their own tags.
+        if pem.tag != ED25519_PEM_SECRET_KEY_TAG
+            && pem.tag != SECP256K1_PEM_SECRET_KEY_TAG
+            && pem.tag.ends_with("PRIVATE KEY")
+        {
+            return Err(Error::UnsupportedAlgorithm(format!("PEM tag {}", pem.tag)));
+        }
+
         let secret_key = Self::from_der(&pem.contents)?;
 
         let bad_tag = |expected_tag: &str| {
@@ -285,6 +409,37 @@ impl AsymmetricKeyExt for SecretKey {
     }
 }
 
+#[async_trait]
+impl SecretKeyExt for SecretKey {
+    fn from_file_with_policy<P: AsRef<Path>>(
+        file: P,
+        allow_world_readable: bool,
+    ) -> Result<Self, SecretKeyLoadError> {
+        let path = file.as_ref();
+        let metadata = fs::metadata(path).map_err(|error| secret_key_read_error(path, error))?;
+        check_secret_key_file_mode(path, &metadata, allow_world_readable)?;
+        let data = fs::read(path).map_err(|error| secret_key_read_error(path, error))?;
+        parse_secret_key_file(path, &data)
+    }
+
+    async fn from_file_async<P: AsRef<Path> + Send>(
+        file: P,
+        allow_world_readable: bool,
+    ) -> Result<Self, SecretKeyLoadError> {
+        let path = file.as_ref().to_owned();
+        let metadata = tokio::fs::metadata(&path)
+            .await
+            .map_err(|error| secret_key_read_error(&path, error))?;
+        check_secret_key_file_mode(&path, &metadata, allow_world_readable)?;
+        let data = tokio::fs::read(&path)
+            .await
+            .map_err(|error| secret_key_read_error(&path, error))?;
+        tokio::task::spawn_blocking(move || parse_secret_key_file(&path, &data))
+            .await
+            .unwrap_or_else(|join_error| panic::resume_unwind(join_error.into_panic()))
+    }
+}
+
 impl AsymmetricKeyExt for PublicKey {
     fn generate_ed25519() -> Result<Self, Error> {
         let mut bytes = [0u8; Self::ED25519_LENGTH];
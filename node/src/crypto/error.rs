@@ -1,4 +1,4 @@
-use std::result;
+use std::{io, path::PathBuf, result};
 
 use base64::DecodeError;
 use hex::FromHexError;
@@ -25,7 +25,7 @@ pub enum Error {
 
     /// Error trying to read a secret key.
     #[error("secret key load failed: {0}")]
-    SecretKeyLoad(ReadFileError),
+    SecretKeyLoad(SecretKeyLoadError),
 
     /// Error trying to read a public key.
     #[error("public key load failed: {0}")]
@@ -43,6 +43,10 @@ pub enum Error {
     #[error("der error: {0}")]
     FromDer(#[from] derp::Error),
 
+    /// The key is of an algorithm other than Ed25519 or secp256k1.
+    #[error("unsupported algorithm: {0}")]
+    UnsupportedAlgorithm(String),
+
     /// Error trying to write a secret key.
     #[error("secret key save failed: {0}")]
     SecretKeySave(WriteFileError),
@@ -80,3 +84,75 @@ impl From<crypto::Error> for Error {
         }
     }
 }
+
+/// Error loading a secret key from a file.
+#[derive(Debug, Error)]
+pub enum SecretKeyLoadError {
+    /// The file doesn't exist.
+    #[error("secret key file '{}' not found: {error}", .path.display())]
+    NotFound {
+        /// Path of the file.
+        path: PathBuf,
+        /// The underlying OS error.
+        #[source]
+        error: io::Error,
+    },
+
+    /// The node isn't permitted to read the file.
+    #[error("permission denied reading secret key file '{}': {error}", .path.display())]
+    PermissionDenied {
+        /// Path of the file.
+        path: PathBuf,
+        /// The underlying OS error.
+        #[source]
+        error: io::Error,
+    },
+
+    /// The file is readable by all users, and loading such files wasn't allowed.
+    #[error(
+        "secret key file '{}' is readable by all users (mode {:o}); restrict its permissions or \
+        set `consensus.allow_world_readable_secret_key`",
+        .path.display(),
+        .mode
+    )]
+    WorldReadable {
+        /// Path of the file.
+        path: PathBuf,
+        /// The permission bits of the file.
+        mode: u32,
+    },
+
+    /// Any other error reading the file.
+    #[error("could not read secret key file '{}': {error}", .path.display())]
+    Read {
+        /// Path of the file.
+        path: PathBuf,
+        /// The underlying OS error.
+        #[source]
+        error: io::Error,
+    },
+
+    /// The file's contents aren't a valid PEM-encoded secret key.
+    #[error(
+        "secret key file '{}' is not a valid PEM-encoded secret key: {detail}",
+        .path.display()
+    )]
+    InvalidPem {
+        /// Path of the file.
+        path: PathBuf,
+        /// Description of the problem.
+        detail: String,
+    },
+
+    /// The file holds a key of an algorithm other than Ed25519 or secp256k1.
+    #[error(
+        "secret key file '{}' holds a key of an unsupported algorithm: {detail}",
+        .path.display()
+    )]
+    UnsupportedAlgorithm {
+        /// Path of the file.
+        path: PathBuf,
+        /// Description of the algorithm.
+        detail: String,
+    },
+}
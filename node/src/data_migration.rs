@@ -205,10 +205,9 @@ pub fn migrate_data(
         .map_err(Error::LoadChainspec)?
         .protocol_config
         .version;
-    let secret_key = new_config
+    let (secret_key, _public_key) = new_config
         .consensus
-        .secret_key_path
-        .load(&new_root)
+        .load_keys(&new_root)
         .map_err(Error::LoadSecretKey)?;
 
     // Get this by actually migrating the global state data.
@@ -67,8 +67,9 @@ use std::{
     collections::{BTreeMap, HashMap, HashSet},
     fmt::{self, Debug, Display, Formatter},
     future::Future,
+    ops::RangeInclusive,
     sync::Arc,
-    time::{Duration, Instant},
+    time::Duration,
 };
 
 use datasize::DataSize;
@@ -76,7 +77,7 @@ use futures::{channel::oneshot, future::BoxFuture, FutureExt};
 use once_cell::sync::Lazy;
 use serde::{de::DeserializeOwned, Serialize};
 use smallvec::{smallvec, SmallVec};
-use tokio::{sync::Semaphore, time};
+use tokio::sync::Semaphore;
 use tracing::error;
 #[cfg(not(feature = "fast-sync"))]
 use tracing::warn;
@@ -89,11 +90,12 @@ use casper_execution_engine::{
         genesis::GenesisResult,
         step::{StepRequest, StepResult},
         upgrade::{UpgradeConfig, UpgradeResult},
-        BalanceRequest, BalanceResult, GetBidsRequest, GetBidsResult, QueryRequest, QueryResult,
+        BalanceRequest, BalanceResult, BalancesRequest, BalancesResult, GetAllBalancesRequest,
+        GetAllBalancesResult, GetBidsRequest, GetBidsResult, QueryRequest, QueryResult,
         MAX_PAYMENT,
     },
     shared::{newtypes::Blake2bHash, stored_value::StoredValue},
-    storage::{protocol_data::ProtocolData, trie::Trie},
+    storage::{lmdb_stats::LmdbStats, protocol_data::ProtocolData, trie::Trie},
 };
 use casper_types::{
     system::auction::EraValidators, EraId, ExecutionResult, Key, ProtocolVersion, PublicKey,
@@ -102,33 +104,38 @@ use casper_types::{
 
 use crate::{
     components::{
+        block_proposer::DeployBufferStatus,
         block_validator::ValidatingBlock,
         chainspec_loader::{CurrentRunInfo, NextUpgrade},
         consensus::{BlockContext, ClContext},
         contract_runtime::EraValidatorsRequest,
         deploy_acceptor,
         fetcher::FetchResult,
+        network,
         small_network::GossipedAddress,
     },
     crypto::hash::Digest,
     effect::requests::LinearChainRequest,
-    reactor::{EventQueueHandle, QueueKind},
+    protocol::Message,
+    reactor::{EventQueueHandle, QueueKind, ReactorEvent, TimerHandle},
     types::{
-        Block, BlockByHeight, BlockHash, BlockHeader, BlockPayload, BlockSignatures, Chainspec,
-        ChainspecInfo, Deploy, DeployHash, DeployHeader, DeployMetadata, FinalitySignature,
-        FinalizedBlock, Item, TimeDiff, Timestamp,
+        Approval, Block, BlockByHeight, BlockHash, BlockHeader, BlockPayload, BlockSignatures,
+        Chainspec, ChainspecInfo, Deploy, DeployHash, DeployHeader, DeployHeaderWithHash,
+        DeployMetadata, DeploySpread, DialBackoff, EraProgress, FinalitySignature, FinalizedBlock,
+        GossipState, Item, NodeId, TimeDiff, Timestamp, ValidatorChange, ValidatorPerformance,
     },
-    utils::Source,
+    utils::{self, Source},
 };
 use announcements::{
-    ChainspecLoaderAnnouncement, ConsensusAnnouncement, ContractRuntimeAnnouncement,
-    ControlAnnouncement, DeployAcceptorAnnouncement, GossiperAnnouncement, LinearChainAnnouncement,
-    NetworkAnnouncement, RpcServerAnnouncement,
+    BlockProposerAnnouncement, ChainspecLoaderAnnouncement, ConsensusAnnouncement,
+    ContractRuntimeAnnouncement, ControlAnnouncement, DeployAcceptorAnnouncement,
+    GossiperAnnouncement, LinearChainAnnouncement, NetworkAnnouncement, RequestLimiterAnnouncement,
+    RpcServerAnnouncement,
 };
 use requests::{
     BlockPayloadRequest, BlockProposerRequest, BlockValidationRequest, ChainspecLoaderRequest,
-    ConsensusRequest, ContractRuntimeRequest, FetcherRequest, MetricsRequest, NetworkInfoRequest,
-    NetworkRequest, StateStoreRequest, StorageRequest,
+    ConsensusRequest, ContractRuntimeRequest, DeployPropagationRequest, FetcherRequest,
+    MetricsRequest, NetworkInfoRequest, NetworkRequest, StateStoreRequest, StorageRequest,
 };
 
 use self::announcements::BlocklistAnnouncement;
@@ -474,11 +481,43 @@ impl<REv> EffectBuilder<REv> {
             .await
     }
 
+    /// Announces that a component's event handler panicked.
+    ///
+    /// Only called by the runner, which catches the panic.
+    pub(crate) async fn announce_component_failed(
+        self,
+        component: &'static str,
+        panic_message: String,
+    ) where
+        REv: From<ControlAnnouncement>,
+    {
+        self.0
+            .schedule(
+                ControlAnnouncement::ComponentFailed {
+                    component,
+                    panic_message,
+                },
+                QueueKind::Control,
+            )
+            .await
+    }
+
     /// Sets a timeout.
     pub(crate) async fn set_timeout(self, timeout: Duration) -> Duration {
-        let then = Instant::now();
-        time::sleep(timeout).await;
-        Instant::now() - then
+        utils::sleep(timeout).await
+    }
+
+    /// Sets a cancellable timeout, scheduling `event` once it has elapsed.
+    ///
+    /// The event is only delivered while the returned handle is kept: dropping the handle or
+    /// calling `TimerHandle::cancel` guarantees the event is never dispatched, so components need
+    /// not check whether a timeout has become stale.
+    pub(crate) fn set_timeout_with_handle<Ev>(self, timeout: Duration, event: Ev) -> TimerHandle
+    where
+        REv: From<Ev> + ReactorEvent,
+        Ev: Send + 'static,
+    {
+        self.0.schedule_after(timeout, event)
     }
 
     /// Retrieve a snapshot of the nodes current metrics formatted as string.
@@ -546,13 +585,15 @@ impl<REv> EffectBuilder<REv> {
     /// Gossips a network message.
     ///
     /// A low-level "gossip" function, selects `count` randomly chosen nodes on the network,
-    /// excluding the indicated ones, and sends each a copy of the message.
+    /// excluding the indicated ones, and sends each a copy of the message.  At least
+    /// `min_validators` of the chosen nodes are validators, if enough are connected.
     ///
     /// Returns the IDs of the chosen nodes.
     pub async fn gossip_message<I, P>(
         self,
         payload: P,
         count: usize,
+        min_validators: usize,
         exclude: HashSet<I>,
     ) -> HashSet<I>
     where
@@ -564,6 +605,7 @@ impl<REv> EffectBuilder<REv> {
             |responder| NetworkRequest::Gossip {
                 payload: Box::new(payload),
                 count,
+                min_validators,
                 exclude,
                 responder,
             },
@@ -585,6 +627,33 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
+    /// Gets the addresses waiting to be redialed after failed connection attempts.
+    pub(crate) async fn network_dial_backoffs<I>(self) -> Vec<DialBackoff>
+    where
+        REv: From<NetworkInfoRequest<I>>,
+        I: Send + 'static,
+    {
+        self.make_request(
+            |responder| NetworkInfoRequest::GetDialBackoffs { responder },
+            QueueKind::Api,
+        )
+        .await
+    }
+
+    /// Gets a snapshot of the gossipsub state, or `None` if the networking component doesn't use
+    /// gossipsub.
+    pub(crate) async fn network_gossip_state<I>(self) -> Option<GossipState>
+    where
+        REv: From<NetworkInfoRequest<I>>,
+        I: Send + 'static,
+    {
+        self.make_request(
+            |responder| NetworkInfoRequest::GetGossipState { responder },
+            QueueKind::Api,
+        )
+        .await
+    }
+
     /// Announces that a network message has been received.
     pub(crate) async fn announce_message_received<I, P>(self, sender: I, payload: P)
     where
@@ -606,7 +675,7 @@ impl<REv> EffectBuilder<REv> {
         self.0
             .schedule(
                 NetworkAnnouncement::GossipOurAddress(our_address),
-                QueueKind::Regular,
+                QueueKind::Gossip,
             )
             .await;
     }
@@ -624,6 +693,72 @@ impl<REv> EffectBuilder<REv> {
             .await;
     }
 
+    /// Announces that a connection to a peer was completed.
+    pub(crate) async fn announce_peer_connected<I, P>(
+        self,
+        peer_id: I,
+        validator: Option<PublicKey>,
+    ) where
+        REv: From<NetworkAnnouncement<I, P>>,
+    {
+        self.0
+            .schedule(
+                NetworkAnnouncement::PeerConnected { peer_id, validator },
+                QueueKind::NetworkIncoming,
+            )
+            .await;
+    }
+
+    /// Announces that a previously completed connection to a peer was lost.
+    pub(crate) async fn announce_peer_disconnected<I, P>(
+        self,
+        peer_id: I,
+        validator: Option<PublicKey>,
+    ) where
+        REv: From<NetworkAnnouncement<I, P>>,
+    {
+        self.0
+            .schedule(
+                NetworkAnnouncement::PeerDisconnected { peer_id, validator },
+                QueueKind::NetworkIncoming,
+            )
+            .await;
+    }
+
+    /// Announces that the number of connected peers changed.
+    pub(crate) async fn announce_peer_count_changed<I, P>(self, peer_count: usize)
+    where
+        REv: From<NetworkAnnouncement<I, P>>,
+    {
+        self.0
+            .schedule(
+                NetworkAnnouncement::PeerCountChanged(peer_count),
+                QueueKind::Regular,
+            )
+            .await;
+    }
+
+    /// Announces that a one-way message could not be delivered to the given peer.
+    pub(crate) async fn announce_message_send_failed<I, P>(
+        self,
+        destination: I,
+        payload: P,
+        error_kind: network::ErrorKind,
+    ) where
+        REv: From<NetworkAnnouncement<I, P>>,
+    {
+        self.0
+            .schedule(
+                NetworkAnnouncement::MessageSendFailed {
+                    destination,
+                    payload,
+                    error_kind,
+                },
+                QueueKind::NetworkIncoming,
+            )
+            .await;
+    }
+
     /// Announces that a gossiper has received a new item, where the item's ID is the complete item.
     pub(crate) async fn announce_complete_item_received_via_gossip<T: Item>(self, item: T::Id)
     where
@@ -637,7 +772,7 @@ impl<REv> EffectBuilder<REv> {
         self.0
             .schedule(
                 GossiperAnnouncement::NewCompleteItem(item),
-                QueueKind::Regular,
+                QueueKind::Gossip,
             )
             .await;
     }
@@ -682,7 +817,7 @@ impl<REv> EffectBuilder<REv> {
         self.0
             .schedule(
                 GossiperAnnouncement::FinishedGossiping(item_id),
-                QueueKind::Regular,
+                QueueKind::Gossip,
             )
             .await;
     }
@@ -692,16 +827,53 @@ impl<REv> EffectBuilder<REv> {
         self,
         deploy: Box<Deploy>,
         source: Source<I>,
+        error: deploy_acceptor::Error,
     ) -> impl Future<Output = ()>
     where
         REv: From<DeployAcceptorAnnouncement<I>>,
     {
         self.0.schedule(
-            DeployAcceptorAnnouncement::InvalidDeploy { deploy, source },
+            DeployAcceptorAnnouncement::InvalidDeploy {
+                deploy,
+                source,
+                error,
+            },
             QueueKind::Regular,
         )
     }
 
+    /// Announces that a buffered deploy has been replaced by one with a higher gas price.
+    pub(crate) async fn announce_deploy_replaced(
+        self,
+        replaced: DeployHash,
+        replacement: DeployHash,
+    ) where
+        REv: From<BlockProposerAnnouncement>,
+    {
+        self.0
+            .schedule(
+                BlockProposerAnnouncement::DeployReplaced {
+                    replaced,
+                    replacement,
+                },
+                QueueKind::Regular,
+            )
+            .await
+    }
+
+    /// Announces that a buffered deploy has been evicted since the buffer was full.
+    pub(crate) async fn announce_deploy_evicted(self, deploy_hash: DeployHash)
+    where
+        REv: From<BlockProposerAnnouncement>,
+    {
+        self.0
+            .schedule(
+                BlockProposerAnnouncement::DeployEvicted { deploy_hash },
+                QueueKind::Regular,
+            )
+            .await
+    }
+
     /// Announce new block has been created.
     pub(crate) async fn announce_linear_chain_block(
         self,
@@ -839,6 +1011,76 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
+    /// Puts the performance of validators in an era into storage, deleting that of eras older than
+    /// `oldest_retained_era`.
+    pub(crate) async fn put_era_performance_to_storage(
+        self,
+        era_id: EraId,
+        performance: BTreeMap<PublicKey, ValidatorPerformance>,
+        oldest_retained_era: EraId,
+    ) where
+        REv: From<StorageRequest>,
+    {
+        self.make_request(
+            |responder| StorageRequest::PutEraPerformance {
+                era_id,
+                performance,
+                oldest_retained_era,
+                responder,
+            },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
+    /// Gets the performance of validators in the given eras from storage, restricted to a single
+    /// validator if `maybe_public_key` is `Some`.
+    pub(crate) async fn get_era_performance_from_storage(
+        self,
+        era_ids: RangeInclusive<EraId>,
+        maybe_public_key: Option<Box<PublicKey>>,
+    ) -> BTreeMap<EraId, BTreeMap<PublicKey, ValidatorPerformance>>
+    where
+        REv: From<StorageRequest>,
+    {
+        self.make_request(
+            |responder| StorageRequest::GetEraPerformance {
+                era_ids,
+                maybe_public_key,
+                responder,
+            },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
+    /// Gets the changes to the validator set taking effect in the given eras from storage.
+    pub(crate) async fn get_validator_changes_from_storage(
+        self,
+        era_ids: RangeInclusive<EraId>,
+    ) -> BTreeMap<EraId, BTreeMap<PublicKey, Vec<ValidatorChange>>>
+    where
+        REv: From<StorageRequest>,
+    {
+        self.make_request(
+            |responder| StorageRequest::GetValidatorChanges { era_ids, responder },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
+    /// Collects the statistics of the storage component's LMDB environment.
+    pub(crate) async fn get_storage_lmdb_stats(self) -> Option<LmdbStats>
+    where
+        REv: From<StorageRequest>,
+    {
+        self.make_request(
+            |responder| StorageRequest::GetLmdbStats { responder },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
     /// Gets the requested block's transfers from storage.
     pub(crate) async fn get_block_transfers_from_storage(
         self,
@@ -1018,6 +1260,43 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
+    /// Gets the headers of the requested deploys from the deploy store, without their bodies.
+    pub(crate) async fn get_deploy_headers_from_storage(
+        self,
+        deploy_hashes: Multiple<DeployHash>,
+    ) -> Vec<Option<DeployHeader>>
+    where
+        REv: From<StorageRequest>,
+    {
+        self.make_request(
+            |responder| StorageRequest::GetDeployHeaders {
+                deploy_hashes: deploy_hashes.to_vec(),
+                responder,
+            },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
+    /// Gets the approvals of the requested deploy from the deploy store, without its header or
+    /// body.
+    pub(crate) async fn get_deploy_approvals_from_storage(
+        self,
+        deploy_hash: DeployHash,
+    ) -> Option<Vec<Approval>>
+    where
+        REv: From<StorageRequest>,
+    {
+        self.make_request(
+            |responder| StorageRequest::GetDeployApprovals {
+                deploy_hash,
+                responder,
+            },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
     /// Stores the given execution results for the deploys in the given block in the linear block
     /// store.
     pub(crate) async fn put_execution_results_to_storage(
@@ -1056,6 +1335,24 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
+    /// Gets the requested deploy's header and metadata from the deploy store.
+    pub(crate) async fn get_deploy_header_and_metadata_from_storage(
+        self,
+        deploy_hash: DeployHash,
+    ) -> Option<(DeployHeader, DeployMetadata)>
+    where
+        REv: From<StorageRequest>,
+    {
+        self.make_request(
+            |responder| StorageRequest::GetDeployHeaderAndMetadata {
+                deploy_hash,
+                responder,
+            },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
     /// Gets the requested block and its associated metadata.
     pub(crate) async fn get_block_at_height_with_metadata_from_storage(
         self,
@@ -1127,6 +1424,28 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
+    /// Gets the requested deploy header using the `DeployHeaderFetcher`, without the deploy's
+    /// body.
+    pub(crate) async fn fetch_deploy_header<I>(
+        self,
+        deploy_hash: DeployHash,
+        peer: I,
+    ) -> Option<FetchResult<DeployHeaderWithHash, I>>
+    where
+        REv: From<FetcherRequest<I, DeployHeaderWithHash>>,
+        I: Send + 'static,
+    {
+        self.make_request(
+            |responder| FetcherRequest::Fetch {
+                id: deploy_hash,
+                peer,
+                responder,
+            },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
     /// Gets the requested block using the `BlockFetcher`
     pub(crate) async fn fetch_block<I>(
         self,
@@ -1190,6 +1509,24 @@ impl<REv> EffectBuilder<REv> {
                     random_bit,
                 })
             },
+            QueueKind::Consensus,
+        )
+        .await
+    }
+
+    /// Requests the stage the given deploy has reached in the block proposer's buffer.
+    pub(crate) async fn get_deploy_buffer_status(
+        self,
+        deploy_hash: DeployHash,
+    ) -> DeployBufferStatus
+    where
+        REv: From<BlockProposerRequest>,
+    {
+        self.make_request(
+            |responder| BlockProposerRequest::GetDeployStatus {
+                deploy_hash,
+                responder,
+            },
             QueueKind::Regular,
         )
         .await
@@ -1221,7 +1558,7 @@ impl<REv> EffectBuilder<REv> {
                 sender,
                 responder,
             },
-            QueueKind::Regular,
+            QueueKind::Consensus,
         )
         .await
     }
@@ -1239,6 +1576,19 @@ impl<REv> EffectBuilder<REv> {
             .await
     }
 
+    /// Announces that the given deploys have been finalized, ahead of the block containing them.
+    pub(crate) async fn announce_deploys_finalized(self, deploy_hashes: Vec<DeployHash>)
+    where
+        REv: From<ConsensusAnnouncement>,
+    {
+        self.0
+            .schedule(
+                ConsensusAnnouncement::DeploysFinalized(deploy_hashes),
+                QueueKind::Regular,
+            )
+            .await
+    }
+
     /// Announces that a finality signature has been created.
     pub(crate) async fn announce_created_finality_signature(
         self,
@@ -1314,6 +1664,19 @@ impl<REv> EffectBuilder<REv> {
             .await
     }
 
+    /// Announces that a request from a peer has been admitted by the request limiter.
+    pub(crate) async fn announce_request_admitted(self, sender: NodeId, payload: Box<Message>)
+    where
+        REv: From<RequestLimiterAnnouncement>,
+    {
+        self.0
+            .schedule(
+                RequestLimiterAnnouncement::RequestAdmitted { sender, payload },
+                QueueKind::NetworkIncoming,
+            )
+            .await
+    }
+
     /// Runs the genesis process on the contract runtime.
     pub(crate) async fn commit_genesis(
         self,
@@ -1512,6 +1875,43 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
+    /// Requests the balances of several purses from the Contract Runtime component.
+    pub(crate) async fn get_balances(
+        self,
+        balances_request: BalancesRequest,
+    ) -> Result<BalancesResult, engine_state::Error>
+    where
+        REv: From<ContractRuntimeRequest>,
+    {
+        self.make_request(
+            |responder| ContractRuntimeRequest::GetBalances {
+                balances_request,
+                responder,
+            },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
+    /// Requests a chunk of the balances of all accounts' main purses from the Contract Runtime
+    /// component.
+    pub(crate) async fn get_all_balances(
+        self,
+        get_all_balances_request: GetAllBalancesRequest,
+    ) -> Result<GetAllBalancesResult, engine_state::Error>
+    where
+        REv: From<ContractRuntimeRequest>,
+    {
+        self.make_request(
+            |responder| ContractRuntimeRequest::GetAllBalances {
+                get_all_balances_request,
+                responder,
+            },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
     /// Returns `ProtocolData` by `ProtocolVersion`.
     ///
     /// This operation is read only.
@@ -1532,6 +1932,18 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
+    /// Collects the statistics of the global state's LMDB environment.
+    pub(crate) async fn get_global_state_lmdb_stats(self) -> Option<LmdbStats>
+    where
+        REv: From<ContractRuntimeRequest>,
+    {
+        self.make_request(
+            |responder| ContractRuntimeRequest::GetLmdbStats { responder },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
     /// Returns a map of validators weights for all eras as known from `root_hash`.
     ///
     /// This operation is read only.
@@ -1718,6 +2130,25 @@ impl<REv> EffectBuilder<REv> {
             .await
     }
 
+    /// Get the progress of the current era from consensus, if it is running.
+    pub(crate) async fn consensus_era_progress(self) -> Option<EraProgress>
+    where
+        REv: From<ConsensusRequest>,
+    {
+        self.make_request(ConsensusRequest::EraProgress, QueueKind::Regular)
+            .await
+    }
+
+    /// Gets the propagation of the most recently probed deploys, or `None` if measuring deploy
+    /// propagation is disabled.
+    pub(crate) async fn get_deploy_spreads(self) -> Option<Vec<DeploySpread>>
+    where
+        REv: From<DeployPropagationRequest>,
+    {
+        self.make_request(DeployPropagationRequest::GetSpreads, QueueKind::Api)
+            .await
+    }
+
     /// Collects the key blocks for the eras identified by provided era IDs. Returns
     /// `Some(HashMap(era_id → block_header))` if all the blocks have been read correctly, and
     /// `None` if at least one was missing. The header for EraId `n` is from the key block for that
@@ -14,11 +14,14 @@ use casper_types::{EraId, ExecutionEffect, ExecutionResult, PublicKey};
 
 use crate::{
     components::{
-        chainspec_loader::NextUpgrade, deploy_acceptor::Error, small_network::GossipedAddress,
+        chainspec_loader::NextUpgrade, deploy_acceptor::Error, network,
+        small_network::GossipedAddress,
     },
     effect::Responder,
+    protocol::Message,
     types::{
-        Block, Deploy, DeployHash, DeployHeader, FinalitySignature, FinalizedBlock, Item, Timestamp,
+        Block, Deploy, DeployHash, DeployHeader, FinalitySignature, FinalizedBlock, Item, NodeId,
+        Timestamp,
     },
     utils::Source,
 };
@@ -45,6 +48,15 @@ pub enum ControlAnnouncement {
         /// Error message.
         msg: String,
     },
+    /// A component's event handler panicked.
+    ///
+    /// Handled according to the failure policy configured for the component.
+    ComponentFailed {
+        /// Name of the component.
+        component: &'static str,
+        /// Message the component panicked with.
+        panic_message: String,
+    },
 }
 
 impl Display for ControlAnnouncement {
@@ -53,11 +65,18 @@ impl Display for ControlAnnouncement {
             ControlAnnouncement::FatalError { file, line, msg } => {
                 write!(f, "fatal error [{}:{}]: {}", file, line, msg)
             }
+            ControlAnnouncement::ComponentFailed {
+                component,
+                panic_message,
+            } => {
+                write!(f, "component {} failed: {}", component, panic_message)
+            }
         }
     }
 }
 
 /// A networking layer announcement.
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug, Serialize)]
 #[must_use]
 pub enum NetworkAnnouncement<I, P> {
@@ -76,6 +95,31 @@ pub enum NetworkAnnouncement<I, P> {
     ///                 not rely on or use this for anything without asking anyone that has written
     ///                 this section of the code first!
     NewPeer(I),
+    /// The number of connected peers changed.
+    PeerCountChanged(usize),
+    /// A connection to a peer was completed.
+    PeerConnected {
+        /// The ID of the peer.
+        peer_id: I,
+        /// The peer's validator public key, if it identified itself with one.
+        validator: Option<PublicKey>,
+    },
+    /// A previously completed connection to a peer was lost.
+    PeerDisconnected {
+        /// The ID of the peer.
+        peer_id: I,
+        /// The peer's validator public key, if it identified itself with one.
+        validator: Option<PublicKey>,
+    },
+    /// A one-way message could not be delivered to a peer.
+    MessageSendFailed {
+        /// The intended recipient of the message.
+        destination: I,
+        /// The message payload.
+        payload: P,
+        /// The category of the failure, indicating whether sending the message again may succeed.
+        error_kind: network::ErrorKind,
+    },
 }
 
 impl<I, P> Display for NetworkAnnouncement<I, P>
@@ -92,6 +136,32 @@ where
             NetworkAnnouncement::NewPeer(id) => {
                 write!(formatter, "new peer connection established to {}", id)
             }
+            NetworkAnnouncement::PeerCountChanged(peer_count) => {
+                write!(formatter, "peer count changed to {}", peer_count)
+            }
+            NetworkAnnouncement::PeerConnected { peer_id, validator } => {
+                write!(formatter, "peer {} connected", peer_id)?;
+                if let Some(public_key) = validator {
+                    write!(formatter, " (validator {})", public_key)?;
+                }
+                Ok(())
+            }
+            NetworkAnnouncement::PeerDisconnected { peer_id, validator } => {
+                write!(formatter, "peer {} disconnected", peer_id)?;
+                if let Some(public_key) = validator {
+                    write!(formatter, " (validator {})", public_key)?;
+                }
+                Ok(())
+            }
+            NetworkAnnouncement::MessageSendFailed {
+                destination,
+                payload,
+                error_kind,
+            } => write!(
+                formatter,
+                "failed to send to {} ({} error): {}",
+                destination, error_kind, payload
+            ),
         }
     }
 }
@@ -120,6 +190,7 @@ impl Display for RpcServerAnnouncement {
 }
 
 /// A `DeployAcceptor` announcement.
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug, Serialize)]
 pub enum DeployAcceptorAnnouncement<I> {
     /// A deploy which wasn't previously stored on this node has been accepted and stored.
@@ -136,6 +207,9 @@ pub enum DeployAcceptorAnnouncement<I> {
         deploy: Box<Deploy>,
         /// The source (peer or client) of the deploy.
         source: Source<I>,
+        /// The reason the deploy was rejected.
+        #[serde(skip_serializing)]
+        error: Error,
     },
 }
 
@@ -148,9 +222,17 @@ impl<I: Display> Display for DeployAcceptorAnnouncement<I> {
                 deploy.id(),
                 source
             ),
-            DeployAcceptorAnnouncement::InvalidDeploy { deploy, source } => {
-                write!(formatter, "invalid deploy {} from {}", deploy.id(), source)
-            }
+            DeployAcceptorAnnouncement::InvalidDeploy {
+                deploy,
+                source,
+                error,
+            } => write!(
+                formatter,
+                "invalid deploy {} from {}: {}",
+                deploy.id(),
+                source,
+                error
+            ),
         }
     }
 }
@@ -160,6 +242,9 @@ impl<I: Display> Display for DeployAcceptorAnnouncement<I> {
 pub enum ConsensusAnnouncement {
     /// A block was finalized.
     Finalized(Box<FinalizedBlock>),
+    /// The deploys and transfers in a block were finalized.  This is announced ahead of the block
+    /// itself, so that components can stop propagating them as early as possible.
+    DeploysFinalized(Vec<DeployHash>),
     /// A finality signature was created.
     CreatedFinalitySignature(Box<FinalitySignature>),
     /// An equivocation has been detected.
@@ -179,6 +264,9 @@ impl Display for ConsensusAnnouncement {
             ConsensusAnnouncement::Finalized(block) => {
                 write!(formatter, "finalized block payload {}", block)
             }
+            ConsensusAnnouncement::DeploysFinalized(deploy_hashes) => {
+                write!(formatter, "finalized {} deploys", deploy_hashes.len())
+            }
             ConsensusAnnouncement::CreatedFinalitySignature(fs) => {
                 write!(formatter, "signed an executed block: {}", fs)
             }
@@ -306,6 +394,39 @@ impl<T: Item> Display for GossiperAnnouncement<T> {
     }
 }
 
+/// A block proposer announcement.
+#[derive(Debug, Serialize)]
+pub enum BlockProposerAnnouncement {
+    /// A buffered deploy has been replaced by a deploy from the same account with the same body
+    /// but a higher gas price.  The replaced deploy will no longer be proposed.
+    DeployReplaced {
+        /// The hash of the deploy which was replaced.
+        replaced: DeployHash,
+        /// The hash of the replacement deploy.
+        replacement: DeployHash,
+    },
+    /// A buffered deploy has been evicted since the buffer exceeded its maximum size.  The evicted
+    /// deploy will no longer be proposed.
+    DeployEvicted {
+        /// The hash of the deploy which was evicted.
+        deploy_hash: DeployHash,
+    },
+}
+
+impl Display for BlockProposerAnnouncement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            BlockProposerAnnouncement::DeployReplaced {
+                replaced,
+                replacement,
+            } => write!(f, "deploy {} replaced by {}", replaced, replacement),
+            BlockProposerAnnouncement::DeployEvicted { deploy_hash } => {
+                write!(f, "deploy {} evicted from full buffer", deploy_hash)
+            }
+        }
+    }
+}
+
 /// A linear chain announcement.
 #[derive(Debug)]
 pub enum LinearChainAnnouncement {
@@ -344,3 +465,25 @@ impl Display for ChainspecLoaderAnnouncement {
         }
     }
 }
+
+/// A request limiter announcement.
+#[derive(Debug, Serialize)]
+pub enum RequestLimiterAnnouncement {
+    /// A `GetRequest` or `GetBatchRequest` from a peer was admitted and should now be served.
+    RequestAdmitted {
+        /// The peer which sent the request.
+        sender: NodeId,
+        /// The request.
+        payload: Box<Message>,
+    },
+}
+
+impl Display for RequestLimiterAnnouncement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            RequestLimiterAnnouncement::RequestAdmitted { sender, payload } => {
+                write!(f, "admitted {} from {}", payload, sender)
+            }
+        }
+    }
+}
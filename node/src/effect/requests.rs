@@ -8,6 +8,7 @@ use std::{
     collections::{BTreeMap, HashMap, HashSet},
     fmt::{self, Debug, Display, Formatter},
     mem,
+    ops::RangeInclusive,
     sync::Arc,
 };
 
@@ -19,7 +20,10 @@ use static_assertions::const_assert;
 use casper_execution_engine::{
     core::engine_state::{
         self,
-        balance::{BalanceRequest, BalanceResult},
+        balance::{
+            BalanceRequest, BalanceResult, BalancesRequest, BalancesResult, GetAllBalancesRequest,
+            GetAllBalancesResult, PurseIdentifier,
+        },
         era_validators::GetEraValidatorsError,
         genesis::GenesisResult,
         query::{GetBidsRequest, GetBidsResult, QueryRequest, QueryResult},
@@ -27,9 +31,10 @@ use casper_execution_engine::{
         upgrade::{UpgradeConfig, UpgradeResult},
     },
     shared::{newtypes::Blake2bHash, stored_value::StoredValue},
-    storage::{protocol_data::ProtocolData, trie::Trie},
+    storage::{lmdb_stats::LmdbStats, protocol_data::ProtocolData, trie::Trie},
 };
 use casper_types::{
+    account::AccountHash,
     system::auction::{EraValidators, ValidatorWeights},
     EraId, ExecutionResult, Key, ProtocolVersion, PublicKey, Transfer, URef,
 };
@@ -37,19 +42,22 @@ use casper_types::{
 use super::Responder;
 use crate::{
     components::{
+        block_proposer::DeployBufferStatus,
         block_validator::ValidatingBlock,
         chainspec_loader::CurrentRunInfo,
         consensus::{BlockContext, ClContext},
         contract_runtime::{EraValidatorsRequest, ValidatorWeightsByEraIdRequest},
         deploy_acceptor::Error,
         fetcher::FetchResult,
+        rpc_server::{GetAllBalancesError, GetBalancesError, GetGossipStateError},
     },
     crypto::hash::Digest,
     rpcs::{chain::BlockIdentifier, docs::OpenRpcSchema},
     types::{
-        Block as LinearBlock, Block, BlockHash, BlockHeader, BlockPayload, BlockSignatures,
-        Chainspec, ChainspecInfo, Deploy, DeployHash, DeployHeader, DeployMetadata, FinalizedBlock,
-        Item, NodeId, StatusFeed, TimeDiff,
+        Approval, Block as LinearBlock, Block, BlockHash, BlockHeader, BlockPayload,
+        BlockSignatures, Chainspec, ChainspecInfo, Deploy, DeployHash, DeployHeader,
+        DeployMetadata, DeploySpread, DeployStatus, DialBackoff, EraProgress, FinalizedBlock,
+        GossipState, Item, NodeId, StatusFeed, TimeDiff, ValidatorChange, ValidatorPerformance,
     },
     utils::DisplayIter,
 };
@@ -110,6 +118,9 @@ pub enum NetworkRequest<I, P> {
         payload: Box<P>,
         /// Number of peers to gossip to. This is an upper bound, otherwise best-effort.
         count: usize,
+        /// Minimum number of the chosen peers which should be validators.  Non-validator peers
+        /// are chosen instead if not enough validators are connected.
+        min_validators: usize,
         /// Node IDs of nodes to exclude from gossiping to.
         #[serde(skip_serializing)]
         exclude: HashSet<I>,
@@ -144,11 +155,13 @@ impl<I, P> NetworkRequest<I, P> {
             NetworkRequest::Gossip {
                 payload,
                 count,
+                min_validators,
                 exclude,
                 responder,
             } => NetworkRequest::Gossip {
                 payload: Box::new(wrap_payload(*payload)),
                 count,
+                min_validators,
                 exclude,
                 responder,
             },
@@ -184,6 +197,17 @@ pub enum NetworkInfoRequest<I> {
         // TODO - change the `String` field to a `libp2p::Multiaddr` once small_network is removed.
         responder: Responder<BTreeMap<I, String>>,
     },
+    /// Get the addresses waiting to be redialed after failed connection attempts.
+    GetDialBackoffs {
+        /// Responder to be called with the waiting addresses.
+        responder: Responder<Vec<DialBackoff>>,
+    },
+    /// Get a snapshot of the gossipsub state.
+    GetGossipState {
+        /// Responder to be called with the gossipsub state, or `None` if the networking component
+        /// doesn't use gossipsub.
+        responder: Responder<Option<GossipState>>,
+    },
 }
 
 impl<I> Display for NetworkInfoRequest<I>
@@ -193,6 +217,8 @@ where
     fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
         match self {
             NetworkInfoRequest::GetPeers { responder: _ } => write!(formatter, "get peers"),
+            NetworkInfoRequest::GetDialBackoffs { .. } => write!(formatter, "get dial backoffs"),
+            NetworkInfoRequest::GetGossipState { .. } => write!(formatter, "get gossip state"),
         }
     }
 }
@@ -301,6 +327,13 @@ pub enum StorageRequest {
         /// Responder to call with the results.
         responder: Responder<Vec<Option<DeployHeader>>>,
     },
+    /// Retrieve the approvals of the deploy with the given hash.
+    GetDeployApprovals {
+        /// Hash of the deploy whose approvals are to be retrieved.
+        deploy_hash: DeployHash,
+        /// Responder to call with the result.
+        responder: Responder<Option<Vec<Approval>>>,
+    },
     /// Retrieve deploys that are finalized and whose TTL hasn't expired yet.
     GetFinalizedDeploys {
         /// Maximum TTL of block we're interested in.
@@ -331,6 +364,13 @@ pub enum StorageRequest {
         /// Responder to call with the results.
         responder: Responder<Option<(Deploy, DeployMetadata)>>,
     },
+    /// Retrieve deploy header and its metadata.
+    GetDeployHeaderAndMetadata {
+        /// Hash of deploy whose header is to be retrieved.
+        deploy_hash: DeployHash,
+        /// Responder to call with the results.
+        responder: Responder<Option<(DeployHeader, DeployMetadata)>>,
+    },
     /// Retrieve block and its metadata by its hash.
     GetBlockAndMetadataByHash {
         /// The hash of the block.
@@ -365,6 +405,39 @@ pub enum StorageRequest {
         /// stored.
         responder: Responder<bool>,
     },
+    /// Store the performance of validators in an era, and delete that of eras older than
+    /// `oldest_retained_era`.
+    PutEraPerformance {
+        /// The era the performance relates to.
+        era_id: EraId,
+        /// The performance of each validator in the era.
+        performance: BTreeMap<PublicKey, ValidatorPerformance>,
+        /// The oldest era whose performance is retained.
+        oldest_retained_era: EraId,
+        /// Responder to call when done storing.
+        responder: Responder<()>,
+    },
+    /// Retrieve the stored performance of validators in a range of eras.
+    GetEraPerformance {
+        /// The eras whose performance is to be retrieved.
+        era_ids: RangeInclusive<EraId>,
+        /// If `Some`, only the performance of this validator is retrieved.
+        maybe_public_key: Option<Box<PublicKey>>,
+        /// Responder to call with the results.
+        responder: Responder<BTreeMap<EraId, BTreeMap<PublicKey, ValidatorPerformance>>>,
+    },
+    /// Retrieve the stored changes to the validator set taking effect in a range of eras.
+    GetValidatorChanges {
+        /// The eras whose validator changes are to be retrieved.
+        era_ids: RangeInclusive<EraId>,
+        /// Responder to call with the results.
+        responder: Responder<BTreeMap<EraId, BTreeMap<PublicKey, Vec<ValidatorChange>>>>,
+    },
+    /// Collect the statistics of the storage component's LMDB environment.
+    GetLmdbStats {
+        /// Responder to call with the statistics, or `None` if collecting them failed.
+        responder: Responder<Option<LmdbStats>>,
+    },
 }
 
 impl Display for StorageRequest {
@@ -406,12 +479,22 @@ impl Display for StorageRequest {
                 "get headers {}",
                 DisplayIter::new(deploy_hashes.iter())
             ),
+            StorageRequest::GetDeployApprovals { deploy_hash, .. } => {
+                write!(formatter, "get approvals for {}", deploy_hash)
+            }
             StorageRequest::PutExecutionResults { block_hash, .. } => {
                 write!(formatter, "put execution results for {}", block_hash)
             }
             StorageRequest::GetDeployAndMetadata { deploy_hash, .. } => {
                 write!(formatter, "get deploy and metadata for {}", deploy_hash)
             }
+            StorageRequest::GetDeployHeaderAndMetadata { deploy_hash, .. } => {
+                write!(
+                    formatter,
+                    "get deploy header and metadata for {}",
+                    deploy_hash
+                )
+            }
             StorageRequest::GetBlockAndMetadataByHash { block_hash, .. } => {
                 write!(
                     formatter,
@@ -442,6 +525,22 @@ impl Display for StorageRequest {
             StorageRequest::GetFinalizedDeploys { ttl, .. } => {
                 write!(formatter, "get finalized deploys, ttl: {:?}", ttl)
             }
+            StorageRequest::PutEraPerformance { era_id, .. } => {
+                write!(formatter, "put validator performance for era {}", era_id)
+            }
+            StorageRequest::GetEraPerformance { era_ids, .. } => write!(
+                formatter,
+                "get validator performance for eras {} to {}",
+                era_ids.start(),
+                era_ids.end()
+            ),
+            StorageRequest::GetValidatorChanges { era_ids, .. } => write!(
+                formatter,
+                "get validator changes for eras {} to {}",
+                era_ids.start(),
+                era_ids.end()
+            ),
+            StorageRequest::GetLmdbStats { .. } => write!(formatter, "get LMDB statistics"),
         }
     }
 }
@@ -483,7 +582,7 @@ impl Display for StateStoreRequest {
 }
 
 /// Details of a request for a list of deploys to propose in a new block.
-#[derive(DataSize, Debug)]
+#[derive(DataSize, Debug, Serialize)]
 pub struct BlockPayloadRequest {
     /// The context in which the new block will be proposed.
     pub(crate) context: BlockContext<ClContext>,
@@ -501,11 +600,18 @@ pub struct BlockPayloadRequest {
 }
 
 /// A `BlockProposer` request.
-#[derive(DataSize, Debug)]
+#[derive(DataSize, Debug, Serialize)]
 #[must_use]
 pub enum BlockProposerRequest {
     /// Request a list of deploys to propose in a new block.
     RequestBlockPayload(BlockPayloadRequest),
+    /// Request the stage the given deploy has reached in the buffer.
+    GetDeployStatus {
+        /// The hash of the deploy.
+        deploy_hash: DeployHash,
+        /// Responder to call with the result.
+        responder: Responder<DeployBufferStatus>,
+    },
 }
 
 impl Display for BlockProposerRequest {
@@ -524,6 +630,9 @@ impl Display for BlockProposerRequest {
                 context.height(),
                 next_finalized
             ),
+            BlockProposerRequest::GetDeployStatus { deploy_hash, .. } => {
+                write!(formatter, "get buffer status of {}", deploy_hash)
+            }
         }
     }
 }
@@ -600,6 +709,24 @@ pub enum RpcRequest<I> {
         /// Responder to call with the result.
         responder: Responder<Result<BalanceResult, engine_state::Error>>,
     },
+    /// Query the balances of several purses at the given root hash.
+    GetBalances {
+        /// The state root hash.
+        state_root_hash: Digest,
+        /// The purses whose balances are requested.
+        purse_identifiers: Vec<PurseIdentifier>,
+        /// Responder to call with the result.
+        responder: Responder<Result<BalancesResult, GetBalancesError>>,
+    },
+    /// Query a page of the balances of all accounts' main purses at the given root hash.
+    GetAllBalances {
+        /// The state root hash.
+        state_root_hash: Digest,
+        /// The account hash after which the page starts, or `None` for the first page.
+        start_after: Option<AccountHash>,
+        /// Responder to call with the result.
+        responder: Responder<Result<GetAllBalancesResult, GetAllBalancesError>>,
+    },
     /// Return the specified deploy and metadata if it exists, else `None`.
     GetDeploy {
         /// The hash of the deploy to be retrieved.
@@ -607,11 +734,40 @@ pub enum RpcRequest<I> {
         /// Responder to call with the result.
         responder: Responder<Option<(Deploy, DeployMetadata)>>,
     },
+    /// Return the lifecycle stage the specified deploy has reached.
+    GetDeployStatus {
+        /// The hash of the deploy.
+        hash: DeployHash,
+        /// Responder to call with the result.
+        responder: Responder<DeployStatus>,
+    },
+    /// Return the performance of validators in the given range of eras, optionally restricted to a
+    /// single validator.
+    GetValidatorPerformance {
+        /// The eras whose validator performance is requested.
+        era_ids: RangeInclusive<EraId>,
+        /// The validator whose performance is requested, or `None` for all validators.
+        maybe_public_key: Option<Box<PublicKey>>,
+        /// Responder to call with the result.
+        responder: Responder<BTreeMap<EraId, BTreeMap<PublicKey, ValidatorPerformance>>>,
+    },
+    /// Return the changes to the validator set taking effect in the given range of eras.
+    GetValidatorChanges {
+        /// The eras whose validator changes are requested.
+        era_ids: RangeInclusive<EraId>,
+        /// Responder to call with the result.
+        responder: Responder<BTreeMap<EraId, BTreeMap<PublicKey, Vec<ValidatorChange>>>>,
+    },
     /// Return the connected peers.
     GetPeers {
         /// Responder to call with the result.
         responder: Responder<BTreeMap<I, String>>,
     },
+    /// Return a snapshot of the gossipsub state.
+    GetGossipState {
+        /// Responder to call with the result.
+        responder: Responder<Result<GossipState, GetGossipStateError>>,
+    },
     /// Return string formatted status or `None` if an error occurred.
     GetStatus {
         /// Responder to call with the result.
@@ -670,8 +826,44 @@ impl<I> Display for RpcRequest<I> {
                 "balance {}, purse_uref: {}",
                 state_root_hash, purse_uref
             ),
+            RpcRequest::GetBalances {
+                state_root_hash,
+                purse_identifiers,
+                ..
+            } => write!(
+                formatter,
+                "balances {}, {} purses",
+                state_root_hash,
+                purse_identifiers.len()
+            ),
+            RpcRequest::GetAllBalances {
+                state_root_hash,
+                start_after,
+                ..
+            } => match start_after {
+                Some(account_hash) => write!(
+                    formatter,
+                    "all balances {}, after {}",
+                    state_root_hash, account_hash
+                ),
+                None => write!(formatter, "all balances {}", state_root_hash),
+            },
             RpcRequest::GetDeploy { hash, .. } => write!(formatter, "get {}", hash),
+            RpcRequest::GetDeployStatus { hash, .. } => write!(formatter, "get status of {}", hash),
+            RpcRequest::GetValidatorPerformance { era_ids, .. } => write!(
+                formatter,
+                "get validator performance for eras {} to {}",
+                era_ids.start(),
+                era_ids.end()
+            ),
+            RpcRequest::GetValidatorChanges { era_ids, .. } => write!(
+                formatter,
+                "get validator changes for eras {} to {}",
+                era_ids.start(),
+                era_ids.end()
+            ),
             RpcRequest::GetPeers { .. } => write!(formatter, "get peers"),
+            RpcRequest::GetGossipState { .. } => write!(formatter, "get gossip state"),
             RpcRequest::GetStatus { .. } => write!(formatter, "get status"),
             RpcRequest::GetMetrics { .. } => write!(formatter, "get metrics"),
         }
@@ -700,6 +892,12 @@ pub enum RestRequest<I> {
         /// Responder to call with the result
         responder: Responder<OpenRpcSchema>,
     },
+    /// Return the propagation of the most recently probed deploys, or `None` if measuring deploy
+    /// propagation is disabled.
+    GetDeployPropagation {
+        /// Responder to call with the result.
+        responder: Responder<Option<Vec<DeploySpread>>>,
+    },
 }
 
 impl<I> Display for RestRequest<I> {
@@ -708,6 +906,7 @@ impl<I> Display for RestRequest<I> {
             RestRequest::GetStatus { .. } => write!(formatter, "get status"),
             RestRequest::GetMetrics { .. } => write!(formatter, "get metrics"),
             RestRequest::GetRpcSchema { .. } => write!(formatter, "get openrpc"),
+            RestRequest::GetDeployPropagation { .. } => write!(formatter, "get deploy propagation"),
         }
     }
 }
@@ -757,6 +956,22 @@ pub enum ContractRuntimeRequest {
         /// Responder to call with the balance result.
         responder: Responder<Result<BalanceResult, engine_state::Error>>,
     },
+    /// A batched balance request.
+    GetBalances {
+        /// Balances request.
+        #[serde(skip_serializing)]
+        balances_request: BalancesRequest,
+        /// Responder to call with the balances result.
+        responder: Responder<Result<BalancesResult, engine_state::Error>>,
+    },
+    /// A request for a chunk of the balances of all accounts' main purses.
+    GetAllBalances {
+        /// All balances request.
+        #[serde(skip_serializing)]
+        get_all_balances_request: GetAllBalancesRequest,
+        /// Responder to call with the all balances result.
+        responder: Responder<Result<GetAllBalancesResult, engine_state::Error>>,
+    },
     /// Returns validator weights.
     GetEraValidators {
         /// Get validators weights request.
@@ -824,6 +1039,11 @@ pub enum ContractRuntimeRequest {
         /// Responder to call with the result.
         responder: Responder<Result<Vec<Blake2bHash>, engine_state::Error>>,
     },
+    /// Collect the statistics of the global state's LMDB environment.
+    GetLmdbStats {
+        /// Responder to call with the statistics, or `None` if collecting them failed.
+        responder: Responder<Option<LmdbStats>>,
+    },
 }
 
 impl Display for ContractRuntimeRequest {
@@ -852,6 +1072,19 @@ impl Display for ContractRuntimeRequest {
                 balance_request, ..
             } => write!(formatter, "balance request: {:?}", balance_request),
 
+            ContractRuntimeRequest::GetBalances {
+                balances_request, ..
+            } => write!(formatter, "balances request: {:?}", balances_request),
+
+            ContractRuntimeRequest::GetAllBalances {
+                get_all_balances_request,
+                ..
+            } => write!(
+                formatter,
+                "all balances request: {:?}",
+                get_all_balances_request
+            ),
+
             ContractRuntimeRequest::GetEraValidators { request, .. } => {
                 write!(formatter, "get era validators: {:?}", request)
             }
@@ -892,6 +1125,9 @@ impl Display for ContractRuntimeRequest {
                     trie_key
                 )
             }
+            ContractRuntimeRequest::GetLmdbStats { .. } => {
+                write!(formatter, "get global state LMDB statistics")
+            }
         }
     }
 }
@@ -976,6 +1212,8 @@ impl<I: Display> Display for LinearChainRequest<I> {
 pub enum ConsensusRequest {
     /// Request for our public key, and if we're a validator, the next round length.
     Status(Responder<Option<(PublicKey, Option<TimeDiff>)>>),
+    /// Request for the progress of the current era.
+    EraProgress(Responder<Option<EraProgress>>),
 }
 
 /// ChainspecLoader component requests.
@@ -995,3 +1233,20 @@ impl Display for ChainspecLoaderRequest {
         }
     }
 }
+
+/// Deploy propagation component requests.
+#[derive(Debug)]
+#[must_use]
+pub enum DeployPropagationRequest {
+    /// Request for the propagation of the most recently probed deploys, or `None` if measuring
+    /// deploy propagation is disabled.
+    GetSpreads(Responder<Option<Vec<DeploySpread>>>),
+}
+
+impl Display for DeployPropagationRequest {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DeployPropagationRequest::GetSpreads(_) => write!(f, "get deploy spreads"),
+        }
+    }
+}
@@ -24,10 +24,15 @@
 
 extern crate test;
 
+pub mod build_info;
+mod chain_archive;
 pub mod components;
 mod config_migration;
+mod config_overrides;
+mod config_validation;
 pub mod crypto;
 mod data_migration;
+mod diagnostics;
 pub mod effect;
 pub mod logging;
 pub mod protocol;
@@ -48,24 +53,43 @@ use once_cell::sync::Lazy;
 #[cfg(not(test))]
 use rand::SeedableRng;
 use signal_hook::{
-    consts::{signal::SIGUSR1, TERM_SIGNALS},
+    consts::{
+        signal::{SIGHUP, SIGUSR1},
+        TERM_SIGNALS,
+    },
     flag,
 };
 
+pub use chain_archive::{
+    export_chain_archive, import_chain_archive, ChainArchiveManifest, ChunkInfo, ChunkKind,
+    Error as ChainArchiveError,
+};
 pub use components::{
+    block_proposer::Config as BlockProposerConfig,
     consensus::Config as ConsensusConfig,
     contract_runtime::Config as ContractRuntimeConfig,
     deploy_acceptor::Config as DeployAcceptorConfig,
+    deploy_propagation::Config as DeployPropagationConfig,
     event_stream_server::Config as EventStreamServerConfig,
     fetcher::Config as FetcherConfig,
     gossiper::{Config as GossipConfig, Error as GossipError},
+    performance_tracker::Config as PerformanceTrackerConfig,
+    request_limiter::Config as RequestLimiterConfig,
     rest_server::Config as RestServerConfig,
     rpc_server::{rpcs, Config as RpcServerConfig},
-    small_network::{Config as SmallNetworkConfig, Error as SmallNetworkError},
+    small_network::{
+        clear_saved_blocklist as clear_saved_peer_blocklist, Config as SmallNetworkConfig,
+        Error as SmallNetworkError,
+    },
     storage::{Config as StorageConfig, Error as StorageError},
 };
 pub use config_migration::{migrate_config, Error as ConfigMigrationError};
+pub use config_overrides::ConfigOverride;
+pub use config_validation::{deserialize_config, ConfigValidationError, InvalidConfigError};
 pub use data_migration::{migrate_data, Error as DataMigrationError};
+pub use diagnostics::{
+    create_diagnostics_bundle, DiagnosticsManifest, DiagnosticsOptions, Error as DiagnosticsError,
+};
 pub use types::NodeRng;
 pub use utils::OS_PAGE_SIZE;
 
@@ -104,6 +128,14 @@ pub static TERMINATION_REQUESTED: Lazy<Arc<AtomicUsize>> =
 pub static QUEUE_DUMP_REQUESTED: Lazy<Arc<AtomicBool>> =
     Lazy::new(|| Arc::new(AtomicBool::new(false)));
 
+/// Global flag that indicates the upgrade schedule should be reloaded from disk.
+pub static UPGRADE_SCHEDULE_RELOAD_REQUESTED: Lazy<Arc<AtomicBool>> =
+    Lazy::new(|| Arc::new(AtomicBool::new(false)));
+
+/// Global flag that indicates the log filter should be reloaded from the config file.
+pub static LOG_FILTER_RELOAD_REQUESTED: Lazy<Arc<AtomicBool>> =
+    Lazy::new(|| Arc::new(AtomicBool::new(false)));
+
 /// Setup UNIX signal hooks for current application.
 pub fn setup_signal_hooks() {
     for signal in TERM_SIGNALS {
@@ -115,6 +147,8 @@ pub fn setup_signal_hooks() {
         .unwrap_or_else(|error| panic!("failed to register signal {}: {}", signal, error));
     }
     let _ = flag::register(SIGUSR1, Arc::clone(&*QUEUE_DUMP_REQUESTED));
+    let _ = flag::register(SIGHUP, Arc::clone(&*UPGRADE_SCHEDULE_RELOAD_REQUESTED));
+    let _ = flag::register(SIGHUP, Arc::clone(&*LOG_FILTER_RELOAD_REQUESTED));
 }
 
 /// Constructs a new `NodeRng`.
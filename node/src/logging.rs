@@ -5,11 +5,12 @@ use std::{env, fmt, io};
 use ansi_term::{Color, Style};
 use anyhow::anyhow;
 use datasize::DataSize;
+use once_cell::sync::OnceCell;
 use serde::{Deserialize, Serialize};
 use smallvec::SmallVec;
 use tracing::{
     field::{Field, Visit},
-    Event, Level, Subscriber,
+    info, Event, Level, Subscriber,
 };
 use tracing_subscriber::{
     fmt::{
@@ -18,10 +19,11 @@ use tracing_subscriber::{
         FmtContext, FormatEvent, FormatFields, FormattedFields,
     },
     registry::LookupSpan,
-    EnvFilter,
+    reload, EnvFilter,
 };
 
 const LOG_CONFIGURATION_ENVVAR: &str = "RUST_LOG";
+const DEFAULT_FILTER: &str = "warn,casper_node=info";
 
 const LOG_FIELD_MESSAGE: &str = "message";
 const LOG_FIELD_TARGET: &str = "log.target";
@@ -47,6 +49,13 @@ pub struct LoggingConfig {
     /// If set, human-readable formats will abbreviate module names, `foo::bar::baz::bizz` will
     /// turn into `f:b:b:bizz`.
     abbreviate_modules: bool,
+
+    /// Filter directives, in the same form as `RUST_LOG`, which take precedence over it.
+    ///
+    /// The filter is reapplied from the config file on `SIGHUP`, so the log level of a running
+    /// node can be changed.
+    #[serde(default)]
+    filter: Option<String>,
 }
 
 impl LoggingConfig {
@@ -56,6 +65,7 @@ impl LoggingConfig {
             format,
             color,
             abbreviate_modules,
+            filter: None,
         }
     }
 }
@@ -251,6 +261,41 @@ where
     }
 }
 
+/// The filter of the installed subscriber, which can be replaced while the node runs.
+struct ReloadableFilter {
+    /// The directives applied if the config sets none, i.e. `RUST_LOG` or the default filter.
+    fallback_directives: String,
+    /// Replaces the filter of the subscriber.
+    reload: Box<dyn Fn(EnvFilter) -> Result<(), reload::Error> + Send + Sync>,
+}
+
+impl ReloadableFilter {
+    fn new<S>(fallback_directives: String, handle: reload::Handle<EnvFilter, S>) -> Self
+    where
+        S: Subscriber + Send + Sync + 'static,
+    {
+        ReloadableFilter {
+            fallback_directives,
+            reload: Box::new(move |filter| handle.reload(filter)),
+        }
+    }
+
+    /// Replaces the filter with one built from `directives`, or from the fallback directives if
+    /// `None`.
+    ///
+    /// If the directives are invalid, the current filter is kept.
+    fn reload(&self, directives: Option<&str>) -> anyhow::Result<()> {
+        let directives = directives.unwrap_or(&self.fallback_directives);
+        let filter = EnvFilter::try_new(directives)?;
+        (self.reload)(filter)?;
+        info!(%directives, "reloaded log filter");
+        Ok(())
+    }
+}
+
+/// The filter of the subscriber installed by `init_with_config`.
+static RELOADABLE_FILTER: OnceCell<ReloadableFilter> = OnceCell::new();
+
 /// Initializes the logging system with the default parameters.
 ///
 /// See `init_params` for details.
@@ -271,26 +316,104 @@ pub fn init_with_config(config: &LoggingConfig) -> anyhow::Result<()> {
         _ => write!(writer, "; {}={:?}", field, value),
     });
 
-    let filter = EnvFilter::new(
-        env::var(LOG_CONFIGURATION_ENVVAR)
-            .as_deref()
-            .unwrap_or("warn,casper_node=info"),
-    );
+    let fallback_directives =
+        env::var(LOG_CONFIGURATION_ENVVAR).unwrap_or_else(|_| DEFAULT_FILTER.to_string());
+    let filter = EnvFilter::new(config.filter.as_deref().unwrap_or(&fallback_directives));
 
-    match config.format {
+    let reloadable_filter = match config.format {
         // Setup a new tracing-subscriber writing to `stdout` for logging.
-        LoggingFormat::Text => tracing_subscriber::fmt()
-            .with_writer(io::stdout)
-            .with_env_filter(filter)
-            .fmt_fields(formatter)
-            .event_format(FmtEvent::new(config.color, config.abbreviate_modules))
-            .try_init(),
+        LoggingFormat::Text => {
+            let builder = tracing_subscriber::fmt()
+                .with_writer(io::stdout)
+                .with_env_filter(filter)
+                .fmt_fields(formatter)
+                .event_format(FmtEvent::new(config.color, config.abbreviate_modules))
+                .with_filter_reloading();
+            let handle = builder.reload_handle();
+            builder
+                .try_init()
+                .map(|()| ReloadableFilter::new(fallback_directives, handle))
+        }
         // JSON logging writes to `stdout` as well but uses the JSON format.
-        LoggingFormat::Json => tracing_subscriber::fmt()
-            .with_writer(io::stdout)
-            .with_env_filter(filter)
-            .json()
-            .try_init(),
+        LoggingFormat::Json => {
+            let builder = tracing_subscriber::fmt()
+                .with_writer(io::stdout)
+                .with_env_filter(filter)
+                .json()
+                .with_filter_reloading();
+            let handle = builder.reload_handle();
+            builder
+                .try_init()
+                .map(|()| ReloadableFilter::new(fallback_directives, handle))
+        }
+    }
+    .map_err(|error| anyhow!(error))?;
+
+    // Only the first call installs a subscriber, so can set the reloadable filter.
+    let _ = RELOADABLE_FILTER.set(reloadable_filter);
+    Ok(())
+}
+
+/// Replaces the filter of the logging system with the one set in `config`, or with the filter set
+/// via `RUST_LOG` or the default filter if `config` sets none.
+///
+/// If the filter is invalid, the current one is kept and an error is returned.
+pub fn reload_filter(config: &LoggingConfig) -> anyhow::Result<()> {
+    RELOADABLE_FILTER
+        .get()
+        .ok_or_else(|| anyhow!("logging has not been initialized"))?
+        .reload(config.filter.as_deref())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{self, Write},
+        sync::{Arc, Mutex},
+    };
+
+    use tracing::debug;
+
+    use super::*;
+
+    /// A writer appending to a shared buffer.
+    struct CapturingWriter(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for CapturingWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn should_reload_filter() {
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let writer_output = Arc::clone(&output);
+        let builder = tracing_subscriber::fmt()
+            .with_writer(move || CapturingWriter(Arc::clone(&writer_output)))
+            .with_env_filter(EnvFilter::new("info"))
+            .with_filter_reloading();
+        let reloadable_filter = ReloadableFilter::new("info".to_string(), builder.reload_handle());
+
+        tracing::subscriber::with_default(builder.finish(), || {
+            debug!("suppressed before reload");
+            reloadable_filter.reload(Some("debug")).unwrap();
+            debug!("emitted after reload");
+            assert!(reloadable_filter
+                .reload(Some("casper_node=loudest"))
+                .is_err());
+            debug!("emitted after invalid reload");
+            reloadable_filter.reload(None).unwrap();
+            debug!("suppressed after reload to fallback");
+        });
+
+        let output = String::from_utf8(output.lock().unwrap().clone()).unwrap();
+        assert!(output.contains("emitted after reload"));
+        assert!(output.contains("emitted after invalid reload"));
+        assert!(!output.contains("suppressed"), "{}", output);
     }
-    .map_err(|error| anyhow!(error))
 }
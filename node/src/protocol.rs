@@ -9,12 +9,29 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     components::{
-        consensus, gossiper,
-        small_network::{GossipedAddress, MessageKind, Payload},
+        consensus, deploy_propagation, gossiper,
+        network::PayloadT,
+        small_network::{legacy_address_message, GossipedAddress, MessageKind, Payload},
     },
-    types::{Deploy, FinalitySignature, Item, SharedObject, Tag},
+    types::{Chainspec, Deploy, FinalitySignature, Item, SharedObject, Tag, TimeDiff},
 };
 
+/// The maximum serialized size in bytes of messages which only carry IDs, addresses or signatures.
+const MAX_SMALL_MESSAGE_SIZE: u32 = 1024;
+
+/// Allowance in bytes for the enum tags and length prefixes wrapping an item whose size is limited
+/// by the chainspec.
+const ENVELOPE_OVERHEAD: u32 = 1024;
+
+/// The maximum number of IDs carried by a single `GetBatchRequest`.
+pub(crate) const MAX_BATCH_ID_COUNT: usize = 100;
+
+/// The maximum serialized size in bytes of a `GetBatchRequest`.
+const MAX_BATCH_REQUEST_SIZE: u32 = 8 * 1024;
+
+/// Allowance in bytes for the length prefix of each serialized ID or item in a batch message.
+const BATCH_ENTRY_OVERHEAD: usize = 8;
+
 /// Reactor message.
 #[derive(Clone, From, Serialize, Deserialize)]
 pub enum Message {
@@ -24,9 +41,8 @@ pub enum Message {
     /// Deploy gossiper component message.
     #[from]
     DeployGossiper(gossiper::Message<Deploy>),
-    /// Address gossiper component message.
-    #[from]
-    AddressGossiper(gossiper::Message<GossipedAddress>),
+    /// Address gossiper component message carrying an unsigned address.
+    AddressGossiper(#[serde(with = "legacy_address_message")] gossiper::Message<GossipedAddress>),
     /// Request to get an item from a peer.
     GetRequest {
         /// The type tag of the requested item.
@@ -44,6 +60,59 @@ pub enum Message {
     /// Finality signature.
     #[from]
     FinalitySignature(Box<FinalitySignature>),
+    /// Request to get several items of the same type from a peer.
+    GetBatchRequest {
+        /// The type tag of the requested items.
+        tag: Tag,
+        /// The serialized IDs of the requested items.
+        serialized_ids: Vec<Vec<u8>>,
+    },
+    /// Response to a `GetBatchRequest`.
+    ///
+    /// A single request may be answered by several responses if the found items don't fit into
+    /// one message.
+    GetBatchResponse {
+        /// The type tag of the contained items.
+        tag: Tag,
+        /// The serialized items which were found.
+        serialized_items: Vec<SharedObject<Vec<u8>>>,
+        /// The serialized IDs of the requested items which were not found.
+        serialized_missing_ids: Vec<Vec<u8>>,
+    },
+    /// Address gossiper component message carrying a signed address record.
+    ///
+    /// Kept separate from `AddressGossiper` so that nodes which don't sign their address records
+    /// drop it as an unknown variant.
+    SignedAddressGossiper(gossiper::Message<GossipedAddress>),
+    /// Rejection of a `GetRequest` or `GetBatchRequest` which the peer is currently too busy
+    /// serving other requests from us to answer.
+    ///
+    /// The requester should back off from the peer or fetch the items from a different one.
+    TryLater {
+        /// The type tag of the requested items.
+        tag: Tag,
+        /// The serialized IDs of the requested items.
+        serialized_ids: Vec<Vec<u8>>,
+        /// The time after which the peer expects to be able to serve our requests again.
+        retry_after: TimeDiff,
+    },
+    /// Deploy propagation component message.
+    #[from]
+    DeployPropagation(deploy_propagation::Message),
+}
+
+impl From<gossiper::Message<GossipedAddress>> for Message {
+    fn from(message: gossiper::Message<GossipedAddress>) -> Self {
+        let item_id = match &message {
+            gossiper::Message::Gossip(item_id) => item_id,
+            gossiper::Message::GossipResponse { item_id, .. } => item_id,
+        };
+        if item_id.is_signed() {
+            Message::SignedAddressGossiper(message)
+        } else {
+            Message::AddressGossiper(message)
+        }
+    }
 }
 
 impl Payload for Message {
@@ -52,8 +121,14 @@ impl Payload for Message {
         match self {
             Message::Consensus(_) => MessageKind::Consensus,
             Message::DeployGossiper(_) => MessageKind::DeployGossip,
-            Message::AddressGossiper(_) => MessageKind::AddressGossip,
-            Message::GetRequest { tag, .. } | Message::GetResponse { tag, .. } => {
+            Message::AddressGossiper(_) | Message::SignedAddressGossiper(_) => {
+                MessageKind::AddressGossip
+            }
+            Message::GetRequest { tag, .. }
+            | Message::GetResponse { tag, .. }
+            | Message::GetBatchRequest { tag, .. }
+            | Message::GetBatchResponse { tag, .. }
+            | Message::TryLater { tag, .. } => {
                 match tag {
                     Tag::Deploy => MessageKind::DeployTransfer,
                     Tag::Block => MessageKind::BlockTransfer,
@@ -62,9 +137,13 @@ impl Payload for Message {
                     Tag::BlockByHeight => MessageKind::BlockTransfer,
                     Tag::BlockHeaderByHash => MessageKind::BlockTransfer,
                     Tag::BlockHeaderAndFinalitySignaturesByHeight => MessageKind::BlockTransfer,
+                    Tag::DeployHeaderWithHash => MessageKind::DeployTransfer,
+                    Tag::DeployApprovals => MessageKind::DeployTransfer,
+                    Tag::BlockTransfers => MessageKind::BlockTransfer,
                 }
             }
             Message::FinalitySignature(_) => MessageKind::Consensus,
+            Message::DeployPropagation(_) => MessageKind::Other,
         }
     }
 
@@ -73,7 +152,7 @@ impl Payload for Message {
         match self {
             Message::Consensus(_) => 0,
             Message::DeployGossiper(_) => 0,
-            Message::AddressGossiper(_) => 0,
+            Message::AddressGossiper(_) | Message::SignedAddressGossiper(_) => 0,
             Message::GetRequest { tag, .. } | Message::GetResponse { tag, .. } => match tag {
                 Tag::Deploy => 1,
                 Tag::Block => 0,
@@ -81,8 +160,48 @@ impl Payload for Message {
                 Tag::BlockByHeight => 0,
                 Tag::BlockHeaderByHash => 0,
                 Tag::BlockHeaderAndFinalitySignaturesByHeight => 0,
+                Tag::DeployHeaderWithHash => 0,
+                Tag::DeployApprovals => 0,
+                Tag::BlockTransfers => 0,
             },
+            Message::GetBatchRequest {
+                tag: Tag::Deploy,
+                serialized_ids,
+            } => serialized_ids.len() as u32,
+            Message::GetBatchResponse {
+                tag: Tag::Deploy,
+                serialized_items,
+                ..
+            } => serialized_items.len() as u32,
+            Message::GetBatchRequest { .. } | Message::GetBatchResponse { .. } => 0,
             Message::FinalitySignature(_) => 0,
+            Message::TryLater { .. } => 0,
+            Message::DeployPropagation(_) => 0,
+        }
+    }
+}
+
+impl PayloadT for Message {
+    fn max_serialized_size(&self, chainspec: &Chainspec) -> u32 {
+        let deploy_config = &chainspec.deploy_config;
+        let max_block_message_size = deploy_config
+            .max_block_size
+            .saturating_add(ENVELOPE_OVERHEAD);
+        match self {
+            Message::Consensus(_) => max_block_message_size,
+            Message::DeployGossiper(_) => MAX_SMALL_MESSAGE_SIZE,
+            Message::AddressGossiper(_) | Message::SignedAddressGossiper(_) => {
+                MAX_SMALL_MESSAGE_SIZE
+            }
+            Message::GetRequest { .. } => MAX_SMALL_MESSAGE_SIZE,
+            Message::GetResponse { tag, .. } => tag
+                .max_item_size(chainspec)
+                .saturating_add(ENVELOPE_OVERHEAD),
+            Message::FinalitySignature(_) => MAX_SMALL_MESSAGE_SIZE,
+            Message::GetBatchRequest { .. } => MAX_BATCH_REQUEST_SIZE,
+            Message::GetBatchResponse { .. } => max_block_message_size,
+            Message::TryLater { .. } => MAX_BATCH_REQUEST_SIZE,
+            Message::DeployPropagation(_) => MAX_BATCH_REQUEST_SIZE,
         }
     }
 }
@@ -102,6 +221,80 @@ impl Message {
         })
     }
 
+    /// Constructs the `GetBatchRequest`s for the given IDs, splitting them so that no request
+    /// exceeds either `MAX_BATCH_ID_COUNT` IDs or `MAX_BATCH_REQUEST_SIZE` bytes.
+    pub(crate) fn new_get_batch_requests<T: Item>(
+        ids: &[T::Id],
+    ) -> Result<Vec<Self>, bincode::Error> {
+        let max_ids_size = (MAX_BATCH_REQUEST_SIZE - ENVELOPE_OVERHEAD) as usize;
+        let mut requests = vec![];
+        let mut serialized_ids = vec![];
+        let mut ids_size = 0;
+        for id in ids {
+            let serialized_id = bincode::serialize(id)?;
+            let entry_size = serialized_id.len() + BATCH_ENTRY_OVERHEAD;
+            if !serialized_ids.is_empty()
+                && (serialized_ids.len() == MAX_BATCH_ID_COUNT
+                    || ids_size + entry_size > max_ids_size)
+            {
+                requests.push(Message::GetBatchRequest {
+                    tag: T::TAG,
+                    serialized_ids: std::mem::take(&mut serialized_ids),
+                });
+                ids_size = 0;
+            }
+            ids_size += entry_size;
+            serialized_ids.push(serialized_id);
+        }
+        if !serialized_ids.is_empty() {
+            requests.push(Message::GetBatchRequest {
+                tag: T::TAG,
+                serialized_ids,
+            });
+        }
+        Ok(requests)
+    }
+
+    /// Constructs the `GetBatchResponse`s answering a `GetBatchRequest`, splitting the found items
+    /// so that no single response exceeds the maximum block size.
+    ///
+    /// The missing IDs are all carried by the final response, so the room they need is left free in
+    /// every response.  At least one response is always returned.
+    pub(crate) fn new_get_batch_responses(
+        tag: Tag,
+        serialized_items: Vec<SharedObject<Vec<u8>>>,
+        serialized_missing_ids: Vec<Vec<u8>>,
+        max_block_size: u32,
+    ) -> Vec<Self> {
+        let missing_ids_size: usize = serialized_missing_ids
+            .iter()
+            .map(|serialized_id| serialized_id.len() + BATCH_ENTRY_OVERHEAD)
+            .sum();
+        let max_items_size = (max_block_size as usize).saturating_sub(missing_ids_size);
+        let mut responses = vec![];
+        let mut batch = vec![];
+        let mut batch_size = 0;
+        for serialized_item in serialized_items {
+            let entry_size = serialized_item.len() + BATCH_ENTRY_OVERHEAD;
+            if !batch.is_empty() && batch_size + entry_size > max_items_size {
+                responses.push(Message::GetBatchResponse {
+                    tag,
+                    serialized_items: std::mem::take(&mut batch),
+                    serialized_missing_ids: vec![],
+                });
+                batch_size = 0;
+            }
+            batch_size += entry_size;
+            batch.push(serialized_item);
+        }
+        responses.push(Message::GetBatchResponse {
+            tag,
+            serialized_items: batch,
+            serialized_missing_ids,
+        });
+        responses
+    }
+
     pub(crate) fn new_get_response_raw_unchecked<T: Item>(
         serialized_item: SharedObject<Vec<u8>>,
     ) -> Self {
@@ -118,6 +311,9 @@ impl Debug for Message {
             Message::Consensus(c) => f.debug_tuple("Consensus").field(&c).finish(),
             Message::DeployGossiper(dg) => f.debug_tuple("DeployGossiper").field(&dg).finish(),
             Message::AddressGossiper(ga) => f.debug_tuple("AddressGossiper").field(&ga).finish(),
+            Message::SignedAddressGossiper(ga) => {
+                f.debug_tuple("SignedAddressGossiper").field(&ga).finish()
+            }
             Message::GetRequest { tag, serialized_id } => f
                 .debug_struct("GetRequest")
                 .field("tag", tag)
@@ -134,6 +330,37 @@ impl Debug for Message {
             Message::FinalitySignature(fs) => {
                 f.debug_tuple("FinalitySignature").field(&fs).finish()
             }
+            Message::GetBatchRequest {
+                tag,
+                serialized_ids,
+            } => f
+                .debug_struct("GetBatchRequest")
+                .field("tag", tag)
+                .field("id_count", &serialized_ids.len())
+                .finish(),
+            Message::GetBatchResponse {
+                tag,
+                serialized_items,
+                serialized_missing_ids,
+            } => f
+                .debug_struct("GetBatchResponse")
+                .field("tag", tag)
+                .field("item_count", &serialized_items.len())
+                .field("missing_id_count", &serialized_missing_ids.len())
+                .finish(),
+            Message::TryLater {
+                tag,
+                serialized_ids,
+                retry_after,
+            } => f
+                .debug_struct("TryLater")
+                .field("tag", tag)
+                .field("id_count", &serialized_ids.len())
+                .field("retry_after", retry_after)
+                .finish(),
+            Message::DeployPropagation(dp) => {
+                f.debug_tuple("DeployPropagation").field(&dp).finish()
+            }
         }
     }
 }
@@ -146,6 +373,9 @@ impl Display for Message {
             Message::AddressGossiper(gossiped_address) => {
                 write!(f, "AddressGossiper::({})", gossiped_address)
             }
+            Message::SignedAddressGossiper(gossiped_address) => {
+                write!(f, "SignedAddressGossiper::({})", gossiped_address)
+            }
             Message::GetRequest { tag, serialized_id } => {
                 write!(f, "GetRequest({}-{:10})", tag, HexFmt(serialized_id))
             }
@@ -156,6 +386,33 @@ impl Display for Message {
             Message::FinalitySignature(fs) => {
                 write!(f, "FinalitySignature::({})", fs)
             }
+            Message::GetBatchRequest {
+                tag,
+                serialized_ids,
+            } => write!(f, "GetBatchRequest({}, {} ids)", tag, serialized_ids.len()),
+            Message::GetBatchResponse {
+                tag,
+                serialized_items,
+                serialized_missing_ids,
+            } => write!(
+                f,
+                "GetBatchResponse({}, {} items, {} missing)",
+                tag,
+                serialized_items.len(),
+                serialized_missing_ids.len()
+            ),
+            Message::TryLater {
+                tag,
+                serialized_ids,
+                retry_after,
+            } => write!(
+                f,
+                "TryLater({}, {} ids, after {})",
+                tag,
+                serialized_ids.len(),
+                retry_after
+            ),
+            Message::DeployPropagation(message) => write!(f, "DeployPropagation::{}", message),
         }
     }
 }
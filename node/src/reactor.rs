@@ -24,16 +24,21 @@
 //! in a step-wise manner using [`crank`](struct.Runner.html#method.crank) or indefinitely using
 //! [`run`](struct.Runner.html#method.crank).
 
+mod component_failure;
 mod event_queue_metrics;
 pub mod initializer;
 pub mod joiner;
 pub mod participating;
 mod queue_kind;
+#[cfg(test)]
+mod tests;
+mod timer;
 
 #[cfg(test)]
 use std::sync::Arc;
 use std::{
     any,
+    cell::RefCell,
     collections::HashMap,
     env,
     fmt::{Debug, Display},
@@ -44,22 +49,27 @@ use std::{
 };
 
 use datasize::DataSize;
+use enum_iterator::IntoEnumIterator;
 use futures::{future::BoxFuture, FutureExt};
 use jemalloc_ctl::{epoch as jemalloc_epoch, stats::allocated as jemalloc_allocated};
 use once_cell::sync::Lazy;
-use prometheus::{self, Histogram, HistogramOpts, IntCounter, IntGauge, Registry};
+use prometheus::{
+    self, Histogram, HistogramOpts, IntCounter, IntCounterVec, IntGauge, Opts, Registry,
+};
 use quanta::{Clock, IntoNanoseconds};
 use serde::Serialize;
 use signal_hook::consts::signal::{SIGINT, SIGQUIT, SIGTERM};
-use tokio::time::{Duration, Instant};
-use tracing::{debug, debug_span, error, info, instrument, trace, warn};
+use tokio::time::{self, Duration, Instant};
+use tracing::{debug, debug_span, error, info, instrument, trace, warn, Span};
 use tracing_futures::Instrument;
 
+use casper_types::ProtocolVersion;
+
 #[cfg(target_os = "linux")]
 use utils::rlimit::{Limit, OpenFiles, ResourceLimit};
 
 use crate::{
-    effect::{announcements::ControlAnnouncement, Effect, EffectBuilder, Effects},
+    effect::{announcements::ControlAnnouncement, Effect, EffectBuilder, EffectExt, Effects},
     types::{ExitCode, Timestamp},
     unregister_metric,
     utils::{self, WeightedRoundRobin},
@@ -67,7 +77,10 @@ use crate::{
 };
 #[cfg(test)]
 use crate::{reactor::initializer::Reactor as InitializerReactor, types::Chainspec};
-pub use queue_kind::QueueKind;
+pub use component_failure::{is_within_panic_boundary, FailurePolicies, FailurePolicy};
+pub use queue_kind::{EventQueueWeights, QueueKind};
+pub use timer::TimerHandle;
+use timer::TimerToken;
 
 /// Optional upper threshold for total RAM allocated in mB before dumping queues to disk.
 const MEM_DUMP_THRESHOLD_MB_ENV_VAR: &str = "CL_MEM_DUMP_THRESHOLD_MB";
@@ -103,6 +116,10 @@ static DISPATCH_EVENT_THRESHOLD: Lazy<Duration> = Lazy::new(|| {
         .unwrap_or_else(|_| DEFAULT_DISPATCH_EVENT_THRESHOLD)
 });
 
+/// The maximum time allowed for a reactor's shutdown sequence to complete before the process exits
+/// regardless.
+const SHUTDOWN_DEADLINE: Duration = Duration::from_secs(10);
+
 #[cfg(target_os = "linux")]
 /// The desired limit for open files.
 const TARGET_OPEN_FILES_LIMIT: Limit = 64_000;
@@ -161,13 +178,82 @@ pub enum ReactorExit {
     ProcessShouldExit(ExitCode),
 }
 
+/// Returns the reason for the node shutting down with `exit_code`, as reported to clients.
+///
+/// `maybe_next_version` is the protocol version of the upgrade the node is stopping for, if known.
+pub(crate) fn shutdown_reason(
+    exit_code: ExitCode,
+    maybe_next_version: Option<ProtocolVersion>,
+) -> String {
+    match (exit_code, maybe_next_version) {
+        (ExitCode::Success, Some(next_version)) => {
+            format!("upgrading to protocol version {}", next_version)
+        }
+        (ExitCode::Success, None) => "stopping for upgrade".to_string(),
+        (ExitCode::Abort, _) => "fatal error".to_string(),
+        (ExitCode::DowngradeVersion, _) => "downgrading to previous version".to_string(),
+        (ExitCode::SigInt, _) => "received SIGINT".to_string(),
+        (ExitCode::SigQuit, _) => "received SIGQUIT".to_string(),
+        (ExitCode::SigTerm, _) => "received SIGTERM".to_string(),
+    }
+}
+
 /// Event scheduler
 ///
 /// The scheduler is a combination of multiple event queues that are polled in a specific order. It
 /// is the central hook for any part of the program that schedules events directly.
 ///
 /// Components rarely use this, but use a bound `EventQueueHandle` instead.
-pub type Scheduler<Ev> = WeightedRoundRobin<Ev, QueueKind>;
+pub type Scheduler<Ev> = WeightedRoundRobin<QueuedEvent<Ev>, QueueKind>;
+
+tokio::task_local! {
+    /// The tracing span of the event which is currently being dispatched or whose effects are
+    /// currently being processed, if any.
+    ///
+    /// Set while dispatching an event and for every task spawned by `process_effects`, it allows
+    /// events scheduled through an `EventQueueHandle` to inherit the span of the event that caused
+    /// them.
+    static EVENT_SPAN: RefCell<Option<Span>>;
+}
+
+/// An event on the scheduler's queues, along with the tracing span it is dispatched in.
+#[derive(Debug, Serialize)]
+#[serde(transparent)]
+pub struct QueuedEvent<Ev> {
+    /// The event itself.
+    pub(crate) event: Ev,
+    /// The span the event was scheduled in, if any.
+    #[serde(skip)]
+    pub(crate) span: Option<Span>,
+    /// The token of the timer which scheduled the event, if any.
+    #[serde(skip)]
+    pub(crate) timer: Option<TimerToken>,
+}
+
+impl<Ev> QueuedEvent<Ev> {
+    /// Creates a new queued event.
+    pub(crate) fn new(event: Ev, span: Option<Span>) -> Self {
+        QueuedEvent {
+            event,
+            span,
+            timer: None,
+        }
+    }
+
+    /// Returns `true` if the event was scheduled by a timer which has since been cancelled, in
+    /// which case it must not be dispatched.
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.timer
+            .as_ref()
+            .map_or(false, |timer| timer.is_cancelled())
+    }
+
+    /// Returns the event, discarding its span.
+    #[cfg(test)]
+    pub(crate) fn into_inner(self) -> Ev {
+        self.event
+    }
+}
 
 /// Event queue handle
 ///
@@ -193,12 +279,79 @@ impl<REv> EventQueueHandle<REv> {
     }
 
     /// Schedule an event on a specific queue.
+    ///
+    /// If called from within an effect, the event inherits the tracing span of the event that
+    /// caused the effect.
     #[inline]
     pub(crate) async fn schedule<Ev>(self, event: Ev, queue_kind: QueueKind)
     where
         REv: From<Ev>,
     {
-        self.0.push(event.into(), queue_kind).await
+        self.schedule_with_span(event, queue_kind, current_event_span())
+            .await
+    }
+
+    /// Schedule an event on a specific queue, to be dispatched within the given tracing span.
+    #[inline]
+    pub(crate) async fn schedule_with_span<Ev>(
+        self,
+        event: Ev,
+        queue_kind: QueueKind,
+        span: Option<Span>,
+    ) where
+        REv: From<Ev>,
+    {
+        self.schedule_queued(QueuedEvent::new(event, span), queue_kind)
+            .await
+    }
+
+    /// Schedule an already queued event, e.g. one taken off another scheduler, keeping its tracing
+    /// span and timer.
+    #[inline]
+    pub(crate) async fn schedule_queued<Ev>(
+        self,
+        queued_event: QueuedEvent<Ev>,
+        queue_kind: QueueKind,
+    ) where
+        REv: From<Ev>,
+    {
+        let QueuedEvent { event, span, timer } = queued_event;
+        let queued_event = QueuedEvent {
+            event: event.into(),
+            span,
+            timer,
+        };
+        self.0.push(queued_event, queue_kind).await
+    }
+
+    /// Schedule an event once `delay` has elapsed, on the queue given by the event's
+    /// `ReactorEvent::queue_kind`.
+    ///
+    /// The event is only dispatched if the returned handle is still alive at that point: once the
+    /// handle has been dropped or cancelled, the event is discarded, even if it is already waiting
+    /// on the queue.
+    pub(crate) fn schedule_after<Ev>(self, delay: Duration, event: Ev) -> TimerHandle
+    where
+        REv: From<Ev> + ReactorEvent,
+        Ev: Send + 'static,
+    {
+        let (handle, token) = TimerHandle::new();
+        let span = current_event_span();
+        tokio::spawn(async move {
+            utils::sleep(delay).await;
+            if token.is_cancelled() {
+                return;
+            }
+            let event = REv::from(event);
+            let queue_kind = event.queue_kind();
+            let queued_event = QueuedEvent {
+                event,
+                span,
+                timer: Some(token),
+            };
+            self.0.push(queued_event, queue_kind).await
+        });
+        handle
     }
 
     /// Returns number of events in each of the scheduler's queues.
@@ -208,6 +361,15 @@ impl<REv> EventQueueHandle<REv> {
     }
 }
 
+/// Returns the tracing span of the event currently being dispatched or whose effects are currently
+/// being processed, if any.
+fn current_event_span() -> Option<Span> {
+    EVENT_SPAN
+        .try_with(|current| current.borrow().clone())
+        .ok()
+        .flatten()
+}
+
 /// Reactor core.
 ///
 /// Any reactor should implement this trait and be executed by the `reactor::run` function.
@@ -255,8 +417,40 @@ pub trait Reactor: Sized {
     /// dispatch events.  The running process may stop or may keep running with a new reactor.
     fn maybe_exit(&self) -> Option<ReactorExit>;
 
+    /// Runs the reactor's orderly shutdown sequence before the process exits with `exit_code`,
+    /// e.g. telling connected clients why the node is stopping and committing pending writes.
+    ///
+    /// The returned future completes once the asynchronous parts of the sequence have finished.
+    fn shutdown(&mut self, _exit_code: ExitCode) -> BoxFuture<'static, ()> {
+        async {}.boxed()
+    }
+
     /// Instructs the reactor to update performance metrics, if any.
     fn update_metrics(&mut self, _event_queue_handle: EventQueueHandle<Self::Event>) {}
+
+    /// Returns the event queue weights set in the given configuration.
+    fn event_queue_weights(_cfg: &Self::Config) -> EventQueueWeights {
+        EventQueueWeights::default()
+    }
+
+    /// Returns the failure policies of the components set in the given configuration.
+    fn failure_policies(_cfg: &Self::Config) -> FailurePolicies {
+        FailurePolicies::default()
+    }
+
+    /// Reinitializes the named component after its event handler panicked, returning the effects
+    /// of its reinitialization.
+    ///
+    /// Returns `None` if the component can't be reinitialized, in which case its failure is treated
+    /// as fatal.
+    fn restart_component(
+        &mut self,
+        _component: &str,
+        _effect_builder: EffectBuilder<Self::Event>,
+        _rng: &mut NodeRng,
+    ) -> Option<Effects<Self::Event>> {
+        None
+    }
 }
 
 /// A reactor event type.
@@ -267,6 +461,19 @@ pub trait ReactorEvent: Send + Debug + From<ControlAnnouncement> + 'static {
     /// [`ControlAnnouncement`](`crate::effect::announcements::ControlAnnouncement`) if the event
     /// is indeed a control announcement variant.
     fn as_control(&self) -> Option<&ControlAnnouncement>;
+
+    /// Returns the queue the event should be scheduled on when produced by an effect.
+    fn queue_kind(&self) -> QueueKind {
+        QueueKind::default()
+    }
+
+    /// Returns the name of the component the event is dispatched to, whose failure policy applies
+    /// if handling the event panics.
+    ///
+    /// Events not handled by a single component are attributed to the reactor itself.
+    fn component_name(&self) -> &'static str {
+        "reactor"
+    }
 }
 
 /// A drop-like trait for `async` compatible drop-and-wait.
@@ -327,6 +534,9 @@ where
 
     /// Last queue dump timestamp
     last_queue_dump: Option<Timestamp>,
+
+    /// Failure policies of the reactor's components.
+    failure_policies: FailurePolicies,
 }
 
 /// Metric data for the Runner
@@ -334,6 +544,8 @@ where
 struct RunnerMetrics {
     /// Total number of events processed.
     events: IntCounter,
+    /// Per queue kind counters of events taken off the queue.
+    dequeued_events: HashMap<QueueKind, IntCounter>,
     /// Histogram of how long it took to dispatch an event.
     event_dispatch_duration: Histogram,
     /// Total allocated RAM in bytes, as reported by jemalloc.
@@ -342,6 +554,8 @@ struct RunnerMetrics {
     consumed_ram_bytes: IntGauge,
     /// Total system RAM in bytes, as reported by sys-info.
     total_ram_bytes: IntGauge,
+    /// Per component counters of panics caught in event handlers.
+    component_failures: IntCounterVec,
     /// Handle to the metrics registry, in case we need to unregister.
     registry: Registry,
 }
@@ -351,6 +565,16 @@ impl RunnerMetrics {
     fn new(registry: &Registry) -> Result<Self, prometheus::Error> {
         let events = IntCounter::new("runner_events", "total event count")?;
 
+        let mut dequeued_events = HashMap::new();
+        for queue_kind in QueueKind::into_enum_iter() {
+            let counter = IntCounter::new(
+                format!("scheduler_queue_{}_dequeued", queue_kind.metrics_name()),
+                format!("total count of events taken off the {} queue", queue_kind),
+            )?;
+            registry.register(Box::new(counter.clone()))?;
+            dequeued_events.insert(queue_kind, counter);
+        }
+
         // Create an event dispatch histogram, putting extra emphasis on the area between 1-10 us.
         let event_dispatch_duration = Histogram::with_opts(
             HistogramOpts::new(
@@ -385,20 +609,30 @@ impl RunnerMetrics {
         let consumed_ram_bytes =
             IntGauge::new("consumed_ram_bytes", "total consumed ram in bytes")?;
         let total_ram_bytes = IntGauge::new("total_ram_bytes", "total system ram in bytes")?;
+        let component_failures = IntCounterVec::new(
+            Opts::new(
+                "component_failures",
+                "number of panics caught in the event handlers of components, by component",
+            ),
+            &["component"],
+        )?;
 
         registry.register(Box::new(events.clone()))?;
         registry.register(Box::new(event_dispatch_duration.clone()))?;
         registry.register(Box::new(allocated_ram_bytes.clone()))?;
         registry.register(Box::new(consumed_ram_bytes.clone()))?;
         registry.register(Box::new(total_ram_bytes.clone()))?;
+        registry.register(Box::new(component_failures.clone()))?;
 
         Ok(RunnerMetrics {
             events,
+            dequeued_events,
             event_dispatch_duration,
             registry: registry.clone(),
             allocated_ram_bytes,
             consumed_ram_bytes,
             total_ram_bytes,
+            component_failures,
         })
     }
 }
@@ -406,10 +640,14 @@ impl RunnerMetrics {
 impl Drop for RunnerMetrics {
     fn drop(&mut self) {
         unregister_metric!(self.registry, self.events);
+        for counter in self.dequeued_events.values() {
+            unregister_metric!(self.registry, counter);
+        }
         unregister_metric!(self.registry, self.event_dispatch_duration);
         unregister_metric!(self.registry, self.allocated_ram_bytes);
         unregister_metric!(self.registry, self.consumed_ram_bytes);
         unregister_metric!(self.registry, self.total_ram_bytes);
+        unregister_metric!(self.registry, self.component_failures);
     }
 }
 
@@ -451,13 +689,14 @@ where
             );
         }
 
-        let scheduler = utils::leak(Scheduler::new(QueueKind::weights()));
+        let scheduler = utils::leak(new_scheduler(&R::event_queue_weights(&cfg)));
+        let failure_policies = R::failure_policies(&cfg);
 
         let event_queue = EventQueueHandle::new(scheduler);
         let (reactor, initial_effects) = R::new(cfg, registry, event_queue, rng)?;
 
         // Run all effects from component instantiation.
-        process_effects(scheduler, initial_effects)
+        process_effects(scheduler, initial_effects, None)
             .instrument(debug_span!("process initial effects"))
             .await;
 
@@ -473,6 +712,7 @@ where
             event_metrics_threshold: 1000,
             clock: Clock::new(),
             last_queue_dump: None,
+            failure_policies,
         })
     }
 
@@ -488,7 +728,7 @@ where
 
         let effects = create_effects(effect_builder);
 
-        process_effects(self.scheduler, effects)
+        process_effects(self.scheduler, effects, None)
             .instrument(debug_span!(
                 "process injected effects",
                 ev = self.event_count
@@ -552,53 +792,110 @@ where
             QUEUE_DUMP_REQUESTED.store(false, Ordering::SeqCst);
         }
 
-        let (event, q) = self.scheduler.pop().await;
-
-        // Create another span for tracing the processing of one event.
-        let event_span = debug_span!("dispatch events", ev = self.event_count);
-        let (effects, keep_going) = event_span.in_scope(|| {
-            // We log events twice, once in display and once in debug mode.
-            let event_as_string = format!("{}", event);
-            debug!(event=%event_as_string, ?q);
-            trace!(?event, ?q);
-
-            // Dispatch the event, then execute the resulting effect.
-            let start = self.clock.start();
-
-            let (effects, keep_going) = if let Some(ctrl_ann) = event.as_control() {
-                // We've received a control event, which will _not_ be handled by the reactor.
-                match ctrl_ann {
-                    ControlAnnouncement::FatalError { file, line, msg } => {
-                        error!(%file, %line, %msg, "fatal error via control announcement");
-                        (Default::default(), false)
-                    }
-                }
-            } else {
-                (
-                    self.reactor.dispatch_event(effect_builder, rng, event),
-                    true,
-                )
-            };
+        let (queued_event, q) = self.scheduler.pop().await;
+        if let Some(counter) = self.metrics.dequeued_events.get(&q) {
+            counter.inc();
+        }
 
-            let end = self.clock.end();
+        // The timer which scheduled the event may have been cancelled after it fired.
+        if queued_event.is_cancelled() {
+            debug!(?q, "discarding event of cancelled timer");
+            return true;
+        }
+        let QueuedEvent { event, span, .. } = queued_event;
+
+        // If the event was scheduled within a span, dispatch it within that span as well. Another
+        // span is created inside it for tracing the processing of one event.
+        let parent_span = span.clone().unwrap_or_else(Span::none);
+        let event_span =
+            parent_span.in_scope(|| debug_span!("dispatch events", ev = self.event_count));
+        // Set if a component failed fatally, to preserve diagnostics before the reactor stops.
+        let mut dump_diagnostics = false;
+        // Events scheduled while dispatching, e.g. by timers, inherit the span like effects do.
+        let dispatch = async {
+            parent_span.in_scope(|| {
+                event_span.in_scope(|| {
+                    // We log events twice, once in display and once in debug mode.
+                    let event_as_string = format!("{}", event);
+                    debug!(event=%event_as_string, ?q);
+                    trace!(?event, ?q);
+
+                    // Dispatch the event, then execute the resulting effect.
+                    let start = self.clock.start();
+
+                    let (effects, keep_going) = if let Some(ctrl_ann) = event.as_control() {
+                        // We've received a control event, which will _not_ be handled by the
+                        // reactor.
+                        match ctrl_ann {
+                            ControlAnnouncement::FatalError { file, line, msg } => {
+                                error!(%file, %line, %msg, "fatal error via control announcement");
+                                (Default::default(), false)
+                            }
+                            ControlAnnouncement::ComponentFailed {
+                                component,
+                                panic_message,
+                            } => {
+                                let (effects, keep_going) = self.handle_component_failure(
+                                    effect_builder,
+                                    rng,
+                                    *component,
+                                    panic_message,
+                                );
+                                dump_diagnostics = !keep_going;
+                                (effects, keep_going)
+                            }
+                        }
+                    } else {
+                        // Catch a panic of the component handling the event, so that it is handled
+                        // according to the component's failure policy.
+                        let component = event.component_name();
+                        match component_failure::catch_panic(|| {
+                            self.reactor.dispatch_event(effect_builder, rng, event)
+                        }) {
+                            Ok(effects) => (effects, true),
+                            Err(panic_message) => {
+                                error!(
+                                    %component,
+                                    %panic_message,
+                                    event = %event_as_string,
+                                    "component panicked while handling event"
+                                );
+                                (
+                                    effect_builder
+                                        .announce_component_failed(component, panic_message)
+                                        .ignore(),
+                                    true,
+                                )
+                            }
+                        }
+                    };
 
-            // Warn if processing took a long time, record to histogram.
-            let delta = self.clock.delta(start, end);
-            if delta > *DISPATCH_EVENT_THRESHOLD {
-                warn!(
-                    ns = delta.into_nanos(),
-                    event = %event_as_string,
-                    "event took very long to dispatch"
-                );
-            }
-            self.metrics
-                .event_dispatch_duration
-                .observe(delta.into_nanos() as f64);
+                    let end = self.clock.end();
 
-            (effects, keep_going)
-        });
+                    // Warn if processing took a long time, record to histogram.
+                    let delta = self.clock.delta(start, end);
+                    if delta > *DISPATCH_EVENT_THRESHOLD {
+                        warn!(
+                            ns = delta.into_nanos(),
+                            event = %event_as_string,
+                            "event took very long to dispatch"
+                        );
+                    }
+                    self.metrics
+                        .event_dispatch_duration
+                        .observe(delta.into_nanos() as f64);
+
+                    (effects, keep_going)
+                })
+            })
+        };
+        let (effects, keep_going) = EVENT_SPAN.scope(RefCell::new(span.clone()), dispatch).await;
+
+        if dump_diagnostics {
+            self.dump_queues().await;
+        }
 
-        process_effects(self.scheduler, effects)
+        process_effects(self.scheduler, effects, span)
             .instrument(debug_span!("process effects", ev = self.event_count))
             .await;
 
@@ -607,6 +904,44 @@ where
         keep_going
     }
 
+    /// Handles the failure of a component according to its failure policy.
+    ///
+    /// Returns the effects of reinitializing the component if it was restarted, and `false` if the
+    /// reactor should stop.
+    fn handle_component_failure(
+        &mut self,
+        effect_builder: EffectBuilder<R::Event>,
+        rng: &mut NodeRng,
+        component: &'static str,
+        panic_message: &str,
+    ) -> (Effects<R::Event>, bool) {
+        self.metrics
+            .component_failures
+            .with_label_values(&[component])
+            .inc();
+        match self.failure_policies.policy(component) {
+            FailurePolicy::Fatal => {}
+            FailurePolicy::Restartable => {
+                match self
+                    .reactor
+                    .restart_component(component, effect_builder, rng)
+                {
+                    Some(effects) => {
+                        warn!(%component, %panic_message, "restarting failed component");
+                        return (effects, true);
+                    }
+                    None => error!(%component, "failed component does not support restarting"),
+                }
+            }
+            FailurePolicy::Ignorable => {
+                warn!(%component, %panic_message, "ignoring failure of component");
+                return (Effects::new(), true);
+            }
+        }
+        error!(%component, %panic_message, "stopping due to failure of component");
+        (Effects::new(), false)
+    }
+
     /// Gets both the allocated and total memory from sys-info + jemalloc
     fn get_allocated_memory() -> Option<AllocatedMem> {
         let mem_info = match sys_info::mem_info() {
@@ -687,6 +1022,12 @@ where
         }
     }
 
+    /// Returns the number of events waiting on the event queue.
+    #[cfg(test)]
+    pub(crate) fn queued_event_count(&self) -> usize {
+        self.scheduler.item_count()
+    }
+
     /// Processes a single event if there is one, returns `None` otherwise.
     #[inline]
     #[cfg(test)]
@@ -716,13 +1057,42 @@ where
                         // since that workaround of making two attempts with the first wrapped in a
                         // timeout should no longer be required.
 
-                        for event in self.scheduler.drain_queue(QueueKind::Control).await {
+                        for QueuedEvent { event, .. } in
+                            self.scheduler.drain_queue(QueueKind::Control).await
+                        {
                             if let Some(ctrl_ann) = event.as_control() {
                                 match ctrl_ann {
                                     ControlAnnouncement::FatalError { file, line, msg } => {
                                         warn!(%file, line=*line, %msg, "exiting due to fatal error scheduled before reactor completion");
                                         return ReactorExit::ProcessShouldExit(ExitCode::Abort);
                                     }
+                                    ControlAnnouncement::ComponentFailed {
+                                        component,
+                                        panic_message,
+                                    } => {
+                                        self.metrics
+                                            .component_failures
+                                            .with_label_values(&[*component])
+                                            .inc();
+                                        // The reactor is done, so there is nothing to restart.
+                                        if self.failure_policies.policy(component)
+                                            == FailurePolicy::Fatal
+                                        {
+                                            warn!(
+                                                %component,
+                                                %panic_message,
+                                                "exiting due to component failure scheduled \
+                                                before reactor completion"
+                                            );
+                                            return ReactorExit::ProcessShouldExit(ExitCode::Abort);
+                                        }
+                                        warn!(
+                                            %component,
+                                            %panic_message,
+                                            "ignoring component failure scheduled before reactor \
+                                            completion"
+                                        );
+                                    }
                                 }
                             } else {
                                 debug!(%event, "found non-control announcement while draining queue")
@@ -743,6 +1113,23 @@ where
         }
     }
 
+    /// Runs the reactor's shutdown sequence before the process exits with `exit_code`.
+    ///
+    /// Stops waiting for the sequence to complete after `SHUTDOWN_DEADLINE`, so that a wedged
+    /// component can't prevent the process from exiting.
+    pub async fn shutdown(&mut self, exit_code: ExitCode) {
+        info!(?exit_code, "running shutdown sequence");
+        if time::timeout(SHUTDOWN_DEADLINE, self.reactor.shutdown(exit_code))
+            .await
+            .is_err()
+        {
+            warn!(
+                deadline = ?SHUTDOWN_DEADLINE,
+                "shutdown sequence did not complete in time, exiting anyway"
+            );
+        }
+    }
+
     /// Returns a reference to the reactor.
     #[inline]
     pub fn reactor(&self) -> &R {
@@ -759,7 +1146,7 @@ where
     #[inline]
     pub async fn drain_into_inner(self) -> R {
         self.scheduler.seal();
-        for event in self.scheduler.drain_queues().await {
+        for QueuedEvent { event, .. } in self.scheduler.drain_queues().await {
             debug!(event=%event, "drained event");
         }
         self.reactor
@@ -773,7 +1160,10 @@ impl Runner<InitializerReactor> {
         chainspec: Arc<Chainspec>,
     ) -> Result<Self, <InitializerReactor as Reactor>::Error> {
         let registry = Registry::new();
-        let scheduler = utils::leak(Scheduler::new(QueueKind::weights()));
+        let scheduler = utils::leak(new_scheduler(&InitializerReactor::event_queue_weights(
+            &cfg,
+        )));
+        let failure_policies = InitializerReactor::failure_policies(&cfg);
 
         let event_queue = EventQueueHandle::new(scheduler);
         let (reactor, initial_effects) =
@@ -781,7 +1171,7 @@ impl Runner<InitializerReactor> {
 
         // Run all effects from component instantiation.
         let span = debug_span!("process initial effects");
-        process_effects(scheduler, initial_effects)
+        process_effects(scheduler, initial_effects, None)
             .instrument(span)
             .await;
 
@@ -801,28 +1191,68 @@ impl Runner<InitializerReactor> {
             event_metrics_threshold: 1000,
             clock: Clock::new(),
             last_queue_dump: None,
+            failure_policies,
         })
     }
 }
 
+/// Creates a new scheduler with the given event queue weights.
+///
+/// The control queue is given strict priority over all other queues.
+fn new_scheduler<I>(weights: &EventQueueWeights) -> WeightedRoundRobin<I, QueueKind> {
+    WeightedRoundRobin::new(weights.weights()).with_strict_priority(QueueKind::Control)
+}
+
 /// Spawns tasks that will process the given effects.
+///
+/// Each effect is run within the given span, if any. Events resulting from an effect are
+/// scheduled within the same span, unless the effect replaced it using [`in_span`].
 #[inline]
-async fn process_effects<Ev>(scheduler: &'static Scheduler<Ev>, effects: Effects<Ev>)
-where
-    Ev: Send + 'static,
+async fn process_effects<Ev>(
+    scheduler: &'static Scheduler<Ev>,
+    effects: Effects<Ev>,
+    span: Option<Span>,
+) where
+    Ev: ReactorEvent,
 {
-    // TODO: Properly carry around priorities.
-    let queue_kind = QueueKind::default();
-
     for effect in effects {
-        tokio::spawn(async move {
-            for event in effect.await {
-                scheduler.push(event, queue_kind).await
+        let span = span.clone();
+        let effect_span = span.clone().unwrap_or_else(Span::none);
+        tokio::spawn(EVENT_SPAN.scope(RefCell::new(span), async move {
+            let events = effect.instrument(effect_span).await;
+            let span = EVENT_SPAN.with(|current| current.borrow().clone());
+            for event in events {
+                let queue_kind = event.queue_kind();
+                scheduler
+                    .push(QueuedEvent::new(event, span.clone()), queue_kind)
+                    .await
             }
-        });
+        }));
     }
 }
 
+/// Runs the given effects within a new tracing span.
+///
+/// The span replaces the one the effects would otherwise inherit, and is passed on to all events
+/// resulting from these effects, as well as to the events those cause in turn.
+pub(crate) fn in_span<Ev>(effects: Effects<Ev>, span: Span) -> Effects<Ev>
+where
+    Ev: Send + 'static,
+{
+    effects
+        .into_iter()
+        .map(|effect| {
+            let span = span.clone();
+            (async move {
+                // Outside of `process_effects` (e.g. in unit tests) there is no span to replace.
+                let _ = EVENT_SPAN.try_with(|current| current.replace(Some(span.clone())));
+                effect.instrument(span).await
+            })
+            .boxed()
+        })
+        .collect()
+}
+
 /// Converts a single effect into another by wrapping it.
 #[inline]
 fn wrap_effect<Ev, REv, F>(wrap: F, effect: Effect<Ev>) -> Effect<REv>
@@ -0,0 +1,150 @@
+//! Failures of components.
+//!
+//! The runner dispatches every event within a panic boundary.  A panic unwinding out of a
+//! component's event handler is caught there and announced as a
+//! [`ControlAnnouncement::ComponentFailed`](crate::effect::announcements::ControlAnnouncement),
+//! which the runner handles according to the failure policy configured for the component:
+//!
+//! * `fatal`: the component may have been left in an inconsistent state, so the reactor stops and
+//!   the node runs its orderly shutdown sequence, after dumping its event queues for diagnostics.
+//! * `restartable`: the reactor reinitializes the component, if it supports doing so.  Otherwise
+//!   the failure is treated as fatal.
+//! * `ignorable`: the failure is counted and the reactor carries on.
+//!
+//! Every component is `fatal` unless configured otherwise.  Whatever the policy, the panic is
+//! logged, and counted in the `component_failures` metric.
+
+use std::{
+    any::Any,
+    cell::Cell,
+    collections::BTreeMap,
+    fmt::{self, Display, Formatter},
+    panic::{self, AssertUnwindSafe},
+};
+
+use datasize::DataSize;
+use serde::{Deserialize, Serialize};
+
+thread_local! {
+    /// Whether the current thread is running code within a panic boundary.
+    static WITHIN_PANIC_BOUNDARY: Cell<bool> = Cell::new(false);
+}
+
+/// The handling of a component whose event handler panicked.
+///
+/// Defaults to "fatal".
+#[derive(Clone, Copy, DataSize, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FailurePolicy {
+    /// Stop the reactor and shut the node down.
+    Fatal,
+    /// Reinitialize the component if it supports it, otherwise stop as for `Fatal`.
+    Restartable,
+    /// Count the failure and continue.
+    Ignorable,
+}
+
+impl Default for FailurePolicy {
+    fn default() -> Self {
+        FailurePolicy::Fatal
+    }
+}
+
+impl Display for FailurePolicy {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            FailurePolicy::Fatal => write!(formatter, "fatal"),
+            FailurePolicy::Restartable => write!(formatter, "restartable"),
+            FailurePolicy::Ignorable => write!(formatter, "ignorable"),
+        }
+    }
+}
+
+/// Configured failure policies of the reactor's components, keyed by component name.
+///
+/// Components without an entry are `fatal`.
+#[derive(Clone, DataSize, Debug, Default, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct FailurePolicies(BTreeMap<String, FailurePolicy>);
+
+impl FailurePolicies {
+    /// Returns the failure policy of the named component.
+    pub fn policy(&self, component: &str) -> FailurePolicy {
+        self.0.get(component).copied().unwrap_or_default()
+    }
+
+    /// Sets the failure policy of the named component.
+    #[cfg(test)]
+    pub(crate) fn with_policy(mut self, component: &str, policy: FailurePolicy) -> Self {
+        let _ = self.0.insert(component.to_string(), policy);
+        self
+    }
+}
+
+/// Returns `true` if a panic on the current thread is going to be caught by a panic boundary.
+///
+/// Panic hooks which abort the process must let such panics unwind instead.
+pub fn is_within_panic_boundary() -> bool {
+    WITHIN_PANIC_BOUNDARY.with(Cell::get)
+}
+
+/// Calls `f` within a panic boundary, returning the message of the panic if it panicked.
+///
+/// `f` is asserted to be unwind safe: state it may have left inconsistent is only used again if
+/// the failure policy of the panicking component allows it.
+pub(crate) fn catch_panic<T>(f: impl FnOnce() -> T) -> Result<T, String> {
+    let was_within_boundary = WITHIN_PANIC_BOUNDARY.with(|within| within.replace(true));
+    let result = panic::catch_unwind(AssertUnwindSafe(f));
+    WITHIN_PANIC_BOUNDARY.with(|within| within.set(was_within_boundary));
+    result.map_err(panic_message)
+}
+
+/// Returns the message a panic was raised with.
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "<non-string panic payload>".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_default_to_fatal() {
+        let policies: FailurePolicies =
+            toml::from_str("deploy_gossiper = 'ignorable'\nperformance_tracker = 'restartable'")
+                .unwrap();
+        assert_eq!(policies.policy("deploy_gossiper"), FailurePolicy::Ignorable);
+        assert_eq!(
+            policies.policy("performance_tracker"),
+            FailurePolicy::Restartable
+        );
+        assert_eq!(policies.policy("storage"), FailurePolicy::Fatal);
+    }
+
+    #[test]
+    fn should_catch_panic_message() {
+        assert_eq!(catch_panic(|| 7), Ok(7));
+        let result: Result<(), String> = catch_panic(|| panic!("static message"));
+        assert_eq!(result, Err("static message".to_string()));
+        let result: Result<(), String> = catch_panic(|| panic!("formatted {}", "message"));
+        assert_eq!(result, Err("formatted message".to_string()));
+        assert!(!is_within_panic_boundary());
+    }
+
+    #[test]
+    fn should_mark_panic_boundary() {
+        assert!(!is_within_panic_boundary());
+        assert_eq!(catch_panic(is_within_panic_boundary), Ok(true));
+        assert_eq!(
+            catch_panic(|| catch_panic(|| ()).map(|_| is_within_panic_boundary())),
+            Ok(Ok(true))
+        );
+        assert!(!is_within_panic_boundary());
+    }
+}
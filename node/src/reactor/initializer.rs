@@ -4,6 +4,7 @@ use std::fmt::{self, Display, Formatter};
 
 use datasize::DataSize;
 use derive_more::From;
+use futures::future::BoxFuture;
 use prometheus::Registry;
 use reactor::ReactorEvent;
 use serde::Serialize;
@@ -31,8 +32,11 @@ use crate::{
         EffectBuilder, Effects,
     },
     protocol::Message,
-    reactor::{self, participating, EventQueueHandle, ReactorExit},
-    types::{chainspec, NodeId},
+    reactor::{
+        self, participating, EventQueueHandle, EventQueueWeights, FailurePolicies, QueueKind,
+        ReactorExit,
+    },
+    types::{chainspec, ExitCode, NodeId},
     utils::WithDir,
     NodeRng,
 };
@@ -71,6 +75,13 @@ impl ReactorEvent for Event {
             None
         }
     }
+
+    fn queue_kind(&self) -> QueueKind {
+        match self {
+            Event::ControlAnnouncement(_) => QueueKind::Control,
+            _ => QueueKind::Regular,
+        }
+    }
 }
 
 impl From<StorageRequest> for Event {
@@ -196,6 +207,7 @@ impl Reactor {
             hard_reset_to_start_of_era,
             chainspec_loader.chainspec().protocol_config.version,
             crashed,
+            registry,
         )?;
 
         let contract_runtime = ContractRuntime::new(
@@ -216,15 +228,14 @@ impl Reactor {
         // on restarts (online checks are an alternative).
         if crashed {
             info!("running trie-store integrity check, this may take a while");
-            if let Some(state_roots) = storage.get_state_root_hashes_for_trie_check() {
-                let missing_trie_keys = contract_runtime.trie_store_check(state_roots.clone());
-                if !missing_trie_keys.is_empty() {
-                    panic!(
-                        "Fatal error! Trie-Key store is not empty.\n {:?}\n \
-                        Wipe the DB to ensure operations.\n Present state_roots: {:?}",
-                        missing_trie_keys, state_roots
-                    )
-                }
+            let state_roots = storage.get_state_root_hashes_for_trie_check()?;
+            let missing_trie_keys = contract_runtime.trie_store_check(state_roots.clone());
+            if !missing_trie_keys.is_empty() {
+                panic!(
+                    "Fatal error! Trie-Key store is not empty.\n {:?}\n \
+                    Wipe the DB to ensure operations.\n Present state_roots: {:?}",
+                    missing_trie_keys, state_roots
+                )
             }
         }
 
@@ -304,16 +315,28 @@ impl reactor::Reactor for Reactor {
     fn maybe_exit(&self) -> Option<ReactorExit> {
         self.chainspec_loader.reactor_exit()
     }
+
+    fn shutdown(&mut self, _exit_code: ExitCode) -> BoxFuture<'static, ()> {
+        self.storage.flush()
+    }
+
+    fn event_queue_weights((_, config): &Self::Config) -> EventQueueWeights {
+        config.value().node.event_queue_weights
+    }
+
+    fn failure_policies((_, config): &Self::Config) -> FailurePolicies {
+        config.value().node.component_failure_policies.clone()
+    }
 }
 
 #[cfg(test)]
 pub mod test {
     use super::*;
     use crate::{
-        components::network::ENABLE_LIBP2P_NET_ENV_VAR, testing::network::NetworkedReactor,
+        components::network_provider::NetworkProviderKind, testing::network::NetworkedReactor,
         types::Chainspec,
     };
-    use std::{env, sync::Arc};
+    use std::sync::Arc;
 
     impl Reactor {
         pub(crate) fn new_with_chainspec(
@@ -332,10 +355,9 @@ pub mod test {
     impl NetworkedReactor for Reactor {
         type NodeId = NodeId;
         fn node_id(&self) -> Self::NodeId {
-            if env::var(ENABLE_LIBP2P_NET_ENV_VAR).is_err() {
-                NodeId::from(&self.small_network_identity)
-            } else {
-                NodeId::from(&self.network_identity)
+            match self.config.value().network.network_provider {
+                NetworkProviderKind::Small => NodeId::from(&self.small_network_identity),
+                NetworkProviderKind::Libp2p => NodeId::from(&self.network_identity),
             }
         }
     }
@@ -4,19 +4,20 @@ mod memory_metrics;
 
 use std::{
     collections::BTreeMap,
-    env,
     fmt::{self, Display, Formatter},
     path::PathBuf,
     sync::Arc,
+    time::Duration,
 };
 
 use datasize::DataSize;
 use derive_more::From;
+use futures::{future::BoxFuture, join, FutureExt};
 use memory_metrics::MemoryMetrics;
 use prometheus::Registry;
 use reactor::ReactorEvent;
 use serde::Serialize;
-use tracing::{debug, error, info, warn};
+use tracing::{debug, error, info, trace, warn};
 
 #[cfg(test)]
 use crate::testing::network::NetworkedReactor;
@@ -27,28 +28,30 @@ use crate::{
         contract_runtime::{self, ContractRuntime},
         deploy_acceptor::{self, DeployAcceptor},
         event_stream_server,
-        event_stream_server::{DeployGetter, EventStreamServer},
-        fetcher::{self, Fetcher},
+        event_stream_server::{DeployGetter, DeployHeaderSummary, EventStreamServer},
+        fetcher::{self, FetchScheduler, Fetcher},
         gossiper::{self, Gossiper},
         linear_chain,
         linear_chain_sync::{self, LinearChainSync},
         metrics::Metrics,
-        network::{self, Network, NetworkIdentity, ENABLE_LIBP2P_NET_ENV_VAR},
+        network::{self, NetworkIdentity},
+        network_provider::NetworkComponent,
         rest_server::{self, RestServer},
-        small_network::{self, GossipedAddress, SmallNetwork, SmallNetworkIdentity},
+        small_network::{self, GossipedAddress, SmallNetworkIdentity},
         storage::{self, Storage},
         Component,
     },
     effect::{
         announcements::{
-            ChainspecLoaderAnnouncement, ContractRuntimeAnnouncement, ControlAnnouncement,
-            DeployAcceptorAnnouncement, GossiperAnnouncement, LinearChainAnnouncement,
-            LinearChainBlock, NetworkAnnouncement,
+            BlocklistAnnouncement, ChainspecLoaderAnnouncement, ContractRuntimeAnnouncement,
+            ControlAnnouncement, DeployAcceptorAnnouncement, GossiperAnnouncement,
+            LinearChainAnnouncement, LinearChainBlock, NetworkAnnouncement,
         },
         requests::{
             BlockProposerRequest, BlockValidationRequest, ChainspecLoaderRequest, ConsensusRequest,
-            ContractRuntimeRequest, FetcherRequest, LinearChainRequest, MetricsRequest,
-            NetworkInfoRequest, NetworkRequest, RestRequest, StateStoreRequest, StorageRequest,
+            ContractRuntimeRequest, DeployPropagationRequest, FetcherRequest, LinearChainRequest,
+            MetricsRequest, NetworkInfoRequest, NetworkRequest, RestRequest, StateStoreRequest,
+            StorageRequest,
         },
         EffectBuilder, EffectExt, Effects,
     },
@@ -58,11 +61,11 @@ use crate::{
         event_queue_metrics::EventQueueMetrics,
         initializer,
         participating::{self, Error, ParticipatingInitConfig},
-        EventQueueHandle, Finalize, ReactorExit,
+        EventQueueHandle, EventQueueWeights, FailurePolicies, Finalize, QueueKind, ReactorExit,
     },
     types::{
-        Block, BlockByHeight, BlockHeader, BlockHeaderWithMetadata, Deploy, ExitCode, NodeId, Tag,
-        Timestamp,
+        chainspec::DeployConfig, Block, BlockByHeight, BlockHeader, BlockHeaderWithMetadata,
+        Deploy, DeployHash, DeployHeaderWithHash, ExitCode, Item, NodeId, Tag, Timestamp,
     },
     utils::{Source, WithDir},
     NodeRng,
@@ -106,6 +109,10 @@ pub enum Event {
     #[from]
     ChainspecLoaderRequest(#[serde(skip_serializing)] ChainspecLoaderRequest),
 
+    /// Network request.
+    #[from]
+    NetworkRequest(#[serde(skip_serializing)] NetworkRequest<NodeId, Message>),
+
     /// Network info request.
     #[from]
     NetworkInfoRequest(#[serde(skip_serializing)] NetworkInfoRequest<NodeId>),
@@ -122,6 +129,10 @@ pub enum Event {
     #[from]
     DeployFetcher(#[serde(skip_serializing)] fetcher::Event<Deploy>),
 
+    /// Deploy header fetcher event.
+    #[from]
+    DeployHeaderFetcher(#[serde(skip_serializing)] fetcher::Event<DeployHeaderWithHash>),
+
     /// Deploy acceptor event.
     #[from]
     DeployAcceptor(#[serde(skip_serializing)] deploy_acceptor::Event),
@@ -159,6 +170,12 @@ pub enum Event {
     #[from]
     DeployFetcherRequest(#[serde(skip_serializing)] FetcherRequest<NodeId, Deploy>),
 
+    /// Deploy header fetcher request.
+    #[from]
+    DeployHeaderFetcherRequest(
+        #[serde(skip_serializing)] FetcherRequest<NodeId, DeployHeaderWithHash>,
+    ),
+
     /// Block validation request.
     #[from]
     BlockValidatorRequest(#[serde(skip_serializing)] BlockValidationRequest<NodeId>),
@@ -200,9 +217,17 @@ pub enum Event {
     #[from]
     ChainspecLoaderAnnouncement(#[serde(skip_serializing)] ChainspecLoaderAnnouncement),
 
+    /// Blocklist announcement.
+    #[from]
+    BlocklistAnnouncement(BlocklistAnnouncement<NodeId>),
+
     /// Consensus request.
     #[from]
     ConsensusRequest(#[serde(skip_serializing)] ConsensusRequest),
+
+    /// Deploy propagation request.
+    #[from]
+    DeployPropagationRequest(#[serde(skip_serializing)] DeployPropagationRequest),
 }
 
 impl ReactorEvent for Event {
@@ -213,6 +238,14 @@ impl ReactorEvent for Event {
             None
         }
     }
+
+    fn queue_kind(&self) -> QueueKind {
+        match self {
+            Event::ControlAnnouncement(_) => QueueKind::Control,
+            Event::AddressGossiper(_) | Event::AddressGossiperAnnouncement(_) => QueueKind::Gossip,
+            _ => QueueKind::Regular,
+        }
+    }
 }
 
 impl From<LinearChainRequest<NodeId>> for Event {
@@ -227,21 +260,9 @@ impl From<StorageRequest> for Event {
     }
 }
 
-impl From<NetworkRequest<NodeId, Message>> for Event {
-    fn from(request: NetworkRequest<NodeId, Message>) -> Self {
-        if env::var(ENABLE_LIBP2P_NET_ENV_VAR).is_ok() {
-            Event::Network(network::Event::from(request))
-        } else {
-            Event::SmallNetwork(small_network::Event::from(request))
-        }
-    }
-}
-
 impl From<NetworkRequest<NodeId, gossiper::Message<GossipedAddress>>> for Event {
     fn from(request: NetworkRequest<NodeId, gossiper::Message<GossipedAddress>>) -> Self {
-        Event::SmallNetwork(small_network::Event::from(
-            request.map_payload(Message::from),
-        ))
+        Event::NetworkRequest(request.map_payload(Message::from))
     }
 }
 
@@ -269,6 +290,7 @@ impl Display for Event {
             Event::MetricsRequest(req) => write!(f, "metrics request: {}", req),
             Event::ChainspecLoader(event) => write!(f, "chainspec loader: {}", event),
             Event::ChainspecLoaderRequest(req) => write!(f, "chainspec loader request: {}", req),
+            Event::NetworkRequest(req) => write!(f, "network request: {}", req),
             Event::NetworkInfoRequest(req) => write!(f, "network info request: {}", req),
             Event::BlockFetcherRequest(request) => write!(f, "block fetcher request: {}", request),
             Event::BlockValidatorRequest(request) => {
@@ -277,6 +299,9 @@ impl Display for Event {
             Event::DeployFetcherRequest(request) => {
                 write!(f, "deploy fetcher request: {}", request)
             }
+            Event::DeployHeaderFetcherRequest(request) => {
+                write!(f, "deploy header fetcher request: {}", request)
+            }
             Event::LinearChainSync(event) => write!(f, "linear chain: {}", event),
             Event::BlockFetcher(event) => write!(f, "block fetcher: {}", event),
             Event::BlockByHeightFetcherRequest(request) => {
@@ -284,6 +309,9 @@ impl Display for Event {
             }
             Event::BlockValidator(event) => write!(f, "block validator event: {}", event),
             Event::DeployFetcher(event) => write!(f, "deploy fetcher event: {}", event),
+            Event::DeployHeaderFetcher(event) => {
+                write!(f, "deploy header fetcher event: {}", event)
+            }
             Event::BlockProposerRequest(req) => write!(f, "block proposer request: {}", req),
             Event::ContractRuntime(event) => write!(f, "contract runtime event: {:?}", event),
             Event::LinearChain(event) => write!(f, "linear chain event: {}", event),
@@ -306,8 +334,14 @@ impl Display for Event {
             Event::ChainspecLoaderAnnouncement(ann) => {
                 write!(f, "chainspec loader announcement: {}", ann)
             }
+            Event::BlocklistAnnouncement(ann) => {
+                write!(f, "blocklist announcement: {}", ann)
+            }
             Event::StateStoreRequest(req) => write!(f, "state store request: {}", req),
             Event::ConsensusRequest(req) => write!(f, "consensus request: {:?}", req),
+            Event::DeployPropagationRequest(req) => {
+                write!(f, "deploy propagation request: {}", req)
+            }
         }
     }
 }
@@ -317,8 +351,10 @@ impl Display for Event {
 pub struct Reactor {
     root: PathBuf,
     metrics: Metrics,
-    network: Network<Event, Message>,
-    small_network: SmallNetwork<Event, Message>,
+    network: NetworkComponent<Event, Message>,
+    small_network_identity: SmallNetworkIdentity,
+    #[data_size(skip)]
+    network_identity: NetworkIdentity,
     address_gossiper: Gossiper<GossipedAddress, Event>,
     config: participating::Config,
     chainspec_loader: ChainspecLoader,
@@ -328,6 +364,7 @@ pub struct Reactor {
     linear_chain_sync: LinearChainSync<NodeId>,
     block_validator: BlockValidator<NodeId>,
     deploy_fetcher: Fetcher<Deploy>,
+    deploy_header_fetcher: Fetcher<DeployHeaderWithHash>,
     linear_chain: linear_chain::LinearChainComponent<NodeId>,
     // Handles request for linear chain block by height.
     block_by_height_fetcher: Fetcher<BlockByHeight>,
@@ -380,31 +417,22 @@ impl reactor::Reactor for Reactor {
 
         let metrics = Metrics::new(registry.clone());
 
-        let network_config = network::Config::from(&config.network);
-        let (network, network_effects) = Network::new(
-            event_queue,
-            network_config,
-            registry,
-            network_identity,
-            chainspec_loader.chainspec(),
-        )?;
-        let (small_network, small_network_effects) = SmallNetwork::new(
+        let (network, mut effects) = NetworkComponent::new(
             event_queue,
             config.network.clone(),
             Some(WithDir::new(&root, &config.consensus)),
+            storage.root_path(),
             registry,
-            small_network_identity,
-            chainspec_loader.chainspec().as_ref(),
+            small_network_identity.clone(),
+            network_identity.clone(),
+            Arc::clone(chainspec_loader.chainspec()),
             None,
         )?;
 
-        let linear_chain_fetcher = Fetcher::new("linear_chain", config.fetcher, registry)?;
-
-        let mut effects = reactor::wrap_effects(Event::Network, network_effects);
-        effects.extend(reactor::wrap_effects(
-            Event::SmallNetwork,
-            small_network_effects,
-        ));
+        // All the fetchers share a single scheduler, bounding the number of fetches in flight.
+        let fetch_scheduler = FetchScheduler::new(&config.fetcher, registry)?;
+        let linear_chain_fetcher =
+            Fetcher::new("linear_chain", config.fetcher, &fetch_scheduler, registry)?;
 
         let address_gossiper =
             Gossiper::new_for_complete_items("address_gossiper", config.gossip, registry)?;
@@ -442,38 +470,68 @@ impl reactor::Reactor for Reactor {
         }
 
         let protocol_version = &chainspec_loader.chainspec().protocol_config.version;
-        let rest_server = RestServer::new(
+        let (rest_server, rest_server_effects) = RestServer::new(
             config.rest_server.clone(),
             effect_builder,
             *protocol_version,
+            true,
+            None,
+            config.overrides(),
         )?;
+        effects.extend(reactor::wrap_effects(
+            Event::RestServer,
+            rest_server_effects,
+        ));
 
         let event_stream_server = EventStreamServer::new(
             config.event_stream_server.clone(),
             storage.root_path().to_path_buf(),
             *protocol_version,
+            chainspec_loader.chainspec().deploy_config.max_deploy_size,
             DeployGetter::new(effect_builder),
+            registry,
         )?;
 
         let block_validator = BlockValidator::new(Arc::clone(chainspec_loader.chainspec()));
 
-        let deploy_fetcher = Fetcher::new("deploy", config.fetcher, registry)?;
+        let deploy_fetcher = Fetcher::new("deploy", config.fetcher, &fetch_scheduler, registry)?;
 
-        let block_by_height_fetcher = Fetcher::new("block_by_height", config.fetcher, registry)?;
+        let deploy_header_fetcher =
+            Fetcher::new("deploy_header", config.fetcher, &fetch_scheduler, registry)?;
+
+        let block_by_height_fetcher = Fetcher::new(
+            "block_by_height",
+            config.fetcher,
+            &fetch_scheduler,
+            registry,
+        )?;
 
         let block_header_and_finality_signatures_by_height_fetcher: Fetcher<
             BlockHeaderWithMetadata,
         > = Fetcher::new(
             "block_header_and_finality_signatures_by_height",
             config.fetcher,
+            &fetch_scheduler,
             registry,
         )?;
 
-        let block_header_by_hash_fetcher: Fetcher<BlockHeader> =
-            Fetcher::new("block_header_by_hash", config.fetcher, registry)?;
+        let block_header_by_hash_fetcher: Fetcher<BlockHeader> = Fetcher::new(
+            "block_header_by_hash",
+            config.fetcher,
+            &fetch_scheduler,
+            registry,
+        )?;
 
-        let deploy_acceptor =
-            DeployAcceptor::new(config.deploy_acceptor, &*chainspec_loader.chainspec());
+        let maybe_next_activation_point = chainspec_loader
+            .next_upgrade()
+            .map(|next_upgrade| next_upgrade.activation_point());
+        let deploy_acceptor = DeployAcceptor::new(
+            WithDir::new(&root, &config.deploy_acceptor),
+            &*chainspec_loader.chainspec(),
+            maybe_next_activation_point,
+            None,
+            registry,
+        )?;
 
         contract_runtime.set_initial_state(
             chainspec_loader.initial_state_root_hash(),
@@ -494,9 +552,6 @@ impl reactor::Reactor for Reactor {
             .into_iter()
             .map(|(pk, motes)| (pk, motes.value()))
             .collect();
-        let maybe_next_activation_point = chainspec_loader
-            .next_upgrade()
-            .map(|next_upgrade| next_upgrade.activation_point());
         let (linear_chain_sync, init_sync_effects) = LinearChainSync::new::<Event, Error>(
             registry,
             effect_builder,
@@ -522,7 +577,8 @@ impl reactor::Reactor for Reactor {
                 root,
                 metrics,
                 network,
-                small_network,
+                small_network_identity,
+                network_identity,
                 address_gossiper,
                 config,
                 chainspec_loader,
@@ -532,6 +588,7 @@ impl reactor::Reactor for Reactor {
                 linear_chain_fetcher,
                 block_validator,
                 deploy_fetcher,
+                deploy_header_fetcher,
                 linear_chain,
                 block_by_height_fetcher,
                 block_header_by_hash_fetcher,
@@ -554,14 +611,13 @@ impl reactor::Reactor for Reactor {
         event: Self::Event,
     ) -> Effects<Self::Event> {
         match event {
-            Event::Network(event) => reactor::wrap_effects(
-                Event::Network,
-                self.network.handle_event(effect_builder, rng, event),
-            ),
-            Event::SmallNetwork(event) => reactor::wrap_effects(
-                Event::SmallNetwork,
-                self.small_network.handle_event(effect_builder, rng, event),
-            ),
+            Event::Network(event) => self
+                .network
+                .handle_network_event(effect_builder, rng, event),
+            Event::SmallNetwork(event) => {
+                self.network
+                    .handle_small_network_event(effect_builder, rng, event)
+            }
             Event::ControlAnnouncement(ctrl_ann) => {
                 unreachable!("unhandled control announcement: {}", ctrl_ann)
             }
@@ -573,6 +629,16 @@ impl reactor::Reactor for Reactor {
                     linear_chain_sync::Event::NewPeerConnected(id),
                 ),
             ),
+            Event::NetworkAnnouncement(NetworkAnnouncement::PeerCountChanged(peer_count)) => {
+                let reactor_event =
+                    Event::RestServer(rest_server::Event::PeerCountChanged(peer_count));
+                self.dispatch_event(effect_builder, rng, reactor_event)
+            }
+            Event::NetworkAnnouncement(NetworkAnnouncement::PeerConnected { .. })
+            | Event::NetworkAnnouncement(NetworkAnnouncement::PeerDisconnected { .. }) => {
+                trace!("connection lifecycle announcement not handled in the joiner reactor");
+                Effects::new()
+            }
             Event::NetworkAnnouncement(NetworkAnnouncement::GossipOurAddress(gossiped_address)) => {
                 let event = gossiper::Event::ItemReceived {
                     item_id: gossiped_address,
@@ -580,6 +646,50 @@ impl reactor::Reactor for Reactor {
                 };
                 self.dispatch_event(effect_builder, rng, Event::AddressGossiper(event))
             }
+            Event::NetworkAnnouncement(NetworkAnnouncement::MessageSendFailed {
+                destination,
+                payload,
+                error_kind,
+            }) => {
+                debug!(%destination, %payload, %error_kind, "failed to send message");
+                // Let the deploy fetcher decide whether to resend its requests or give up on the
+                // peer.
+                let serialized_ids = match payload {
+                    Message::GetRequest {
+                        tag: Tag::Deploy,
+                        serialized_id,
+                    } => vec![serialized_id],
+                    Message::GetBatchRequest {
+                        tag: Tag::Deploy,
+                        serialized_ids,
+                    } => serialized_ids,
+                    _ => return Effects::new(),
+                };
+                let mut effects = Effects::new();
+                for serialized_id in serialized_ids {
+                    let id = match bincode::deserialize(&serialized_id) {
+                        Ok(deploy_hash) => deploy_hash,
+                        Err(error) => {
+                            error!(
+                                "failed to decode {:?} sent to {}: {}",
+                                serialized_id, destination, error
+                            );
+                            continue;
+                        }
+                    };
+                    let event = fetcher::Event::SendFailed {
+                        id,
+                        peer: destination,
+                        error_kind,
+                    };
+                    effects.extend(self.dispatch_event(
+                        effect_builder,
+                        rng,
+                        Event::DeployFetcher(event),
+                    ));
+                }
+                effects
+            }
             Event::NetworkAnnouncement(NetworkAnnouncement::MessageReceived {
                 sender,
                 payload,
@@ -630,21 +740,117 @@ impl reactor::Reactor for Reactor {
                     tag: Tag::Deploy,
                     serialized_item,
                 } => {
-                    let deploy = match bincode::deserialize(&serialized_item) {
-                        Ok(deploy) => Box::new(deploy),
-                        Err(err) => {
-                            error!("failed to decode deploy from {}: {}", sender, err);
-                            return Effects::new();
-                        }
-                    };
-                    let event = Event::DeployAcceptor(deploy_acceptor::Event::Accept {
-                        deploy,
-                        source: Source::Peer(sender),
-                        responder: None,
+                    let event = Event::DeployAcceptor(deploy_acceptor::Event::ReceivedFromPeer {
+                        serialized_deploy: serialized_item,
+                        sender,
                     });
                     self.dispatch_event(effect_builder, rng, event)
                 }
-                Message::AddressGossiper(message) => {
+                Message::GetResponse {
+                    tag: Tag::DeployHeaderWithHash,
+                    serialized_item,
+                } => {
+                    let deploy_header: DeployHeaderWithHash =
+                        match bincode::deserialize(&serialized_item) {
+                            Ok(deploy_header) => deploy_header,
+                            Err(err) => {
+                                error!("failed to decode deploy header from {}: {}", sender, err);
+                                return Effects::new();
+                            }
+                        };
+                    let event = deploy_header_received(
+                        &self.chainspec_loader.chainspec().deploy_config,
+                        deploy_header,
+                        sender,
+                    );
+                    self.dispatch_event(effect_builder, rng, Event::DeployHeaderFetcher(event))
+                }
+                Message::GetBatchResponse {
+                    tag,
+                    serialized_items,
+                    serialized_missing_ids,
+                } => {
+                    // Each item is handled as though it arrived in its own get response, so is
+                    // verified individually.
+                    let mut effects = Effects::new();
+                    for serialized_item in serialized_items {
+                        let payload = Message::GetResponse {
+                            tag,
+                            serialized_item,
+                        };
+                        let event =
+                            Event::NetworkAnnouncement(NetworkAnnouncement::MessageReceived {
+                                sender,
+                                payload,
+                            });
+                        effects.extend(self.dispatch_event(effect_builder, rng, event));
+                    }
+                    for serialized_id in serialized_missing_ids {
+                        let id: DeployHash = match bincode::deserialize(&serialized_id) {
+                            Ok(deploy_hash) => deploy_hash,
+                            Err(err) => {
+                                error!("failed to decode {} id from {}: {}", tag, sender, err);
+                                continue;
+                            }
+                        };
+                        let peer = sender;
+                        let event = match tag {
+                            Tag::Deploy => {
+                                Event::DeployFetcher(fetcher::Event::AbsentRemotely { id, peer })
+                            }
+                            Tag::DeployHeaderWithHash => {
+                                Event::DeployHeaderFetcher(fetcher::Event::AbsentRemotely {
+                                    id,
+                                    peer,
+                                })
+                            }
+                            _ => {
+                                debug!(%tag, %sender, "missing ids ignored in joiner reactor");
+                                break;
+                            }
+                        };
+                        effects.extend(self.dispatch_event(effect_builder, rng, event));
+                    }
+                    effects
+                }
+                Message::TryLater {
+                    tag,
+                    serialized_ids,
+                    retry_after,
+                } => {
+                    let retry_after = Duration::from(retry_after);
+                    let mut effects = Effects::new();
+                    for serialized_id in serialized_ids {
+                        let result = match tag {
+                            Tag::Block => try_later_event(&serialized_id, sender, retry_after)
+                                .map(Event::BlockFetcher),
+                            Tag::BlockByHeight => {
+                                try_later_event(&serialized_id, sender, retry_after)
+                                    .map(Event::BlockByHeightFetcher)
+                            }
+                            Tag::Deploy => try_later_event(&serialized_id, sender, retry_after)
+                                .map(Event::DeployFetcher),
+                            Tag::DeployHeaderWithHash => {
+                                try_later_event(&serialized_id, sender, retry_after)
+                                    .map(Event::DeployHeaderFetcher)
+                            }
+                            _ => {
+                                debug!(%tag, %sender, "try-later ignored in joiner reactor");
+                                break;
+                            }
+                        };
+                        match result {
+                            Ok(event) => {
+                                effects.extend(self.dispatch_event(effect_builder, rng, event))
+                            }
+                            Err(err) => {
+                                error!("failed to decode {} id from {}: {}", tag, sender, err)
+                            }
+                        }
+                    }
+                    effects
+                }
+                Message::AddressGossiper(message) | Message::SignedAddressGossiper(message) => {
                     let event = Event::AddressGossiper(gossiper::Event::MessageReceived {
                         sender,
                         message,
@@ -664,7 +870,9 @@ impl reactor::Reactor for Reactor {
                 deploy,
                 source,
             }) => {
-                let event = event_stream_server::Event::DeployAccepted(*deploy.id());
+                let event = event_stream_server::Event::DeployAccepted(Box::new(
+                    DeployHeaderSummary::from(&*deploy),
+                ));
                 let mut effects =
                     self.dispatch_event(effect_builder, rng, Event::EventStreamServer(event));
 
@@ -683,11 +891,17 @@ impl reactor::Reactor for Reactor {
             Event::DeployAcceptorAnnouncement(DeployAcceptorAnnouncement::InvalidDeploy {
                 deploy,
                 source,
+                error,
             }) => {
                 let deploy_hash = *deploy.id();
-                let peer = source;
-                warn!(?deploy_hash, ?peer, "Invalid deploy received from a peer.");
-                Effects::new()
+                let peer = source.clone();
+                warn!(?deploy_hash, ?peer, %error, "Invalid deploy received from a peer.");
+                let event = event_stream_server::Event::DeployRejected {
+                    deploy_hash,
+                    reason: error.to_string(),
+                    source,
+                };
+                self.dispatch_event(effect_builder, rng, Event::EventStreamServer(event))
             }
             Event::Storage(event) => reactor::wrap_effects(
                 Event::Storage,
@@ -731,6 +945,16 @@ impl reactor::Reactor for Reactor {
             Event::DeployFetcherRequest(request) => {
                 self.dispatch_event(effect_builder, rng, Event::DeployFetcher(request.into()))
             }
+            Event::DeployHeaderFetcher(event) => reactor::wrap_effects(
+                Event::DeployHeaderFetcher,
+                self.deploy_header_fetcher
+                    .handle_event(effect_builder, rng, event),
+            ),
+            Event::DeployHeaderFetcherRequest(request) => self.dispatch_event(
+                effect_builder,
+                rng,
+                Event::DeployHeaderFetcher(request.into()),
+            ),
             Event::BlockByHeightFetcherRequest(request) => self.dispatch_event(
                 effect_builder,
                 rng,
@@ -812,7 +1036,7 @@ impl reactor::Reactor for Reactor {
                 gossiped_address,
             )) => {
                 let reactor_event = Event::SmallNetwork(small_network::Event::PeerAddressReceived(
-                    gossiped_address,
+                    Box::new(gossiped_address),
                 ));
                 self.dispatch_event(effect_builder, rng, reactor_event)
             }
@@ -822,17 +1046,38 @@ impl reactor::Reactor for Reactor {
             }
 
             Event::LinearChainAnnouncement(LinearChainAnnouncement::BlockAdded(block)) => {
-                let mut effects = reactor::wrap_effects(
+                let mut effects = Effects::new();
+                // If the block commits an upgrade, the deploy acceptor switches to its deploy
+                // config before any other event is handled.
+                if let Some(deploy_config) =
+                    self.chainspec_loader.upgrade_deploy_config(block.header())
+                {
+                    let reactor_event = Event::DeployAcceptor(
+                        deploy_acceptor::Event::DeployConfigActivated(deploy_config),
+                    );
+                    effects.extend(self.dispatch_event(effect_builder, rng, reactor_event));
+                }
+
+                let reactor_event = Event::DeployAcceptor(deploy_acceptor::Event::BlockAdded(
+                    Box::new(block.header().clone()),
+                ));
+                effects.extend(self.dispatch_event(effect_builder, rng, reactor_event));
+
+                effects.extend(reactor::wrap_effects(
                     Event::EventStreamServer,
                     self.event_stream_server.handle_event(
                         effect_builder,
                         rng,
                         event_stream_server::Event::BlockAdded(block.clone()),
                     ),
-                );
+                ));
                 let reactor_event =
                     Event::LinearChainSync(linear_chain_sync::Event::BlockHandled(block.clone()));
                 effects.extend(self.dispatch_event(effect_builder, rng, reactor_event));
+                let reactor_event = Event::RestServer(rest_server::Event::BlockAdded {
+                    height: block.height(),
+                });
+                effects.extend(self.dispatch_event(effect_builder, rng, reactor_event));
                 effects.extend(self.dispatch_event(
                     effect_builder,
                     rng,
@@ -869,14 +1114,11 @@ impl reactor::Reactor for Reactor {
             Event::StateStoreRequest(req) => {
                 self.dispatch_event(effect_builder, rng, Event::Storage(req.into()))
             }
-            Event::NetworkInfoRequest(req) => {
-                let event = if env::var(ENABLE_LIBP2P_NET_ENV_VAR).is_ok() {
-                    Event::Network(network::Event::from(req))
-                } else {
-                    Event::SmallNetwork(small_network::Event::from(req))
-                };
-                self.dispatch_event(effect_builder, rng, event)
+            Event::NetworkRequest(req) => {
+                self.network
+                    .handle_network_request(effect_builder, rng, req)
             }
+            Event::NetworkInfoRequest(req) => self.network.handle_network_info_request(req),
             Event::ChainspecLoaderAnnouncement(
                 ChainspecLoaderAnnouncement::UpgradeActivationPointRead(next_upgrade),
             ) => {
@@ -890,13 +1132,32 @@ impl reactor::Reactor for Reactor {
                         next_upgrade.activation_point(),
                     ));
                 effects.extend(self.dispatch_event(effect_builder, rng, reactor_event));
+
+                let reactor_event =
+                    Event::DeployAcceptor(deploy_acceptor::Event::GotUpgradeActivationPoint(
+                        next_upgrade.activation_point(),
+                    ));
+                effects.extend(self.dispatch_event(effect_builder, rng, reactor_event));
                 effects
             }
+            Event::BlocklistAnnouncement(ann) => {
+                self.network
+                    .handle_blocklist_announcement(effect_builder, rng, ann)
+            }
             // This is done to handle status requests from the RestServer
             Event::ConsensusRequest(ConsensusRequest::Status(responder)) => {
                 // no consensus, respond with None
                 responder.respond(None).ignore()
             }
+            Event::ConsensusRequest(ConsensusRequest::EraProgress(responder)) => {
+                // no consensus, respond with None
+                responder.respond(None).ignore()
+            }
+            // This is done to handle deploy propagation requests from the RestServer
+            Event::DeployPropagationRequest(DeployPropagationRequest::GetSpreads(responder)) => {
+                // deploy propagation is not measured while joining, respond with None
+                responder.respond(None).ignore()
+            }
         }
     }
 
@@ -910,11 +1171,34 @@ impl reactor::Reactor for Reactor {
         }
     }
 
+    fn shutdown(&mut self, exit_code: ExitCode) -> BoxFuture<'static, ()> {
+        let reason = self.chainspec_loader.shutdown_reason(exit_code);
+        let event_stream_stopped = self.event_stream_server.shutdown(reason);
+        let writes_flushed = self.storage.flush();
+        async move {
+            join!(event_stream_stopped, writes_flushed);
+        }
+        .boxed()
+    }
+
     fn update_metrics(&mut self, event_queue_handle: EventQueueHandle<Self::Event>) {
         self.memory_metrics.estimate(self);
         self.event_queue_metrics
             .record_event_queue_counts(&event_queue_handle);
     }
+    fn event_queue_weights(config: &Self::Config) -> EventQueueWeights {
+        config.value().config.value().node.event_queue_weights
+    }
+
+    fn failure_policies(config: &Self::Config) -> FailurePolicies {
+        config
+            .value()
+            .config
+            .value()
+            .node
+            .component_failure_policies
+            .clone()
+    }
 }
 
 impl Reactor {
@@ -937,25 +1221,59 @@ impl Reactor {
             storage: self.storage,
             maybe_latest_block_header,
             event_stream_server: self.event_stream_server,
-            small_network_identity: SmallNetworkIdentity::from(&self.small_network),
-            network_identity: NetworkIdentity::from(&self.network),
+            small_network_identity: self.small_network_identity,
+            network_identity: self.network_identity,
         };
         self.network.finalize().await;
-        self.small_network.finalize().await;
         self.rest_server.finalize().await;
         Ok(config)
     }
 }
 
+/// Decodes the ID of an item the given peer asked us to request again later.
+fn try_later_event<T: Item>(
+    serialized_id: &[u8],
+    peer: NodeId,
+    retry_after: Duration,
+) -> Result<fetcher::Event<T>, bincode::Error> {
+    let id = bincode::deserialize(serialized_id)?;
+    Ok(fetcher::Event::TryLater {
+        id,
+        peer,
+        retry_after,
+    })
+}
+
+/// Returns the fetcher event for a deploy header received from a peer.
+///
+/// The header is only accepted if hashed using the scheme for the deploy's timestamp, as the
+/// deploy acceptor requires of full deploys.
+fn deploy_header_received(
+    deploy_config: &DeployConfig,
+    deploy_header: DeployHeaderWithHash,
+    sender: NodeId,
+) -> fetcher::Event<DeployHeaderWithHash> {
+    let hash_scheme = deploy_config.hash_scheme(deploy_header.header().timestamp());
+    match deploy_header.is_valid(hash_scheme) {
+        Ok(()) => fetcher::Event::GotRemotely {
+            item: Box::new(deploy_header),
+            source: Source::Peer(sender),
+        },
+        Err(err) => {
+            warn!(%deploy_header, %sender, %err, "invalid deploy header received");
+            fetcher::Event::AbsentRemotely {
+                id: *deploy_header.deploy_hash(),
+                peer: sender,
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 impl NetworkedReactor for Reactor {
     type NodeId = NodeId;
     fn node_id(&self) -> Self::NodeId {
-        if env::var(ENABLE_LIBP2P_NET_ENV_VAR).is_err() {
-            self.small_network.node_id()
-        } else {
-            self.network.node_id()
-        }
+        self.network.node_id()
     }
 }
 
@@ -966,3 +1284,80 @@ impl Reactor {
         &self.storage
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use casper_execution_engine::core::engine_state::executable_deploy_item::ExecutableDeployItem;
+    use casper_types::{bytesrepr::Bytes, RuntimeArgs, SecretKey};
+
+    use super::*;
+    use crate::{
+        crypto::AsymmetricKeyExt,
+        testing::TestRng,
+        types::{DeployHashScheme, TimeDiff},
+    };
+
+    /// Creates the header of a deploy with the given timestamp, hashed using `hash_scheme`.
+    fn new_deploy_header(
+        rng: &mut TestRng,
+        timestamp: Timestamp,
+        hash_scheme: DeployHashScheme,
+    ) -> DeployHeaderWithHash {
+        let payment = ExecutableDeployItem::ModuleBytes {
+            module_bytes: Bytes::new(),
+            args: RuntimeArgs::new(),
+        };
+        let session = ExecutableDeployItem::ModuleBytes {
+            module_bytes: Bytes::from(vec![0; 100]),
+            args: RuntimeArgs::new(),
+        };
+        let deploy = Deploy::new_with_hash_scheme(
+            timestamp,
+            TimeDiff::from(60_000),
+            1,
+            vec![],
+            "casper-example".to_string(),
+            payment,
+            session,
+            &SecretKey::random(rng),
+            hash_scheme,
+        );
+        DeployHeaderWithHash::new(*deploy.id(), deploy.header().clone())
+    }
+
+    fn is_accepted(event: fetcher::Event<DeployHeaderWithHash>) -> bool {
+        match event {
+            fetcher::Event::GotRemotely { .. } => true,
+            fetcher::Event::AbsentRemotely { .. } => false,
+            event => panic!("unexpected fetcher event {}", event),
+        }
+    }
+
+    /// Simulates fetching deploy headers after domain-separated hashing has activated.
+    #[test]
+    fn should_check_fetched_deploy_header_using_scheme_for_deploy_timestamp() {
+        let mut rng = TestRng::new();
+        let sender = NodeId::random(&mut rng);
+        let activation = Timestamp::now();
+        let deploy_config = DeployConfig {
+            domain_separated_hashing_activation: Some(activation),
+            ..DeployConfig::default()
+        };
+        let before_activation = activation - TimeDiff::from(1);
+        let mut check = |timestamp, hash_scheme| {
+            let deploy_header = new_deploy_header(&mut rng, timestamp, hash_scheme);
+            is_accepted(deploy_header_received(
+                &deploy_config,
+                deploy_header,
+                sender,
+            ))
+        };
+
+        // A legacy-hashed header of a deploy created before the activation is still accepted.
+        assert!(check(before_activation, DeployHashScheme::Legacy));
+        assert!(!check(before_activation, DeployHashScheme::DomainSeparated));
+
+        assert!(check(activation, DeployHashScheme::DomainSeparated));
+        assert!(!check(activation, DeployHashScheme::Legacy));
+    }
+}
@@ -13,10 +13,8 @@ pub(super) struct MemoryMetrics {
 
     /// Estimated heap memory usage of metrics component.
     mem_metrics: IntGauge,
-    /// Estimated heap memory usage of network component.
+    /// Estimated heap memory usage of the running network component.
     mem_network: IntGauge,
-    /// Estimated heap memory usage of small_network component.
-    mem_small_network: IntGauge,
     /// Estimated heap memory usage of address_gossiper component.
     mem_address_gossiper: IntGauge,
     /// Estimated heap memory usage of the configuration for the validator node.
@@ -51,10 +49,6 @@ impl MemoryMetrics {
         let mem_total = IntGauge::new("joiner_mem_total", "total memory usage in bytes")?;
         let mem_metrics = IntGauge::new("joiner_mem_metrics", "metrics memory usage in bytes")?;
         let mem_network = IntGauge::new("joiner_mem_network", "network memory usage in bytes")?;
-        let mem_small_network = IntGauge::new(
-            "joiner_mem_small_network",
-            "small network memory usage in bytes",
-        )?;
         let mem_address_gossiper = IntGauge::new(
             "joiner_mem_address_gossiper",
             "address_gossiper memory usage in bytes",
@@ -101,7 +95,6 @@ impl MemoryMetrics {
         registry.register(Box::new(mem_total.clone()))?;
         registry.register(Box::new(mem_metrics.clone()))?;
         registry.register(Box::new(mem_network.clone()))?;
-        registry.register(Box::new(mem_small_network.clone()))?;
         registry.register(Box::new(mem_address_gossiper.clone()))?;
         registry.register(Box::new(mem_config.clone()))?;
         registry.register(Box::new(mem_chainspec_loader.clone()))?;
@@ -118,7 +111,6 @@ impl MemoryMetrics {
             mem_total,
             mem_metrics,
             mem_network,
-            mem_small_network,
             mem_address_gossiper,
             mem_config,
             mem_chainspec_loader,
@@ -140,7 +132,6 @@ impl MemoryMetrics {
 
         let metrics = reactor.metrics.estimate_heap_size() as i64;
         let network = reactor.network.estimate_heap_size() as i64;
-        let small_network = reactor.small_network.estimate_heap_size() as i64;
         let address_gossiper = reactor.address_gossiper.estimate_heap_size() as i64;
         let config = reactor.config.estimate_heap_size() as i64;
         let chainspec_loader = reactor.chainspec_loader.estimate_heap_size() as i64;
@@ -154,7 +145,6 @@ impl MemoryMetrics {
 
         let total = metrics
             + network
-            + small_network
             + address_gossiper
             + config
             + chainspec_loader
@@ -169,7 +159,6 @@ impl MemoryMetrics {
         self.mem_total.set(total);
         self.mem_metrics.set(metrics);
         self.mem_network.set(network);
-        self.mem_small_network.set(small_network);
         self.mem_address_gossiper.set(address_gossiper);
         self.mem_config.set(config);
         self.mem_chainspec_loader.set(chainspec_loader);
@@ -189,7 +178,6 @@ impl MemoryMetrics {
         %duration_s,
         %metrics,
         %network,
-        %small_network,
         %address_gossiper,
         %config ,
         %chainspec_loader,
@@ -209,7 +197,6 @@ impl Drop for MemoryMetrics {
         unregister_metric!(self.registry, self.mem_total);
         unregister_metric!(self.registry, self.mem_metrics);
         unregister_metric!(self.registry, self.mem_network);
-        unregister_metric!(self.registry, self.mem_small_network);
         unregister_metric!(self.registry, self.mem_address_gossiper);
         unregister_metric!(self.registry, self.mem_config);
         unregister_metric!(self.registry, self.mem_chainspec_loader);
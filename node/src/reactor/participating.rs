@@ -9,7 +9,7 @@ mod memory_metrics;
 mod tests;
 
 use std::{
-    env,
+    collections::BTreeMap,
     fmt::{self, Debug, Display, Formatter},
     path::PathBuf,
     sync::Arc,
@@ -17,11 +17,14 @@ use std::{
 
 use datasize::DataSize;
 use derive_more::From;
+use futures::{future::BoxFuture, join, FutureExt};
 use prometheus::Registry;
 use reactor::ReactorEvent;
 use serde::Serialize;
 use tracing::{debug, error, trace, warn};
 
+use casper_types::{PublicKey, U512};
+
 #[cfg(test)]
 use crate::testing::network::NetworkedReactor;
 
@@ -33,36 +36,47 @@ use crate::{
         consensus::{self, EraSupervisor, HighwayProtocol},
         contract_runtime::{self, ContractRuntime},
         deploy_acceptor::{self, DeployAcceptor},
-        event_stream_server::{self, EventStreamServer},
-        fetcher::{self, Fetcher},
+        deploy_propagation::{self, DeployPropagation},
+        event_stream_server::{self, DeployHeaderSummary, EventStreamServer},
+        fetcher::{self, FetchScheduler, Fetcher},
         gossiper::{self, Gossiper},
         linear_chain,
         metrics::Metrics,
-        network::{self, Network, NetworkIdentity, ENABLE_LIBP2P_NET_ENV_VAR},
+        network::{self, NetworkIdentity},
+        network_provider::NetworkComponent,
+        performance_tracker::{self, PerformanceTracker},
+        request_limiter::{self, RequestLimiter},
         rest_server::{self, RestServer},
         rpc_server::{self, RpcServer},
-        small_network::{self, GossipedAddress, SmallNetwork, SmallNetworkIdentity},
+        small_network::{self, GossipedAddress, SmallNetworkIdentity},
         storage::{self, Storage},
         Component,
     },
     effect::{
         announcements::{
-            BlocklistAnnouncement, ChainspecLoaderAnnouncement, ConsensusAnnouncement,
-            ContractRuntimeAnnouncement, ControlAnnouncement, DeployAcceptorAnnouncement,
-            GossiperAnnouncement, LinearChainAnnouncement, LinearChainBlock, NetworkAnnouncement,
+            BlockProposerAnnouncement, BlocklistAnnouncement, ChainspecLoaderAnnouncement,
+            ConsensusAnnouncement, ContractRuntimeAnnouncement, ControlAnnouncement,
+            DeployAcceptorAnnouncement, GossiperAnnouncement, LinearChainAnnouncement,
+            LinearChainBlock, NetworkAnnouncement, RequestLimiterAnnouncement,
             RpcServerAnnouncement,
         },
         requests::{
             BlockProposerRequest, BlockValidationRequest, ChainspecLoaderRequest, ConsensusRequest,
-            ContractRuntimeRequest, FetcherRequest, LinearChainRequest, MetricsRequest,
-            NetworkInfoRequest, NetworkRequest, RestRequest, RpcRequest, StateStoreRequest,
-            StorageRequest,
+            ContractRuntimeRequest, DeployPropagationRequest, FetcherRequest, LinearChainRequest,
+            MetricsRequest, NetworkInfoRequest, NetworkRequest, RestRequest, RpcRequest,
+            StateStoreRequest, StorageRequest,
         },
         EffectBuilder, EffectExt, Effects,
     },
     protocol::Message,
-    reactor::{self, event_queue_metrics::EventQueueMetrics, EventQueueHandle, ReactorExit},
-    types::{BlockHash, BlockHeader, Deploy, ExitCode, NodeId, Tag},
+    reactor::{
+        self, event_queue_metrics::EventQueueMetrics, EventQueueHandle, EventQueueWeights,
+        FailurePolicies, QueueKind, ReactorExit,
+    },
+    types::{
+        BlockHash, BlockHeader, BlockTransfers, Chainspec, Deploy, DeployApprovals, DeployHash,
+        DeployHeaderWithHash, ExitCode, Item, NodeId, SharedObject, Tag,
+    },
     utils::{Source, WithDir},
     NodeRng,
 };
@@ -123,6 +137,15 @@ pub enum Event {
     /// Linear chain event.
     #[from]
     LinearChain(#[serde(skip_serializing)] linear_chain::Event<NodeId>),
+    /// Performance tracker event.
+    #[from]
+    PerformanceTracker(#[serde(skip_serializing)] performance_tracker::Event),
+    /// Request limiter event.
+    #[from]
+    RequestLimiter(#[serde(skip_serializing)] request_limiter::Event),
+    /// Deploy propagation event.
+    #[from]
+    DeployPropagation(#[serde(skip_serializing)] deploy_propagation::Event),
 
     // Requests
     /// Network request.
@@ -181,12 +204,18 @@ pub enum Event {
     /// Linear chain announcement.
     #[from]
     LinearChainAnnouncement(#[serde(skip_serializing)] LinearChainAnnouncement),
+    /// Block proposer announcement.
+    #[from]
+    BlockProposerAnnouncement(#[serde(skip_serializing)] BlockProposerAnnouncement),
     /// Chainspec loader announcement.
     #[from]
     ChainspecLoaderAnnouncement(#[serde(skip_serializing)] ChainspecLoaderAnnouncement),
     /// Blocklist announcement.
     #[from]
     BlocklistAnnouncement(BlocklistAnnouncement<NodeId>),
+    /// Request limiter announcement.
+    #[from]
+    RequestLimiterAnnouncement(#[serde(skip_serializing)] RequestLimiterAnnouncement),
 }
 
 impl ReactorEvent for Event {
@@ -197,6 +226,59 @@ impl ReactorEvent for Event {
             None
         }
     }
+
+    fn queue_kind(&self) -> QueueKind {
+        match self {
+            Event::ControlAnnouncement(_) => QueueKind::Control,
+            Event::Consensus(_) | Event::ConsensusAnnouncement(_) => QueueKind::Consensus,
+            Event::DeployGossiper(_)
+            | Event::AddressGossiper(_)
+            | Event::DeployGossiperAnnouncement(_)
+            | Event::AddressGossiperAnnouncement(_) => QueueKind::Gossip,
+            _ => QueueKind::Regular,
+        }
+    }
+
+    fn component_name(&self) -> &'static str {
+        match self {
+            Event::Network(_)
+            | Event::SmallNetwork(_)
+            | Event::NetworkRequest(_)
+            | Event::NetworkInfoRequest(_) => "network",
+            Event::BlockProposer(_) | Event::BlockProposerRequest(_) => "block_proposer",
+            Event::Storage(_) | Event::StorageRequest(_) | Event::StateStoreRequest(_) => "storage",
+            Event::RpcServer(_) => "rpc_server",
+            Event::RestServer(_) => "rest_server",
+            Event::EventStreamServer(_) => "event_stream_server",
+            Event::ChainspecLoader(_) | Event::ChainspecLoaderRequest(_) => "chainspec_loader",
+            Event::Consensus(_) => "consensus",
+            Event::DeployAcceptor(_) => "deploy_acceptor",
+            Event::DeployFetcher(_) | Event::DeployFetcherRequest(_) => "deploy_fetcher",
+            Event::DeployGossiper(_) => "deploy_gossiper",
+            Event::AddressGossiper(_) => "address_gossiper",
+            Event::ContractRuntime(_) => "contract_runtime",
+            Event::BlockValidator(_) | Event::BlockValidatorRequest(_) => "block_validator",
+            Event::LinearChain(_) => "linear_chain",
+            Event::PerformanceTracker(_) => "performance_tracker",
+            Event::RequestLimiter(_) => "request_limiter",
+            Event::DeployPropagation(_) => "deploy_propagation",
+            Event::MetricsRequest(_) => "metrics",
+            // Announcements may be dispatched to several components.
+            Event::ControlAnnouncement(_)
+            | Event::NetworkAnnouncement(_)
+            | Event::RpcServerAnnouncement(_)
+            | Event::DeployAcceptorAnnouncement(_)
+            | Event::ConsensusAnnouncement(_)
+            | Event::ContractRuntimeAnnouncement(_)
+            | Event::DeployGossiperAnnouncement(_)
+            | Event::AddressGossiperAnnouncement(_)
+            | Event::LinearChainAnnouncement(_)
+            | Event::BlockProposerAnnouncement(_)
+            | Event::ChainspecLoaderAnnouncement(_)
+            | Event::BlocklistAnnouncement(_)
+            | Event::RequestLimiterAnnouncement(_) => "reactor",
+        }
+    }
 }
 
 impl From<RpcRequest<NodeId>> for Event {
@@ -229,6 +311,18 @@ impl From<NetworkRequest<NodeId, gossiper::Message<GossipedAddress>>> for Event
     }
 }
 
+impl From<NetworkRequest<NodeId, deploy_propagation::Message>> for Event {
+    fn from(request: NetworkRequest<NodeId, deploy_propagation::Message>) -> Self {
+        Event::NetworkRequest(request.map_payload(Message::from))
+    }
+}
+
+impl From<DeployPropagationRequest> for Event {
+    fn from(request: DeployPropagationRequest) -> Self {
+        Event::DeployPropagation(deploy_propagation::Event::Request(request))
+    }
+}
+
 impl From<ContractRuntimeRequest> for Event {
     fn from(request: ContractRuntimeRequest) -> Event {
         Event::ContractRuntime(contract_runtime::Event::Request(Box::new(request)))
@@ -266,6 +360,9 @@ impl Display for Event {
             Event::ContractRuntime(event) => write!(f, "contract runtime: {:?}", event),
             Event::LinearChain(event) => write!(f, "linear-chain event {}", event),
             Event::BlockValidator(event) => write!(f, "block validator: {}", event),
+            Event::PerformanceTracker(event) => write!(f, "performance tracker: {}", event),
+            Event::RequestLimiter(event) => write!(f, "request limiter: {}", event),
+            Event::DeployPropagation(event) => write!(f, "deploy propagation: {}", event),
             Event::NetworkRequest(req) => write!(f, "network request: {}", req),
             Event::NetworkInfoRequest(req) => write!(f, "network info request: {}", req),
             Event::ChainspecLoaderRequest(req) => write!(f, "chainspec loader request: {}", req),
@@ -294,12 +391,18 @@ impl Display for Event {
                 write!(f, "address gossiper announcement: {}", ann)
             }
             Event::LinearChainAnnouncement(ann) => write!(f, "linear chain announcement: {}", ann),
+            Event::BlockProposerAnnouncement(ann) => {
+                write!(f, "block proposer announcement: {}", ann)
+            }
             Event::ChainspecLoaderAnnouncement(ann) => {
                 write!(f, "chainspec loader announcement: {}", ann)
             }
             Event::BlocklistAnnouncement(ann) => {
                 write!(f, "blocklist announcement: {}", ann)
             }
+            Event::RequestLimiterAnnouncement(ann) => {
+                write!(f, "request limiter announcement: {}", ann)
+            }
         }
     }
 }
@@ -335,8 +438,7 @@ impl Debug for ParticipatingInitConfig {
 #[derive(DataSize, Debug)]
 pub struct Reactor {
     metrics: Metrics,
-    small_network: SmallNetwork<Event, Message>,
-    network: Network<Event, Message>,
+    network: NetworkComponent<Event, Message>,
     address_gossiper: Gossiper<GossipedAddress, Event>,
     storage: Storage,
     contract_runtime: ContractRuntime,
@@ -352,6 +454,9 @@ pub struct Reactor {
     block_proposer: BlockProposer,
     block_validator: BlockValidator<NodeId>,
     linear_chain: LinearChainComponent<NodeId>,
+    performance_tracker: PerformanceTracker,
+    request_limiter: RequestLimiter,
+    deploy_propagation: DeployPropagation,
 
     // Non-components.
     #[data_size(skip)] // Never allocates heap data.
@@ -359,6 +464,9 @@ pub struct Reactor {
 
     #[data_size(skip)]
     event_queue_metrics: EventQueueMetrics,
+
+    /// The performance tracker's configuration, kept for restarting it.
+    performance_tracker_config: performance_tracker::Config,
 }
 
 #[cfg(test)]
@@ -374,6 +482,401 @@ impl Reactor {
     }
 }
 
+impl Reactor {
+    /// Serves a request from a peer which has been admitted by the request limiter.
+    fn serve_request(
+        &mut self,
+        effect_builder: EffectBuilder<Event>,
+        rng: &mut NodeRng,
+        sender: NodeId,
+        payload: Message,
+    ) -> Effects<Event> {
+        match payload {
+            Message::GetRequest { tag, serialized_id } => {
+                self.handle_get_request(effect_builder, rng, sender, tag, serialized_id)
+            }
+            Message::GetBatchRequest {
+                tag,
+                serialized_ids,
+            } => self.handle_get_batch_request(effect_builder, sender, tag, serialized_ids),
+            other => {
+                error!(%other, %sender, "cannot serve message which is not a get request");
+                Effects::new()
+            }
+        }
+    }
+
+    /// Answers a get request with the requested item, read from storage directly or by the linear
+    /// chain component.
+    fn handle_get_request(
+        &mut self,
+        effect_builder: EffectBuilder<Event>,
+        rng: &mut NodeRng,
+        sender: NodeId,
+        tag: Tag,
+        serialized_id: Vec<u8>,
+    ) -> Effects<Event> {
+        let request = match tag {
+            Tag::Deploy => {
+                let deploy_hash = match bincode::deserialize(&serialized_id) {
+                    Ok(hash) => hash,
+                    Err(error) => {
+                        error!(
+                            "failed to decode {:?} from {}: {}",
+                            serialized_id, sender, error
+                        );
+                        return Effects::new();
+                    }
+                };
+
+                match self
+                    .storage
+                    .handle_deduplicated_legacy_direct_deploy_request(deploy_hash)
+                {
+                    Some(serialized_item) => {
+                        let message =
+                            Message::new_get_response_raw_unchecked::<Deploy>(serialized_item);
+                        return effect_builder.send_message(sender, message).ignore();
+                    }
+
+                    None => {
+                        debug!(%sender, %deploy_hash, "failed to get deploy (not found)");
+                        return Effects::new();
+                    }
+                }
+            }
+            Tag::Block => {
+                let block_hash = match bincode::deserialize(&serialized_id) {
+                    Ok(hash) => hash,
+                    Err(error) => {
+                        error!(
+                            "failed to decode {:?} from {}: {}",
+                            serialized_id, sender, error
+                        );
+                        return Effects::new();
+                    }
+                };
+                LinearChainRequest::BlockRequest(block_hash, sender)
+            }
+            Tag::BlockByHeight => {
+                let height = match bincode::deserialize(&serialized_id) {
+                    Ok(block_by_height) => block_by_height,
+                    Err(error) => {
+                        error!(
+                            "failed to decode {:?} from {}: {}",
+                            serialized_id, sender, error
+                        );
+                        return Effects::new();
+                    }
+                };
+                LinearChainRequest::BlockAtHeight(height, sender)
+            }
+            Tag::GossipedAddress => {
+                warn!("received get request for gossiped-address from {}", sender);
+                return Effects::new();
+            }
+            Tag::BlockHeaderByHash => {
+                let block_hash: BlockHash = match bincode::deserialize(&serialized_id) {
+                    Ok(block_hash) => block_hash,
+                    Err(error) => {
+                        error!(
+                            "failed to decode {:?} from {}: {}",
+                            serialized_id, sender, error
+                        );
+                        return Effects::new();
+                    }
+                };
+
+                match self.storage.read_block_header_by_hash(&block_hash) {
+                    Ok(Some(block_header)) => {
+                        match Message::new_get_response(&block_header) {
+                            Err(error) => {
+                                error!("failed to create get-response: {}", error);
+                                return Effects::new();
+                            }
+                            Ok(message) => {
+                                return effect_builder.send_message(sender, message).ignore();
+                            }
+                        };
+                    }
+                    Ok(None) => {
+                        debug!("failed to get {} for {}", block_hash, sender);
+                        return Effects::new();
+                    }
+                    Err(error) => {
+                        error!("failed to get {} for {}: {}", block_hash, sender, error);
+                        return Effects::new();
+                    }
+                }
+            }
+            Tag::BlockHeaderAndFinalitySignaturesByHeight => {
+                let block_height = match bincode::deserialize(&serialized_id) {
+                    Ok(block_height) => block_height,
+                    Err(error) => {
+                        error!(
+                            "failed to decode {:?} from {}: {}",
+                            serialized_id, sender, error
+                        );
+                        return Effects::new();
+                    }
+                };
+                match self
+                    .storage
+                    .read_block_header_and_finality_signatures_by_height(block_height)
+                {
+                    Ok(Some(block_header)) => {
+                        match Message::new_get_response(&block_header) {
+                            Ok(message) => {
+                                return effect_builder.send_message(sender, message).ignore();
+                            }
+                            Err(error) => {
+                                error!("failed to create get-response: {}", error);
+                                return Effects::new();
+                            }
+                        };
+                    }
+                    Ok(None) => {
+                        debug!("failed to get {} for {}", block_height, sender);
+                        return Effects::new();
+                    }
+                    Err(error) => {
+                        error!("failed to get {} for {}: {}", block_height, sender, error);
+                        return Effects::new();
+                    }
+                }
+            }
+            Tag::DeployHeaderWithHash => {
+                let deploy_hash = match bincode::deserialize(&serialized_id) {
+                    Ok(deploy_hash) => deploy_hash,
+                    Err(error) => {
+                        error!(
+                            "failed to decode {:?} from {}: {}",
+                            serialized_id, sender, error
+                        );
+                        return Effects::new();
+                    }
+                };
+
+                match self.storage.read_deploy_header_with_hash(&deploy_hash) {
+                    Ok(Some(deploy_header)) => {
+                        match Message::new_get_response(&deploy_header) {
+                            Ok(message) => {
+                                return effect_builder.send_message(sender, message).ignore();
+                            }
+                            Err(error) => {
+                                error!("failed to create get-response: {}", error);
+                                return Effects::new();
+                            }
+                        };
+                    }
+                    Ok(None) => {
+                        debug!("failed to get {} for {}", deploy_hash, sender);
+                        return Effects::new();
+                    }
+                    Err(error) => {
+                        error!("failed to get {} for {}: {}", deploy_hash, sender, error);
+                        return Effects::new();
+                    }
+                }
+            }
+            Tag::DeployApprovals => {
+                let deploy_hash = match bincode::deserialize(&serialized_id) {
+                    Ok(deploy_hash) => deploy_hash,
+                    Err(error) => {
+                        error!(
+                            "failed to decode {:?} from {}: {}",
+                            serialized_id, sender, error
+                        );
+                        return Effects::new();
+                    }
+                };
+
+                match self.storage.read_deploy_approvals(&deploy_hash) {
+                    Ok(Some(deploy_approvals)) => {
+                        match Message::new_get_response(&deploy_approvals) {
+                            Ok(message) => {
+                                return effect_builder.send_message(sender, message).ignore();
+                            }
+                            Err(error) => {
+                                error!("failed to create get-response: {}", error);
+                                return Effects::new();
+                            }
+                        };
+                    }
+                    Ok(None) => {
+                        debug!("failed to get {} for {}", deploy_hash, sender);
+                        return Effects::new();
+                    }
+                    Err(error) => {
+                        error!("failed to get {} for {}: {}", deploy_hash, sender, error);
+                        return Effects::new();
+                    }
+                }
+            }
+            Tag::BlockTransfers => {
+                let block_hash = match bincode::deserialize(&serialized_id) {
+                    Ok(block_hash) => block_hash,
+                    Err(error) => {
+                        error!(
+                            "failed to decode {:?} from {}: {}",
+                            serialized_id, sender, error
+                        );
+                        return Effects::new();
+                    }
+                };
+
+                match self.storage.read_block_transfers(&block_hash) {
+                    Ok(Some(transfers)) => {
+                        let block_transfers = BlockTransfers::new(block_hash, transfers);
+                        match Message::new_get_response(&block_transfers) {
+                            Ok(message) => {
+                                return effect_builder.send_message(sender, message).ignore();
+                            }
+                            Err(error) => {
+                                error!("failed to create get-response: {}", error);
+                                return Effects::new();
+                            }
+                        };
+                    }
+                    Ok(None) => {
+                        debug!("failed to get transfers of {} for {}", block_hash, sender);
+                        return Effects::new();
+                    }
+                    Err(error) => {
+                        error!(
+                            "failed to get transfers of {} for {}: {}",
+                            block_hash, sender, error
+                        );
+                        return Effects::new();
+                    }
+                }
+            }
+        };
+        reactor::wrap_effects(
+            Event::LinearChain,
+            self.linear_chain.handle_event(
+                effect_builder,
+                rng,
+                linear_chain::Event::Request(request),
+            ),
+        )
+    }
+
+    /// Answers a batched get request with the requested items held in storage, reporting all
+    /// others as missing.
+    fn handle_get_batch_request(
+        &mut self,
+        effect_builder: EffectBuilder<Event>,
+        sender: NodeId,
+        tag: Tag,
+        serialized_ids: Vec<Vec<u8>>,
+    ) -> Effects<Event> {
+        let is_batchable = match tag {
+            Tag::Deploy => Deploy::BATCHABLE,
+            Tag::DeployHeaderWithHash => DeployHeaderWithHash::BATCHABLE,
+            Tag::DeployApprovals => DeployApprovals::BATCHABLE,
+            Tag::Block
+            | Tag::GossipedAddress
+            | Tag::BlockByHeight
+            | Tag::BlockHeaderByHash
+            | Tag::BlockHeaderAndFinalitySignaturesByHeight
+            | Tag::BlockTransfers => false,
+        };
+
+        let mut serialized_items = vec![];
+        let mut serialized_missing_ids = vec![];
+        if is_batchable {
+            for serialized_id in serialized_ids {
+                match self.read_batched_item(sender, tag, &serialized_id) {
+                    Some(serialized_item) => serialized_items.push(serialized_item),
+                    None => serialized_missing_ids.push(serialized_id),
+                }
+            }
+        } else {
+            warn!(%tag, %sender, "received get batch request for unbatchable items");
+            serialized_missing_ids = serialized_ids;
+        }
+
+        let max_block_size = self
+            .chainspec_loader
+            .chainspec()
+            .deploy_config
+            .max_block_size;
+        let mut effects = Effects::new();
+        for message in Message::new_get_batch_responses(
+            tag,
+            serialized_items,
+            serialized_missing_ids,
+            max_block_size,
+        ) {
+            effects.extend(effect_builder.send_message(sender, message).ignore());
+        }
+        effects
+    }
+
+    /// Reads a single item requested as part of a batch from storage, returning it serialized.
+    ///
+    /// Only items of a batchable type are read.
+    fn read_batched_item(
+        &mut self,
+        sender: NodeId,
+        tag: Tag,
+        serialized_id: &[u8],
+    ) -> Option<SharedObject<Vec<u8>>> {
+        let deploy_hash: DeployHash = match bincode::deserialize(serialized_id) {
+            Ok(deploy_hash) => deploy_hash,
+            Err(error) => {
+                error!(
+                    "failed to decode {:?} from {}: {}",
+                    serialized_id, sender, error
+                );
+                return None;
+            }
+        };
+
+        let maybe_serialized_item = match tag {
+            Tag::Deploy => {
+                return self
+                    .storage
+                    .handle_deduplicated_legacy_direct_deploy_request(deploy_hash);
+            }
+            Tag::DeployHeaderWithHash => self
+                .storage
+                .read_deploy_header_with_hash(&deploy_hash)
+                .map(|maybe_item| maybe_item.map(|item| bincode::serialize(&item))),
+            Tag::DeployApprovals => self
+                .storage
+                .read_deploy_approvals(&deploy_hash)
+                .map(|maybe_item| maybe_item.map(|item| bincode::serialize(&item))),
+            Tag::Block
+            | Tag::GossipedAddress
+            | Tag::BlockByHeight
+            | Tag::BlockHeaderByHash
+            | Tag::BlockHeaderAndFinalitySignaturesByHeight
+            | Tag::BlockTransfers => return None,
+        };
+
+        match maybe_serialized_item {
+            Ok(Some(Ok(serialized_item))) => Some(SharedObject::owned(serialized_item)),
+            Ok(Some(Err(error))) => {
+                error!(
+                    "failed to serialize {} for {}: {}",
+                    deploy_hash, sender, error
+                );
+                None
+            }
+            Ok(None) => {
+                debug!("failed to get {} for {}", deploy_hash, sender);
+                None
+            }
+            Err(error) => {
+                error!("failed to get {} for {}: {}", deploy_hash, sender, error);
+                None
+            }
+        }
+    }
+}
+
 impl reactor::Reactor for Reactor {
     type Event = Event;
 
@@ -407,30 +910,42 @@ impl reactor::Reactor for Reactor {
         let metrics = Metrics::new(registry.clone());
 
         let effect_builder = EffectBuilder::new(event_queue);
-        let network_config = network::Config::from(&config.network);
-        let (network, network_effects) = Network::new(
-            event_queue,
-            network_config,
-            registry,
-            network_identity,
-            chainspec_loader.chainspec(),
-        )?;
 
         let address_gossiper =
             Gossiper::new_for_complete_items("address_gossiper", config.gossip, registry)?;
 
         let protocol_version = &chainspec_loader.chainspec().protocol_config.version;
-        let rpc_server =
-            RpcServer::new(config.rpc_server.clone(), effect_builder, *protocol_version)?;
-        let rest_server = RestServer::new(
+        let config_overrides = config.overrides();
+        let rpc_server = RpcServer::new(
+            config.rpc_server.clone(),
+            effect_builder,
+            *protocol_version,
+            config_overrides.clone(),
+        )?;
+        let (rest_server, rest_server_effects) = RestServer::new(
             config.rest_server.clone(),
             effect_builder,
             *protocol_version,
+            false,
+            maybe_latest_block_header
+                .as_ref()
+                .map(|block_header| block_header.height()),
+            config_overrides,
         )?;
 
-        let deploy_acceptor =
-            DeployAcceptor::new(config.deploy_acceptor, &*chainspec_loader.chainspec());
-        let deploy_fetcher = Fetcher::new("deploy", config.fetcher, registry)?;
+        let maybe_next_activation_point = chainspec_loader
+            .next_upgrade()
+            .map(|next_upgrade| next_upgrade.activation_point());
+        let deploy_acceptor = DeployAcceptor::new(
+            WithDir::new(&root, &config.deploy_acceptor),
+            &*chainspec_loader.chainspec(),
+            maybe_next_activation_point,
+            maybe_latest_block_header.as_ref(),
+            registry,
+        )?;
+        let fetch_scheduler = FetchScheduler::new(&config.fetcher, registry)?;
+        let deploy_fetcher = Fetcher::new("deploy", config.fetcher, &fetch_scheduler, registry)?;
+        let request_limiter = RequestLimiter::new(config.request_limiter, registry)?;
         let deploy_gossiper = Gossiper::new_for_partial_items(
             "deploy_gossiper",
             config.gossip,
@@ -445,6 +960,7 @@ impl reactor::Reactor for Reactor {
                 .map(|block_header| block_header.height() + 1)
                 .unwrap_or(0),
             chainspec_loader.chainspec().as_ref(),
+            config.block_proposer,
         )?;
 
         let initial_era = maybe_latest_block_header.as_ref().map_or_else(
@@ -452,21 +968,24 @@ impl reactor::Reactor for Reactor {
             |block_header| block_header.next_block_era_id(),
         );
 
-        let (small_network, small_network_effects) = SmallNetwork::new(
+        let (network, network_effects) = NetworkComponent::new(
             event_queue,
             config.network,
             Some(WithDir::new(&root, &config.consensus)),
+            storage.root_path(),
             registry,
             small_network_identity,
-            chainspec_loader.chainspec().as_ref(),
+            network_identity,
+            Arc::clone(chainspec_loader.chainspec()),
             Some(initial_era),
         )?;
 
         let mut effects = reactor::wrap_effects(Event::BlockProposer, block_proposer_effects);
+        effects.extend(reactor::wrap_effects(
+            Event::RestServer,
+            rest_server_effects,
+        ));
 
-        let maybe_next_activation_point = chainspec_loader
-            .next_upgrade()
-            .map(|next_upgrade| next_upgrade.activation_point());
         let (consensus, init_consensus_effects) = EraSupervisor::new(
             initial_era,
             WithDir::new(root, config.consensus),
@@ -495,10 +1014,29 @@ impl reactor::Reactor for Reactor {
             chainspec_loader.chainspec().core_config.unbonding_delay,
         )?;
 
-        effects.extend(reactor::wrap_effects(Event::Network, network_effects));
+        let performance_tracker_config = config.performance_tracker;
+        let (performance_tracker, performance_tracker_effects) = PerformanceTracker::new(
+            performance_tracker_config,
+            &storage,
+            genesis_validator_weights(chainspec_loader.chainspec()),
+            effect_builder,
+        )?;
+        effects.extend(reactor::wrap_effects(
+            Event::PerformanceTracker,
+            performance_tracker_effects,
+        ));
+
+        let (deploy_propagation, deploy_propagation_effects) =
+            DeployPropagation::new(config.deploy_propagation, registry, effect_builder)?;
+        effects.extend(reactor::wrap_effects(
+            Event::DeployPropagation,
+            deploy_propagation_effects,
+        ));
+
+        effects.extend(network_effects);
         effects.extend(reactor::wrap_effects(
-            Event::SmallNetwork,
-            small_network_effects,
+            Event::DeployAcceptor,
+            deploy_acceptor.start_checking_account_policy(effect_builder),
         ));
         effects.extend(reactor::wrap_effects(
             Event::ChainspecLoader,
@@ -511,7 +1049,6 @@ impl reactor::Reactor for Reactor {
             Reactor {
                 metrics,
                 network,
-                small_network,
                 address_gossiper,
                 storage,
                 contract_runtime,
@@ -526,8 +1063,12 @@ impl reactor::Reactor for Reactor {
                 block_proposer,
                 block_validator,
                 linear_chain,
+                performance_tracker,
+                request_limiter,
+                deploy_propagation,
                 memory_metrics,
                 event_queue_metrics,
+                performance_tracker_config,
             },
             effects,
         ))
@@ -540,14 +1081,13 @@ impl reactor::Reactor for Reactor {
         event: Event,
     ) -> Effects<Self::Event> {
         match event {
-            Event::Network(event) => reactor::wrap_effects(
-                Event::Network,
-                self.network.handle_event(effect_builder, rng, event),
-            ),
-            Event::SmallNetwork(event) => reactor::wrap_effects(
-                Event::SmallNetwork,
-                self.small_network.handle_event(effect_builder, rng, event),
-            ),
+            Event::Network(event) => self
+                .network
+                .handle_network_event(effect_builder, rng, event),
+            Event::SmallNetwork(event) => {
+                self.network
+                    .handle_small_network_event(effect_builder, rng, event)
+            }
             Event::BlockProposer(event) => reactor::wrap_effects(
                 Event::BlockProposer,
                 self.block_proposer.handle_event(effect_builder, rng, event),
@@ -611,24 +1151,33 @@ impl reactor::Reactor for Reactor {
                 Event::LinearChain,
                 self.linear_chain.handle_event(effect_builder, rng, event),
             ),
+            Event::PerformanceTracker(event) => reactor::wrap_effects(
+                Event::PerformanceTracker,
+                self.performance_tracker
+                    .handle_event(effect_builder, rng, event),
+            ),
+            Event::RequestLimiter(event) => reactor::wrap_effects(
+                Event::RequestLimiter,
+                self.request_limiter
+                    .handle_event(effect_builder, rng, event),
+            ),
+            Event::DeployPropagation(event) => reactor::wrap_effects(
+                Event::DeployPropagation,
+                self.deploy_propagation
+                    .handle_event(effect_builder, rng, event),
+            ),
 
             // Requests:
             Event::NetworkRequest(req) => {
-                let event = if env::var(ENABLE_LIBP2P_NET_ENV_VAR).is_ok() {
-                    Event::Network(network::Event::from(req))
-                } else {
-                    Event::SmallNetwork(small_network::Event::from(req))
-                };
-                self.dispatch_event(effect_builder, rng, event)
-            }
-            Event::NetworkInfoRequest(req) => {
-                let event = if env::var(ENABLE_LIBP2P_NET_ENV_VAR).is_ok() {
-                    Event::Network(network::Event::from(req))
-                } else {
-                    Event::SmallNetwork(small_network::Event::from(req))
-                };
-                self.dispatch_event(effect_builder, rng, event)
+                // Every response served to a peer is charged to its budget, whichever component
+                // sent it.
+                if let NetworkRequest::SendMessage { dest, payload, .. } = &req {
+                    self.request_limiter.record_outgoing(**dest, payload);
+                }
+                self.network
+                    .handle_network_request(effect_builder, rng, req)
             }
+            Event::NetworkInfoRequest(req) => self.network.handle_network_info_request(req),
             Event::DeployFetcherRequest(req) => {
                 self.dispatch_event(effect_builder, rng, Event::DeployFetcher(req.into()))
             }
@@ -669,170 +1218,24 @@ impl reactor::Reactor for Reactor {
                     Message::DeployGossiper(message) => {
                         Event::DeployGossiper(gossiper::Event::MessageReceived { sender, message })
                     }
-                    Message::AddressGossiper(message) => {
+                    Message::AddressGossiper(message) | Message::SignedAddressGossiper(message) => {
                         Event::AddressGossiper(gossiper::Event::MessageReceived { sender, message })
                     }
-                    Message::GetRequest { tag, serialized_id } => match tag {
-                        Tag::Deploy => {
-                            let deploy_hash = match bincode::deserialize(&serialized_id) {
-                                Ok(hash) => hash,
-                                Err(error) => {
-                                    error!(
-                                        "failed to decode {:?} from {}: {}",
-                                        serialized_id, sender, error
-                                    );
-                                    return Effects::new();
-                                }
-                            };
-
-                            match self
-                                .storage
-                                .handle_deduplicated_legacy_direct_deploy_request(deploy_hash)
-                            {
-                                Some(serialized_item) => {
-                                    let message = Message::new_get_response_raw_unchecked::<Deploy>(
-                                        serialized_item,
-                                    );
-                                    return effect_builder.send_message(sender, message).ignore();
-                                }
-
-                                None => {
-                                    debug!(%sender, %deploy_hash, "failed to get deploy (not found)");
-                                    return Effects::new();
-                                }
-                            }
-                        }
-                        Tag::Block => {
-                            let block_hash = match bincode::deserialize(&serialized_id) {
-                                Ok(hash) => hash,
-                                Err(error) => {
-                                    error!(
-                                        "failed to decode {:?} from {}: {}",
-                                        serialized_id, sender, error
-                                    );
-                                    return Effects::new();
-                                }
-                            };
-                            Event::LinearChain(linear_chain::Event::Request(
-                                LinearChainRequest::BlockRequest(block_hash, sender),
-                            ))
-                        }
-                        Tag::BlockByHeight => {
-                            let height = match bincode::deserialize(&serialized_id) {
-                                Ok(block_by_height) => block_by_height,
-                                Err(error) => {
-                                    error!(
-                                        "failed to decode {:?} from {}: {}",
-                                        serialized_id, sender, error
-                                    );
-                                    return Effects::new();
-                                }
-                            };
-                            Event::LinearChain(linear_chain::Event::Request(
-                                LinearChainRequest::BlockAtHeight(height, sender),
-                            ))
-                        }
-                        Tag::GossipedAddress => {
-                            warn!("received get request for gossiped-address from {}", sender);
-                            return Effects::new();
-                        }
-                        Tag::BlockHeaderByHash => {
-                            let block_hash: BlockHash = match bincode::deserialize(&serialized_id) {
-                                Ok(block_hash) => block_hash,
-                                Err(error) => {
-                                    error!(
-                                        "failed to decode {:?} from {}: {}",
-                                        serialized_id, sender, error
-                                    );
-                                    return Effects::new();
-                                }
-                            };
-
-                            match self.storage.read_block_header_by_hash(&block_hash) {
-                                Ok(Some(block_header)) => {
-                                    match Message::new_get_response(&block_header) {
-                                        Err(error) => {
-                                            error!("failed to create get-response: {}", error);
-                                            return Effects::new();
-                                        }
-                                        Ok(message) => {
-                                            return effect_builder
-                                                .send_message(sender, message)
-                                                .ignore();
-                                        }
-                                    };
-                                }
-                                Ok(None) => {
-                                    debug!("failed to get {} for {}", block_hash, sender);
-                                    return Effects::new();
-                                }
-                                Err(error) => {
-                                    error!(
-                                        "failed to get {} for {}: {}",
-                                        block_hash, sender, error
-                                    );
-                                    return Effects::new();
-                                }
-                            }
-                        }
-                        Tag::BlockHeaderAndFinalitySignaturesByHeight => {
-                            let block_height = match bincode::deserialize(&serialized_id) {
-                                Ok(block_height) => block_height,
-                                Err(error) => {
-                                    error!(
-                                        "failed to decode {:?} from {}: {}",
-                                        serialized_id, sender, error
-                                    );
-                                    return Effects::new();
-                                }
-                            };
-                            match self
-                                .storage
-                                .read_block_header_and_finality_signatures_by_height(block_height)
-                            {
-                                Ok(Some(block_header)) => {
-                                    match Message::new_get_response(&block_header) {
-                                        Ok(message) => {
-                                            return effect_builder
-                                                .send_message(sender, message)
-                                                .ignore();
-                                        }
-                                        Err(error) => {
-                                            error!("failed to create get-response: {}", error);
-                                            return Effects::new();
-                                        }
-                                    };
-                                }
-                                Ok(None) => {
-                                    debug!("failed to get {} for {}", block_height, sender);
-                                    return Effects::new();
-                                }
-                                Err(error) => {
-                                    error!(
-                                        "failed to get {} for {}: {}",
-                                        block_height, sender, error
-                                    );
-                                    return Effects::new();
-                                }
-                            }
-                        }
-                    },
+                    request @ Message::GetRequest { .. }
+                    | request @ Message::GetBatchRequest { .. } => {
+                        Event::RequestLimiter(request_limiter::Event::RequestReceived {
+                            sender,
+                            payload: Box::new(request),
+                        })
+                    }
                     Message::GetResponse {
                         tag,
                         serialized_item,
                     } => match tag {
                         Tag::Deploy => {
-                            let deploy = match bincode::deserialize(&serialized_item) {
-                                Ok(deploy) => Box::new(deploy),
-                                Err(error) => {
-                                    error!("failed to decode deploy from {}: {}", sender, error);
-                                    return Effects::new();
-                                }
-                            };
-                            Event::DeployAcceptor(deploy_acceptor::Event::Accept {
-                                deploy,
-                                source: Source::Peer(sender),
-                                responder: None,
+                            Event::DeployAcceptor(deploy_acceptor::Event::ReceivedFromPeer {
+                                serialized_deploy: serialized_item,
+                                sender,
                             })
                         }
                         Tag::Block => {
@@ -871,9 +1274,112 @@ impl reactor::Reactor for Reactor {
                             );
                             return Effects::new();
                         }
+                        Tag::DeployHeaderWithHash => {
+                            error!(
+                                "cannot handle get response for deploy-header-with-hash from {}",
+                                sender
+                            );
+                            return Effects::new();
+                        }
+                        Tag::DeployApprovals => {
+                            error!(
+                                "cannot handle get response for deploy-approvals from {}",
+                                sender
+                            );
+                            return Effects::new();
+                        }
+                        Tag::BlockTransfers => {
+                            error!(
+                                "cannot handle get response for block-transfers from {}",
+                                sender
+                            );
+                            return Effects::new();
+                        }
                     },
-                    Message::FinalitySignature(fs) => {
-                        Event::LinearChain(linear_chain::Event::FinalitySignatureReceived(fs, true))
+                    Message::FinalitySignature(fs) => Event::LinearChain(
+                        linear_chain::Event::FinalitySignatureReceived(fs, Some(sender)),
+                    ),
+                    Message::GetBatchResponse {
+                        tag,
+                        serialized_items,
+                        serialized_missing_ids,
+                    } => {
+                        // Each item is handled as though it arrived in its own get response, so is
+                        // verified individually.
+                        let mut effects = Effects::new();
+                        for serialized_item in serialized_items {
+                            let payload = Message::GetResponse {
+                                tag,
+                                serialized_item,
+                            };
+                            let event =
+                                Event::NetworkAnnouncement(NetworkAnnouncement::MessageReceived {
+                                    sender,
+                                    payload,
+                                });
+                            effects.extend(self.dispatch_event(effect_builder, rng, event));
+                        }
+                        if tag == Tag::Deploy {
+                            for serialized_id in serialized_missing_ids {
+                                let id = match bincode::deserialize(&serialized_id) {
+                                    Ok(deploy_hash) => deploy_hash,
+                                    Err(error) => {
+                                        error!(
+                                            "failed to decode {:?} from {}: {}",
+                                            serialized_id, sender, error
+                                        );
+                                        continue;
+                                    }
+                                };
+                                let event = fetcher::Event::AbsentRemotely { id, peer: sender };
+                                effects.extend(self.dispatch_event(
+                                    effect_builder,
+                                    rng,
+                                    Event::DeployFetcher(event),
+                                ));
+                            }
+                        }
+                        return effects;
+                    }
+                    Message::TryLater {
+                        tag,
+                        serialized_ids,
+                        retry_after,
+                    } => {
+                        if tag != Tag::Deploy {
+                            debug!(%tag, %sender, "try-later ignored in participating reactor");
+                            return Effects::new();
+                        }
+                        let mut effects = Effects::new();
+                        for serialized_id in serialized_ids {
+                            let id = match bincode::deserialize(&serialized_id) {
+                                Ok(deploy_hash) => deploy_hash,
+                                Err(error) => {
+                                    error!(
+                                        "failed to decode {:?} from {}: {}",
+                                        serialized_id, sender, error
+                                    );
+                                    continue;
+                                }
+                            };
+                            let event = fetcher::Event::TryLater {
+                                id,
+                                peer: sender,
+                                retry_after: retry_after.into(),
+                            };
+                            effects.extend(self.dispatch_event(
+                                effect_builder,
+                                rng,
+                                Event::DeployFetcher(event),
+                            ));
+                        }
+                        return effects;
+                    }
+                    Message::DeployPropagation(message) => {
+                        Event::DeployPropagation(deploy_propagation::Event::MessageReceived {
+                            sender,
+                            message,
+                        })
                     }
                 };
                 self.dispatch_event(effect_builder, rng, reactor_event)
@@ -889,6 +1395,84 @@ impl reactor::Reactor for Reactor {
                 trace!("new peer announcement not handled in the participating reactor");
                 Effects::new()
             }
+            Event::NetworkAnnouncement(NetworkAnnouncement::PeerCountChanged(peer_count)) => {
+                let reactor_event =
+                    Event::RestServer(rest_server::Event::PeerCountChanged(peer_count));
+                self.dispatch_event(effect_builder, rng, reactor_event)
+            }
+            Event::NetworkAnnouncement(NetworkAnnouncement::PeerConnected {
+                peer_id,
+                validator: Some(validator),
+            }) => {
+                let reactor_event =
+                    Event::Consensus(consensus::Event::ValidatorConnected { peer_id, validator });
+                self.dispatch_event(effect_builder, rng, reactor_event)
+            }
+            Event::NetworkAnnouncement(NetworkAnnouncement::PeerDisconnected {
+                peer_id,
+                validator: Some(validator),
+            }) => {
+                let reactor_event = Event::Consensus(consensus::Event::ValidatorDisconnected {
+                    peer_id,
+                    validator,
+                });
+                self.dispatch_event(effect_builder, rng, reactor_event)
+            }
+            Event::NetworkAnnouncement(NetworkAnnouncement::PeerConnected {
+                validator: None,
+                ..
+            })
+            | Event::NetworkAnnouncement(NetworkAnnouncement::PeerDisconnected {
+                validator: None,
+                ..
+            }) => {
+                // Only connections to validators are of interest to consensus.
+                Effects::new()
+            }
+            Event::NetworkAnnouncement(NetworkAnnouncement::MessageSendFailed {
+                destination,
+                payload,
+                error_kind,
+            }) => {
+                debug!(%destination, %payload, %error_kind, "failed to send message");
+                // Let the deploy fetcher decide whether to resend its requests or give up on the
+                // peer.
+                let serialized_ids = match payload {
+                    Message::GetRequest {
+                        tag: Tag::Deploy,
+                        serialized_id,
+                    } => vec![serialized_id],
+                    Message::GetBatchRequest {
+                        tag: Tag::Deploy,
+                        serialized_ids,
+                    } => serialized_ids,
+                    _ => return Effects::new(),
+                };
+                let mut effects = Effects::new();
+                for serialized_id in serialized_ids {
+                    let id = match bincode::deserialize(&serialized_id) {
+                        Ok(deploy_hash) => deploy_hash,
+                        Err(error) => {
+                            error!(
+                                "failed to decode {:?} sent to {}: {}",
+                                serialized_id, destination, error
+                            );
+                            continue;
+                        }
+                    };
+                    let event = fetcher::Event::SendFailed {
+                        id,
+                        peer: destination,
+                        error_kind,
+                    };
+                    effects.extend(self.dispatch_event(
+                        effect_builder,
+                        rng,
+                        Event::DeployFetcher(event),
+                    ));
+                }
+                effects
+            }
             Event::RpcServerAnnouncement(RpcServerAnnouncement::DeployReceived {
                 deploy,
                 responder,
@@ -911,13 +1495,22 @@ impl reactor::Reactor for Reactor {
                 let mut effects =
                     self.dispatch_event(effect_builder, rng, Event::DeployGossiper(event));
 
-                let event = event_stream_server::Event::DeployAccepted(*deploy.id());
+                let event = event_stream_server::Event::DeployAccepted(Box::new(
+                    DeployHeaderSummary::from(&*deploy),
+                ));
                 effects.extend(self.dispatch_event(
                     effect_builder,
                     rng,
                     Event::EventStreamServer(event),
                 ));
 
+                let event = deploy_propagation::Event::DeployAccepted(*deploy.id());
+                effects.extend(self.dispatch_event(
+                    effect_builder,
+                    rng,
+                    Event::DeployPropagation(event),
+                ));
+
                 let event = fetcher::Event::GotRemotely {
                     item: deploy,
                     source,
@@ -931,32 +1524,74 @@ impl reactor::Reactor for Reactor {
                 effects
             }
             Event::DeployAcceptorAnnouncement(DeployAcceptorAnnouncement::InvalidDeploy {
-                deploy: _,
-                source: _,
-            }) => Effects::new(),
+                deploy,
+                source,
+                error,
+            }) => {
+                let event = event_stream_server::Event::DeployRejected {
+                    deploy_hash: *deploy.id(),
+                    reason: error.to_string(),
+                    source,
+                };
+                self.dispatch_event(effect_builder, rng, Event::EventStreamServer(event))
+            }
             Event::ConsensusAnnouncement(consensus_announcement) => match consensus_announcement {
                 ConsensusAnnouncement::Finalized(block) => {
+                    let reactor_event_rest =
+                        Event::RestServer(rest_server::Event::BlockFinalized {
+                            height: block.height(),
+                        });
+                    let mut effects = self.dispatch_event(effect_builder, rng, reactor_event_rest);
                     let reactor_event =
                         Event::BlockProposer(block_proposer::Event::FinalizedBlock(block));
-                    self.dispatch_event(effect_builder, rng, reactor_event)
+                    effects.extend(self.dispatch_event(effect_builder, rng, reactor_event));
+                    effects
+                }
+                ConsensusAnnouncement::DeploysFinalized(deploy_hashes) => {
+                    // Finalized deploys can't be included in another block, so there's no point
+                    // propagating them any further before they expire.
+                    let retain_for = self
+                        .chainspec_loader
+                        .chainspec()
+                        .deploy_config
+                        .max_ttl
+                        .into();
+                    let reactor_event =
+                        Event::DeployGossiper(gossiper::Event::ItemsFinishedExternally {
+                            item_ids: deploy_hashes.clone(),
+                            retain_for,
+                        });
+                    let mut effects = self.dispatch_event(effect_builder, rng, reactor_event);
+                    let reactor_event = Event::DeployAcceptor(
+                        deploy_acceptor::Event::DeploysFinalized(deploy_hashes),
+                    );
+                    effects.extend(self.dispatch_event(effect_builder, rng, reactor_event));
+                    effects
                 }
                 ConsensusAnnouncement::CreatedFinalitySignature(fs) => self.dispatch_event(
                     effect_builder,
                     rng,
-                    Event::LinearChain(linear_chain::Event::FinalitySignatureReceived(fs, false)),
+                    Event::LinearChain(linear_chain::Event::FinalitySignatureReceived(fs, None)),
                 ),
                 ConsensusAnnouncement::Fault {
                     era_id,
                     public_key,
                     timestamp,
                 } => {
+                    let reactor_event =
+                        Event::PerformanceTracker(performance_tracker::Event::Fault {
+                            era_id,
+                            public_key: public_key.clone(),
+                        });
+                    let mut effects = self.dispatch_event(effect_builder, rng, reactor_event);
                     let reactor_event =
                         Event::EventStreamServer(event_stream_server::Event::Fault {
                             era_id,
                             public_key: *public_key,
                             timestamp,
                         });
-                    self.dispatch_event(effect_builder, rng, reactor_event)
+                    effects.extend(self.dispatch_event(effect_builder, rng, reactor_event));
+                    effects
                 }
             },
             Event::ContractRuntimeAnnouncement(ContractRuntimeAnnouncement::LinearChainBlock(
@@ -1026,7 +1661,7 @@ impl reactor::Reactor for Reactor {
                 gossiped_address,
             )) => {
                 let reactor_event = Event::SmallNetwork(small_network::Event::PeerAddressReceived(
-                    gossiped_address,
+                    Box::new(gossiped_address),
                 ));
                 self.dispatch_event(effect_builder, rng, reactor_event)
             }
@@ -1035,13 +1670,46 @@ impl reactor::Reactor for Reactor {
                 Effects::new()
             }
             Event::LinearChainAnnouncement(LinearChainAnnouncement::BlockAdded(block)) => {
+                let mut effects = Effects::new();
+                // If the block commits an upgrade, the deploy acceptor and block proposer switch to
+                // its deploy config before any other event is handled.
+                if let Some(deploy_config) =
+                    self.chainspec_loader.upgrade_deploy_config(block.header())
+                {
+                    let reactor_event = Event::DeployAcceptor(
+                        deploy_acceptor::Event::DeployConfigActivated(deploy_config),
+                    );
+                    effects.extend(self.dispatch_event(effect_builder, rng, reactor_event));
+                    let reactor_event = Event::BlockProposer(
+                        block_proposer::Event::DeployConfigActivated(deploy_config),
+                    );
+                    effects.extend(self.dispatch_event(effect_builder, rng, reactor_event));
+                }
+
+                let reactor_event = Event::DeployAcceptor(deploy_acceptor::Event::BlockAdded(
+                    Box::new(block.header().clone()),
+                ));
+                effects.extend(self.dispatch_event(effect_builder, rng, reactor_event));
+
                 let reactor_event_consensus = Event::Consensus(consensus::Event::BlockAdded(
                     Box::new(block.header().clone()),
                 ));
                 let reactor_event_es =
                     Event::EventStreamServer(event_stream_server::Event::BlockAdded(block.clone()));
-                let mut effects = self.dispatch_event(effect_builder, rng, reactor_event_es);
+                let reactor_event_rest = Event::RestServer(rest_server::Event::BlockAdded {
+                    height: block.height(),
+                });
+                let reactor_event_performance_tracker = Event::PerformanceTracker(
+                    performance_tracker::Event::BlockAdded(block.clone()),
+                );
+                effects.extend(self.dispatch_event(effect_builder, rng, reactor_event_es));
+                effects.extend(self.dispatch_event(
+                    effect_builder,
+                    rng,
+                    reactor_event_performance_tracker,
+                ));
                 effects.extend(self.dispatch_event(effect_builder, rng, reactor_event_consensus));
+                effects.extend(self.dispatch_event(effect_builder, rng, reactor_event_rest));
                 effects.extend(self.dispatch_event(
                     effect_builder,
                     rng,
@@ -1050,8 +1718,39 @@ impl reactor::Reactor for Reactor {
                 effects
             }
             Event::LinearChainAnnouncement(LinearChainAnnouncement::NewFinalitySignature(fs)) => {
+                let reactor_event = Event::PerformanceTracker(
+                    performance_tracker::Event::FinalitySignature(fs.clone()),
+                );
+                let mut effects = self.dispatch_event(effect_builder, rng, reactor_event);
                 let reactor_event =
                     Event::EventStreamServer(event_stream_server::Event::FinalitySignature(fs));
+                effects.extend(self.dispatch_event(effect_builder, rng, reactor_event));
+                effects
+            }
+            Event::BlockProposerAnnouncement(BlockProposerAnnouncement::DeployReplaced {
+                replaced,
+                replacement,
+            }) => {
+                let reactor_event =
+                    Event::EventStreamServer(event_stream_server::Event::DeployReplaced {
+                        replaced,
+                        replacement,
+                    });
+                let mut effects = self.dispatch_event(effect_builder, rng, reactor_event);
+
+                let reactor_event = Event::DeployGossiper(gossiper::Event::RegossipItem {
+                    item_id: replacement,
+                });
+                effects.extend(self.dispatch_event(effect_builder, rng, reactor_event));
+                effects
+            }
+            Event::BlockProposerAnnouncement(BlockProposerAnnouncement::DeployEvicted {
+                deploy_hash,
+            }) => {
+                let reactor_event =
+                    Event::EventStreamServer(event_stream_server::Event::DeployEvicted {
+                        deploy_hash,
+                    });
                 self.dispatch_event(effect_builder, rng, reactor_event)
             }
             Event::ChainspecLoaderAnnouncement(
@@ -1066,10 +1765,24 @@ impl reactor::Reactor for Reactor {
                     next_upgrade.activation_point(),
                 ));
                 effects.extend(self.dispatch_event(effect_builder, rng, reactor_event));
+
+                let reactor_event =
+                    Event::DeployAcceptor(deploy_acceptor::Event::GotUpgradeActivationPoint(
+                        next_upgrade.activation_point(),
+                    ));
+                effects.extend(self.dispatch_event(effect_builder, rng, reactor_event));
                 effects
             }
             Event::BlocklistAnnouncement(ann) => {
-                self.dispatch_event(effect_builder, rng, Event::SmallNetwork(ann.into()))
+                self.network
+                    .handle_blocklist_announcement(effect_builder, rng, ann)
+            }
+            Event::RequestLimiterAnnouncement(RequestLimiterAnnouncement::RequestAdmitted {
+                sender,
+                payload,
+            }) => {
+                let effects = self.serve_request(effect_builder, rng, sender, *payload);
+                request_limiter::track_serving(sender, effects)
             }
         }
     }
@@ -1085,16 +1798,65 @@ impl reactor::Reactor for Reactor {
             .stop_for_upgrade()
             .then(|| ReactorExit::ProcessShouldExit(ExitCode::Success))
     }
+
+    fn shutdown(&mut self, exit_code: ExitCode) -> BoxFuture<'static, ()> {
+        let reason = self.chainspec_loader.shutdown_reason(exit_code);
+        let event_stream_stopped = self.event_stream_server.shutdown(reason.clone());
+        self.rpc_server.shutdown(reason);
+        let writes_flushed = self.storage.flush();
+        async move {
+            join!(event_stream_stopped, writes_flushed);
+        }
+        .boxed()
+    }
+
+    fn event_queue_weights(config: &Self::Config) -> EventQueueWeights {
+        config.config.node.event_queue_weights
+    }
+
+    fn failure_policies(config: &Self::Config) -> FailurePolicies {
+        config.config.node.component_failure_policies.clone()
+    }
+
+    fn restart_component(
+        &mut self,
+        component: &str,
+        effect_builder: EffectBuilder<Self::Event>,
+        _rng: &mut NodeRng,
+    ) -> Option<Effects<Self::Event>> {
+        match component {
+            // The performance tracker rebuilds all its state from storage.
+            "performance_tracker" => {
+                let (performance_tracker, effects) = PerformanceTracker::new(
+                    self.performance_tracker_config,
+                    &self.storage,
+                    genesis_validator_weights(self.chainspec_loader.chainspec()),
+                    effect_builder,
+                )
+                .map_err(|error| error!(%error, "failed to restart performance tracker"))
+                .ok()?;
+                self.performance_tracker = performance_tracker;
+                Some(reactor::wrap_effects(Event::PerformanceTracker, effects))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Returns the weights of the validators in era 0, as set in the chainspec.
+fn genesis_validator_weights(chainspec: &Chainspec) -> BTreeMap<PublicKey, U512> {
+    chainspec
+        .network_config
+        .chainspec_validator_stakes()
+        .into_iter()
+        .map(|(public_key, stake)| (public_key, stake.value()))
+        .collect()
 }
 
 #[cfg(test)]
 impl NetworkedReactor for Reactor {
     type NodeId = NodeId;
     fn node_id(&self) -> Self::NodeId {
-        if env::var(ENABLE_LIBP2P_NET_ENV_VAR).is_ok() {
-            self.network.node_id()
-        } else {
-            self.small_network.node_id()
-        }
+        self.network.node_id()
     }
 }
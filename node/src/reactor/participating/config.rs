@@ -1,10 +1,17 @@
+use std::path::Path;
+
 use datasize::DataSize;
 use serde::Deserialize;
 
 use crate::{
-    logging::LoggingConfig, types::NodeConfig, ConsensusConfig, ContractRuntimeConfig,
-    DeployAcceptorConfig, EventStreamServerConfig, FetcherConfig, GossipConfig, RestServerConfig,
-    RpcServerConfig, SmallNetworkConfig, StorageConfig,
+    config_overrides::{self, ConfigOverride},
+    config_validation::InvalidConfigError,
+    logging::LoggingConfig,
+    types::NodeConfig,
+    BlockProposerConfig, ConsensusConfig, ContractRuntimeConfig, DeployAcceptorConfig,
+    DeployPropagationConfig, EventStreamServerConfig, FetcherConfig, GossipConfig,
+    PerformanceTrackerConfig, RequestLimiterConfig, RestServerConfig, RpcServerConfig,
+    SmallNetworkConfig, StorageConfig,
 };
 
 /// Root configuration.
@@ -36,4 +43,227 @@ pub struct Config {
     pub contract_runtime: ContractRuntimeConfig,
     /// Deploy acceptor configuration.
     pub deploy_acceptor: DeployAcceptorConfig,
+    /// Block proposer configuration.
+    pub block_proposer: BlockProposerConfig,
+    /// Validator performance tracker configuration.
+    pub performance_tracker: PerformanceTrackerConfig,
+    /// Request limiter configuration.
+    pub request_limiter: RequestLimiterConfig,
+    /// Deploy propagation measurement configuration.
+    pub deploy_propagation: DeployPropagationConfig,
+}
+
+impl Config {
+    /// Checks whether the values set in the config make sense, returning every problem found.
+    ///
+    /// Relative paths are resolved against `root`, the directory of the config file.
+    pub fn validate(&self, root: &Path) -> Result<(), InvalidConfigError> {
+        let mut errors = vec![];
+        self.network.validate(&mut errors);
+        self.event_stream_server.validate(&mut errors);
+        self.rest_server.validate(&mut errors);
+        self.rpc_server.validate(&mut errors);
+        self.storage.validate(root, &mut errors);
+        self.gossip.validate(&mut errors);
+        self.fetcher.validate(&mut errors);
+        self.request_limiter.validate(&mut errors);
+        self.deploy_propagation.validate(&mut errors);
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(InvalidConfigError(errors))
+        }
+    }
+
+    /// Returns the settings whose values differ from their defaults, ordered by section and then
+    /// by name, with secrets redacted.
+    pub fn overrides(&self) -> Vec<ConfigOverride> {
+        let default = Config::default();
+        let mut overrides = vec![];
+        macro_rules! diff_sections {
+            ($($section:ident),*) => {
+                $(config_overrides::diff_section(
+                    stringify!($section),
+                    &self.$section,
+                    &default.$section,
+                    &mut overrides,
+                );)*
+            };
+        }
+        diff_sections!(
+            node,
+            logging,
+            consensus,
+            network,
+            event_stream_server,
+            rest_server,
+            rpc_server,
+            storage,
+            gossip,
+            fetcher,
+            contract_runtime,
+            deploy_acceptor,
+            block_proposer,
+            performance_tracker,
+            request_limiter,
+            deploy_propagation
+        );
+        overrides
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use toml::Value;
+
+    use super::*;
+    use crate::{
+        config_validation::deserialize_config,
+        utils::{External, RESOURCES_PATH},
+    };
+
+    /// Returns the directory of the invalid config fixtures.
+    fn fixtures_dir() -> PathBuf {
+        RESOURCES_PATH.join("test/invalid/config")
+    }
+
+    /// Returns the local config as a TOML table, with the values of the given fixture overriding
+    /// those of the same sections and keys.
+    fn local_config_with(fixture: &str) -> Value {
+        let read = |path: PathBuf| -> Value {
+            toml::from_str(&fs::read_to_string(&path).unwrap())
+                .unwrap_or_else(|error| panic!("should parse {}: {}", path.display(), error))
+        };
+        let mut table = read(RESOURCES_PATH.join("local/config.toml"));
+        let overrides = read(fixtures_dir().join(format!("{}.toml", fixture)));
+        for (section, values) in overrides.as_table().unwrap() {
+            let section_table = table
+                .get_mut(section)
+                .and_then(Value::as_table_mut)
+                .unwrap_or_else(|| panic!("local config should have section {}", section));
+            for (key, value) in values.as_table().unwrap() {
+                section_table.insert(key.clone(), value.clone());
+            }
+        }
+        table
+    }
+
+    /// Validates the local config overridden by the given fixture, and returns the problems found.
+    fn validation_errors(fixture: &str) -> InvalidConfigError {
+        let config: Config =
+            deserialize_config(local_config_with(fixture)).expect("should deserialize");
+        config
+            .validate(&fixtures_dir())
+            .expect_err("should be invalid")
+    }
+
+    fn invalid_fields(fixture: &str) -> Vec<String> {
+        validation_errors(fixture)
+            .0
+            .iter()
+            .map(|error| error.field().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn local_config_should_be_valid() {
+        let table =
+            toml::from_str(&fs::read_to_string(RESOURCES_PATH.join("local/config.toml")).unwrap())
+                .unwrap();
+        let config: Config = deserialize_config(table).unwrap();
+        assert_eq!(config.validate(&RESOURCES_PATH.join("local")), Ok(()));
+    }
+
+    #[test]
+    fn should_report_all_invalid_addresses() {
+        assert_eq!(
+            invalid_fields("addresses"),
+            vec![
+                "network.bind_address",
+                "network.public_address",
+                "network.known_addresses[1]",
+                "network.known_addresses[2]",
+                "event_stream_server.address",
+                "rest_server.address",
+            ]
+        );
+    }
+
+    #[test]
+    fn should_report_all_out_of_range_values() {
+        assert_eq!(
+            invalid_fields("out_of_range"),
+            vec![
+                "network.gossip_interval",
+                "network.request_timeout",
+                "network.max_one_way_message_size",
+                "rest_server.qps_limit",
+                "rpc_server.qps_limit",
+                "storage.max_state_store_size",
+                "storage.lmdb_utilization_warning_percent",
+                "gossip.infection_target",
+                "gossip.get_remainder_timeout_secs",
+                "fetcher.get_from_peer_timeout",
+                "fetcher.max_in_flight_fetches",
+                "request_limiter.queue_timeout",
+                "deploy_propagation.probe_interval",
+                "deploy_propagation.deploys_per_probe",
+            ]
+        );
+    }
+
+    #[test]
+    fn should_report_unusable_storage_dir() {
+        assert_eq!(invalid_fields("storage"), vec!["storage.path"]);
+    }
+
+    #[test]
+    fn should_list_all_inconsistent_values() {
+        assert_eq!(
+            validation_errors("inconsistent").to_string(),
+            "config has 2 problem(s):\n  \
+            [network.max_gossip_message_size] is 2097152, but must not be greater than \
+            [network.max_one_way_message_size] 1048576\n  \
+            [event_stream_server.max_concurrent_websocket_subscribers] is 20, but must not be \
+            greater than [event_stream_server.max_concurrent_subscribers] 10"
+        );
+    }
+
+    #[test]
+    fn should_list_only_overridden_settings() {
+        let mut config = Config::default();
+        assert!(config.overrides().is_empty());
+
+        config.consensus.secret_key_path = External::from_path("secret_keys/secret_key.pem");
+        config.network.bind_address = String::from("127.0.0.1:34553");
+        config.network.known_addresses = vec![String::from("127.0.0.1:34554")];
+        config.rest_server.qps_limit = 7;
+
+        let overrides: Vec<String> = config.overrides().iter().map(ToString::to_string).collect();
+        assert_eq!(
+            overrides,
+            vec![
+                r#"consensus.secret_key_path = "secret_keys/secret_key.pem" (default <unset>)"#,
+                r#"network.bind_address = "127.0.0.1:34553" (default "0.0.0.0:34553")"#,
+                r#"network.known_addresses = ["127.0.0.1:34554"] (default [])"#,
+                "rest_server.qps_limit = 7 (default 100)",
+            ]
+        );
+    }
+
+    #[test]
+    fn should_report_path_of_malformed_values() {
+        for (fixture, expected_field) in &[
+            ("malformed_duration", "network.isolation_reconnect_delay"),
+            ("malformed_size", "network.max_one_way_message_size"),
+        ] {
+            let error = deserialize_config::<Config>(local_config_with(fixture))
+                .expect_err("should fail to deserialize");
+            assert_eq!(error.0.len(), 1);
+            assert_eq!(error.0[0].field(), *expected_field);
+        }
+    }
 }
@@ -1,7 +1,9 @@
 use thiserror::Error;
 
 use crate::{
-    components::{contract_runtime, network, small_network, storage},
+    components::{
+        contract_runtime, deploy_acceptor, event_stream_server, network_provider, storage,
+    },
     utils::ListeningError,
 };
 
@@ -12,13 +14,9 @@ pub enum Error {
     #[error("prometheus (metrics) error: {0}")]
     Metrics(#[from] prometheus::Error),
 
-    /// `Network` component error.
-    #[error("network error: {0}")]
-    Network(#[from] network::Error),
-
-    /// `SmallNetwork` component error.
-    #[error("small network error: {0}")]
-    SmallNetwork(#[from] small_network::Error),
+    /// Networking component error.
+    #[error("network provider error: {0}")]
+    NetworkProvider(#[from] network_provider::ConstructionError),
 
     /// An error starting one of the HTTP servers.
     #[error("http server listening error: {0}")]
@@ -32,6 +30,14 @@ pub enum Error {
     #[error("consensus error: {0}")]
     Consensus(#[from] anyhow::Error),
 
+    /// `DeployAcceptor` component error.
+    #[error("deploy acceptor error: {0}")]
+    DeployAcceptor(#[from] deploy_acceptor::ConstructionError),
+
+    /// `EventStreamServer` component error.
+    #[error("event stream server error: {0}")]
+    EventStreamServer(#[from] event_stream_server::ConstructionError),
+
     /// `ContractRuntime` component error.
     #[error("contract runtime config error: {0}")]
     ContractRuntime(#[from] contract_runtime::ConfigError),
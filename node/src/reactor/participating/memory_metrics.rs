@@ -1,11 +1,9 @@
-use std::env;
-
 use datasize::DataSize;
 use prometheus::{self, Histogram, HistogramOpts, IntGauge, Registry};
 use tracing::debug;
 
 use super::Reactor;
-use crate::{components::network::ENABLE_LIBP2P_NET_ENV_VAR, unregister_metric};
+use crate::unregister_metric;
 
 /// Metrics for memory usage.
 #[derive(Debug)]
@@ -147,11 +145,7 @@ impl MemoryMetrics {
         let timer = self.mem_estimator_runtime_s.start_timer();
 
         let metrics = reactor.metrics.estimate_heap_size() as i64;
-        let net = if env::var(ENABLE_LIBP2P_NET_ENV_VAR).is_ok() {
-            reactor.network.estimate_heap_size() as i64
-        } else {
-            reactor.small_network.estimate_heap_size() as i64
-        };
+        let net = reactor.network.estimate_heap_size() as i64;
         let address_gossiper = reactor.address_gossiper.estimate_heap_size() as i64;
         let storage = reactor.storage.estimate_heap_size() as i64;
         let contract_runtime = reactor.contract_runtime.estimate_heap_size() as i64;
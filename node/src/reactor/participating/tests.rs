@@ -1,4 +1,9 @@
-use std::{collections::BTreeMap, sync::Arc, time::Duration};
+use std::{
+    collections::BTreeMap,
+    future::Future,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
 use anyhow::bail;
 use log::info;
@@ -7,17 +12,35 @@ use num_rational::Ratio;
 use rand::Rng;
 use tempfile::TempDir;
 
-use casper_execution_engine::shared::motes::Motes;
-use casper_types::{system::auction::DelegationRate, EraId, PublicKey, SecretKey, U512};
+use casper_execution_engine::{
+    core::engine_state::{
+        executable_deploy_item::ExecutableDeployItem, GetBidsRequest, GetBidsResult,
+    },
+    shared::motes::Motes,
+};
+use casper_types::{
+    account::AccountHash,
+    bytesrepr::Bytes,
+    runtime_args,
+    system::{
+        auction::{self, DelegationRate},
+        mint, standard_payment,
+    },
+    AsymmetricType, ContractHash, EraId, PublicKey, RuntimeArgs, SecretKey, Transfer, U512,
+};
 
 use crate::{
-    components::{consensus, gossiper, small_network, storage},
+    components::{
+        consensus, contract_runtime::EraValidatorsRequest, gossiper, small_network, storage,
+    },
     crypto::AsymmetricKeyExt,
+    effect::{EffectBuilder, EffectExt},
     reactor::{initializer, joiner, participating, ReactorExit, Runner},
-    testing::{self, network::Network, TestRng},
+    testing::{self, network::Network, ConditionCheckReactor, TestRng},
     types::{
         chainspec::{AccountConfig, AccountsConfig, ValidatorConfig},
-        ActivationPoint, Chainspec, Timestamp,
+        json_compatibility::AuctionState,
+        ActivationPoint, Chainspec, Deploy, NodeId, TimeDiff, Timestamp, ValidatorChange,
     },
     utils::{External, Loadable, WithDir, RESOURCES_PATH},
     NodeRng,
@@ -85,7 +108,7 @@ impl TestChain {
 
         chainspec.core_config.minimum_era_height = 1;
         chainspec.highway_config.finality_threshold_fraction = Ratio::new(34, 100);
-        chainspec.core_config.era_duration = 10.into();
+        chainspec.core_config.era_duration = 0.into();
         chainspec.core_config.auction_delay = 1;
         chainspec.core_config.unbonding_delay = 3;
 
@@ -96,6 +119,16 @@ impl TestChain {
         }
     }
 
+    /// Adds a genesis account with the given balance, which is not a validator.
+    fn add_account(&mut self, public_key: PublicKey, balance: U512) {
+        let chainspec = Arc::get_mut(&mut self.chainspec).expect("chainspec should not be shared");
+        let accounts_config = &chainspec.network_config.accounts_config;
+        let mut accounts = accounts_config.accounts().to_vec();
+        accounts.push(AccountConfig::new(public_key, Motes::new(balance), None));
+        let delegators = accounts_config.delegators().to_vec();
+        chainspec.network_config.accounts_config = AccountsConfig::new(accounts, delegators);
+    }
+
     /// Creates an initializer/validator configuration for the `idx`th validator.
     fn create_node_config(&mut self, idx: usize, first_node_port: u16) -> participating::Config {
         // Set the network configuration.
@@ -199,6 +232,501 @@ async fn run_participating_network() {
         .await;
 }
 
+#[tokio::test]
+async fn should_track_validator_performance() {
+    testing::init_logging();
+
+    let mut rng = crate::new_rng();
+
+    // Three validators run nodes, while a fourth with a small stake is bonded but offline.
+    const NETWORK_SIZE: usize = 3;
+    let keys: Vec<Arc<SecretKey>> = (0..NETWORK_SIZE)
+        .map(|_| Arc::new(SecretKey::random(&mut rng)))
+        .collect();
+    let mut stakes: BTreeMap<PublicKey, U512> = keys
+        .iter()
+        .map(|secret_key| (PublicKey::from(&**secret_key), U512::from(100)))
+        .collect();
+    let offline_public_key = PublicKey::from(&SecretKey::random(&mut rng));
+    stakes.insert(offline_public_key.clone(), U512::from(1));
+    let online_public_keys: Vec<PublicKey> = keys
+        .iter()
+        .map(|secret_key| PublicKey::from(&**secret_key))
+        .collect();
+
+    let mut chain = TestChain::new_with_keys(&mut rng, keys, stakes);
+    let mut net = chain
+        .create_initialized_network(&mut rng)
+        .await
+        .expect("network initialization failed");
+
+    net.settle_on(
+        &mut rng,
+        is_in_era(EraId::from(3)),
+        Duration::from_secs(180),
+    )
+    .await;
+
+    // All nodes should have tracked era 1, in which every genesis validator was bonded.  Each era
+    // consists of just its switch block, and signatures of the genesis era's only block can't be
+    // checked against that era's validators, so that era isn't used.
+    let era_id = EraId::from(1);
+    for runner in net.nodes().values() {
+        let stored = runner
+            .reactor()
+            .inner()
+            .storage()
+            .read_era_performance(era_id..=era_id, None)
+            .expect("should read validator performance");
+        let performance = &stored[&era_id];
+
+        let offline = &performance[&offline_public_key];
+        assert_eq!(offline.blocks_proposed, 0);
+        assert_eq!(offline.finality_signatures_provided, 0);
+        assert!(offline.finality_signatures_missed() > 0);
+
+        for public_key in &online_public_keys {
+            assert!(performance[public_key].finality_signatures_provided > 0);
+            assert!(!performance[public_key].equivocated);
+        }
+    }
+}
+
+#[tokio::test]
+async fn should_report_era_progress() {
+    testing::init_logging();
+
+    const NETWORK_SIZE: usize = 3;
+    const ERA_DURATION: TimeDiff = TimeDiff::from_seconds(20);
+    const MINIMUM_ERA_HEIGHT: u64 = 2;
+
+    let mut rng = crate::new_rng();
+
+    let mut chain = TestChain::new(&mut rng, NETWORK_SIZE);
+    {
+        let chainspec = Arc::get_mut(&mut chain.chainspec).expect("chainspec should not be shared");
+        chainspec.core_config.era_duration = ERA_DURATION;
+        chainspec.core_config.minimum_era_height = MINIMUM_ERA_HEIGHT;
+    }
+
+    let mut net = chain
+        .create_initialized_network(&mut rng)
+        .await
+        .expect("network initialization failed");
+
+    let era_id = EraId::from(1);
+    net.settle_on(&mut rng, is_in_era(era_id), Duration::from_secs(120))
+        .await;
+
+    // Sample the era's progress as seen by one node, once at the start of the era and once some
+    // blocks have been finalized in it.
+    let era_progress = |nodes: &Nodes| {
+        nodes
+            .values()
+            .next()
+            .unwrap()
+            .reactor()
+            .inner()
+            .consensus()
+            .era_progress(Timestamp::now())
+            .expect("current era should be active")
+    };
+    let mut samples = vec![era_progress(net.nodes())];
+    net.settle_on(
+        &mut rng,
+        |nodes: &Nodes| era_progress(nodes).era_height >= MINIMUM_ERA_HEIGHT,
+        Duration::from_secs(60),
+    )
+    .await;
+    samples.push(era_progress(net.nodes()));
+
+    net.settle_on(
+        &mut rng,
+        is_in_era(era_id.successor()),
+        Duration::from_secs(60),
+    )
+    .await;
+
+    // The era ends with its switch block.
+    let actual_era_end = net
+        .nodes()
+        .values()
+        .next()
+        .unwrap()
+        .reactor()
+        .inner()
+        .storage()
+        .transactional_get_switch_block_by_era_id(era_id.value())
+        .expect("missing switch block")
+        .header()
+        .timestamp();
+
+    for progress in samples {
+        assert_eq!(progress.era_id, era_id);
+        assert_eq!(progress.era_duration, ERA_DURATION);
+        assert_eq!(progress.minimum_era_height, MINIMUM_ERA_HEIGHT);
+        assert!(progress.era_start <= actual_era_end);
+
+        // The switch block is proposed in the first round after the era's conditions are met, so
+        // allow for a few rounds of difference.
+        let round_length = progress
+            .round_length
+            .expect("validators should know the round length");
+        let tolerance = round_length * 3;
+        let estimated_era_end = progress
+            .estimated_era_end
+            .expect("era end should be estimated");
+        assert!(
+            estimated_era_end.saturating_diff(actual_era_end) <= tolerance
+                && actual_era_end.saturating_diff(estimated_era_end) <= tolerance,
+            "estimated era end {} too far from actual era end {}",
+            estimated_era_end,
+            actual_era_end
+        );
+    }
+}
+
+/// Creates a native transfer deploy from `sender` to `target`, tagged with the given `id`.
+fn new_native_transfer(
+    chainspec: &Chainspec,
+    sender: &SecretKey,
+    target: AccountHash,
+    amount: U512,
+    id: u64,
+) -> Deploy {
+    let payment = ExecutableDeployItem::ModuleBytes {
+        module_bytes: Bytes::new(),
+        args: runtime_args! { standard_payment::ARG_AMOUNT => U512::from(10_000) },
+    };
+    let session = ExecutableDeployItem::Transfer {
+        args: runtime_args! {
+            mint::ARG_AMOUNT => amount,
+            mint::ARG_TARGET => target,
+            mint::ARG_ID => Some(id),
+        },
+    };
+    Deploy::new(
+        Timestamp::now(),
+        TimeDiff::from_seconds(600),
+        1,
+        vec![],
+        chainspec.network_config.name.clone(),
+        payment,
+        session,
+        sender,
+    )
+}
+
+/// Returns the transfers stored for every block the node has executed so far, keyed by height.
+fn stored_block_transfers(
+    runner: &Runner<ConditionCheckReactor<participating::Reactor>>,
+) -> BTreeMap<u64, Vec<Transfer>> {
+    let storage = runner.reactor().inner().storage();
+    let mut block_transfers = BTreeMap::new();
+    for height in 0.. {
+        let block_hash = match storage
+            .read_block_header_and_finality_signatures_by_height(height)
+            .expect("should read block header")
+        {
+            Some(header_with_metadata) => header_with_metadata.block_header.hash(),
+            None => break,
+        };
+        // Blocks are stored before their execution results, so the newest block might not have
+        // any transfers recorded yet.
+        if let Some(transfers) = storage
+            .read_block_transfers(&block_hash)
+            .expect("should read block transfers")
+        {
+            block_transfers.insert(height, transfers);
+        }
+    }
+    block_transfers
+}
+
+#[tokio::test]
+async fn should_store_executed_transfers_per_block() {
+    testing::init_logging();
+
+    const NETWORK_SIZE: usize = 3;
+    const TRANSFER_COUNT: u64 = 3;
+
+    let mut rng = crate::new_rng();
+
+    // Fund a sender account which is not a validator.
+    let sender = SecretKey::random(&mut rng);
+    let mut chain = TestChain::new(&mut rng, NETWORK_SIZE);
+    chain.add_account(PublicKey::from(&sender), U512::from(u64::MAX));
+
+    let mut net = chain
+        .create_initialized_network(&mut rng)
+        .await
+        .expect("network initialization failed");
+
+    net.settle_on(&mut rng, is_in_era(EraId::from(1)), Duration::from_secs(90))
+        .await;
+
+    // Submit a few transfers with distinct amounts and ids to one of the nodes.
+    let minimum_amount = U512::from(chain.chainspec.deploy_config.native_transfer_minimum_motes);
+    let mut expected = BTreeMap::new();
+    let node_id = *net.nodes().keys().next().unwrap();
+    for id in 1..=TRANSFER_COUNT {
+        let target = PublicKey::from(&SecretKey::random(&mut rng)).to_account_hash();
+        let amount = minimum_amount + U512::from(id);
+        let deploy = new_native_transfer(&chain.chainspec, &sender, target, amount, id);
+        let deploy_hash = casper_types::DeployHash::new(deploy.id().inner().to_array());
+        expected.insert(deploy_hash, (target, amount, id));
+        net.process_injected_effect_on(&node_id, |effect_builder| {
+            effect_builder
+                .announce_deploy_received(Box::new(deploy), None)
+                .ignore()
+        })
+        .await;
+    }
+
+    // Wait until every node has executed all of the transfers.
+    let all_executed = |nodes: &Nodes| {
+        nodes.values().all(|runner| {
+            stored_block_transfers(runner).values().flatten().count() == expected.len()
+        })
+    };
+    net.settle_on(&mut rng, all_executed, Duration::from_secs(90))
+        .await;
+
+    for runner in net.nodes().values() {
+        let block_transfers = stored_block_transfers(runner);
+
+        // The first block was executed before any transfers were submitted, so it is recorded as
+        // having none.
+        assert_eq!(block_transfers.get(&0), Some(&vec![]));
+
+        let mut remaining = expected.clone();
+        for transfer in block_transfers.values().flatten() {
+            let (target, amount, id) = remaining
+                .remove(&transfer.deploy_hash)
+                .expect("unexpected transfer");
+            assert_eq!(transfer.from, PublicKey::from(&sender).to_account_hash());
+            assert_eq!(transfer.to, Some(target));
+            assert_eq!(transfer.amount, amount);
+            assert_eq!(transfer.id, Some(id));
+        }
+        assert!(remaining.is_empty());
+    }
+}
+
+/// Runs `query` on the given node via an injected effect, returning its output once the network has
+/// processed it.
+async fn query_node<T, F, Fut>(
+    net: &mut Network<participating::Reactor>,
+    rng: &mut TestRng,
+    node_id: &NodeId,
+    query: F,
+) -> T
+where
+    F: FnOnce(EffectBuilder<participating::Event>) -> Fut,
+    Fut: Future<Output = T> + Send + 'static,
+    T: Send + 'static,
+{
+    let output = Arc::new(Mutex::new(None));
+    let output_clone = Arc::clone(&output);
+    net.process_injected_effect_on(node_id, move |effect_builder| {
+        let query = query(effect_builder);
+        async move {
+            *output_clone.lock().unwrap() = Some(query.await);
+        }
+        .ignore()
+    })
+    .await;
+    net.settle_on(
+        rng,
+        |_| output.lock().unwrap().is_some(),
+        Duration::from_secs(30),
+    )
+    .await;
+    let result = output.lock().unwrap().take();
+    result.expect("query should have completed")
+}
+
+/// Creates a deploy bidding `amount` from `bidder`'s account by calling the auction contract.
+fn new_add_bid(
+    chainspec: &Chainspec,
+    bidder: &SecretKey,
+    auction_hash: ContractHash,
+    amount: U512,
+    delegation_rate: DelegationRate,
+) -> Deploy {
+    let payment = ExecutableDeployItem::ModuleBytes {
+        module_bytes: Bytes::new(),
+        args: runtime_args! { standard_payment::ARG_AMOUNT => U512::from(10_000_000_000u64) },
+    };
+    let session = ExecutableDeployItem::StoredContractByHash {
+        hash: auction_hash,
+        entry_point: auction::METHOD_ADD_BID.to_string(),
+        args: runtime_args! {
+            auction::ARG_PUBLIC_KEY => PublicKey::from(bidder),
+            auction::ARG_AMOUNT => amount,
+            auction::ARG_DELEGATION_RATE => delegation_rate,
+        },
+    };
+    Deploy::new(
+        Timestamp::now(),
+        TimeDiff::from_seconds(600),
+        1,
+        vec![],
+        chainspec.network_config.name.clone(),
+        payment,
+        session,
+        bidder,
+    )
+}
+
+#[tokio::test]
+async fn should_reflect_new_bid_in_auction_info_and_validator_changes() {
+    testing::init_logging();
+
+    const NETWORK_SIZE: usize = 3;
+
+    let mut rng = crate::new_rng();
+
+    // Fund a bidder account which is not a validator.
+    let bidder = SecretKey::random(&mut rng);
+    let bidder_public_key = PublicKey::from(&bidder);
+    let mut chain = TestChain::new(&mut rng, NETWORK_SIZE);
+    chain.add_account(bidder_public_key.clone(), U512::from(u64::MAX));
+
+    let mut net = chain
+        .create_initialized_network(&mut rng)
+        .await
+        .expect("network initialization failed");
+
+    net.settle_on(&mut rng, is_in_era(EraId::from(1)), Duration::from_secs(90))
+        .await;
+
+    // Bid via the auction contract, whose hash is read from one of the nodes.
+    let node_id = *net.nodes().keys().next().unwrap();
+    let protocol_version = chain.chainspec.protocol_config.version;
+    let auction_hash = query_node(&mut net, &mut rng, &node_id, move |effect_builder| {
+        effect_builder.get_protocol_data(protocol_version)
+    })
+    .await
+    .expect("should get protocol data")
+    .expect("should have protocol data")
+    .auction();
+    let bid_amount = U512::from(1_000_000_000_000u64);
+    let delegation_rate: DelegationRate = 10;
+    let deploy = new_add_bid(
+        &chain.chainspec,
+        &bidder,
+        auction_hash,
+        bid_amount,
+        delegation_rate,
+    );
+    net.process_injected_effect_on(&node_id, |effect_builder| {
+        effect_builder
+            .announce_deploy_received(Box::new(deploy), None)
+            .ignore()
+    })
+    .await;
+
+    // Wait until every node has recorded the bidder joining the validator set.
+    let all_eras = EraId::from(0)..=EraId::from(u64::MAX);
+    let bidder_added = |nodes: &Nodes| {
+        nodes.values().all(|runner| {
+            runner
+                .reactor()
+                .inner()
+                .storage()
+                .read_validator_changes(all_eras.clone())
+                .expect("should read validator changes")
+                .values()
+                .any(|changes| changes.contains_key(&bidder_public_key))
+        })
+    };
+    net.settle_on(&mut rng, bidder_added, Duration::from_secs(300))
+        .await;
+
+    // The bid was placed in era 1 or later, so the bidder can only join in era 3 or later, given
+    // the auction delay of 1.
+    let expected_change = ValidatorChange::Added { weight: bid_amount };
+    let mut added_era_ids = Vec::new();
+    for runner in net.nodes().values() {
+        let stored = runner
+            .reactor()
+            .inner()
+            .storage()
+            .read_validator_changes(all_eras.clone())
+            .expect("should read validator changes");
+        let (era_id, changes) = stored
+            .iter()
+            .find_map(|(era_id, changes)| Some((*era_id, changes.get(&bidder_public_key)?)))
+            .expect("should have bidder's changes");
+        assert!(era_id >= EraId::from(3));
+        assert_eq!(changes, &vec![expected_change.clone()]);
+        added_era_ids.push(era_id);
+    }
+    added_era_ids.dedup();
+    assert_eq!(added_era_ids.len(), 1);
+    let added_era_id = added_era_ids[0];
+    assert_eq!(
+        serde_json::to_value(&expected_change).unwrap(),
+        serde_json::json!({ "Added": { "weight": "1000000000000" } })
+    );
+
+    // The auction state as of the switch block which recorded the bidder as a validator of the
+    // next era holds the bid, and lists the bidder as a validator in that era.
+    let block_header = net.nodes()[&node_id]
+        .reactor()
+        .inner()
+        .storage()
+        .read_switch_block_header_by_era_id(added_era_id.checked_sub(1).unwrap())
+        .expect("should read switch block header")
+        .expect("should have switch block header");
+    let state_root_hash = *block_header.state_root_hash();
+    let bids = query_node(&mut net, &mut rng, &node_id, move |effect_builder| {
+        effect_builder.get_bids(GetBidsRequest::new(state_root_hash.into()))
+    })
+    .await
+    .ok()
+    .and_then(|result| match result {
+        GetBidsResult::Success { bids } => Some(bids),
+        GetBidsResult::RootNotFound => None,
+    });
+    let era_validators = query_node(&mut net, &mut rng, &node_id, move |effect_builder| {
+        effect_builder.get_era_validators_from_contract_runtime(EraValidatorsRequest::new(
+            state_root_hash.into(),
+            protocol_version,
+        ))
+    })
+    .await
+    .ok();
+    let auction_state =
+        AuctionState::new(state_root_hash, block_header.height(), era_validators, bids);
+    let json = serde_json::to_value(&auction_state).unwrap();
+    let bidder_hex = bidder_public_key.to_hex();
+
+    let bid = json["bids"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|bid| bid["public_key"] == bidder_hex.as_str())
+        .expect("should have bidder's bid");
+    assert_eq!(bid["bid"]["staked_amount"], bid_amount.to_string().as_str());
+    assert_eq!(bid["bid"]["delegation_rate"], delegation_rate);
+
+    let era_validators = json["era_validators"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|era| era["era_id"] == added_era_id.value())
+        .expect("should have validators of the era the bidder joined");
+    let bidder_weight = era_validators["validator_weights"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|weights| weights["public_key"] == bidder_hex.as_str())
+        .expect("should have bidder's weight");
+    assert_eq!(bidder_weight["weight"], bid_amount.to_string().as_str());
+}
+
 // TODO: fix this test
 #[tokio::test]
 async fn run_equivocator_network() {
@@ -3,11 +3,15 @@
 //! The reactor's event queue uses different queues to group events by priority and polls them in a
 //! round-robin manner. This way, events are only competing for time within one queue, non-congested
 //! queues can always assume to be speedily processed.
+//!
+//! Control events are the exception: they are given strict priority over all other queues, bounded
+//! only by the control queue's weight to ensure the remaining queues are never starved.
 
 use std::{fmt::Display, num::NonZeroUsize};
 
+use datasize::DataSize;
 use enum_iterator::IntoEnumIterator;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 /// Scheduling priority.
 ///
@@ -16,6 +20,10 @@ use serde::Serialize;
 pub enum QueueKind {
     /// Control messages for the runtime itself.
     Control,
+    /// Events relating to the consensus protocol.
+    ///
+    /// These are time-sensitive, as delaying them can cause the node to miss rounds.
+    Consensus,
     /// Network events that were initiated outside of this node.
     ///
     /// Their load may vary and grouping them together in one queue aides DoS protection.
@@ -26,6 +34,8 @@ pub enum QueueKind {
     ///
     /// This is the default queue.
     Regular,
+    /// Gossip protocol events, which are mostly tolerant of delays.
+    Gossip,
     /// Reporting events on the local node.
     ///
     /// Metric events take precedence over most other events since missing a request for metrics
@@ -37,9 +47,11 @@ impl Display for QueueKind {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let str_value = match self {
             QueueKind::Control => "Control",
+            QueueKind::Consensus => "Consensus",
             QueueKind::NetworkIncoming => "NetworkIncoming",
             QueueKind::Network => "Network",
             QueueKind::Regular => "Regular",
+            QueueKind::Gossip => "Gossip",
             QueueKind::Api => "Api",
         };
         write!(f, "{}", str_value)
@@ -53,36 +65,188 @@ impl Default for QueueKind {
 }
 
 impl QueueKind {
-    /// Returns the weight of a specific queue.
+    /// Returns the default weight of a specific queue.
     ///
     /// The weight determines how many events are at most processed from a specific queue during
     /// each event processing round.
-    fn weight(self) -> NonZeroUsize {
-        NonZeroUsize::new(match self {
+    fn default_weight(self) -> usize {
+        match self {
             // Note: Control events should be very rare, but we do want to process them right away.
             QueueKind::Control => 32,
+            QueueKind::Consensus => 16,
             QueueKind::NetworkIncoming => 4,
             QueueKind::Network => 4,
             QueueKind::Regular => 8,
+            QueueKind::Gossip => 4,
             QueueKind::Api => 16,
-        })
-        .expect("weight must be positive")
+        }
     }
 
-    /// Return weights of all possible `Queue`s.
+    /// Return default weights of all possible `Queue`s.
+    #[cfg(test)]
     pub(crate) fn weights() -> Vec<(Self, NonZeroUsize)> {
-        QueueKind::into_enum_iter()
-            .map(|q| (q, q.weight()))
-            .collect()
+        EventQueueWeights::default().weights()
     }
 
     pub(crate) fn metrics_name(&self) -> &str {
         match self {
             QueueKind::Control => "control",
+            QueueKind::Consensus => "consensus",
             QueueKind::NetworkIncoming => "network_incoming",
             QueueKind::Network => "network",
             QueueKind::Regular => "regular",
+            QueueKind::Gossip => "gossip",
             QueueKind::Api => "api",
         }
     }
 }
+
+/// Configured weights of the reactor's event queues.
+///
+/// A weight of zero is treated as one, so that every queue is guaranteed a minimum share of each
+/// event processing round. The control queue has strict priority over all other queues, its weight
+/// bounds the number of control events processed before yielding to another queue.
+#[derive(Copy, Clone, DataSize, Debug, Deserialize, Serialize)]
+// Disallow unknown fields to ensure config files and command-line overrides contain valid keys.
+#[serde(default, deny_unknown_fields)]
+pub struct EventQueueWeights {
+    /// Weight of the control queue.
+    pub control: usize,
+    /// Weight of the consensus queue.
+    pub consensus: usize,
+    /// Weight of the queue for incoming network events.
+    pub network_incoming: usize,
+    /// Weight of the queue for outgoing network events.
+    pub network: usize,
+    /// Weight of the regular queue.
+    pub regular: usize,
+    /// Weight of the gossip queue.
+    pub gossip: usize,
+    /// Weight of the API queue.
+    pub api: usize,
+}
+
+impl Default for EventQueueWeights {
+    fn default() -> Self {
+        EventQueueWeights {
+            control: QueueKind::Control.default_weight(),
+            consensus: QueueKind::Consensus.default_weight(),
+            network_incoming: QueueKind::NetworkIncoming.default_weight(),
+            network: QueueKind::Network.default_weight(),
+            regular: QueueKind::Regular.default_weight(),
+            gossip: QueueKind::Gossip.default_weight(),
+            api: QueueKind::Api.default_weight(),
+        }
+    }
+}
+
+impl EventQueueWeights {
+    /// Returns the weight of a specific queue.
+    fn weight(&self, queue_kind: QueueKind) -> NonZeroUsize {
+        let weight = match queue_kind {
+            QueueKind::Control => self.control,
+            QueueKind::Consensus => self.consensus,
+            QueueKind::NetworkIncoming => self.network_incoming,
+            QueueKind::Network => self.network,
+            QueueKind::Regular => self.regular,
+            QueueKind::Gossip => self.gossip,
+            QueueKind::Api => self.api,
+        };
+        NonZeroUsize::new(weight.max(1)).expect("weight must be positive")
+    }
+
+    /// Returns the weights of all possible `Queue`s, in scheduling order.
+    pub(crate) fn weights(&self) -> Vec<(QueueKind, NonZeroUsize)> {
+        QueueKind::into_enum_iter()
+            .map(|q| (q, self.weight(q)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reactor::new_scheduler;
+
+    /// Number of events used to flood a queue.
+    const FLOOD_SIZE: u32 = 10_000;
+
+    /// Returns the number of events popped in one full round of the scheduler.
+    fn round_length(weights: &EventQueueWeights) -> usize {
+        weights
+            .weights()
+            .iter()
+            .map(|(_, weight)| weight.get())
+            .sum()
+    }
+
+    #[tokio::test]
+    async fn should_dispatch_consensus_events_despite_flooded_regular_queue() {
+        let weights = EventQueueWeights::default();
+        let scheduler = new_scheduler::<u32>(&weights);
+        for event in 0..FLOOD_SIZE {
+            scheduler.push(event, QueueKind::Regular).await;
+        }
+
+        // Start processing the flood, then schedule a consensus event behind it.
+        assert_eq!(scheduler.pop().await, (0, QueueKind::Regular));
+        scheduler.push(FLOOD_SIZE, QueueKind::Consensus).await;
+
+        let mut iterations = 0;
+        loop {
+            iterations += 1;
+            let (event, queue_kind) = scheduler.pop().await;
+            if queue_kind == QueueKind::Consensus {
+                assert_eq!(event, FLOOD_SIZE);
+                break;
+            }
+            assert!(
+                iterations < round_length(&weights),
+                "consensus event not dispatched within one scheduling round"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn should_dispatch_control_events_first() {
+        let scheduler = new_scheduler::<u32>(&EventQueueWeights::default());
+        for event in 0..FLOOD_SIZE {
+            scheduler.push(event, QueueKind::Regular).await;
+            scheduler.push(event, QueueKind::Consensus).await;
+        }
+        scheduler.push(FLOOD_SIZE, QueueKind::Control).await;
+
+        assert_eq!(scheduler.pop().await, (FLOOD_SIZE, QueueKind::Control));
+    }
+
+    #[tokio::test]
+    async fn should_drain_lowest_queue_despite_flooded_higher_queues() {
+        // Zero weights are treated as one, so even an unweighted queue is not starved.
+        let weights = EventQueueWeights {
+            gossip: 0,
+            ..EventQueueWeights::default()
+        };
+        let scheduler = new_scheduler::<u32>(&weights);
+        for event in 0..FLOOD_SIZE {
+            scheduler.push(event, QueueKind::Control).await;
+            scheduler.push(event, QueueKind::Consensus).await;
+            scheduler.push(event, QueueKind::Regular).await;
+        }
+        scheduler.push(FLOOD_SIZE, QueueKind::Gossip).await;
+
+        // Control events take strict priority for up to a full control weight before each round
+        // robin step, so allow for those on top of one full round.
+        let bound = round_length(&weights) * (weights.control + 1);
+        let mut iterations = 0;
+        loop {
+            iterations += 1;
+            if scheduler.pop().await == (FLOOD_SIZE, QueueKind::Gossip) {
+                break;
+            }
+            assert!(
+                iterations < bound,
+                "gossip event not dispatched within bounded number of iterations"
+            );
+        }
+    }
+}
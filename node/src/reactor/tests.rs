@@ -0,0 +1,444 @@
+//! Tests of the shutdown sequence a reactor runs before the process exits, and of the handling of
+//! components which panic.
+
+use std::{
+    collections::HashMap,
+    convert::Infallible,
+    fmt::{self, Display, Formatter},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use derive_more::From;
+use futures::{future::BoxFuture, join, FutureExt};
+use prometheus::Registry;
+use reqwest::Response;
+use serde::Serialize;
+use tempfile::TempDir;
+use tokio::time;
+
+use casper_types::ProtocolVersion;
+
+use super::*;
+use crate::{
+    components::{
+        event_stream_server::{self, DeployGetter, EventStreamServer, SseData},
+        storage::{self, Storage},
+        Component,
+    },
+    effect::{
+        announcements::ControlAnnouncement, requests::StorageRequest, EffectBuilder, EffectExt,
+        Effects,
+    },
+    types::{Deploy, ExitCode},
+    utils::WithDir,
+    NodeRng,
+};
+
+/// The protocol version of the upgrade the test reactor stops for.
+const NEXT_VERSION: ProtocolVersion = ProtocolVersion::from_parts(1, 1, 0);
+
+/// The time within which an SSE client is expected to have received its whole stream.
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Test-reactor event.
+#[derive(Debug, From, Serialize)]
+enum Event {
+    #[from]
+    Storage(#[serde(skip_serializing)] storage::Event),
+    #[from]
+    StorageRequest(#[serde(skip_serializing)] StorageRequest),
+    #[from]
+    Panicker(PanickerEvent),
+    #[from]
+    ControlAnnouncement(ControlAnnouncement),
+}
+
+impl ReactorEvent for Event {
+    fn as_control(&self) -> Option<&ControlAnnouncement> {
+        if let Self::ControlAnnouncement(ref ctrl_ann) = self {
+            Some(ctrl_ann)
+        } else {
+            None
+        }
+    }
+
+    fn component_name(&self) -> &'static str {
+        match self {
+            Event::Storage(_) | Event::StorageRequest(_) => "storage",
+            Event::Panicker(_) => "panicker",
+            Event::ControlAnnouncement(_) => "reactor",
+        }
+    }
+}
+
+impl Display for Event {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, formatter)
+    }
+}
+
+/// Event of the panicking test component.
+#[derive(Debug, Serialize)]
+enum PanickerEvent {
+    /// An event handled without panicking.
+    Work,
+    /// An event whose handling panics.
+    Panic,
+}
+
+/// Test component which panics on request, after updating its state.
+#[derive(Debug, Default)]
+struct Panicker {
+    /// The number of events handled since the component was last (re)initialized.
+    events_handled: usize,
+    /// The number of times the component was reinitialized.
+    restarts: usize,
+}
+
+impl<REv> Component<REv> for Panicker {
+    type Event = PanickerEvent;
+    type ConstructionError = Infallible;
+
+    fn handle_event(
+        &mut self,
+        _effect_builder: EffectBuilder<REv>,
+        _rng: &mut NodeRng,
+        event: Self::Event,
+    ) -> Effects<Self::Event> {
+        self.events_handled += 1;
+        if let PanickerEvent::Panic = event {
+            panic!("poisoned by event {}", self.events_handled);
+        }
+        Effects::new()
+    }
+}
+
+/// The directory holding the test reactor's storage and event stream ID cache, shared across
+/// restarts, and the failure policies of its components.
+struct TestConfig {
+    root: PathBuf,
+    failure_policies: FailurePolicies,
+}
+
+impl TestConfig {
+    fn storage_config(&self) -> WithDir<storage::Config> {
+        let config = storage::Config::default_for_tests_at(self.root.join("lmdb"));
+        WithDir::new(&self.root, config)
+    }
+}
+
+/// Test reactor with storage, an event stream server and a component which panics on request,
+/// which stops for an upgrade to `NEXT_VERSION`.
+#[derive(Debug)]
+struct TestReactor {
+    storage: Storage,
+    event_stream_server: EventStreamServer,
+    panicker: Panicker,
+}
+
+impl Reactor for TestReactor {
+    type Event = Event;
+    type Config = TestConfig;
+    type Error = anyhow::Error;
+
+    fn new(
+        cfg: Self::Config,
+        registry: &Registry,
+        _event_queue: EventQueueHandle<Self::Event>,
+        _rng: &mut NodeRng,
+    ) -> Result<(Self, Effects<Self::Event>), Self::Error> {
+        let storage = Storage::new(
+            &cfg.storage_config(),
+            None,
+            ProtocolVersion::V1_0_0,
+            false,
+            registry,
+        )?;
+        let event_stream_server = EventStreamServer::new(
+            event_stream_server::Config::default(),
+            cfg.root,
+            ProtocolVersion::V1_0_0,
+            u32::MAX,
+            DeployGetter::with_deploys(HashMap::new()),
+            registry,
+        )?;
+        let reactor = TestReactor {
+            storage,
+            event_stream_server,
+            panicker: Panicker::default(),
+        };
+        Ok((reactor, Effects::new()))
+    }
+
+    fn dispatch_event(
+        &mut self,
+        effect_builder: EffectBuilder<Self::Event>,
+        rng: &mut NodeRng,
+        event: Self::Event,
+    ) -> Effects<Self::Event> {
+        match event {
+            Event::Storage(event) => wrap_effects(
+                Event::Storage,
+                self.storage.handle_event(effect_builder, rng, event),
+            ),
+            Event::StorageRequest(request) => {
+                self.dispatch_event(effect_builder, rng, Event::Storage(request.into()))
+            }
+            Event::Panicker(event) => wrap_effects(
+                Event::Panicker,
+                self.panicker.handle_event(effect_builder, rng, event),
+            ),
+            Event::ControlAnnouncement(ctrl_ann) => panic!("unexpected {}", ctrl_ann),
+        }
+    }
+
+    fn maybe_exit(&self) -> Option<ReactorExit> {
+        None
+    }
+
+    fn shutdown(&mut self, exit_code: ExitCode) -> BoxFuture<'static, ()> {
+        let reason = shutdown_reason(exit_code, Some(NEXT_VERSION));
+        let event_stream_stopped = self.event_stream_server.shutdown(reason);
+        let writes_flushed = self.storage.flush();
+        async move {
+            join!(event_stream_stopped, writes_flushed);
+        }
+        .boxed()
+    }
+
+    fn failure_policies(cfg: &Self::Config) -> FailurePolicies {
+        cfg.failure_policies.clone()
+    }
+
+    fn restart_component(
+        &mut self,
+        component: &str,
+        _effect_builder: EffectBuilder<Self::Event>,
+        _rng: &mut NodeRng,
+    ) -> Option<Effects<Self::Event>> {
+        if component != "panicker" {
+            return None;
+        }
+        self.panicker = Panicker {
+            events_handled: 0,
+            restarts: self.panicker.restarts + 1,
+        };
+        Some(Effects::new())
+    }
+}
+
+/// Subscribes to the test reactor's event stream at `/events/<final_path_element>`.
+async fn subscribe(runner: &Runner<TestReactor>, final_path_element: &str) -> Response {
+    let url = format!(
+        "http://{}/events/{}",
+        runner.reactor().event_stream_server.listening_address(),
+        final_path_element
+    );
+    reqwest::get(&url).await.expect("should subscribe")
+}
+
+/// Returns the data of the last event in the client's stream, waiting for the server to close it.
+async fn last_event_data(response: Response) -> String {
+    let response_text = time::timeout(TIMEOUT, response.text())
+        .await
+        .expect("stream should be closed by the server")
+        .expect("stream should not error");
+    response_text
+        .lines()
+        .filter_map(|line| line.strip_prefix("data:"))
+        .last()
+        .expect("should have received events")
+        .to_string()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn should_announce_shutdown_and_flush_storage() {
+    let mut rng = crate::new_rng();
+    let temp_dir = TempDir::new().unwrap();
+    let config = TestConfig {
+        root: temp_dir.path().to_path_buf(),
+        failure_policies: FailurePolicies::default(),
+    };
+    let mut runner = Runner::<TestReactor>::new(config, &mut rng).await.unwrap();
+
+    // Attach a client to each of the event streams.
+    let main_client = subscribe(&runner, "main").await;
+    let deploys_client = subscribe(&runner, "deploys").await;
+    let sigs_client = subscribe(&runner, "sigs").await;
+
+    // Store a deploy, which is only batched for writing by the time the storage request has been
+    // handled.
+    let deploy = Deploy::random(&mut rng);
+    let deploy_hash = *deploy.id();
+    runner
+        .process_injected_effects(|effect_builder| {
+            effect_builder
+                .put_deploy_to_storage(Box::new(deploy.clone()))
+                .ignore()
+        })
+        .await;
+    assert!(runner.crank(&mut rng).await);
+    assert!(runner.reactor().storage.has_pending_writes());
+
+    runner.shutdown(ExitCode::Success).await;
+
+    // Every client should have received the reason as the final event on its stream.
+    let expected_data = serde_json::to_string(&SseData::Shutdown {
+        reason: "upgrading to protocol version 1.1.0".to_string(),
+    })
+    .unwrap();
+    assert_eq!(last_event_data(main_client).await, expected_data);
+    assert_eq!(last_event_data(deploys_client).await, expected_data);
+    assert_eq!(last_event_data(sigs_client).await, expected_data);
+
+    // The deploy should have been committed, surviving a restart.
+    drop(runner);
+    let config = TestConfig {
+        root: temp_dir.path().to_path_buf(),
+        failure_policies: FailurePolicies::default(),
+    };
+    let storage = Storage::new(
+        &config.storage_config(),
+        None,
+        ProtocolVersion::V1_0_0,
+        false,
+        &Registry::new(),
+    )
+    .unwrap();
+    assert_eq!(storage.get_deploy_by_hash(deploy_hash), Some(deploy));
+}
+
+/// Returns the storage of the test reactor whose config has the given root, as after a restart.
+fn reopen_storage(root: &Path) -> Storage {
+    let config = TestConfig {
+        root: root.to_path_buf(),
+        failure_policies: FailurePolicies::default(),
+    };
+    Storage::new(
+        &config.storage_config(),
+        None,
+        ProtocolVersion::V1_0_0,
+        false,
+        &Registry::new(),
+    )
+    .unwrap()
+}
+
+/// Creates a runner for the test reactor, whose panicking component has the given failure policy.
+async fn runner_with_policy(
+    root: &Path,
+    policy: FailurePolicy,
+    rng: &mut NodeRng,
+) -> Runner<TestReactor> {
+    let config = TestConfig {
+        root: root.to_path_buf(),
+        failure_policies: FailurePolicies::default().with_policy("panicker", policy),
+    };
+    Runner::<TestReactor>::new(config, rng).await.unwrap()
+}
+
+/// Has the panicking test component handle the given event, returning whether the runner should
+/// keep going.
+async fn handle_by_panicker(
+    runner: &mut Runner<TestReactor>,
+    rng: &mut NodeRng,
+    event: PanickerEvent,
+) -> bool {
+    let panics = matches!(event, PanickerEvent::Panic);
+    runner
+        .process_injected_effects(|effect_builder| {
+            effect_builder
+                .immediately()
+                .event(move |_| Event::Panicker(event))
+        })
+        .await;
+    let keep_going = runner.crank(rng).await;
+    if !panics {
+        return keep_going;
+    }
+    // The panic is caught and announced, the announcement handled with the next event.
+    assert!(keep_going);
+    runner.crank(rng).await
+}
+
+/// Returns the number of failures of the panicking test component counted by the runner.
+fn panicker_failures(runner: &Runner<TestReactor>) -> u64 {
+    runner
+        .metrics
+        .component_failures
+        .with_label_values(&["panicker"])
+        .get()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn should_shut_down_and_flush_storage_on_panic_of_fatal_component() {
+    let mut rng = crate::new_rng();
+    let temp_dir = TempDir::new().unwrap();
+    let mut runner = runner_with_policy(temp_dir.path(), FailurePolicy::Fatal, &mut rng).await;
+
+    // Store a deploy, which is only batched for writing by the time the storage request has been
+    // handled.
+    let deploy = Deploy::random(&mut rng);
+    let deploy_hash = *deploy.id();
+    runner
+        .process_injected_effects(|effect_builder| {
+            effect_builder
+                .put_deploy_to_storage(Box::new(deploy.clone()))
+                .ignore()
+        })
+        .await;
+    assert!(runner.crank(&mut rng).await);
+
+    assert!(handle_by_panicker(&mut runner, &mut rng, PanickerEvent::Work).await);
+    assert!(!handle_by_panicker(&mut runner, &mut rng, PanickerEvent::Panic).await);
+    assert_eq!(panicker_failures(&runner), 1);
+    assert_eq!(runner.reactor().panicker.restarts, 0);
+    // The event queues should have been dumped for diagnosing the failure.
+    assert!(runner.last_queue_dump.is_some());
+
+    // The shutdown sequence should still commit the deploy.
+    runner.shutdown(ExitCode::Abort).await;
+    drop(runner);
+    assert_eq!(
+        reopen_storage(temp_dir.path()).get_deploy_by_hash(deploy_hash),
+        Some(deploy)
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn should_restart_restartable_component_after_panic() {
+    let mut rng = crate::new_rng();
+    let temp_dir = TempDir::new().unwrap();
+    let mut runner =
+        runner_with_policy(temp_dir.path(), FailurePolicy::Restartable, &mut rng).await;
+
+    assert!(handle_by_panicker(&mut runner, &mut rng, PanickerEvent::Work).await);
+    assert!(handle_by_panicker(&mut runner, &mut rng, PanickerEvent::Panic).await);
+    assert_eq!(panicker_failures(&runner), 1);
+    assert_eq!(runner.reactor().panicker.restarts, 1);
+    assert_eq!(runner.reactor().panicker.events_handled, 0);
+    assert!(runner.last_queue_dump.is_none());
+
+    // The reinitialized component should handle further events.
+    assert!(handle_by_panicker(&mut runner, &mut rng, PanickerEvent::Work).await);
+    assert_eq!(runner.reactor().panicker.events_handled, 1);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn should_count_and_continue_after_panic_of_ignorable_component() {
+    let mut rng = crate::new_rng();
+    let temp_dir = TempDir::new().unwrap();
+    let mut runner = runner_with_policy(temp_dir.path(), FailurePolicy::Ignorable, &mut rng).await;
+
+    assert!(handle_by_panicker(&mut runner, &mut rng, PanickerEvent::Work).await);
+    assert!(handle_by_panicker(&mut runner, &mut rng, PanickerEvent::Panic).await);
+    assert!(handle_by_panicker(&mut runner, &mut rng, PanickerEvent::Panic).await);
+    assert_eq!(panicker_failures(&runner), 2);
+    assert!(runner.last_queue_dump.is_none());
+
+    // The component should keep its state and handle further events.
+    assert!(handle_by_panicker(&mut runner, &mut rng, PanickerEvent::Work).await);
+    assert_eq!(runner.reactor().panicker.restarts, 0);
+    assert_eq!(runner.reactor().panicker.events_handled, 4);
+}
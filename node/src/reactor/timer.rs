@@ -0,0 +1,218 @@
+//! Cancellable timers.
+//!
+//! A timer set via [`EventQueueHandle::schedule_after`](super::EventQueueHandle::schedule_after)
+//! returns a [`TimerHandle`], while the event it schedules carries the matching [`TimerToken`].
+//! Dropping or cancelling the handle invalidates the token, which is checked once the timer expires
+//! and again by the reactor right before dispatching the event. A cancelled timer's event is
+//! therefore never delivered, even if it was already waiting on the event queue.
+
+use std::sync::{Arc, Weak};
+
+use datasize::DataSize;
+
+/// Handle to a pending timer.
+///
+/// The timer's event is only dispatched while its handle is alive.
+#[derive(DataSize, Debug)]
+#[must_use = "dropping a timer handle cancels the timer"]
+pub struct TimerHandle {
+    /// Only held to keep the timer's token valid.
+    #[data_size(skip)]
+    _alive: Arc<()>,
+}
+
+impl TimerHandle {
+    /// Creates a new timer handle along with the token to attach to the timer's event.
+    pub(crate) fn new() -> (Self, TimerToken) {
+        let alive = Arc::new(());
+        let token = TimerToken(Arc::downgrade(&alive));
+        (TimerHandle { _alive: alive }, token)
+    }
+
+    /// Cancels the timer.
+    ///
+    /// Equivalent to dropping the handle. Has no effect if the timer's event has already been
+    /// dispatched.
+    #[cfg(test)]
+    pub(crate) fn cancel(self) {
+        drop(self)
+    }
+}
+
+/// Token attached to the event of a timer, used to check whether the timer has been cancelled.
+#[derive(Clone, Debug)]
+pub(crate) struct TimerToken(Weak<()>);
+
+impl TimerToken {
+    /// Returns `true` if the timer's handle has been dropped or cancelled.
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.0.strong_count() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fmt::{self, Display, Formatter},
+        time::Duration,
+    };
+
+    use derive_more::From;
+    use prometheus::Registry;
+    use serde::Serialize;
+    use tokio::time;
+
+    use super::*;
+    use crate::{
+        effect::{announcements::ControlAnnouncement, EffectBuilder, Effects},
+        reactor::{EventQueueHandle, Reactor, ReactorEvent, ReactorExit, Runner},
+        NodeRng,
+    };
+
+    const TIMEOUT: Duration = Duration::from_millis(10);
+
+    /// Test-reactor event.
+    #[derive(Debug, From, Serialize)]
+    enum Event {
+        #[from]
+        Timeout(u32),
+        #[from]
+        ControlAnnouncement(ControlAnnouncement),
+    }
+
+    impl ReactorEvent for Event {
+        fn as_control(&self) -> Option<&ControlAnnouncement> {
+            if let Self::ControlAnnouncement(ref ctrl_ann) = self {
+                Some(ctrl_ann)
+            } else {
+                None
+            }
+        }
+    }
+
+    impl Display for Event {
+        fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+            fmt::Debug::fmt(self, formatter)
+        }
+    }
+
+    /// Test reactor which records the timeouts dispatched to it.
+    #[derive(Debug, Default)]
+    struct TestReactor {
+        timeouts: Vec<u32>,
+    }
+
+    impl Reactor for TestReactor {
+        type Event = Event;
+        type Config = ();
+        type Error = prometheus::Error;
+
+        fn new(
+            _cfg: Self::Config,
+            _registry: &Registry,
+            _event_queue: EventQueueHandle<Self::Event>,
+            _rng: &mut NodeRng,
+        ) -> Result<(Self, Effects<Self::Event>), Self::Error> {
+            Ok((TestReactor::default(), Effects::new()))
+        }
+
+        fn dispatch_event(
+            &mut self,
+            _effect_builder: EffectBuilder<Self::Event>,
+            _rng: &mut NodeRng,
+            event: Self::Event,
+        ) -> Effects<Self::Event> {
+            match event {
+                Event::Timeout(id) => self.timeouts.push(id),
+                Event::ControlAnnouncement(ctrl_ann) => panic!("unexpected {}", ctrl_ann),
+            }
+            Effects::new()
+        }
+
+        fn maybe_exit(&self) -> Option<ReactorExit> {
+            None
+        }
+    }
+
+    async fn new_runner(rng: &mut NodeRng) -> Runner<TestReactor> {
+        Runner::new((), rng)
+            .await
+            .expect("should create test reactor")
+    }
+
+    fn set_timeout(runner: &Runner<TestReactor>, id: u32) -> TimerHandle {
+        let effect_builder = EffectBuilder::new(EventQueueHandle::new(runner.scheduler));
+        effect_builder.set_timeout_with_handle(TIMEOUT, Event::Timeout(id))
+    }
+
+    /// Waits until the given number of timer events are waiting on the runner's event queue.
+    async fn wait_for_queued_events(runner: &Runner<TestReactor>, count: usize) {
+        time::timeout(Duration::from_secs(1), async {
+            while runner.scheduler.item_count() < count {
+                time::sleep(Duration::from_millis(1)).await;
+            }
+        })
+        .await
+        .expect("timer should have fired")
+    }
+
+    #[tokio::test]
+    async fn should_dispatch_event_of_pending_timer() {
+        let mut rng = crate::new_rng();
+        let mut runner = new_runner(&mut rng).await;
+
+        let _handle = set_timeout(&runner, 1);
+        wait_for_queued_events(&runner, 1).await;
+        assert_eq!(runner.try_crank(&mut rng).await, Some(true));
+
+        assert_eq!(runner.reactor().timeouts, vec![1]);
+    }
+
+    #[tokio::test]
+    async fn should_not_dispatch_event_if_cancelled_before_firing() {
+        let mut rng = crate::new_rng();
+        let mut runner = new_runner(&mut rng).await;
+
+        set_timeout(&runner, 1).cancel();
+        drop(set_timeout(&runner, 2));
+        let _handle = set_timeout(&runner, 3);
+
+        // Only the timer which is still pending should have scheduled its event.
+        wait_for_queued_events(&runner, 1).await;
+        time::sleep(TIMEOUT * 5).await;
+        assert_eq!(runner.scheduler.item_count(), 1);
+        while runner.try_crank(&mut rng).await.is_some() {}
+
+        assert_eq!(runner.reactor().timeouts, vec![3]);
+    }
+
+    #[tokio::test]
+    async fn should_ignore_cancellation_after_dispatch() {
+        let mut rng = crate::new_rng();
+        let mut runner = new_runner(&mut rng).await;
+
+        let handle = set_timeout(&runner, 1);
+        wait_for_queued_events(&runner, 1).await;
+        assert_eq!(runner.try_crank(&mut rng).await, Some(true));
+        handle.cancel();
+
+        assert_eq!(runner.reactor().timeouts, vec![1]);
+        assert_eq!(runner.try_crank(&mut rng).await, None);
+    }
+
+    #[tokio::test]
+    async fn should_not_dispatch_event_if_cancelled_after_firing() {
+        let mut rng = crate::new_rng();
+        let mut runner = new_runner(&mut rng).await;
+
+        // Both timers fire, but one is cancelled while its event is already on the queue, i.e.
+        // within the same scheduler tick in which it would have been dispatched.
+        let cancelled_handle = set_timeout(&runner, 1);
+        let _handle = set_timeout(&runner, 2);
+        wait_for_queued_events(&runner, 2).await;
+        cancelled_handle.cancel();
+        while runner.try_crank(&mut rng).await.is_some() {}
+
+        assert_eq!(runner.reactor().timeouts, vec![2]);
+    }
+}
@@ -6,6 +6,7 @@
 mod condition_check_reactor;
 mod multi_stage_test_reactor;
 pub mod network;
+pub mod simulation;
 pub mod test_clock;
 mod test_rng;
 
@@ -29,7 +30,7 @@ use crate::{
     components::Component,
     effect::{announcements::ControlAnnouncement, EffectBuilder, Effects, Responder},
     logging,
-    reactor::{EventQueueHandle, QueueKind, ReactorEvent, Scheduler},
+    reactor::{EventQueueHandle, QueueKind, QueuedEvent, ReactorEvent, Scheduler},
 };
 pub(crate) use condition_check_reactor::ConditionCheckReactor;
 pub(crate) use multi_stage_test_reactor::MultiStageTestReactor;
@@ -45,6 +46,28 @@ pub fn bincode_roundtrip<T: Serialize + DeserializeOwned + Eq + Debug>(value: &T
     assert_eq!(*value, deserialized);
 }
 
+/// Returns the shape of the JSON encoding of `value`.
+///
+/// Every string, number and boolean is replaced by the name of its JSON type, while object keys,
+/// array lengths and `null`s are kept.  Comparing shapes pins which fields are present, which are
+/// `null` and which are (possibly empty) collections, without depending on the fixture's values.
+pub(crate) fn json_shape<T: Serialize>(value: &T) -> serde_json::Value {
+    fn shape(value: serde_json::Value) -> serde_json::Value {
+        match value {
+            serde_json::Value::Null => serde_json::Value::Null,
+            serde_json::Value::Bool(_) => serde_json::json!("bool"),
+            serde_json::Value::Number(_) => serde_json::json!("number"),
+            serde_json::Value::String(_) => serde_json::json!("string"),
+            serde_json::Value::Array(elements) => elements.into_iter().map(shape).collect(),
+            serde_json::Value::Object(fields) => fields
+                .into_iter()
+                .map(|(key, value)| (key, shape(value)))
+                .collect(),
+        }
+    }
+    shape(serde_json::to_value(value).unwrap())
+}
+
 /// Create an unused port on localhost.
 pub(crate) fn unused_port_on_localhost() -> u16 {
     // Unfortunately a randomly generated port by a random number generator still has a chance to
@@ -256,7 +279,8 @@ impl<REv: 'static> ComponentHarness<REv> {
 
             // Iterate over all events that currently are inside the queue and fish out any fatal.
             for _ in 0..(self.scheduler.item_count()) {
-                let (ev, _queue_kind) = self.runtime.block_on(self.scheduler.pop());
+                let (QueuedEvent { event: ev, .. }, _queue_kind) =
+                    self.runtime.block_on(self.scheduler.pop());
 
                 if let Some(ctrl_ann) = ev.as_control() {
                     match ctrl_ann {
@@ -266,6 +290,9 @@ impl<REv: 'static> ComponentHarness<REv> {
                                 fatal
                             )
                         }
+                        failed @ ControlAnnouncement::ComponentFailed { .. } => {
+                            panic!("a component failure was announced: {}", failed)
+                        }
                     }
                 } else {
                     debug!(?ev, "ignoring event while looking for a fatal")
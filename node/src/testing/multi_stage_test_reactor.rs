@@ -164,8 +164,8 @@ where
 {
     // Note: This will keep waiting forever if the sending end disappears, which is fine for tests.
     loop {
-        let (event, queue_kind) = source.pop().await;
-        target_queue.schedule(event, queue_kind).await;
+        let (queued_event, queue_kind) = source.pop().await;
+        target_queue.schedule_queued(queued_event, queue_kind).await;
     }
 }
 
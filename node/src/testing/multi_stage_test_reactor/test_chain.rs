@@ -1,4 +1,4 @@
-use std::{sync::Arc, time::Duration};
+use std::{collections::BTreeMap, sync::Arc, time::Duration};
 
 use log::info;
 use num::Zero;
@@ -95,25 +95,28 @@ impl TestChain {
         // Load the `local` chainspec.
         let mut chainspec: Chainspec = Chainspec::from_resources("local");
 
-        // Override accounts with those generated from the keys.
-        let genesis_accounts = std::iter::once(&first_node_secret_key_with_stake)
-            .chain(other_secret_keys_with_stakes.iter())
-            .map(|staked_secret_key| {
-                let public_key = PublicKey::from(&*staked_secret_key.secret_key);
-                let validator_config = ValidatorConfig::new(
-                    Motes::new(U512::from(staked_secret_key.stake)),
-                    DelegationRate::zero(),
-                );
-                AccountConfig::new(
-                    public_key,
-                    Motes::new(U512::from(rng.gen_range(10000..99999999))),
-                    Some(validator_config),
-                )
-            })
-            .collect();
+        // Override accounts with those generated from the keys.  Nodes sharing a key, e.g. to
+        // equivocate, share a single genesis account.
+        let genesis_accounts: BTreeMap<PublicKey, AccountConfig> =
+            std::iter::once(&first_node_secret_key_with_stake)
+                .chain(other_secret_keys_with_stakes.iter())
+                .map(|staked_secret_key| {
+                    let public_key = PublicKey::from(&*staked_secret_key.secret_key);
+                    let validator_config = ValidatorConfig::new(
+                        Motes::new(U512::from(staked_secret_key.stake)),
+                        DelegationRate::zero(),
+                    );
+                    let account_config = AccountConfig::new(
+                        public_key.clone(),
+                        Motes::new(U512::from(rng.gen_range(10000..99999999))),
+                        Some(validator_config),
+                    );
+                    (public_key, account_config)
+                })
+                .collect();
         let delegators = vec![];
         chainspec.network_config.accounts_config =
-            AccountsConfig::new(genesis_accounts, delegators);
+            AccountsConfig::new(genesis_accounts.into_values().collect(), delegators);
 
         // Make the genesis timestamp 45 seconds from now, to allow for all validators to start up.
         chainspec.protocol_config.activation_point =
@@ -121,7 +124,7 @@ impl TestChain {
 
         chainspec.core_config.minimum_era_height = 4;
         chainspec.highway_config.finality_threshold_fraction = Ratio::new(34, 100);
-        chainspec.core_config.era_duration = 10.into();
+        chainspec.core_config.era_duration = 0.into();
         chainspec.core_config.auction_delay = 1;
         chainspec.core_config.unbonding_delay = 3;
 
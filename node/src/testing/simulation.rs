@@ -0,0 +1,591 @@
+//! Deterministic simulation of a network of test reactors in virtual time.
+//!
+//! A [`Simulation`] drives a [`Network`] on the current thread, which must be running a
+//! single-threaded tokio runtime, as `#[tokio::test]` does by default.  While the simulation
+//! exists:
+//!
+//! * [`Timestamp::now`] returns the simulation's virtual time, which starts at a fixed moment and
+//!   only moves when advanced explicitly, and
+//! * all timers set via the effect builder or event queue wait in virtual time, firing in order of
+//!   their deadlines as [`Simulation::advance_time`] reaches them.
+//!
+//! Events are dispatched one at a time, on a node chosen by the test's `TestRng` from those with
+//! queued events, so the interleaving of events across nodes is reproducible from the rng's seed.
+//! Effects which run on other threads or wait on real IO are not under the simulation's control,
+//! so only reactors without such effects are simulated deterministically.
+
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    fmt::{self, Debug, Formatter},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use futures::{
+    channel::oneshot,
+    future::{self, BoxFuture, FutureExt},
+};
+use rand::seq::SliceRandom;
+use serde::Serialize;
+use tokio::task;
+use tracing::debug;
+
+use casper_deploy_utils::Clock;
+
+use super::{
+    network::{Network, NetworkedReactor, Nodes},
+    TestRng,
+};
+use crate::{
+    reactor::Reactor,
+    types::{TimeDiff, Timestamp},
+};
+
+/// The virtual time at which every simulation starts, in milliseconds since the Unix epoch.
+const VIRTUAL_EPOCH_MILLIS: u64 = 1_600_000_000_000;
+
+/// The number of times pending effects are given the chance to run before a network without queued
+/// events is considered idle.
+const IDLE_YIELDS: usize = 10;
+
+thread_local! {
+    /// The clock of the simulation active on this thread, if any.
+    static ACTIVE_CLOCK: RefCell<Option<VirtualClock>> = RefCell::new(None);
+}
+
+/// Returns a timer which fires once `duration` has elapsed in virtual time, resolving to the time
+/// actually elapsed, or `None` if no simulation is active on the current thread.
+pub(crate) fn virtual_timer(duration: Duration) -> Option<BoxFuture<'static, Duration>> {
+    let receiver = ACTIVE_CLOCK.with(|active_clock| {
+        active_clock
+            .borrow()
+            .as_ref()
+            .map(|clock| clock.add_timer(duration))
+    })?;
+    Some(
+        async move {
+            match receiver.await {
+                Ok(elapsed) => elapsed,
+                // Timers which are still pending when their simulation ends never fire.
+                Err(oneshot::Canceled) => future::pending().await,
+            }
+        }
+        .boxed(),
+    )
+}
+
+/// A clock which only advances when explicitly told to.
+#[derive(Clone, Default)]
+pub struct VirtualClock(Arc<Mutex<ClockState>>);
+
+#[derive(Default)]
+struct ClockState {
+    /// The virtual time elapsed since the start of the simulation.
+    elapsed: Duration,
+    /// Pending timers by deadline, with ties broken by the order in which they were set.
+    ///
+    /// Each holds the duration it was set for, which is sent once it fires.
+    timers: BTreeMap<(Duration, u64), (Duration, oneshot::Sender<Duration>)>,
+    /// The total number of timers set so far.
+    timer_count: u64,
+}
+
+impl VirtualClock {
+    /// Returns the virtual time elapsed since the start of the simulation.
+    pub fn elapsed(&self) -> Duration {
+        self.0.lock().expect("clock lock poisoned").elapsed
+    }
+
+    /// Returns the number of timers which have not fired yet.
+    pub fn pending_timers(&self) -> usize {
+        self.0.lock().expect("clock lock poisoned").timers.len()
+    }
+
+    /// Adds a timer, returning the receiver notified once it fires.
+    fn add_timer(&self, duration: Duration) -> oneshot::Receiver<Duration> {
+        let (sender, receiver) = oneshot::channel();
+        let mut state = self.0.lock().expect("clock lock poisoned");
+        let key = (state.elapsed + duration, state.timer_count);
+        state.timer_count += 1;
+        state.timers.insert(key, (duration, sender));
+        receiver
+    }
+
+    /// Fires the earliest timer, provided its deadline is no later than `until`, advancing the
+    /// clock to the deadline.
+    ///
+    /// Returns `false` if no timer was due.
+    fn fire_next_due(&self, until: Duration) -> bool {
+        let mut state = self.0.lock().expect("clock lock poisoned");
+        let deadline = match state.timers.keys().next() {
+            Some(&(deadline, id)) if deadline <= until => (deadline, id),
+            _ => return false,
+        };
+        let (duration, sender) = state.timers.remove(&deadline).expect("timer should exist");
+        state.elapsed = deadline.0;
+        // The timer's effect may have been dropped in the meantime.
+        let _ = sender.send(duration);
+        true
+    }
+
+    /// Moves the clock forwards to `elapsed`.
+    fn advance_to(&self, elapsed: Duration) {
+        let mut state = self.0.lock().expect("clock lock poisoned");
+        state.elapsed = state.elapsed.max(elapsed);
+    }
+}
+
+impl Clock for VirtualClock {
+    fn now(&self) -> Timestamp {
+        Timestamp::from(VIRTUAL_EPOCH_MILLIS) + TimeDiff::from(self.elapsed())
+    }
+}
+
+impl Debug for VirtualClock {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("VirtualClock")
+            .field("elapsed", &self.elapsed())
+            .field("pending_timers", &self.pending_timers())
+            .finish()
+    }
+}
+
+/// A network of test reactors running in virtual time, with a seeded scheduler.
+///
+/// Only one simulation may be active per thread.  The system clock and real timers are used again
+/// once it has been dropped.
+pub struct Simulation<R: Reactor + NetworkedReactor> {
+    /// The simulated network.
+    network: Network<R>,
+    /// The virtual clock of the simulation.
+    clock: VirtualClock,
+}
+
+impl<R> Simulation<R>
+where
+    R: Reactor + NetworkedReactor,
+    R::NodeId: Ord,
+    R::Event: Serialize,
+    R::Error: From<prometheus::Error> + From<R::Error>,
+{
+    /// Starts a simulation with an empty network on the current thread.
+    ///
+    /// Nodes should only be added via [`Simulation::network_mut`], so that the timers they set on
+    /// creation are virtual.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a simulation is already active on the current thread.
+    pub fn new() -> Self {
+        let clock = VirtualClock::default();
+        ACTIVE_CLOCK.with(|active_clock| {
+            let mut active_clock = active_clock.borrow_mut();
+            assert!(
+                active_clock.is_none(),
+                "cannot run multiple simulations on the same thread"
+            );
+            *active_clock = Some(clock.clone());
+        });
+        Timestamp::set_thread_clock(Some(Arc::new(clock.clone())));
+        Simulation {
+            network: Network::new(),
+            clock,
+        }
+    }
+
+    /// Returns the simulated network.
+    pub fn network(&self) -> &Network<R> {
+        &self.network
+    }
+
+    /// Returns the simulated network, mutable.
+    pub fn network_mut(&mut self) -> &mut Network<R> {
+        &mut self.network
+    }
+
+    /// Returns the virtual clock of the simulation.
+    pub fn clock(&self) -> &VirtualClock {
+        &self.clock
+    }
+
+    /// Dispatches a single event on a node chosen at random from those with queued events.
+    ///
+    /// Returns `false` if no node has any queued events.
+    pub async fn step(&mut self, rng: &mut TestRng) -> bool {
+        let mut node_ids: Vec<_> = self
+            .network
+            .nodes()
+            .iter()
+            .filter(|(_, runner)| runner.queued_event_count() > 0)
+            .map(|(node_id, _)| node_id.clone())
+            .collect();
+        // The nodes are held in a hash map, so are sorted to make the choice depend only on `rng`.
+        node_ids.sort();
+        match node_ids.choose(rng).cloned() {
+            Some(node_id) => self.network.crank(&node_id, rng).await == 1,
+            None => false,
+        }
+    }
+
+    /// Dispatches events until the network is idle, i.e. no node has queued events even after
+    /// giving all pending effects the chance to run.
+    ///
+    /// Returns the number of events dispatched.
+    pub async fn settle(&mut self, rng: &mut TestRng) -> usize {
+        let mut event_count = 0;
+        loop {
+            if self.step(rng).await {
+                event_count += 1;
+            } else if !self.run_effects().await {
+                return event_count;
+            }
+        }
+    }
+
+    /// Dispatches events on the given node until one satisfies `condition`, advancing virtual time
+    /// to the next timer's deadline whenever the node is idle.
+    ///
+    /// Other nodes only progress by running their pending effects.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node becomes idle with no timer pending before `condition` has been met.
+    pub async fn crank_until<F>(&mut self, node_id: &R::NodeId, rng: &mut TestRng, condition: F)
+    where
+        F: Fn(&R::Event) -> bool + Send + 'static,
+    {
+        self.network
+            .nodes_mut()
+            .get_mut(node_id)
+            .expect("should find node")
+            .reactor_mut()
+            .set_condition_checker(Box::new(condition));
+
+        loop {
+            if self.network.crank(node_id, rng).await == 0 {
+                if !self.run_effects().await && !self.clock.fire_next_due(Duration::MAX) {
+                    panic!("{} became idle before meeting condition", node_id);
+                }
+                continue;
+            }
+            let runner = self.network.nodes().get(node_id).expect("should find node");
+            if runner.reactor().condition_result() {
+                debug!("{} met condition", node_id);
+                return;
+            }
+        }
+    }
+
+    /// Advances virtual time by `duration`, firing due timers in order of their deadlines.
+    ///
+    /// The network is settled before the first timer fires and again after each, so timers set in
+    /// the meantime also fire if due within `duration`.
+    pub async fn advance_time(&mut self, rng: &mut TestRng, duration: Duration) {
+        let until = self.clock.elapsed() + duration;
+        self.settle(rng).await;
+        while self.clock.fire_next_due(until) {
+            self.settle(rng).await;
+        }
+        self.clock.advance_to(until);
+        debug!(elapsed = ?self.clock.elapsed(), "advanced virtual time");
+    }
+
+    /// Runs the network until `condition` holds, advancing virtual time to the next timer's
+    /// deadline whenever the network is idle.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `condition` doesn't hold once the network is idle and no timer is due within
+    /// `within` of virtual time.
+    pub async fn settle_on<F>(&mut self, rng: &mut TestRng, condition: F, within: Duration)
+    where
+        F: Fn(&Nodes<R>) -> bool,
+    {
+        let until = self.clock.elapsed() + within;
+        loop {
+            self.settle(rng).await;
+            if condition(self.network.nodes()) {
+                debug!(elapsed = ?self.clock.elapsed(), "network settled on meeting condition");
+                return;
+            }
+            if !self.clock.fire_next_due(until) {
+                panic!(
+                    "network did not settle on condition within {:?} of virtual time",
+                    within
+                );
+            }
+        }
+    }
+
+    /// Yields to pending effects, returning `true` as soon as any node has queued events.
+    async fn run_effects(&self) -> bool {
+        for _ in 0..IDLE_YIELDS {
+            task::yield_now().await;
+            if self
+                .network
+                .nodes()
+                .values()
+                .any(|runner| runner.queued_event_count() > 0)
+            {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+impl<R> Default for Simulation<R>
+where
+    R: Reactor + NetworkedReactor,
+    R::NodeId: Ord,
+    R::Event: Serialize,
+    R::Error: From<prometheus::Error> + From<R::Error>,
+{
+    fn default() -> Self {
+        Simulation::new()
+    }
+}
+
+impl<R: Reactor + NetworkedReactor> Drop for Simulation<R> {
+    fn drop(&mut self) {
+        Timestamp::set_thread_clock(None);
+        ACTIVE_CLOCK.with(|active_clock| active_clock.borrow_mut().take());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fmt::{self, Display, Formatter},
+        sync::{Arc, Mutex},
+        thread,
+        time::Duration,
+    };
+
+    use derive_more::From;
+    use prometheus::Registry;
+    use rand::Rng;
+    use serde::Serialize;
+    use tokio::runtime;
+
+    use super::*;
+    use crate::{
+        effect::{announcements::ControlAnnouncement, EffectBuilder, EffectExt, Effects},
+        reactor::{EventQueueHandle, ReactorEvent, ReactorExit},
+        NodeRng,
+    };
+
+    /// The number of ticks each node dispatches.
+    const TICK_COUNT: u32 = 5;
+    /// The number of events each tick causes to be dispatched immediately.
+    const ECHO_COUNT: u32 = 2;
+    /// The duration of the timer each node sets on creation.
+    const EXPIRY: Duration = Duration::from_secs(5);
+
+    /// Test-reactor event.
+    #[derive(Debug, From, Serialize)]
+    enum Event {
+        /// A tick, which schedules the next one after a random delay.
+        Tick(u32),
+        /// An event caused immediately by a tick.
+        Echo(u32, u32),
+        /// The timer set on creation expired after the given duration.
+        Expired(Duration),
+        #[from]
+        ControlAnnouncement(ControlAnnouncement),
+    }
+
+    impl ReactorEvent for Event {
+        fn as_control(&self) -> Option<&ControlAnnouncement> {
+            if let Self::ControlAnnouncement(ref ctrl_ann) = self {
+                Some(ctrl_ann)
+            } else {
+                None
+            }
+        }
+    }
+
+    impl Display for Event {
+        fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+            fmt::Debug::fmt(self, formatter)
+        }
+    }
+
+    /// Log of the events dispatched across all nodes, along with their node and dispatch time.
+    type Log = Arc<Mutex<Vec<(u64, String, Timestamp)>>>;
+
+    /// Test reactor which logs the events dispatched to it.
+    #[derive(Debug)]
+    struct TestReactor {
+        node_id: u64,
+        log: Log,
+    }
+
+    impl Reactor for TestReactor {
+        type Event = Event;
+        type Config = Log;
+        type Error = prometheus::Error;
+
+        fn new(
+            log: Self::Config,
+            _registry: &Registry,
+            event_queue: EventQueueHandle<Self::Event>,
+            rng: &mut NodeRng,
+        ) -> Result<(Self, Effects<Self::Event>), Self::Error> {
+            let effect_builder = EffectBuilder::new(event_queue);
+            let mut effects = async {}.event(|()| Event::Tick(0));
+            effects.extend(effect_builder.set_timeout(EXPIRY).event(Event::Expired));
+            let reactor = TestReactor {
+                node_id: rng.gen(),
+                log,
+            };
+            Ok((reactor, effects))
+        }
+
+        fn dispatch_event(
+            &mut self,
+            effect_builder: EffectBuilder<Self::Event>,
+            rng: &mut NodeRng,
+            event: Self::Event,
+        ) -> Effects<Self::Event> {
+            let mut effects = Effects::new();
+            if let Event::Tick(tick) = event {
+                for echo in 0..ECHO_COUNT {
+                    effects.extend(async {}.event(move |()| Event::Echo(tick, echo)));
+                }
+                if tick + 1 < TICK_COUNT {
+                    let delay = Duration::from_millis(rng.gen_range(1..4));
+                    effects.extend(
+                        effect_builder
+                            .set_timeout(delay)
+                            .event(move |_| Event::Tick(tick + 1)),
+                    );
+                }
+            }
+            self.log
+                .lock()
+                .unwrap()
+                .push((self.node_id, event.to_string(), Timestamp::now()));
+            effects
+        }
+
+        fn maybe_exit(&self) -> Option<ReactorExit> {
+            None
+        }
+    }
+
+    impl NetworkedReactor for TestReactor {
+        type NodeId = u64;
+
+        fn node_id(&self) -> u64 {
+            self.node_id
+        }
+    }
+
+    /// Runs a simulation of `node_count` nodes until all their ticks have been dispatched,
+    /// returning the log of dispatched events.
+    async fn run_ticks(rng: &mut TestRng, node_count: usize) -> Vec<(u64, String, Timestamp)> {
+        let log = Log::default();
+        let mut simulation = Simulation::<TestReactor>::new();
+        for _ in 0..node_count {
+            simulation
+                .network_mut()
+                .add_node_with_config(Arc::clone(&log), rng)
+                .await
+                .expect("should add node");
+        }
+
+        let expected_event_count = node_count * (TICK_COUNT * (1 + ECHO_COUNT)) as usize;
+        let all_ticked = |_: &Nodes<TestReactor>| log.lock().unwrap().len() == expected_event_count;
+        simulation
+            .settle_on(rng, all_ticked, Duration::from_secs(1))
+            .await;
+
+        drop(simulation);
+        Arc::try_unwrap(log)
+            .expect("nodes should have been dropped")
+            .into_inner()
+            .unwrap()
+    }
+
+    #[test]
+    fn should_reproduce_event_ordering_from_seed() {
+        let seed: [u8; 16] = TestRng::new().gen();
+
+        // Only one `TestRng` may exist per thread, so each run gets a thread of its own.
+        let run = move || {
+            thread::spawn(move || {
+                let mut rng = TestRng::from_seed(seed);
+                runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                    .expect("should build runtime")
+                    .block_on(run_ticks(&mut rng, 3))
+            })
+            .join()
+            .expect("simulation should not panic")
+        };
+        let first_log = run();
+        let second_log = run();
+
+        assert_eq!(first_log, second_log);
+
+        // Events of different nodes should have been interleaved.
+        let node_changes = first_log
+            .windows(2)
+            .filter(|pair| pair[0].0 != pair[1].0)
+            .count();
+        assert!(node_changes >= 2, "{:?}", first_log);
+    }
+
+    #[tokio::test]
+    async fn should_fire_timer_exactly_at_virtual_deadline() {
+        let mut rng = crate::new_rng();
+        let log = Log::default();
+        let mut simulation = Simulation::<TestReactor>::new();
+        let start = Timestamp::now();
+        simulation
+            .network_mut()
+            .add_node_with_config(Arc::clone(&log), &mut rng)
+            .await
+            .expect("should add node");
+
+        let expired = |log: &Log| {
+            log.lock()
+                .unwrap()
+                .iter()
+                .filter(|(_, event, _)| event.starts_with("Expired"))
+                .map(|(_, event, timestamp)| (event.clone(), *timestamp))
+                .collect::<Vec<_>>()
+        };
+
+        // Virtual time should only pass when advanced, regardless of the ticks being dispatched.
+        simulation.settle(&mut rng).await;
+        assert_eq!(Timestamp::now(), start);
+
+        simulation
+            .advance_time(&mut rng, EXPIRY - Duration::from_millis(1))
+            .await;
+        assert!(expired(&log).is_empty());
+        assert_eq!(
+            Timestamp::now(),
+            start + TimeDiff::from(EXPIRY) - TimeDiff::from(1)
+        );
+
+        simulation
+            .advance_time(&mut rng, Duration::from_millis(1))
+            .await;
+        let expected_expiry = (
+            Event::Expired(EXPIRY).to_string(),
+            start + TimeDiff::from(EXPIRY),
+        );
+        assert_eq!(expired(&log), vec![expected_expiry]);
+        assert_eq!(simulation.clock().pending_timers(), 0);
+
+        // The system clock should be used again once the simulation has ended.
+        drop(simulation);
+        assert!(Timestamp::now() > start + TimeDiff::from(Duration::from_secs(3_600)));
+    }
+}
@@ -17,7 +17,9 @@
 //! * creation and validation of self-signed certificates
 //!   ([`generate_node_cert`](fn.generate_node_cert.html)),
 //! * signing and verification of arbitrary values using keys from certificates
-//!   ([`Signature`](struct.Signature.html), [`Signed`](struct.Signed.html)), and
+//!   ([`Signature`](struct.Signature.html), [`Signed`](struct.Signed.html)),
+//! * raw signing and verification of byte strings using a compressed public key
+//!   ([`sign_raw`](fn.sign_raw.html), [`verify_raw`](fn.verify_raw.html)), and
 //! * `serde` support for certificates ([`x509_serde`](x509_serde/index.html))
 
 use std::{
@@ -39,6 +41,7 @@ use openssl::{
     asn1::{Asn1Integer, Asn1IntegerRef, Asn1Time},
     bn::{BigNum, BigNumContext},
     ec,
+    ecdsa::EcdsaSig,
     error::ErrorStack,
     hash::{DigestBytes, MessageDigest},
     nid,
@@ -146,6 +149,13 @@ pub struct KeyFingerprint(Sha512);
 impl KeyFingerprint {
     /// Size of digest in bytes.
     pub const LENGTH: usize = Sha512::SIZE;
+
+    /// Calculates the fingerprint of a public key given in compressed form.
+    ///
+    /// Matches the key fingerprint of a certificate with the same public key.
+    pub(crate) fn from_compressed_public_key(public_key: &[u8]) -> Self {
+        KeyFingerprint(Sha512::new(public_key))
+    }
 }
 
 impl AsRef<[u8]> for KeyFingerprint {
@@ -530,6 +540,104 @@ pub(crate) fn validate_cert(cert: X509) -> Result<TlsCert, ValidationError> {
     })
 }
 
+/// Length in bytes of a public key on `SIGNATURE_CURVE` in compressed form.
+pub(crate) const COMPRESSED_PUBLIC_KEY_LENGTH: usize = 67;
+
+/// Length in bytes of a single scalar on `SIGNATURE_CURVE`.
+const SCALAR_LENGTH: usize = 66;
+
+/// Length in bytes of a raw signature, i.e. the concatenated, zero-padded `r` and `s` scalars.
+pub(crate) const RAW_SIGNATURE_LENGTH: usize = 2 * SCALAR_LENGTH;
+
+/// Error creating or verifying a raw signature.
+#[derive(Debug, Error)]
+pub enum SignatureError {
+    /// The key does not use the correct curve.
+    #[error("the key does not use the correct curve")]
+    WrongCurve,
+    /// The public key could not be decoded or fails the key check.
+    #[error("invalid public key")]
+    InvalidPublicKey(#[source] ErrorStack),
+    /// The signature does not match the signed data and public key.
+    #[error("the signature is invalid")]
+    InvalidSignature,
+    /// OpenSSL failed to process the key or signature.
+    #[error(transparent)]
+    Ssl(#[from] ErrorStack),
+}
+
+/// Returns the public key of `secret_key` in compressed form.
+pub(crate) fn compressed_public_key(
+    secret_key: &PKeyRef<Private>,
+) -> Result<[u8; COMPRESSED_PUBLIC_KEY_LENGTH], SignatureError> {
+    let ec_key = secret_key.ec_key()?;
+    if ec_key.group().curve_name() != Some(SIGNATURE_CURVE) {
+        return Err(SignatureError::WrongCurve);
+    }
+
+    let mut big_num_context = BigNumContext::new()?;
+    let buf = ec_key.public_key().to_bytes(
+        ec_key.group(),
+        ec::PointConversionForm::COMPRESSED,
+        &mut big_num_context,
+    )?;
+    buf.as_slice()
+        .try_into()
+        .map_err(|_| SignatureError::WrongCurve)
+}
+
+/// Signs the SHA512 digest of `data` with `secret_key`, returning the raw signature.
+pub(crate) fn sign_raw(
+    secret_key: &PKeyRef<Private>,
+    data: &[u8],
+) -> Result<[u8; RAW_SIGNATURE_LENGTH], SignatureError> {
+    let ec_key = secret_key.ec_key()?;
+    if ec_key.group().curve_name() != Some(SIGNATURE_CURVE) {
+        return Err(SignatureError::WrongCurve);
+    }
+
+    let signature = EcdsaSig::sign(Sha512::new(data).bytes(), &ec_key)?;
+
+    // Both scalars are stored big-endian, left-padded with zeros to `SCALAR_LENGTH`.
+    let mut raw_signature = [0; RAW_SIGNATURE_LENGTH];
+    for (scalar, buf) in [signature.r(), signature.s()]
+        .iter()
+        .zip(raw_signature.chunks_mut(SCALAR_LENGTH))
+    {
+        let scalar_bytes = scalar.to_vec();
+        buf[SCALAR_LENGTH - scalar_bytes.len()..].copy_from_slice(&scalar_bytes);
+    }
+    Ok(raw_signature)
+}
+
+/// Verifies a raw signature created by `sign_raw` against `data` and the compressed
+/// `public_key`.
+pub(crate) fn verify_raw(
+    public_key: &[u8],
+    data: &[u8],
+    raw_signature: &[u8; RAW_SIGNATURE_LENGTH],
+) -> Result<(), SignatureError> {
+    let group =
+        ec::EcGroup::from_curve_name(SIGNATURE_CURVE).expect("broken constant SIGNATURE_CURVE");
+    let mut big_num_context = BigNumContext::new()?;
+    let point = ec::EcPoint::from_bytes(&group, public_key, &mut big_num_context)
+        .map_err(SignatureError::InvalidPublicKey)?;
+    let ec_key =
+        ec::EcKey::from_public_key(&group, &point).map_err(SignatureError::InvalidPublicKey)?;
+    ec_key
+        .check_key()
+        .map_err(SignatureError::InvalidPublicKey)?;
+
+    let (r, s) = raw_signature.split_at(SCALAR_LENGTH);
+    let signature =
+        EcdsaSig::from_private_components(BigNum::from_slice(r)?, BigNum::from_slice(s)?)?;
+    if signature.verify(Sha512::new(data).bytes(), &ec_key)? {
+        Ok(())
+    } else {
+        Err(SignatureError::InvalidSignature)
+    }
+}
+
 /// Loads a certificate from a file.
 pub(crate) fn load_cert<P: AsRef<Path>>(src: P) -> anyhow::Result<X509> {
     let pem = read_file(src.as_ref()).with_context(|| "failed to load certificate")?;
@@ -817,7 +925,10 @@ impl Hash for Sha512 {
 
 #[cfg(test)]
 mod test {
-    use super::{generate_node_cert, mkname, name_to_string, validate_cert, TlsCert};
+    use super::{
+        compressed_public_key, generate_node_cert, mkname, name_to_string, sign_raw, validate_cert,
+        verify_raw, KeyFingerprint, SignatureError, TlsCert,
+    };
 
     #[test]
     fn simple_name_to_string() {
@@ -843,4 +954,30 @@ mod test {
 
         assert_eq!(serialized, serialized_again);
     }
+
+    #[test]
+    fn test_raw_signature_roundtrip() {
+        let (cert, private_key) = generate_node_cert().expect("failed to generate key, cert pair");
+        let tls_cert = validate_cert(cert).expect("generated cert is not valid");
+
+        let public_key = compressed_public_key(&private_key).expect("could not encode public key");
+        assert_eq!(
+            KeyFingerprint::from_compressed_public_key(&public_key),
+            tls_cert.public_key_fingerprint()
+        );
+
+        let signature = sign_raw(&private_key, b"data").expect("could not sign");
+        verify_raw(&public_key, b"data", &signature).expect("signature should be valid");
+        assert!(matches!(
+            verify_raw(&public_key, b"other data", &signature),
+            Err(SignatureError::InvalidSignature)
+        ));
+
+        let (_, other_private_key) = generate_node_cert().expect("failed to generate key");
+        let other_public_key = compressed_public_key(&other_private_key).unwrap();
+        assert!(matches!(
+            verify_raw(&other_public_key, b"data", &signature),
+            Err(SignatureError::InvalidSignature)
+        ));
+    }
 }
@@ -4,7 +4,10 @@ pub(crate) mod appendable_block;
 mod block;
 pub mod chainspec;
 mod deploy;
+mod deploy_spread;
+mod deploy_status;
 mod exit_code;
+mod gossip_state;
 mod item;
 pub mod json_compatibility;
 mod node_config;
@@ -12,7 +15,10 @@ mod node_id;
 mod peers_map;
 mod shared_object;
 mod status_feed;
+mod summary;
 mod timestamp;
+mod validator_change;
+mod validator_performance;
 
 use rand::{CryptoRng, RngCore};
 #[cfg(not(test))]
@@ -20,24 +26,35 @@ use rand_chacha::ChaCha20Rng;
 
 pub use block::{
     json_compatibility::JsonBlock, Block, BlockBody, BlockHash, BlockHeader, BlockSignatures,
-    BlockValidationError, FinalitySignature,
+    BlockTransfers, BlockValidationError, FinalitySignature,
 };
 pub(crate) use block::{BlockByHeight, BlockHeaderWithMetadata, BlockPayload, FinalizedBlock};
 pub(crate) use chainspec::ActivationPoint;
 pub use chainspec::Chainspec;
 pub use datasize::DataSize;
 pub use deploy::{
-    Approval, Deploy, DeployHash, DeployHeader, DeployMetadata, DeployOrTransferHash,
+    decode_canonical_deploy, Approval, Deploy, DeployApprovals, DeployDecodingError, DeployHash,
+    DeployHashScheme, DeployHeader, DeployHeaderWithHash, DeployMetadata, DeployOrTransferHash,
     DeployValidationFailure, Error as DeployError, ExcessiveSizeError as ExcessiveSizeDeployError,
 };
+pub(crate) use deploy::{deploy_span, DeployExt};
+pub use deploy_spread::DeploySpread;
+pub use deploy_status::DeployStatus;
 pub use exit_code::ExitCode;
-pub use item::{Item, Tag};
+pub use gossip_state::{GossipState, GossipTopicState};
+pub(crate) use item::FetchPriority;
+pub use item::{Item, ParseTagError, Tag, UnexpectedTrieError};
 pub use node_config::NodeConfig;
 pub(crate) use node_id::NodeId;
 pub use peers_map::PeersMap;
 pub(crate) use shared_object::SharedObject;
-pub use status_feed::{ChainspecInfo, GetStatusResult, StatusFeed};
+pub use status_feed::{
+    ChainspecInfo, DialBackoff, EraProgress, GetStatusResult, StatusFeed, StorageStatus,
+};
+pub(crate) use summary::Summary;
 pub use timestamp::{TimeDiff, Timestamp};
+pub use validator_change::ValidatorChange;
+pub use validator_performance::ValidatorPerformance;
 
 /// An object-safe RNG trait that requires a cryptographically strong random number generator.
 pub trait CryptoRngCore: CryptoRng + RngCore {}
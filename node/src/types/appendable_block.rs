@@ -76,7 +76,7 @@ impl AppendableBlock {
         }
         if !deploy_info
             .header
-            .is_valid(&self.deploy_config, self.timestamp)
+            .is_valid(&self.deploy_config.deploy_limits(), self.timestamp)
         {
             return Err(AddError::InvalidDeploy);
         }
@@ -103,7 +103,7 @@ impl AppendableBlock {
         }
         if !deploy_info
             .header
-            .is_valid(&self.deploy_config, self.timestamp)
+            .is_valid(&self.deploy_config.deploy_limits(), self.timestamp)
         {
             return Err(AddError::InvalidDeploy);
         }
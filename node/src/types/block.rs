@@ -30,7 +30,7 @@ use thiserror::Error;
 use casper_types::system::auction::BLOCK_REWARD;
 use casper_types::{
     bytesrepr::{self, FromBytes, ToBytes},
-    EraId, ProtocolVersion, PublicKey, SecretKey, Signature, U512,
+    EraId, ProtocolVersion, PublicKey, SecretKey, Signature, Transfer, U512,
 };
 
 use super::{Item, Tag, Timestamp};
@@ -944,6 +944,31 @@ pub enum BlockValidationError {
         /// The actual hash of the block
         actual: BlockHash,
     },
+
+    /// The block, or its header or transfers, is not the one which was requested
+    UnexpectedBlock {
+        /// The hash of the requested block
+        expected: BlockHash,
+        /// The actual hash of the block
+        actual: BlockHash,
+    },
+
+    /// The block, or its header, is not at the requested height
+    UnexpectedHeight {
+        /// The requested height
+        expected: u64,
+        /// The actual height of the block
+        actual: u64,
+    },
+
+    /// The finality signatures accompanying a block header are for a different block or era
+    UnexpectedSignatures {
+        /// The hash of the block header
+        block_hash: BlockHash,
+    },
+
+    /// A finality signature is not a valid signature of the block
+    InvalidSignature(crypto::Error),
 }
 
 impl Display for BlockValidationError {
@@ -952,6 +977,8 @@ impl Display for BlockValidationError {
     }
 }
 
+impl StdError for BlockValidationError {}
+
 impl From<bytesrepr::Error> for BlockValidationError {
     fn from(err: bytesrepr::Error) -> Self {
         BlockValidationError::SerializationError(err)
@@ -1136,14 +1163,14 @@ impl Block {
     /// Check the integrity of a block by hashing its body and header
     pub fn verify(&self) -> Result<(), BlockValidationError> {
         let actual_body_hash = self.body.hash();
-        if self.header.body_hash != actual_body_hash {
+        if !self.header.body_hash.ct_eq(&actual_body_hash) {
             return Err(BlockValidationError::UnexpectedBodyHash {
                 expected_by_block_header: self.header.body_hash,
                 actual: actual_body_hash,
             });
         }
         let actual_header_hash = self.header.hash();
-        if self.hash != actual_header_hash {
+        if !self.hash.inner().ct_eq(actual_header_hash.inner()) {
             return Err(BlockValidationError::UnexpectedBlockHash {
                 expected_by_block: self.hash,
                 actual: actual_header_hash,
@@ -1262,13 +1289,23 @@ impl FromBytes for Block {
 
 impl Item for Block {
     type Id = BlockHash;
+    type ValidationError = BlockValidationError;
 
     const TAG: Tag = Tag::Block;
-    const ID_IS_COMPLETE_ITEM: bool = false;
 
     fn id(&self) -> Self::Id {
         *self.hash()
     }
+
+    fn validate(&self, expected_id: &Self::Id) -> Result<(), Self::ValidationError> {
+        if self.hash != *expected_id {
+            return Err(BlockValidationError::UnexpectedBlock {
+                expected: *expected_id,
+                actual: self.hash,
+            });
+        }
+        self.verify()
+    }
 }
 
 /// A wrapper around `Block` for the purposes of fetching blocks by height in linear chain.
@@ -1312,17 +1349,97 @@ impl Display for BlockByHeight {
 
 impl Item for BlockByHeight {
     type Id = u64;
+    type ValidationError = BlockValidationError;
 
     const TAG: Tag = Tag::BlockByHeight;
-    const ID_IS_COMPLETE_ITEM: bool = false;
 
     fn id(&self) -> Self::Id {
         self.height()
     }
+
+    fn validate(&self, expected_id: &Self::Id) -> Result<(), Self::ValidationError> {
+        let actual = self.height();
+        if actual != *expected_id {
+            return Err(BlockValidationError::UnexpectedHeight {
+                expected: *expected_id,
+                actual,
+            });
+        }
+        match self {
+            BlockByHeight::Absent(_) => Ok(()),
+            BlockByHeight::Block(block) => block.verify(),
+        }
+    }
+}
+
+/// The transfers executed in a block, along with the block's hash.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct BlockTransfers {
+    block_hash: BlockHash,
+    transfers: Vec<Transfer>,
+}
+
+impl BlockTransfers {
+    /// Constructs a new `BlockTransfers`.
+    pub(crate) fn new(block_hash: BlockHash, transfers: Vec<Transfer>) -> Self {
+        BlockTransfers {
+            block_hash,
+            transfers,
+        }
+    }
+
+    /// Returns the hash of the block.
+    pub fn block_hash(&self) -> &BlockHash {
+        &self.block_hash
+    }
+
+    /// Returns the transfers executed in the block.
+    pub fn transfers(&self) -> &[Transfer] {
+        &self.transfers
+    }
+}
+
+impl Display for BlockTransfers {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(
+            formatter,
+            "block-transfers({}, {} transfers)",
+            self.block_hash,
+            self.transfers.len()
+        )
+    }
+}
+
+impl Item for BlockTransfers {
+    type Id = BlockHash;
+    type ValidationError = BlockValidationError;
+
+    const TAG: Tag = Tag::BlockTransfers;
+
+    fn id(&self) -> Self::Id {
+        self.block_hash
+    }
+
+    /// Only the block hash can be checked, as the transfers themselves aren't committed to by the
+    /// block.
+    fn validate(&self, expected_id: &Self::Id) -> Result<(), Self::ValidationError> {
+        if self.block_hash != *expected_id {
+            return Err(BlockValidationError::UnexpectedBlock {
+                expected: *expected_id,
+                actual: self.block_hash,
+            });
+        }
+        Ok(())
+    }
 }
 
 pub(crate) mod json_compatibility {
+    #[cfg(test)]
+    use serde_json::json;
+
     use super::*;
+    #[cfg(test)]
+    use crate::testing;
 
     #[derive(Serialize, Deserialize, Debug, JsonSchema, Clone, PartialEq, Eq, DataSize)]
     #[serde(deny_unknown_fields)]
@@ -1582,6 +1699,69 @@ pub(crate) mod json_compatibility {
         let block_deserialized = Block::from(json_block);
         assert_eq!(block, block_deserialized);
     }
+
+    #[test]
+    fn should_pin_json_block_shape() {
+        let validator_weight = json!({ "validator": "string", "weight": "string" });
+        let expected = json!({
+            "hash": "string",
+            "header": {
+                "parent_hash": "string",
+                "state_root_hash": "string",
+                "body_hash": "string",
+                "random_bit": "bool",
+                "accumulated_seed": "string",
+                "era_end": {
+                    "era_report": {
+                        "equivocators": ["string"],
+                        "rewards": [{ "validator": "string", "amount": "number" }],
+                        "inactive_validators": ["string"]
+                    },
+                    "next_era_validator_weights": [
+                        validator_weight,
+                        validator_weight,
+                        validator_weight
+                    ]
+                },
+                "timestamp": "string",
+                "era_id": "number",
+                "height": "number",
+                "protocol_version": "string"
+            },
+            "body": {
+                "proposer": "string",
+                "deploy_hashes": ["string"],
+                "transfer_hashes": []
+            },
+            "proofs": [{ "public_key": "string", "signature": "string" }]
+        });
+        assert_eq!(testing::json_shape(JsonBlock::doc_example()), expected);
+    }
+
+    #[test]
+    fn should_serialize_empty_collections_and_absent_options_in_json_block() {
+        let mut json_block = JsonBlock::doc_example().clone();
+        json_block.header.era_end = None;
+        json_block.body.deploy_hashes.clear();
+        json_block.proofs.clear();
+
+        let shape = testing::json_shape(&json_block);
+        assert_eq!(shape["header"]["era_end"], serde_json::Value::Null);
+        assert_eq!(shape["body"]["deploy_hashes"], json!([]));
+        assert_eq!(shape["body"]["transfer_hashes"], json!([]));
+        assert_eq!(shape["proofs"], json!([]));
+
+        // An absent `Option` field is still accepted, and decodes the same as an explicit `null`.
+        let mut json_value = serde_json::to_value(&json_block).unwrap();
+        json_value["header"]
+            .as_object_mut()
+            .unwrap()
+            .remove("era_end");
+        assert_eq!(
+            serde_json::from_value::<JsonBlock>(json_value).unwrap(),
+            json_block
+        );
+    }
 }
 
 /// A validator's signature of a block, to confirm it is finalized. Clients and joining nodes should
@@ -3,6 +3,7 @@
 
 mod accounts_config;
 mod activation_point;
+mod approval_algorithms;
 mod core_config;
 mod deploy_config;
 mod error;
@@ -12,12 +13,12 @@ mod network_config;
 mod parse_toml;
 mod protocol_config;
 
-use std::{fmt::Debug, path::Path};
+use std::{convert::TryFrom, fmt::Debug, path::Path};
 
 use datasize::DataSize;
 #[cfg(test)]
 use rand::Rng;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use tracing::{error, warn};
 
 use casper_execution_engine::{
@@ -31,11 +32,17 @@ use casper_types::{
 
 #[cfg(test)]
 pub(crate) use self::accounts_config::{AccountConfig, ValidatorConfig};
-pub use self::error::Error;
+pub use self::error::{ChainspecValidationError, Error};
 pub(crate) use self::{
-    accounts_config::AccountsConfig, activation_point::ActivationPoint, core_config::CoreConfig,
-    deploy_config::DeployConfig, global_state_update::GlobalStateUpdate,
-    highway_config::HighwayConfig, network_config::NetworkConfig, protocol_config::ProtocolConfig,
+    accounts_config::AccountsConfig,
+    activation_point::ActivationPoint,
+    approval_algorithms::{ApprovalAlgorithm, ApprovalAlgorithms},
+    core_config::CoreConfig,
+    deploy_config::DeployConfig,
+    global_state_update::GlobalStateUpdate,
+    highway_config::HighwayConfig,
+    network_config::NetworkConfig,
+    protocol_config::ProtocolConfig,
 };
 #[cfg(test)]
 use crate::testing::TestRng;
@@ -49,7 +56,7 @@ pub const CHAINSPEC_NAME: &str = "chainspec.toml";
 
 /// A collection of configuration settings describing the state of the system at genesis and after
 /// upgrades to basic system functionality occurring after genesis.
-#[derive(DataSize, PartialEq, Eq, Serialize, Debug)]
+#[derive(DataSize, PartialEq, Eq, Serialize, Deserialize, Debug)]
 pub struct Chainspec {
     #[serde(rename = "protocol")]
     pub(crate) protocol_config: ProtocolConfig,
@@ -68,19 +75,76 @@ pub struct Chainspec {
 }
 
 impl Chainspec {
+    /// Checks whether the values set in the chainspec make sense, returning every problem found.
+    pub fn validate(&self) -> Result<(), Vec<ChainspecValidationError>> {
+        let mut errors = vec![];
+        self.protocol_config.validate(&mut errors);
+        self.network_config.validate(&mut errors);
+        self.core_config.validate(&mut errors);
+        self.highway_config.validate(&mut errors);
+        self.deploy_config.validate(&mut errors);
+
+        // If the era duration is set to zero, we will treat it as explicitly stating that eras
+        // should be defined by height only.  Otherwise an era must last at least one round.  Round
+        // exponents which are too large have already been reported above.
+        let era_duration_ms = self.core_config.era_duration.millis();
+        if let Some(min_round_ms) =
+            1u64.checked_shl(u32::from(self.highway_config.minimum_round_exponent))
+        {
+            if era_duration_ms > 0 && era_duration_ms < min_round_ms {
+                errors.push(ChainspecValidationError::inconsistent(
+                    "core.era_duration",
+                    format!(
+                        "is {}, but must be 0 or at least the minimum round length {}",
+                        self.core_config.era_duration,
+                        self.highway_config.min_round_length()
+                    ),
+                ));
+            }
+        }
+
+        if self.deploy_config.max_deploy_size > self.network_config.maximum_net_message_size {
+            errors.push(ChainspecValidationError::inconsistent(
+                "deploys.max_deploy_size",
+                format!(
+                    "is {}, but must not be greater than [network.maximum_net_message_size] {}",
+                    self.deploy_config.max_deploy_size,
+                    self.network_config.maximum_net_message_size
+                ),
+            ));
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
     /// Returns `false` and logs errors if the values set in the config don't make sense.
     pub(crate) fn is_valid(&self) -> bool {
-        let min_era_ms = 1u64 << self.highway_config.minimum_round_exponent;
-        // If the era duration is set to zero, we will treat it as explicitly stating that eras
-        // should be defined by height only.
+        let min_era_ms = 1u64
+            .checked_shl(u32::from(self.highway_config.minimum_round_exponent))
+            .unwrap_or(u64::MAX);
         if self.core_config.era_duration.millis() > 0
             && self.core_config.era_duration.millis()
-                < self.core_config.minimum_era_height * min_era_ms
+                < self
+                    .core_config
+                    .minimum_era_height
+                    .saturating_mul(min_era_ms)
         {
             warn!("era duration is less than minimum era height * round length!");
         }
 
-        self.protocol_config.is_valid() && self.highway_config.is_valid()
+        match self.validate() {
+            Ok(()) => true,
+            Err(errors) => {
+                for error in errors {
+                    error!(%error, "invalid chainspec");
+                }
+                false
+            }
+        }
     }
 
     /// Serializes `self` and hashes the resulting bytes.
@@ -173,11 +237,21 @@ impl FromBytes for Chainspec {
     }
 }
 
+impl TryFrom<&Path> for Chainspec {
+    type Error = Error;
+
+    /// Parses the chainspec file at `chainspec_path`, along with the accounts file and any global
+    /// state update file in the same directory.
+    fn try_from(chainspec_path: &Path) -> Result<Self, Self::Error> {
+        parse_toml::parse_toml(chainspec_path)
+    }
+}
+
 impl Loadable for Chainspec {
     type Error = Error;
 
     fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, Self::Error> {
-        parse_toml::parse_toml(path.as_ref().join(CHAINSPEC_NAME))
+        Chainspec::try_from(path.as_ref().join(CHAINSPEC_NAME).as_path())
     }
 }
 
@@ -218,7 +292,7 @@ mod tests {
     };
     use casper_types::{EraId, ProtocolVersion, U512};
 
-    use super::*;
+    use super::{error::ChainspecAccountsLoadError, *};
     use crate::{
         types::{TimeDiff, Timestamp},
         utils::RESOURCES_PATH,
@@ -373,6 +447,9 @@ mod tests {
         assert_eq!(spec.deploy_config.max_block_size, 12);
         assert_eq!(spec.deploy_config.block_max_deploy_count, 125);
         assert_eq!(spec.deploy_config.block_gas_limit, 13);
+        assert_eq!(spec.deploy_config.max_chain_name_length, 128);
+        assert_eq!(spec.deploy_config.max_named_key_length, 256);
+        assert_eq!(spec.deploy_config.max_gas_price, 1_000);
 
         assert_eq!(spec.wasm_config, *EXPECTED_GENESIS_WASM_COSTS);
     }
@@ -393,6 +470,39 @@ mod tests {
         bytesrepr::test_serialization_roundtrip(&chainspec);
     }
 
+    #[test]
+    fn should_change_hash_if_chainspec_file_changes() {
+        let tempdir = tempfile::tempdir().expect("should create temp dir");
+        for file_name in &[CHAINSPEC_NAME, "accounts.toml"] {
+            fs::copy(
+                RESOURCES_PATH.join("production").join(file_name),
+                tempdir.path().join(file_name),
+            )
+            .expect("should copy file");
+        }
+        let chainspec = Chainspec::from_path(tempdir.path()).expect("should load chainspec");
+        assert_eq!(
+            chainspec.hash(),
+            Chainspec::from_resources("production").hash()
+        );
+
+        // Change a single byte of the network name.
+        let path = tempdir.path().join(CHAINSPEC_NAME);
+        let mut contents = fs::read(&path).expect("should read file");
+        let index = contents
+            .windows(b"'casper'".len())
+            .position(|window| window == b"'casper'")
+            .expect("should contain network name")
+            + 1;
+        contents[index] = b'k';
+        fs::write(&path, contents).expect("should write file");
+
+        let modified_chainspec =
+            Chainspec::from_path(tempdir.path()).expect("should load modified chainspec");
+        assert_eq!(modified_chainspec.network_config.name, "kasper");
+        assert_ne!(chainspec.hash(), modified_chainspec.hash());
+    }
+
     #[ignore = "We probably need to reconsider our approach here"]
     #[test]
     fn should_have_deterministic_chainspec_hash() {
@@ -421,4 +531,124 @@ mod tests {
         // With equal hashes
         assert_eq!(chainspec.hash(), chainspec_unordered.hash());
     }
+
+    /// Loads the chainspec at the given path relative to the resources dir, and returns the fields
+    /// reported by validation.
+    fn invalid_fields(rel_path: &str) -> Vec<String> {
+        Chainspec::from_resources(rel_path)
+            .validate()
+            .expect_err("should be invalid")
+            .into_iter()
+            .map(|error| match error {
+                ChainspecValidationError::OutOfRange { field, .. }
+                | ChainspecValidationError::Inconsistent { field, .. }
+                | ChainspecValidationError::DuplicateAccount { field, .. }
+                | ChainspecValidationError::MalformedKey { field, .. } => field,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn production_chainspec_should_be_valid() {
+        let chainspec = Chainspec::from_resources("production");
+        assert_eq!(chainspec.validate(), Ok(()));
+        assert!(chainspec.is_valid());
+    }
+
+    #[test]
+    fn should_report_all_out_of_range_values() {
+        let fields = invalid_fields("test/invalid/out_of_range");
+        assert_eq!(
+            fields,
+            vec![
+                "accounts[01b4815d64e022127b117d25436e4b9a9d9d1338b0c6cb09db3995d4d3ac84d86b]\
+                .validator.delegation_rate",
+                "accounts[01ee8b5ec54251a9f2f92edbbf328fcdb78449ae2cca2743d591f1b7e83b6d9049]\
+                .validator.bonded_amount",
+                "core.validator_slots",
+                "highway.finality_threshold_fraction",
+                "deploys.max_named_key_length",
+                "deploys.block_gas_limit",
+                "deploys.max_gas_price",
+                "deploys.allowed_approval_algorithms",
+            ]
+        );
+    }
+
+    #[test]
+    fn should_report_all_inconsistent_values() {
+        let fields = invalid_fields("test/invalid/inconsistent");
+        assert_eq!(
+            fields,
+            vec![
+                "delegators[020248509e67db3127f82d5224c5c18eac00f96d1edeadbadc8eb2c8606227b56873]\
+                .validator_public_key",
+                "core.unbonding_delay",
+                "deploys.max_deploy_size",
+                "core.era_duration",
+                "deploys.max_deploy_size",
+            ]
+        );
+    }
+
+    #[test]
+    fn should_report_duplicate_accounts() {
+        let errors = Chainspec::from_resources("test/invalid/duplicate_accounts")
+            .validate()
+            .expect_err("should be invalid");
+        assert_eq!(
+            errors,
+            vec![
+                ChainspecValidationError::DuplicateAccount {
+                    field: "accounts".to_string(),
+                    public_key:
+                        "01ee8b5ec54251a9f2f92edbbf328fcdb78449ae2cca2743d591f1b7e83b6d9049"
+                            .to_string(),
+                },
+                ChainspecValidationError::DuplicateAccount {
+                    field:
+                        "delegators[01b4815d64e022127b117d25436e4b9a9d9d1338b0c6cb09db3995d4d3ac84d86b]"
+                            .to_string(),
+                    public_key:
+                        "020248509e67db3127f82d5224c5c18eac00f96d1edeadbadc8eb2c8606227b56873"
+                            .to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn should_report_all_malformed_keys() {
+        let path = RESOURCES_PATH
+            .join("test/invalid/malformed_keys")
+            .join(CHAINSPEC_NAME);
+        let errors = match Chainspec::try_from(path.as_path()) {
+            Err(Error::LoadChainspecAccounts(ChainspecAccountsLoadError::MalformedKeys(
+                errors,
+            ))) => errors,
+            result => panic!("expected malformed keys error, got {:?}", result),
+        };
+        let fields: Vec<_> = errors
+            .into_iter()
+            .map(|error| match error {
+                ChainspecValidationError::MalformedKey { field, .. } => field,
+                error => panic!("expected malformed key error, got {}", error),
+            })
+            .collect();
+        assert_eq!(
+            fields,
+            vec![
+                "accounts[1].public_key",
+                "delegators[0].delegator_public_key"
+            ]
+        );
+    }
+
+    #[test]
+    fn json_roundtrip() {
+        let chainspec = Chainspec::from_resources("production");
+        let json = serde_json::to_string(&chainspec).expect("should encode to JSON");
+        let decoded: Chainspec = serde_json::from_str(&json).expect("should decode from JSON");
+        assert_eq!(decoded, chainspec);
+    }
 }
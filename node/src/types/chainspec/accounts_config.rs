@@ -4,19 +4,24 @@ mod account_config;
 mod delegator_config;
 mod validator_config;
 
-use std::path::Path;
+use std::{collections::BTreeSet, path::Path};
 
 use datasize::DataSize;
 use serde::{Deserialize, Deserializer, Serialize};
+use toml::Value;
 
 use casper_execution_engine::core::engine_state::GenesisAccount;
-use casper_types::bytesrepr::{self, FromBytes, ToBytes};
+use casper_types::{
+    bytesrepr::{self, FromBytes, ToBytes},
+    system::auction::DELEGATION_RATE_DENOMINATOR,
+    AsymmetricType, PublicKey,
+};
 
 #[cfg(test)]
 use crate::testing::TestRng;
 use crate::utils::{self, Loadable};
 
-use super::error::ChainspecAccountsLoadError;
+use super::error::{ChainspecAccountsLoadError, ChainspecValidationError};
 pub use account_config::AccountConfig;
 pub use delegator_config::DelegatorConfig;
 pub use validator_config::ValidatorConfig;
@@ -57,6 +62,77 @@ impl AccountsConfig {
         &self.delegators
     }
 
+    /// Checks whether the accounts and delegators make sense, pushing any problems to `errors`.
+    pub(super) fn validate(&self, errors: &mut Vec<ChainspecValidationError>) {
+        let mut account_keys = BTreeSet::new();
+        for account in &self.accounts {
+            let public_key = account.public_key();
+            let field = format!("accounts[{}]", public_key.to_hex());
+            if public_key == PublicKey::System {
+                errors.push(ChainspecValidationError::MalformedKey {
+                    field: format!("{}.public_key", field),
+                    reason: "the system public key cannot be used for an account".to_string(),
+                });
+            }
+            if !account_keys.insert(public_key.clone()) {
+                errors.push(ChainspecValidationError::DuplicateAccount {
+                    field: "accounts".to_string(),
+                    public_key: public_key.to_hex(),
+                });
+            }
+            if let Some(validator_config) = account.validator_config() {
+                if validator_config.bonded_amount().value().is_zero() {
+                    errors.push(ChainspecValidationError::out_of_range(
+                        &format!("{}.validator.bonded_amount", field),
+                        0,
+                        "greater than 0",
+                    ));
+                }
+                if validator_config.delegation_rate() > DELEGATION_RATE_DENOMINATOR {
+                    errors.push(ChainspecValidationError::out_of_range(
+                        &format!("{}.validator.delegation_rate", field),
+                        validator_config.delegation_rate(),
+                        format!("at most {}", DELEGATION_RATE_DENOMINATOR),
+                    ));
+                }
+            }
+        }
+
+        let genesis_validators: BTreeSet<PublicKey> = self
+            .accounts
+            .iter()
+            .filter(|account| account.is_genesis_validator())
+            .map(|account| account.public_key())
+            .collect();
+        let mut delegations = BTreeSet::new();
+        for delegator in &self.delegators {
+            let delegator_public_key = delegator.delegator_public_key();
+            let validator_public_key = delegator.validator_public_key();
+            let field = format!("delegators[{}]", delegator_public_key.to_hex());
+            if *delegator_public_key == PublicKey::System {
+                errors.push(ChainspecValidationError::MalformedKey {
+                    field: format!("{}.delegator_public_key", field),
+                    reason: "the system public key cannot be used for a delegator".to_string(),
+                });
+            }
+            if !delegations.insert((delegator_public_key, validator_public_key)) {
+                errors.push(ChainspecValidationError::DuplicateAccount {
+                    field: format!("delegators[{}]", validator_public_key.to_hex()),
+                    public_key: delegator_public_key.to_hex(),
+                });
+            }
+            if !genesis_validators.contains(validator_public_key) {
+                errors.push(ChainspecValidationError::inconsistent(
+                    &format!("{}.validator_public_key", field),
+                    format!(
+                        "is {}, which is not a genesis validator in [accounts]",
+                        validator_public_key.to_hex()
+                    ),
+                ));
+            }
+        }
+    }
+
     #[cfg(test)]
     /// Generates a random instance using a `TestRng`.
     pub fn random(rng: &mut TestRng) -> Self {
@@ -111,11 +187,57 @@ impl Loadable for AccountsConfig {
             return Ok(AccountsConfig::new(vec![], vec![]));
         }
         let bytes = utils::read_file(accounts_path)?;
-        let toml_chainspec: AccountsConfig = toml::from_slice(&bytes)?;
+        let toml_chainspec: AccountsConfig = toml::from_slice(&bytes).map_err(|error| {
+            // Report every malformed key at once rather than only the first one hit by the
+            // decoder.
+            let errors = malformed_keys(&bytes);
+            if errors.is_empty() {
+                ChainspecAccountsLoadError::from(error)
+            } else {
+                ChainspecAccountsLoadError::MalformedKeys(errors)
+            }
+        })?;
         Ok(toml_chainspec)
     }
 }
 
+/// Returns an error for each account or delegator public key in the TOML-encoded accounts file
+/// which can't be parsed, identified by its position in the file.
+fn malformed_keys(bytes: &[u8]) -> Vec<ChainspecValidationError> {
+    let value: Value = match toml::from_slice(bytes) {
+        Ok(value) => value,
+        Err(_) => return vec![],
+    };
+    let tables: [(&str, &[&str]); 2] = [
+        ("accounts", &["public_key"]),
+        (
+            "delegators",
+            &["validator_public_key", "delegator_public_key"],
+        ),
+    ];
+    let mut errors = vec![];
+    for (table_name, key_names) in tables.iter() {
+        let entries = match value.get(table_name).and_then(Value::as_array) {
+            Some(entries) => entries,
+            None => continue,
+        };
+        for (index, entry) in entries.iter().enumerate() {
+            for key_name in key_names.iter() {
+                let field = format!("{}[{}].{}", table_name, index, key_name);
+                let reason = match entry.get(key_name).and_then(Value::as_str) {
+                    Some(hex_key) => match PublicKey::from_hex(hex_key) {
+                        Ok(_) => continue,
+                        Err(error) => error.to_string(),
+                    },
+                    None => "expected a hex-encoded public key".to_string(),
+                };
+                errors.push(ChainspecValidationError::MalformedKey { field, reason });
+            }
+        }
+    }
+    errors
+}
+
 impl From<AccountsConfig> for Vec<GenesisAccount> {
     fn from(accounts_config: AccountsConfig) -> Self {
         let mut genesis_accounts = Vec::with_capacity(accounts_config.accounts.len());
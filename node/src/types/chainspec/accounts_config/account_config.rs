@@ -6,7 +6,7 @@ use serde::{Deserialize, Serialize};
 
 use casper_execution_engine::{core::engine_state::GenesisAccount, shared::motes::Motes};
 use casper_types::{
-    bytesrepr::{self, FromBytes, ToBytes},
+    bytesrepr::{self, FromBytes, NotU8, ToBytes},
     PublicKey,
 };
 #[cfg(test)]
@@ -52,6 +52,10 @@ impl AccountConfig {
         self.validator.is_some()
     }
 
+    pub fn validator_config(&self) -> Option<ValidatorConfig> {
+        self.validator
+    }
+
     #[cfg(test)]
     /// Generates a random instance using a `TestRng`.
     pub fn random(rng: &mut TestRng) -> Self {
@@ -114,6 +118,8 @@ impl FromBytes for AccountConfig {
     }
 }
 
+impl NotU8 for AccountConfig {}
+
 impl From<AccountConfig> for GenesisAccount {
     fn from(account_config: AccountConfig) -> Self {
         let genesis_validator = account_config.validator.map(Into::into);
@@ -5,7 +5,7 @@ use serde::{Deserialize, Serialize};
 
 use casper_execution_engine::{core::engine_state::GenesisAccount, shared::motes::Motes};
 use casper_types::{
-    bytesrepr::{self, FromBytes, ToBytes},
+    bytesrepr::{self, FromBytes, NotU8, ToBytes},
     PublicKey,
 };
 #[cfg(test)]
@@ -131,6 +131,8 @@ impl FromBytes for DelegatorConfig {
     }
 }
 
+impl NotU8 for DelegatorConfig {}
+
 impl From<DelegatorConfig> for GenesisAccount {
     fn from(delegator_config: DelegatorConfig) -> Self {
         GenesisAccount::delegator(
@@ -112,7 +112,7 @@ impl FromBytes for ActivationPoint {
                 let (timestamp, remainder) = Timestamp::from_bytes(remainder)?;
                 Ok((ActivationPoint::Genesis(timestamp), remainder))
             }
-            _ => Err(bytesrepr::Error::Formatting),
+            tag => Err(bytesrepr::Error::invalid_tag::<Self>(tag)),
         }
     }
 }
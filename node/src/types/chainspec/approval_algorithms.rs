@@ -0,0 +1,178 @@
+use std::fmt::{self, Display, Formatter};
+
+use datasize::DataSize;
+#[cfg(test)]
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use casper_types::{
+    bytesrepr::{self, FromBytes, ToBytes},
+    PublicKey,
+};
+
+#[cfg(test)]
+use crate::testing::TestRng;
+
+/// A signature algorithm with which a deploy approval may be signed.
+#[derive(Copy, Clone, DataSize, PartialEq, Eq, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum ApprovalAlgorithm {
+    Ed25519,
+    Secp256k1,
+}
+
+impl ApprovalAlgorithm {
+    /// Returns the algorithm of the given signer's key, or `None` for the system account.
+    pub(crate) fn of(signer: &PublicKey) -> Option<Self> {
+        match signer {
+            PublicKey::System => None,
+            PublicKey::Ed25519(_) => Some(ApprovalAlgorithm::Ed25519),
+            PublicKey::Secp256k1(_) => Some(ApprovalAlgorithm::Secp256k1),
+        }
+    }
+
+    /// Returns the name of the algorithm as used in the chainspec.
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            ApprovalAlgorithm::Ed25519 => "ed25519",
+            ApprovalAlgorithm::Secp256k1 => "secp256k1",
+        }
+    }
+}
+
+impl Display for ApprovalAlgorithm {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        formatter.write_str(self.name())
+    }
+}
+
+/// The set of algorithms with which deploy approvals may be signed.
+///
+/// Represented in the chainspec as a list of algorithm names.  All algorithms are allowed by
+/// default.  Approvals by the system account are never restricted.
+#[derive(Copy, Clone, DataSize, PartialEq, Eq, Serialize, Deserialize, Debug)]
+#[serde(into = "Vec<ApprovalAlgorithm>", from = "Vec<ApprovalAlgorithm>")]
+pub struct ApprovalAlgorithms {
+    ed25519: bool,
+    secp256k1: bool,
+}
+
+impl ApprovalAlgorithms {
+    /// Returns whether `algorithm` is in the set.
+    pub(crate) fn contains(&self, algorithm: ApprovalAlgorithm) -> bool {
+        match algorithm {
+            ApprovalAlgorithm::Ed25519 => self.ed25519,
+            ApprovalAlgorithm::Secp256k1 => self.secp256k1,
+        }
+    }
+
+    /// Returns whether no algorithm at all is in the set.
+    pub(crate) fn is_empty(&self) -> bool {
+        !self.ed25519 && !self.secp256k1
+    }
+
+    /// Generates a random non-empty instance using a `TestRng`.
+    #[cfg(test)]
+    pub(crate) fn random(rng: &mut TestRng) -> Self {
+        let ed25519 = rng.gen();
+        ApprovalAlgorithms {
+            ed25519,
+            secp256k1: !ed25519 || rng.gen(),
+        }
+    }
+}
+
+impl Default for ApprovalAlgorithms {
+    fn default() -> Self {
+        ApprovalAlgorithms {
+            ed25519: true,
+            secp256k1: true,
+        }
+    }
+}
+
+impl From<Vec<ApprovalAlgorithm>> for ApprovalAlgorithms {
+    fn from(algorithms: Vec<ApprovalAlgorithm>) -> Self {
+        ApprovalAlgorithms {
+            ed25519: algorithms.contains(&ApprovalAlgorithm::Ed25519),
+            secp256k1: algorithms.contains(&ApprovalAlgorithm::Secp256k1),
+        }
+    }
+}
+
+impl From<ApprovalAlgorithms> for Vec<ApprovalAlgorithm> {
+    fn from(algorithms: ApprovalAlgorithms) -> Self {
+        [ApprovalAlgorithm::Ed25519, ApprovalAlgorithm::Secp256k1]
+            .iter()
+            .copied()
+            .filter(|algorithm| algorithms.contains(*algorithm))
+            .collect()
+    }
+}
+
+impl ToBytes for ApprovalAlgorithms {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        let mut buffer = bytesrepr::allocate_buffer(self)?;
+        buffer.extend(self.ed25519.to_bytes()?);
+        buffer.extend(self.secp256k1.to_bytes()?);
+        Ok(buffer)
+    }
+
+    fn serialized_length(&self) -> usize {
+        self.ed25519.serialized_length() + self.secp256k1.serialized_length()
+    }
+}
+
+impl FromBytes for ApprovalAlgorithms {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (ed25519, remainder) = bool::from_bytes(bytes)?;
+        let (secp256k1, remainder) = bool::from_bytes(remainder)?;
+        Ok((ApprovalAlgorithms { ed25519, secp256k1 }, remainder))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct Wrapper {
+        #[serde(default)]
+        allowed_approval_algorithms: ApprovalAlgorithms,
+    }
+
+    fn parse(toml: &str) -> ApprovalAlgorithms {
+        toml::from_str::<Wrapper>(toml)
+            .unwrap()
+            .allowed_approval_algorithms
+    }
+
+    #[test]
+    fn should_allow_all_algorithms_by_default() {
+        let algorithms = parse("");
+        assert_eq!(algorithms, ApprovalAlgorithms::default());
+        assert!(algorithms.contains(ApprovalAlgorithm::Ed25519));
+        assert!(algorithms.contains(ApprovalAlgorithm::Secp256k1));
+    }
+
+    #[test]
+    fn should_parse_list_of_names() {
+        let algorithms = parse("allowed_approval_algorithms = ['ed25519']");
+        assert!(algorithms.contains(ApprovalAlgorithm::Ed25519));
+        assert!(!algorithms.contains(ApprovalAlgorithm::Secp256k1));
+
+        assert!(parse("allowed_approval_algorithms = []").is_empty());
+        assert!(toml::from_str::<Wrapper>("allowed_approval_algorithms = ['rsa']").is_err());
+    }
+
+    #[test]
+    fn system_account_should_have_no_algorithm() {
+        assert_eq!(ApprovalAlgorithm::of(&PublicKey::System), None);
+    }
+
+    #[test]
+    fn bytesrepr_roundtrip() {
+        let mut rng = crate::new_rng();
+        bytesrepr::test_serialization_roundtrip(&ApprovalAlgorithms::random(&mut rng));
+    }
+}
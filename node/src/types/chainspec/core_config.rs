@@ -6,6 +6,7 @@ use serde::{Deserialize, Serialize};
 
 use casper_types::bytesrepr::{self, FromBytes, ToBytes};
 
+use super::ChainspecValidationError;
 #[cfg(test)]
 use crate::testing::TestRng;
 use crate::types::TimeDiff;
@@ -30,6 +31,46 @@ pub struct CoreConfig {
     pub(crate) round_seigniorage_rate: Ratio<u64>,
 }
 
+impl CoreConfig {
+    /// Checks whether the values set in the config make sense, pushing any problems to `errors`.
+    pub(super) fn validate(&self, errors: &mut Vec<ChainspecValidationError>) {
+        if self.validator_slots == 0 {
+            errors.push(ChainspecValidationError::out_of_range(
+                "core.validator_slots",
+                self.validator_slots,
+                "greater than 0",
+            ));
+        }
+
+        if self.minimum_era_height == 0 {
+            errors.push(ChainspecValidationError::out_of_range(
+                "core.minimum_era_height",
+                self.minimum_era_height,
+                "greater than 0",
+            ));
+        }
+
+        if self.round_seigniorage_rate > Ratio::new(1, 1) {
+            errors.push(ChainspecValidationError::out_of_range(
+                "core.round_seigniorage_rate",
+                self.round_seigniorage_rate,
+                "in the range [0, 1]",
+            ));
+        }
+
+        // Unbonding funds must not be released before the validator's bid stops taking effect.
+        if self.unbonding_delay <= self.auction_delay {
+            errors.push(ChainspecValidationError::inconsistent(
+                "core.unbonding_delay",
+                format!(
+                    "is {}, but must be greater than [core.auction_delay] {}",
+                    self.unbonding_delay, self.auction_delay
+                ),
+            ));
+        }
+    }
+}
+
 #[cfg(test)]
 impl CoreConfig {
     /// Generates a random instance using a `TestRng`.
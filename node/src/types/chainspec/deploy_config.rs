@@ -8,6 +8,7 @@ use num_traits::Zero;
 use rand::Rng;
 use serde::{Deserialize, Serialize};
 
+use casper_deploy_utils::{DeployHashScheme, DeployLimits};
 #[cfg(test)]
 use casper_execution_engine::core::engine_state::MAX_PAYMENT_AMOUNT;
 use casper_execution_engine::shared::motes::Motes;
@@ -16,9 +17,10 @@ use casper_types::{
     U512,
 };
 
+use super::{ApprovalAlgorithms, ChainspecValidationError};
 #[cfg(test)]
 use crate::testing::TestRng;
-use crate::types::TimeDiff;
+use crate::types::{TimeDiff, Timestamp};
 
 #[derive(Copy, Clone, DataSize, PartialEq, Eq, Serialize, Deserialize, Debug)]
 // Disallow unknown fields to ensure config files and command-line overrides contain valid keys.
@@ -35,6 +37,97 @@ pub struct DeployConfig {
     pub(crate) payment_args_max_length: u32,
     pub(crate) session_args_max_length: u32,
     pub(crate) native_transfer_minimum_motes: u64,
+    #[serde(default)]
+    pub(crate) max_transfers_per_batch: u32,
+    pub(crate) max_chain_name_length: u32,
+    pub(crate) max_named_key_length: u32,
+    pub(crate) max_gas_price: u64,
+    #[serde(default)]
+    pub(crate) allowed_approval_algorithms: ApprovalAlgorithms,
+    /// The time from which deploys are hashed in their respective domains, if ever.  Deploys with
+    /// earlier timestamps are hashed using the legacy scheme.
+    #[serde(default)]
+    pub(crate) domain_separated_hashing_activation: Option<Timestamp>,
+}
+
+impl DeployConfig {
+    /// Returns the limits against which deploys are checked for acceptability.
+    pub(crate) fn deploy_limits(&self) -> DeployLimits {
+        DeployLimits {
+            max_ttl: self.max_ttl,
+            max_dependencies: self.max_dependencies,
+            max_deploy_size: self.max_deploy_size,
+            payment_args_max_length: self.payment_args_max_length,
+            session_args_max_length: self.session_args_max_length,
+            native_transfer_minimum_motes: self.native_transfer_minimum_motes,
+            max_transfers_per_batch: self.max_transfers_per_batch,
+            max_chain_name_length: self.max_chain_name_length,
+            max_named_key_length: self.max_named_key_length,
+            max_gas_price: self.max_gas_price,
+        }
+    }
+
+    /// Returns the scheme with which a deploy with the given timestamp must have been hashed.
+    pub(crate) fn hash_scheme(&self, deploy_timestamp: Timestamp) -> DeployHashScheme {
+        DeployHashScheme::for_timestamp(deploy_timestamp, self.domain_separated_hashing_activation)
+    }
+
+    /// Checks whether the values set in the config make sense, pushing any problems to `errors`.
+    pub(super) fn validate(&self, errors: &mut Vec<ChainspecValidationError>) {
+        if self.max_ttl.millis() == 0 {
+            errors.push(ChainspecValidationError::out_of_range(
+                "deploys.max_ttl",
+                self.max_ttl,
+                "greater than 0",
+            ));
+        }
+
+        for (field, value) in &[
+            ("deploys.max_block_size", self.max_block_size),
+            ("deploys.max_deploy_size", self.max_deploy_size),
+            ("deploys.max_chain_name_length", self.max_chain_name_length),
+            ("deploys.max_named_key_length", self.max_named_key_length),
+        ] {
+            if *value == 0 {
+                errors.push(ChainspecValidationError::out_of_range(
+                    field,
+                    value,
+                    "greater than 0",
+                ));
+            }
+        }
+
+        for (field, value) in &[
+            ("deploys.block_gas_limit", self.block_gas_limit),
+            ("deploys.max_gas_price", self.max_gas_price),
+        ] {
+            if *value == 0 {
+                errors.push(ChainspecValidationError::out_of_range(
+                    field,
+                    value,
+                    "greater than 0",
+                ));
+            }
+        }
+
+        if self.allowed_approval_algorithms.is_empty() {
+            errors.push(ChainspecValidationError::out_of_range(
+                "deploys.allowed_approval_algorithms",
+                "empty",
+                "non-empty",
+            ));
+        }
+
+        if self.max_deploy_size > self.max_block_size {
+            errors.push(ChainspecValidationError::inconsistent(
+                "deploys.max_deploy_size",
+                format!(
+                    "is {}, but must not be greater than [deploys.max_block_size] {}",
+                    self.max_deploy_size, self.max_block_size
+                ),
+            ));
+        }
+    }
 }
 
 #[cfg(test)]
@@ -53,6 +146,16 @@ impl DeployConfig {
         let session_args_max_length = rng.gen();
         let native_transfer_minimum_motes =
             rng.gen_range(MAX_PAYMENT_AMOUNT..1_000_000_000_000_000);
+        let max_transfers_per_batch = rng.gen_range(0..100);
+        let max_chain_name_length = rng.gen_range(1..1_000);
+        let max_named_key_length = rng.gen_range(1..1_000);
+        let max_gas_price = rng.gen_range(100..1_000_000);
+        let allowed_approval_algorithms = ApprovalAlgorithms::random(rng);
+        let domain_separated_hashing_activation = if rng.gen() {
+            Some(Timestamp::random(rng))
+        } else {
+            None
+        };
 
         DeployConfig {
             max_payment_cost,
@@ -66,6 +169,12 @@ impl DeployConfig {
             payment_args_max_length,
             session_args_max_length,
             native_transfer_minimum_motes,
+            max_transfers_per_batch,
+            max_chain_name_length,
+            max_named_key_length,
+            max_gas_price,
+            allowed_approval_algorithms,
+            domain_separated_hashing_activation,
         }
     }
 }
@@ -85,6 +194,12 @@ impl Default for DeployConfig {
             payment_args_max_length: 1024,
             session_args_max_length: 1024,
             native_transfer_minimum_motes: MAX_PAYMENT_AMOUNT,
+            max_transfers_per_batch: 20,
+            max_chain_name_length: 128,
+            max_named_key_length: 256,
+            max_gas_price: 1_000,
+            allowed_approval_algorithms: ApprovalAlgorithms::default(),
+            domain_separated_hashing_activation: None,
         }
     }
 }
@@ -103,6 +218,12 @@ impl ToBytes for DeployConfig {
         buffer.extend(self.payment_args_max_length.to_bytes()?);
         buffer.extend(self.session_args_max_length.to_bytes()?);
         buffer.extend(self.native_transfer_minimum_motes.to_bytes()?);
+        buffer.extend(self.max_transfers_per_batch.to_bytes()?);
+        buffer.extend(self.max_chain_name_length.to_bytes()?);
+        buffer.extend(self.max_named_key_length.to_bytes()?);
+        buffer.extend(self.max_gas_price.to_bytes()?);
+        buffer.extend(self.allowed_approval_algorithms.to_bytes()?);
+        buffer.extend(self.domain_separated_hashing_activation.to_bytes()?);
         Ok(buffer)
     }
 
@@ -118,6 +239,12 @@ impl ToBytes for DeployConfig {
             + self.payment_args_max_length.serialized_length()
             + self.session_args_max_length.serialized_length()
             + self.native_transfer_minimum_motes.serialized_length()
+            + self.max_transfers_per_batch.serialized_length()
+            + self.max_chain_name_length.serialized_length()
+            + self.max_named_key_length.serialized_length()
+            + self.max_gas_price.serialized_length()
+            + self.allowed_approval_algorithms.serialized_length()
+            + self.domain_separated_hashing_activation.serialized_length()
     }
 }
 
@@ -135,6 +262,13 @@ impl FromBytes for DeployConfig {
         let (payment_args_max_length, remainder) = u32::from_bytes(remainder)?;
         let (session_args_max_length, remainder) = u32::from_bytes(remainder)?;
         let (native_transfer_minimum_motes, remainder) = u64::from_bytes(remainder)?;
+        let (max_transfers_per_batch, remainder) = u32::from_bytes(remainder)?;
+        let (max_chain_name_length, remainder) = u32::from_bytes(remainder)?;
+        let (max_named_key_length, remainder) = u32::from_bytes(remainder)?;
+        let (max_gas_price, remainder) = u64::from_bytes(remainder)?;
+        let (allowed_approval_algorithms, remainder) = ApprovalAlgorithms::from_bytes(remainder)?;
+        let (domain_separated_hashing_activation, remainder) =
+            Option::<Timestamp>::from_bytes(remainder)?;
         let config = DeployConfig {
             max_payment_cost,
             max_ttl,
@@ -147,6 +281,12 @@ impl FromBytes for DeployConfig {
             payment_args_max_length,
             session_args_max_length,
             native_transfer_minimum_motes,
+            max_transfers_per_batch,
+            max_chain_name_length,
+            max_named_key_length,
+            max_gas_price,
+            allowed_approval_algorithms,
+            domain_separated_hashing_activation,
         };
         Ok((config, remainder))
     }
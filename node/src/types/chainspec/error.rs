@@ -1,5 +1,6 @@
-use std::{io, path::PathBuf};
+use std::{fmt::Display, io, path::PathBuf};
 
+use itertools::Itertools;
 use thiserror::Error;
 use uint::FromDecStrErr;
 
@@ -77,6 +78,10 @@ pub enum ChainspecAccountsLoadError {
     /// Error while decoding a chainspec account's key hash from base-64 format.
     #[error("crypto module error: {0}")]
     Crypto(#[from] crate::crypto::Error),
+
+    /// One or more keys in the accounts file are malformed.
+    #[error("malformed keys: {}", display_errors(.0))]
+    MalformedKeys(Vec<ChainspecValidationError>),
 }
 
 /// Error loading global state update file.
@@ -98,3 +103,67 @@ pub enum GlobalStateUpdateLoadError {
     #[error("decoding from formatted string error: {0}")]
     DecodingKeyFromStr(String),
 }
+
+/// A problem found while validating a chainspec, identifying the offending field by its path, e.g.
+/// `core.unbonding_delay`.
+#[derive(Clone, PartialEq, Eq, Debug, Error)]
+pub enum ChainspecValidationError {
+    /// A value is outside its permitted range.
+    #[error("[{field}] is {value}, but must be {expected}")]
+    OutOfRange {
+        /// The path of the field.
+        field: String,
+        /// The value of the field.
+        value: String,
+        /// A description of the permitted range.
+        expected: String,
+    },
+
+    /// A value is inconsistent with the value of another field.
+    #[error("[{field}] {reason}")]
+    Inconsistent {
+        /// The path of the field.
+        field: String,
+        /// A description of the inconsistency.
+        reason: String,
+    },
+
+    /// The same account is specified more than once.
+    #[error("[{field}] contains {public_key} more than once")]
+    DuplicateAccount {
+        /// The path of the field.
+        field: String,
+        /// The public key of the duplicated account.
+        public_key: String,
+    },
+
+    /// A key or hash is malformed.
+    #[error("[{field}] is malformed: {reason}")]
+    MalformedKey {
+        /// The path of the field.
+        field: String,
+        /// A description of the problem.
+        reason: String,
+    },
+}
+
+impl ChainspecValidationError {
+    pub(super) fn out_of_range<V: Display, E: Display>(field: &str, value: V, expected: E) -> Self {
+        ChainspecValidationError::OutOfRange {
+            field: field.to_string(),
+            value: value.to_string(),
+            expected: expected.to_string(),
+        }
+    }
+
+    pub(super) fn inconsistent<R: Display>(field: &str, reason: R) -> Self {
+        ChainspecValidationError::Inconsistent {
+            field: field.to_string(),
+            reason: reason.to_string(),
+        }
+    }
+}
+
+fn display_errors(errors: &[ChainspecValidationError]) -> String {
+    errors.iter().map(ToString::to_string).join("; ")
+}
@@ -48,6 +48,16 @@ impl Loadable for Option<GlobalStateUpdateConfig> {
 #[derive(Clone, PartialEq, Eq, Serialize, Deserialize, DataSize, Debug)]
 pub struct GlobalStateUpdate(pub(crate) BTreeMap<Key, Bytes>);
 
+impl Loadable for Option<GlobalStateUpdate> {
+    type Error = GlobalStateUpdateLoadError;
+
+    fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, Self::Error> {
+        Option::<GlobalStateUpdateConfig>::from_path(path)?
+            .map(GlobalStateUpdate::try_from)
+            .transpose()
+    }
+}
+
 impl ToBytes for GlobalStateUpdate {
     fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
         self.0.to_bytes()
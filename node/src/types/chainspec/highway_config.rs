@@ -3,14 +3,18 @@ use num::rational::Ratio;
 #[cfg(test)]
 use rand::Rng;
 use serde::{Deserialize, Serialize};
-use tracing::error;
 
 use casper_types::bytesrepr::{self, FromBytes, ToBytes};
 
+use super::ChainspecValidationError;
 #[cfg(test)]
 use crate::testing::TestRng;
 use crate::types::TimeDiff;
 
+/// Round exponents must be lower than this, as round lengths are computed as a power of two
+/// milliseconds held in a `u64`.
+const MAX_ROUND_EXPONENT_LIMIT: u8 = 64;
+
 #[derive(Copy, Clone, DataSize, PartialEq, Eq, Serialize, Deserialize, Debug)]
 // Disallow unknown fields to ensure config files and command-line overrides contain valid keys.
 #[serde(deny_unknown_fields)]
@@ -26,36 +30,62 @@ pub(crate) struct HighwayConfig {
 }
 
 impl HighwayConfig {
-    /// Checks whether the values set in the config make sense and returns `false` if they don't.
-    pub(super) fn is_valid(&self) -> bool {
+    /// Checks whether the values set in the config make sense, pushing any problems to `errors`.
+    pub(super) fn validate(&self, errors: &mut Vec<ChainspecValidationError>) {
+        for (field, exponent) in &[
+            (
+                "highway.minimum_round_exponent",
+                self.minimum_round_exponent,
+            ),
+            (
+                "highway.maximum_round_exponent",
+                self.maximum_round_exponent,
+            ),
+        ] {
+            if *exponent >= MAX_ROUND_EXPONENT_LIMIT {
+                errors.push(ChainspecValidationError::out_of_range(
+                    field,
+                    exponent,
+                    format!("less than {}", MAX_ROUND_EXPONENT_LIMIT),
+                ));
+            }
+        }
+
         if self.minimum_round_exponent > self.maximum_round_exponent {
-            error!(
-                min = %self.minimum_round_exponent,
-                max = %self.maximum_round_exponent,
-                "minimum round exponent is greater than the maximum round exponent",
-            );
-            return false;
+            errors.push(ChainspecValidationError::inconsistent(
+                "highway.minimum_round_exponent",
+                format!(
+                    "is {}, but must not be greater than [highway.maximum_round_exponent] {}",
+                    self.minimum_round_exponent, self.maximum_round_exponent
+                ),
+            ));
         }
 
         if self.finality_threshold_fraction <= Ratio::new(0, 1)
             || self.finality_threshold_fraction >= Ratio::new(1, 1)
         {
-            error!(
-                ftf = %self.finality_threshold_fraction,
-                "finality threshold fraction is not in the range (0, 1)",
-            );
-            return false;
+            errors.push(ChainspecValidationError::out_of_range(
+                "highway.finality_threshold_fraction",
+                self.finality_threshold_fraction,
+                "in the range (0, 1)",
+            ));
         }
 
         if self.reduced_reward_multiplier > Ratio::new(1, 1) {
-            error!(
-                rrm = %self.reduced_reward_multiplier,
-                "reduced reward multiplier is not in the range [0, 1]",
-            );
-            return false;
+            errors.push(ChainspecValidationError::out_of_range(
+                "highway.reduced_reward_multiplier",
+                self.reduced_reward_multiplier,
+                "in the range [0, 1]",
+            ));
         }
+    }
 
-        true
+    /// Returns `true` if `validate` finds no problems with the config.
+    #[cfg(test)]
+    pub(super) fn is_valid(&self) -> bool {
+        let mut errors = vec![];
+        self.validate(&mut errors);
+        errors.is_empty()
     }
 
     /// Returns the length of the shortest allowed round.
@@ -1,7 +1,7 @@
 use datasize::DataSize;
 #[cfg(test)]
 use rand::Rng;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use casper_execution_engine::shared::motes::Motes;
 use casper_types::{
@@ -9,11 +9,11 @@ use casper_types::{
     PublicKey,
 };
 
-use super::AccountsConfig;
+use super::{AccountsConfig, ChainspecValidationError};
 #[cfg(test)]
 use crate::testing::TestRng;
 
-#[derive(Clone, DataSize, PartialEq, Eq, Serialize, Debug)]
+#[derive(Clone, DataSize, PartialEq, Eq, Serialize, Deserialize, Debug)]
 pub struct NetworkConfig {
     /// The network name.
     pub(crate) name: String,
@@ -40,6 +40,27 @@ impl NetworkConfig {
             })
             .collect()
     }
+
+    /// Checks whether the values set in the config make sense, pushing any problems to `errors`.
+    pub(super) fn validate(&self, errors: &mut Vec<ChainspecValidationError>) {
+        if self.name.is_empty() {
+            errors.push(ChainspecValidationError::out_of_range(
+                "network.name",
+                "empty",
+                "non-empty",
+            ));
+        }
+
+        if self.maximum_net_message_size == 0 {
+            errors.push(ChainspecValidationError::out_of_range(
+                "network.maximum_net_message_size",
+                self.maximum_net_message_size,
+                "greater than 0",
+            ));
+        }
+
+        self.accounts_config.validate(errors);
+    }
 }
 
 #[cfg(test)]
@@ -6,7 +6,7 @@
 //! contains the accounts' details, rather than the chainspec file containing the accounts' details
 //! itself.
 
-use std::{convert::TryFrom, path::Path};
+use std::path::Path;
 
 use serde::{Deserialize, Serialize};
 
@@ -14,9 +14,8 @@ use casper_execution_engine::shared::{system_config::SystemConfig, wasm_config::
 use casper_types::{EraId, ProtocolVersion};
 
 use super::{
-    accounts_config::AccountsConfig, global_state_update::GlobalStateUpdateConfig, ActivationPoint,
-    Chainspec, CoreConfig, DeployConfig, Error, GlobalStateUpdate, HighwayConfig, NetworkConfig,
-    ProtocolConfig,
+    accounts_config::AccountsConfig, ActivationPoint, Chainspec, CoreConfig, DeployConfig, Error,
+    GlobalStateUpdate, HighwayConfig, NetworkConfig, ProtocolConfig,
 };
 use crate::utils::{self, Loadable};
 
@@ -100,9 +99,7 @@ pub(super) fn parse_toml<P: AsRef<Path>>(chainspec_path: P) -> Result<Chainspec,
         maximum_net_message_size: toml_chainspec.network.maximum_net_message_size,
     };
 
-    let global_state_update = Option::<GlobalStateUpdateConfig>::from_path(root)?
-        .map(GlobalStateUpdate::try_from)
-        .transpose()?;
+    let global_state_update = Option::<GlobalStateUpdate>::from_path(root)?;
 
     let protocol_config = ProtocolConfig {
         version: toml_chainspec.protocol.version,
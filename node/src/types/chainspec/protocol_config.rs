@@ -7,14 +7,13 @@ use datasize::DataSize;
 #[cfg(test)]
 use rand::Rng;
 use serde::{Deserialize, Serialize};
-use tracing::error;
 
 use casper_types::{
     bytesrepr::{self, FromBytes, ToBytes},
     EraId, ProtocolVersion,
 };
 
-use super::{ActivationPoint, GlobalStateUpdate};
+use super::{ActivationPoint, ChainspecValidationError, GlobalStateUpdate};
 #[cfg(test)]
 use crate::testing::TestRng;
 
@@ -35,51 +34,53 @@ pub struct ProtocolConfig {
 }
 
 impl ProtocolConfig {
-    /// Checks whether the values set in the config make sense and returns `false` if they don't.
-    pub(super) fn is_valid(&self) -> bool {
+    /// Checks whether the values set in the config make sense, pushing any problems to `errors`.
+    pub(super) fn validate(&self, errors: &mut Vec<ChainspecValidationError>) {
+        let activation_point = self.activation_point.era_id();
         // If this is not an emergency restart config, assert the `last_emergency_restart` is `None`
         // or less than `activation_point`.
         if self.global_state_update.is_none() {
             if let Some(last_emergency_restart) = self.last_emergency_restart {
-                let activation_point = self.activation_point.era_id();
                 if last_emergency_restart >= activation_point {
-                    error!(
-                        %activation_point,
-                        %last_emergency_restart,
-                        "[protocol.last_emergency_restart] must be lower than \
-                        [protocol.activation_point] in the chainspec."
-                    );
-                    return false;
-                };
+                    errors.push(ChainspecValidationError::inconsistent(
+                        "protocol.last_emergency_restart",
+                        format!(
+                            "is {}, but must be lower than [protocol.activation_point] {}",
+                            last_emergency_restart, activation_point
+                        ),
+                    ));
+                }
             }
-            return true;
+            return;
         }
 
         // If this IS an emergency restart config, assert the `last_emergency_restart` is `Some` and
         // equal to `activation_point`.
-        let last_emergency_restart = match self.last_emergency_restart {
-            Some(era_id) => era_id,
+        let reason = match self.last_emergency_restart {
             None => {
-                error!(
-                    "[protocol.last_emergency_restart] must exist in the chainspec since a global \
-                    state update was provided, implying this upgrade is an emergency restart."
-                );
-                return false;
+                "must exist since a global state update was provided, implying this upgrade is \
+                an emergency restart"
+                    .to_string()
             }
+            Some(last_emergency_restart) if last_emergency_restart != activation_point => format!(
+                "is {}, but must equal [protocol.activation_point] {} since a global state update \
+                was provided, implying this upgrade is an emergency restart",
+                last_emergency_restart, activation_point
+            ),
+            Some(_) => return,
         };
-        let activation_point = self.activation_point.era_id();
-        if activation_point != last_emergency_restart {
-            error!(
-                %activation_point,
-                %last_emergency_restart,
-                "[protocol.last_emergency_restart] must equal [protocol.activation_point] in the \
-                chainspec since a global state update was provided, implying this upgrade is an \
-                emergency restart."
-            );
-            return false;
-        }
+        errors.push(ChainspecValidationError::inconsistent(
+            "protocol.last_emergency_restart",
+            reason,
+        ));
+    }
 
-        true
+    /// Returns `true` if `validate` finds no problems with the config.
+    #[cfg(test)]
+    pub(super) fn is_valid(&self) -> bool {
+        let mut errors = vec![];
+        self.validate(&mut errors);
+        errors.is_empty()
     }
 
     /// Generates a random instance using a `TestRng`.
@@ -0,0 +1,100 @@
+use datasize::DataSize;
+use serde::{Deserialize, Serialize};
+
+use crate::types::{DeployHash, Timestamp};
+
+/// The spread of the times at which a deploy was first seen by the peers probed for it.
+///
+/// Each delta is the number of milliseconds from this node first seeing the deploy to a peer first
+/// seeing it.  The times are read from each node's own clock, so deltas include any clock skew
+/// between the nodes and may be negative.
+#[derive(Clone, DataSize, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DeploySpread {
+    /// The hash of the deploy.
+    pub deploy_hash: DeployHash,
+    /// The time at which this node first saw the deploy.
+    pub first_seen: Timestamp,
+    /// The number of peers probed for the deploy.
+    pub peers_probed: u32,
+    /// The number of probed peers which responded.
+    pub peers_responded: u32,
+    /// The number of responding peers which hadn't seen the deploy.
+    pub peers_not_seen: u32,
+    /// The smallest delta of the peers which had seen the deploy.
+    pub min_delta_millis: Option<i64>,
+    /// The median delta of the peers which had seen the deploy.
+    pub median_delta_millis: Option<i64>,
+    /// The largest delta of the peers which had seen the deploy.
+    pub max_delta_millis: Option<i64>,
+}
+
+impl DeploySpread {
+    /// Constructs the spread of a deploy first seen by this node at `first_seen` from the times
+    /// reported by each responding peer, `None` meaning the peer hadn't seen the deploy.
+    pub(crate) fn new(
+        deploy_hash: DeployHash,
+        first_seen: Timestamp,
+        peers_probed: u32,
+        reports: &[Option<Timestamp>],
+    ) -> Self {
+        let mut deltas: Vec<i64> = reports
+            .iter()
+            .flatten()
+            .map(|peer_first_seen| peer_first_seen.millis() as i64 - first_seen.millis() as i64)
+            .collect();
+        deltas.sort_unstable();
+        let median_delta_millis = match deltas.len() {
+            0 => None,
+            len if len % 2 == 1 => Some(deltas[len / 2]),
+            len => Some((deltas[len / 2 - 1] + deltas[len / 2]) / 2),
+        };
+        DeploySpread {
+            deploy_hash,
+            first_seen,
+            peers_probed,
+            peers_responded: reports.len() as u32,
+            peers_not_seen: (reports.len() - deltas.len()) as u32,
+            min_delta_millis: deltas.first().copied(),
+            median_delta_millis,
+            max_delta_millis: deltas.last().copied(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestRng;
+
+    #[test]
+    fn should_compute_spread_of_reported_times() {
+        let mut rng = TestRng::new();
+        let deploy_hash = DeployHash::random(&mut rng);
+        let first_seen = Timestamp::from(10_000);
+        let at = |millis: u64| Some(Timestamp::from(millis));
+
+        let spread = DeploySpread::new(
+            deploy_hash,
+            first_seen,
+            5,
+            &[at(10_300), None, at(9_900), at(10_100)],
+        );
+        assert_eq!(spread.peers_probed, 5);
+        assert_eq!(spread.peers_responded, 4);
+        assert_eq!(spread.peers_not_seen, 1);
+        assert_eq!(spread.min_delta_millis, Some(-100));
+        assert_eq!(spread.median_delta_millis, Some(100));
+        assert_eq!(spread.max_delta_millis, Some(300));
+
+        // The median of an even number of deltas is the mean of the middle two.
+        let spread = DeploySpread::new(deploy_hash, first_seen, 2, &[at(10_100), at(10_400)]);
+        assert_eq!(spread.median_delta_millis, Some(250));
+
+        let spread = DeploySpread::new(deploy_hash, first_seen, 3, &[None, None]);
+        assert_eq!(spread.peers_not_seen, 2);
+        assert_eq!(spread.min_delta_millis, None);
+        assert_eq!(spread.median_delta_millis, None);
+        assert_eq!(spread.max_delta_millis, None);
+    }
+}
@@ -0,0 +1,388 @@
+// TODO - remove once schemars stops causing warning.
+#![allow(clippy::field_reassign_with_default)]
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use casper_execution_engine::shared::{gas::Gas, motes::Motes};
+use casper_types::{ExecutionResult, U512};
+
+use super::{BlockHash, BlockHeader, DeployHeader, DeployMetadata, Timestamp};
+use crate::components::block_proposer::DeployBufferStatus;
+
+/// The lifecycle stage a deploy has reached, as far as this node knows.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "status", rename_all = "snake_case", deny_unknown_fields)]
+pub enum DeployStatus {
+    /// The deploy is not known to this node.
+    Unknown,
+    /// The deploy has been accepted and stored, but not yet included in a block.
+    Pending {
+        /// Whether the deploy is buffered for inclusion in a block proposed by this node.  It is
+        /// only buffered once it has been gossiped to the network.
+        buffered: bool,
+    },
+    /// The deploy has been included in at least one block proposed by this node, none of which
+    /// has been finalized yet.
+    Proposed {
+        /// The timestamp of the latest such block.
+        latest_proposal_timestamp: Timestamp,
+    },
+    /// The deploy has been included in a finalized block, which has not been executed yet.
+    Finalized {
+        /// The hash of the finalized block, if it is already stored.
+        block_hash: Option<BlockHash>,
+    },
+    /// The deploy has been executed in a finalized block.
+    Executed {
+        /// The hash of the block in which the deploy was executed.
+        block_hash: BlockHash,
+        /// The height of that block, if its header is already stored.
+        block_height: Option<u64>,
+        /// The gas cost of the execution.
+        cost: U512,
+        /// The cost of the execution in motes, i.e. the gas cost multiplied by the deploy's gas
+        /// price, or `None` if the multiplication overflows.
+        cost_in_motes: Option<U512>,
+        /// The error message if the execution failed, or `None` if it succeeded.
+        error_message: Option<String>,
+    },
+    /// The deploy expired before being included in a finalized block.
+    Expired {
+        /// The time at which the deploy expired.
+        expired_at: Timestamp,
+    },
+    /// The deploy has been replaced by one with the same body and a higher gas price, and will
+    /// never be proposed by this node.
+    Replaced,
+    /// The deploy has been evicted from the full buffer, and will never be proposed by this node.
+    Evicted,
+}
+
+impl DeployStatus {
+    /// Aggregates the deploy's status from what the block proposer's buffer, the deploy store and
+    /// the block store know of it at `now`.
+    ///
+    /// Since these are queried one after the other, they may disagree if the deploy advanced in
+    /// the meantime.  The most advanced stage wins, so e.g. a deploy executed since the buffer was
+    /// queried is reported as executed rather than pending.
+    pub(crate) fn new(
+        buffer_status: DeployBufferStatus,
+        maybe_header_and_metadata: Option<(DeployHeader, DeployMetadata)>,
+        maybe_block_header: Option<BlockHeader>,
+        now: Timestamp,
+    ) -> Self {
+        let maybe_block_hash = maybe_block_header.as_ref().map(BlockHeader::hash);
+
+        let (header, metadata) = match maybe_header_and_metadata {
+            Some(header_and_metadata) => header_and_metadata,
+            None => {
+                return match (buffer_status, maybe_block_hash) {
+                    (_, Some(block_hash)) => DeployStatus::Finalized {
+                        block_hash: Some(block_hash),
+                    },
+                    (DeployBufferStatus::Finalized, None) => {
+                        DeployStatus::Finalized { block_hash: None }
+                    }
+                    _ => DeployStatus::Unknown,
+                };
+            }
+        };
+
+        // Prefer the result of executing the deploy in the block the block store has indexed it
+        // under, falling back to any other block's.
+        let maybe_execution = maybe_block_header
+            .as_ref()
+            .and_then(|block_header| {
+                let block_hash = block_header.hash();
+                metadata
+                    .execution_results
+                    .get(&block_hash)
+                    .map(|result| (block_hash, Some(block_header.height()), result))
+            })
+            .or_else(|| {
+                metadata
+                    .execution_results
+                    .iter()
+                    .next()
+                    .map(|(block_hash, result)| (*block_hash, None, result))
+            });
+        if let Some((block_hash, block_height, result)) = maybe_execution {
+            let (cost, error_message) = match result {
+                ExecutionResult::Failure {
+                    cost,
+                    error_message,
+                    ..
+                } => (*cost, Some(error_message.clone())),
+                ExecutionResult::Success { cost, .. } => (*cost, None),
+            };
+            let cost_in_motes =
+                Motes::from_gas(Gas::new(cost), header.gas_price()).map(|motes| motes.value());
+            return DeployStatus::Executed {
+                block_hash,
+                block_height,
+                cost,
+                cost_in_motes,
+                error_message,
+            };
+        }
+
+        if maybe_block_hash.is_some() || buffer_status == DeployBufferStatus::Finalized {
+            return DeployStatus::Finalized {
+                block_hash: maybe_block_hash,
+            };
+        }
+
+        match buffer_status {
+            DeployBufferStatus::Replaced => DeployStatus::Replaced,
+            DeployBufferStatus::Evicted => DeployStatus::Evicted,
+            _ if header.expired(now) => DeployStatus::Expired {
+                expired_at: header.expires(),
+            },
+            DeployBufferStatus::Proposed { latest_proposal } => DeployStatus::Proposed {
+                latest_proposal_timestamp: latest_proposal,
+            },
+            DeployBufferStatus::Pending => DeployStatus::Pending { buffered: true },
+            DeployBufferStatus::Unknown | DeployBufferStatus::Finalized => {
+                DeployStatus::Pending { buffered: false }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use rand::Rng;
+
+    use casper_types::{EraId, ProtocolVersion};
+
+    use super::*;
+    use crate::{
+        testing::TestRng,
+        types::{Block, Deploy, TimeDiff},
+    };
+
+    fn header_and_metadata(rng: &mut TestRng) -> (DeployHeader, DeployMetadata) {
+        let deploy = Deploy::random(rng);
+        (deploy.header().clone(), DeployMetadata::default())
+    }
+
+    /// Returns a time at which the given header hasn't expired yet.
+    fn before_expiry(header: &DeployHeader) -> Timestamp {
+        header.timestamp()
+    }
+
+    /// Returns a time at which the given header has expired.
+    fn after_expiry(header: &DeployHeader) -> Timestamp {
+        header.expires() + TimeDiff::from(Duration::from_millis(1))
+    }
+
+    fn random_block_header(rng: &mut TestRng) -> BlockHeader {
+        Block::random_with_specifics(rng, EraId::from(1), 10, ProtocolVersion::V1_0_0, false)
+            .take_header()
+    }
+
+    #[test]
+    fn should_report_unknown_deploy() {
+        let mut rng = crate::new_rng();
+        assert_eq!(
+            DeployStatus::new(DeployBufferStatus::Unknown, None, None, Timestamp::now()),
+            DeployStatus::Unknown
+        );
+        // The buffer never holds a deploy missing from storage, but if it did, it couldn't be
+        // reported as pending without the deploy's header.
+        let (header, _) = header_and_metadata(&mut rng);
+        assert_eq!(
+            DeployStatus::new(
+                DeployBufferStatus::Pending,
+                None,
+                None,
+                before_expiry(&header)
+            ),
+            DeployStatus::Unknown
+        );
+    }
+
+    #[test]
+    fn should_report_pending_deploy() {
+        let mut rng = crate::new_rng();
+        let (header, metadata) = header_and_metadata(&mut rng);
+        let now = before_expiry(&header);
+        assert_eq!(
+            DeployStatus::new(
+                DeployBufferStatus::Unknown,
+                Some((header.clone(), metadata.clone())),
+                None,
+                now
+            ),
+            DeployStatus::Pending { buffered: false }
+        );
+        assert_eq!(
+            DeployStatus::new(
+                DeployBufferStatus::Pending,
+                Some((header, metadata)),
+                None,
+                now
+            ),
+            DeployStatus::Pending { buffered: true }
+        );
+    }
+
+    #[test]
+    fn expiry_should_take_precedence_over_pending_and_proposed() {
+        let mut rng = crate::new_rng();
+        let (header, metadata) = header_and_metadata(&mut rng);
+        let now = after_expiry(&header);
+        let expected = DeployStatus::Expired {
+            expired_at: header.expires(),
+        };
+        for &buffer_status in &[
+            DeployBufferStatus::Unknown,
+            DeployBufferStatus::Pending,
+            DeployBufferStatus::Proposed {
+                latest_proposal: header.timestamp(),
+            },
+        ] {
+            assert_eq!(
+                DeployStatus::new(
+                    buffer_status,
+                    Some((header.clone(), metadata.clone())),
+                    None,
+                    now
+                ),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn replacement_and_eviction_should_take_precedence_over_expiry() {
+        let mut rng = crate::new_rng();
+        let (header, metadata) = header_and_metadata(&mut rng);
+        let now = after_expiry(&header);
+        assert_eq!(
+            DeployStatus::new(
+                DeployBufferStatus::Replaced,
+                Some((header.clone(), metadata.clone())),
+                None,
+                now
+            ),
+            DeployStatus::Replaced
+        );
+        assert_eq!(
+            DeployStatus::new(
+                DeployBufferStatus::Evicted,
+                Some((header, metadata)),
+                None,
+                now
+            ),
+            DeployStatus::Evicted
+        );
+    }
+
+    #[test]
+    fn finalization_should_take_precedence_over_buffer_status() {
+        let mut rng = crate::new_rng();
+        let (header, metadata) = header_and_metadata(&mut rng);
+        let block_header = random_block_header(&mut rng);
+        let now = after_expiry(&header);
+        assert_eq!(
+            DeployStatus::new(
+                DeployBufferStatus::Finalized,
+                Some((header.clone(), metadata.clone())),
+                None,
+                now
+            ),
+            DeployStatus::Finalized { block_hash: None }
+        );
+        // The block store may already have the block while the buffer hasn't caught up yet.
+        assert_eq!(
+            DeployStatus::new(
+                DeployBufferStatus::Pending,
+                Some((header, metadata)),
+                Some(block_header.clone()),
+                now
+            ),
+            DeployStatus::Finalized {
+                block_hash: Some(block_header.hash())
+            }
+        );
+    }
+
+    #[test]
+    fn execution_should_take_precedence_over_everything_else() {
+        let mut rng = crate::new_rng();
+        let (header, mut metadata) = header_and_metadata(&mut rng);
+        let block_header = random_block_header(&mut rng);
+        let result: ExecutionResult = rng.gen();
+        metadata
+            .execution_results
+            .insert(block_header.hash(), result.clone());
+
+        let (cost, error_message) = match result {
+            ExecutionResult::Failure {
+                cost,
+                error_message,
+                ..
+            } => (cost, Some(error_message)),
+            ExecutionResult::Success { cost, .. } => (cost, None),
+        };
+        let expected = DeployStatus::Executed {
+            block_hash: block_header.hash(),
+            block_height: Some(block_header.height()),
+            cost,
+            cost_in_motes: Motes::from_gas(Gas::new(cost), header.gas_price())
+                .map(|motes| motes.value()),
+            error_message,
+        };
+
+        for &buffer_status in &[
+            DeployBufferStatus::Unknown,
+            DeployBufferStatus::Pending,
+            DeployBufferStatus::Proposed {
+                latest_proposal: header.timestamp(),
+            },
+            DeployBufferStatus::Finalized,
+            DeployBufferStatus::Replaced,
+            DeployBufferStatus::Evicted,
+        ] {
+            assert_eq!(
+                DeployStatus::new(
+                    buffer_status,
+                    Some((header.clone(), metadata.clone())),
+                    Some(block_header.clone()),
+                    after_expiry(&header)
+                ),
+                expected
+            );
+        }
+
+        // Without the block's header, the block height is unknown.
+        let status = DeployStatus::new(
+            DeployBufferStatus::Pending,
+            Some((header.clone(), metadata)),
+            None,
+            before_expiry(&header),
+        );
+        assert!(matches!(
+            status,
+            DeployStatus::Executed {
+                block_height: None,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn should_serialize_status_as_tag() {
+        let json = serde_json::to_value(&DeployStatus::Pending { buffered: true }).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"status": "pending", "buffered": true})
+        );
+        let json = serde_json::to_value(&DeployStatus::Unknown).unwrap();
+        assert_eq!(json, serde_json::json!({"status": "unknown"}));
+    }
+}
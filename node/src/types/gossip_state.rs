@@ -0,0 +1,73 @@
+// TODO - remove once schemars stops causing warning.
+#![allow(clippy::field_reassign_with_default)]
+
+use once_cell::sync::Lazy;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::components::rpc_server::rpcs::docs::DocExample;
+
+static GOSSIP_STATE: Lazy<GossipState> = Lazy::new(|| {
+    let peer_1 = String::from("p2p-12D3KooWD3eckifWpRn9wQpMG9R9hX3sD158z7EqHWmweQAJU5SA");
+    let peer_2 = String::from("p2p-QmYyQSo1c1Ym7orWxLYvCrM2EmxFTANf8wXmmE7DWjhx5N");
+    GossipState {
+        topics: vec![GossipTopicState {
+            topic: String::from("all"),
+            subscribed: true,
+            mesh_peers: vec![peer_1.clone()],
+            subscribers: vec![peer_1, peer_2],
+            published: 12,
+            publish_failures: 1,
+            received: 340,
+            rejected: 2,
+        }],
+    }
+});
+
+/// A snapshot of the gossipsub state of the libp2p-based network component, for troubleshooting.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GossipState {
+    /// The topics this node or any of its peers are subscribed to, ordered by name.
+    pub topics: Vec<GossipTopicState>,
+}
+
+impl GossipState {
+    /// Returns the state of the given topic, if it's known.
+    pub fn topic(&self, topic: &str) -> Option<&GossipTopicState> {
+        self.topics
+            .iter()
+            .find(|topic_state| topic_state.topic == topic)
+    }
+}
+
+impl DocExample for GossipState {
+    fn doc_example() -> &'static Self {
+        &*GOSSIP_STATE
+    }
+}
+
+/// The gossipsub state of a single topic.
+///
+/// Peers are given by node ID, in ascending order.  The counters cover the time since the node
+/// started.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GossipTopicState {
+    /// The name of the topic.
+    pub topic: String,
+    /// Whether this node is subscribed to the topic.
+    pub subscribed: bool,
+    /// The peers in this node's mesh for the topic, to which full messages are forwarded.
+    pub mesh_peers: Vec<String>,
+    /// The connected peers known to be subscribed to the topic.
+    pub subscribers: Vec<String>,
+    /// The number of messages this node has published to the topic.
+    pub published: u64,
+    /// The number of messages this node failed to publish to the topic, e.g. for want of peers.
+    pub publish_failures: u64,
+    /// The number of messages received on the topic.
+    pub received: u64,
+    /// The number of received messages which were discarded as invalid.
+    pub rejected: u64,
+}
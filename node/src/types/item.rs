@@ -1,34 +1,34 @@
 use std::{
-    fmt::{Debug, Display},
+    error::Error as StdError,
+    fmt::{self, Debug, Display, Formatter},
     hash::Hash,
+    str::FromStr,
 };
 
-use derive_more::Display;
-use serde::{de::DeserializeOwned, Serialize};
-use serde_repr::{Deserialize_repr, Serialize_repr};
+use serde::{
+    de::{self, DeserializeOwned, Unexpected},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use thiserror::Error;
 
-use crate::types::{BlockHash, BlockHeader, BlockHeaderWithMetadata};
+use crate::types::{
+    BlockHash, BlockHeader, BlockHeaderWithMetadata, BlockValidationError, Chainspec,
+};
 use casper_execution_engine::{
     shared::{newtypes::Blake2bHash, stored_value::StoredValue},
     storage::trie::Trie,
 };
 use casper_types::{bytesrepr::ToBytes, Key};
 
+/// The maximum serialized size in bytes of items whose size is not limited by the chainspec.
+const MAX_SMALL_ITEM_SIZE: u32 = 1024;
+
 /// An identifier for a specific type implementing the `Item` trait.  Each different implementing
 /// type should have a unique `Tag` variant.
-#[derive(
-    Clone,
-    Copy,
-    PartialEq,
-    Eq,
-    PartialOrd,
-    Ord,
-    Hash,
-    Serialize_repr,
-    Deserialize_repr,
-    Debug,
-    Display,
-)]
+///
+/// Tags are serialized as their `u8` discriminant in binary formats, and as their stable lowercase
+/// name in human-readable ones.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
 #[repr(u8)]
 pub enum Tag {
     /// A deploy.
@@ -43,6 +43,165 @@ pub enum Tag {
     BlockHeaderByHash,
     /// A block header and its finality signatures requested by its height in the linear chain.
     BlockHeaderAndFinalitySignaturesByHeight,
+    /// A deploy's header, without its body or approvals, requested by the deploy's hash.
+    DeployHeaderWithHash,
+    /// A deploy's approvals, without its header or body, requested by the deploy's hash.
+    DeployApprovals,
+    /// The transfers executed in a block, requested by the block's hash.
+    BlockTransfers,
+}
+
+/// The limit bounding the serialized size of an item.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum ItemSizeLimit {
+    /// Bounded by the chainspec's `deploys.max_deploy_size`.
+    MaxDeploySize,
+    /// Bounded by the chainspec's `deploys.max_block_size`.
+    MaxBlockSize,
+    /// Bounded by `MAX_SMALL_ITEM_SIZE`, regardless of the chainspec.
+    Small,
+}
+
+/// The priority class in which fetches of an item from peers are scheduled.
+///
+/// Ordered from highest to lowest priority.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub(crate) enum FetchPriority {
+    /// Needed to make progress syncing or executing the next block.
+    High,
+    /// Needed, but not to make immediate progress.
+    Normal,
+    /// Fetched ahead of being needed.
+    Low,
+}
+
+/// The registered properties of the item type identified by a `Tag`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) struct TagInfo {
+    /// Whether the item's ID _is_ the complete item.
+    pub(crate) id_is_complete_item: bool,
+    /// The limit bounding the serialized size of the item.
+    pub(crate) size_limit: ItemSizeLimit,
+    /// The priority class in which fetches of the item are scheduled.
+    pub(crate) fetch_priority: FetchPriority,
+}
+
+impl Tag {
+    /// All tags, in order of their discriminants.
+    pub const ALL: [Tag; 9] = [
+        Tag::Deploy,
+        Tag::Block,
+        Tag::GossipedAddress,
+        Tag::BlockByHeight,
+        Tag::BlockHeaderByHash,
+        Tag::BlockHeaderAndFinalitySignaturesByHeight,
+        Tag::DeployHeaderWithHash,
+        Tag::DeployApprovals,
+        Tag::BlockTransfers,
+    ];
+
+    /// Returns the stable name of the tag, suitable for use in config files and as a metrics label.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Tag::Deploy => "deploy",
+            Tag::Block => "block",
+            Tag::GossipedAddress => "gossiped_address",
+            Tag::BlockByHeight => "block_by_height",
+            Tag::BlockHeaderByHash => "block_header_by_hash",
+            Tag::BlockHeaderAndFinalitySignaturesByHeight => {
+                "block_header_and_finality_signatures_by_height"
+            }
+            Tag::DeployHeaderWithHash => "deploy_header_with_hash",
+            Tag::DeployApprovals => "deploy_approvals",
+            Tag::BlockTransfers => "block_transfers",
+        }
+    }
+
+    /// Returns the registered properties of the item type identified by the tag.
+    ///
+    /// This is the single place in which the properties of each item type are registered.
+    pub(crate) const fn info(self) -> TagInfo {
+        use FetchPriority::{High, Low, Normal};
+        use ItemSizeLimit::{MaxBlockSize, MaxDeploySize, Small};
+
+        // Deploy headers are fetched up front when syncing, while the full deploys are only
+        // fetched once needed to execute their block.
+        let (id_is_complete_item, size_limit, fetch_priority) = match self {
+            Tag::Deploy => (false, MaxDeploySize, High),
+            Tag::Block => (false, MaxBlockSize, High),
+            Tag::GossipedAddress => (true, Small, Normal),
+            Tag::BlockByHeight => (false, MaxBlockSize, High),
+            Tag::BlockHeaderByHash => (false, MaxBlockSize, High),
+            Tag::BlockHeaderAndFinalitySignaturesByHeight => (false, MaxBlockSize, High),
+            Tag::DeployHeaderWithHash => (false, MaxDeploySize, Low),
+            Tag::DeployApprovals => (false, MaxDeploySize, Normal),
+            Tag::BlockTransfers => (false, MaxBlockSize, Normal),
+        };
+        TagInfo {
+            id_is_complete_item,
+            size_limit,
+            fetch_priority,
+        }
+    }
+
+    /// Returns the maximum serialized size in bytes of an item identified by the tag.
+    pub(crate) fn max_item_size(self, chainspec: &Chainspec) -> u32 {
+        match self.info().size_limit {
+            ItemSizeLimit::MaxDeploySize => chainspec.deploy_config.max_deploy_size,
+            ItemSizeLimit::MaxBlockSize => chainspec.deploy_config.max_block_size,
+            ItemSizeLimit::Small => MAX_SMALL_ITEM_SIZE,
+        }
+    }
+}
+
+impl Display for Tag {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        write!(formatter, "{}", self.name())
+    }
+}
+
+/// Error returned when parsing a `Tag` from a string.
+#[derive(Debug, Error)]
+#[error("unknown tag '{0}'")]
+pub struct ParseTagError(String);
+
+impl FromStr for Tag {
+    type Err = ParseTagError;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Tag::ALL
+            .iter()
+            .find(|tag| tag.name() == name)
+            .copied()
+            .ok_or_else(|| ParseTagError(name.to_string()))
+    }
+}
+
+impl Serialize for Tag {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(self.name())
+        } else {
+            serializer.serialize_u8(*self as u8)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Tag {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            let name = String::deserialize(deserializer)?;
+            Tag::from_str(&name).map_err(de::Error::custom)
+        } else {
+            let discriminant = u8::deserialize(deserializer)?;
+            Tag::ALL.get(discriminant as usize).copied().ok_or_else(|| {
+                de::Error::invalid_value(
+                    Unexpected::Unsigned(discriminant as u64),
+                    &"a tag discriminant",
+                )
+            })
+        }
+    }
 }
 
 /// A trait which allows an implementing type to be used by the gossiper and fetcher components, and
@@ -51,42 +210,178 @@ pub enum Tag {
 pub trait Item: Clone + Serialize + DeserializeOwned + Send + Sync + Debug + Display {
     /// The type of ID of the item.
     type Id: Copy + Eq + Hash + Serialize + DeserializeOwned + Send + Sync + Debug + Display;
+    /// The error returned when the item fails validation.
+    type ValidationError: StdError;
     /// The tag representing the type of the item.
     const TAG: Tag;
-    /// Whether the item's ID _is_ the complete item or not.
-    const ID_IS_COMPLETE_ITEM: bool;
+    /// Whether the item's ID _is_ the complete item or not, as registered for its `TAG`.
+    const ID_IS_COMPLETE_ITEM: bool = Self::TAG.info().id_is_complete_item;
+    /// Whether gossiping the item should prioritize validator peers over non-validator peers.
+    const PRIORITIZE_VALIDATORS: bool = false;
+    /// Whether the fetcher may request several items of this type from a peer in a single
+    /// `GetBatchRequest`.  Peers must serve batch requests for every batchable type.
+    const BATCHABLE: bool = false;
 
     /// The ID of the specific item.
     fn id(&self) -> Self::Id;
+
+    /// Checks that the item is the one identified by `expected_id`, and is internally consistent,
+    /// e.g. that its hashes are correct and its signatures valid.
+    ///
+    /// The fetcher calls this on every item received from a peer before passing it on, so should
+    /// check everything about the item which can be checked without consulting other components.
+    fn validate(&self, expected_id: &Self::Id) -> Result<(), Self::ValidationError>;
+}
+
+/// Error returned when a trie is not the one requested, i.e. it doesn't hash to the requested key.
+#[derive(Debug, Error)]
+#[error("trie hashes to {actual} rather than the requested {expected}")]
+pub struct UnexpectedTrieError {
+    expected: Blake2bHash,
+    actual: Blake2bHash,
 }
 
 impl Item for Trie<Key, StoredValue> {
     type Id = Blake2bHash;
+    type ValidationError = UnexpectedTrieError;
     const TAG: Tag = Tag::Deploy;
-    const ID_IS_COMPLETE_ITEM: bool = false;
 
     fn id(&self) -> Self::Id {
         let node_bytes = self.to_bytes().expect("Could not serialize trie to bytes");
         Blake2bHash::new(&node_bytes)
     }
+
+    fn validate(&self, expected_id: &Self::Id) -> Result<(), Self::ValidationError> {
+        let actual = self.id();
+        if actual != *expected_id {
+            return Err(UnexpectedTrieError {
+                expected: *expected_id,
+                actual,
+            });
+        }
+        Ok(())
+    }
 }
 
 impl Item for BlockHeader {
     type Id = BlockHash;
+    type ValidationError = BlockValidationError;
     const TAG: Tag = Tag::BlockHeaderByHash;
-    const ID_IS_COMPLETE_ITEM: bool = false;
 
     fn id(&self) -> Self::Id {
         self.hash()
     }
+
+    fn validate(&self, expected_id: &Self::Id) -> Result<(), Self::ValidationError> {
+        let actual = self.hash();
+        if actual != *expected_id {
+            return Err(BlockValidationError::UnexpectedBlock {
+                expected: *expected_id,
+                actual,
+            });
+        }
+        Ok(())
+    }
 }
 
 impl Item for BlockHeaderWithMetadata {
     type Id = u64;
+    type ValidationError = BlockValidationError;
     const TAG: Tag = Tag::BlockHeaderAndFinalitySignaturesByHeight;
-    const ID_IS_COMPLETE_ITEM: bool = false;
 
     fn id(&self) -> Self::Id {
         self.block_header.height()
     }
+
+    fn validate(&self, expected_id: &Self::Id) -> Result<(), Self::ValidationError> {
+        let actual = self.block_header.height();
+        if actual != *expected_id {
+            return Err(BlockValidationError::UnexpectedHeight {
+                expected: *expected_id,
+                actual,
+            });
+        }
+        let block_hash = self.block_header.hash();
+        if self.block_signatures.block_hash != block_hash
+            || self.block_signatures.era_id != self.block_header.era_id()
+        {
+            return Err(BlockValidationError::UnexpectedSignatures { block_hash });
+        }
+        self.block_signatures
+            .verify()
+            .map_err(BlockValidationError::InvalidSignature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the tag following the given one.  The exhaustive match ensures this fails to compile
+    /// if a variant is added without being considered here, and hence in `Tag::ALL`.
+    fn next(tag: Tag) -> Option<Tag> {
+        match tag {
+            Tag::Deploy => Some(Tag::Block),
+            Tag::Block => Some(Tag::GossipedAddress),
+            Tag::GossipedAddress => Some(Tag::BlockByHeight),
+            Tag::BlockByHeight => Some(Tag::BlockHeaderByHash),
+            Tag::BlockHeaderByHash => Some(Tag::BlockHeaderAndFinalitySignaturesByHeight),
+            Tag::BlockHeaderAndFinalitySignaturesByHeight => Some(Tag::DeployHeaderWithHash),
+            Tag::DeployHeaderWithHash => Some(Tag::DeployApprovals),
+            Tag::DeployApprovals => Some(Tag::BlockTransfers),
+            Tag::BlockTransfers => None,
+        }
+    }
+
+    #[test]
+    fn all_should_contain_every_tag_in_discriminant_order() {
+        let mut expected = vec![Tag::Deploy];
+        while let Some(tag) = next(*expected.last().unwrap()) {
+            expected.push(tag);
+        }
+        assert_eq!(Tag::ALL.to_vec(), expected);
+
+        for (index, tag) in Tag::ALL.iter().enumerate() {
+            assert_eq!(*tag as usize, index);
+        }
+    }
+
+    #[test]
+    fn should_round_trip_via_string() {
+        for tag in Tag::ALL.iter() {
+            let name = tag.to_string();
+            assert_eq!(name, name.to_lowercase());
+            assert_eq!(name.parse::<Tag>().unwrap(), *tag);
+        }
+        assert!("Deploy".parse::<Tag>().is_err());
+        assert!("".parse::<Tag>().is_err());
+    }
+
+    #[test]
+    fn should_round_trip_via_serde() {
+        for tag in Tag::ALL.iter() {
+            let json = serde_json::to_string(tag).unwrap();
+            assert_eq!(json, format!("\"{}\"", tag.name()));
+            assert_eq!(serde_json::from_str::<Tag>(&json).unwrap(), *tag);
+
+            let bytes = bincode::serialize(tag).unwrap();
+            assert_eq!(bytes, vec![*tag as u8]);
+            assert_eq!(bincode::deserialize::<Tag>(&bytes).unwrap(), *tag);
+        }
+
+        let invalid = bincode::serialize(&(Tag::ALL.len() as u8)).unwrap();
+        assert!(bincode::deserialize::<Tag>(&invalid).is_err());
+    }
+
+    #[test]
+    fn only_gossiped_address_should_be_its_own_id() {
+        for tag in Tag::ALL.iter() {
+            assert_eq!(
+                tag.info().id_is_complete_item,
+                *tag == Tag::GossipedAddress,
+                "wrong registration for {}",
+                tag
+            );
+        }
+    }
 }
@@ -62,6 +62,15 @@ impl From<&DomainContract> for Contract {
     }
 }
 
+impl Contract {
+    /// Returns the entry point with the given name, if the contract has one.
+    pub fn entry_point(&self, name: &str) -> Option<&EntryPoint> {
+        self.entry_points
+            .iter()
+            .find(|entry_point| entry_point.name() == name)
+    }
+}
+
 /// Contract definition, metadata, and security container.
 #[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize, DataSize, JsonSchema)]
 #[serde(deny_unknown_fields)]
@@ -1,7 +1,10 @@
 use datasize::DataSize;
 use serde::{Deserialize, Serialize};
 
-use crate::types::BlockHash;
+use crate::{
+    reactor::{EventQueueWeights, FailurePolicies},
+    types::BlockHash,
+};
 
 /// Node configuration.
 #[derive(Default, DataSize, Debug, Deserialize, Serialize)]
@@ -10,4 +13,10 @@ use crate::types::BlockHash;
 pub struct NodeConfig {
     /// Hash used as a trust anchor when joining, if any.
     pub trusted_hash: Option<BlockHash>,
+    /// Weights of the reactor's event queues, used to prioritize some kinds of events over others.
+    #[serde(default)]
+    pub event_queue_weights: EventQueueWeights,
+    /// Handling of components whose event handlers panic, by component name.
+    #[serde(default)]
+    pub component_failure_policies: FailurePolicies,
 }
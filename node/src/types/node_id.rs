@@ -12,12 +12,42 @@ use once_cell::sync::Lazy;
 #[cfg(test)]
 use rand::{Rng, RngCore};
 use serde::{de::Error as SerdeError, Deserialize, Deserializer, Serialize, Serializer};
+use thiserror::Error;
 
 #[cfg(test)]
 use crate::testing::TestRng;
 use crate::{rpcs::docs::DocExample, tls::KeyFingerprint};
 
+/// The prefix of the textual form of a `NodeId::Tls`.
+const TLS_PREFIX: &str = "tls-";
+/// The prefix of the textual form of a `NodeId::P2p`.
+const P2P_PREFIX: &str = "p2p-";
+
+/// Error parsing a `NodeId` from its textual form.
+#[derive(Debug, Error)]
+pub enum NodeIdParseError {
+    /// The node ID doesn't start with a known prefix.
+    #[error("node ID must start with '{}' or '{}': {0}", TLS_PREFIX, P2P_PREFIX)]
+    UnknownPrefix(String),
+    /// The key fingerprint of a TLS node ID isn't valid hex.
+    #[error("invalid hex-encoded key fingerprint: {0}")]
+    InvalidHex(#[from] hex::FromHexError),
+    /// The key fingerprint of a TLS node ID has the wrong length.
+    #[error(
+        "key fingerprint must be {} bytes, but is {0} bytes",
+        KeyFingerprint::LENGTH
+    )]
+    WrongLength(usize),
+    /// The peer ID of a libp2p node ID isn't valid.
+    #[error("invalid base58-encoded peer ID: {0}")]
+    InvalidPeerId(String),
+}
+
 /// The network identifier for a node.
+///
+/// Its textual form, as used by `Display`, `FromStr` and human-readable serialization formats, is
+/// `tls-<hex-encoded key fingerprint>` for nodes using the small_network component, or
+/// `p2p-<base58-encoded peer ID>` for nodes using the libp2p-based network component.
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, DataSize)]
 pub enum NodeId {
     Tls(KeyFingerprint),
@@ -52,6 +82,24 @@ impl NodeId {
         NodeId::P2p(peer_id)
     }
 
+    /// Parses the hex-encoded key fingerprint of a TLS node ID.
+    fn from_tls_hex(hex_value: &str) -> Result<Self, NodeIdParseError> {
+        let bytes = hex::decode(hex_value)?;
+        if bytes.len() != KeyFingerprint::LENGTH {
+            return Err(NodeIdParseError::WrongLength(bytes.len()));
+        }
+        let mut array = [0_u8; KeyFingerprint::LENGTH];
+        array.copy_from_slice(bytes.as_slice());
+        Ok(NodeId::Tls(KeyFingerprint::from(array)))
+    }
+
+    /// Parses the base58-encoded peer ID of a libp2p node ID.
+    fn from_p2p_base58(b58_value: &str) -> Result<Self, NodeIdParseError> {
+        PeerId::from_str(b58_value)
+            .map(NodeId::P2p)
+            .map_err(|error| NodeIdParseError::InvalidPeerId(error.to_string()))
+    }
+
     /// Returns the raw bytes of the underlying hash of the ID, if there is any.
     #[inline]
     pub fn hash_bytes(&self) -> Option<&[u8]> {
@@ -70,9 +118,21 @@ enum NodeIdAsBytes {
     P2p(Vec<u8>),
 }
 
-/// Used to serialize and deserialize `NodeID` where the (de)serializer is a human-readable type.
-#[derive(Serialize, Deserialize)]
+/// Used to deserialize `NodeID` where the deserializer is a human-readable type.
+///
+/// Node IDs were previously serialized in human-readable formats as e.g. `{"Tls": "<hex>"}`, so
+/// this form is still accepted for data persisted by earlier versions.
+#[derive(Deserialize)]
+#[serde(untagged)]
 enum NodeIdAsString {
+    Text(String),
+    Legacy(LegacyNodeIdAsString),
+}
+
+/// The form in which `NodeID` was previously serialized where the serializer is a human-readable
+/// type.
+#[derive(Deserialize)]
+enum LegacyNodeIdAsString {
     Tls(String),
     P2p(String),
 }
@@ -80,13 +140,7 @@ enum NodeIdAsString {
 impl Serialize for NodeId {
     fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
         if serializer.is_human_readable() {
-            let helper = match self {
-                NodeId::Tls(key_fingerprint) => {
-                    NodeIdAsString::Tls(hex::encode(key_fingerprint.as_ref()))
-                }
-                NodeId::P2p(peer_id) => NodeIdAsString::P2p(peer_id.to_base58()),
-            };
-            return helper.serialize(serializer);
+            return self.to_string().serialize(serializer);
         }
 
         let helper = match self {
@@ -101,21 +155,16 @@ impl<'de> Deserialize<'de> for NodeId {
     fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
         if deserializer.is_human_readable() {
             let helper = NodeIdAsString::deserialize(deserializer)?;
-            match helper {
-                NodeIdAsString::Tls(hex_value) => {
-                    let bytes = hex::decode(hex_value).map_err(D::Error::custom)?;
-                    if bytes.len() != KeyFingerprint::LENGTH {
-                        return Err(SerdeError::custom("wrong length"));
-                    }
-                    let mut array = [0_u8; KeyFingerprint::LENGTH];
-                    array.copy_from_slice(bytes.as_slice());
-                    return Ok(NodeId::Tls(KeyFingerprint::from(array)));
+            let result = match helper {
+                NodeIdAsString::Text(value) => NodeId::from_str(&value),
+                NodeIdAsString::Legacy(LegacyNodeIdAsString::Tls(hex_value)) => {
+                    NodeId::from_tls_hex(&hex_value)
                 }
-                NodeIdAsString::P2p(b58_value) => {
-                    let peer_id = PeerId::from_str(&b58_value).map_err(D::Error::custom)?;
-                    return Ok(NodeId::P2p(peer_id));
+                NodeIdAsString::Legacy(LegacyNodeIdAsString::P2p(b58_value)) => {
+                    NodeId::from_p2p_base58(&b58_value)
                 }
-            }
+            };
+            return result.map_err(D::Error::custom);
         }
 
         let helper = NodeIdAsBytes::deserialize(deserializer)?;
@@ -141,14 +190,7 @@ impl DocExample for NodeId {
 
 impl Debug for NodeId {
     fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
-        match self {
-            NodeId::Tls(key_fingerprint) => write!(
-                formatter,
-                "NodeId::Tls({})",
-                HexFmt(key_fingerprint.as_ref())
-            ),
-            NodeId::P2p(peer_id) => write!(formatter, "PeerId::P2p({})", peer_id.to_base58()),
-        }
+        Display::fmt(self, formatter)
     }
 }
 
@@ -157,18 +199,25 @@ impl Display for NodeId {
         match self {
             NodeId::Tls(key_fingerprint) => write!(
                 formatter,
-                "NodeId::Tls({:10})",
+                "{}{}",
+                TLS_PREFIX,
                 HexFmt(key_fingerprint.as_ref())
             ),
-            NodeId::P2p(peer_id) => {
-                let base58_peer_id = peer_id.to_base58();
-                write!(
-                    formatter,
-                    "NodeId::P2p({}..{})",
-                    &base58_peer_id[8..12],
-                    &base58_peer_id[(base58_peer_id.len() - 4)..]
-                )
-            }
+            NodeId::P2p(peer_id) => write!(formatter, "{}{}", P2P_PREFIX, peer_id.to_base58()),
+        }
+    }
+}
+
+impl FromStr for NodeId {
+    type Err = NodeIdParseError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        if let Some(hex_value) = input.strip_prefix(TLS_PREFIX) {
+            NodeId::from_tls_hex(hex_value)
+        } else if let Some(b58_value) = input.strip_prefix(P2P_PREFIX) {
+            NodeId::from_p2p_base58(b58_value)
+        } else {
+            Err(NodeIdParseError::UnknownPrefix(input.to_string()))
         }
     }
 }
@@ -231,4 +280,91 @@ mod test {
         let decoded = serde_json::from_str(&json_string).unwrap();
         assert_eq!(node_id, decoded);
     }
+
+    #[test]
+    fn string_roundtrip_tls() {
+        let mut rng = crate::new_rng();
+        let node_id = NodeId::random_tls(&mut rng);
+        let text = node_id.to_string();
+        assert!(text.starts_with(TLS_PREFIX));
+        assert_eq!(text.parse::<NodeId>().unwrap(), node_id);
+        assert_eq!(
+            serde_json::to_string(&node_id).unwrap(),
+            format!("\"{}\"", text)
+        );
+    }
+
+    #[test]
+    fn string_roundtrip_p2p() {
+        let mut rng = crate::new_rng();
+        let node_id = NodeId::random_p2p(&mut rng);
+        let text = node_id.to_string();
+        assert!(text.starts_with(P2P_PREFIX));
+        assert_eq!(text.parse::<NodeId>().unwrap(), node_id);
+        assert_eq!(
+            serde_json::to_string(&node_id).unwrap(),
+            format!("\"{}\"", text)
+        );
+    }
+
+    #[test]
+    fn should_fail_to_parse_invalid_strings() {
+        let mut rng = crate::new_rng();
+        let tls_text = NodeId::random_tls(&mut rng).to_string();
+        let p2p_text = NodeId::random_p2p(&mut rng).to_string();
+
+        assert!(matches!(
+            tls_text.replacen(TLS_PREFIX, "tcp-", 1).parse::<NodeId>(),
+            Err(NodeIdParseError::UnknownPrefix(_))
+        ));
+        assert!(matches!(
+            tls_text.replacen(TLS_PREFIX, "", 1).parse::<NodeId>(),
+            Err(NodeIdParseError::UnknownPrefix(_))
+        ));
+        assert!(matches!(
+            format!("{}zz", TLS_PREFIX).parse::<NodeId>(),
+            Err(NodeIdParseError::InvalidHex(_))
+        ));
+        assert!(matches!(
+            tls_text[..tls_text.len() - 2].parse::<NodeId>(),
+            Err(NodeIdParseError::WrongLength(length)) if length == KeyFingerprint::LENGTH - 1
+        ));
+        assert!(matches!(
+            format!("{}0OIl", P2P_PREFIX).parse::<NodeId>(),
+            Err(NodeIdParseError::InvalidPeerId(_))
+        ));
+        assert!(matches!(
+            p2p_text
+                .replacen(P2P_PREFIX, TLS_PREFIX, 1)
+                .parse::<NodeId>(),
+            Err(NodeIdParseError::InvalidHex(_))
+        ));
+    }
+
+    #[test]
+    fn should_deserialize_legacy_json() {
+        let mut rng = crate::new_rng();
+
+        let tls_node_id = NodeId::random_tls(&mut rng);
+        let legacy_json = match tls_node_id {
+            NodeId::Tls(key_fingerprint) => {
+                format!(r#"{{"Tls":"{}"}}"#, hex::encode(key_fingerprint.as_ref()))
+            }
+            NodeId::P2p(_) => unreachable!(),
+        };
+        assert_eq!(
+            serde_json::from_str::<NodeId>(&legacy_json).unwrap(),
+            tls_node_id
+        );
+
+        let p2p_node_id = NodeId::random_p2p(&mut rng);
+        let legacy_json = match p2p_node_id {
+            NodeId::P2p(peer_id) => format!(r#"{{"P2p":"{}"}}"#, peer_id.to_base58()),
+            NodeId::Tls(_) => unreachable!(),
+        };
+        assert_eq!(
+            serde_json::from_str::<NodeId>(&legacy_json).unwrap(),
+            p2p_node_id
+        );
+    }
 }
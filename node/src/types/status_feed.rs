@@ -7,17 +7,21 @@ use std::{
     net::{IpAddr, Ipv4Addr, SocketAddr},
 };
 
+use datasize::DataSize;
 use once_cell::sync::Lazy;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use casper_execution_engine::storage::lmdb_stats::LmdbStats;
 use casper_types::{EraId, ProtocolVersion, PublicKey};
 
 use crate::{
+    build_info::{BuildInfo, BUILD_INFO},
     components::{
-        chainspec_loader::NextUpgrade,
+        chainspec_loader::{NextUpgrade, ScheduledUpgrade, UpgradeSchedule},
         rpc_server::rpcs::docs::{DocExample, DOCS_EXAMPLE_PROTOCOL_VERSION},
     },
+    config_overrides::ConfigOverride,
     crypto::{hash::Digest, AsymmetricKeyExt},
     types::{ActivationPoint, Block, BlockHash, NodeId, PeersMap, TimeDiff, Timestamp},
 };
@@ -27,13 +31,77 @@ static CHAINSPEC_INFO: Lazy<ChainspecInfo> = Lazy::new(|| {
         ActivationPoint::EraId(EraId::from(42)),
         ProtocolVersion::from_parts(2, 0, 1),
     );
+    let upgrade_schedule = UpgradeSchedule::new(vec![
+        ScheduledUpgrade::new(
+            ActivationPoint::EraId(EraId::from(42)),
+            ProtocolVersion::from_parts(2, 0, 1),
+            false,
+        ),
+        ScheduledUpgrade::new(
+            ActivationPoint::EraId(EraId::from(50)),
+            ProtocolVersion::from_parts(2, 1, 0),
+            true,
+        ),
+    ]);
     ChainspecInfo {
         name: String::from("casper-example"),
+        protocol_version: DOCS_EXAMPLE_PROTOCOL_VERSION,
+        chainspec_hash: Digest::from([3u8; Digest::LENGTH]),
         starting_state_root_hash: Digest::from([2u8; Digest::LENGTH]),
         next_upgrade: Some(next_upgrade),
+        upgrade_schedule,
     }
 });
 
+static ERA_PROGRESS: Lazy<EraProgress> = Lazy::new(|| {
+    let era_start = *Timestamp::doc_example();
+    EraProgress::new(
+        EraId::from(42),
+        era_start,
+        TimeDiff::from(7_200_000),
+        100,
+        40,
+        Some(TimeDiff::from(1 << 16)),
+        era_start + TimeDiff::from(1_800_000),
+    )
+});
+
+static STORAGE_STATUS: Lazy<StorageStatus> = Lazy::new(|| {
+    let mut block_store_entries = BTreeMap::new();
+    block_store_entries.insert(String::from("block_header"), 1_000);
+    block_store_entries.insert(String::from("deploys"), 20_000);
+    let mut global_state_entries = BTreeMap::new();
+    global_state_entries.insert(String::from("trie_store"), 1_500_000);
+    StorageStatus {
+        node_storage: Some(LmdbStats {
+            map_size: 36_507_222_016,
+            page_size: 4096,
+            last_used_page: 250_000,
+            free_pages: 2_000,
+            entries: block_store_entries,
+        }),
+        global_state: Some(LmdbStats {
+            map_size: 805_306_368_000,
+            page_size: 4096,
+            last_used_page: 5_000_000,
+            free_pages: 40_000,
+            entries: global_state_entries,
+        }),
+    }
+});
+
+static DIAL_BACKOFF: Lazy<DialBackoff> = Lazy::new(|| DialBackoff {
+    address: String::from("127.0.0.1:34567"),
+    failed_attempts: 3,
+    next_attempt_in: TimeDiff::from(6_000),
+});
+
+static CONFIG_OVERRIDE: Lazy<ConfigOverride> = Lazy::new(|| ConfigOverride {
+    setting: String::from("network.bind_address"),
+    value: Some(String::from("\"0.0.0.0:35000\"")),
+    default: Some(String::from("\"0.0.0.0:34553\"")),
+});
+
 static GET_STATUS_RESULT: Lazy<GetStatusResult> = Lazy::new(|| {
     let node_id = NodeId::doc_example();
     let socket_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 54321);
@@ -42,9 +110,13 @@ static GET_STATUS_RESULT: Lazy<GetStatusResult> = Lazy::new(|| {
     let status_feed = StatusFeed::<NodeId> {
         last_added_block: Some(Block::doc_example().clone()),
         peers,
+        dial_backoffs: vec![DialBackoff::doc_example().clone()],
         chainspec_info: ChainspecInfo::doc_example().clone(),
         our_public_signing_key: Some(PublicKey::doc_example().clone()),
         round_length: Some(TimeDiff::from(1 << 16)),
+        era_progress: Some(EraProgress::doc_example().clone()),
+        storage: StorageStatus::doc_example().clone(),
+        config_overrides: vec![ConfigOverride::doc_example().clone()],
         version: crate::VERSION_STRING.as_str(),
     };
     GetStatusResult::new(status_feed, DOCS_EXAMPLE_PROTOCOL_VERSION)
@@ -55,11 +127,17 @@ static GET_STATUS_RESULT: Lazy<GetStatusResult> = Lazy::new(|| {
 pub struct ChainspecInfo {
     /// Name of the network.
     name: String,
+    /// The protocol version of the chainspec, as also used in the network protocol ID.
+    protocol_version: ProtocolVersion,
+    /// The hash of the chainspec.
+    chainspec_hash: Digest,
     /// The state root hash with which this session is starting.  It will be the result of running
     /// `ContractRuntime::commit_genesis()` or `ContractRuntime::upgrade()` or else the state root
     /// hash specified in the highest block on startup.
     starting_state_root_hash: Digest,
     next_upgrade: Option<NextUpgrade>,
+    /// The upgrades staged on disk after the current protocol version.
+    upgrade_schedule: UpgradeSchedule,
 }
 
 impl DocExample for ChainspecInfo {
@@ -71,17 +149,146 @@ impl DocExample for ChainspecInfo {
 impl ChainspecInfo {
     pub(crate) fn new(
         chainspec_network_name: String,
+        protocol_version: ProtocolVersion,
+        chainspec_hash: Digest,
         starting_state_root_hash: Digest,
         next_upgrade: Option<NextUpgrade>,
+        upgrade_schedule: UpgradeSchedule,
     ) -> Self {
         ChainspecInfo {
             name: chainspec_network_name,
+            protocol_version,
+            chainspec_hash,
             starting_state_root_hash,
             next_upgrade,
+            upgrade_schedule,
+        }
+    }
+}
+
+/// The progress of the current era, as seen by the consensus component.
+#[derive(Clone, DataSize, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct EraProgress {
+    /// The current era ID.
+    pub era_id: EraId,
+    /// The scheduled start time of the current era.
+    pub era_start: Timestamp,
+    /// The minimum duration of an era, as configured in the chainspec.
+    pub era_duration: TimeDiff,
+    /// The minimum number of blocks in an era, as configured in the chainspec.
+    pub minimum_era_height: u64,
+    /// The number of blocks finalized in the current era so far.
+    pub era_height: u64,
+    /// The current round length if this node is a validator.
+    pub round_length: Option<TimeDiff>,
+    /// A best-effort estimate of when the current era will end, if one can be made.
+    pub estimated_era_end: Option<Timestamp>,
+}
+
+impl EraProgress {
+    pub(crate) fn new(
+        era_id: EraId,
+        era_start: Timestamp,
+        era_duration: TimeDiff,
+        minimum_era_height: u64,
+        era_height: u64,
+        round_length: Option<TimeDiff>,
+        now: Timestamp,
+    ) -> Self {
+        let estimated_era_end = estimate_era_end(
+            era_start,
+            era_duration,
+            minimum_era_height,
+            era_height,
+            round_length,
+            now,
+        );
+        EraProgress {
+            era_id,
+            era_start,
+            era_duration,
+            minimum_era_height,
+            era_height,
+            round_length,
+            estimated_era_end,
         }
     }
 }
 
+impl DocExample for EraProgress {
+    fn doc_example() -> &'static Self {
+        &*ERA_PROGRESS
+    }
+}
+
+/// The usage of the node's LMDB environments.
+///
+/// Each environment has a fixed maximum size, its `map_size`, and the node fails once it's full.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct StorageStatus {
+    /// The statistics of the storage component's environment, holding blocks and deploys, or
+    /// `None` if they couldn't be collected.
+    pub node_storage: Option<LmdbStats>,
+    /// The statistics of the global state's environment, or `None` if they couldn't be
+    /// collected.
+    pub global_state: Option<LmdbStats>,
+}
+
+impl DocExample for StorageStatus {
+    fn doc_example() -> &'static Self {
+        &*STORAGE_STATUS
+    }
+}
+
+/// An address the node failed to connect to and is waiting to redial.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct DialBackoff {
+    /// The network address.
+    pub address: String,
+    /// The number of consecutive failed attempts to connect.
+    pub failed_attempts: u8,
+    /// The time remaining until the next attempt to connect.
+    pub next_attempt_in: TimeDiff,
+}
+
+impl DocExample for DialBackoff {
+    fn doc_example() -> &'static Self {
+        &*DIAL_BACKOFF
+    }
+}
+
+/// Estimates when an era will end, given its progress at the time `now`.
+///
+/// An era ends once it has lasted at least `era_duration` and contains at least
+/// `minimum_era_height` blocks.  The remaining blocks are assumed to be finalized at the average
+/// rate observed in the era so far or, if there were none yet, at one block per round.  Returns
+/// `None` if neither rate is known.
+fn estimate_era_end(
+    era_start: Timestamp,
+    era_duration: TimeDiff,
+    minimum_era_height: u64,
+    era_height: u64,
+    round_length: Option<TimeDiff>,
+    now: Timestamp,
+) -> Option<Timestamp> {
+    let earliest_end = era_start + era_duration;
+    let remaining_blocks = minimum_era_height.saturating_sub(era_height);
+    if remaining_blocks == 0 {
+        return Some(earliest_end.max(now));
+    }
+    let elapsed = now.saturating_diff(era_start);
+    let block_interval = if era_height > 0 && elapsed > TimeDiff::from(0) {
+        elapsed / era_height
+    } else {
+        round_length?
+    };
+    let height_reached = now + block_interval.saturating_mul(remaining_blocks);
+    Some(earliest_end.max(height_reached))
+}
+
 /// Data feed for client "info_get_status" endpoint.
 #[derive(Debug, Serialize)]
 #[serde(bound = "I: Eq + Hash + Ord + Serialize")]
@@ -90,22 +297,35 @@ pub struct StatusFeed<I> {
     pub last_added_block: Option<Block>,
     /// The peer nodes which are connected to this node.
     pub peers: BTreeMap<I, String>,
+    /// The addresses the node is waiting to redial after failing to connect.
+    pub dial_backoffs: Vec<DialBackoff>,
     /// The chainspec info for this node.
     pub chainspec_info: ChainspecInfo,
     /// Our public signing key.
     pub our_public_signing_key: Option<PublicKey>,
     /// The next round length if this node is a validator.
     pub round_length: Option<TimeDiff>,
+    /// The progress of the current era, if this node is participating in consensus.
+    pub era_progress: Option<EraProgress>,
+    /// The usage of the node's LMDB environments.
+    pub storage: StorageStatus,
+    /// The settings of the node's config which differ from their defaults.
+    pub config_overrides: Vec<ConfigOverride>,
     /// The compiled node version.
     pub version: &'static str,
 }
 
 impl<I> StatusFeed<I> {
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
         last_added_block: Option<Block>,
         peers: BTreeMap<I, String>,
+        dial_backoffs: Vec<DialBackoff>,
         chainspec_info: ChainspecInfo,
         consensus_status: Option<(PublicKey, Option<TimeDiff>)>,
+        era_progress: Option<EraProgress>,
+        storage: StorageStatus,
+        config_overrides: Vec<ConfigOverride>,
     ) -> Self {
         let (our_public_signing_key, round_length) = match consensus_status {
             Some((public_key, round_length)) => (Some(public_key), round_length),
@@ -114,9 +334,13 @@ impl<I> StatusFeed<I> {
         StatusFeed {
             last_added_block,
             peers,
+            dial_backoffs,
             chainspec_info,
             our_public_signing_key,
             round_length,
+            era_progress,
+            storage,
+            config_overrides,
             version: crate::VERSION_STRING.as_str(),
         }
     }
@@ -156,20 +380,40 @@ pub struct GetStatusResult {
     pub api_version: ProtocolVersion,
     /// The chainspec name.
     pub chainspec_name: String,
+    /// The protocol version of the chainspec, as also used in the network protocol ID.
+    #[schemars(with = "String")]
+    pub protocol_version: ProtocolVersion,
+    /// The hash of the chainspec.
+    pub chainspec_hash: Digest,
     /// The state root hash used at the start of the current session.
     pub starting_state_root_hash: Digest,
     /// The node ID and network address of each connected peer.
     pub peers: PeersMap,
+    /// The addresses the node is waiting to redial after failing to connect.
+    pub dial_backoffs: Vec<DialBackoff>,
     /// The minimal info of the last block from the linear chain.
     pub last_added_block_info: Option<MinimalBlockInfo>,
     /// Our public signing key.
     pub our_public_signing_key: Option<PublicKey>,
     /// The next round length if this node is a validator.
     pub round_length: Option<TimeDiff>,
+    /// The progress of the current era, or `None` if this node is not yet participating in
+    /// consensus, e.g. while it is still syncing.
+    pub consensus: Option<EraProgress>,
+    /// The usage of the node's LMDB environments.
+    pub storage: StorageStatus,
     /// Information about the next scheduled upgrade.
     pub next_upgrade: Option<NextUpgrade>,
+    /// The upgrades staged on disk after the current protocol version, ordered by activation
+    /// point.
+    pub upgrade_schedule: UpgradeSchedule,
     /// The compiled node version.
     pub build_version: String,
+    /// Information about how the node was built.
+    pub build: BuildInfo,
+    /// The settings of the node's config which differ from their defaults, ordered by section and
+    /// then by name, with secrets redacted.
+    pub config_overrides: Vec<ConfigOverride>,
 }
 
 impl GetStatusResult {
@@ -177,19 +421,146 @@ impl GetStatusResult {
         GetStatusResult {
             api_version,
             chainspec_name: status_feed.chainspec_info.name,
+            protocol_version: status_feed.chainspec_info.protocol_version,
+            chainspec_hash: status_feed.chainspec_info.chainspec_hash,
             starting_state_root_hash: status_feed.chainspec_info.starting_state_root_hash,
             peers: PeersMap::from(status_feed.peers),
+            dial_backoffs: status_feed.dial_backoffs,
             last_added_block_info: status_feed.last_added_block.map(Into::into),
             our_public_signing_key: status_feed.our_public_signing_key,
             round_length: status_feed.round_length,
+            consensus: status_feed.era_progress,
+            storage: status_feed.storage,
             next_upgrade: status_feed.chainspec_info.next_upgrade,
+            upgrade_schedule: status_feed.chainspec_info.upgrade_schedule,
             build_version: crate::VERSION_STRING.clone(),
+            build: BUILD_INFO.clone(),
+            config_overrides: status_feed.config_overrides,
         }
     }
 }
 
+impl DocExample for ConfigOverride {
+    fn doc_example() -> &'static Self {
+        &*CONFIG_OVERRIDE
+    }
+}
+
 impl DocExample for GetStatusResult {
     fn doc_example() -> &'static Self {
         &*GET_STATUS_RESULT
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_report_peers_by_textual_node_id() {
+        let node_id = NodeId::doc_example();
+        let expected = node_id.to_string();
+        assert!(expected.starts_with("tls-"));
+        assert_eq!(expected.parse::<NodeId>().unwrap(), *node_id);
+
+        let json = serde_json::to_value(GetStatusResult::doc_example()).unwrap();
+        assert_eq!(json["peers"][0]["node_id"], expected);
+    }
+
+    #[test]
+    fn should_report_time_until_next_dial_attempt() {
+        let json = serde_json::to_value(GetStatusResult::doc_example()).unwrap();
+        let dial_backoff = &json["dial_backoffs"][0];
+        assert_eq!(dial_backoff["address"], "127.0.0.1:34567");
+        assert_eq!(dial_backoff["failed_attempts"], 3);
+        assert_eq!(dial_backoff["next_attempt_in"], "6s");
+    }
+
+    #[test]
+    fn should_report_config_overrides() {
+        let json = serde_json::to_value(GetStatusResult::doc_example()).unwrap();
+        let config_override = &json["config_overrides"][0];
+        assert_eq!(config_override["setting"], "network.bind_address");
+        assert_eq!(config_override["value"], r#""0.0.0.0:35000""#);
+        assert_eq!(config_override["default"], r#""0.0.0.0:34553""#);
+    }
+
+    #[test]
+    fn should_report_build_and_chainspec_identity() {
+        let json = serde_json::to_value(GetStatusResult::doc_example()).unwrap();
+
+        assert_eq!(
+            json["protocol_version"],
+            DOCS_EXAMPLE_PROTOCOL_VERSION.to_string()
+        );
+        assert_eq!(
+            json["chainspec_hash"],
+            serde_json::to_value(ChainspecInfo::doc_example().chainspec_hash).unwrap()
+        );
+        for field in &[
+            "crate_version",
+            "git_sha",
+            "build_timestamp",
+            "profile",
+            "rustc_version",
+        ] {
+            assert!(
+                json["build"][field].is_string(),
+                "status should include build field {}",
+                field
+            );
+        }
+        assert_eq!(json["build"]["crate_version"], env!("CARGO_PKG_VERSION"));
+        assert!(json["build_version"]
+            .as_str()
+            .unwrap()
+            .contains(json["build"]["git_sha"].as_str().unwrap()));
+    }
+
+    #[test]
+    fn should_estimate_era_end_from_observed_block_rate() {
+        let era_start = Timestamp::from(1_000_000);
+        let era_duration = TimeDiff::from(10_000);
+        let round_length = Some(TimeDiff::from(1_000));
+
+        // 4 blocks in 8 seconds: the 6 missing blocks should take another 12 seconds, which is
+        // later than the end of the era's minimum duration.
+        let now = era_start + TimeDiff::from(8_000);
+        let estimate = estimate_era_end(era_start, era_duration, 10, 4, round_length, now);
+        assert_eq!(estimate, Some(era_start + TimeDiff::from(20_000)));
+
+        // With only 5 blocks required, the duration is the limiting factor.
+        let estimate = estimate_era_end(era_start, era_duration, 5, 4, round_length, now);
+        assert_eq!(estimate, Some(era_start + era_duration));
+    }
+
+    #[test]
+    fn should_estimate_era_end_from_round_length_without_blocks() {
+        let era_start = Timestamp::from(1_000_000);
+        let era_duration = TimeDiff::from(0);
+
+        let estimate = estimate_era_end(
+            era_start,
+            era_duration,
+            3,
+            0,
+            Some(TimeDiff::from(1_000)),
+            era_start,
+        );
+        assert_eq!(estimate, Some(era_start + TimeDiff::from(3_000)));
+
+        // Without a known round length, no estimate can be made.
+        let estimate = estimate_era_end(era_start, era_duration, 3, 0, None, era_start);
+        assert_eq!(estimate, None);
+    }
+
+    #[test]
+    fn should_not_estimate_era_end_in_the_past() {
+        let era_start = Timestamp::from(1_000_000);
+        let now = era_start + TimeDiff::from(30_000);
+
+        // Both conditions are met already: the era should end with the next block.
+        let estimate = estimate_era_end(era_start, TimeDiff::from(10_000), 5, 8, None, now);
+        assert_eq!(estimate, Some(now));
+    }
+}
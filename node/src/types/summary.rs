@@ -0,0 +1,185 @@
+//! Compact, single-line summaries of deploys, blocks and finality signatures for logging.
+//!
+//! A summary starts with the kind of the object, followed by space-separated `key=value` pairs, so
+//! that e.g. every log line mentioning an account can be found by grepping for `account=<prefix>`.
+//! Hashes are shown in their usual short form, and public keys as the first ten characters of their
+//! hex form.
+
+use std::fmt::{self, Display, Formatter};
+
+use casper_types::{bytesrepr::ToBytes, AsymmetricType, PublicKey};
+
+use super::{Block, Deploy, DeployHeader, FinalitySignature};
+
+/// The number of characters of a public key's hex form shown in a summary.
+const PUBLIC_KEY_PREFIX_LENGTH: usize = 10;
+
+/// Types which can be summarized in a single line, for use in logs where their full `Display`
+/// output would be overwhelming.
+pub(crate) trait Summary {
+    /// Writes the summary of `self` to `formatter`.
+    fn fmt_summary(&self, formatter: &mut Formatter<'_>) -> fmt::Result;
+
+    /// Returns a value whose `Display` output is the summary of `self`.
+    ///
+    /// The summary is only formatted if it is displayed, so this is cheap to pass to a log macro
+    /// whose level is disabled.
+    fn summary(&self) -> DisplaySummary<'_, Self> {
+        DisplaySummary(self)
+    }
+}
+
+/// Displays the summary of the wrapped value.
+pub(crate) struct DisplaySummary<'a, T: ?Sized>(&'a T);
+
+impl<'a, T: Summary + ?Sized> Display for DisplaySummary<'a, T> {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        self.0.fmt_summary(formatter)
+    }
+}
+
+/// Displays the first characters of the hex form of a public key.
+struct PublicKeyPrefix<'a>(&'a PublicKey);
+
+impl<'a> Display for PublicKeyPrefix<'a> {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        let hex = self.0.to_hex();
+        let prefix = hex.get(..PUBLIC_KEY_PREFIX_LENGTH).unwrap_or(&hex);
+        formatter.write_str(prefix)
+    }
+}
+
+impl Summary for Deploy {
+    fn fmt_summary(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            formatter,
+            "deploy hash={} account={} timestamp={} size={} transfer={}",
+            self.id().inner(),
+            PublicKeyPrefix(self.header().account()),
+            self.header().timestamp(),
+            self.serialized_length(),
+            self.session().is_transfer()
+        )
+    }
+}
+
+impl Summary for DeployHeader {
+    fn fmt_summary(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            formatter,
+            "deploy-header account={} timestamp={} ttl={} gas_price={} deps={}",
+            PublicKeyPrefix(self.account()),
+            self.timestamp(),
+            self.ttl(),
+            self.gas_price(),
+            self.dependencies().len()
+        )
+    }
+}
+
+impl Summary for Block {
+    fn fmt_summary(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            formatter,
+            "block hash={} height={} era={} deploys={} transfers={}",
+            self.hash().inner(),
+            self.height(),
+            self.header().era_id().value(),
+            self.deploy_hashes().len(),
+            self.transfer_hashes().len()
+        )
+    }
+}
+
+impl Summary for FinalitySignature {
+    fn fmt_summary(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            formatter,
+            "finality-signature block={} era={} signer={}",
+            self.block_hash.inner(),
+            self.era_id.value(),
+            PublicKeyPrefix(&self.public_key)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::SecretKey;
+
+    use super::*;
+    use crate::{crypto::AsymmetricKeyExt, rpcs::docs::DocExample};
+
+    /// The longest a summary of any of the fixtures may be.
+    const MAX_SUMMARY_LENGTH: usize = 120;
+
+    /// The summarized prefix of the public key of `SecretKey::doc_example()`.
+    const DOC_EXAMPLE_ACCOUNT: &str = "01d9bf2148";
+
+    fn assert_summary<T: Summary>(value: &T, expected: String) {
+        let summary = value.summary().to_string();
+        assert_eq!(summary, expected);
+        assert!(!summary.contains('\n'));
+        assert!(
+            summary.len() <= MAX_SUMMARY_LENGTH,
+            "summary '{}' is longer than {} characters",
+            summary,
+            MAX_SUMMARY_LENGTH
+        );
+    }
+
+    #[test]
+    fn should_summarize_deploy() {
+        let deploy = Deploy::doc_example();
+        assert_summary(
+            deploy,
+            format!(
+                "deploy hash={} account={} timestamp=2020-11-17T00:39:24.072Z size={} \
+                 transfer=true",
+                deploy.id().inner(),
+                DOC_EXAMPLE_ACCOUNT,
+                deploy.serialized_length()
+            ),
+        );
+        assert_summary(
+            deploy.header(),
+            format!(
+                "deploy-header account={} timestamp=2020-11-17T00:39:24.072Z ttl=1h gas_price=1 \
+                 deps=1",
+                DOC_EXAMPLE_ACCOUNT
+            ),
+        );
+    }
+
+    #[test]
+    fn should_summarize_block() {
+        let block = Block::doc_example();
+        assert_summary(
+            block,
+            format!(
+                "block hash={} height=10 era=1 deploys=1 transfers=0",
+                block.hash().inner()
+            ),
+        );
+    }
+
+    #[test]
+    fn should_summarize_finality_signature() {
+        let block = Block::doc_example();
+        let secret_key = SecretKey::doc_example();
+        let finality_signature = FinalitySignature::new(
+            *block.hash(),
+            block.header().era_id(),
+            secret_key,
+            PublicKey::from(secret_key),
+        );
+        assert_summary(
+            &finality_signature,
+            format!(
+                "finality-signature block={} era=1 signer={}",
+                block.hash().inner(),
+                DOC_EXAMPLE_ACCOUNT
+            ),
+        );
+    }
+}
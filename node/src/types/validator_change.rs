@@ -0,0 +1,35 @@
+// TODO - remove once schemars stops causing warning.
+#![allow(clippy::field_reassign_with_default)]
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use casper_types::U512;
+
+/// A change to the status of a single validator, taking effect at the start of an era.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub enum ValidatorChange {
+    /// The validator joined the validator set.
+    Added {
+        /// The validator's weight in the era.
+        weight: U512,
+    },
+    /// The validator left the validator set.
+    Removed {
+        /// The validator's weight in the previous era.
+        previous_weight: U512,
+    },
+    /// The validator's weight changed.
+    WeightChanged {
+        /// The validator's weight in the previous era.
+        previous_weight: U512,
+        /// The validator's weight in the era.
+        weight: U512,
+    },
+    /// The validator equivocated in the previous era, and is banned from the validator set.
+    Banned,
+    /// The validator was inactive in the previous era, and its bid was deactivated, evicting it
+    /// from the validator set once the auction takes this into account.
+    Evicted,
+}
@@ -0,0 +1,34 @@
+// TODO - remove once schemars stops causing warning.
+#![allow(clippy::field_reassign_with_default)]
+
+use datasize::DataSize;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// The performance of a single validator during a single era.
+#[derive(
+    Clone, Copy, DataSize, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(deny_unknown_fields)]
+pub struct ValidatorPerformance {
+    /// The number of blocks of the era proposed by the validator.
+    pub blocks_proposed: u64,
+    /// The number of blocks of the era the validator was expected to propose, i.e. its share of
+    /// the era's blocks in proportion to its weight, rounded down.
+    pub blocks_expected: u64,
+    /// The number of finality signatures the validator provided for blocks of the era.
+    pub finality_signatures_provided: u64,
+    /// The number of finality signatures the validator was expected to provide, i.e. one per
+    /// block of the era if it was a validator in the era, otherwise zero.
+    pub finality_signatures_expected: u64,
+    /// Whether the validator was found to have equivocated in the era.
+    pub equivocated: bool,
+}
+
+impl ValidatorPerformance {
+    /// Returns the number of expected finality signatures the validator didn't provide.
+    pub fn finality_signatures_missed(&self) -> u64 {
+        self.finality_signatures_expected
+            .saturating_sub(self.finality_signatures_provided)
+    }
+}
@@ -9,6 +9,7 @@ pub mod milliseconds;
 pub mod pid_file;
 #[cfg(target_os = "linux")]
 pub(crate) mod rlimit;
+pub mod rolling_bloom_filter;
 mod round_robin;
 
 use std::{
@@ -22,7 +23,7 @@ use std::{
     os::unix::fs::OpenOptionsExt,
     path::{Path, PathBuf},
     sync::Arc,
-    time::Duration,
+    time::{Duration, Instant},
 };
 #[cfg(test)]
 use std::{env, str::FromStr};
@@ -428,6 +429,22 @@ pub async fn wait_for_arc_drop<T>(arc: Arc<T>, attempts: usize, retry_delay: Dur
     false
 }
 
+/// Waits until `duration` has elapsed, returning the time which actually elapsed.
+///
+/// All timers of the effect builder and event queue are based on this function.  In tests, it
+/// elapses in virtual time if a [`Simulation`](crate::testing::simulation::Simulation) is active on
+/// the current thread.
+pub(crate) async fn sleep(duration: Duration) -> Duration {
+    #[cfg(test)]
+    if let Some(timer) = crate::testing::simulation::virtual_timer(duration) {
+        return timer.await;
+    }
+
+    let then = Instant::now();
+    tokio::time::sleep(duration).await;
+    Instant::now() - then
+}
+
 #[cfg(test)]
 mod tests {
     use std::{sync::Arc, time::Duration};
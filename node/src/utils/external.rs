@@ -54,6 +54,11 @@ pub enum External<T> {
 }
 
 impl<T> External<T> {
+    /// Returns whether the value has not been specified.
+    pub fn is_missing(&self) -> bool {
+        matches!(self, External::Missing)
+    }
+
     /// Creates an external from a value.
     pub fn from_value(value: T) -> Self {
         External::Loaded(value)
@@ -63,15 +68,14 @@ impl<T> External<T> {
     pub fn from_path<P: AsRef<Path>>(path: P) -> Self {
         External::Path(path.as_ref().to_owned())
     }
-}
 
-impl<T> External<T>
-where
-    T: Loadable,
-{
-    /// Loads the value if not loaded already, resolving relative paths from `root` or returns
-    /// available value. If the value is `Missing`, returns an error.
-    pub fn load<P: AsRef<Path>>(self, root: P) -> Result<T, LoadError<T::Error>> {
+    /// Like [`External::load`], but loads the value from a path via `load_from_path`.
+    pub fn load_with<P, F, E>(self, root: P, load_from_path: F) -> Result<T, LoadError<E>>
+    where
+        P: AsRef<Path>,
+        F: FnOnce(&Path) -> Result<T, E>,
+        E: Debug + Display,
+    {
         match self {
             External::Path(path) => {
                 let full_path = if path.is_relative() {
@@ -80,7 +84,7 @@ where
                     path
                 };
 
-                T::from_path(&full_path).map_err(move |error| LoadError::Failed {
+                load_from_path(&full_path).map_err(move |error| LoadError::Failed {
                     error,
                     // We canonicalize `full_path` here, with `ReadFileError` we get extra
                     // information about the absolute path this way if the latter is relative. It
@@ -92,6 +96,18 @@ where
             External::Missing => Err(LoadError::Missing),
         }
     }
+}
+
+impl<T> External<T>
+where
+    T: Loadable,
+{
+    /// Loads the value if not loaded already, resolving relative paths from `root` or returns
+    /// available value. If the value is `Missing`, returns an error.
+    #[allow(clippy::redundant_closure)] // `T::from_path` isn't general over the path's lifetime.
+    pub fn load<P: AsRef<Path>>(self, root: P) -> Result<T, LoadError<T::Error>> {
+        self.load_with(root, |path| T::from_path(path))
+    }
 
     /// Returns the full path to the external item, or `None` if the type is `Loaded` or `Missing`.
     pub fn full_path<P: AsRef<Path>>(&self, root: P) -> Option<PathBuf> {
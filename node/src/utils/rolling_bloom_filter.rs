@@ -0,0 +1,204 @@
+//! A Bloom filter over digests which expire.
+
+use std::{collections::BTreeMap, convert::TryInto};
+
+use datasize::DataSize;
+
+use crate::{
+    crypto::hash::Digest,
+    types::{TimeDiff, Timestamp},
+};
+
+/// The false positive rate each generation is sized for when holding the expected number of
+/// entries.
+const TARGET_FALSE_POSITIVE_RATE: f64 = 0.01;
+
+/// The maximum number of bits in a single generation, i.e. 16 MiB.  Beyond the number of entries
+/// this can hold at the target rate, the false positive rate is allowed to rise instead.
+const MAX_BITS_PER_GENERATION: u64 = 1 << 27;
+
+/// The maximum number of bits set per entry.
+const MAX_HASH_COUNT: u32 = 16;
+
+/// A Bloom filter over digests which each expire at a given time.
+///
+/// Entries are grouped into generations by their expiry time, each generation covering a fixed
+/// span of expiry times, and a whole generation is dropped once all of its span has passed.  This
+/// keeps the filter's occupancy, and hence its false positive rate, bounded by the number of
+/// unexpired entries rather than growing for as long as entries are added.
+///
+/// The digests are used directly as the source of the filter's hash values, so they must be
+/// cryptographic hashes of the items they represent.
+#[derive(Clone, DataSize, Debug)]
+pub struct RollingBloomFilter {
+    /// The span of expiry times covered by each generation.
+    generation_span: TimeDiff,
+    /// The number of bits in each generation.
+    bits_per_generation: u64,
+    /// The number of bits set per entry.
+    hash_count: u32,
+    /// The generations, keyed by the index of the span of expiry times they cover.
+    generations: BTreeMap<u64, Generation>,
+}
+
+/// The entries whose expiry times fall within one span.
+#[derive(Clone, DataSize, Debug)]
+struct Generation {
+    /// The filter's bits.
+    words: Vec<u64>,
+    /// The number of bits which are set.
+    set_bit_count: u64,
+}
+
+impl RollingBloomFilter {
+    /// Creates a new filter whose generations each cover `generation_span` of expiry times and are
+    /// sized to hold `expected_entries_per_generation` entries at a false positive rate of 1%.
+    ///
+    /// Generations are allocated as entries are added to them, not up front.
+    pub fn new(generation_span: TimeDiff, expected_entries_per_generation: u64) -> Self {
+        let expected_entries = expected_entries_per_generation.max(1) as f64;
+        let optimal_bits = -expected_entries * TARGET_FALSE_POSITIVE_RATE.ln()
+            / (std::f64::consts::LN_2 * std::f64::consts::LN_2);
+        // Round up to a whole number of words.
+        let bits_per_generation =
+            ((optimal_bits.ceil() as u64).min(MAX_BITS_PER_GENERATION) + 63) / 64 * 64;
+        let optimal_hash_count =
+            bits_per_generation as f64 / expected_entries * std::f64::consts::LN_2;
+        let hash_count = (optimal_hash_count.round() as u32)
+            .max(1)
+            .min(MAX_HASH_COUNT);
+
+        RollingBloomFilter {
+            generation_span: TimeDiff::from(generation_span.millis().max(1)),
+            bits_per_generation,
+            hash_count,
+            generations: BTreeMap::new(),
+        }
+    }
+
+    /// Adds an entry which expires at `expires`.
+    pub fn insert(&mut self, digest: &Digest, expires: Timestamp) {
+        let generation_index = expires.millis() / self.generation_span.millis();
+        let words = (self.bits_per_generation / 64) as usize;
+        let generation = self
+            .generations
+            .entry(generation_index)
+            .or_insert_with(|| Generation {
+                words: vec![0; words],
+                set_bit_count: 0,
+            });
+        for bit in bit_indices(digest, self.hash_count, self.bits_per_generation) {
+            let (word, mask) = ((bit / 64) as usize, 1_u64 << (bit % 64));
+            if generation.words[word] & mask == 0 {
+                generation.words[word] |= mask;
+                generation.set_bit_count += 1;
+            }
+        }
+    }
+
+    /// Returns `false` if the digest has definitely not been added since its generation was last
+    /// pruned, or `true` if it probably has.
+    pub fn may_contain(&self, digest: &Digest) -> bool {
+        self.generations.values().any(|generation| {
+            bit_indices(digest, self.hash_count, self.bits_per_generation)
+                .all(|bit| generation.words[(bit / 64) as usize] & (1_u64 << (bit % 64)) != 0)
+        })
+    }
+
+    /// Drops every generation in which all entries have expired by `current_instant`.
+    pub fn prune(&mut self, current_instant: Timestamp) {
+        let span = self.generation_span.millis();
+        let now = current_instant.millis();
+        self.generations
+            .retain(|index, _| index.saturating_add(1).saturating_mul(span) > now);
+    }
+
+    /// Returns the estimated probability that `may_contain` returns `true` for a digest which has
+    /// not been added.
+    pub fn estimated_false_positive_rate(&self) -> f64 {
+        let true_negative_rate: f64 = self
+            .generations
+            .values()
+            .map(|generation| {
+                let fill_ratio = generation.set_bit_count as f64 / self.bits_per_generation as f64;
+                1.0 - fill_ratio.powi(self.hash_count as i32)
+            })
+            .product();
+        1.0 - true_negative_rate
+    }
+}
+
+impl Default for RollingBloomFilter {
+    /// Returns a filter with day-long generations, each sized for a thousand entries.
+    fn default() -> Self {
+        RollingBloomFilter::new(TimeDiff::from_seconds(24 * 60 * 60), 1_000)
+    }
+}
+
+/// Returns the indices of the bits representing the digest, derived from its first 16 bytes by
+/// double hashing.
+fn bit_indices(digest: &Digest, hash_count: u32, bit_count: u64) -> impl Iterator<Item = u64> {
+    let bytes = digest.to_array();
+    let first = u64::from_le_bytes(bytes[..8].try_into().expect("digest has at least 8 bytes"));
+    // Must be odd, so that successive indices don't cycle early.
+    let second = u64::from_le_bytes(bytes[8..16].try_into().expect("digest has 16 bytes")) | 1;
+    (0..u64::from(hash_count))
+        .map(move |index| first.wrapping_add(index.wrapping_mul(second)) % bit_count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestRng;
+
+    const SPAN: u64 = 1_000;
+
+    #[test]
+    fn should_contain_inserted_digests_until_pruned() {
+        let mut rng = TestRng::new();
+        let mut filter = RollingBloomFilter::new(TimeDiff::from(SPAN), 100);
+
+        let early = Digest::random(&mut rng);
+        let late = Digest::random(&mut rng);
+        filter.insert(&early, Timestamp::from(SPAN / 2));
+        filter.insert(&late, Timestamp::from(SPAN * 3 / 2));
+
+        filter.prune(Timestamp::from(SPAN - 1));
+        assert!(filter.may_contain(&early));
+        assert!(filter.may_contain(&late));
+
+        // The whole of the first generation's span has passed, so it is dropped.
+        filter.prune(Timestamp::from(SPAN));
+        assert!(!filter.may_contain(&early));
+        assert!(filter.may_contain(&late));
+
+        filter.prune(Timestamp::from(SPAN * 2));
+        assert!(!filter.may_contain(&late));
+        assert!(filter.generations.is_empty());
+    }
+
+    #[test]
+    fn should_keep_false_positive_rate_near_target_at_expected_occupancy() {
+        let mut rng = TestRng::new();
+        let expected_entries = 10_000;
+        let mut filter = RollingBloomFilter::new(TimeDiff::from(SPAN), expected_entries);
+        for _ in 0..expected_entries {
+            filter.insert(&Digest::random(&mut rng), Timestamp::zero());
+        }
+
+        let estimate = filter.estimated_false_positive_rate();
+        assert!(estimate > 0.0 && estimate < 2.0 * TARGET_FALSE_POSITIVE_RATE);
+
+        let trials = 100_000;
+        let false_positives = (0..trials)
+            .filter(|_| filter.may_contain(&Digest::random(&mut rng)))
+            .count();
+        let measured = false_positives as f64 / trials as f64;
+        assert!(
+            (measured - estimate).abs() < TARGET_FALSE_POSITIVE_RATE / 2.0,
+            "measured {} vs estimated {}",
+            measured,
+            estimate
+        );
+    }
+}
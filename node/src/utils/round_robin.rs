@@ -29,6 +29,10 @@ use tracing::warn;
 /// are passed to the constructor function.
 ///
 /// The scheduler keeps track internally which queue needs to be popped next.
+///
+/// Optionally, a single queue can be given strict priority. Items from it are returned ahead of all
+/// other queues, but at most as many times in a row as its weight, after which one item is returned
+/// through regular round-robin scheduling. This prevents the priority queue from starving others.
 #[derive(Debug)]
 pub struct WeightedRoundRobin<I, K> {
     /// Current iteration state.
@@ -37,6 +41,9 @@ pub struct WeightedRoundRobin<I, K> {
     /// A list of slots that are round-robin'd.
     slots: Vec<Slot<K>>,
 
+    /// The slot of the queue with strict priority, if any.
+    priority_slot: Option<Slot<K>>,
+
     /// Actual queues.
     queues: HashMap<K, QueueState<I>>,
 
@@ -100,6 +107,9 @@ struct IterationState<K> {
 
     /// The position of the active slot. Used to calculate the next slot.
     active_slot_idx: usize,
+
+    /// Number of items returned in a row from the priority queue.
+    priority_streak: usize,
 }
 
 /// An internal slot in the round-robin scheduler.
@@ -228,14 +238,32 @@ where
             state: Mutex::new(IterationState {
                 active_slot,
                 active_slot_idx: 0,
+                priority_streak: 0,
             }),
             slots,
+            priority_slot: None,
             queues,
             total: Semaphore::new(0),
             sealed: AtomicBool::new(false),
         }
     }
 
+    /// Gives the queue identified by key strict priority over all other queues.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if the queue identified by key `queue` does not exist.
+    pub(crate) fn with_strict_priority(mut self, queue: K) -> Self {
+        let slot = self
+            .slots
+            .iter()
+            .find(|slot| slot.key == queue)
+            .copied()
+            .expect("tried to prioritize non-existent queue");
+        self.priority_slot = Some(slot);
+        self
+    }
+
     /// Pushes an item to a queue identified by key.
     ///
     /// ## Panics
@@ -267,6 +295,24 @@ where
 
         let mut inner = self.state.lock().await;
 
+        if let Some(priority_slot) = self.priority_slot {
+            if inner.priority_streak < priority_slot.tickets {
+                let queue_state = self
+                    .queues
+                    .get(&priority_slot.key)
+                    .expect("the priority queue disappeared. this should not happen");
+
+                if let Some(item) = queue_state.queue.lock().await.pop_front() {
+                    queue_state.dec_count();
+                    inner.priority_streak += 1;
+                    return (item, priority_slot.key);
+                }
+            }
+            // Either the priority queue is empty or it has used up its streak, so let the other
+            // queues have their turn.
+            inner.priority_streak = 0;
+        }
+
         // We know we have at least one item in a queue.
         loop {
             let queue_state = self
@@ -395,6 +441,28 @@ mod tests {
         assert_eq!(('c', QueueKind::One), scheduler.pop().await);
     }
 
+    #[tokio::test]
+    async fn should_prioritize_strict_priority_queue_without_starving_others() {
+        let scheduler = WeightedRoundRobin::<char, QueueKind>::new(weights())
+            .with_strict_priority(QueueKind::Two);
+        scheduler.push('a', QueueKind::One).await;
+        scheduler.push('b', QueueKind::One).await;
+        for item in &['c', 'd', 'e', 'f', 'g'] {
+            scheduler.push(*item, QueueKind::Two).await;
+        }
+
+        // The priority queue is polled first, but only twice in a row (its weight) before one item
+        // is returned via regular round-robin scheduling, which may itself pick the priority queue.
+        assert_eq!(('c', QueueKind::Two), scheduler.pop().await);
+        assert_eq!(('d', QueueKind::Two), scheduler.pop().await);
+        assert_eq!(('a', QueueKind::One), scheduler.pop().await);
+        assert_eq!(('e', QueueKind::Two), scheduler.pop().await);
+        assert_eq!(('f', QueueKind::Two), scheduler.pop().await);
+        assert_eq!(('g', QueueKind::Two), scheduler.pop().await);
+        assert_eq!(('b', QueueKind::One), scheduler.pop().await);
+        assert_eq!(scheduler.item_count(), 0);
+    }
+
     #[tokio::test]
     async fn can_seal_queue() {
         let scheduler = WeightedRoundRobin::<char, QueueKind>::new(weights());
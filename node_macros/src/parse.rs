@@ -19,7 +19,10 @@ use syn::{
     Expr, Ident, ItemType, Path, Token, Type,
 };
 
-use crate::{rust_type::RustType, util::to_ident};
+use crate::{
+    rust_type::RustType,
+    util::{suffix_ident, to_ident},
+};
 use proc_macro2::TokenStream;
 
 #[derive(Debug)]
@@ -169,11 +172,29 @@ impl Parse for ReactorDefinition {
         let _: Token!(:) = content.parse()?;
         braced!(requests_content in content);
 
-        let requests: Vec<_> = requests_content
+        let mut requests: Vec<_> = requests_content
             .parse_terminated::<RequestDefinition, Token!(;)>(RequestDefinition::parse)?
             .into_iter()
             .collect();
 
+        // Requests of the same generic type with different type arguments, e.g. fetcher requests
+        // for different items, need distinct variants.
+        let request_idents: Vec<_> = requests
+            .iter()
+            .map(|req| req.request_type.ident().to_string())
+            .collect();
+        for req in requests.iter_mut() {
+            let ident = req.request_type.ident().to_string();
+            if request_idents
+                .iter()
+                .filter(|other| **other == ident)
+                .count()
+                > 1
+            {
+                req.disambiguate_variant_ident();
+            }
+        }
+
         // Announcements.
         let announcements_content;
         let _: kw::announcements = content.parse()?;
@@ -385,13 +406,28 @@ impl Parse for EventDefinition {
 pub(crate) struct RequestDefinition {
     pub request_type: RustType,
     pub target: Target,
+    /// Ident of the request's variant, if other than the request type's identifier.
+    variant_ident: Option<Ident>,
 }
 
 impl RequestDefinition {
     /// Returns an ident identifying the request that is suitable for a variant, e.g.
     /// `NetworkRequest`.
     pub fn variant_ident(&self) -> Ident {
-        self.request_type.ident()
+        self.variant_ident
+            .clone()
+            .unwrap_or_else(|| self.request_type.ident())
+    }
+
+    /// Suffixes the request's variant ident with its last type argument, e.g.
+    /// `FetcherRequestDeploy` for `FetcherRequest<NodeId, Deploy>`.
+    fn disambiguate_variant_ident(&mut self) {
+        if let Some(last_arg_ident) = self.request_type.last_type_arg_ident() {
+            self.variant_ident = Some(suffix_ident(
+                &self.request_type.ident(),
+                &last_arg_ident.to_string(),
+            ));
+        }
     }
 
     /// Returns the type of the request.
@@ -422,6 +458,7 @@ impl Parse for RequestDefinition {
         Ok(RequestDefinition {
             request_type,
             target,
+            variant_ident: None,
         })
     }
 }
@@ -9,7 +9,7 @@ use std::{
 use inflector::cases::snakecase::to_snake_case;
 use proc_macro2::TokenStream;
 use quote::quote;
-use syn::{Ident, Path, Type};
+use syn::{GenericArgument, Ident, Path, PathArguments, Type};
 
 use crate::util::to_ident;
 
@@ -51,6 +51,30 @@ impl RustType {
         quote!(#ident #args)
     }
 
+    /// Returns the identifier of the last type argument without its own type arguments, if any,
+    /// e.g. `Deploy` for `FetcherRequest<NodeId, Deploy>`.
+    pub fn last_type_arg_ident(&self) -> Option<Ident> {
+        let arguments = &self
+            .0
+            .segments
+            .last()
+            .expect("type has no last part?")
+            .arguments;
+        match arguments {
+            PathArguments::AngleBracketed(args) => {
+                args.args.iter().rev().find_map(|arg| match arg {
+                    GenericArgument::Type(Type::Path(type_path)) => type_path
+                        .path
+                        .segments
+                        .last()
+                        .map(|segment| segment.ident.clone()),
+                    _ => None,
+                })
+            }
+            _ => None,
+        }
+    }
+
     /// Returns the full type as it was given in the macro call.
     pub fn as_given(&self) -> &Path {
         &self.0
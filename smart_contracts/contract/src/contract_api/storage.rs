@@ -17,6 +17,11 @@ use crate::{
     unwrap_or_revert::UnwrapOrRevert,
 };
 
+/// Re-exported so that contract authors can check, e.g. in tests of their contracts, that the
+/// values they store fit within the size limits of global state via
+/// [`CLType::max_serialized_length`](casper_types::CLType::max_serialized_length).
+pub use casper_types::SizeBounds;
+
 /// Reads value under `uref` in the global state.
 pub fn read<T: CLTyped + FromBytes>(uref: URef) -> Result<Option<T>, bytesrepr::Error> {
     let key: Key = uref.into();
@@ -125,7 +125,7 @@ fn small_function() {
 #[no_mangle]
 pub extern "C" fn call() {
     let seed: u64 = runtime::get_named_arg(ARG_SEED);
-    let (random_bytes, source_account, destination_account): (Vec<u8>, AccountHash, AccountHash) =
+    let (random_bytes, source_account, destination_account): (Bytes, AccountHash, AccountHash) =
         runtime::get_named_arg(ARG_OTHERS);
 
     // ========== storage, execution and upgrading of contracts ====================================
@@ -10,7 +10,7 @@ use casper_contract::{
 };
 use casper_types::{
     bytesrepr,
-    bytesrepr::{Error, FromBytes, ToBytes, U8_SERIALIZED_LENGTH},
+    bytesrepr::{Error, FromBytes, NotU8, ToBytes, U8_SERIALIZED_LENGTH},
     runtime_args, ApiError, CLType, CLTyped, ContractHash, ContractPackageHash, EntryPointType,
     Key, Phase, RuntimeArgs, Tagged, URef, U512,
 };
@@ -136,6 +136,8 @@ impl FromBytes for Call {
     }
 }
 
+impl NotU8 for Call {}
+
 impl CLTyped for Call {
     fn cl_type() -> CLType {
         CLType::Any
@@ -250,7 +250,7 @@ fn measure_arg_size(bytes: usize) {
         .expect("should have key")
         .into();
 
-    let argument: Vec<u8> = iter::repeat(b'1').take(bytes).collect();
+    let argument: Bytes = iter::repeat(b'1').take(bytes).collect::<Vec<u8>>().into();
 
     runtime::call_versioned_contract::<()>(
         contract_package_hash,
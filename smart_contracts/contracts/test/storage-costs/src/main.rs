@@ -10,8 +10,8 @@ use casper_contract::{
     unwrap_or_revert::UnwrapOrRevert,
 };
 use casper_types::{
-    contracts::NamedKeys, CLType, EntryPoint, EntryPointAccess, EntryPointType, EntryPoints, Key,
-    U512,
+    bytesrepr::Bytes, contracts::NamedKeys, CLType, EntryPoint, EntryPointAccess, EntryPointType,
+    EntryPoints, Key, U512,
 };
 
 const WRITE_FUNCTION_SMALL_NAME: &str = "write_function_small";
@@ -43,7 +43,7 @@ pub extern "C" fn write_function_small() {
     let uref = runtime::get_key(WRITE_KEY_NAME)
         .and_then(Key::into_uref)
         .unwrap_or_revert();
-    storage::write(uref, WRITE_SMALL_VALUE.to_vec());
+    storage::write(uref, Bytes::from(WRITE_SMALL_VALUE));
 }
 
 #[no_mangle]
@@ -51,7 +51,7 @@ pub extern "C" fn write_function_large() {
     let uref = runtime::get_key(WRITE_KEY_NAME)
         .and_then(Key::into_uref)
         .unwrap_or_revert();
-    storage::write(uref, WRITE_LARGE_VALUE.to_vec());
+    storage::write(uref, Bytes::from(WRITE_LARGE_VALUE));
 }
 
 #[no_mangle]
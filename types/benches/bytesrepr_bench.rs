@@ -4,11 +4,12 @@ use std::{collections::BTreeMap, iter};
 
 use casper_types::{
     account::AccountHash,
-    bytesrepr::{self, Bytes, FromBytes, ToBytes},
+    bytesrepr::{self, Bytes, BytesRef, FromBytes, FromBytesRef, ToBytes},
     AccessRights, CLTyped, CLValue, Key, URef, U128, U256, U512,
 };
 
 static KB: usize = 1024;
+static MB: usize = 1024 * KB;
 static BATCH: usize = 4 * KB;
 
 const TEST_I32: i32 = 123_456_789;
@@ -55,6 +56,24 @@ fn deserialize_vector_of_u8(b: &mut Bencher) {
     b.iter(|| Bytes::from_bytes(black_box(&data)))
 }
 
+fn prepare_large_serialized_bytes() -> Vec<u8> {
+    (0..4 * MB)
+        .map(|value| value as u8)
+        .collect::<Bytes>()
+        .to_bytes()
+        .unwrap()
+}
+
+fn deserialize_large_bytes(b: &mut Bencher) {
+    let data = prepare_large_serialized_bytes();
+    b.iter(|| Bytes::from_bytes(black_box(&data)))
+}
+
+fn deserialize_large_bytes_ref(b: &mut Bencher) {
+    let data = prepare_large_serialized_bytes();
+    b.iter(|| BytesRef::from_bytes_ref(black_box(&data)))
+}
+
 fn serialize_u8(b: &mut Bencher) {
     b.iter(|| ToBytes::to_bytes(black_box(&129u8)));
 }
@@ -442,6 +461,8 @@ fn bytesrepr_bench(c: &mut Criterion) {
     c.bench_function("deserialize_vector_of_i32s", deserialize_vector_of_i32s);
     c.bench_function("serialize_vector_of_u8", serialize_vector_of_u8);
     c.bench_function("deserialize_vector_of_u8", deserialize_vector_of_u8);
+    c.bench_function("deserialize_large_bytes", deserialize_large_bytes);
+    c.bench_function("deserialize_large_bytes_ref", deserialize_large_bytes_ref);
     c.bench_function("serialize_u8", serialize_u8);
     c.bench_function("deserialize_u8", deserialize_u8);
     c.bench_function("serialize_i32", serialize_i32);
@@ -556,6 +556,12 @@ impl From<bytesrepr::Error> for ApiError {
             bytesrepr::Error::Formatting => ApiError::Formatting,
             bytesrepr::Error::LeftOverBytes => ApiError::LeftOverBytes,
             bytesrepr::Error::OutOfMemory => ApiError::OutOfMemory,
+            bytesrepr::Error::ExceededLimit => ApiError::Formatting,
+            bytesrepr::Error::ExceededRecursionDepth => ApiError::Formatting,
+            bytesrepr::Error::InvalidTag { .. } => ApiError::Formatting,
+            bytesrepr::Error::InvalidBool(_) => ApiError::Formatting,
+            bytesrepr::Error::Utf8 => ApiError::Formatting,
+            bytesrepr::Error::ZeroDenominator => ApiError::Formatting,
         }
     }
 }
@@ -1,5 +1,6 @@
 //! Contains serialization and deserialization code for types used throughout the system.
 mod bytes;
+mod bytes_ref;
 
 // Can be removed once https://github.com/rust-lang/rustfmt/issues/3362 is resolved.
 #[rustfmt::skip]
@@ -11,9 +12,7 @@ use alloc::{
     string::String,
     vec::Vec,
 };
-#[cfg(debug_assertions)]
-use core::any;
-use core::{mem, ptr::NonNull};
+use core::{any, cmp, mem, ptr::NonNull};
 
 use num_integer::Integer;
 use num_rational::Ratio;
@@ -22,6 +21,7 @@ use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 pub use bytes::Bytes;
+pub use bytes_ref::BytesRef;
 
 /// The number of bytes in a serialized `()`.
 pub const UNIT_SERIALIZED_LENGTH: usize = 0;
@@ -81,6 +81,21 @@ pub trait FromBytes: Sized {
     }
 }
 
+/// A type which can be deserialized from a slice while borrowing from it, rather than copying data
+/// such as large byte arrays out of the input.
+///
+/// Every [`FromBytes`] type can be deserialized this way too, in which case nothing is borrowed.
+pub trait FromBytesRef<'a>: Sized {
+    /// Deserializes the slice into `Self`, which may hold references into `bytes`.
+    fn from_bytes_ref(bytes: &'a [u8]) -> Result<(Self, &'a [u8]), Error>;
+}
+
+impl<'a, T: FromBytes> FromBytesRef<'a> for T {
+    fn from_bytes_ref(bytes: &'a [u8]) -> Result<(Self, &'a [u8]), Error> {
+        T::from_bytes(bytes)
+    }
+}
+
 /// Returns a `Vec<u8>` initialized with sufficient capacity to hold `to_be_serialized` after
 /// serialization.
 pub fn unchecked_allocate_buffer<T: ToBytes>(to_be_serialized: &T) -> Vec<u8> {
@@ -99,13 +114,15 @@ pub fn allocate_buffer<T: ToBytes>(to_be_serialized: &T) -> Result<Vec<u8>, Erro
 }
 
 /// Serialization and deserialization errors.
+///
+/// Malformed input is reported by the most specific variant available, with `Formatting` used for
+/// any other kind.
 #[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 #[cfg_attr(feature = "std", derive(Error))]
-#[repr(u8)]
 pub enum Error {
     /// Early end of stream while deserializing.
     #[cfg_attr(feature = "std", error("Deserialization error: early end of stream"))]
-    EarlyEndOfStream = 0,
+    EarlyEndOfStream,
     /// Formatting error while deserializing.
     #[cfg_attr(feature = "std", error("Deserialization error: formatting"))]
     Formatting,
@@ -115,6 +132,60 @@ pub enum Error {
     /// Out of memory error.
     #[cfg_attr(feature = "std", error("Serialization error: out of memory"))]
     OutOfMemory,
+    /// A declared length exceeded the permitted maximum while deserializing.
+    #[cfg_attr(feature = "std", error("Deserialization error: exceeded length limit"))]
+    ExceededLimit,
+    /// A value was nested more deeply than permitted while deserializing.
+    #[cfg_attr(
+        feature = "std",
+        error("Deserialization error: exceeded recursion depth")
+    )]
+    ExceededRecursionDepth,
+    /// A tag not denoting any variant of the type being deserialized.
+    #[cfg_attr(
+        feature = "std",
+        error("Deserialization error: invalid tag {tag} for {type_name}")
+    )]
+    InvalidTag {
+        /// The invalid tag.
+        tag: u8,
+        /// The name of the outermost type being deserialized, as given by
+        /// [`core::any::type_name`].
+        ///
+        /// Where the tag belongs to a value nested in an `Option`, `Result` or collection, this is
+        /// the name of the enclosing generic type, which includes that of the nested one.  Only
+        /// intended for diagnostics, and not preserved by serde serialization.
+        #[serde(skip)]
+        type_name: &'static str,
+    },
+    /// A byte other than `0` or `1` while deserializing a `bool`.
+    #[cfg_attr(feature = "std", error("Deserialization error: invalid bool {0}"))]
+    InvalidBool(u8),
+    /// Invalid UTF-8 while deserializing a `String`.
+    #[cfg_attr(feature = "std", error("Deserialization error: invalid UTF-8"))]
+    Utf8,
+    /// A zero denominator while serializing or deserializing a `Ratio`.
+    #[cfg_attr(feature = "std", error("Serialization error: zero denominator"))]
+    ZeroDenominator,
+}
+
+impl Error {
+    /// Returns an [`Error::InvalidTag`] for `tag` having been read while deserializing a `T`.
+    pub fn invalid_tag<T: ?Sized>(tag: u8) -> Self {
+        Error::InvalidTag {
+            tag,
+            type_name: any::type_name::<T>(),
+        }
+    }
+
+    /// Returns `self`, naming `T` as the type being deserialized if it is an
+    /// [`Error::InvalidTag`], so that a failure nested within a `T` names the outermost type.
+    fn within<T: ?Sized>(self) -> Self {
+        match self {
+            Error::InvalidTag { tag, .. } => Error::invalid_tag::<T>(tag),
+            error => error,
+        }
+    }
 }
 
 /// Deserializes `bytes` into an instance of `T`.
@@ -130,11 +201,44 @@ pub fn deserialize<T: FromBytes>(bytes: Vec<u8>) -> Result<T, Error> {
     }
 }
 
+/// Deserializes `bytes` into an instance of `T`, which may borrow from `bytes`.
+///
+/// Returns an error if the bytes cannot be deserialized into `T` or if not all of the input bytes
+/// are consumed in the operation.
+pub fn deserialize_ref<'a, T: FromBytesRef<'a>>(bytes: &'a [u8]) -> Result<T, Error> {
+    let (t, remainder) = T::from_bytes_ref(bytes)?;
+    if remainder.is_empty() {
+        Ok(t)
+    } else {
+        Err(Error::LeftOverBytes)
+    }
+}
+
 /// Serializes `t` into a `Vec<u8>`.
 pub fn serialize(t: impl ToBytes) -> Result<Vec<u8>, Error> {
     t.into_bytes()
 }
 
+/// A type whose serialized form starts with a `u32` declaring its length: the number of bytes for
+/// strings and byte arrays, or the number of elements for collections.
+pub trait LengthPrefixed: FromBytes {}
+
+/// Deserializes a length-prefixed `T` from `bytes`, checking its declared length against `max_len`
+/// first.
+///
+/// Returns `Error::ExceededLimit` without deserializing any of the value if the declared length is
+/// greater than `max_len`.
+pub fn from_bytes_with_limit<T: LengthPrefixed>(
+    bytes: &[u8],
+    max_len: usize,
+) -> Result<(T, &[u8]), Error> {
+    let (declared_len, _) = u32::from_bytes(bytes)?;
+    if declared_len as usize > max_len {
+        return Err(Error::ExceededLimit);
+    }
+    T::from_bytes(bytes)
+}
+
 pub(crate) fn safe_split_at(bytes: &[u8], n: usize) -> Result<(&[u8], &[u8]), Error> {
     if n > bytes.len() {
         Err(Error::EarlyEndOfStream)
@@ -176,7 +280,7 @@ impl FromBytes for bool {
             Some((byte, rem)) => match byte {
                 1 => Ok((true, rem)),
                 0 => Ok((false, rem)),
-                _ => Err(Error::Formatting),
+                _ => Err(Error::InvalidBool(*byte)),
             },
         }
     }
@@ -310,18 +414,151 @@ impl ToBytes for String {
 impl FromBytes for String {
     fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), Error> {
         let (size, remainder) = u32::from_bytes(bytes)?;
+        // Check the declared size against the remaining input and validate the contents before
+        // copying them, so that malformed input never causes an allocation.
         let (str_bytes, remainder) = safe_split_at(remainder, size as usize)?;
-        let result = String::from_utf8(str_bytes.to_vec()).map_err(|_| Error::Formatting)?;
-        Ok((result, remainder))
+        let result = str::from_utf8(str_bytes).map_err(|_| Error::Utf8)?;
+        Ok((String::from(result), remainder))
     }
 }
 
-fn ensure_efficient_serialization<T>() {
-    #[cfg(debug_assertions)]
-    debug_assert_ne!(
-        any::type_name::<T>(),
-        any::type_name::<u8>(),
-        "You should use Bytes newtype wrapper for efficiency"
+impl LengthPrefixed for String {}
+
+/// A type which may be serialized as the elements of a `Vec` or `VecDeque`, i.e. any type other
+/// than `u8`.
+///
+/// A `Vec<u8>` should instead be wrapped in the [`Bytes`] newtype to be serialized efficiently, so
+/// serializing or deserializing a `Vec<u8>` fails to compile.
+///
+/// `NotU8` is a plain marker trait implemented explicitly for the types of this crate other than
+/// `u8`.  As both the trait and `u8` are defined outside any downstream crate, the orphan rule
+/// prevents it ever being implemented for `u8`.  A downstream type serialized as the elements of a
+/// `Vec` must implement it too.
+pub trait NotU8 {}
+
+macro_rules! impl_not_u8 {
+    ($($type:ty),* $(,)?) => {
+        $(impl NotU8 for $type {})*
+    };
+}
+
+// Containers, whose elements are constrained by their own serialization impls if at all, and this
+// crate's serializable types other than `u8`.
+mod not_u8_impls {
+    use alloc::{
+        boxed::Box,
+        collections::{BTreeMap, BTreeSet, VecDeque},
+        string::String,
+        vec::Vec,
+    };
+
+    use num_rational::Ratio;
+
+    use super::{Bytes, NotU8};
+    use crate::{
+        account::{AccountHash, Weight},
+        contracts::{
+            ContractPackageStatus, ContractVersionKey, EntryPoint, EntryPointAccess,
+            EntryPointType, EntryPoints, Group, Parameter,
+        },
+        system::{
+            auction::{
+                self, Bid, Delegator, EraInfo, SeigniorageAllocation, SeigniorageRecipient,
+                UnbondingPurse,
+            },
+            handle_payment, mint, CallStackElement,
+        },
+        AccessRights, BlockTime, CLType, CLValue, Contract, ContractHash, ContractPackage,
+        ContractPackageHash, ContractWasm, ContractWasmHash, DeployHash, DeployInfo, EraId,
+        ExecutionEffect, ExecutionResult, Key, NamedArg, NamedKey, OpKind, Operation, Phase,
+        ProtocolVersion, PublicKey, RuntimeArgs, SecretKey, SemVer, Signature, Transfer,
+        TransferAddr, Transform, TransformEntry, URef, U128, U256, U512,
+    };
+
+    impl<T: ?Sized> NotU8 for Box<T> {}
+    impl<T> NotU8 for Vec<T> {}
+    impl<T> NotU8 for VecDeque<T> {}
+    impl<T> NotU8 for BTreeSet<T> {}
+    impl<K, V> NotU8 for BTreeMap<K, V> {}
+    impl<T> NotU8 for Option<T> {}
+    impl<T, E> NotU8 for Result<T, E> {}
+    impl<T> NotU8 for Ratio<T> {}
+    impl<T, const N: usize> NotU8 for [T; N] {}
+    impl<T1> NotU8 for (T1,) {}
+    impl<T1, T2> NotU8 for (T1, T2) {}
+    impl<T1, T2, T3> NotU8 for (T1, T2, T3) {}
+    impl<T1, T2, T3, T4> NotU8 for (T1, T2, T3, T4) {}
+
+    impl_not_u8!(
+        (),
+        bool,
+        i32,
+        i64,
+        u16,
+        u32,
+        u64,
+        str,
+        &str,
+        String,
+        U128,
+        U256,
+        U512,
+        Bytes
+    );
+
+    impl_not_u8!(
+        AccessRights,
+        AccountHash,
+        Bid,
+        BlockTime,
+        CallStackElement,
+        CLType,
+        CLValue,
+        Contract,
+        ContractHash,
+        ContractPackage,
+        ContractPackageHash,
+        ContractPackageStatus,
+        ContractVersionKey,
+        ContractWasm,
+        ContractWasmHash,
+        Delegator,
+        DeployHash,
+        DeployInfo,
+        EntryPoint,
+        EntryPointAccess,
+        EntryPointType,
+        EntryPoints,
+        EraId,
+        EraInfo,
+        ExecutionEffect,
+        ExecutionResult,
+        Group,
+        Key,
+        NamedArg,
+        NamedKey,
+        OpKind,
+        Operation,
+        Parameter,
+        Phase,
+        ProtocolVersion,
+        PublicKey,
+        RuntimeArgs,
+        SecretKey,
+        SeigniorageAllocation,
+        SeigniorageRecipient,
+        SemVer,
+        Signature,
+        Transfer,
+        TransferAddr,
+        Transform,
+        TransformEntry,
+        UnbondingPurse,
+        URef,
+        Weight,
+        auction::Error,
+        handle_payment::Error,
+        mint::Error,
     );
 }
 
@@ -329,10 +566,8 @@ fn iterator_serialized_length<'a, T: 'a + ToBytes>(ts: impl Iterator<Item = &'a
     U32_SERIALIZED_LENGTH + ts.map(ToBytes::serialized_length).sum::<usize>()
 }
 
-impl<T: ToBytes> ToBytes for Vec<T> {
+impl<T: ToBytes + NotU8> ToBytes for Vec<T> {
     fn to_bytes(&self) -> Result<Vec<u8>, Error> {
-        ensure_efficient_serialization::<T>();
-
         let mut result = try_vec_with_capacity(self.serialized_length())?;
         result.append(&mut (self.len() as u32).to_bytes()?);
 
@@ -344,8 +579,6 @@ impl<T: ToBytes> ToBytes for Vec<T> {
     }
 
     fn into_bytes(self) -> Result<Vec<u8>, Error> {
-        ensure_efficient_serialization::<T>();
-
         let mut result = allocate_buffer(&self)?;
         result.append(&mut (self.len() as u32).to_bytes()?);
 
@@ -379,21 +612,20 @@ fn try_vec_with_capacity<T>(capacity: usize) -> Result<Vec<T>, Error> {
     unsafe { Ok(Vec::from_raw_parts(ptr.as_ptr(), 0, capacity)) }
 }
 
-fn vec_from_vec<T: FromBytes>(bytes: Vec<u8>) -> Result<(Vec<T>, Vec<u8>), Error> {
-    ensure_efficient_serialization::<T>();
-
+fn vec_from_vec<T: FromBytes + NotU8>(bytes: Vec<u8>) -> Result<(Vec<T>, Vec<u8>), Error> {
     Vec::<T>::from_bytes(bytes.as_slice()).map(|(x, remainder)| (x, Vec::from(remainder)))
 }
 
-impl<T: FromBytes> FromBytes for Vec<T> {
+impl<T: FromBytes + NotU8> FromBytes for Vec<T> {
     fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), Error> {
-        ensure_efficient_serialization::<T>();
-
         let (count, mut stream) = u32::from_bytes(bytes)?;
 
-        let mut result = try_vec_with_capacity(count as usize)?;
+        // Don't reserve more memory up front than the size of the remaining input, whatever the
+        // declared count; the vector grows as required if the elements really are that many.
+        let max_capacity = stream.len() / cmp::max(mem::size_of::<T>(), 1);
+        let mut result = try_vec_with_capacity(cmp::min(count as usize, max_capacity))?;
         for _ in 0..count {
-            let (value, remainder) = T::from_bytes(stream)?;
+            let (value, remainder) = T::from_bytes(stream).map_err(Error::within::<Self>)?;
             result.push(value);
             stream = remainder;
         }
@@ -406,7 +638,9 @@ impl<T: FromBytes> FromBytes for Vec<T> {
     }
 }
 
-impl<T: ToBytes> ToBytes for VecDeque<T> {
+impl<T: FromBytes + NotU8> LengthPrefixed for Vec<T> {}
+
+impl<T: ToBytes + NotU8> ToBytes for VecDeque<T> {
     fn to_bytes(&self) -> Result<Vec<u8>, Error> {
         let (slice1, slice2) = self.as_slices();
         let mut result = allocate_buffer(self)?;
@@ -428,14 +662,14 @@ impl<T: ToBytes> ToBytes for VecDeque<T> {
     }
 }
 
-impl<T: FromBytes> FromBytes for VecDeque<T> {
+impl<T: FromBytes + NotU8> FromBytes for VecDeque<T> {
     fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), Error> {
-        let (vec, bytes) = Vec::from_bytes(bytes)?;
+        let (vec, bytes) = Vec::<T>::from_bytes(bytes).map_err(Error::within::<Self>)?;
         Ok((VecDeque::from(vec), bytes))
     }
 
     fn from_vec(bytes: Vec<u8>) -> Result<(Self, Vec<u8>), Error> {
-        let (vec, bytes) = vec_from_vec(bytes)?;
+        let (vec, bytes) = vec_from_vec::<T>(bytes).map_err(Error::within::<Self>)?;
         Ok((VecDeque::from(vec), bytes))
     }
 }
@@ -499,7 +733,7 @@ impl<V: FromBytes + Ord> FromBytes for BTreeSet<V> {
         let (num_keys, mut stream) = u32::from_bytes(bytes)?;
         let mut result = BTreeSet::new();
         for _ in 0..num_keys {
-            let (v, rem) = V::from_bytes(stream)?;
+            let (v, rem) = V::from_bytes(stream).map_err(Error::within::<Self>)?;
             result.insert(v);
             stream = rem;
         }
@@ -544,8 +778,8 @@ where
         let (num_keys, mut stream) = u32::from_bytes(bytes)?;
         let mut result = BTreeMap::new();
         for _ in 0..num_keys {
-            let (k, rem) = K::from_bytes(stream)?;
-            let (v, rem) = V::from_bytes(rem)?;
+            let (k, rem) = K::from_bytes(stream).map_err(Error::within::<Self>)?;
+            let (v, rem) = V::from_bytes(rem).map_err(Error::within::<Self>)?;
             result.insert(k, v);
             stream = rem;
         }
@@ -584,10 +818,10 @@ impl<T: FromBytes> FromBytes for Option<T> {
         match tag {
             OPTION_NONE_TAG => Ok((None, rem)),
             OPTION_SOME_TAG => {
-                let (t, rem) = T::from_bytes(rem)?;
+                let (t, rem) = T::from_bytes(rem).map_err(Error::within::<Self>)?;
                 Ok((Some(t), rem))
             }
-            _ => Err(Error::Formatting),
+            tag => Err(Error::invalid_tag::<Self>(tag)),
         }
     }
 }
@@ -618,14 +852,14 @@ impl<T: FromBytes, E: FromBytes> FromBytes for Result<T, E> {
         let (variant, rem) = u8::from_bytes(bytes)?;
         match variant {
             RESULT_ERR_TAG => {
-                let (value, rem) = E::from_bytes(rem)?;
+                let (value, rem) = E::from_bytes(rem).map_err(Error::within::<Self>)?;
                 Ok((Err(value), rem))
             }
             RESULT_OK_TAG => {
-                let (value, rem) = T::from_bytes(rem)?;
+                let (value, rem) = T::from_bytes(rem).map_err(Error::within::<Self>)?;
                 Ok((Ok(value), rem))
             }
-            _ => Err(Error::Formatting),
+            tag => Err(Error::invalid_tag::<Self>(tag)),
         }
     }
 }
@@ -1065,7 +1299,7 @@ where
 {
     fn to_bytes(&self) -> Result<Vec<u8>, Error> {
         if self.denom().is_zero() {
-            return Err(Error::Formatting);
+            return Err(Error::ZeroDenominator);
         }
         (self.numer().clone(), self.denom().clone()).into_bytes()
     }
@@ -1082,7 +1316,7 @@ where
     fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), Error> {
         let ((numer, denom), rem): ((T, T), &[u8]) = FromBytes::from_bytes(bytes)?;
         if denom.is_zero() {
-            return Err(Error::Formatting);
+            return Err(Error::ZeroDenominator);
         }
         Ok((Ratio::new(numer, denom), rem))
     }
@@ -1094,7 +1328,7 @@ where
 ///
 /// For safety you should prefer to use [`vec_u8_to_bytes`]. For efficiency reasons you should also
 /// avoid using serializing Vec<u8>.
-fn u8_slice_to_bytes(bytes: &[u8]) -> Result<Vec<u8>, Error> {
+pub(crate) fn u8_slice_to_bytes(bytes: &[u8]) -> Result<Vec<u8>, Error> {
     let serialized_length = u8_slice_serialized_length(bytes);
     let mut vec = try_vec_with_capacity(serialized_length)?;
     let length_prefix = bytes.len() as u32;
@@ -1117,7 +1351,7 @@ pub(crate) fn vec_u8_to_bytes(vec: &Vec<u8>) -> Result<Vec<u8>, Error> {
 ///
 /// This function adds a length prefix in the beginning.
 #[inline(always)]
-fn u8_slice_serialized_length(bytes: &[u8]) -> usize {
+pub(crate) fn u8_slice_serialized_length(bytes: &[u8]) -> usize {
     U32_SERIALIZED_LENGTH + bytes.len()
 }
 
@@ -1155,22 +1389,119 @@ mod tests {
     #[test]
     fn should_not_serialize_zero_denominator() {
         let malicious = Ratio::new_raw(1, 0);
-        assert_eq!(malicious.to_bytes().unwrap_err(), Error::Formatting);
+        assert_eq!(malicious.to_bytes().unwrap_err(), Error::ZeroDenominator);
     }
 
     #[test]
     fn should_not_deserialize_zero_denominator() {
         let malicious_bytes = (1u64, 0u64).to_bytes().unwrap();
         let result: Result<Ratio<u64>, Error> = super::deserialize(malicious_bytes);
-        assert_eq!(result.unwrap_err(), Error::Formatting);
+        assert_eq!(result.unwrap_err(), Error::ZeroDenominator);
+    }
+
+    #[test]
+    fn should_fail_to_deserialize_invalid_bool() {
+        assert_eq!(bool::from_bytes(&[2]).unwrap_err(), Error::InvalidBool(2));
+    }
+
+    #[test]
+    fn should_fail_to_deserialize_invalid_utf8() {
+        let mut malicious_bytes = 2u32.to_bytes().unwrap();
+        malicious_bytes.extend_from_slice(&[0xc3, 0x28]);
+        assert_eq!(
+            String::from_bytes(&malicious_bytes).unwrap_err(),
+            Error::Utf8
+        );
+    }
+
+    #[test]
+    fn should_fail_to_deserialize_invalid_tag() {
+        assert_eq!(
+            Option::<u64>::from_bytes(&[2]).unwrap_err(),
+            Error::InvalidTag {
+                tag: 2,
+                type_name: any::type_name::<Option<u64>>()
+            }
+        );
+        assert_eq!(
+            Result::<u64, String>::from_bytes(&[3]).unwrap_err(),
+            Error::InvalidTag {
+                tag: 3,
+                type_name: any::type_name::<Result<u64, String>>()
+            }
+        );
+    }
+
+    #[test]
+    fn should_name_outermost_type_in_nested_failure() {
+        // The tag of the `Result` is invalid, and the error names the `Vec` of `Option`s of it.
+        let mut malicious_bytes = 2u32.to_bytes().unwrap();
+        malicious_bytes.extend_from_slice(&[OPTION_NONE_TAG, OPTION_SOME_TAG, 3]);
+        assert_eq!(
+            Vec::<Option<Result<u64, String>>>::from_bytes(&malicious_bytes).unwrap_err(),
+            Error::InvalidTag {
+                tag: 3,
+                type_name: any::type_name::<Vec<Option<Result<u64, String>>>>()
+            }
+        );
+
+        // Other errors are passed through unchanged.
+        let malicious_bytes = [OPTION_SOME_TAG, 2];
+        assert_eq!(
+            Option::<bool>::from_bytes(&malicious_bytes).unwrap_err(),
+            Error::InvalidBool(2)
+        );
+    }
+
+    #[test]
+    fn should_fail_to_deserialize_truncated_string() {
+        let bytes = String::from("truncated").to_bytes().unwrap();
+        for len in 0..bytes.len() {
+            let result = String::from_bytes(&bytes[..len]);
+            assert_eq!(result.unwrap_err(), Error::EarlyEndOfStream);
+        }
+
+        // A declared length far beyond the input must fail without trying to allocate for it.
+        let mut malicious = u32::max_value().to_bytes().unwrap();
+        malicious.extend_from_slice(b"short");
+        let result = String::from_bytes(&malicious);
+        assert_eq!(result.unwrap_err(), Error::EarlyEndOfStream);
+    }
+
+    #[test]
+    fn should_deserialize_string_at_limit() {
+        let string = String::from("exact");
+        let bytes = string.to_bytes().unwrap();
+        let (deserialized, remainder) =
+            from_bytes_with_limit::<String>(&bytes, string.len()).unwrap();
+        assert_eq!(deserialized, string);
+        assert!(remainder.is_empty());
+    }
+
+    #[test]
+    fn should_fail_to_deserialize_string_over_limit() {
+        let string = String::from("too long");
+        let bytes = string.to_bytes().unwrap();
+        let result = from_bytes_with_limit::<String>(&bytes, string.len() - 1);
+        assert_eq!(result.unwrap_err(), Error::ExceededLimit);
+
+        // The limit is checked before the contents, so truncated input is rejected likewise.
+        let result = from_bytes_with_limit::<String>(&bytes[..U32_SERIALIZED_LENGTH], 0);
+        assert_eq!(result.unwrap_err(), Error::ExceededLimit);
+
+        // Truncated input within the limit is reported as such.
+        let result = from_bytes_with_limit::<String>(&bytes[..bytes.len() - 1], string.len());
+        assert_eq!(result.unwrap_err(), Error::EarlyEndOfStream);
     }
 
-    #[cfg(debug_assertions)]
     #[test]
-    #[should_panic(expected = "You should use Bytes newtype wrapper for efficiency")]
-    fn should_fail_to_serialize_slice_of_u8() {
-        let bytes = b"0123456789".to_vec();
-        bytes.to_bytes().unwrap();
+    fn should_fail_to_deserialize_vec_over_limit() {
+        let vec = vec![1u32, 2, 3];
+        let bytes = vec.to_bytes().unwrap();
+        let (deserialized, _) = from_bytes_with_limit::<Vec<u32>>(&bytes, vec.len()).unwrap();
+        assert_eq!(deserialized, vec);
+        let result = from_bytes_with_limit::<Vec<u32>>(&bytes, vec.len() - 1);
+        assert_eq!(result.unwrap_err(), Error::ExceededLimit);
     }
 }
 
@@ -24,6 +24,12 @@ use super::{Error, FromBytes, ToBytes};
 use crate::{CLType, CLTyped};
 
 /// A newtype wrapper for bytes that has efficient serialization routines.
+///
+/// A `Vec<u8>` can't be serialized or deserialized via [`ToBytes`] or [`FromBytes`] directly; doing
+/// so is a compile error, as `u8` doesn't implement [`NotU8`](super::NotU8).  Such a value should
+/// be converted to and from `Bytes` instead, e.g. via `Bytes::from(vec)` and
+/// `Vec::<u8>::from(bytes)`.  The serialized form is unchanged by this conversion, and `Bytes` has
+/// the same `CLType` as a `Vec<u8>`.
 #[derive(Clone, Ord, PartialOrd, Eq, PartialEq, Debug, Default, Hash)]
 pub struct Bytes(Vec<u8>);
 
@@ -119,6 +125,8 @@ impl FromBytes for Bytes {
     }
 }
 
+impl super::LengthPrefixed for Bytes {}
+
 impl Index<usize> for Bytes {
     type Output = u8;
 
@@ -267,16 +275,39 @@ impl<'de> Visitor<'de> for BytesVisitor {
     }
 }
 
+/// Visitor used for human-readable formats, accepting a hex-encoded string or, for backwards
+/// compatibility with older encodings, a sequence of integers.
+struct HexOrSeqVisitor;
+
+impl<'de> Visitor<'de> for HexOrSeqVisitor {
+    type Value = Bytes;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("hex-encoded string or byte array")
+    }
+
+    fn visit_seq<V>(self, visitor: V) -> Result<Bytes, V::Error>
+    where
+        V: SeqAccess<'de>,
+    {
+        BytesVisitor.visit_seq(visitor)
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Bytes, E>
+    where
+        E: SerdeError,
+    {
+        base16::decode(v).map(Bytes).map_err(SerdeError::custom)
+    }
+}
+
 impl<'de> Deserialize<'de> for Bytes {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'de>,
     {
         if deserializer.is_human_readable() {
-            let hex_string = String::deserialize(deserializer)?;
-            base16::decode(&hex_string)
-                .map(Bytes)
-                .map_err(SerdeError::custom)
+            deserializer.deserialize_any(HexOrSeqVisitor)
         } else {
             let bytes = deserializer.deserialize_byte_buf(BytesVisitor)?;
             Ok(bytes)
@@ -324,6 +355,19 @@ mod tests {
         bytesrepr::test_serialization_roundtrip(&data);
     }
 
+    #[test]
+    fn should_serialize_bytes_as_length_prefixed_raw_bytes() {
+        let data: Bytes = TRUTH.into();
+        let expected_serialized = vec![4, 0, 0, 0, 0xde, 0xad, 0xbe, 0xef];
+        assert_eq!(data.serialized_length(), expected_serialized.len());
+        assert_eq!(data.to_bytes().unwrap(), expected_serialized);
+        assert_eq!(data.clone().into_bytes().unwrap(), expected_serialized);
+        assert_eq!(
+            bytesrepr::deserialize::<Bytes>(expected_serialized).unwrap(),
+            data
+        );
+    }
+
     #[test]
     fn should_fail_to_serialize_deserialize_malicious_bytes() {
         let data: Bytes = vec![1, 2, 3, 4, 5].into();
@@ -358,6 +402,15 @@ mod tests {
         assert_eq!(bytes_de, Bytes::from(truth));
     }
 
+    #[test]
+    fn should_deserialize_legacy_byte_array_from_json() {
+        let bytes_de: Bytes = serde_json::from_value(json!([222, 173, 190, 239])).unwrap();
+        assert_eq!(bytes_de, Bytes::from(TRUTH));
+
+        assert!(serde_json::from_value::<Bytes>(json!([256])).is_err());
+        assert!(serde_json::from_value::<Bytes>(json!("not hex")).is_err());
+    }
+
     #[test]
     fn should_ser_de_readable() {
         let truth: Bytes = TRUTH.into();
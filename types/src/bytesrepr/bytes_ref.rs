@@ -0,0 +1,149 @@
+use alloc::vec::Vec;
+use core::ops::Deref;
+
+use super::{Bytes, Error, FromBytes, FromBytesRef, ToBytes};
+
+/// A borrowed view of a run of bytes, serialized identically to [`Bytes`].
+///
+/// Deserializing via [`FromBytesRef`] borrows the bytes from the input rather than copying them.
+/// Conversions to and from [`Bytes`] are explicit: `Bytes::from(bytes_ref)` copies the viewed
+/// bytes, and `BytesRef::from(&bytes)` borrows them.
+#[derive(Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Debug, Default, Hash)]
+pub struct BytesRef<'a>(&'a [u8]);
+
+impl<'a> BytesRef<'a> {
+    /// Constructs a new view of the given slice.
+    pub fn new(bytes: &'a [u8]) -> Self {
+        BytesRef(bytes)
+    }
+
+    /// Returns the viewed slice, borrowed for the lifetime of the underlying buffer.
+    pub fn as_slice(&self) -> &'a [u8] {
+        self.0
+    }
+}
+
+impl<'a> Deref for BytesRef<'a> {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        self.0
+    }
+}
+
+impl<'a> AsRef<[u8]> for BytesRef<'a> {
+    fn as_ref(&self) -> &[u8] {
+        self.0
+    }
+}
+
+impl<'a> From<&'a [u8]> for BytesRef<'a> {
+    fn from(bytes: &'a [u8]) -> Self {
+        BytesRef(bytes)
+    }
+}
+
+impl<'a> From<&'a Bytes> for BytesRef<'a> {
+    fn from(bytes: &'a Bytes) -> Self {
+        BytesRef(bytes.as_slice())
+    }
+}
+
+impl<'a> From<BytesRef<'a>> for Bytes {
+    fn from(bytes_ref: BytesRef<'a>) -> Self {
+        Bytes::from(bytes_ref.0)
+    }
+}
+
+impl<'a> ToBytes for BytesRef<'a> {
+    #[inline(always)]
+    fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        super::u8_slice_to_bytes(self.0)
+    }
+
+    #[inline(always)]
+    fn serialized_length(&self) -> usize {
+        super::u8_slice_serialized_length(self.0)
+    }
+}
+
+impl<'a> FromBytesRef<'a> for BytesRef<'a> {
+    fn from_bytes_ref(bytes: &'a [u8]) -> Result<(Self, &'a [u8]), Error> {
+        let (size, remainder) = u32::from_bytes(bytes)?;
+        let (result, remainder) = super::safe_split_at(remainder, size as usize)?;
+        Ok((BytesRef(result), remainder))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::BytesRef;
+    use crate::bytesrepr::{
+        self, Bytes, Error, FromBytes, FromBytesRef, ToBytes, U32_SERIALIZED_LENGTH,
+    };
+
+    /// Returns a serialized `Bytes` with a multi-megabyte payload.
+    fn large_serialized_bytes() -> Vec<u8> {
+        let payload = (0..4 * 1024 * 1024)
+            .map(|index| index as u8)
+            .collect::<Bytes>();
+        payload.to_bytes().unwrap()
+    }
+
+    #[test]
+    fn borrowed_and_owned_decoding_should_agree() {
+        let mut serialized = large_serialized_bytes();
+        serialized.extend_from_slice(&[1, 2, 3]);
+
+        let (owned, owned_remainder) = Bytes::from_bytes(&serialized).unwrap();
+        let (borrowed, borrowed_remainder) = BytesRef::from_bytes_ref(&serialized).unwrap();
+        assert_eq!(owned.as_slice(), borrowed.as_slice());
+        assert_eq!(owned_remainder, borrowed_remainder);
+        assert_eq!(Bytes::from(borrowed), owned);
+        assert_eq!(BytesRef::from(&owned), borrowed);
+
+        // Both forms serialize identically.
+        assert_eq!(borrowed.serialized_length(), owned.serialized_length());
+        assert_eq!(borrowed.to_bytes().unwrap(), owned.to_bytes().unwrap());
+    }
+
+    #[test]
+    fn should_borrow_from_input() {
+        let serialized = large_serialized_bytes();
+        let borrowed: BytesRef = bytesrepr::deserialize_ref(&serialized).unwrap();
+        assert!(core::ptr::eq(
+            borrowed.as_slice(),
+            &serialized[U32_SERIALIZED_LENGTH..]
+        ));
+    }
+
+    #[test]
+    fn should_fail_to_decode_truncated_input() {
+        let serialized = large_serialized_bytes();
+        let truncated = &serialized[..serialized.len() - 1];
+        assert_eq!(
+            BytesRef::from_bytes_ref(truncated).unwrap_err(),
+            Error::EarlyEndOfStream
+        );
+        assert_eq!(
+            Bytes::from_bytes(truncated).unwrap_err(),
+            Error::EarlyEndOfStream
+        );
+    }
+
+    #[test]
+    fn should_fail_to_deserialize_with_left_over_bytes() {
+        let mut serialized = BytesRef::new(&[0xde, 0xad]).to_bytes().unwrap();
+        serialized.push(0);
+        assert_eq!(
+            bytesrepr::deserialize_ref::<BytesRef>(&serialized).unwrap_err(),
+            Error::LeftOverBytes
+        );
+        assert_eq!(
+            bytesrepr::deserialize::<Bytes>(serialized).unwrap_err(),
+            Error::LeftOverBytes
+        );
+    }
+}
@@ -15,8 +15,13 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    bytesrepr::{self, FromBytes, ToBytes},
-    Key, URef, U128, U256, U512,
+    bytesrepr::{
+        self, FromBytes, ToBytes, BOOL_SERIALIZED_LENGTH, I32_SERIALIZED_LENGTH,
+        I64_SERIALIZED_LENGTH, U128_SERIALIZED_LENGTH, U256_SERIALIZED_LENGTH,
+        U32_SERIALIZED_LENGTH, U512_SERIALIZED_LENGTH, U64_SERIALIZED_LENGTH, U8_SERIALIZED_LENGTH,
+        UNIT_SERIALIZED_LENGTH,
+    },
+    Key, PublicKey, URef, U128, U256, U512, UREF_SERIALIZED_LENGTH,
 };
 
 const CL_TYPE_TAG_BOOL: u8 = 0;
@@ -43,6 +48,9 @@ const CL_TYPE_TAG_TUPLE3: u8 = 20;
 const CL_TYPE_TAG_ANY: u8 = 21;
 const CL_TYPE_TAG_PUBLIC_KEY: u8 = 22;
 
+/// The maximum depth to which a [`CLType`] can be nested and still be deserialized.
+pub const CL_TYPE_RECURSION_DEPTH: u8 = 50;
+
 /// Casper types, i.e. types which can be stored and manipulated by smart contracts.
 ///
 /// Provides a description of the underlying data type of a [`CLValue`](crate::CLValue).
@@ -137,6 +145,116 @@ impl CLType {
     pub fn is_option(&self) -> bool {
         matches!(self, Self::Option(..))
     }
+
+    /// Returns an upper bound on the length of the `Vec<u8>` resulting from calling `to_bytes()` on
+    /// a [`CLValue`](crate::CLValue) of this type, i.e. the bound on
+    /// [`CLValue::serialized_length`](crate::CLValue::serialized_length).
+    ///
+    /// A serialized `CLValue` comprises a 4-byte length prefix, the serialized value and the
+    /// serialized `CLType`.  The serialized value of each type is at most:
+    ///
+    /// * `Bool`, `U8`: 1 byte
+    /// * `I32`, `U32`: 4 bytes
+    /// * `I64`, `U64`: 8 bytes
+    /// * `U128`, `U256`, `U512`: a 1-byte length prefix followed by up to 16, 32 or 64 bytes
+    ///   respectively
+    /// * `Unit`: 0 bytes
+    /// * `String`: a 4-byte length prefix followed by up to `bounds.max_string_length` bytes
+    /// * `Key`: [`Key::max_serialized_length`] bytes
+    /// * `URef`: 33 bytes
+    /// * `PublicKey`: a 1-byte tag followed by up to 33 bytes
+    /// * `Option`: a 1-byte tag followed by the inner value if `Some`
+    /// * `List`: a 4-byte length prefix followed by up to `bounds.max_list_length` elements
+    /// * `ByteArray`: exactly its length
+    /// * `Result`: a 1-byte tag followed by the larger of the `Ok` and `Err` values
+    /// * `Map`: a 4-byte length prefix followed by up to `bounds.max_map_length` keys and values
+    /// * `Tuple1`, `Tuple2`, `Tuple3`: the sum of their elements
+    ///
+    /// Returns `None` if the type contains a `String`, `List` or `Map` for which `bounds` provides
+    /// no maximum length, if it contains `Any`, which can hold a value of any size, or if the bound
+    /// overflows `usize`.
+    pub fn max_serialized_length(&self, bounds: &SizeBounds) -> Option<usize> {
+        self.max_value_serialized_length(bounds)?
+            .checked_add(U32_SERIALIZED_LENGTH + self.serialized_length())
+    }
+
+    /// Returns an upper bound on the serialized length of a value of this type, excluding the
+    /// `CLValue`'s length prefix and type.
+    fn max_value_serialized_length(&self, bounds: &SizeBounds) -> Option<usize> {
+        let length = match self {
+            CLType::Bool => BOOL_SERIALIZED_LENGTH,
+            CLType::I32 => I32_SERIALIZED_LENGTH,
+            CLType::I64 => I64_SERIALIZED_LENGTH,
+            CLType::U8 => U8_SERIALIZED_LENGTH,
+            CLType::U32 => U32_SERIALIZED_LENGTH,
+            CLType::U64 => U64_SERIALIZED_LENGTH,
+            CLType::U128 => U8_SERIALIZED_LENGTH + U128_SERIALIZED_LENGTH,
+            CLType::U256 => U8_SERIALIZED_LENGTH + U256_SERIALIZED_LENGTH,
+            CLType::U512 => U8_SERIALIZED_LENGTH + U512_SERIALIZED_LENGTH,
+            CLType::Unit => UNIT_SERIALIZED_LENGTH,
+            CLType::String => bounds
+                .max_string_length?
+                .checked_add(U32_SERIALIZED_LENGTH)?,
+            CLType::Key => Key::max_serialized_length(),
+            CLType::URef => UREF_SERIALIZED_LENGTH,
+            CLType::PublicKey => {
+                U8_SERIALIZED_LENGTH
+                    + PublicKey::ED25519_LENGTH
+                        .max(PublicKey::SECP256K1_LENGTH)
+                        .max(PublicKey::SYSTEM_LENGTH)
+            }
+            CLType::Option(cl_type) => cl_type
+                .max_value_serialized_length(bounds)?
+                .checked_add(U8_SERIALIZED_LENGTH)?,
+            CLType::List(cl_type) => bounds
+                .max_list_length?
+                .checked_mul(cl_type.max_value_serialized_length(bounds)?)?
+                .checked_add(U32_SERIALIZED_LENGTH)?,
+            CLType::ByteArray(length) => *length as usize,
+            CLType::Result { ok, err } => ok
+                .max_value_serialized_length(bounds)?
+                .max(err.max_value_serialized_length(bounds)?)
+                .checked_add(U8_SERIALIZED_LENGTH)?,
+            CLType::Map { key, value } => bounds
+                .max_map_length?
+                .checked_mul(
+                    key.max_value_serialized_length(bounds)?
+                        .checked_add(value.max_value_serialized_length(bounds)?)?,
+                )?
+                .checked_add(U32_SERIALIZED_LENGTH)?,
+            CLType::Tuple1(cl_type_array) => max_tuple_serialized_length(cl_type_array, bounds)?,
+            CLType::Tuple2(cl_type_array) => max_tuple_serialized_length(cl_type_array, bounds)?,
+            CLType::Tuple3(cl_type_array) => max_tuple_serialized_length(cl_type_array, bounds)?,
+            CLType::Any => return None,
+        };
+        Some(length)
+    }
+}
+
+/// Returns the sum of the upper bounds on the serialized lengths of a tuple's elements.
+fn max_tuple_serialized_length(
+    cl_type_array: &[Box<CLType>],
+    bounds: &SizeBounds,
+) -> Option<usize> {
+    cl_type_array.iter().try_fold(0_usize, |total, cl_type| {
+        total.checked_add(cl_type.max_value_serialized_length(bounds)?)
+    })
+}
+
+/// The maximum lengths of the constituents of a [`CLType`] which are otherwise unbounded, used to
+/// calculate an upper bound on the serialized length of a [`CLValue`](crate::CLValue) via
+/// [`CLType::max_serialized_length`].
+///
+/// Each bound applies to every constituent of the given kind, including nested ones.  A bound of
+/// `None` leaves that kind of constituent unbounded.
+#[derive(Copy, Clone, Default, PartialEq, Eq, Debug)]
+pub struct SizeBounds {
+    /// The maximum length in bytes of a `String`, once UTF-8 encoded.
+    pub max_string_length: Option<usize>,
+    /// The maximum number of elements of a `List`.
+    pub max_list_length: Option<usize>,
+    /// The maximum number of entries of a `Map`.
+    pub max_map_length: Option<usize>,
 }
 
 /// Returns the `CLType` describing a "named key" on the system, i.e. a `(String, Key)`.
@@ -198,89 +316,99 @@ impl CLType {
     }
 }
 
-#[allow(clippy::cognitive_complexity)]
 impl FromBytes for CLType {
     fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
-        let (tag, remainder) = u8::from_bytes(bytes)?;
-        match tag {
-            CL_TYPE_TAG_BOOL => Ok((CLType::Bool, remainder)),
-            CL_TYPE_TAG_I32 => Ok((CLType::I32, remainder)),
-            CL_TYPE_TAG_I64 => Ok((CLType::I64, remainder)),
-            CL_TYPE_TAG_U8 => Ok((CLType::U8, remainder)),
-            CL_TYPE_TAG_U32 => Ok((CLType::U32, remainder)),
-            CL_TYPE_TAG_U64 => Ok((CLType::U64, remainder)),
-            CL_TYPE_TAG_U128 => Ok((CLType::U128, remainder)),
-            CL_TYPE_TAG_U256 => Ok((CLType::U256, remainder)),
-            CL_TYPE_TAG_U512 => Ok((CLType::U512, remainder)),
-            CL_TYPE_TAG_UNIT => Ok((CLType::Unit, remainder)),
-            CL_TYPE_TAG_STRING => Ok((CLType::String, remainder)),
-            CL_TYPE_TAG_KEY => Ok((CLType::Key, remainder)),
-            CL_TYPE_TAG_UREF => Ok((CLType::URef, remainder)),
-            CL_TYPE_TAG_PUBLIC_KEY => Ok((CLType::PublicKey, remainder)),
-            CL_TYPE_TAG_OPTION => {
-                let (inner_type, remainder) = CLType::from_bytes(remainder)?;
-                let cl_type = CLType::Option(Box::new(inner_type));
-                Ok((cl_type, remainder))
-            }
-            CL_TYPE_TAG_LIST => {
-                let (inner_type, remainder) = CLType::from_bytes(remainder)?;
-                let cl_type = CLType::List(Box::new(inner_type));
-                Ok((cl_type, remainder))
-            }
-            CL_TYPE_TAG_BYTE_ARRAY => {
-                let (len, remainder) = u32::from_bytes(remainder)?;
-                let cl_type = CLType::ByteArray(len);
-                Ok((cl_type, remainder))
-            }
-            CL_TYPE_TAG_RESULT => {
-                let (ok_type, remainder) = CLType::from_bytes(remainder)?;
-                let (err_type, remainder) = CLType::from_bytes(remainder)?;
-                let cl_type = CLType::Result {
-                    ok: Box::new(ok_type),
-                    err: Box::new(err_type),
-                };
-                Ok((cl_type, remainder))
-            }
-            CL_TYPE_TAG_MAP => {
-                let (key_type, remainder) = CLType::from_bytes(remainder)?;
-                let (value_type, remainder) = CLType::from_bytes(remainder)?;
-                let cl_type = CLType::Map {
-                    key: Box::new(key_type),
-                    value: Box::new(value_type),
-                };
-                Ok((cl_type, remainder))
-            }
-            CL_TYPE_TAG_TUPLE1 => {
-                let (mut inner_types, remainder) = parse_cl_tuple_types(1, remainder)?;
-                // NOTE: Assumed safe as `parse_cl_tuple_types` is expected to have exactly 1
-                // element
-                let cl_type = CLType::Tuple1([inner_types.pop_front().unwrap()]);
-                Ok((cl_type, remainder))
-            }
-            CL_TYPE_TAG_TUPLE2 => {
-                let (mut inner_types, remainder) = parse_cl_tuple_types(2, remainder)?;
-                // NOTE: Assumed safe as `parse_cl_tuple_types` is expected to have exactly 2
-                // elements
-                let cl_type = CLType::Tuple2([
-                    inner_types.pop_front().unwrap(),
-                    inner_types.pop_front().unwrap(),
-                ]);
-                Ok((cl_type, remainder))
-            }
-            CL_TYPE_TAG_TUPLE3 => {
-                let (mut inner_types, remainder) = parse_cl_tuple_types(3, remainder)?;
-                // NOTE: Assumed safe as `parse_cl_tuple_types` is expected to have exactly 3
-                // elements
-                let cl_type = CLType::Tuple3([
-                    inner_types.pop_front().unwrap(),
-                    inner_types.pop_front().unwrap(),
-                    inner_types.pop_front().unwrap(),
-                ]);
-                Ok((cl_type, remainder))
-            }
-            CL_TYPE_TAG_ANY => Ok((CLType::Any, remainder)),
-            _ => Err(bytesrepr::Error::Formatting),
+        depth_limited_from_bytes(0, bytes)
+    }
+}
+
+/// Deserializes a `CLType`, failing if the type is nested more than [`CL_TYPE_RECURSION_DEPTH`]
+/// levels deep rather than recursing for as long as the input supplies compound type tags.
+#[allow(clippy::cognitive_complexity)]
+fn depth_limited_from_bytes(depth: u8, bytes: &[u8]) -> Result<(CLType, &[u8]), bytesrepr::Error> {
+    if depth >= CL_TYPE_RECURSION_DEPTH {
+        return Err(bytesrepr::Error::ExceededRecursionDepth);
+    }
+    let depth = depth + 1;
+    let (tag, remainder) = u8::from_bytes(bytes)?;
+    match tag {
+        CL_TYPE_TAG_BOOL => Ok((CLType::Bool, remainder)),
+        CL_TYPE_TAG_I32 => Ok((CLType::I32, remainder)),
+        CL_TYPE_TAG_I64 => Ok((CLType::I64, remainder)),
+        CL_TYPE_TAG_U8 => Ok((CLType::U8, remainder)),
+        CL_TYPE_TAG_U32 => Ok((CLType::U32, remainder)),
+        CL_TYPE_TAG_U64 => Ok((CLType::U64, remainder)),
+        CL_TYPE_TAG_U128 => Ok((CLType::U128, remainder)),
+        CL_TYPE_TAG_U256 => Ok((CLType::U256, remainder)),
+        CL_TYPE_TAG_U512 => Ok((CLType::U512, remainder)),
+        CL_TYPE_TAG_UNIT => Ok((CLType::Unit, remainder)),
+        CL_TYPE_TAG_STRING => Ok((CLType::String, remainder)),
+        CL_TYPE_TAG_KEY => Ok((CLType::Key, remainder)),
+        CL_TYPE_TAG_UREF => Ok((CLType::URef, remainder)),
+        CL_TYPE_TAG_PUBLIC_KEY => Ok((CLType::PublicKey, remainder)),
+        CL_TYPE_TAG_OPTION => {
+            let (inner_type, remainder) = depth_limited_from_bytes(depth, remainder)?;
+            let cl_type = CLType::Option(Box::new(inner_type));
+            Ok((cl_type, remainder))
+        }
+        CL_TYPE_TAG_LIST => {
+            let (inner_type, remainder) = depth_limited_from_bytes(depth, remainder)?;
+            let cl_type = CLType::List(Box::new(inner_type));
+            Ok((cl_type, remainder))
+        }
+        CL_TYPE_TAG_BYTE_ARRAY => {
+            let (len, remainder) = u32::from_bytes(remainder)?;
+            let cl_type = CLType::ByteArray(len);
+            Ok((cl_type, remainder))
+        }
+        CL_TYPE_TAG_RESULT => {
+            let (ok_type, remainder) = depth_limited_from_bytes(depth, remainder)?;
+            let (err_type, remainder) = depth_limited_from_bytes(depth, remainder)?;
+            let cl_type = CLType::Result {
+                ok: Box::new(ok_type),
+                err: Box::new(err_type),
+            };
+            Ok((cl_type, remainder))
         }
+        CL_TYPE_TAG_MAP => {
+            let (key_type, remainder) = depth_limited_from_bytes(depth, remainder)?;
+            let (value_type, remainder) = depth_limited_from_bytes(depth, remainder)?;
+            let cl_type = CLType::Map {
+                key: Box::new(key_type),
+                value: Box::new(value_type),
+            };
+            Ok((cl_type, remainder))
+        }
+        CL_TYPE_TAG_TUPLE1 => {
+            let (mut inner_types, remainder) = parse_cl_tuple_types(depth, 1, remainder)?;
+            // NOTE: Assumed safe as `parse_cl_tuple_types` is expected to have exactly 1
+            // element
+            let cl_type = CLType::Tuple1([inner_types.pop_front().unwrap()]);
+            Ok((cl_type, remainder))
+        }
+        CL_TYPE_TAG_TUPLE2 => {
+            let (mut inner_types, remainder) = parse_cl_tuple_types(depth, 2, remainder)?;
+            // NOTE: Assumed safe as `parse_cl_tuple_types` is expected to have exactly 2
+            // elements
+            let cl_type = CLType::Tuple2([
+                inner_types.pop_front().unwrap(),
+                inner_types.pop_front().unwrap(),
+            ]);
+            Ok((cl_type, remainder))
+        }
+        CL_TYPE_TAG_TUPLE3 => {
+            let (mut inner_types, remainder) = parse_cl_tuple_types(depth, 3, remainder)?;
+            // NOTE: Assumed safe as `parse_cl_tuple_types` is expected to have exactly 3
+            // elements
+            let cl_type = CLType::Tuple3([
+                inner_types.pop_front().unwrap(),
+                inner_types.pop_front().unwrap(),
+                inner_types.pop_front().unwrap(),
+            ]);
+            Ok((cl_type, remainder))
+        }
+        CL_TYPE_TAG_ANY => Ok((CLType::Any, remainder)),
+        tag => Err(bytesrepr::Error::invalid_tag::<CLType>(tag)),
     }
 }
 
@@ -297,12 +425,13 @@ fn serialize_cl_tuple_type<'a, T: IntoIterator<Item = &'a Box<CLType>>>(
 }
 
 fn parse_cl_tuple_types(
+    depth: u8,
     count: usize,
     mut bytes: &[u8],
 ) -> Result<(VecDeque<Box<CLType>>, &[u8]), bytesrepr::Error> {
     let mut cl_types = VecDeque::with_capacity(count);
     for _ in 0..count {
-        let (cl_type, remainder) = CLType::from_bytes(bytes)?;
+        let (cl_type, remainder) = depth_limited_from_bytes(depth, bytes)?;
         cl_types.push_back(Box::new(cl_type));
         bytes = remainder;
     }
@@ -492,7 +621,7 @@ mod tests {
     use super::*;
     use crate::{
         bytesrepr::{FromBytes, ToBytes},
-        AccessRights, CLValue,
+        AccessRights, CLValue, SecretKey,
     };
 
     fn round_trip<T: CLTyped + FromBytes + ToBytes + PartialEq + Debug + Clone>(value: &T) {
@@ -713,4 +842,160 @@ mod tests {
         let any = Any("Any test".to_string());
         round_trip(&any);
     }
+
+    #[test]
+    fn should_deserialize_nested_cl_type_up_to_recursion_limit() {
+        let mut cl_type = CLType::Unit;
+        for _ in 1..CL_TYPE_RECURSION_DEPTH {
+            cl_type = CLType::Option(Box::new(cl_type));
+        }
+        let mut bytes = Vec::new();
+        cl_type.append_bytes(&mut bytes).unwrap();
+        let parsed: CLType = bytesrepr::deserialize(bytes).unwrap();
+        assert_eq!(parsed, cl_type);
+
+        let too_deep = CLType::List(Box::new(cl_type));
+        let mut bytes = Vec::new();
+        too_deep.append_bytes(&mut bytes).unwrap();
+        let result: Result<CLType, _> = bytesrepr::deserialize(bytes);
+        assert_eq!(
+            result.unwrap_err(),
+            bytesrepr::Error::ExceededRecursionDepth
+        );
+    }
+
+    #[test]
+    fn should_fail_to_deserialize_deeply_nested_cl_type_without_overflowing_stack() {
+        let mut malicious = vec![CL_TYPE_TAG_TUPLE2; 1_000_000];
+        malicious.push(CL_TYPE_TAG_UNIT);
+        let result = CLType::from_bytes(&malicious);
+        assert_eq!(
+            result.unwrap_err(),
+            bytesrepr::Error::ExceededRecursionDepth
+        );
+    }
+
+    const BOUNDS: SizeBounds = SizeBounds {
+        max_string_length: Some(10),
+        max_list_length: Some(3),
+        max_map_length: Some(2),
+    };
+
+    /// Asserts that the bound for the type of `value` is no less than the serialized length of
+    /// `value` as a `CLValue`, and exceeds it by no more than `slack` bytes.
+    fn assert_bound<T: CLTyped + ToBytes>(value: T, slack: usize) {
+        let cl_value = CLValue::from_t(value).unwrap();
+        let actual = cl_value.serialized_length();
+        assert_eq!(actual, cl_value.to_bytes().unwrap().len());
+        let bound = cl_value
+            .cl_type()
+            .max_serialized_length(&BOUNDS)
+            .unwrap_or_else(|| panic!("{:?} should be bounded", cl_value.cl_type()));
+        assert!(
+            bound >= actual && bound - actual <= slack,
+            "bound of {} for {:?} should be within {} bytes above actual length of {}",
+            bound,
+            cl_value.cl_type(),
+            slack,
+            actual
+        );
+    }
+
+    fn string_of_max_length() -> String {
+        "0123456789".to_string()
+    }
+
+    #[test]
+    fn should_bound_serialized_length_of_fixed_size_types() {
+        assert_bound(true, 0);
+        assert_bound(i32::MIN, 0);
+        assert_bound(i64::MIN, 0);
+        assert_bound(u8::MAX, 0);
+        assert_bound(u32::MAX, 0);
+        assert_bound(u64::MAX, 0);
+        assert_bound((), 0);
+        assert_bound(URef::new([1; 32], AccessRights::READ_ADD_WRITE), 0);
+        assert_bound([7_u8; 32], 0);
+    }
+
+    #[test]
+    fn should_bound_serialized_length_of_variable_size_types() {
+        // Large unsigned integers are serialized without trailing zero bytes.
+        assert_bound(U128::MAX, 0);
+        assert_bound(U256::MAX, 0);
+        assert_bound(U512::MAX, 0);
+        assert_bound(U512::one(), U512_SERIALIZED_LENGTH - 1);
+
+        assert_bound(string_of_max_length(), 0);
+        assert_bound(String::new(), 10);
+
+        assert_bound(Key::from(URef::new([1; 32], AccessRights::READ)), 0);
+        assert_bound(Key::Hash([2; 32]), 1);
+
+        let secp256k1_key = PublicKey::from(
+            &SecretKey::secp256k1_from_bytes([8; SecretKey::SECP256K1_LENGTH]).unwrap(),
+        );
+        assert_bound(secp256k1_key, 0);
+        let ed25519_key = PublicKey::from(
+            &SecretKey::ed25519_from_bytes([7; SecretKey::ED25519_LENGTH]).unwrap(),
+        );
+        assert_bound(ed25519_key, 1);
+    }
+
+    #[test]
+    fn should_bound_serialized_length_of_compound_types() {
+        assert_bound(Some(u64::MAX), 0);
+        assert_bound(Option::<u64>::None, U64_SERIALIZED_LENGTH);
+
+        assert_bound(vec![string_of_max_length(); 3], 0);
+        assert_bound(vec![1_u64], 2 * U64_SERIALIZED_LENGTH);
+
+        assert_bound(Result::<u64, String>::Err(string_of_max_length()), 0);
+        assert_bound(Result::<u64, String>::Ok(1), 14 - U64_SERIALIZED_LENGTH);
+
+        let mut map = BTreeMap::new();
+        map.insert(string_of_max_length(), U512::MAX);
+        map.insert("9876543210".to_string(), U512::MAX);
+        assert_bound(map, 0);
+
+        assert_bound((u64::MAX,), 0);
+        assert_bound((u8::MAX, string_of_max_length()), 0);
+        assert_bound((true, U512::MAX, Some(vec![string_of_max_length(); 3])), 0);
+    }
+
+    #[test]
+    fn should_not_bound_serialized_length_of_unbounded_types() {
+        let unbounded = SizeBounds::default();
+        assert!(CLType::String.max_serialized_length(&unbounded).is_none());
+        assert!(CLType::List(Box::new(CLType::U8))
+            .max_serialized_length(&unbounded)
+            .is_none());
+        let map = CLType::Map {
+            key: Box::new(CLType::U8),
+            value: Box::new(CLType::U8),
+        };
+        assert!(map.max_serialized_length(&unbounded).is_none());
+        assert!(CLType::Any.max_serialized_length(&BOUNDS).is_none());
+
+        // Only the unbounded constituent prevents a bound.
+        let bounds = SizeBounds {
+            max_string_length: None,
+            ..BOUNDS
+        };
+        assert!(CLType::List(Box::new(CLType::U8))
+            .max_serialized_length(&bounds)
+            .is_some());
+        assert!(CLType::List(Box::new(CLType::String))
+            .max_serialized_length(&bounds)
+            .is_none());
+
+        // A bound which overflows is treated as unbounded.
+        let bounds = SizeBounds {
+            max_list_length: Some(usize::MAX),
+            ..BOUNDS
+        };
+        assert!(CLType::List(Box::new(CLType::U64))
+            .max_serialized_length(&bounds)
+            .is_none());
+    }
 }
@@ -1,13 +1,16 @@
 // TODO - remove once schemars stops causing warning.
 #![allow(clippy::field_reassign_with_default)]
 
-use alloc::{string::String, vec::Vec};
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
 use core::fmt;
 
 use datasize::DataSize;
 #[cfg(feature = "std")]
 use schemars::{gen::SchemaGenerator, schema::Schema, JsonSchema};
-use serde::{de::Error as SerdeError, Deserialize, Deserializer, Serialize, Serializer};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::Value;
 #[cfg(feature = "std")]
 use thiserror::Error;
@@ -131,6 +134,28 @@ impl CLValue {
     pub fn serialized_length(&self) -> usize {
         self.cl_type.serialized_length() + U32_SERIALIZED_LENGTH + self.bytes.len()
     }
+
+    /// Returns a JSON representation of the underlying value from which `self` can be exactly
+    /// reconstructed via [`CLValue::from_json_lossless`].
+    ///
+    /// Values which have no unambiguous JSON mapping degrade to an object of the form
+    /// `{"raw_bytes": "<HEX STRING>"}` holding the serialized underlying value.
+    pub fn to_json_lossless(&self) -> Value {
+        jsonrepr::cl_value_to_json_lossless(self)
+    }
+
+    /// Constructs a `CLValue` of the given type from a JSON value as produced by
+    /// [`CLValue::to_json_lossless`].
+    ///
+    /// Returns `None` if `json_value` doesn't represent a value of type `cl_type`.
+    pub fn from_json_lossless(cl_type: CLType, json_value: &Value) -> Option<CLValue> {
+        jsonrepr::cl_value_from_json(&cl_type, json_value)
+    }
+
+    /// Returns the output of [`CLValue::to_json_lossless`] as a compact JSON string.
+    pub fn parsed_to_string(&self) -> String {
+        self.to_json_lossless().to_string()
+    }
 }
 
 impl ToBytes for CLValue {
@@ -183,7 +208,8 @@ impl JsonSchema for CLValue {
 #[cfg_attr(feature = "std", schemars(rename = "CLValue"))]
 struct CLValueJson {
     cl_type: CLType,
-    bytes: String,
+    #[cfg_attr(feature = "std", schemars(with = "String"))]
+    bytes: Bytes,
     parsed: Option<Value>,
 }
 
@@ -192,7 +218,7 @@ impl Serialize for CLValue {
         if serializer.is_human_readable() {
             CLValueJson {
                 cl_type: self.cl_type.clone(),
-                bytes: base16::encode_lower(&self.bytes),
+                bytes: self.bytes.clone(),
                 parsed: jsonrepr::cl_value_to_json(self),
             }
             .serialize(serializer)
@@ -206,17 +232,12 @@ impl<'de> Deserialize<'de> for CLValue {
     fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
         let (cl_type, bytes) = if deserializer.is_human_readable() {
             let json = CLValueJson::deserialize(deserializer)?;
-            (
-                json.cl_type.clone(),
-                base16::decode(&json.bytes).map_err(D::Error::custom)?,
-            )
+            (json.cl_type, json.bytes)
         } else {
-            <(CLType, Vec<u8>)>::deserialize(deserializer)?
+            let (cl_type, bytes) = <(CLType, Vec<u8>)>::deserialize(deserializer)?;
+            (cl_type, bytes.into())
         };
-        Ok(CLValue {
-            cl_type,
-            bytes: bytes.into(),
-        })
+        Ok(CLValue { cl_type, bytes })
     }
 }
 
@@ -259,6 +280,30 @@ mod tests {
         assert_eq!(cl_value, decoded);
     }
 
+    #[test]
+    fn should_deserialize_legacy_byte_array_from_json() {
+        let cl_value = CLValue::from_t(42_i32).unwrap();
+        let json_string = r#"{"cl_type":"I32","bytes":[42,0,0,0],"parsed":42}"#;
+        let decoded: CLValue = serde_json::from_str(json_string).unwrap();
+        assert_eq!(cl_value, decoded);
+    }
+
+    #[test]
+    fn lossless_json_roundtrip() {
+        let cl_value = CLValue::from_t((1_u8, Some(String::from("a")))).unwrap();
+        assert_eq!(cl_value.parsed_to_string(), r#"[1,"a"]"#);
+        let decoded =
+            CLValue::from_json_lossless(cl_value.cl_type().clone(), &cl_value.to_json_lossless())
+                .unwrap();
+        assert_eq!(cl_value, decoded);
+
+        let cl_value = CLValue::from_components(CLType::Any, vec![255]);
+        assert_eq!(cl_value.parsed_to_string(), r#"{"raw_bytes":"ff"}"#);
+        let decoded =
+            CLValue::from_json_lossless(CLType::Any, &cl_value.to_json_lossless()).unwrap();
+        assert_eq!(cl_value, decoded);
+    }
+
     fn check_to_json<T: CLTyped + ToBytes + FromBytes>(value: T, expected: &str) {
         let cl_value = CLValue::from_t(value).unwrap();
         let cl_value_as_json = serde_json::to_string(&cl_value).unwrap();
@@ -1,13 +1,24 @@
-use alloc::{string::String, vec, vec::Vec};
+use alloc::{boxed::Box, string::String, vec, vec::Vec};
+use core::convert::TryFrom;
 
-use serde::Serialize;
+use serde::{de::DeserializeOwned, Serialize};
 use serde_json::{json, Value};
 
 use crate::{
-    bytesrepr::{self, FromBytes, OPTION_NONE_TAG, OPTION_SOME_TAG, RESULT_ERR_TAG, RESULT_OK_TAG},
+    bytesrepr::{
+        self, FromBytes, ToBytes, OPTION_NONE_TAG, OPTION_SOME_TAG, RESULT_ERR_TAG, RESULT_OK_TAG,
+    },
     CLType, CLValue, Key, PublicKey, URef, U128, U256, U512,
 };
 
+/// The name of the sole field of the JSON object used to represent a `CLValue` which can't be
+/// represented losslessly in any other way.
+const RAW_BYTES_TAG: &str = "raw_bytes";
+const RESULT_OK_NAME: &str = "Ok";
+const RESULT_ERR_NAME: &str = "Err";
+const MAP_KEY_NAME: &str = "key";
+const MAP_VALUE_NAME: &str = "value";
+
 /// Returns a best-effort attempt to convert the `CLValue` into a meaningful JSON value.
 pub fn cl_value_to_json(cl_value: &CLValue) -> Option<Value> {
     to_json(cl_value.cl_type(), cl_value.inner_bytes()).and_then(|(json_value, remainder)| {
@@ -19,6 +30,34 @@ pub fn cl_value_to_json(cl_value: &CLValue) -> Option<Value> {
     })
 }
 
+/// Converts the `CLValue` into a JSON value from which it can be exactly reconstructed via
+/// [`cl_value_from_json`].
+///
+/// Where the best-effort conversion fails, or where its output is ambiguous (e.g. `Some(())` and
+/// `None` both map to `null`), the value is represented as a JSON object with a single field
+/// `"raw_bytes"` holding the hex-encoded serialized value.
+pub fn cl_value_to_json_lossless(cl_value: &CLValue) -> Value {
+    cl_value_to_json(cl_value)
+        .filter(|json_value| {
+            cl_value_from_json(cl_value.cl_type(), json_value).as_ref() == Some(cl_value)
+        })
+        .unwrap_or_else(|| json!({ RAW_BYTES_TAG: hex::encode(cl_value.inner_bytes()) }))
+}
+
+/// Attempts to reconstruct a `CLValue` of the given type from its JSON representation as produced
+/// by [`cl_value_to_json_lossless`].
+pub fn cl_value_from_json(cl_type: &CLType, json_value: &Value) -> Option<CLValue> {
+    let bytes = match raw_bytes_from_json(json_value) {
+        Some(bytes) => bytes,
+        None => {
+            let mut bytes = Vec::new();
+            from_json(cl_type, json_value, &mut bytes)?;
+            bytes
+        }
+    };
+    Some(CLValue::from_components(cl_type.clone(), bytes))
+}
+
 fn to_json<'a>(cl_type: &CLType, bytes: &'a [u8]) -> Option<(Value, &'a [u8])> {
     match cl_type {
         CLType::Bool => simple_type_to_json::<bool>(bytes),
@@ -62,22 +101,22 @@ fn to_json<'a>(cl_type: &CLType, bytes: &'a [u8]) -> Option<(Value, &'a [u8])> {
             match variant {
                 RESULT_ERR_TAG => {
                     let (value, remainder) = to_json(err, remainder)?;
-                    Some((json!({ "Err": value }), remainder))
+                    Some((json!({ RESULT_ERR_NAME: value }), remainder))
                 }
                 RESULT_OK_TAG => {
                     let (value, remainder) = to_json(ok, remainder)?;
-                    Some((json!({ "Ok": value }), remainder))
+                    Some((json!({ RESULT_OK_NAME: value }), remainder))
                 }
                 _ => None,
             }
         }
         CLType::Map { key, value } => {
-            let (num_keys, mut stream) = u32::from_bytes(bytes).unwrap();
+            let (num_keys, mut stream) = u32::from_bytes(bytes).ok()?;
             let mut result: Vec<Value> = Vec::new();
             for _ in 0..num_keys {
                 let (k, remainder) = to_json(key, stream)?;
                 let (v, remainder) = to_json(value, remainder)?;
-                result.push(json!({"key": k, "value": v}));
+                result.push(json!({ MAP_KEY_NAME: k, MAP_VALUE_NAME: v }));
                 stream = remainder;
             }
             Some((json!(result), stream))
@@ -106,10 +145,118 @@ fn simple_type_to_json<T: FromBytes + Serialize>(bytes: &[u8]) -> Option<(Value,
     Some((json!(value), remainder))
 }
 
+fn raw_bytes_from_json(json_value: &Value) -> Option<Vec<u8>> {
+    let object = json_value.as_object()?;
+    if object.len() != 1 {
+        return None;
+    }
+    hex::decode(object.get(RAW_BYTES_TAG)?.as_str()?).ok()
+}
+
+fn from_json(cl_type: &CLType, json_value: &Value, bytes: &mut Vec<u8>) -> Option<()> {
+    match cl_type {
+        CLType::Bool => simple_type_from_json::<bool>(json_value, bytes),
+        CLType::I32 => simple_type_from_json::<i32>(json_value, bytes),
+        CLType::I64 => simple_type_from_json::<i64>(json_value, bytes),
+        CLType::U8 => simple_type_from_json::<u8>(json_value, bytes),
+        CLType::U32 => simple_type_from_json::<u32>(json_value, bytes),
+        CLType::U64 => simple_type_from_json::<u64>(json_value, bytes),
+        CLType::U128 => simple_type_from_json::<U128>(json_value, bytes),
+        CLType::U256 => simple_type_from_json::<U256>(json_value, bytes),
+        CLType::U512 => simple_type_from_json::<U512>(json_value, bytes),
+        CLType::Unit => simple_type_from_json::<()>(json_value, bytes),
+        CLType::String => simple_type_from_json::<String>(json_value, bytes),
+        CLType::Key => simple_type_from_json::<Key>(json_value, bytes),
+        CLType::URef => simple_type_from_json::<URef>(json_value, bytes),
+        CLType::PublicKey => simple_type_from_json::<PublicKey>(json_value, bytes),
+        CLType::Option(inner_cl_type) => {
+            if json_value.is_null() {
+                bytes.push(OPTION_NONE_TAG);
+                Some(())
+            } else {
+                bytes.push(OPTION_SOME_TAG);
+                from_json(inner_cl_type, json_value, bytes)
+            }
+        }
+        CLType::List(inner_cl_type) => {
+            let items = json_value.as_array()?;
+            let count = u32::try_from(items.len()).ok()?;
+            bytes.extend(count.into_bytes().ok()?);
+            for item in items {
+                from_json(inner_cl_type, item, bytes)?;
+            }
+            Some(())
+        }
+        CLType::ByteArray(length) => {
+            let decoded = hex::decode(json_value.as_str()?).ok()?;
+            if decoded.len() != *length as usize {
+                return None;
+            }
+            bytes.extend(decoded);
+            Some(())
+        }
+        CLType::Result { ok, err } => {
+            let object = json_value.as_object()?;
+            if object.len() != 1 {
+                return None;
+            }
+            if let Some(value) = object.get(RESULT_OK_NAME) {
+                bytes.push(RESULT_OK_TAG);
+                from_json(ok, value, bytes)
+            } else {
+                bytes.push(RESULT_ERR_TAG);
+                from_json(err, object.get(RESULT_ERR_NAME)?, bytes)
+            }
+        }
+        CLType::Map { key, value } => {
+            let entries = json_value.as_array()?;
+            let count = u32::try_from(entries.len()).ok()?;
+            bytes.extend(count.into_bytes().ok()?);
+            for entry in entries {
+                let entry = entry.as_object()?;
+                if entry.len() != 2 {
+                    return None;
+                }
+                from_json(key, entry.get(MAP_KEY_NAME)?, bytes)?;
+                from_json(value, entry.get(MAP_VALUE_NAME)?, bytes)?;
+            }
+            Some(())
+        }
+        CLType::Tuple1(arr) => tuple_from_json(arr, json_value, bytes),
+        CLType::Tuple2(arr) => tuple_from_json(arr, json_value, bytes),
+        CLType::Tuple3(arr) => tuple_from_json(arr, json_value, bytes),
+        CLType::Any => None,
+    }
+}
+
+fn simple_type_from_json<T: DeserializeOwned + ToBytes>(
+    json_value: &Value,
+    bytes: &mut Vec<u8>,
+) -> Option<()> {
+    let value: T = serde_json::from_value(json_value.clone()).ok()?;
+    bytes.extend(value.into_bytes().ok()?);
+    Some(())
+}
+
+fn tuple_from_json(
+    cl_types: &[Box<CLType>],
+    json_value: &Value,
+    bytes: &mut Vec<u8>,
+) -> Option<()> {
+    let items = json_value.as_array()?;
+    if items.len() != cl_types.len() {
+        return None;
+    }
+    for (cl_type, item) in cl_types.iter().zip(items) {
+        from_json(cl_type, item, bytes)?;
+    }
+    Some(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{bytesrepr::ToBytes, AsymmetricType, CLTyped, SecretKey};
+    use crate::{AccessRights, AsymmetricType, CLTyped, SecretKey};
     use alloc::collections::BTreeMap;
 
     fn test_value<T: ToBytes + Serialize + Clone + CLTyped>(value: T) {
@@ -228,4 +375,92 @@ mod tests {
         test_value((v1.clone(), v2.clone()));
         test_value((v1, v2, v3));
     }
+
+    fn check_lossless_round_trip<T: ToBytes + CLTyped>(value: T) {
+        let cl_value = CLValue::from_t(value).unwrap();
+        let cl_value_as_json = cl_value_to_json_lossless(&cl_value);
+        assert_eq!(
+            Some(&cl_value_as_json),
+            cl_value_to_json(&cl_value).as_ref(),
+            "should not need to fall back to raw bytes for {:?}",
+            cl_value.cl_type()
+        );
+        let decoded = cl_value_from_json(cl_value.cl_type(), &cl_value_as_json).unwrap();
+        assert_eq!(decoded, cl_value);
+    }
+
+    #[test]
+    fn simple_types_should_round_trip_via_json() {
+        check_lossless_round_trip(true);
+        check_lossless_round_trip(i32::min_value());
+        check_lossless_round_trip(i64::max_value());
+        check_lossless_round_trip(u8::max_value());
+        check_lossless_round_trip(u32::max_value());
+        check_lossless_round_trip(u64::max_value());
+        check_lossless_round_trip(U128::max_value());
+        check_lossless_round_trip(U256::max_value());
+        check_lossless_round_trip(U512::max_value());
+        check_lossless_round_trip(());
+        check_lossless_round_trip(String::from("Hello"));
+        check_lossless_round_trip(Key::Hash([7; 32]));
+        check_lossless_round_trip(URef::new([8; 32], AccessRights::READ_ADD_WRITE));
+        check_lossless_round_trip(URef::new([9; 32], AccessRights::NONE));
+        check_lossless_round_trip(PublicKey::from(
+            &SecretKey::ed25519_from_bytes([3; SecretKey::ED25519_LENGTH]).unwrap(),
+        ));
+    }
+
+    #[test]
+    fn compound_types_should_round_trip_via_json() {
+        check_lossless_round_trip(Some(vec![1_u64, 2, 3]));
+        check_lossless_round_trip(Option::<String>::None);
+        check_lossless_round_trip(vec![vec![true], vec![], vec![false, true]]);
+        check_lossless_round_trip([1_u8; 32]);
+        check_lossless_round_trip(Result::<u8, String>::Ok(1));
+        check_lossless_round_trip(Result::<u8, String>::Err(String::from("Upsss")));
+        check_lossless_round_trip((1_i32,));
+        check_lossless_round_trip((1_i32, String::from("two")));
+        check_lossless_round_trip((1_i32, String::from("two"), [3_u8; 3]));
+
+        let mut map = BTreeMap::new();
+        map.insert(
+            String::from("a"),
+            Some(URef::new([1; 32], AccessRights::READ)),
+        );
+        map.insert(String::from("b"), None);
+        check_lossless_round_trip(map);
+    }
+
+    #[test]
+    fn ambiguous_value_should_fall_back_to_raw_bytes() {
+        // Both `Some(())` and `None` are represented as `null` by the best-effort conversion.
+        let cl_value = CLValue::from_t(Some(())).unwrap();
+        assert_eq!(cl_value_to_json(&cl_value), Some(Value::Null));
+
+        let cl_value_as_json = cl_value_to_json_lossless(&cl_value);
+        assert_eq!(cl_value_as_json, json!({ "raw_bytes": "01" }));
+        let decoded = cl_value_from_json(cl_value.cl_type(), &cl_value_as_json).unwrap();
+        assert_eq!(decoded, cl_value);
+    }
+
+    #[test]
+    fn unsupported_type_should_fall_back_to_raw_bytes() {
+        let cl_value = CLValue::from_components(CLType::Any, vec![1, 2, 3]);
+        assert!(cl_value_to_json(&cl_value).is_none());
+
+        let cl_value_as_json = cl_value_to_json_lossless(&cl_value);
+        assert_eq!(cl_value_as_json, json!({ "raw_bytes": "010203" }));
+        let decoded = cl_value_from_json(&CLType::Any, &cl_value_as_json).unwrap();
+        assert_eq!(decoded, cl_value);
+    }
+
+    #[test]
+    fn mismatched_json_should_not_convert() {
+        assert!(cl_value_from_json(&CLType::U8, &json!(256)).is_none());
+        assert!(cl_value_from_json(&CLType::ByteArray(2), &json!("010203")).is_none());
+        assert!(
+            cl_value_from_json(&CLType::Tuple1([Box::new(CLType::Bool)]), &json!([])).is_none()
+        );
+        assert!(cl_value_from_json(&CLType::Any, &json!(1)).is_none());
+    }
 }
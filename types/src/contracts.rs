@@ -1102,7 +1102,7 @@ impl FromBytes for EntryPointType {
         match value {
             0 => Ok((EntryPointType::Session, bytes)),
             1 => Ok((EntryPointType::Contract, bytes)),
-            _ => Err(bytesrepr::Error::Formatting),
+            _ => Err(bytesrepr::Error::invalid_tag::<Self>(value)),
         }
     }
 }
@@ -1309,7 +1309,7 @@ impl FromBytes for EntryPointAccess {
                 let result = EntryPointAccess::Groups(groups);
                 Ok((result, bytes))
             }
-            _ => Err(bytesrepr::Error::Formatting),
+            tag => Err(bytesrepr::Error::invalid_tag::<Self>(tag)),
         }
     }
 }
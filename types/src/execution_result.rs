@@ -82,21 +82,18 @@ static EXECUTION_RESULT: Lazy<ExecutionResult> = Lazy::new(|| {
 
     let transforms = vec![
         TransformEntry {
-            key: "uref-2c4a11c062a8a337bfc97e27fd66291caeb2c65865dcb5d3ef3759c4c97efecb-007"
+            key: "deploy-af684263911154d26fa05be9963171802801a0b6aff8f199b7391eacb8edc9e1"
                 .to_string(),
-            transform: Transform::AddUInt64(8u64),
+            transform: Transform::Identity,
         },
         TransformEntry {
-            key: "deploy-af684263911154d26fa05be9963171802801a0b6aff8f199b7391eacb8edc9e1"
+            key: "uref-2c4a11c062a8a337bfc97e27fd66291caeb2c65865dcb5d3ef3759c4c97efecb-007"
                 .to_string(),
-            transform: Transform::Identity,
+            transform: Transform::AddUInt64(8u64),
         },
     ];
 
-    let effect = ExecutionEffect {
-        operations,
-        transforms,
-    };
+    let effect = ExecutionEffect::new(operations, transforms);
 
     let transfers = vec![
         TransferAddr::new([89; KEY_HASH_LENGTH]),
@@ -169,10 +166,7 @@ impl Distribution<ExecutionResult> for Standard {
             });
         }
 
-        let effect = ExecutionEffect {
-            operations,
-            transforms,
-        };
+        let effect = ExecutionEffect::new(operations, transforms);
 
         let transfer_count = rng.gen_range(0..6);
         let mut transfers = vec![];
@@ -282,12 +276,16 @@ impl FromBytes for ExecutionResult {
                 };
                 Ok((execution_result, remainder))
             }
-            _ => Err(bytesrepr::Error::Formatting),
+            tag => Err(bytesrepr::Error::invalid_tag::<Self>(tag)),
         }
     }
 }
 
 /// The effect of executing a single deploy.
+///
+/// The operations and transforms of every effect produced by the node are in canonical order,
+/// i.e. each sorted by key, with entries sharing a key kept in the order they were made.  Keys are
+/// compared as their formatted strings, byte by byte.
 #[derive(Clone, Eq, PartialEq, Serialize, Deserialize, Default, Debug)]
 #[cfg_attr(feature = "std", derive(JsonSchema))]
 #[serde(deny_unknown_fields)]
@@ -298,6 +296,37 @@ pub struct ExecutionEffect {
     pub transforms: Vec<TransformEntry>,
 }
 
+impl ExecutionEffect {
+    /// Returns a new effect of the given operations and transforms, sorted into canonical order.
+    pub fn new(operations: Vec<Operation>, transforms: Vec<TransformEntry>) -> Self {
+        let mut effect = ExecutionEffect {
+            operations,
+            transforms,
+        };
+        effect.canonicalize();
+        effect
+    }
+
+    /// Sorts the operations and transforms into canonical order.
+    pub fn canonicalize(&mut self) {
+        self.operations
+            .sort_by(|lhs, rhs| lhs.key.as_bytes().cmp(rhs.key.as_bytes()));
+        self.transforms
+            .sort_by(|lhs, rhs| lhs.key.as_bytes().cmp(rhs.key.as_bytes()));
+    }
+
+    /// Returns whether the operations and transforms are in canonical order.
+    pub fn is_canonical(&self) -> bool {
+        self.operations
+            .windows(2)
+            .all(|pair| pair[0].key.as_bytes() <= pair[1].key.as_bytes())
+            && self
+                .transforms
+                .windows(2)
+                .all(|pair| pair[0].key.as_bytes() <= pair[1].key.as_bytes())
+    }
+}
+
 impl ToBytes for ExecutionEffect {
     fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
         let mut buffer = bytesrepr::allocate_buffer(self)?;
@@ -394,7 +423,7 @@ impl FromBytes for OpKind {
             OP_WRITE_TAG => Ok((OpKind::Write, remainder)),
             OP_ADD_TAG => Ok((OpKind::Add, remainder)),
             OP_NOOP_TAG => Ok((OpKind::NoOp, remainder)),
-            _ => Err(bytesrepr::Error::Formatting),
+            tag => Err(bytesrepr::Error::invalid_tag::<Self>(tag)),
         }
     }
 }
@@ -622,7 +651,7 @@ impl FromBytes for Transform {
                 let (value, remainder) = String::from_bytes(remainder)?;
                 Ok((Transform::Failure(value), remainder))
             }
-            _ => Err(bytesrepr::Error::Formatting),
+            tag => Err(bytesrepr::Error::invalid_tag::<Self>(tag)),
         }
     }
 }
@@ -683,4 +712,56 @@ mod tests {
         let execution_result: ExecutionResult = rng.gen();
         bytesrepr::test_serialization_roundtrip(&execution_result);
     }
+
+    #[test]
+    fn should_sort_effect_by_key() {
+        let transform = |key: &str, value: u64| TransformEntry {
+            key: key.to_string(),
+            transform: Transform::AddUInt64(value),
+        };
+        let operation = |key: &str| Operation {
+            key: key.to_string(),
+            kind: OpKind::Read,
+        };
+        // Keys of different types interleaved, with a repeated key.
+        let effect = ExecutionEffect::new(
+            vec![operation("uref-01-007"), operation("account-hash-02")],
+            vec![
+                transform("uref-01-007", 1),
+                transform("hash-03", 2),
+                transform("account-hash-02", 3),
+                transform("deploy-04", 4),
+                transform("hash-03", 5),
+                transform("balance-05", 6),
+            ],
+        );
+
+        assert!(effect.is_canonical());
+        let keys_and_values: Vec<_> = effect
+            .transforms
+            .iter()
+            .map(|entry| match entry.transform {
+                Transform::AddUInt64(value) => (entry.key.as_str(), value),
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(
+            keys_and_values,
+            vec![
+                ("account-hash-02", 3),
+                ("balance-05", 6),
+                ("deploy-04", 4),
+                ("hash-03", 2),
+                ("hash-03", 5),
+                ("uref-01-007", 1),
+            ]
+        );
+        assert_eq!(effect.operations[0].key, "account-hash-02");
+
+        let mut unsorted = effect.clone();
+        unsorted.transforms.reverse();
+        assert!(!unsorted.is_canonical());
+        unsorted.canonicalize();
+        assert!(unsorted.is_canonical());
+    }
 }
@@ -1,11 +1,8 @@
-use alloc::{
-    format,
-    string::{String, ToString},
-    vec::Vec,
-};
+use alloc::{format, string::String, vec::Vec};
 use core::{
     convert::TryFrom,
     fmt::{self, Debug, Display, Formatter},
+    num::ParseIntError,
     str::FromStr,
 };
 
@@ -22,13 +19,14 @@ use rand::{
 use serde::{de::Error as SerdeError, Deserialize, Deserializer, Serialize, Serializer};
 
 use crate::{
-    account::{self, AccountHash, AccountHashBytes},
+    account::{AccountHash, AccountHashBytes, ACCOUNT_HASH_FORMATTED_STRING_PREFIX},
     bytesrepr::{self, Error, FromBytes, ToBytes, U64_SERIALIZED_LENGTH},
     contract_wasm::ContractWasmHash,
     contracts::{ContractHash, ContractPackageHash},
-    uref::{self, URef, URefAddr, UREF_SERIALIZED_LENGTH},
-    DeployHash, EraId, Tagged, TransferAddr, TransferFromStrError, DEPLOY_HASH_LENGTH,
-    TRANSFER_ADDR_LENGTH, UREF_ADDR_LENGTH,
+    transfer::TRANSFER_ADDR_FORMATTED_STRING_PREFIX,
+    uref::{URef, URefAddr, UREF_FORMATTED_STRING_PREFIX, UREF_SERIALIZED_LENGTH},
+    DeployHash, EraId, Tagged, TransferAddr, DEPLOY_HASH_LENGTH, TRANSFER_ADDR_LENGTH,
+    UREF_ADDR_LENGTH,
 };
 
 const HASH_PREFIX: &str = "hash-";
@@ -39,6 +37,20 @@ const BID_PREFIX: &str = "bid-";
 const WITHDRAW_PREFIX: &str = "withdraw-";
 const DICTIONARY_PREFIX: &str = "dictionary-";
 
+/// The prefixes of all formatted `Key` variants.
+const KEY_PREFIXES: &[&str] = &[
+    ACCOUNT_HASH_FORMATTED_STRING_PREFIX,
+    HASH_PREFIX,
+    UREF_FORMATTED_STRING_PREFIX,
+    TRANSFER_ADDR_FORMATTED_STRING_PREFIX,
+    DEPLOY_INFO_PREFIX,
+    ERA_INFO_PREFIX,
+    BALANCE_PREFIX,
+    BID_PREFIX,
+    WITHDRAW_PREFIX,
+    DICTIONARY_PREFIX,
+];
+
 /// The number of bytes in a Blake2b hash
 pub const BLAKE2B_DIGEST_LENGTH: usize = 32;
 /// The number of bytes in a [`Key::Hash`].
@@ -112,59 +124,105 @@ pub enum Key {
     Dictionary(DictionaryAddr),
 }
 
-#[derive(Debug)]
+/// Error while parsing a [`Key`] or [`URef`] from a formatted string.
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum FromStrError {
-    Account(account::FromStrError),
-    Hash(String),
-    URef(uref::FromStrError),
-    Transfer(TransferFromStrError),
-    DeployInfo(String),
-    EraInfo(String),
-    Balance(String),
-    Bid(String),
-    Withdraw(String),
-    Dictionary(String),
-    UnknownPrefix,
-}
-
-impl From<account::FromStrError> for FromStrError {
-    fn from(error: account::FromStrError) -> Self {
-        FromStrError::Account(error)
-    }
-}
-
-impl From<TransferFromStrError> for FromStrError {
-    fn from(error: TransferFromStrError) -> Self {
-        FromStrError::Transfer(error)
-    }
-}
-
-impl From<uref::FromStrError> for FromStrError {
-    fn from(error: uref::FromStrError) -> Self {
-        FromStrError::URef(error)
-    }
+    /// The input doesn't start with any of the expected prefixes.
+    InvalidPrefix {
+        /// The prefixes which would have been accepted.
+        expected_one_of: &'static [&'static str],
+    },
+    /// The portion following the prefix isn't valid hex.
+    InvalidHex {
+        /// The prefix preceding the hex-encoded portion.
+        prefix: &'static str,
+        /// The error decoding the hex-encoded portion.
+        error: base16::DecodeError,
+    },
+    /// The portion following the prefix decodes to the wrong number of bytes.
+    InvalidHexLength {
+        /// The prefix preceding the hex-encoded portion.
+        prefix: &'static str,
+        /// The expected number of bytes.
+        expected: usize,
+        /// The actual number of bytes.
+        got: usize,
+    },
+    /// The formatted `URef` has no access rights suffix.
+    MissingAccessRights,
+    /// The access rights suffix of the formatted `URef` isn't a valid octal access rights value.
+    InvalidAccessRights {
+        /// The given suffix.
+        suffix: String,
+    },
+    /// The portion following the era info prefix isn't a valid era ID.
+    InvalidEraId(ParseIntError),
 }
 
 impl Display for FromStrError {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         match self {
-            FromStrError::Account(error) => write!(f, "account-key from string error: {}", error),
-            FromStrError::Hash(error) => write!(f, "hash-key from string error: {}", error),
-            FromStrError::URef(error) => write!(f, "uref-key from string error: {}", error),
-            FromStrError::Transfer(error) => write!(f, "transfer-key from string error: {}", error),
-            FromStrError::DeployInfo(error) => {
-                write!(f, "deploy-info-key from string error: {}", error)
+            FromStrError::InvalidPrefix { expected_one_of } => {
+                write!(f, "invalid prefix, expected one of ")?;
+                for (index, prefix) in expected_one_of.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "'{}'", prefix)?;
+                }
+                Ok(())
+            }
+            FromStrError::InvalidHex { prefix, error } => {
+                write!(f, "failed to decode hex following '{}': {}", prefix, error)
+            }
+            FromStrError::InvalidHexLength {
+                prefix,
+                expected,
+                got,
+            } => write!(
+                f,
+                "hex following '{}' should decode to {} bytes, but decodes to {}",
+                prefix, expected, got
+            ),
+            FromStrError::MissingAccessRights => write!(
+                f,
+                "missing access rights suffix, expected e.g. '-007' following the address"
+            ),
+            FromStrError::InvalidAccessRights { suffix } => write!(
+                f,
+                "invalid access rights suffix '{}', expected an octal value from '000' to '007'",
+                suffix
+            ),
+            FromStrError::InvalidEraId(error) => {
+                write!(
+                    f,
+                    "failed to parse era ID following '{}': {}",
+                    ERA_INFO_PREFIX, error
+                )
             }
-            FromStrError::EraInfo(error) => write!(f, "era-info-key from string error: {}", error),
-            FromStrError::Balance(error) => write!(f, "balance-key from string error: {}", error),
-            FromStrError::Bid(error) => write!(f, "bid-key from string error: {}", error),
-            FromStrError::Withdraw(error) => write!(f, "withdraw-key from string error: {}", error),
-            FromStrError::UnknownPrefix => write!(f, "unknown prefix for key"),
-            FromStrError::Dictionary(error) => write!(f, "dictionary from string error: {}", error),
         }
     }
 }
 
+/// Decodes the hex-encoded portion of a formatted string following `prefix` into `addr`, which
+/// must be exactly filled.
+pub(crate) fn decode_hex_addr(
+    prefix: &'static str,
+    hex: &str,
+    addr: &mut [u8],
+) -> Result<(), FromStrError> {
+    let bytes = base16::decode(hex).map_err(|error| FromStrError::InvalidHex { prefix, error })?;
+    if bytes.len() != addr.len() {
+        return Err(FromStrError::InvalidHexLength {
+            prefix,
+            expected: addr.len(),
+            got: bytes.len(),
+        });
+    }
+    addr.copy_from_slice(&bytes);
+    Ok(())
+}
+
 impl Key {
     // This method is not intended to be used by third party crates.
     #[doc(hidden)]
@@ -236,78 +294,70 @@ impl Key {
 
     /// Parses a string formatted as per `Self::to_formatted_string()` into a `Key`.
     pub fn from_formatted_str(input: &str) -> Result<Key, FromStrError> {
-        match AccountHash::from_formatted_str(input) {
-            Ok(account_hash) => return Ok(Key::Account(account_hash)),
-            Err(account::FromStrError::InvalidPrefix) => {}
-            Err(error) => return Err(error.into()),
+        if let Some(hex) = input.strip_prefix(ACCOUNT_HASH_FORMATTED_STRING_PREFIX) {
+            let mut account_hash = AccountHashBytes::default();
+            decode_hex_addr(ACCOUNT_HASH_FORMATTED_STRING_PREFIX, hex, &mut account_hash)?;
+            return Ok(Key::Account(AccountHash::new(account_hash)));
         }
 
         if let Some(hex) = input.strip_prefix(HASH_PREFIX) {
-            let addr =
-                base16::decode(hex).map_err(|error| FromStrError::Hash(error.to_string()))?;
-            let hash_addr = HashAddr::try_from(addr.as_ref())
-                .map_err(|error| FromStrError::Hash(error.to_string()))?;
+            let mut hash_addr = HashAddr::default();
+            decode_hex_addr(HASH_PREFIX, hex, &mut hash_addr)?;
             return Ok(Key::Hash(hash_addr));
         }
 
-        if let Some(hex) = input.strip_prefix(DEPLOY_INFO_PREFIX) {
-            let hash =
-                base16::decode(hex).map_err(|error| FromStrError::DeployInfo(error.to_string()))?;
-            let hash_array = <[u8; DEPLOY_HASH_LENGTH]>::try_from(hash.as_ref())
-                .map_err(|error| FromStrError::DeployInfo(error.to_string()))?;
-            return Ok(Key::DeployInfo(DeployHash::new(hash_array)));
+        if input.starts_with(UREF_FORMATTED_STRING_PREFIX) {
+            return URef::from_formatted_str(input).map(Key::URef);
         }
 
-        match TransferAddr::from_formatted_str(input) {
-            Ok(transfer_addr) => return Ok(Key::Transfer(transfer_addr)),
-            Err(TransferFromStrError::InvalidPrefix) => {}
-            Err(error) => return Err(error.into()),
+        if let Some(hex) = input.strip_prefix(TRANSFER_ADDR_FORMATTED_STRING_PREFIX) {
+            let mut transfer_addr = [0; TRANSFER_ADDR_LENGTH];
+            decode_hex_addr(
+                TRANSFER_ADDR_FORMATTED_STRING_PREFIX,
+                hex,
+                &mut transfer_addr,
+            )?;
+            return Ok(Key::Transfer(TransferAddr::new(transfer_addr)));
         }
 
-        match URef::from_formatted_str(input) {
-            Ok(uref) => return Ok(Key::URef(uref)),
-            Err(uref::FromStrError::InvalidPrefix) => {}
-            Err(error) => return Err(error.into()),
+        if let Some(hex) = input.strip_prefix(DEPLOY_INFO_PREFIX) {
+            let mut deploy_hash = [0; DEPLOY_HASH_LENGTH];
+            decode_hex_addr(DEPLOY_INFO_PREFIX, hex, &mut deploy_hash)?;
+            return Ok(Key::DeployInfo(DeployHash::new(deploy_hash)));
         }
 
         if let Some(era_id_str) = input.strip_prefix(ERA_INFO_PREFIX) {
-            let era_id = EraId::from_str(era_id_str)
-                .map_err(|error| FromStrError::EraInfo(error.to_string()))?;
+            let era_id = EraId::from_str(era_id_str).map_err(FromStrError::InvalidEraId)?;
             return Ok(Key::EraInfo(era_id));
         }
 
         if let Some(hex) = input.strip_prefix(BALANCE_PREFIX) {
-            let addr =
-                base16::decode(hex).map_err(|error| FromStrError::Balance(error.to_string()))?;
-            let uref_addr = URefAddr::try_from(addr.as_ref())
-                .map_err(|error| FromStrError::Balance(error.to_string()))?;
+            let mut uref_addr = URefAddr::default();
+            decode_hex_addr(BALANCE_PREFIX, hex, &mut uref_addr)?;
             return Ok(Key::Balance(uref_addr));
         }
 
         if let Some(hex) = input.strip_prefix(BID_PREFIX) {
-            let hash = base16::decode(hex).map_err(|error| FromStrError::Bid(error.to_string()))?;
-            let account_hash = AccountHashBytes::try_from(hash.as_ref())
-                .map_err(|error| FromStrError::Bid(error.to_string()))?;
+            let mut account_hash = AccountHashBytes::default();
+            decode_hex_addr(BID_PREFIX, hex, &mut account_hash)?;
             return Ok(Key::Bid(AccountHash::new(account_hash)));
         }
 
         if let Some(hex) = input.strip_prefix(WITHDRAW_PREFIX) {
-            let hash =
-                base16::decode(hex).map_err(|error| FromStrError::Withdraw(error.to_string()))?;
-            let account_hash = AccountHashBytes::try_from(hash.as_ref())
-                .map_err(|error| FromStrError::Withdraw(error.to_string()))?;
+            let mut account_hash = AccountHashBytes::default();
+            decode_hex_addr(WITHDRAW_PREFIX, hex, &mut account_hash)?;
             return Ok(Key::Withdraw(AccountHash::new(account_hash)));
         }
 
-        if let Some(dictionary_addr) = input.strip_prefix(DICTIONARY_PREFIX) {
-            let dictionary_addr_bytes = base16::decode(dictionary_addr)
-                .map_err(|error| FromStrError::Dictionary(error.to_string()))?;
-            let addr = DictionaryAddr::try_from(dictionary_addr_bytes.as_ref())
-                .map_err(|error| FromStrError::Dictionary(error.to_string()))?;
-            return Ok(Key::Dictionary(addr));
+        if let Some(hex) = input.strip_prefix(DICTIONARY_PREFIX) {
+            let mut dictionary_addr = DictionaryAddr::default();
+            decode_hex_addr(DICTIONARY_PREFIX, hex, &mut dictionary_addr)?;
+            return Ok(Key::Dictionary(dictionary_addr));
         }
 
-        Err(FromStrError::UnknownPrefix)
+        Err(FromStrError::InvalidPrefix {
+            expected_one_of: KEY_PREFIXES,
+        })
     }
 
     /// Returns the inner bytes of `self` if `self` is of type [`Key::Account`], otherwise returns
@@ -558,7 +608,7 @@ impl FromBytes for Key {
                 let (addr, rem) = DictionaryAddr::from_bytes(remainder)?;
                 Ok((Key::Dictionary(addr), rem))
             }
-            _ => Err(Error::Formatting),
+            tag => Err(Error::invalid_tag::<Self>(tag)),
         }
     }
 }
@@ -728,12 +778,12 @@ impl<'de> Deserialize<'de> for Key {
 mod tests {
     use std::string::ToString;
 
+    use proptest::prelude::*;
+
     use super::*;
     use crate::{
-        account::ACCOUNT_HASH_FORMATTED_STRING_PREFIX,
         bytesrepr::{Error, FromBytes},
-        transfer::TRANSFER_ADDR_FORMATTED_STRING_PREFIX,
-        uref::UREF_FORMATTED_STRING_PREFIX,
+        gens::key_arb,
         AccessRights, URef,
     };
 
@@ -843,15 +893,25 @@ mod tests {
 
     #[test]
     fn abuse_vec_key() {
-        // Prefix is 2^32-1 = shouldn't allocate that much
+        // Prefix is 2^32-1 = shouldn't allocate that much, as the allocation is bounded by the
+        // remaining input
         let bytes: Vec<u8> = vec![255, 255, 255, 255, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9];
         let res: Result<(Vec<Key>, &[u8]), _> = FromBytes::from_bytes(&bytes);
-        #[cfg(target_os = "linux")]
-        assert_eq!(res.expect_err("should fail"), Error::OutOfMemory);
-        #[cfg(target_os = "macos")]
         assert_eq!(res.expect_err("should fail"), Error::EarlyEndOfStream);
     }
 
+    #[test]
+    fn should_fail_to_deserialize_invalid_tag() {
+        let bytes = [u8::max_value(), 0, 1, 2, 3];
+        assert_eq!(
+            Key::from_bytes(&bytes).unwrap_err(),
+            Error::InvalidTag {
+                tag: u8::max_value(),
+                type_name: core::any::type_name::<Key>()
+            }
+        );
+    }
+
     #[test]
     fn check_key_account_getters() {
         let account = [42; 32];
@@ -906,70 +966,143 @@ mod tests {
     }
 
     #[test]
-    fn should_fail_to_parse_key_from_str() {
-        assert!(
-            Key::from_formatted_str(ACCOUNT_HASH_FORMATTED_STRING_PREFIX)
-                .unwrap_err()
-                .to_string()
-                .starts_with("account-key from string error: ")
-        );
-        assert!(Key::from_formatted_str(HASH_PREFIX)
-            .unwrap_err()
-            .to_string()
-            .starts_with("hash-key from string error: "));
-        assert!(Key::from_formatted_str(UREF_FORMATTED_STRING_PREFIX)
-            .unwrap_err()
-            .to_string()
-            .starts_with("uref-key from string error: "));
-        assert!(
-            Key::from_formatted_str(TRANSFER_ADDR_FORMATTED_STRING_PREFIX)
-                .unwrap_err()
-                .to_string()
-                .starts_with("transfer-key from string error: ")
-        );
-        assert!(Key::from_formatted_str(DEPLOY_INFO_PREFIX)
-            .unwrap_err()
-            .to_string()
-            .starts_with("deploy-info-key from string error: "));
-        assert!(Key::from_formatted_str(ERA_INFO_PREFIX)
-            .unwrap_err()
-            .to_string()
-            .starts_with("era-info-key from string error: "));
-        assert!(Key::from_formatted_str(BALANCE_PREFIX)
-            .unwrap_err()
-            .to_string()
-            .starts_with("balance-key from string error: "));
-        assert!(Key::from_formatted_str(BID_PREFIX)
-            .unwrap_err()
-            .to_string()
-            .starts_with("bid-key from string error: "));
-        assert!(Key::from_formatted_str(WITHDRAW_PREFIX)
-            .unwrap_err()
-            .to_string()
-            .starts_with("withdraw-key from string error: "));
+    fn should_fail_to_parse_key_with_invalid_prefix() {
+        let expected_error = FromStrError::InvalidPrefix {
+            expected_one_of: KEY_PREFIXES,
+        };
 
         let invalid_prefix = "a-0000000000000000000000000000000000000000000000000000000000000000";
-        assert_eq!(
-            Key::from_formatted_str(invalid_prefix)
-                .unwrap_err()
-                .to_string(),
-            "unknown prefix for key"
-        );
-
         let missing_hyphen_prefix =
             "hash0000000000000000000000000000000000000000000000000000000000000000";
+        let no_prefix = "0000000000000000000000000000000000000000000000000000000000000000";
+        for input in &["", invalid_prefix, missing_hyphen_prefix, no_prefix] {
+            assert_eq!(
+                Key::from_formatted_str(input).unwrap_err(),
+                expected_error,
+                "{}",
+                input
+            );
+        }
+
+        assert!(Key::from_formatted_str(no_prefix)
+            .unwrap_err()
+            .to_string()
+            .starts_with("invalid prefix, expected one of 'account-hash-', 'hash-', 'uref-'"));
+    }
+
+    #[test]
+    fn should_fail_to_parse_key_with_invalid_hex() {
+        let hex_prefixes = [
+            ACCOUNT_HASH_FORMATTED_STRING_PREFIX,
+            HASH_PREFIX,
+            UREF_FORMATTED_STRING_PREFIX,
+            TRANSFER_ADDR_FORMATTED_STRING_PREFIX,
+            DEPLOY_INFO_PREFIX,
+            BALANCE_PREFIX,
+            BID_PREFIX,
+            WITHDRAW_PREFIX,
+            DICTIONARY_PREFIX,
+        ];
+        for &prefix in &hex_prefixes {
+            // URefs additionally require an access rights suffix.
+            let suffix = if prefix == UREF_FORMATTED_STRING_PREFIX {
+                "-007"
+            } else {
+                ""
+            };
+
+            let invalid_byte = format!("{}{}0g{}", prefix, &HEX_STRING[2..], suffix);
+            assert!(
+                matches!(
+                    Key::from_formatted_str(&invalid_byte),
+                    Err(FromStrError::InvalidHex {
+                        prefix: error_prefix,
+                        error: base16::DecodeError::InvalidByte { .. },
+                    }) if error_prefix == prefix
+                ),
+                "{}",
+                invalid_byte
+            );
+
+            let odd_length = format!("{}{}0{}", prefix, HEX_STRING, suffix);
+            assert!(
+                matches!(
+                    Key::from_formatted_str(&odd_length),
+                    Err(FromStrError::InvalidHex {
+                        prefix: error_prefix,
+                        error: base16::DecodeError::InvalidLength { .. },
+                    }) if error_prefix == prefix
+                ),
+                "{}",
+                odd_length
+            );
+
+            let short = format!("{}{}{}", prefix, &HEX_STRING[2..], suffix);
+            assert_eq!(
+                Key::from_formatted_str(&short).unwrap_err(),
+                FromStrError::InvalidHexLength {
+                    prefix,
+                    expected: 32,
+                    got: 31
+                },
+                "{}",
+                short
+            );
+
+            let long = format!("{}{}00{}", prefix, HEX_STRING, suffix);
+            assert_eq!(
+                Key::from_formatted_str(&long).unwrap_err(),
+                FromStrError::InvalidHexLength {
+                    prefix,
+                    expected: 32,
+                    got: 33
+                },
+                "{}",
+                long
+            );
+        }
+
         assert_eq!(
-            Key::from_formatted_str(missing_hyphen_prefix)
+            Key::from_formatted_str(HASH_PREFIX)
                 .unwrap_err()
                 .to_string(),
-            "unknown prefix for key"
+            "hex following 'hash-' should decode to 32 bytes, but decodes to 0"
         );
+    }
 
-        let no_prefix = "0000000000000000000000000000000000000000000000000000000000000000";
+    #[test]
+    fn should_fail_to_parse_uref_key_with_invalid_access_rights() {
+        let missing_suffix = format!("{}{}", UREF_FORMATTED_STRING_PREFIX, HEX_STRING);
         assert_eq!(
-            Key::from_formatted_str(no_prefix).unwrap_err().to_string(),
-            "unknown prefix for key"
+            Key::from_formatted_str(&missing_suffix).unwrap_err(),
+            FromStrError::MissingAccessRights
         );
+
+        for suffix in &["", "abc", "8", "010", "400"] {
+            let input = format!("{}{}-{}", UREF_FORMATTED_STRING_PREFIX, HEX_STRING, suffix);
+            assert_eq!(
+                Key::from_formatted_str(&input).unwrap_err(),
+                FromStrError::InvalidAccessRights {
+                    suffix: suffix.to_string()
+                },
+                "{}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn should_fail_to_parse_era_info_key_with_invalid_era_id() {
+        for input in &["era-", "era--1", "era-x", "era-18446744073709551616"] {
+            assert!(
+                matches!(
+                    Key::from_formatted_str(input),
+                    Err(FromStrError::InvalidEraId(_))
+                ),
+                "{}",
+                input
+            );
+        }
     }
 
     #[test]
@@ -1030,4 +1163,12 @@ mod tests {
         round_trip(&Key::Bid(AccountHash::new(zeros)));
         round_trip(&Key::Withdraw(AccountHash::new(zeros)));
     }
+
+    proptest! {
+        #[test]
+        fn formatted_string_roundtrip(key in key_arb()) {
+            let formatted_string = key.to_formatted_string();
+            prop_assert_eq!(Key::from_formatted_str(&formatted_string), Ok(key));
+        }
+    }
 }
@@ -44,7 +44,7 @@ impl ToBytes for Phase {
 impl FromBytes for Phase {
     fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), Error> {
         let (id, rest) = u8::from_bytes(bytes)?;
-        let phase = FromPrimitive::from_u8(id).ok_or(Error::Formatting)?;
+        let phase = FromPrimitive::from_u8(id).ok_or_else(|| Error::invalid_tag::<Self>(id))?;
         Ok((phase, rest))
     }
 }
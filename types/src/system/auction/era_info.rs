@@ -146,7 +146,7 @@ impl FromBytes for SeigniorageAllocation {
                     rem,
                 ))
             }
-            _ => Err(bytesrepr::Error::Formatting),
+            tag => Err(bytesrepr::Error::invalid_tag::<Self>(tag)),
         }
     }
 }
@@ -10,6 +10,8 @@ pub const ARG_TO: &str = "to";
 pub const ARG_SOURCE: &str = "source";
 /// Named constant for `target`.
 pub const ARG_TARGET: &str = "target";
+/// Named constant for `transfers`, the batch of transfers in a batched native transfer.
+pub const ARG_TRANSFERS: &str = "transfers";
 /// Named constant for `round_seigniorage_rate` used in installer.
 pub const ARG_ROUND_SEIGNIORAGE_RATE: &str = "round_seigniorage_rate";
 
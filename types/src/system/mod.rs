@@ -340,7 +340,8 @@ impl ToBytes for CallStackElement {
 impl FromBytes for CallStackElement {
     fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
         let (tag, remainder): (u8, &[u8]) = FromBytes::from_bytes(bytes)?;
-        let tag = CallStackElementTag::from_u8(tag).ok_or(bytesrepr::Error::Formatting)?;
+        let tag = CallStackElementTag::from_u8(tag)
+            .ok_or_else(|| bytesrepr::Error::invalid_tag::<Self>(tag))?;
         match tag {
             CallStackElementTag::Session => {
                 let (account_hash, remainder) = AccountHash::from_bytes(remainder)?;
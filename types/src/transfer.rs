@@ -487,4 +487,30 @@ mod tests {
         let decoded = serde_json::from_str(&json_string).unwrap();
         assert_eq!(transfer_address, decoded);
     }
+
+    #[test]
+    fn transfer_json_should_include_absent_options_as_null() {
+        let transfer = Transfer::default();
+        let json_value = serde_json::to_value(&transfer).unwrap();
+        let mut fields: Vec<_> = json_value.as_object().unwrap().keys().cloned().collect();
+        fields.sort();
+        assert_eq!(
+            fields,
+            vec![
+                "amount",
+                "deploy_hash",
+                "from",
+                "gas",
+                "id",
+                "source",
+                "target",
+                "to"
+            ]
+        );
+        assert!(json_value["to"].is_null());
+        assert!(json_value["id"].is_null());
+
+        let decoded: Transfer = serde_json::from_value(json_value).unwrap();
+        assert_eq!(decoded, transfer);
+    }
 }
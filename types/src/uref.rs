@@ -1,12 +1,14 @@
 // TODO - remove once schemars stops causing warning.
 #![allow(clippy::field_reassign_with_default)]
 
-use alloc::{format, string::String, vec::Vec};
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
 use core::{
-    array::TryFromSliceError,
     convert::TryFrom,
     fmt::{self, Debug, Display, Formatter},
-    num::ParseIntError,
 };
 
 use datasize::DataSize;
@@ -22,9 +24,11 @@ use serde::{de::Error as SerdeError, Deserialize, Deserializer, Serialize, Seria
 use crate::{
     bytesrepr,
     bytesrepr::{Error, FromBytes},
-    AccessRights, ApiError, Key, ACCESS_RIGHTS_SERIALIZED_LENGTH,
+    key, AccessRights, ApiError, Key, ACCESS_RIGHTS_SERIALIZED_LENGTH,
 };
 
+pub use crate::key::FromStrError;
+
 /// The number of bytes in a [`URef`] address.
 pub const UREF_ADDR_LENGTH: usize = 32;
 
@@ -36,58 +40,6 @@ pub(super) const UREF_FORMATTED_STRING_PREFIX: &str = "uref-";
 /// The address of a `URef` (unforgeable reference) on the network.
 pub type URefAddr = [u8; UREF_ADDR_LENGTH];
 
-/// Error while parsing a URef from a formatted string.
-#[derive(Debug)]
-pub enum FromStrError {
-    /// Prefix is not "uref-".
-    InvalidPrefix,
-    /// No access rights as suffix.
-    MissingSuffix,
-    /// Access rights are invalid.
-    InvalidAccessRights,
-    /// Failed to decode address portion of URef.
-    Hex(base16::DecodeError),
-    /// Failed to parse an int.
-    Int(ParseIntError),
-    /// The address portion is the wrong length.
-    Address(TryFromSliceError),
-}
-
-impl From<base16::DecodeError> for FromStrError {
-    fn from(error: base16::DecodeError) -> Self {
-        FromStrError::Hex(error)
-    }
-}
-
-impl From<ParseIntError> for FromStrError {
-    fn from(error: ParseIntError) -> Self {
-        FromStrError::Int(error)
-    }
-}
-
-impl From<TryFromSliceError> for FromStrError {
-    fn from(error: TryFromSliceError) -> Self {
-        FromStrError::Address(error)
-    }
-}
-
-impl Display for FromStrError {
-    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        match self {
-            FromStrError::InvalidPrefix => write!(f, "prefix is not 'uref-'"),
-            FromStrError::MissingSuffix => write!(f, "no access rights as suffix"),
-            FromStrError::InvalidAccessRights => write!(f, "invalid access rights"),
-            FromStrError::Hex(error) => {
-                write!(f, "failed to decode address portion from hex: {}", error)
-            }
-            FromStrError::Int(error) => write!(f, "failed to parse an int: {}", error),
-            FromStrError::Address(error) => {
-                write!(f, "address portion is the wrong length: {}", error)
-            }
-        }
-    }
-}
-
 /// Represents an unforgeable reference, containing an address in the network's global storage and
 /// the [`AccessRights`] of the reference.
 ///
@@ -183,17 +135,23 @@ impl URef {
 
     /// Parses a string formatted as per `Self::to_formatted_string()` into a `URef`.
     pub fn from_formatted_str(input: &str) -> Result<Self, FromStrError> {
-        let remainder = input
-            .strip_prefix(UREF_FORMATTED_STRING_PREFIX)
-            .ok_or(FromStrError::InvalidPrefix)?;
-        let parts = remainder.splitn(2, '-').collect::<Vec<_>>();
-        if parts.len() != 2 {
-            return Err(FromStrError::MissingSuffix);
-        }
-        let addr = URefAddr::try_from(base16::decode(parts[0])?.as_ref())?;
-        let access_rights_value = u8::from_str_radix(parts[1], 8)?;
-        let access_rights = AccessRights::from_bits(access_rights_value)
-            .ok_or(FromStrError::InvalidAccessRights)?;
+        let remainder = input.strip_prefix(UREF_FORMATTED_STRING_PREFIX).ok_or(
+            FromStrError::InvalidPrefix {
+                expected_one_of: &[UREF_FORMATTED_STRING_PREFIX],
+            },
+        )?;
+        let (hex, suffix) = match remainder.split_once('-') {
+            Some(parts) => parts,
+            None => return Err(FromStrError::MissingAccessRights),
+        };
+        let mut addr = URefAddr::default();
+        key::decode_hex_addr(UREF_FORMATTED_STRING_PREFIX, hex, &mut addr)?;
+        let access_rights = u8::from_str_radix(suffix, 8)
+            .ok()
+            .and_then(AccessRights::from_bits)
+            .ok_or_else(|| FromStrError::InvalidAccessRights {
+                suffix: suffix.to_string(),
+            })?;
         Ok(URef(addr, access_rights))
     }
 }
@@ -326,36 +284,78 @@ mod tests {
         round_trip(URef::new([0; 32], AccessRights::NONE));
         round_trip(URef::new([255; 32], AccessRights::READ_ADD_WRITE));
 
+        let expected_prefix_error = FromStrError::InvalidPrefix {
+            expected_one_of: &[UREF_FORMATTED_STRING_PREFIX],
+        };
         let invalid_prefix =
             "ref-0000000000000000000000000000000000000000000000000000000000000000-000";
-        assert!(URef::from_formatted_str(invalid_prefix).is_err());
+        assert_eq!(
+            URef::from_formatted_str(invalid_prefix).unwrap_err(),
+            expected_prefix_error
+        );
 
         let invalid_prefix =
             "uref0000000000000000000000000000000000000000000000000000000000000000-000";
-        assert!(URef::from_formatted_str(invalid_prefix).is_err());
+        assert_eq!(
+            URef::from_formatted_str(invalid_prefix).unwrap_err(),
+            expected_prefix_error
+        );
 
         let short_addr = "uref-00000000000000000000000000000000000000000000000000000000000000-000";
-        assert!(URef::from_formatted_str(short_addr).is_err());
+        assert_eq!(
+            URef::from_formatted_str(short_addr).unwrap_err(),
+            FromStrError::InvalidHexLength {
+                prefix: UREF_FORMATTED_STRING_PREFIX,
+                expected: UREF_ADDR_LENGTH,
+                got: 31
+            }
+        );
 
         let long_addr =
             "uref-000000000000000000000000000000000000000000000000000000000000000000-000";
-        assert!(URef::from_formatted_str(long_addr).is_err());
+        assert_eq!(
+            URef::from_formatted_str(long_addr).unwrap_err(),
+            FromStrError::InvalidHexLength {
+                prefix: UREF_FORMATTED_STRING_PREFIX,
+                expected: UREF_ADDR_LENGTH,
+                got: 33
+            }
+        );
 
         let invalid_hex =
             "uref-000000000000000000000000000000000000000000000000000000000000000g-000";
-        assert!(URef::from_formatted_str(invalid_hex).is_err());
+        assert!(matches!(
+            URef::from_formatted_str(invalid_hex).unwrap_err(),
+            FromStrError::InvalidHex {
+                prefix: UREF_FORMATTED_STRING_PREFIX,
+                error: base16::DecodeError::InvalidByte { .. }
+            }
+        ));
 
         let invalid_suffix_separator =
             "uref-0000000000000000000000000000000000000000000000000000000000000000:000";
-        assert!(URef::from_formatted_str(invalid_suffix_separator).is_err());
+        assert_eq!(
+            URef::from_formatted_str(invalid_suffix_separator).unwrap_err(),
+            FromStrError::MissingAccessRights
+        );
 
         let invalid_suffix =
             "uref-0000000000000000000000000000000000000000000000000000000000000000-abc";
-        assert!(URef::from_formatted_str(invalid_suffix).is_err());
+        assert_eq!(
+            URef::from_formatted_str(invalid_suffix).unwrap_err(),
+            FromStrError::InvalidAccessRights {
+                suffix: "abc".to_string()
+            }
+        );
 
         let invalid_access_rights =
             "uref-0000000000000000000000000000000000000000000000000000000000000000-200";
-        assert!(URef::from_formatted_str(invalid_access_rights).is_err());
+        assert_eq!(
+            URef::from_formatted_str(invalid_access_rights).unwrap_err(),
+            FromStrError::InvalidAccessRights {
+                suffix: "200".to_string()
+            }
+        );
     }
 
     #[test]
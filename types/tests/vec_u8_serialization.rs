@@ -0,0 +1,11 @@
+//! Checks that serializing a `Vec<u8>` via `bytesrepr` fails to compile, while serializing the
+//! equivalent `Bytes`, or a `Vec` of any other type, compiles and is unaffected.
+
+#[test]
+fn vec_u8_serialization() {
+    let test_cases = trybuild::TestCases::new();
+    test_cases.pass("tests/vec_u8_serialization/bytes_round_trip.rs");
+    test_cases.pass("tests/vec_u8_serialization/non_u8_vec_round_trip.rs");
+    test_cases.compile_fail("tests/vec_u8_serialization/vec_u8_to_bytes.rs");
+    test_cases.compile_fail("tests/vec_u8_serialization/vec_u8_from_bytes.rs");
+}
@@ -0,0 +1,8 @@
+use casper_types::bytesrepr::{self, Bytes, ToBytes};
+
+fn main() {
+    let bytes = Bytes::from(vec![1, 2, 3]);
+    let serialized = bytes.to_bytes().unwrap();
+    assert_eq!(serialized, vec![3, 0, 0, 0, 1, 2, 3]);
+    assert_eq!(bytesrepr::deserialize::<Bytes>(serialized).unwrap(), bytes);
+}
@@ -0,0 +1,20 @@
+use casper_types::{
+    bytesrepr::{self, Bytes, ToBytes},
+    AccessRights, URef,
+};
+
+fn main() {
+    let vec = vec![
+        (
+            Some(1_u8),
+            String::from("a"),
+            Bytes::from(vec![2]),
+            URef::new([3; 32], AccessRights::READ),
+        ),
+        (None, String::new(), Bytes::new(), URef::default()),
+    ];
+    let serialized = vec.to_bytes().unwrap();
+    let deserialized: Vec<(Option<u8>, String, Bytes, URef)> =
+        bytesrepr::deserialize(serialized).unwrap();
+    assert_eq!(deserialized, vec);
+}
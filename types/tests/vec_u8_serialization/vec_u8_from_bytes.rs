@@ -0,0 +1,5 @@
+use casper_types::bytesrepr;
+
+fn main() {
+    let _ = bytesrepr::deserialize::<Vec<u8>>(vec![3, 0, 0, 0, 1, 2, 3]);
+}
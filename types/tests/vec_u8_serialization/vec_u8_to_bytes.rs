@@ -0,0 +1,5 @@
+use casper_types::bytesrepr;
+
+fn main() {
+    let _ = bytesrepr::serialize(Vec::<u8>::new());
+}